@@ -0,0 +1,292 @@
+use std::io;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use common::file_slice::{FileHandle, FileSlice};
+use common::{HasLen, OwnedBytes};
+use lru::LruCache;
+
+/// Default size of the blocks read from the underlying file.
+pub const DEFAULT_BLOCK_NUM_BYTES: usize = 64 * 1024;
+
+/// LRU cache of fixed-size blocks of columnar files.
+///
+/// Opening a column or looking up a term of a column dictionary reads bytes from the
+/// underlying [`FileSlice`]. When the directory is not backed by `mmap` (e.g. when it reads from
+/// the network), these reads can be expensive. Wrapping the file of a columnar with
+/// [`BlockCache::wrap`] serves these reads from the blocks kept in the cache.
+///
+/// Blocks are identified by the id of the file they belong to, so that they survive the
+/// reopening of a file.
+///
+/// A single cache can be shared by several columnars: its capacity is then shared by all of
+/// their files.
+pub struct BlockCache {
+    cache: Mutex<BlockCacheInner>,
+    block_num_bytes: NonZeroUsize,
+    capacity_num_bytes: usize,
+    cache_hits: AtomicUsize,
+    cache_misses: AtomicUsize,
+}
+
+struct BlockCacheInner {
+    // Blocks are identified by the id of their file and their ordinal in the file.
+    blocks: LruCache<(u128, usize), OwnedBytes>,
+    num_bytes: usize,
+}
+
+/// Hit and miss statistics of a [`BlockCache`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BlockCacheStats {
+    /// The number of blocks in the cache.
+    pub num_entries: usize,
+    /// The number of bytes held by the cache.
+    pub num_bytes: usize,
+    /// The number of blocks served from the cache.
+    pub cache_hits: usize,
+    /// The number of blocks read from the underlying file.
+    pub cache_misses: usize,
+}
+
+impl BlockCache {
+    /// Creates a cache holding at most `capacity_num_bytes` bytes, read in blocks of
+    /// [`DEFAULT_BLOCK_NUM_BYTES`] bytes.
+    pub fn with_capacity(capacity_num_bytes: usize) -> BlockCache {
+        BlockCache::with_block_num_bytes(capacity_num_bytes, DEFAULT_BLOCK_NUM_BYTES)
+    }
+
+    /// Creates a cache holding at most `capacity_num_bytes` bytes, read in blocks of
+    /// `block_num_bytes` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_num_bytes` is 0.
+    pub fn with_block_num_bytes(capacity_num_bytes: usize, block_num_bytes: usize) -> BlockCache {
+        let block_num_bytes =
+            NonZeroUsize::new(block_num_bytes).expect("The block size must be greater than 0");
+        BlockCache {
+            cache: Mutex::new(BlockCacheInner {
+                blocks: LruCache::unbounded(),
+                num_bytes: 0,
+            }),
+            block_num_bytes,
+            capacity_num_bytes,
+            cache_hits: AtomicUsize::default(),
+            cache_misses: AtomicUsize::default(),
+        }
+    }
+
+    /// Returns the maximum number of bytes held by the cache.
+    pub fn capacity_num_bytes(&self) -> usize {
+        self.capacity_num_bytes
+    }
+
+    /// Returns the size of the blocks read from the underlying files.
+    pub fn block_num_bytes(&self) -> usize {
+        self.block_num_bytes.get()
+    }
+
+    /// Returns a file slice reading `file_slice` through the cache.
+    ///
+    /// `file_id` identifies the content of the file: files wrapped with the same id share their
+    /// blocks, and are therefore expected to be identical.
+    pub fn wrap(self: &Arc<Self>, file_id: u128, file_slice: FileSlice) -> FileSlice {
+        let cached_file = CachedFileHandle {
+            file_id,
+            file_slice,
+            block_cache: self.clone(),
+        };
+        FileSlice::new(Arc::new(cached_file))
+    }
+
+    /// Returns the hit and miss statistics of the cache.
+    pub fn stats(&self) -> BlockCacheStats {
+        let (num_entries, num_bytes) = {
+            let cache = self.cache.lock().unwrap();
+            (cache.blocks.len(), cache.num_bytes)
+        };
+        BlockCacheStats {
+            num_entries,
+            num_bytes,
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn get_block(
+        &self,
+        file_id: u128,
+        block_ord: usize,
+        file_slice: &FileSlice,
+    ) -> io::Result<OwnedBytes> {
+        let key = (file_id, block_ord);
+        if let Some(block) = self.cache.lock().unwrap().blocks.get(&key).cloned() {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(block);
+        }
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+        let block_start = block_ord * self.block_num_bytes();
+        let block_end = (block_start + self.block_num_bytes()).min(file_slice.len());
+        let block = file_slice.read_bytes_slice(block_start..block_end)?;
+        if block.len() <= self.capacity_num_bytes {
+            let mut cache = self.cache.lock().unwrap();
+            if let Some(previous_block) = cache.blocks.put(key, block.clone()) {
+                cache.num_bytes -= previous_block.len();
+            }
+            cache.num_bytes += block.len();
+            while cache.num_bytes > self.capacity_num_bytes {
+                let Some((_, evicted_block)) = cache.blocks.pop_lru() else {
+                    break;
+                };
+                cache.num_bytes -= evicted_block.len();
+            }
+        }
+        Ok(block)
+    }
+}
+
+#[derive(Clone)]
+struct CachedFileHandle {
+    file_id: u128,
+    file_slice: FileSlice,
+    block_cache: Arc<BlockCache>,
+}
+
+impl std::fmt::Debug for CachedFileHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedFileHandle")
+            .field("file_id", &self.file_id)
+            .field("file_slice", &self.file_slice)
+            .finish()
+    }
+}
+
+impl HasLen for CachedFileHandle {
+    fn len(&self) -> usize {
+        self.file_slice.len()
+    }
+}
+
+impl FileHandle for CachedFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        if range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let block_num_bytes = self.block_cache.block_num_bytes();
+        let first_block_ord = range.start / block_num_bytes;
+        let last_block_ord = (range.end - 1) / block_num_bytes;
+        if first_block_ord == last_block_ord {
+            let block =
+                self.block_cache
+                    .get_block(self.file_id, first_block_ord, &self.file_slice)?;
+            let block_start = first_block_ord * block_num_bytes;
+            return Ok(block.slice(range.start - block_start..range.end - block_start));
+        }
+        let mut buffer = Vec::with_capacity(range.len());
+        for block_ord in first_block_ord..=last_block_ord {
+            let block = self
+                .block_cache
+                .get_block(self.file_id, block_ord, &self.file_slice)?;
+            let block_start = block_ord * block_num_bytes;
+            let start = range.start.max(block_start) - block_start;
+            let end = range.end.min(block_start + block.len()) - block_start;
+            buffer.extend_from_slice(&block.as_slice()[start..end]);
+        }
+        Ok(OwnedBytes::new(buffer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_file(num_bytes: usize) -> FileSlice {
+        let data: Vec<u8> = (0..num_bytes).map(|i| (i % 251) as u8).collect();
+        FileSlice::from(data)
+    }
+
+    #[test]
+    fn test_block_cache_read() {
+        let file = test_file(1_000);
+        let block_cache = Arc::new(BlockCache::with_block_num_bytes(10_000, 64));
+        let cached_file = block_cache.wrap(0, file.clone());
+        assert_eq!(cached_file.len(), 1_000);
+        for range in [0..0, 0..1, 3..64, 60..70, 0..1_000, 999..1_000, 100..900] {
+            assert_eq!(
+                cached_file
+                    .read_bytes_slice(range.clone())
+                    .unwrap()
+                    .as_slice(),
+                file.read_bytes_slice(range).unwrap().as_slice()
+            );
+        }
+        assert_eq!(
+            cached_file.slice(100..200).read_bytes().unwrap().as_slice(),
+            file.slice(100..200).read_bytes().unwrap().as_slice()
+        );
+        let stats = block_cache.stats();
+        assert_eq!(stats.num_entries, 16);
+        assert_eq!(stats.num_bytes, 1_000);
+        assert_eq!(stats.cache_misses, 16);
+    }
+
+    #[test]
+    fn test_block_cache_hits() {
+        let block_cache = Arc::new(BlockCache::with_block_num_bytes(10_000, 64));
+        let cached_file = block_cache.wrap(0, test_file(1_000));
+        cached_file.read_bytes_slice(0..10).unwrap();
+        cached_file.read_bytes_slice(10..20).unwrap();
+        cached_file.read_bytes_slice(60..70).unwrap();
+        assert_eq!(
+            block_cache.stats(),
+            BlockCacheStats {
+                num_entries: 2,
+                num_bytes: 128,
+                cache_hits: 2,
+                cache_misses: 2,
+            }
+        );
+        // Reopening a file reuses its blocks.
+        let reopened_cached_file = block_cache.wrap(0, test_file(1_000));
+        reopened_cached_file.read_bytes_slice(0..10).unwrap();
+        assert_eq!(block_cache.stats().cache_hits, 3);
+        // Files with different ids do not share their blocks.
+        let other_cached_file = block_cache.wrap(1, test_file(1_000));
+        other_cached_file.read_bytes_slice(0..10).unwrap();
+        assert_eq!(block_cache.stats().cache_misses, 3);
+    }
+
+    #[test]
+    fn test_block_cache_eviction() {
+        let block_cache = Arc::new(BlockCache::with_block_num_bytes(200, 64));
+        let cached_file = block_cache.wrap(0, test_file(1_000));
+        cached_file.read_bytes_slice(0..1).unwrap();
+        cached_file.read_bytes_slice(64..65).unwrap();
+        cached_file.read_bytes_slice(128..129).unwrap();
+        // Block 0 is the least recently used block.
+        cached_file.read_bytes_slice(64..65).unwrap();
+        cached_file.read_bytes_slice(192..193).unwrap();
+        let stats = block_cache.stats();
+        assert_eq!(stats.num_entries, 3);
+        assert_eq!(stats.num_bytes, 192);
+        cached_file.read_bytes_slice(64..65).unwrap();
+        cached_file.read_bytes_slice(0..1).unwrap();
+        let stats = block_cache.stats();
+        assert_eq!(stats.cache_hits, 2);
+        assert_eq!(stats.cache_misses, 5);
+    }
+
+    #[test]
+    fn test_block_cache_block_larger_than_capacity() {
+        let block_cache = Arc::new(BlockCache::with_block_num_bytes(10, 64));
+        let file = test_file(100);
+        let cached_file = block_cache.wrap(0, file.clone());
+        assert_eq!(
+            cached_file.read_bytes().unwrap().as_slice(),
+            file.read_bytes().unwrap().as_slice()
+        );
+        assert_eq!(block_cache.stats().num_entries, 0);
+    }
+}
@@ -50,10 +50,33 @@ impl BytesColumn {
         self.term_ord_column.num_docs()
     }
 
+    /// Returns the term ordinals of the values of a given row.
+    ///
+    /// Term ordinals follow the order of the terms, so that they can be compared, grouped or
+    /// sorted on without looking the terms up. They are local to the column, and are only
+    /// meaningful within a segment.
     pub fn term_ords(&self, row_id: RowId) -> impl Iterator<Item = u64> + '_ {
         self.term_ord_column.values_for_doc(row_id)
     }
 
+    /// Returns the ordinal associated to the given term, if it is present in the column.
+    pub fn term_ord<K: AsRef<[u8]>>(&self, term: K) -> io::Result<Option<u64>> {
+        self.dictionary.term_ord(term)
+    }
+
+    /// Calls `cb` with the term of each of the given _sorted_ ordinals.
+    ///
+    /// This is much faster than calling [`BytesColumn::ord_to_bytes`] for each of them.
+    ///
+    /// Returns `false` if one of the terms does not exist.
+    pub fn sorted_ords_to_bytes_cb<F: FnMut(&[u8]) -> io::Result<()>>(
+        &self,
+        sorted_ords: impl Iterator<Item = u64>,
+        cb: F,
+    ) -> io::Result<bool> {
+        self.dictionary.sorted_ords_to_term_cb(sorted_ords, cb)
+    }
+
     /// Returns the column of ordinals
     pub fn ords(&self) -> &Column<u64> {
         &self.term_ord_column
@@ -110,6 +133,23 @@ impl StrColumn {
         }
         Ok(true)
     }
+
+    /// Calls `cb` with the term of each of the given _sorted_ ordinals.
+    ///
+    /// Returns `false` if one of the terms does not exist.
+    pub fn sorted_ords_to_str_cb<F: FnMut(&str) -> io::Result<()>>(
+        &self,
+        sorted_ords: impl Iterator<Item = u64>,
+        mut cb: F,
+    ) -> io::Result<bool> {
+        self.0
+            .dictionary
+            .sorted_ords_to_term_cb(sorted_ords, |term_bytes| {
+                let term = std::str::from_utf8(term_bytes)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not valid utf-8"))?;
+                cb(term)
+            })
+    }
 }
 
 impl Deref for StrColumn {
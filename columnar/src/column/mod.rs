@@ -1,6 +1,7 @@
 mod dictionary_encoded;
 mod serialize;
 
+use std::collections::BTreeMap;
 use std::fmt::{self, Debug};
 use std::io::Write;
 use std::ops::{Range, RangeInclusive};
@@ -9,14 +10,14 @@ use std::sync::Arc;
 use common::BinarySerializable;
 pub use dictionary_encoded::{BytesColumn, StrColumn};
 pub use serialize::{
-    open_column_bytes, open_column_str, open_column_u64, open_column_u128,
-    open_column_u128_as_compact_u64, serialize_column_mappable_to_u64,
+    open_column_bytes, open_column_str, open_column_u64, open_column_u64_codec_stats,
+    open_column_u128, open_column_u128_as_compact_u64, serialize_column_mappable_to_u64,
     serialize_column_mappable_to_u128,
 };
 
 use crate::column_index::{ColumnIndex, Set};
 use crate::column_values::monotonic_mapping::StrictlyMonotonicMappingToInternal;
-use crate::column_values::{ColumnValues, monotonic_map_column};
+use crate::column_values::{ColumnValues, monotonic_map_column, updated_column_values};
 use crate::{Cardinality, DocId, EmptyColumnValues, MonotonicallyMappableToU64, RowId};
 
 #[derive(Clone)]
@@ -55,6 +56,25 @@ impl<T: MonotonicallyMappableToU64> Column<T> {
             values,
         }
     }
+
+    /// Returns a view of the column in which the value of some documents is replaced.
+    ///
+    /// `updates` maps doc ids to the `u64` representation of their new value.
+    /// Only documents with exactly one value are updated.
+    pub(crate) fn with_updated_values(self, updates: &BTreeMap<DocId, u64>) -> Column<T> {
+        let num_docs = self.num_docs();
+        let updated_rows: Vec<(RowId, T)> = updates
+            .range(..num_docs)
+            .filter_map(|(&doc, &value)| {
+                let row_ids = self.index.value_row_ids(doc);
+                (row_ids.len() == 1).then(|| (row_ids.start, T::from_u64(value)))
+            })
+            .collect();
+        Column {
+            values: updated_column_values(self.values, updated_rows),
+            index: self.index,
+        }
+    }
 }
 
 impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
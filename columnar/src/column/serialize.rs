@@ -8,8 +8,9 @@ use sstable::Dictionary;
 use crate::column::{BytesColumn, Column};
 use crate::column_index::{SerializableColumnIndex, serialize_column_index};
 use crate::column_values::{
-    CodecType, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
-    load_u64_based_column_values, serialize_column_values_u128, serialize_u64_based_column_values,
+    CodecType, ColumnCodecStats, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
+    load_u64_based_column_codec_stats, load_u64_based_column_values, serialize_column_values_u128,
+    serialize_u64_based_column_values,
 };
 use crate::iterable::Iterable;
 use crate::{StrColumn, Version};
@@ -25,17 +26,15 @@ pub fn serialize_column_mappable_to_u128<T: MonotonicallyMappableToU128>(
     Ok(())
 }
 
+/// Serializes a column, encoding its values with the most compact of `codec_types`.
 pub fn serialize_column_mappable_to_u64<T: MonotonicallyMappableToU64>(
     column_index: SerializableColumnIndex<'_>,
     column_values: &impl Iterable<T>,
+    codec_types: &[CodecType],
     output: &mut impl Write,
 ) -> io::Result<()> {
     let column_index_num_bytes = serialize_column_index(column_index, output)?;
-    serialize_u64_based_column_values(
-        column_values,
-        &[CodecType::Bitpacked, CodecType::BlockwiseLinear],
-        output,
-    )?;
+    serialize_u64_based_column_values(column_values, codec_types, output)?;
     output.write_all(&column_index_num_bytes.to_le_bytes())?;
     Ok(())
 }
@@ -60,6 +59,20 @@ pub fn open_column_u64<T: MonotonicallyMappableToU64>(
     })
 }
 
+/// Returns the codec stats of the values of a column serialized with
+/// [`serialize_column_mappable_to_u64`].
+pub fn open_column_u64_codec_stats(bytes: OwnedBytes) -> io::Result<ColumnCodecStats> {
+    let (body, column_index_num_bytes_payload) = bytes.rsplit(4);
+    let column_index_num_bytes = u32::from_le_bytes(
+        column_index_num_bytes_payload
+            .as_slice()
+            .try_into()
+            .unwrap(),
+    );
+    let (_column_index_data, column_values_data) = body.split(column_index_num_bytes as usize);
+    load_u64_based_column_codec_stats(column_values_data)
+}
+
 pub fn open_column_u128<T: MonotonicallyMappableToU128>(
     bytes: OwnedBytes,
     format_version: Version,
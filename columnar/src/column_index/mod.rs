@@ -26,6 +26,11 @@ pub enum ColumnIndex {
         num_docs: u32,
     },
     Full,
+    /// Index of the documents with a value, for columns with at most one value per document.
+    ///
+    /// The documents are split into blocks, encoded either as a bitset or as the list of the
+    /// documents with a value, depending on their density. The values themselves are only
+    /// stored for these documents, so that mostly-missing columns take very little space.
     Optional(OptionalIndex),
     /// In addition, at index num_rows, an extra value is added
     /// containing the overall number of values.
@@ -20,6 +20,7 @@ pub(crate) mod monotonic_mapping_u128;
 mod stats;
 mod u128_based;
 mod u64_based;
+mod updated_column;
 mod vec_column;
 
 mod monotonic_column;
@@ -27,13 +28,15 @@ mod monotonic_column;
 pub(crate) use merge::MergedColumnValues;
 pub use stats::ColumnStats;
 pub use u64_based::{
-    ALL_U64_CODEC_TYPES, CodecType, load_u64_based_column_values,
+    ALL_U64_CODEC_TYPES, CodecType, ColumnCodecStats, DEFAULT_U64_CODEC_TYPES,
+    load_u64_based_column_codec_stats, load_u64_based_column_values,
     serialize_and_load_u64_based_column_values, serialize_u64_based_column_values,
 };
 pub use u128_based::{
     CompactSpaceU64Accessor, open_u128_as_compact_u64, open_u128_mapped,
     serialize_column_values_u128,
 };
+pub(crate) use updated_column::updated_column_values;
 pub use vec_column::VecColumn;
 
 pub use self::monotonic_column::monotonic_map_column;
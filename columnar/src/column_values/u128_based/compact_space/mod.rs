@@ -141,6 +141,20 @@ impl CompactSpace {
             })
     }
 
+    /// Returns the smallest value in the compact space, which unpacks to a value greater or equal
+    /// to `value`.
+    ///
+    /// If `value` is greater than all values of the compact space, the value after the end of the
+    /// compact space is returned.
+    fn u128_to_next_compact(&self, value: u128) -> u32 {
+        self.u128_to_compact(value).unwrap_or_else(|pos| {
+            self.ranges_mapping
+                .get(pos)
+                .map(|range_mapping| range_mapping.compact_start)
+                .unwrap_or_else(|| self.amplitude_compact_space() as u32 + 1)
+        })
+    }
+
     /// Unpacks a value from compact space u32 to u128 space
     fn compact_to_u128(&self, compact: u32) -> u128 {
         let pos = self
@@ -309,6 +323,14 @@ impl CompactSpaceU64Accessor {
     pub fn compact_to_u128(&self, compact: u32) -> u128 {
         self.0.compact_to_u128(compact)
     }
+    /// Convert a u128 value to the smallest compact space value, which unpacks to a value greater
+    /// or equal to it.
+    ///
+    /// The conversion is monotonic, so it can be used to convert the bounds of a u128 range to the
+    /// compact space.
+    pub fn u128_to_next_compact(&self, value: u128) -> u32 {
+        self.0.params.compact_space.u128_to_next_compact(value)
+    }
 }
 
 impl ColumnValues<u64> for CompactSpaceU64Accessor {
@@ -537,6 +559,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compact_space_next_compact_test() {
+        let ips: BTreeSet<u128> = [2u128, 4u128, 1000, 1001, 1260].into_iter().collect();
+        let compact_space = get_compact_space(&ips, ips.len() as u32, 11);
+        let amplitude = compact_space.amplitude_compact_space() as u32;
+        assert_eq!(compact_space.u128_to_next_compact(0), 1);
+        assert_eq!(compact_space.u128_to_next_compact(1261), amplitude + 1);
+        for value in 0..1300u128 {
+            let compact = compact_space.u128_to_next_compact(value);
+            if compact <= amplitude {
+                assert!(compact_space.compact_to_u128(compact) >= value);
+            } else {
+                assert!(value > 1260);
+            }
+            if compact > 1 {
+                assert!(compact_space.compact_to_u128(compact - 1) < value);
+            }
+        }
+    }
+
     #[test]
     fn compact_space_amplitude_test() {
         let ips = &[100000u128, 1000000].into_iter().collect();
@@ -95,6 +95,31 @@ pub const ALL_U64_CODEC_TYPES: [CodecType; 3] = [
     CodecType::BlockwiseLinear,
 ];
 
+/// Codecs considered when serializing a column, unless others are specified for it.
+pub const DEFAULT_U64_CODEC_TYPES: [CodecType; 2] =
+    [CodecType::Bitpacked, CodecType::BlockwiseLinear];
+
+/// Codec used to serialize u64-based column values, along with the space they take.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnCodecStats {
+    /// Codec that was selected when serializing the values.
+    pub codec_type: CodecType,
+    /// Number of values.
+    pub num_vals: u32,
+    /// Number of bytes taken by the values, excluding the column index.
+    pub num_bytes: u64,
+}
+
+impl ColumnCodecStats {
+    /// Returns the average number of bits used per value.
+    pub fn bits_per_value(&self) -> f64 {
+        if self.num_vals == 0 {
+            return 0.0;
+        }
+        (self.num_bytes * 8) as f64 / self.num_vals as f64
+    }
+}
+
 impl CodecType {
     fn to_code(self) -> u8 {
         self as u8
@@ -200,6 +225,22 @@ pub fn load_u64_based_column_values<T: MonotonicallyMappableToU64>(
     codec_type.load(bytes)
 }
 
+/// Returns the codec used to serialize u64-based column values, and the space they take.
+pub fn load_u64_based_column_codec_stats(bytes: OwnedBytes) -> io::Result<ColumnCodecStats> {
+    let num_bytes = bytes.len() as u64;
+    let codec_type: CodecType = bytes
+        .first()
+        .copied()
+        .and_then(CodecType::try_from_code)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Failed to read codec type"))?;
+    let num_vals = load_u64_based_column_values::<u64>(bytes)?.num_vals();
+    Ok(ColumnCodecStats {
+        codec_type,
+        num_vals,
+        num_bytes,
+    })
+}
+
 /// Helper function to serialize a column (autodetect from all codecs) and then open it
 pub fn serialize_and_load_u64_based_column_values<T: MonotonicallyMappableToU64>(
     vals: &dyn Iterable,
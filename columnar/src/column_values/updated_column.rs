@@ -0,0 +1,152 @@
+use std::fmt::Debug;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+
+use crate::{ColumnValues, RowId};
+
+/// View over a column of values, in which some rows have been assigned a new value.
+struct UpdatedColumnValues<T> {
+    from_column: Arc<dyn ColumnValues<T>>,
+    // Sorted by row id.
+    updates: Vec<(RowId, T)>,
+    min_value: T,
+    max_value: T,
+}
+
+/// Creates a view of a column, where the value of the given rows is replaced.
+///
+/// `updates` is required to be sorted by row id, and to only contain row ids
+/// that are within the column.
+pub(crate) fn updated_column_values<T>(
+    from_column: Arc<dyn ColumnValues<T>>,
+    updates: Vec<(RowId, T)>,
+) -> Arc<dyn ColumnValues<T>>
+where
+    T: PartialOrd + Copy + Debug + Send + Sync + 'static,
+{
+    if updates.is_empty() {
+        return from_column;
+    }
+    debug_assert!(updates.windows(2).all(|window| window[0].0 < window[1].0));
+    let mut min_value = from_column.min_value();
+    let mut max_value = from_column.max_value();
+    for &(_, value) in &updates {
+        if value < min_value {
+            min_value = value;
+        }
+        if value > max_value {
+            max_value = value;
+        }
+    }
+    Arc::new(UpdatedColumnValues {
+        from_column,
+        updates,
+        min_value,
+        max_value,
+    })
+}
+
+impl<T> UpdatedColumnValues<T> {
+    fn updates_in_range(&self, row_id_range: Range<RowId>) -> &[(RowId, T)] {
+        let start = self
+            .updates
+            .partition_point(|(row_id, _)| *row_id < row_id_range.start);
+        let end = self
+            .updates
+            .partition_point(|(row_id, _)| *row_id < row_id_range.end);
+        &self.updates[start..end]
+    }
+}
+
+impl<T> ColumnValues<T> for UpdatedColumnValues<T>
+where T: PartialOrd + Copy + Debug + Send + Sync + 'static
+{
+    #[inline]
+    fn get_val(&self, idx: u32) -> T {
+        match self
+            .updates
+            .binary_search_by_key(&idx, |(row_id, _)| *row_id)
+        {
+            Ok(pos) => self.updates[pos].1,
+            Err(_) => self.from_column.get_val(idx),
+        }
+    }
+
+    fn min_value(&self) -> T {
+        self.min_value
+    }
+
+    fn max_value(&self) -> T {
+        self.max_value
+    }
+
+    fn num_vals(&self) -> u32 {
+        self.from_column.num_vals()
+    }
+
+    fn get_row_ids_for_value_range(
+        &self,
+        value_range: RangeInclusive<T>,
+        row_id_range: Range<RowId>,
+        row_id_hits: &mut Vec<RowId>,
+    ) {
+        let updates = self.updates_in_range(row_id_range.clone());
+        if updates.is_empty() {
+            self.from_column
+                .get_row_ids_for_value_range(value_range, row_id_range, row_id_hits);
+            return;
+        }
+        let mut from_column_hits = Vec::new();
+        self.from_column.get_row_ids_for_value_range(
+            value_range.clone(),
+            row_id_range,
+            &mut from_column_hits,
+        );
+        let start = row_id_hits.len();
+        row_id_hits.extend(from_column_hits.into_iter().filter(|row_id| {
+            updates
+                .binary_search_by_key(row_id, |(updated_row_id, _)| *updated_row_id)
+                .is_err()
+        }));
+        row_id_hits.extend(
+            updates
+                .iter()
+                .filter(|(_, value)| value_range.contains(value))
+                .map(|(updated_row_id, _)| *updated_row_id),
+        );
+        row_id_hits[start..].sort_unstable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::column_values::VecColumn;
+
+    #[test]
+    fn test_updated_column_values() {
+        let vals: Vec<u64> = (0..10u64).map(|el| el * 10).collect();
+        let col: Arc<dyn ColumnValues<u64>> = Arc::new(VecColumn::from(vals));
+        let updated = updated_column_values(col, vec![(2, 100), (5, 0), (7, 55)]);
+        assert_eq!(
+            updated.iter().collect::<Vec<u64>>(),
+            [0, 10, 100, 30, 40, 0, 60, 55, 80, 90]
+        );
+        assert_eq!(updated.min_value(), 0);
+        assert_eq!(updated.max_value(), 100);
+
+        let mut row_ids = Vec::new();
+        updated.get_row_ids_for_value_range(40..=60, 0..10, &mut row_ids);
+        assert_eq!(row_ids, [4, 6, 7]);
+
+        let mut row_ids = Vec::new();
+        updated.get_row_ids_for_value_range(0..=100, 1..9, &mut row_ids);
+        assert_eq!(row_ids, [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let mut row_ids = Vec::new();
+        updated.get_row_ids_for_value_range(0..=0, 0..10, &mut row_ids);
+        assert_eq!(row_ids, [0, 5]);
+    }
+}
@@ -4,8 +4,8 @@ use std::net::Ipv6Addr;
 
 use serde::{Deserialize, Serialize};
 
-use crate::InvalidData;
 use crate::value::NumericalType;
+use crate::{GeoPoint, InvalidData};
 
 /// The column type represents the column type.
 /// Any changes need to be propagated to `COLUMN_TYPES`.
@@ -20,6 +20,8 @@ pub enum ColumnType {
     Bool = 5u8,
     IpAddr = 6u8,
     DateTime = 7u8,
+    U128 = 8u8,
+    GeoPoint = 9u8,
 }
 
 impl fmt::Display for ColumnType {
@@ -33,13 +35,15 @@ impl fmt::Display for ColumnType {
             ColumnType::Bool => "bool",
             ColumnType::IpAddr => "ip",
             ColumnType::DateTime => "datetime",
+            ColumnType::U128 => "u128",
+            ColumnType::GeoPoint => "geo_point",
         };
         write!(f, "{short_str}")
     }
 }
 
 // The order needs to match _exactly_ the order in the enum
-const COLUMN_TYPES: [ColumnType; 8] = [
+const COLUMN_TYPES: [ColumnType; 10] = [
     ColumnType::I64,
     ColumnType::U64,
     ColumnType::F64,
@@ -48,6 +52,8 @@ const COLUMN_TYPES: [ColumnType; 8] = [
     ColumnType::Bool,
     ColumnType::IpAddr,
     ColumnType::DateTime,
+    ColumnType::U128,
+    ColumnType::GeoPoint,
 ];
 
 impl ColumnType {
@@ -83,7 +89,9 @@ impl ColumnType {
             | ColumnType::Str
             | ColumnType::Bool
             | ColumnType::IpAddr
-            | ColumnType::DateTime => None,
+            | ColumnType::DateTime
+            | ColumnType::U128
+            | ColumnType::GeoPoint => None,
         }
     }
 }
@@ -152,6 +160,26 @@ impl HasAssociatedColumnType for Ipv6Addr {
     }
 }
 
+impl HasAssociatedColumnType for u128 {
+    fn column_type() -> ColumnType {
+        ColumnType::U128
+    }
+
+    fn default_value() -> Self {
+        0u128
+    }
+}
+
+impl HasAssociatedColumnType for GeoPoint {
+    fn column_type() -> ColumnType {
+        ColumnType::GeoPoint
+    }
+
+    fn default_value() -> Self {
+        GeoPoint::new(0.0, 0.0).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,6 +6,7 @@ use sstable::{SSTable, Streamer, TermOrdinal, VoidSSTable};
 use super::term_merger::{TermMerger, TermsWithSegmentOrd};
 use crate::column::serialize_column_mappable_to_u64;
 use crate::column_index::SerializableColumnIndex;
+use crate::column_values::DEFAULT_U64_CODEC_TYPES;
 use crate::iterable::Iterable;
 use crate::{BytesColumn, MergeRowOrder, ShuffleMergeOrder};
 
@@ -28,7 +29,12 @@ pub fn merge_bytes_or_str_column(
         term_ord_mapping: &term_ord_mapping,
         merge_row_order,
     };
-    serialize_column_mappable_to_u64(column_index, &remapped_term_ordinals_values, output)?;
+    serialize_column_mappable_to_u64(
+        column_index,
+        &remapped_term_ordinals_values,
+        &DEFAULT_U64_CODEC_TYPES,
+        output,
+    )?;
     output.write_all(&dictionary_num_bytes.to_le_bytes())?;
     Ok(())
 }
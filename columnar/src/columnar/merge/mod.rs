@@ -2,7 +2,7 @@ mod merge_dict_column;
 mod merge_mapping;
 mod term_merger;
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
 use std::net::Ipv6Addr;
 use std::sync::Arc;
@@ -11,7 +11,9 @@ pub use merge_mapping::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
 
 use super::writer::ColumnarSerializer;
 use crate::column::{serialize_column_mappable_to_u64, serialize_column_mappable_to_u128};
-use crate::column_values::MergedColumnValues;
+use crate::column_values::{
+    CodecType, DEFAULT_U64_CODEC_TYPES, MergedColumnValues, MonotonicallyMappableToU128,
+};
 use crate::columnar::ColumnarReader;
 use crate::columnar::merge::merge_dict_column::merge_bytes_or_str_column;
 use crate::columnar::writer::CompatibleNumericalTypes;
@@ -38,6 +40,8 @@ pub(crate) enum ColumnTypeCategory {
     Bool,
     IpAddr,
     DateTime,
+    U128,
+    GeoPoint,
 }
 
 impl From<ColumnType> for ColumnTypeCategory {
@@ -51,6 +55,8 @@ impl From<ColumnType> for ColumnTypeCategory {
             ColumnType::Bool => ColumnTypeCategory::Bool,
             ColumnType::IpAddr => ColumnTypeCategory::IpAddr,
             ColumnType::DateTime => ColumnTypeCategory::DateTime,
+            ColumnType::U128 => ColumnTypeCategory::U128,
+            ColumnType::GeoPoint => ColumnTypeCategory::GeoPoint,
         }
     }
 }
@@ -78,6 +84,26 @@ pub fn merge_columnar(
     required_columns: &[(String, ColumnType)],
     merge_row_order: MergeRowOrder,
     output: &mut impl io::Write,
+) -> io::Result<()> {
+    merge_columnar_with_codec_types(
+        columnar_readers,
+        required_columns,
+        &HashMap::new(),
+        merge_row_order,
+        output,
+    )
+}
+
+/// Same as [`merge_columnar`], restricting the codecs considered to encode the values of some of
+/// the numerical, bool and datetime columns.
+///
+/// See [`ColumnarWriter::record_column_codec_types`](crate::ColumnarWriter::record_column_codec_types).
+pub fn merge_columnar_with_codec_types(
+    columnar_readers: &[&ColumnarReader],
+    required_columns: &[(String, ColumnType)],
+    column_codec_types: &HashMap<String, Vec<CodecType>>,
+    merge_row_order: MergeRowOrder,
+    output: &mut impl io::Write,
 ) -> io::Result<()> {
     let mut serializer = ColumnarSerializer::new(output);
     let num_docs_per_columnar = columnar_readers
@@ -100,6 +126,10 @@ pub fn merge_columnar(
         assert_eq!(columns.len(), columnar_readers.len());
         coerce_columns(column_type_after_merge, &mut columns)?;
 
+        let codec_types: &[CodecType] = column_codec_types
+            .get(&column_name)
+            .map(Vec::as_slice)
+            .unwrap_or(&DEFAULT_U64_CODEC_TYPES);
         let mut column_serializer =
             serializer.start_serialize_column(column_name.as_bytes(), column_type_after_merge);
         merge_column(
@@ -107,6 +137,7 @@ pub fn merge_columnar(
             &num_docs_per_columnar,
             columns,
             &merge_row_order,
+            codec_types,
             &mut column_serializer,
         )?;
         column_serializer.finalize()?;
@@ -123,7 +154,11 @@ fn dynamic_column_to_u64_monotonic(dynamic_column: DynamicColumn) -> Option<Colu
         DynamicColumn::U64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::F64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::DateTime(column) => Some(column.to_u64_monotonic()),
-        DynamicColumn::IpAddr(_) | DynamicColumn::Bytes(_) | DynamicColumn::Str(_) => None,
+        DynamicColumn::GeoPoint(column) => Some(column.to_u64_monotonic()),
+        DynamicColumn::IpAddr(_)
+        | DynamicColumn::U128(_)
+        | DynamicColumn::Bytes(_)
+        | DynamicColumn::Str(_) => None,
     }
 }
 
@@ -132,6 +167,7 @@ fn merge_column(
     num_docs_per_column: &[u32],
     columns_to_merge: Vec<Option<DynamicColumn>>,
     merge_row_order: &MergeRowOrder,
+    codec_types: &[CodecType],
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
     match column_type {
@@ -139,6 +175,7 @@ fn merge_column(
         | ColumnType::U64
         | ColumnType::F64
         | ColumnType::DateTime
+        | ColumnType::GeoPoint
         | ColumnType::Bool => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
             let mut column_values: Vec<Option<Arc<dyn ColumnValues>>> =
@@ -164,35 +201,26 @@ fn merge_column(
                 column_values: &column_values[..],
                 merge_row_order,
             };
-            serialize_column_mappable_to_u64(merged_column_index, &merge_column_values, wrt)?;
+            serialize_column_mappable_to_u64(
+                merged_column_index,
+                &merge_column_values,
+                codec_types,
+                wrt,
+            )?;
         }
         ColumnType::IpAddr => {
-            let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
-            let mut column_values: Vec<Option<Arc<dyn ColumnValues<Ipv6Addr>>>> =
-                Vec::with_capacity(columns_to_merge.len());
-            for (i, dynamic_column_opt) in columns_to_merge.into_iter().enumerate() {
-                if let Some(DynamicColumn::IpAddr(Column { index: idx, values })) =
-                    dynamic_column_opt
-                {
-                    column_indexes.push(idx);
-                    column_values.push(Some(values));
-                } else {
-                    column_indexes.push(ColumnIndex::Empty {
-                        num_docs: num_docs_per_column[i],
-                    });
-                    column_values.push(None);
-                }
-            }
-
-            let merged_column_index =
-                crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
-            let merge_column_values = MergedColumnValues {
-                column_indexes: &column_indexes[..],
-                column_values: &column_values,
-                merge_row_order,
-            };
-
-            serialize_column_mappable_to_u128(merged_column_index, &merge_column_values, wrt)?;
+            let columns: Vec<Option<Column<Ipv6Addr>>> = columns_to_merge
+                .into_iter()
+                .map(|dynamic_column_opt| dynamic_column_opt.and_then(Option::from))
+                .collect();
+            merge_column_mappable_to_u128(columns, num_docs_per_column, merge_row_order, wrt)?;
+        }
+        ColumnType::U128 => {
+            let columns: Vec<Option<Column<u128>>> = columns_to_merge
+                .into_iter()
+                .map(|dynamic_column_opt| dynamic_column_opt.and_then(Option::from))
+                .collect();
+            merge_column_mappable_to_u128(columns, num_docs_per_column, merge_row_order, wrt)?;
         }
         ColumnType::Bytes | ColumnType::Str => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
@@ -224,6 +252,38 @@ fn merge_column(
     Ok(())
 }
 
+fn merge_column_mappable_to_u128<T: MonotonicallyMappableToU128>(
+    columns_to_merge: Vec<Option<Column<T>>>,
+    num_docs_per_column: &[u32],
+    merge_row_order: &MergeRowOrder,
+    wrt: &mut impl io::Write,
+) -> io::Result<()> {
+    let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
+    let mut column_values: Vec<Option<Arc<dyn ColumnValues<T>>>> =
+        Vec::with_capacity(columns_to_merge.len());
+    for (i, column_opt) in columns_to_merge.into_iter().enumerate() {
+        if let Some(Column { index: idx, values }) = column_opt {
+            column_indexes.push(idx);
+            column_values.push(Some(values));
+        } else {
+            column_indexes.push(ColumnIndex::Empty {
+                num_docs: num_docs_per_column[i],
+            });
+            column_values.push(None);
+        }
+    }
+
+    let merged_column_index =
+        crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
+    let merge_column_values = MergedColumnValues {
+        column_indexes: &column_indexes[..],
+        column_values: &column_values,
+        merge_row_order,
+    };
+
+    serialize_column_mappable_to_u128(merged_column_index, &merge_column_values, wrt)
+}
+
 struct GroupedColumns {
     required_column_type: Option<ColumnType>,
     columns: Vec<Option<DynamicColumn>>,
@@ -467,7 +527,9 @@ fn min_max_if_numerical(column: &DynamicColumn) -> Option<(NumericalValue, Numer
         DynamicColumn::F64(column) => Some((column.min_value().into(), column.max_value().into())),
         DynamicColumn::Bool(_)
         | DynamicColumn::IpAddr(_)
+        | DynamicColumn::U128(_)
         | DynamicColumn::DateTime(_)
+        | DynamicColumn::GeoPoint(_)
         | DynamicColumn::Bytes(_)
         | DynamicColumn::Str(_) => None,
     }
@@ -2,12 +2,17 @@ mod column_type;
 mod format_version;
 mod merge;
 mod reader;
+mod updates;
 mod writer;
 
 pub use column_type::{ColumnType, HasAssociatedColumnType};
 pub use format_version::{CURRENT_VERSION, Version};
 #[cfg(test)]
 pub(crate) use merge::ColumnTypeCategory;
-pub use merge::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, merge_columnar};
+pub use merge::{
+    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, merge_columnar,
+    merge_columnar_with_codec_types,
+};
 pub use reader::ColumnarReader;
+pub use updates::ColumnValueUpdates;
 pub use writer::ColumnarWriter;
@@ -5,7 +5,7 @@ use common::file_slice::FileSlice;
 use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
 use sstable::{Dictionary, RangeSSTable};
 
-use crate::columnar::{ColumnType, format_version};
+use crate::columnar::{ColumnType, ColumnValueUpdates, format_version};
 use crate::dynamic_column::DynamicColumnHandle;
 use crate::{RowId, Version};
 
@@ -21,6 +21,7 @@ pub struct ColumnarReader {
     column_data: FileSlice,
     num_docs: RowId,
     format_version: Version,
+    updates: ColumnValueUpdates,
 }
 
 impl fmt::Debug for ColumnarReader {
@@ -56,6 +57,7 @@ fn read_all_columns_in_stream(
     mut stream: sstable::Streamer<'_, RangeSSTable>,
     column_data: &FileSlice,
     format_version: Version,
+    updates: &ColumnValueUpdates,
 ) -> io::Result<Vec<DynamicColumnHandle>> {
     let mut results = Vec::new();
     while stream.advance() {
@@ -67,10 +69,13 @@ fn read_all_columns_in_stream(
             .map_err(|_| io_invalid_data(format!("Unknown column code `{column_code}`")))?;
         let range = stream.value();
         let file_slice = column_data.slice(range.start as usize..range.end as usize);
+        // The last two bytes are respectively the 0u8 separator and the column_type.
+        let column_name = String::from_utf8_lossy(&key_bytes[..key_bytes.len() - 2]);
         let dynamic_column_handle = DynamicColumnHandle {
             file_slice,
             column_type,
             format_version,
+            updated_values: updates.column_updates(&column_name, column_type),
         };
         results.push(dynamic_column_handle);
     }
@@ -114,9 +119,20 @@ impl ColumnarReader {
             column_data,
             num_docs: num_rows,
             format_version,
+            updates: ColumnValueUpdates::default(),
         })
     }
 
+    /// Returns a reader in which the values of the columns are overridden by `updates`.
+    pub fn with_updates(self, updates: ColumnValueUpdates) -> ColumnarReader {
+        ColumnarReader { updates, ..self }
+    }
+
+    /// Returns the updates overriding the values of the columns.
+    pub fn updates(&self) -> &ColumnValueUpdates {
+        &self.updates
+    }
+
     pub fn num_docs(&self) -> RowId {
         self.num_docs
     }
@@ -145,6 +161,7 @@ impl ColumnarReader {
                     file_slice,
                     column_type,
                     format_version: self.format_version,
+                    updated_values: self.updates.column_updates(&column_name, column_type),
                 };
                 Some((column_name, column_handle))
             } else {
@@ -168,7 +185,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.updates,
+        )
     }
 
     /// Get all columns for the given column name.
@@ -178,7 +200,12 @@ impl ColumnarReader {
     pub fn read_columns(&self, column_name: &str) -> io::Result<Vec<DynamicColumnHandle>> {
         let prefix = column_dictionary_prefix_for_column_name(column_name);
         let stream = self.column_dictionary.prefix_range(prefix).into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.updates,
+        )
     }
 
     pub async fn read_subpath_columns_async(
@@ -191,7 +218,12 @@ impl ColumnarReader {
             .prefix_range(prefix)
             .into_stream_async()
             .await?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.updates,
+        )
     }
 
     /// Get all inner columns for a given JSON prefix, i.e columns for which the name starts
@@ -205,7 +237,12 @@ impl ColumnarReader {
             .column_dictionary
             .prefix_range(prefix.as_bytes())
             .into_stream()?;
-        read_all_columns_in_stream(stream, &self.column_data, self.format_version)
+        read_all_columns_in_stream(
+            stream,
+            &self.column_data,
+            self.format_version,
+            &self.updates,
+        )
     }
 
     /// Return the number of columns in the columnar.
@@ -218,7 +255,7 @@ impl ColumnarReader {
 mod tests {
     use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
 
-    use crate::{ColumnType, ColumnarReader, ColumnarWriter};
+    use crate::{ColumnType, ColumnValueUpdates, ColumnarReader, ColumnarWriter, DynamicColumn};
 
     #[test]
     fn test_list_columns() {
@@ -269,6 +306,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_columns_with_updates() {
+        let mut columnar_writer = ColumnarWriter::default();
+        columnar_writer.record_column_type("col", ColumnType::U64, false);
+        columnar_writer.record_numerical(0, "col", 1u64);
+        columnar_writer.record_numerical(2, "col", 3u64);
+        columnar_writer.record_numerical(3, "col", 4u64);
+        columnar_writer.record_numerical(3, "col", 5u64);
+        let mut buffer = Vec::new();
+        columnar_writer.serialize(4, &mut buffer).unwrap();
+        let mut updates = ColumnValueUpdates::default();
+        updates.insert("col", ColumnType::U64, 0, 10);
+        updates.insert("col", ColumnType::U64, 1, 11);
+        updates.insert("col", ColumnType::U64, 3, 12);
+        updates.insert("col", ColumnType::I64, 2, 13);
+        let columnar = ColumnarReader::open(buffer).unwrap().with_updates(updates);
+        let columns = columnar.read_columns("col").unwrap();
+        assert_eq!(columns.len(), 1);
+        let DynamicColumn::U64(column) = columns[0].open().unwrap() else {
+            panic!("expected a u64 column");
+        };
+        let vals_for_doc = |doc| column.values_for_doc(doc).collect::<Vec<u64>>();
+        assert_eq!(vals_for_doc(0), [10]);
+        // Docs without a value or with several values are not updated.
+        assert!(vals_for_doc(1).is_empty());
+        assert_eq!(vals_for_doc(2), [3]);
+        assert_eq!(vals_for_doc(3), [4, 5]);
+        assert_eq!(column.max_value(), 10);
+
+        let (_, column_handle) = columnar.list_columns().unwrap().pop().unwrap();
+        let lenient_column = column_handle.open_u64_lenient().unwrap().unwrap();
+        assert_eq!(lenient_column.first(0), Some(10));
+    }
+
     #[test]
     fn test_read_subpath_columns() {
         let mut columnar_writer = ColumnarWriter::default();
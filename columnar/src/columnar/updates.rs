@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+use common::{BinarySerializable, VInt};
+
+use crate::RowId;
+use crate::columnar::ColumnType;
+
+/// Values overriding the values stored in a columnar, for some rows.
+///
+/// Values are expressed in their `u64` internal representation (see
+/// [`MonotonicallyMappableToU64`](crate::MonotonicallyMappableToU64)), and can only
+/// target numerical, boolean and datetime columns.
+///
+/// An update only applies to rows holding exactly one value in the targeted column.
+/// Rows without any value, or with several values, are left untouched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnValueUpdates {
+    columns: BTreeMap<(String, ColumnType), Arc<BTreeMap<RowId, u64>>>,
+}
+
+impl ColumnValueUpdates {
+    /// Records that `row_id` should now have the value `value` in the column
+    /// `(column_name, column_type)`.
+    ///
+    /// Overrides any previous update for the same row.
+    pub fn insert(
+        &mut self,
+        column_name: &str,
+        column_type: ColumnType,
+        row_id: RowId,
+        value: u64,
+    ) {
+        let column_updates = self
+            .columns
+            .entry((column_name.to_string(), column_type))
+            .or_default();
+        Arc::make_mut(column_updates).insert(row_id, value);
+    }
+
+    /// Applies all of the updates of `other` on top of `self`.
+    pub fn merge(&mut self, other: &ColumnValueUpdates) {
+        for ((column_name, column_type), other_updates) in &other.columns {
+            for (&row_id, &value) in other_updates.iter() {
+                self.insert(column_name, *column_type, row_id, value);
+            }
+        }
+    }
+
+    /// Returns true if there are no updates.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Returns the updates associated with a given column, if any.
+    pub(crate) fn column_updates(
+        &self,
+        column_name: &str,
+        column_type: ColumnType,
+    ) -> Option<Arc<BTreeMap<RowId, u64>>> {
+        self.columns
+            .get(&(column_name.to_string(), column_type))
+            .cloned()
+    }
+}
+
+impl BinarySerializable for ColumnValueUpdates {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.columns.len() as u64).serialize(writer)?;
+        for ((column_name, column_type), column_updates) in &self.columns {
+            column_name.serialize(writer)?;
+            column_type.to_code().serialize(writer)?;
+            VInt(column_updates.len() as u64).serialize(writer)?;
+            for (row_id, value) in column_updates.iter() {
+                row_id.serialize(writer)?;
+                value.serialize(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<ColumnValueUpdates> {
+        let num_columns = VInt::deserialize(reader)?.val();
+        let mut columns = BTreeMap::new();
+        for _ in 0..num_columns {
+            let column_name = String::deserialize(reader)?;
+            let column_type = ColumnType::try_from_code(u8::deserialize(reader)?)?;
+            let num_updates = VInt::deserialize(reader)?.val();
+            let mut column_updates = BTreeMap::new();
+            for _ in 0..num_updates {
+                let row_id = RowId::deserialize(reader)?;
+                let value = u64::deserialize(reader)?;
+                column_updates.insert(row_id, value);
+            }
+            columns.insert((column_name, column_type), Arc::new(column_updates));
+        }
+        Ok(ColumnValueUpdates { columns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::BinarySerializable;
+
+    use super::ColumnValueUpdates;
+    use crate::ColumnType;
+
+    #[test]
+    fn test_column_value_updates_merge() {
+        let mut updates = ColumnValueUpdates::default();
+        assert!(updates.is_empty());
+        updates.insert("popularity", ColumnType::U64, 3, 10);
+        updates.insert("popularity", ColumnType::U64, 5, 11);
+        let mut other = ColumnValueUpdates::default();
+        other.insert("popularity", ColumnType::U64, 3, 12);
+        other.insert("price", ColumnType::F64, 1, 13);
+        updates.merge(&other);
+        let popularity = updates
+            .column_updates("popularity", ColumnType::U64)
+            .unwrap();
+        assert_eq!(
+            popularity.iter().collect::<Vec<_>>(),
+            [(&3, &12), (&5, &11)]
+        );
+        assert!(
+            updates
+                .column_updates("popularity", ColumnType::I64)
+                .is_none()
+        );
+        assert_eq!(
+            updates
+                .column_updates("price", ColumnType::F64)
+                .unwrap()
+                .get(&1),
+            Some(&13)
+        );
+    }
+
+    #[test]
+    fn test_column_value_updates_serialization() {
+        let mut updates = ColumnValueUpdates::default();
+        updates.insert("popularity", ColumnType::U64, 3, 10);
+        updates.insert("popularity", ColumnType::U64, 1_000_000, u64::MAX);
+        updates.insert("date", ColumnType::DateTime, 7, 2);
+        let mut buffer = Vec::new();
+        updates.serialize(&mut buffer).unwrap();
+        let deserialized = ColumnValueUpdates::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deserialized, updates);
+    }
+}
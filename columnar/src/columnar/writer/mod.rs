@@ -3,6 +3,7 @@ mod column_writers;
 mod serializer;
 mod value_index;
 
+use std::collections::HashMap;
 use std::io;
 use std::net::Ipv6Addr;
 
@@ -14,7 +15,9 @@ pub(crate) use serializer::ColumnarSerializer;
 use stacker::{Addr, ArenaHashMap, MemoryArena};
 
 use crate::column_index::{SerializableColumnIndex, SerializableOptionalIndex};
-use crate::column_values::{MonotonicallyMappableToU64, MonotonicallyMappableToU128};
+use crate::column_values::{
+    CodecType, DEFAULT_U64_CODEC_TYPES, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
+};
 use crate::columnar::column_type::ColumnType;
 use crate::columnar::writer::column_writers::{
     ColumnWriter, NumericalColumnWriter, StrOrBytesColumnWriter,
@@ -22,7 +25,7 @@ use crate::columnar::writer::column_writers::{
 use crate::columnar::writer::value_index::{IndexBuilder, PreallocatedIndexBuilders};
 use crate::dictionary::{DictionaryBuilder, TermIdMapping, UnorderedId};
 use crate::value::{Coerce, NumericalType, NumericalValue};
-use crate::{Cardinality, RowId};
+use crate::{Cardinality, GeoPoint, RowId};
 
 /// This is a set of buffers that are used to temporarily write the values into before passing them
 /// to the fast field codecs.
@@ -31,6 +34,7 @@ struct SpareBuffers {
     value_index_builders: PreallocatedIndexBuilders,
     u64_values: Vec<u64>,
     ip_addr_values: Vec<Ipv6Addr>,
+    u128_values: Vec<u128>,
 }
 
 /// Makes it possible to create a new columnar.
@@ -52,11 +56,18 @@ pub struct ColumnarWriter {
     datetime_field_hash_map: ArenaHashMap,
     bool_field_hash_map: ArenaHashMap,
     ip_addr_field_hash_map: ArenaHashMap,
+    // Every hash map reserves a memory page upfront. u128 columns being rare, this one is only
+    // allocated on use.
+    u128_field_hash_map: Option<ArenaHashMap>,
+    // Allocated on use, like the u128 hash map.
+    geo_point_field_hash_map: Option<ArenaHashMap>,
     bytes_field_hash_map: ArenaHashMap,
     str_field_hash_map: ArenaHashMap,
     arena: MemoryArena,
     // Dictionaries used to store dictionary-encoded values.
     dictionaries: Vec<DictionaryBuilder>,
+    // Codecs to pick from for some of the columns, instead of the default ones.
+    column_codec_types: HashMap<Vec<u8>, Vec<CodecType>>,
     buffers: SpareBuffers,
 }
 
@@ -68,6 +79,14 @@ impl ColumnarWriter {
             + self.bytes_field_hash_map.mem_usage()
             + self.str_field_hash_map.mem_usage()
             + self.ip_addr_field_hash_map.mem_usage()
+            + self
+                .u128_field_hash_map
+                .as_ref()
+                .map_or(0, |hash_map| hash_map.mem_usage())
+            + self
+                .geo_point_field_hash_map
+                .as_ref()
+                .map_or(0, |hash_map| hash_map.mem_usage())
             + self.datetime_field_hash_map.mem_usage()
             + self
                 .dictionaries
@@ -145,9 +164,40 @@ impl ColumnarWriter {
                 column_name.as_bytes(),
                 |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
             ),
+            ColumnType::U128 => self
+                .u128_field_hash_map
+                .get_or_insert_with(ArenaHashMap::default)
+                .mutate_or_create(
+                    column_name.as_bytes(),
+                    |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
+                ),
+            ColumnType::GeoPoint => self
+                .geo_point_field_hash_map
+                .get_or_insert_with(ArenaHashMap::default)
+                .mutate_or_create(
+                    column_name.as_bytes(),
+                    |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
+                ),
         }
     }
 
+    /// Restricts the codecs considered to encode the values of the numerical, bool, datetime and
+    /// geo point columns named `column_name`. The most compact of them is picked.
+    ///
+    /// By default, the most compact of [`DEFAULT_U64_CODEC_TYPES`] is picked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `codec_types` is empty.
+    pub fn record_column_codec_types(&mut self, column_name: &str, codec_types: &[CodecType]) {
+        assert!(
+            !codec_types.is_empty(),
+            "At least one codec is required for column {column_name:?}"
+        );
+        self.column_codec_types
+            .insert(column_name.as_bytes().to_vec(), codec_types.to_vec());
+    }
+
     pub fn record_numerical<T: Into<NumericalValue> + Copy>(
         &mut self,
         doc: RowId,
@@ -177,6 +227,22 @@ impl ColumnarWriter {
         );
     }
 
+    pub fn record_u128(&mut self, doc: RowId, column_name: &str, val: u128) {
+        let (hash_map, arena) = (
+            self.u128_field_hash_map
+                .get_or_insert_with(ArenaHashMap::default),
+            &mut self.arena,
+        );
+        hash_map.mutate_or_create(
+            column_name.as_bytes(),
+            |column_opt: Option<ColumnWriter>| {
+                let mut column: ColumnWriter = column_opt.unwrap_or_default();
+                column.record(doc, val, arena);
+                column
+            },
+        );
+    }
+
     pub fn record_bool(&mut self, doc: RowId, column_name: &str, val: bool) {
         let (hash_map, arena) = (&mut self.bool_field_hash_map, &mut self.arena);
         hash_map.mutate_or_create(
@@ -205,6 +271,22 @@ impl ColumnarWriter {
         );
     }
 
+    pub fn record_geo_point(&mut self, doc: RowId, column_name: &str, geo_point: GeoPoint) {
+        let (hash_map, arena) = (
+            self.geo_point_field_hash_map
+                .get_or_insert_with(ArenaHashMap::default),
+            &mut self.arena,
+        );
+        hash_map.mutate_or_create(
+            column_name.as_bytes(),
+            |column_opt: Option<ColumnWriter>| {
+                let mut column: ColumnWriter = column_opt.unwrap_or_default();
+                column.record(doc, NumericalValue::U64(geo_point.to_u64()), arena);
+                column
+            },
+        );
+    }
+
     pub fn record_str(&mut self, doc: RowId, column_name: &str, value: &str) {
         let (hash_map, arena, dictionaries) = (
             &mut self.str_field_hash_map,
@@ -279,6 +361,18 @@ impl ColumnarWriter {
                 .iter()
                 .map(|(column_name, addr)| (column_name, ColumnType::IpAddr, addr)),
         );
+        columns.extend(
+            self.u128_field_hash_map
+                .iter()
+                .flat_map(|hash_map| hash_map.iter())
+                .map(|(column_name, addr)| (column_name, ColumnType::U128, addr)),
+        );
+        columns.extend(
+            self.geo_point_field_hash_map
+                .iter()
+                .flat_map(|hash_map| hash_map.iter())
+                .map(|(column_name, addr)| (column_name, ColumnType::GeoPoint, addr)),
+        );
         columns.extend(
             self.datetime_field_hash_map
                 .iter()
@@ -294,6 +388,11 @@ impl ColumnarWriter {
                 // index).
                 continue;
             }
+            let codec_types: &[CodecType] = self
+                .column_codec_types
+                .get(column_name)
+                .map(Vec::as_slice)
+                .unwrap_or(&DEFAULT_U64_CODEC_TYPES);
             match column_type {
                 ColumnType::Bool => {
                     let column_writer: ColumnWriter = self.bool_field_hash_map.read(addr);
@@ -304,6 +403,7 @@ impl ColumnarWriter {
                         cardinality,
                         num_docs,
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -323,6 +423,24 @@ impl ColumnarWriter {
                     )?;
                     column_serializer.finalize()?;
                 }
+                ColumnType::U128 => {
+                    let column_writer: ColumnWriter = self
+                        .u128_field_hash_map
+                        .as_ref()
+                        .expect("u128 columns are only listed if their hash map exists")
+                        .read(addr);
+                    let cardinality = column_writer.get_cardinality(num_docs);
+                    let mut column_serializer =
+                        serializer.start_serialize_column(column_name, ColumnType::U128);
+                    serialize_u128_column(
+                        cardinality,
+                        num_docs,
+                        column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        buffers,
+                        &mut column_serializer,
+                    )?;
+                    column_serializer.finalize()?;
+                }
                 ColumnType::Bytes | ColumnType::Str => {
                     let str_or_bytes_column_writer: StrOrBytesColumnWriter =
                         if column_type == ColumnType::Bytes {
@@ -362,6 +480,7 @@ impl ColumnarWriter {
                         num_docs,
                         numerical_type,
                         numerical_column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -377,6 +496,27 @@ impl ColumnarWriter {
                         num_docs,
                         NumericalType::I64,
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
+                        buffers,
+                        &mut column_serializer,
+                    )?;
+                    column_serializer.finalize()?;
+                }
+                ColumnType::GeoPoint => {
+                    let column_writer: ColumnWriter = self
+                        .geo_point_field_hash_map
+                        .as_ref()
+                        .expect("geo point columns are only listed if their hash map exists")
+                        .read(addr);
+                    let cardinality = column_writer.get_cardinality(num_docs);
+                    let mut column_serializer =
+                        serializer.start_serialize_column(column_name, ColumnType::GeoPoint);
+                    serialize_numerical_column(
+                        cardinality,
+                        num_docs,
+                        NumericalType::U64,
+                        column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        codec_types,
                         buffers,
                         &mut column_serializer,
                     )?;
@@ -427,6 +567,7 @@ fn serialize_bytes_or_str_column(
         cardinality,
         num_docs,
         sort_values_within_row,
+        &DEFAULT_U64_CODEC_TYPES,
         value_index_builders,
         u64_values,
         &mut wrt,
@@ -440,6 +581,7 @@ fn serialize_numerical_column(
     num_docs: RowId,
     numerical_type: NumericalType,
     op_iterator: impl Iterator<Item = ColumnOperation<NumericalValue>>,
+    codec_types: &[CodecType],
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -455,6 +597,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -466,6 +609,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -477,6 +621,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -490,6 +635,7 @@ fn serialize_bool_column(
     cardinality: Cardinality,
     num_docs: RowId,
     column_operations_it: impl Iterator<Item = ColumnOperation<bool>>,
+    codec_types: &[CodecType],
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
 ) -> io::Result<()> {
@@ -506,6 +652,7 @@ fn serialize_bool_column(
         cardinality,
         num_docs,
         false,
+        codec_types,
         value_index_builders,
         u64_values,
         wrt,
@@ -536,6 +683,29 @@ fn serialize_ip_addr_column(
     Ok(())
 }
 
+fn serialize_u128_column(
+    cardinality: Cardinality,
+    num_docs: RowId,
+    column_operations_it: impl Iterator<Item = ColumnOperation<u128>>,
+    buffers: &mut SpareBuffers,
+    wrt: &mut impl io::Write,
+) -> io::Result<()> {
+    let SpareBuffers {
+        value_index_builders,
+        u128_values,
+        ..
+    } = buffers;
+    send_to_serialize_column_mappable_to_u128(
+        column_operations_it,
+        cardinality,
+        num_docs,
+        value_index_builders,
+        u128_values,
+        wrt,
+    )?;
+    Ok(())
+}
+
 fn send_to_serialize_column_mappable_to_u128<
     T: Copy + Ord + std::fmt::Debug + Send + Sync + MonotonicallyMappableToU128 + PartialOrd,
 >(
@@ -581,11 +751,13 @@ fn send_to_serialize_column_mappable_to_u128<
     Ok(())
 }
 
+#[expect(clippy::too_many_arguments)]
 fn send_to_serialize_column_mappable_to_u64(
     op_iterator: impl Iterator<Item = ColumnOperation<u64>>,
     cardinality: Cardinality,
     num_rows: RowId,
     sort_values_within_row: bool,
+    codec_types: &[CodecType],
     value_index_builders: &mut PreallocatedIndexBuilders,
     values: &mut Vec<u64>,
     mut wrt: impl io::Write,
@@ -625,6 +797,7 @@ fn send_to_serialize_column_mappable_to_u64(
     crate::column::serialize_column_mappable_to_u64(
         serializable_column_index,
         &&values[..],
+        codec_types,
         &mut wrt,
     )?;
     Ok(())
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::net::Ipv6Addr;
 use std::sync::Arc;
 use std::{fmt, io};
@@ -6,9 +7,12 @@ use common::file_slice::FileSlice;
 use common::{ByteCount, DateTime, HasLen, OwnedBytes};
 
 use crate::column::{BytesColumn, Column, StrColumn};
-use crate::column_values::{StrictlyMonotonicFn, monotonic_map_column};
+use crate::column_values::{ColumnCodecStats, StrictlyMonotonicFn, monotonic_map_column};
 use crate::columnar::ColumnType;
-use crate::{Cardinality, ColumnIndex, ColumnValues, NumericalType, Version};
+use crate::{
+    Cardinality, ColumnIndex, ColumnValues, GeoPoint, MonotonicallyMappableToU64, NumericalType,
+    RowId, Version,
+};
 
 #[derive(Clone)]
 pub enum DynamicColumn {
@@ -20,6 +24,8 @@ pub enum DynamicColumn {
     DateTime(Column<DateTime>),
     Bytes(BytesColumn),
     Str(StrColumn),
+    U128(Column<u128>),
+    GeoPoint(Column<GeoPoint>),
 }
 
 impl fmt::Debug for DynamicColumn {
@@ -34,6 +40,8 @@ impl fmt::Debug for DynamicColumn {
             DynamicColumn::DateTime(col) => write!(f, "{col:?}")?,
             DynamicColumn::Bytes(col) => write!(f, "{col:?}")?,
             DynamicColumn::Str(col) => write!(f, "{col:?}")?,
+            DynamicColumn::U128(col) => write!(f, "{col:?}")?,
+            DynamicColumn::GeoPoint(col) => write!(f, "{col:?}")?,
         }
         write!(f, "]")
     }
@@ -50,6 +58,8 @@ impl DynamicColumn {
             DynamicColumn::DateTime(c) => &c.index,
             DynamicColumn::Bytes(c) => &c.ords().index,
             DynamicColumn::Str(c) => &c.ords().index,
+            DynamicColumn::U128(c) => &c.index,
+            DynamicColumn::GeoPoint(c) => &c.index,
         }
     }
 
@@ -67,6 +77,8 @@ impl DynamicColumn {
             DynamicColumn::DateTime(c) => c.values.num_vals(),
             DynamicColumn::Bytes(c) => c.ords().values.num_vals(),
             DynamicColumn::Str(c) => c.ords().values.num_vals(),
+            DynamicColumn::U128(c) => c.values.num_vals(),
+            DynamicColumn::GeoPoint(c) => c.values.num_vals(),
         }
     }
 
@@ -80,6 +92,8 @@ impl DynamicColumn {
             DynamicColumn::DateTime(_) => ColumnType::DateTime,
             DynamicColumn::Bytes(_) => ColumnType::Bytes,
             DynamicColumn::Str(_) => ColumnType::Str,
+            DynamicColumn::U128(_) => ColumnType::U128,
+            DynamicColumn::GeoPoint(_) => ColumnType::GeoPoint,
         }
     }
 
@@ -227,12 +241,16 @@ static_dynamic_conversions!(Column<DateTime>, DateTime);
 static_dynamic_conversions!(StrColumn, Str);
 static_dynamic_conversions!(BytesColumn, Bytes);
 static_dynamic_conversions!(Column<Ipv6Addr>, IpAddr);
+static_dynamic_conversions!(Column<u128>, U128);
+static_dynamic_conversions!(Column<GeoPoint>, GeoPoint);
 
 #[derive(Clone, Debug)]
 pub struct DynamicColumnHandle {
     pub(crate) file_slice: FileSlice,
     pub(crate) column_type: ColumnType,
     pub(crate) format_version: Version,
+    /// Values overriding the values stored in the column, for some docs.
+    pub(crate) updated_values: Option<Arc<BTreeMap<RowId, u64>>>,
 }
 
 impl DynamicColumnHandle {
@@ -248,10 +266,10 @@ impl DynamicColumnHandle {
     }
 
     /// Returns the `u64` fast field reader reader associated with `fields` of types
-    /// Str, u64, i64, f64, bool, ip, or datetime.
+    /// Str, u64, i64, f64, bool, ip, u128, datetime or geo point.
     ///
-    /// Notice that for IpAddr, the fastfield reader will return the u64 representation of the
-    /// IpAddr.
+    /// Notice that for IpAddr and u128, the fastfield reader will return the u64 representation
+    /// of the value. For geo points, it returns their morton code.
     /// In order to convert to u128 back cast to `CompactSpaceU64Accessor` and call
     /// `compact_to_u128`.
     ///
@@ -265,7 +283,7 @@ impl DynamicColumnHandle {
                     crate::column::open_column_bytes(column_bytes, self.format_version)?;
                 Ok(Some(column.term_ord_column))
             }
-            ColumnType::IpAddr => {
+            ColumnType::IpAddr | ColumnType::U128 => {
                 let column = crate::column::open_column_u128_as_compact_u64(
                     column_bytes,
                     self.format_version,
@@ -276,14 +294,45 @@ impl DynamicColumnHandle {
             | ColumnType::I64
             | ColumnType::U64
             | ColumnType::F64
-            | ColumnType::DateTime => {
-                let column =
-                    crate::column::open_column_u64::<u64>(column_bytes, self.format_version)?;
+            | ColumnType::DateTime
+            | ColumnType::GeoPoint => {
+                let column = self.open_column_u64::<u64>(column_bytes)?;
                 Ok(Some(column))
             }
         }
     }
 
+    /// Returns the codec used to encode the values of the column, along with the space they take.
+    ///
+    /// Returns `None` for the str, bytes, ip and u128 columns, whose values are not encoded with
+    /// one of the u64-based codecs.
+    pub fn codec_stats(&self) -> io::Result<Option<ColumnCodecStats>> {
+        match self.column_type {
+            ColumnType::Str | ColumnType::Bytes | ColumnType::IpAddr | ColumnType::U128 => Ok(None),
+            ColumnType::Bool
+            | ColumnType::I64
+            | ColumnType::U64
+            | ColumnType::F64
+            | ColumnType::DateTime
+            | ColumnType::GeoPoint => {
+                let column_bytes = self.file_slice.read_bytes()?;
+                let codec_stats = crate::column::open_column_u64_codec_stats(column_bytes)?;
+                Ok(Some(codec_stats))
+            }
+        }
+    }
+
+    fn open_column_u64<T: MonotonicallyMappableToU64>(
+        &self,
+        column_bytes: OwnedBytes,
+    ) -> io::Result<Column<T>> {
+        let column = crate::column::open_column_u64::<T>(column_bytes, self.format_version)?;
+        Ok(match &self.updated_values {
+            Some(updated_values) => column.with_updated_values(updated_values),
+            None => column,
+        })
+    }
+
     fn open_internal(&self, column_bytes: OwnedBytes) -> io::Result<DynamicColumn> {
         let dynamic_column: DynamicColumn = match self.column_type {
             ColumnType::Bytes => {
@@ -292,26 +341,19 @@ impl DynamicColumnHandle {
             ColumnType::Str => {
                 crate::column::open_column_str(column_bytes, self.format_version)?.into()
             }
-            ColumnType::I64 => {
-                crate::column::open_column_u64::<i64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::U64 => {
-                crate::column::open_column_u64::<u64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::F64 => {
-                crate::column::open_column_u64::<f64>(column_bytes, self.format_version)?.into()
-            }
-            ColumnType::Bool => {
-                crate::column::open_column_u64::<bool>(column_bytes, self.format_version)?.into()
-            }
+            ColumnType::I64 => self.open_column_u64::<i64>(column_bytes)?.into(),
+            ColumnType::U64 => self.open_column_u64::<u64>(column_bytes)?.into(),
+            ColumnType::F64 => self.open_column_u64::<f64>(column_bytes)?.into(),
+            ColumnType::Bool => self.open_column_u64::<bool>(column_bytes)?.into(),
             ColumnType::IpAddr => {
                 crate::column::open_column_u128::<Ipv6Addr>(column_bytes, self.format_version)?
                     .into()
             }
-            ColumnType::DateTime => {
-                crate::column::open_column_u64::<DateTime>(column_bytes, self.format_version)?
-                    .into()
+            ColumnType::DateTime => self.open_column_u64::<DateTime>(column_bytes)?.into(),
+            ColumnType::U128 => {
+                crate::column::open_column_u128::<u128>(column_bytes, self.format_version)?.into()
             }
+            ColumnType::GeoPoint => self.open_column_u64::<GeoPoint>(column_bytes)?.into(),
         };
         Ok(dynamic_column)
     }
@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+
+use crate::MonotonicallyMappableToU64;
+
+const LAT_RANGE: f64 = 180.0;
+const LON_RANGE: f64 = 360.0;
+const NUM_CELLS: f64 = (1u64 << 32) as f64;
+const EARTH_MEAN_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A point on earth, given by its latitude and longitude in degrees.
+///
+/// In a column, geo points are stored as `u64`: the latitude and the longitude are each quantized
+/// on 32 bits, and their bits interleaved (morton encoding). The stored coordinates are therefore
+/// precise to about a centimeter.
+///
+/// Points are ordered and compared by their morton code. The order has no geographical meaning,
+/// but points close in space tend to be close in this order.
+#[derive(Clone, Copy, Debug)]
+pub struct GeoPoint {
+    lat: f64,
+    lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a new geo point.
+    ///
+    /// Returns `None` if the latitude is not within `[-90, 90]` or the longitude is not within
+    /// `[-180, 180]`.
+    pub fn new(lat: f64, lon: f64) -> Option<GeoPoint> {
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return None;
+        }
+        Some(GeoPoint { lat, lon })
+    }
+
+    /// Returns the latitude in degrees.
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    /// Returns the longitude in degrees.
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Returns the great-circle distance to `other` in meters, using the haversine formula.
+    pub fn distance_meters(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_MEAN_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+fn quantize(val: f64, min: f64, range: f64) -> u32 {
+    ((val - min) / range * NUM_CELLS).min(u32::MAX as f64) as u32
+}
+
+fn dequantize(cell: u32, min: f64, range: f64) -> f64 {
+    cell as f64 * range / NUM_CELLS + min
+}
+
+/// Spreads the bits of `val` over the even bits of a `u64`.
+fn spread_bits(val: u32) -> u64 {
+    let mut val = val as u64;
+    val = (val | (val << 16)) & 0x0000_FFFF_0000_FFFF;
+    val = (val | (val << 8)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val << 2)) & 0x3333_3333_3333_3333;
+    (val | (val << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Inverse of [`spread_bits`]. The odd bits are ignored.
+fn compact_bits(val: u64) -> u32 {
+    let mut val = val & 0x5555_5555_5555_5555;
+    val = (val | (val >> 1)) & 0x3333_3333_3333_3333;
+    val = (val | (val >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    val = (val | (val >> 4)) & 0x00FF_00FF_00FF_00FF;
+    val = (val | (val >> 8)) & 0x0000_FFFF_0000_FFFF;
+    (val | (val >> 16)) as u32
+}
+
+impl MonotonicallyMappableToU64 for GeoPoint {
+    /// Returns the morton code of the point. The longitude takes the odd bits, and the latitude
+    /// the even bits.
+    fn to_u64(self) -> u64 {
+        let lat_cell = quantize(self.lat, -90.0, LAT_RANGE);
+        let lon_cell = quantize(self.lon, -180.0, LON_RANGE);
+        (spread_bits(lon_cell) << 1) | spread_bits(lat_cell)
+    }
+
+    fn from_u64(val: u64) -> GeoPoint {
+        GeoPoint {
+            lat: dequantize(compact_bits(val), -90.0, LAT_RANGE),
+            lon: dequantize(compact_bits(val >> 1), -180.0, LON_RANGE),
+        }
+    }
+}
+
+impl PartialEq for GeoPoint {
+    fn eq(&self, other: &GeoPoint) -> bool {
+        self.to_u64() == other.to_u64()
+    }
+}
+
+impl PartialOrd for GeoPoint {
+    fn partial_cmp(&self, other: &GeoPoint) -> Option<Ordering> {
+        Some(self.to_u64().cmp(&other.to_u64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_geo_point_new() {
+        assert!(GeoPoint::new(90.0, 180.0).is_some());
+        assert!(GeoPoint::new(-90.0, -180.0).is_some());
+        assert!(GeoPoint::new(90.1, 0.0).is_none());
+        assert!(GeoPoint::new(0.0, -180.1).is_none());
+        assert!(GeoPoint::new(f64::NAN, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_geo_point_morton_code() {
+        assert_eq!(GeoPoint::new(-90.0, -180.0).unwrap().to_u64(), 0);
+        assert_eq!(GeoPoint::new(90.0, 180.0).unwrap().to_u64(), u64::MAX);
+        // The longitude takes the odd bits.
+        assert_eq!(GeoPoint::from_u64(0b10).lat(), -90.0);
+        assert!(GeoPoint::from_u64(0b10).lon() > -180.0);
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let tokyo = GeoPoint::new(35.6762, 139.6503).unwrap();
+        assert_eq!(paris.distance_meters(&paris), 0.0);
+        let distance = paris.distance_meters(&tokyo);
+        assert!((distance - 9_712_000.0).abs() < 10_000.0, "{distance}");
+        assert_eq!(distance, tokyo.distance_meters(&paris));
+    }
+
+    proptest! {
+        #[test]
+        fn test_geo_point_roundtrip(lat in -90.0f64..=90.0, lon in -180.0f64..=180.0) {
+            let point = GeoPoint::new(lat, lon).unwrap();
+            let code = point.to_u64();
+            let decoded = GeoPoint::from_u64(code);
+            prop_assert_eq!(decoded.to_u64(), code);
+            prop_assert!((decoded.lat() - lat).abs() < 1e-7);
+            prop_assert!((decoded.lon() - lon).abs() < 1e-7);
+            prop_assert!(point.distance_meters(&decoded) < 0.02);
+        }
+    }
+}
@@ -11,6 +11,8 @@
 //!     associated to field names.
 //!   - **[merge_columnar]**: Contains the functionalities to merge multiple ColumnarReader or
 //!     segments into a single one.
+//!   - **[BlockCache]**: An LRU cache of blocks of columnar files, for directories on which reads
+//!     are expensive.
 //!
 //! - **column**: A single column, which contains
 //!     - [column_index]: Resolves the rows for a document id. Manages the cardinality of the
@@ -30,26 +32,32 @@ use std::fmt::Display;
 use std::io;
 
 mod block_accessor;
+mod block_cache;
 mod column;
 pub mod column_index;
 pub mod column_values;
 mod columnar;
 mod dictionary;
 mod dynamic_column;
+mod geo_point;
 mod iterable;
 pub(crate) mod utils;
 mod value;
 
 pub use block_accessor::ColumnBlockAccessor;
+pub use block_cache::{BlockCache, BlockCacheStats, DEFAULT_BLOCK_NUM_BYTES};
 pub use column::{BytesColumn, Column, StrColumn};
 pub use column_index::ColumnIndex;
 pub use column_values::{
-    ColumnValues, EmptyColumnValues, MonotonicallyMappableToU64, MonotonicallyMappableToU128,
+    CodecType, ColumnCodecStats, ColumnValues, EmptyColumnValues, MonotonicallyMappableToU64,
+    MonotonicallyMappableToU128,
 };
 pub use columnar::{
-    CURRENT_VERSION, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
-    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, Version, merge_columnar,
+    CURRENT_VERSION, ColumnType, ColumnValueUpdates, ColumnarReader, ColumnarWriter,
+    HasAssociatedColumnType, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, Version,
+    merge_columnar, merge_columnar_with_codec_types,
 };
+pub use geo_point::GeoPoint;
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
 
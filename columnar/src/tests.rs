@@ -6,12 +6,12 @@ use common::DateTime;
 use proptest::prelude::*;
 use proptest::sample::subsequence;
 
-use crate::column_values::MonotonicallyMappableToU128;
+use crate::column_values::{CodecType, MonotonicallyMappableToU64, MonotonicallyMappableToU128};
 use crate::columnar::{ColumnType, ColumnTypeCategory};
 use crate::dynamic_column::{DynamicColumn, DynamicColumnHandle};
 use crate::value::{Coerce, NumericalValue};
 use crate::{
-    BytesColumn, Cardinality, Column, ColumnarReader, ColumnarWriter, RowAddr, RowId,
+    BytesColumn, Cardinality, Column, ColumnarReader, ColumnarWriter, GeoPoint, RowAddr, RowId,
     ShuffleMergeOrder, StackMergeOrder,
 };
 
@@ -91,6 +91,109 @@ fn test_dataframe_writer_u64_multivalued() {
     assert_eq!(divisor_col.num_docs(), 7);
 }
 
+#[test]
+fn test_dataframe_writer_column_codec_types() {
+    let mut dataframe_writer = ColumnarWriter::default();
+    dataframe_writer.record_column_codec_types("bitpacked", &[CodecType::Bitpacked]);
+    for row_id in 0..1_000u32 {
+        let val = row_id as u64 * 1_000 + row_id as u64 % 10;
+        dataframe_writer.record_numerical(row_id, "bitpacked", val);
+        dataframe_writer.record_numerical(row_id, "auto", val);
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer.serialize(1_000, &mut buffer).unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let codec_stats = |columnar: &ColumnarReader, column_name: &str| {
+        let cols: Vec<DynamicColumnHandle> = columnar.read_columns(column_name).unwrap();
+        cols[0].codec_stats().unwrap().unwrap()
+    };
+    let bitpacked_codec_stats = codec_stats(&columnar, "bitpacked");
+    assert_eq!(bitpacked_codec_stats.codec_type, CodecType::Bitpacked);
+    assert_eq!(bitpacked_codec_stats.num_vals, 1_000);
+    // The amplitude of the values requires 20 bits.
+    assert!(bitpacked_codec_stats.bits_per_value() >= 20.0);
+    let auto_codec_stats = codec_stats(&columnar, "auto");
+    assert_eq!(auto_codec_stats.codec_type, CodecType::BlockwiseLinear);
+    assert!(auto_codec_stats.bits_per_value() < 8.0);
+
+    let mut column_codec_types = HashMap::new();
+    column_codec_types.insert("auto".to_string(), vec![CodecType::Bitpacked]);
+    let mut output: Vec<u8> = Vec::new();
+    let stack_merge_order = StackMergeOrder::stack(&[&columnar]);
+    crate::merge_columnar_with_codec_types(
+        &[&columnar],
+        &[],
+        &column_codec_types,
+        crate::MergeRowOrder::Stack(stack_merge_order),
+        &mut output,
+    )
+    .unwrap();
+    let merged_columnar = ColumnarReader::open(output).unwrap();
+    let merged_codec_stats = codec_stats(&merged_columnar, "auto");
+    assert_eq!(merged_codec_stats.codec_type, CodecType::Bitpacked);
+    assert_eq!(merged_codec_stats, bitpacked_codec_stats);
+}
+
+#[test]
+fn test_dataframe_writer_u128() {
+    let mut dataframe_writer = ColumnarWriter::default();
+    dataframe_writer.record_u128(1, "uuid", u128::MAX - 1);
+    dataframe_writer.record_u128(3, "uuid", 1u128 << 100);
+    dataframe_writer.record_u128(3, "uuid", 12);
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer.serialize(5, &mut buffer).unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let cols: Vec<DynamicColumnHandle> = columnar.read_columns("uuid").unwrap();
+    assert_eq!(cols.len(), 1);
+    assert_eq!(cols[0].column_type(), ColumnType::U128);
+    let DynamicColumn::U128(u128_col) = cols[0].open().unwrap() else {
+        panic!();
+    };
+    assert_eq!(u128_col.get_cardinality(), Cardinality::Multivalued);
+    let vals: Vec<Vec<u128>> = (0..5)
+        .map(|row_id| u128_col.values_for_doc(row_id).collect())
+        .collect();
+    assert_eq!(
+        vals,
+        vec![
+            vec![],
+            vec![u128::MAX - 1],
+            vec![],
+            vec![1u128 << 100, 12],
+            vec![]
+        ]
+    );
+    let mut row_ids = Vec::new();
+    u128_col.get_docids_for_value_range(10..=(1u128 << 100), 0..5, &mut row_ids);
+    assert_eq!(row_ids, vec![3]);
+}
+
+#[test]
+fn test_dataframe_writer_geo_point() {
+    let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+    let tokyo = GeoPoint::new(35.6762, 139.6503).unwrap();
+    let mut dataframe_writer = ColumnarWriter::default();
+    dataframe_writer.record_geo_point(1, "location", paris);
+    dataframe_writer.record_geo_point(3, "location", tokyo);
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer.serialize(4, &mut buffer).unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let cols: Vec<DynamicColumnHandle> = columnar.read_columns("location").unwrap();
+    assert_eq!(cols.len(), 1);
+    assert_eq!(cols[0].column_type(), ColumnType::GeoPoint);
+    let DynamicColumn::GeoPoint(geo_point_col) = cols[0].open().unwrap() else {
+        panic!()
+    };
+    assert_eq!(geo_point_col.get_cardinality(), Cardinality::Optional);
+    let vals: Vec<Option<GeoPoint>> = (0..4).map(|row_id| geo_point_col.first(row_id)).collect();
+    assert_eq!(vals, vec![None, Some(paris), None, Some(tokyo)]);
+    let decoded_paris = vals[1].unwrap();
+    assert!(decoded_paris.distance_meters(&paris) < 0.02);
+    // The lenient u64 column exposes the morton codes.
+    let u64_col = cols[0].open_u64_lenient().unwrap().unwrap();
+    assert_eq!(u64_col.first(3), Some(tokyo.to_u64()));
+}
+
 #[test]
 fn test_dataframe_writer_ip_addr() {
     let mut dataframe_writer = ColumnarWriter::default();
@@ -153,6 +256,29 @@ fn test_dataframe_writer_numerical() {
     assert_eq!(column_i64.first(6), None); //< we can change the spec for that one.
 }
 
+#[test]
+fn test_sparse_column_num_bytes() {
+    const NUM_DOCS: u32 = 1_000_000;
+    let mut buffer = Vec::new();
+    let mut columnar_writer = ColumnarWriter::default();
+    // The values are present in 0.1% of the documents.
+    for doc in (0..NUM_DOCS).step_by(1_000) {
+        columnar_writer.record_numerical(doc, "sparse", i64::from(doc) * 7 % 1_000_003);
+    }
+    columnar_writer.serialize(NUM_DOCS, &mut buffer).unwrap();
+    let columnar_reader = ColumnarReader::open(buffer).unwrap();
+    let col_handles = columnar_reader.read_columns("sparse").unwrap();
+    // The size of the column depends on the number of values, not on the number of documents.
+    assert!(col_handles[0].num_bytes().get_bytes() < 10_000);
+    let DynamicColumn::I64(column) = col_handles[0].open().unwrap() else {
+        panic!();
+    };
+    assert_eq!(column.get_cardinality(), Cardinality::Optional);
+    assert_eq!(column.first(999), None);
+    assert_eq!(column.first(5_000), Some(35_000));
+    assert_eq!(column.first(NUM_DOCS - 1_000), Some(992_982));
+}
+
 #[test]
 fn test_dictionary_encoded_str() {
     let mut buffer = Vec::new();
@@ -187,6 +313,46 @@ fn test_dictionary_encoded_str() {
     assert_eq!(term_buffer, "b");
 }
 
+#[test]
+fn test_dictionary_encoded_str_multivalued() {
+    let mut buffer = Vec::new();
+    let mut columnar_writer = ColumnarWriter::default();
+    columnar_writer.record_str(0, "tags", "rust");
+    columnar_writer.record_str(0, "tags", "search");
+    columnar_writer.record_str(2, "tags", "index");
+    columnar_writer.record_str(2, "tags", "rust");
+    columnar_writer.serialize(3, &mut buffer).unwrap();
+    let columnar_reader = ColumnarReader::open(buffer).unwrap();
+    let col_handles = columnar_reader.read_columns("tags").unwrap();
+    let DynamicColumn::Str(str_col) = col_handles[0].open().unwrap() else {
+        panic!();
+    };
+    let mut row_ords: Vec<Vec<u64>> = (0..3)
+        .map(|row_id| str_col.term_ords(row_id).collect())
+        .collect();
+    for ords in &mut row_ords {
+        ords.sort();
+    }
+    assert_eq!(row_ords, vec![vec![1, 2], vec![], vec![0, 1]]);
+    assert_eq!(str_col.term_ord("rust").unwrap(), Some(1));
+    assert_eq!(str_col.term_ord("java").unwrap(), None);
+    let mut terms = Vec::new();
+    assert!(
+        str_col
+            .sorted_ords_to_str_cb([0, 2].into_iter(), |term| {
+                terms.push(term.to_string());
+                Ok(())
+            })
+            .unwrap()
+    );
+    assert_eq!(terms, vec!["index", "search"]);
+    assert!(
+        !str_col
+            .sorted_ords_to_str_cb([3].into_iter(), |_| Ok(()))
+            .unwrap()
+    );
+}
+
 #[test]
 fn test_dictionary_encoded_bytes() {
     let mut buffer = Vec::new();
@@ -252,8 +418,10 @@ enum ColumnValue {
     Bytes(&'static [u8]),
     Numerical(NumericalValue),
     IpAddr(Ipv6Addr),
+    U128(u128),
     Bool(bool),
     DateTime(DateTime),
+    GeoPoint(GeoPoint),
 }
 
 impl<T: Into<NumericalValue>> From<T> for ColumnValue {
@@ -269,8 +437,10 @@ impl ColumnValue {
             ColumnValue::Bytes(_) => ColumnTypeCategory::Bytes,
             ColumnValue::Numerical(_) => ColumnTypeCategory::Numerical,
             ColumnValue::IpAddr(_) => ColumnTypeCategory::IpAddr,
+            ColumnValue::U128(_) => ColumnTypeCategory::U128,
             ColumnValue::Bool(_) => ColumnTypeCategory::Bool,
             ColumnValue::DateTime(_) => ColumnTypeCategory::DateTime,
+            ColumnValue::GeoPoint(_) => ColumnTypeCategory::GeoPoint,
         }
     }
 }
@@ -303,9 +473,11 @@ fn column_value_strategy() -> impl Strategy<Value = ColumnValue> {
             0,
             ip_addr_byte
         ))),
+        1 => (1u128..3u128).prop_map(|val| ColumnValue::U128(val << 100)),
         1 => any::<bool>().prop_map(ColumnValue::Bool),
         1 => (679_723_993i64..1_679_723_995i64)
-            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) })
+            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) }),
+        1 => (1u64..3u64).prop_map(|val| ColumnValue::GeoPoint(GeoPoint::from_u64(val << 40))),
     ]
 }
 
@@ -353,12 +525,18 @@ fn build_columnar_with_mapping(docs: &[Vec<(&'static str, ColumnValue)>]) -> Col
                 ColumnValue::IpAddr(ip_addr) => {
                     columnar_writer.record_ip_addr(doc_id as u32, column_name, ip_addr);
                 }
+                ColumnValue::U128(val) => {
+                    columnar_writer.record_u128(doc_id as u32, column_name, val);
+                }
                 ColumnValue::Bool(bool_val) => {
                     columnar_writer.record_bool(doc_id as u32, column_name, bool_val);
                 }
                 ColumnValue::DateTime(date_time) => {
                     columnar_writer.record_datetime(doc_id as u32, column_name, date_time);
                 }
+                ColumnValue::GeoPoint(geo_point) => {
+                    columnar_writer.record_geo_point(doc_id as u32, column_name, geo_point);
+                }
             }
         }
     }
@@ -461,6 +639,12 @@ fn assert_dyn_column_eq(
         (DynamicColumn::IpAddr(left_col), DynamicColumn::IpAddr(right_col)) => {
             assert_column_eq(left_col, right_col);
         }
+        (DynamicColumn::U128(left_col), DynamicColumn::U128(right_col)) => {
+            assert_column_eq(left_col, right_col);
+        }
+        (DynamicColumn::GeoPoint(left_col), DynamicColumn::GeoPoint(right_col)) => {
+            assert_column_eq(left_col, right_col);
+        }
         (DynamicColumn::Bytes(left_col), DynamicColumn::Bytes(right_col)) => {
             assert_bytes_column_eq(left_col, right_col);
         }
@@ -506,6 +690,24 @@ impl AssertEqualToColumnValue for Ipv6Addr {
     }
 }
 
+impl AssertEqualToColumnValue for u128 {
+    fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
+        let ColumnValue::U128(val) = column_value else {
+            panic!()
+        };
+        assert_eq!(self, val);
+    }
+}
+
+impl AssertEqualToColumnValue for GeoPoint {
+    fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
+        let ColumnValue::GeoPoint(val) = column_value else {
+            panic!()
+        };
+        assert_eq!(self, val);
+    }
+}
+
 impl<T: Coerce + PartialEq + Debug + Into<NumericalValue>> AssertEqualToColumnValue for T {
     fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
         let ColumnValue::Numerical(num) = column_value else {
@@ -617,8 +819,12 @@ proptest! {
                     assert_column_values(col, expected_col_values),
                 DynamicColumn::IpAddr(col) =>
                     assert_column_values(col, expected_col_values),
+                DynamicColumn::U128(col) =>
+                    assert_column_values(col, expected_col_values),
                 DynamicColumn::DateTime(col) =>
                     assert_column_values(col, expected_col_values),
+                DynamicColumn::GeoPoint(col) =>
+                    assert_column_values(col, expected_col_values),
                 DynamicColumn::Bytes(col) =>
                     assert_bytes_column_values(col, expected_col_values, false),
                 DynamicColumn::Str(col) =>
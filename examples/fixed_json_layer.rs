@@ -79,6 +79,7 @@ pub mod fixed_json_layer {
                         position: 0,
                         text: text.to_string(),
                         position_length: 1,
+                        keyword: false,
                     });
                 } else {
                     for (position, token) in words.iter().enumerate() {
@@ -89,6 +90,7 @@ pub mod fixed_json_layer {
                             position,
                             text: prefixed_token,
                             position_length: 1,
+                            keyword: false,
                         });
                     }
                 }
@@ -100,6 +102,7 @@ pub mod fixed_json_layer {
                     position: 0,
                     text: text.to_string(),
                     position_length: 1,
+                    keyword: false,
                 });
             }
 
@@ -182,6 +185,7 @@ impl PathPrefixNgramTokenStream {
                     position,
                     text: prefixed_token_text,
                     position_length: 1,
+                    keyword: false,
                 });
                 position += 1;
             }
@@ -196,6 +200,7 @@ impl PathPrefixNgramTokenStream {
                     position,
                     text: ngram_token.text,
                     position_length: 1,
+                    keyword: false,
                 });
                 position += 1;
             }
@@ -5,13 +5,15 @@ use serde::Serialize;
 mod infallible;
 mod occur;
 mod query_grammar;
+mod simple_query;
 mod user_input_ast;
 
 pub use crate::infallible::LenientError;
 pub use crate::occur::Occur;
 use crate::query_grammar::{parse_to_ast, parse_to_ast_lenient};
+pub use crate::simple_query::{SimpleQueryFlags, parse_simple_query};
 pub use crate::user_input_ast::{
-    Delimiter, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral,
+    Delimiter, Fuzziness, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral,
 };
 
 #[derive(Debug, Serialize)]
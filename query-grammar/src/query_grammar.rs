@@ -15,7 +15,7 @@ use nom::sequence::{delimited, preceded, separated_pair, terminated, tuple};
 use super::user_input_ast::{UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral};
 use crate::Occur;
 use crate::infallible::*;
-use crate::user_input_ast::Delimiter;
+use crate::user_input_ast::{Delimiter, Fuzziness};
 
 // Note: '-' char is only forbidden at the beginning of a field name, would be clearer to add it to
 // special characters.
@@ -227,12 +227,14 @@ fn term_or_phrase(inp: &str) -> IResult<&str, UserInputLeaf> {
     map(
         tuple((simple_term, fallible(slop_or_prefix_val))),
         |((delimiter, phrase), (slop, prefix))| {
+            let (phrase, fuzziness) = split_fuzziness(delimiter, phrase);
             UserInputLiteral {
                 field_name: None,
                 phrase,
                 delimiter,
                 slop,
                 prefix,
+                fuzziness,
             }
             .into()
         },
@@ -245,6 +247,7 @@ fn term_or_phrase_infallible(inp: &str) -> JResult<&str, Option<UserInputLeaf>>
         tuple_infallible((simple_term_infallible(")^"), slop_or_prefix_val)),
         |((delimiter_phrase, (slop, prefix)), errors)| {
             let leaf = if let Some((delimiter, phrase)) = delimiter_phrase {
+                let (phrase, fuzziness) = split_fuzziness(delimiter, phrase);
                 Some(
                     UserInputLiteral {
                         field_name: None,
@@ -252,6 +255,7 @@ fn term_or_phrase_infallible(inp: &str) -> JResult<&str, Option<UserInputLeaf>>
                         delimiter,
                         slop,
                         prefix,
+                        fuzziness,
                     }
                     .into(),
                 )
@@ -263,6 +267,7 @@ fn term_or_phrase_infallible(inp: &str) -> JResult<&str, Option<UserInputLeaf>>
                         delimiter: Delimiter::None,
                         slop,
                         prefix,
+                        fuzziness: None,
                     }
                     .into(),
                 )
@@ -274,6 +279,36 @@ fn term_or_phrase_infallible(inp: &str) -> JResult<&str, Option<UserInputLeaf>>
     )(inp)
 }
 
+/// Splits the fuzziness operator (`~` or `~<distance>`) off an unquoted term.
+///
+/// The operator is only recognized at the end of the term, and if it is not escaped.
+/// Terms starting with `~` are left untouched.
+fn split_fuzziness(delimiter: Delimiter, mut phrase: String) -> (String, Option<Fuzziness>) {
+    if delimiter != Delimiter::None {
+        return (phrase, None);
+    }
+    let Some(tilde_pos) = phrase.rfind('~') else {
+        return (phrase, None);
+    };
+    let (term, distance) = (&phrase[..tilde_pos], &phrase[tilde_pos + 1..]);
+    if term.is_empty() || term.ends_with('\\') {
+        return (phrase, None);
+    }
+    let fuzziness = if distance.is_empty() {
+        Fuzziness::Auto
+    } else {
+        // `parse` would also accept a leading `+`.
+        match distance.parse::<u8>() {
+            Ok(distance_val) if distance.bytes().all(|b| b.is_ascii_digit()) => {
+                Fuzziness::Distance(distance_val)
+            }
+            _ => return (phrase, None),
+        }
+    };
+    phrase.truncate(tilde_pos);
+    (phrase, Some(fuzziness))
+}
+
 fn term_group(inp: &str) -> IResult<&str, UserInputAst> {
     map(
         tuple((
@@ -1629,6 +1664,43 @@ mod test {
         test_parse_query_to_ast_helper("\"a b\"~300^2", "(\"a b\"~300)^2");
     }
 
+    #[test]
+    fn test_fuzziness() {
+        let phrase_and_fuzziness = |query: &str| {
+            let (query_lenient, errs) = parse_to_ast_lenient(query);
+            assert!(errs.is_empty());
+            let UserInputAst::Leaf(leaf) = parse_to_ast(query).unwrap().1 else {
+                panic!("expected a leaf");
+            };
+            assert_eq!(format!("{query_lenient:?}"), format!("{leaf:?}"));
+            let UserInputLeaf::Literal(literal) = *leaf else {
+                panic!("expected a literal");
+            };
+            (literal.phrase, literal.fuzziness)
+        };
+        assert_eq!(
+            phrase_and_fuzziness("abc~1"),
+            ("abc".to_string(), Some(Fuzziness::Distance(1)))
+        );
+        assert_eq!(
+            phrase_and_fuzziness("title:abc~"),
+            ("abc".to_string(), Some(Fuzziness::Auto))
+        );
+        assert_eq!(phrase_and_fuzziness("~abc"), ("~abc".to_string(), None));
+        assert_eq!(phrase_and_fuzziness("a~b"), ("a~b".to_string(), None));
+        assert_eq!(phrase_and_fuzziness("abc~+1"), ("abc~+1".to_string(), None));
+        assert_eq!(
+            phrase_and_fuzziness("abc~300"),
+            ("abc~300".to_string(), None)
+        );
+        assert_eq!(
+            phrase_and_fuzziness(r"abc\~1"),
+            (r"abc\~1".to_string(), None)
+        );
+        assert_eq!(phrase_and_fuzziness("\"abc\"~1").1, None);
+        test_parse_query_to_ast_helper("abc~2^3", "(abc~2)^3");
+    }
+
     #[test]
     fn test_phrase_prefix() {
         test_parse_query_to_ast_helper("\"a b\"*", "\"a b\"*");
@@ -1654,6 +1726,12 @@ mod test {
         test_parse_query_to_ast_helper("a:b*", "\"a\":b*");
         test_parse_query_to_ast_helper("a:*b", "\"a\":*b");
         test_parse_query_to_ast_helper(r#"a:*def*"#, "\"a\":*def*");
+
+        test_parse_query_to_ast_helper("_exists_:a", "$exists(\"a\")");
+        test_parse_query_to_ast_helper("_exists_:a.b", "$exists(\"a.b\")");
+        test_parse_query_to_ast_helper("_exists_:\"a b\"", "$exists(\"a b\")");
+        test_parse_query_to_ast_helper("NOT _exists_:a", "(-$exists(\"a\"))");
+        test_parse_query_to_ast_helper("-_exists_:a b", "(-$exists(\"a\") *b)");
     }
 
     #[test]
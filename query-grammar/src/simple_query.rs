@@ -0,0 +1,315 @@
+use std::iter::Peekable;
+use std::ops::BitOr;
+use std::str::Chars;
+
+use crate::{Delimiter, Fuzziness, Occur, UserInputAst, UserInputLeaf, UserInputLiteral};
+
+/// Set of the operators enabled in a simple query string.
+///
+/// A disabled operator is not an error: its characters are simply handled as a part
+/// of the surrounding term.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SimpleQueryFlags(u8);
+
+impl SimpleQueryFlags {
+    /// No operator is enabled: the whole query is a list of terms.
+    pub const NONE: SimpleQueryFlags = SimpleQueryFlags(0);
+    /// `+term`: the term is required.
+    pub const AND: SimpleQueryFlags = SimpleQueryFlags(1);
+    /// `a | b`: either side of the operator should match.
+    pub const OR: SimpleQueryFlags = SimpleQueryFlags(1 << 1);
+    /// `-term`: the term must not match.
+    pub const NOT: SimpleQueryFlags = SimpleQueryFlags(1 << 2);
+    /// `"big bad wolf"`: phrase query.
+    pub const PHRASE: SimpleQueryFlags = SimpleQueryFlags(1 << 3);
+    /// `wol*`: prefix query.
+    pub const PREFIX: SimpleQueryFlags = SimpleQueryFlags(1 << 4);
+    /// `(a | b) c`: grouping.
+    pub const PRECEDENCE: SimpleQueryFlags = SimpleQueryFlags(1 << 5);
+    /// `wolf~1`: fuzzy term query.
+    pub const FUZZY: SimpleQueryFlags = SimpleQueryFlags(1 << 6);
+    /// `"big wolf"~2`: phrase query with a slop.
+    pub const SLOP: SimpleQueryFlags = SimpleQueryFlags(1 << 7);
+    /// All of the operators are enabled.
+    pub const ALL: SimpleQueryFlags = SimpleQueryFlags(u8::MAX);
+
+    /// Returns true if all of the operators of `flags` are enabled.
+    pub fn contains(self, flags: SimpleQueryFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+impl Default for SimpleQueryFlags {
+    fn default() -> Self {
+        SimpleQueryFlags::ALL
+    }
+}
+
+impl BitOr for SimpleQueryFlags {
+    type Output = SimpleQueryFlags;
+
+    fn bitor(self, other: SimpleQueryFlags) -> SimpleQueryFlags {
+        SimpleQueryFlags(self.0 | other.0)
+    }
+}
+
+struct SimpleQueryParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    flags: SimpleQueryFlags,
+}
+
+impl SimpleQueryParser<'_> {
+    fn is_enabled(&self, flag: SimpleQueryFlags) -> bool {
+        self.flags.contains(flag)
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    /// Parses a list of clauses, up to the end of the query or to the closing parenthesis
+    /// of the current group. A missing closing parenthesis is implicitly added.
+    fn parse_disjunction(&mut self, in_group: bool) -> UserInputAst {
+        let mut alternatives: Vec<UserInputAst> = Vec::new();
+        let mut clauses: Vec<(Option<Occur>, UserInputAst)> = Vec::new();
+        loop {
+            self.skip_whitespaces();
+            match self.chars.peek().copied() {
+                None => break,
+                Some(')') if in_group => {
+                    self.chars.next();
+                    break;
+                }
+                Some('|') if self.is_enabled(SimpleQueryFlags::OR) => {
+                    self.chars.next();
+                    alternatives.extend(build_conjunction(std::mem::take(&mut clauses)));
+                    continue;
+                }
+                _ => {}
+            }
+            let occur = if self.is_enabled(SimpleQueryFlags::NOT)
+                && self.chars.next_if_eq(&'-').is_some()
+            {
+                Some(Occur::MustNot)
+            } else if self.is_enabled(SimpleQueryFlags::AND)
+                && self.chars.next_if_eq(&'+').is_some()
+            {
+                Some(Occur::Must)
+            } else {
+                None
+            };
+            if let Some(ast) = self.parse_operand(in_group) {
+                clauses.push((occur, ast));
+            }
+        }
+        alternatives.extend(build_conjunction(clauses));
+        match alternatives.len() {
+            0 => UserInputAst::empty_query(),
+            _ => UserInputAst::or(alternatives),
+        }
+    }
+
+    fn parse_operand(&mut self, in_group: bool) -> Option<UserInputAst> {
+        match self.chars.peek().copied() {
+            Some('(') if self.is_enabled(SimpleQueryFlags::PRECEDENCE) => {
+                self.chars.next();
+                let ast = self.parse_disjunction(true);
+                match ast {
+                    UserInputAst::Clause(ref clauses) if clauses.is_empty() => None,
+                    ast => Some(ast),
+                }
+            }
+            Some('"') if self.is_enabled(SimpleQueryFlags::PHRASE) => {
+                self.chars.next();
+                if let Some(phrase) = self.parse_phrase() {
+                    return Some(phrase);
+                }
+                // Unbalanced quote, the rest of the query is parsed as if it was not there.
+                self.parse_term(in_group)
+            }
+            _ => self.parse_term(in_group),
+        }
+    }
+
+    /// Parses a phrase, after its opening quote.
+    ///
+    /// Returns `None` and leaves the input untouched if the phrase is not closed.
+    fn parse_phrase(&mut self) -> Option<UserInputAst> {
+        let mut lookahead = self.chars.clone();
+        let mut phrase = String::new();
+        loop {
+            match lookahead.next()? {
+                '"' => break,
+                c => phrase.push(c),
+            }
+        }
+        self.chars = lookahead;
+        let mut slop = 0;
+        let mut prefix = false;
+        if self.is_enabled(SimpleQueryFlags::SLOP) && self.chars.peek() == Some(&'~') {
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            let digits: String =
+                std::iter::from_fn(|| lookahead.next_if(char::is_ascii_digit)).collect();
+            if let Ok(parsed_slop) = digits.parse() {
+                slop = parsed_slop;
+                self.chars = lookahead;
+            }
+        } else if self.is_enabled(SimpleQueryFlags::PREFIX) {
+            prefix = self.chars.next_if_eq(&'*').is_some();
+        }
+        let leaf: UserInputLeaf = UserInputLiteral {
+            field_name: None,
+            phrase,
+            delimiter: Delimiter::DoubleQuotes,
+            slop,
+            prefix,
+            fuzziness: None,
+        }
+        .into();
+        Some(leaf.into())
+    }
+
+    fn is_term_boundary(&self, c: char, in_group: bool) -> bool {
+        c.is_whitespace()
+            || (c == '|' && self.is_enabled(SimpleQueryFlags::OR))
+            || (c == ')' && in_group)
+    }
+
+    fn parse_term(&mut self, in_group: bool) -> Option<UserInputAst> {
+        let mut term = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if self.is_term_boundary(c, in_group) {
+                break;
+            }
+            term.push(c);
+            self.chars.next();
+        }
+        if term.is_empty() {
+            return None;
+        }
+        let mut prefix = false;
+        let mut fuzziness = None;
+        if self.is_enabled(SimpleQueryFlags::PREFIX) && term.len() > 1 && term.ends_with('*') {
+            term.pop();
+            prefix = true;
+        } else if let Some(tilde_pos) = term
+            .rfind('~')
+            .filter(|&pos| pos > 0 && self.is_enabled(SimpleQueryFlags::FUZZY))
+        {
+            let distance = &term[tilde_pos + 1..];
+            fuzziness = if distance.is_empty() {
+                Some(Fuzziness::Auto)
+            } else {
+                distance.parse().ok().map(Fuzziness::Distance)
+            };
+            if fuzziness.is_some() {
+                term.truncate(tilde_pos);
+            }
+        }
+        let leaf: UserInputLeaf = UserInputLiteral {
+            field_name: None,
+            phrase: term,
+            delimiter: Delimiter::None,
+            slop: 0,
+            prefix,
+            fuzziness,
+        }
+        .into();
+        Some(leaf.into())
+    }
+}
+
+fn build_conjunction(mut clauses: Vec<(Option<Occur>, UserInputAst)>) -> Option<UserInputAst> {
+    match clauses.len() {
+        0 => None,
+        1 if clauses[0].0.is_none() => clauses.pop().map(|(_, ast)| ast),
+        _ => Some(UserInputAst::Clause(clauses)),
+    }
+}
+
+/// Parses a "simple query string".
+///
+/// Contrary to [`parse_query`](crate::parse_query), this parser never fails: it is meant
+/// to be exposed to end users directly. Unbalanced quotes and parentheses are ignored,
+/// and the reserved characters of disabled operators are parsed as a part of the terms.
+///
+/// The supported operators are:
+/// - `+term` makes the term required, `-term` excludes it.
+/// - `a | b` matches either `a` or `b`. It has a lower precedence than the whitespace, i.e. `a b |
+///   c` is `(a b) | c`.
+/// - `"big bad wolf"` is a phrase, and `"big wolf"~2` a phrase with a slop of 2.
+/// - `wol*` matches the terms starting with `wol`.
+/// - `wolf~1` matches the terms at a Levenshtein distance of at most 1 from `wolf`. The distance is
+///   chosen automatically for `wolf~`.
+/// - `(a | b) c` groups clauses.
+///
+/// Terms separated by whitespaces have no explicit occur, and use the default one of the
+/// query parser. The literals never target a field explicitly: `title:wolf` is a plain term.
+pub fn parse_simple_query(query: &str, flags: SimpleQueryFlags) -> UserInputAst {
+    let mut parser = SimpleQueryParser {
+        chars: query.chars().peekable(),
+        flags,
+    };
+    parser.parse_disjunction(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SimpleQueryFlags, parse_simple_query};
+
+    fn test_simple_query_helper(query: &str, flags: SimpleQueryFlags, expected: &str) {
+        let ast = parse_simple_query(query, flags);
+        assert_eq!(format!("{ast:?}"), expected, "query: {query}");
+    }
+
+    #[test]
+    fn test_simple_query_operators() {
+        let flags = SimpleQueryFlags::ALL;
+        test_simple_query_helper("", flags, "<emptyclause>");
+        test_simple_query_helper("  ", flags, "<emptyclause>");
+        test_simple_query_helper("wolf", flags, "wolf");
+        test_simple_query_helper("big wolf", flags, "(*big *wolf)");
+        test_simple_query_helper("+big -bad wolf", flags, "(+big -bad *wolf)");
+        test_simple_query_helper("big | bad wolf", flags, "(?big ?(*bad *wolf))");
+        test_simple_query_helper("big|bad", flags, "(?big ?bad)");
+        test_simple_query_helper("(big | bad) wolf", flags, "(*(?big ?bad) *wolf)");
+        test_simple_query_helper("-(big bad) wolf", flags, "(-(*big *bad) *wolf)");
+        test_simple_query_helper("\"big bad\" wolf", flags, "(*\"big bad\" *wolf)");
+        test_simple_query_helper("\"big bad\"~2", flags, "\"big bad\"~2");
+        test_simple_query_helper("\"big bad\"*", flags, "\"big bad\"*");
+        test_simple_query_helper("wol*", flags, "wol*");
+        test_simple_query_helper("wolf~", flags, "wolf~");
+        test_simple_query_helper("wolf~1", flags, "wolf~1");
+    }
+
+    #[test]
+    fn test_simple_query_never_fails() {
+        let flags = SimpleQueryFlags::ALL;
+        test_simple_query_helper("\"big bad wolf", flags, "(*big *bad *wolf)");
+        test_simple_query_helper("(big bad", flags, "(*big *bad)");
+        test_simple_query_helper("big) bad", flags, "(*big) *bad)");
+        test_simple_query_helper("()", flags, "<emptyclause>");
+        test_simple_query_helper("| big |", flags, "big");
+        test_simple_query_helper("+ - wolf", flags, "wolf");
+        test_simple_query_helper("title:wolf^2", flags, "title:wolf^2");
+        test_simple_query_helper("* ~ wolf~x", flags, "(** *~ *wolf~x)");
+    }
+
+    #[test]
+    fn test_simple_query_disabled_operators() {
+        let flags = SimpleQueryFlags::NONE;
+        test_simple_query_helper("+big -bad", flags, "(*+big *-bad)");
+        test_simple_query_helper("big|bad", flags, "big|bad");
+        test_simple_query_helper("\"big bad\"", flags, "(*\"big *bad\")");
+        test_simple_query_helper("(wol* wolf~1)", flags, "(*(wol* *wolf~1))");
+        let flags = SimpleQueryFlags::PHRASE | SimpleQueryFlags::OR;
+        assert!(flags.contains(SimpleQueryFlags::OR));
+        assert!(!flags.contains(SimpleQueryFlags::NOT));
+        test_simple_query_helper(
+            "\"big bad\"~2 | -wolf",
+            flags,
+            "(?(*\"big bad\" *~2) ?-wolf)",
+        );
+    }
+}
@@ -5,6 +5,9 @@ use serde::Serialize;
 
 use crate::Occur;
 
+/// Reserved field name of the `_exists_:field` syntax.
+const EXISTS_FIELD_NAME: &str = "_exists_";
+
 #[derive(PartialEq, Clone, Serialize)]
 #[serde(tag = "type")]
 #[serde(rename_all = "snake_case")]
@@ -28,6 +31,17 @@ pub enum UserInputLeaf {
 impl UserInputLeaf {
     pub(crate) fn set_field(self, field: Option<String>) -> Self {
         match self {
+            // `_exists_:field` is an alternative syntax for `field:*`.
+            UserInputLeaf::Literal(literal)
+                if field.as_deref() == Some(EXISTS_FIELD_NAME)
+                    && literal.slop == 0
+                    && !literal.prefix
+                    && literal.fuzziness.is_none() =>
+            {
+                UserInputLeaf::Exists {
+                    field: literal.phrase,
+                }
+            }
             UserInputLeaf::Literal(mut literal) => {
                 literal.field_name = field;
                 UserInputLeaf::Literal(literal)
@@ -115,6 +129,16 @@ pub enum Delimiter {
     None,
 }
 
+/// Maximum number of edits allowed by a fuzzy term, e.g. `term~1`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fuzziness {
+    /// `term~`: the distance is chosen depending on the length of the term.
+    Auto,
+    /// `term~<distance>`
+    Distance(u8),
+}
+
 #[derive(PartialEq, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub struct UserInputLiteral {
@@ -123,6 +147,8 @@ pub struct UserInputLiteral {
     pub delimiter: Delimiter,
     pub slop: u32,
     pub prefix: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzziness: Option<Fuzziness>,
 }
 
 impl fmt::Debug for UserInputLiteral {
@@ -145,6 +171,11 @@ impl fmt::Debug for UserInputLiteral {
                 write!(formatter, "{}", self.phrase)?;
             }
         }
+        match self.fuzziness {
+            Some(Fuzziness::Auto) => write!(formatter, "~")?,
+            Some(Fuzziness::Distance(distance)) => write!(formatter, "~{distance}")?,
+            None => {}
+        }
         if self.slop > 0 {
             write!(formatter, "~{}", self.slop)?;
         } else if self.prefix {
@@ -336,6 +367,7 @@ mod tests {
             delimiter: Delimiter::None,
             slop: 0,
             prefix: false,
+            fuzziness: None,
         };
         let ast = UserInputAst::Leaf(Box::new(UserInputLeaf::Literal(literal)));
         let json = serde_json::to_string(&ast).unwrap();
@@ -402,6 +434,7 @@ mod tests {
                         delimiter: Delimiter::None,
                         slop: 0,
                         prefix: false,
+                        fuzziness: None,
                     }))),
                 ),
             ])),
@@ -429,6 +462,7 @@ mod tests {
                     delimiter: Delimiter::None,
                     slop: 0,
                     prefix: false,
+                    fuzziness: None,
                 }))),
             ),
         ]);
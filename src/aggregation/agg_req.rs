@@ -31,12 +31,19 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    AutoDateHistogramAggregation, CompositeAggregation, DateHistogramAggregationReq, DateRounding,
+    FiltersAggregation, GeoGrid, GeoHashGridAggregation, GeoTileGridAggregation,
+    HistogramAggregation, RangeAggregation, TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
-    MaxAggregation, MinAggregation, PercentilesAggregationReq, StatsAggregation, SumAggregation,
-    TopHitsAggregationReq,
+    MaxAggregation, MinAggregation, PercentilesAggregationReq, RateAggregation,
+    ScriptedMetricAggregation, StatsAggregation, SumAggregation, TopHitsAggregationReq,
+    TopMetricsAggregationReq,
+};
+use super::pipeline::{
+    BucketScriptAggregation, BucketSelectorAggregation, CumulativeSumAggregation,
+    DerivativeAggregation, MovingAvgAggregation,
 };
 
 /// The top-level aggregation request structure, which contains [`Aggregation`] and their user
@@ -126,9 +133,24 @@ pub enum AggregationVariants {
     /// Put data into a date histogram.
     #[serde(rename = "date_histogram")]
     DateHistogram(DateHistogramAggregationReq),
+    /// Put data into a date histogram with an automatically picked interval.
+    #[serde(rename = "auto_date_histogram")]
+    AutoDateHistogram(AutoDateHistogramAggregation),
     /// Put data into buckets of terms.
     #[serde(rename = "terms")]
     Terms(TermsAggregation),
+    /// Page through the buckets of the combinations of several sources.
+    #[serde(rename = "composite")]
+    Composite(CompositeAggregation),
+    /// Put the documents matching each of several queries into a bucket.
+    #[serde(rename = "filters")]
+    Filters(FiltersAggregation),
+    /// Put geo points into the cells of a geohash grid.
+    #[serde(rename = "geohash_grid")]
+    GeoHashGrid(GeoHashGridAggregation),
+    /// Put geo points into the tiles of a map.
+    #[serde(rename = "geotile_grid")]
+    GeoTileGrid(GeoTileGridAggregation),
 
     // Metric aggregation types
     /// Computes the average of the extracted values.
@@ -158,12 +180,38 @@ pub enum AggregationVariants {
     /// Computes the sum of the extracted values.
     #[serde(rename = "percentiles")]
     Percentiles(PercentilesAggregationReq),
+    /// Computes a rate per unit of time in the buckets of a date histogram.
+    #[serde(rename = "rate")]
+    Rate(RateAggregation),
     /// Finds the top k values matching some order
     #[serde(rename = "top_hits")]
     TopHits(TopHitsAggregationReq),
+    /// Returns the values of some fields of the top documents by a sort field
+    #[serde(rename = "top_metrics")]
+    TopMetrics(TopMetricsAggregationReq),
     /// Computes an estimate of the number of unique values
     #[serde(rename = "cardinality")]
     Cardinality(CardinalityAggregationReq),
+    /// Computes a custom metric with user supplied closures
+    #[serde(rename = "scripted_metric")]
+    ScriptedMetric(ScriptedMetricAggregation),
+
+    // Pipeline aggregation types
+    /// Computes the derivative of a value of the buckets of the parent histogram.
+    #[serde(rename = "derivative")]
+    Derivative(DerivativeAggregation),
+    /// Computes the moving average of a value of the buckets of the parent histogram.
+    #[serde(rename = "moving_avg")]
+    MovingAvg(MovingAvgAggregation),
+    /// Computes the cumulative sum of a value of the buckets of the parent histogram.
+    #[serde(rename = "cumulative_sum")]
+    CumulativeSum(CumulativeSumAggregation),
+    /// Computes a value for each bucket of the parent aggregation with a script.
+    #[serde(rename = "bucket_script")]
+    BucketScript(BucketScriptAggregation),
+    /// Removes the buckets of the parent aggregation for which a script is false.
+    #[serde(rename = "bucket_selector")]
+    BucketSelector(BucketSelectorAggregation),
 }
 
 impl AggregationVariants {
@@ -174,6 +222,24 @@ impl AggregationVariants {
             AggregationVariants::Range(range) => vec![range.field.as_str()],
             AggregationVariants::Histogram(histogram) => vec![histogram.field.as_str()],
             AggregationVariants::DateHistogram(histogram) => vec![histogram.field.as_str()],
+            AggregationVariants::AutoDateHistogram(histogram) => vec![histogram.field.as_str()],
+            AggregationVariants::Composite(composite) => composite
+                .sources()
+                .map(|(_, source)| source.field())
+                .collect(),
+            AggregationVariants::Filters(_) => vec![],
+            AggregationVariants::GeoHashGrid(geohash_grid) => {
+                vec![
+                    geohash_grid.lat_field.as_str(),
+                    geohash_grid.lon_field.as_str(),
+                ]
+            }
+            AggregationVariants::GeoTileGrid(geotile_grid) => {
+                vec![
+                    geotile_grid.lat_field.as_str(),
+                    geotile_grid.lon_field.as_str(),
+                ]
+            }
             AggregationVariants::Average(avg) => vec![avg.field_name()],
             AggregationVariants::Count(count) => vec![count.field_name()],
             AggregationVariants::Max(max) => vec![max.field_name()],
@@ -182,8 +248,71 @@ impl AggregationVariants {
             AggregationVariants::ExtendedStats(extended_stats) => vec![extended_stats.field_name()],
             AggregationVariants::Sum(sum) => vec![sum.field_name()],
             AggregationVariants::Percentiles(per) => vec![per.field_name()],
+            AggregationVariants::Rate(rate) => rate.field_name().into_iter().collect(),
             AggregationVariants::TopHits(top_hits) => top_hits.field_names(),
+            AggregationVariants::TopMetrics(top_metrics) => {
+                let mut field_names = vec![top_metrics.sort_field_name()];
+                field_names.extend(top_metrics.metric_field_names());
+                field_names
+            }
             AggregationVariants::Cardinality(per) => vec![per.field_name()],
+            AggregationVariants::ScriptedMetric(scripted_metric) => scripted_metric.field_names(),
+            AggregationVariants::Derivative(_)
+            | AggregationVariants::MovingAvg(_)
+            | AggregationVariants::CumulativeSum(_)
+            | AggregationVariants::BucketScript(_)
+            | AggregationVariants::BucketSelector(_) => vec![],
+        }
+    }
+
+    /// Returns true if the aggregation creates buckets.
+    pub(crate) fn is_bucket(&self) -> bool {
+        matches!(
+            self,
+            AggregationVariants::Range(_)
+                | AggregationVariants::Histogram(_)
+                | AggregationVariants::DateHistogram(_)
+                | AggregationVariants::AutoDateHistogram(_)
+                | AggregationVariants::Terms(_)
+                | AggregationVariants::Composite(_)
+                | AggregationVariants::Filters(_)
+                | AggregationVariants::GeoHashGrid(_)
+                | AggregationVariants::GeoTileGrid(_)
+        )
+    }
+
+    /// Returns true if the aggregation is a pipeline aggregation, which is computed on the
+    /// results of its sibling aggregations instead of being collected.
+    pub(crate) fn is_pipeline(&self) -> bool {
+        matches!(
+            self,
+            AggregationVariants::Derivative(_)
+                | AggregationVariants::MovingAvg(_)
+                | AggregationVariants::CumulativeSum(_)
+                | AggregationVariants::BucketScript(_)
+                | AggregationVariants::BucketSelector(_)
+        )
+    }
+
+    /// Returns the buckets paths used by a pipeline aggregation.
+    pub(crate) fn get_buckets_paths(&self) -> Vec<&str> {
+        match self {
+            AggregationVariants::Derivative(derivative) => vec![derivative.buckets_path.as_str()],
+            AggregationVariants::MovingAvg(moving_avg) => vec![moving_avg.buckets_path.as_str()],
+            AggregationVariants::CumulativeSum(cumulative_sum) => {
+                vec![cumulative_sum.buckets_path.as_str()]
+            }
+            AggregationVariants::BucketScript(bucket_script) => bucket_script
+                .buckets_path
+                .values()
+                .map(String::as_str)
+                .collect(),
+            AggregationVariants::BucketSelector(bucket_selector) => bucket_selector
+                .buckets_path
+                .values()
+                .map(String::as_str)
+                .collect(),
+            _ => vec![],
         }
     }
 
@@ -202,12 +331,48 @@ impl AggregationVariants {
             _ => Ok(None),
         }
     }
+    pub(crate) fn as_date_rounding(&self) -> crate::Result<Option<DateRounding>> {
+        match &self {
+            AggregationVariants::DateHistogram(histogram) => histogram.to_date_rounding(),
+            _ => Ok(None),
+        }
+    }
+    pub(crate) fn as_auto_date_histogram(&self) -> Option<&AutoDateHistogramAggregation> {
+        match &self {
+            AggregationVariants::AutoDateHistogram(histogram) => Some(histogram),
+            _ => None,
+        }
+    }
     pub(crate) fn as_term(&self) -> Option<&TermsAggregation> {
         match &self {
             AggregationVariants::Terms(terms) => Some(terms),
             _ => None,
         }
     }
+    /// Returns the grid and the size of a geo grid aggregation.
+    pub(crate) fn as_geo_grid(&self) -> Option<(GeoGrid, u32)> {
+        match &self {
+            AggregationVariants::GeoHashGrid(geohash_grid) => {
+                Some((geohash_grid.grid(), geohash_grid.size))
+            }
+            AggregationVariants::GeoTileGrid(geotile_grid) => {
+                Some((geotile_grid.grid(), geotile_grid.size))
+            }
+            _ => None,
+        }
+    }
+    pub(crate) fn as_composite(&self) -> Option<&CompositeAggregation> {
+        match &self {
+            AggregationVariants::Composite(composite) => Some(composite),
+            _ => None,
+        }
+    }
+    pub(crate) fn as_filters(&self) -> Option<&FiltersAggregation> {
+        match &self {
+            AggregationVariants::Filters(filters) => Some(filters),
+            _ => None,
+        }
+    }
     pub(crate) fn as_top_hits(&self) -> Option<&TopHitsAggregationReq> {
         match &self {
             AggregationVariants::TopHits(top_hits) => Some(top_hits),
@@ -215,6 +380,20 @@ impl AggregationVariants {
         }
     }
 
+    pub(crate) fn as_top_metrics(&self) -> Option<&TopMetricsAggregationReq> {
+        match &self {
+            AggregationVariants::TopMetrics(top_metrics) => Some(top_metrics),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_scripted_metric(&self) -> Option<&ScriptedMetricAggregation> {
+        match &self {
+            AggregationVariants::ScriptedMetric(scripted_metric) => Some(scripted_metric),
+            _ => None,
+        }
+    }
+
     pub(crate) fn as_percentile(&self) -> Option<&PercentilesAggregationReq> {
         match &self {
             AggregationVariants::Percentiles(percentile_req) => Some(percentile_req),
@@ -2,16 +2,19 @@
 
 use std::collections::HashMap;
 use std::io;
+use std::sync::Arc;
 
 use columnar::{Column, ColumnBlockAccessor, ColumnType, DynamicColumn, StrColumn};
+use common::BitSet;
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    AutoDateHistogramAggregation, CompositeSource, DateHistogramAggregationReq,
+    HistogramAggregation, TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
-    MaxAggregation, MinAggregation, StatsAggregation, SumAggregation,
+    MaxAggregation, MinAggregation, StatsAggregation, StoredFieldsAccessor, SumAggregation,
 };
 use super::segment_agg_result::AggregationLimitsGuard;
 use super::VecWithNames;
@@ -48,16 +51,27 @@ pub struct AggregationWithAccessor {
     pub(crate) limits: AggregationLimitsGuard,
     pub(crate) column_block_accessor: ColumnBlockAccessor<u64>,
     /// Used for missing term aggregation, which checks all columns for existence.
-    /// And also for `top_hits` aggregation, which may sort on multiple fields.
+    /// And also for `top_hits` aggregation, which may sort on multiple fields, and for the
+    /// `composite` aggregation, which reads one column per source.
     /// By convention the missing aggregation is chosen, when this property is set
     /// (instead bein set in `agg`).
     /// If this needs to used by other aggregations, we need to refactor this.
     // NOTE: we can make all other aggregations use this instead of the `accessor` and `field_type`
     // (making them obsolete) But will it have a performance impact?
     pub(crate) accessors: Vec<(Column<u64>, ColumnType)>,
+    /// The str columns of the `accessors`, used for the `composite` aggregation which may
+    /// resolve the terms of several fields.
+    pub(crate) str_dict_columns: Vec<Option<StrColumn>>,
     /// Map field names to all associated column accessors.
     /// This field is used for `docvalue_fields`, which is currently only supported for `top_hits`.
     pub(crate) value_accessors: HashMap<String, Vec<DynamicColumn>>,
+    /// Reads stored fields from the doc store. This field is used for `stored_fields`, which is
+    /// currently only supported for `top_hits`.
+    pub(crate) stored_fields_accessor: Option<StoredFieldsAccessor>,
+    /// The documents of the segment matching each query of the `filters` aggregation.
+    pub(crate) filter_doc_sets: Vec<Arc<BitSet>>,
+    /// The columns of the fields of the `scripted_metric` aggregation, converted to f64.
+    pub(crate) f64_accessors: Vec<Column<f64>>,
     pub(crate) agg: Aggregation,
 }
 
@@ -81,7 +95,11 @@ impl AggregationWithAccessor {
                 segment_ordinal,
                 accessor,
                 accessors: Default::default(),
+                str_dict_columns: Default::default(),
                 value_accessors: Default::default(),
+                stored_fields_accessor: None,
+                filter_doc_sets: Default::default(),
+                f64_accessors: Default::default(),
                 field_type: column_type,
                 sub_aggregation: get_aggs_with_segment_accessor_and_validate(
                     sub_aggregation,
@@ -101,8 +119,10 @@ impl AggregationWithAccessor {
 
         let add_agg_with_accessors = |agg: &Aggregation,
                                       accessors: Vec<(Column<u64>, ColumnType)>,
+                                      str_dict_columns: Vec<Option<StrColumn>>,
                                       aggs: &mut Vec<AggregationWithAccessor>,
-                                      value_accessors: HashMap<String, Vec<DynamicColumn>>|
+                                      value_accessors: HashMap<String, Vec<DynamicColumn>>,
+                                      stored_fields_accessor: Option<StoredFieldsAccessor>|
          -> crate::Result<()> {
             let (accessor, field_type) = accessors.first().expect("at least one accessor");
             let limits = limits.clone();
@@ -111,8 +131,12 @@ impl AggregationWithAccessor {
                 // TODO: We should do away with the `accessor` field altogether
                 accessor: accessor.clone(),
                 value_accessors,
+                stored_fields_accessor,
+                filter_doc_sets: Default::default(),
+                f64_accessors: Default::default(),
                 field_type: *field_type,
                 accessors,
+                str_dict_columns,
                 sub_aggregation: get_aggs_with_segment_accessor_and_validate(
                     sub_aggregation,
                     reader,
@@ -133,7 +157,30 @@ impl AggregationWithAccessor {
         use AggregationVariants::*;
 
         match agg.agg {
-            Range(RangeAggregation {
+            Range(ref range_req) => {
+                let allowed_column_types = [
+                    ColumnType::F64,
+                    ColumnType::U64,
+                    ColumnType::I64,
+                    ColumnType::DateTime,
+                    ColumnType::IpAddr,
+                ];
+                // In case the column is empty we want the shim column to match the type of the
+                // ranges
+                let fallback_type = if range_req.is_ip_range() {
+                    ColumnType::IpAddr
+                } else {
+                    ColumnType::U64
+                };
+                let (accessor, column_type) = get_ff_reader_with_fallback_type(
+                    reader,
+                    &range_req.field,
+                    Some(&allowed_column_types),
+                    fallback_type,
+                )?;
+                add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
+            }
+            Histogram(HistogramAggregation {
                 field: ref field_name,
                 ..
             }) => {
@@ -141,23 +188,89 @@ impl AggregationWithAccessor {
                     get_ff_reader(reader, field_name, Some(get_numeric_or_date_column_types()))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
-            Histogram(HistogramAggregation {
+            DateHistogram(DateHistogramAggregationReq {
                 field: ref field_name,
                 ..
             }) => {
                 let (accessor, column_type) =
-                    get_ff_reader(reader, field_name, Some(get_numeric_or_date_column_types()))?;
+                    // Only DateTime is supported for DateHistogram
+                    get_ff_reader(reader, field_name, Some(&[ColumnType::DateTime]))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
-            DateHistogram(DateHistogramAggregationReq {
+            AutoDateHistogram(AutoDateHistogramAggregation {
                 field: ref field_name,
                 ..
             }) => {
                 let (accessor, column_type) =
-                    // Only DateTime is supported for DateHistogram
                     get_ff_reader(reader, field_name, Some(&[ColumnType::DateTime]))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            Composite(ref composite) => {
+                composite.validate()?;
+                let mut accessors = Vec::new();
+                let mut str_dict_columns = Vec::new();
+                for (_, source) in composite.sources() {
+                    accessors.push(get_ff_reader(
+                        reader,
+                        source.field(),
+                        Some(source.allowed_column_types()),
+                    )?);
+                    let str_dict_column = if matches!(source, CompositeSource::Terms(_)) {
+                        reader.fast_fields().str(source.field())?
+                    } else {
+                        None
+                    };
+                    str_dict_columns.push(str_dict_column);
+                }
+                add_agg_with_accessors(
+                    &agg,
+                    accessors,
+                    str_dict_columns,
+                    &mut res,
+                    Default::default(),
+                    None,
+                )?;
+            }
+            Filters(ref filters) => {
+                filters.validate()?;
+                let filter_doc_sets = filters.filter_doc_sets(reader)?;
+                // The filters are evaluated on the inverted index, they don't read a fast field.
+                let accessor = Column::build_empty_column(reader.num_docs());
+                add_agg_with_accessor(&agg, accessor, ColumnType::U64, &mut res)?;
+                res[0].filter_doc_sets = filter_doc_sets;
+            }
+            GeoHashGrid(ref geohash_grid) => {
+                geohash_grid.validate()?;
+                let accessors = get_geo_point_ff_readers(
+                    reader,
+                    &geohash_grid.lat_field,
+                    &geohash_grid.lon_field,
+                )?;
+                add_agg_with_accessors(
+                    &agg,
+                    accessors,
+                    Default::default(),
+                    &mut res,
+                    Default::default(),
+                    None,
+                )?;
+            }
+            GeoTileGrid(ref geotile_grid) => {
+                geotile_grid.validate()?;
+                let accessors = get_geo_point_ff_readers(
+                    reader,
+                    &geotile_grid.lat_field,
+                    &geotile_grid.lon_field,
+                )?;
+                add_agg_with_accessors(
+                    &agg,
+                    accessors,
+                    Default::default(),
+                    &mut res,
+                    Default::default(),
+                    None,
+                )?;
+            }
             Terms(TermsAggregation {
                 field: ref field_name,
                 ref missing,
@@ -224,7 +337,14 @@ impl AggregationWithAccessor {
                         .iter()
                         .map(|c_t| (c_t.0.clone(), c_t.1))
                         .collect();
-                    add_agg_with_accessors(&agg, accessors, &mut res, Default::default())?;
+                    add_agg_with_accessors(
+                        &agg,
+                        accessors,
+                        Default::default(),
+                        &mut res,
+                        Default::default(),
+                        None,
+                    )?;
                 }
 
                 for (accessor, column_type) in column_and_types {
@@ -251,7 +371,11 @@ impl AggregationWithAccessor {
                         missing_value_for_accessor,
                         accessor,
                         accessors: Default::default(),
+                        str_dict_columns: Default::default(),
                         value_accessors: Default::default(),
+                        stored_fields_accessor: None,
+                        filter_doc_sets: Default::default(),
+                        f64_accessors: Default::default(),
                         field_type: column_type,
                         sub_aggregation: get_aggs_with_segment_accessor_and_validate(
                             sub_aggregation,
@@ -321,6 +445,20 @@ impl AggregationWithAccessor {
                 )?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            Rate(ref rate) => {
+                rate.validate()?;
+                let (accessor, column_type) = if let Some(field_name) = rate.field_name() {
+                    let allowed_column_types = [ColumnType::I64, ColumnType::U64, ColumnType::F64];
+                    get_ff_reader(reader, field_name, Some(&allowed_column_types))?
+                } else {
+                    // Without field, the documents are counted.
+                    (
+                        Column::build_empty_column(reader.max_doc()),
+                        ColumnType::U64,
+                    )
+                };
+                add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
+            }
             TopHits(ref mut top_hits) => {
                 top_hits.validate_and_resolve_field_names(reader.fast_fields().columnar())?;
                 let accessors: Vec<(Column<u64>, ColumnType)> = top_hits
@@ -342,8 +480,50 @@ impl AggregationWithAccessor {
                     })
                     .collect::<crate::Result<_>>()?;
 
-                add_agg_with_accessors(&agg, accessors, &mut res, value_accessors)?;
+                let stored_fields_accessor = StoredFieldsAccessor::open(top_hits, reader)?;
+
+                add_agg_with_accessors(
+                    &agg,
+                    accessors,
+                    Default::default(),
+                    &mut res,
+                    value_accessors,
+                    stored_fields_accessor,
+                )?;
             }
+            TopMetrics(ref top_metrics) => {
+                top_metrics.validate()?;
+                let (accessor, column_type) = get_ff_reader(
+                    reader,
+                    top_metrics.sort_field_name(),
+                    Some(get_numeric_or_date_column_types()),
+                )?;
+                let value_accessors = top_metrics
+                    .metric_field_names()
+                    .iter()
+                    .map(|field_name| {
+                        let columns = reader
+                            .fast_fields()
+                            .dynamic_column_handles(field_name)?
+                            .iter()
+                            .map(|handle| handle.open())
+                            .collect::<io::Result<_>>()?;
+                        Ok((field_name.to_string(), columns))
+                    })
+                    .collect::<crate::Result<_>>()?;
+                add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
+                res[0].value_accessors = value_accessors;
+            }
+            ScriptedMetric(ref scripted_metric) => {
+                let f64_accessors = scripted_metric.columns(reader)?;
+                // The closures read their own columns, there is no main fast field.
+                let accessor = Column::build_empty_column(reader.max_doc());
+                add_agg_with_accessor(&agg, accessor, ColumnType::F64, &mut res)?;
+                res[0].f64_accessors = f64_accessors;
+            }
+            // Pipeline aggregations are computed on the final result, they don't collect.
+            Derivative(_) | MovingAvg(_) | CumulativeSum(_) | BucketScript(_)
+            | BucketSelector(_) => {}
         };
 
         Ok(res)
@@ -426,19 +606,37 @@ fn get_ff_reader(
     reader: &SegmentReader,
     field_name: &str,
     allowed_column_types: Option<&[ColumnType]>,
+) -> crate::Result<(columnar::Column<u64>, ColumnType)> {
+    get_ff_reader_with_fallback_type(reader, field_name, allowed_column_types, ColumnType::U64)
+}
+
+/// Get fast field reader or empty with `fallback_type` as default.
+fn get_ff_reader_with_fallback_type(
+    reader: &SegmentReader,
+    field_name: &str,
+    allowed_column_types: Option<&[ColumnType]>,
+    fallback_type: ColumnType,
 ) -> crate::Result<(columnar::Column<u64>, ColumnType)> {
     let ff_fields = reader.fast_fields();
     let ff_field_with_type = ff_fields
         .u64_lenient_for_type(allowed_column_types, field_name)?
-        .unwrap_or_else(|| {
-            (
-                Column::build_empty_column(reader.num_docs()),
-                ColumnType::U64,
-            )
-        });
+        .unwrap_or_else(|| (Column::build_empty_column(reader.num_docs()), fallback_type));
     Ok(ff_field_with_type)
 }
 
+/// Get the latitude and the longitude fast field readers of geo points.
+fn get_geo_point_ff_readers(
+    reader: &SegmentReader,
+    lat_field_name: &str,
+    lon_field_name: &str,
+) -> crate::Result<Vec<(columnar::Column<u64>, ColumnType)>> {
+    let allowed_column_types = [ColumnType::F64, ColumnType::I64, ColumnType::U64];
+    Ok(vec![
+        get_ff_reader(reader, lat_field_name, Some(&allowed_column_types))?,
+        get_ff_reader(reader, lon_field_name, Some(&allowed_column_types))?,
+    ])
+}
+
 fn get_dynamic_columns(
     reader: &SegmentReader,
     field_name: &str,
@@ -10,8 +10,10 @@ use serde::{Deserialize, Serialize};
 
 use super::bucket::GetDocCount;
 use super::metric::{
-    ExtendedStats, PercentilesMetricResult, SingleMetricResult, Stats, TopHitsMetricResult,
+    ExtendedStats, PercentilesMetricResult, ScriptedMetricResult, SingleMetricResult, Stats,
+    TopHitsMetricResult, TopMetricsResult,
 };
+use super::pipeline::PipelineBucket;
 use super::{AggregationError, Key};
 use crate::TantivyError;
 
@@ -97,10 +99,18 @@ pub enum MetricResult {
     Sum(SingleMetricResult),
     /// Percentiles metric result.
     Percentiles(PercentilesMetricResult),
+    /// Rate metric result.
+    Rate(SingleMetricResult),
     /// Top hits metric result
     TopHits(TopHitsMetricResult),
+    /// Top metrics metric result
+    TopMetrics(TopMetricsResult),
     /// Cardinality metric result
     Cardinality(SingleMetricResult),
+    /// Scripted metric result
+    ScriptedMetric(ScriptedMetricResult),
+    /// Value computed by a pipeline aggregation.
+    SimpleValue(SingleMetricResult),
 }
 
 impl MetricResult {
@@ -116,10 +126,14 @@ impl MetricResult {
             MetricResult::Percentiles(_) => Err(TantivyError::AggregationError(
                 AggregationError::InvalidRequest("percentiles can't be used to order".to_string()),
             )),
+            MetricResult::Rate(rate) => Ok(rate.value),
             MetricResult::TopHits(_) => Err(TantivyError::AggregationError(
                 AggregationError::InvalidRequest("top_hits can't be used to order".to_string()),
             )),
+            MetricResult::TopMetrics(top_metrics) => top_metrics.get_value(agg_property),
             MetricResult::Cardinality(card) => Ok(card.value),
+            MetricResult::ScriptedMetric(scripted_metric) => scripted_metric.get_value(),
+            MetricResult::SimpleValue(simple_value) => Ok(simple_value.value),
         }
     }
 }
@@ -144,6 +158,15 @@ pub enum BucketResult {
         /// See [`HistogramAggregation`](super::bucket::HistogramAggregation)
         buckets: BucketEntries<BucketEntry>,
     },
+    /// This is the auto date histogram result
+    AutoDateHistogram {
+        /// The buckets, without holes between the first and the last bucket.
+        ///
+        /// See [`AutoDateHistogramAggregation`](super::bucket::AutoDateHistogramAggregation)
+        buckets: Vec<BucketEntry>,
+        /// The interval of the buckets, e.g. `7d`.
+        interval: String,
+    },
     /// This is the term result
     Terms {
         /// The buckets.
@@ -156,6 +179,31 @@ pub enum BucketResult {
         /// The upper bound error for the doc count of each term.
         doc_count_error_upper_bound: Option<u64>,
     },
+    /// This is the composite result
+    Composite {
+        /// The key of the last bucket, to pass as `after` to get the next page of buckets.
+        /// `None` if there are no buckets left.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        after_key: Option<FxHashMap<String, Option<Key>>>,
+        /// The buckets, sorted by composite key.
+        ///
+        /// See [`CompositeAggregation`](super::bucket::CompositeAggregation)
+        buckets: Vec<CompositeBucketEntry>,
+    },
+    /// This is the filters result
+    Filters {
+        /// The buckets, by name.
+        ///
+        /// See [`FiltersAggregation`](super::bucket::FiltersAggregation)
+        buckets: FxHashMap<String, FiltersBucketEntry>,
+    },
+    /// This is the geohash or geotile grid result
+    GeoGrid {
+        /// The buckets, sorted by descending doc count.
+        ///
+        /// See [`GeoHashGridAggregation`](super::bucket::GeoHashGridAggregation)
+        buckets: Vec<BucketEntry>,
+    },
 }
 
 impl BucketResult {
@@ -167,11 +215,24 @@ impl BucketResult {
             BucketResult::Histogram { buckets } => {
                 buckets.iter().map(|bucket| bucket.get_bucket_count()).sum()
             }
+            BucketResult::AutoDateHistogram { buckets, .. } => {
+                buckets.iter().map(|bucket| bucket.get_bucket_count()).sum()
+            }
             BucketResult::Terms {
                 buckets,
                 sum_other_doc_count: _,
                 doc_count_error_upper_bound: _,
             } => buckets.iter().map(|bucket| bucket.get_bucket_count()).sum(),
+            BucketResult::Composite { buckets, .. } => {
+                buckets.iter().map(|bucket| bucket.get_bucket_count()).sum()
+            }
+            BucketResult::Filters { buckets } => buckets
+                .values()
+                .map(|bucket| bucket.get_bucket_count())
+                .sum(),
+            BucketResult::GeoGrid { buckets } => {
+                buckets.iter().map(|bucket| bucket.get_bucket_count()).sum()
+            }
         }
     }
 }
@@ -250,6 +311,87 @@ impl GetDocCount for BucketEntry {
         self.doc_count
     }
 }
+impl PipelineBucket for BucketEntry {
+    fn sub_aggregation(&self) -> &AggregationResults {
+        &self.sub_aggregation
+    }
+    fn sub_aggregation_mut(&mut self) -> &mut AggregationResults {
+        &mut self.sub_aggregation
+    }
+}
+
+/// This is the composite entry for a bucket, which contains the values of the sources, count,
+/// and optionally sub-aggregations.
+///
+/// # JSON Format
+/// ```json
+/// {
+///   ...
+///     "my_composite": {
+///       "after_key": { "product": "car", "color": null },
+///       "buckets": [
+///         {
+///           "key": { "product": "bike", "color": "red" },
+///           "doc_count": 5
+///         },
+///         {
+///           "key": { "product": "car", "color": null },
+///           "doc_count": 2
+///         }
+///       ]
+///    }
+///    ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeBucketEntry {
+    /// The value of each source, `None` for a missing value.
+    pub key: FxHashMap<String, Option<Key>>,
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    #[serde(flatten)]
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+impl CompositeBucketEntry {
+    pub(crate) fn get_bucket_count(&self) -> u64 {
+        1 + self.sub_aggregation.get_bucket_count()
+    }
+}
+
+/// This is the entry for the bucket of a filter, which contains a count, and optionally
+/// sub-aggregations.
+///
+/// # JSON Format
+/// ```json
+/// {
+///   ...
+///     "my_filters": {
+///       "buckets": {
+///         "errors": {
+///           "doc_count": 5
+///         },
+///         "warnings": {
+///           "doc_count": 2
+///         }
+///       }
+///    }
+///    ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FiltersBucketEntry {
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    #[serde(flatten)]
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+impl FiltersBucketEntry {
+    pub(crate) fn get_bucket_count(&self) -> u64 {
+        1 + self.sub_aggregation.get_bucket_count()
+    }
+}
 
 /// This is the range entry for a bucket, which contains a key, count, and optionally
 /// sub-aggregations.
@@ -308,3 +450,16 @@ impl RangeBucketEntry {
         1 + self.sub_aggregation.get_bucket_count()
     }
 }
+impl GetDocCount for RangeBucketEntry {
+    fn doc_count(&self) -> u64 {
+        self.doc_count
+    }
+}
+impl PipelineBucket for RangeBucketEntry {
+    fn sub_aggregation(&self) -> &AggregationResults {
+        &self.sub_aggregation
+    }
+    fn sub_aggregation_mut(&mut self) -> &mut AggregationResults {
+        &mut self.sub_aggregation
+    }
+}
@@ -0,0 +1,1029 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use columnar::{ColumnType, MonotonicallyMappableToU64, StrColumn};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use super::{parse_into_milliseconds, parse_offset_into_milliseconds, Order};
+use crate::aggregation::agg_req_with_accessor::{
+    AggregationWithAccessor, AggregationsWithAccessor,
+};
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateCompositeBucketEntry, IntermediateCompositeBucketResult, IntermediateKey,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+use crate::aggregation::{f64_from_fastfield_u64, Key};
+use crate::TantivyError;
+
+/// Creates a bucket for every combination of the values of several sources, and pages through
+/// the buckets in the order of their composite key.
+///
+/// Unlike the terms aggregation, the composite aggregation does not return the top buckets: the
+/// buckets are returned sorted by key, `size` buckets at a time. The `after_key` of a response is
+/// passed as `after` in the next request to get the next page of buckets, so that all of the
+/// buckets of a high-cardinality field can be enumerated with a bounded amount of memory.
+///
+/// The sources are compared in the order of the request, each of them in its own `order`,
+/// ascending by default. A source can be:
+/// - `terms`: one value per term of a text, `u64`, `i64`, `f64` or bool fast field.
+/// - `histogram`: one value per `interval` of a numeric fast field.
+/// - `date_histogram`: one value per `fixed_interval` of a date fast field, as a timestamp in
+///   milliseconds.
+///
+/// A document with several values for a source falls into all of the corresponding buckets.
+/// Documents without value for a source are ignored, unless `missing_bucket` is set on the
+/// source: their key is then `null`, which comes first in ascending order and last in descending
+/// order.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`CompositeBucketEntry`](crate::aggregation::agg_result::CompositeBucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "sales": {
+///         "composite": {
+///             "size": 2,
+///             "sources": [
+///                 { "product": { "terms": { "field": "product" } } },
+///                 { "day": { "date_histogram": { "field": "date", "fixed_interval": "1d" } } }
+///             ],
+///             "after": { "product": "bike", "day": 1546300800000 }
+///         }
+///     }
+/// }
+/// ```
+///
+/// # Response JSON Format
+/// ```json
+/// {
+///     ...
+///     "aggregations": {
+///         "sales": {
+///             "after_key": { "product": "bike", "day": 1546473600000 },
+///             "buckets": [
+///                 { "key": { "product": "bike", "day": 1546387200000 }, "doc_count": 4 },
+///                 { "key": { "product": "bike", "day": 1546473600000 }, "doc_count": 1 }
+///             ]
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompositeAggregation {
+    /// The sources of the composite key, in the order they are compared. Each entry maps the
+    /// name of a source to its definition.
+    pub sources: Vec<HashMap<String, CompositeSource>>,
+    /// The number of buckets to return. Defaults to 10.
+    #[serde(default = "default_size")]
+    pub size: u32,
+    /// Only the buckets whose key comes after this key are returned. This is the `after_key` of
+    /// the previous page.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub after: Option<HashMap<String, Option<Key>>>,
+}
+
+fn default_size() -> u32 {
+    10
+}
+
+fn default_order() -> Order {
+    Order::Asc
+}
+
+/// A source of the composite key of a [`CompositeAggregation`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CompositeSource {
+    /// One value per term.
+    #[serde(rename = "terms")]
+    Terms(TermsCompositeSource),
+    /// One value per histogram bucket.
+    #[serde(rename = "histogram")]
+    Histogram(HistogramCompositeSource),
+    /// One value per date histogram bucket.
+    #[serde(rename = "date_histogram")]
+    DateHistogram(DateHistogramCompositeSource),
+}
+
+/// Uses the terms of a field as values of the composite key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TermsCompositeSource {
+    /// The field to aggregate on.
+    pub field: String,
+    /// The order of the values. Defaults to ascending.
+    #[serde(default = "default_order")]
+    pub order: Order,
+    /// Whether the documents without value for the field get a `null` key.
+    #[serde(default)]
+    pub missing_bucket: bool,
+}
+
+/// Uses the histogram bucket of the values of a numeric field as values of the composite key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistogramCompositeSource {
+    /// The field to aggregate on.
+    pub field: String,
+    /// The interval to chunk the values into. The key of a bucket is the lower bound of its
+    /// interval.
+    pub interval: f64,
+    /// The order of the values. Defaults to ascending.
+    #[serde(default = "default_order")]
+    pub order: Order,
+    /// Whether the documents without value for the field get a `null` key.
+    #[serde(default)]
+    pub missing_bucket: bool,
+}
+
+/// Uses the date histogram bucket of the values of a date field as values of the composite key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DateHistogramCompositeSource {
+    /// The field to aggregate on.
+    pub field: String,
+    /// The interval to chunk the dates into, with the same syntax as the `fixed_interval` of the
+    /// [`DateHistogramAggregationReq`](super::DateHistogramAggregationReq).
+    pub fixed_interval: String,
+    /// Shifts the grid of the intervals, with the same syntax as the `offset` of the
+    /// [`DateHistogramAggregationReq`](super::DateHistogramAggregationReq).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub offset: Option<String>,
+    /// The order of the values. Defaults to ascending.
+    #[serde(default = "default_order")]
+    pub order: Order,
+    /// Whether the documents without value for the field get a `null` key.
+    #[serde(default)]
+    pub missing_bucket: bool,
+}
+
+impl CompositeSource {
+    /// Returns the name of the field used by the source.
+    pub fn field(&self) -> &str {
+        match self {
+            CompositeSource::Terms(terms) => &terms.field,
+            CompositeSource::Histogram(histogram) => &histogram.field,
+            CompositeSource::DateHistogram(histogram) => &histogram.field,
+        }
+    }
+
+    fn order(&self) -> Order {
+        match self {
+            CompositeSource::Terms(terms) => terms.order,
+            CompositeSource::Histogram(histogram) => histogram.order,
+            CompositeSource::DateHistogram(histogram) => histogram.order,
+        }
+    }
+
+    fn missing_bucket(&self) -> bool {
+        match self {
+            CompositeSource::Terms(terms) => terms.missing_bucket,
+            CompositeSource::Histogram(histogram) => histogram.missing_bucket,
+            CompositeSource::DateHistogram(histogram) => histogram.missing_bucket,
+        }
+    }
+
+    pub(crate) fn allowed_column_types(&self) -> &'static [ColumnType] {
+        match self {
+            CompositeSource::Terms(_) => &[
+                ColumnType::Str,
+                ColumnType::U64,
+                ColumnType::I64,
+                ColumnType::F64,
+                ColumnType::Bool,
+            ],
+            CompositeSource::Histogram(_) => &[ColumnType::F64, ColumnType::U64, ColumnType::I64],
+            CompositeSource::DateHistogram(_) => &[ColumnType::DateTime],
+        }
+    }
+}
+
+impl CompositeAggregation {
+    /// Returns the names and the definitions of the sources, in the order they are compared.
+    pub(crate) fn sources(&self) -> impl Iterator<Item = (&str, &CompositeSource)> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.iter())
+            .map(|(name, source)| (name.as_str(), source))
+    }
+
+    pub(crate) fn orders(&self) -> Vec<Order> {
+        self.sources().map(|(_, source)| source.order()).collect()
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.sources.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "composite aggregation requires at least one source".to_string(),
+            ));
+        }
+        if self.size == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "size in composite aggregation must be greater than 0".to_string(),
+            ));
+        }
+        let mut names = HashSet::new();
+        for source in &self.sources {
+            if source.len() != 1 {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "expected exactly one named source per entry of the composite sources, got \
+                     {:?}",
+                    source.keys().collect::<Vec<_>>()
+                )));
+            }
+            let (name, source) = source.iter().next().unwrap();
+            if !names.insert(name.as_str()) {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "duplicate source name {name:?} in composite aggregation"
+                )));
+            }
+            match source {
+                CompositeSource::Terms(_) => {}
+                CompositeSource::Histogram(histogram) => {
+                    if !(histogram.interval.is_finite() && histogram.interval > 0.0) {
+                        return Err(TantivyError::InvalidArgument(format!(
+                            "interval of histogram source {name:?} must be a positive number, got \
+                             {}",
+                            histogram.interval
+                        )));
+                    }
+                }
+                CompositeSource::DateHistogram(histogram) => {
+                    parse_into_milliseconds(&histogram.fixed_interval)?;
+                    if let Some(offset) = histogram.offset.as_ref() {
+                        parse_offset_into_milliseconds(offset)?;
+                    }
+                }
+            }
+        }
+        if let Some(after) = self.after.as_ref() {
+            if after.len() != names.len() || !names.iter().all(|name| after.contains_key(*name)) {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "after key of composite aggregation must have a value for each of the sources \
+                     {names:?}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two values of a source regardless of its order.
+///
+/// Numbers are compared by value whatever their type, and come before strings.
+fn cmp_keys(left: &IntermediateKey, right: &IntermediateKey) -> Ordering {
+    fn as_i128(key: &IntermediateKey) -> Option<i128> {
+        match key {
+            IntermediateKey::I64(val) => Some(*val as i128),
+            IntermediateKey::U64(val) => Some(*val as i128),
+            IntermediateKey::Bool(val) => Some(*val as i128),
+            _ => None,
+        }
+    }
+    fn as_f64(key: &IntermediateKey) -> Option<f64> {
+        match key {
+            IntermediateKey::F64(val) => Some(*val),
+            IntermediateKey::I64(val) => Some(*val as f64),
+            IntermediateKey::U64(val) => Some(*val as f64),
+            IntermediateKey::Bool(val) => Some(*val as u64 as f64),
+            IntermediateKey::Str(_) | IntermediateKey::IpAddr(_) => None,
+        }
+    }
+    match (as_f64(left), as_f64(right)) {
+        (Some(left_f64), Some(right_f64)) => match (as_i128(left), as_i128(right)) {
+            (Some(left_int), Some(right_int)) => left_int.cmp(&right_int),
+            _ => left_f64.total_cmp(&right_f64),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => left.partial_cmp(right).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Compares two composite keys in the order of the sources. A `None` value is a missing value.
+pub(crate) fn cmp_composite_keys(
+    left: &[Option<IntermediateKey>],
+    right: &[Option<IntermediateKey>],
+    orders: &[Order],
+) -> Ordering {
+    for ((left, right), order) in left.iter().zip(right).zip(orders) {
+        let ordering = match (left, right) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(left), Some(right)) => cmp_keys(left, right),
+        };
+        let ordering = match order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[derive(Clone, Debug)]
+enum SegmentSourceKind {
+    Terms,
+    /// The interval and the offset are in nanoseconds for dates.
+    Histogram {
+        interval: f64,
+        offset: f64,
+        is_date: bool,
+    },
+}
+
+/// The value of the `after` key for a source, converted for the segment.
+#[derive(Clone, Debug)]
+enum SegmentAfterValue {
+    Missing,
+    /// The term ordinal of the after term if it is in the dictionary of the segment, or else the
+    /// ordinal of the first term greater than the after term.
+    TermOrd {
+        ord: u64,
+        exact: bool,
+    },
+    Key(IntermediateKey),
+}
+
+#[derive(Clone, Debug)]
+struct SegmentCompositeSource {
+    kind: SegmentSourceKind,
+    column_type: ColumnType,
+    order: Order,
+    missing_bucket: bool,
+}
+
+impl SegmentCompositeSource {
+    /// Converts a value of the column into the value of the source in the segment: the value
+    /// itself for the terms, or the `f64` key of the bucket for the histograms, both of them being
+    /// monotonic with the key of the source.
+    #[inline]
+    fn segment_value(&self, val: u64) -> u64 {
+        match self.kind {
+            SegmentSourceKind::Terms => val,
+            SegmentSourceKind::Histogram {
+                interval,
+                offset,
+                is_date,
+            } => {
+                let val = f64_from_fastfield_u64(val, &self.column_type);
+                let mut key = ((val - offset) / interval).floor() * interval + offset;
+                if is_date {
+                    // The keys of the dates are in milliseconds.
+                    key /= 1_000_000.0;
+                }
+                key.to_u64()
+            }
+        }
+    }
+
+    fn is_term_ord(&self) -> bool {
+        matches!(self.kind, SegmentSourceKind::Terms) && self.column_type == ColumnType::Str
+    }
+
+    /// Converts a value of the source in the segment into its key. Term ordinals are resolved by
+    /// the caller.
+    fn numerical_key(&self, value: u64) -> IntermediateKey {
+        match (&self.kind, self.column_type) {
+            (SegmentSourceKind::Histogram { .. }, _) => IntermediateKey::F64(f64::from_u64(value)),
+            (SegmentSourceKind::Terms, ColumnType::I64) => {
+                IntermediateKey::I64(i64::from_u64(value))
+            }
+            (SegmentSourceKind::Terms, ColumnType::F64) => {
+                IntermediateKey::F64(f64::from_u64(value))
+            }
+            (SegmentSourceKind::Terms, ColumnType::Bool) => {
+                IntermediateKey::Bool(bool::from_u64(value))
+            }
+            (SegmentSourceKind::Terms, _) => IntermediateKey::U64(value),
+        }
+    }
+
+    /// Maps a value of the source to a rank, so that the composite keys of the segment sort in
+    /// the order of the request when compared lexicographically.
+    #[inline]
+    fn rank(&self, value: Option<u64>) -> u128 {
+        match (self.order, value) {
+            (Order::Asc, None) => 0,
+            (Order::Asc, Some(value)) => value as u128 + 1,
+            (Order::Desc, Some(value)) => (u64::MAX - value) as u128,
+            (Order::Desc, None) => u64::MAX as u128 + 1,
+        }
+    }
+
+    fn value_from_rank(&self, rank: u128) -> Option<u64> {
+        match self.order {
+            Order::Asc => rank.checked_sub(1).map(|value| value as u64),
+            Order::Desc => (rank <= u64::MAX as u128).then(|| u64::MAX - rank as u64),
+        }
+    }
+
+    fn cmp_to_after(&self, value: Option<u64>, after: &SegmentAfterValue) -> Ordering {
+        let ordering = match (value, after) {
+            (None, SegmentAfterValue::Missing) => Ordering::Equal,
+            (None, _) => Ordering::Less,
+            (Some(_), SegmentAfterValue::Missing) => Ordering::Greater,
+            (
+                Some(ord),
+                SegmentAfterValue::TermOrd {
+                    ord: after_ord,
+                    exact,
+                },
+            ) => {
+                if *exact || ord < *after_ord {
+                    ord.cmp(after_ord)
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (Some(value), SegmentAfterValue::Key(after_key)) => {
+                if self.is_term_ord() {
+                    // The after key is a number, which comes before the strings.
+                    Ordering::Greater
+                } else {
+                    cmp_keys(&self.numerical_key(value), after_key)
+                }
+            }
+        };
+        match self.order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+}
+
+fn segment_after_value(
+    after: Option<&Key>,
+    source: &SegmentCompositeSource,
+    str_dict_column: Option<&StrColumn>,
+) -> crate::Result<SegmentAfterValue> {
+    let after_value = match (after, str_dict_column) {
+        (None, _) => SegmentAfterValue::Missing,
+        (Some(Key::Str(term)), Some(str_dict_column)) if source.is_term_ord() => {
+            let dictionary = str_dict_column.dictionary();
+            if let Some(ord) = dictionary.term_ord(term)? {
+                SegmentAfterValue::TermOrd { ord, exact: true }
+            } else {
+                let mut stream = dictionary.range().gt(term).into_stream()?;
+                let ord = if stream.advance() {
+                    stream.term_ord()
+                } else {
+                    u64::MAX
+                };
+                SegmentAfterValue::TermOrd { ord, exact: false }
+            }
+        }
+        (Some(key), _) => SegmentAfterValue::Key(key.clone().into()),
+    };
+    Ok(after_value)
+}
+
+#[derive(Clone, Debug)]
+struct SegmentCompositeBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+/// The collector keeps the `size` smallest composite keys after the `after` key of the segment,
+/// which contain the `size` smallest keys after the `after` key of all of the segments.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentCompositeCollector {
+    sources: Vec<SegmentCompositeSource>,
+    after: Option<Vec<SegmentAfterValue>>,
+    size: usize,
+    /// The buckets, by the ranks of their values.
+    buckets: BTreeMap<Vec<u128>, SegmentCompositeBucketEntry>,
+    sub_aggregation_blueprint: Option<Box<dyn SegmentAggregationCollector>>,
+    /// The values of the current document, per source.
+    source_values: Vec<Vec<Option<u64>>>,
+    accessor_idx: usize,
+}
+
+impl SegmentAggregationCollector for SegmentCompositeCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let bucket = self.into_intermediate_bucket_result(agg_with_accessor)?;
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+
+        Ok(())
+    }
+
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+
+        for ((source, values), (accessor, _)) in self
+            .sources
+            .iter()
+            .zip(self.source_values.iter_mut())
+            .zip(bucket_agg_accessor.accessors.iter())
+        {
+            values.clear();
+            values.extend(
+                accessor
+                    .values_for_doc(doc)
+                    .map(|val| Some(source.segment_value(val))),
+            );
+            if values.is_empty() {
+                if !source.missing_bucket {
+                    return Ok(());
+                }
+                values.push(None);
+            }
+            values.sort_unstable();
+            values.dedup();
+        }
+
+        // Multivalued fields put the document in each combination of their values.
+        let mut value_idxs = vec![0; self.sources.len()];
+        loop {
+            self.collect_key(doc, &value_idxs, bucket_agg_accessor)?;
+            let mut source_idx = value_idxs.len();
+            loop {
+                if source_idx == 0 {
+                    return Ok(());
+                }
+                source_idx -= 1;
+                value_idxs[source_idx] += 1;
+                if value_idxs[source_idx] < self.source_values[source_idx].len() {
+                    break;
+                }
+                value_idxs[source_idx] = 0;
+            }
+        }
+    }
+
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        for doc in docs {
+            self.collect(*doc, agg_with_accessor)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        for bucket in self.buckets.values_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(sub_aggregation_accessor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SegmentCompositeCollector {
+    pub(crate) fn from_req_and_validate(
+        req: &CompositeAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        column_types: &[ColumnType],
+        str_dict_columns: &[Option<StrColumn>],
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        req.validate()?;
+        let sources: Vec<SegmentCompositeSource> = req
+            .sources()
+            .zip(column_types)
+            .map(|((_, source), column_type)| {
+                let kind = match source {
+                    CompositeSource::Terms(_) => SegmentSourceKind::Terms,
+                    CompositeSource::Histogram(histogram) => SegmentSourceKind::Histogram {
+                        interval: histogram.interval,
+                        offset: 0.0,
+                        is_date: false,
+                    },
+                    CompositeSource::DateHistogram(histogram) => {
+                        let offset = histogram
+                            .offset
+                            .as_ref()
+                            .map(|offset| parse_offset_into_milliseconds(offset))
+                            .transpose()?
+                            .unwrap_or(0);
+                        SegmentSourceKind::Histogram {
+                            interval: parse_into_milliseconds(&histogram.fixed_interval)? as f64
+                                * 1_000_000.0,
+                            offset: offset as f64 * 1_000_000.0,
+                            is_date: true,
+                        }
+                    }
+                };
+                Ok(SegmentCompositeSource {
+                    kind,
+                    column_type: *column_type,
+                    order: source.order(),
+                    missing_bucket: source.missing_bucket(),
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+
+        let after = req
+            .after
+            .as_ref()
+            .map(|after| {
+                req.sources()
+                    .zip(&sources)
+                    .zip(str_dict_columns)
+                    .map(|(((name, _), source), str_dict_column)| {
+                        let after_value = after.get(name).and_then(Option::as_ref);
+                        segment_after_value(after_value, source, str_dict_column.as_ref())
+                    })
+                    .collect::<crate::Result<Vec<_>>>()
+            })
+            .transpose()?;
+
+        let sub_aggregation_blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+
+        Ok(SegmentCompositeCollector {
+            source_values: vec![Vec::new(); sources.len()],
+            sources,
+            after,
+            size: req.size as usize,
+            buckets: BTreeMap::new(),
+            sub_aggregation_blueprint,
+            accessor_idx,
+        })
+    }
+
+    fn is_after_cursor(&self, value_idxs: &[usize]) -> bool {
+        let Some(after) = self.after.as_ref() else {
+            return true;
+        };
+        for (((source, values), value_idx), after_value) in self
+            .sources
+            .iter()
+            .zip(&self.source_values)
+            .zip(value_idxs)
+            .zip(after)
+        {
+            match source.cmp_to_after(values[*value_idx], after_value) {
+                Ordering::Less => return false,
+                Ordering::Greater => return true,
+                Ordering::Equal => {}
+            }
+        }
+        // The key is the after key itself.
+        false
+    }
+
+    #[inline]
+    fn collect_key(
+        &mut self,
+        doc: crate::DocId,
+        value_idxs: &[usize],
+        bucket_agg_accessor: &mut AggregationWithAccessor,
+    ) -> crate::Result<()> {
+        if !self.is_after_cursor(value_idxs) {
+            return Ok(());
+        }
+        let key: Vec<u128> = self
+            .sources
+            .iter()
+            .zip(&self.source_values)
+            .zip(value_idxs)
+            .map(|((source, values), value_idx)| source.rank(values[*value_idx]))
+            .collect();
+        if self.buckets.len() >= self.size {
+            if let Some((last_key, _)) = self.buckets.last_key_value() {
+                if &key > last_key {
+                    return Ok(());
+                }
+            }
+        }
+        let bucket = self
+            .buckets
+            .entry(key)
+            .or_insert_with(|| SegmentCompositeBucketEntry {
+                doc_count: 0,
+                sub_aggregation: self.sub_aggregation_blueprint.clone(),
+            });
+        bucket.doc_count += 1;
+        if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+            sub_aggregation.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+        }
+        if self.buckets.len() > self.size {
+            self.buckets.pop_last();
+        }
+        Ok(())
+    }
+
+    fn into_intermediate_bucket_result(
+        self,
+        agg_with_accessor: &AggregationWithAccessor,
+    ) -> crate::Result<IntermediateBucketResult> {
+        let mut entries = FxHashMap::default();
+        let mut term = String::new();
+        for (ranks, bucket) in self.buckets {
+            let mut key = Vec::with_capacity(ranks.len());
+            for ((source, rank), str_dict_column) in self
+                .sources
+                .iter()
+                .zip(ranks)
+                .zip(&agg_with_accessor.str_dict_columns)
+            {
+                let Some(value) = source.value_from_rank(rank) else {
+                    key.push(None);
+                    continue;
+                };
+                if !source.is_term_ord() {
+                    key.push(Some(source.numerical_key(value)));
+                    continue;
+                }
+                let found = str_dict_column
+                    .as_ref()
+                    .map(|str_dict_column| str_dict_column.ord_to_str(value, &mut term))
+                    .transpose()?
+                    .unwrap_or(false);
+                if !found {
+                    return Err(TantivyError::InternalError(format!(
+                        "could not find term ordinal {value} in the dictionary"
+                    )));
+                }
+                key.push(Some(IntermediateKey::Str(term.clone())));
+            }
+
+            let mut sub_aggregation_res = IntermediateAggregationResults::default();
+            if let Some(sub_aggregation) = bucket.sub_aggregation {
+                sub_aggregation.add_intermediate_aggregation_result(
+                    &agg_with_accessor.sub_aggregation,
+                    &mut sub_aggregation_res,
+                )?;
+            }
+            entries.insert(
+                key,
+                IntermediateCompositeBucketEntry {
+                    doc_count: bucket.doc_count,
+                    sub_aggregation: sub_aggregation_res,
+                },
+            );
+        }
+        Ok(IntermediateBucketResult::Composite {
+            buckets: IntermediateCompositeBucketResult { entries },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{DateTime, TantivyDocument};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let product = schema_builder.add_text_field("product", STRING | FAST);
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let quantity = schema_builder.add_i64_field("quantity", FAST);
+        let date = schema_builder.add_date_field("date", FAST);
+        let day = 86_400i64;
+        let docs: Vec<TantivyDocument> = [
+            ("bike", Some("red"), 120.0, 1, 0),
+            ("bike", Some("blue"), 150.0, 2, day),
+            ("car", Some("red"), 15_000.0, 1, day),
+            ("bike", None, 99.0, 3, 2 * day),
+            ("scooter", Some("red"), 400.0, 2, 0),
+            ("car", None, 22_000.0, 1, 2 * day),
+            ("bike", Some("red"), 130.0, 4, 2 * day),
+            ("scooter", Some("blue"), 350.0, 1, day),
+        ]
+        .into_iter()
+        .map(
+            |(product_val, color_val, price_val, quantity_val, timestamp)| {
+                let mut doc = doc!(
+                    product => product_val,
+                    price => price_val,
+                    quantity => quantity_val as i64,
+                    date => DateTime::from_timestamp_secs(timestamp),
+                );
+                if let Some(color_val) = color_val {
+                    doc.add_text(color, color_val);
+                }
+                doc
+            },
+        )
+        .collect();
+        // Three docs per segment.
+        let segment_and_docs = docs.chunks(3).map(|docs| docs.to_vec()).collect();
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    fn bucket_keys(res: &Value) -> Vec<Value> {
+        res["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket["key"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn composite_aggregation_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "sales": {
+                    "composite": {
+                        "sources": [
+                            { "product": { "terms": { "field": "product" } } },
+                            { "color": { "terms": { "field": "color", "order": "desc" } } }
+                        ]
+                    },
+                    "aggs": {
+                        "avg_quantity": { "avg": { "field": "quantity" } }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["sales"]["buckets"],
+                json!([
+                    { "key": { "product": "bike", "color": "red" }, "doc_count": 2,
+                      "avg_quantity": { "value": 2.5 } },
+                    { "key": { "product": "bike", "color": "blue" }, "doc_count": 1,
+                      "avg_quantity": { "value": 2.0 } },
+                    { "key": { "product": "car", "color": "red" }, "doc_count": 1,
+                      "avg_quantity": { "value": 1.0 } },
+                    { "key": { "product": "scooter", "color": "red" }, "doc_count": 1,
+                      "avg_quantity": { "value": 2.0 } },
+                    { "key": { "product": "scooter", "color": "blue" }, "doc_count": 1,
+                      "avg_quantity": { "value": 1.0 } },
+                ])
+            );
+            assert_eq!(
+                res["sales"]["after_key"],
+                json!({ "product": "scooter", "color": "blue" })
+            );
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "sales": {
+                    "composite": {
+                        "sources": [
+                            { "color": { "terms": { "field": "color", "missing_bucket": true } } },
+                            { "price": { "histogram": { "field": "price", "interval": 100.0,
+                                                        "order": "desc" } } }
+                        ],
+                        "size": 20
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                bucket_keys(&res["sales"]),
+                vec![
+                    json!({ "color": null, "price": 22_000.0 }),
+                    json!({ "color": null, "price": 0.0 }),
+                    json!({ "color": "blue", "price": 300.0 }),
+                    json!({ "color": "blue", "price": 100.0 }),
+                    json!({ "color": "red", "price": 15_000.0 }),
+                    json!({ "color": "red", "price": 400.0 }),
+                    json!({ "color": "red", "price": 100.0 }),
+                ]
+            );
+            assert_eq!(res["sales"]["buckets"][6]["doc_count"], 2);
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "sales": {
+                    "composite": {
+                        "sources": [
+                            { "day": { "date_histogram": { "field": "date", "fixed_interval": "1d",
+                                                           "order": "desc" } } },
+                            { "quantity": { "terms": { "field": "quantity" } } }
+                        ],
+                        "size": 3
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["sales"]["buckets"],
+                json!([
+                    { "key": { "day": 172_800_000.0, "quantity": 1 }, "doc_count": 1 },
+                    { "key": { "day": 172_800_000.0, "quantity": 3 }, "doc_count": 1 },
+                    { "key": { "day": 172_800_000.0, "quantity": 4 }, "doc_count": 1 },
+                ])
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn composite_aggregation_paging() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let sources = json!([
+            { "product": { "terms": { "field": "product", "order": "desc" } } },
+            { "color": { "terms": { "field": "color", "missing_bucket": true } } },
+            { "day": { "date_histogram": { "field": "date", "fixed_interval": "1d" } } }
+        ]);
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "sales": { "composite": { "sources": sources, "size": 100 } }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        let all_buckets = res["sales"]["buckets"].as_array().unwrap().clone();
+        assert_eq!(all_buckets.len(), 8);
+        assert_eq!(
+            all_buckets[0]["key"],
+            json!({ "product": "scooter", "color": "blue", "day": 86_400_000.0 })
+        );
+
+        let mut paged_buckets = Vec::new();
+        let mut after_key = Value::Null;
+        loop {
+            let mut composite = json!({ "sources": sources, "size": 3 });
+            if !after_key.is_null() {
+                composite["after"] = after_key.clone();
+            }
+            let agg_req: Aggregations =
+                serde_json::from_value(json!({ "sales": { "composite": composite } })).unwrap();
+            let res = exec_request(agg_req, &index)?;
+            let buckets = res["sales"]["buckets"].as_array().unwrap();
+            assert!(buckets.len() <= 3);
+            if buckets.is_empty() {
+                assert!(res["sales"].get("after_key").is_none());
+                break;
+            }
+            paged_buckets.extend(buckets.iter().cloned());
+            after_key = res["sales"]["after_key"].clone();
+        }
+        assert_eq!(paged_buckets, all_buckets);
+
+        // An after key which is not in the index.
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "sales": { "composite": {
+                "sources": sources,
+                "after": { "product": "bus", "color": "red", "day": 0.0 }
+            } }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            bucket_keys(&res["sales"]),
+            vec![
+                json!({ "product": "bike", "color": null, "day": 172_800_000.0 }),
+                json!({ "product": "bike", "color": "blue", "day": 86_400_000.0 }),
+                json!({ "product": "bike", "color": "red", "day": 0.0 }),
+                json!({ "product": "bike", "color": "red", "day": 172_800_000.0 }),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn composite_aggregation_invalid_request() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let invalid_requests = [
+            json!({ "sources": [] }),
+            json!({ "sources": [{ "product": { "terms": { "field": "product" } } }], "size": 0 }),
+            json!({ "sources": [
+                { "product": { "terms": { "field": "product" } } },
+                { "product": { "terms": { "field": "color" } } }
+            ] }),
+            json!({ "sources": [{ "price": { "histogram": { "field": "price", "interval": 0.0 } } }] }),
+            json!({ "sources": [{ "day": { "date_histogram": { "field": "date",
+                                                               "fixed_interval": "1month" } } }] }),
+            json!({
+                "sources": [{ "product": { "terms": { "field": "product" } } }],
+                "after": { "color": "red" }
+            }),
+        ];
+        for composite in invalid_requests {
+            let agg_req: Aggregations =
+                serde_json::from_value(json!({ "sales": { "composite": composite } })).unwrap();
+            assert!(exec_request(agg_req, &index).is_err());
+        }
+        Ok(())
+    }
+}
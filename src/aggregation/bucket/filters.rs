@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::BitSet;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_req_with_accessor::{
+    AggregationWithAccessor, AggregationsWithAccessor,
+};
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateFiltersBucketEntry, IntermediateFiltersBucketResult,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+use crate::index::SegmentReader;
+use crate::query::{EnableScoring, QueryParser};
+use crate::tokenizer::TokenizerManager;
+use crate::TantivyError;
+
+const DEFAULT_OTHER_BUCKET_KEY: &str = "_other_";
+
+/// Creates a bucket per named query, containing the documents matching the query.
+///
+/// The queries use the [`QueryParser`] syntax, and have to name the fields they search, e.g.
+/// `body:error AND level:warn`. They are tokenized with the default tokenizers of the
+/// [`TokenizerManager`]. A document matching several queries falls into each of their buckets.
+///
+/// If `other_bucket` is set, or if an `other_bucket_key` is given, the documents matching none of
+/// the queries fall into an additional bucket, named by `other_bucket_key` (`_other_` by default).
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`FiltersBucketEntry`](crate::aggregation::agg_result::FiltersBucketEntry) on the
+/// `AggregationCollector`, by bucket name.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "messages": {
+///         "filters": {
+///             "filters": {
+///                 "errors": "body:error",
+///                 "warnings": "body:warning"
+///             },
+///             "other_bucket": true
+///         },
+///         "aggs": {
+///             "avg_duration": { "avg": { "field": "duration" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FiltersAggregation {
+    /// The query of each bucket, by bucket name.
+    pub filters: HashMap<String, String>,
+    /// Whether to add a bucket for the documents matching none of the queries.
+    #[serde(default)]
+    pub other_bucket: bool,
+    /// The name of the bucket of the documents matching none of the queries. Setting it implies
+    /// `other_bucket`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub other_bucket_key: Option<String>,
+}
+
+impl FiltersAggregation {
+    /// Returns the name of the bucket of the documents matching none of the queries, if there is
+    /// such a bucket.
+    fn other_bucket_key(&self) -> Option<&str> {
+        match &self.other_bucket_key {
+            Some(other_bucket_key) => Some(other_bucket_key),
+            None if self.other_bucket => Some(DEFAULT_OTHER_BUCKET_KEY),
+            None => None,
+        }
+    }
+
+    /// Returns the names of the buckets: the sorted names of the queries, followed by the name of
+    /// the other bucket.
+    pub(crate) fn bucket_names(&self) -> Vec<&str> {
+        let mut bucket_names: Vec<&str> = self.filters.keys().map(String::as_str).collect();
+        bucket_names.sort_unstable();
+        bucket_names.extend(self.other_bucket_key());
+        bucket_names
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if let Some(other_bucket_key) = self.other_bucket_key() {
+            if self.filters.contains_key(other_bucket_key) {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "other_bucket_key {other_bucket_key:?} of filters aggregation is also the \
+                     name of a filter"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the documents of the segment matching each query, in the order of the sorted
+    /// names of the queries.
+    pub(crate) fn filter_doc_sets(
+        &self,
+        reader: &SegmentReader,
+    ) -> crate::Result<Vec<Arc<BitSet>>> {
+        let schema = reader.schema();
+        let query_parser =
+            QueryParser::new(schema.clone(), Vec::new(), TokenizerManager::default());
+        let mut filters: Vec<(&String, &String)> = self.filters.iter().collect();
+        filters.sort_unstable();
+        filters
+            .into_iter()
+            .map(|(name, query)| {
+                let query = query_parser.parse_query(query).map_err(|err| {
+                    TantivyError::InvalidArgument(format!(
+                        "could not parse the query of filter {name:?}: {err}"
+                    ))
+                })?;
+                let weight = query.weight(EnableScoring::disabled_from_schema(schema))?;
+                let mut doc_set = BitSet::with_max_value(reader.max_doc());
+                weight.for_each_no_score(reader, &mut |docs| {
+                    for &doc in docs {
+                        doc_set.insert(doc);
+                    }
+                })?;
+                Ok(Arc::new(doc_set))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SegmentFiltersBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+impl SegmentFiltersBucketEntry {
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        sub_aggregation_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.doc_count += 1;
+        if let Some(sub_aggregation) = self.sub_aggregation.as_mut() {
+            sub_aggregation.collect(doc, sub_aggregation_accessor)?;
+        }
+        Ok(())
+    }
+}
+
+/// The collector counts the documents matching each filter.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentFiltersCollector {
+    /// A bucket per filter, in the order of the `filter_doc_sets` of the accessor, followed by the
+    /// other bucket, if any.
+    buckets: Vec<SegmentFiltersBucketEntry>,
+    has_other_bucket: bool,
+    accessor_idx: usize,
+}
+
+impl SegmentAggregationCollector for SegmentFiltersCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let bucket = self.into_intermediate_bucket_result(agg_with_accessor)?;
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+
+        Ok(())
+    }
+
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+
+        for &doc in docs {
+            let mut matches_a_filter = false;
+            for (bucket, doc_set) in self
+                .buckets
+                .iter_mut()
+                .zip(&bucket_agg_accessor.filter_doc_sets)
+            {
+                if doc_set.contains(doc) {
+                    matches_a_filter = true;
+                    bucket.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+                }
+            }
+            if self.has_other_bucket && !matches_a_filter {
+                let other_bucket = self.buckets.last_mut().expect("other bucket");
+                other_bucket.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        for bucket in self.buckets.iter_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(sub_aggregation_accessor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SegmentFiltersCollector {
+    pub(crate) fn from_req_and_validate(
+        req: &FiltersAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        let sub_aggregation_blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+        let buckets = req
+            .bucket_names()
+            .iter()
+            .map(|_| SegmentFiltersBucketEntry {
+                doc_count: 0,
+                sub_aggregation: sub_aggregation_blueprint.clone(),
+            })
+            .collect();
+        Ok(SegmentFiltersCollector {
+            buckets,
+            has_other_bucket: req.other_bucket_key().is_some(),
+            accessor_idx,
+        })
+    }
+
+    fn into_intermediate_bucket_result(
+        self,
+        agg_with_accessor: &AggregationWithAccessor,
+    ) -> crate::Result<IntermediateBucketResult> {
+        let req = agg_with_accessor
+            .agg
+            .agg
+            .as_filters()
+            .expect("unexpected aggregation, expected filters aggregation");
+        let mut entries = FxHashMap::default();
+        for (bucket_name, bucket) in req.bucket_names().into_iter().zip(self.buckets) {
+            let mut sub_aggregation_res = IntermediateAggregationResults::default();
+            if let Some(sub_aggregation) = bucket.sub_aggregation {
+                sub_aggregation.add_intermediate_aggregation_result(
+                    &agg_with_accessor.sub_aggregation,
+                    &mut sub_aggregation_res,
+                )?;
+            }
+            entries.insert(
+                bucket_name.to_string(),
+                IntermediateFiltersBucketEntry {
+                    doc_count: bucket.doc_count,
+                    sub_aggregation: sub_aggregation_res,
+                },
+            );
+        }
+        Ok(IntermediateBucketResult::Filters {
+            buckets: IntermediateFiltersBucketResult { entries },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::TantivyDocument;
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let level = schema_builder.add_text_field("level", STRING | FAST);
+        let duration = schema_builder.add_f64_field("duration", FAST);
+        let segment_and_docs = vec![
+            vec![
+                doc!(body => "disk error", level => "error", duration => 10.0),
+                doc!(body => "slow disk warning", level => "warn", duration => 20.0),
+            ],
+            vec![
+                doc!(body => "network error", level => "error", duration => 30.0),
+                doc!(body => "all good", level => "info", duration => 1.0),
+                doc!(body => "network warning", level => "warn", duration => 2.0),
+            ],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn filters_agg_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "messages": {
+                    "filters": {
+                        "filters": {
+                            "errors": "body:error",
+                            "disk": "body:disk",
+                            "network_warnings": "body:network AND level:warn",
+                            "none": "body:unknown",
+                        },
+                        "other_bucket": true
+                    },
+                    "aggs": {
+                        "duration": { "sum": { "field": "duration" } }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["messages"],
+                json!({
+                    "buckets": {
+                        "errors": { "doc_count": 2, "duration": { "value": 40.0 } },
+                        "disk": { "doc_count": 2, "duration": { "value": 30.0 } },
+                        "network_warnings": { "doc_count": 1, "duration": { "value": 2.0 } },
+                        "none": { "doc_count": 0, "duration": { "value": 0.0 } },
+                        "_other_": { "doc_count": 1, "duration": { "value": 1.0 } },
+                    }
+                })
+            );
+
+            // The filters can be nested in other bucket aggregations, and have a custom other bucket.
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "levels": {
+                    "terms": { "field": "level" },
+                    "aggs": {
+                        "messages": {
+                            "filters": {
+                                "filters": { "network": "body:network" },
+                                "other_bucket_key": "local"
+                            }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["levels"]["buckets"],
+                json!([
+                    {
+                        "key": "error",
+                        "doc_count": 2,
+                        "messages": {
+                            "buckets": {
+                                "network": { "doc_count": 1 },
+                                "local": { "doc_count": 1 }
+                            }
+                        }
+                    },
+                    {
+                        "key": "warn",
+                        "doc_count": 2,
+                        "messages": {
+                            "buckets": {
+                                "network": { "doc_count": 1 },
+                                "local": { "doc_count": 1 }
+                            }
+                        }
+                    },
+                    {
+                        "key": "info",
+                        "doc_count": 1,
+                        "messages": {
+                            "buckets": {
+                                "network": { "doc_count": 0 },
+                                "local": { "doc_count": 1 }
+                            }
+                        }
+                    },
+                ])
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn filters_agg_invalid_request_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "messages": {
+                "filters": { "filters": { "errors": "unknown_field:error" } }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'could not parse the query of filter \"errors\": \
+             Field does not exist: 'unknown_field''"
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "messages": {
+                "filters": {
+                    "filters": { "errors": "body:error" },
+                    "other_bucket_key": "errors"
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'other_bucket_key \"errors\" of filters aggregation \
+             is also the name of a filter'"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,636 @@
+use std::f64::consts::PI;
+
+use columnar::ColumnType;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_limits::MemoryConsumption;
+use crate::aggregation::agg_req_with_accessor::{
+    AggregationWithAccessor, AggregationsWithAccessor,
+};
+use crate::aggregation::f64_from_fastfield_u64;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateGeoGridBucketEntry, IntermediateGeoGridBucketResult,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+use crate::TantivyError;
+
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const MAX_GEOHASH_PRECISION: u8 = 12;
+const MAX_GEOTILE_PRECISION: u8 = 29;
+/// The latitudes of the web mercator projection are within `[-MAX_TILE_LAT, MAX_TILE_LAT]`.
+const MAX_TILE_LAT: f64 = 85.051_128_779_806_59;
+
+/// Groups the geo points into the cells of a [geohash](https://en.wikipedia.org/wiki/Geohash)
+/// grid, e.g. to render a heat map.
+///
+/// A geo point is stored as a latitude and a longitude `f64` fast fields, in degrees. The key of
+/// a bucket is the geohash of its cell, whose length is the `precision`, from 1 (cells of about
+/// 5,000km) to 12 (cells of a few centimeters).
+///
+/// The buckets are sorted by descending doc count, and at most `size` of them are returned. A
+/// document with several points falls into the cell of each of its points, the latitudes and
+/// longitudes being paired in the order of the values.
+///
+/// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
+/// [`BucketEntry`](crate::aggregation::agg_result::BucketEntry) on the
+/// `AggregationCollector`.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "stores": {
+///         "geohash_grid": {
+///             "lat_field": "lat",
+///             "lon_field": "lon",
+///             "precision": 3
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoHashGridAggregation {
+    /// The fast field containing the latitudes.
+    pub lat_field: String,
+    /// The fast field containing the longitudes.
+    pub lon_field: String,
+    /// The length of the geohashes, within `[1, 12]`. Defaults to 5.
+    #[serde(default = "default_geohash_precision")]
+    pub precision: u8,
+    /// Only the points within the bounding box are aggregated.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bounds: Option<GeoBoundingBox>,
+    /// The maximum number of buckets to return. Defaults to 10,000.
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+/// Groups the geo points into the tiles of a map, e.g. to render a heat map.
+///
+/// The tiles are the tiles of the web mercator projection used by most of the online maps. The
+/// key of a bucket is `{zoom}/{x}/{y}`, the zoom being the `precision`, from 0 (a single tile for
+/// the whole world) to 29.
+///
+/// Like the [`GeoHashGridAggregation`], the geo points are stored as a latitude and a longitude
+/// `f64` fast fields, in degrees. The points beyond the latitudes of the projection, about 85
+/// degrees, fall into the tiles of the edges of the map.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "stores": {
+///         "geotile_grid": {
+///             "lat_field": "lat",
+///             "lon_field": "lon",
+///             "precision": 8,
+///             "bounds": { "top": 49.0, "left": 2.0, "bottom": 48.0, "right": 3.0 }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoTileGridAggregation {
+    /// The fast field containing the latitudes.
+    pub lat_field: String,
+    /// The fast field containing the longitudes.
+    pub lon_field: String,
+    /// The zoom level of the tiles, within `[0, 29]`. Defaults to 7.
+    #[serde(default = "default_geotile_precision")]
+    pub precision: u8,
+    /// Only the points within the bounding box are aggregated.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bounds: Option<GeoBoundingBox>,
+    /// The maximum number of buckets to return. Defaults to 10,000.
+    #[serde(default = "default_size")]
+    pub size: u32,
+}
+
+fn default_geohash_precision() -> u8 {
+    5
+}
+
+fn default_geotile_precision() -> u8 {
+    7
+}
+
+fn default_size() -> u32 {
+    10_000
+}
+
+/// A bounding box, in degrees. The box crosses the antimeridian if `left` is greater than
+/// `right`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoBoundingBox {
+    /// The maximum latitude.
+    pub top: f64,
+    /// The minimum longitude.
+    pub left: f64,
+    /// The minimum latitude.
+    pub bottom: f64,
+    /// The maximum longitude.
+    pub right: f64,
+}
+
+impl GeoBoundingBox {
+    fn validate(&self) -> crate::Result<()> {
+        let is_lat = |lat: f64| (-90.0..=90.0).contains(&lat);
+        let is_lon = |lon: f64| (-180.0..=180.0).contains(&lon);
+        if !(is_lat(self.top) && is_lat(self.bottom) && is_lon(self.left) && is_lon(self.right)) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "bounds of geo grid aggregation are out of range: {self:?}"
+            )));
+        }
+        if self.top < self.bottom {
+            return Err(TantivyError::InvalidArgument(format!(
+                "top of the bounds of geo grid aggregation is below the bottom: {self:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        let contains_lon = if self.left <= self.right {
+            (self.left..=self.right).contains(&lon)
+        } else {
+            lon >= self.left || lon <= self.right
+        };
+        contains_lon && (self.bottom..=self.top).contains(&lat)
+    }
+}
+
+/// The grid of a geo grid aggregation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GeoGrid {
+    GeoHash { precision: u8 },
+    GeoTile { precision: u8 },
+}
+
+impl GeoGrid {
+    /// Returns the cell of a point, as an integer identifying the cell within the grid.
+    fn cell(&self, lat: f64, lon: f64) -> u64 {
+        match *self {
+            GeoGrid::GeoHash { precision } => {
+                let num_bits = 5 * precision as u32;
+                let num_lon_bits = num_bits.div_ceil(2);
+                let num_lat_bits = num_bits / 2;
+                let lon_bits = quantize((lon + 180.0) / 360.0, num_lon_bits);
+                let lat_bits = quantize((lat + 90.0) / 180.0, num_lat_bits);
+                // The bits are interleaved, starting with the most significant bit of the
+                // longitude.
+                let mut cell = 0;
+                for bit_idx in 0..num_bits {
+                    let (value, num_value_bits) = if bit_idx % 2 == 0 {
+                        (lon_bits, num_lon_bits)
+                    } else {
+                        (lat_bits, num_lat_bits)
+                    };
+                    let bit = (value >> (num_value_bits - 1 - bit_idx / 2)) & 1;
+                    cell = (cell << 1) | bit;
+                }
+                cell
+            }
+            GeoGrid::GeoTile { precision } => {
+                let lat_rad = lat.clamp(-MAX_TILE_LAT, MAX_TILE_LAT).to_radians();
+                let x = quantize((lon + 180.0) / 360.0, precision as u32);
+                let y = quantize(
+                    (1.0 - (lat_rad.tan() + 1.0 / lat_rad.cos()).ln() / PI) / 2.0,
+                    precision as u32,
+                );
+                (x << 32) | y
+            }
+        }
+    }
+
+    /// Returns the key of a cell, the geohash or `{zoom}/{x}/{y}`.
+    pub(crate) fn cell_key(&self, cell: u64) -> String {
+        match *self {
+            GeoGrid::GeoHash { precision } => (0..precision)
+                .rev()
+                .map(|char_idx| GEOHASH_ALPHABET[((cell >> (5 * char_idx)) & 31) as usize] as char)
+                .collect(),
+            GeoGrid::GeoTile { precision } => {
+                format!("{precision}/{}/{}", cell >> 32, cell & u32::MAX as u64)
+            }
+        }
+    }
+}
+
+/// Maps a value of `[0, 1]` to one of the `2^num_bits` intervals of the same width.
+fn quantize(value: f64, num_bits: u32) -> u64 {
+    let num_intervals = 1u64 << num_bits;
+    ((value * num_intervals as f64) as u64).min(num_intervals - 1)
+}
+
+impl GeoHashGridAggregation {
+    pub(crate) fn grid(&self) -> GeoGrid {
+        GeoGrid::GeoHash {
+            precision: self.precision,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if !(1..=MAX_GEOHASH_PRECISION).contains(&self.precision) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "precision of geohash_grid aggregation must be within [1, \
+                 {MAX_GEOHASH_PRECISION}], got {}",
+                self.precision
+            )));
+        }
+        validate_grid_params(self.size, self.bounds.as_ref())
+    }
+}
+
+impl GeoTileGridAggregation {
+    pub(crate) fn grid(&self) -> GeoGrid {
+        GeoGrid::GeoTile {
+            precision: self.precision,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.precision > MAX_GEOTILE_PRECISION {
+            return Err(TantivyError::InvalidArgument(format!(
+                "precision of geotile_grid aggregation must be within [0, \
+                 {MAX_GEOTILE_PRECISION}], got {}",
+                self.precision
+            )));
+        }
+        validate_grid_params(self.size, self.bounds.as_ref())
+    }
+}
+
+fn validate_grid_params(size: u32, bounds: Option<&GeoBoundingBox>) -> crate::Result<()> {
+    if size == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "size in geo grid aggregation must be greater than 0".to_string(),
+        ));
+    }
+    if let Some(bounds) = bounds {
+        bounds.validate()?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+struct SegmentGeoGridBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+/// The collector counts the documents of each cell of the grid.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentGeoGridCollector {
+    grid: GeoGrid,
+    bounds: Option<GeoBoundingBox>,
+    lat_column_type: ColumnType,
+    lon_column_type: ColumnType,
+    buckets: FxHashMap<u64, SegmentGeoGridBucketEntry>,
+    sub_aggregation_blueprint: Option<Box<dyn SegmentAggregationCollector>>,
+    /// The cells of the current document.
+    doc_cells: Vec<u64>,
+    accessor_idx: usize,
+}
+
+impl SegmentAggregationCollector for SegmentGeoGridCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let bucket = self.into_intermediate_bucket_result(agg_with_accessor)?;
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+
+        Ok(())
+    }
+
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        let mem_pre = self.get_memory_consumption();
+
+        for &doc in docs {
+            let (lat_column, _) = &bucket_agg_accessor.accessors[0];
+            let (lon_column, _) = &bucket_agg_accessor.accessors[1];
+            self.doc_cells.clear();
+            for (lat, lon) in lat_column
+                .values_for_doc(doc)
+                .zip(lon_column.values_for_doc(doc))
+            {
+                let lat = f64_from_fastfield_u64(lat, &self.lat_column_type);
+                let lon = f64_from_fastfield_u64(lon, &self.lon_column_type);
+                if !((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)) {
+                    continue;
+                }
+                if self.bounds.is_some_and(|bounds| !bounds.contains(lat, lon)) {
+                    continue;
+                }
+                self.doc_cells.push(self.grid.cell(lat, lon));
+            }
+            // A document is counted once per cell.
+            self.doc_cells.sort_unstable();
+            self.doc_cells.dedup();
+            for cell in &self.doc_cells {
+                let bucket =
+                    self.buckets
+                        .entry(*cell)
+                        .or_insert_with(|| SegmentGeoGridBucketEntry {
+                            doc_count: 0,
+                            sub_aggregation: self.sub_aggregation_blueprint.clone(),
+                        });
+                bucket.doc_count += 1;
+                if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                    sub_aggregation.collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+                }
+            }
+        }
+
+        let mem_delta = self.get_memory_consumption() - mem_pre;
+        if mem_delta > 0 {
+            bucket_agg_accessor
+                .limits
+                .add_memory_consumed(mem_delta as u64)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        for bucket in self.buckets.values_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(sub_aggregation_accessor)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SegmentGeoGridCollector {
+    pub(crate) fn from_req_and_validate(
+        grid: GeoGrid,
+        bounds: Option<GeoBoundingBox>,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        (lat_column_type, lon_column_type): (ColumnType, ColumnType),
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        let sub_aggregation_blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+        Ok(SegmentGeoGridCollector {
+            grid,
+            bounds,
+            lat_column_type,
+            lon_column_type,
+            buckets: FxHashMap::default(),
+            sub_aggregation_blueprint,
+            doc_cells: Vec::new(),
+            accessor_idx,
+        })
+    }
+
+    fn get_memory_consumption(&self) -> usize {
+        std::mem::size_of::<Self>() + self.buckets.memory_consumption()
+    }
+
+    fn into_intermediate_bucket_result(
+        self,
+        agg_with_accessor: &AggregationWithAccessor,
+    ) -> crate::Result<IntermediateBucketResult> {
+        let mut entries = FxHashMap::default();
+        for (cell, bucket) in self.buckets {
+            let mut sub_aggregation_res = IntermediateAggregationResults::default();
+            if let Some(sub_aggregation) = bucket.sub_aggregation {
+                sub_aggregation.add_intermediate_aggregation_result(
+                    &agg_with_accessor.sub_aggregation,
+                    &mut sub_aggregation_res,
+                )?;
+            }
+            entries.insert(
+                cell,
+                IntermediateGeoGridBucketEntry {
+                    doc_count: bucket.doc_count,
+                    sub_aggregation: sub_aggregation_res,
+                },
+            );
+        }
+        Ok(IntermediateBucketResult::GeoGrid {
+            buckets: IntermediateGeoGridBucketResult { entries },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoGrid;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::TantivyDocument;
+
+    const PARIS: (f64, f64) = (48.8566, 2.3522);
+    const LONDON: (f64, f64) = (51.5074, -0.1278);
+    const NEW_YORK: (f64, f64) = (40.7128, -74.0060);
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let lat = schema_builder.add_f64_field("lat", FAST);
+        let lon = schema_builder.add_f64_field("lon", FAST);
+        let kind = schema_builder.add_text_field("kind", STRING | FAST);
+        let segments = [
+            vec![vec![PARIS], vec![PARIS], vec![LONDON]],
+            vec![vec![PARIS, LONDON], vec![NEW_YORK], vec![]],
+            vec![vec![PARIS, PARIS]],
+        ];
+        let segment_and_docs = segments
+            .into_iter()
+            .map(|docs| {
+                docs.into_iter()
+                    .map(|points| {
+                        let mut doc = TantivyDocument::default();
+                        for (point_lat, point_lon) in &points {
+                            doc.add_f64(lat, *point_lat);
+                            doc.add_f64(lon, *point_lon);
+                        }
+                        doc.add_text(kind, if points.len() > 1 { "multi" } else { "single" });
+                        doc
+                    })
+                    .collect()
+            })
+            .collect();
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn test_geo_grid_cells() {
+        let geohash = |precision, (lat, lon): (f64, f64)| {
+            let grid = GeoGrid::GeoHash { precision };
+            grid.cell_key(grid.cell(lat, lon))
+        };
+        assert_eq!(geohash(1, PARIS), "u");
+        assert_eq!(geohash(5, PARIS), "u09tv");
+        assert_eq!(geohash(12, PARIS), "u09tvw0f64r7");
+        assert_eq!(geohash(5, LONDON), "gcpvj");
+        assert_eq!(geohash(5, NEW_YORK), "dr5re");
+        assert_eq!(geohash(3, (-90.0, -180.0)), "000");
+        assert_eq!(geohash(3, (90.0, 180.0)), "zzz");
+
+        let geotile = |precision, (lat, lon): (f64, f64)| {
+            let grid = GeoGrid::GeoTile { precision };
+            grid.cell_key(grid.cell(lat, lon))
+        };
+        assert_eq!(geotile(0, PARIS), "0/0/0");
+        assert_eq!(geotile(7, PARIS), "7/64/44");
+        assert_eq!(geotile(7, NEW_YORK), "7/37/48");
+        assert_eq!(geotile(2, (90.0, 180.0)), "2/3/0");
+        assert_eq!(geotile(2, (-90.0, -180.0)), "2/0/3");
+    }
+
+    #[test]
+    fn geohash_grid_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+                "cities": {
+                    "geohash_grid": { "lat_field": "lat", "lon_field": "lon", "precision": 5 }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["cities"],
+                serde_json::json!({
+                    "buckets": [
+                        { "key": "u09tv", "doc_count": 4 },
+                        { "key": "gcpvj", "doc_count": 2 },
+                        { "key": "dr5re", "doc_count": 1 },
+                    ]
+                })
+            );
+
+            // The bounds filter the points, and the size the buckets.
+            let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+                "cities": {
+                    "geohash_grid": {
+                        "lat_field": "lat",
+                        "lon_field": "lon",
+                        "precision": 1,
+                        "size": 1,
+                        "bounds": { "top": 60.0, "left": -10.0, "bottom": 40.0, "right": 10.0 }
+                    },
+                    "aggs": { "kinds": { "terms": { "field": "kind" } } }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(res["cities"]["buckets"][0]["key"], "u");
+            assert_eq!(res["cities"]["buckets"][0]["doc_count"], 4);
+            assert_eq!(
+                res["cities"]["buckets"][0]["kinds"]["buckets"],
+                serde_json::json!([
+                    { "key": "multi", "doc_count": 2 },
+                    { "key": "single", "doc_count": 2 },
+                ])
+            );
+            assert_eq!(res["cities"]["buckets"].as_array().unwrap().len(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn geotile_grid_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "cities": {
+                "geotile_grid": { "lat_field": "lat", "lon_field": "lon" }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["cities"],
+            serde_json::json!({
+                "buckets": [
+                    { "key": "7/64/44", "doc_count": 4 },
+                    { "key": "7/63/42", "doc_count": 2 },
+                    { "key": "7/37/48", "doc_count": 1 },
+                ]
+            })
+        );
+
+        // The bounds cross the antimeridian.
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "cities": {
+                "geotile_grid": {
+                    "lat_field": "lat",
+                    "lon_field": "lon",
+                    "precision": 0,
+                    "bounds": { "top": 90.0, "left": 0.0, "bottom": -90.0, "right": -100.0 }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["cities"],
+            serde_json::json!({ "buckets": [{ "key": "0/0/0", "doc_count": 4 }] })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn geo_grid_invalid_request() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "cities": {
+                "geohash_grid": { "lat_field": "lat", "lon_field": "lon", "precision": 13 }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'precision of geohash_grid aggregation must be \
+             within [1, 12], got 13'"
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(serde_json::json!({
+            "cities": {
+                "geotile_grid": {
+                    "lat_field": "lat",
+                    "lon_field": "lon",
+                    "bounds": { "top": 10.0, "left": 0.0, "bottom": 20.0, "right": 10.0 }
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert!(err.to_string().contains("below the bottom"));
+        Ok(())
+    }
+}
@@ -0,0 +1,695 @@
+use columnar::MonotonicallyMappableToU64;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use super::date_histogram::{NS_PER_DAY, NS_PER_HOUR, NS_PER_MINUTE, NS_PER_SECOND};
+use super::time_zone::{civil_from_days, days_from_civil};
+use super::SegmentHistogramBucketEntry;
+use crate::aggregation::agg_limits::MemoryConsumption;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults,
+    IntermediateAutoDateHistogramBucketResult, IntermediateBucketResult,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+use crate::aggregation::AggregationError;
+use crate::TantivyError;
+
+/// AutoDateHistogramAggregation is similar to `DateHistogramAggregationReq`, but the interval is
+/// picked automatically, so that the matching documents are put into at most `buckets` buckets.
+///
+/// The interval is the shortest one of the following intervals which produces at most `buckets`
+/// buckets between the first and the last date:
+/// * `1s`, `5s`, `10s`, `30s`
+/// * `1m`, `5m`, `10m`, `30m`
+/// * `1h`, `3h`, `12h`
+/// * `1d`, `7d`
+/// * `1M`, `3M`
+/// * `1y`, `5y`, `10y`, `20y`, `50y`, `100y`
+///
+/// The empty buckets between the first and the last bucket are returned, and the picked interval
+/// is returned along with the buckets.
+///
+/// The range of the dates is not known upfront: the dates are collected in buckets of a calendar
+/// unit, which is increased while collecting when there are too many buckets for the unit.
+///
+/// # Limitations/Compatibility
+/// The buckets are computed in UTC, the `time_zone` parameter is unsupported.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_over_time": {
+///         "auto_date_histogram": {
+///             "field": "date",
+///             "buckets": 10
+///         }
+///     }
+/// }
+/// ```
+///
+/// Response
+/// ```json
+/// {
+///     "sales_over_time": {
+///         "buckets": [
+///             { "key_as_string": "2015-01-01T00:00:00Z", "key": 1420070400000.0, "doc_count": 3 },
+///             { "key_as_string": "2015-02-01T00:00:00Z", "key": 1422748800000.0, "doc_count": 2 }
+///         ],
+///         "interval": "1M"
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AutoDateHistogramAggregation {
+    /// The field to aggregate on.
+    pub field: String,
+    /// The maximum number of buckets to return. Defaults to 10.
+    #[serde(default = "default_buckets")]
+    pub buckets: u32,
+    /// The shortest calendar unit of the interval, one of `second`, `minute`, `hour`, `day`,
+    /// `month` and `year`. Defaults to `second`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub minimum_interval: Option<String>,
+}
+
+fn default_buckets() -> u32 {
+    10
+}
+
+impl AutoDateHistogramAggregation {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.buckets == 0 {
+            return Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(
+                    "buckets of auto_date_histogram aggregation must be greater than 0".to_string(),
+                ),
+            ));
+        }
+        self.minimum_unit()?;
+        Ok(())
+    }
+
+    /// Returns the shortest unit of the buckets.
+    pub(crate) fn minimum_unit(&self) -> crate::Result<RoundingUnit> {
+        let Some(minimum_interval) = self.minimum_interval.as_ref() else {
+            return Ok(RoundingUnit::Second);
+        };
+        let unit = match minimum_interval.as_str() {
+            "second" => RoundingUnit::Second,
+            "minute" => RoundingUnit::Minute,
+            "hour" => RoundingUnit::Hour,
+            "day" => RoundingUnit::Day,
+            "month" => RoundingUnit::Month,
+            "year" => RoundingUnit::Year,
+            _ => {
+                return Err(TantivyError::AggregationError(
+                    AggregationError::InvalidRequest(format!(
+                        "minimum_interval {minimum_interval:?} of auto_date_histogram aggregation \
+                         must be one of second, minute, hour, day, month and year"
+                    )),
+                ))
+            }
+        };
+        Ok(unit)
+    }
+
+    /// Returns the interval of the buckets of the dates between `min` and `max`, the dates being
+    /// at least rounded to `unit`.
+    pub(crate) fn pick_interval(
+        &self,
+        unit: RoundingUnit,
+        min: i64,
+        max: i64,
+    ) -> (RoundingUnit, i64) {
+        let mut intervals = RoundingUnit::ALL
+            .iter()
+            .filter(|candidate| **candidate >= unit)
+            .flat_map(|unit| {
+                unit.inner_intervals()
+                    .iter()
+                    .map(move |multiple| (*unit, *multiple))
+            });
+        let mut interval = intervals.next().expect("there is at least one interval");
+        while interval.0.num_buckets(interval.1, min, max) > self.buckets as i64 {
+            let Some(next_interval) = intervals.next() else {
+                break;
+            };
+            interval = next_interval;
+        }
+        interval
+    }
+}
+
+/// The calendar units in which the dates of an auto_date_histogram are rounded, from the
+/// shortest to the longest.
+///
+/// Each unit is a multiple of the previous one in UTC, so that the dates rounded to a unit can be
+/// rounded to a longer unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) enum RoundingUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+impl RoundingUnit {
+    const ALL: [RoundingUnit; 6] = [
+        RoundingUnit::Second,
+        RoundingUnit::Minute,
+        RoundingUnit::Hour,
+        RoundingUnit::Day,
+        RoundingUnit::Month,
+        RoundingUnit::Year,
+    ];
+
+    /// Returns the number of units of the intervals based on the unit.
+    fn inner_intervals(self) -> &'static [i64] {
+        match self {
+            RoundingUnit::Second | RoundingUnit::Minute => &[1, 5, 10, 30],
+            RoundingUnit::Hour => &[1, 3, 12],
+            RoundingUnit::Day => &[1, 7],
+            RoundingUnit::Month => &[1, 3],
+            RoundingUnit::Year => &[1, 5, 10, 20, 50, 100],
+        }
+    }
+
+    fn next(self) -> Option<RoundingUnit> {
+        let pos = RoundingUnit::ALL
+            .iter()
+            .position(|unit| *unit == self)
+            .expect("unit is in ALL");
+        RoundingUnit::ALL.get(pos + 1).copied()
+    }
+
+    /// Returns the maximum number of buckets of the unit for which the unit may still produce at
+    /// most `target_buckets` buckets.
+    fn max_buckets(self, target_buckets: u32) -> usize {
+        let longest_interval = *self.inner_intervals().last().expect("unit has intervals");
+        target_buckets as usize * longest_interval as usize
+    }
+
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            RoundingUnit::Second => "s",
+            RoundingUnit::Minute => "m",
+            RoundingUnit::Hour => "h",
+            RoundingUnit::Day => "d",
+            RoundingUnit::Month => "M",
+            RoundingUnit::Year => "y",
+        }
+    }
+
+    /// Returns the index of the unit of a date in nanoseconds.
+    ///
+    /// The fixed duration units are counted from the unix epoch, the months from the year 0 and
+    /// the years are the years of the dates.
+    fn index(self, date: i64) -> i64 {
+        let year_and_month = || {
+            let (year, month, _) = civil_from_days(date.div_euclid(NS_PER_DAY));
+            (year, month as i64)
+        };
+        match self {
+            RoundingUnit::Second => date.div_euclid(NS_PER_SECOND),
+            RoundingUnit::Minute => date.div_euclid(NS_PER_MINUTE),
+            RoundingUnit::Hour => date.div_euclid(NS_PER_HOUR),
+            RoundingUnit::Day => date.div_euclid(NS_PER_DAY),
+            RoundingUnit::Month => {
+                let (year, month) = year_and_month();
+                year * 12 + month - 1
+            }
+            RoundingUnit::Year => year_and_month().0,
+        }
+    }
+
+    /// Returns the start in nanoseconds of the unit with the given index.
+    fn start(self, index: i64) -> i64 {
+        match self {
+            RoundingUnit::Second => index.saturating_mul(NS_PER_SECOND),
+            RoundingUnit::Minute => index.saturating_mul(NS_PER_MINUTE),
+            RoundingUnit::Hour => index.saturating_mul(NS_PER_HOUR),
+            RoundingUnit::Day => index.saturating_mul(NS_PER_DAY),
+            RoundingUnit::Month => {
+                let month = index.rem_euclid(12) as u32 + 1;
+                days_from_civil(index.div_euclid(12), month, 1).saturating_mul(NS_PER_DAY)
+            }
+            RoundingUnit::Year => days_from_civil(index, 1, 1).saturating_mul(NS_PER_DAY),
+        }
+    }
+
+    /// Rounds a date in nanoseconds down to the start of its unit.
+    pub(crate) fn round(self, date: i64) -> i64 {
+        self.start(self.index(date))
+    }
+
+    /// Returns the position of the bucket of `multiple` units of the date.
+    pub(crate) fn bucket_pos(self, multiple: i64, date: i64) -> i64 {
+        self.index(date).div_euclid(multiple)
+    }
+
+    /// Returns the start of the bucket of `multiple` units at the given position.
+    pub(crate) fn bucket_start(self, multiple: i64, bucket_pos: i64) -> i64 {
+        self.start(bucket_pos.saturating_mul(multiple))
+    }
+
+    /// Returns the number of buckets of `multiple` units from the bucket of `min` to the bucket of
+    /// `max`.
+    fn num_buckets(self, multiple: i64, min: i64, max: i64) -> i64 {
+        self.bucket_pos(multiple, max) - self.bucket_pos(multiple, min) + 1
+    }
+}
+
+/// The collector puts the dates into buckets of a calendar unit, which is increased whenever
+/// there are too many buckets to fit the requested number of buckets.
+#[derive(Clone, Debug)]
+pub struct SegmentAutoDateHistogramCollector {
+    unit: RoundingUnit,
+    target_buckets: u32,
+    /// The buckets by their start in nanoseconds.
+    buckets: FxHashMap<i64, SegmentHistogramBucketEntry>,
+    sub_aggregations: FxHashMap<i64, Box<dyn SegmentAggregationCollector>>,
+    sub_aggregation_blueprint: Option<Box<dyn SegmentAggregationCollector>>,
+    /// The buckets collected before the last change of unit, rounded to the current unit.
+    previous_buckets: IntermediateAutoDateHistogramBucketResult,
+    accessor_idx: usize,
+}
+
+impl SegmentAggregationCollector for SegmentAutoDateHistogramCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let mut collector = *self;
+        let buckets = collector.take_intermediate_buckets(&agg_with_accessor.sub_aggregation)?;
+        results.push(
+            name,
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::AutoDateHistogram {
+                buckets,
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: crate::DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[crate::DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let bucket_agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+
+        let mem_pre = self.get_memory_consumption();
+
+        bucket_agg_accessor
+            .column_block_accessor
+            .fetch_block(docs, &bucket_agg_accessor.accessor);
+
+        for (doc, raw_val) in bucket_agg_accessor
+            .column_block_accessor
+            .iter_docid_vals(docs, &bucket_agg_accessor.accessor)
+        {
+            let key = self.unit.round(i64::from_u64(raw_val));
+            let bucket = self
+                .buckets
+                .entry(key)
+                .or_insert_with(|| SegmentHistogramBucketEntry {
+                    key: key as f64,
+                    doc_count: 0,
+                });
+            bucket.doc_count += 1;
+            if let Some(sub_aggregation_blueprint) = self.sub_aggregation_blueprint.as_mut() {
+                self.sub_aggregations
+                    .entry(key)
+                    .or_insert_with(|| sub_aggregation_blueprint.clone())
+                    .collect(doc, &mut bucket_agg_accessor.sub_aggregation)?;
+            }
+        }
+
+        let mem_delta = self.get_memory_consumption() as i64 - mem_pre as i64;
+        if mem_delta > 0 {
+            bucket_agg_accessor
+                .limits
+                .add_memory_consumed(mem_delta as u64)?;
+        }
+
+        if self.buckets.len() > self.unit.max_buckets(self.target_buckets) {
+            self.increase_unit(&mut bucket_agg_accessor.sub_aggregation)?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let sub_aggregation_accessor =
+            &mut agg_with_accessor.aggs.values[self.accessor_idx].sub_aggregation;
+
+        for sub_aggregation in self.sub_aggregations.values_mut() {
+            sub_aggregation.flush(sub_aggregation_accessor)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SegmentAutoDateHistogramCollector {
+    pub(crate) fn from_req_and_validate(
+        req: &AutoDateHistogramAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        req.validate()?;
+        let sub_aggregation_blueprint = if sub_aggregation.is_empty() {
+            None
+        } else {
+            Some(build_segment_agg_collector(sub_aggregation)?)
+        };
+        let unit = req.minimum_unit()?;
+        Ok(Self {
+            unit,
+            target_buckets: req.buckets,
+            buckets: Default::default(),
+            sub_aggregations: Default::default(),
+            sub_aggregation_blueprint,
+            previous_buckets: IntermediateAutoDateHistogramBucketResult::empty(unit),
+            accessor_idx,
+        })
+    }
+
+    fn get_memory_consumption(&self) -> usize {
+        let self_mem = std::mem::size_of::<Self>();
+        let sub_aggs_mem = self.sub_aggregations.memory_consumption();
+        let buckets_mem = self.buckets.memory_consumption();
+        self_mem + sub_aggs_mem + buckets_mem
+    }
+
+    /// Converts the buckets into intermediate buckets, along with the buckets collected before
+    /// the last change of unit.
+    fn take_intermediate_buckets(
+        &mut self,
+        sub_aggregation: &AggregationsWithAccessor,
+    ) -> crate::Result<IntermediateAutoDateHistogramBucketResult> {
+        let mut buckets = Vec::with_capacity(self.buckets.len());
+        for (key, bucket) in self.buckets.drain() {
+            buckets.push(bucket.into_intermediate_bucket_entry(
+                self.sub_aggregations.remove(&key),
+                sub_aggregation,
+            )?);
+        }
+        buckets.sort_unstable_by(|left, right| left.key.total_cmp(&right.key));
+        let mut result = std::mem::replace(
+            &mut self.previous_buckets,
+            IntermediateAutoDateHistogramBucketResult::empty(self.unit),
+        );
+        result.merge_fruits(IntermediateAutoDateHistogramBucketResult {
+            unit: self.unit,
+            buckets,
+        })?;
+        Ok(result)
+    }
+
+    /// Increases the unit until there are few enough buckets for the unit.
+    ///
+    /// The sub aggregations of the buckets can't be merged while collecting, so the buckets are
+    /// converted into intermediate buckets first.
+    fn increase_unit(
+        &mut self,
+        sub_aggregation: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        for sub_aggregation_collector in self.sub_aggregations.values_mut() {
+            sub_aggregation_collector.flush(sub_aggregation)?;
+        }
+        let mut buckets = self.take_intermediate_buckets(sub_aggregation)?;
+        while buckets.buckets.len() > buckets.unit.max_buckets(self.target_buckets) {
+            let Some(next_unit) = buckets.unit.next() else {
+                break;
+            };
+            buckets.round_to_unit(next_unit)?;
+        }
+        self.unit = buckets.unit;
+        self.previous_buckets = buckets;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+
+    use super::super::date_histogram::tests::get_test_index_from_docs;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::exec_request;
+    use crate::Index;
+
+    fn exec(index: &Index, agg_req: Value) -> crate::Result<Value> {
+        let agg_req: Aggregations = serde_json::from_value(agg_req).unwrap();
+        exec_request(agg_req, index)
+    }
+
+    #[test]
+    fn auto_date_histogram_test_single_segment() -> crate::Result<()> {
+        auto_date_histogram_test(true)
+    }
+
+    #[test]
+    fn auto_date_histogram_test_multi_segment() -> crate::Result<()> {
+        auto_date_histogram_test(false)
+    }
+
+    fn auto_date_histogram_test(merge_segments: bool) -> crate::Result<()> {
+        let docs = vec![
+            vec![
+                r#"{ "date": "2015-01-01T00:00:00Z" }"#,
+                r#"{ "date": "2015-01-01T05:30:00Z" }"#,
+            ],
+            vec![r#"{ "date": "2015-01-01T11:00:00Z" }"#],
+            vec![r#"{ "date": "2015-01-02T00:10:00Z" }"#],
+        ];
+        let index = get_test_index_from_docs(merge_segments, &docs)?;
+
+        // 1h and 3h intervals produce more than 5 buckets.
+        let res = exec(
+            &index,
+            json!({ "histo": { "auto_date_histogram": { "field": "date", "buckets": 5 } } }),
+        )?;
+        assert_eq!(
+            res["histo"],
+            json!({
+                "buckets": [
+                    { "key_as_string": "2015-01-01T00:00:00Z", "key": 1420070400000.0, "doc_count": 3 },
+                    { "key_as_string": "2015-01-01T12:00:00Z", "key": 1420113600000.0, "doc_count": 0 },
+                    { "key_as_string": "2015-01-02T00:00:00Z", "key": 1420156800000.0, "doc_count": 1 }
+                ],
+                "interval": "12h"
+            })
+        );
+
+        let res = exec(
+            &index,
+            json!({ "histo": { "auto_date_histogram": { "field": "date", "buckets": 30 } } }),
+        )?;
+        assert_eq!(res["histo"]["interval"], "1h");
+        assert_eq!(res["histo"]["buckets"].as_array().unwrap().len(), 25);
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_date_histogram_calendar_test_single_segment() -> crate::Result<()> {
+        auto_date_histogram_calendar_test(true)
+    }
+
+    #[test]
+    fn auto_date_histogram_calendar_test_multi_segment() -> crate::Result<()> {
+        auto_date_histogram_calendar_test(false)
+    }
+
+    fn auto_date_histogram_calendar_test(merge_segments: bool) -> crate::Result<()> {
+        let docs = vec![
+            vec![r#"{ "date": "2015-01-15T10:00:00Z" }"#],
+            vec![
+                r#"{ "date": "2015-03-03T00:00:00Z" }"#,
+                r#"{ "date": "2016-02-10T00:00:00Z" }"#,
+            ],
+        ];
+        let index = get_test_index_from_docs(merge_segments, &docs)?;
+
+        // The monthly buckets are too many, the buckets are quarters.
+        let res = exec(
+            &index,
+            json!({
+                "histo": {
+                    "auto_date_histogram": { "field": "date", "buckets": 10 },
+                    "aggs": { "total": { "cumulative_sum": { "buckets_path": "_count" } } }
+                }
+            }),
+        )?;
+        assert_eq!(res["histo"]["interval"], "3M");
+        let buckets: Vec<(&str, u64, f64)> = res["histo"]["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket["key_as_string"].as_str().unwrap(),
+                    bucket["doc_count"].as_u64().unwrap(),
+                    bucket["total"]["value"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                ("2015-01-01T00:00:00Z", 2, 2.0),
+                ("2015-04-01T00:00:00Z", 0, 2.0),
+                ("2015-07-01T00:00:00Z", 0, 2.0),
+                ("2015-10-01T00:00:00Z", 0, 2.0),
+                ("2016-01-01T00:00:00Z", 1, 3.0),
+            ]
+        );
+
+        let res = exec(
+            &index,
+            json!({ "histo": { "auto_date_histogram": { "field": "date", "buckets": 1 } } }),
+        )?;
+        assert_eq!(
+            res["histo"],
+            json!({
+                "buckets": [
+                    { "key_as_string": "2015-01-01T00:00:00Z", "key": 1420070400000.0, "doc_count": 3 }
+                ],
+                "interval": "5y"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_date_histogram_increase_unit_test_single_segment() -> crate::Result<()> {
+        auto_date_histogram_increase_unit_test(true)
+    }
+
+    #[test]
+    fn auto_date_histogram_increase_unit_test_multi_segment() -> crate::Result<()> {
+        auto_date_histogram_increase_unit_test(false)
+    }
+
+    fn auto_date_histogram_increase_unit_test(merge_segments: bool) -> crate::Result<()> {
+        // One document per minute, the 100 seconds are too many to produce 2 buckets. The unit
+        // of the first segment is increased while collecting, not the one of the second segment.
+        let docs: Vec<String> = (0..100)
+            .map(|minute| {
+                format!(
+                    r#"{{ "date": "2015-01-01T{:02}:{:02}:00Z", "mixed": {{ "minute": {minute} }} }}"#,
+                    minute / 60,
+                    minute % 60
+                )
+            })
+            .collect();
+        let segment_and_docs: Vec<Vec<&str>> = docs
+            .chunks(70)
+            .map(|docs| docs.iter().map(String::as_str).collect())
+            .collect();
+        let index = get_test_index_from_docs(merge_segments, &segment_and_docs)?;
+
+        let res = exec(
+            &index,
+            json!({
+                "histo": {
+                    "auto_date_histogram": { "field": "date", "buckets": 2 },
+                    "aggs": { "sum_minutes": { "sum": { "field": "mixed.minute" } } }
+                }
+            }),
+        )?;
+        assert_eq!(
+            res["histo"],
+            json!({
+                "buckets": [
+                    {
+                        "key_as_string": "2015-01-01T00:00:00Z",
+                        "key": 1420070400000.0,
+                        "doc_count": 60,
+                        "sum_minutes": { "value": 1770.0 }
+                    },
+                    {
+                        "key_as_string": "2015-01-01T01:00:00Z",
+                        "key": 1420074000000.0,
+                        "doc_count": 40,
+                        "sum_minutes": { "value": 3180.0 }
+                    }
+                ],
+                "interval": "1h"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_date_histogram_empty_test() -> crate::Result<()> {
+        let index = get_test_index_from_docs(false, &[vec![r#"{ "text": "aaa" }"#]])?;
+
+        let res = exec(
+            &index,
+            json!({ "histo": { "auto_date_histogram": { "field": "date" } } }),
+        )?;
+        assert_eq!(res["histo"], json!({ "buckets": [], "interval": "1s" }));
+
+        let res = exec(
+            &index,
+            json!({
+                "histo": { "auto_date_histogram": { "field": "date", "minimum_interval": "day" } }
+            }),
+        )?;
+        assert_eq!(res["histo"], json!({ "buckets": [], "interval": "1d" }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_date_histogram_invalid_request_test() -> crate::Result<()> {
+        let index =
+            get_test_index_from_docs(false, &[vec![r#"{ "date": "2015-01-01T00:00:00Z" }"#]])?;
+
+        let err = exec(
+            &index,
+            json!({ "histo": { "auto_date_histogram": { "field": "date", "buckets": 0 } } }),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "InvalidRequest: \"buckets of auto_date_histogram aggregation must be greater than 0\""
+        );
+
+        let err = exec(
+            &index,
+            json!({
+                "histo": { "auto_date_histogram": { "field": "date", "minimum_interval": "week" } }
+            }),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("minimum_interval \\\"week\\\""));
+
+        Ok(())
+    }
+}
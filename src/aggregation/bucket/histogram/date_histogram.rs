@@ -1,20 +1,23 @@
 use serde::{Deserialize, Serialize};
 
+use super::time_zone::{civil_from_days, days_from_civil, TimeZone};
 use super::{HistogramAggregation, HistogramBounds};
 use crate::aggregation::*;
 
 /// DateHistogramAggregation is similar to `HistogramAggregation`, but it can only be used with date
 /// type.
 ///
-/// Currently only **fixed time** intervals are supported. Calendar-aware time intervals are not
-/// supported.
+/// The buckets are either **fixed time** intervals, or **calendar-aware** intervals like months,
+/// whose duration varies. The bucket boundaries can be computed in the local time of a
+/// `time_zone`, in which case they follow the daylight saving time transitions.
 ///
 /// Like the histogram, values are rounded down into the closest bucket.
 ///
 /// For this calculation all fastfield values are converted to f64.
 ///
 /// # Limitations/Compatibility
-/// Only fixed time intervals are supported.
+/// Only single calendar units are supported as calendar intervals, e.g. `1M` but not `2M`.
+/// Time zone names are resolved with the zoneinfo files of the system.
 ///
 /// # JSON Format
 /// ```json
@@ -28,6 +31,18 @@ use crate::aggregation::*;
 /// }
 /// ```
 ///
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": {
+///             "field": "date",
+///             "calendar_interval": "month",
+///             "time_zone": "Europe/Paris"
+///         }
+///     }
+/// }
+/// ```
+///
 /// Response
 /// See [`BucketEntry`](crate::aggregation::agg_result::BucketEntry)
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -35,8 +50,19 @@ pub struct DateHistogramAggregationReq {
     #[doc(hidden)]
     /// Only for validation
     pub interval: Option<String>,
-    #[doc(hidden)]
-    /// Only for validation
+    /// The calendar-aware interval to chunk your data range. Each bucket spans one calendar unit,
+    /// starting at the beginning of the unit in the `time_zone`.
+    ///
+    /// The accepted units for calendar intervals are:
+    /// * `minute`, `1m`
+    /// * `hour`, `1h`
+    /// * `day`, `1d`
+    /// * `week`, `1w`: weeks start on Monday.
+    /// * `month`, `1M`
+    /// * `quarter`, `1q`
+    /// * `year`, `1y`
+    ///
+    /// Exactly one of `fixed_interval` and `calendar_interval` has to be set.
     pub calendar_interval: Option<String>,
     /// The field to aggregate on.
     pub field: String,
@@ -50,8 +76,8 @@ pub struct DateHistogramAggregationReq {
     /// never deviate, regardless of where they fall on the calendar. One second is always
     /// composed of 1000ms. This allows fixed intervals to be specified in any multiple of the
     /// supported units. However, it means fixed intervals cannot express other units such as
-    /// months, since the duration of a month is not a fixed quantity. Attempting to specify a
-    /// calendar interval like month or quarter will return an Error.
+    /// months, since the duration of a month is not a fixed quantity. Use `calendar_interval` for
+    /// those.
     ///
     /// The accepted units for fixed intervals are:
     /// * `ms`: milliseconds
@@ -63,7 +89,7 @@ pub struct DateHistogramAggregationReq {
     /// Fractional time values are not supported, but you can address this by shifting to another
     /// time unit (e.g., `1.5h` could instead be specified as `90m`).
     ///
-    /// `Option` for validation, the parameter is not optional
+    /// Exactly one of `fixed_interval` and `calendar_interval` has to be set.
     pub fixed_interval: Option<String>,
     /// Intervals implicitly defines an absolute grid of buckets `[interval * k, interval * (k +
     /// 1))`.
@@ -74,7 +100,19 @@ pub struct DateHistogramAggregationReq {
     ///
     /// The `offset` parameter is has the same syntax as the `fixed_interval` parameter, but
     /// also allows for negative values.
+    ///
+    /// With a `calendar_interval`, the offset shifts the start of each calendar unit, e.g. an
+    /// offset of `6h` makes daily buckets start at 6 am.
     pub offset: Option<String>,
+    /// The time zone in which the bucket boundaries are computed, `UTC` by default.
+    ///
+    /// Accepted values are `UTC`, fixed offsets like `+01:00` or `-0530`, and the names of the
+    /// IANA time zone database like `America/New_York`. With a named time zone, the buckets follow
+    /// the daylight saving time transitions, e.g. daily buckets start at local midnight, and may
+    /// span 23 or 25 hours.
+    ///
+    /// The keys of the buckets remain timestamps in milliseconds.
+    pub time_zone: Option<String>,
     /// The minimum number of documents in a bucket to be returned. Defaults to 0.
     pub min_doc_count: Option<u64>,
     /// Limits the data range to `[min, max]` closed interval.
@@ -119,9 +157,16 @@ pub struct DateHistogramAggregationReq {
 impl DateHistogramAggregationReq {
     pub(crate) fn to_histogram_req(&self) -> crate::Result<HistogramAggregation> {
         self.validate()?;
+        let interval = if let Some(calendar_interval) = self.calendar_interval.as_ref() {
+            // Only used for validation, the calendar-aware buckets are computed by the
+            // `DateRounding`.
+            CalendarUnit::parse(calendar_interval)?.approximate_duration_ms()
+        } else {
+            parse_into_milliseconds(self.fixed_interval.as_ref().unwrap())?
+        };
         Ok(HistogramAggregation {
             field: self.field.to_string(),
-            interval: parse_into_milliseconds(self.fixed_interval.as_ref().unwrap())? as f64,
+            interval: interval as f64,
             offset: self
                 .offset
                 .as_ref()
@@ -136,17 +181,27 @@ impl DateHistogramAggregationReq {
         })
     }
 
+    /// Returns the rounding of the dates into buckets, or `None` if the buckets are fixed
+    /// intervals in UTC, which are computed like the buckets of a `HistogramAggregation`.
+    pub(crate) fn to_date_rounding(&self) -> crate::Result<Option<DateRounding>> {
+        self.validate()?;
+        if self.calendar_interval.is_none() && self.time_zone.is_none() {
+            return Ok(None);
+        }
+        DateRounding::new(
+            self.fixed_interval.as_deref(),
+            self.calendar_interval.as_deref(),
+            self.offset.as_deref(),
+            self.time_zone.as_deref(),
+        )
+        .map(Some)
+    }
+
     fn validate(&self) -> crate::Result<()> {
         if let Some(interval) = self.interval.as_ref() {
             return Err(crate::TantivyError::InvalidArgument(format!(
-                "`interval` parameter {interval:?} in date histogram is unsupported, only \
-                 `fixed_interval` is supported"
-            )));
-        }
-        if let Some(interval) = self.calendar_interval.as_ref() {
-            return Err(crate::TantivyError::InvalidArgument(format!(
-                "`calendar_interval` parameter {interval:?} in date histogram is unsupported, \
-                 only `fixed_interval` is supported"
+                "`interval` parameter {interval:?} in date histogram is unsupported, use \
+                 `fixed_interval` or `calendar_interval` instead"
             )));
         }
         if self.format.is_some() {
@@ -155,18 +210,227 @@ impl DateHistogramAggregationReq {
             ));
         }
 
-        if self.fixed_interval.is_none() {
-            return Err(crate::TantivyError::InvalidArgument(
-                "fixed_interval in date histogram is missing".to_string(),
-            ));
+        match (&self.fixed_interval, &self.calendar_interval) {
+            (Some(fixed_interval), None) => {
+                parse_into_milliseconds(fixed_interval)?;
+            }
+            (None, Some(calendar_interval)) => {
+                CalendarUnit::parse(calendar_interval)?;
+            }
+            (Some(_), Some(_)) => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    "fixed_interval and calendar_interval in date histogram cannot be set at the \
+                     same time"
+                        .to_string(),
+                ));
+            }
+            (None, None) => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    "fixed_interval or calendar_interval in date histogram is missing".to_string(),
+                ));
+            }
+        }
+        if let Some(time_zone) = self.time_zone.as_ref() {
+            TimeZone::parse(time_zone)?;
         }
-
-        parse_into_milliseconds(self.fixed_interval.as_ref().unwrap())?;
 
         Ok(())
     }
 }
 
+pub(super) const NS_PER_SECOND: i64 = 1_000_000_000;
+pub(super) const NS_PER_MINUTE: i64 = 60 * NS_PER_SECOND;
+pub(super) const NS_PER_HOUR: i64 = 60 * NS_PER_MINUTE;
+pub(super) const NS_PER_DAY: i64 = 24 * NS_PER_HOUR;
+const NS_PER_WEEK: i64 = 7 * NS_PER_DAY;
+
+/// The units of a calendar interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CalendarUnit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl CalendarUnit {
+    fn parse(calendar_interval: &str) -> Result<CalendarUnit, AggregationError> {
+        let unit = match calendar_interval {
+            "minute" | "1m" => CalendarUnit::Minute,
+            "hour" | "1h" => CalendarUnit::Hour,
+            "day" | "1d" => CalendarUnit::Day,
+            "week" | "1w" => CalendarUnit::Week,
+            "month" | "1M" => CalendarUnit::Month,
+            "quarter" | "1q" => CalendarUnit::Quarter,
+            "year" | "1y" => CalendarUnit::Year,
+            _ => {
+                return Err(DateHistogramParseError::CalendarUnitNotRecognized(
+                    calendar_interval.to_string(),
+                )
+                .into())
+            }
+        };
+        Ok(unit)
+    }
+
+    fn approximate_duration_ms(self) -> i64 {
+        let duration_ns = match self {
+            CalendarUnit::Minute => NS_PER_MINUTE,
+            CalendarUnit::Hour => NS_PER_HOUR,
+            CalendarUnit::Day => NS_PER_DAY,
+            CalendarUnit::Week => NS_PER_WEEK,
+            CalendarUnit::Month => 30 * NS_PER_DAY,
+            CalendarUnit::Quarter => 91 * NS_PER_DAY,
+            CalendarUnit::Year => 365 * NS_PER_DAY,
+        };
+        duration_ns / 1_000_000
+    }
+
+    /// Rounds a local time in nanoseconds down to the start of its calendar unit.
+    fn round_down(self, local: i64) -> i64 {
+        let round_down_to_months = |num_months: u32| {
+            let (year, month, _) = civil_from_days(local.div_euclid(NS_PER_DAY));
+            let month = month - (month - 1) % num_months;
+            days_from_civil(year, month, 1) * NS_PER_DAY
+        };
+        match self {
+            CalendarUnit::Minute => round_down_to_multiple(local, NS_PER_MINUTE),
+            CalendarUnit::Hour => round_down_to_multiple(local, NS_PER_HOUR),
+            CalendarUnit::Day => round_down_to_multiple(local, NS_PER_DAY),
+            // 1970-01-05 is a Monday.
+            CalendarUnit::Week => {
+                round_down_to_multiple(local - 4 * NS_PER_DAY, NS_PER_WEEK) + 4 * NS_PER_DAY
+            }
+            CalendarUnit::Month => round_down_to_months(1),
+            CalendarUnit::Quarter => round_down_to_months(3),
+            CalendarUnit::Year => round_down_to_months(12),
+        }
+    }
+
+    /// Adds one calendar unit to a local time rounded down by `round_down`.
+    fn add_one(self, local: i64) -> i64 {
+        let add_months = |num_months: i64| {
+            let (year, month, _) = civil_from_days(local.div_euclid(NS_PER_DAY));
+            let month_index = year * 12 + month as i64 - 1 + num_months;
+            let month = month_index.rem_euclid(12) as u32 + 1;
+            days_from_civil(month_index.div_euclid(12), month, 1) * NS_PER_DAY
+        };
+        match self {
+            CalendarUnit::Minute => local + NS_PER_MINUTE,
+            CalendarUnit::Hour => local + NS_PER_HOUR,
+            CalendarUnit::Day => local + NS_PER_DAY,
+            CalendarUnit::Week => local + NS_PER_WEEK,
+            CalendarUnit::Month => add_months(1),
+            CalendarUnit::Quarter => add_months(3),
+            CalendarUnit::Year => add_months(12),
+        }
+    }
+}
+
+fn round_down_to_multiple(value: i64, interval: i64) -> i64 {
+    value.div_euclid(interval) * interval
+}
+
+#[derive(Clone, Copy, Debug)]
+enum DateInterval {
+    /// A fixed interval in nanoseconds.
+    Fixed(i64),
+    Calendar(CalendarUnit),
+}
+
+/// Rounds dates down to the start of their bucket, the buckets being computed in the local time
+/// of a time zone.
+///
+/// All of the dates are in nanoseconds since the unix epoch.
+#[derive(Clone, Debug)]
+pub(crate) struct DateRounding {
+    interval: DateInterval,
+    /// The offset of the buckets in nanoseconds.
+    offset: i64,
+    time_zone: TimeZone,
+}
+
+impl DateRounding {
+    pub(crate) fn new(
+        fixed_interval: Option<&str>,
+        calendar_interval: Option<&str>,
+        offset: Option<&str>,
+        time_zone: Option<&str>,
+    ) -> crate::Result<DateRounding> {
+        let interval = match (fixed_interval, calendar_interval) {
+            (Some(fixed_interval), None) => {
+                DateInterval::Fixed(parse_into_milliseconds(fixed_interval)? * 1_000_000)
+            }
+            (None, Some(calendar_interval)) => {
+                DateInterval::Calendar(CalendarUnit::parse(calendar_interval)?)
+            }
+            _ => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    "exactly one of fixed_interval and calendar_interval has to be set".to_string(),
+                ))
+            }
+        };
+        let offset = offset
+            .map(parse_offset_into_milliseconds)
+            .transpose()?
+            .unwrap_or(0)
+            * 1_000_000;
+        let time_zone = time_zone
+            .map(TimeZone::parse)
+            .transpose()?
+            .unwrap_or(TimeZone::Fixed(0));
+        Ok(DateRounding {
+            interval,
+            offset,
+            time_zone,
+        })
+    }
+
+    pub(crate) fn time_zone(&self) -> &TimeZone {
+        &self.time_zone
+    }
+
+    /// Returns the start of the bucket of the date.
+    pub(crate) fn round(&self, date: i64) -> i64 {
+        let local = self.round_down_local(self.to_local(date));
+        self.to_utc(local)
+    }
+
+    /// Returns the start of the bucket following the bucket starting at `bucket_start`.
+    pub(crate) fn next_bucket(&self, bucket_start: i64) -> i64 {
+        let local = self.round_down_local(self.to_local(bucket_start));
+        let next_local = match self.interval {
+            DateInterval::Fixed(interval) => local.saturating_add(interval),
+            DateInterval::Calendar(unit) => unit.add_one(local - self.offset) + self.offset,
+        };
+        self.to_utc(next_local)
+    }
+
+    fn round_down_local(&self, local: i64) -> i64 {
+        let local = local.saturating_sub(self.offset);
+        let rounded = match self.interval {
+            DateInterval::Fixed(interval) => round_down_to_multiple(local, interval),
+            DateInterval::Calendar(unit) => unit.round_down(local),
+        };
+        rounded.saturating_add(self.offset)
+    }
+
+    fn to_local(&self, date: i64) -> i64 {
+        let offset = self.time_zone.offset_at(date.div_euclid(NS_PER_SECOND));
+        date.saturating_add(offset * NS_PER_SECOND)
+    }
+
+    fn to_utc(&self, local: i64) -> i64 {
+        let timestamp = self.time_zone.local_to_utc(local.div_euclid(NS_PER_SECOND));
+        timestamp
+            .saturating_mul(NS_PER_SECOND)
+            .saturating_add(local.rem_euclid(NS_PER_SECOND))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 /// Errors when parsing the fixed interval for `DateHistogramAggregationReq`.
 pub enum DateHistogramParseError {
@@ -185,9 +449,12 @@ pub enum DateHistogramParseError {
     /// Value out of bounds
     #[error("passed value is out of bounds: {0:?}")]
     OutOfBounds(String),
+    /// Calendar interval not recognized in passed String
+    #[error("Calendar interval not recognized in passed String {0:?}")]
+    CalendarUnitNotRecognized(String),
 }
 
-fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
+pub(crate) fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
     let is_sign = |byte| &[byte] == b"-" || &[byte] == b"+";
     if input.is_empty() {
         return Err(DateHistogramParseError::InvalidOffset(input.to_string()).into());
@@ -207,7 +474,7 @@ fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError>
     }
 }
 
-fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
+pub(crate) fn parse_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
     let split_boundary = input
         .as_bytes()
         .iter()
@@ -669,7 +936,165 @@ pub(crate) mod tests {
         let err = exec_request(agg_req, &index).unwrap_err();
         assert_eq!(
             err.to_string(),
-            r#"An invalid argument was passed: '`interval` parameter "30d" in date histogram is unsupported, only `fixed_interval` is supported'"#
+            r#"An invalid argument was passed: '`interval` parameter "30d" in date histogram is unsupported, use `fixed_interval` or `calendar_interval` instead'"#
+        );
+    }
+
+    #[test]
+    fn histogram_test_calendar_interval_and_time_zone() {
+        let docs = vec![
+            vec![
+                r#"{ "date": "2023-01-15T10:00:00Z" }"#,
+                r#"{ "date": "2023-01-31T23:30:00Z" }"#,
+            ],
+            vec![
+                r#"{ "date": "2023-03-26T00:30:00Z" }"#,
+                r#"{ "date": "2023-03-26T12:00:00Z" }"#,
+                r#"{ "date": "2023-05-10T00:00:00Z" }"#,
+            ],
+        ];
+        let index = get_test_index_from_docs(false, &docs).unwrap();
+        let exec = |date_histogram: serde_json::Value| {
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "sales_over_time": { "date_histogram": date_histogram }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index).unwrap();
+            res["sales_over_time"]["buckets"].clone()
+        };
+
+        // Months in UTC, with the empty months.
+        let res = exec(json!({ "field": "date", "calendar_interval": "month" }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1672531200000.0, "key_as_string": "2023-01-01T00:00:00Z", "doc_count": 2 },
+                { "key": 1675209600000.0, "key_as_string": "2023-02-01T00:00:00Z", "doc_count": 0 },
+                { "key": 1677628800000.0, "key_as_string": "2023-03-01T00:00:00Z", "doc_count": 2 },
+                { "key": 1680307200000.0, "key_as_string": "2023-04-01T00:00:00Z", "doc_count": 0 },
+                { "key": 1682899200000.0, "key_as_string": "2023-05-01T00:00:00Z", "doc_count": 1 },
+            ])
+        );
+
+        let res = exec(json!({ "field": "date", "calendar_interval": "1q" }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1672531200000.0, "key_as_string": "2023-01-01T00:00:00Z", "doc_count": 4 },
+                { "key": 1680307200000.0, "key_as_string": "2023-04-01T00:00:00Z", "doc_count": 1 },
+            ])
+        );
+
+        let res = exec(json!({ "field": "date", "calendar_interval": "year" }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1672531200000.0, "key_as_string": "2023-01-01T00:00:00Z", "doc_count": 5 },
+            ])
+        );
+
+        // Weeks start on Monday in the local time.
+        let res = exec(json!({
+            "field": "date",
+            "calendar_interval": "week",
+            "time_zone": "-05:00",
+            "min_doc_count": 1
+        }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1673240400000.0, "key_as_string": "2023-01-09T00:00:00-05:00", "doc_count": 1 },
+                { "key": 1675054800000.0, "key_as_string": "2023-01-30T00:00:00-05:00", "doc_count": 1 },
+                { "key": 1679288400000.0, "key_as_string": "2023-03-20T00:00:00-05:00", "doc_count": 2 },
+                { "key": 1683522000000.0, "key_as_string": "2023-05-08T00:00:00-05:00", "doc_count": 1 },
+            ])
+        );
+
+        // The zoneinfo files are not installed everywhere.
+        if TimeZone::parse("Europe/Paris").is_err() {
+            return;
+        }
+        let res = exec(json!({
+            "field": "date",
+            "calendar_interval": "month",
+            "time_zone": "Europe/Paris"
+        }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1672527600000.0, "key_as_string": "2023-01-01T00:00:00+01:00", "doc_count": 1 },
+                { "key": 1675206000000.0, "key_as_string": "2023-02-01T00:00:00+01:00", "doc_count": 1 },
+                { "key": 1677625200000.0, "key_as_string": "2023-03-01T00:00:00+01:00", "doc_count": 2 },
+                { "key": 1680300000000.0, "key_as_string": "2023-04-01T00:00:00+02:00", "doc_count": 0 },
+                { "key": 1682892000000.0, "key_as_string": "2023-05-01T00:00:00+02:00", "doc_count": 1 },
+            ])
+        );
+
+        // The day of the switch to the daylight saving time lasts 23 hours.
+        let bounds = json!({ "min": "2023-03-25T00:00:00Z", "max": "2023-03-27T12:00:00Z" });
+        let res = exec(json!({
+            "field": "date",
+            "calendar_interval": "day",
+            "time_zone": "Europe/Paris",
+            "hard_bounds": bounds,
+            "extended_bounds": bounds
+        }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1679698800000.0, "key_as_string": "2023-03-25T00:00:00+01:00", "doc_count": 0 },
+                { "key": 1679785200000.0, "key_as_string": "2023-03-26T00:00:00+01:00", "doc_count": 2 },
+                { "key": 1679868000000.0, "key_as_string": "2023-03-27T00:00:00+02:00", "doc_count": 0 },
+            ])
+        );
+
+        // Fixed intervals are computed in the local time as well.
+        let res = exec(json!({
+            "field": "date",
+            "fixed_interval": "12h",
+            "time_zone": "Europe/Paris",
+            "min_doc_count": 1
+        }));
+        assert_eq!(
+            res,
+            json!([
+                { "key": 1673737200000.0, "key_as_string": "2023-01-15T00:00:00+01:00", "doc_count": 1 },
+                { "key": 1675206000000.0, "key_as_string": "2023-02-01T00:00:00+01:00", "doc_count": 1 },
+                { "key": 1679785200000.0, "key_as_string": "2023-03-26T00:00:00+01:00", "doc_count": 1 },
+                { "key": 1679824800000.0, "key_as_string": "2023-03-26T12:00:00+02:00", "doc_count": 1 },
+                { "key": 1683669600000.0, "key_as_string": "2023-05-10T00:00:00+02:00", "doc_count": 1 },
+            ])
+        );
+    }
+
+    #[test]
+    fn histogram_test_invalid_calendar_req() {
+        let index = get_test_index_from_docs(false, &[]).unwrap();
+        let exec = |date_histogram: serde_json::Value| {
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "sales_over_time": { "date_histogram": date_histogram }
+            }))
+            .unwrap();
+            exec_request(agg_req, &index).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            exec(json!({ "field": "date", "calendar_interval": "2M" })),
+            r#"Date histogram parse error: CalendarUnitNotRecognized("2M")"#
+        );
+        assert_eq!(
+            exec(json!({ "field": "date", "calendar_interval": "1M", "fixed_interval": "30d" })),
+            "An invalid argument was passed: 'fixed_interval and calendar_interval in date \
+             histogram cannot be set at the same time'"
+        );
+        assert_eq!(
+            exec(json!({ "field": "date" })),
+            "An invalid argument was passed: 'fixed_interval or calendar_interval in date \
+             histogram is missing'"
+        );
+        assert_eq!(
+            exec(json!({ "field": "date", "calendar_interval": "1M", "time_zone": "+25:00" })),
+            r#"An invalid argument was passed: 'Unknown time zone "+25:00"'"#
         );
     }
 }
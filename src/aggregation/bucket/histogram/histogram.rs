@@ -4,6 +4,7 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use tantivy_bitpacker::minmax;
 
+use super::date_histogram::DateRounding;
 use crate::aggregation::agg_limits::MemoryConsumption;
 use crate::aggregation::agg_req::Aggregations;
 use crate::aggregation::agg_req_with_accessor::{
@@ -260,6 +261,9 @@ pub struct SegmentHistogramCollector {
     column_type: ColumnType,
     interval: f64,
     offset: f64,
+    /// Set for the date histograms with calendar intervals or a time zone. The buckets are then
+    /// identified by their key in nanoseconds rather than by their position.
+    date_rounding: Option<DateRounding>,
     bounds: HistogramBounds,
     accessor_idx: usize,
 }
@@ -301,23 +305,33 @@ impl SegmentAggregationCollector for SegmentHistogramCollector {
         let bounds = self.bounds;
         let interval = self.interval;
         let offset = self.offset;
-        let get_bucket_pos = |val| (get_bucket_pos_f64(val, interval, offset) as i64);
+        let date_rounding = self.date_rounding.as_ref();
+        let get_bucket_pos = |raw_val: u64, val: f64| {
+            if let Some(date_rounding) = date_rounding {
+                date_rounding.round(i64::from_u64(raw_val))
+            } else {
+                get_bucket_pos_f64(val, interval, offset) as i64
+            }
+        };
 
         bucket_agg_accessor
             .column_block_accessor
             .fetch_block(docs, &bucket_agg_accessor.accessor);
 
-        for (doc, val) in bucket_agg_accessor
+        for (doc, raw_val) in bucket_agg_accessor
             .column_block_accessor
             .iter_docid_vals(docs, &bucket_agg_accessor.accessor)
         {
-            let val = self.f64_from_fastfield_u64(val);
-
-            let bucket_pos = get_bucket_pos(val);
+            let val = self.f64_from_fastfield_u64(raw_val);
 
             if bounds.contains(val) {
+                let bucket_pos = get_bucket_pos(raw_val, val);
                 let bucket = self.buckets.entry(bucket_pos).or_insert_with(|| {
-                    let key = get_bucket_key_from_pos(bucket_pos as f64, interval, offset);
+                    let key = if date_rounding.is_some() {
+                        bucket_pos as f64
+                    } else {
+                        get_bucket_key_from_pos(bucket_pos as f64, interval, offset)
+                    };
                     SegmentHistogramBucketEntry { key, doc_count: 0 }
                 });
                 bucket.doc_count += 1;
@@ -384,6 +398,7 @@ impl SegmentHistogramCollector {
 
     pub(crate) fn from_req_and_validate(
         mut req: HistogramAggregation,
+        date_rounding: Option<DateRounding>,
         sub_aggregation: &mut AggregationsWithAccessor,
         field_type: ColumnType,
         accessor_idx: usize,
@@ -410,6 +425,7 @@ impl SegmentHistogramCollector {
             column_type: field_type,
             interval: req.interval,
             offset: req.offset.unwrap_or(0.0),
+            date_rounding,
             bounds,
             sub_aggregations: Default::default(),
             sub_aggregation_blueprint,
@@ -437,6 +453,7 @@ fn get_bucket_key_from_pos(bucket_pos: f64, interval: f64, offset: f64) -> f64 {
 fn intermediate_buckets_to_final_buckets_fill_gaps(
     buckets: Vec<IntermediateHistogramBucketEntry>,
     histogram_req: &HistogramAggregation,
+    date_rounding: Option<&DateRounding>,
     sub_aggregation: &Aggregations,
     limits: &mut AggregationLimitsGuard,
 ) -> crate::Result<Vec<BucketEntry>> {
@@ -446,18 +463,24 @@ fn intermediate_buckets_to_final_buckets_fill_gaps(
     // extended_bounds from the request
     let min_max = minmax(buckets.iter().map(|bucket| bucket.key));
 
-    // memory check upfront
-    let (_, first_bucket_num, last_bucket_num) =
-        generate_bucket_pos_with_opt_minmax(histogram_req, min_max);
-
-    // It's based on user input, so we need to account for overflows
-    let added_buckets = ((last_bucket_num.saturating_sub(first_bucket_num)).max(0) as u64)
-        .saturating_sub(buckets.len() as u64);
-    limits.add_memory_consumed(
-        added_buckets * std::mem::size_of::<IntermediateHistogramBucketEntry>() as u64,
-    )?;
-    // create buckets
-    let fill_gaps_buckets = generate_buckets_with_opt_minmax(histogram_req, min_max);
+    let fill_gaps_buckets = if let Some(date_rounding) = date_rounding {
+        // The number of buckets is unknown upfront, so the memory is checked while they are
+        // generated.
+        generate_date_buckets_with_opt_minmax(histogram_req, date_rounding, min_max, limits)?
+    } else {
+        // memory check upfront
+        let (_, first_bucket_num, last_bucket_num) =
+            generate_bucket_pos_with_opt_minmax(histogram_req, min_max);
+
+        // It's based on user input, so we need to account for overflows
+        let added_buckets = ((last_bucket_num.saturating_sub(first_bucket_num)).max(0) as u64)
+            .saturating_sub(buckets.len() as u64);
+        limits.add_memory_consumed(
+            added_buckets * std::mem::size_of::<IntermediateHistogramBucketEntry>() as u64,
+        )?;
+        // create buckets
+        generate_buckets_with_opt_minmax(histogram_req, min_max)
+    };
 
     let empty_sub_aggregation = IntermediateAggregationResults::empty_from_req(sub_aggregation);
 
@@ -495,6 +518,7 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
     buckets: Vec<IntermediateHistogramBucketEntry>,
     is_date_agg: bool,
     histogram_req: &HistogramAggregation,
+    date_rounding: Option<&DateRounding>,
     sub_aggregation: &Aggregations,
     limits: &mut AggregationLimitsGuard,
 ) -> crate::Result<Vec<BucketEntry>> {
@@ -512,6 +536,7 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
         intermediate_buckets_to_final_buckets_fill_gaps(
             buckets,
             &histogram_req,
+            date_rounding,
             sub_aggregation,
             limits,
         )?
@@ -530,7 +555,15 @@ pub(crate) fn intermediate_histogram_buckets_to_final_buckets(
     if is_date_agg {
         for bucket in buckets.iter_mut() {
             if let crate::aggregation::Key::F64(ref mut val) = bucket.key {
-                let key_as_string = format_date(*val as i64)?;
+                let key_as_string = if let Some(date_rounding) = date_rounding {
+                    // The key is formatted in the time zone of the request.
+                    let offset = date_rounding
+                        .time_zone()
+                        .offset_at((*val as i64).div_euclid(1_000_000_000));
+                    format_date_with_offset(*val as i64, offset)?
+                } else {
+                    format_date(*val as i64)?
+                };
                 *val /= 1_000_000.0;
                 bucket.key_as_string = Some(key_as_string);
             }
@@ -574,6 +607,34 @@ pub(crate) fn generate_bucket_pos_with_opt_minmax(
     (offset, first_bucket_num, last_bucket_num)
 }
 
+/// Generates the date buckets of the rounding
+/// Range is computed for provided min_max and request extended_bounds/hard_bounds
+/// returns empty vec when there is no range to span
+fn generate_date_buckets_with_opt_minmax(
+    req: &HistogramAggregation,
+    date_rounding: &DateRounding,
+    min_max: Option<(f64, f64)>,
+    limits: &mut AggregationLimitsGuard,
+) -> crate::Result<Vec<f64>> {
+    let (min, max) = get_req_min_max(req, min_max);
+    let mut buckets = Vec::new();
+    if min > max {
+        return Ok(buckets);
+    }
+    let mut bucket_key = date_rounding.round(min as i64);
+    while bucket_key as f64 <= max {
+        limits
+            .add_memory_consumed(std::mem::size_of::<IntermediateHistogramBucketEntry>() as u64)?;
+        buckets.push(bucket_key as f64);
+        let next_bucket_key = date_rounding.next_bucket(bucket_key);
+        if next_bucket_key <= bucket_key {
+            break;
+        }
+        bucket_key = next_bucket_key;
+    }
+    Ok(buckets)
+}
+
 /// Generates buckets with req.interval
 /// Range is computed for provided min_max and request extended_bounds/hard_bounds
 /// returns empty vec when there is no range to span
@@ -1,4 +1,7 @@
+mod auto_date_histogram;
 mod date_histogram;
 mod histogram;
+mod time_zone;
+pub use auto_date_histogram::*;
 pub use date_histogram::*;
 pub use histogram::*;
@@ -0,0 +1,676 @@
+//! Time zones of the date histogram.
+//!
+//! A time zone is either a fixed offset to UTC, or a zone of the IANA time zone database, whose
+//! rules are read from the zoneinfo files (TZif format, RFC 8536) installed on the system.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::TantivyError;
+
+const SECONDS_PER_HOUR: i64 = 3_600;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// The directories searched for the zoneinfo files, after the `TZDIR` environment variable.
+const ZONEINFO_DIRECTORIES: [&str; 3] = [
+    "/usr/share/zoneinfo",
+    "/usr/lib/zoneinfo",
+    "/usr/share/lib/zoneinfo",
+];
+
+/// Zones are loaded once, since they are typically used by every segment of a request.
+static ZONES: Lazy<Mutex<HashMap<String, Arc<ZoneRules>>>> = Lazy::new(Default::default);
+
+/// A time zone, used to compute the bucket boundaries in local time.
+#[derive(Clone, Debug)]
+pub(crate) enum TimeZone {
+    /// A fixed offset to UTC, in seconds.
+    Fixed(i64),
+    /// A zone of the IANA time zone database.
+    Zone(Arc<ZoneRules>),
+}
+
+impl TimeZone {
+    /// Parses `UTC`, a fixed offset like `+01:00` or `-0530`, or the name of a zone of the IANA
+    /// time zone database like `Europe/Paris`.
+    pub(crate) fn parse(name: &str) -> crate::Result<TimeZone> {
+        if name == "UTC" || name == "Z" {
+            return Ok(TimeZone::Fixed(0));
+        }
+        if name.starts_with(['+', '-']) {
+            return parse_fixed_offset(name)
+                .map(TimeZone::Fixed)
+                .ok_or_else(|| unknown_time_zone(name));
+        }
+        let mut zones = ZONES.lock().unwrap();
+        if let Some(zone) = zones.get(name) {
+            return Ok(TimeZone::Zone(zone.clone()));
+        }
+        let zone = Arc::new(ZoneRules::load(name)?);
+        zones.insert(name.to_string(), zone.clone());
+        Ok(TimeZone::Zone(zone))
+    }
+
+    /// Returns the offset to UTC, in seconds, at the given unix timestamp in seconds.
+    pub(crate) fn offset_at(&self, timestamp: i64) -> i64 {
+        match self {
+            TimeZone::Fixed(offset) => *offset,
+            TimeZone::Zone(zone) => zone.offset_at(timestamp),
+        }
+    }
+
+    /// Converts a local time, in seconds since the unix epoch, to a unix timestamp in seconds.
+    ///
+    /// A local time occurring twice, when the clocks are turned back, is resolved to its first
+    /// occurrence. A local time skipped when the clocks are turned forward is resolved to the
+    /// instant of the transition.
+    pub(crate) fn local_to_utc(&self, local: i64) -> i64 {
+        let offset_before = self.offset_at(local.saturating_sub(SECONDS_PER_DAY));
+        let offset_after = self.offset_at(local.saturating_add(SECONDS_PER_DAY));
+        let first_occurrence = [offset_before, offset_after]
+            .into_iter()
+            .map(|offset| local - offset)
+            .filter(|&timestamp| self.offset_at(timestamp) == local - timestamp)
+            .min();
+        if let Some(timestamp) = first_occurrence {
+            return timestamp;
+        }
+        // The local time is in a gap: the transition is the first instant between the two
+        // candidates with the offset following the gap.
+        let (mut low, mut high) = (local - offset_after, local - offset_before);
+        let offset_low = self.offset_at(low);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if self.offset_at(middle) == offset_low {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        high
+    }
+}
+
+fn unknown_time_zone(name: &str) -> TantivyError {
+    TantivyError::InvalidArgument(format!("Unknown time zone {name:?}"))
+}
+
+/// Parses an offset like `+01:00`, `-0530` or `+05` into seconds.
+fn parse_fixed_offset(offset: &str) -> Option<i64> {
+    let (sign, offset) = match offset.as_bytes().first()? {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = if let Some(hours_minutes) = offset.split_once(':') {
+        hours_minutes
+    } else if offset.len() == 4 {
+        offset.split_at(2)
+    } else {
+        (offset, "0")
+    };
+    let is_number = |number: &str| {
+        (1..=2).contains(&number.len()) && number.bytes().all(|byte| byte.is_ascii_digit())
+    };
+    if !is_number(hours) || !is_number(minutes) {
+        return None;
+    }
+    let hours: i64 = hours.parse().ok()?;
+    let minutes: i64 = minutes.parse().ok()?;
+    if hours > 18 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * SECONDS_PER_HOUR + minutes * 60))
+}
+
+/// The rules of a zone of the IANA time zone database.
+#[derive(Debug)]
+pub(crate) struct ZoneRules {
+    /// Unix timestamps of the transitions, in seconds, sorted.
+    transitions: Vec<i64>,
+    /// Offset to UTC from each of the transitions.
+    offsets: Vec<i64>,
+    /// Offset to UTC before the first transition.
+    initial_offset: i64,
+    /// Rule for the instants following the last transition.
+    footer: Option<PosixRule>,
+}
+
+impl ZoneRules {
+    fn load(name: &str) -> crate::Result<ZoneRules> {
+        let is_valid_name = !name.is_empty()
+            && !name.starts_with('/')
+            && name
+                .split('/')
+                .all(|component| !component.is_empty() && component != "." && component != "..");
+        if !is_valid_name {
+            return Err(unknown_time_zone(name));
+        }
+        let directories = std::env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(ZONEINFO_DIRECTORIES.iter().map(PathBuf::from));
+        for directory in directories {
+            if let Ok(data) = std::fs::read(directory.join(name)) {
+                return ZoneRules::parse(&data).ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "Invalid zoneinfo file for time zone {name:?}"
+                    ))
+                });
+            }
+        }
+        Err(unknown_time_zone(name))
+    }
+
+    /// Parses a TZif file. The 64-bit data and the footer are used when available.
+    fn parse(data: &[u8]) -> Option<ZoneRules> {
+        let header = TzifHeader::parse(data)?;
+        if header.version == 0 {
+            let (zone_rules, _) =
+                ZoneRules::parse_data_block(&header, &data[TzifHeader::LEN..], 4)?;
+            return Some(zone_rules);
+        }
+        let data = data.get(TzifHeader::LEN + header.data_block_len(4)..)?;
+        let header = TzifHeader::parse(data)?;
+        let (mut zone_rules, data_block_len) =
+            ZoneRules::parse_data_block(&header, &data[TzifHeader::LEN..], 8)?;
+        let footer = data.get(TzifHeader::LEN + data_block_len..)?;
+        let footer = footer.strip_prefix(b"\n")?;
+        let footer_end = footer.iter().position(|&byte| byte == b'\n')?;
+        if footer_end > 0 {
+            let footer = std::str::from_utf8(&footer[..footer_end]).ok()?;
+            zone_rules.footer = Some(PosixRule::parse(footer)?);
+        }
+        Some(zone_rules)
+    }
+
+    fn parse_data_block(
+        header: &TzifHeader,
+        data: &[u8],
+        time_len: usize,
+    ) -> Option<(ZoneRules, usize)> {
+        let data_block_len = header.data_block_len(time_len);
+        let data = data.get(..data_block_len)?;
+        let (times, data) = data.split_at(header.time_count * time_len);
+        let (type_indices, data) = data.split_at(header.time_count);
+        let types = &data[..header.type_count * 6];
+
+        let type_offsets: Vec<i64> = types
+            .chunks_exact(6)
+            .map(|local_time_type| {
+                i32::from_be_bytes(local_time_type[..4].try_into().unwrap()) as i64
+            })
+            .collect();
+        let transitions = times
+            .chunks_exact(time_len)
+            .map(|time| {
+                if time_len == 8 {
+                    i64::from_be_bytes(time.try_into().unwrap())
+                } else {
+                    i32::from_be_bytes(time.try_into().unwrap()) as i64
+                }
+            })
+            .collect();
+        let offsets = type_indices
+            .iter()
+            .map(|&type_index| type_offsets.get(type_index as usize).copied())
+            .collect::<Option<Vec<i64>>>()?;
+        let zone_rules = ZoneRules {
+            transitions,
+            offsets,
+            initial_offset: *type_offsets.first()?,
+            footer: None,
+        };
+        Some((zone_rules, data_block_len))
+    }
+
+    fn offset_at(&self, timestamp: i64) -> i64 {
+        let num_transitions = self
+            .transitions
+            .partition_point(|&transition| transition <= timestamp);
+        if num_transitions == self.transitions.len() {
+            if let Some(footer) = &self.footer {
+                return footer.offset_at(timestamp);
+            }
+        }
+        if num_transitions == 0 {
+            self.initial_offset
+        } else {
+            self.offsets[num_transitions - 1]
+        }
+    }
+}
+
+struct TzifHeader {
+    version: u8,
+    is_ut_count: usize,
+    is_std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl TzifHeader {
+    const LEN: usize = 44;
+
+    fn parse(data: &[u8]) -> Option<TzifHeader> {
+        let header = data.get(..TzifHeader::LEN)?;
+        if &header[..4] != b"TZif" {
+            return None;
+        }
+        let count = |index: usize| {
+            let start = 20 + index * 4;
+            u32::from_be_bytes(header[start..start + 4].try_into().unwrap()) as usize
+        };
+        Some(TzifHeader {
+            version: header[4],
+            is_ut_count: count(0),
+            is_std_count: count(1),
+            leap_count: count(2),
+            time_count: count(3),
+            type_count: count(4),
+            char_count: count(5),
+        })
+    }
+
+    fn data_block_len(&self, time_len: usize) -> usize {
+        self.time_count * (time_len + 1)
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_len + 4)
+            + self.is_std_count
+            + self.is_ut_count
+    }
+}
+
+/// A POSIX TZ rule like `CET-1CEST,M3.5.0,M10.5.0/3`, which describes the offsets of a zone
+/// following its last transition.
+#[derive(Debug)]
+struct PosixRule {
+    std_offset: i64,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug)]
+struct DstRule {
+    dst_offset: i64,
+    /// Start of the daylight saving time, in local standard time.
+    start: (RuleDate, i64),
+    /// End of the daylight saving time, in local daylight saving time.
+    end: (RuleDate, i64),
+}
+
+#[derive(Debug)]
+enum RuleDate {
+    /// `Jn`: the day of the year within `[1, 365]`, February 29 never being counted.
+    JulianWithoutLeapDay(i64),
+    /// `n`: the day of the year within `[0, 365]`.
+    Julian(i64),
+    /// `Mm.w.d`: the day `d` of the week (0 is Sunday) of the week `w` of the month `m`, the
+    /// week 5 being the last week of the month.
+    MonthWeekDay { month: u32, week: i64, weekday: i64 },
+}
+
+impl RuleDate {
+    /// Returns the day of the date in the given year, in days since the unix epoch.
+    fn day(&self, year: i64) -> i64 {
+        let first_day_of_year = days_from_civil(year, 1, 1);
+        match *self {
+            RuleDate::JulianWithoutLeapDay(day) => {
+                let leap_day = if is_leap_year(year) && day >= 60 {
+                    1
+                } else {
+                    0
+                };
+                first_day_of_year + day - 1 + leap_day
+            }
+            RuleDate::Julian(day) => first_day_of_year + day,
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first_day = days_from_civil(year, month, 1);
+                let first_weekday = weekday_from_days(first_day);
+                let mut day = first_day + (weekday - first_weekday).rem_euclid(7) + (week - 1) * 7;
+                let next_month_first_day = if month == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, month + 1, 1)
+                };
+                while day >= next_month_first_day {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+impl PosixRule {
+    fn parse(rule: &str) -> Option<PosixRule> {
+        let mut parser = PosixRuleParser {
+            input: rule.as_bytes(),
+            pos: 0,
+        };
+        parser.name()?;
+        // POSIX offsets are positive west of Greenwich.
+        let std_offset = -parser.time()?;
+        if parser.is_done() {
+            return Some(PosixRule {
+                std_offset,
+                dst: None,
+            });
+        }
+        parser.name()?;
+        let dst_offset = if parser.peek() == Some(b',') {
+            std_offset + SECONDS_PER_HOUR
+        } else {
+            -parser.time()?
+        };
+        parser.expect(b',')?;
+        let start = parser.date_time()?;
+        parser.expect(b',')?;
+        let end = parser.date_time()?;
+        if !parser.is_done() {
+            return None;
+        }
+        Some(PosixRule {
+            std_offset,
+            dst: Some(DstRule {
+                dst_offset,
+                start,
+                end,
+            }),
+        })
+    }
+
+    fn offset_at(&self, timestamp: i64) -> i64 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let (year, _, _) =
+            civil_from_days((timestamp + self.std_offset).div_euclid(SECONDS_PER_DAY));
+        let (start_date, start_time) = &dst.start;
+        let (end_date, end_time) = &dst.end;
+        let dst_start = start_date.day(year) * SECONDS_PER_DAY + start_time - self.std_offset;
+        let dst_end = end_date.day(year) * SECONDS_PER_DAY + end_time - dst.dst_offset;
+        let is_dst = if dst_start < dst_end {
+            (dst_start..dst_end).contains(&timestamp)
+        } else {
+            // The daylight saving time spans the new year, as in the southern hemisphere.
+            !(dst_end..dst_start).contains(&timestamp)
+        };
+        if is_dst {
+            dst.dst_offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+struct PosixRuleParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl PosixRuleParser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.peek() != Some(byte) {
+            return None;
+        }
+        self.pos += 1;
+        Some(())
+    }
+
+    /// Parses a zone abbreviation, either alphabetic or quoted like `<+0330>`.
+    fn name(&mut self) -> Option<()> {
+        let start = self.pos;
+        if self.peek() == Some(b'<') {
+            let len = self.input[start..].iter().position(|&byte| byte == b'>')?;
+            self.pos += len + 1;
+            return Some(());
+        }
+        while self.peek().is_some_and(|byte| byte.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        (self.pos > start).then_some(())
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.input[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// Parses a signed duration `[+-]hh[:mm[:ss]]` into seconds.
+    fn time(&mut self) -> Option<i64> {
+        let sign = if self.peek() == Some(b'-') { -1 } else { 1 };
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.pos += 1;
+        }
+        let mut seconds = self.number()? * SECONDS_PER_HOUR;
+        for unit in [60, 1] {
+            if self.peek() != Some(b':') {
+                break;
+            }
+            self.pos += 1;
+            seconds += self.number()? * unit;
+        }
+        Some(sign * seconds)
+    }
+
+    /// Parses a transition date, followed by an optional time defaulting to 02:00:00.
+    fn date_time(&mut self) -> Option<(RuleDate, i64)> {
+        let date = match self.peek()? {
+            b'J' => {
+                self.pos += 1;
+                let day = self.number()?;
+                (1..=365)
+                    .contains(&day)
+                    .then_some(RuleDate::JulianWithoutLeapDay(day))?
+            }
+            b'M' => {
+                self.pos += 1;
+                let month = self.number()?;
+                self.expect(b'.')?;
+                let week = self.number()?;
+                self.expect(b'.')?;
+                let weekday = self.number()?;
+                if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                    return None;
+                }
+                RuleDate::MonthWeekDay {
+                    month: month as u32,
+                    week,
+                    weekday,
+                }
+            }
+            _ => {
+                let day = self.number()?;
+                (0..=365).contains(&day).then_some(RuleDate::Julian(day))?
+            }
+        };
+        let time = if self.peek() == Some(b'/') {
+            self.pos += 1;
+            self.time()?
+        } else {
+            2 * SECONDS_PER_HOUR
+        };
+        Some((date, time))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the day of the week of a day since the unix epoch, 0 being Sunday.
+fn weekday_from_days(days: i64) -> i64 {
+    // 1970-01-01 is a Thursday.
+    (days + 4).rem_euclid(7)
+}
+
+/// Returns the number of days since the unix epoch of a date of the proleptic Gregorian
+/// calendar.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the `(year, month, day)` date of the proleptic Gregorian calendar of a number of days
+/// since the unix epoch.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(date_time: &str) -> i64 {
+        time::OffsetDateTime::parse(date_time, &time::format_description::well_known::Rfc3339)
+            .unwrap()
+            .unix_timestamp()
+    }
+
+    #[test]
+    fn test_civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in -1_000_000..1_000_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(weekday_from_days(days_from_civil(2024, 3, 31)), 0);
+    }
+
+    #[test]
+    fn test_parse_fixed_offset() {
+        assert_eq!(parse_fixed_offset("+01:00"), Some(3_600));
+        assert_eq!(parse_fixed_offset("-0530"), Some(-19_800));
+        assert_eq!(parse_fixed_offset("+05"), Some(18_000));
+        assert_eq!(parse_fixed_offset("+5:30"), Some(19_800));
+        assert_eq!(parse_fixed_offset("+19:00"), None);
+        assert_eq!(parse_fixed_offset("+01:60"), None);
+        assert_eq!(parse_fixed_offset("+01:00:00"), None);
+        assert_eq!(parse_fixed_offset("01:00"), None);
+        assert!(TimeZone::parse("+１:00").is_err());
+        assert!(TimeZone::parse("../etc/passwd").is_err());
+        assert!(TimeZone::parse("Mars/Olympus_Mons").is_err());
+    }
+
+    #[test]
+    fn test_posix_rule() {
+        let paris = PosixRule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        assert_eq!(paris.offset_at(timestamp("2040-03-25T00:59:59Z")), 3_600);
+        assert_eq!(paris.offset_at(timestamp("2040-03-25T01:00:00Z")), 7_200);
+        assert_eq!(paris.offset_at(timestamp("2040-10-28T00:59:59Z")), 7_200);
+        assert_eq!(paris.offset_at(timestamp("2040-10-28T01:00:00Z")), 3_600);
+
+        let sydney = PosixRule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.offset_at(timestamp("2040-01-15T00:00:00Z")), 39_600);
+        assert_eq!(sydney.offset_at(timestamp("2040-06-15T00:00:00Z")), 36_000);
+
+        let tehran = PosixRule::parse("<+0330>-3:30").unwrap();
+        assert_eq!(tehran.offset_at(0), 12_600);
+
+        assert!(PosixRule::parse("CET-1CEST,M13.5.0,M10.5.0/3").is_none());
+        assert!(PosixRule::parse("CET-1CEST,M3.5.0").is_none());
+    }
+
+    #[test]
+    fn test_local_to_utc() {
+        let paris = PosixRule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        let paris = TimeZone::Zone(Arc::new(ZoneRules {
+            transitions: Vec::new(),
+            offsets: Vec::new(),
+            initial_offset: 3_600,
+            footer: Some(paris),
+        }));
+        let local = |date_time: &str| timestamp(&format!("{date_time}Z"));
+        assert_eq!(
+            paris.local_to_utc(local("2040-01-15T12:00:00")),
+            timestamp("2040-01-15T11:00:00Z")
+        );
+        // Skipped local time.
+        assert_eq!(
+            paris.local_to_utc(local("2040-03-25T02:30:00")),
+            timestamp("2040-03-25T01:00:00Z")
+        );
+        // Repeated local time.
+        assert_eq!(
+            paris.local_to_utc(local("2040-10-28T02:30:00")),
+            timestamp("2040-10-28T00:30:00Z")
+        );
+        assert_eq!(
+            TimeZone::parse("-08:00")
+                .unwrap()
+                .local_to_utc(local("2040-01-15T12:00:00")),
+            timestamp("2040-01-15T20:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_zoneinfo() {
+        // The zoneinfo files are not installed everywhere.
+        let Ok(new_york) = TimeZone::parse("America/New_York") else {
+            return;
+        };
+        assert_eq!(
+            new_york.offset_at(timestamp("2021-03-14T06:59:59Z")),
+            -18_000
+        );
+        assert_eq!(
+            new_york.offset_at(timestamp("2021-03-14T07:00:00Z")),
+            -14_400
+        );
+        assert_eq!(
+            new_york.offset_at(timestamp("2050-07-01T00:00:00Z")),
+            -14_400
+        );
+        assert_eq!(
+            new_york.offset_at(timestamp("1850-01-01T00:00:00Z")),
+            -17_762
+        );
+    }
+}
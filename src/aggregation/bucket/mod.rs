@@ -19,9 +19,17 @@
 //! ## Supported Bucket Aggregations
 //! - [Histogram](HistogramAggregation)
 //! - [DateHistogram](DateHistogramAggregationReq)
+//! - [AutoDateHistogram](AutoDateHistogramAggregation)
 //! - [Range](RangeAggregation)
 //! - [Terms](TermsAggregation)
-
+//! - [Composite](CompositeAggregation)
+//! - [Filters](FiltersAggregation)
+//! - [GeoHashGrid](GeoHashGridAggregation)
+//! - [GeoTileGrid](GeoTileGridAggregation)
+
+mod composite;
+mod filters;
+mod geo_grid;
 mod histogram;
 mod range;
 mod term_agg;
@@ -30,6 +38,9 @@ mod term_missing_agg;
 use std::collections::HashMap;
 use std::fmt;
 
+pub use composite::*;
+pub use filters::*;
+pub use geo_grid::*;
 pub use histogram::*;
 pub use range::*;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
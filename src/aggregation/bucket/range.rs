@@ -1,8 +1,12 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::net::{IpAddr, Ipv6Addr};
 use std::ops::Range;
 
+use columnar::column_values::CompactSpaceU64Accessor;
+use columnar::Column;
 use rustc_hash::FxHashMap;
-use serde::{Deserialize, Serialize};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
 use crate::aggregation::intermediate_agg_result::{
@@ -13,6 +17,7 @@ use crate::aggregation::segment_agg_result::{
     build_segment_agg_collector, SegmentAggregationCollector,
 };
 use crate::aggregation::*;
+use crate::schema::IntoIpv6Addr;
 use crate::TantivyError;
 
 /// Provide user-defined buckets to aggregate on.
@@ -32,6 +37,9 @@ use crate::TantivyError;
 /// [`IntermediateRangeBucketEntry`](crate::aggregation::intermediate_agg_result::IntermediateRangeBucketEntry) on the
 /// `DistributedAggregationCollector`.
 ///
+/// On ip fields, the ranges are defined by ip addresses, or by a network block in CIDR notation
+/// with `mask`. The key of a bucket defined by a mask is the mask.
+///
 /// # Limitations/Compatibility
 /// Overlapping ranges are not yet supported.
 ///
@@ -49,6 +57,19 @@ use crate::TantivyError;
 ///     }
 /// }
 /// ```
+///
+/// On an ip field:
+/// ```json
+/// {
+///     "networks": {
+///         "field": "client_ip",
+///         "ranges": [
+///             { "mask": "10.0.0.0/8" },
+///             { "from": "192.168.0.1", "to": "192.168.0.255" }
+///         ]
+///     }
+/// }
+/// ```
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct RangeAggregation {
     /// The field to aggregate on.
@@ -61,6 +82,17 @@ pub struct RangeAggregation {
     pub keyed: bool,
 }
 
+impl RangeAggregation {
+    /// Returns true if the ranges are defined by ip addresses or masks.
+    pub(crate) fn is_ip_range(&self) -> bool {
+        self.ranges.iter().any(|range| {
+            range.mask.is_some()
+                || matches!(range.from, Some(RangeBound::IpAddr(_)))
+                || matches!(range.to, Some(RangeBound::IpAddr(_)))
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// The range for one range bucket.
 pub struct RangeAggregationRange {
@@ -69,20 +101,81 @@ pub struct RangeAggregationRange {
     pub key: Option<String>,
     /// The from range value, which is inclusive in the range.
     /// `None` equals to an open ended interval.
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        default,
-        deserialize_with = "deserialize_option_f64"
-    )]
-    pub from: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub from: Option<RangeBound>,
     /// The to range value, which is not inclusive in the range.
     /// `None` equals to an open ended interval.
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        default,
-        deserialize_with = "deserialize_option_f64"
-    )]
-    pub to: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub to: Option<RangeBound>,
+    /// The network block of the range in CIDR notation, e.g. `10.0.0.0/8`.
+    /// Only supported on ip fields, instead of `from` and `to`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mask: Option<String>,
+}
+
+/// A range value of a [`RangeAggregationRange`].
+///
+/// Numbers are used on numeric and date fields, ip addresses on ip fields. Both may be passed as
+/// strings in JSON, e.g. `"3.5"` or `"192.168.0.1"`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum RangeBound {
+    /// A numeric range value.
+    F64(f64),
+    /// An ip address range value.
+    IpAddr(IpAddr),
+}
+
+impl From<f64> for RangeBound {
+    fn from(val: f64) -> Self {
+        RangeBound::F64(val)
+    }
+}
+
+impl From<IpAddr> for RangeBound {
+    fn from(ip_addr: IpAddr) -> Self {
+        RangeBound::IpAddr(ip_addr)
+    }
+}
+
+impl<'de> Deserialize<'de> for RangeBound {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct RangeBoundVisitor;
+
+        impl Visitor<'_> for RangeBoundVisitor {
+            type Value = RangeBound;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a float, or a string containing a float or an ip address")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where E: de::Error {
+                if let Ok(ip_addr) = value.parse::<IpAddr>() {
+                    return Ok(RangeBound::IpAddr(ip_addr));
+                }
+                crate::aggregation::parse_str_into_f64(value).map(RangeBound::F64)
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+            where E: de::Error {
+                Ok(RangeBound::F64(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where E: de::Error {
+                Ok(RangeBound::F64(value as f64))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where E: de::Error {
+                Ok(RangeBound::F64(value as f64))
+            }
+        }
+
+        deserializer.deserialize_any(RangeBoundVisitor)
+    }
 }
 
 impl From<Range<f64>> for RangeAggregationRange {
@@ -90,32 +183,34 @@ impl From<Range<f64>> for RangeAggregationRange {
         let from = if range.start == f64::MIN {
             None
         } else {
-            Some(range.start)
+            Some(range.start.into())
         };
         let to = if range.end == f64::MAX {
             None
         } else {
-            Some(range.end)
+            Some(range.end.into())
         };
         RangeAggregationRange {
             key: None,
             from,
             to,
+            mask: None,
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
-/// Internally used u64 range for one range bucket.
-pub(crate) struct InternalRangeAggregationRange {
+/// Internally used range for one range bucket, in `u64` fast field value space, or in `u128` for
+/// ip fields.
+pub(crate) struct InternalRangeAggregationRange<T = u64> {
     /// Custom key for the range bucket
     key: Option<String>,
-    /// `u64` range value
-    range: Range<u64>,
+    /// The range value
+    range: Range<T>,
 }
 
-impl From<Range<u64>> for InternalRangeAggregationRange {
-    fn from(range: Range<u64>) -> Self {
+impl<T> From<Range<T>> for InternalRangeAggregationRange<T> {
+    fn from(range: Range<T>) -> Self {
         InternalRangeAggregationRange { key: None, range }
     }
 }
@@ -146,6 +241,10 @@ pub(crate) struct SegmentRangeBucketEntry {
     /// The to range of the bucket. Equals `f64::MAX` when `None`. Open interval, `to` is not
     /// inclusive.
     pub to: Option<f64>,
+    /// The from range of the bucket on ip fields. `None` equals to an open ended interval.
+    pub from_ip: Option<Ipv6Addr>,
+    /// The to range of the bucket on ip fields. `None` equals to an open ended interval.
+    pub to_ip: Option<Ipv6Addr>,
 }
 
 impl Debug for SegmentRangeBucketEntry {
@@ -155,6 +254,8 @@ impl Debug for SegmentRangeBucketEntry {
             .field("doc_count", &self.doc_count)
             .field("from", &self.from)
             .field("to", &self.to)
+            .field("from_ip", &self.from_ip)
+            .field("to_ip", &self.to_ip)
             .finish()
     }
 }
@@ -177,6 +278,8 @@ impl SegmentRangeBucketEntry {
             sub_aggregation: sub_aggregation_res,
             from: self.from,
             to: self.to,
+            from_ip: self.from_ip,
+            to_ip: self.to_ip,
         })
     }
 }
@@ -195,8 +298,13 @@ impl SegmentAggregationCollector for SegmentRangeCollector {
             .buckets
             .into_iter()
             .map(move |range_bucket| {
+                let key = if field_type == ColumnType::IpAddr {
+                    ip_range_to_string(range_bucket.bucket.from_ip, range_bucket.bucket.to_ip)
+                } else {
+                    range_to_string(&range_bucket.range, &field_type)?
+                };
                 Ok((
-                    range_to_string(&range_bucket.range, &field_type)?,
+                    key,
                     range_bucket
                         .bucket
                         .into_intermediate_bucket_entry(sub_agg)?,
@@ -272,8 +380,18 @@ impl SegmentRangeCollector {
         sub_aggregation: &mut AggregationsWithAccessor,
         limits: &mut AggregationLimitsGuard,
         field_type: ColumnType,
+        accessor: &Column<u64>,
         accessor_idx: usize,
     ) -> crate::Result<Self> {
+        if field_type == ColumnType::IpAddr {
+            return Self::from_ip_req_and_validate(
+                req,
+                sub_aggregation,
+                limits,
+                accessor,
+                accessor_idx,
+            );
+        }
         // The range input on the request is f64.
         // We need to convert to u64 ranges, because we read the values as u64.
         // The mapping from the conversion is monotonic so ordering is preserved.
@@ -309,6 +427,8 @@ impl SegmentRangeCollector {
                         key,
                         from,
                         to,
+                        from_ip: None,
+                        to_ip: None,
                     },
                 })
             })
@@ -325,12 +445,89 @@ impl SegmentRangeCollector {
         })
     }
 
+    /// The values of an ip column are read in the compact space of the column, which is specific
+    /// to the segment. The ranges are validated in `u128` space and then converted to the compact
+    /// space of the segment, which is monotonic, so the ordering is preserved. Ranges without
+    /// values in the segment may become empty.
+    fn from_ip_req_and_validate(
+        req: &RangeAggregation,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        limits: &mut AggregationLimitsGuard,
+        accessor: &Column<u64>,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        // The column is empty, if the field does not exist in the segment.
+        let compact_space_accessor = accessor
+            .values
+            .clone()
+            .downcast_arc::<CompactSpaceU64Accessor>()
+            .ok();
+        let to_compact = |val: u128| {
+            compact_space_accessor
+                .as_ref()
+                .map(|accessor| accessor.u128_to_next_compact(val) as u64)
+                .unwrap_or(0)
+        };
+
+        let buckets: Vec<_> = extend_validate_ip_ranges(&req.ranges)?
+            .iter()
+            .map(|range| {
+                let from_ip = (range.range.start != u128::MIN).then(|| range.range.start.into());
+                let to_ip = (range.range.end != u128::MAX).then(|| range.range.end.into());
+                let key = range
+                    .key
+                    .clone()
+                    .unwrap_or_else(|| ip_range_to_string(from_ip, to_ip));
+                let start = if from_ip.is_some() {
+                    to_compact(range.range.start)
+                } else {
+                    u64::MIN
+                };
+                let end = if to_ip.is_some() {
+                    to_compact(range.range.end)
+                } else {
+                    u64::MAX
+                };
+                let sub_aggregation = if sub_aggregation.is_empty() {
+                    None
+                } else {
+                    Some(build_segment_agg_collector(sub_aggregation)?)
+                };
+
+                Ok(SegmentRangeAndBucketEntry {
+                    range: start..end,
+                    bucket: SegmentRangeBucketEntry {
+                        doc_count: 0,
+                        sub_aggregation,
+                        key: Key::Str(key),
+                        from: None,
+                        to: None,
+                        from_ip,
+                        to_ip,
+                    },
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+
+        limits.add_memory_consumed(
+            buckets.len() as u64 * std::mem::size_of::<SegmentRangeAndBucketEntry>() as u64,
+        )?;
+
+        Ok(SegmentRangeCollector {
+            buckets,
+            column_type: ColumnType::IpAddr,
+            accessor_idx,
+        })
+    }
+
     #[inline]
     fn get_bucket_pos(&self, val: u64) -> usize {
+        // Empty ranges may share their start with the next range, so we take the last range
+        // starting before the value.
         let pos = self
             .buckets
-            .binary_search_by_key(&val, |probe| probe.range.start)
-            .unwrap_or_else(|pos| pos - 1);
+            .partition_point(|probe| probe.range.start <= val)
+            - 1;
         debug_assert!(self.buckets[pos].range.contains(&val));
         pos
     }
@@ -352,16 +549,27 @@ fn to_u64_range(
     range: &RangeAggregationRange,
     field_type: &ColumnType,
 ) -> crate::Result<InternalRangeAggregationRange> {
+    if let Some(mask) = &range.mask {
+        return Err(TantivyError::InvalidArgument(format!(
+            "range with mask {mask:?} is only supported on ip fields"
+        )));
+    }
+    let to_u64 = |bound: RangeBound| match bound {
+        RangeBound::F64(val) => f64_to_fastfield_u64(val, field_type)
+            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".to_string())),
+        RangeBound::IpAddr(ip_addr) => Err(TantivyError::InvalidArgument(format!(
+            "range with ip address {ip_addr} is only supported on ip fields"
+        ))),
+    };
+
     let start = if let Some(from) = range.from {
-        f64_to_fastfield_u64(from, field_type)
-            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".to_string()))?
+        to_u64(from)?
     } else {
         u64::MIN
     };
 
     let end = if let Some(to) = range.to {
-        f64_to_fastfield_u64(to, field_type)
-            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".to_string()))?
+        to_u64(to)?
     } else {
         u64::MAX
     };
@@ -372,29 +580,114 @@ fn to_u64_range(
     })
 }
 
+/// Converts the user provided ip range to the `u128` value space of ip addresses, where ipv4
+/// addresses are mapped to ipv6.
+fn to_u128_range(
+    range: &RangeAggregationRange,
+) -> crate::Result<InternalRangeAggregationRange<u128>> {
+    if let Some(mask) = &range.mask {
+        if range.from.is_some() || range.to.is_some() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "range with mask {mask:?} can't also have a from or to value"
+            )));
+        }
+        return Ok(InternalRangeAggregationRange {
+            key: Some(range.key.clone().unwrap_or_else(|| mask.to_string())),
+            range: mask_to_u128_range(mask)?,
+        });
+    }
+    let to_u128 = |bound: RangeBound| match bound {
+        RangeBound::IpAddr(ip_addr) => Ok(u128::from(ip_addr.into_ipv6_addr())),
+        RangeBound::F64(val) => Err(TantivyError::InvalidArgument(format!(
+            "range on ip field expects ip addresses or masks, got {val}"
+        ))),
+    };
+
+    let start = if let Some(from) = range.from {
+        to_u128(from)?
+    } else {
+        u128::MIN
+    };
+
+    let end = if let Some(to) = range.to {
+        to_u128(to)?
+    } else {
+        u128::MAX
+    };
+
+    Ok(InternalRangeAggregationRange {
+        key: range.key.clone(),
+        range: start..end,
+    })
+}
+
+/// Converts a network block in CIDR notation, e.g. `10.0.0.0/8`, to its `u128` range.
+fn mask_to_u128_range(mask: &str) -> crate::Result<Range<u128>> {
+    let invalid_mask = || {
+        TantivyError::InvalidArgument(format!(
+            "invalid mask {mask:?}, expected an ip address and a prefix length, e.g. \
+             \"10.0.0.0/8\""
+        ))
+    };
+    let (ip_addr, prefix_len) = mask.split_once('/').ok_or_else(invalid_mask)?;
+    let ip_addr: IpAddr = ip_addr.parse().map_err(|_| invalid_mask())?;
+    let prefix_len: u32 = prefix_len.parse().map_err(|_| invalid_mask())?;
+    // Ipv4 addresses are mapped to the last 32 bits of ipv6 addresses.
+    let num_host_bits = match ip_addr {
+        IpAddr::V4(_) if prefix_len <= 32 => 32 - prefix_len,
+        IpAddr::V6(_) if prefix_len <= 128 => 128 - prefix_len,
+        _ => return Err(invalid_mask()),
+    };
+    if num_host_bits == 128 {
+        return Ok(u128::MIN..u128::MAX);
+    }
+    let block_size = 1u128 << num_host_bits;
+    let start = u128::from(ip_addr.into_ipv6_addr()) & !(block_size - 1);
+    let end = start.saturating_add(block_size);
+    Ok(start..end)
+}
+
 /// Extends the provided buckets to contain the whole value range, by inserting buckets at the
 /// beginning and end and filling gaps.
 fn extend_validate_ranges(
     buckets: &[RangeAggregationRange],
     field_type: &ColumnType,
 ) -> crate::Result<Vec<InternalRangeAggregationRange>> {
-    let mut converted_buckets = buckets
+    let converted_buckets = buckets
         .iter()
         .map(|range| to_u64_range(range, field_type))
         .collect::<crate::Result<Vec<_>>>()?;
+    extend_validate_converted_ranges(converted_buckets, u64::MIN, u64::MAX)
+}
 
-    converted_buckets.sort_by_key(|bucket| bucket.range.start);
-    if converted_buckets[0].range.start != u64::MIN {
-        converted_buckets.insert(0, (u64::MIN..converted_buckets[0].range.start).into());
+/// Same as [`extend_validate_ranges`], for the ranges of an ip field.
+fn extend_validate_ip_ranges(
+    buckets: &[RangeAggregationRange],
+) -> crate::Result<Vec<InternalRangeAggregationRange<u128>>> {
+    let converted_buckets = buckets
+        .iter()
+        .map(to_u128_range)
+        .collect::<crate::Result<Vec<_>>>()?;
+    extend_validate_converted_ranges(converted_buckets, u128::MIN, u128::MAX)
+}
+
+fn extend_validate_converted_ranges<T: Copy + Ord + Debug>(
+    mut converted_buckets: Vec<InternalRangeAggregationRange<T>>,
+    min: T,
+    max: T,
+) -> crate::Result<Vec<InternalRangeAggregationRange<T>>> {
+    converted_buckets.sort_by_key(|bucket| (bucket.range.start, bucket.range.end));
+    if converted_buckets[0].range.start != min {
+        converted_buckets.insert(0, (min..converted_buckets[0].range.start).into());
     }
 
-    if converted_buckets[converted_buckets.len() - 1].range.end != u64::MAX {
+    if converted_buckets[converted_buckets.len() - 1].range.end != max {
         converted_buckets
-            .push((converted_buckets[converted_buckets.len() - 1].range.end..u64::MAX).into());
+            .push((converted_buckets[converted_buckets.len() - 1].range.end..max).into());
     }
 
     // fill up holes in the ranges
-    let find_hole = |converted_buckets: &[InternalRangeAggregationRange]| {
+    let find_hole = |converted_buckets: &[InternalRangeAggregationRange<T>]| {
         for (pos, ranges) in converted_buckets.windows(2).enumerate() {
             if ranges[0].range.end > ranges[1].range.start {
                 return Err(TantivyError::InvalidArgument(format!(
@@ -446,6 +739,21 @@ pub(crate) fn range_to_key(range: &Range<u64>, field_type: &ColumnType) -> crate
     Ok(Key::Str(range_to_string(range, field_type)?))
 }
 
+/// Renders an ip range, where `None` is an open ended interval, e.g. `10.0.0.0-10.1.0.0`.
+fn ip_range_to_string(from: Option<Ipv6Addr>, to: Option<Ipv6Addr>) -> String {
+    let to_str = |val: Option<Ipv6Addr>| val.map(format_ip).unwrap_or_else(|| "*".to_string());
+    format!("{}-{}", to_str(from), to_str(to))
+}
+
+/// Formats an ip address, preferring the ipv4 representation if possible.
+pub(crate) fn format_ip(ip_addr: Ipv6Addr) -> String {
+    if let Some(ip_addr) = ip_addr.to_ipv4_mapped() {
+        ip_addr.to_string()
+    } else {
+        ip_addr.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -457,6 +765,9 @@ mod tests {
         exec_request, exec_request_with_query, get_test_index_2_segments,
         get_test_index_with_num_docs,
     };
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter};
 
     pub fn get_collector_from_ranges(
         ranges: Vec<RangeAggregationRange>,
@@ -473,6 +784,7 @@ mod tests {
             &mut Default::default(),
             &mut AggregationLimitsGuard::default(),
             field_type,
+            &Column::build_empty_column(0),
             0,
         )
         .expect("unexpected error")
@@ -681,6 +993,209 @@ mod tests {
         Ok(())
     }
 
+    fn get_ip_test_index(merge_segments: bool) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let ip_field = schema_builder.add_ip_addr_field("ip", FAST);
+        let score_field = schema_builder.add_u64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let ip = |ip_addr: &str| ip_addr.parse::<IpAddr>().unwrap().into_ipv6_addr();
+        index_writer.add_document(doc!(ip_field => ip("10.0.0.1"), score_field => 1u64))?;
+        index_writer.add_document(doc!(ip_field => ip("10.1.2.3"), score_field => 2u64))?;
+        index_writer.add_document(doc!(ip_field => ip("::1"), score_field => 3u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(score_field => 4u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(ip_field => ip("8.8.8.8"), score_field => 5u64))?;
+        index_writer.add_document(doc!(ip_field => ip("10.0.0.5"), score_field => 6u64))?;
+        index_writer.add_document(doc!(ip_field => ip("192.168.0.1"), score_field => 7u64))?;
+        index_writer.add_document(doc!(ip_field => ip("192.168.0.10"), score_field => 8u64))?;
+        index_writer.commit()?;
+        if merge_segments {
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        Ok(index)
+    }
+
+    #[test]
+    fn range_ip_test_single_segment() -> crate::Result<()> {
+        range_ip_test_with_opt(true)
+    }
+
+    #[test]
+    fn range_ip_test_multi_segment() -> crate::Result<()> {
+        range_ip_test_with_opt(false)
+    }
+
+    fn range_ip_test_with_opt(merge_segments: bool) -> crate::Result<()> {
+        let index = get_ip_test_index(merge_segments)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "networks": {
+                "range": {
+                    "field": "ip",
+                    "ranges": [
+                        {"from": "192.168.0.1", "to": "192.168.0.10"},
+                        {"mask": "10.0.0.0/8"},
+                    ]
+                },
+                "aggs": {
+                    "score": { "sum": { "field": "score" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+
+        assert_eq!(
+            res["networks"],
+            json!({
+                "buckets": [
+                    {
+                        "key": "*-10.0.0.0",
+                        "doc_count": 2,
+                        "to_as_string": "10.0.0.0",
+                        "score": { "value": 8.0 }
+                    },
+                    {
+                        "key": "10.0.0.0/8",
+                        "doc_count": 3,
+                        "from_as_string": "10.0.0.0",
+                        "to_as_string": "11.0.0.0",
+                        "score": { "value": 9.0 }
+                    },
+                    {
+                        "key": "11.0.0.0-192.168.0.1",
+                        "doc_count": 0,
+                        "from_as_string": "11.0.0.0",
+                        "to_as_string": "192.168.0.1",
+                        "score": { "value": 0.0 }
+                    },
+                    {
+                        "key": "192.168.0.1-192.168.0.10",
+                        "doc_count": 1,
+                        "from_as_string": "192.168.0.1",
+                        "to_as_string": "192.168.0.10",
+                        "score": { "value": 7.0 }
+                    },
+                    {
+                        "key": "192.168.0.10-*",
+                        "doc_count": 1,
+                        "from_as_string": "192.168.0.10",
+                        "score": { "value": 8.0 }
+                    }
+                ]
+            })
+        );
+
+        // Ranges without values in some segments
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "networks": {
+                "range": {
+                    "field": "ip",
+                    "ranges": [
+                        {"key": "loopback", "mask": "::1/128"},
+                        {"from": "10.0.0.2", "to": "10.0.0.4"},
+                        {"mask": "10.0.0.4/31"},
+                    ],
+                    "keyed": true
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+
+        assert_eq!(res["networks"]["buckets"]["loopback"]["doc_count"], 1);
+        assert_eq!(
+            res["networks"]["buckets"]["10.0.0.2-10.0.0.4"]["doc_count"],
+            0
+        );
+        assert_eq!(res["networks"]["buckets"]["10.0.0.4/31"]["doc_count"], 1);
+        assert_eq!(
+            res["networks"]["buckets"]["10.0.0.4/31"]["to_as_string"],
+            "10.0.0.6"
+        );
+        assert_eq!(res["networks"]["buckets"]["10.0.0.6-*"]["doc_count"], 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn range_ip_invalid_request_test() -> crate::Result<()> {
+        let index = get_ip_test_index(false)?;
+
+        let exec_ranges = |field: &str, ranges: Value| {
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "networks": { "range": { "field": field, "ranges": ranges } }
+            }))
+            .unwrap();
+            exec_request(agg_req, &index).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            exec_ranges("ip", json!([{"from": 1.0}])),
+            "An invalid argument was passed: 'range on ip field expects ip addresses or masks, \
+             got 1'"
+        );
+        assert_eq!(
+            exec_ranges("ip", json!([{"mask": "10.0.0.0/8", "to": "11.0.0.0"}])),
+            "An invalid argument was passed: 'range with mask \"10.0.0.0/8\" can't also have a \
+             from or to value'"
+        );
+        assert_eq!(
+            exec_ranges("ip", json!([{"mask": "10.0.0.0/33"}])),
+            "An invalid argument was passed: 'invalid mask \"10.0.0.0/33\", expected an ip \
+             address and a prefix length, e.g. \"10.0.0.0/8\"'"
+        );
+        assert_eq!(
+            exec_ranges("score", json!([{"to": "10.0.0.1"}])),
+            "An invalid argument was passed: 'range with ip address 10.0.0.1 is only supported on \
+             ip fields'"
+        );
+        assert_eq!(
+            exec_ranges("score", json!([{"mask": "10.0.0.0/8"}])),
+            "An invalid argument was passed: 'range with mask \"10.0.0.0/8\" is only supported on \
+             ip fields'"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn mask_to_u128_range_test() {
+        let ip = |ip_addr: &str| u128::from(ip_addr.parse::<IpAddr>().unwrap().into_ipv6_addr());
+
+        assert_eq!(
+            mask_to_u128_range("10.1.2.3/8").unwrap(),
+            ip("10.0.0.0")..ip("11.0.0.0")
+        );
+        assert_eq!(
+            mask_to_u128_range("192.168.0.7/32").unwrap(),
+            ip("192.168.0.7")..ip("192.168.0.8")
+        );
+        assert_eq!(
+            mask_to_u128_range("0.0.0.0/0").unwrap(),
+            ip("0.0.0.0")..ip("::1:0:0:0")
+        );
+        assert_eq!(
+            mask_to_u128_range("2001:db8::/32").unwrap(),
+            ip("2001:db8::")..ip("2001:db9::")
+        );
+        assert_eq!(
+            mask_to_u128_range("ffff::/16").unwrap(),
+            ip("ffff::")..u128::MAX
+        );
+        assert_eq!(mask_to_u128_range("::/0").unwrap(), u128::MIN..u128::MAX);
+        assert!(mask_to_u128_range("10.0.0.0").is_err());
+        assert!(mask_to_u128_range("::/129").is_err());
+        assert!(mask_to_u128_range("10.0.0/8").is_err());
+    }
+
     #[test]
     fn range_custom_key_keyed_buckets_test() -> crate::Result<()> {
         let index = get_test_index_with_num_docs(false, 100)?;
@@ -795,8 +1310,9 @@ mod tests {
         let ranges = vec![
             RangeAggregationRange {
                 key: None,
-                to: Some(10.0),
+                to: Some(10.0.into()),
                 from: None,
+                mask: None,
             },
             (10.0..100.0).into(),
         ];
@@ -805,14 +1321,16 @@ mod tests {
         let ranges = vec![
             RangeAggregationRange {
                 key: None,
-                to: Some(10.0),
+                to: Some(10.0.into()),
                 from: None,
+                mask: None,
             },
             (10.0..100.0).into(),
             RangeAggregationRange {
                 key: None,
                 to: None,
-                from: Some(100.0),
+                from: Some(100.0.into()),
+                mask: None,
             },
         ];
         check_ranges(ranges);
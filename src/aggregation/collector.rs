@@ -7,6 +7,8 @@ use super::segment_agg_result::{
     build_segment_agg_collector, AggregationLimitsGuard, SegmentAggregationCollector,
 };
 use crate::aggregation::agg_req_with_accessor::get_aggs_with_segment_accessor_and_validate;
+use crate::aggregation::metric::validate_rate_aggregations;
+use crate::aggregation::pipeline::validate_pipeline_aggregations;
 use crate::collector::{Collector, SegmentCollector};
 use crate::index::SegmentReader;
 use crate::{DocId, SegmentOrdinal, TantivyError};
@@ -149,6 +151,8 @@ impl AggregationSegmentCollector {
         segment_ordinal: SegmentOrdinal,
         limits: &AggregationLimitsGuard,
     ) -> crate::Result<Self> {
+        validate_pipeline_aggregations(agg, None)?;
+        validate_rate_aggregations(agg, None)?;
         let mut aggs_with_accessor =
             get_aggs_with_segment_accessor_and_validate(agg, reader, segment_ordinal, limits)?;
         let result =
@@ -1,5 +1,5 @@
 use time::format_description::well_known::Rfc3339;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
 
 use crate::TantivyError;
 
@@ -14,3 +14,20 @@ pub(crate) fn format_date(val: i64) -> crate::Result<String> {
         .map_err(|_err| TantivyError::InvalidArgument("Could not serialize date".to_string()))?;
     Ok(key_as_string)
 }
+
+/// Formats the date like `format_date`, in the local time of the given offset to UTC in seconds.
+pub(crate) fn format_date_with_offset(val: i64, offset: i64) -> crate::Result<String> {
+    let datetime = OffsetDateTime::from_unix_timestamp_nanos(val as i128)
+        .and_then(|datetime| {
+            let offset = UtcOffset::from_whole_seconds(offset as i32)?;
+            Ok(datetime.to_offset(offset))
+        })
+        .map_err(|err| {
+            TantivyError::InvalidArgument(format!(
+                "Could not convert {val:?} to OffsetDateTime, err {err:?}"
+            ))
+        })?;
+    datetime
+        .format(&Rfc3339)
+        .map_err(|_err| TantivyError::InvalidArgument("Could not serialize date".to_string()))
+}
@@ -13,15 +13,23 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
-use super::agg_result::{AggregationResult, BucketResult, MetricResult, RangeBucketEntry};
+use super::agg_result::{
+    AggregationResult, BucketResult, CompositeBucketEntry, FiltersBucketEntry, MetricResult,
+    RangeBucketEntry,
+};
 use super::bucket::{
-    cut_off_buckets, get_agg_name_and_property, intermediate_histogram_buckets_to_final_buckets,
-    GetDocCount, Order, OrderTarget, RangeAggregation, TermsAggregation,
+    cmp_composite_keys, cut_off_buckets, format_ip, get_agg_name_and_property,
+    intermediate_histogram_buckets_to_final_buckets, AutoDateHistogramAggregation,
+    CompositeAggregation, FiltersAggregation, GeoGrid, GetDocCount, Order, OrderTarget,
+    RangeAggregation, RoundingUnit, TermsAggregation,
 };
 use super::metric::{
-    IntermediateAverage, IntermediateCount, IntermediateExtendedStats, IntermediateMax,
-    IntermediateMin, IntermediateStats, IntermediateSum, PercentilesCollector, TopHitsTopNComputer,
+    apply_rate_aggregations, IntermediateAverage, IntermediateCount, IntermediateExtendedStats,
+    IntermediateMax, IntermediateMin, IntermediateRate, IntermediateScriptedMetric,
+    IntermediateStats, IntermediateSum, IntermediateTopMetrics, PercentilesCollector,
+    TopHitsTopNComputer,
 };
+use super::pipeline::apply_pipeline_aggregations;
 use super::segment_agg_result::AggregationLimitsGuard;
 use super::{format_date, AggregationError, Key, SerializedKey};
 use crate::aggregation::agg_result::{AggregationResults, BucketEntries, BucketEntry};
@@ -158,7 +166,8 @@ impl IntermediateAggregationResults {
         // Handle empty results
         if results.len() != req.len() {
             for (key, req) in req.iter() {
-                if !results.contains_key(key) {
+                // Pipeline aggregations are computed by their parent aggregation.
+                if !results.contains_key(key) && !req.agg.is_pipeline() {
                     let empty_res = empty_from_req(req);
                     results.insert(key.to_string(), empty_res.into_final_result(req, limits)?);
                 }
@@ -170,7 +179,7 @@ impl IntermediateAggregationResults {
 
     pub(crate) fn empty_from_req(req: &Aggregations) -> Self {
         let mut aggs_res: FxHashMap<String, IntermediateAggregationResult> = FxHashMap::default();
-        for (key, req) in req.iter() {
+        for (key, req) in req.iter().filter(|(_, req)| !req.agg.is_pipeline()) {
             let empty_res = empty_from_req(req);
             aggs_res.insert(key.to_string(), empty_res);
         }
@@ -211,6 +220,26 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
                 is_date_agg: true,
             })
         }
+        AutoDateHistogram(ref req) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::AutoDateHistogram {
+                buckets: IntermediateAutoDateHistogramBucketResult::empty(
+                    req.minimum_unit().unwrap_or(RoundingUnit::Second),
+                ),
+            })
+        }
+        Composite(_) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::Composite {
+                buckets: Default::default(),
+            })
+        }
+        Filters(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filters {
+            buckets: Default::default(),
+        }),
+        GeoHashGrid(_) | GeoTileGrid(_) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::GeoGrid {
+                buckets: Default::default(),
+            })
+        }
         Average(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::Average(
             IntermediateAverage::default(),
         )),
@@ -232,15 +261,27 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
         Sum(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::Sum(
             IntermediateSum::default(),
         )),
-        Percentiles(_) => IntermediateAggregationResult::Metric(
-            IntermediateMetricResult::Percentiles(PercentilesCollector::default()),
+        Percentiles(ref req) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::Percentiles(PercentilesCollector::from_req(req)),
         ),
+        Rate(_) => {
+            IntermediateAggregationResult::Metric(IntermediateMetricResult::Rate(Box::default()))
+        }
         TopHits(ref req) => IntermediateAggregationResult::Metric(
-            IntermediateMetricResult::TopHits(TopHitsTopNComputer::new(req)),
+            IntermediateMetricResult::TopHits(Box::new(TopHitsTopNComputer::new(req))),
+        ),
+        TopMetrics(ref req) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::TopMetrics(Box::new(IntermediateTopMetrics::from_req(req))),
+        ),
+        Cardinality(ref req) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::Cardinality(CardinalityCollector::from_req(req)),
         ),
-        Cardinality(_) => IntermediateAggregationResult::Metric(
-            IntermediateMetricResult::Cardinality(CardinalityCollector::default()),
+        ScriptedMetric(_) => IntermediateAggregationResult::Metric(
+            IntermediateMetricResult::ScriptedMetric(Box::default()),
         ),
+        Derivative(_) | MovingAvg(_) | CumulativeSum(_) | BucketScript(_) | BucketSelector(_) => {
+            panic!("pipeline aggregations don't have an intermediate result")
+        }
     }
 }
 
@@ -264,7 +305,7 @@ impl IntermediateAggregationResult {
                 AggregationResult::BucketResult(bucket.into_final_bucket_result(req, limits)?)
             }
             IntermediateAggregationResult::Metric(metric) => {
-                AggregationResult::MetricResult(metric.into_final_metric_result(req))
+                AggregationResult::MetricResult(metric.into_final_metric_result(req)?)
             }
         };
         Ok(res)
@@ -303,15 +344,21 @@ pub enum IntermediateMetricResult {
     ExtendedStats(IntermediateExtendedStats),
     /// Intermediate sum result.
     Sum(IntermediateSum),
+    /// Intermediate rate result.
+    Rate(Box<IntermediateRate>),
     /// Intermediate top_hits result
-    TopHits(TopHitsTopNComputer),
+    TopHits(Box<TopHitsTopNComputer>),
+    /// Intermediate top_metrics result
+    TopMetrics(Box<IntermediateTopMetrics>),
     /// Intermediate cardinality result
     Cardinality(CardinalityCollector),
+    /// Intermediate scripted_metric result
+    ScriptedMetric(Box<IntermediateScriptedMetric>),
 }
 
 impl IntermediateMetricResult {
-    fn into_final_metric_result(self, req: &Aggregation) -> MetricResult {
-        match self {
+    fn into_final_metric_result(self, req: &Aggregation) -> crate::Result<MetricResult> {
+        let res = match self {
             IntermediateMetricResult::Average(intermediate_avg) => {
                 MetricResult::Average(intermediate_avg.finalize().into())
             }
@@ -337,13 +384,30 @@ impl IntermediateMetricResult {
                 percentiles
                     .into_final_result(req.agg.as_percentile().expect("unexpected metric type")),
             ),
+            IntermediateMetricResult::Rate(intermediate_rate) => {
+                MetricResult::Rate(intermediate_rate.finalize().into())
+            }
             IntermediateMetricResult::TopHits(top_hits) => {
                 MetricResult::TopHits(top_hits.into_final_result())
             }
+            IntermediateMetricResult::TopMetrics(top_metrics) => MetricResult::TopMetrics(
+                top_metrics
+                    .into_final_result(req.agg.as_top_metrics().expect("unexpected metric type")),
+            ),
             IntermediateMetricResult::Cardinality(cardinality) => {
                 MetricResult::Cardinality(cardinality.finalize().into())
             }
-        }
+            IntermediateMetricResult::ScriptedMetric(scripted_metric) => {
+                MetricResult::ScriptedMetric(
+                    scripted_metric.into_final_result(
+                        req.agg
+                            .as_scripted_metric()
+                            .expect("unexpected metric type"),
+                    )?,
+                )
+            }
+        };
+        Ok(res)
     }
 
     // TODO: this is our top-of-the-chain fruit merge mech
@@ -388,8 +452,17 @@ impl IntermediateMetricResult {
             ) => {
                 left.merge_fruits(right)?;
             }
+            (IntermediateMetricResult::Rate(left), IntermediateMetricResult::Rate(right)) => {
+                left.merge_fruits(*right);
+            }
             (IntermediateMetricResult::TopHits(left), IntermediateMetricResult::TopHits(right)) => {
-                left.merge_fruits(right)?;
+                left.merge_fruits(*right)?;
+            }
+            (
+                IntermediateMetricResult::TopMetrics(left),
+                IntermediateMetricResult::TopMetrics(right),
+            ) => {
+                left.merge_fruits(*right);
             }
             (
                 IntermediateMetricResult::Cardinality(left),
@@ -397,6 +470,12 @@ impl IntermediateMetricResult {
             ) => {
                 left.merge_fruits(right)?;
             }
+            (
+                IntermediateMetricResult::ScriptedMetric(left),
+                IntermediateMetricResult::ScriptedMetric(right),
+            ) => {
+                left.merge_fruits(*right);
+            }
             _ => {
                 panic!("incompatible fruit types in tree or missing merge_fruits handler");
             }
@@ -421,11 +500,31 @@ pub enum IntermediateBucketResult {
         /// The histogram buckets
         buckets: Vec<IntermediateHistogramBucketEntry>,
     },
+    /// Auto date histogram aggregation
+    AutoDateHistogram {
+        /// The buckets of a calendar unit
+        buckets: IntermediateAutoDateHistogramBucketResult,
+    },
     /// Term aggregation
     Terms {
         /// The term buckets
         buckets: IntermediateTermBucketResult,
     },
+    /// Composite aggregation
+    Composite {
+        /// The composite buckets
+        buckets: IntermediateCompositeBucketResult,
+    },
+    /// Filters aggregation
+    Filters {
+        /// The buckets of the filters
+        buckets: IntermediateFiltersBucketResult,
+    },
+    /// Geohash or geotile grid aggregation
+    GeoGrid {
+        /// The cell buckets
+        buckets: IntermediateGeoGridBucketResult,
+    },
 }
 
 impl IntermediateBucketResult {
@@ -436,9 +535,16 @@ impl IntermediateBucketResult {
     ) -> crate::Result<BucketResult> {
         match self {
             IntermediateBucketResult::Range(range_res) => {
-                let mut buckets: Vec<RangeBucketEntry> = range_res
-                    .buckets
-                    .into_values()
+                let mut entries: Vec<IntermediateRangeBucketEntry> =
+                    range_res.buckets.into_values().collect();
+                entries.sort_by(|left, right| {
+                    left.from
+                        .unwrap_or(f64::MIN)
+                        .total_cmp(&right.from.unwrap_or(f64::MIN))
+                        .then_with(|| left.from_ip.cmp(&right.from_ip))
+                });
+                let mut buckets: Vec<RangeBucketEntry> = entries
+                    .into_iter()
                     .map(|bucket| {
                         bucket.into_final_bucket_entry(
                             req.sub_aggregation(),
@@ -450,12 +556,7 @@ impl IntermediateBucketResult {
                         )
                     })
                     .collect::<crate::Result<Vec<_>>>()?;
-
-                buckets.sort_by(|left, right| {
-                    left.from
-                        .unwrap_or(f64::MIN)
-                        .total_cmp(&right.from.unwrap_or(f64::MIN))
-                });
+                apply_pipeline_aggregations(&mut buckets, req.sub_aggregation())?;
 
                 let is_keyed = req
                     .agg
@@ -482,13 +583,23 @@ impl IntermediateBucketResult {
                     .agg
                     .as_histogram()?
                     .expect("unexpected aggregation, expected histogram aggregation");
-                let buckets = intermediate_histogram_buckets_to_final_buckets(
+                let date_rounding = req.agg.as_date_rounding()?;
+                let mut buckets = intermediate_histogram_buckets_to_final_buckets(
                     buckets,
                     is_date_agg,
                     histogram_req,
+                    date_rounding.as_ref(),
                     req.sub_aggregation(),
                     limits,
                 )?;
+                if let AggregationVariants::DateHistogram(date_histogram_req) = &req.agg {
+                    apply_rate_aggregations(
+                        &mut buckets,
+                        date_histogram_req,
+                        req.sub_aggregation(),
+                    )?;
+                }
+                apply_pipeline_aggregations(&mut buckets, req.sub_aggregation())?;
 
                 let buckets = if histogram_req.keyed {
                     let mut bucket_map =
@@ -509,6 +620,34 @@ impl IntermediateBucketResult {
                 req.sub_aggregation(),
                 limits,
             ),
+            IntermediateBucketResult::Composite { buckets } => buckets.into_final_result(
+                req.agg
+                    .as_composite()
+                    .expect("unexpected aggregation, expected composite aggregation"),
+                req.sub_aggregation(),
+                limits,
+            ),
+            IntermediateBucketResult::Filters { buckets } => buckets.into_final_result(
+                req.agg
+                    .as_filters()
+                    .expect("unexpected aggregation, expected filters aggregation"),
+                req.sub_aggregation(),
+                limits,
+            ),
+            IntermediateBucketResult::GeoGrid { buckets } => {
+                let (grid, size) = req
+                    .agg
+                    .as_geo_grid()
+                    .expect("unexpected aggregation, expected geo grid aggregation");
+                buckets.into_final_result(grid, size, req.sub_aggregation(), limits)
+            }
+            IntermediateBucketResult::AutoDateHistogram { buckets } => buckets.into_final_result(
+                req.agg
+                    .as_auto_date_histogram()
+                    .expect("unexpected aggregation, expected auto_date_histogram aggregation"),
+                req.sub_aggregation(),
+                limits,
+            ),
         }
     }
 
@@ -562,15 +701,67 @@ impl IntermediateBucketResult {
 
                 *buckets_left = buckets?;
             }
+            (
+                IntermediateBucketResult::Composite {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::Composite {
+                    buckets: buckets_right,
+                },
+            ) => {
+                merge_maps(&mut buckets_left.entries, buckets_right.entries)?;
+            }
+            (
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_right,
+                },
+            ) => {
+                merge_maps(&mut buckets_left.entries, buckets_right.entries)?;
+            }
+            (
+                IntermediateBucketResult::GeoGrid {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::GeoGrid {
+                    buckets: buckets_right,
+                },
+            ) => {
+                merge_maps(&mut buckets_left.entries, buckets_right.entries)?;
+            }
             (IntermediateBucketResult::Range(_), _) => {
                 panic!("try merge on different types")
             }
+            (
+                IntermediateBucketResult::AutoDateHistogram {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::AutoDateHistogram {
+                    buckets: buckets_right,
+                },
+            ) => {
+                buckets_left.merge_fruits(buckets_right)?;
+            }
             (IntermediateBucketResult::Histogram { .. }, _) => {
                 panic!("try merge on different types")
             }
+            (IntermediateBucketResult::AutoDateHistogram { .. }, _) => {
+                panic!("try merge on different types")
+            }
             (IntermediateBucketResult::Terms { .. }, _) => {
                 panic!("try merge on different types")
             }
+            (IntermediateBucketResult::Composite { .. }, _) => {
+                panic!("try merge on different types")
+            }
+            (IntermediateBucketResult::Filters { .. }, _) => {
+                panic!("try merge on different types")
+            }
+            (IntermediateBucketResult::GeoGrid { .. }, _) => {
+                panic!("try merge on different types")
+            }
         }
         Ok(())
     }
@@ -672,6 +863,7 @@ impl IntermediateTermBucketResult {
         // actual error count for the returned terms.
         let (_term_doc_count_before_cutoff, sum_other_doc_count) =
             cut_off_buckets(&mut buckets, req.size as usize);
+        apply_pipeline_aggregations(&mut buckets, sub_aggregation_req)?;
 
         let doc_count_error_upper_bound = if req.show_term_doc_count_error {
             Some(self.doc_count_error_upper_bound)
@@ -687,6 +879,259 @@ impl IntermediateTermBucketResult {
     }
 }
 
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Composite aggregation, with the smallest composite keys after the `after` key of each segment.
+/// A `None` value in a key is a missing value.
+pub struct IntermediateCompositeBucketResult {
+    pub(crate) entries: FxHashMap<Vec<Option<IntermediateKey>>, IntermediateCompositeBucketEntry>,
+}
+
+impl IntermediateCompositeBucketResult {
+    pub(crate) fn into_final_result(
+        self,
+        req: &CompositeAggregation,
+        sub_aggregation_req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<BucketResult> {
+        let orders = req.orders();
+        let mut entries: Vec<_> = self.entries.into_iter().collect();
+        entries.sort_by(|(left, _), (right, _)| cmp_composite_keys(left, right, &orders));
+        // Each segment kept its `size` smallest keys, so that the `size` smallest keys of the
+        // merged result have their complete doc count.
+        entries.truncate(req.size as usize);
+
+        let source_names: Vec<&str> = req.sources().map(|(name, _)| name).collect();
+        let buckets: Vec<CompositeBucketEntry> = entries
+            .into_iter()
+            .map(|(key, entry)| {
+                Ok(CompositeBucketEntry {
+                    key: source_names
+                        .iter()
+                        .zip(key)
+                        .map(|(name, value)| (name.to_string(), value.map(Key::from)))
+                        .collect(),
+                    doc_count: entry.doc_count,
+                    sub_aggregation: entry
+                        .sub_aggregation
+                        .into_final_result_internal(sub_aggregation_req, limits)?,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        let after_key = buckets.last().map(|bucket| bucket.key.clone());
+        Ok(BucketResult::Composite { after_key, buckets })
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Geo grid aggregation, with the buckets by cell of the grid.
+pub struct IntermediateGeoGridBucketResult {
+    pub(crate) entries: FxHashMap<u64, IntermediateGeoGridBucketEntry>,
+}
+
+impl IntermediateGeoGridBucketResult {
+    pub(crate) fn into_final_result(
+        self,
+        grid: GeoGrid,
+        size: u32,
+        sub_aggregation_req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<BucketResult> {
+        let mut entries: Vec<(String, IntermediateGeoGridBucketEntry)> = self
+            .entries
+            .into_iter()
+            .map(|(cell, entry)| (grid.cell_key(cell), entry))
+            .collect();
+        entries.sort_by(|(left_key, left), (right_key, right)| {
+            right
+                .doc_count
+                .cmp(&left.doc_count)
+                .then_with(|| left_key.cmp(right_key))
+        });
+        entries.truncate(size as usize);
+
+        let mut buckets = entries
+            .into_iter()
+            .map(|(key, entry)| {
+                Ok(BucketEntry {
+                    key_as_string: None,
+                    key: Key::Str(key),
+                    doc_count: entry.doc_count,
+                    sub_aggregation: entry
+                        .sub_aggregation
+                        .into_final_result_internal(sub_aggregation_req, limits)?,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        apply_pipeline_aggregations(&mut buckets, sub_aggregation_req)?;
+        Ok(BucketResult::GeoGrid { buckets })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Auto date histogram aggregation, with the buckets of a calendar unit sorted by key.
+pub struct IntermediateAutoDateHistogramBucketResult {
+    pub(crate) unit: RoundingUnit,
+    pub(crate) buckets: Vec<IntermediateHistogramBucketEntry>,
+}
+
+impl IntermediateAutoDateHistogramBucketResult {
+    pub(crate) fn empty(unit: RoundingUnit) -> Self {
+        Self {
+            unit,
+            buckets: Vec::new(),
+        }
+    }
+
+    /// Rounds the keys of the buckets to a longer unit, merging the buckets with the same key.
+    pub(crate) fn round_to_unit(&mut self, unit: RoundingUnit) -> crate::Result<()> {
+        if unit <= self.unit {
+            return Ok(());
+        }
+        let mut buckets: Vec<IntermediateHistogramBucketEntry> =
+            Vec::with_capacity(self.buckets.len());
+        for mut bucket in self.buckets.drain(..) {
+            bucket.key = unit.round(bucket.key as i64) as f64;
+            match buckets.last_mut() {
+                Some(last_bucket) if last_bucket.key == bucket.key => {
+                    last_bucket.merge_fruits(bucket)?
+                }
+                _ => buckets.push(bucket),
+            }
+        }
+        self.unit = unit;
+        self.buckets = buckets;
+        Ok(())
+    }
+
+    pub(crate) fn merge_fruits(
+        &mut self,
+        mut other: IntermediateAutoDateHistogramBucketResult,
+    ) -> crate::Result<()> {
+        let unit = self.unit.max(other.unit);
+        self.round_to_unit(unit)?;
+        other.round_to_unit(unit)?;
+        self.buckets = self
+            .buckets
+            .drain(..)
+            .merge_join_by(other.buckets, |left, right| left.key.total_cmp(&right.key))
+            .map(|either| match either {
+                itertools::EitherOrBoth::Both(mut left, right) => {
+                    left.merge_fruits(right)?;
+                    Ok(left)
+                }
+                itertools::EitherOrBoth::Left(left) => Ok(left),
+                itertools::EitherOrBoth::Right(right) => Ok(right),
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(())
+    }
+
+    pub(crate) fn into_final_result(
+        mut self,
+        req: &AutoDateHistogramAggregation,
+        sub_aggregation_req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<BucketResult> {
+        let (Some(first_bucket), Some(last_bucket)) = (self.buckets.first(), self.buckets.last())
+        else {
+            return Ok(BucketResult::AutoDateHistogram {
+                buckets: Vec::new(),
+                interval: format!("1{}", self.unit.suffix()),
+            });
+        };
+        let (min, max) = (first_bucket.key as i64, last_bucket.key as i64);
+        let (unit, multiple) = req.pick_interval(self.unit, min, max);
+        self.round_to_unit(unit)?;
+
+        // Merge the buckets into buckets of `multiple` units, and fill the gaps between them.
+        let first_bucket_pos = unit.bucket_pos(multiple, min);
+        let num_buckets = (unit.bucket_pos(multiple, max) - first_bucket_pos + 1) as u64;
+        limits.add_memory_consumed(
+            num_buckets.saturating_sub(self.buckets.len() as u64)
+                * std::mem::size_of::<IntermediateHistogramBucketEntry>() as u64,
+        )?;
+        let empty_sub_aggregation =
+            IntermediateAggregationResults::empty_from_req(sub_aggregation_req);
+        let mut buckets: Vec<IntermediateHistogramBucketEntry> =
+            Vec::with_capacity(num_buckets as usize);
+        let mut next_bucket_pos = first_bucket_pos;
+        for mut bucket in self.buckets {
+            let bucket_pos = unit.bucket_pos(multiple, bucket.key as i64);
+            if bucket_pos < next_bucket_pos {
+                let last_bucket = buckets.last_mut().expect("a bucket has been pushed");
+                last_bucket.merge_fruits(bucket)?;
+                continue;
+            }
+            for empty_bucket_pos in next_bucket_pos..bucket_pos {
+                buckets.push(IntermediateHistogramBucketEntry {
+                    key: unit.bucket_start(multiple, empty_bucket_pos) as f64,
+                    doc_count: 0,
+                    sub_aggregation: empty_sub_aggregation.clone(),
+                });
+            }
+            bucket.key = unit.bucket_start(multiple, bucket_pos) as f64;
+            buckets.push(bucket);
+            next_bucket_pos = bucket_pos + 1;
+        }
+
+        let mut buckets = buckets
+            .into_iter()
+            .map(|bucket| {
+                let key = bucket.key as i64;
+                let mut bucket = bucket.into_final_bucket_entry(sub_aggregation_req, limits)?;
+                // The keys are in milliseconds like the keys of the date histogram.
+                bucket.key = Key::F64(key as f64 / 1_000_000.0);
+                bucket.key_as_string = Some(format_date(key)?);
+                Ok(bucket)
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        apply_pipeline_aggregations(&mut buckets, sub_aggregation_req)?;
+        Ok(BucketResult::AutoDateHistogram {
+            buckets,
+            interval: format!("{multiple}{}", unit.suffix()),
+        })
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Filters aggregation, by bucket name
+pub struct IntermediateFiltersBucketResult {
+    pub(crate) entries: FxHashMap<String, IntermediateFiltersBucketEntry>,
+}
+
+impl IntermediateFiltersBucketResult {
+    pub(crate) fn into_final_result(
+        mut self,
+        req: &FiltersAggregation,
+        sub_aggregation_req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<BucketResult> {
+        // Every bucket is returned, also the ones without documents.
+        let buckets = req
+            .bucket_names()
+            .into_iter()
+            .map(|bucket_name| {
+                let entry = self.entries.remove(bucket_name).unwrap_or_else(|| {
+                    IntermediateFiltersBucketEntry {
+                        doc_count: 0,
+                        sub_aggregation: IntermediateAggregationResults::empty_from_req(
+                            sub_aggregation_req,
+                        ),
+                    }
+                });
+                let bucket = FiltersBucketEntry {
+                    doc_count: entry.doc_count,
+                    sub_aggregation: entry
+                        .sub_aggregation
+                        .into_final_result_internal(sub_aggregation_req, limits)?,
+                };
+                Ok((bucket_name.to_string(), bucket))
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(BucketResult::Filters { buckets })
+    }
+}
+
 trait MergeFruits {
     fn merge_fruits(&mut self, other: Self) -> crate::Result<()>;
 }
@@ -750,6 +1195,10 @@ pub struct IntermediateRangeBucketEntry {
     pub from: Option<f64>,
     /// The to range of the bucket. Equals `f64::MAX` when `None`.
     pub to: Option<f64>,
+    /// The from range of the bucket on ip fields. `None` equals to an open ended interval.
+    pub from_ip: Option<Ipv6Addr>,
+    /// The to range of the bucket on ip fields. `None` equals to an open ended interval.
+    pub to_ip: Option<Ipv6Addr>,
 }
 
 impl IntermediateRangeBucketEntry {
@@ -784,6 +1233,10 @@ impl IntermediateRangeBucketEntry {
                 range_bucket_entry.from_as_string = Some(key_as_string);
             }
         }
+        if column_type == Some(ColumnType::IpAddr) {
+            range_bucket_entry.to_as_string = self.to_ip.map(format_ip);
+            range_bucket_entry.from_as_string = self.from_ip.map(format_ip);
+        }
 
         Ok(range_bucket_entry)
     }
@@ -807,6 +1260,59 @@ impl MergeFruits for IntermediateTermBucketEntry {
     }
 }
 
+/// This is the composite entry for a bucket, which contains a count, and optionally
+/// sub_aggregations.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateCompositeBucketEntry {
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+impl MergeFruits for IntermediateCompositeBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateCompositeBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
+/// This is the geo grid entry for a bucket, which contains a count, and optionally
+/// sub_aggregations.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateGeoGridBucketEntry {
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+/// The intermediate bucket of a filter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateFiltersBucketEntry {
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+impl MergeFruits for IntermediateFiltersBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateFiltersBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
+impl MergeFruits for IntermediateGeoGridBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateGeoGridBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
 impl MergeFruits for IntermediateRangeBucketEntry {
     fn merge_fruits(&mut self, other: IntermediateRangeBucketEntry) -> crate::Result<()> {
         self.doc_count += other.doc_count;
@@ -843,6 +1349,8 @@ mod tests {
                     sub_aggregation: Default::default(),
                     from: None,
                     to: None,
+                    from_ip: None,
+                    to_ip: None,
                 },
             );
         }
@@ -873,6 +1381,8 @@ mod tests {
                     doc_count: *doc_count,
                     from: None,
                     to: None,
+                    from_ip: None,
+                    to_ip: None,
                     sub_aggregation: get_sub_test_tree(&[(
                         sub_aggregation_key.to_string(),
                         *sub_aggregation_count,
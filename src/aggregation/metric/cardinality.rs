@@ -18,6 +18,11 @@ use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
 use crate::aggregation::*;
 use crate::TantivyError;
 
+const DEFAULT_PRECISION: u8 = 16;
+const MIN_PRECISION: u8 = 4;
+const MAX_PRECISION: u8 = 18;
+const MAX_PRECISION_THRESHOLD: u64 = 40_000;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct BuildSaltedHasher {
     salt: u8,
@@ -85,6 +90,20 @@ impl BuildHasher for BuildSaltedHasher {
 /// The cardinality aggregation provides an approximate count, which is usually
 /// accurate within a small error range. This trade-off allows for efficient
 /// computation even on very large datasets.
+///
+/// The `precision_threshold` parameter trades memory for accuracy: counts below the threshold
+/// are expected to be close to exact, while counts above it get an error of a few percent.
+/// The memory used per bucket grows with the threshold, which is capped at 40000. Without a
+/// threshold, the counts are close to exact up to about 12000 distinct values.
+///
+/// ```JSON
+/// {
+///     "cardinality": {
+///         "field": "user_id",
+///         "precision_threshold": 1000
+///     }
+/// }
+/// ```
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CardinalityAggregationReq {
     /// The field name to compute the percentiles on.
@@ -95,6 +114,9 @@ pub struct CardinalityAggregationReq {
     /// { "field": "my_numbers", "missing": "10.0" }
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub missing: Option<Key>,
+    /// Counts below this threshold are expected to be close to exact.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub precision_threshold: Option<u64>,
 }
 
 impl CardinalityAggregationReq {
@@ -103,12 +125,25 @@ impl CardinalityAggregationReq {
         Self {
             field: field_name,
             missing: None,
+            precision_threshold: None,
         }
     }
     /// Returns the field name the aggregation is computed on.
     pub fn field_name(&self) -> &str {
         &self.field
     }
+
+    /// Returns the precision of the HyperLogLog++ sketch, i.e. the log2 of its number of
+    /// registers.
+    pub(crate) fn precision(&self) -> u8 {
+        let Some(precision_threshold) = self.precision_threshold else {
+            return DEFAULT_PRECISION;
+        };
+        // The sketch stays accurate as long as the number of registers is a few times larger
+        // than the number of distinct values.
+        let num_registers = precision_threshold.min(MAX_PRECISION_THRESHOLD) as f64 / 0.75 * 4.0;
+        (num_registers.log2().ceil() as u8).clamp(MIN_PRECISION, MAX_PRECISION)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -121,13 +156,17 @@ pub(crate) struct SegmentCardinalityCollector {
 }
 
 impl SegmentCardinalityCollector {
-    pub fn from_req(column_type: ColumnType, accessor_idx: usize, missing: &Option<Key>) -> Self {
+    pub fn from_req(
+        req: &CardinalityAggregationReq,
+        column_type: ColumnType,
+        accessor_idx: usize,
+    ) -> Self {
         Self {
-            cardinality: CardinalityCollector::new(column_type as u8),
+            cardinality: CardinalityCollector::new(column_type as u8, req.precision()),
             entries: Default::default(),
             column_type,
             accessor_idx,
-            missing: missing.clone(),
+            missing: req.missing.clone(),
         }
     }
 
@@ -280,7 +319,7 @@ pub struct CardinalityCollector {
 }
 impl Default for CardinalityCollector {
     fn default() -> Self {
-        Self::new(0)
+        Self::new(0, DEFAULT_PRECISION)
     }
 }
 
@@ -296,12 +335,17 @@ impl CardinalityCollector {
         Some(self.sketch.clone().count().trunc())
     }
 
-    fn new(salt: u8) -> Self {
+    fn new(salt: u8, precision: u8) -> Self {
         Self {
-            sketch: HyperLogLogPlus::new(16, BuildSaltedHasher { salt }).unwrap(),
+            sketch: HyperLogLogPlus::new(precision, BuildSaltedHasher { salt }).unwrap(),
         }
     }
 
+    /// Creates an empty collector with the precision of the request.
+    pub(crate) fn from_req(req: &CardinalityAggregationReq) -> Self {
+        Self::new(0, req.precision())
+    }
+
     pub(crate) fn merge_fruits(&mut self, right: CardinalityCollector) -> crate::Result<()> {
         self.sketch.merge(&right.sketch).map_err(|err| {
             TantivyError::AggregationError(AggregationError::InternalError(format!(
@@ -321,6 +365,7 @@ mod tests {
 
     use columnar::MonotonicallyMappableToU64;
 
+    use super::CardinalityAggregationReq;
     use crate::aggregation::agg_req::Aggregations;
     use crate::aggregation::tests::{exec_request, get_test_index_from_terms};
     use crate::schema::{IntoIpv6Addr, Schema, FAST};
@@ -470,4 +515,76 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn cardinality_precision_from_threshold() {
+        let precision = |precision_threshold: Option<u64>| {
+            let mut req = CardinalityAggregationReq::from_field_name("id".to_string());
+            req.precision_threshold = precision_threshold;
+            req.precision()
+        };
+        assert_eq!(precision(None), 16);
+        assert_eq!(precision(Some(0)), 4);
+        assert_eq!(precision(Some(100)), 10);
+        assert_eq!(precision(Some(3_000)), 14);
+        assert_eq!(precision(Some(40_000)), 18);
+        assert_eq!(precision(Some(1_000_000)), 18);
+    }
+
+    #[test]
+    fn cardinality_aggregation_precision_threshold() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", FAST);
+        let bucket_field = schema_builder.add_u64_field("bucket", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut writer = index.writer_for_tests()?;
+            for i in 0..20_000u64 {
+                writer.add_document(doc!(id_field => i % 5_000, bucket_field => (i % 2) * 10))?;
+                if i % 7_000 == 0 {
+                    writer.commit()?;
+                }
+            }
+            writer.commit()?;
+        }
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "histogram": {
+                "histogram": {
+                    "field": "bucket",
+                    "interval": 5,
+                    "min_doc_count": 0
+                },
+                "aggs": {
+                    "default_precision": {
+                        "cardinality": {
+                            "field": "id"
+                        }
+                    },
+                    "low_precision": {
+                        "cardinality": {
+                            "field": "id",
+                            "precision_threshold": 100
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        let buckets = res["histogram"]["buckets"].as_array().unwrap();
+        assert_eq!(buckets.len(), 3);
+        // The empty bucket gets sketches with the precision of the request.
+        assert_eq!(buckets[1]["default_precision"]["value"], 0.0);
+        assert_eq!(buckets[1]["low_precision"]["value"], 0.0);
+        for bucket in [&buckets[0], &buckets[2]] {
+            let default_precision = bucket["default_precision"]["value"].as_f64().unwrap();
+            assert!((2_475.0..=2_525.0).contains(&default_precision));
+            let low_precision = bucket["low_precision"]["value"].as_f64().unwrap();
+            assert!((2_125.0..=2_875.0).contains(&low_precision));
+        }
+
+        Ok(())
+    }
 }
@@ -15,6 +15,9 @@
 //! - [Sum](SumAggregation)
 //! - [Count](CountAggregation)
 //! - [Percentiles](PercentilesAggregationReq)
+//! - [Rate](RateAggregation)
+//! - [TopMetrics](TopMetricsAggregationReq)
+//! - [ScriptedMetric](ScriptedMetricAggregation)
 
 mod average;
 mod cardinality;
@@ -23,9 +26,13 @@ mod extended_stats;
 mod max;
 mod min;
 mod percentiles;
+mod rate;
+mod scripted_metric;
 mod stats;
 mod sum;
+mod tdigest;
 mod top_hits;
+mod top_metrics;
 
 use std::collections::HashMap;
 
@@ -36,13 +43,18 @@ pub use extended_stats::*;
 pub use max::*;
 pub use min::*;
 pub use percentiles::*;
+pub use rate::*;
 use rustc_hash::FxHashMap;
+pub use scripted_metric::*;
 use serde::{Deserialize, Serialize};
 pub use stats::*;
 pub use sum::*;
 pub use top_hits::*;
+pub use top_metrics::*;
 
+use crate::aggregation::AggregationError;
 use crate::schema::OwnedValue;
+use crate::{DocAddress, TantivyError};
 
 /// Single-metric aggregations use this common result structure.
 ///
@@ -103,6 +115,17 @@ pub struct TopHitsVecEntry {
     #[serde(rename = "docvalue_fields")]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub doc_value_fields: HashMap<String, OwnedValue>,
+
+    /// Values of the stored fields of the document, for queries that include
+    /// `stored_fields`.
+    #[serde(rename = "stored_fields")]
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub stored_fields: HashMap<String, OwnedValue>,
+
+    /// The address of the document.
+    ///
+    /// Like any document address, it is only valid for the searcher that ran the aggregation.
+    pub doc_address: DocAddress,
 }
 
 /// The top_hits metric aggregation results a list of top hits by sort criteria.
@@ -114,6 +137,83 @@ pub struct TopHitsMetricResult {
     pub hits: Vec<TopHitsVecEntry>,
 }
 
+/// The top_metrics metric results entry
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsEntry {
+    /// The sort value of the document.
+    pub sort: Vec<f64>,
+    /// The values of the metrics of the document, `null` if the document has no value.
+    pub metrics: HashMap<String, OwnedValue>,
+}
+
+/// The top_metrics metric aggregation results the metrics of the top documents by the sort
+/// criteria.
+///
+/// The main reason for wrapping it in `top` is to match elasticsearch output structure.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsResult {
+    /// The top documents.
+    pub top: Vec<TopMetricsEntry>,
+}
+
+impl TopMetricsResult {
+    /// Returns the value of a metric of the first top document.
+    ///
+    /// The metric may be omitted if the aggregation returns a single metric.
+    pub(crate) fn get_value(&self, metric: &str) -> crate::Result<Option<f64>> {
+        let Some(entry) = self.top.first() else {
+            return Ok(None);
+        };
+        let value = match entry.metrics.get(metric) {
+            Some(value) => value,
+            None if metric.is_empty() && entry.metrics.len() == 1 => {
+                entry.metrics.values().next().expect("one metric")
+            }
+            None => {
+                return Err(TantivyError::AggregationError(
+                    AggregationError::InvalidRequest(format!(
+                        "unknown metric {metric:?} of top_metrics aggregation"
+                    )),
+                ))
+            }
+        };
+        match value {
+            OwnedValue::Null => Ok(None),
+            OwnedValue::U64(val) => Ok(Some(*val as f64)),
+            OwnedValue::I64(val) => Ok(Some(*val as f64)),
+            OwnedValue::F64(val) => Ok(Some(*val)),
+            _ => Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(format!(
+                    "metric {metric:?} of top_metrics aggregation is not numeric"
+                )),
+            )),
+        }
+    }
+}
+
+/// The scripted_metric aggregation returns the value computed by its `reduce` closure.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedMetricResult {
+    /// The value of the metric.
+    pub value: OwnedValue,
+}
+
+impl ScriptedMetricResult {
+    pub(crate) fn get_value(&self) -> crate::Result<Option<f64>> {
+        match self.value {
+            OwnedValue::Null => Ok(None),
+            OwnedValue::U64(val) => Ok(Some(val as f64)),
+            OwnedValue::I64(val) => Ok(Some(val as f64)),
+            OwnedValue::F64(val) => Ok(Some(val)),
+            _ => Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(
+                    "value of scripted_metric aggregation is not numeric".to_string(),
+                ),
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::aggregation::agg_req::Aggregations;
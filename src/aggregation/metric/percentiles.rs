@@ -2,6 +2,7 @@ use std::fmt::Debug;
 
 use serde::{Deserialize, Serialize};
 
+use super::tdigest::TDigest;
 use super::*;
 use crate::aggregation::agg_req_with_accessor::{
     AggregationWithAccessor, AggregationsWithAccessor,
@@ -69,6 +70,24 @@ use crate::{DocId, TantivyError};
 /// calculating exact percentiles for large data sets can be computationally
 /// expensive and time-consuming. As a result, many percentile aggregation
 /// algorithms use approximation techniques to provide faster results.
+///
+/// By default, the percentiles are estimated with a DDSketch, which has a relative error of
+/// 1% on the values. A t-digest can be used instead, which is most accurate on the extreme
+/// percentiles, such as p99 or p99.9. Its `compression` bounds the number of centroids kept
+/// per sketch, 100 by default: a higher compression gives more accurate percentiles at the cost
+/// of more memory.
+///
+/// ```JSON
+/// {
+///     "percentiles": {
+///         "field": "load_time",
+///         "percents": [95, 99, 99.9],
+///         "tdigest": {
+///             "compression": 200
+///         }
+///     }
+/// }
+/// ```
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PercentilesAggregationReq {
     /// The field name to compute the percentiles on.
@@ -89,6 +108,29 @@ pub struct PercentilesAggregationReq {
         deserialize_with = "deserialize_option_f64"
     )]
     pub missing: Option<f64>,
+    /// Estimates the percentiles with a t-digest instead of a DDSketch.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub tdigest: Option<TDigestOptions>,
+}
+
+/// The options of the t-digest used by the [`PercentilesAggregationReq`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TDigestOptions {
+    /// Bounds the number of centroids of the t-digest. Defaults to 100.
+    #[serde(default = "default_compression")]
+    pub compression: f64,
+}
+
+impl Default for TDigestOptions {
+    fn default() -> Self {
+        TDigestOptions {
+            compression: default_compression(),
+        }
+    }
+}
+
+fn default_compression() -> f64 {
+    100.0
 }
 fn default_percentiles() -> &'static [f64] {
     &[1.0, 5.0, 25.0, 50.0, 75.0, 95.0, 99.0]
@@ -105,6 +147,7 @@ impl PercentilesAggregationReq {
             percents: None,
             keyed: default_as_true(),
             missing: None,
+            tdigest: None,
         }
     }
     /// Returns the field name the aggregation is computed on.
@@ -126,6 +169,16 @@ impl PercentilesAggregationReq {
                 ));
             }
         }
+        if let Some(tdigest) = self.tdigest.as_ref() {
+            if !(tdigest.compression.is_finite() && tdigest.compression > 0.0) {
+                return Err(TantivyError::AggregationError(
+                    AggregationError::InvalidRequest(format!(
+                        "The t-digest compression has to be a positive number, got {}",
+                        tdigest.compression
+                    )),
+                ));
+            }
+        }
 
         Ok(())
     }
@@ -139,10 +192,16 @@ pub(crate) struct SegmentPercentilesCollector {
     missing: Option<u64>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+enum PercentilesSketch {
+    DDSketch(sketches_ddsketch::DDSketch),
+    TDigest(TDigest),
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 /// The percentiles collector used during segment collection and for merging results.
 pub struct PercentilesCollector {
-    sketch: sketches_ddsketch::DDSketch,
+    sketch: PercentilesSketch,
 }
 impl Default for PercentilesCollector {
     fn default() -> Self {
@@ -152,8 +211,12 @@ impl Default for PercentilesCollector {
 
 impl Debug for PercentilesCollector {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let sketch_len = match &self.sketch {
+            PercentilesSketch::DDSketch(sketch) => sketch.length() as u64,
+            PercentilesSketch::TDigest(digest) => digest.count(),
+        };
         f.debug_struct("IntermediatePercentiles")
-            .field("sketch_len", &self.sketch.length())
+            .field("sketch_len", &sketch_len)
             .finish()
     }
 }
@@ -175,24 +238,16 @@ fn format_percentile(percentile: f64) -> String {
 impl PercentilesCollector {
     /// Convert result into final result. This will query the quantils from the underlying quantil
     /// collector.
-    pub fn into_final_result(self, req: &PercentilesAggregationReq) -> PercentilesMetricResult {
+    pub fn into_final_result(mut self, req: &PercentilesAggregationReq) -> PercentilesMetricResult {
         let percentiles: &[f64] = req
             .percents
             .as_ref()
             .map(|el| el.as_ref())
             .unwrap_or(default_percentiles());
-        let iter_quantile_and_values = percentiles.iter().cloned().map(|percentile| {
-            (
-                percentile,
-                self.sketch
-                    .quantile(percentile / 100.0)
-                    .expect(
-                        "quantil out of range. This error should have been caught during \
-                         validation phase",
-                    )
-                    .unwrap_or(f64::NAN),
-            )
-        });
+        let iter_quantile_and_values = percentiles
+            .iter()
+            .cloned()
+            .map(|percentile| (percentile, self.quantile(percentile / 100.0)));
 
         let values = if req.keyed {
             PercentileValues::HashMap(
@@ -213,18 +268,60 @@ impl PercentilesCollector {
     fn new() -> Self {
         let ddsketch_config = sketches_ddsketch::Config::defaults();
         let sketch = sketches_ddsketch::DDSketch::new(ddsketch_config);
-        Self { sketch }
+        Self {
+            sketch: PercentilesSketch::DDSketch(sketch),
+        }
+    }
+
+    /// Creates an empty collector using the sketch configured in the request.
+    pub(crate) fn from_req(req: &PercentilesAggregationReq) -> Self {
+        if let Some(tdigest) = req.tdigest.as_ref() {
+            Self {
+                sketch: PercentilesSketch::TDigest(TDigest::new(tdigest.compression)),
+            }
+        } else {
+            Self::new()
+        }
     }
+
     fn collect(&mut self, val: f64) {
-        self.sketch.add(val);
+        match &mut self.sketch {
+            PercentilesSketch::DDSketch(sketch) => sketch.add(val),
+            PercentilesSketch::TDigest(digest) => digest.add(val),
+        }
+    }
+
+    /// Returns the estimated value at quantile `q`, or NaN if no value has been collected.
+    fn quantile(&mut self, q: f64) -> f64 {
+        let value_opt = match &mut self.sketch {
+            PercentilesSketch::DDSketch(sketch) => sketch.quantile(q).expect(
+                "quantil out of range. This error should have been caught during validation phase",
+            ),
+            PercentilesSketch::TDigest(digest) => digest.quantile(q),
+        };
+        value_opt.unwrap_or(f64::NAN)
     }
 
     pub(crate) fn merge_fruits(&mut self, right: PercentilesCollector) -> crate::Result<()> {
-        self.sketch.merge(&right.sketch).map_err(|err| {
-            TantivyError::AggregationError(AggregationError::InternalError(format!(
-                "Error while merging percentiles {err:?}"
-            )))
-        })?;
+        match (&mut self.sketch, &right.sketch) {
+            (PercentilesSketch::DDSketch(left), PercentilesSketch::DDSketch(right)) => {
+                left.merge(right).map_err(|err| {
+                    TantivyError::AggregationError(AggregationError::InternalError(format!(
+                        "Error while merging percentiles {err:?}"
+                    )))
+                })?;
+            }
+            (PercentilesSketch::TDigest(left), PercentilesSketch::TDigest(right)) => {
+                left.merge(right);
+            }
+            _ => {
+                return Err(TantivyError::AggregationError(
+                    AggregationError::InternalError(
+                        "Cannot merge percentiles estimated with different sketches".to_string(),
+                    ),
+                ));
+            }
+        }
 
         Ok(())
     }
@@ -243,7 +340,7 @@ impl SegmentPercentilesCollector {
 
         Ok(Self {
             field_type,
-            percentiles: PercentilesCollector::new(),
+            percentiles: PercentilesCollector::from_req(req),
             accessor_idx,
             missing,
         })
@@ -339,7 +436,7 @@ mod tests {
     use more_asserts::{assert_ge, assert_le};
     use rand::rngs::StdRng;
     use rand::SeedableRng;
-    use serde_json::Value;
+    use serde_json::{json, Value};
 
     use crate::aggregation::agg_req::Aggregations;
     use crate::aggregation::agg_result::AggregationResults;
@@ -490,15 +587,28 @@ mod tests {
 
     #[test]
     fn test_aggregation_percentiles_single_seg() -> crate::Result<()> {
-        test_aggregation_percentiles(true)
+        test_aggregation_percentiles(true, None)
     }
 
     #[test]
     fn test_aggregation_percentiles_multi_seg() -> crate::Result<()> {
-        test_aggregation_percentiles(false)
+        test_aggregation_percentiles(false, None)
+    }
+
+    #[test]
+    fn test_aggregation_percentiles_tdigest_single_seg() -> crate::Result<()> {
+        test_aggregation_percentiles(true, Some(200.0))
     }
 
-    fn test_aggregation_percentiles(merge_segments: bool) -> crate::Result<()> {
+    #[test]
+    fn test_aggregation_percentiles_tdigest_multi_seg() -> crate::Result<()> {
+        test_aggregation_percentiles(false, Some(200.0))
+    }
+
+    fn test_aggregation_percentiles(
+        merge_segments: bool,
+        tdigest_compression: Option<f64>,
+    ) -> crate::Result<()> {
         use rand_distr::Distribution;
         let num_values_in_segment = [100, 30_000, 8000];
         let lg_norm = rand_distr::LogNormal::new(2.996f64, 0.979f64).unwrap();
@@ -519,7 +629,7 @@ mod tests {
         all_values.sort_unstable_by(|a, b| a.total_cmp(b));
 
         fn get_exact_quantil(q: f64, all_values: &[f64]) -> f64 {
-            let q = q / 100.0;
+            let q = q.min(100.0) / 100.0;
             assert!((0f64..=1f64).contains(&q));
 
             let index = (all_values.len() as f64 * q).ceil() as usize;
@@ -542,16 +652,19 @@ mod tests {
 
         let reader = index.reader()?;
 
-        let agg_req_str = r#"
-        {
-          "mypercentiles": {
-            "percentiles": {
-              "field": "score_f64",
-              "percents": [ 95, 99, 99.9 ]
+        let mut percentiles_req = json!({
+            "field": "score_f64",
+            "percents": [ 95, 99, 99.9 ]
+        });
+        if let Some(compression) = tdigest_compression {
+            percentiles_req["tdigest"] = json!({ "compression": compression });
+        }
+        let agg_req_1: Aggregations = serde_json::from_value(json!({
+            "mypercentiles": {
+                "percentiles": percentiles_req
             }
-          }
-        } "#;
-        let agg_req_1: Aggregations = serde_json::from_str(agg_req_str).unwrap();
+        }))
+        .unwrap();
 
         let collector = AggregationCollector::from_aggs(agg_req_1, Default::default());
 
@@ -561,24 +674,32 @@ mod tests {
         let res: Value = serde_json::from_str(&serde_json::to_string(&agg_res)?)?;
         let vals = &res["mypercentiles"]["values"];
 
-        let check_quantil = |exact_quantil: f64, val: f64| {
-            let lower = exact_quantil - exact_quantil * 0.02;
-            let upper = exact_quantil + exact_quantil * 0.02;
+        let check_quantil = |percent: f64, val: f64| {
+            let (lower, upper) = if tdigest_compression.is_some() {
+                // The error of a t-digest is bounded in rank rather than in value.
+                (
+                    get_exact_quantil(percent - 0.1, &all_values),
+                    get_exact_quantil(percent + 0.1, &all_values),
+                )
+            } else {
+                let exact_quantil = get_exact_quantil(percent, &all_values);
+                (
+                    exact_quantil - exact_quantil * 0.02,
+                    exact_quantil + exact_quantil * 0.02,
+                )
+            };
             assert_le!(val, upper);
             assert_ge!(val, lower);
         };
 
         let val = vals["95.0"].as_f64().unwrap();
-        let exact_quantil = get_exact_quantil(95.0, &all_values);
-        check_quantil(exact_quantil, val);
+        check_quantil(95.0, val);
 
         let val = vals["99.0"].as_f64().unwrap();
-        let exact_quantil = get_exact_quantil(99.0, &all_values);
-        check_quantil(exact_quantil, val);
+        check_quantil(99.0, val);
 
         let val = vals["99.9"].as_f64().unwrap();
-        let exact_quantil = get_exact_quantil(99.9, &all_values);
-        check_quantil(exact_quantil, val);
+        check_quantil(99.9, val);
 
         Ok(())
     }
@@ -688,4 +809,43 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_percentiles_tdigest_parameters() -> crate::Result<()> {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let index = get_test_index_from_values(false, &values)?;
+        let searcher = index.reader()?.searcher();
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "mypercentiles": {
+                "percentiles": {
+                    "field": "score",
+                    "percents": [ 0, 50, 100 ],
+                    "tdigest": {}
+                }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let agg_res: AggregationResults = searcher.search(&AllQuery, &collector)?;
+        let res: Value = serde_json::to_value(agg_res)?;
+        assert_eq!(
+            res["mypercentiles"]["values"],
+            json!({ "0.0": 1.0, "50.0": 3.0, "100.0": 5.0 })
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "mypercentiles": {
+                "percentiles": {
+                    "field": "score",
+                    "tdigest": { "compression": 0.0 }
+                }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let err = searcher.search(&AllQuery, &collector).unwrap_err();
+        assert!(err.to_string().contains("compression"));
+        Ok(())
+    }
 }
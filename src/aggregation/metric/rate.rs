@@ -0,0 +1,455 @@
+use std::fmt::Debug;
+
+use columnar::ColumnType;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::agg_req::{AggregationVariants, Aggregations};
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::agg_result::{AggregationResult, BucketEntry, MetricResult};
+use crate::aggregation::bucket::DateHistogramAggregationReq;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::aggregation::{f64_from_fastfield_u64, AggregationError, Key};
+use crate::{DocId, TantivyError};
+
+/// A single-value metric aggregation that computes a rate per unit of time in the buckets of a
+/// `date_histogram` aggregation, of which it has to be a direct sub-aggregation.
+/// See [super::SingleMetricResult] for return value.
+///
+/// Without a `field`, the rate is the number of documents of the bucket per `unit`. With a
+/// `field`, it is the sum of its values, or the number of its values with the `value_count`
+/// [mode](RateMode), per `unit`.
+///
+/// The value of a bucket is divided by the duration of the bucket, which varies with a
+/// `calendar_interval`, e.g. a monthly bucket lasts 28 to 31 days. If the date histogram has
+/// `hard_bounds`, only the part of the first and last buckets inside the bounds is taken into
+/// account, so that the rate of these partial buckets is not underestimated.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": {
+///             "field": "date",
+///             "calendar_interval": "month"
+///         },
+///         "aggs": {
+///             "sales_per_day": {
+///                 "rate": {
+///                     "field": "price",
+///                     "unit": "day"
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RateAggregation {
+    /// The field to compute the rate of. The documents are counted if it is not set.
+    pub field: Option<String>,
+    /// The unit of time of the rate.
+    pub unit: RateUnit,
+    /// How the values of the field are accumulated, `sum` by default. It can only be set along
+    /// with a `field`.
+    pub mode: Option<RateMode>,
+}
+
+/// The unit of time of a [`RateAggregation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateUnit {
+    /// Rate per second.
+    #[serde(rename = "second")]
+    Second,
+    /// Rate per minute.
+    #[serde(rename = "minute")]
+    Minute,
+    /// Rate per hour.
+    #[serde(rename = "hour")]
+    Hour,
+    /// Rate per day.
+    #[serde(rename = "day")]
+    Day,
+    /// Rate per week.
+    #[serde(rename = "week")]
+    Week,
+}
+
+impl RateUnit {
+    fn duration_ms(self) -> f64 {
+        let seconds = match self {
+            RateUnit::Second => 1,
+            RateUnit::Minute => 60,
+            RateUnit::Hour => 60 * 60,
+            RateUnit::Day => 24 * 60 * 60,
+            RateUnit::Week => 7 * 24 * 60 * 60,
+        };
+        (seconds * 1000) as f64
+    }
+}
+
+/// How the values of the field of a [`RateAggregation`] are accumulated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateMode {
+    /// The values are summed up.
+    #[default]
+    #[serde(rename = "sum")]
+    Sum,
+    /// The values are counted.
+    #[serde(rename = "value_count")]
+    ValueCount,
+}
+
+impl RateAggregation {
+    /// Creates a new [`RateAggregation`] instance counting the documents per `unit`.
+    pub fn from_unit(unit: RateUnit) -> Self {
+        Self {
+            field: None,
+            unit,
+            mode: None,
+        }
+    }
+
+    /// Returns the field name the aggregation is computed on, if any.
+    pub fn field_name(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.field.is_none() && self.mode.is_some() {
+            return Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(
+                    "mode of rate aggregation can only be set along with a field".to_string(),
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the rate aggregations of the request tree are direct sub-aggregations of a
+/// `date_histogram` aggregation.
+pub(crate) fn validate_rate_aggregations(
+    aggs: &Aggregations,
+    parent: Option<&AggregationVariants>,
+) -> crate::Result<()> {
+    for (name, agg) in aggs {
+        if matches!(agg.agg, AggregationVariants::Rate(_))
+            && !matches!(parent, Some(AggregationVariants::DateHistogram(_)))
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "rate aggregation {name:?} must be a sub-aggregation of a date_histogram \
+                 aggregation"
+            )));
+        }
+        validate_rate_aggregations(&agg.sub_aggregation, Some(&agg.agg))?;
+    }
+    Ok(())
+}
+
+/// Divides the values of the rate sub-aggregations of the buckets of a date histogram by the
+/// duration of the buckets.
+///
+/// The keys of the buckets are expected to be in milliseconds.
+pub(crate) fn apply_rate_aggregations(
+    buckets: &mut [BucketEntry],
+    req: &DateHistogramAggregationReq,
+    sub_aggregation_req: &Aggregations,
+) -> crate::Result<()> {
+    let rates: Vec<(&str, &RateAggregation)> = sub_aggregation_req
+        .iter()
+        .filter_map(|(name, agg)| match &agg.agg {
+            AggregationVariants::Rate(rate) => Some((name.as_str(), rate)),
+            _ => None,
+        })
+        .collect();
+    if rates.is_empty() {
+        return Ok(());
+    }
+    let histogram_req = req.to_histogram_req()?;
+    let date_rounding = req.to_date_rounding()?;
+    for bucket in buckets {
+        let Key::F64(mut start) = bucket.key else {
+            continue;
+        };
+        let mut end = if let Some(date_rounding) = date_rounding.as_ref() {
+            date_rounding.next_bucket((start * 1_000_000.0) as i64) as f64 / 1_000_000.0
+        } else {
+            start + histogram_req.interval
+        };
+        if let Some(hard_bounds) = histogram_req.hard_bounds {
+            start = start.max(hard_bounds.min);
+            end = end.min(hard_bounds.max);
+        }
+        let duration = end - start;
+        for (name, rate) in &rates {
+            if let Some(AggregationResult::MetricResult(MetricResult::Rate(result))) =
+                bucket.sub_aggregation.0.get_mut(*name)
+            {
+                result.value = result
+                    .value
+                    .filter(|_| duration > 0.0)
+                    .map(|value| value * rate.unit.duration_ms() / duration);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Intermediate result of the rate aggregation that can be combined with other intermediate
+/// results.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateRate {
+    /// The sum or the count of the bucket, not yet divided by the duration of the bucket.
+    value: f64,
+}
+
+impl IntermediateRate {
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateRate) {
+        self.value += other.value;
+    }
+    /// Computes the value of the bucket, which is divided by the duration of the bucket by the
+    /// parent date histogram.
+    pub fn finalize(&self) -> Option<f64> {
+        Some(self.value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SegmentRateCollector {
+    /// `None` if the documents are counted.
+    mode: Option<RateMode>,
+    field_type: ColumnType,
+    value: f64,
+    accessor_idx: usize,
+}
+
+impl SegmentRateCollector {
+    pub fn from_req(req: &RateAggregation, field_type: ColumnType, accessor_idx: usize) -> Self {
+        let mode = req.field.as_ref().map(|_| req.mode.unwrap_or_default());
+        Self {
+            mode,
+            field_type,
+            value: 0.0,
+            accessor_idx,
+        }
+    }
+}
+
+impl SegmentAggregationCollector for SegmentRateCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(IntermediateMetricResult::Rate(Box::new(
+                IntermediateRate { value: self.value },
+            ))),
+        )
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let Some(mode) = self.mode else {
+            self.value += docs.len() as f64;
+            return Ok(());
+        };
+        let agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        agg_accessor
+            .column_block_accessor
+            .fetch_block(docs, &agg_accessor.accessor);
+        match mode {
+            RateMode::Sum => {
+                for val in agg_accessor.column_block_accessor.iter_vals() {
+                    self.value += f64_from_fastfield_u64(val, &self.field_type);
+                }
+            }
+            RateMode::ValueCount => {
+                self.value += agg_accessor.column_block_accessor.iter_vals().count() as f64;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST};
+    use crate::{DateTime, TantivyDocument};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let date = schema_builder.add_date_field("date", FAST);
+        let bytes = schema_builder.add_u64_field("bytes", FAST);
+        // 2024-02-10T00:10:00Z, 00:20, 00:40 and 02:20.
+        let segment_and_docs = vec![
+            vec![
+                doc!(date => DateTime::from_timestamp_secs(1707523800), bytes => 120u64),
+                doc!(date => DateTime::from_timestamp_secs(1707524400), bytes => 240u64),
+            ],
+            vec![
+                doc!(date => DateTime::from_timestamp_secs(1707525600)),
+                doc!(date => DateTime::from_timestamp_secs(1707531600), bytes => 1800u64),
+            ],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn rate_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "per_hour": {
+                    "date_histogram": { "field": "date", "fixed_interval": "1h" },
+                    "aggs": {
+                        "docs_per_minute": { "rate": { "unit": "minute" } },
+                        "bytes_per_minute": { "rate": { "field": "bytes", "unit": "minute" } },
+                        "values_per_hour": {
+                            "rate": { "field": "bytes", "unit": "hour", "mode": "value_count" }
+                        },
+                        "bytes_per_minute_derivative": {
+                            "derivative": { "buckets_path": "bytes_per_minute" }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            let buckets = &res["per_hour"]["buckets"];
+            assert_eq!(buckets[0]["doc_count"], 3);
+            assert_eq!(buckets[0]["docs_per_minute"]["value"], 0.05);
+            assert_eq!(buckets[0]["bytes_per_minute"]["value"], 6.0);
+            assert_eq!(buckets[0]["values_per_hour"]["value"], 2.0);
+            assert_eq!(buckets[1]["doc_count"], 0);
+            assert_eq!(buckets[1]["docs_per_minute"]["value"], 0.0);
+            assert_eq!(buckets[1]["bytes_per_minute"]["value"], 0.0);
+            assert_eq!(buckets[1]["bytes_per_minute_derivative"]["value"], -6.0);
+            assert_eq!(buckets[2]["bytes_per_minute"]["value"], 30.0);
+            assert_eq!(buckets[2]["values_per_hour"]["value"], 1.0);
+            assert_eq!(buckets[2]["bytes_per_minute_derivative"]["value"], 30.0);
+
+            // The monthly bucket of February 2024 lasts 29 days.
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "per_month": {
+                    "date_histogram": { "field": "date", "calendar_interval": "month" },
+                    "aggs": {
+                        "docs_per_day": { "rate": { "unit": "day" } }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["per_month"]["buckets"][0]["key_as_string"],
+                "2024-02-01T00:00:00Z"
+            );
+            assert_eq!(
+                res["per_month"]["buckets"][0]["docs_per_day"]["value"],
+                4.0 / 29.0
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rate_partial_buckets_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        // The bounds only cover the last 30 minutes of the first bucket and the first 30 minutes
+        // of the last bucket.
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "per_hour": {
+                "date_histogram": {
+                    "field": "date",
+                    "fixed_interval": "1h",
+                    "hard_bounds": { "min": 1707525000000i64, "max": 1707532200000i64 }
+                },
+                "aggs": {
+                    "docs_per_minute": { "rate": { "unit": "minute" } },
+                    "bytes_per_minute": { "rate": { "field": "bytes", "unit": "minute" } }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        let buckets = &res["per_hour"]["buckets"];
+        assert_eq!(buckets[0]["doc_count"], 1);
+        assert_eq!(buckets[0]["docs_per_minute"]["value"], 1.0 / 30.0);
+        assert_eq!(buckets[1]["docs_per_minute"]["value"], 0.0);
+        assert_eq!(buckets[2]["doc_count"], 1);
+        assert_eq!(buckets[2]["bytes_per_minute"]["value"], 60.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rate_invalid_request_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "per_hour": {
+                "date_histogram": { "field": "date", "fixed_interval": "1h" },
+                "aggs": {
+                    "docs_per_minute": { "rate": { "unit": "minute", "mode": "sum" } }
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "InvalidRequest: \"mode of rate aggregation can only be set along with a field\""
+        );
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "docs_per_minute": { "rate": { "unit": "minute" } }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'rate aggregation \"docs_per_minute\" must be a \
+             sub-aggregation of a date_histogram aggregation'"
+        );
+
+        let res: serde_json::Result<Aggregations> = serde_json::from_value(json!({
+            "per_hour": {
+                "date_histogram": { "field": "date", "fixed_interval": "1h" },
+                "aggs": {
+                    "docs_per_month": { "rate": { "unit": "month" } }
+                }
+            }
+        }));
+        assert!(res.is_err());
+
+        Ok(())
+    }
+}
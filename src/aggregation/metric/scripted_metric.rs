@@ -0,0 +1,462 @@
+use std::any::Any;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use columnar::{Column, NumericalType};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::ScriptedMetricResult;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::index::SegmentReader;
+use crate::schema::OwnedValue;
+use crate::DocId;
+
+/// # Scripted Metric
+///
+/// The scripted metric aggregation computes a custom metric with user supplied closures, which
+/// run inside the segment traversal of the aggregation framework:
+/// - `init` creates the state of a segment (or of a bucket of a segment).
+/// - `map` is called with the state for each document, along with the fast field columns of the
+///   `fields` of the aggregation, in the same order.
+/// - `combine` turns the state of a segment into a partial result.
+/// - `reduce` computes the final value of the metric from the partial results of all segments.
+///
+/// The columns are numeric fast field columns converted to `f64`. A field without numeric values
+/// in a segment is seen as an empty column.
+///
+/// The partial results are serialized to JSON in the intermediate result, so that they can be
+/// merged across indices with the `DistributedAggregationCollector`. Note that non finite floats
+/// are serialized as `null`.
+///
+/// Since it holds closures, the aggregation can only be built with
+/// [`ScriptedMetricAggregation::new`]. It can't be serialized or deserialized, but it can be added
+/// to a deserialized request, e.g. as a sub aggregation of a `terms` aggregation.
+///
+/// The result is the value returned by `reduce`:
+/// ```json
+/// { "value": 42.0 }
+/// ```
+///
+/// # Example
+/// ```
+/// use tantivy::aggregation::metric::ScriptedMetricAggregation;
+/// use tantivy::schema::OwnedValue;
+///
+/// // The spread between the largest and the smallest price.
+/// let spread = ScriptedMetricAggregation::new(
+///     vec!["price".to_string()],
+///     || None,
+///     |min_max: &mut Option<(f64, f64)>, doc, columns| {
+///         for price in columns[0].values_for_doc(doc) {
+///             let (min, max) = min_max.get_or_insert((price, price));
+///             *min = min.min(price);
+///             *max = max.max(price);
+///         }
+///     },
+///     |min_max| min_max,
+///     |min_maxs| {
+///         min_maxs
+///             .into_iter()
+///             .flatten()
+///             .reduce(|(min1, max1), (min2, max2)| (min1.min(min2), max1.max(max2)))
+///             .map(|(min, max)| OwnedValue::F64(max - min))
+///             .unwrap_or(OwnedValue::Null)
+///     },
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ScriptedMetricAggregation {
+    fields: Vec<String>,
+    script: Arc<dyn Script>,
+}
+
+impl ScriptedMetricAggregation {
+    /// Creates a scripted metric aggregation on `fields`, from its `init`, `map`, `combine` and
+    /// `reduce` closures.
+    pub fn new<S, C>(
+        fields: Vec<String>,
+        init: impl Fn() -> S + Send + Sync + 'static,
+        map: impl Fn(&mut S, DocId, &[Column<f64>]) + Send + Sync + 'static,
+        combine: impl Fn(S) -> C + Send + Sync + 'static,
+        reduce: impl Fn(Vec<C>) -> OwnedValue + Send + Sync + 'static,
+    ) -> Self
+    where
+        S: Clone + 'static,
+        C: Serialize + DeserializeOwned + 'static,
+    {
+        let script = Closures {
+            init,
+            map,
+            combine,
+            reduce,
+            _types: PhantomData,
+        };
+        Self {
+            fields,
+            script: Arc::new(script),
+        }
+    }
+
+    /// Return the fields passed to the `map` closure.
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.iter().map(String::as_str).collect()
+    }
+
+    /// Opens the columns of the fields in a segment, in the order of the fields.
+    pub(crate) fn columns(&self, reader: &SegmentReader) -> crate::Result<Vec<Column<f64>>> {
+        self.fields
+            .iter()
+            .map(|field_name| {
+                for handle in reader.fast_fields().dynamic_column_handles(field_name)? {
+                    let column = handle.open()?.coerce_numerical(NumericalType::F64);
+                    if let Some(column) = column.and_then(Into::into) {
+                        return Ok(column);
+                    }
+                }
+                Ok(Column::build_empty_column(reader.max_doc()))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for ScriptedMetricAggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScriptedMetricAggregation")
+            .field("fields", &self.fields)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Two requests are equal if they share the same closures.
+impl PartialEq for ScriptedMetricAggregation {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields && Arc::ptr_eq(&self.script, &other.script)
+    }
+}
+
+impl Serialize for ScriptedMetricAggregation {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(
+            "scripted_metric aggregation can't be serialized",
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for ScriptedMetricAggregation {
+    fn deserialize<D: Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom(
+            "scripted_metric aggregation can't be deserialized, it needs to be built with \
+             `ScriptedMetricAggregation::new`",
+        ))
+    }
+}
+
+/// The closures of a [`ScriptedMetricAggregation`], with their state and partial result types
+/// erased.
+trait Script: Send + Sync {
+    fn init(&self) -> Box<dyn Any>;
+    fn clone_state(&self, state: &dyn Any) -> Box<dyn Any>;
+    fn map(&self, state: &mut dyn Any, doc: DocId, columns: &[Column<f64>]);
+    /// Returns the partial result of the state, serialized to JSON.
+    fn combine(&self, state: Box<dyn Any>) -> crate::Result<String>;
+    fn reduce(&self, partial_results: &[String]) -> crate::Result<OwnedValue>;
+}
+
+struct Closures<S, C, I, M, Co, R> {
+    init: I,
+    map: M,
+    combine: Co,
+    reduce: R,
+    _types: PhantomData<fn(S) -> C>,
+}
+
+impl<S, C, I, M, Co, R> Script for Closures<S, C, I, M, Co, R>
+where
+    S: Clone + 'static,
+    C: Serialize + DeserializeOwned + 'static,
+    I: Fn() -> S + Send + Sync,
+    M: Fn(&mut S, DocId, &[Column<f64>]) + Send + Sync,
+    Co: Fn(S) -> C + Send + Sync,
+    R: Fn(Vec<C>) -> OwnedValue + Send + Sync,
+{
+    fn init(&self) -> Box<dyn Any> {
+        Box::new((self.init)())
+    }
+
+    fn clone_state(&self, state: &dyn Any) -> Box<dyn Any> {
+        let state = state
+            .downcast_ref::<S>()
+            .expect("state of scripted_metric has the wrong type");
+        Box::new(state.clone())
+    }
+
+    fn map(&self, state: &mut dyn Any, doc: DocId, columns: &[Column<f64>]) {
+        let state = state
+            .downcast_mut::<S>()
+            .expect("state of scripted_metric has the wrong type");
+        (self.map)(state, doc, columns)
+    }
+
+    fn combine(&self, state: Box<dyn Any>) -> crate::Result<String> {
+        let state = *state
+            .downcast::<S>()
+            .expect("state of scripted_metric has the wrong type");
+        Ok(serde_json::to_string(&(self.combine)(state))?)
+    }
+
+    fn reduce(&self, partial_results: &[String]) -> crate::Result<OwnedValue> {
+        let partial_results = partial_results
+            .iter()
+            .map(|partial_result| serde_json::from_str(partial_result))
+            .collect::<serde_json::Result<Vec<C>>>()?;
+        Ok((self.reduce)(partial_results))
+    }
+}
+
+/// Intermediate result of the scripted_metric aggregation that can be combined with other
+/// intermediate results.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateScriptedMetric {
+    /// The partial results of the segments, serialized to JSON.
+    partial_results: Vec<String>,
+}
+
+impl IntermediateScriptedMetric {
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateScriptedMetric) {
+        self.partial_results.extend(other.partial_results);
+    }
+
+    pub(crate) fn into_final_result(
+        self,
+        req: &ScriptedMetricAggregation,
+    ) -> crate::Result<ScriptedMetricResult> {
+        let value = req.script.reduce(&self.partial_results)?;
+        Ok(ScriptedMetricResult { value })
+    }
+}
+
+pub(crate) struct SegmentScriptedMetricCollector {
+    script: Arc<dyn Script>,
+    state: Box<dyn Any>,
+    columns: Vec<Column<f64>>,
+    accessor_idx: usize,
+}
+
+impl SegmentScriptedMetricCollector {
+    pub fn from_req(
+        req: &ScriptedMetricAggregation,
+        columns: Vec<Column<f64>>,
+        accessor_idx: usize,
+    ) -> Self {
+        Self {
+            script: req.script.clone(),
+            state: req.script.init(),
+            columns,
+            accessor_idx,
+        }
+    }
+}
+
+impl Clone for SegmentScriptedMetricCollector {
+    fn clone(&self) -> Self {
+        Self {
+            script: self.script.clone(),
+            state: self.script.clone_state(self.state.as_ref()),
+            columns: self.columns.clone(),
+            accessor_idx: self.accessor_idx,
+        }
+    }
+}
+
+impl fmt::Debug for SegmentScriptedMetricCollector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SegmentScriptedMetricCollector")
+            .field("accessor_idx", &self.accessor_idx)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SegmentAggregationCollector for SegmentScriptedMetricCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let partial_result = self.script.combine(self.state)?;
+        let intermediate_result =
+            IntermediateMetricResult::ScriptedMetric(Box::new(IntermediateScriptedMetric {
+                partial_results: vec![partial_result],
+            }));
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(intermediate_result),
+        )
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        _agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        for &doc in docs {
+            self.script.map(self.state.as_mut(), doc, &self.columns);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::ScriptedMetricAggregation;
+    use crate::aggregation::agg_req::{Aggregation, AggregationVariants, Aggregations};
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{OwnedValue, Schema, FAST, STRING};
+    use crate::TantivyDocument;
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let host = schema_builder.add_text_field("host", STRING | FAST);
+        let latency = schema_builder.add_f64_field("latency", FAST);
+        let retries = schema_builder.add_u64_field("retries", FAST);
+        let segment_and_docs = vec![
+            vec![
+                doc!(host => "a", latency => 1.0, retries => 1u64),
+                doc!(host => "b", latency => 2.0),
+                doc!(host => "a", latency => 10.0, retries => 2u64),
+            ],
+            vec![
+                doc!(host => "b", latency => 5.0, retries => 3u64),
+                doc!(host => "a"),
+            ],
+            vec![doc!(host => "a", latency => 4.0)],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    /// The spread between the largest and the smallest value of a field.
+    fn spread(field: &str) -> Aggregation {
+        let scripted_metric = ScriptedMetricAggregation::new(
+            vec![field.to_string()],
+            || None,
+            |min_max: &mut Option<(f64, f64)>, doc, columns| {
+                for val in columns[0].values_for_doc(doc) {
+                    let (min, max) = min_max.get_or_insert((val, val));
+                    *min = min.min(val);
+                    *max = max.max(val);
+                }
+            },
+            |min_max| min_max,
+            |min_maxs| {
+                min_maxs
+                    .into_iter()
+                    .flatten()
+                    .reduce(|(min1, max1), (min2, max2)| (min1.min(min2), max1.max(max2)))
+                    .map(|(min, max)| OwnedValue::F64(max - min))
+                    .unwrap_or(OwnedValue::Null)
+            },
+        );
+        Aggregation {
+            agg: AggregationVariants::ScriptedMetric(scripted_metric),
+            sub_aggregation: Default::default(),
+        }
+    }
+
+    /// Counts the documents of each segment with a value in all the fields.
+    fn docs_with_all_fields(fields: &[&str]) -> Aggregation {
+        let scripted_metric = ScriptedMetricAggregation::new(
+            fields.iter().map(|field| field.to_string()).collect(),
+            || 0u64,
+            |count, doc, columns| {
+                if columns.iter().all(|column| column.first(doc).is_some()) {
+                    *count += 1;
+                }
+            },
+            |count| count,
+            |counts: Vec<u64>| OwnedValue::U64(counts.iter().sum()),
+        );
+        Aggregation {
+            agg: AggregationVariants::ScriptedMetric(scripted_metric),
+            sub_aggregation: Default::default(),
+        }
+    }
+
+    #[test]
+    fn scripted_metric_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let mut agg_req: Aggregations = serde_json::from_value(json!({
+                "hosts": { "terms": { "field": "host", "order": { "latency_spread": "asc" } } }
+            }))
+            .unwrap();
+            agg_req.insert("latency_spread".to_string(), spread("latency"));
+            agg_req.insert(
+                "complete".to_string(),
+                docs_with_all_fields(&["latency", "retries"]),
+            );
+            agg_req.insert("missing_spread".to_string(), spread("missing"));
+            let hosts = agg_req.get_mut("hosts").unwrap();
+            hosts
+                .sub_aggregation
+                .insert("latency_spread".to_string(), spread("latency"));
+            hosts
+                .sub_aggregation
+                .insert("retries_spread".to_string(), spread("retries"));
+
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(res["latency_spread"]["value"], 9.0);
+            assert_eq!(res["complete"]["value"], 3);
+            assert_eq!(res["missing_spread"]["value"], Value::Null);
+
+            // The terms are ordered by the spread of their latency.
+            assert_eq!(res["hosts"]["buckets"][0]["key"], "b");
+            assert_eq!(res["hosts"]["buckets"][0]["latency_spread"]["value"], 3.0);
+            assert_eq!(res["hosts"]["buckets"][0]["retries_spread"]["value"], 0.0);
+            assert_eq!(res["hosts"]["buckets"][1]["key"], "a");
+            assert_eq!(res["hosts"]["buckets"][1]["latency_spread"]["value"], 9.0);
+            assert_eq!(res["hosts"]["buckets"][1]["retries_spread"]["value"], 1.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn scripted_metric_serde_test() {
+        let agg_req: serde_json::Result<Aggregations> = serde_json::from_value(json!({
+            "spread": { "scripted_metric": { "fields": ["latency"] } }
+        }));
+        assert!(agg_req
+            .unwrap_err()
+            .to_string()
+            .contains("scripted_metric aggregation can't be deserialized"));
+
+        let agg_req: Aggregations = [("spread".to_string(), spread("latency"))].into();
+        assert_eq!(agg_req.clone(), agg_req);
+        assert_ne!(
+            agg_req.get("spread").unwrap(),
+            &spread("latency"),
+            "requests with different closures are not equal"
+        );
+        assert!(serde_json::to_string(&agg_req).is_err());
+    }
+}
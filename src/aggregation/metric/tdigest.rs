@@ -0,0 +1,233 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest, as described in "Computing Extremely Accurate Quantiles Using t-Digests"
+/// by Ted Dunning and Otmar Ertl.
+///
+/// The values are summarized by centroids, which are small around the extreme quantiles and
+/// larger around the median, so that the tail percentiles are estimated accurately. The
+/// `compression` bounds the number of centroids: a higher compression gives a more accurate
+/// estimate at the cost of more memory.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct TDigest {
+    compression: f64,
+    // Sorted by mean.
+    centroids: Vec<Centroid>,
+    // Values and centroids added since the last compression.
+    unmerged: Vec<Centroid>,
+    total_weight: f64,
+    min: f64,
+    max: f64,
+    // The centroids are merged alternately from the lowest and from the highest mean, which
+    // avoids biasing the estimate towards one of the tails.
+    merge_from_highest: bool,
+}
+
+impl TDigest {
+    pub(crate) fn new(compression: f64) -> TDigest {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            unmerged: Vec::new(),
+            total_weight: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            merge_from_highest: false,
+        }
+    }
+
+    /// Returns the number of values added to the digest.
+    pub(crate) fn count(&self) -> u64 {
+        self.total_weight as u64
+    }
+
+    pub(crate) fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.unmerged.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        self.total_weight += 1.0;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.unmerged.len() >= self.buffer_capacity() {
+            self.compress();
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: &TDigest) {
+        if other.total_weight == 0.0 {
+            return;
+        }
+        self.unmerged.extend_from_slice(&other.centroids);
+        self.unmerged.extend_from_slice(&other.unmerged);
+        self.total_weight += other.total_weight;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.compress();
+    }
+
+    /// Estimates the value at quantile `q`, within `[0, 1]`.
+    ///
+    /// Returns `None` if the digest is empty.
+    pub(crate) fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+        let first = self.centroids.first()?;
+        let last = self.centroids.last()?;
+        let index = q * self.total_weight;
+        if index <= 0.0 {
+            return Some(self.min);
+        }
+        if index >= self.total_weight {
+            return Some(self.max);
+        }
+        // The weight of a centroid is considered to be spread around its mean, so the quantiles
+        // are interpolated between the means of the centroids, and between the extreme
+        // centroids and the min and max values.
+        if index < first.weight / 2.0 {
+            return Some(self.min + (first.mean - self.min) * index / (first.weight / 2.0));
+        }
+        let mut weight_so_far = first.weight / 2.0;
+        for (left, right) in self.centroids.iter().zip(self.centroids.iter().skip(1)) {
+            let weight_between_means = (left.weight + right.weight) / 2.0;
+            if index < weight_so_far + weight_between_means {
+                let ratio = (index - weight_so_far) / weight_between_means;
+                return Some(left.mean + (right.mean - left.mean) * ratio);
+            }
+            weight_so_far += weight_between_means;
+        }
+        let ratio = (index - weight_so_far) / (last.weight / 2.0);
+        Some((last.mean + (self.max - last.mean) * ratio).min(self.max))
+    }
+
+    fn buffer_capacity(&self) -> usize {
+        ((self.compression * 5.0).ceil() as usize).max(1)
+    }
+
+    /// Returns the highest quantile a centroid starting at quantile `q` can extend to, according
+    /// to the `k1` scale function `k(q) = compression / (2 * PI) * asin(2q - 1)`: a centroid
+    /// spans at most one unit of `k`.
+    fn quantile_limit(&self, q: f64) -> f64 {
+        let angle = (2.0 * q - 1.0).clamp(-1.0, 1.0).asin() + 2.0 * PI / self.compression;
+        (angle.min(FRAC_PI_2).sin() + 1.0) / 2.0
+    }
+
+    fn compress(&mut self) {
+        if self.unmerged.is_empty() {
+            return;
+        }
+        let mut centroids = std::mem::take(&mut self.centroids);
+        centroids.append(&mut self.unmerged);
+        if self.merge_from_highest {
+            centroids.sort_by(|left, right| right.mean.total_cmp(&left.mean));
+        } else {
+            centroids.sort_by(|left, right| left.mean.total_cmp(&right.mean));
+        }
+
+        let mut merged_centroids = Vec::new();
+        let mut centroids_it = centroids.into_iter();
+        let Some(mut current) = centroids_it.next() else {
+            return;
+        };
+        let mut weight_so_far = 0.0;
+        let mut weight_limit = self.total_weight * self.quantile_limit(0.0);
+        for centroid in centroids_it {
+            if weight_so_far + current.weight + centroid.weight <= weight_limit {
+                current.weight += centroid.weight;
+                current.mean += (centroid.mean - current.mean) * centroid.weight / current.weight;
+            } else {
+                weight_so_far += current.weight;
+                merged_centroids.push(current);
+                weight_limit =
+                    self.total_weight * self.quantile_limit(weight_so_far / self.total_weight);
+                current = centroid;
+            }
+        }
+        merged_centroids.push(current);
+        if self.merge_from_highest {
+            merged_centroids.reverse();
+        }
+        self.centroids = merged_centroids;
+        self.merge_from_highest = !self.merge_from_highest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::TDigest;
+
+    fn exact_quantile(sorted_values: &[f64], q: f64) -> f64 {
+        let index = ((sorted_values.len() - 1) as f64 * q).round() as usize;
+        sorted_values[index]
+    }
+
+    #[test]
+    fn test_tdigest_empty() {
+        let mut digest = TDigest::new(100.0);
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(digest.count(), 0);
+    }
+
+    #[test]
+    fn test_tdigest_small() {
+        let mut digest = TDigest::new(100.0);
+        for value in [5.0, 1.0, 3.0] {
+            digest.add(value);
+        }
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(0.5), Some(3.0));
+        assert_eq!(digest.quantile(1.0), Some(5.0));
+    }
+
+    #[test]
+    fn test_tdigest_accuracy_and_merge() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut values: Vec<f64> = (0..100_000)
+            .map(|_| rng.gen_range(0.0f64..1.0).powi(3) * 1_000.0)
+            .collect();
+        let mut digest = TDigest::new(100.0);
+        let mut merged_digest = TDigest::new(100.0);
+        for chunk in values.chunks(7_000) {
+            let mut chunk_digest = TDigest::new(100.0);
+            for &value in chunk {
+                digest.add(value);
+                chunk_digest.add(value);
+            }
+            merged_digest.merge(&chunk_digest);
+        }
+        assert_eq!(digest.count(), 100_000);
+        assert_eq!(merged_digest.count(), 100_000);
+        // The number of centroids is bounded by the compression.
+        assert!(merged_digest.centroids.len() <= 100);
+
+        values.sort_by(|left, right| left.total_cmp(right));
+        for q in [0.01f64, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99, 0.999] {
+            // The error is bounded in rank rather than in value, by about the rank span of a
+            // centroid, which is lower for the extreme quantiles.
+            let max_rank_error = 2.0 * PI * (q * (1.0 - q)).sqrt() / 100.0;
+            for estimate in [digest.quantile(q), merged_digest.quantile(q)] {
+                let estimate = estimate.unwrap();
+                let low = exact_quantile(&values, (q - max_rank_error).max(0.0));
+                let high = exact_quantile(&values, (q + max_rank_error).min(1.0));
+                assert!(
+                    (low..=high).contains(&estimate),
+                    "q={q} estimate={estimate} expected within [{low}, {high}]"
+                );
+            }
+        }
+    }
+}
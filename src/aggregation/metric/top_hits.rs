@@ -16,8 +16,9 @@ use crate::aggregation::intermediate_agg_result::{
 use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
 use crate::aggregation::AggregationError;
 use crate::collector::TopNComputer;
-use crate::schema::OwnedValue;
-use crate::{DocAddress, DocId, SegmentOrdinal};
+use crate::schema::{Field, OwnedValue};
+use crate::store::StoreReader;
+use crate::{DocAddress, DocId, SegmentOrdinal, SegmentReader, TantivyDocument};
 
 /// # Top Hits
 ///
@@ -33,12 +34,12 @@ use crate::{DocAddress, DocId, SegmentOrdinal};
 /// used as a sub-aggregation, inside a `terms` aggregation or a `filters` aggregation,
 /// for example.
 ///
-/// Note that this aggregator does not return the actual document addresses, but
-/// rather a list of the values of the fields that were requested to be retrieved.
-/// These values can be specified in the `docvalue_fields` parameter, which can include
-/// a list of fast fields to be retrieved. At the moment, only fast fields are supported
-/// but it is possible that we support the `fields` parameter to retrieve any stored
-/// field in the future.
+/// Each hit contains the address of the document, along with the values of the fields
+/// that were requested to be retrieved:
+/// - the `docvalue_fields` parameter can include a list of fast fields, or globs matching fast
+///   fields, to be retrieved.
+/// - the `stored_fields` parameter can include a list of stored fields to be retrieved from the doc
+///   store. Fetching them is more expensive than fetching fast fields.
 ///
 /// The following example demonstrates a request for the top_hits aggregation:
 /// ```JSON
@@ -57,7 +58,8 @@ use crate::{DocAddress, DocId, SegmentOrdinal};
 ///                 "sort": [
 ///                     { "date": "desc" }
 ///                 ]
-///                 "docvalue_fields": ["date", "title", "iden"]
+///                 "docvalue_fields": ["date", "title", "iden"],
+///                 "stored_fields": ["body"]
 ///             }
 ///         }
 /// }
@@ -75,7 +77,11 @@ use crate::{DocAddress, DocId, SegmentOrdinal};
 ///             "date": "<date_RFC3339>",
 ///             "title": "<title>",
 ///             "iden": "<iden>"
-///           }
+///           },
+///           "stored_fields": {
+///             "body": ["<body>"]
+///           },
+///           "doc_address": { "segment_ord": <segment_ord>, "doc_id": <doc_id> }
 ///         },
 ///         {
 ///           "score": [<time_u64>]
@@ -83,7 +89,11 @@ use crate::{DocAddress, DocId, SegmentOrdinal};
 ///             "date": "<date_RFC3339>",
 ///             "title": "<title>",
 ///             "iden": "<iden>"
-///           }
+///           },
+///           "stored_fields": {
+///             "body": ["<body>"]
+///           },
+///           "doc_address": { "segment_ord": <segment_ord>, "doc_id": <doc_id> }
 ///         }
 ///     ]
 /// }
@@ -98,6 +108,9 @@ pub struct TopHitsAggregationReq {
     #[serde(default)]
     doc_value_fields: Vec<String>,
 
+    #[serde(default)]
+    stored_fields: Vec<String>,
+
     // Not supported
     _source: Option<serde_json::Value>,
     fields: Option<serde_json::Value>,
@@ -108,9 +121,9 @@ pub struct TopHitsAggregationReq {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
-struct KeyOrder {
-    field: String,
-    order: Order,
+pub(crate) struct KeyOrder {
+    pub(crate) field: String,
+    pub(crate) order: Order,
 }
 
 impl Serialize for KeyOrder {
@@ -124,15 +137,16 @@ impl Serialize for KeyOrder {
 
 impl<'de> Deserialize<'de> for KeyOrder {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where D: Deserializer<'de> {
+    where
+        D: Deserializer<'de>,
+    {
         let mut key_order = <HashMap<String, Order>>::deserialize(deserializer)?.into_iter();
         let (field, order) = key_order.next().ok_or(serde::de::Error::custom(
-            "Expected exactly one key-value pair in sort parameter of top_hits, found none",
+            "Expected exactly one key-value pair in sort parameter, found none",
         ))?;
         if key_order.next().is_some() {
             return Err(serde::de::Error::custom(format!(
-                "Expected exactly one key-value pair in sort parameter of top_hits, found \
-                 {key_order:?}"
+                "Expected exactly one key-value pair in sort parameter, found {key_order:?}"
             )));
         }
         Ok(Self { field, order })
@@ -151,8 +165,8 @@ fn globbed_string_to_regex(glob: &str) -> Result<Regex, crate::TantivyError> {
 fn use_doc_value_fields_err(parameter: &str) -> crate::Result<()> {
     Err(crate::TantivyError::AggregationError(
         AggregationError::InvalidRequest(format!(
-            "The `{parameter}` parameter is not supported, only `docvalue_fields` is supported in \
-             `top_hits` aggregation"
+            "The `{parameter}` parameter is not supported, only `docvalue_fields` and \
+             `stored_fields` are supported in `top_hits` aggregation"
         )),
     ))
 }
@@ -237,6 +251,11 @@ impl TopHitsAggregationReq {
         self.doc_value_fields.iter().map(|s| s.as_str()).collect()
     }
 
+    /// Return the stored fields retrieved by the aggregator from the doc store.
+    pub fn stored_field_names(&self) -> Vec<&str> {
+        self.stored_fields.iter().map(|s| s.as_str()).collect()
+    }
+
     fn get_document_field_data(
         &self,
         accessors: &HashMap<String, Vec<DynamicColumn>>,
@@ -250,62 +269,7 @@ impl TopHitsAggregationReq {
                     .get(field)
                     .unwrap_or_else(|| panic!("field '{field}' not found in accessors"));
 
-                let values: Vec<FastFieldValue> = accessors
-                    .iter()
-                    .flat_map(|accessor| match accessor {
-                        DynamicColumn::U64(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::U64)
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::I64(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::I64)
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::F64(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::F64)
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::Bytes(accessor) => accessor
-                            .term_ords(doc_id)
-                            .map(|term_ord| {
-                                let mut buffer = vec![];
-                                assert!(
-                                    accessor
-                                        .ord_to_bytes(term_ord, &mut buffer)
-                                        .expect("could not read term dictionary"),
-                                    "term corresponding to term_ord does not exist"
-                                );
-                                FastFieldValue::Bytes(buffer)
-                            })
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::Str(accessor) => accessor
-                            .term_ords(doc_id)
-                            .map(|term_ord| {
-                                let mut buffer = vec![];
-                                assert!(
-                                    accessor
-                                        .ord_to_bytes(term_ord, &mut buffer)
-                                        .expect("could not read term dictionary"),
-                                    "term corresponding to term_ord does not exist"
-                                );
-                                FastFieldValue::Str(String::from_utf8(buffer).unwrap())
-                            })
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::Bool(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::Bool)
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::IpAddr(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::IpAddr)
-                            .collect::<Vec<_>>(),
-                        DynamicColumn::DateTime(accessor) => accessor
-                            .values_for_doc(doc_id)
-                            .map(FastFieldValue::Date)
-                            .collect::<Vec<_>>(),
-                    })
-                    .collect();
-
+                let values = get_fast_field_values(accessors, doc_id);
                 (field.to_owned(), FastFieldValue::Array(values))
             })
             .collect();
@@ -313,6 +277,139 @@ impl TopHitsAggregationReq {
     }
 }
 
+/// Returns the values of a document in the columns of a fast field.
+pub(crate) fn get_fast_field_values(
+    accessors: &[DynamicColumn],
+    doc_id: DocId,
+) -> Vec<FastFieldValue> {
+    accessors
+        .iter()
+        .flat_map(|accessor| match accessor {
+            DynamicColumn::U64(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::U64)
+                .collect::<Vec<_>>(),
+            DynamicColumn::I64(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::I64)
+                .collect::<Vec<_>>(),
+            DynamicColumn::F64(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::F64)
+                .collect::<Vec<_>>(),
+            DynamicColumn::Bytes(accessor) => accessor
+                .term_ords(doc_id)
+                .map(|term_ord| {
+                    let mut buffer = vec![];
+                    assert!(
+                        accessor
+                            .ord_to_bytes(term_ord, &mut buffer)
+                            .expect("could not read term dictionary"),
+                        "term corresponding to term_ord does not exist"
+                    );
+                    FastFieldValue::Bytes(buffer)
+                })
+                .collect::<Vec<_>>(),
+            DynamicColumn::Str(accessor) => accessor
+                .term_ords(doc_id)
+                .map(|term_ord| {
+                    let mut buffer = vec![];
+                    assert!(
+                        accessor
+                            .ord_to_bytes(term_ord, &mut buffer)
+                            .expect("could not read term dictionary"),
+                        "term corresponding to term_ord does not exist"
+                    );
+                    FastFieldValue::Str(String::from_utf8(buffer).unwrap())
+                })
+                .collect::<Vec<_>>(),
+            DynamicColumn::Bool(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::Bool)
+                .collect::<Vec<_>>(),
+            DynamicColumn::IpAddr(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::IpAddr)
+                .collect::<Vec<_>>(),
+            // The width of fixed-width bytes is not known at this point, so that the full 16
+            // bytes are returned.
+            DynamicColumn::U128(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(|val| FastFieldValue::Bytes(val.to_be_bytes().to_vec()))
+                .collect::<Vec<_>>(),
+            DynamicColumn::DateTime(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(FastFieldValue::Date)
+                .collect::<Vec<_>>(),
+            // Geo points are returned as `[lon, lat]`, like in GeoJSON.
+            DynamicColumn::GeoPoint(accessor) => accessor
+                .values_for_doc(doc_id)
+                .map(|geo_point| {
+                    FastFieldValue::Array(vec![
+                        FastFieldValue::F64(geo_point.lon()),
+                        FastFieldValue::F64(geo_point.lat()),
+                    ])
+                })
+                .collect::<Vec<_>>(),
+        })
+        .collect()
+}
+
+/// Reads the `stored_fields` of the top hits of a segment from its doc store.
+pub(crate) struct StoredFieldsAccessor {
+    store_reader: StoreReader,
+    fields: Vec<(String, Field)>,
+}
+
+impl StoredFieldsAccessor {
+    /// Returns `None` if the request does not retrieve any stored field.
+    pub fn open(
+        req: &TopHitsAggregationReq,
+        reader: &SegmentReader,
+    ) -> crate::Result<Option<StoredFieldsAccessor>> {
+        if req.stored_fields.is_empty() {
+            return Ok(None);
+        }
+        let schema = reader.schema();
+        let fields = req
+            .stored_fields
+            .iter()
+            .map(|field_name| {
+                let field = schema.get_field(field_name)?;
+                if !schema.get_field_entry(field).is_stored() {
+                    return Err(crate::TantivyError::AggregationError(
+                        AggregationError::InvalidRequest(format!(
+                            "Field `{field_name}` in `stored_fields` of `top_hits` aggregation is \
+                             not stored"
+                        )),
+                    ));
+                }
+                Ok((field_name.to_string(), field))
+            })
+            .collect::<crate::Result<_>>()?;
+        // The top hits are spread over the segment, caching blocks would not help much.
+        let store_reader = reader.get_store_reader(1)?;
+        Ok(Some(StoredFieldsAccessor {
+            store_reader,
+            fields,
+        }))
+    }
+
+    /// Returns the JSON representation of the stored field values of the document.
+    fn get_document_stored_fields(&self, doc_id: DocId) -> crate::Result<HashMap<String, String>> {
+        let doc: TantivyDocument = self.store_reader.get(doc_id)?;
+        self.fields
+            .iter()
+            .map(|(field_name, field)| {
+                let values = OwnedValue::Array(doc.get_all(*field).map(OwnedValue::from).collect());
+                let values_json = serde_json::to_string(&values)
+                    .map_err(|err| crate::TantivyError::InternalError(err.to_string()))?;
+                Ok((field_name.clone(), values_json))
+            })
+            .collect()
+    }
+}
+
 /// A retrieved value from a fast field.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FastFieldValue {
@@ -400,6 +497,10 @@ struct DocSortValuesAndFields {
     #[serde(rename = "docvalue_fields")]
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     doc_value_fields: HashMap<String, FastFieldValue>,
+
+    /// JSON representation of the stored field values. `OwnedValue` requires a self-describing
+    /// format, which the intermediate results may not be serialized with.
+    stored_fields: HashMap<String, String>,
 }
 
 impl Ord for DocSortValuesAndFields {
@@ -475,6 +576,17 @@ impl TopHitsTopNComputer {
                     .into_iter()
                     .map(|(k, v)| (k, v.into()))
                     .collect(),
+                stored_fields: doc
+                    .feature
+                    .stored_fields
+                    .into_iter()
+                    .map(|(k, v)| {
+                        let values = serde_json::from_str(&v)
+                            .expect("stored field values should be serialized as JSON");
+                        (k, values)
+                    })
+                    .collect(),
+                doc_address: doc.doc,
             })
             .collect();
 
@@ -509,23 +621,31 @@ impl TopHitsSegmentCollector {
     fn into_top_hits_collector(
         self,
         value_accessors: &HashMap<String, Vec<DynamicColumn>>,
+        stored_fields_accessor: Option<&StoredFieldsAccessor>,
         req: &TopHitsAggregationReq,
-    ) -> TopHitsTopNComputer {
+    ) -> crate::Result<TopHitsTopNComputer> {
         let mut top_hits_computer = TopHitsTopNComputer::new(req);
         let top_results = self.top_n.into_vec();
 
         for res in top_results {
             let doc_value_fields = req.get_document_field_data(value_accessors, res.doc.doc_id);
+            let stored_fields = match stored_fields_accessor {
+                Some(stored_fields_accessor) => {
+                    stored_fields_accessor.get_document_stored_fields(res.doc.doc_id)?
+                }
+                None => HashMap::new(),
+            };
             top_hits_computer.collect(
                 DocSortValuesAndFields {
                     sorts: res.feature,
                     doc_value_fields,
+                    stored_fields,
                 },
                 res.doc,
             );
         }
 
-        top_hits_computer
+        Ok(top_hits_computer)
     }
 
     /// TODO add a specialized variant for a single sort field
@@ -571,15 +691,18 @@ impl SegmentAggregationCollector for TopHitsSegmentCollector {
         let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
 
         let value_accessors = &agg_with_accessor.aggs.values[self.accessor_idx].value_accessors;
+        let stored_fields_accessor = agg_with_accessor.aggs.values[self.accessor_idx]
+            .stored_fields_accessor
+            .as_ref();
         let tophits_req = &agg_with_accessor.aggs.values[self.accessor_idx]
             .agg
             .agg
             .as_top_hits()
             .expect("aggregation request must be of type top hits");
 
-        let intermediate_result = IntermediateMetricResult::TopHits(
-            self.into_top_hits_collector(value_accessors, tophits_req),
-        );
+        let intermediate_result = IntermediateMetricResult::TopHits(Box::new(
+            self.into_top_hits_collector(value_accessors, stored_fields_accessor, tophits_req)?,
+        ));
         results.push(
             name,
             IntermediateAggregationResult::Metric(intermediate_result),
@@ -632,11 +755,13 @@ mod tests {
     use crate::aggregation::agg_req::Aggregations;
     use crate::aggregation::agg_result::AggregationResults;
     use crate::aggregation::bucket::tests::get_test_index_from_docs;
+    use crate::aggregation::intermediate_agg_result::IntermediateAggregationResults;
     use crate::aggregation::tests::get_test_index_from_values;
-    use crate::aggregation::AggregationCollector;
+    use crate::aggregation::{AggregationCollector, DistributedAggregationCollector};
     use crate::collector::ComparableDoc;
     use crate::query::AllQuery;
-    use crate::schema::OwnedValue;
+    use crate::schema::{OwnedValue, Schema, FAST, STORED, STRING, TEXT};
+    use crate::{DocAddress, Index, IndexWriter};
 
     fn invert_order(cmp_feature: DocValueAndOrder) -> DocValueAndOrder {
         let DocValueAndOrder { value, order } = cmp_feature;
@@ -701,6 +826,7 @@ mod tests {
                 order: Order::Asc,
             }],
             doc_value_fields: Default::default(),
+            stored_fields: Default::default(),
         };
 
         let features_2 = DocSortValuesAndFields {
@@ -709,6 +835,7 @@ mod tests {
                 order: Order::Asc,
             }],
             doc_value_fields: Default::default(),
+            stored_fields: Default::default(),
         };
 
         assert!(features_1 < features_2);
@@ -774,6 +901,7 @@ mod tests {
                         order: Order::Asc,
                     }],
                     doc_value_fields: Default::default(),
+                    stored_fields: Default::default(),
                 },
             },
             ComparableDoc {
@@ -787,6 +915,7 @@ mod tests {
                         order: Order::Asc,
                     }],
                     doc_value_fields: Default::default(),
+                    stored_fields: Default::default(),
                 },
             },
             ComparableDoc {
@@ -800,6 +929,7 @@ mod tests {
                         order: Order::Asc,
                     }],
                     doc_value_fields: Default::default(),
+                    stored_fields: Default::default(),
                 },
             },
         ];
@@ -818,14 +948,20 @@ mod tests {
                     super::TopHitsVecEntry {
                         sort: vec![docs[0].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        stored_fields: Default::default(),
+                        doc_address: docs[0].doc,
                     },
                     super::TopHitsVecEntry {
                         sort: vec![docs[1].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        stored_fields: Default::default(),
+                        doc_address: docs[1].doc,
                     },
                     super::TopHitsVecEntry {
                         sort: vec![docs[2].feature.sorts[0].value],
                         doc_value_fields: Default::default(),
+                        stored_fields: Default::default(),
+                        doc_address: docs[2].doc,
                     },
                 ]
             }
@@ -869,9 +1005,26 @@ mod tests {
         let reader = index.reader()?;
         let searcher = reader.searcher();
 
-        let agg_res =
+        let mut agg_res =
             serde_json::to_value(searcher.search(&AllQuery, &collector).unwrap()).unwrap();
 
+        // The segment ordinals depend on the segment ids, so the doc addresses are checked
+        // against the sort values rather than against fixed addresses.
+        for hit in agg_res["top_hits_req"]["hits"].as_array_mut().unwrap() {
+            let doc_address: DocAddress = serde_json::from_value(
+                hit.as_object_mut().unwrap().remove("doc_address").unwrap(),
+            )?;
+            let date_column = searcher
+                .segment_reader(doc_address.segment_ord)
+                .fast_fields()
+                .date("date")?;
+            let date = date_column.first(doc_address.doc_id).unwrap();
+            assert_eq!(
+                hit["sort"][0],
+                common::i64_to_u64(date.into_timestamp_nanos())
+            );
+        }
+
         let date_2017 = datetime!(2017-06-15 00:00:00 UTC);
         let date_2016 = datetime!(2016-01-02 00:00:00 UTC);
 
@@ -913,4 +1066,82 @@ mod tests {
     fn test_aggregation_top_hits_multi_segment() -> crate::Result<()> {
         test_aggregation_top_hits(false)
     }
+
+    #[test]
+    fn test_aggregation_top_hits_stored_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "book", rank => 2u64, body => "Dune"))?;
+        index_writer.add_document(doc!(category => "book", rank => 5u64, body => "Emma"))?;
+        index_writer.add_document(doc!(category => "film", rank => 1u64))?;
+        index_writer.add_document(doc!(category => "film", rank => 3u64, body => "Heat"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let aggs: Aggregations = serde_json::from_value(json!({
+            "categories": {
+                "terms": { "field": "category" },
+                "aggs": {
+                    "best": {
+                        "top_hits": {
+                            "size": 1,
+                            "sort": [ { "rank": "desc" } ],
+                            "docvalue_fields": ["rank"],
+                            "stored_fields": ["body"]
+                        }
+                    }
+                }
+            }
+        }))?;
+        let collector = AggregationCollector::from_aggs(aggs.clone(), Default::default());
+        let agg_res = serde_json::to_value(searcher.search(&AllQuery, &collector)?)?;
+        let buckets = &agg_res["categories"]["buckets"];
+        assert_eq!(
+            buckets[0]["best"]["hits"],
+            json!([{
+                "sort": [5],
+                "docvalue_fields": { "rank": [5] },
+                "stored_fields": { "body": ["Emma"] },
+                "doc_address": { "segment_ord": 0, "doc_id": 1 }
+            }])
+        );
+        assert_eq!(
+            buckets[1]["best"]["hits"],
+            json!([{
+                "sort": [3],
+                "docvalue_fields": { "rank": [3] },
+                "stored_fields": { "body": ["Heat"] },
+                "doc_address": { "segment_ord": 0, "doc_id": 3 }
+            }])
+        );
+
+        // The stored values go through the intermediate results of a distributed aggregation.
+        let collector =
+            DistributedAggregationCollector::from_aggs(aggs.clone(), Default::default());
+        let intermediate_agg_res = searcher.search(&AllQuery, &collector)?;
+        let intermediate_agg_res_bytes = postcard::to_allocvec(&intermediate_agg_res).unwrap();
+        let intermediate_agg_res: IntermediateAggregationResults =
+            postcard::from_bytes(&intermediate_agg_res_bytes).unwrap();
+        let distributed_agg_res =
+            intermediate_agg_res.into_final_result(aggs, Default::default())?;
+        assert_eq!(serde_json::to_value(distributed_agg_res)?, agg_res);
+
+        let aggs: Aggregations = serde_json::from_value(json!({
+            "best": {
+                "top_hits": {
+                    "size": 1,
+                    "sort": [ { "rank": "desc" } ],
+                    "stored_fields": ["rank"]
+                }
+            }
+        }))?;
+        let collector = AggregationCollector::from_aggs(aggs, Default::default());
+        let err = searcher.search(&AllQuery, &collector).unwrap_err();
+        assert!(err.to_string().contains("not stored"));
+        Ok(())
+    }
 }
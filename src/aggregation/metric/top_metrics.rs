@@ -0,0 +1,488 @@
+use columnar::DynamicColumn;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::top_hits::{get_fast_field_values, KeyOrder};
+use super::{FastFieldValue, TopMetricsEntry, TopMetricsResult};
+use crate::aggregation::agg_req_with_accessor::{
+    AggregationWithAccessor, AggregationsWithAccessor,
+};
+use crate::aggregation::bucket::Order;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateMetricResult,
+};
+use crate::aggregation::segment_agg_result::SegmentAggregationCollector;
+use crate::aggregation::{f64_from_fastfield_u64, AggregationError};
+use crate::schema::OwnedValue;
+use crate::{DocId, TantivyError};
+
+/// # Top Metrics
+///
+/// The top metrics aggregation returns the values of some fast fields of the documents with the
+/// highest or lowest value of a sort field, e.g. "the latest status of each host".
+///
+/// Unlike `top_hits`, it only keeps track of the sort value of the best documents while
+/// collecting, and reads the `metrics` fields once per segment for the documents it returns.
+///
+/// The sort field needs to be a numeric or date fast field. Documents without a sort value are
+/// ignored. The metrics fields can be any fast field, the first value of the field is returned
+/// for each document, or `null` if the document has none.
+///
+/// The `metrics` parameter accepts a single field or a list of fields. `size` defaults to 1.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "latest_status": {
+///         "top_metrics": {
+///             "metrics": [{ "field": "status" }, { "field": "latency" }],
+///             "sort": { "timestamp": "desc" },
+///             "size": 1
+///         }
+///     }
+/// }
+/// ```
+///
+/// The result contains the sort value and the metrics of the top documents:
+/// ```json
+/// {
+///     "top": [
+///         { "sort": [1700000000.0], "metrics": { "status": "ok", "latency": 12.0 } }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsAggregationReq {
+    #[serde(deserialize_with = "deserialize_metrics")]
+    metrics: Vec<TopMetricsField>,
+    sort: KeyOrder,
+    #[serde(default = "default_size")]
+    size: usize,
+}
+
+fn default_size() -> usize {
+    1
+}
+
+/// A field returned by the [`TopMetricsAggregationReq`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopMetricsField {
+    /// The name of the fast field.
+    pub field: String,
+}
+
+fn deserialize_metrics<'de, D>(deserializer: D) -> Result<Vec<TopMetricsField>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(TopMetricsField),
+        Many(Vec<TopMetricsField>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(field) => vec![field],
+        OneOrMany::Many(fields) => fields,
+    })
+}
+
+impl TopMetricsAggregationReq {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.size == 0 {
+            return Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(
+                    "size of top_metrics aggregation must be greater than 0".to_string(),
+                ),
+            ));
+        }
+        if self.metrics.is_empty() {
+            return Err(TantivyError::AggregationError(
+                AggregationError::InvalidRequest(
+                    "top_metrics aggregation requires at least one metric".to_string(),
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Return the field the documents are sorted by.
+    pub fn sort_field_name(&self) -> &str {
+        &self.sort.field
+    }
+
+    /// Return the fields returned for the top documents.
+    pub fn metric_field_names(&self) -> Vec<&str> {
+        self.metrics
+            .iter()
+            .map(|metric| metric.field.as_str())
+            .collect()
+    }
+}
+
+/// A top document of a [`IntermediateTopMetrics`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct IntermediateTopMetricsEntry {
+    sort: f64,
+    /// The values of the metrics, in the order of the request.
+    metrics: Vec<Option<FastFieldValue>>,
+}
+
+/// Intermediate result of the top_metrics aggregation that can be combined with other
+/// intermediate results.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateTopMetrics {
+    order: Order,
+    size: usize,
+    /// The top documents, best first.
+    top: Vec<IntermediateTopMetricsEntry>,
+}
+
+impl IntermediateTopMetrics {
+    pub(crate) fn from_req(req: &TopMetricsAggregationReq) -> Self {
+        Self {
+            order: req.sort.order,
+            size: req.size,
+            top: Vec::new(),
+        }
+    }
+
+    /// Merges the other intermediate result into self.
+    pub fn merge_fruits(&mut self, other: IntermediateTopMetrics) {
+        let order = self.order;
+        self.top.extend(other.top);
+        // The sort is stable, on ties the documents of self come first.
+        self.top.sort_by(|left, right| match order {
+            Order::Asc => left.sort.total_cmp(&right.sort),
+            Order::Desc => right.sort.total_cmp(&left.sort),
+        });
+        self.top.truncate(self.size);
+    }
+
+    pub(crate) fn into_final_result(self, req: &TopMetricsAggregationReq) -> TopMetricsResult {
+        let top = self
+            .top
+            .into_iter()
+            .map(|entry| TopMetricsEntry {
+                sort: vec![entry.sort],
+                metrics: req
+                    .metrics
+                    .iter()
+                    .zip(entry.metrics)
+                    .map(|(metric, value)| {
+                        let value = value.map(OwnedValue::from).unwrap_or(OwnedValue::Null);
+                        (metric.field.clone(), value)
+                    })
+                    .collect(),
+            })
+            .collect();
+        TopMetricsResult { top }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentTopMetricsCollector {
+    order: Order,
+    size: usize,
+    /// The sort value in fast field value space and the doc id of the top documents of the
+    /// segment, best first.
+    top: Vec<(u64, DocId)>,
+    accessor_idx: usize,
+}
+
+impl SegmentTopMetricsCollector {
+    pub fn from_req(req: &TopMetricsAggregationReq, accessor_idx: usize) -> Self {
+        Self {
+            order: req.sort.order,
+            size: req.size,
+            top: Vec::new(),
+            accessor_idx,
+        }
+    }
+
+    /// Returns true if `left` sorts before `right`.
+    #[inline]
+    fn is_better(&self, left: u64, right: u64) -> bool {
+        match self.order {
+            Order::Asc => left < right,
+            Order::Desc => left > right,
+        }
+    }
+
+    #[inline]
+    fn collect_value(&mut self, doc: DocId, val: u64) {
+        if self.top.len() == self.size {
+            let (worst_val, _) = self.top[self.size - 1];
+            if !self.is_better(val, worst_val) {
+                return;
+            }
+        }
+        // A document with several values is kept once, with its best value.
+        if let Some(pos) = self.top.iter().position(|(_, top_doc)| *top_doc == doc) {
+            if !self.is_better(val, self.top[pos].0) {
+                return;
+            }
+            self.top.remove(pos);
+        }
+        let pos = self
+            .top
+            .iter()
+            .position(|(top_val, _)| self.is_better(val, *top_val))
+            .unwrap_or(self.top.len());
+        self.top.insert(pos, (val, doc));
+        self.top.truncate(self.size);
+    }
+
+    fn into_intermediate_result(
+        self,
+        agg_with_accessor: &AggregationWithAccessor,
+        req: &TopMetricsAggregationReq,
+    ) -> IntermediateTopMetrics {
+        let top = self
+            .top
+            .iter()
+            .map(|(val, doc)| IntermediateTopMetricsEntry {
+                sort: f64_from_fastfield_u64(*val, &agg_with_accessor.field_type),
+                metrics: req
+                    .metrics
+                    .iter()
+                    .map(|metric| {
+                        let accessors = agg_with_accessor
+                            .value_accessors
+                            .get(&metric.field)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default();
+                        first_value(accessors, *doc)
+                    })
+                    .collect(),
+            })
+            .collect();
+        IntermediateTopMetrics {
+            order: self.order,
+            size: self.size,
+            top,
+        }
+    }
+}
+
+fn first_value(accessors: &[DynamicColumn], doc: DocId) -> Option<FastFieldValue> {
+    get_fast_field_values(accessors, doc).into_iter().next()
+}
+
+impl SegmentAggregationCollector for SegmentTopMetricsCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        let top_metrics_req = agg_with_accessor
+            .agg
+            .agg
+            .as_top_metrics()
+            .expect("aggregation request must be of type top metrics");
+
+        let intermediate_result = IntermediateMetricResult::TopMetrics(Box::new(
+            self.into_intermediate_result(agg_with_accessor, top_metrics_req),
+        ));
+        results.push(
+            name,
+            IntermediateAggregationResult::Metric(intermediate_result),
+        )
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        let agg_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        agg_accessor
+            .column_block_accessor
+            .fetch_block(docs, &agg_accessor.accessor);
+        for (doc, val) in agg_accessor
+            .column_block_accessor
+            .iter_docid_vals(docs, &agg_accessor.accessor)
+        {
+            self.collect_value(doc, val);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::TantivyDocument;
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let host = schema_builder.add_text_field("host", STRING | FAST);
+        let status = schema_builder.add_text_field("status", STRING | FAST);
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let latency = schema_builder.add_f64_field("latency", FAST);
+        let segment_and_docs = vec![
+            vec![
+                doc!(host => "a", status => "ok", timestamp => 10i64, latency => 1.0),
+                doc!(host => "b", status => "ok", timestamp => 20i64, latency => 2.0),
+                doc!(host => "a", status => "down", latency => 100.0),
+            ],
+            vec![
+                doc!(host => "a", status => "slow", timestamp => 30i64, latency => 30.0),
+                doc!(host => "b", status => "down", timestamp => 5i64),
+            ],
+            vec![doc!(host => "a", status => "ok", timestamp => 25i64, latency => 3.0)],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn top_metrics_test() -> crate::Result<()> {
+        for merge_segments in [false, true] {
+            let (schema, segment_and_docs) = get_test_docs();
+            let index = get_test_index_from_docs(merge_segments, schema, segment_and_docs)?;
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "hosts": {
+                    "terms": { "field": "host" },
+                    "aggs": {
+                        "latest": {
+                            "top_metrics": {
+                                "metrics": [{ "field": "status" }, { "field": "latency" }],
+                                "sort": { "timestamp": "desc" }
+                            }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["hosts"]["buckets"][0]["latest"],
+                json!({
+                    "top": [
+                        { "sort": [30.0], "metrics": { "status": "slow", "latency": 30.0 } }
+                    ]
+                })
+            );
+            assert_eq!(
+                res["hosts"]["buckets"][1]["latest"],
+                json!({
+                    "top": [
+                        { "sort": [20.0], "metrics": { "status": "ok", "latency": 2.0 } }
+                    ]
+                })
+            );
+
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "earliest": {
+                    "top_metrics": {
+                        "metrics": { "field": "latency" },
+                        "sort": { "timestamp": "asc" },
+                        "size": 3
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(
+                res["earliest"],
+                json!({
+                    "top": [
+                        { "sort": [5.0], "metrics": { "latency": null } },
+                        { "sort": [10.0], "metrics": { "latency": 1.0 } },
+                        { "sort": [20.0], "metrics": { "latency": 2.0 } }
+                    ]
+                })
+            );
+
+            // Order the terms by a metric of the top document.
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "hosts": {
+                    "terms": { "field": "host", "order": { "latest.latency": "asc" } },
+                    "aggs": {
+                        "latest": {
+                            "top_metrics": {
+                                "metrics": { "field": "latency" },
+                                "sort": { "timestamp": "desc" }
+                            }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            assert_eq!(res["hosts"]["buckets"][0]["key"], "b");
+            assert_eq!(res["hosts"]["buckets"][1]["key"], "a");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn top_metrics_missing_fields_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "top": {
+                "top_metrics": {
+                    "metrics": { "field": "not_a_field" },
+                    "sort": { "latency": "desc" }
+                }
+            },
+            "no_sort": {
+                "top_metrics": {
+                    "metrics": { "field": "status" },
+                    "sort": { "not_a_field": "desc" }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["top"],
+            json!({ "top": [{ "sort": [100.0], "metrics": { "not_a_field": null } }] })
+        );
+        assert_eq!(res["no_sort"], json!({ "top": [] }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn top_metrics_invalid_request_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(true, schema, segment_and_docs)?;
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "top": {
+                "top_metrics": {
+                    "metrics": { "field": "status" },
+                    "sort": { "timestamp": "desc" },
+                    "size": 0
+                }
+            }
+        }))
+        .unwrap();
+        let err = exec_request(agg_req, &index).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "InvalidRequest: \"size of top_metrics aggregation must be greater than 0\""
+        );
+
+        Ok(())
+    }
+}
@@ -7,7 +7,8 @@
 //! - How many errors with status code 500 do we have per day?
 //! - What is the average listing price of cars grouped by color?
 //!
-//! There are two categories: [Metrics](metric) and [Buckets](bucket).
+//! There are two categories: [Metrics](metric) and [Buckets](bucket). [Pipeline](pipeline)
+//! aggregations post-process the results of the buckets.
 //!
 //! ## Prerequisite
 //! Currently aggregations work only on [fast fields](`crate::fastfield`). Fast fields
@@ -39,8 +40,10 @@
 //! - [Bucket](bucket)
 //!     - [Histogram](bucket::HistogramAggregation)
 //!     - [DateHistogram](bucket::DateHistogramAggregationReq)
+//!     - [AutoDateHistogram](bucket::AutoDateHistogramAggregation)
 //!     - [Range](bucket::RangeAggregation)
 //!     - [Terms](bucket::TermsAggregation)
+//!     - [Filters](bucket::FiltersAggregation)
 //! - [Metric](metric)
 //!     - [Average](metric::AverageAggregation)
 //!     - [Stats](metric::StatsAggregation)
@@ -50,8 +53,16 @@
 //!     - [Sum](metric::SumAggregation)
 //!     - [Count](metric::CountAggregation)
 //!     - [Percentiles](metric::PercentilesAggregationReq)
+//!     - [Rate](metric::RateAggregation)
 //!     - [Cardinality](metric::CardinalityAggregationReq)
 //!     - [TopHits](metric::TopHitsAggregationReq)
+//!     - [TopMetrics](metric::TopMetricsAggregationReq)
+//! - [Pipeline](pipeline)
+//!     - [Derivative](pipeline::DerivativeAggregation)
+//!     - [MovingAvg](pipeline::MovingAvgAggregation)
+//!     - [CumulativeSum](pipeline::CumulativeSumAggregation)
+//!     - [BucketScript](pipeline::BucketScriptAggregation)
+//!     - [BucketSelector](pipeline::BucketSelectorAggregation)
 //!
 //! # Example
 //! Compute the average metric, by building [`agg_req::Aggregations`], which is built from an
@@ -138,6 +149,7 @@ mod date;
 mod error;
 pub mod intermediate_agg_result;
 pub mod metric;
+pub mod pipeline;
 
 mod segment_agg_result;
 use std::collections::HashMap;
@@ -154,7 +166,7 @@ pub use collector::{
     DEFAULT_BUCKET_LIMIT,
 };
 use columnar::{ColumnType, MonotonicallyMappableToU64};
-pub(crate) use date::format_date;
+pub(crate) use date::{format_date, format_date_with_offset};
 pub use error::AggregationError;
 use itertools::Itertools;
 use serde::de::{self, Visitor};
@@ -435,7 +447,7 @@ mod tests {
     use crate::indexer::NoMergePolicy;
     use crate::query::{AllQuery, TermQuery};
     use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, FAST, STRING};
-    use crate::{Index, IndexWriter, Term};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
 
     pub fn get_test_index_with_num_docs(
         merge_segments: bool,
@@ -576,6 +588,32 @@ mod tests {
         Ok(index)
     }
 
+    /// Creates an index with one segment per entry of `segment_and_docs`, merged into a single
+    /// segment if `merge_segments` is true.
+    pub fn get_test_index_from_docs(
+        merge_segments: bool,
+        schema: Schema,
+        segment_and_docs: Vec<Vec<TantivyDocument>>,
+    ) -> crate::Result<Index> {
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for docs in segment_and_docs {
+            for doc in docs {
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        if merge_segments {
+            let segment_ids = index.searchable_segment_ids()?;
+            if segment_ids.len() > 1 {
+                index_writer.merge(&segment_ids).wait()?;
+                index_writer.wait_merging_threads()?;
+            }
+        }
+        Ok(index)
+    }
+
     pub fn get_test_index_2_segments(merge_segments: bool) -> crate::Result<Index> {
         let mut schema_builder = Schema::builder();
         let text_fieldtype = crate::schema::TextOptions::default()
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::script::Script;
+use super::{bucket_value, GapPolicy, PipelineBucket};
+use crate::TantivyError;
+
+/// A parent pipeline aggregation that computes a value for each bucket of a multi-bucket
+/// aggregation, by running a script on values of the bucket.
+///
+/// The `buckets_path` maps the variables of the script to the path of their value, see
+/// [`super`] for the syntax. The value of the buckets with a missing variable is `null`, unless
+/// the `gap_policy` is `insert_zeros`.
+///
+/// See [`BucketSelectorAggregation`] for the syntax of the script.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": { "field": "date", "calendar_interval": "month" },
+///         "aggs": {
+///             "sales": { "sum": { "field": "price" } },
+///             "avg_sale": {
+///                 "bucket_script": {
+///                     "buckets_path": { "total": "sales", "count": "_count" },
+///                     "script": "params.total / params.count"
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BucketScriptAggregation {
+    /// The path to the value of each variable of the script.
+    pub buckets_path: HashMap<String, String>,
+    /// The script computing the value of a bucket.
+    pub script: String,
+    /// How to handle buckets without value.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+impl BucketScriptAggregation {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        parse_script(&self.script, &self.buckets_path)?;
+        Ok(())
+    }
+
+    pub(crate) fn compute<B: PipelineBucket>(
+        &self,
+        buckets: &[B],
+    ) -> crate::Result<Vec<Option<f64>>> {
+        let script = parse_script(&self.script, &self.buckets_path)?;
+        buckets
+            .iter()
+            .map(|bucket| {
+                let params = bucket_params(bucket, &self.buckets_path, self.gap_policy)?;
+                Ok(params.map(|params| script.eval(&params)))
+            })
+            .collect()
+    }
+}
+
+/// A parent pipeline aggregation that removes the buckets of a multi-bucket aggregation for which
+/// a script evaluates to false.
+///
+/// The `buckets_path` maps the variables of the script to the path of their value, see
+/// [`super`] for the syntax. The buckets with a missing variable are kept, unless the
+/// `gap_policy` is `insert_zeros`.
+///
+/// The script is an arithmetic expression on the variables, which are referred to as
+/// `params.<name>` or as `<name>`. It supports numbers, `true` and `false`, the operators `+`,
+/// `-`, `*`, `/`, `%`, `<`, `<=`, `>`, `>=`, `==`, `!=`, `&&`, `||`, `!` and parentheses. Any value
+/// but `0` is true.
+///
+/// The buckets are selected after all other pipeline aggregations of the parent aggregation
+/// are computed.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": { "field": "date", "calendar_interval": "month" },
+///         "aggs": {
+///             "sales": { "sum": { "field": "price" } },
+///             "big_months": {
+///                 "bucket_selector": {
+///                     "buckets_path": { "total": "sales" },
+///                     "script": "params.total > 200"
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BucketSelectorAggregation {
+    /// The path to the value of each variable of the script.
+    pub buckets_path: HashMap<String, String>,
+    /// The condition for a bucket to be kept.
+    pub script: String,
+    /// How to handle buckets without value.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+impl BucketSelectorAggregation {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        parse_script(&self.script, &self.buckets_path)?;
+        Ok(())
+    }
+
+    pub(crate) fn select<B: PipelineBucket>(&self, buckets: &mut Vec<B>) -> crate::Result<()> {
+        let script = parse_script(&self.script, &self.buckets_path)?;
+        let is_selected = buckets
+            .iter()
+            .map(|bucket| {
+                let params = bucket_params(bucket, &self.buckets_path, self.gap_policy)?;
+                Ok(params.is_none_or(|params| script.eval_condition(&params)))
+            })
+            .collect::<crate::Result<Vec<bool>>>()?;
+        let mut is_selected = is_selected.into_iter();
+        buckets.retain(|_| is_selected.next().unwrap_or(true));
+        Ok(())
+    }
+}
+
+fn parse_script(source: &str, buckets_path: &HashMap<String, String>) -> crate::Result<Script> {
+    let script = Script::parse(source)?;
+    if let Some(variable) = script
+        .variables()
+        .into_iter()
+        .find(|variable| !buckets_path.contains_key(*variable))
+    {
+        return Err(TantivyError::InvalidArgument(format!(
+            "script variable {variable:?} is not defined in buckets_path"
+        )));
+    }
+    Ok(script)
+}
+
+/// Returns the values of the variables of the script for a bucket, or `None` if a value is
+/// missing.
+fn bucket_params<'a, B: PipelineBucket>(
+    bucket: &B,
+    buckets_path: &'a HashMap<String, String>,
+    gap_policy: GapPolicy,
+) -> crate::Result<Option<HashMap<&'a str, f64>>> {
+    let mut params = HashMap::with_capacity(buckets_path.len());
+    for (variable, path) in buckets_path {
+        let Some(value) = bucket_value(bucket, path, gap_policy)? else {
+            return Ok(None);
+        };
+        params.insert(variable.as_str(), value);
+    }
+    Ok(Some(params))
+}
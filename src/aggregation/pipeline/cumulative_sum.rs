@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use super::{bucket_value, GapPolicy, PipelineBucket};
+
+/// A parent pipeline aggregation that computes the cumulative sum of a value of the buckets of a
+/// `histogram` or `date_histogram` aggregation. Buckets without value don't change the sum.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": { "field": "date", "calendar_interval": "month" },
+///         "aggs": {
+///             "sales": { "sum": { "field": "price" } },
+///             "cumulative_sales": { "cumulative_sum": { "buckets_path": "sales" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CumulativeSumAggregation {
+    /// The path to the value to sum. See [`super`] for the syntax.
+    pub buckets_path: String,
+}
+
+impl CumulativeSumAggregation {
+    pub(crate) fn compute<B: PipelineBucket>(
+        &self,
+        buckets: &[B],
+    ) -> crate::Result<Vec<Option<f64>>> {
+        let mut sum = 0.0;
+        buckets
+            .iter()
+            .map(|bucket| {
+                sum += bucket_value(bucket, &self.buckets_path, GapPolicy::InsertZeros)?
+                    .unwrap_or(0.0);
+                Ok(Some(sum))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use super::{bucket_value, GapPolicy, PipelineBucket};
+
+/// A parent pipeline aggregation that computes the derivative of a value of the buckets of a
+/// `histogram` or `date_histogram` aggregation, i.e. the difference between the value of a bucket
+/// and the value of the previous bucket.
+///
+/// The derivative of the first bucket, and of the buckets without value, is `null`.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": { "field": "date", "calendar_interval": "month" },
+///         "aggs": {
+///             "sales": { "sum": { "field": "price" } },
+///             "sales_derivative": { "derivative": { "buckets_path": "sales" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DerivativeAggregation {
+    /// The path to the value to derive. See [`super`] for the syntax.
+    pub buckets_path: String,
+    /// How to handle buckets without value.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+impl DerivativeAggregation {
+    pub(crate) fn compute<B: PipelineBucket>(
+        &self,
+        buckets: &[B],
+    ) -> crate::Result<Vec<Option<f64>>> {
+        let mut previous_value = None;
+        buckets
+            .iter()
+            .map(|bucket| {
+                let value = bucket_value(bucket, &self.buckets_path, self.gap_policy)?;
+                let Some(value) = value else {
+                    return Ok(None);
+                };
+                let derivative = previous_value.map(|previous_value| value - previous_value);
+                previous_value = Some(value);
+                Ok(derivative)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,546 @@
+//! Module for all pipeline aggregations.
+//!
+//! Pipeline aggregations don't collect documents, they compute values from the results of other
+//! aggregations. They are sub-aggregations of a multi-bucket aggregation, the "parent", and
+//! compute a value for each bucket of the parent from the values of their sibling aggregations,
+//! which are computed first. These values are [`SingleMetricResult`] results, which can be used
+//! by other pipeline aggregations.
+//!
+//! The values a pipeline aggregation works on are referred to by a `buckets_path`, which is
+//! either:
+//! - `_count`, the document count of the bucket,
+//! - the name of a sibling single-value metric or pipeline aggregation, e.g. `avg_price`,
+//! - the name and the property of a sibling multi-value metric aggregation, e.g. `price_stats.max`.
+//!
+//! A value can be missing in a bucket, e.g. the average of an empty bucket. How this is handled
+//! is controlled by the [`GapPolicy`] of the aggregation.
+//!
+//! ## Supported Pipeline Aggregations
+//! - [Derivative](DerivativeAggregation)
+//! - [MovingAvg](MovingAvgAggregation)
+//! - [CumulativeSum](CumulativeSumAggregation)
+//! - [BucketScript](BucketScriptAggregation)
+//! - [BucketSelector](BucketSelectorAggregation)
+//!
+//! [`SingleMetricResult`]: super::metric::SingleMetricResult
+
+mod bucket_script;
+mod cumulative_sum;
+mod derivative;
+mod moving_avg;
+mod script;
+
+pub use bucket_script::*;
+pub use cumulative_sum::*;
+pub use derivative::*;
+pub use moving_avg::*;
+use serde::{Deserialize, Serialize};
+
+use super::agg_req::{AggregationVariants, Aggregations};
+use super::agg_result::{AggregationResult, AggregationResults, MetricResult};
+use super::bucket::{get_agg_name_and_property, GetDocCount};
+use crate::TantivyError;
+
+/// How a pipeline aggregation handles the buckets without value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GapPolicy {
+    /// Skip the buckets without value.
+    #[default]
+    #[serde(rename = "skip")]
+    Skip,
+    /// Use zero as the value of the buckets without value.
+    #[serde(rename = "insert_zeros")]
+    InsertZeros,
+}
+
+/// A bucket of a multi-bucket aggregation result, on which pipeline aggregations can be computed.
+pub(crate) trait PipelineBucket: GetDocCount {
+    fn sub_aggregation(&self) -> &AggregationResults;
+    fn sub_aggregation_mut(&mut self) -> &mut AggregationResults;
+}
+
+/// Returns the value of a bucket at the `buckets_path`, or `None` if it is missing.
+fn bucket_value<B: PipelineBucket>(
+    bucket: &B,
+    buckets_path: &str,
+    gap_policy: GapPolicy,
+) -> crate::Result<Option<f64>> {
+    let value = if buckets_path == "_count" {
+        Some(bucket.doc_count() as f64)
+    } else {
+        let (agg_name, agg_property) = get_agg_name_and_property(buckets_path);
+        bucket
+            .sub_aggregation()
+            .get_value_from_aggregation(agg_name, agg_property)?
+    };
+    match (value, gap_policy) {
+        (Some(value), _) if !value.is_nan() => Ok(Some(value)),
+        (_, GapPolicy::Skip) => Ok(None),
+        (_, GapPolicy::InsertZeros) => Ok(Some(0.0)),
+    }
+}
+
+/// Computes the pipeline aggregations of `sub_aggregation_req` on the buckets of their parent
+/// aggregation, in the order of the buckets.
+pub(crate) fn apply_pipeline_aggregations<B: PipelineBucket>(
+    buckets: &mut Vec<B>,
+    sub_aggregation_req: &Aggregations,
+) -> crate::Result<()> {
+    for (name, pipeline) in sorted_pipeline_aggregations(sub_aggregation_req)? {
+        let values = match pipeline {
+            AggregationVariants::Derivative(derivative) => derivative.compute(buckets)?,
+            AggregationVariants::MovingAvg(moving_avg) => moving_avg.compute(buckets)?,
+            AggregationVariants::CumulativeSum(cumulative_sum) => {
+                cumulative_sum.compute(buckets)?
+            }
+            AggregationVariants::BucketScript(bucket_script) => bucket_script.compute(buckets)?,
+            AggregationVariants::BucketSelector(bucket_selector) => {
+                bucket_selector.select(buckets)?;
+                continue;
+            }
+            _ => unreachable!("only pipeline aggregations are sorted"),
+        };
+        for (bucket, value) in buckets.iter_mut().zip(values) {
+            bucket.sub_aggregation_mut().0.insert(
+                name.to_string(),
+                AggregationResult::MetricResult(MetricResult::SimpleValue(value.into())),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the pipeline aggregations of `aggs`, sorted so that a pipeline aggregation comes
+/// after the pipeline aggregations it uses the values of. The bucket selectors come last.
+fn sorted_pipeline_aggregations(
+    aggs: &Aggregations,
+) -> crate::Result<Vec<(&str, &AggregationVariants)>> {
+    let mut pending: Vec<(&str, &AggregationVariants)> = aggs
+        .iter()
+        .filter(|(_, agg)| agg.agg.is_pipeline())
+        .map(|(name, agg)| (name.as_str(), &agg.agg))
+        .collect();
+    // Sort by name, so that the bucket selectors are applied in a deterministic order.
+    pending.sort_by_key(|(name, _)| *name);
+    let mut sorted = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready_pos = pending
+            .iter()
+            .position(|(_, agg)| {
+                agg.get_buckets_paths().into_iter().all(|buckets_path| {
+                    let (agg_name, _) = get_agg_name_and_property(buckets_path);
+                    !pending.iter().any(|(name, _)| *name == agg_name)
+                })
+            })
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "the buckets_path of the pipeline aggregations {:?} form a cycle",
+                    pending.iter().map(|(name, _)| name).collect::<Vec<_>>()
+                ))
+            })?;
+        sorted.push(pending.remove(ready_pos));
+    }
+    sorted.sort_by_key(|(_, agg)| matches!(agg, AggregationVariants::BucketSelector(_)));
+    Ok(sorted)
+}
+
+/// Checks that the pipeline aggregations of the request tree are sub-aggregations of a supported
+/// parent aggregation, and that their `buckets_path` refers to sibling aggregations with a value.
+pub(crate) fn validate_pipeline_aggregations(
+    aggs: &Aggregations,
+    parent: Option<&AggregationVariants>,
+) -> crate::Result<()> {
+    for (name, agg) in aggs {
+        if agg.agg.is_pipeline() {
+            validate_pipeline_aggregation(name, &agg.agg, aggs, parent)?;
+            if !agg.sub_aggregation.is_empty() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "pipeline aggregation {name:?} can't have sub-aggregations"
+                )));
+            }
+        }
+        validate_pipeline_aggregations(&agg.sub_aggregation, Some(&agg.agg))?;
+    }
+    sorted_pipeline_aggregations(aggs)?;
+    Ok(())
+}
+
+fn validate_pipeline_aggregation(
+    name: &str,
+    pipeline: &AggregationVariants,
+    siblings: &Aggregations,
+    parent: Option<&AggregationVariants>,
+) -> crate::Result<()> {
+    use AggregationVariants::*;
+    let is_histogram_parent = matches!(
+        parent,
+        Some(Histogram(_) | DateHistogram(_) | AutoDateHistogram(_))
+    );
+    let is_supported_parent = match pipeline {
+        Derivative(_) | MovingAvg(_) | CumulativeSum(_) => is_histogram_parent,
+        _ => {
+            is_histogram_parent
+                || matches!(
+                    parent,
+                    Some(Terms(_) | Range(_) | GeoHashGrid(_) | GeoTileGrid(_))
+                )
+        }
+    };
+    if !is_supported_parent {
+        let supported_parents = if matches!(pipeline, BucketScript(_) | BucketSelector(_)) {
+            "a histogram, date_histogram, auto_date_histogram, terms, range, geohash_grid or \
+             geotile_grid aggregation"
+        } else {
+            "a histogram, date_histogram or auto_date_histogram aggregation"
+        };
+        return Err(TantivyError::InvalidArgument(format!(
+            "pipeline aggregation {name:?} must be a sub-aggregation of {supported_parents}"
+        )));
+    }
+    match pipeline {
+        MovingAvg(moving_avg) => moving_avg.validate()?,
+        BucketScript(bucket_script) => bucket_script.validate()?,
+        BucketSelector(bucket_selector) => bucket_selector.validate()?,
+        _ => {}
+    }
+    for buckets_path in pipeline.get_buckets_paths() {
+        if buckets_path == "_count" {
+            continue;
+        }
+        let (agg_name, _) = get_agg_name_and_property(buckets_path);
+        let has_value = siblings.get(agg_name).is_some_and(|sibling| {
+            !sibling.agg.is_bucket()
+                && !matches!(sibling.agg, Percentiles(_) | TopHits(_) | BucketSelector(_))
+        });
+        if !has_value {
+            return Err(TantivyError::InvalidArgument(format!(
+                "buckets_path {buckets_path:?} of pipeline aggregation {name:?} must refer to \
+                 `_count` or to a sibling metric or pipeline aggregation with a numeric value"
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::{exec_request, get_test_index_from_docs};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{Index, TantivyDocument};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let x = schema_builder.add_f64_field("x", FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let color = schema_builder.add_text_field("color", STRING | FAST);
+        // The histogram on `x` with an interval of 10 has the buckets:
+        // - 0: 2 docs, sum of price 30
+        // - 10: 1 doc, sum of price 5
+        // - 20: no doc
+        // - 30: 3 docs, sum of price 150
+        let segment_and_docs = vec![
+            vec![
+                doc!(x => 1.0, price => 10.0, color => "red"),
+                doc!(x => 11.0, price => 5.0, color => "blue"),
+                doc!(x => 31.0, price => 40.0, color => "red"),
+            ],
+            vec![
+                doc!(x => 2.0, price => 20.0, color => "blue"),
+                doc!(x => 32.0, price => 50.0, color => "green"),
+                doc!(x => 35.0, price => 60.0, color => "red"),
+            ],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    fn histogram_with_pipelines(index: &Index, pipelines: Value) -> crate::Result<Value> {
+        let mut aggs = json!({
+            "sales": { "sum": { "field": "price" } },
+            "avg_price": { "avg": { "field": "price" } },
+            "price_stats": { "stats": { "field": "price" } },
+        });
+        aggs.as_object_mut()
+            .unwrap()
+            .extend(pipelines.as_object().unwrap().clone());
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "histo": {
+                "histogram": { "field": "x", "interval": 10.0 },
+                "aggs": aggs
+            }
+        }))
+        .unwrap();
+        exec_request(agg_req, index)
+    }
+
+    /// Returns the value of the aggregation `name` in each bucket of the histogram.
+    fn bucket_values(res: &Value, name: &str) -> Vec<Value> {
+        res["histo"]["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket[name]["value"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn pipeline_derivative_and_cumulative_sum_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let res = histogram_with_pipelines(
+            &index,
+            json!({
+                "sales_deriv": { "derivative": { "buckets_path": "sales" } },
+                "avg_deriv": { "derivative": { "buckets_path": "avg_price" } },
+                "avg_deriv_zeros": {
+                    "derivative": { "buckets_path": "avg_price", "gap_policy": "insert_zeros" }
+                },
+                "max_deriv": { "derivative": { "buckets_path": "price_stats.max" } },
+                "cumulative_sales": { "cumulative_sum": { "buckets_path": "sales" } },
+                "cumulative_count": { "cumulative_sum": { "buckets_path": "_count" } },
+                // Pipeline aggregations can use the values of other pipeline aggregations.
+                "cumulative_sales_deriv": {
+                    "derivative": { "buckets_path": "cumulative_sales" }
+                },
+            }),
+        )?;
+        assert_eq!(
+            bucket_values(&res, "sales_deriv"),
+            vec![json!(null), json!(-25.0), json!(-5.0), json!(150.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "avg_deriv"),
+            vec![json!(null), json!(-10.0), json!(null), json!(45.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "avg_deriv_zeros"),
+            vec![json!(null), json!(-10.0), json!(-5.0), json!(50.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "max_deriv"),
+            vec![json!(null), json!(-15.0), json!(null), json!(55.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "cumulative_sales"),
+            vec![json!(30.0), json!(35.0), json!(35.0), json!(185.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "cumulative_count"),
+            vec![json!(2.0), json!(3.0), json!(3.0), json!(6.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "cumulative_sales_deriv"),
+            vec![json!(null), json!(5.0), json!(0.0), json!(150.0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pipeline_moving_avg_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let res = histogram_with_pipelines(
+            &index,
+            json!({
+                "simple": { "moving_avg": { "buckets_path": "sales", "window": 2 } },
+                "simple_skip": { "moving_avg": { "buckets_path": "avg_price", "window": 2 } },
+                "linear": {
+                    "moving_avg": { "buckets_path": "_count", "window": 2, "model": "linear" }
+                },
+                "ewma": {
+                    "moving_avg": {
+                        "buckets_path": "sales",
+                        "model": "ewma",
+                        "settings": { "alpha": 0.5 }
+                    }
+                },
+            }),
+        )?;
+        assert_eq!(
+            bucket_values(&res, "simple"),
+            vec![json!(30.0), json!(17.5), json!(2.5), json!(75.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "simple_skip"),
+            vec![json!(15.0), json!(10.0), json!(null), json!(27.5)]
+        );
+        assert_eq!(
+            bucket_values(&res, "linear"),
+            vec![json!(2.0), json!(4.0 / 3.0), json!(1.0 / 3.0), json!(2.0)]
+        );
+        assert_eq!(
+            bucket_values(&res, "ewma"),
+            vec![json!(30.0), json!(17.5), json!(8.75), json!(79.375)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pipeline_bucket_script_and_selector_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let res = histogram_with_pipelines(
+            &index,
+            json!({
+                "avg_sale": {
+                    "bucket_script": {
+                        "buckets_path": { "total": "sales", "count": "_count" },
+                        "script": "params.total / params.count"
+                    }
+                },
+                "spread": {
+                    "bucket_script": {
+                        "buckets_path": { "max": "price_stats.max", "min": "price_stats.min" },
+                        "script": "(max - min) * 2"
+                    }
+                },
+                "big_buckets": {
+                    "bucket_selector": {
+                        "buckets_path": { "total": "cumulative_sales", "count": "_count" },
+                        "script": "params.total > 30 && params.count > 0"
+                    }
+                },
+                "cumulative_sales": { "cumulative_sum": { "buckets_path": "sales" } },
+            }),
+        )?;
+        let keys: Vec<Value> = res["histo"]["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket["key"].clone())
+            .collect();
+        assert_eq!(keys, vec![json!(10.0), json!(30.0)]);
+        assert_eq!(
+            bucket_values(&res, "avg_sale"),
+            vec![json!(5.0), json!(50.0)]
+        );
+        assert_eq!(bucket_values(&res, "spread"), vec![json!(0.0), json!(40.0)]);
+        // The bucket selector has no result.
+        assert_eq!(res["histo"]["buckets"][0]["big_buckets"], Value::Null);
+
+        // Bucket scripts and selectors are supported on the other multi-bucket aggregations.
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "colors": {
+                "terms": { "field": "color" },
+                "aggs": {
+                    "sales": { "sum": { "field": "price" } },
+                    "avg_sale": {
+                        "bucket_script": {
+                            "buckets_path": { "total": "sales", "count": "_count" },
+                            "script": "total / count"
+                        }
+                    },
+                    "several_docs": {
+                        "bucket_selector": {
+                            "buckets_path": { "count": "_count" },
+                            "script": "count >= 2"
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+        assert_eq!(
+            res["colors"]["buckets"],
+            json!([
+                {
+                    "key": "red",
+                    "doc_count": 3,
+                    "sales": { "value": 110.0 },
+                    "avg_sale": { "value": 110.0 / 3.0 }
+                },
+                {
+                    "key": "blue",
+                    "doc_count": 2,
+                    "sales": { "value": 25.0 },
+                    "avg_sale": { "value": 12.5 }
+                },
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pipeline_invalid_request_test() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let index = get_test_index_from_docs(false, schema, segment_and_docs)?;
+        let invalid_requests = [
+            (
+                json!({ "sales_deriv": { "derivative": { "buckets_path": "_count" } } }),
+                "pipeline aggregation \"sales_deriv\" must be a sub-aggregation of a histogram, \
+                 date_histogram or auto_date_histogram aggregation",
+            ),
+            (
+                json!({
+                    "colors": {
+                        "terms": { "field": "color" },
+                        "aggs": { "sales_deriv": { "derivative": { "buckets_path": "_count" } } }
+                    }
+                }),
+                "pipeline aggregation \"sales_deriv\" must be a sub-aggregation of a histogram, \
+                 date_histogram or auto_date_histogram aggregation",
+            ),
+            (
+                json!({
+                    "histo": {
+                        "histogram": { "field": "x", "interval": 10.0 },
+                        "aggs": { "sales_deriv": { "derivative": { "buckets_path": "sales" } } }
+                    }
+                }),
+                "buckets_path \"sales\" of pipeline aggregation \"sales_deriv\" must refer to \
+                 `_count` or to a sibling metric or pipeline aggregation with a numeric value",
+            ),
+            (
+                json!({
+                    "histo": {
+                        "histogram": { "field": "x", "interval": 10.0 },
+                        "aggs": {
+                            "a": { "derivative": { "buckets_path": "b" } },
+                            "b": { "cumulative_sum": { "buckets_path": "a" } }
+                        }
+                    }
+                }),
+                "the buckets_path of the pipeline aggregations [\"a\", \"b\"] form a cycle",
+            ),
+            (
+                json!({
+                    "histo": {
+                        "histogram": { "field": "x", "interval": 10.0 },
+                        "aggs": {
+                            "ratio": {
+                                "bucket_script": {
+                                    "buckets_path": { "count": "_count" },
+                                    "script": "count / total"
+                                }
+                            }
+                        }
+                    }
+                }),
+                "script variable \"total\" is not defined in buckets_path",
+            ),
+            (
+                json!({
+                    "histo": {
+                        "histogram": { "field": "x", "interval": 10.0 },
+                        "aggs": {
+                            "avg": { "moving_avg": { "buckets_path": "_count", "window": 0 } }
+                        }
+                    }
+                }),
+                "window of moving_avg aggregation must be greater than 0",
+            ),
+        ];
+        for (agg_req, expected_err) in invalid_requests {
+            let agg_req: Aggregations = serde_json::from_value(agg_req).unwrap();
+            let err = exec_request(agg_req, &index).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("An invalid argument was passed: '{expected_err}'")
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{bucket_value, GapPolicy, PipelineBucket};
+use crate::TantivyError;
+
+/// A parent pipeline aggregation that computes the moving average of a value of the buckets of a
+/// `histogram` or `date_histogram` aggregation.
+///
+/// The average of a bucket is computed on the values of the `window` last buckets with a value, up
+/// to and including the bucket. The moving average of the buckets without value is `null`.
+///
+/// # JSON Format
+/// ```json
+/// {
+///     "sales_per_month": {
+///         "date_histogram": { "field": "date", "calendar_interval": "month" },
+///         "aggs": {
+///             "sales": { "sum": { "field": "price" } },
+///             "sales_moving_avg": {
+///                 "moving_avg": {
+///                     "buckets_path": "sales",
+///                     "window": 3,
+///                     "model": "ewma",
+///                     "settings": { "alpha": 0.5 }
+///                 }
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MovingAvgAggregation {
+    /// The path to the value to average. See [`super`] for the syntax.
+    pub buckets_path: String,
+    /// The number of buckets to average. Defaults to 5.
+    #[serde(default = "default_window")]
+    pub window: u32,
+    /// How the values of the window are weighted.
+    #[serde(default)]
+    pub model: MovingAvgModel,
+    /// The settings of the model.
+    #[serde(default)]
+    pub settings: MovingAvgSettings,
+    /// How to handle buckets without value.
+    #[serde(default)]
+    pub gap_policy: GapPolicy,
+}
+
+fn default_window() -> u32 {
+    5
+}
+
+/// The model of a [`MovingAvgAggregation`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovingAvgModel {
+    /// All the values have the same weight.
+    #[default]
+    #[serde(rename = "simple")]
+    Simple,
+    /// The weight of the values decreases linearly with their age.
+    #[serde(rename = "linear")]
+    Linear,
+    /// The weight of the values decreases exponentially with their age, by a factor of
+    /// `1 - alpha`.
+    #[serde(rename = "ewma")]
+    Ewma,
+}
+
+/// The settings of the model of a [`MovingAvgAggregation`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MovingAvgSettings {
+    /// The smoothing factor of the `ewma` model, within `[0, 1]`. Defaults to 0.3.
+    ///
+    /// A higher alpha gives more weight to the recent values.
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+}
+
+fn default_alpha() -> f64 {
+    0.3
+}
+
+impl Default for MovingAvgSettings {
+    fn default() -> Self {
+        Self {
+            alpha: default_alpha(),
+        }
+    }
+}
+
+impl MovingAvgAggregation {
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        if self.window == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "window of moving_avg aggregation must be greater than 0".to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.settings.alpha) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "alpha of moving_avg aggregation must be within [0, 1], got {}",
+                self.settings.alpha
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn compute<B: PipelineBucket>(
+        &self,
+        buckets: &[B],
+    ) -> crate::Result<Vec<Option<f64>>> {
+        let mut window = VecDeque::with_capacity(self.window as usize);
+        buckets
+            .iter()
+            .map(|bucket| {
+                let Some(value) = bucket_value(bucket, &self.buckets_path, self.gap_policy)? else {
+                    return Ok(None);
+                };
+                if window.len() == self.window as usize {
+                    window.pop_front();
+                }
+                window.push_back(value);
+                Ok(Some(self.average(&window)))
+            })
+            .collect()
+    }
+
+    /// Averages the values of the window, from the oldest to the most recent.
+    fn average(&self, window: &VecDeque<f64>) -> f64 {
+        match self.model {
+            MovingAvgModel::Simple => window.iter().sum::<f64>() / window.len() as f64,
+            MovingAvgModel::Linear => {
+                let (weighted_sum, total_weight) = window.iter().enumerate().fold(
+                    (0.0, 0.0),
+                    |(weighted_sum, total_weight), (pos, value)| {
+                        let weight = (pos + 1) as f64;
+                        (weighted_sum + value * weight, total_weight + weight)
+                    },
+                );
+                weighted_sum / total_weight
+            }
+            MovingAvgModel::Ewma => {
+                let alpha = self.settings.alpha;
+                let mut values = window.iter();
+                let first_value = *values.next().expect("the window is not empty");
+                values.fold(first_value, |average, value| {
+                    alpha * value + (1.0 - alpha) * average
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use crate::aggregation::AggregationError;
+use crate::TantivyError;
+
+/// A script of the `bucket_script` and `bucket_selector` aggregations.
+///
+/// Scripts are arithmetic expressions on the variables of the `buckets_path`, which can be
+/// referred to as `params.<name>` or as `<name>`. They support:
+/// - numbers, and `true` and `false`, which are `1` and `0`,
+/// - the arithmetic operators `+`, `-`, `*`, `/`, `%`,
+/// - the comparison operators `<`, `<=`, `>`, `>=`, `==`, `!=`,
+/// - the logical operators `&&`, `||`, `!`, where any value but `0` is true,
+/// - parentheses.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Script {
+    expr: Expr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn from_token(token: &str) -> Option<BinaryOp> {
+        let op = match token {
+            "+" => BinaryOp::Add,
+            "-" => BinaryOp::Sub,
+            "*" => BinaryOp::Mul,
+            "/" => BinaryOp::Div,
+            "%" => BinaryOp::Rem,
+            "<" => BinaryOp::Lt,
+            "<=" => BinaryOp::Le,
+            ">" => BinaryOp::Gt,
+            ">=" => BinaryOp::Ge,
+            "==" => BinaryOp::Eq,
+            "!=" => BinaryOp::Ne,
+            "&&" => BinaryOp::And,
+            "||" => BinaryOp::Or,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// Returns the binding power of the operator: operators with a higher precedence bind
+    /// tighter.
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Ne => 3,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+        }
+    }
+
+    fn apply(self, left: f64, right: f64) -> f64 {
+        let from_bool = |value: bool| if value { 1.0 } else { 0.0 };
+        match self {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => left / right,
+            BinaryOp::Rem => left % right,
+            BinaryOp::Lt => from_bool(left < right),
+            BinaryOp::Le => from_bool(left <= right),
+            BinaryOp::Gt => from_bool(left > right),
+            BinaryOp::Ge => from_bool(left >= right),
+            BinaryOp::Eq => from_bool(left == right),
+            BinaryOp::Ne => from_bool(left != right),
+            BinaryOp::And => from_bool(is_true(left) && is_true(right)),
+            BinaryOp::Or => from_bool(is_true(left) || is_true(right)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Operator(&'static str),
+    OpenParen,
+    CloseParen,
+}
+
+const OPERATORS: [&str; 16] = [
+    "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(", ")",
+];
+
+fn tokenize(source: &str) -> crate::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut remaining = source.trim_start();
+    while let Some(first_char) = remaining.chars().next() {
+        let token_len = if first_char.is_ascii_digit() || first_char == '.' {
+            let len = remaining
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(remaining.len());
+            let number = remaining[..len].parse().map_err(|_| {
+                script_error(source, &format!("invalid number {:?}", &remaining[..len]))
+            })?;
+            tokens.push(Token::Number(number));
+            len
+        } else if first_char.is_ascii_alphabetic() || first_char == '_' {
+            let len = remaining
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(remaining.len());
+            tokens.push(Token::Identifier(remaining[..len].to_string()));
+            len
+        } else {
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| remaining.starts_with(**operator))
+                .ok_or_else(|| {
+                    script_error(source, &format!("unexpected character {first_char:?}"))
+                })?;
+            tokens.push(match *operator {
+                "(" => Token::OpenParen,
+                ")" => Token::CloseParen,
+                operator => Token::Operator(operator),
+            });
+            operator.len()
+        };
+        remaining = remaining[token_len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_expr(&mut self, min_precedence: u8) -> crate::Result<Expr> {
+        let mut left = self.parse_unary()?;
+        while let Some(Token::Operator(operator)) = self.peek() {
+            let Some(op) = BinaryOp::from_token(operator) else {
+                break;
+            };
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.parse_expr(op.precedence() + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> crate::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Identifier(identifier)) => match identifier.as_str() {
+                "true" => Ok(Expr::Number(1.0)),
+                "false" => Ok(Expr::Number(0.0)),
+                _ => {
+                    let name = identifier.strip_prefix("params.").unwrap_or(&identifier);
+                    Ok(Expr::Variable(name.to_string()))
+                }
+            },
+            Some(Token::Operator("-")) => Ok(Expr::Neg(Box::new(self.parse_unary()?))),
+            Some(Token::Operator("!")) => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::OpenParen) => {
+                let expr = self.parse_expr(0)?;
+                if self.next() != Some(Token::CloseParen) {
+                    return Err(script_error(self.source, "expected `)`"));
+                }
+                Ok(expr)
+            }
+            Some(token) => Err(script_error(
+                self.source,
+                &format!("unexpected token {token:?}"),
+            )),
+            None => Err(script_error(self.source, "unexpected end of script")),
+        }
+    }
+}
+
+fn script_error(source: &str, message: &str) -> TantivyError {
+    TantivyError::AggregationError(AggregationError::InvalidRequest(format!(
+        "could not parse script {source:?}: {message}"
+    )))
+}
+
+fn is_true(value: f64) -> bool {
+    value != 0.0 && !value.is_nan()
+}
+
+impl Script {
+    pub(crate) fn parse(source: &str) -> crate::Result<Script> {
+        let mut parser = Parser {
+            source,
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let expr = parser.parse_expr(0)?;
+        if let Some(token) = parser.peek() {
+            return Err(script_error(source, &format!("unexpected token {token:?}")));
+        }
+        Ok(Script { expr })
+    }
+
+    /// Returns the names of the variables used by the script.
+    pub(crate) fn variables(&self) -> Vec<&str> {
+        fn collect<'a>(expr: &'a Expr, variables: &mut Vec<&'a str>) {
+            match expr {
+                Expr::Number(_) => {}
+                Expr::Variable(name) => variables.push(name),
+                Expr::Neg(expr) | Expr::Not(expr) => collect(expr, variables),
+                Expr::Binary(_, left, right) => {
+                    collect(left, variables);
+                    collect(right, variables);
+                }
+            }
+        }
+        let mut variables = Vec::new();
+        collect(&self.expr, &mut variables);
+        variables
+    }
+
+    /// Evaluates the script. Variables missing from `params` evaluate to `NaN`.
+    pub(crate) fn eval(&self, params: &HashMap<&str, f64>) -> f64 {
+        fn eval(expr: &Expr, params: &HashMap<&str, f64>) -> f64 {
+            match expr {
+                Expr::Number(number) => *number,
+                Expr::Variable(name) => params.get(name.as_str()).copied().unwrap_or(f64::NAN),
+                Expr::Neg(expr) => -eval(expr, params),
+                Expr::Not(expr) => {
+                    if is_true(eval(expr, params)) {
+                        0.0
+                    } else {
+                        1.0
+                    }
+                }
+                Expr::Binary(op, left, right) => op.apply(eval(left, params), eval(right, params)),
+            }
+        }
+        eval(&self.expr, params)
+    }
+
+    /// Evaluates the script as a condition.
+    pub(crate) fn eval_condition(&self, params: &HashMap<&str, f64>) -> bool {
+        is_true(self.eval(params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Script;
+
+    fn eval(source: &str) -> f64 {
+        let params = HashMap::from([("a", 6.0), ("b", 4.0)]);
+        Script::parse(source).unwrap().eval(&params)
+    }
+
+    #[test]
+    fn test_script_eval() {
+        assert_eq!(eval("params.a / params.b"), 1.5);
+        assert_eq!(eval("a + b * 2"), 14.0);
+        assert_eq!(eval("(a + b) * 2"), 20.0);
+        assert_eq!(eval("a - b - 1"), 1.0);
+        assert_eq!(eval("a % b"), 2.0);
+        assert_eq!(eval("-a + 1.5"), -4.5);
+        assert_eq!(eval("a > b && b >= 4"), 1.0);
+        assert_eq!(eval("a < b || !(b == 4)"), 0.0);
+        assert_eq!(eval("a != b"), 1.0);
+        assert_eq!(eval("true && !false"), 1.0);
+        assert!(eval("params.c * 2").is_nan());
+        assert!(eval("a / 0 > 1") == 1.0);
+    }
+
+    #[test]
+    fn test_script_variables() {
+        let script = Script::parse("params.total / (params.count + count)").unwrap();
+        assert_eq!(script.variables(), vec!["total", "count", "count"]);
+    }
+
+    #[test]
+    fn test_script_parse_errors() {
+        for source in ["", "a +", "(a + b", "a b", "a ^ b", "1.2.3", "a + )"] {
+            assert!(
+                Script::parse(source).is_err(),
+                "{source:?} should not parse"
+            );
+        }
+        let err = Script::parse("a $ b").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "InvalidRequest: \"could not parse script \\\"a $ b\\\": unexpected character '$'\""
+        );
+    }
+}
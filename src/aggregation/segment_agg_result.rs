@@ -5,10 +5,16 @@
 
 use std::fmt::Debug;
 
+use columnar::ColumnType;
+
 pub(crate) use super::agg_limits::AggregationLimitsGuard;
 use super::agg_req::AggregationVariants;
 use super::agg_req_with_accessor::{AggregationWithAccessor, AggregationsWithAccessor};
-use super::bucket::{SegmentHistogramCollector, SegmentRangeCollector, SegmentTermCollector};
+use super::bucket::{
+    SegmentAutoDateHistogramCollector, SegmentCompositeCollector, SegmentFiltersCollector,
+    SegmentGeoGridCollector, SegmentHistogramCollector, SegmentRangeCollector,
+    SegmentTermCollector,
+};
 use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::metric::{
     AverageAggregation, CountAggregation, ExtendedStatsAggregation, MaxAggregation, MinAggregation,
@@ -17,8 +23,8 @@ use super::metric::{
 };
 use crate::aggregation::bucket::TermMissingAgg;
 use crate::aggregation::metric::{
-    CardinalityAggregationReq, SegmentCardinalityCollector, SegmentExtendedStatsCollector,
-    TopHitsSegmentCollector,
+    SegmentCardinalityCollector, SegmentExtendedStatsCollector, SegmentRateCollector,
+    SegmentScriptedMetricCollector, SegmentTopMetricsCollector, TopHitsSegmentCollector,
 };
 
 pub(crate) trait SegmentAggregationCollector: CollectorClone + Debug {
@@ -105,20 +111,63 @@ pub(crate) fn build_single_agg_segment_collector(
             &mut req.sub_aggregation,
             &mut req.limits,
             req.field_type,
+            &req.accessor,
             accessor_idx,
         )?)),
         Histogram(histogram) => Ok(Box::new(SegmentHistogramCollector::from_req_and_validate(
             histogram.clone(),
+            None,
             &mut req.sub_aggregation,
             req.field_type,
             accessor_idx,
         )?)),
         DateHistogram(histogram) => Ok(Box::new(SegmentHistogramCollector::from_req_and_validate(
             histogram.to_histogram_req()?,
+            histogram.to_date_rounding()?,
             &mut req.sub_aggregation,
             req.field_type,
             accessor_idx,
         )?)),
+        AutoDateHistogram(histogram) => Ok(Box::new(
+            SegmentAutoDateHistogramCollector::from_req_and_validate(
+                histogram,
+                &mut req.sub_aggregation,
+                accessor_idx,
+            )?,
+        )),
+        Composite(composite) => {
+            let column_types: Vec<ColumnType> = req
+                .accessors
+                .iter()
+                .map(|(_, column_type)| *column_type)
+                .collect();
+            Ok(Box::new(SegmentCompositeCollector::from_req_and_validate(
+                composite,
+                &mut req.sub_aggregation,
+                &column_types,
+                &req.str_dict_columns,
+                accessor_idx,
+            )?))
+        }
+        Filters(filters) => Ok(Box::new(SegmentFiltersCollector::from_req_and_validate(
+            filters,
+            &mut req.sub_aggregation,
+            accessor_idx,
+        )?)),
+        GeoHashGrid(geohash_grid) => Ok(Box::new(SegmentGeoGridCollector::from_req_and_validate(
+            geohash_grid.grid(),
+            geohash_grid.bounds,
+            &mut req.sub_aggregation,
+            (req.accessors[0].1, req.accessors[1].1),
+            accessor_idx,
+        )?)),
+        GeoTileGrid(geotile_grid) => Ok(Box::new(SegmentGeoGridCollector::from_req_and_validate(
+            geotile_grid.grid(),
+            geotile_grid.bounds,
+            &mut req.sub_aggregation,
+            (req.accessors[0].1, req.accessors[1].1),
+            accessor_idx,
+        )?)),
         Average(AverageAggregation { missing, .. }) => {
             Ok(Box::new(SegmentStatsCollector::from_req(
                 req.field_type,
@@ -167,14 +216,37 @@ pub(crate) fn build_single_agg_segment_collector(
                 accessor_idx,
             )?,
         )),
+        Rate(rate_req) => Ok(Box::new(SegmentRateCollector::from_req(
+            rate_req,
+            req.field_type,
+            accessor_idx,
+        ))),
         TopHits(top_hits_req) => Ok(Box::new(TopHitsSegmentCollector::from_req(
             top_hits_req,
             accessor_idx,
             req.segment_ordinal,
         ))),
-        Cardinality(CardinalityAggregationReq { missing, .. }) => Ok(Box::new(
-            SegmentCardinalityCollector::from_req(req.field_type, accessor_idx, missing),
-        )),
+        TopMetrics(top_metrics_req) => Ok(Box::new(SegmentTopMetricsCollector::from_req(
+            top_metrics_req,
+            accessor_idx,
+        ))),
+        Cardinality(cardinality_req) => Ok(Box::new(SegmentCardinalityCollector::from_req(
+            cardinality_req,
+            req.field_type,
+            accessor_idx,
+        ))),
+        ScriptedMetric(scripted_metric_req) => {
+            Ok(Box::new(SegmentScriptedMetricCollector::from_req(
+                scripted_metric_req,
+                req.f64_accessors.clone(),
+                accessor_idx,
+            )))
+        }
+        Derivative(_) | MovingAvg(_) | CumulativeSum(_) | BucketScript(_) | BucketSelector(_) => {
+            Err(crate::TantivyError::InternalError(
+                "pipeline aggregations are not collected".to_string(),
+            ))
+        }
     }
 }
 
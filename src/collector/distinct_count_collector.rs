@@ -0,0 +1,314 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+use columnar::{BytesColumn, Column};
+use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
+use rustc_hash::FxHashSet;
+
+use crate::collector::sort_key_top_collector::column_type_of;
+use crate::collector::{Collector, SegmentCollector};
+use crate::schema::Type;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+const DEFAULT_PRECISION: u8 = 14;
+
+type Sketch = HyperLogLogPlus<u64, BuildHasherDefault<DefaultHasher>>;
+
+fn new_sketch(precision: u8) -> Sketch {
+    HyperLogLogPlus::new(precision, BuildHasherDefault::default())
+        .expect("precision should have been validated")
+}
+
+/// The `DistinctCount` collector estimates the number of distinct values of a fast field among
+/// the matching documents.
+///
+/// The estimate is computed with a HyperLogLog++ sketch per segment, the sketches being merged
+/// once all of the segments have been searched. Unlike a terms aggregation, the distinct values
+/// are never materialized: numeric values are hashed right away, and str or bytes values are
+/// only tracked by term ordinal until the end of the segment. A sketch takes at most
+/// `2^precision` bytes. The standard error of the estimate is about `1.04 / sqrt(2^precision)`,
+/// and the count is exact for small cardinalities.
+///
+/// All of the values of multivalued fields are counted. The field can be a str, bytes, u64,
+/// i64, f64, bool or date fast field. See also [`Searcher::distinct_count`].
+///
+/// ```rust
+/// use tantivy::collector::DistinctCount;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STRING};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let user_id = schema_builder.add_text_field("user_id", STRING | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// for i in 0..1_000 {
+///     index_writer.add_document(doc!(user_id => format!("user-{}", i % 100)))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let distinct_users = searcher.search(&AllQuery, &DistinctCount::for_field("user_id"))?;
+/// assert_eq!(distinct_users, 100);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Searcher::distinct_count`]: crate::Searcher::distinct_count
+pub struct DistinctCount {
+    field: String,
+    precision: u8,
+}
+
+impl DistinctCount {
+    /// Creates a `DistinctCount` collector over the given fast field.
+    pub fn for_field(field: impl ToString) -> DistinctCount {
+        DistinctCount {
+            field: field.to_string(),
+            precision: DEFAULT_PRECISION,
+        }
+    }
+
+    /// Sets the precision of the sketch, 14 by default.
+    ///
+    /// The sketch has `2^precision` registers: a higher precision gives a better estimate at the
+    /// cost of more memory.
+    ///
+    /// # Panics
+    /// The method panics if `precision` is not within `[4, 18]`.
+    #[must_use]
+    pub fn with_precision(mut self, precision: u8) -> DistinctCount {
+        assert!(
+            (4..=18).contains(&precision),
+            "The precision must be within [4, 18], got {precision}."
+        );
+        self.precision = precision;
+        self
+    }
+
+    fn open_values(&self, segment_reader: &SegmentReader) -> crate::Result<SegmentValues> {
+        let schema = segment_reader.schema();
+        let (field, _) = schema
+            .find_field(&self.field)
+            .ok_or_else(|| TantivyError::FieldNotFound(self.field.clone()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a fast field.",
+                self.field
+            )));
+        }
+        let fast_fields = segment_reader.fast_fields();
+        let value_type = field_entry.field_type().value_type();
+        let segment_values = match value_type {
+            Type::Str => fast_fields
+                .str(&self.field)?
+                .map(|str_column| SegmentValues::TermOrds {
+                    bytes_column: BytesColumn::from(str_column),
+                    term_ords: FxHashSet::default(),
+                }),
+            Type::Bytes => {
+                fast_fields
+                    .bytes(&self.field)?
+                    .map(|bytes_column| SegmentValues::TermOrds {
+                        bytes_column,
+                        term_ords: FxHashSet::default(),
+                    })
+            }
+            _ => {
+                let column_type = column_type_of(value_type).ok_or_else(|| {
+                    TantivyError::SchemaError(format!(
+                        "Counting the distinct values of field {:?} of type {value_type:?} is not \
+                         supported.",
+                        self.field
+                    ))
+                })?;
+                fast_fields
+                    .u64_lenient_for_type(Some(&[column_type]), &self.field)?
+                    .map(|(column, _)| SegmentValues::Values(column))
+            }
+        };
+        Ok(segment_values.unwrap_or(SegmentValues::Missing))
+    }
+}
+
+impl Collector for DistinctCount {
+    type Fruit = u64;
+
+    type Child = DistinctCountSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(DistinctCountSegmentCollector {
+            sketch: new_sketch(self.precision),
+            values: self.open_values(segment_reader)?,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<crate::Result<Sketch>>) -> crate::Result<u64> {
+        let mut sketch = new_sketch(self.precision);
+        for segment_sketch in segment_fruits {
+            sketch.merge(&segment_sketch?).map_err(|err| {
+                TantivyError::InternalError(format!("Failed to merge the sketches: {err}"))
+            })?;
+        }
+        Ok(sketch.count().round() as u64)
+    }
+}
+
+enum SegmentValues {
+    // The term ordinals are specific to the segment, so the terms are only hashed on harvest,
+    // once per distinct term.
+    TermOrds {
+        bytes_column: BytesColumn,
+        term_ords: FxHashSet<u64>,
+    },
+    Values(Column<u64>),
+    Missing,
+}
+
+/// Segment collector of the [`DistinctCount`] collector.
+pub struct DistinctCountSegmentCollector {
+    sketch: Sketch,
+    values: SegmentValues,
+}
+
+impl SegmentCollector for DistinctCountSegmentCollector {
+    type Fruit = crate::Result<Sketch>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        match &mut self.values {
+            SegmentValues::TermOrds {
+                bytes_column,
+                term_ords,
+            } => term_ords.extend(bytes_column.term_ords(doc)),
+            SegmentValues::Values(column) => {
+                for value in column.values_for_doc(doc) {
+                    self.sketch.insert(&value);
+                }
+            }
+            SegmentValues::Missing => {}
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        if let SegmentValues::TermOrds {
+            bytes_column,
+            term_ords,
+        } = self.values
+        {
+            let mut term_ords: Vec<u64> = term_ords.into_iter().collect();
+            term_ords.sort_unstable();
+            bytes_column
+                .dictionary()
+                .sorted_ords_to_term_cb(term_ords.into_iter(), |term| {
+                    self.sketch.insert_any(&term);
+                    Ok(())
+                })?;
+        }
+        Ok(self.sketch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DistinctCount;
+    use crate::collector::tests::create_searcher;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{TantivyDocument, Term};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let tags = schema_builder.add_text_field("tags", STRING | FAST);
+        let value = schema_builder.add_i64_field("value", FAST);
+        schema_builder.add_text_field("text", TEXT);
+        let mut segment_and_docs = vec![Vec::new()];
+        for i in 0..10_000i64 {
+            let category_text = if i % 2 == 0 { "even" } else { "odd" };
+            segment_and_docs.last_mut().unwrap().push(doc!(
+                category => category_text,
+                tags => format!("tag-{}", i % 7),
+                tags => format!("tag-{}", i % 11),
+                value => i % 5_000,
+            ));
+            if i % 3_000 == 0 {
+                segment_and_docs.push(Vec::new());
+            }
+        }
+        segment_and_docs
+            .last_mut()
+            .unwrap()
+            .push(doc!(category => "none"));
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn test_distinct_count_terms() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        assert_eq!(
+            searcher.search(&AllQuery, &DistinctCount::for_field("category"))?,
+            3
+        );
+        assert_eq!(
+            searcher.search(&AllQuery, &DistinctCount::for_field("tags"))?,
+            11
+        );
+        let category = searcher.schema().get_field("category")?;
+        let query = TermQuery::new(
+            Term::from_field_text(category, "none"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.distinct_count(&query, "tags")?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_count_values() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let distinct_values = searcher.distinct_count(&AllQuery, "value")?;
+        assert!((4_900..5_100).contains(&distinct_values));
+
+        let category = searcher.schema().get_field("category")?;
+        let query = TermQuery::new(
+            Term::from_field_text(category, "odd"),
+            IndexRecordOption::Basic,
+        );
+        let distinct_odd_values = searcher.distinct_count(&query, "value")?;
+        assert!((2_450..2_550).contains(&distinct_odd_values));
+
+        let coarse_distinct_values = searcher.search(
+            &AllQuery,
+            &DistinctCount::for_field("value").with_precision(4),
+        )?;
+        assert!((2_500..7_500).contains(&coarse_distinct_values));
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_count_invalid_field() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        assert!(searcher.distinct_count(&AllQuery, "text").is_err());
+        assert!(searcher.distinct_count(&AllQuery, "unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_distinct_count_invalid_precision() {
+        let _ = DistinctCount::for_field("value").with_precision(19);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use columnar::{BytesColumn, Column};
+
+use super::top_score_collector::TopNComputer;
+use crate::collector::{Collector, ComparableDoc, SegmentCollector};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `DiversifiedTopDocs` collector returns the top-K documents by score, while limiting the
+/// number of documents sharing a same key, e.g. an author, a domain or a cluster id.
+///
+/// The key of a document is the first value of a fast field. Once `max_per_key` documents with
+/// a given key have been selected, the following documents with this key are skipped, and the
+/// remaining slots are filled with the next best documents. This avoids result pages dominated
+/// by a single source.
+///
+/// The key fast field can be of any type. Documents without any value are never skipped.
+///
+/// The ties on the score are broken by the document address, like in [`TopDocs`].
+///
+/// [`TopDocs`]: crate::collector::TopDocs
+///
+/// ```rust
+/// use tantivy::collector::DiversifiedTopDocs;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{Schema, FAST, STRING, TEXT};
+/// use tantivy::{doc, DocAddress, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let author = schema_builder.add_text_field("author", STRING | FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "Diary of a diary", author => "Dan"))?;
+/// index_writer.add_document(doc!(title => "Diary of a diary addict", author => "Dan"))?;
+/// index_writer.add_document(doc!(title => "The Diary of a Young Girl", author => "Anne"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+/// let top_docs = searcher.search(&query, &DiversifiedTopDocs::with_limit(2, "author", 1))?;
+///
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+/// assert_eq!(top_docs[1].1, DocAddress::new(0, 2));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DiversifiedTopDocs {
+    limit: usize,
+    key_field: String,
+    max_per_key: usize,
+}
+
+impl DiversifiedTopDocs {
+    /// Creates a collector returning at most `limit` documents, and at most `max_per_key`
+    /// documents sharing the same value for the fast field `key_field`.
+    ///
+    /// # Panics
+    /// The method panics if `limit` or `max_per_key` is 0.
+    pub fn with_limit(
+        limit: usize,
+        key_field: impl ToString,
+        max_per_key: usize,
+    ) -> DiversifiedTopDocs {
+        assert!(limit >= 1, "Limit must be strictly greater than 0.");
+        assert!(
+            max_per_key >= 1,
+            "The maximum number of documents per key must be strictly greater than 0."
+        );
+        DiversifiedTopDocs {
+            limit,
+            key_field: key_field.to_string(),
+            max_per_key,
+        }
+    }
+}
+
+/// The key of a document, comparable across segments.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DiversityKey {
+    /// Bytes of a term, for str and bytes fast fields.
+    Term(Vec<u8>),
+    /// `u64` representation of the value, for the other fast fields.
+    Value(u64),
+}
+
+/// A document collected by [`DiversifiedTopSegmentCollector`], along with its key.
+pub type DiversifiedHit = (ComparableDoc<Score, DocAddress, true>, Option<DiversityKey>);
+
+enum KeyColumn {
+    Terms(BytesColumn),
+    Values(Column<u64>),
+    Missing,
+}
+
+impl KeyColumn {
+    fn open(segment_reader: &SegmentReader, key_field: &str) -> crate::Result<KeyColumn> {
+        let fast_fields = segment_reader.fast_fields();
+        if let Some(str_column) = fast_fields.str(key_field)? {
+            return Ok(KeyColumn::Terms(BytesColumn::clone(&str_column)));
+        }
+        if let Some(bytes_column) = fast_fields.bytes(key_field)? {
+            return Ok(KeyColumn::Terms(bytes_column));
+        }
+        if let Some((column, _)) = fast_fields.u64_lenient(key_field)? {
+            return Ok(KeyColumn::Values(column));
+        }
+        Ok(KeyColumn::Missing)
+    }
+
+    /// Returns the segment local key of a document, i.e. its term ordinal or value.
+    fn local_key(&self, doc: DocId) -> Option<u64> {
+        match self {
+            KeyColumn::Terms(bytes_column) => bytes_column.term_ords(doc).next(),
+            KeyColumn::Values(column) => column.first(doc),
+            KeyColumn::Missing => None,
+        }
+    }
+
+    fn to_key(&self, local_key: u64) -> crate::Result<DiversityKey> {
+        match self {
+            KeyColumn::Terms(bytes_column) => {
+                let mut term_bytes = Vec::new();
+                bytes_column.ord_to_bytes(local_key, &mut term_bytes)?;
+                Ok(DiversityKey::Term(term_bytes))
+            }
+            KeyColumn::Values(_) | KeyColumn::Missing => Ok(DiversityKey::Value(local_key)),
+        }
+    }
+}
+
+impl Collector for DiversifiedTopDocs {
+    type Fruit = Vec<(Score, DocAddress)>;
+
+    type Child = DiversifiedTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let key_column = KeyColumn::open(segment_reader, &self.key_field)?;
+        Ok(DiversifiedTopSegmentCollector {
+            segment_ord: segment_local_id,
+            limit: self.limit,
+            max_per_key: self.max_per_key,
+            key_column,
+            top_n_per_key: HashMap::new(),
+            top_n_without_key: TopNComputer::new(self.limit),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<crate::Result<Vec<DiversifiedHit>>>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut hits = Vec::new();
+        for segment_hits in segment_fruits {
+            hits.extend(segment_hits?);
+        }
+        hits.sort_unstable_by(|(left, _), (right, _)| left.cmp(right));
+        let mut num_hits_per_key: HashMap<DiversityKey, usize> = HashMap::new();
+        let mut top_docs = Vec::with_capacity(self.limit);
+        for (hit, key_opt) in hits {
+            if top_docs.len() == self.limit {
+                break;
+            }
+            if let Some(key) = key_opt {
+                let num_hits = num_hits_per_key.entry(key).or_default();
+                if *num_hits == self.max_per_key {
+                    continue;
+                }
+                *num_hits += 1;
+            }
+            top_docs.push((hit.feature, hit.doc));
+        }
+        Ok(top_docs)
+    }
+}
+
+/// Segment collector of the [`DiversifiedTopDocs`] collector.
+///
+/// It keeps the `max_per_key` best documents of each key of the segment. Of those, only the
+/// `limit` best documents can be part of the final result.
+pub struct DiversifiedTopSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    limit: usize,
+    max_per_key: usize,
+    key_column: KeyColumn,
+    top_n_per_key: HashMap<u64, TopNComputer<Score, DocId>>,
+    top_n_without_key: TopNComputer<Score, DocId>,
+}
+
+impl SegmentCollector for DiversifiedTopSegmentCollector {
+    type Fruit = crate::Result<Vec<DiversifiedHit>>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let Some(local_key) = self.key_column.local_key(doc) else {
+            self.top_n_without_key.push(score, doc);
+            return;
+        };
+        let max_per_key = self.max_per_key;
+        self.top_n_per_key
+            .entry(local_key)
+            .or_insert_with(|| TopNComputer::new(max_per_key))
+            .push(score, doc);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut hits: Vec<(ComparableDoc<Score, DocId, true>, Option<u64>)> = self
+            .top_n_without_key
+            .into_vec()
+            .into_iter()
+            .map(|hit| (hit, None))
+            .collect();
+        for (local_key, top_n) in self.top_n_per_key {
+            hits.extend(
+                top_n
+                    .into_vec()
+                    .into_iter()
+                    .map(|hit| (hit, Some(local_key))),
+            );
+        }
+        hits.sort_unstable_by(|(left, _), (right, _)| left.cmp(right));
+        hits.truncate(self.limit);
+        // Only the keys of the documents that may be returned are resolved.
+        hits.into_iter()
+            .map(|(hit, local_key_opt)| {
+                let key_opt = local_key_opt
+                    .map(|local_key| self.key_column.to_key(local_key))
+                    .transpose()?;
+                let hit = ComparableDoc {
+                    feature: hit.feature,
+                    doc: DocAddress::new(self.segment_ord, hit.doc),
+                };
+                Ok((hit, key_opt))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiversifiedTopDocs;
+    use crate::collector::tests::create_searcher;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, CustomScoreQuery, Query};
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{DocId, Score, Searcher, SegmentReader, TantivyDocument};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let rank = schema_builder.add_u64_field("rank", FAST);
+        let author = schema_builder.add_text_field("author", STRING | FAST);
+        let cluster = schema_builder.add_u64_field("cluster", FAST);
+        // The authors have different term ordinals in both segments.
+        let segment_and_docs = vec![
+            vec![
+                doc!(rank => 9u64, author => "zoe", cluster => 1u64),
+                doc!(rank => 8u64, author => "zoe", cluster => 1u64),
+                doc!(rank => 3u64, author => "bob", cluster => 2u64),
+            ],
+            vec![
+                doc!(rank => 10u64, author => "zoe"),
+                doc!(rank => 7u64, author => "amy", cluster => 1u64),
+                doc!(rank => 6u64),
+                doc!(rank => 5u64),
+            ],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    fn rank_query() -> impl Query {
+        CustomScoreQuery::new(Box::new(AllQuery), |segment_reader: &SegmentReader| {
+            let rank_column = segment_reader.fast_fields().u64("rank")?;
+            Ok(move |doc: DocId, _score: Score| rank_column.first(doc).unwrap() as Score)
+        })
+    }
+
+    fn top_ranks(searcher: &Searcher, collector: &DiversifiedTopDocs) -> crate::Result<Vec<Score>> {
+        let top_docs = searcher.search(&rank_query(), collector)?;
+        Ok(top_docs.into_iter().map(|(score, _)| score).collect())
+    }
+
+    #[test]
+    fn test_diversified_top_docs_by_str_field() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let collector = DiversifiedTopDocs::with_limit(4, "author", 1);
+        assert_eq!(top_ranks(&searcher, &collector)?, vec![10.0, 7.0, 6.0, 5.0]);
+        let collector = DiversifiedTopDocs::with_limit(10, "author", 2);
+        assert_eq!(
+            top_ranks(&searcher, &collector)?,
+            vec![10.0, 9.0, 7.0, 6.0, 5.0, 3.0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diversified_top_docs_by_numeric_field() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let collector = DiversifiedTopDocs::with_limit(3, "cluster", 1);
+        assert_eq!(top_ranks(&searcher, &collector)?, vec![10.0, 9.0, 6.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diversified_top_docs_missing_key_field() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let collector = DiversifiedTopDocs::with_limit(5, "missing", 1);
+        let diversified_top_docs = searcher.search(&rank_query(), &collector)?;
+        let top_docs = searcher.search(&rank_query(), &TopDocs::with_limit(5))?;
+        assert_eq!(diversified_top_docs, top_docs);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_diversified_top_docs_zero_max_per_key() {
+        DiversifiedTopDocs::with_limit(5, "author", 0);
+    }
+}
@@ -88,6 +88,9 @@ use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
 mod count_collector;
 pub use self::count_collector::Count;
 
+mod distinct_count_collector;
+pub use self::distinct_count_collector::DistinctCount;
+
 mod histogram_collector;
 pub use histogram_collector::HistogramCollector;
 
@@ -96,10 +99,19 @@ pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
 mod top_collector;
 
+mod total_hits;
+pub use self::total_hits::{TotalHits, TotalHitsRelation};
+
 mod top_score_collector;
 pub use self::top_collector::ComparableDoc;
 pub use self::top_score_collector::{TopDocs, TopNComputer};
 
+mod diversified_top_collector;
+pub use self::diversified_top_collector::DiversifiedTopDocs;
+
+mod sort_key_top_collector;
+pub use self::sort_key_top_collector::{MissingValue, SortKey};
+
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
@@ -115,6 +127,12 @@ pub use self::docset_collector::DocSetCollector;
 mod filter_collector_wrapper;
 pub use self::filter_collector_wrapper::{BytesFilterCollector, FilterCollector};
 
+mod sampling_collector;
+pub use self::sampling_collector::{Sampled, SamplingCollector};
+
+mod streaming_collector;
+pub use self::streaming_collector::{StreamedHit, StreamingCollector};
+
 /// `Fruit` is the type for the result of our collection.
 /// e.g. `usize` for the `Count` collector.
 pub trait Fruit: Send + downcast_rs::Downcast {}
@@ -0,0 +1,247 @@
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// The `SamplingCollector` only passes a fraction of the matching documents on to an inner
+/// collector.
+///
+/// The sample is deterministic: a document is part of the sample if the hash of its doc id,
+/// mixed with a seed, falls below a threshold derived from the sampling rate. Running the same
+/// search on the same searcher twice yields the same sample.
+///
+/// The fruit exposes the sampling rate alongside the fruit of the inner collector, so that the
+/// counts computed over the sample, e.g. by an
+/// [`AggregationCollector`](crate::aggregation::AggregationCollector), can be scaled back to an
+/// estimate over all of the matching documents. This makes exploratory analytics affordable on
+/// very large indexes.
+///
+/// ```rust
+/// use tantivy::collector::{Count, SamplingCollector};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// for i in 0..1_000 {
+///     index_writer.add_document(doc!(title => format!("book {i}")))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let sampled_count = searcher.search(&AllQuery, &SamplingCollector::new(Count, 0.1))?;
+///
+/// assert_eq!(sampled_count.sampling_rate, 0.1);
+/// assert!((50..150).contains(&sampled_count.fruit));
+/// let estimated_count = sampled_count.scale_count(sampled_count.fruit as u64);
+/// assert!((500..1_500).contains(&estimated_count));
+/// # Ok(())
+/// # }
+/// ```
+pub struct SamplingCollector<TCollector> {
+    collector: TCollector,
+    sampling_rate: f64,
+    seed: u64,
+}
+
+impl<TCollector: Collector> SamplingCollector<TCollector> {
+    /// Creates a new `SamplingCollector`, passing a fraction `sampling_rate` of the matching
+    /// documents on to `collector`.
+    ///
+    /// # Panics
+    /// The method panics if `sampling_rate` is not within `(0, 1]`.
+    pub fn new(collector: TCollector, sampling_rate: f64) -> SamplingCollector<TCollector> {
+        assert!(
+            sampling_rate > 0.0 && sampling_rate <= 1.0,
+            "The sampling rate must be within (0, 1], got {sampling_rate}."
+        );
+        SamplingCollector {
+            collector,
+            sampling_rate,
+            seed: 0,
+        }
+    }
+
+    /// Sets the seed mixed with the doc ids, in order to draw a different sample.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> SamplingCollector<TCollector> {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the fraction of the matching documents that is collected.
+    pub fn sampling_rate(&self) -> f64 {
+        self.sampling_rate
+    }
+}
+
+/// Fruit of a [`SamplingCollector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sampled<TFruit> {
+    /// Fruit of the inner collector, computed over the sample.
+    pub fruit: TFruit,
+    /// Fraction of the matching documents that has been collected.
+    pub sampling_rate: f64,
+}
+
+impl<TFruit> Sampled<TFruit> {
+    /// Scales a count computed over the sample into an estimate of the count over all of the
+    /// matching documents.
+    pub fn scale_count(&self, count: u64) -> u64 {
+        (count as f64 / self.sampling_rate).round() as u64
+    }
+}
+
+/// Finalizer of the 64-bit murmur3 hash, which is enough to scatter consecutive doc ids.
+#[inline]
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^= hash >> 33;
+    hash
+}
+
+impl<TCollector: Collector> Collector for SamplingCollector<TCollector> {
+    type Fruit = Sampled<TCollector::Fruit>;
+
+    type Child = SamplingSegmentCollector<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let segment_collector = self
+            .collector
+            .for_segment(segment_local_id, segment_reader)?;
+        // The hashes are uniformly distributed over `[0, 2^64)`.
+        let threshold = (self.sampling_rate * (1u128 << 64) as f64) as u128;
+        Ok(SamplingSegmentCollector {
+            segment_collector,
+            seed: self.seed,
+            threshold,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        let fruit = self.collector.merge_fruits(segment_fruits)?;
+        Ok(Sampled {
+            fruit,
+            sampling_rate: self.sampling_rate,
+        })
+    }
+}
+
+/// Segment collector of the [`SamplingCollector`].
+pub struct SamplingSegmentCollector<TSegmentCollector> {
+    segment_collector: TSegmentCollector,
+    seed: u64,
+    threshold: u128,
+}
+
+impl<TSegmentCollector: SegmentCollector> SegmentCollector
+    for SamplingSegmentCollector<TSegmentCollector>
+{
+    type Fruit = TSegmentCollector::Fruit;
+
+    #[inline]
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if (mix(self.seed ^ doc as u64) as u128) < self.threshold {
+            self.segment_collector.collect(doc, score);
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.segment_collector.harvest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SamplingCollector;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::tests::create_searcher;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST};
+    use crate::TantivyDocument;
+
+    fn get_test_docs(num_docs: u64) -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let value = schema_builder.add_u64_field("value", FAST);
+        let docs = (0..num_docs).map(|i| doc!(value => i % 2)).collect();
+        (schema_builder.build(), vec![docs])
+    }
+
+    #[test]
+    fn test_sampling_collector() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs(10_000);
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let all = searcher.search(&AllQuery, &SamplingCollector::new(Count, 1.0))?;
+        assert_eq!(all.fruit, 10_000);
+        assert_eq!(all.scale_count(all.fruit as u64), 10_000);
+
+        let sampled = searcher.search(&AllQuery, &SamplingCollector::new(Count, 0.25))?;
+        assert_eq!(sampled.sampling_rate, 0.25);
+        assert!((2_300..2_700).contains(&sampled.fruit));
+        assert!((9_200..10_800).contains(&sampled.scale_count(sampled.fruit as u64)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampling_collector_is_deterministic() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs(1_000);
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let sample = |seed: u64| {
+            let collector = SamplingCollector::new(DocSetCollector, 0.5).with_seed(seed);
+            searcher
+                .search(&AllQuery, &collector)
+                .map(|sampled| sampled.fruit)
+        };
+        assert_eq!(sample(0)?, sample(0)?);
+        assert_ne!(sample(0)?, sample(1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sampled_aggregation() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs(10_000);
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let aggs: Aggregations = serde_json::from_value(json!({
+            "values": { "terms": { "field": "value" } }
+        }))
+        .unwrap();
+        let collector = SamplingCollector::new(
+            AggregationCollector::from_aggs(aggs, Default::default()),
+            0.1,
+        );
+        let sampled = searcher.search(&AllQuery, &collector)?;
+        let agg_res: &AggregationResults = &sampled.fruit;
+        let agg_res_json = serde_json::to_value(agg_res)?;
+        for bucket in agg_res_json["values"]["buckets"].as_array().unwrap() {
+            let doc_count = sampled.scale_count(bucket["doc_count"].as_u64().unwrap());
+            assert!((4_000..6_000).contains(&doc_count));
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sampling_collector_invalid_rate() {
+        SamplingCollector::new(Count, 0.0);
+    }
+}
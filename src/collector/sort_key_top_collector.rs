@@ -0,0 +1,525 @@
+use std::cmp::Ordering;
+
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector};
+use crate::schema::{OwnedValue, Type};
+use crate::{DateTime, DocAddress, DocId, Order, Score, SegmentReader, TantivyError};
+
+/// Defines what happens to the documents without any value for a sort key.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum MissingValue {
+    /// The documents without a value are ranked before all of the other documents.
+    First,
+    /// The documents without a value are ranked after all of the other documents.
+    #[default]
+    Last,
+    /// The documents without a value are ranked as if they had the given value.
+    ///
+    /// The value must match the type of the fast field, e.g. `OwnedValue::I64` for an i64
+    /// fast field, `OwnedValue::Date` for a date fast field.
+    Value(OwnedValue),
+}
+
+#[derive(Clone, Debug)]
+enum SortKeyKind {
+    Score,
+    FastField {
+        field: String,
+        missing: MissingValue,
+    },
+}
+
+/// A sort key of [`TopDocs::order_by`](crate::collector::TopDocs::order_by).
+///
+/// A sort key is either the score of the documents, or the first value of a fast field. Each
+/// sort key has its own direction.
+#[derive(Clone, Debug)]
+pub struct SortKey {
+    kind: SortKeyKind,
+    order: Order,
+}
+
+impl SortKey {
+    /// Sorts the documents by score.
+    ///
+    /// The score can be used as any of the sort keys, e.g. to break the ties of the previous
+    /// ones.
+    pub fn score(order: Order) -> SortKey {
+        SortKey {
+            kind: SortKeyKind::Score,
+            order,
+        }
+    }
+
+    /// Sorts the documents by the first value of a fast field.
+    ///
+    /// The fast field must be of type u64, i64, f64, bool or date. The documents without a value
+    /// are ranked last, see [`SortKey::missing`].
+    pub fn fast_field(field: impl ToString, order: Order) -> SortKey {
+        SortKey {
+            kind: SortKeyKind::FastField {
+                field: field.to_string(),
+                missing: MissingValue::default(),
+            },
+            order,
+        }
+    }
+
+    /// Sets how the documents without a value for the fast field are ranked.
+    ///
+    /// This has no effect on a score sort key.
+    #[must_use]
+    pub fn missing(mut self, missing_value: MissingValue) -> SortKey {
+        if let SortKeyKind::FastField { missing, .. } = &mut self.kind {
+            *missing = missing_value;
+        }
+        self
+    }
+}
+
+/// Value of a sort key for a document, along with its rank.
+///
+/// The rank is such that the greater the rank, the better the document, regardless of the
+/// sort direction.
+#[derive(Clone, Debug)]
+pub struct SortKeyValue {
+    /// Rank of the documents without a value relatively to the other ones: 2 if they come
+    /// first, 0 if they come last. 1 is used for the documents with a value.
+    missing_rank: u8,
+    rank: u64,
+    value: OwnedValue,
+}
+
+const MISSING_FIRST: u8 = 2;
+const NOT_MISSING: u8 = 1;
+const MISSING_LAST: u8 = 0;
+
+impl SortKeyValue {
+    fn new(rank: u64, order: &Order, value: OwnedValue) -> SortKeyValue {
+        let rank = if order.is_desc() {
+            rank
+        } else {
+            u64::MAX - rank
+        };
+        SortKeyValue {
+            missing_rank: NOT_MISSING,
+            rank,
+            value,
+        }
+    }
+
+    fn missing(missing_rank: u8) -> SortKeyValue {
+        SortKeyValue {
+            missing_rank,
+            rank: 0,
+            value: OwnedValue::Null,
+        }
+    }
+
+    fn key(&self) -> (u8, u64) {
+        (self.missing_rank, self.rank)
+    }
+}
+
+/// Values of all of the sort keys for a document, compared lexicographically.
+#[derive(Clone, Debug)]
+pub struct SortKeyValues(Vec<SortKeyValue>);
+
+impl PartialEq for SortKeyValues {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for SortKeyValues {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let left = self.0.iter().map(SortKeyValue::key);
+        let right = other.0.iter().map(SortKeyValue::key);
+        Some(left.cmp(right))
+    }
+}
+
+/// Converts a value read from a `u64` column back to its typed value.
+pub(crate) fn to_owned_value(value: u64, column_type: ColumnType) -> OwnedValue {
+    match column_type {
+        ColumnType::I64 => OwnedValue::I64(i64::from_u64(value)),
+        ColumnType::F64 => OwnedValue::F64(f64::from_u64(value)),
+        ColumnType::Bool => OwnedValue::Bool(bool::from_u64(value)),
+        ColumnType::DateTime => OwnedValue::Date(DateTime::from_u64(value)),
+        _ => OwnedValue::U64(value),
+    }
+}
+
+fn to_u64(value: &OwnedValue, value_type: Type) -> Option<u64> {
+    match (value, value_type) {
+        (OwnedValue::U64(val), Type::U64) => Some(val.to_u64()),
+        (OwnedValue::I64(val), Type::I64) => Some(val.to_u64()),
+        (OwnedValue::F64(val), Type::F64) => Some(val.to_u64()),
+        (OwnedValue::Bool(val), Type::Bool) => Some(val.to_u64()),
+        (OwnedValue::Date(val), Type::Date) => Some(val.to_u64()),
+        _ => None,
+    }
+}
+
+/// Returns the column type of the fast fields of type `value_type` that can be read as `u64`.
+pub(crate) fn column_type_of(value_type: Type) -> Option<ColumnType> {
+    match value_type {
+        Type::U64 => Some(ColumnType::U64),
+        Type::I64 => Some(ColumnType::I64),
+        Type::F64 => Some(ColumnType::F64),
+        Type::Bool => Some(ColumnType::Bool),
+        Type::Date => Some(ColumnType::DateTime),
+        _ => None,
+    }
+}
+
+enum SegmentSortKeyReader {
+    Score {
+        order: Order,
+    },
+    FastField {
+        column_opt: Option<Column<u64>>,
+        column_type: ColumnType,
+        order: Order,
+        missing: SortKeyValue,
+    },
+}
+
+impl SegmentSortKeyReader {
+    fn open(sort_key: &SortKey, segment_reader: &SegmentReader) -> crate::Result<Self> {
+        let order = sort_key.order.clone();
+        let (field_name, missing) = match &sort_key.kind {
+            SortKeyKind::Score => return Ok(SegmentSortKeyReader::Score { order }),
+            SortKeyKind::FastField { field, missing } => (field, missing),
+        };
+        let schema = segment_reader.schema();
+        let (field, _) = schema
+            .find_field(field_name)
+            .ok_or_else(|| TantivyError::FieldNotFound(field_name.to_string()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {field_name:?} is not a fast field."
+            )));
+        }
+        let value_type = field_entry.field_type().value_type();
+        let column_type = column_type_of(value_type).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Sorting by field {field_name:?} of type {value_type:?} is not supported."
+            ))
+        })?;
+        let missing = match missing {
+            MissingValue::First => SortKeyValue::missing(MISSING_FIRST),
+            MissingValue::Last => SortKeyValue::missing(MISSING_LAST),
+            MissingValue::Value(value) => {
+                let rank = to_u64(value, value_type).ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "Missing value {value:?} does not match the type {value_type:?} of field \
+                         {field_name:?}."
+                    ))
+                })?;
+                SortKeyValue::new(rank, &order, value.clone())
+            }
+        };
+        let column_opt = segment_reader
+            .fast_fields()
+            .u64_lenient_for_type(Some(&[column_type]), field_name)?
+            .map(|(column, _)| column);
+        Ok(SegmentSortKeyReader::FastField {
+            column_opt,
+            column_type,
+            order,
+            missing,
+        })
+    }
+
+    #[inline]
+    fn sort_key_value(&self, doc: DocId, score: Score) -> SortKeyValue {
+        match self {
+            SegmentSortKeyReader::Score { order } => SortKeyValue::new(
+                (score as f64).to_u64(),
+                order,
+                OwnedValue::F64(score as f64),
+            ),
+            SegmentSortKeyReader::FastField {
+                column_opt,
+                column_type,
+                order,
+                missing,
+            } => match column_opt.as_ref().and_then(|column| column.first(doc)) {
+                Some(value) => SortKeyValue::new(value, order, to_owned_value(value, *column_type)),
+                None => missing.clone(),
+            },
+        }
+    }
+}
+
+/// Collector of [`TopDocs::order_by`](crate::collector::TopDocs::order_by).
+pub(crate) struct SortKeyTopCollector {
+    sort_keys: Vec<SortKey>,
+    collector: TopCollector<SortKeyValues>,
+}
+
+impl SortKeyTopCollector {
+    pub fn new(
+        sort_keys: Vec<SortKey>,
+        collector: TopCollector<SortKeyValues>,
+    ) -> SortKeyTopCollector {
+        SortKeyTopCollector {
+            sort_keys,
+            collector,
+        }
+    }
+}
+
+impl Collector for SortKeyTopCollector {
+    type Fruit = Vec<(Vec<OwnedValue>, DocAddress)>;
+
+    type Child = SortKeyTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let key_readers = self
+            .sort_keys
+            .iter()
+            .map(|sort_key| SegmentSortKeyReader::open(sort_key, segment_reader))
+            .collect::<crate::Result<_>>()?;
+        let segment_collector = self.collector.for_segment(segment_local_id, segment_reader);
+        Ok(SortKeyTopSegmentCollector {
+            segment_collector,
+            key_readers,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.sort_keys
+            .iter()
+            .any(|sort_key| matches!(sort_key.kind, SortKeyKind::Score))
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<Vec<(SortKeyValues, DocAddress)>>,
+    ) -> crate::Result<Self::Fruit> {
+        let top_docs = self.collector.merge_fruits(segment_fruits)?;
+        Ok(top_docs
+            .into_iter()
+            .map(|(sort_key_values, doc_address)| {
+                let values = sort_key_values
+                    .0
+                    .into_iter()
+                    .map(|sort_key_value| sort_key_value.value)
+                    .collect();
+                (values, doc_address)
+            })
+            .collect())
+    }
+}
+
+/// Segment collector of [`TopDocs::order_by`](crate::collector::TopDocs::order_by).
+pub struct SortKeyTopSegmentCollector {
+    segment_collector: TopSegmentCollector<SortKeyValues>,
+    key_readers: Vec<SegmentSortKeyReader>,
+}
+
+impl SegmentCollector for SortKeyTopSegmentCollector {
+    type Fruit = Vec<(SortKeyValues, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let sort_key_values = self
+            .key_readers
+            .iter()
+            .map(|key_reader| key_reader.sort_key_value(doc, score))
+            .collect();
+        self.segment_collector
+            .collect(doc, SortKeyValues(sort_key_values));
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.segment_collector.harvest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MissingValue, SortKey};
+    use crate::collector::tests::create_searcher;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, OwnedValue, Schema, FAST, STRING, TEXT};
+    use crate::{DocAddress, Order, Searcher, TantivyDocument, Term};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_u64_field("category", FAST);
+        let price = schema_builder.add_f64_field("price", FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_text_field("name", STRING);
+        let segment_and_docs = vec![
+            vec![
+                doc!(category => 1u64, price => 10.0f64, text => "a"),
+                doc!(category => 2u64, price => 5.0f64, text => "a a"),
+                doc!(category => 1u64, text => "a a a"),
+            ],
+            vec![
+                doc!(category => 2u64, price => 7.5f64, text => "a"),
+                doc!(category => 1u64, price => 20.0f64, text => "a a"),
+                doc!(price => 1.0f64, text => "b"),
+            ],
+        ];
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    fn sort_values(
+        searcher: &Searcher,
+        top_docs: TopDocs,
+        sort_keys: Vec<SortKey>,
+    ) -> crate::Result<Vec<Vec<OwnedValue>>> {
+        let top_docs: Vec<(Vec<OwnedValue>, DocAddress)> =
+            searcher.search(&AllQuery, &top_docs.order_by(sort_keys))?;
+        Ok(top_docs.into_iter().map(|(values, _)| values).collect())
+    }
+
+    #[test]
+    fn test_sort_keys_mixed_directions() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let values = sort_values(
+            &searcher,
+            TopDocs::with_limit(10),
+            vec![
+                SortKey::fast_field("category", Order::Asc),
+                SortKey::fast_field("price", Order::Desc),
+            ],
+        )?;
+        assert_eq!(
+            values,
+            vec![
+                vec![OwnedValue::U64(1), OwnedValue::F64(20.0)],
+                vec![OwnedValue::U64(1), OwnedValue::F64(10.0)],
+                vec![OwnedValue::U64(1), OwnedValue::Null],
+                vec![OwnedValue::U64(2), OwnedValue::F64(7.5)],
+                vec![OwnedValue::U64(2), OwnedValue::F64(5.0)],
+                vec![OwnedValue::Null, OwnedValue::F64(1.0)],
+            ]
+        );
+        let values = sort_values(
+            &searcher,
+            TopDocs::with_limit(2).and_offset(1),
+            vec![
+                SortKey::fast_field("category", Order::Desc),
+                SortKey::fast_field("price", Order::Asc),
+            ],
+        )?;
+        assert_eq!(
+            values,
+            vec![
+                vec![OwnedValue::U64(2), OwnedValue::F64(7.5)],
+                vec![OwnedValue::U64(1), OwnedValue::F64(10.0)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_keys_missing_value_policy() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let price_values =
+            |missing: MissingValue, order: Order| -> crate::Result<Vec<OwnedValue>> {
+                let values = sort_values(
+                    &searcher,
+                    TopDocs::with_limit(10),
+                    vec![SortKey::fast_field("price", order).missing(missing)],
+                )?;
+                Ok(values
+                    .into_iter()
+                    .map(|mut values| values.remove(0))
+                    .collect())
+            };
+        let prices = price_values(MissingValue::First, Order::Asc)?;
+        assert_eq!(prices[0], OwnedValue::Null);
+        assert_eq!(prices[1], OwnedValue::F64(1.0));
+        let prices = price_values(MissingValue::First, Order::Desc)?;
+        assert_eq!(prices[0], OwnedValue::Null);
+        assert_eq!(prices[1], OwnedValue::F64(20.0));
+        let prices = price_values(MissingValue::Last, Order::Asc)?;
+        assert_eq!(prices[5], OwnedValue::Null);
+        let prices = price_values(MissingValue::Value(OwnedValue::F64(6.0)), Order::Asc)?;
+        assert_eq!(
+            prices,
+            vec![
+                OwnedValue::F64(1.0),
+                OwnedValue::F64(5.0),
+                OwnedValue::F64(6.0),
+                OwnedValue::F64(7.5),
+                OwnedValue::F64(10.0),
+                OwnedValue::F64(20.0),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_keys_score_tie_breaker() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let text = searcher.schema().get_field("text")?;
+        let query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = TopDocs::with_limit(10).order_by(vec![
+            SortKey::fast_field("category", Order::Asc),
+            SortKey::score(Order::Desc),
+            SortKey::fast_field("price", Order::Asc),
+        ]);
+        let top_docs: Vec<(Vec<OwnedValue>, DocAddress)> = searcher.search(&query, &top_docs)?;
+        let categories_and_prices: Vec<(OwnedValue, OwnedValue)> = top_docs
+            .iter()
+            .map(|(values, _)| (values[0].clone(), values[2].clone()))
+            .collect();
+        // Within a category, the docs with more occurrences of "a" come first.
+        assert_eq!(
+            categories_and_prices,
+            vec![
+                (OwnedValue::U64(1), OwnedValue::Null),
+                (OwnedValue::U64(1), OwnedValue::F64(20.0)),
+                (OwnedValue::U64(1), OwnedValue::F64(10.0)),
+                (OwnedValue::U64(2), OwnedValue::F64(5.0)),
+                (OwnedValue::U64(2), OwnedValue::F64(7.5)),
+            ]
+        );
+        for (values, doc_address) in &top_docs {
+            let OwnedValue::F64(score) = values[1] else {
+                panic!("the score should be a f64");
+            };
+            let explanation = query.explain(&searcher, *doc_address)?;
+            assert!((score - explanation.value() as f64).abs() < 1e-6);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_keys_invalid_fields() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let search = |sort_key: SortKey| {
+            searcher.search(&AllQuery, &TopDocs::with_limit(10).order_by(vec![sort_key]))
+        };
+        assert!(search(SortKey::fast_field("text", Order::Asc)).is_err());
+        assert!(search(SortKey::fast_field("name", Order::Asc)).is_err());
+        assert!(search(SortKey::fast_field("unknown", Order::Asc)).is_err());
+        assert!(search(
+            SortKey::fast_field("price", Order::Asc)
+                .missing(MissingValue::Value(OwnedValue::I64(1)))
+        )
+        .is_err());
+        Ok(())
+    }
+}
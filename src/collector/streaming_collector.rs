@@ -0,0 +1,338 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+
+use columnar::{Column, ColumnType};
+
+use crate::collector::sort_key_top_collector::{column_type_of, to_owned_value};
+use crate::collector::{Collector, SegmentCollector};
+use crate::schema::OwnedValue;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// A matching document streamed by a [`StreamingCollector`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamedHit {
+    /// Address of the matching document.
+    pub doc_address: DocAddress,
+    /// First value of each of the fast fields requested with
+    /// [`StreamingCollector::with_fast_field`], in the same order. The value is
+    /// `OwnedValue::Null` if the document has no value for the fast field.
+    pub fast_field_values: Vec<OwnedValue>,
+}
+
+#[derive(Clone)]
+enum HitSink {
+    Channel(SyncSender<StreamedHit>),
+    Callback(Arc<dyn Fn(StreamedHit) + Send + Sync>),
+}
+
+/// The `StreamingCollector` hands the matching documents over to a channel or a callback as the
+/// segments are searched, instead of buffering them.
+///
+/// This makes it possible to export tens of millions of matching documents with a bounded
+/// amount of memory. When the hits are sent to a bounded channel, the search blocks whenever the
+/// channel is full, so that a slow consumer slows down the search rather than letting the hits
+/// pile up. The search must therefore run on a different thread than the consumer.
+///
+/// The documents of a given segment are streamed in increasing doc id order, but the segments
+/// may be interleaved if the search runs on a multithreaded executor. If the receiver is dropped
+/// before the end of the search, the remaining hits are discarded.
+///
+/// The fruit is the number of streamed hits.
+///
+/// ```rust
+/// use tantivy::collector::StreamingCollector;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{OwnedValue, Schema, FAST};
+/// use tantivy::{doc, Index};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+/// for i in 0..1_000u64 {
+///     index_writer.add_document(doc!(price => i))?;
+/// }
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let (collector, receiver) = StreamingCollector::bounded(16);
+/// let collector = collector.with_fast_field("price");
+/// let search = std::thread::spawn(move || searcher.search(&AllQuery, &collector));
+///
+/// let mut sum = 0;
+/// for hit in receiver {
+///     if let OwnedValue::U64(price) = hit.fast_field_values[0] {
+///         sum += price;
+///     }
+/// }
+/// assert_eq!(search.join().unwrap()?, 1_000);
+/// assert_eq!(sum, 499_500);
+/// # Ok(())
+/// # }
+/// ```
+pub struct StreamingCollector {
+    sink: HitSink,
+    fast_fields: Vec<String>,
+    // Set as soon as the receiver is dropped, so that all of the segments stop streaming.
+    disconnected: Arc<AtomicBool>,
+}
+
+impl StreamingCollector {
+    /// Creates a `StreamingCollector` sending the hits to a bounded channel of capacity
+    /// `capacity`, and returns it along with the receiving end of the channel.
+    ///
+    /// The channel is closed once the collector, and the search using it, are dropped.
+    pub fn bounded(capacity: usize) -> (StreamingCollector, Receiver<StreamedHit>) {
+        let (sender, receiver) = sync_channel(capacity);
+        (StreamingCollector::from_sender(sender), receiver)
+    }
+
+    /// Creates a `StreamingCollector` sending the hits to `sender`.
+    pub fn from_sender(sender: SyncSender<StreamedHit>) -> StreamingCollector {
+        StreamingCollector {
+            sink: HitSink::Channel(sender),
+            fast_fields: Vec::new(),
+            disconnected: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a `StreamingCollector` calling `callback` on every hit.
+    ///
+    /// The callback may be called concurrently from several threads if the search runs on a
+    /// multithreaded executor. Blocking in the callback blocks the search.
+    pub fn from_callback<F>(callback: F) -> StreamingCollector
+    where
+        F: Fn(StreamedHit) + Send + Sync + 'static,
+    {
+        StreamingCollector {
+            sink: HitSink::Callback(Arc::new(callback)),
+            fast_fields: Vec::new(),
+            disconnected: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Adds the first value of a fast field to the streamed hits.
+    ///
+    /// The fast field must be of type u64, i64, f64, bool or date.
+    #[must_use]
+    pub fn with_fast_field(mut self, fast_field: impl ToString) -> StreamingCollector {
+        self.fast_fields.push(fast_field.to_string());
+        self
+    }
+
+    fn open_fast_field(
+        &self,
+        field_name: &str,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<(Option<Column<u64>>, ColumnType)> {
+        let schema = segment_reader.schema();
+        let (field, _) = schema
+            .find_field(field_name)
+            .ok_or_else(|| TantivyError::FieldNotFound(field_name.to_string()))?;
+        let field_entry = schema.get_field_entry(field);
+        if !field_entry.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {field_name:?} is not a fast field."
+            )));
+        }
+        let value_type = field_entry.field_type().value_type();
+        let column_type = column_type_of(value_type).ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Streaming the values of field {field_name:?} of type {value_type:?} is not \
+                 supported."
+            ))
+        })?;
+        let column_opt = segment_reader
+            .fast_fields()
+            .u64_lenient_for_type(Some(&[column_type]), field_name)?
+            .map(|(column, _)| column);
+        Ok((column_opt, column_type))
+    }
+}
+
+impl Collector for StreamingCollector {
+    type Fruit = u64;
+
+    type Child = StreamingSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        let fast_field_readers = self
+            .fast_fields
+            .iter()
+            .map(|field_name| self.open_fast_field(field_name, segment_reader))
+            .collect::<crate::Result<_>>()?;
+        Ok(StreamingSegmentCollector {
+            segment_local_id,
+            sink: self.sink.clone(),
+            fast_field_readers,
+            disconnected: self.disconnected.clone(),
+            num_streamed_hits: 0,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<u64>) -> crate::Result<u64> {
+        Ok(segment_fruits.into_iter().sum())
+    }
+}
+
+/// Segment collector of the [`StreamingCollector`].
+pub struct StreamingSegmentCollector {
+    segment_local_id: SegmentOrdinal,
+    sink: HitSink,
+    fast_field_readers: Vec<(Option<Column<u64>>, ColumnType)>,
+    disconnected: Arc<AtomicBool>,
+    num_streamed_hits: u64,
+}
+
+impl SegmentCollector for StreamingSegmentCollector {
+    type Fruit = u64;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        if self.disconnected.load(Ordering::Relaxed) {
+            return;
+        }
+        let fast_field_values = self
+            .fast_field_readers
+            .iter()
+            .map(|(column_opt, column_type)| {
+                column_opt
+                    .as_ref()
+                    .and_then(|column| column.first(doc))
+                    .map(|value| to_owned_value(value, *column_type))
+                    .unwrap_or(OwnedValue::Null)
+            })
+            .collect();
+        let hit = StreamedHit {
+            doc_address: DocAddress::new(self.segment_local_id, doc),
+            fast_field_values,
+        };
+        match &self.sink {
+            HitSink::Channel(sender) => {
+                if sender.send(hit).is_err() {
+                    self.disconnected.store(true, Ordering::Relaxed);
+                    return;
+                }
+            }
+            HitSink::Callback(callback) => callback(hit),
+        }
+        self.num_streamed_hits += 1;
+    }
+
+    fn harvest(self) -> u64 {
+        self.num_streamed_hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::StreamingCollector;
+    use crate::collector::tests::create_searcher;
+    use crate::collector::DocSetCollector;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, OwnedValue, Schema, Value, FAST, STRING};
+    use crate::{TantivyDocument, Term};
+
+    fn get_test_docs() -> (Schema, Vec<Vec<TantivyDocument>>) {
+        let mut schema_builder = Schema::builder();
+        let parity = schema_builder.add_text_field("parity", STRING);
+        let value = schema_builder.add_i64_field("value", FAST);
+        let mut segment_and_docs = vec![Vec::new()];
+        for i in 0..100i64 {
+            let parity_text = if i % 2 == 0 { "even" } else { "odd" };
+            let doc = if i == 99 {
+                doc!(parity => parity_text)
+            } else {
+                doc!(parity => parity_text, value => i)
+            };
+            segment_and_docs.last_mut().unwrap().push(doc);
+            if i % 30 == 0 {
+                segment_and_docs.push(Vec::new());
+            }
+        }
+        (schema_builder.build(), segment_and_docs)
+    }
+
+    #[test]
+    fn test_streaming_collector_channel() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let parity = searcher.schema().get_field("parity")?;
+        let query = TermQuery::new(
+            Term::from_field_text(parity, "odd"),
+            IndexRecordOption::Basic,
+        );
+        let expected_docs = searcher.search(&query, &DocSetCollector)?;
+
+        let (collector, receiver) = StreamingCollector::bounded(1);
+        let collector = collector.with_fast_field("value");
+        let search_searcher = searcher.clone();
+        let search = std::thread::spawn(move || search_searcher.search(&query, &collector));
+        let hits: Vec<_> = receiver.into_iter().collect();
+        assert_eq!(search.join().unwrap()?, 50);
+
+        assert_eq!(hits.len(), 50);
+        let streamed_docs = hits.iter().map(|hit| hit.doc_address).collect();
+        assert_eq!(expected_docs, streamed_docs);
+        let mut values: Vec<OwnedValue> = hits
+            .into_iter()
+            .map(|mut hit| hit.fast_field_values.remove(0))
+            .collect();
+        values.sort_by_key(|value| value.as_i64().unwrap_or(i64::MAX));
+        let mut expected_values: Vec<OwnedValue> =
+            (0..49).map(|i| OwnedValue::I64(2 * i + 1)).collect();
+        expected_values.push(OwnedValue::Null);
+        assert_eq!(values, expected_values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_collector_callback() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let doc_addresses = Arc::new(Mutex::new(Vec::new()));
+        let doc_addresses_clone = doc_addresses.clone();
+        let collector = StreamingCollector::from_callback(move |hit| {
+            doc_addresses_clone.lock().unwrap().push(hit.doc_address);
+        });
+        assert_eq!(searcher.search(&AllQuery, &collector)?, 100);
+        let doc_addresses = doc_addresses.lock().unwrap();
+        assert_eq!(doc_addresses.len(), 100);
+        assert!(doc_addresses
+            .iter()
+            .zip(doc_addresses.iter().skip(1))
+            .all(|(left, right)| left.segment_ord != right.segment_ord || left < right));
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_collector_dropped_receiver() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let (collector, receiver) = StreamingCollector::bounded(10);
+        drop(receiver);
+        assert_eq!(searcher.search(&AllQuery, &collector)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_collector_invalid_fast_field() -> crate::Result<()> {
+        let (schema, segment_and_docs) = get_test_docs();
+        let searcher = create_searcher(schema, segment_and_docs)?;
+        let collector = StreamingCollector::from_callback(|_| {}).with_fast_field("parity");
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
-use columnar::{BytesColumn, Column};
+use std::collections::HashMap;
+
+use columnar::{BytesColumn, Column, StrColumn};
 
 use super::*;
+use crate::indexer::NoMergePolicy;
 use crate::query::{AllQuery, QueryParser};
-use crate::schema::{Schema, FAST, TEXT};
+use crate::schema::{Schema, FAST, STRING, TEXT};
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
 use crate::{DateTime, DocAddress, Index, Searcher, TantivyDocument};
@@ -278,6 +281,107 @@ impl SegmentCollector for BytesFastFieldSegmentCollector {
     }
 }
 
+/// Counts the documents of each of the values of a multi-valued str fast field.
+struct TermCountsTestCollector {
+    field: String,
+}
+
+struct TermCountsSegmentCollector {
+    column_opt: Option<StrColumn>,
+    counts_per_term_ord: HashMap<u64, u64>,
+}
+
+impl Collector for TermCountsTestCollector {
+    type Fruit = HashMap<String, u64>;
+    type Child = TermCountsSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<TermCountsSegmentCollector> {
+        Ok(TermCountsSegmentCollector {
+            column_opt: segment_reader.fast_fields().str(&self.field)?,
+            counts_per_term_ord: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_counts: Vec<HashMap<String, u64>>,
+    ) -> crate::Result<HashMap<String, u64>> {
+        let mut counts = HashMap::new();
+        for (term, count) in segment_counts.into_iter().flatten() {
+            *counts.entry(term).or_default() += count;
+        }
+        Ok(counts)
+    }
+}
+
+impl SegmentCollector for TermCountsSegmentCollector {
+    type Fruit = HashMap<String, u64>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        if let Some(column) = self.column_opt.as_ref() {
+            for term_ord in column.term_ords(doc) {
+                *self.counts_per_term_ord.entry(term_ord).or_default() += 1;
+            }
+        }
+    }
+
+    fn harvest(self) -> HashMap<String, u64> {
+        let Some(column) = self.column_opt else {
+            return HashMap::new();
+        };
+        let mut term_ord_counts: Vec<(u64, u64)> = self.counts_per_term_ord.into_iter().collect();
+        term_ord_counts.sort_unstable();
+        let mut counts = HashMap::new();
+        let mut term_ord_counts_it = term_ord_counts.iter();
+        column
+            .sorted_ords_to_str_cb(
+                term_ord_counts.iter().map(|(term_ord, _)| *term_ord),
+                |term| {
+                    let (_, count) = term_ord_counts_it.next().unwrap();
+                    counts.insert(term.to_string(), *count);
+                    Ok(())
+                },
+            )
+            .unwrap();
+        counts
+    }
+}
+
+#[test]
+fn test_term_ords_multivalued_str_fast_field() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let tags_field = schema_builder.add_text_field("tags", STRING | FAST);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer.add_document(doc!(tags_field => "rust", tags_field => "search"))?;
+    index_writer.add_document(doc!(tags_field => "rust"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(tags_field => "search", tags_field => "index"))?;
+    index_writer.add_document(TantivyDocument::default())?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    let counts = searcher.search(
+        &AllQuery,
+        &TermCountsTestCollector {
+            field: "tags".to_string(),
+        },
+    )?;
+    let expected_counts: HashMap<String, u64> = [("index", 1), ("rust", 2), ("search", 2)]
+        .into_iter()
+        .map(|(term, count)| (term.to_string(), count))
+        .collect();
+    assert_eq!(counts, expected_counts);
+    Ok(())
+}
+
 fn make_test_searcher() -> crate::Result<Searcher> {
     let schema = Schema::builder().build();
     let index = Index::create_in_ram(schema);
@@ -288,6 +392,23 @@ fn make_test_searcher() -> crate::Result<Searcher> {
     Ok(index.reader()?.searcher())
 }
 
+/// Creates a searcher over an index with one segment per entry of `segment_and_docs`.
+pub fn create_searcher(
+    schema: Schema,
+    segment_and_docs: Vec<Vec<TantivyDocument>>,
+) -> crate::Result<Searcher> {
+    let index = Index::create_in_ram(schema);
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer.set_merge_policy(Box::new(NoMergePolicy));
+    for docs in segment_and_docs {
+        for doc in docs {
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+    }
+    Ok(index.reader()?.searcher())
+}
+
 #[test]
 fn test_option_collector_some() -> crate::Result<()> {
     let searcher = make_test_searcher()?;
@@ -4,6 +4,7 @@ use std::marker::PhantomData;
 use serde::{Deserialize, Serialize};
 
 use super::top_score_collector::TopNComputer;
+use crate::docset::TERMINATED;
 use crate::index::SegmentReader;
 use crate::{DocAddress, DocId, SegmentOrdinal};
 
@@ -68,9 +69,46 @@ impl<T: PartialOrd, D: PartialOrd, const R: bool> PartialEq for ComparableDoc<T,
 
 impl<T: PartialOrd, D: PartialOrd, const R: bool> Eq for ComparableDoc<T, D, R> {}
 
+/// Position of the last hit of a previous page within a segment.
+///
+/// A document comes after it if its feature is lower, or if the features are equal and its
+/// address is greater.
+#[derive(Clone)]
+pub(crate) struct SegmentSearchAfter<T> {
+    feature: T,
+    /// On a tie, documents come after the hit if their doc id is greater or equal.
+    first_doc_on_tie: DocId,
+}
+
+impl<T: PartialOrd> SegmentSearchAfter<T> {
+    fn new(feature: T, segment_ord: SegmentOrdinal, doc_address: DocAddress) -> Self {
+        let first_doc_on_tie = match segment_ord.cmp(&doc_address.segment_ord) {
+            Ordering::Less => TERMINATED,
+            Ordering::Equal => doc_address.doc_id + 1,
+            Ordering::Greater => 0,
+        };
+        SegmentSearchAfter {
+            feature,
+            first_doc_on_tie,
+        }
+    }
+
+    /// Returns true if the document `doc` with the given feature comes after the hit.
+    #[inline]
+    pub fn accepts(&self, feature: &T, doc: DocId) -> bool {
+        match feature.partial_cmp(&self.feature) {
+            Some(Ordering::Less) => true,
+            Some(Ordering::Equal) => doc >= self.first_doc_on_tie,
+            Some(Ordering::Greater) | None => false,
+        }
+    }
+}
+
 pub(crate) struct TopCollector<T> {
     pub limit: usize,
     pub offset: usize,
+    /// Feature and address of the last hit of the previous page.
+    pub search_after: Option<(T, DocAddress)>,
     _marker: PhantomData<T>,
 }
 
@@ -86,6 +124,7 @@ where T: PartialOrd + Clone
         Self {
             limit,
             offset: 0,
+            search_after: None,
             _marker: PhantomData,
         }
     }
@@ -99,6 +138,27 @@ where T: PartialOrd + Clone
         self
     }
 
+    /// Only collect the documents coming after the hit with the given feature and address,
+    /// i.e. the last hit of the previous page.
+    pub fn search_after(mut self, search_after: Option<(T, DocAddress)>) -> TopCollector<T> {
+        self.search_after = search_after;
+        self
+    }
+
+    /// Returns the position of the last hit of the previous page, within the segment
+    /// `segment_ord`.
+    pub(crate) fn segment_search_after(
+        &self,
+        segment_ord: SegmentOrdinal,
+    ) -> Option<SegmentSearchAfter<T>> {
+        let (feature, doc_address) = self.search_after.as_ref()?;
+        Some(SegmentSearchAfter::new(
+            feature.clone(),
+            segment_ord,
+            *doc_address,
+        ))
+    }
+
     pub fn merge_fruits(
         &self,
         children: Vec<Vec<(T, DocAddress)>>,
@@ -121,16 +181,20 @@ where T: PartialOrd + Clone
             .collect())
     }
 
-    pub(crate) fn for_segment<F: PartialOrd + Clone>(
+    pub(crate) fn for_segment(
         &self,
         segment_id: SegmentOrdinal,
         _: &SegmentReader,
-    ) -> TopSegmentCollector<F> {
-        TopSegmentCollector::new(segment_id, self.limit + self.offset)
+    ) -> TopSegmentCollector<T> {
+        let mut segment_collector = TopSegmentCollector::new(segment_id, self.limit + self.offset);
+        segment_collector.search_after = self.segment_search_after(segment_id);
+        segment_collector
     }
 
     /// Create a new TopCollector with the same limit and offset.
     ///
+    /// The position of the previous page is not kept, as it relies on the feature type.
+    ///
     /// Ideally we would use Into but the blanket implementation seems to cause the Scorer traits
     /// to fail.
     #[doc(hidden)]
@@ -138,6 +202,7 @@ where T: PartialOrd + Clone
         TopCollector {
             limit: self.limit,
             offset: self.offset,
+            search_after: None,
             _marker: PhantomData,
         }
     }
@@ -154,6 +219,7 @@ pub(crate) struct TopSegmentCollector<T> {
     /// have top-semantics instead of bottom semantics.
     topn_computer: TopNComputer<T, DocId>,
     segment_ord: u32,
+    search_after: Option<SegmentSearchAfter<T>>,
 }
 
 impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
@@ -161,6 +227,7 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
         TopSegmentCollector {
             topn_computer: TopNComputer::new(limit),
             segment_ord,
+            search_after: None,
         }
     }
 }
@@ -189,6 +256,11 @@ impl<T: PartialOrd + Clone> TopSegmentCollector<T> {
     /// will compare the lowest scoring item with the given one and keep whichever is greater.
     #[inline]
     pub fn collect(&mut self, doc: DocId, feature: T) {
+        if let Some(search_after) = &self.search_after {
+            if !search_after.accepts(&feature, doc) {
+                return;
+            }
+        }
         self.topn_computer.push(feature, doc);
     }
 }
@@ -9,13 +9,16 @@ use super::Collector;
 use crate::collector::custom_score_top_collector::{
     CustomScoreTopCollector, CustomScoreTopSegmentCollector,
 };
+use crate::collector::sort_key_top_collector::SortKeyTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
     CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    SortKey, TotalHits,
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
+use crate::schema::OwnedValue;
 use crate::termdict::TermOrdinal;
 use crate::{DocAddress, DocId, Order, Score, SegmentOrdinal, SegmentReader, TantivyError};
 
@@ -43,6 +46,17 @@ where
         segment_local_id: crate::SegmentOrdinal,
         segment: &SegmentReader,
     ) -> crate::Result<Self::Child> {
+        let schema_type = TFastValue::to_type();
+        if let Some(runtime_field_type) = segment.fast_fields().runtime_field_type(&self.field) {
+            let requested_type = runtime_field_type.value_type();
+            if schema_type != requested_type {
+                return Err(TantivyError::SchemaError(format!(
+                    "Runtime field {:?} is of type {schema_type:?}!={requested_type:?}",
+                    self.field
+                )));
+            }
+            return self.collector.for_segment(segment_local_id, segment);
+        }
         let schema = segment.schema();
         let field = schema.get_field(&self.field)?;
         let field_entry = schema.get_field_entry(field);
@@ -52,7 +66,6 @@ where
                 field_entry.name()
             )));
         }
-        let schema_type = TFastValue::to_type();
         let requested_type = field_entry.field_type().value_type();
         if schema_type != requested_type {
             return Err(TantivyError::SchemaError(format!(
@@ -243,6 +256,219 @@ impl SegmentCollector for StringConvertSegmentCollector {
     }
 }
 
+struct EarlyTerminatingTopCollector<TFastValue: FastValue> {
+    collector: FastFieldConvertCollector<CustomScoreTopCollector<ScorerByField, u64>, TFastValue>,
+    /// Number of hits after which the collection of a segment sorted by the same field and order
+    /// can stop, or `None` if the collection cannot stop early.
+    num_competitive_hits: Option<usize>,
+}
+
+impl<TFastValue: FastValue> EarlyTerminatingTopCollector<TFastValue> {
+    fn is_sorted_by_collector_field(&self, segment_reader: &SegmentReader) -> bool {
+        segment_reader
+            .sort_by_field()
+            .map(|sort_by_field| {
+                sort_by_field.field == self.collector.field
+                    && sort_by_field.order == self.collector.order
+            })
+            .unwrap_or(false)
+    }
+}
+
+impl<TFastValue: FastValue> Collector for EarlyTerminatingTopCollector<TFastValue> {
+    type Fruit = (Vec<(TFastValue, DocAddress)>, TotalHits);
+
+    type Child = EarlyTerminatingTopSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(EarlyTerminatingTopSegmentCollector {
+            segment_collector: self.collector.for_segment(segment_local_id, segment)?,
+            num_hits: 0,
+            terminated_early: false,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(Vec<(u64, DocAddress)>, TotalHits)>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut total_hits = TotalHits::exact(0);
+        let mut top_docs_fruits = Vec::with_capacity(segment_fruits.len());
+        for (top_docs, segment_total_hits) in segment_fruits {
+            total_hits = total_hits.merge(segment_total_hits);
+            top_docs_fruits.push(top_docs);
+        }
+        let top_docs = self.collector.merge_fruits(top_docs_fruits)?;
+        Ok((top_docs, total_hits))
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        let mut segment_collector = self.for_segment(segment_ord, reader)?;
+        let alive_bitset = reader.alive_bitset();
+        let is_alive = |doc: DocId| {
+            alive_bitset
+                .map(|alive_bitset| alive_bitset.is_alive(doc))
+                .unwrap_or(true)
+        };
+        match self.num_competitive_hits {
+            Some(num_competitive_hits) if self.is_sorted_by_collector_field(reader) => {
+                // The documents are visited in the order of the collector, so the first
+                // `num_competitive_hits` documents are the top documents of the segment.
+                weight.for_each_no_score_until(reader, &mut |doc| {
+                    if !is_alive(doc) {
+                        return true;
+                    }
+                    if segment_collector.num_hits as usize >= num_competitive_hits {
+                        segment_collector.num_hits += 1;
+                        segment_collector.terminated_early = true;
+                        return false;
+                    }
+                    segment_collector.collect(doc, 0.0);
+                    true
+                })?;
+            }
+            _ => {
+                weight.for_each_no_score(reader, &mut |docs| {
+                    for &doc in docs {
+                        if is_alive(doc) {
+                            segment_collector.collect(doc, 0.0);
+                        }
+                    }
+                })?;
+            }
+        }
+        Ok(segment_collector.harvest())
+    }
+}
+
+struct EarlyTerminatingTopSegmentCollector {
+    segment_collector: CustomScoreTopSegmentCollector<ScorerByFastFieldReader, u64>,
+    num_hits: u64,
+    terminated_early: bool,
+}
+
+impl SegmentCollector for EarlyTerminatingTopSegmentCollector {
+    type Fruit = (Vec<(u64, DocAddress)>, TotalHits);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.num_hits += 1;
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let total_hits = if self.terminated_early {
+            TotalHits::lower_bound(self.num_hits)
+        } else {
+            TotalHits::exact(self.num_hits)
+        };
+        (self.segment_collector.harvest(), total_hits)
+    }
+}
+
+struct TopDocsWithTotalHits {
+    top_docs: TopDocs,
+    track_total_hits_up_to: usize,
+}
+
+impl TopDocsWithTotalHits {
+    /// Number of hits counted per segment. Counting one hit past the threshold tells whether the
+    /// threshold has been exceeded.
+    fn segment_count_limit(&self) -> u32 {
+        u32::try_from(self.track_total_hits_up_to)
+            .unwrap_or(u32::MAX)
+            .saturating_add(1)
+    }
+}
+
+impl Collector for TopDocsWithTotalHits {
+    type Fruit = (Vec<(Score, DocAddress)>, TotalHits);
+
+    type Child = TopScoreSegmentCollectorWithTotalHits;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(TopScoreSegmentCollectorWithTotalHits {
+            segment_collector: self.top_docs.for_segment(segment_local_id, reader)?,
+            num_hits: 0,
+            count_limit: self.segment_count_limit(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<(Vec<(Score, DocAddress)>, u32)>,
+    ) -> crate::Result<Self::Fruit> {
+        let mut num_hits = 0u64;
+        let mut top_docs_fruits = Vec::with_capacity(segment_fruits.len());
+        for (top_docs, segment_num_hits) in segment_fruits {
+            num_hits += segment_num_hits as u64;
+            top_docs_fruits.push(top_docs);
+        }
+        let top_docs = self.top_docs.merge_fruits(top_docs_fruits)?;
+        let threshold = self.track_total_hits_up_to as u64;
+        let total_hits = if num_hits > threshold {
+            TotalHits::lower_bound(threshold)
+        } else {
+            TotalHits::exact(num_hits)
+        };
+        Ok((top_docs, total_hits))
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        // Counting the hits separately lets the top docs be collected with pruning, while the
+        // count stops at the threshold.
+        let num_hits = weight.count_up_to(reader, self.segment_count_limit())?;
+        let top_docs = self.top_docs.collect_segment(weight, segment_ord, reader)?;
+        Ok((top_docs, num_hits))
+    }
+}
+
+struct TopScoreSegmentCollectorWithTotalHits {
+    segment_collector: TopScoreSegmentCollector,
+    num_hits: u32,
+    count_limit: u32,
+}
+
+impl SegmentCollector for TopScoreSegmentCollectorWithTotalHits {
+    type Fruit = (Vec<(Score, DocAddress)>, u32);
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.num_hits < self.count_limit {
+            self.num_hits += 1;
+        }
+        self.segment_collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        (self.segment_collector.harvest(), self.num_hits)
+    }
+}
+
 /// The `TopDocs` collector keeps track of the top `K` documents
 /// sorted by their score.
 ///
@@ -287,14 +513,18 @@ impl SegmentCollector for StringConvertSegmentCollector {
 /// # Ok(())
 /// # }
 /// ```
-pub struct TopDocs(TopCollector<Score>);
+pub struct TopDocs {
+    collector: TopCollector<Score>,
+    /// Fast field value, as a `u64`, and address of the last hit of the previous page.
+    fast_value_search_after: Option<(u64, DocAddress)>,
+}
 
 impl fmt::Debug for TopDocs {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
             "TopDocs(limit={}, offset={})",
-            self.0.limit, self.0.offset
+            self.collector.limit, self.collector.offset
         )
     }
 }
@@ -351,7 +581,10 @@ impl TopDocs {
     /// # Panics
     /// The method panics if limit is 0
     pub fn with_limit(limit: usize) -> TopDocs {
-        TopDocs(TopCollector::with_limit(limit))
+        TopDocs {
+            collector: TopCollector::with_limit(limit),
+            fast_value_search_after: None,
+        }
     }
 
     /// Skip the first "offset" documents when collecting.
@@ -396,7 +629,132 @@ impl TopDocs {
     /// ```
     #[must_use]
     pub fn and_offset(self, offset: usize) -> TopDocs {
-        TopDocs(self.0.and_offset(offset))
+        TopDocs {
+            collector: self.collector.and_offset(offset),
+            ..self
+        }
+    }
+
+    /// Only collects the documents ranked after the given hit, typically the last hit of the
+    /// previous page.
+    ///
+    /// Contrary to [`TopDocs::and_offset`], the documents of the previous pages are not
+    /// collected again, so that the cost of fetching a page does not depend on its depth.
+    ///
+    /// The documents are ranked by decreasing score, and then by increasing address. Doc
+    /// addresses being specific to a searcher, all of the pages must be fetched using the same
+    /// searcher.
+    ///
+    /// This only applies to the ranking by score. When ranking the documents by a fast field,
+    /// use [`TopDocs::search_after_fast_value`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, TEXT};
+    /// use tantivy::{doc, DocAddress, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let schema = schema_builder.build();
+    /// let index = Index::create_in_ram(schema);
+    ///
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+    /// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of Lena Mukhina"))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// let first_page = searcher.search(&query, &TopDocs::with_limit(2))?;
+    /// let (last_score, last_doc_address) = first_page[1];
+    /// let second_page = searcher.search(
+    ///     &query,
+    ///     &TopDocs::with_limit(2).search_after(last_score, last_doc_address),
+    /// )?;
+    ///
+    /// assert_eq!(second_page.len(), 1);
+    /// assert_eq!(second_page[0].1, DocAddress::new(0, 3));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn search_after(self, score: Score, doc_address: DocAddress) -> TopDocs {
+        TopDocs {
+            collector: self.collector.search_after(Some((score, doc_address))),
+            ..self
+        }
+    }
+
+    /// Only collects the documents ranked after the given hit, when ranking the documents by a
+    /// fast field with [`TopDocs::order_by_fast_field`] or [`TopDocs::order_by_u64_field`].
+    ///
+    /// `value` is the fast field value of the last hit of the previous page. The documents
+    /// with the same value are ranked by increasing address. See [`TopDocs::search_after`].
+    #[must_use]
+    pub fn search_after_fast_value<TFastValue: FastValue>(
+        self,
+        value: TFastValue,
+        doc_address: DocAddress,
+    ) -> TopDocs {
+        TopDocs {
+            fast_value_search_after: Some((value.to_u64(), doc_address)),
+            ..self
+        }
+    }
+
+    /// Also returns the total number of hits, counted exactly up to `threshold` hits.
+    ///
+    /// Past `threshold`, the total number of hits is reported as
+    /// [`TotalHitsRelation::GreaterThanOrEqualTo`](crate::collector::TotalHitsRelation) with a
+    /// value of `threshold`. The hits past the threshold do not have to be counted, which lets
+    /// the top documents be collected with the same pruning as a plain `TopDocs` collector.
+    /// Combining `TopDocs` with [`Count`](crate::collector::Count) instead requires visiting and
+    /// scoring every matching document. Use `usize::MAX` to always count the hits exactly.
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, TEXT};
+    /// # use tantivy::{doc, Index};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::{TopDocs, TotalHits};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let title = schema_builder.add_text_field("title", TEXT);
+    /// #   let schema = schema_builder.build();
+    /// #   let index = Index::create_in_ram(schema);
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// #   for _ in 0..50 {
+    /// #       index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+    /// #   }
+    /// #   index_writer.commit()?;
+    /// #   let searcher = index.reader()?.searcher();
+    /// #   let query = QueryParser::for_index(&index, vec![title]).parse_query("diary")?;
+    /// let (top_docs, total_hits) =
+    ///     searcher.search(&query, &TopDocs::with_limit(3).track_total_hits(10))?;
+    /// assert_eq!(top_docs.len(), 3);
+    /// assert_eq!(total_hits, TotalHits::lower_bound(10));
+    ///
+    /// let (_, total_hits) =
+    ///     searcher.search(&query, &TopDocs::with_limit(3).track_total_hits(1_000))?;
+    /// assert_eq!(total_hits, TotalHits::exact(50));
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn track_total_hits(
+        self,
+        threshold: usize,
+    ) -> impl Collector<Fruit = (Vec<(Score, DocAddress)>, TotalHits)> {
+        TopDocsWithTotalHits {
+            top_docs: self,
+            track_total_hits_up_to: threshold,
+        }
     }
 
     /// Set top-K to rank documents by a given fast field.
@@ -475,12 +833,22 @@ impl TopDocs {
         field: impl ToString,
         order: Order,
     ) -> impl Collector<Fruit = Vec<(u64, DocAddress)>> {
+        // The scorer ranks the documents by the u64 representation of their value, reversed for
+        // the ascending order.
+        let search_after = self.fast_value_search_after.map(|(value, doc_address)| {
+            let feature = if order.is_desc() {
+                value
+            } else {
+                u64::MAX - value
+            };
+            (feature, doc_address)
+        });
         CustomScoreTopCollector::new(
             ScorerByField {
                 field: field.to_string(),
                 order,
             },
-            self.0.into_tscore(),
+            self.collector.into_tscore().search_after(search_after),
         )
     }
 
@@ -570,20 +938,107 @@ impl TopDocs {
         }
     }
 
+    /// Like [`TopDocs::order_by_fast_field`], but also returns the total number of hits, and
+    /// stops the collection of a segment early if the index is sorted by the same field and
+    /// order.
+    ///
+    /// When the index is sorted by `fast_field` in `order`, see
+    /// [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field), the documents of
+    /// a segment are visited in the order of the collector: once `limit + offset` matching
+    /// documents have been collected, the rest of the segment cannot contain any better
+    /// document and is skipped. The total number of hits is then a lower bound.
+    ///
+    /// On an index that is not sorted, or that is sorted differently, this collector visits all
+    /// of the matching documents, and the total number of hits is exact. The collection never
+    /// stops early when [`TopDocs::search_after_fast_value`] is used.
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, FAST, TEXT};
+    /// # use tantivy::{doc, Index, IndexSettings, IndexSortByField, Order};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::{TopDocs, TotalHitsRelation};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let title = schema_builder.add_text_field("title", TEXT);
+    /// #   let timestamp = schema_builder.add_u64_field("timestamp", FAST);
+    /// #   let schema = schema_builder.build();
+    /// let settings = IndexSettings {
+    ///     sort_by_field: Some(IndexSortByField {
+    ///         field: "timestamp".to_string(),
+    ///         order: Order::Desc,
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let index = Index::builder().schema(schema).settings(settings).create_in_ram()?;
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// #   for i in 0..100u64 {
+    /// #       index_writer.add_document(doc!(title => "news", timestamp => i))?;
+    /// #   }
+    /// #   index_writer.commit()?;
+    /// #   let searcher = index.reader()?.searcher();
+    /// #   let query = QueryParser::for_index(&index, vec![title]).parse_query("news")?;
+    /// let latest = TopDocs::with_limit(3).order_by_fast_field_early_terminated("timestamp", Order::Desc);
+    /// let (top_docs, total_hits) = searcher.search(&query, &latest)?;
+    /// let timestamps: Vec<u64> = top_docs.into_iter().map(|(timestamp, _)| timestamp).collect();
+    /// assert_eq!(timestamps, vec![99, 98, 97]);
+    /// assert_eq!(total_hits.relation, TotalHitsRelation::GreaterThanOrEqualTo);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn order_by_fast_field_early_terminated<TFastValue>(
+        self,
+        fast_field: impl ToString,
+        order: Order,
+    ) -> impl Collector<Fruit = (Vec<(TFastValue, DocAddress)>, TotalHits)>
+    where
+        TFastValue: FastValue,
+    {
+        let num_competitive_hits = if self.fast_value_search_after.is_none() {
+            Some(self.collector.limit + self.collector.offset)
+        } else {
+            None
+        };
+        let search_after = self.fast_value_search_after.map(|(value, doc_address)| {
+            let feature = if order.is_desc() {
+                value
+            } else {
+                u64::MAX - value
+            };
+            (feature, doc_address)
+        });
+        let u64_collector = CustomScoreTopCollector::new(
+            ScorerByField {
+                field: fast_field.to_string(),
+                order: order.clone(),
+            },
+            self.collector.into_tscore().search_after(search_after),
+        );
+        EarlyTerminatingTopCollector {
+            collector: FastFieldConvertCollector {
+                collector: u64_collector,
+                field: fast_field.to_string(),
+                fast_value: PhantomData,
+                order,
+            },
+            num_competitive_hits,
+        }
+    }
+
     /// Like `order_by_fast_field`, but for a `String` fast field.
     pub fn order_by_string_fast_field(
         self,
         fast_field: impl ToString,
         order: Order,
     ) -> impl Collector<Fruit = Vec<(String, DocAddress)>> {
-        let limit = self.0.limit;
-        let offset = self.0.offset;
+        let limit = self.collector.limit;
+        let offset = self.collector.offset;
         let u64_collector = CustomScoreTopCollector::new(
             ScorerByField {
                 field: fast_field.to_string(),
                 order: order.clone(),
             },
-            self.0.into_tscore(),
+            self.collector.into_tscore(),
         );
         StringConvertCollector {
             collector: u64_collector,
@@ -594,6 +1049,63 @@ impl TopDocs {
         }
     }
 
+    /// Set top-K to rank documents by several sort keys, each with its own direction.
+    ///
+    /// The documents are compared on the first sort key, then the ties are broken by the
+    /// second sort key, and so on. A sort key is either a u64, i64, f64, bool or date fast field,
+    /// or the score of the documents, anywhere in the list. The documents without a value for a
+    /// fast field are ranked according to the [`MissingValue`](crate::collector::MissingValue)
+    /// policy of the sort key. The remaining ties are broken by doc address.
+    ///
+    /// The fruit contains the values of the sort keys of each document, in the order of the
+    /// sort keys. The value of a missing fast field is `OwnedValue::Null`, unless a replacement
+    /// value was given.
+    ///
+    /// ```rust
+    /// # use tantivy::schema::{Schema, FAST, TEXT};
+    /// # use tantivy::{doc, Index, DocAddress, Order};
+    /// # use tantivy::query::QueryParser;
+    /// use tantivy::collector::{MissingValue, SortKey, TopDocs};
+    /// use tantivy::schema::OwnedValue;
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// #   let mut schema_builder = Schema::builder();
+    /// #   let title = schema_builder.add_text_field("title", TEXT);
+    /// #   let year = schema_builder.add_i64_field("year", FAST);
+    /// #   let rating = schema_builder.add_f64_field("rating", FAST);
+    /// #   let schema = schema_builder.build();
+    /// #   let index = Index::create_in_ram(schema);
+    /// #   let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// #   index_writer.add_document(doc!(title => "The Name of the Wind", year => 2007i64, rating => 4.5f64))?;
+    /// #   index_writer.add_document(doc!(title => "The Wise Man's Fear", year => 2011i64, rating => 4.5f64))?;
+    /// #   index_writer.add_document(doc!(title => "The Doors of Stone", rating => 5.0f64))?;
+    /// #   index_writer.commit()?;
+    /// #   let reader = index.reader()?;
+    /// #   let searcher = reader.searcher();
+    /// #   let query = QueryParser::for_index(&index, vec![title]).parse_query("the")?;
+    /// // Best rated first, then most recent first, books without a year last.
+    /// let top_docs = TopDocs::with_limit(10).order_by(vec![
+    ///     SortKey::fast_field("rating", Order::Desc),
+    ///     SortKey::fast_field("year", Order::Desc).missing(MissingValue::Last),
+    /// ]);
+    /// let docs: Vec<(Vec<OwnedValue>, DocAddress)> = searcher.search(&query, &top_docs)?;
+    /// assert_eq!(docs[0].0, vec![OwnedValue::F64(5.0), OwnedValue::Null]);
+    /// assert_eq!(docs[1].0, vec![OwnedValue::F64(4.5), OwnedValue::I64(2011)]);
+    /// assert_eq!(docs[2].0, vec![OwnedValue::F64(4.5), OwnedValue::I64(2007)]);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// To rank by a single fast field, [`TopDocs::order_by_fast_field`] is faster.
+    pub fn order_by(
+        self,
+        sort_keys: Vec<SortKey>,
+    ) -> impl Collector<Fruit = Vec<(Vec<OwnedValue>, DocAddress)>> {
+        SortKeyTopCollector::new(sort_keys, self.collector.into_tscore())
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to tweak or replace
@@ -699,7 +1211,7 @@ impl TopDocs {
         TScoreSegmentTweaker: ScoreSegmentTweaker<TScore> + 'static,
         TScoreTweaker: ScoreTweaker<TScore, Child = TScoreSegmentTweaker> + Send + Sync,
     {
-        TweakedScoreTopCollector::new(score_tweaker, self.0.into_tscore())
+        TweakedScoreTopCollector::new(score_tweaker, self.collector.into_tscore())
     }
 
     /// Ranks the documents using a custom score.
@@ -812,7 +1324,7 @@ impl TopDocs {
         TCustomSegmentScorer: CustomSegmentScorer<TScore> + 'static,
         TCustomScorer: CustomScorer<TScore, Child = TCustomSegmentScorer> + Send + Sync,
     {
-        CustomScoreTopCollector::new(custom_score, self.0.into_tscore())
+        CustomScoreTopCollector::new(custom_score, self.collector.into_tscore())
     }
 }
 
@@ -826,7 +1338,7 @@ impl Collector for TopDocs {
         segment_local_id: SegmentOrdinal,
         reader: &SegmentReader,
     ) -> crate::Result<Self::Child> {
-        let collector = self.0.for_segment(segment_local_id, reader);
+        let collector = self.collector.for_segment(segment_local_id, reader);
         Ok(TopScoreSegmentCollector(collector))
     }
 
@@ -838,7 +1350,7 @@ impl Collector for TopDocs {
         &self,
         child_fruits: Vec<Vec<(Score, DocAddress)>>,
     ) -> crate::Result<Self::Fruit> {
-        self.0.merge_fruits(child_fruits)
+        self.collector.merge_fruits(child_fruits)
     }
 
     fn collect_segment(
@@ -847,14 +1359,20 @@ impl Collector for TopDocs {
         segment_ord: u32,
         reader: &SegmentReader,
     ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
-        let heap_len = self.0.limit + self.0.offset;
+        let heap_len = self.collector.limit + self.collector.offset;
         let mut top_n: TopNComputer<_, _> = TopNComputer::new(heap_len);
+        let search_after = self.collector.segment_search_after(segment_ord);
+        let is_before_search_after = |doc: DocId, score: Score| {
+            search_after
+                .as_ref()
+                .is_some_and(|search_after| !search_after.accepts(&score, doc))
+        };
 
         if let Some(alive_bitset) = reader.alive_bitset() {
             let mut threshold = Score::MIN;
             top_n.threshold = Some(threshold);
             weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
-                if alive_bitset.is_deleted(doc) {
+                if alive_bitset.is_deleted(doc) || is_before_search_after(doc, score) {
                     return threshold;
                 }
                 top_n.push(score, doc);
@@ -863,6 +1381,9 @@ impl Collector for TopDocs {
             })?;
         } else {
             weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
+                if is_before_search_after(doc, score) {
+                    return top_n.threshold.unwrap_or(Score::MIN);
+                }
                 top_n.push(score, doc);
                 top_n.threshold.unwrap_or(Score::MIN)
             })?;
@@ -1054,14 +1575,14 @@ mod tests {
 
     use super::{TopDocs, TopNComputer};
     use crate::collector::top_collector::ComparableDoc;
-    use crate::collector::{Collector, DocSetCollector};
+    use crate::collector::{Collector, Count, DocSetCollector, TotalHits};
     use crate::query::{AllQuery, Query, QueryParser};
     use crate::schema::{Field, Schema, FAST, STORED, TEXT};
     use crate::time::format_description::well_known::Rfc3339;
     use crate::time::OffsetDateTime;
     use crate::{
-        assert_nearly_equals, DateTime, DocAddress, DocId, Index, IndexWriter, Order, Score,
-        SegmentReader,
+        assert_nearly_equals, DateTime, DocAddress, DocId, Index, IndexSettings, IndexSortByField,
+        IndexWriter, Order, Score, SegmentReader,
     };
 
     fn make_index() -> crate::Result<Index> {
@@ -1689,6 +2210,235 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_top_docs_search_after() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_i64_field(SIZE, FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for segment_docs in [
+            &[
+                ("beer", Some(3i64)),
+                ("beer beer", Some(-1)),
+                ("wine", None),
+            ][..],
+            &[
+                ("beer", Some(3)),
+                ("beer wine", Some(7)),
+                ("beer", Some(-1)),
+            ],
+            &[
+                ("beer", None),
+                ("beer beer beer", Some(3)),
+                ("deleted", Some(3)),
+            ],
+        ] {
+            for &(text, size_opt) in segment_docs {
+                let mut doc = doc!(title => text);
+                if let Some(size_value) = size_opt {
+                    doc.add_i64(size, size_value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(crate::Term::from_field_text(title, "deleted"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let beer_query = query_parser.parse_query("beer")?;
+        for query in [&AllQuery as &dyn Query, beer_query.as_ref()] {
+            let all_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            let mut paginated_docs = searcher.search(query, &TopDocs::with_limit(2))?;
+            while let Some(&(score, doc_address)) = paginated_docs.last() {
+                let top_docs = TopDocs::with_limit(2).search_after(score, doc_address);
+                let page = searcher.search(query, &top_docs)?;
+                if page.is_empty() {
+                    break;
+                }
+                paginated_docs.extend(page);
+            }
+            assert_eq!(paginated_docs, all_docs);
+        }
+
+        for order in [Order::Desc, Order::Asc] {
+            let all_docs: Vec<(i64, DocAddress)> = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(10).order_by_fast_field(SIZE, order.clone()),
+            )?;
+            assert_eq!(all_docs.len(), 8);
+            let mut paginated_docs: Vec<(i64, DocAddress)> = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(3).order_by_fast_field(SIZE, order.clone()),
+            )?;
+            while let Some(&(value, doc_address)) = paginated_docs.last() {
+                let top_docs = TopDocs::with_limit(3)
+                    .search_after_fast_value(value, doc_address)
+                    .order_by_fast_field(SIZE, order.clone());
+                let page: Vec<(i64, DocAddress)> = searcher.search(&AllQuery, &top_docs)?;
+                if page.is_empty() {
+                    break;
+                }
+                paginated_docs.extend(page);
+            }
+            assert_eq!(paginated_docs, all_docs);
+        }
+        Ok(())
+    }
+
+    fn index_sorted_by_size(sort_by_field: Option<IndexSortByField>) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field(TITLE, TEXT);
+        let size = schema_builder.add_i64_field(SIZE, FAST);
+        let schema = schema_builder.build();
+        let settings = IndexSettings {
+            sort_by_field,
+            ..Default::default()
+        };
+        let index = Index::builder()
+            .schema(schema)
+            .settings(settings)
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..300i64 {
+            let text = if i % 10 == 0 { "deleted beer" } else { "beer" };
+            let mut doc = doc!(title => text);
+            if i % 7 != 0 {
+                doc.add_i64(size, (i * 37) % 50 - 20);
+            }
+            index_writer.add_document(doc)?;
+            if i % 100 == 99 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.delete_term(crate::Term::from_field_text(title, "deleted"));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_order_by_fast_field_early_terminated() -> crate::Result<()> {
+        for order in [Order::Desc, Order::Asc] {
+            let index = index_sorted_by_size(Some(IndexSortByField {
+                field: SIZE.to_string(),
+                order: order.clone(),
+            }))?;
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.segment_readers().len(), 3);
+            let title = index.schema().get_field(TITLE)?;
+            let query = QueryParser::for_index(&index, vec![title]).parse_query("beer")?;
+            for (limit, offset) in [(1, 0), (5, 0), (5, 12), (1_000, 0)] {
+                let expected: Vec<(i64, DocAddress)> = searcher.search(
+                    &query,
+                    &TopDocs::with_limit(limit)
+                        .and_offset(offset)
+                        .order_by_fast_field(SIZE, order.clone()),
+                )?;
+                let (top_docs, total_hits): (Vec<(i64, DocAddress)>, TotalHits) = searcher.search(
+                    &query,
+                    &TopDocs::with_limit(limit)
+                        .and_offset(offset)
+                        .order_by_fast_field_early_terminated(SIZE, order.clone()),
+                )?;
+                assert_eq!(top_docs, expected);
+                if limit + offset < 90 {
+                    assert_eq!(
+                        total_hits,
+                        TotalHits::lower_bound(3 * (limit + offset + 1) as u64)
+                    );
+                } else {
+                    assert_eq!(total_hits, TotalHits::exact(270));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_by_fast_field_early_terminated_not_sorted() -> crate::Result<()> {
+        for sort_by_field in [
+            None,
+            Some(IndexSortByField {
+                field: SIZE.to_string(),
+                order: Order::Asc,
+            }),
+        ] {
+            let index = index_sorted_by_size(sort_by_field)?;
+            let searcher = index.reader()?.searcher();
+            let (top_docs, total_hits): (Vec<(i64, DocAddress)>, TotalHits) = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(3).order_by_fast_field_early_terminated(SIZE, Order::Desc),
+            )?;
+            let expected: Vec<(i64, DocAddress)> = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(3).order_by_fast_field(SIZE, Order::Desc),
+            )?;
+            assert_eq!(top_docs, expected);
+            assert_eq!(total_hits, TotalHits::exact(270));
+
+            // The collection does not stop early when paginating with `search_after`.
+            let (last_value, last_doc_address) = *top_docs.last().unwrap();
+            let (_, total_hits): (Vec<(i64, DocAddress)>, TotalHits) = searcher.search(
+                &AllQuery,
+                &TopDocs::with_limit(3)
+                    .search_after_fast_value(last_value, last_doc_address)
+                    .order_by_fast_field_early_terminated(SIZE, Order::Desc),
+            )?;
+            assert_eq!(total_hits, TotalHits::exact(270));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_track_total_hits() -> crate::Result<()> {
+        let index = index_sorted_by_size(None)?;
+        let searcher = index.reader()?.searcher();
+        let title = index.schema().get_field(TITLE)?;
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let beer_query = query_parser.parse_query("beer")?;
+        let deleted_query = query_parser.parse_query("deleted")?;
+        let boolean_query = query_parser.parse_query("beer -deleted")?;
+        for query in [
+            &AllQuery as &dyn Query,
+            beer_query.as_ref(),
+            deleted_query.as_ref(),
+            boolean_query.as_ref(),
+        ] {
+            let num_hits = searcher.search(query, &Count)?;
+            let expected_top_docs = searcher.search(query, &TopDocs::with_limit(5))?;
+            for threshold in [
+                0,
+                5,
+                num_hits.saturating_sub(1),
+                num_hits,
+                num_hits + 1,
+                usize::MAX,
+            ] {
+                let (top_docs, total_hits) =
+                    searcher.search(query, &TopDocs::with_limit(5).track_total_hits(threshold))?;
+                assert_eq!(top_docs, expected_top_docs);
+                let expected_total_hits = if num_hits > threshold {
+                    TotalHits::lower_bound(threshold as u64)
+                } else {
+                    TotalHits::exact(num_hits as u64)
+                };
+                assert_eq!(total_hits, expected_total_hits);
+
+                // The counting is the same when the collector is nested in another collector.
+                let ((_, total_hits), _) = searcher.search(
+                    query,
+                    &(TopDocs::with_limit(5).track_total_hits(threshold), Count),
+                )?;
+                assert_eq!(total_hits, expected_total_hits);
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_topn_computer_asc() {
         let mut computer: TopNComputer<u32, u32, false> = TopNComputer::new(2);
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// Relation between [`TotalHits::value`] and the actual number of documents matching the query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TotalHitsRelation {
+    /// The number of hits is exact.
+    #[serde(rename = "eq")]
+    EqualTo,
+    /// The number of hits is a lower bound of the actual number of hits.
+    #[serde(rename = "gte")]
+    GreaterThanOrEqualTo,
+}
+
+/// Number of documents matching a query, which may only be a lower bound when the collection
+/// stopped before visiting all of the matching documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotalHits {
+    /// Number of hits.
+    pub value: u64,
+    /// Whether `value` is exact or a lower bound.
+    pub relation: TotalHitsRelation,
+}
+
+impl TotalHits {
+    /// Creates an exact number of hits.
+    pub fn exact(value: u64) -> TotalHits {
+        TotalHits {
+            value,
+            relation: TotalHitsRelation::EqualTo,
+        }
+    }
+
+    /// Creates a lower bound of the number of hits.
+    pub fn lower_bound(value: u64) -> TotalHits {
+        TotalHits {
+            value,
+            relation: TotalHitsRelation::GreaterThanOrEqualTo,
+        }
+    }
+
+    /// Returns true if the number of hits is exact.
+    pub fn is_exact(&self) -> bool {
+        self.relation == TotalHitsRelation::EqualTo
+    }
+
+    /// Adds up the number of hits of several segments. The sum is exact if all of the counts
+    /// are exact.
+    pub fn merge(self, other: TotalHits) -> TotalHits {
+        let relation = if self.is_exact() && other.is_exact() {
+            TotalHitsRelation::EqualTo
+        } else {
+            TotalHitsRelation::GreaterThanOrEqualTo
+        };
+        TotalHits {
+            value: self.value + other.value,
+            relation,
+        }
+    }
+}
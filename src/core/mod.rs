@@ -1,6 +1,8 @@
 mod executor;
 #[doc(hidden)]
 pub mod json_utils;
+mod multi_searcher;
+mod scroll;
 pub mod searcher;
 
 use std::path::Path;
@@ -8,12 +10,21 @@ use std::path::Path;
 use once_cell::sync::Lazy;
 
 pub use self::executor::Executor;
+pub use self::multi_searcher::MultiSearcher;
+pub use self::scroll::{Scroll, ScrollCursor};
 pub use self::searcher::{Searcher, SearcherGeneration};
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
 pub static META_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("meta.json"));
 
+/// The prepared meta file contains the information about the list of segments of a commit
+/// that was made durable, but is not visible yet.
+///
+/// See [`PreparedCommit::persist`](crate::indexer::PreparedCommit::persist).
+pub static PREPARED_META_FILEPATH: Lazy<&'static Path> =
+    Lazy::new(|| Path::new("meta.prepared.json"));
+
 /// The managed file contains a list of files that were created by the tantivy
 /// and will therefore be garbage collected when they are deemed useless by tantivy.
 ///
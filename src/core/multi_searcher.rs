@@ -0,0 +1,189 @@
+use crate::collector::Collector;
+use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Field, Term};
+use crate::{DocAddress, Searcher, TantivyError};
+
+/// Searches several indexes sharing the same schema as if they were a single index.
+///
+/// The segments of the searchers are numbered one after the other: the segment ordinals
+/// of the [`DocAddress`]es returned by the collectors refer to this global numbering, and
+/// should be fetched with [`MultiSearcher::doc`].
+///
+/// Scores are computed from the statistics of all of the indexes, so that they are
+/// comparable across indexes.
+#[derive(Clone)]
+pub struct MultiSearcher {
+    searchers: Vec<Searcher>,
+}
+
+impl MultiSearcher {
+    /// Creates a searcher over the indexes of `searchers`.
+    ///
+    /// Returns an error if the searchers do not all share the same schema.
+    pub fn new(searchers: Vec<Searcher>) -> crate::Result<MultiSearcher> {
+        if let Some((first_searcher, other_searchers)) = searchers.split_first() {
+            if other_searchers
+                .iter()
+                .any(|searcher| searcher.schema() != first_searcher.schema())
+            {
+                return Err(TantivyError::InvalidArgument(
+                    "The searchers of a multi searcher must share the same schema".to_string(),
+                ));
+            }
+        }
+        Ok(MultiSearcher { searchers })
+    }
+
+    /// Returns the searchers of the individual indexes.
+    pub fn searchers(&self) -> &[Searcher] {
+        &self.searchers
+    }
+
+    /// Returns the overall number of documents in the indexes.
+    pub fn num_docs(&self) -> u64 {
+        self.searchers.iter().map(Searcher::num_docs).sum()
+    }
+
+    /// Return the overall number of documents containing the given term.
+    pub fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        let mut total_doc_freq = 0;
+        for searcher in &self.searchers {
+            total_doc_freq += searcher.doc_freq(term)?;
+        }
+        Ok(total_doc_freq)
+    }
+
+    /// Fetches a document given a [`DocAddress`] returned by a search on this searcher.
+    pub fn doc<D: DocumentDeserialize>(&self, doc_address: DocAddress) -> crate::Result<D> {
+        let mut segment_ord = doc_address.segment_ord;
+        for searcher in &self.searchers {
+            let num_segments = searcher.segment_readers().len() as u32;
+            if segment_ord < num_segments {
+                return searcher.doc(DocAddress::new(segment_ord, doc_address.doc_id));
+            }
+            segment_ord -= num_segments;
+        }
+        Err(TantivyError::InvalidArgument(format!(
+            "Segment ordinal {} is out of bounds",
+            doc_address.segment_ord
+        )))
+    }
+
+    /// Runs a query on the segments of all of the indexes.
+    ///
+    /// See [`Searcher::search`].
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let mut fruits = Vec::new();
+        let mut segment_ord = 0u32;
+        for searcher in &self.searchers {
+            let enabled_scoring = if collector.requires_scoring() {
+                EnableScoring::enabled_from_statistics_provider(self, searcher)
+            } else {
+                EnableScoring::disabled_from_searcher(searcher)
+            };
+            let weight = searcher.weight(query, enabled_scoring)?;
+            for segment_reader in searcher.segment_readers() {
+                fruits.push(collector.collect_segment(
+                    weight.as_ref(),
+                    segment_ord,
+                    segment_reader,
+                )?);
+                segment_ord += 1;
+            }
+        }
+        collector.merge_fruits(fruits)
+    }
+}
+
+impl Bm25StatisticsProvider for MultiSearcher {
+    fn total_num_tokens(&self, field: Field) -> crate::Result<u64> {
+        let mut total_num_tokens = 0u64;
+        for searcher in &self.searchers {
+            total_num_tokens += searcher.total_num_tokens(field)?;
+        }
+        Ok(total_num_tokens)
+    }
+
+    fn total_num_docs(&self) -> crate::Result<u64> {
+        let mut total_num_docs = 0u64;
+        for searcher in &self.searchers {
+            total_num_docs += searcher.total_num_docs()?;
+        }
+        Ok(total_num_docs)
+    }
+
+    fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+        self.doc_freq(term)
+    }
+
+    fn total_term_freq(&self, term: &Term) -> crate::Result<u64> {
+        let mut total_term_freq = 0u64;
+        for searcher in &self.searchers {
+            total_term_freq += searcher.total_term_freq(term)?;
+        }
+        Ok(total_term_freq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiSearcher;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter, Searcher, TantivyDocument, Term};
+
+    fn create_searcher(schema: &Schema, texts: &[&str]) -> crate::Result<Searcher> {
+        let text_field = schema.get_field("text").unwrap();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for text in texts {
+            index_writer.add_document(doc!(text_field => *text))?;
+        }
+        index_writer.commit()?;
+        Ok(index.reader()?.searcher())
+    }
+
+    #[test]
+    fn test_multi_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let searcher1 = create_searcher(&schema, &["hello", "hello world", "bye"])?;
+        let searcher2 = create_searcher(&schema, &["hello hello hello", "world"])?;
+        let multi_searcher = MultiSearcher::new(vec![searcher1, searcher2])?;
+        assert_eq!(multi_searcher.num_docs(), 5);
+        let term = Term::from_field_text(text_field, "hello");
+        assert_eq!(multi_searcher.doc_freq(&term)?, 3);
+
+        let query = TermQuery::new(term, IndexRecordOption::WithFreqs);
+        assert_eq!(multi_searcher.search(&query, &Count)?, 3);
+        let top_docs = multi_searcher.search(&query, &TopDocs::with_limit(1))?;
+        let doc: TantivyDocument = multi_searcher.doc(top_docs[0].1)?;
+        assert_eq!(
+            doc.get_first(text_field).and_then(|value| value.as_str()),
+            Some("hello hello hello")
+        );
+        assert!(multi_searcher
+            .doc::<TantivyDocument>(crate::DocAddress::new(2, 0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_searcher_requires_same_schema() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        let searcher1 = create_searcher(&schema_builder.build(), &[])?;
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", STRING);
+        let searcher2 = create_searcher(&schema_builder.build(), &[])?;
+        assert!(MultiSearcher::new(vec![searcher1, searcher2]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,492 @@
+use columnar::DynamicColumn;
+use serde::{Deserialize, Serialize};
+
+use crate::aggregation::metric::get_fast_field_values;
+use crate::query::{EnableScoring, Query, Scorer, Weight};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Field, OwnedValue};
+use crate::store::StoreReader;
+use crate::{DocAddress, DocId, DocSet, Searcher, SegmentOrdinal, TantivyError, TERMINATED};
+
+/// The position of a [`Scroll`], from which it can be resumed with
+/// [`Searcher::scroll_from`].
+///
+/// A cursor is only valid for the searcher it was created with: resuming a scroll on a
+/// searcher of another generation returns an error. Scrolls exported over several requests
+/// should use the searcher of a point in time, see
+/// [`IndexReader::open_point_in_time`](crate::IndexReader::open_point_in_time).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScrollCursor {
+    generation_id: u64,
+    segment_ord: SegmentOrdinal,
+    doc: DocId,
+}
+
+/// The values of the fast fields of a batch of documents.
+type FastFieldRows = Vec<(DocAddress, Vec<OwnedValue>)>;
+
+/// The scorer and the readers of the segment being scrolled.
+struct SegmentScroll {
+    segment_ord: SegmentOrdinal,
+    scorer: Box<dyn Scorer>,
+    store_reader: Option<StoreReader>,
+    // The columns of the fast fields of the last batch of fast field rows, with the names of the
+    // fields.
+    columns: Option<(Vec<String>, Vec<Vec<DynamicColumn>>)>,
+}
+
+/// Iterates over all of the documents matching a query, in doc address order and by batches.
+///
+/// Unlike a search, a scroll neither scores nor ranks the documents, and it does not need to
+/// hold all of them in memory: it is designed to export the documents matching a query rather
+/// than to find the best ones. Each batch holds up to `batch_size` documents of a single segment.
+/// The segment being scrolled keeps its scorer and its readers from a batch to the next.
+///
+/// ```rust
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST, STORED, TEXT};
+/// use tantivy::{doc, Index, IndexWriter, TantivyDocument};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT | STORED);
+/// let year = schema_builder.add_u64_field("year", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// for (i, book) in ["Dune", "Hyperion", "Neuromancer"].into_iter().enumerate() {
+///     index_writer.add_document(doc!(title => book, year => 1965 + 10 * i as u64))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let mut scroll = searcher.scroll(&AllQuery, 2)?;
+/// let batch = scroll.next_fast_field_rows(&["year"])?.unwrap();
+/// assert_eq!(batch.len(), 2);
+/// // The scroll can be resumed from its cursor, as long as the searcher is the same.
+/// let cursor = scroll.cursor();
+/// let mut scroll = searcher.scroll_from(&AllQuery, 2, cursor)?;
+/// let batch = scroll.next_documents::<TantivyDocument>()?.unwrap();
+/// assert_eq!(batch.len(), 1);
+/// assert!(scroll.next_documents::<TantivyDocument>()?.is_none());
+/// # Ok(())
+/// # }
+/// ```
+pub struct Scroll<'a> {
+    searcher: &'a Searcher,
+    weight: Box<dyn Weight>,
+    batch_size: usize,
+    // The address of the next document to visit.
+    segment_ord: SegmentOrdinal,
+    next_doc: DocId,
+    segment: Option<SegmentScroll>,
+    docs: Vec<DocId>,
+}
+
+impl<'a> Scroll<'a> {
+    pub(crate) fn new(
+        searcher: &'a Searcher,
+        query: &dyn Query,
+        batch_size: usize,
+        cursor: Option<ScrollCursor>,
+    ) -> crate::Result<Scroll<'a>> {
+        if batch_size == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The batch size of a scroll must be at least 1".to_string(),
+            ));
+        }
+        let (segment_ord, next_doc) = match cursor {
+            Some(cursor) => {
+                if cursor.generation_id != searcher.generation().generation_id() {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The scroll cursor was created by the searcher of generation {}, it \
+                         cannot be resumed on the searcher of generation {}",
+                        cursor.generation_id,
+                        searcher.generation().generation_id()
+                    )));
+                }
+                (cursor.segment_ord, cursor.doc)
+            }
+            None => (0, 0),
+        };
+        let weight = searcher.weight(query, EnableScoring::disabled_from_searcher(searcher))?;
+        Ok(Scroll {
+            searcher,
+            weight,
+            batch_size,
+            segment_ord,
+            next_doc,
+            segment: None,
+            docs: Vec::with_capacity(batch_size),
+        })
+    }
+
+    /// Returns the position of the scroll, right after the last batch returned.
+    pub fn cursor(&self) -> ScrollCursor {
+        ScrollCursor {
+            generation_id: self.searcher.generation().generation_id(),
+            segment_ord: self.segment_ord,
+            doc: self.next_doc,
+        }
+    }
+
+    /// Collects the next batch of alive documents in `self.docs`, leaving `self.segment` on their
+    /// segment.
+    ///
+    /// Returns false once all of the documents have been visited.
+    fn fill_batch(&mut self) -> crate::Result<bool> {
+        self.docs.clear();
+        let segment_readers = self.searcher.segment_readers();
+        while self.docs.is_empty() {
+            let Some(segment_reader) = segment_readers.get(self.segment_ord as usize) else {
+                return Ok(false);
+            };
+            if self.segment.as_ref().map(|segment| segment.segment_ord) != Some(self.segment_ord) {
+                let mut scorer = self.weight.scorer(segment_reader, 1.0)?;
+                if scorer.doc() < self.next_doc {
+                    scorer.seek(self.next_doc);
+                }
+                self.segment = Some(SegmentScroll {
+                    segment_ord: self.segment_ord,
+                    scorer,
+                    store_reader: None,
+                    columns: None,
+                });
+            }
+            let segment = self.segment.as_mut().expect("The segment was just opened");
+            let alive_bitset = segment_reader.alive_bitset();
+            let mut doc = segment.scorer.doc();
+            while doc != TERMINATED && self.docs.len() < self.batch_size {
+                if !alive_bitset.is_some_and(|alive_bitset| alive_bitset.is_deleted(doc)) {
+                    self.docs.push(doc);
+                }
+                doc = segment.scorer.advance();
+            }
+            if doc == TERMINATED {
+                // The segment is kept to read the documents of the batch, it is replaced by the
+                // next one on the next call.
+                self.segment_ord += 1;
+                self.next_doc = 0;
+            } else {
+                self.next_doc = doc;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the addresses of the next batch of documents, or `None` once all of the
+    /// documents matching the query have been returned.
+    pub fn next_doc_addresses(&mut self) -> crate::Result<Option<Vec<DocAddress>>> {
+        if !self.fill_batch()? {
+            return Ok(None);
+        }
+        let segment_ord = self.batch_segment_ord();
+        Ok(Some(
+            self.docs
+                .iter()
+                .map(|&doc| DocAddress::new(segment_ord, doc))
+                .collect(),
+        ))
+    }
+
+    /// Returns the next batch of documents, read from the doc store, or `None` once all of the
+    /// documents matching the query have been returned.
+    pub fn next_documents<D: DocumentDeserialize>(
+        &mut self,
+    ) -> crate::Result<Option<Vec<(DocAddress, D)>>> {
+        self.next_stored_documents(None)
+    }
+
+    /// Same as [`Scroll::next_documents`], but only loads the values of the given stored
+    /// `fields`, see [`Searcher::doc_projected`].
+    pub fn next_documents_projected<D: DocumentDeserialize>(
+        &mut self,
+        fields: &[Field],
+    ) -> crate::Result<Option<Vec<(DocAddress, D)>>> {
+        self.next_stored_documents(Some(fields))
+    }
+
+    fn next_stored_documents<D: DocumentDeserialize>(
+        &mut self,
+        fields: Option<&[Field]>,
+    ) -> crate::Result<Option<Vec<(DocAddress, D)>>> {
+        if !self.fill_batch()? {
+            return Ok(None);
+        }
+        let segment_ord = self.batch_segment_ord();
+        let segment = self.segment.as_mut().expect("A batch has a segment");
+        if segment.store_reader.is_none() {
+            // The documents are read in doc id order: caching the last block is enough.
+            let segment_reader = self.searcher.segment_reader(segment_ord);
+            segment.store_reader = Some(segment_reader.get_store_reader(1)?);
+        }
+        let store_reader = segment
+            .store_reader
+            .as_ref()
+            .expect("The store was just opened");
+        let mut documents = Vec::with_capacity(self.docs.len());
+        for &doc in &self.docs {
+            let document = match fields {
+                Some(fields) => store_reader.get_projected(doc, fields)?,
+                None => store_reader.get(doc)?,
+            };
+            documents.push((DocAddress::new(segment_ord, doc), document));
+        }
+        Ok(Some(documents))
+    }
+
+    /// Returns the values of the fast fields `field_names` of the next batch of documents, or
+    /// `None` once all of the documents matching the query have been returned.
+    ///
+    /// The values of a document are given in the order of `field_names`: the value of a field
+    /// is [`OwnedValue::Null`] if the document has no value, the value itself if it has a single
+    /// one, and an [`OwnedValue::Array`] of them otherwise.
+    pub fn next_fast_field_rows(
+        &mut self,
+        field_names: &[&str],
+    ) -> crate::Result<Option<FastFieldRows>> {
+        if !self.fill_batch()? {
+            return Ok(None);
+        }
+        let segment_ord = self.batch_segment_ord();
+        let segment = self.segment.as_mut().expect("A batch has a segment");
+        let has_columns = segment
+            .columns
+            .as_ref()
+            .is_some_and(|(column_field_names, _)| column_field_names == field_names);
+        if !has_columns {
+            let fast_fields = self.searcher.segment_reader(segment_ord).fast_fields();
+            let mut columns = Vec::with_capacity(field_names.len());
+            for field_name in field_names {
+                let field_columns = fast_fields
+                    .dynamic_column_handles(field_name)?
+                    .into_iter()
+                    .map(|handle| handle.open())
+                    .collect::<std::io::Result<Vec<DynamicColumn>>>()?;
+                columns.push(field_columns);
+            }
+            let field_names = field_names.iter().map(ToString::to_string).collect();
+            segment.columns = Some((field_names, columns));
+        }
+        let (_, columns) = segment
+            .columns
+            .as_ref()
+            .expect("The columns were just opened");
+        let rows = self
+            .docs
+            .iter()
+            .map(|&doc| {
+                let values = columns
+                    .iter()
+                    .map(|field_columns| {
+                        let mut values = get_fast_field_values(field_columns, doc);
+                        match values.len() {
+                            0 => OwnedValue::Null,
+                            1 => OwnedValue::from(values.pop().unwrap()),
+                            _ => OwnedValue::Array(
+                                values.into_iter().map(OwnedValue::from).collect(),
+                            ),
+                        }
+                    })
+                    .collect();
+                (DocAddress::new(segment_ord, doc), values)
+            })
+            .collect();
+        Ok(Some(rows))
+    }
+
+    /// The segment of the documents of the last batch.
+    fn batch_segment_ord(&self) -> SegmentOrdinal {
+        self.segment
+            .as_ref()
+            .expect("A batch has a segment")
+            .segment_ord
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, OffsetDateTime};
+
+    use crate::collector::{Count, DocSetCollector};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{
+        IndexRecordOption, OwnedValue, Schema, Value, FAST, INDEXED, STORED, STRING,
+    };
+    use crate::{DateTime, DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_scroll() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED | FAST);
+        let color = schema_builder.add_text_field("color", STRING | STORED);
+        let tag = schema_builder.add_u64_field("tag", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_id in 0..3u64 {
+            for doc_id in 0..5u64 {
+                let id_value = segment_id * 5 + doc_id;
+                let color_value = if id_value % 2 == 0 { "red" } else { "blue" };
+                let mut doc = doc!(id => id_value, color => color_value);
+                for tag_value in 0..id_value % 3 {
+                    doc.add_u64(tag, tag_value);
+                }
+                index_writer.add_document(doc)?;
+            }
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_u64(id, 6));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        // The batches follow the doc address order, never span segments, and skip the deleted
+        // documents.
+        let mut scroll = searcher.scroll(&AllQuery, 3)?;
+        let mut batches = Vec::new();
+        while let Some(batch) = scroll.next_doc_addresses()? {
+            assert!(batch
+                .iter()
+                .all(|doc_address| doc_address.segment_ord == batch[0].segment_ord));
+            batches.push(batch.len());
+        }
+        // The segment with the deleted document has 4 documents left.
+        batches.sort();
+        assert_eq!(batches, [1, 2, 2, 3, 3, 3]);
+        let mut doc_addresses = Vec::new();
+        let mut scroll = searcher.scroll(&AllQuery, 4)?;
+        while let Some(batch) = scroll.next_doc_addresses()? {
+            doc_addresses.extend(batch);
+        }
+        let mut expected_doc_addresses: Vec<DocAddress> = searcher
+            .search(&AllQuery, &DocSetCollector)?
+            .into_iter()
+            .collect();
+        expected_doc_addresses.sort();
+        assert_eq!(doc_addresses, expected_doc_addresses);
+
+        // Only the documents matching the query are returned.
+        let red_query = TermQuery::new(
+            Term::from_field_text(color, "red"),
+            IndexRecordOption::Basic,
+        );
+        let mut ids = Vec::new();
+        let mut scroll = searcher.scroll(&red_query, 2)?;
+        while let Some(batch) = scroll.next_documents::<TantivyDocument>()? {
+            for (_, doc) in batch {
+                ids.push(doc.get_first(id).unwrap().as_u64().unwrap());
+            }
+        }
+        ids.sort();
+        assert_eq!(ids, [0, 2, 4, 8, 10, 12, 14]);
+        assert_eq!(searcher.search(&red_query, &Count)?, ids.len());
+
+        // The fast field rows hold null, single values or arrays.
+        let mut scroll = searcher.scroll(&AllQuery, 3)?;
+        let mut rows: Vec<Vec<OwnedValue>> = Vec::new();
+        while let Some(batch) = scroll.next_fast_field_rows(&["id", "tag", "missing"])? {
+            rows.extend(batch.into_iter().map(|(_, values)| values));
+        }
+        assert_eq!(rows.len(), 14);
+        rows.sort_by_key(|values| match values[0] {
+            OwnedValue::U64(id_value) => id_value,
+            _ => u64::MAX,
+        });
+        assert_eq!(
+            rows[..3],
+            [
+                vec![OwnedValue::U64(0), OwnedValue::Null, OwnedValue::Null],
+                vec![OwnedValue::U64(1), OwnedValue::U64(0), OwnedValue::Null],
+                vec![
+                    OwnedValue::U64(2),
+                    OwnedValue::Array(vec![OwnedValue::U64(0), OwnedValue::U64(1)]),
+                    OwnedValue::Null
+                ],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scroll_resume_from_cursor() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for id_value in 0..7u64 {
+            index_writer.add_document(doc!(id => id_value))?;
+            if id_value % 3 == 2 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+
+        let mut ids = Vec::new();
+        let mut cursor = searcher.scroll(&AllQuery, 2)?.cursor();
+        loop {
+            // Each batch is read by a new scroll, resumed from the cursor of the previous one.
+            let mut scroll = searcher.scroll_from(&AllQuery, 2, cursor)?;
+            let Some(batch) = scroll.next_documents_projected::<TantivyDocument>(&[id])? else {
+                break;
+            };
+            for (_, doc) in batch {
+                ids.push(doc.get_first(id).unwrap().as_u64().unwrap());
+            }
+            cursor = scroll.cursor();
+        }
+        ids.sort();
+        assert_eq!(ids, [0, 1, 2, 3, 4, 5, 6]);
+        let cursor_json = serde_json::to_string(&cursor).unwrap();
+        let cursor = serde_json::from_str(&cursor_json).unwrap();
+        assert!(searcher
+            .scroll_from(&AllQuery, 2, cursor)?
+            .next_doc_addresses()?
+            .is_none());
+
+        // A cursor cannot be resumed on a searcher of another generation.
+        index_writer.add_document(doc!(id => 7u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert!(reader.searcher().scroll_from(&AllQuery, 2, cursor).is_err());
+        assert!(searcher.scroll(&AllQuery, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scroll_skips_expired_docs() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED | FAST);
+        let expires_at = schema_builder.add_date_field("expires_at", FAST);
+        let mut index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let now = OffsetDateTime::now_utc();
+        index_writer.add_document(doc!(
+            id => 0u64,
+            expires_at => DateTime::from_utc(now - Duration::hours(1)),
+        ))?;
+        index_writer.add_document(doc!(
+            id => 1u64,
+            expires_at => DateTime::from_utc(now + Duration::hours(1)),
+        ))?;
+        index_writer.add_document(doc!(id => 2u64))?;
+        index_writer.commit()?;
+        // The expired document is not deleted yet, but it must not be exported.
+        index.settings_mut().expiration_field = Some("expires_at".to_string());
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let mut ids = Vec::new();
+        let mut scroll = searcher.scroll(&AllQuery, 2)?;
+        while let Some(batch) = scroll.next_documents::<TantivyDocument>()? {
+            for (_, doc) in batch {
+                ids.push(doc.get_first(id).unwrap().as_u64().unwrap());
+            }
+        }
+        ids.sort();
+        assert_eq!(ids, [1, 2]);
+        Ok(())
+    }
+}
@@ -1,16 +1,22 @@
-use std::collections::BTreeMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::{fmt, io};
 
-use crate::collector::Collector;
-use crate::core::Executor;
+use crate::collector::{Collector, DistinctCount};
+use crate::core::{Executor, Scroll, ScrollCursor};
+use crate::fastfield::RuntimeField;
 use crate::index::{SegmentId, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::indexer::expiration::expired_docs_query;
+use crate::query::{
+    AdditiveDocBoostWeight, Bm25StatisticsProvider, BooleanQuery, EnableScoring, Occur, Query,
+    Weight,
+};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Field, Schema, Term};
 use crate::space_usage::SearcherSpaceUsage;
-use crate::store::{CacheStats, StoreReader};
-use crate::{DocAddress, Index, Opstamp, TrackedObject};
+use crate::store::{CacheStats, JsonPathFilter, StoreReader};
+use crate::{telemetry, DocAddress, DocBoostMode, Index, Opstamp, TantivyError, TrackedObject};
 
 /// Identifies the searcher generation accessed by a [`Searcher`].
 ///
@@ -90,6 +96,76 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Fetches a document from tantivy's store given a [`DocAddress`], only loading the values
+    /// of the given stored `fields`.
+    ///
+    /// The values of the other fields are skipped without being decoded. This is cheaper than
+    /// [`Searcher::doc`] when documents have many stored fields and only a few of them are
+    /// needed.
+    pub fn doc_projected<D: DocumentDeserialize>(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_projected(doc_address.doc_id, fields)
+    }
+
+    /// Fetches a document from tantivy's store given a [`DocAddress`], only keeping the paths of
+    /// the values of `json_field` selected by `filter`.
+    ///
+    /// The other paths are skipped without being decoded, which avoids materializing large
+    /// JSON values when only a few of their paths are needed.
+    pub fn doc_with_json_filter<D: DocumentDeserialize>(
+        &self,
+        doc_address: DocAddress,
+        json_field: Field,
+        filter: &JsonPathFilter,
+    ) -> crate::Result<D> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_with_json_filter(doc_address.doc_id, json_field, filter)
+    }
+
+    /// Returns a searcher over the same segments, whose fast fields include `runtime_fields`.
+    ///
+    /// The runtime fields replace the ones of this searcher, if any. Returns an error if a
+    /// runtime field has the name of a field of the schema, or if two of them have the same name.
+    pub fn with_runtime_fields(
+        &self,
+        runtime_fields: Vec<RuntimeField>,
+    ) -> crate::Result<Searcher> {
+        for (ord, runtime_field) in runtime_fields.iter().enumerate() {
+            let name = runtime_field.name();
+            if self.schema().get_field(name).is_ok() {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The runtime field {name:?} has the name of a field of the schema"
+                )));
+            }
+            if runtime_fields[..ord]
+                .iter()
+                .any(|previous_field| previous_field.name() == name)
+            {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The runtime field {name:?} is defined twice"
+                )));
+            }
+        }
+        let segment_readers = self
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.with_runtime_fields(&runtime_fields))
+            .collect();
+        let inner = SearcherInner {
+            schema: self.inner.schema.clone(),
+            index: self.inner.index.clone(),
+            segment_readers,
+            store_readers: self.inner.store_readers.clone(),
+            generation: self.inner.generation.clone(),
+            total_term_freqs: Mutex::default(),
+        };
+        Ok(Searcher::from(Arc::new(inner)))
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -140,6 +216,32 @@ impl Searcher {
         Ok(total_doc_freq)
     }
 
+    /// Return the overall number of occurrences of the given term.
+    ///
+    /// The result is cached, as it may require decoding the whole posting lists of the term.
+    pub fn total_term_freq(&self, term: &Term) -> crate::Result<u64> {
+        if let Some(&total_term_freq) = self
+            .inner
+            .total_term_freqs
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .get(term)
+        {
+            return Ok(total_term_freq);
+        }
+        let mut total_term_freq = 0u64;
+        for segment_reader in &self.inner.segment_readers {
+            let inverted_index = segment_reader.inverted_index(term.field())?;
+            total_term_freq += inverted_index.total_term_freq(term)?;
+        }
+        self.inner
+            .total_term_freqs
+            .lock()
+            .expect("Lock poisoned. This should never happen")
+            .insert(term.clone(), total_term_freq);
+        Ok(total_term_freq)
+    }
+
     /// Return the overall number of documents containing
     /// the given term in an asynchronous manner.
     #[cfg(feature = "quickwit")]
@@ -185,6 +287,37 @@ impl Searcher {
         self.search_with_statistics_provider(query, collector, self)
     }
 
+    /// Estimates the number of distinct values of a fast field among the documents matching
+    /// `query`.
+    ///
+    /// This is a shortcut for searching with a [`DistinctCount`] collector with the default
+    /// precision.
+    pub fn distinct_count(&self, query: &dyn Query, fast_field: &str) -> crate::Result<u64> {
+        self.search(query, &DistinctCount::for_field(fast_field))
+    }
+
+    /// Returns a [`Scroll`] iterating over all of the documents matching `query` in doc address
+    /// order, by batches of at most `batch_size` documents.
+    ///
+    /// The documents are neither scored nor ranked: this is meant to export the documents
+    /// matching a query, rather than to search for the best ones.
+    pub fn scroll(&self, query: &dyn Query, batch_size: usize) -> crate::Result<Scroll<'_>> {
+        Scroll::new(self, query, batch_size, None)
+    }
+
+    /// Resumes a [`Scroll`] from a [`ScrollCursor`] returned by [`Scroll::cursor`].
+    ///
+    /// The scroll has to be resumed on the same searcher, with the same query. Returns an error
+    /// if the cursor was created by a searcher of another generation.
+    pub fn scroll_from(
+        &self,
+        query: &dyn Query,
+        batch_size: usize,
+        cursor: ScrollCursor,
+    ) -> crate::Result<Scroll<'_>> {
+        Scroll::new(self, query, batch_size, Some(cursor))
+    }
+
     /// Same as [`search(...)`](Searcher::search) but allows specifying
     /// a [Bm25StatisticsProvider].
     ///
@@ -224,17 +357,113 @@ impl Searcher {
         executor: &Executor,
         enabled_scoring: EnableScoring,
     ) -> crate::Result<C::Fruit> {
-        let weight = query.weight(enabled_scoring)?;
+        telemetry::enter_span!("search", query = ?query);
+        telemetry::record_search();
+        let start = Instant::now();
+        let weight = {
+            telemetry::enter_span!("weight");
+            self.weight(query, enabled_scoring)?
+        };
+        telemetry::record_search_phase("weight", start.elapsed());
+        let start = Instant::now();
+        // The segments may be collected on other threads, which do not inherit the current span.
+        #[cfg(feature = "tracing")]
+        let search_span = tracing::Span::current();
         let segment_readers = self.segment_readers();
         let fruits = executor.map(
             |(segment_ord, segment_reader)| {
+                telemetry::enter_span!(
+                    parent: &search_span,
+                    "collect_segment",
+                    segment_id = %segment_reader.segment_id(),
+                    segment_ord
+                );
                 collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
             },
             segment_readers.iter().enumerate(),
         )?;
+        telemetry::record_search_phase("collect", start.elapsed());
+        let start = Instant::now();
+        let fruit = {
+            telemetry::enter_span!("merge_fruits");
+            collector.merge_fruits(fruits)?
+        };
+        telemetry::record_search_phase("merge", start.elapsed());
+        Ok(fruit)
+    }
+
+    /// Same as [`search(...)`](Searcher::search), but runs the collection of each segment as a
+    /// blocking task of the current tokio runtime.
+    ///
+    /// The segments are collected concurrently, so that their IO overlaps. This is useful with
+    /// high latency directories, like an
+    /// [`AsyncDirectoryAdapter`](crate::directory::AsyncDirectoryAdapter).
+    ///
+    /// Must be called from within a tokio runtime.
+    #[cfg(feature = "async-directory")]
+    pub async fn search_async<C: Collector + 'static>(
+        &self,
+        query: &dyn Query,
+        collector: C,
+    ) -> crate::Result<C::Fruit> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(self)
+        } else {
+            EnableScoring::disabled_from_searcher(self)
+        };
+        let weight: Arc<dyn Weight> = Arc::from(self.weight(query, enabled_scoring)?);
+        let collector = Arc::new(collector);
+        let segment_tasks: Vec<_> = self
+            .segment_readers()
+            .iter()
+            .enumerate()
+            .map(|(segment_ord, segment_reader)| {
+                let weight = weight.clone();
+                let collector = collector.clone();
+                let segment_reader = segment_reader.clone();
+                tokio::task::spawn_blocking(move || {
+                    collector.collect_segment(weight.as_ref(), segment_ord as u32, &segment_reader)
+                })
+            })
+            .collect();
+        let mut fruits = Vec::with_capacity(segment_tasks.len());
+        for segment_task in segment_tasks {
+            let fruit = segment_task.await.map_err(|join_error| {
+                crate::TantivyError::ErrorInThread(join_error.to_string())
+            })??;
+            fruits.push(fruit);
+        }
         collector.merge_fruits(fruits)
     }
 
+    /// Creates the weight of `query` for the segments of this searcher.
+    pub(crate) fn weight<Q: Query + ?Sized>(
+        &self,
+        query: &Q,
+        enabled_scoring: EnableScoring,
+    ) -> crate::Result<Box<dyn Weight>> {
+        let is_scoring_enabled = enabled_scoring.is_scoring_enabled();
+        // Expired documents that were not deleted yet are excluded from the results.
+        let weight = if let Some(expired_docs_query) = expired_docs_query(&self.inner.index) {
+            BooleanQuery::new(vec![
+                (Occur::Must, query.box_clone()),
+                (Occur::MustNot, Box::new(expired_docs_query)),
+            ])
+            .weight(enabled_scoring)?
+        } else {
+            query.weight(enabled_scoring)?
+        };
+        // The additive doc boost is added once to the score of the whole query.
+        let settings = self.inner.index.settings();
+        if is_scoring_enabled
+            && settings.doc_boost_field.is_some()
+            && settings.doc_boost_mode == DocBoostMode::Add
+        {
+            return Ok(Box::new(AdditiveDocBoostWeight::new(weight)));
+        }
+        Ok(weight)
+    }
+
     /// Summarize total space usage of this searcher.
     pub fn space_usage(&self) -> io::Result<SearcherSpaceUsage> {
         let mut space_usage = SearcherSpaceUsage::new();
@@ -259,8 +488,10 @@ pub(crate) struct SearcherInner {
     schema: Schema,
     index: Index,
     segment_readers: Vec<SegmentReader>,
-    store_readers: Vec<StoreReader>,
+    store_readers: Arc<Vec<StoreReader>>,
     generation: TrackedObject<SearcherGeneration>,
+    /// Cache of the total term frequencies, used by the language model similarities.
+    total_term_freqs: Mutex<HashMap<Term, u64>>,
 }
 
 impl SearcherInner {
@@ -289,8 +520,9 @@ impl SearcherInner {
             schema,
             index,
             segment_readers,
-            store_readers,
+            store_readers: Arc::new(store_readers),
             generation,
+            total_term_freqs: Mutex::default(),
         })
     }
 }
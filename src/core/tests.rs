@@ -4,11 +4,16 @@ use crate::index::SegmentId;
 use crate::indexer::{LogMergePolicy, NoMergePolicy};
 use crate::postings::Postings;
 use crate::query::TermQuery;
-use crate::schema::{Field, IndexRecordOption, Schema, INDEXED, STRING, TEXT};
-use crate::tokenizer::TokenizerManager;
+use crate::schema::{
+    Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STRING, TEXT,
+};
+use crate::tokenizer::{
+    AnalyzerDefinition, CharFilterDefinition, TokenFilterDefinition, TokenizerDefinition,
+    TokenizerManager,
+};
 use crate::{
-    Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexWriter, ReloadPolicy,
-    TantivyDocument, Term,
+    Directory, DocSet, Index, IndexBuilder, IndexReader, IndexSettings, IndexSortByField,
+    IndexWriter, Order, ReloadPolicy, TantivyDocument, Term,
 };
 
 #[test]
@@ -40,6 +45,45 @@ fn test_set_tokenizer_manager() {
     assert!(index.tokenizers().get("raw").is_none());
 }
 
+#[test]
+fn test_analyzer_definition_survives_reopening() -> crate::Result<()> {
+    let analyzer_definition = AnalyzerDefinition::new(TokenizerDefinition::Simple)
+        .char_filter(CharFilterDefinition::HtmlStrip)
+        .filter(TokenFilterDefinition::LowerCaser);
+    let mut schema_builder = Schema::builder();
+    let body_field = schema_builder.add_text_field(
+        "body",
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("html")
+                .set_analyzer_definition(analyzer_definition),
+        ),
+    );
+    let schema = schema_builder.build();
+    let directory = RamDirectory::create();
+    {
+        let index = Index::create(directory.clone(), schema, IndexSettings::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body_field => "<p>Hello</p><b>World</b>"))?;
+        index_writer.commit()?;
+    }
+    // The analyzer is rebuilt from the schema, without being registered again.
+    let index = Index::open(directory)?;
+    assert!(index.tokenizer_for_field(body_field).is_ok());
+    let searcher = index.reader()?.searcher();
+    let term_query = TermQuery::new(
+        Term::from_field_text(body_field, "world"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.search(&term_query, &Count)?, 1);
+    let term_query = TermQuery::new(
+        Term::from_field_text(body_field, "b"),
+        IndexRecordOption::Basic,
+    );
+    assert_eq!(searcher.search(&term_query, &Count)?, 0);
+    Ok(())
+}
+
 #[test]
 fn test_index_exists() {
     let directory: Box<dyn Directory> = Box::new(RamDirectory::create());
@@ -180,6 +224,32 @@ mod mmap_specific {
         Ok(())
     }
 
+    #[test]
+    fn test_index_reload_if_changed_different_directories() -> crate::Result<()> {
+        let schema = throw_away_schema();
+        let field = schema.get_field("num_likes").unwrap();
+        let tempdir = TempDir::new().unwrap();
+        let write_index = Index::create_in_dir(tempdir.path(), schema).unwrap();
+        let read_index = Index::open_in_dir(tempdir.path()).unwrap();
+        let reader = read_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert!(!reader.reload_if_changed()?);
+        let mut writer: IndexWriter = write_index.writer_for_tests()?;
+        writer.add_document(doc!(field=>1u64))?;
+        writer.commit()?;
+        assert_eq!(reader.searcher().num_docs(), 0);
+        assert!(reader.reload_if_changed()?);
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert!(!reader.reload_if_changed()?);
+        writer.add_document(doc!(field=>2u64))?;
+        writer.commit()?;
+        assert!(reader.reload_if_changed()?);
+        assert_eq!(reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+
     #[test]
     fn test_index_on_commit_reload_policy_different_directories() -> crate::Result<()> {
         let schema = throw_away_schema();
@@ -300,6 +370,88 @@ fn test_single_segment_index_writer() -> crate::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_single_segment_index_writer_sorted() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let rank_field = schema_builder.add_i64_field("rank", FAST);
+    let schema = schema_builder.build();
+    let settings = IndexSettings {
+        sort_by_field: Some(IndexSortByField {
+            field: "rank".to_string(),
+            order: Order::Asc,
+        }),
+        ..Default::default()
+    };
+    let mut single_segment_index_writer = Index::builder()
+        .schema(schema)
+        .settings(settings)
+        .single_segment_index_writer(RamDirectory::default(), 15_000_000)?;
+    for rank in [3i64, -2, 10, 0] {
+        single_segment_index_writer.add_document(doc!(rank_field => rank))?;
+    }
+    let index = single_segment_index_writer.finalize()?;
+    let searcher = index.reader()?.searcher();
+    let ranks = searcher.segment_reader(0).fast_fields().i64("rank")?;
+    let values: Vec<i64> = (0..4).map(|doc| ranks.first(doc).unwrap()).collect();
+    assert_eq!(values, vec![-2, 0, 3, 10]);
+    Ok(())
+}
+
+#[test]
+fn test_index_sort_by_field_validation() {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("text", TEXT | FAST);
+    schema_builder.add_u64_field("not_fast", INDEXED);
+    schema_builder.add_u64_field("fast", FAST);
+    let schema = schema_builder.build();
+    let create_sorted_by = |field: &str| {
+        let settings = IndexSettings {
+            sort_by_field: Some(IndexSortByField {
+                field: field.to_string(),
+                order: Order::Desc,
+            }),
+            ..Default::default()
+        };
+        Index::builder()
+            .schema(schema.clone())
+            .settings(settings)
+            .create_in_ram()
+    };
+    assert!(create_sorted_by("fast").is_ok());
+    assert!(create_sorted_by("not_fast").is_err());
+    assert!(create_sorted_by("text").is_err());
+    assert!(create_sorted_by("unknown").is_err());
+}
+
+#[test]
+fn test_index_then_sort_by_fields_validation() {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_u64_field("not_fast", INDEXED);
+    schema_builder.add_u64_field("fast", FAST);
+    schema_builder.add_i64_field("other_fast", FAST);
+    let schema = schema_builder.build();
+    let sort_by = |field: &str| IndexSortByField {
+        field: field.to_string(),
+        order: Order::Asc,
+    };
+    let create_sorted_by = |sort_by_field: Option<IndexSortByField>,
+                            then_sort_by_fields: Vec<IndexSortByField>| {
+        let settings = IndexSettings {
+            sort_by_field,
+            then_sort_by_fields,
+            ..Default::default()
+        };
+        Index::builder()
+            .schema(schema.clone())
+            .settings(settings)
+            .create_in_ram()
+    };
+    assert!(create_sorted_by(Some(sort_by("fast")), vec![sort_by("other_fast")]).is_ok());
+    assert!(create_sorted_by(Some(sort_by("fast")), vec![sort_by("not_fast")]).is_err());
+    assert!(create_sorted_by(Some(sort_by("fast")), vec![sort_by("unknown")]).is_err());
+    assert!(create_sorted_by(None, vec![sort_by("other_fast")]).is_err());
+}
+
 #[test]
 fn test_merging_segment_update_docfreq() {
     let mut schema_builder = Schema::builder();
@@ -466,3 +618,40 @@ fn test_non_text_json_term_freq_bitpacked() {
         assert_eq!(postings.term_freq(), 1u32);
     }
 }
+
+#[test]
+fn test_fast_field_block_cache() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let num_likes_field = schema_builder.add_u64_field("num_likes", FAST);
+    let schema = schema_builder.build();
+    let mut index = Index::create_in_ram(schema);
+    assert!(index.fast_field_block_cache_stats().is_none());
+    index.set_fast_field_block_cache_num_bytes(1_000_000);
+    let mut index_writer: IndexWriter = index.writer_for_tests()?;
+    for num_likes in 0..1_000u64 {
+        index_writer.add_document(doc!(num_likes_field => num_likes))?;
+    }
+    index_writer.commit()?;
+
+    let read_num_likes = |reader: &IndexReader| -> crate::Result<Vec<u64>> {
+        let searcher = reader.searcher();
+        let column = searcher.segment_reader(0).fast_fields().u64("num_likes")?;
+        Ok(column.values_for_doc(500).collect())
+    };
+    let reader = index.reader()?;
+    assert_eq!(read_num_likes(&reader)?, vec![500]);
+    let stats = index.fast_field_block_cache_stats().unwrap();
+    assert!(stats.cache_misses > 0);
+    assert!(stats.num_bytes > 0);
+
+    // A new reader reads the same blocks from the cache.
+    let new_reader = index.reader()?;
+    assert_eq!(read_num_likes(&new_reader)?, vec![500]);
+    let new_stats = index.fast_field_block_cache_stats().unwrap();
+    assert_eq!(new_stats.cache_misses, stats.cache_misses);
+    assert!(new_stats.cache_hits > stats.cache_hits);
+
+    index.set_fast_field_block_cache_num_bytes(0);
+    assert!(index.fast_field_block_cache_stats().is_none());
+    Ok(())
+}
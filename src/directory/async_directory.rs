@@ -0,0 +1,249 @@
+use std::io::{self, BufWriter, Cursor, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, result};
+
+use async_trait::async_trait;
+use common::HasLen;
+use tokio::runtime::Handle;
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, FileHandle, OwnedBytes, TerminatingWrite, WatchCallback,
+    WatchCallbackList, WatchHandle, WritePtr,
+};
+
+/// Async counterpart of the [`Directory`] trait.
+///
+/// An `AsyncDirectory` is used by tantivy through an [`AsyncDirectoryAdapter`], whose file
+/// handles implement both the blocking and the async reads. This makes it possible for
+/// [`Searcher::search_async()`](crate::Searcher::search_async) to overlap the IO of the
+/// different segments.
+///
+/// Like for [`Directory`], the files written with [`AsyncDirectory::write()`] are never
+/// modified, while the files written with [`AsyncDirectory::atomic_write()`] are small and
+/// replaced atomically.
+#[async_trait]
+pub trait AsyncDirectory: fmt::Debug + Send + Sync + 'static {
+    /// Returns the length of a file.
+    async fn file_len(&self, path: &Path) -> result::Result<usize, OpenReadError>;
+
+    /// Reads a range of bytes of a file.
+    async fn read_bytes(&self, path: &Path, byte_range: Range<usize>) -> io::Result<OwnedBytes>;
+
+    /// Writes the content of a file, creating it if it does not exist.
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    /// Reads the full content of a file written using [`AsyncDirectory::atomic_write()`].
+    async fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError>;
+
+    /// Atomically replaces the content of a file with data.
+    async fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()>;
+
+    /// Removes a file.
+    ///
+    /// Removing a nonexistent file returns a [`DeleteError::FileDoesNotExist`].
+    async fn delete(&self, path: &Path) -> result::Result<(), DeleteError>;
+
+    /// Returns true if and only if the file exists.
+    async fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError>;
+
+    /// Syncs the directory, so that the newly written files are stored durably.
+    async fn sync_directory(&self) -> io::Result<()>;
+}
+
+/// Exposes an [`AsyncDirectory`] as a [`Directory`].
+///
+/// The blocking operations are run with the given runtime [`Handle`], and block the calling
+/// thread: they should not be called from within an async context. Searches issued from an
+/// async context should go through
+/// [`Searcher::search_async()`](crate::Searcher::search_async), which runs them on the blocking
+/// thread pool.
+#[derive(Clone)]
+pub struct AsyncDirectoryAdapter {
+    directory: Arc<dyn AsyncDirectory>,
+    runtime: Handle,
+    watch_router: Arc<WatchCallbackList>,
+}
+
+impl fmt::Debug for AsyncDirectoryAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncDirectoryAdapter({:?})", self.directory)
+    }
+}
+
+impl AsyncDirectoryAdapter {
+    /// Creates a new `AsyncDirectoryAdapter`, running the blocking operations with `runtime`.
+    pub fn new<D: AsyncDirectory>(directory: D, runtime: Handle) -> AsyncDirectoryAdapter {
+        AsyncDirectoryAdapter {
+            directory: Arc::new(directory),
+            runtime,
+            watch_router: Default::default(),
+        }
+    }
+}
+
+/// File handle associated with the [`AsyncDirectoryAdapter`].
+struct AsyncFileHandle {
+    path: PathBuf,
+    len: usize,
+    directory: Arc<dyn AsyncDirectory>,
+    runtime: Handle,
+}
+
+impl fmt::Debug for AsyncFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AsyncFileHandle({:?}, {:?})", self.directory, self.path)
+    }
+}
+
+impl HasLen for AsyncFileHandle {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[async_trait]
+impl FileHandle for AsyncFileHandle {
+    fn read_bytes(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.runtime
+            .block_on(self.directory.read_bytes(&self.path, byte_range))
+    }
+
+    async fn read_bytes_async(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+        self.directory.read_bytes(&self.path, byte_range).await
+    }
+}
+
+/// Writer associated with the [`AsyncDirectoryAdapter`].
+///
+/// The content of the file is buffered, and written on flush.
+struct AsyncFileWriter {
+    path: PathBuf,
+    data: Cursor<Vec<u8>>,
+    directory: AsyncDirectoryAdapter,
+}
+
+impl Write for AsyncFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let directory = &self.directory;
+        directory
+            .runtime
+            .block_on(directory.directory.write(&self.path, self.data.get_ref()))
+    }
+}
+
+impl TerminatingWrite for AsyncFileWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        self.flush()
+    }
+}
+
+impl Directory for AsyncDirectoryAdapter {
+    fn get_file_handle(&self, path: &Path) -> result::Result<Arc<dyn FileHandle>, OpenReadError> {
+        let len = self.runtime.block_on(self.directory.file_len(path))?;
+        Ok(Arc::new(AsyncFileHandle {
+            path: path.to_path_buf(),
+            len,
+            directory: self.directory.clone(),
+            runtime: self.runtime.clone(),
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.runtime.block_on(self.directory.delete(path))
+    }
+
+    fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError> {
+        self.runtime.block_on(self.directory.exists(path))
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let exists = self.exists(path).map_err(|open_read_error| {
+            OpenWriteError::wrap_io_error(io::Error::other(open_read_error), path.to_path_buf())
+        })?;
+        if exists {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_path_buf()));
+        }
+        // force the creation of the file to mimic the MMap directory.
+        self.runtime
+            .block_on(self.directory.write(path, &[]))
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        let writer = AsyncFileWriter {
+            path: path.to_path_buf(),
+            data: Cursor::new(Vec::new()),
+            directory: self.clone(),
+        };
+        Ok(BufWriter::new(Box::new(writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        self.runtime.block_on(self.directory.atomic_read(path))
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.runtime
+            .block_on(self.directory.atomic_write(path, data))?;
+        if path == *META_FILEPATH {
+            drop(self.watch_router.broadcast());
+        }
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.runtime.block_on(self.directory.sync_directory())
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.watch_router.subscribe(watch_callback))
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::AsyncDirectoryAdapter;
+    use crate::collector::Count;
+    use crate::directory::TokioDirectory;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_search_async() -> crate::Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let tempdir = TempDir::new().unwrap();
+        let directory = AsyncDirectoryAdapter::new(
+            TokioDirectory::open(tempdir.path())?,
+            runtime.handle().clone(),
+        );
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(directory, schema_builder.build(), Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..3 {
+            index_writer.add_document(doc!(text => "hello"))?;
+            index_writer.add_document(doc!(text => "world"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::Basic,
+        );
+        let count = runtime.block_on(searcher.search_async(&query, Count))?;
+        assert_eq!(count, 3);
+        Ok(())
+    }
+}
@@ -185,6 +185,15 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
     /// effectively stored durably.
     fn sync_directory(&self) -> io::Result<()>;
 
+    /// Returns the number of bytes that can still be written in the directory, or `None` if
+    /// the directory is not bounded.
+    ///
+    /// The [`IndexWriter`](crate::IndexWriter) refuses the merges and commits that would not
+    /// fit in the remaining space. See [`QuotaDirectory`](crate::directory::QuotaDirectory).
+    fn available_bytes(&self) -> Option<u64> {
+        None
+    }
+
     /// Acquire a lock in the directory given in the [`Lock`].
     ///
     /// The method is blocking or not depending on the [`Lock`] object.
@@ -208,6 +217,18 @@ pub trait Directory: DirectoryClone + fmt::Debug + Send + Sync + 'static {
         }
     }
 
+    /// Acquire a shared lock in the directory given in the [`Lock`].
+    ///
+    /// Several shared locks on the same [`Lock`] can be held at the same time, possibly by
+    /// different processes, but they exclude the locks acquired with
+    /// [`Directory::acquire_lock()`].
+    ///
+    /// Directories that do not support shared locks acquire an exclusive lock instead, which
+    /// is what the default implementation does.
+    fn acquire_shared_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.acquire_lock(lock)
+    }
+
     /// Registers a callback that will be called whenever a change on the `meta.json`
     /// using the [`Directory::atomic_write()`] API is detected.
     ///
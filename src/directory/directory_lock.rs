@@ -50,8 +50,10 @@ pub static INDEX_WRITER_LOCK: Lazy<Lock> = Lazy::new(|| Lock {
 /// `IndexReader::reload()` from being garbage collected.
 ///
 /// It makes it possible for another process to safely consume
-/// our index in-writing. Ideally, we may have preferred `RWLock` semantics
-/// here, but it is difficult to achieve on Windows.
+/// our index in-writing. Readers acquire it as a shared lock (see
+/// [`Directory::acquire_shared_lock`](crate::Directory::acquire_shared_lock)), so that any
+/// number of reader processes can open segments concurrently, while the garbage collection
+/// acquires it exclusively.
 ///
 /// Opening segment readers is a very fast process.
 pub static META_LOCK: Lazy<Lock> = Lazy::new(|| Lock {
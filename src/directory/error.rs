@@ -88,6 +88,33 @@ impl OpenWriteError {
         }
     }
 }
+/// Error returned when a write would exceed the byte budget of a
+/// [`QuotaDirectory`](crate::directory::QuotaDirectory).
+#[derive(Debug, Clone, Error)]
+#[error(
+    "Disk quota exceeded: {num_bytes_requested} bytes were requested while only \
+     {num_bytes_available} bytes are available."
+)]
+pub struct QuotaExceededError {
+    /// Number of bytes that could still be written when the operation was refused.
+    pub num_bytes_available: u64,
+    /// Number of bytes the refused operation required.
+    pub num_bytes_requested: u64,
+}
+
+impl QuotaExceededError {
+    /// Wraps the error into an `io::Error`, so that it can go through the `io::Write`
+    /// implementations.
+    pub(crate) fn into_io_error(self) -> io::Error {
+        io::Error::new(io::ErrorKind::StorageFull, self)
+    }
+
+    /// Returns the `QuotaExceededError` wrapped in an `io::Error`, if any.
+    pub(crate) fn from_io_error(io_error: &io::Error) -> Option<&QuotaExceededError> {
+        io_error.get_ref()?.downcast_ref::<QuotaExceededError>()
+    }
+}
+
 /// Type of index incompatibility between the library and the index found on disk
 /// Used to catch and provide a hint to solve this incompatibility issue
 #[derive(Clone)]
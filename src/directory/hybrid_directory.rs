@@ -0,0 +1,354 @@
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::{fmt, result};
+
+use common::HasLen;
+
+use crate::directory::error::{
+    DeleteError, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
+};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, FileSlice, Lock, MmapDirectory,
+    RamDirectory, TerminatingWrite, WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Extensions of the segment files that may be spilled to disk: the doc store, the postings,
+/// the positions and the blobs. They are large, and mostly read sequentially or for a few
+/// documents.
+const SPILLABLE_EXTENSIONS: [&str; 5] = ["store", "temp", "idx", "pos", "blobs"];
+
+fn is_spillable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SPILLABLE_EXTENSIONS.contains(&extension))
+}
+
+/// Directory keeping the files in memory up to a memory budget, and spilling the large
+/// files to disk past that budget.
+///
+/// The small files that are accessed randomly at search time (term dictionaries, fast fields,
+/// fieldnorms, ...) always stay in memory. The doc store, the postings and the positions are
+/// kept in memory as long as the files held in memory fit in the budget, and are written to
+/// a [`MmapDirectory`] otherwise.
+///
+/// This is a middle ground between a [`RamDirectory`] and a [`MmapDirectory`] for medium-sized
+/// ephemeral indexes: an index that fits in the budget never touches the disk, while a larger
+/// one does not exhaust the memory.
+#[derive(Clone)]
+pub struct HybridDirectory {
+    inner: Arc<InnerHybridDirectory>,
+}
+
+struct InnerHybridDirectory {
+    ram_directory: RamDirectory,
+    mmap_directory: MmapDirectory,
+    memory_budget_in_bytes: usize,
+    /// Number of bytes of the files held in memory, including the files being written.
+    num_bytes_in_ram: AtomicUsize,
+}
+
+impl fmt::Debug for HybridDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HybridDirectory({:?}, {}/{} bytes in memory)",
+            self.inner.mmap_directory,
+            self.mem_usage(),
+            self.inner.memory_budget_in_bytes
+        )
+    }
+}
+
+impl HybridDirectory {
+    /// Creates a `HybridDirectory` spilling to a temporary directory.
+    ///
+    /// The temporary directory is deleted when the `HybridDirectory` and all of its clones are
+    /// dropped.
+    pub fn create(
+        memory_budget_in_bytes: usize,
+    ) -> result::Result<HybridDirectory, OpenDirectoryError> {
+        let mmap_directory = MmapDirectory::create_from_tempdir()?;
+        Ok(HybridDirectory::with_mmap_directory(
+            mmap_directory,
+            memory_budget_in_bytes,
+        ))
+    }
+
+    /// Creates a `HybridDirectory` spilling to the given directory.
+    ///
+    /// The files spilled to disk are not deleted when the `HybridDirectory` is dropped.
+    pub fn open(
+        spill_directory_path: impl AsRef<Path>,
+        memory_budget_in_bytes: usize,
+    ) -> result::Result<HybridDirectory, OpenDirectoryError> {
+        let mmap_directory = MmapDirectory::open(spill_directory_path)?;
+        Ok(HybridDirectory::with_mmap_directory(
+            mmap_directory,
+            memory_budget_in_bytes,
+        ))
+    }
+
+    fn with_mmap_directory(
+        mmap_directory: MmapDirectory,
+        memory_budget_in_bytes: usize,
+    ) -> HybridDirectory {
+        HybridDirectory {
+            inner: Arc::new(InnerHybridDirectory {
+                ram_directory: RamDirectory::create(),
+                mmap_directory,
+                memory_budget_in_bytes,
+                num_bytes_in_ram: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns the number of bytes of the files held in memory.
+    pub fn mem_usage(&self) -> usize {
+        self.inner.num_bytes_in_ram.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the file was spilled to disk.
+    pub fn is_spilled(&self, path: &Path) -> bool {
+        self.inner.mmap_directory.exists(path).unwrap_or(false)
+    }
+
+    fn is_over_budget(&self) -> bool {
+        self.mem_usage() > self.inner.memory_budget_in_bytes
+    }
+
+    fn ram_file_len(&self, path: &Path) -> Option<usize> {
+        let file_slice = self.inner.ram_directory.open_read(path).ok()?;
+        Some(file_slice.len())
+    }
+}
+
+/// Writer associated with the [`HybridDirectory`].
+///
+/// The content is buffered in memory, and published in the [`RamDirectory`] on flush, until
+/// the memory budget is exceeded. Spillable files are then written to disk.
+struct HybridWriter {
+    path: PathBuf,
+    directory: HybridDirectory,
+    data: Vec<u8>,
+    spilled_writer: Option<WritePtr>,
+}
+
+impl HybridWriter {
+    fn spill(&mut self) -> io::Result<()> {
+        let inner = &self.directory.inner;
+        let mut spilled_writer = inner
+            .mmap_directory
+            .open_write(&self.path)
+            .map_err(io::Error::other)?;
+        spilled_writer.write_all(&self.data)?;
+        inner
+            .ram_directory
+            .delete(&self.path)
+            .map_err(io::Error::other)?;
+        inner
+            .num_bytes_in_ram
+            .fetch_sub(self.data.len(), Ordering::Relaxed);
+        self.data = Vec::new();
+        self.spilled_writer = Some(spilled_writer);
+        Ok(())
+    }
+}
+
+impl Write for HybridWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(spilled_writer) = self.spilled_writer.as_mut() {
+            return spilled_writer.write(buf);
+        }
+        self.data.extend_from_slice(buf);
+        self.directory
+            .inner
+            .num_bytes_in_ram
+            .fetch_add(buf.len(), Ordering::Relaxed);
+        if is_spillable(&self.path) && self.directory.is_over_budget() {
+            debug!("Memory budget exceeded, spilling {:?} to disk", self.path);
+            self.spill()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(spilled_writer) = self.spilled_writer.as_mut() {
+            return spilled_writer.flush();
+        }
+        self.directory
+            .inner
+            .ram_directory
+            .atomic_write(&self.path, &self.data)
+    }
+}
+
+impl TerminatingWrite for HybridWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        if let Some(spilled_writer) = self.spilled_writer.as_mut() {
+            return spilled_writer.terminate_ref(token);
+        }
+        self.flush()
+    }
+}
+
+impl Directory for HybridDirectory {
+    fn get_file_handle(&self, path: &Path) -> result::Result<Arc<dyn FileHandle>, OpenReadError> {
+        match self.inner.ram_directory.get_file_handle(path) {
+            Err(OpenReadError::FileDoesNotExist(_)) => {
+                self.inner.mmap_directory.get_file_handle(path)
+            }
+            file_handle_res => file_handle_res,
+        }
+    }
+
+    fn open_read(&self, path: &Path) -> result::Result<FileSlice, OpenReadError> {
+        match self.inner.ram_directory.open_read(path) {
+            Err(OpenReadError::FileDoesNotExist(_)) => self.inner.mmap_directory.open_read(path),
+            file_slice_res => file_slice_res,
+        }
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        if let Some(num_bytes) = self.ram_file_len(path) {
+            self.inner.ram_directory.delete(path)?;
+            self.inner
+                .num_bytes_in_ram
+                .fetch_sub(num_bytes, Ordering::Relaxed);
+            return Ok(());
+        }
+        self.inner.mmap_directory.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError> {
+        Ok(self.inner.ram_directory.exists(path)? || self.inner.mmap_directory.exists(path)?)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let exists = self.exists(path).map_err(|open_read_error| {
+            OpenWriteError::wrap_io_error(io::Error::other(open_read_error), path.to_path_buf())
+        })?;
+        if exists {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_path_buf()));
+        }
+        // force the creation of the file to mimic the MMap directory.
+        self.inner
+            .ram_directory
+            .atomic_write(path, &[])
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(BufWriter::new(Box::new(HybridWriter {
+            path: path.to_path_buf(),
+            directory: self.clone(),
+            data: Vec::new(),
+            spilled_writer: None,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        match self.inner.ram_directory.atomic_read(path) {
+            Err(OpenReadError::FileDoesNotExist(_)) => self.inner.mmap_directory.atomic_read(path),
+            data_res => data_res,
+        }
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let previous_num_bytes = self.ram_file_len(path).unwrap_or(0);
+        self.inner.ram_directory.atomic_write(path, data)?;
+        self.inner
+            .num_bytes_in_ram
+            .fetch_add(data.len(), Ordering::Relaxed);
+        self.inner
+            .num_bytes_in_ram
+            .fetch_sub(previous_num_bytes, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.inner.mmap_directory.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.inner.ram_directory.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.inner.ram_directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use super::HybridDirectory;
+    use crate::collector::Count;
+    use crate::directory::{Directory, TerminatingWrite};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_hybrid_directory_spills_large_files() {
+        let directory = HybridDirectory::create(100).unwrap();
+        let fast_path = Path::new("seg.fast");
+        let store_path = Path::new("seg.store");
+        let mut write = directory.open_write(fast_path).unwrap();
+        write.write_all(&[1u8; 80]).unwrap();
+        write.terminate().unwrap();
+        let mut write = directory.open_write(store_path).unwrap();
+        write.write_all(&[2u8; 80]).unwrap();
+        write.terminate().unwrap();
+        assert!(!directory.is_spilled(fast_path));
+        assert!(directory.is_spilled(store_path));
+        assert_eq!(directory.mem_usage(), 80);
+        assert_eq!(
+            directory
+                .open_read(store_path)
+                .unwrap()
+                .read_bytes()
+                .unwrap(),
+            &[2u8; 80][..]
+        );
+        assert_eq!(
+            directory
+                .open_read(fast_path)
+                .unwrap()
+                .read_bytes()
+                .unwrap(),
+            &[1u8; 80][..]
+        );
+        assert!(directory.open_write(store_path).is_err());
+        directory.delete(fast_path).unwrap();
+        directory.delete(store_path).unwrap();
+        assert_eq!(directory.mem_usage(), 0);
+        assert!(!directory.exists(store_path).unwrap());
+    }
+
+    #[test]
+    fn test_hybrid_directory_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let directory = HybridDirectory::create(1_000)?;
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..1_000 {
+            index_writer.add_document(doc!(text => format!("hello {i}")))?;
+        }
+        index_writer.commit()?;
+        let segment_meta = &index.searchable_segment_metas()?[0];
+        let files = segment_meta.list_files();
+        assert!(files.iter().any(|path| directory.is_spilled(path)));
+        assert!(directory.mem_usage() < 100_000);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 1_000);
+        let doc: TantivyDocument = searcher.doc(crate::DocAddress::new(0, 0))?;
+        assert!(doc.get_first(text).is_some());
+        Ok(())
+    }
+}
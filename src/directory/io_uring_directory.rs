@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::ops::Range;
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, io, result};
+
+use async_trait::async_trait;
+use crossbeam_channel::{Receiver, Sender};
+use io_uring::{opcode, types, IoUring};
+
+use crate::directory::error::{DeleteError, OpenDirectoryError, OpenReadError};
+use crate::directory::{AsyncDirectory, OwnedBytes, TokioDirectory};
+
+/// Number of entries of the submission queue, which is also the maximum number of reads
+/// submitted at once.
+const RING_SIZE: u32 = 64;
+
+/// A read submitted to the ring.
+struct ReadRequest {
+    file: Arc<File>,
+    offset: u64,
+    buffer: Vec<u8>,
+    result_sender: oneshot::Sender<io::Result<Vec<u8>>>,
+}
+
+impl ReadRequest {
+    /// Sends the result of the read, given the result of its completion queue entry.
+    fn complete(mut self, result: i32) {
+        let read_result = if result < 0 {
+            Err(io::Error::from_raw_os_error(-result))
+        } else {
+            // Short reads are completed with a blocking read.
+            let num_read_bytes = result as usize;
+            self.file
+                .read_exact_at(
+                    &mut self.buffer[num_read_bytes..],
+                    self.offset + num_read_bytes as u64,
+                )
+                .map(|()| self.buffer)
+        };
+        // The reader may have given up on the result.
+        let _ = self.result_sender.send(read_result);
+    }
+
+    fn fail(self, io_error: &io::Error) {
+        let _ = self
+            .result_sender
+            .send(Err(io::Error::new(io_error.kind(), io_error.to_string())));
+    }
+}
+
+/// Submits the reads received on `requests` to the ring, batching the reads received while
+/// the previous batch was processed.
+fn run_ring(mut ring: IoUring, requests: Receiver<ReadRequest>) {
+    while let Ok(first_request) = requests.recv() {
+        let mut batch = vec![first_request];
+        batch.extend(requests.try_iter().take(RING_SIZE as usize - 1));
+        for (request_id, request) in batch.iter_mut().enumerate() {
+            let read_entry = opcode::Read::new(
+                types::Fd(request.file.as_raw_fd()),
+                request.buffer.as_mut_ptr(),
+                request.buffer.len() as u32,
+            )
+            .offset(request.offset)
+            .build()
+            .user_data(request_id as u64);
+            // Safety: the buffer and the file of the request are kept alive, and are not
+            // accessed, until the completion of the read.
+            unsafe { ring.submission().push(&read_entry) }
+                .expect("the batch should fit in the submission queue");
+        }
+        let mut results: Vec<Option<i32>> = vec![None; batch.len()];
+        let mut num_pending_reads = batch.len();
+        while num_pending_reads > 0 {
+            match ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(io_error) if io_error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(io_error) => {
+                    // The state of the ring is unknown: the reads may still be in flight, so
+                    // their buffers and files are leaked rather than released.
+                    error!("io_uring failure, stopping the ring: {io_error:?}");
+                    for mut request in batch {
+                        std::mem::forget(std::mem::take(&mut request.buffer));
+                        std::mem::forget(request.file.clone());
+                        request.fail(&io_error);
+                    }
+                    for request in requests.try_iter() {
+                        request.fail(&io_error);
+                    }
+                    std::mem::forget(ring);
+                    return;
+                }
+            }
+            for completion_entry in ring.completion() {
+                results[completion_entry.user_data() as usize] = Some(completion_entry.result());
+                num_pending_reads -= 1;
+            }
+        }
+        for (request, result) in batch.into_iter().zip(results) {
+            request.complete(result.expect("all the reads of the batch should be completed"));
+        }
+    }
+}
+
+/// An [`AsyncDirectory`] storing the files in a directory of the local filesystem, and reading
+/// them with io_uring.
+///
+/// The reads are submitted to a ring driven by a dedicated thread, which batches the reads
+/// issued concurrently. The other operations go through a [`TokioDirectory`].
+///
+/// Only available on Linux, with a kernel supporting io_uring (5.6+).
+#[derive(Clone)]
+pub struct IoUringDirectory {
+    inner: Arc<InnerIoUringDirectory>,
+}
+
+struct InnerIoUringDirectory {
+    tokio_directory: TokioDirectory,
+    /// Open files, by path.
+    files: Mutex<HashMap<PathBuf, Arc<File>>>,
+    requests: Sender<ReadRequest>,
+}
+
+impl fmt::Debug for IoUringDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IoUringDirectory({:?})", self.inner.tokio_directory)
+    }
+}
+
+impl IoUringDirectory {
+    /// Opens an `IoUringDirectory` in the given directory.
+    ///
+    /// Returns an error if the `directory_path` does not exist or is not a directory, or if
+    /// io_uring is not supported.
+    pub fn open(
+        directory_path: impl AsRef<Path>,
+    ) -> result::Result<IoUringDirectory, OpenDirectoryError> {
+        let directory_path = directory_path.as_ref();
+        let tokio_directory = TokioDirectory::open(directory_path)?;
+        let ring = IoUring::new(RING_SIZE).map_err(|io_error| {
+            OpenDirectoryError::wrap_io_error(io_error, directory_path.to_path_buf())
+        })?;
+        let (requests, requests_receiver) = crossbeam_channel::unbounded();
+        std::thread::Builder::new()
+            .name("io-uring-directory".to_string())
+            .spawn(move || run_ring(ring, requests_receiver))
+            .map_err(|io_error| {
+                OpenDirectoryError::wrap_io_error(io_error, directory_path.to_path_buf())
+            })?;
+        Ok(IoUringDirectory {
+            inner: Arc::new(InnerIoUringDirectory {
+                tokio_directory,
+                files: Default::default(),
+                requests,
+            }),
+        })
+    }
+
+    fn open_file(&self, path: &Path) -> io::Result<Arc<File>> {
+        let mut files = self.inner.files.lock().unwrap();
+        if let Some(file) = files.get(path) {
+            return Ok(file.clone());
+        }
+        let file = Arc::new(File::open(self.inner.tokio_directory.resolve_path(path))?);
+        files.insert(path.to_path_buf(), file.clone());
+        Ok(file)
+    }
+
+    fn close_file(&self, path: &Path) {
+        self.inner.files.lock().unwrap().remove(path);
+    }
+}
+
+#[async_trait]
+impl AsyncDirectory for IoUringDirectory {
+    async fn file_len(&self, path: &Path) -> result::Result<usize, OpenReadError> {
+        self.inner.tokio_directory.file_len(path).await
+    }
+
+    async fn read_bytes(&self, path: &Path, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+        if byte_range.is_empty() {
+            return Ok(OwnedBytes::empty());
+        }
+        let (result_sender, result_receiver) = oneshot::channel();
+        let request = ReadRequest {
+            file: self.open_file(path)?,
+            offset: byte_range.start as u64,
+            buffer: vec![0u8; byte_range.len()],
+            result_sender,
+        };
+        self.inner
+            .requests
+            .send(request)
+            .map_err(|_| io::Error::other("the io_uring thread has stopped"))?;
+        let buffer = result_receiver
+            .await
+            .map_err(|_| io::Error::other("the io_uring thread has stopped"))??;
+        Ok(OwnedBytes::new(buffer))
+    }
+
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.close_file(path);
+        self.inner.tokio_directory.write(path, data).await
+    }
+
+    async fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        self.inner.tokio_directory.atomic_read(path).await
+    }
+
+    async fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.close_file(path);
+        self.inner.tokio_directory.atomic_write(path, data).await
+    }
+
+    async fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.close_file(path);
+        self.inner.tokio_directory.delete(path).await
+    }
+
+    async fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError> {
+        self.inner.tokio_directory.exists(path).await
+    }
+
+    async fn sync_directory(&self) -> io::Result<()> {
+        self.inner.tokio_directory.sync_directory().await
+    }
+}
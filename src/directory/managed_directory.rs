@@ -256,6 +256,13 @@ impl ManagedDirectory {
         Ok(footer.crc() == crc)
     }
 
+    /// Returns the wrapped directory.
+    ///
+    /// The files written through it are neither managed nor given a footer.
+    pub(crate) fn inner_directory(&self) -> &dyn Directory {
+        self.directory.as_ref()
+    }
+
     /// List all managed files
     pub fn list_managed_files(&self) -> HashSet<PathBuf> {
         let managed_paths = self
@@ -315,6 +322,10 @@ impl Directory for ManagedDirectory {
         self.directory.acquire_lock(lock)
     }
 
+    fn acquire_shared_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.directory.acquire_shared_lock(lock)
+    }
+
     fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
         self.directory.watch(watch_callback)
     }
@@ -323,6 +334,10 @@ impl Directory for ManagedDirectory {
         self.directory.sync_directory()?;
         Ok(())
     }
+
+    fn available_bytes(&self) -> Option<u64> {
+        self.directory.available_bytes()
+    }
 }
 
 impl Clone for ManagedDirectory {
@@ -496,6 +496,41 @@ impl Directory for MmapDirectory {
         })))
     }
 
+    fn acquire_shared_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        let full_path = self.resolve_path(&lock.filepath);
+        let file: File = match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&full_path)
+        {
+            Ok(file) => file,
+            // Read-only processes may not be allowed to create or write the lock file. Shared
+            // locks do not require write access.
+            Err(create_error) => match File::open(&full_path) {
+                Ok(file) => file,
+                // The lock file has never been created: no process ever acquired an exclusive
+                // lock in this directory, and this process cannot create the file to
+                // synchronize with the ones that would.
+                Err(open_error) if open_error.kind() == io::ErrorKind::NotFound => {
+                    debug!("Cannot create the lock file {full_path:?}: {create_error:?}");
+                    return Ok(DirectoryLock::from(Box::new(())));
+                }
+                Err(open_error) => return Err(LockError::wrap_io_error(open_error)),
+            },
+        };
+        // The std methods of the same name shadow the `FileExt` ones on recent compilers.
+        if lock.is_blocking {
+            FileExt::lock_shared(&file).map_err(LockError::wrap_io_error)?;
+        } else if !FileExt::try_lock_shared(&file).map_err(|_| LockError::LockBusy)? {
+            return Err(LockError::LockBusy);
+        }
+        Ok(DirectoryLock::from(Box::new(ReleaseLockFile {
+            path: lock.filepath.clone(),
+            _file: file,
+        })))
+    }
+
     fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
         Ok(self.inner.watch(watch_callback))
     }
@@ -538,6 +573,29 @@ mod tests {
     use crate::schema::{Schema, SchemaBuilder, TEXT};
     use crate::{Index, IndexSettings, IndexWriter, ReloadPolicy};
 
+    #[test]
+    fn test_shared_lock() {
+        let mmap_directory = MmapDirectory::create_from_tempdir().unwrap();
+        let lock = Lock {
+            filepath: PathBuf::from("test.lock"),
+            is_blocking: false,
+        };
+        let shared_lock = mmap_directory.acquire_shared_lock(&lock).unwrap();
+        let other_shared_lock = mmap_directory.acquire_shared_lock(&lock).unwrap();
+        assert!(matches!(
+            mmap_directory.acquire_lock(&lock),
+            Err(LockError::LockBusy)
+        ));
+        drop(shared_lock);
+        drop(other_shared_lock);
+        let exclusive_lock = mmap_directory.acquire_lock(&lock).unwrap();
+        assert!(matches!(
+            mmap_directory.acquire_shared_lock(&lock),
+            Err(LockError::LockBusy)
+        ));
+        drop(exclusive_lock);
+    }
+
     #[test]
     fn test_open_non_existent_path() {
         assert!(MmapDirectory::open(PathBuf::from("./nowhere")).is_err());
@@ -675,9 +733,10 @@ mod tests {
             reader.reload().unwrap();
             let num_segments = reader.searcher().segment_readers().len();
             assert!(num_segments <= 4);
-            let num_components_except_deletes_and_tempstore =
-                crate::index::SegmentComponent::iterator().len() - 2;
-            let max_num_mmapped = num_components_except_deletes_and_tempstore * num_segments;
+            let num_components_except_deletes_updates_and_tempstore =
+                crate::index::SegmentComponent::iterator().len() - 3;
+            let max_num_mmapped =
+                num_components_except_deletes_updates_and_tempstore * num_segments;
             assert_eventually(|| {
                 let num_mmapped = mmap_directory.get_cache_info().mmapped.len();
                 if num_mmapped > max_num_mmapped {
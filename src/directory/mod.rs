@@ -3,13 +3,26 @@
 #[cfg(feature = "mmap")]
 mod mmap_directory;
 
+#[cfg(feature = "async-directory")]
+mod async_directory;
 mod directory;
 mod directory_lock;
 mod file_watcher;
 pub mod footer;
+#[cfg(feature = "mmap")]
+mod hybrid_directory;
+#[cfg(all(feature = "io-uring-directory", target_os = "linux"))]
+mod io_uring_directory;
 mod managed_directory;
+#[cfg(feature = "object-store")]
+mod object_store_directory;
+mod quota_directory;
 mod ram_directory;
+#[cfg(feature = "async-directory")]
+mod tokio_directory;
 mod watch_event_router;
+#[cfg(feature = "webhdfs")]
+mod webhdfs_store;
 
 /// Errors specific to the directory module.
 pub mod error;
@@ -22,10 +35,17 @@ use std::path::PathBuf;
 pub use common::file_slice::{FileHandle, FileSlice};
 pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 
+#[cfg(feature = "async-directory")]
+pub use self::async_directory::{AsyncDirectory, AsyncDirectoryAdapter};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+#[cfg(all(feature = "io-uring-directory", target_os = "linux"))]
+pub use self::io_uring_directory::IoUringDirectory;
+pub use self::quota_directory::QuotaDirectory;
 pub use self::ram_directory::RamDirectory;
+#[cfg(feature = "async-directory")]
+pub use self::tokio_directory::TokioDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
 /// Outcome of the Garbage collection
@@ -45,9 +65,15 @@ pub struct GarbageCollectionResult {
 #[cfg(all(feature = "mmap", unix))]
 pub use memmap2::Advice;
 
+#[cfg(feature = "mmap")]
+pub use self::hybrid_directory::HybridDirectory;
 pub use self::managed_directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
 pub use self::mmap_directory::MmapDirectory;
+#[cfg(feature = "object-store")]
+pub use self::object_store_directory::ObjectStoreDirectory;
+#[cfg(feature = "webhdfs")]
+pub use self::webhdfs_store::WebHdfsStore;
 
 /// Write object for Directory.
 ///
@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, result};
+
+use bytes::Bytes;
+use object_store::path::Path as ObjectPath;
+use object_store::{
+    MultipartUpload, ObjectStore, PutMode, PutOptions, PutPayload, UpdateVersion, WriteMultipart,
+};
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{
+    DeleteError, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
+};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, MmapDirectory, OwnedBytes,
+    TerminatingWrite, WatchCallback, WatchCallbackList, WatchHandle, WritePtr,
+};
+
+/// Files larger than this are uploaded in parts of this size, if the object store supports
+/// multipart uploads.
+const UPLOAD_PART_NUM_BYTES: usize = 8 * 1024 * 1024;
+
+/// Maximum number of parts of a file being uploaded at the same time.
+const MAX_CONCURRENT_PART_UPLOADS: usize = 4;
+
+/// A [`Directory`] storing the index in an object store (S3, GCS, Azure...), through the
+/// [`object_store`] crate.
+///
+/// The `object-store` feature does not enable any cloud backend of `object_store`. The S3, GCS
+/// and Azure stores are respectively enabled by the `object-store-aws`, `object-store-gcp` and
+/// `object-store-azure` features, and the WebDAV one by the `webdav` feature.
+///
+/// The files are read through a local cache, held in a [`MmapDirectory`]: a file is
+/// downloaded the first time it is opened, and then read from the cache. This is safe because
+/// the files written with [`Directory::open_write()`] are never modified.
+///
+/// Written files are first persisted in the local cache, and uploaded to the object store
+/// when the directory is synced, which happens when committing, before `meta.json` is saved.
+/// Large files are uploaded in several parts, if the object store supports multipart uploads.
+///
+/// Files written with [`Directory::atomic_write()`], like `meta.json`, bypass the cache and
+/// are written with a conditional put, which fails if the file has been modified since this
+/// directory last read or wrote it. This prevents two writers from overwriting each
+/// other's commits. Object stores that do not implement conditional puts, like WebDAV or
+/// [`WebHdfsStore`](crate::directory::WebHdfsStore), are written unconditionally.
+///
+/// Locks are only held in the local cache, and do not prevent writers running on other hosts.
+/// The changes made by other hosts are not watched either: readers should reload their
+/// searcher manually.
+///
+/// The object store operations are run on a runtime owned by the directory, and block the
+/// calling thread. The directory should not be used from within an async context.
+#[derive(Clone)]
+pub struct ObjectStoreDirectory {
+    inner: Arc<InnerObjectStoreDirectory>,
+}
+
+struct InnerObjectStoreDirectory {
+    store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+    cache: MmapDirectory,
+    runtime: tokio::runtime::Runtime,
+    /// Files written in the cache, and not yet uploaded.
+    pending_uploads: Mutex<HashSet<PathBuf>>,
+    /// Held while uploading the pending files, so that concurrent syncs do not upload the same
+    /// files.
+    upload_lock: Mutex<()>,
+    /// Versions of the atomically written files, as last read or written by this directory.
+    versions: Mutex<HashMap<PathBuf, UpdateVersion>>,
+    watch_router: WatchCallbackList,
+}
+
+impl fmt::Debug for ObjectStoreDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ObjectStoreDirectory({}, {})",
+            self.inner.store, self.inner.prefix
+        )
+    }
+}
+
+impl ObjectStoreDirectory {
+    /// Opens the index stored in `store` under `prefix`, caching its files in `cache`.
+    ///
+    /// The cache directory should not be shared with other directories.
+    pub fn open(
+        store: Arc<dyn ObjectStore>,
+        prefix: ObjectPath,
+        cache: MmapDirectory,
+    ) -> Result<ObjectStoreDirectory, OpenDirectoryError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("object-store-directory")
+            .enable_all()
+            .build()
+            .map_err(|io_error| {
+                OpenDirectoryError::wrap_io_error(io_error, PathBuf::from(prefix.as_ref()))
+            })?;
+        Ok(ObjectStoreDirectory {
+            inner: Arc::new(InnerObjectStoreDirectory {
+                store,
+                prefix,
+                cache,
+                runtime,
+                pending_uploads: Default::default(),
+                upload_lock: Default::default(),
+                versions: Default::default(),
+                watch_router: Default::default(),
+            }),
+        })
+    }
+
+    /// Opens the index stored on the WebDAV server at `url`, caching its files in `cache`.
+    #[cfg(feature = "webdav")]
+    pub fn open_webdav(
+        url: &str,
+        cache: MmapDirectory,
+    ) -> Result<ObjectStoreDirectory, OpenDirectoryError> {
+        let store = object_store::http::HttpBuilder::new()
+            .with_url(url)
+            .build()
+            .map_err(|error| OpenDirectoryError::wrap_io_error(error.into(), PathBuf::from(url)))?;
+        ObjectStoreDirectory::open(Arc::new(store), ObjectPath::default(), cache)
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.inner.runtime.block_on(future)
+    }
+
+    fn object_path(&self, path: &Path) -> ObjectPath {
+        let mut object_path = self.inner.prefix.clone();
+        for component in path.iter() {
+            object_path = object_path.child(component.to_string_lossy().as_ref());
+        }
+        object_path
+    }
+
+    /// Downloads the file into the cache, if it is not there already.
+    fn fetch_into_cache(&self, path: &Path) -> Result<(), OpenReadError> {
+        if self.inner.cache.exists(path)? {
+            return Ok(());
+        }
+        let object_path = self.object_path(path);
+        let bytes = self
+            .block_on(async { self.inner.store.get(&object_path).await?.bytes().await })
+            .map_err(|error| match error {
+                object_store::Error::NotFound { .. } => {
+                    OpenReadError::FileDoesNotExist(path.to_path_buf())
+                }
+                error => OpenReadError::wrap_io_error(io::Error::from(error), path.to_path_buf()),
+            })?;
+        self.inner
+            .cache
+            .atomic_write(path, &bytes)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))
+    }
+
+    fn exists_in_store(&self, path: &Path) -> io::Result<bool> {
+        let object_path = self.object_path(path);
+        match self.block_on(self.inner.store.head(&object_path)) {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Uploads the files written in the cache since the last upload.
+    ///
+    /// The pending uploads are not locked while uploading, so that files can be written and
+    /// deleted in the meantime.
+    fn upload_pending_files(&self) -> io::Result<()> {
+        let _upload_guard = self.inner.upload_lock.lock().unwrap();
+        let pending_uploads: Vec<PathBuf> = self
+            .inner
+            .pending_uploads
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect();
+        for path in pending_uploads {
+            let bytes = match self.inner.cache.open_read(&path) {
+                Ok(file_slice) => file_slice.read_bytes()?,
+                // The file was deleted since the uploads were listed.
+                Err(OpenReadError::FileDoesNotExist(_)) => continue,
+                Err(error) => return Err(io::Error::other(error)),
+            };
+            let object_path = self.object_path(&path);
+            self.block_on(upload(&*self.inner.store, &object_path, bytes))?;
+            let is_pending_upload = self.inner.pending_uploads.lock().unwrap().remove(&path);
+            if !is_pending_upload {
+                // The file was deleted while it was uploaded.
+                self.block_on(self.inner.store.delete(&object_path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Uploads `bytes` to `object_path`, in several parts if the file is large.
+///
+/// The parts are sliced from `bytes` without being copied.
+async fn upload(
+    store: &dyn ObjectStore,
+    object_path: &ObjectPath,
+    bytes: OwnedBytes,
+) -> object_store::Result<()> {
+    if bytes.len() > UPLOAD_PART_NUM_BYTES {
+        match store.put_multipart(object_path).await {
+            Ok(multipart_upload) => return upload_parts(multipart_upload, bytes).await,
+            Err(object_store::Error::NotImplemented) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    store
+        .put(object_path, PutPayload::from(Bytes::from_owner(bytes)))
+        .await?;
+    Ok(())
+}
+
+async fn upload_parts(
+    multipart_upload: Box<dyn MultipartUpload>,
+    bytes: OwnedBytes,
+) -> object_store::Result<()> {
+    let mut writer = WriteMultipart::new_with_chunk_size(multipart_upload, UPLOAD_PART_NUM_BYTES);
+    for start in (0..bytes.len()).step_by(UPLOAD_PART_NUM_BYTES) {
+        if let Err(error) = writer.wait_for_capacity(MAX_CONCURRENT_PART_UPLOADS).await {
+            // Cleaning up the uploaded parts is best effort, the error worth reporting is the
+            // one of the upload.
+            let _ = writer.abort().await;
+            return Err(error);
+        }
+        let end = (start + UPLOAD_PART_NUM_BYTES).min(bytes.len());
+        writer.put(Bytes::from_owner(bytes.slice(start..end)));
+    }
+    writer.finish().await?;
+    Ok(())
+}
+
+/// Writer associated with the [`ObjectStoreDirectory`].
+///
+/// Writes into the cache, and schedules the upload of the file once it is terminated.
+struct CachedFileWriter {
+    path: PathBuf,
+    cache_writer: Option<WritePtr>,
+    directory: ObjectStoreDirectory,
+}
+
+impl CachedFileWriter {
+    fn cache_writer(&mut self) -> io::Result<&mut WritePtr> {
+        self.cache_writer
+            .as_mut()
+            .ok_or_else(|| io::Error::other(format!("{:?} was already terminated", self.path)))
+    }
+}
+
+impl Write for CachedFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.cache_writer()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.cache_writer()?.flush()
+    }
+}
+
+impl TerminatingWrite for CachedFileWriter {
+    fn terminate_ref(&mut self, _: AntiCallToken) -> io::Result<()> {
+        if let Some(cache_writer) = self.cache_writer.take() {
+            cache_writer.terminate()?;
+            self.directory
+                .inner
+                .pending_uploads
+                .lock()
+                .unwrap()
+                .insert(self.path.clone());
+        }
+        Ok(())
+    }
+}
+
+impl Directory for ObjectStoreDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.fetch_into_cache(path)?;
+        self.inner.cache.get_file_handle(path)
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        let wrap_io_error = |io_error: io::Error| DeleteError::IoError {
+            io_error: Arc::new(io_error),
+            filepath: path.to_path_buf(),
+        };
+        let is_pending_upload = self.inner.pending_uploads.lock().unwrap().remove(path);
+        let is_in_cache = match self.inner.cache.delete(path) {
+            Ok(()) => true,
+            Err(DeleteError::FileDoesNotExist(_)) => false,
+            Err(error) => return Err(error),
+        };
+        if is_pending_upload {
+            return Ok(());
+        }
+        if !self.exists_in_store(path).map_err(wrap_io_error)? {
+            return if is_in_cache {
+                Ok(())
+            } else {
+                Err(DeleteError::FileDoesNotExist(path.to_path_buf()))
+            };
+        }
+        let object_path = self.object_path(path);
+        self.block_on(self.inner.store.delete(&object_path))
+            .map_err(|error| wrap_io_error(error.into()))
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        if self.inner.cache.exists(path)? {
+            return Ok(true);
+        }
+        self.exists_in_store(path)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let exists_in_store = self
+            .exists_in_store(path)
+            .map_err(|io_error| OpenWriteError::wrap_io_error(io_error, path.to_path_buf()))?;
+        if exists_in_store {
+            return Err(OpenWriteError::FileAlreadyExists(path.to_path_buf()));
+        }
+        let cache_writer = self.inner.cache.open_write(path)?;
+        let writer = CachedFileWriter {
+            path: path.to_path_buf(),
+            cache_writer: Some(cache_writer),
+            directory: self.clone(),
+        };
+        Ok(BufWriter::new(Box::new(writer)))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let object_path = self.object_path(path);
+        let (bytes, version) = self
+            .block_on(async {
+                let get_result = self.inner.store.get(&object_path).await?;
+                let version = UpdateVersion {
+                    e_tag: get_result.meta.e_tag.clone(),
+                    version: get_result.meta.version.clone(),
+                };
+                let bytes = get_result.bytes().await?;
+                Ok::<_, object_store::Error>((bytes, version))
+            })
+            .map_err(|error| match error {
+                object_store::Error::NotFound { .. } => {
+                    OpenReadError::FileDoesNotExist(path.to_path_buf())
+                }
+                error => OpenReadError::wrap_io_error(io::Error::from(error), path.to_path_buf()),
+            })?;
+        self.inner
+            .versions
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), version);
+        Ok(bytes.to_vec())
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        // The files referenced by `data` need to be uploaded first.
+        self.upload_pending_files()?;
+        let mut versions = self.inner.versions.lock().unwrap();
+        let mode = match versions.get(path) {
+            Some(version) => PutMode::Update(version.clone()),
+            None => PutMode::Create,
+        };
+        let object_path = self.object_path(path);
+        let payload = PutPayload::from(data.to_vec());
+        let put_result = self
+            .block_on(async {
+                let store = &self.inner.store;
+                match store
+                    .put_opts(&object_path, payload.clone(), PutOptions::from(mode))
+                    .await
+                {
+                    Err(object_store::Error::NotImplemented) => {
+                        store.put(&object_path, payload).await
+                    }
+                    put_result => put_result,
+                }
+            })
+            .map_err(|error| match error {
+                object_store::Error::Precondition { .. }
+                | object_store::Error::AlreadyExists { .. } => io::Error::other(format!(
+                    "{path:?} was modified concurrently by another writer"
+                )),
+                error => error.into(),
+            })?;
+        versions.insert(path.to_path_buf(), UpdateVersion::from(put_result));
+        drop(versions);
+        if path == *META_FILEPATH {
+            drop(self.inner.watch_router.broadcast());
+        }
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.upload_pending_files()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.cache.acquire_lock(lock)
+    }
+
+    fn acquire_shared_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.inner.cache.acquire_shared_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        Ok(self.inner.watch_router.subscribe(watch_callback))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use common::TerminatingWrite;
+    use object_store::memory::InMemory;
+    use object_store::ObjectStore;
+
+    use super::{ObjectStoreDirectory, UPLOAD_PART_NUM_BYTES};
+    use crate::directory::MmapDirectory;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Directory, Index, IndexWriter};
+
+    fn open_directory(store: &Arc<dyn ObjectStore>) -> ObjectStoreDirectory {
+        let cache = MmapDirectory::create_from_tempdir().unwrap();
+        ObjectStoreDirectory::open(store.clone(), "index".into(), cache).unwrap()
+    }
+
+    #[test]
+    fn test_object_store_directory_uploads_on_sync() -> crate::Result<()> {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let directory = open_directory(&store);
+        let path = Path::new("segment.idx");
+        let mut writer = directory.open_write(path)?;
+        writer.write_all(b"segment")?;
+        writer.terminate()?;
+
+        let other_directory = open_directory(&store);
+        assert!(!other_directory.exists(path)?);
+        directory.sync_directory()?;
+        assert!(other_directory.exists(path)?);
+        let bytes = other_directory.open_read(path)?.read_bytes()?;
+        assert_eq!(bytes.as_slice(), b"segment");
+        assert!(other_directory.open_write(path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_uploads_large_files_in_parts() -> crate::Result<()> {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let directory = open_directory(&store);
+        let path = Path::new("segment.idx");
+        let data: Vec<u8> = (0..2 * UPLOAD_PART_NUM_BYTES + 1)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut writer = directory.open_write(path)?;
+        writer.write_all(&data)?;
+        writer.terminate()?;
+        directory.sync_directory()?;
+
+        let bytes = open_directory(&store).open_read(path)?.read_bytes()?;
+        assert_eq!(bytes.as_slice(), &data[..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_directory_concurrent_commits() -> crate::Result<()> {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(
+            open_directory(&store),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let other_index = Index::open(open_directory(&store))?;
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        let searcher = Index::open(open_directory(&store))?.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+
+        // `other_index` did not see the commit of `index_writer`.
+        let mut other_index_writer: IndexWriter = other_index.writer_for_tests()?;
+        other_index_writer.add_document(doc!(text => "world"))?;
+        assert!(other_index_writer.commit().is_err());
+        Ok(())
+    }
+}
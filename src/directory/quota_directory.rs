@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fmt, result};
+
+use crate::core::{MANAGED_FILEPATH, META_FILEPATH};
+use crate::directory::error::{
+    DeleteError, LockError, OpenReadError, OpenWriteError, QuotaExceededError,
+};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, FileSlice, Lock, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+use crate::error::DataCorruption;
+
+struct QuotaState {
+    max_num_bytes: u64,
+    num_bytes_used: u64,
+    file_sizes: HashMap<PathBuf, u64>,
+}
+
+impl QuotaState {
+    fn available_bytes(&self) -> u64 {
+        self.max_num_bytes.saturating_sub(self.num_bytes_used)
+    }
+
+    /// Accounts for `num_bytes` more bytes in the file, or fails if they do not fit in the
+    /// budget.
+    fn reserve(&mut self, path: &Path, num_bytes: u64) -> io::Result<()> {
+        let num_bytes_available = self.available_bytes();
+        if num_bytes > num_bytes_available {
+            return Err(QuotaExceededError {
+                num_bytes_available,
+                num_bytes_requested: num_bytes,
+            }
+            .into_io_error());
+        }
+        self.num_bytes_used += num_bytes;
+        *self.file_sizes.entry(path.to_path_buf()).or_default() += num_bytes;
+        Ok(())
+    }
+
+    fn set_file_size(&mut self, path: &Path, num_bytes: u64) {
+        let previous_num_bytes = self
+            .file_sizes
+            .insert(path.to_path_buf(), num_bytes)
+            .unwrap_or(0);
+        self.num_bytes_used = self.num_bytes_used - previous_num_bytes + num_bytes;
+    }
+
+    fn remove_file(&mut self, path: &Path) {
+        if let Some(num_bytes) = self.file_sizes.remove(path) {
+            self.num_bytes_used -= num_bytes;
+        }
+    }
+}
+
+/// Wrapper of directories bounding the number of bytes they hold.
+///
+/// The `QuotaDirectory` tracks the size of the files written and deleted through it, and
+/// refuses the writes that would exceed its budget with a [`QuotaExceededError`]. The
+/// [`IndexWriter`](crate::IndexWriter) also checks the remaining space before merging
+/// segments or flushing a commit, and fails with
+/// [`TantivyError::QuotaExceeded`](crate::TantivyError::QuotaExceeded) rather than running out
+/// of space halfway through. Ingestion can then be throttled until some space is reclaimed,
+/// by deleting documents and merging, or by raising the budget.
+///
+/// Atomic writes, which are used for the small metadata files such as `meta.json`, are
+/// accounted for but never refused, so that an index over its budget can still be
+/// committed and garbage collected.
+///
+/// The initial usage is computed from the files managed by tantivy when the directory is
+/// wrapped. Clones share their budget.
+#[derive(Clone)]
+pub struct QuotaDirectory {
+    directory: Box<dyn Directory>,
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl fmt::Debug for QuotaDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        write!(
+            f,
+            "QuotaDirectory({:?}, {}/{} bytes)",
+            self.directory, state.num_bytes_used, state.max_num_bytes
+        )
+    }
+}
+
+fn file_len(directory: &dyn Directory, path: &Path) -> Option<u64> {
+    let file_handle = directory.get_file_handle(path).ok()?;
+    Some(file_handle.len() as u64)
+}
+
+impl QuotaDirectory {
+    /// Wraps a directory, bounding the number of bytes it holds to `max_num_bytes`.
+    ///
+    /// The files already present in the directory count towards the budget.
+    pub fn wrap<D: Directory>(directory: D, max_num_bytes: u64) -> crate::Result<QuotaDirectory> {
+        let directory: Box<dyn Directory> = Box::new(directory);
+        let mut paths: HashSet<PathBuf> = match directory.atomic_read(&MANAGED_FILEPATH) {
+            Ok(data) => serde_json::from_slice(&data).map_err(|err| {
+                DataCorruption::new(
+                    MANAGED_FILEPATH.to_path_buf(),
+                    format!("Managed file cannot be deserialized: {err:?}. "),
+                )
+            })?,
+            Err(OpenReadError::FileDoesNotExist(_)) => HashSet::new(),
+            Err(open_read_error) => return Err(open_read_error.into()),
+        };
+        paths.insert(MANAGED_FILEPATH.to_path_buf());
+        paths.insert(META_FILEPATH.to_path_buf());
+        let file_sizes: HashMap<PathBuf, u64> = paths
+            .into_iter()
+            .filter_map(|path| {
+                let num_bytes = file_len(&*directory, &path)?;
+                Some((path, num_bytes))
+            })
+            .collect();
+        let num_bytes_used = file_sizes.values().sum();
+        Ok(QuotaDirectory {
+            directory,
+            state: Arc::new(Mutex::new(QuotaState {
+                max_num_bytes,
+                num_bytes_used,
+                file_sizes,
+            })),
+        })
+    }
+
+    /// Returns the maximum number of bytes the directory may hold.
+    pub fn max_num_bytes(&self) -> u64 {
+        self.state.lock().unwrap().max_num_bytes
+    }
+
+    /// Updates the maximum number of bytes the directory may hold.
+    ///
+    /// Lowering the budget below the current usage does not delete anything, but refuses any
+    /// further write.
+    pub fn set_max_num_bytes(&self, max_num_bytes: u64) {
+        self.state.lock().unwrap().max_num_bytes = max_num_bytes;
+    }
+
+    /// Returns the number of bytes currently held by the directory.
+    pub fn num_bytes_used(&self) -> u64 {
+        self.state.lock().unwrap().num_bytes_used
+    }
+}
+
+/// Writer associated with the [`QuotaDirectory`], reserving the bytes before writing them.
+struct QuotaWriter {
+    path: PathBuf,
+    writer: Box<dyn TerminatingWrite>,
+    state: Arc<Mutex<QuotaState>>,
+}
+
+impl Write for QuotaWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.state
+            .lock()
+            .unwrap()
+            .reserve(&self.path, buf.len() as u64)?;
+        self.writer.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl TerminatingWrite for QuotaWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.writer.terminate_ref(token)
+    }
+}
+
+impl Directory for QuotaDirectory {
+    fn get_file_handle(&self, path: &Path) -> result::Result<Arc<dyn FileHandle>, OpenReadError> {
+        self.directory.get_file_handle(path)
+    }
+
+    fn open_read(&self, path: &Path) -> result::Result<FileSlice, OpenReadError> {
+        self.directory.open_read(path)
+    }
+
+    fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        self.directory.delete(path)?;
+        self.state.lock().unwrap().remove_file(path);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError> {
+        self.directory.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> result::Result<WritePtr, OpenWriteError> {
+        let writer = self
+            .directory
+            .open_write(path)?
+            .into_inner()
+            .map_err(|_| ())
+            .expect("buffer should be empty");
+        self.state.lock().unwrap().set_file_size(path, 0);
+        Ok(BufWriter::new(Box::new(QuotaWriter {
+            path: path.to_path_buf(),
+            writer,
+            state: self.state.clone(),
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        self.directory.atomic_read(path)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.directory.atomic_write(path, data)?;
+        self.state
+            .lock()
+            .unwrap()
+            .set_file_size(path, data.len() as u64);
+        Ok(())
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.directory.sync_directory()
+    }
+
+    fn available_bytes(&self) -> Option<u64> {
+        let available_bytes = self.state.lock().unwrap().available_bytes();
+        Some(match self.directory.available_bytes() {
+            Some(inner_available_bytes) => available_bytes.min(inner_available_bytes),
+            None => available_bytes,
+        })
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.directory.acquire_lock(lock)
+    }
+
+    fn acquire_shared_lock(&self, lock: &Lock) -> result::Result<DirectoryLock, LockError> {
+        self.directory.acquire_shared_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.directory.watch(watch_callback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+
+    use super::QuotaDirectory;
+    use crate::directory::{Directory, RamDirectory, TerminatingWrite};
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter, TantivyError};
+
+    #[test]
+    fn test_quota_directory_refuses_writes() {
+        let directory = QuotaDirectory::wrap(RamDirectory::create(), 10).unwrap();
+        let path = Path::new("file");
+        let mut write = directory.open_write(path).unwrap();
+        write.write_all(&[1u8; 8]).unwrap();
+        write.flush().unwrap();
+        write.write_all(&[1u8; 4]).unwrap();
+        let io_error = write.flush().unwrap_err();
+        assert!(matches!(
+            TantivyError::from(io_error),
+            TantivyError::QuotaExceeded(quota_exceeded_error)
+                if quota_exceeded_error.num_bytes_available == 2
+                    && quota_exceeded_error.num_bytes_requested == 4
+        ));
+        drop(write);
+        assert_eq!(directory.num_bytes_used(), 8);
+        assert_eq!(directory.available_bytes(), Some(2));
+        // Atomic writes are never refused.
+        directory
+            .atomic_write(Path::new("meta"), &[1u8; 4])
+            .unwrap();
+        assert_eq!(directory.num_bytes_used(), 12);
+        assert_eq!(directory.available_bytes(), Some(0));
+        directory.delete(path).unwrap();
+        directory
+            .atomic_write(Path::new("meta"), &[1u8; 2])
+            .unwrap();
+        assert_eq!(directory.num_bytes_used(), 2);
+        let mut write = directory.open_write(Path::new("other")).unwrap();
+        write.write_all(&[1u8; 8]).unwrap();
+        write.terminate().unwrap();
+        assert_eq!(directory.available_bytes(), Some(0));
+    }
+
+    #[test]
+    fn test_quota_directory_initial_usage() -> crate::Result<()> {
+        let ram_directory = RamDirectory::create();
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(
+            ram_directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        drop(index_writer);
+        let directory = QuotaDirectory::wrap(ram_directory.clone(), u64::MAX)?;
+        assert_eq!(
+            directory.num_bytes_used() as usize,
+            ram_directory.total_mem_usage()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quota_directory_refuses_commit_and_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let directory = QuotaDirectory::wrap(RamDirectory::create(), u64::MAX)?;
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..2 {
+            index_writer.add_document(doc!(text => "hello happy tax payer"))?;
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+
+        directory.set_max_num_bytes(directory.num_bytes_used() + 10);
+        let merge_res = index_writer.merge(&segment_ids).wait();
+        assert!(matches!(merge_res, Err(TantivyError::QuotaExceeded(_))));
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+
+        index_writer.add_document(doc!(text => "hello"))?;
+        let commit_res = index_writer.commit();
+        assert!(matches!(commit_res, Err(TantivyError::QuotaExceeded(_))));
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        index_writer.rollback()?;
+
+        // Once the budget is raised, merging and committing work again.
+        directory.set_max_num_bytes(u64::MAX);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        index.load_metas()?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 2);
+        Ok(())
+    }
+}
@@ -63,6 +63,204 @@ mod mmap_directory_tests {
     }
 }
 
+#[cfg(feature = "object-store")]
+mod object_store_directory_tests {
+    use std::sync::Arc;
+
+    use object_store::memory::InMemory;
+
+    use crate::directory::{MmapDirectory, ObjectStoreDirectory};
+
+    type DirectoryImpl = ObjectStoreDirectory;
+
+    fn make_directory() -> DirectoryImpl {
+        let cache = MmapDirectory::create_from_tempdir().unwrap();
+        ObjectStoreDirectory::open(Arc::new(InMemory::new()), "index".into(), cache).unwrap()
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+#[cfg(all(feature = "async-directory", feature = "mmap"))]
+mod tokio_directory_tests {
+    use once_cell::sync::Lazy;
+    use tempfile::TempDir;
+
+    use crate::directory::{AsyncDirectoryAdapter, TokioDirectory};
+
+    type DirectoryImpl = AsyncDirectoryAdapter;
+
+    static RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    });
+
+    static TEMPDIR: Lazy<TempDir> = Lazy::new(|| TempDir::new().unwrap());
+
+    fn make_directory() -> DirectoryImpl {
+        let tempdir = TEMPDIR.path().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir(&tempdir).unwrap();
+        let directory = TokioDirectory::open(tempdir).unwrap();
+        AsyncDirectoryAdapter::new(directory, RUNTIME.handle().clone())
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
+#[cfg(all(feature = "io-uring-directory", feature = "mmap", target_os = "linux"))]
+mod io_uring_directory_tests {
+    use once_cell::sync::Lazy;
+    use tempfile::TempDir;
+
+    use crate::directory::{AsyncDirectoryAdapter, IoUringDirectory};
+
+    type DirectoryImpl = AsyncDirectoryAdapter;
+
+    static RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+    });
+
+    static TEMPDIR: Lazy<TempDir> = Lazy::new(|| TempDir::new().unwrap());
+
+    fn make_directory() -> DirectoryImpl {
+        let tempdir = TEMPDIR.path().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir(&tempdir).unwrap();
+        let directory = IoUringDirectory::open(tempdir).unwrap();
+        AsyncDirectoryAdapter::new(directory, RUNTIME.handle().clone())
+    }
+
+    #[test]
+    fn test_simple() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_simple(&directory)
+    }
+
+    #[test]
+    fn test_write_create_the_file() {
+        let directory = make_directory();
+        super::test_write_create_the_file(&directory);
+    }
+
+    #[test]
+    fn test_rewrite_forbidden() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_rewrite_forbidden(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_delete() -> crate::Result<()> {
+        let directory = make_directory();
+        super::test_directory_delete(&directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_lock_non_blocking() {
+        let directory = make_directory();
+        super::test_lock_non_blocking(&directory);
+    }
+
+    #[test]
+    fn test_lock_blocking() {
+        let directory = make_directory();
+        super::test_lock_blocking(&directory);
+    }
+
+    #[test]
+    fn test_watch() {
+        let directory = make_directory();
+        super::test_watch(&directory);
+    }
+}
+
 mod ram_directory_tests {
     use crate::directory::RamDirectory;
 
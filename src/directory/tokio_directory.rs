@@ -0,0 +1,129 @@
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::result;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::directory::error::{DeleteError, OpenDirectoryError, OpenReadError};
+use crate::directory::{AsyncDirectory, OwnedBytes};
+
+/// An [`AsyncDirectory`] storing the files in a directory of the local filesystem, through
+/// [`tokio::fs`].
+#[derive(Clone, Debug)]
+pub struct TokioDirectory {
+    root_path: PathBuf,
+}
+
+impl TokioDirectory {
+    /// Opens a `TokioDirectory` in the given directory.
+    ///
+    /// Returns an error if the `directory_path` does not exist or is not a directory.
+    pub fn open(
+        directory_path: impl AsRef<Path>,
+    ) -> result::Result<TokioDirectory, OpenDirectoryError> {
+        let directory_path = directory_path.as_ref();
+        if !directory_path.exists() {
+            return Err(OpenDirectoryError::DoesNotExist(
+                directory_path.to_path_buf(),
+            ));
+        }
+        if !directory_path.is_dir() {
+            return Err(OpenDirectoryError::NotADirectory(
+                directory_path.to_path_buf(),
+            ));
+        }
+        Ok(TokioDirectory {
+            root_path: directory_path.to_path_buf(),
+        })
+    }
+
+    pub(crate) fn resolve_path(&self, path: &Path) -> PathBuf {
+        self.root_path.join(path)
+    }
+}
+
+#[async_trait]
+impl AsyncDirectory for TokioDirectory {
+    async fn file_len(&self, path: &Path) -> result::Result<usize, OpenReadError> {
+        let metadata = tokio::fs::metadata(self.resolve_path(path))
+            .await
+            .map_err(|io_error| {
+                if io_error.kind() == io::ErrorKind::NotFound {
+                    OpenReadError::FileDoesNotExist(path.to_path_buf())
+                } else {
+                    OpenReadError::wrap_io_error(io_error, path.to_path_buf())
+                }
+            })?;
+        Ok(metadata.len() as usize)
+    }
+
+    async fn read_bytes(&self, path: &Path, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+        let mut file = tokio::fs::File::open(self.resolve_path(path)).await?;
+        file.seek(SeekFrom::Start(byte_range.start as u64)).await?;
+        let mut buffer = vec![0u8; byte_range.len()];
+        file.read_exact(&mut buffer).await?;
+        Ok(OwnedBytes::new(buffer))
+    }
+
+    async fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let mut file = tokio::fs::File::create(self.resolve_path(path)).await?;
+        file.write_all(data).await?;
+        file.flush().await
+    }
+
+    async fn atomic_read(&self, path: &Path) -> result::Result<Vec<u8>, OpenReadError> {
+        tokio::fs::read(self.resolve_path(path))
+            .await
+            .map_err(|io_error| {
+                if io_error.kind() == io::ErrorKind::NotFound {
+                    OpenReadError::FileDoesNotExist(path.to_path_buf())
+                } else {
+                    OpenReadError::wrap_io_error(io_error, path.to_path_buf())
+                }
+            })
+    }
+
+    async fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let full_path = self.resolve_path(path);
+        let tmp_path = full_path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let mut tmp_file = tokio::fs::File::create(&tmp_path).await?;
+        tmp_file.write_all(data).await?;
+        tmp_file.sync_all().await?;
+        drop(tmp_file);
+        tokio::fs::rename(&tmp_path, &full_path).await
+    }
+
+    async fn delete(&self, path: &Path) -> result::Result<(), DeleteError> {
+        tokio::fs::remove_file(self.resolve_path(path))
+            .await
+            .map_err(|io_error| {
+                if io_error.kind() == io::ErrorKind::NotFound {
+                    DeleteError::FileDoesNotExist(path.to_path_buf())
+                } else {
+                    DeleteError::IoError {
+                        io_error: Arc::new(io_error),
+                        filepath: path.to_path_buf(),
+                    }
+                }
+            })
+    }
+
+    async fn exists(&self, path: &Path) -> result::Result<bool, OpenReadError> {
+        tokio::fs::try_exists(self.resolve_path(path))
+            .await
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))
+    }
+
+    async fn sync_directory(&self) -> io::Result<()> {
+        if cfg!(unix) {
+            tokio::fs::File::open(&self.root_path)
+                .await?
+                .sync_all()
+                .await?;
+        }
+        Ok(())
+    }
+}
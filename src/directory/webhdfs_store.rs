@@ -0,0 +1,714 @@
+use std::fmt;
+use std::ops::Range;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use object_store::path::Path as ObjectPath;
+use object_store::{
+    GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult,
+};
+use reqwest::header::LOCATION;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const STORE: &str = "WebHDFS";
+
+type Result<T> = object_store::Result<T>;
+
+fn generic_error<E: Into<Box<dyn std::error::Error + Send + Sync>>>(
+    error: E,
+) -> object_store::Error {
+    object_store::Error::Generic {
+        store: STORE,
+        source: error.into(),
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(response: Response) -> Result<T> {
+    let body = response.bytes().await.map_err(generic_error)?;
+    serde_json::from_slice(&body).map_err(generic_error)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileStatus {
+    length: u64,
+    modification_time: i64,
+    path_suffix: String,
+    #[serde(rename = "type")]
+    file_type: String,
+}
+
+impl FileStatus {
+    fn is_directory(&self) -> bool {
+        self.file_type == "DIRECTORY"
+    }
+
+    fn object_meta(&self, location: ObjectPath) -> ObjectMeta {
+        ObjectMeta {
+            location,
+            last_modified: DateTime::<Utc>::from_timestamp_millis(self.modification_time)
+                .unwrap_or_default(),
+            size: self.length,
+            e_tag: None,
+            version: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct FileStatusResponse {
+    file_status: FileStatus,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct FileStatuses {
+    file_status: Vec<FileStatus>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListStatusResponse {
+    file_statuses: FileStatuses,
+}
+
+#[derive(Deserialize)]
+struct BooleanResponse {
+    boolean: bool,
+}
+
+/// An [`ObjectStore`] storing the objects in HDFS, through the WebHDFS REST API of the
+/// namenode (or of an HttpFS gateway).
+///
+/// It is meant to be used with an
+/// [`ObjectStoreDirectory`](crate::directory::ObjectStoreDirectory), to serve an index
+/// stored in HDFS:
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use tantivy::directory::{MmapDirectory, ObjectStoreDirectory, WebHdfsStore};
+/// # fn main() -> tantivy::Result<()> {
+/// let store = WebHdfsStore::new("http://namenode:9870", "/indexes/logs")?.with_user("tantivy");
+/// let cache = MmapDirectory::open("/var/cache/tantivy/logs")?;
+/// let directory = ObjectStoreDirectory::open(Arc::new(store), Default::default(), cache)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Objects are written to a temporary file, which is then renamed, so that readers never see
+/// a partially written object. HDFS does not support conditional writes: overwriting an
+/// object with [`PutMode::Update`] is not implemented, and
+/// [`ObjectStoreDirectory`](crate::directory::ObjectStoreDirectory) falls back to
+/// unconditional writes. Multipart uploads are not implemented either.
+///
+/// Only the pseudo authentication of WebHDFS (`user.name`) is supported.
+#[derive(Clone, Debug)]
+pub struct WebHdfsStore {
+    client: Client,
+    /// URL of the WebHDFS API, e.g. `http://namenode:9870/webhdfs/v1`.
+    base_url: String,
+    /// HDFS directory the object paths are relative to, without a trailing slash.
+    root: String,
+    user: Option<String>,
+}
+
+impl fmt::Display for WebHdfsStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WebHdfsStore({}{})", self.base_url, self.root)
+    }
+}
+
+impl WebHdfsStore {
+    /// Creates a `WebHdfsStore` for the namenode at `namenode_url` (e.g.
+    /// `http://namenode:9870`), storing the objects under the HDFS directory `root`.
+    pub fn new(namenode_url: &str, root: &str) -> crate::Result<WebHdfsStore> {
+        let client = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|error| crate::TantivyError::InvalidArgument(error.to_string()))?;
+        Ok(WebHdfsStore {
+            client,
+            base_url: format!("{}/webhdfs/v1", namenode_url.trim_end_matches('/')),
+            root: format!("/{}", root.trim_matches('/'))
+                .trim_end_matches('/')
+                .to_string(),
+            user: None,
+        })
+    }
+
+    /// Sets the user the requests are made as.
+    pub fn with_user(mut self, user: &str) -> WebHdfsStore {
+        self.user = Some(user.to_string());
+        self
+    }
+
+    fn hdfs_path(&self, location: &ObjectPath) -> String {
+        format!("{}/{}", self.root, location)
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        hdfs_path: &str,
+        op: &str,
+        params: &[(&str, &str)],
+    ) -> Result<RequestBuilder> {
+        let mut url =
+            Url::parse(&format!("{}{hdfs_path}", self.base_url)).map_err(generic_error)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("op", op);
+            if let Some(user) = &self.user {
+                query.append_pair("user.name", user);
+            }
+            for (key, value) in params {
+                query.append_pair(key, value);
+            }
+        }
+        Ok(self.client.request(method, url))
+    }
+
+    /// Sends the request, and converts the error status codes into errors.
+    async fn send(&self, request: RequestBuilder, hdfs_path: &str) -> Result<Response> {
+        let response = request.send().await.map_err(generic_error)?;
+        let status = response.status();
+        if status.is_success() || status.is_redirection() {
+            return Ok(response);
+        }
+        let message = response.text().await.unwrap_or_default();
+        let path = hdfs_path.to_string();
+        Err(match status {
+            StatusCode::NOT_FOUND => object_store::Error::NotFound {
+                path,
+                source: message.into(),
+            },
+            StatusCode::UNAUTHORIZED => object_store::Error::Unauthenticated {
+                path,
+                source: message.into(),
+            },
+            StatusCode::FORBIDDEN => object_store::Error::PermissionDenied {
+                path,
+                source: message.into(),
+            },
+            _ => generic_error(format!("{status}: {message}")),
+        })
+    }
+
+    /// Sends a request which the namenode redirects to a datanode, and sends the redirected
+    /// request, with `body` if any.
+    async fn send_redirected(
+        &self,
+        method: Method,
+        request: RequestBuilder,
+        hdfs_path: &str,
+        body: Option<Bytes>,
+    ) -> Result<Response> {
+        let response = self.send(request, hdfs_path).await?;
+        if !response.status().is_redirection() {
+            return Err(generic_error(format!(
+                "expected a redirection to a datanode, got {}",
+                response.status()
+            )));
+        }
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| generic_error("redirection without a location"))?;
+        let mut redirected_request = self.client.request(method, location);
+        if let Some(body) = body {
+            redirected_request = redirected_request.body(body);
+        }
+        self.send(redirected_request, hdfs_path).await
+    }
+
+    async fn boolean_op(
+        &self,
+        method: Method,
+        hdfs_path: &str,
+        op: &str,
+        params: &[(&str, &str)],
+    ) -> Result<bool> {
+        let request = self.request(method, hdfs_path, op, params)?;
+        let response = self.send(request, hdfs_path).await?;
+        let boolean_response: BooleanResponse = read_json(response).await?;
+        Ok(boolean_response.boolean)
+    }
+
+    async fn file_status(&self, location: &ObjectPath) -> Result<ObjectMeta> {
+        let hdfs_path = self.hdfs_path(location);
+        let request = self.request(Method::GET, &hdfs_path, "GETFILESTATUS", &[])?;
+        let response = self.send(request, &hdfs_path).await?;
+        let status_response: FileStatusResponse = read_json(response).await?;
+        if status_response.file_status.is_directory() {
+            return Err(object_store::Error::NotFound {
+                path: hdfs_path,
+                source: "the path is a directory".into(),
+            });
+        }
+        Ok(status_response.file_status.object_meta(location.clone()))
+    }
+
+    /// Lists the content of the directory `prefix`, returning an empty list if it does not
+    /// exist.
+    async fn list_status(&self, prefix: &ObjectPath) -> Result<Vec<FileStatus>> {
+        let hdfs_path = self.hdfs_path(prefix);
+        let request = self.request(Method::GET, &hdfs_path, "LISTSTATUS", &[])?;
+        match self.send(request, &hdfs_path).await {
+            Ok(response) => {
+                let list_response: ListStatusResponse = read_json(response).await?;
+                Ok(list_response.file_statuses.file_status)
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(Vec::new()),
+            Err(error) => Err(error),
+        }
+    }
+
+    async fn list_recursive(&self, prefix: ObjectPath) -> Result<Vec<ObjectMeta>> {
+        let mut objects = Vec::new();
+        let mut directories = vec![prefix];
+        while let Some(directory) = directories.pop() {
+            for file_status in self.list_status(&directory).await? {
+                let location = directory.child(file_status.path_suffix.as_str());
+                if file_status.is_directory() {
+                    directories.push(location);
+                } else {
+                    objects.push(file_status.object_meta(location));
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn delete_path(&self, hdfs_path: &str) -> Result<bool> {
+        self.boolean_op(
+            Method::DELETE,
+            hdfs_path,
+            "DELETE",
+            &[("recursive", "false")],
+        )
+        .await
+    }
+
+    async fn rename_path(&self, from: &str, to: &str) -> Result<bool> {
+        self.boolean_op(Method::PUT, from, "RENAME", &[("destination", to)])
+            .await
+    }
+
+    /// Writes the object into a temporary file, and renames it.
+    async fn write_object(
+        &self,
+        location: &ObjectPath,
+        data: Bytes,
+        overwrite: bool,
+    ) -> Result<PutResult> {
+        let hdfs_path = self.hdfs_path(location);
+        let tmp_path = format!("{hdfs_path}.{}.tmp", uuid::Uuid::new_v4());
+        let request = self.request(Method::PUT, &tmp_path, "CREATE", &[("overwrite", "false")])?;
+        self.send_redirected(Method::PUT, request, &tmp_path, Some(data))
+            .await?;
+        if overwrite {
+            self.delete_path(&hdfs_path).await?;
+        }
+        if !self.rename_path(&tmp_path, &hdfs_path).await? {
+            self.delete_path(&tmp_path).await?;
+            return Err(object_store::Error::AlreadyExists {
+                path: hdfs_path,
+                source: "the destination of the rename already exists".into(),
+            });
+        }
+        Ok(PutResult {
+            e_tag: None,
+            version: None,
+        })
+    }
+
+    async fn read_range(&self, location: &ObjectPath, range: Range<u64>) -> Result<Bytes> {
+        if range.is_empty() {
+            return Ok(Bytes::new());
+        }
+        let hdfs_path = self.hdfs_path(location);
+        let offset = range.start.to_string();
+        let length = (range.end - range.start).to_string();
+        let request = self.request(
+            Method::GET,
+            &hdfs_path,
+            "OPEN",
+            &[("offset", &offset), ("length", &length)],
+        )?;
+        let response = self
+            .send_redirected(Method::GET, request, &hdfs_path, None)
+            .await?;
+        response.bytes().await.map_err(generic_error)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for WebHdfsStore {
+    async fn put_opts(
+        &self,
+        location: &ObjectPath,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> Result<PutResult> {
+        let overwrite = match opts.mode {
+            PutMode::Overwrite => true,
+            PutMode::Create => false,
+            PutMode::Update(_) => return Err(object_store::Error::NotImplemented),
+        };
+        self.write_object(location, Bytes::from(payload), overwrite)
+            .await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        _location: &ObjectPath,
+        _opts: PutMultipartOptions,
+    ) -> Result<Box<dyn MultipartUpload>> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn get_opts(&self, location: &ObjectPath, options: GetOptions) -> Result<GetResult> {
+        let meta = self.file_status(location).await?;
+        options.check_preconditions(&meta)?;
+        let range = match &options.range {
+            Some(get_range) => get_range.as_range(meta.size).map_err(generic_error)?,
+            None => 0..meta.size,
+        };
+        let data = if options.head {
+            Bytes::new()
+        } else {
+            self.read_range(location, range.clone()).await?
+        };
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(stream::once(async move { Ok(data) }).boxed()),
+            meta,
+            range,
+            attributes: Default::default(),
+        })
+    }
+
+    async fn delete(&self, location: &ObjectPath) -> Result<()> {
+        self.delete_path(&self.hdfs_path(location)).await?;
+        Ok(())
+    }
+
+    fn list(&self, prefix: Option<&ObjectPath>) -> BoxStream<'static, Result<ObjectMeta>> {
+        let store = self.clone();
+        let prefix = prefix.cloned().unwrap_or_default();
+        stream::once(async move { store.list_recursive(prefix).await })
+            .map(|objects_res| match objects_res {
+                Ok(objects) => stream::iter(objects.into_iter().map(Ok)).boxed(),
+                Err(error) => stream::once(async move { Err(error) }).boxed(),
+            })
+            .flatten()
+            .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&ObjectPath>) -> Result<ListResult> {
+        let prefix = prefix.cloned().unwrap_or_default();
+        let mut list_result = ListResult {
+            common_prefixes: Vec::new(),
+            objects: Vec::new(),
+        };
+        for file_status in self.list_status(&prefix).await? {
+            let location = prefix.child(file_status.path_suffix.as_str());
+            if file_status.is_directory() {
+                list_result.common_prefixes.push(location);
+            } else {
+                list_result.objects.push(file_status.object_meta(location));
+            }
+        }
+        Ok(list_result)
+    }
+
+    async fn copy(&self, from: &ObjectPath, to: &ObjectPath) -> Result<()> {
+        let data = self.get(from).await?.bytes().await?;
+        self.write_object(to, data, true).await?;
+        Ok(())
+    }
+
+    async fn rename(&self, from: &ObjectPath, to: &ObjectPath) -> Result<()> {
+        let to_path = self.hdfs_path(to);
+        self.delete_path(&to_path).await?;
+        if !self.rename_path(&self.hdfs_path(from), &to_path).await? {
+            return Err(object_store::Error::NotFound {
+                path: self.hdfs_path(from),
+                source: "the rename failed".into(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn copy_if_not_exists(&self, from: &ObjectPath, to: &ObjectPath) -> Result<()> {
+        let data = self.get(from).await?.bytes().await?;
+        self.write_object(to, data, false).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    use object_store::path::Path as ObjectPath;
+    use object_store::{ObjectStore, PutMode, PutPayload};
+
+    use super::WebHdfsStore;
+    use crate::collector::Count;
+    use crate::directory::{MmapDirectory, ObjectStoreDirectory};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter};
+
+    type Files = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+    fn percent_decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'%' => {
+                    decoded.push(u8::from_str_radix(&value[i + 1..i + 3], 16).unwrap());
+                    i += 3;
+                }
+                b'+' => {
+                    decoded.push(b' ');
+                    i += 1;
+                }
+                byte => {
+                    decoded.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8(decoded).unwrap()
+    }
+
+    fn file_status_json(name: &str, length: usize, is_directory: bool) -> String {
+        let file_type = if is_directory { "DIRECTORY" } else { "FILE" };
+        format!(
+            r#"{{"length":{length},"modificationTime":0,"pathSuffix":"{name}","type":"{file_type}"}}"#
+        )
+    }
+
+    /// Handles a request of the WebHDFS API, returning the status, extra headers and body of
+    /// the response.
+    fn handle_request(
+        files: &Files,
+        method: &str,
+        target: &str,
+        body: Vec<u8>,
+        port: u16,
+    ) -> (u16, String, Vec<u8>) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let path = percent_decode(path.strip_prefix("/webhdfs/v1").unwrap());
+        let params: BTreeMap<String, String> = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.to_string(), percent_decode(value)))
+            .collect();
+        let redirect = || {
+            let location = format!("http://127.0.0.1:{port}{target}&datanode=true");
+            (307, format!("Location: {location}\r\n"), Vec::new())
+        };
+        let boolean = |value: bool| {
+            (
+                200,
+                String::new(),
+                format!(r#"{{"boolean":{value}}}"#).into(),
+            )
+        };
+        let mut files = files.lock().unwrap();
+        let dir_prefix = format!("{}/", path.trim_end_matches('/'));
+        let is_directory = files.keys().any(|file| file.starts_with(&dir_prefix));
+        match (method, params["op"].as_str()) {
+            ("GET", "GETFILESTATUS") => match files.get(&path) {
+                Some(data) => {
+                    let json = format!(
+                        r#"{{"FileStatus":{}}}"#,
+                        file_status_json("", data.len(), false)
+                    );
+                    (200, String::new(), json.into())
+                }
+                None if is_directory => {
+                    let json = format!(r#"{{"FileStatus":{}}}"#, file_status_json("", 0, true));
+                    (200, String::new(), json.into())
+                }
+                None => (404, String::new(), Vec::new()),
+            },
+            ("GET", "LISTSTATUS") => {
+                if !is_directory {
+                    return (404, String::new(), Vec::new());
+                }
+                let mut children: BTreeMap<String, Option<usize>> = BTreeMap::new();
+                for (file, data) in files.iter() {
+                    if let Some(suffix) = file.strip_prefix(&dir_prefix) {
+                        match suffix.split_once('/') {
+                            Some((child_dir, _)) => children.insert(child_dir.to_string(), None),
+                            None => children.insert(suffix.to_string(), Some(data.len())),
+                        };
+                    }
+                }
+                let statuses: Vec<String> = children
+                    .iter()
+                    .map(|(name, len)| file_status_json(name, len.unwrap_or(0), len.is_none()))
+                    .collect();
+                let json = format!(
+                    r#"{{"FileStatuses":{{"FileStatus":[{}]}}}}"#,
+                    statuses.join(",")
+                );
+                (200, String::new(), json.into())
+            }
+            ("GET", "OPEN") if !params.contains_key("datanode") => redirect(),
+            ("GET", "OPEN") => match files.get(&path) {
+                Some(data) => {
+                    let offset: usize = params["offset"].parse().unwrap();
+                    let length: usize = params["length"].parse().unwrap();
+                    (200, String::new(), data[offset..offset + length].to_vec())
+                }
+                None => (404, String::new(), Vec::new()),
+            },
+            ("PUT", "CREATE") if !params.contains_key("datanode") => redirect(),
+            ("PUT", "CREATE") => {
+                if files.contains_key(&path) && params["overwrite"] == "false" {
+                    return (403, String::new(), Vec::new());
+                }
+                files.insert(path, body);
+                (201, String::new(), Vec::new())
+            }
+            ("PUT", "RENAME") => {
+                let destination = &params["destination"];
+                if files.contains_key(destination) || !files.contains_key(&path) {
+                    return boolean(false);
+                }
+                let data = files.remove(&path).unwrap();
+                files.insert(destination.clone(), data);
+                boolean(true)
+            }
+            ("DELETE", "DELETE") => boolean(files.remove(&path).is_some()),
+            _ => (400, String::new(), Vec::new()),
+        }
+    }
+
+    fn handle_connection(files: &Files, stream: TcpStream, port: u16) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = (parts.next().unwrap(), parts.next().unwrap());
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let (status, headers, response_body) = handle_request(files, method, target, body, port);
+        let mut stream = stream;
+        write!(
+            stream,
+            "HTTP/1.1 {status} Status\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            response_body.len()
+        )
+        .unwrap();
+        stream.write_all(&response_body).unwrap();
+    }
+
+    /// Starts a WebHDFS server keeping the files in memory, returning its URL.
+    fn start_mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let files = Files::default();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                handle_connection(&files, stream.unwrap(), port);
+            }
+        });
+        format!("http://127.0.0.1:{port}")
+    }
+
+    #[test]
+    fn test_webhdfs_store() -> crate::Result<()> {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let store = WebHdfsStore::new(&start_mock_server(), "/indexes")?.with_user("tantivy");
+        runtime.block_on(async {
+            let path = ObjectPath::from("index/a.txt");
+            assert!(matches!(
+                store.head(&path).await,
+                Err(object_store::Error::NotFound { .. })
+            ));
+            store.put(&path, PutPayload::from("hello")).await.unwrap();
+            assert_eq!(store.head(&path).await.unwrap().size, 5);
+            let data = store.get(&path).await.unwrap().bytes().await.unwrap();
+            assert_eq!(&data[..], b"hello");
+            assert_eq!(&store.get_range(&path, 1..4).await.unwrap()[..], b"ell");
+            assert!(matches!(
+                store
+                    .put_opts(&path, PutPayload::from("world"), PutMode::Create.into())
+                    .await,
+                Err(object_store::Error::AlreadyExists { .. })
+            ));
+            store.put(&path, PutPayload::from("world")).await.unwrap();
+            let data = store.get(&path).await.unwrap().bytes().await.unwrap();
+            assert_eq!(&data[..], b"world");
+            store
+                .put(&ObjectPath::from("index/sub/b.txt"), PutPayload::from("b"))
+                .await
+                .unwrap();
+            let list_result = store
+                .list_with_delimiter(Some(&ObjectPath::from("index")))
+                .await
+                .unwrap();
+            assert_eq!(list_result.objects.len(), 1);
+            assert_eq!(
+                list_result.common_prefixes,
+                vec![ObjectPath::from("index/sub")]
+            );
+            store.delete(&path).await.unwrap();
+            assert!(store.head(&path).await.is_err());
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn test_webhdfs_object_store_directory() -> crate::Result<()> {
+        let store = WebHdfsStore::new(&start_mock_server(), "/indexes")?;
+        let cache = MmapDirectory::create_from_tempdir()?;
+        let directory = ObjectStoreDirectory::open(Arc::new(store.clone()), "index".into(), cache)?;
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create(directory, schema_builder.build(), Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for _ in 0..2 {
+            index_writer.add_document(doc!(text => "hello"))?;
+            index_writer.commit()?;
+        }
+        drop(index_writer);
+
+        let cache = MmapDirectory::create_from_tempdir()?;
+        let directory = ObjectStoreDirectory::open(Arc::new(store), "index".into(), cache)?;
+        let index = Index::open(directory)?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        Ok(())
+    }
+}
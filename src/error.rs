@@ -9,6 +9,7 @@ use thiserror::Error;
 use crate::aggregation::AggregationError;
 use crate::directory::error::{
     Incompatibility, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
+    QuotaExceededError,
 };
 use crate::fastfield::FastFieldNotAvailableError;
 use crate::schema::document::DeserializeError;
@@ -110,10 +111,16 @@ pub enum TantivyError {
     #[error("Deserialize error: {0}")]
     /// An error occurred while attempting to deserialize a document.
     DeserializeError(DeserializeError),
+    /// The operation would exceed the byte budget of the directory.
+    #[error("{0}")]
+    QuotaExceeded(QuotaExceededError),
 }
 
 impl From<io::Error> for TantivyError {
     fn from(io_err: io::Error) -> TantivyError {
+        if let Some(quota_exceeded_error) = QuotaExceededError::from_io_error(&io_err) {
+            return TantivyError::QuotaExceeded(quota_exceeded_error.clone());
+        }
         TantivyError::IoError(Arc::new(io_err))
     }
 }
@@ -0,0 +1,60 @@
+use columnar::Column;
+
+use crate::DocId;
+
+/// Maps a value of at most 16 bytes to a `u128`.
+///
+/// The bytes are left-aligned and padded with zeros, so that the order of the `u128`s matches
+/// the lexicographical order of values of the same width.
+pub(crate) fn fixed_width_bytes_to_u128(bytes: &[u8]) -> u128 {
+    debug_assert!(bytes.len() <= 16);
+    let mut buffer = [0u8; 16];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    u128::from_be_bytes(buffer)
+}
+
+/// Inverse of [`fixed_width_bytes_to_u128`].
+pub(crate) fn u128_to_fixed_width_bytes(val: u128, width: usize) -> Vec<u8> {
+    val.to_be_bytes()[..width].to_vec()
+}
+
+/// Fast field column of a bytes field declared as fixed-width.
+///
+/// See [`BytesOptions::set_fixed_width`](crate::schema::BytesOptions::set_fixed_width).
+#[derive(Clone)]
+pub struct FixedWidthBytesColumn {
+    column: Column<u128>,
+    width: usize,
+}
+
+impl FixedWidthBytesColumn {
+    pub(crate) fn new(column: Column<u128>, width: usize) -> FixedWidthBytesColumn {
+        FixedWidthBytesColumn { column, width }
+    }
+
+    /// Returns the width of the values.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the underlying column.
+    ///
+    /// The values are left-aligned in the `u128`s, and padded with zeros.
+    pub fn column(&self) -> &Column<u128> {
+        &self.column
+    }
+
+    /// Returns the first value of a document, if any.
+    pub fn first(&self, doc: DocId) -> Option<Vec<u8>> {
+        self.column
+            .first(doc)
+            .map(|val| u128_to_fixed_width_bytes(val, self.width))
+    }
+
+    /// Returns the values of a document.
+    pub fn values_for_doc(&self, doc: DocId) -> impl Iterator<Item = Vec<u8>> + '_ {
+        self.column
+            .values_for_doc(doc)
+            .map(|val| u128_to_fixed_width_bytes(val, self.width))
+    }
+}
@@ -25,7 +25,10 @@ use columnar::MonotonicallyMappableToU64;
 pub use self::alive_bitset::{intersect_alive_bitsets, write_alive_bitset, AliveBitSet};
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
+pub(crate) use self::fixed_width_bytes::fixed_width_bytes_to_u128;
+pub use self::fixed_width_bytes::FixedWidthBytesColumn;
 pub use self::readers::FastFieldReaders;
+pub use self::runtime_field::{RuntimeField, RuntimeFieldType};
 pub use self::writer::FastFieldsWriter;
 use crate::schema::Type;
 use crate::DateTime;
@@ -33,7 +36,9 @@ use crate::DateTime;
 mod alive_bitset;
 mod error;
 mod facet_reader;
+mod fixed_width_bytes;
 mod readers;
+mod runtime_field;
 mod writer;
 
 /// Trait for types that are allowed for fast fields:
@@ -79,6 +84,7 @@ mod tests {
     use std::ops::{Range, RangeInclusive};
     use std::path::Path;
 
+    use columnar::column_values::CodecType;
     use columnar::StrColumn;
     use common::{ByteCount, DateTimePrecision, HasLen, TerminatingWrite};
     use once_cell::sync::Lazy;
@@ -91,8 +97,9 @@ mod tests {
     use crate::index::SegmentId;
     use crate::merge_policy::NoMergePolicy;
     use crate::schema::{
-        DateOptions, Facet, FacetOptions, Field, JsonObjectOptions, Schema, SchemaBuilder,
-        TantivyDocument, TextOptions, FAST, INDEXED, STORED, STRING, TEXT,
+        BytesOptions, DateOptions, Facet, FacetOptions, FastFieldCodec, Field, FloatPrecision,
+        JsonObjectOptions, NumericOptions, Schema, SchemaBuilder, TantivyDocument, TextOptions,
+        FAST, INDEXED, STORED, STRING, TEXT,
     };
     use crate::time::OffsetDateTime;
     use crate::tokenizer::{LowerCaser, RawTokenizer, TextAnalyzer, TokenizerManager};
@@ -904,6 +911,157 @@ mod tests {
         readers.column_num_bytes("field").unwrap()
     }
 
+    #[test]
+    fn test_float_fast_field_precision() {
+        let size_f64 = test_float_fast_field_with_precision(FloatPrecision::F64);
+        // 16 bits per value at most.
+        let size_f16 = test_float_fast_field_with_precision(FloatPrecision::F16);
+        assert!(size_f16.get_bytes() < 100 + 1000 * 16 / 8);
+        let size_bf16 = test_float_fast_field_with_precision(FloatPrecision::BF16);
+        assert!(size_bf16.get_bytes() < 100 + 1000 * 16 / 8);
+        assert!(size_bf16.get_bytes() * 3 < size_f64.get_bytes());
+    }
+
+    fn test_float_fast_field_with_precision(precision: FloatPrecision) -> ByteCount {
+        let mut rng = StdRng::seed_from_u64(2u64);
+        let vals: Vec<f64> = std::iter::repeat_with(|| rng.gen_range(0.0..1_000.0))
+            .take(1_000)
+            .collect();
+        let numeric_options = NumericOptions::default()
+            .set_fast()
+            .set_fast_precision(precision);
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_f64_field("field", numeric_options);
+        let schema = schema_builder.build();
+
+        let docs: Vec<TantivyDocument> = vals.iter().map(|val| doc!(field=>*val)).collect();
+
+        let directory = get_index(&docs[..], &schema).unwrap();
+        let path = Path::new("test");
+        let file = directory.open_read(path).unwrap();
+        let readers = FastFieldReaders::open(file, schema).unwrap();
+        let col = readers.f64("field").unwrap();
+
+        for (i, val) in vals.iter().enumerate() {
+            let read_val: f64 = col.first(i as u32).unwrap();
+            assert_eq!(read_val, precision.round(*val));
+            let max_relative_error = match precision {
+                FloatPrecision::F64 => 0.0,
+                FloatPrecision::F16 => 1.0 / 2048.0,
+                FloatPrecision::BF16 => 1.0 / 256.0,
+            };
+            assert!((read_val - val).abs() <= val * max_relative_error);
+        }
+        readers.column_num_bytes("field").unwrap()
+    }
+
+    #[test]
+    fn test_fast_field_codec() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let auto_field = schema_builder.add_u64_field("auto", FAST);
+        let linear_field = schema_builder.add_u64_field(
+            "linear",
+            NumericOptions::from(FAST).set_fast_codec(FastFieldCodec::Linear),
+        );
+        let bitpacked_field = schema_builder.add_u64_field(
+            "bitpacked",
+            NumericOptions::from(FAST).set_fast_codec(FastFieldCodec::Bitpacked),
+        );
+        let date_field = schema_builder.add_date_field(
+            "date",
+            DateOptions::from(FAST).set_fast_codec(FastFieldCodec::Bitpacked),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for val in 0..2_000u64 {
+            index_writer.add_document(doc!(
+                auto_field => val * 1_000,
+                linear_field => val * 3 + 7,
+                bitpacked_field => val * 1_000 + val % 10,
+                date_field => DateTime::from_timestamp_secs(val as i64 * 60),
+            ))?;
+            if val == 999 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        for segment_reader in searcher.segment_readers() {
+            let linear_codec_stats = segment_reader.fast_fields().codec_stats("linear")?.unwrap();
+            assert_eq!(linear_codec_stats.codec_type, CodecType::Linear);
+            assert_eq!(linear_codec_stats.num_vals, 1_000);
+            assert!(linear_codec_stats.bits_per_value() < 1.0);
+        }
+
+        // The codecs are kept on merge.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        let auto_codec_stats = fast_fields.codec_stats("auto")?.unwrap();
+        assert_eq!(auto_codec_stats.num_vals, 2_000);
+        let linear_codec_stats = fast_fields.codec_stats("linear")?.unwrap();
+        assert_eq!(linear_codec_stats.codec_type, CodecType::Linear);
+        let bitpacked_codec_stats = fast_fields.codec_stats("bitpacked")?.unwrap();
+        assert_eq!(bitpacked_codec_stats.codec_type, CodecType::Bitpacked);
+        // The amplitude of the values requires 21 bits.
+        assert!(bitpacked_codec_stats.bits_per_value() >= 21.0);
+        let date_codec_stats = fast_fields.codec_stats("date")?.unwrap();
+        assert_eq!(date_codec_stats.codec_type, CodecType::Bitpacked);
+        assert!(fast_fields.codec_stats("missing")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_width_bytes_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let uuid_field =
+            schema_builder.add_bytes_field("uuid", BytesOptions::from(FAST).set_fixed_width(16));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let mut rng = StdRng::seed_from_u64(3u64);
+        let uuids: Vec<[u8; 16]> = std::iter::repeat_with(|| rng.gen()).take(100).collect();
+        for uuids_chunk in uuids.chunks(50) {
+            for uuid in uuids_chunk {
+                index_writer.add_document(doc!(uuid_field => uuid.to_vec()))?;
+            }
+            index_writer.commit()?;
+        }
+        // Values of a different width are rejected.
+        index_writer.add_document(doc!(uuid_field => vec![0u8; 4]))?;
+        assert!(index_writer.commit().is_err());
+        drop(index_writer);
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let uuid_column = segment_reader
+            .fast_fields()
+            .fixed_width_bytes("uuid")?
+            .unwrap();
+        assert_eq!(uuid_column.width(), 16);
+        let mut read_uuids: Vec<Vec<u8>> = (0..segment_reader.max_doc())
+            .map(|doc| uuid_column.first(doc).unwrap())
+            .collect();
+        read_uuids.sort();
+        let mut expected_uuids: Vec<Vec<u8>> = uuids.iter().map(|uuid| uuid.to_vec()).collect();
+        expected_uuids.sort();
+        assert_eq!(read_uuids, expected_uuids);
+        assert!(segment_reader.fast_fields().bytes("uuid")?.is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_gcd_bug_regression_1757() {
         let mut schema_builder = Schema::builder();
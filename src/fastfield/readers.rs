@@ -3,13 +3,15 @@ use std::net::Ipv6Addr;
 use std::sync::Arc;
 
 use columnar::{
-    BytesColumn, Column, ColumnType, ColumnValues, ColumnarReader, DynamicColumn,
-    DynamicColumnHandle, HasAssociatedColumnType, StrColumn,
+    BytesColumn, Column, ColumnCodecStats, ColumnType, ColumnValueUpdates, ColumnValues,
+    ColumnarReader, DynamicColumn, DynamicColumnHandle, HasAssociatedColumnType, StrColumn,
 };
 use common::ByteCount;
 
 use crate::core::json_utils::encode_column_name;
 use crate::directory::FileSlice;
+use crate::fastfield::runtime_field::RuntimeColumns;
+use crate::fastfield::{FixedWidthBytesColumn, RuntimeField, RuntimeFieldType};
 use crate::schema::{Field, FieldEntry, FieldType, Schema};
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
 use crate::TantivyError;
@@ -22,12 +24,68 @@ use crate::TantivyError;
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    runtime_columns: Option<Arc<RuntimeColumns>>,
 }
 
 impl FastFieldReaders {
+    #[cfg(test)]
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
-        let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Self::open_with_updates(fast_field_file, schema, ColumnValueUpdates::default())
+    }
+
+    /// Opens the fast fields, overriding some of their values with `updates`.
+    pub(crate) fn open_with_updates(
+        fast_field_file: FileSlice,
+        schema: Schema,
+        updates: ColumnValueUpdates,
+    ) -> io::Result<FastFieldReaders> {
+        let columnar = Arc::new(ColumnarReader::open(fast_field_file)?.with_updates(updates));
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            runtime_columns: None,
+        })
+    }
+
+    /// Returns the fast fields extended with `runtime_fields`, replacing the previous ones.
+    pub(crate) fn with_runtime_fields(&self, runtime_fields: &[RuntimeField]) -> FastFieldReaders {
+        let fast_fields = FastFieldReaders {
+            runtime_columns: None,
+            ..self.clone()
+        };
+        let runtime_columns = if runtime_fields.is_empty() {
+            None
+        } else {
+            Some(Arc::new(RuntimeColumns::new(
+                fast_fields.clone(),
+                runtime_fields,
+            )))
+        };
+        FastFieldReaders {
+            runtime_columns,
+            ..fast_fields
+        }
+    }
+
+    /// Returns the type of the runtime field `field_name`, or `None` if there is no such runtime
+    /// field.
+    pub fn runtime_field_type(&self, field_name: &str) -> Option<RuntimeFieldType> {
+        let runtime_field = self.runtime_columns.as_ref()?.runtime_field(field_name)?;
+        Some(runtime_field.field_type())
+    }
+
+    /// Returns the columns associated with `field_name`, a runtime field or a fast field of the
+    /// schema, or `None` if there is no such field.
+    fn read_columns(&self, field_name: &str) -> crate::Result<Option<Vec<DynamicColumnHandle>>> {
+        if let Some(runtime_columns) = &self.runtime_columns {
+            if let Some(column_handles) = runtime_columns.read_columns(field_name)? {
+                return Ok(Some(column_handles));
+            }
+        }
+        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+            return Ok(None);
+        };
+        Ok(Some(self.columnar.read_columns(&resolved_field_name)?))
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -133,17 +191,32 @@ impl FastFieldReaders {
     ///
     /// Returns 0 if the column does not exist.
     pub fn column_num_bytes(&self, field: &str) -> crate::Result<ByteCount> {
-        let Some(resolved_field_name) = self.resolve_field(field)? else {
+        let Some(column_handles) = self.read_columns(field)? else {
             return Ok(0u64.into());
         };
-        Ok(self
-            .columnar
-            .read_columns(&resolved_field_name)?
+        Ok(column_handles
             .into_iter()
             .map(|column_handle| column_handle.num_bytes())
             .sum())
     }
 
+    /// Returns the codec used to encode the values of a numerical, bool or date fast field in
+    /// this segment, along with the number of bits per value it achieved.
+    ///
+    /// Returns `None` if the column does not exist, or if its values are not encoded with one
+    /// of the [`FastFieldCodec`](crate::schema::FastFieldCodec)s.
+    pub fn codec_stats(&self, field_name: &str) -> crate::Result<Option<ColumnCodecStats>> {
+        let Some(column_handles) = self.read_columns(field_name)? else {
+            return Ok(None);
+        };
+        for column_handle in column_handles {
+            if let Some(codec_stats) = column_handle.codec_stats()? {
+                return Ok(Some(codec_stats));
+            }
+        }
+        Ok(None)
+    }
+
     /// Returns a typed column value object.
     ///
     /// In that column value:
@@ -196,6 +269,11 @@ impl FastFieldReaders {
     }
 
     /// Returns a `str` column.
+    ///
+    /// The column gives access to the term ordinals of the values of each document, through
+    /// [`BytesColumn::term_ords`], along with the dictionary of the segment mapping them back to
+    /// their terms. Collectors grouping or sorting on multi-valued text fields can work on these
+    /// ordinals, and only look up the terms they return.
     pub fn str(&self, field_name: &str) -> crate::Result<Option<StrColumn>> {
         let Some(dynamic_column_handle) =
             self.dynamic_column_handle(field_name, ColumnType::Str)?
@@ -217,18 +295,37 @@ impl FastFieldReaders {
         Ok(dynamic_column.into())
     }
 
+    /// Returns the column of a bytes field declared as fixed-width.
+    ///
+    /// If `field_name` is not a fixed-width bytes field, this method returns an Error.
+    pub fn fixed_width_bytes(
+        &self,
+        field_name: &str,
+    ) -> crate::Result<Option<FixedWidthBytesColumn>> {
+        let field = self.schema.get_field(field_name)?;
+        let width = match self.schema.get_field_entry(field).field_type() {
+            FieldType::Bytes(bytes_options) => bytes_options.fixed_width(),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!(
+                "Field `{field_name}` is not a fixed-width bytes field."
+            ))
+        })?;
+        let column_opt: Option<Column<u128>> = self.column_opt(field_name)?;
+        Ok(column_opt.map(|column| FixedWidthBytesColumn::new(column, width)))
+    }
+
     /// Returns a `dynamic_column_handle`.
     pub fn dynamic_column_handle(
         &self,
         field_name: &str,
         column_type: ColumnType,
     ) -> crate::Result<Option<DynamicColumnHandle>> {
-        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+        let Some(column_handles) = self.read_columns(field_name)? else {
             return Ok(None);
         };
-        let dynamic_column_handle_opt = self
-            .columnar
-            .read_columns(&resolved_field_name)?
+        let dynamic_column_handle_opt = column_handles
             .into_iter()
             .find(|column| column.column_type() == column_type);
         Ok(dynamic_column_handle_opt)
@@ -239,15 +336,8 @@ impl FastFieldReaders {
         &self,
         field_name: &str,
     ) -> crate::Result<Vec<DynamicColumnHandle>> {
-        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
-            return Ok(Vec::new());
-        };
-        let dynamic_column_handles = self
-            .columnar
-            .read_columns(&resolved_field_name)?
-            .into_iter()
-            .collect();
-        Ok(dynamic_column_handles)
+        let column_handles = self.read_columns(field_name)?.unwrap_or_default();
+        Ok(column_handles)
     }
 
     /// Returns all `dynamic_column_handle` that are inner fields of the provided JSON path.
@@ -306,10 +396,10 @@ impl FastFieldReaders {
         type_white_list_opt: Option<&[ColumnType]>,
         field_name: &str,
     ) -> crate::Result<Option<(Column<u64>, ColumnType)>> {
-        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+        let Some(column_handles) = self.read_columns(field_name)? else {
             return Ok(None);
         };
-        for col in self.columnar.read_columns(&resolved_field_name)? {
+        for col in column_handles {
             if let Some(type_white_list) = type_white_list_opt {
                 if !type_white_list.contains(&col.column_type()) {
                     continue;
@@ -334,10 +424,10 @@ impl FastFieldReaders {
         field_name: &str,
     ) -> crate::Result<Vec<(Column<u64>, ColumnType)>> {
         let mut columns_and_types = Vec::new();
-        let Some(resolved_field_name) = self.resolve_field(field_name)? else {
+        let Some(column_handles) = self.read_columns(field_name)? else {
             return Ok(columns_and_types);
         };
-        for col in self.columnar.read_columns(&resolved_field_name)? {
+        for col in column_handles {
             if let Some(type_white_list) = type_white_list_opt {
                 if !type_white_list.contains(&col.column_type()) {
                     continue;
@@ -0,0 +1,438 @@
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::{
+    Column, ColumnType, ColumnarReader, ColumnarWriter, DynamicColumn, DynamicColumnHandle,
+    NumericalType,
+};
+use common::DateTime;
+use once_cell::sync::OnceCell;
+
+use crate::fastfield::FastFieldReaders;
+use crate::schema::Type;
+use crate::DocId;
+
+/// The name of the column of a runtime field, in the columnar holding its values.
+const RUNTIME_COLUMN_NAME: &str = "value";
+
+/// Computes the value of a runtime field from the values of its source fields.
+type ComputeFn = dyn Fn(&[f64]) -> Option<f64> + Send + Sync;
+
+/// The type of the values of a [`RuntimeField`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimeFieldType {
+    /// `u64` values, the values computed by the runtime field are truncated.
+    U64,
+    /// `i64` values, the values computed by the runtime field are truncated.
+    I64,
+    /// `f64` values.
+    F64,
+    /// Dates, computed by the runtime field as timestamps in microseconds.
+    Date,
+}
+
+impl RuntimeFieldType {
+    fn column_type(self) -> ColumnType {
+        match self {
+            RuntimeFieldType::U64 => ColumnType::U64,
+            RuntimeFieldType::I64 => ColumnType::I64,
+            RuntimeFieldType::F64 => ColumnType::F64,
+            RuntimeFieldType::Date => ColumnType::DateTime,
+        }
+    }
+
+    /// Returns the type of the values of the runtime field, as if it was a field of the schema.
+    pub fn value_type(self) -> Type {
+        match self {
+            RuntimeFieldType::U64 => Type::U64,
+            RuntimeFieldType::I64 => Type::I64,
+            RuntimeFieldType::F64 => Type::F64,
+            RuntimeFieldType::Date => Type::Date,
+        }
+    }
+}
+
+/// A virtual fast field, computed at search time from the fast fields of the schema.
+///
+/// The value of a runtime field for a document is computed by a closure, from the first values
+/// of the document for each of the `source_fields`, in the same order. The numerical source
+/// fields are given as `f64`, and the date source fields as timestamps in microseconds. A
+/// document without any value for one of the source fields, or for which the closure returns
+/// `None`, has no value.
+///
+/// Runtime fields are added to a searcher with
+/// [`Searcher::with_runtime_fields`](crate::Searcher::with_runtime_fields). They can then be
+/// used like fast fields by aggregations, to sort with
+/// [`TopDocs::order_by_fast_field`](crate::collector::TopDocs::order_by_fast_field), and to filter
+/// with a [`RuntimeFieldRangeQuery`](crate::query::RuntimeFieldRangeQuery).
+///
+/// The values of a runtime field are computed for all of the documents of a segment the first
+/// time they are read, and kept along with the searcher.
+///
+/// # Example
+/// ```
+/// use tantivy::fastfield::{RuntimeField, RuntimeFieldType};
+///
+/// let price_with_tax = RuntimeField::new(
+///     "price_with_tax",
+///     RuntimeFieldType::F64,
+///     &["price", "tax_rate"],
+///     |values| Some(values[0] * (1.0 + values[1])),
+/// );
+/// const MICROS_PER_DAY: f64 = 86_400_000_000.0;
+/// let day = RuntimeField::new("day", RuntimeFieldType::Date, &["timestamp"], |values| {
+///     Some((values[0] / MICROS_PER_DAY).floor() * MICROS_PER_DAY)
+/// });
+/// ```
+#[derive(Clone)]
+pub struct RuntimeField {
+    name: String,
+    field_type: RuntimeFieldType,
+    source_fields: Vec<String>,
+    compute: Arc<ComputeFn>,
+}
+
+impl RuntimeField {
+    /// Creates a runtime field `name` of type `field_type`, computing its values from the values
+    /// of `source_fields` with `compute`.
+    pub fn new(
+        name: &str,
+        field_type: RuntimeFieldType,
+        source_fields: &[&str],
+        compute: impl Fn(&[f64]) -> Option<f64> + Send + Sync + 'static,
+    ) -> RuntimeField {
+        RuntimeField {
+            name: name.to_string(),
+            field_type,
+            source_fields: source_fields.iter().map(ToString::to_string).collect(),
+            compute: Arc::new(compute),
+        }
+    }
+
+    /// The name of the runtime field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the values of the runtime field.
+    pub fn field_type(&self) -> RuntimeFieldType {
+        self.field_type
+    }
+
+    /// The fast fields the values of the runtime field are computed from.
+    pub fn source_fields(&self) -> &[String] {
+        &self.source_fields
+    }
+
+    /// Computes the values of the runtime field for all of the documents of a segment, from its
+    /// `fast_fields`.
+    fn compute_columnar(&self, fast_fields: &FastFieldReaders) -> crate::Result<ColumnarReader> {
+        let num_docs = fast_fields.columnar().num_docs();
+        let source_columns = self
+            .source_fields
+            .iter()
+            .map(|source_field| SourceColumn::open(fast_fields, source_field))
+            .collect::<crate::Result<Vec<SourceColumn>>>()?;
+        let mut columnar_writer = ColumnarWriter::default();
+        columnar_writer.record_column_type(
+            RUNTIME_COLUMN_NAME,
+            self.field_type.column_type(),
+            false,
+        );
+        let mut source_values = vec![0.0; source_columns.len()];
+        'docs: for doc in 0..num_docs {
+            for (source_value, source_column) in source_values.iter_mut().zip(&source_columns) {
+                let Some(value) = source_column.first(doc) else {
+                    continue 'docs;
+                };
+                *source_value = value;
+            }
+            let Some(value) = (self.compute)(&source_values) else {
+                continue;
+            };
+            match self.field_type {
+                RuntimeFieldType::U64 => {
+                    columnar_writer.record_numerical(doc, RUNTIME_COLUMN_NAME, value as u64)
+                }
+                RuntimeFieldType::I64 => {
+                    columnar_writer.record_numerical(doc, RUNTIME_COLUMN_NAME, value as i64)
+                }
+                RuntimeFieldType::F64 => {
+                    columnar_writer.record_numerical(doc, RUNTIME_COLUMN_NAME, value)
+                }
+                RuntimeFieldType::Date => columnar_writer.record_datetime(
+                    doc,
+                    RUNTIME_COLUMN_NAME,
+                    DateTime::from_timestamp_micros(value as i64),
+                ),
+            }
+        }
+        let mut columnar_bytes = Vec::new();
+        columnar_writer.serialize(num_docs, &mut columnar_bytes)?;
+        Ok(ColumnarReader::open(columnar_bytes)?)
+    }
+}
+
+impl fmt::Debug for RuntimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeField")
+            .field("name", &self.name)
+            .field("field_type", &self.field_type)
+            .field("source_fields", &self.source_fields)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A fast field a runtime field is computed from.
+enum SourceColumn {
+    Numerical(Column<f64>),
+    Date(Column<DateTime>),
+    /// The field has no numerical or date values in the segment.
+    Missing,
+}
+
+impl SourceColumn {
+    fn open(fast_fields: &FastFieldReaders, field_name: &str) -> crate::Result<SourceColumn> {
+        for handle in fast_fields.dynamic_column_handles(field_name)? {
+            match handle.open()? {
+                DynamicColumn::DateTime(column) => return Ok(SourceColumn::Date(column)),
+                dynamic_column => {
+                    if let Some(DynamicColumn::F64(column)) =
+                        dynamic_column.coerce_numerical(NumericalType::F64)
+                    {
+                        return Ok(SourceColumn::Numerical(column));
+                    }
+                }
+            }
+        }
+        Ok(SourceColumn::Missing)
+    }
+
+    fn first(&self, doc: DocId) -> Option<f64> {
+        match self {
+            SourceColumn::Numerical(column) => column.first(doc),
+            SourceColumn::Date(column) => column
+                .first(doc)
+                .map(|date| date.into_timestamp_micros() as f64),
+            SourceColumn::Missing => None,
+        }
+    }
+}
+
+/// The runtime fields of a segment, whose values are computed on first access.
+pub(crate) struct RuntimeColumns {
+    fast_fields: FastFieldReaders,
+    runtime_fields: Vec<(RuntimeField, OnceCell<ColumnarReader>)>,
+}
+
+impl RuntimeColumns {
+    /// Creates the runtime columns computed from the segment's `fast_fields`, which must not
+    /// have runtime fields themselves.
+    pub fn new(fast_fields: FastFieldReaders, runtime_fields: &[RuntimeField]) -> RuntimeColumns {
+        RuntimeColumns {
+            fast_fields,
+            runtime_fields: runtime_fields
+                .iter()
+                .map(|runtime_field| (runtime_field.clone(), OnceCell::new()))
+                .collect(),
+        }
+    }
+
+    /// Returns the runtime field `field_name`, if any.
+    pub fn runtime_field(&self, field_name: &str) -> Option<&RuntimeField> {
+        self.runtime_fields
+            .iter()
+            .map(|(runtime_field, _)| runtime_field)
+            .find(|runtime_field| runtime_field.name == field_name)
+    }
+
+    /// Returns the column of the runtime field `field_name`, or `None` if there is no such
+    /// runtime field.
+    pub fn read_columns(
+        &self,
+        field_name: &str,
+    ) -> crate::Result<Option<Vec<DynamicColumnHandle>>> {
+        let Some((runtime_field, columnar)) = self
+            .runtime_fields
+            .iter()
+            .find(|(runtime_field, _)| runtime_field.name == field_name)
+        else {
+            return Ok(None);
+        };
+        let columnar =
+            columnar.get_or_try_init(|| runtime_field.compute_columnar(&self.fast_fields))?;
+        Ok(Some(columnar.read_columns(RUNTIME_COLUMN_NAME)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use common::DateTime;
+
+    use super::{RuntimeField, RuntimeFieldType};
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::{Count, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, RuntimeFieldRangeQuery};
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter, Order, Searcher};
+
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+    fn searcher_with_runtime_fields() -> crate::Result<Searcher> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let quantity = schema_builder.add_u64_field("quantity", FAST);
+        let timestamp = schema_builder.add_date_field("timestamp", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let day = |day: i64, hour: i64| {
+            DateTime::from_timestamp_micros(day * MICROS_PER_DAY + hour * 3_600_000_000)
+        };
+        index_writer.add_document(doc!(price => 2.5, quantity => 4u64, timestamp => day(0, 3)))?;
+        index_writer
+            .add_document(doc!(price => 10.0, quantity => 1u64, timestamp => day(0, 20)))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(price => 1.0, quantity => 3u64, timestamp => day(1, 1)))?;
+        // A document without quantity has no total.
+        index_writer.add_document(doc!(price => 6.0, timestamp => day(2, 12)))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        searcher.with_runtime_fields(vec![
+            RuntimeField::new(
+                "total",
+                RuntimeFieldType::F64,
+                &["price", "quantity"],
+                |values| Some(values[0] * values[1]),
+            ),
+            RuntimeField::new("day", RuntimeFieldType::Date, &["timestamp"], |values| {
+                let micros_per_day = MICROS_PER_DAY as f64;
+                Some((values[0] / micros_per_day).floor() * micros_per_day)
+            }),
+            RuntimeField::new(
+                "rounded_price",
+                RuntimeFieldType::I64,
+                &["price"],
+                |values| (values[0] > 2.0).then(|| values[0].round()),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_runtime_field_sort_and_filter() -> crate::Result<()> {
+        let searcher = searcher_with_runtime_fields()?;
+        let top_docs = searcher.search(
+            &AllQuery,
+            &TopDocs::with_limit(3).order_by_fast_field::<f64>("total", Order::Desc),
+        )?;
+        let totals: Vec<f64> = top_docs.iter().map(|(total, _)| *total).collect();
+        assert_eq!(totals, [10.0, 10.0, 3.0]);
+
+        let count = |query: RuntimeFieldRangeQuery| searcher.search(&query, &Count);
+        assert_eq!(
+            count(RuntimeFieldRangeQuery::new(
+                "total",
+                Bound::Included(3.0),
+                Bound::Excluded(10.0)
+            ))?,
+            1
+        );
+        assert_eq!(
+            count(RuntimeFieldRangeQuery::new(
+                "total",
+                Bound::<f64>::Unbounded,
+                Bound::Unbounded
+            ))?,
+            3
+        );
+        assert_eq!(
+            count(RuntimeFieldRangeQuery::new(
+                "rounded_price",
+                Bound::Included(3i64),
+                Bound::Unbounded
+            ))?,
+            3
+        );
+        assert_eq!(
+            count(RuntimeFieldRangeQuery::new(
+                "day",
+                Bound::Included(DateTime::from_timestamp_micros(MICROS_PER_DAY)),
+                Bound::Unbounded
+            ))?,
+            2
+        );
+
+        // The type of the runtime field is checked.
+        assert!(searcher
+            .search(
+                &AllQuery,
+                &TopDocs::with_limit(3).order_by_fast_field::<u64>("total", Order::Desc),
+            )
+            .is_err());
+        assert!(count(RuntimeFieldRangeQuery::new(
+            "total",
+            Bound::Included(3u64),
+            Bound::Unbounded
+        ))
+        .is_err());
+        assert!(count(RuntimeFieldRangeQuery::new(
+            "price",
+            Bound::Included(3.0),
+            Bound::Unbounded
+        ))
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_field_aggregations() -> crate::Result<()> {
+        let searcher = searcher_with_runtime_fields()?;
+        let aggregations: Aggregations = serde_json::from_value(serde_json::json!({
+            "total_stats": { "stats": { "field": "total" } },
+            "per_day": { "date_histogram": { "field": "day", "fixed_interval": "1d" } },
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(aggregations, Default::default());
+        let aggregation_results = searcher.search(&AllQuery, &collector)?;
+        let results = serde_json::to_value(aggregation_results).unwrap();
+        assert_eq!(results["total_stats"]["count"], 3);
+        assert_eq!(results["total_stats"]["sum"], 23.0);
+        let doc_counts: Vec<u64> = results["per_day"]["buckets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| bucket["doc_count"].as_u64().unwrap())
+            .collect();
+        assert_eq!(doc_counts, [2, 1, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_field_invalid_names() -> crate::Result<()> {
+        let searcher = searcher_with_runtime_fields()?;
+        let runtime_field = |name: &str| {
+            RuntimeField::new(name, RuntimeFieldType::F64, &["price"], |values| {
+                Some(values[0])
+            })
+        };
+        assert!(searcher
+            .with_runtime_fields(vec![runtime_field("price")])
+            .is_err());
+        assert!(searcher
+            .with_runtime_fields(vec![runtime_field("copy"), runtime_field("copy")])
+            .is_err());
+        // The runtime fields replace the previous ones.
+        let searcher = searcher.with_runtime_fields(vec![runtime_field("copy")])?;
+        let fast_fields = searcher.segment_reader(0).fast_fields();
+        assert_eq!(
+            fast_fields.runtime_field_type("copy"),
+            Some(RuntimeFieldType::F64)
+        );
+        assert_eq!(fast_fields.runtime_field_type("total"), None);
+        Ok(())
+    }
+}
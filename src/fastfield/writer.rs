@@ -4,8 +4,12 @@ use columnar::{ColumnarWriter, NumericalValue};
 use common::{DateTimePrecision, JsonPathWriter};
 use tokenizer_api::Token;
 
+use crate::fastfield::fixed_width_bytes_to_u128;
 use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema, Type};
+use crate::schema::{
+    field_type_fast_codec, field_type_to_column_type, Field, FieldType, FloatPrecision, Schema,
+    Type,
+};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::{DocId, TantivyError};
 
@@ -19,6 +23,8 @@ pub struct FastFieldsWriter {
     fast_field_names: Vec<Option<String>>, //< TODO see if we can hash the field name hash too.
     per_field_tokenizer: Vec<Option<TextAnalyzer>>,
     date_precisions: Vec<DateTimePrecision>,
+    float_precisions: Vec<FloatPrecision>,
+    fixed_widths: Vec<Option<usize>>,
     expand_dots: Vec<bool>,
     num_docs: DocId,
     // Buffer that we recycle to avoid allocation.
@@ -44,6 +50,8 @@ impl FastFieldsWriter {
             std::iter::repeat_with(DateTimePrecision::default)
                 .take(schema.num_fields())
                 .collect();
+        let mut float_precisions = vec![FloatPrecision::default(); schema.num_fields()];
+        let mut fixed_widths = vec![None; schema.num_fields()];
         let mut expand_dots = vec![false; schema.num_fields()];
         let mut per_field_tokenizer: Vec<Option<TextAnalyzer>> = vec![None; schema.num_fields()];
         // TODO see other types
@@ -56,6 +64,13 @@ impl FastFieldsWriter {
             if let FieldType::Date(date_options) = field_entry.field_type() {
                 date_precisions[field_id.field_id() as usize] = date_options.get_precision();
             }
+            if let FieldType::F64(numeric_options) = field_entry.field_type() {
+                float_precisions[field_id.field_id() as usize] =
+                    numeric_options.get_fast_precision();
+            }
+            if let FieldType::Bytes(bytes_options) = field_entry.field_type() {
+                fixed_widths[field_id.field_id() as usize] = bytes_options.fixed_width();
+            }
             if let FieldType::JsonObject(json_object_options) = field_entry.field_type() {
                 if let Some(tokenizer_name) = json_object_options.get_fast_field_tokenizer_name() {
                     let text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
@@ -81,13 +96,17 @@ impl FastFieldsWriter {
             }
 
             let sort_values_within_row = value_type == Type::Facet;
-            if let Some(column_type) = value_type_to_column_type(value_type) {
+            if let Some(column_type) = field_type_to_column_type(field_entry.field_type()) {
                 columnar_writer.record_column_type(
                     field_entry.name(),
                     column_type,
                     sort_values_within_row,
                 );
             }
+            if let Some(fast_codec) = field_type_fast_codec(field_entry.field_type()) {
+                columnar_writer
+                    .record_column_codec_types(field_entry.name(), &[fast_codec.codec_type()]);
+            }
         }
         Ok(FastFieldsWriter {
             columnar_writer,
@@ -95,6 +114,8 @@ impl FastFieldsWriter {
             per_field_tokenizer,
             num_docs: 0u32,
             date_precisions,
+            float_precisions,
+            fixed_widths,
             expand_dots,
             json_path_buffer: JsonPathWriter::default(),
         })
@@ -159,10 +180,11 @@ impl FastFieldsWriter {
                     );
                 }
                 ReferenceValueLeaf::F64(val) => {
+                    let float_precision = self.float_precisions[field.field_id() as usize];
                     self.columnar_writer.record_numerical(
                         doc_id,
                         field_name,
-                        NumericalValue::from(val),
+                        NumericalValue::from(float_precision.round(val)),
                     );
                 }
                 ReferenceValueLeaf::Date(val) => {
@@ -175,7 +197,21 @@ impl FastFieldsWriter {
                     self.columnar_writer.record_str(doc_id, field_name, val);
                 }
                 ReferenceValueLeaf::Bytes(val) => {
-                    self.columnar_writer.record_bytes(doc_id, field_name, val);
+                    if let Some(width) = self.fixed_widths[field.field_id() as usize] {
+                        if val.len() != width {
+                            return Err(TantivyError::InvalidArgument(format!(
+                                "Field {field_name:?} expects values of {width} bytes, got {}",
+                                val.len()
+                            )));
+                        }
+                        self.columnar_writer.record_u128(
+                            doc_id,
+                            field_name,
+                            fixed_width_bytes_to_u128(val),
+                        );
+                    } else {
+                        self.columnar_writer.record_bytes(doc_id, field_name, val);
+                    }
                 }
                 ReferenceValueLeaf::IpAddr(val) => {
                     self.columnar_writer.record_ip_addr(doc_id, field_name, val);
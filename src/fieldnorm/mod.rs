@@ -33,10 +33,13 @@ mod tests {
     use once_cell::sync::Lazy;
 
     use crate::directory::{CompositeFile, Directory, RamDirectory, WritePtr};
-    use crate::fieldnorm::{FieldNormReader, FieldNormsSerializer, FieldNormsWriter};
+    use crate::fieldnorm::{
+        FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter,
+    };
     use crate::query::{EnableScoring, Query, TermQuery};
     use crate::schema::{
-        Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED, TEXT,
+        Field, FieldNormPrecision, IndexRecordOption, Schema, TextFieldIndexing, TextOptions,
+        STORED, TEXT,
     };
     use crate::{Index, Term, TERMINATED};
 
@@ -91,6 +94,13 @@ mod tests {
             assert_eq!(fieldnorm_reader.fieldnorm(2u32), 5u32);
             assert_eq!(fieldnorm_reader.fieldnorm(3u32), 3u32);
         }
+        {
+            let fieldnorm_readers = FieldNormReaders::open(file)?;
+            assert!(fieldnorm_readers.get_field(*FIELD)?.is_none());
+            let fieldnorm_reader = fieldnorm_readers.get_field(*TXT_FIELD)?.unwrap();
+            assert_eq!(fieldnorm_reader.precision(), FieldNormPrecision::Compact);
+            assert_eq!(fieldnorm_reader.fieldnorm(2u32), 5u32);
+        }
         Ok(())
     }
 
@@ -151,4 +161,74 @@ mod tests {
         assert_eq!(scorer.advance(), TERMINATED);
         Ok(())
     }
+
+    #[test]
+    fn test_fieldnorm_linear_precision() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let compact = schema_builder.add_text_field("compact", TEXT);
+        let linear = schema_builder.add_text_field(
+            "linear",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_index_option(IndexRecordOption::WithFreqs)
+                    .set_fieldnorm_precision(FieldNormPrecision::Linear),
+            ),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut writer = index.writer_for_tests()?;
+        for num_tokens in [48, 49, 300] {
+            let text = vec!["hello"; num_tokens].join(" ");
+            writer.add_document(doc!(compact=>text.clone(), linear=>text))?;
+        }
+        writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let segment_reader = searcher.segment_reader(0);
+
+        let compact_reader = segment_reader.get_fieldnorms_reader(compact)?;
+        assert_eq!(compact_reader.precision(), FieldNormPrecision::Compact);
+        assert_eq!(compact_reader.fieldnorm(0), 48);
+        assert_eq!(compact_reader.fieldnorm(1), 48);
+        let linear_reader = segment_reader.get_fieldnorms_reader(linear)?;
+        assert_eq!(linear_reader.precision(), FieldNormPrecision::Linear);
+        assert_eq!(linear_reader.fieldnorm(0), 48);
+        assert_eq!(linear_reader.fieldnorm(1), 49);
+        assert_eq!(linear_reader.fieldnorm(2), 255);
+
+        let weight = |field: Field| {
+            let query = TermQuery::new(
+                Term::from_field_text(field, "hello"),
+                IndexRecordOption::Basic,
+            );
+            query.weight(EnableScoring::enabled_from_searcher(&searcher))
+        };
+        let scores = |field: Field| -> crate::Result<Vec<f32>> {
+            let weight = weight(field)?;
+            let mut scorer = weight.scorer(segment_reader, 1.0)?;
+            let mut scores = Vec::new();
+            while scorer.doc() != TERMINATED {
+                scores.push(scorer.score());
+                scorer.advance();
+            }
+            Ok(scores)
+        };
+        // The documents only differ by one token: the compact fieldnorms round them to the
+        // same length.
+        let compact_scores = scores(compact)?;
+        assert_eq!(compact_scores[0], compact_scores[1]);
+        let linear_scores = scores(linear)?;
+        assert!(linear_scores[0] > linear_scores[1]);
+        assert!(linear_scores[1] > linear_scores[2]);
+
+        let explanation = weight(linear)?.explain(segment_reader, 1)?;
+        assert_eq!(explanation.value(), linear_scores[1]);
+        let dl = explanation.details()[2]
+            .details()
+            .iter()
+            .find(|detail| detail.description() == "dl, length of field")
+            .unwrap();
+        assert_eq!(dl.value(), 49.0);
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use super::{fieldnorm_to_id, id_to_fieldnorm};
 use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
-use crate::schema::Field;
+use crate::schema::{Field, FieldNormPrecision, Schema};
 use crate::space_usage::PerFieldSpaceUsage;
 use crate::DocId;
 
@@ -15,21 +15,46 @@ use crate::DocId;
 #[derive(Clone)]
 pub struct FieldNormReaders {
     data: Arc<CompositeFile>,
+    schema: Option<Schema>,
 }
 
 impl FieldNormReaders {
     /// Creates a field norm reader.
+    ///
+    /// All fieldnorms are read with the [`FieldNormPrecision::Compact`] precision.
     pub fn open(file: FileSlice) -> crate::Result<FieldNormReaders> {
         let data = CompositeFile::open(&file)?;
         Ok(FieldNormReaders {
             data: Arc::new(data),
+            schema: None,
+        })
+    }
+
+    /// Creates a field norm reader.
+    ///
+    /// The schema gives the [`FieldNormPrecision`] of each field.
+    pub fn open_with_schema(file: FileSlice, schema: Schema) -> crate::Result<FieldNormReaders> {
+        let data = CompositeFile::open(&file)?;
+        Ok(FieldNormReaders {
+            data: Arc::new(data),
+            schema: Some(schema),
         })
     }
 
     /// Returns the FieldNormReader for a specific field.
     pub fn get_field(&self, field: Field) -> crate::Result<Option<FieldNormReader>> {
         if let Some(file) = self.data.open_read(field) {
-            let fieldnorm_reader = FieldNormReader::open(file)?;
+            let precision = self
+                .schema
+                .as_ref()
+                .map(|schema| {
+                    schema
+                        .get_field_entry(field)
+                        .field_type()
+                        .fieldnorm_precision()
+                })
+                .unwrap_or(FieldNormPrecision::Compact);
+            let fieldnorm_reader = FieldNormReader::open_with_precision(file, precision)?;
             Ok(Some(fieldnorm_reader))
         } else {
             Ok(None)
@@ -62,7 +87,10 @@ impl From<ReaderImplEnum> for FieldNormReader {
 
 #[derive(Clone)]
 enum ReaderImplEnum {
-    FromData(OwnedBytes),
+    FromData {
+        data: OwnedBytes,
+        precision: FieldNormPrecision,
+    },
     Const {
         num_docs: u32,
         fieldnorm_id: u8,
@@ -88,18 +116,35 @@ impl FieldNormReader {
 
     /// Opens a field norm reader given its file.
     pub fn open(fieldnorm_file: FileSlice) -> crate::Result<Self> {
+        FieldNormReader::open_with_precision(fieldnorm_file, FieldNormPrecision::Compact)
+    }
+
+    /// Opens a field norm reader given its file, and the precision its fieldnorms were
+    /// encoded with.
+    pub fn open_with_precision(
+        fieldnorm_file: FileSlice,
+        precision: FieldNormPrecision,
+    ) -> crate::Result<Self> {
         let data = fieldnorm_file.read_bytes()?;
-        Ok(FieldNormReader::new(data))
+        Ok(FieldNormReader::new(data, precision))
     }
 
-    fn new(data: OwnedBytes) -> Self {
-        ReaderImplEnum::FromData(data).into()
+    fn new(data: OwnedBytes, precision: FieldNormPrecision) -> Self {
+        ReaderImplEnum::FromData { data, precision }.into()
+    }
+
+    /// Returns the precision the fieldnorms are encoded with.
+    pub fn precision(&self) -> FieldNormPrecision {
+        match &self.0 {
+            ReaderImplEnum::FromData { precision, .. } => *precision,
+            ReaderImplEnum::Const { .. } => FieldNormPrecision::Compact,
+        }
     }
 
     /// Returns the number of documents in this segment.
     pub fn num_docs(&self) -> u32 {
         match &self.0 {
-            ReaderImplEnum::FromData(data) => data.len() as u32,
+            ReaderImplEnum::FromData { data, .. } => data.len() as u32,
             ReaderImplEnum::Const { num_docs, .. } => *num_docs,
         }
     }
@@ -115,9 +160,9 @@ impl FieldNormReader {
     /// `fieldnorm_id` by doing a simple table lookup.
     pub fn fieldnorm(&self, doc_id: DocId) -> u32 {
         match &self.0 {
-            ReaderImplEnum::FromData(data) => {
+            ReaderImplEnum::FromData { data, precision } => {
                 let fieldnorm_id = data.as_slice()[doc_id as usize];
-                id_to_fieldnorm(fieldnorm_id)
+                precision.id_to_fieldnorm(fieldnorm_id)
             }
             ReaderImplEnum::Const { fieldnorm, .. } => *fieldnorm,
         }
@@ -127,7 +172,7 @@ impl FieldNormReader {
     #[inline]
     pub fn fieldnorm_id(&self, doc_id: DocId) -> u8 {
         match &self.0 {
-            ReaderImplEnum::FromData(data) => {
+            ReaderImplEnum::FromData { data, .. } => {
                 let fieldnorm_id = data.as_slice()[doc_id as usize];
                 fieldnorm_id
             }
@@ -156,7 +201,7 @@ impl FieldNormReader {
             .map(FieldNormReader::fieldnorm_to_id)
             .collect::<Vec<u8>>();
         let field_norms_data = OwnedBytes::new(field_norms_id);
-        FieldNormReader::new(field_norms_data)
+        FieldNormReader::new(field_norms_data, FieldNormPrecision::Compact)
     }
 }
 
@@ -1,8 +1,8 @@
 use std::cmp::Ordering;
 use std::{io, iter};
 
-use super::{fieldnorm_to_id, FieldNormsSerializer};
-use crate::schema::{Field, Schema};
+use super::FieldNormsSerializer;
+use crate::schema::{Field, FieldNormPrecision, Schema};
 use crate::DocId;
 
 /// The `FieldNormsWriter` is in charge of tracking the fieldnorm byte
@@ -12,6 +12,7 @@ use crate::DocId;
 /// byte per document per field.
 pub struct FieldNormsWriter {
     fieldnorms_buffers: Vec<Option<Vec<u8>>>,
+    precisions: Vec<FieldNormPrecision>,
 }
 
 impl FieldNormsWriter {
@@ -39,7 +40,14 @@ impl FieldNormsWriter {
         for field in FieldNormsWriter::fields_with_fieldnorm(schema) {
             fieldnorms_buffers[field.field_id() as usize] = Some(Vec::with_capacity(1_000));
         }
-        FieldNormsWriter { fieldnorms_buffers }
+        let precisions = schema
+            .fields()
+            .map(|(_, field_entry)| field_entry.field_type().fieldnorm_precision())
+            .collect();
+        FieldNormsWriter {
+            fieldnorms_buffers,
+            precisions,
+        }
     }
 
     /// The memory used inclusive childs
@@ -64,8 +72,8 @@ impl FieldNormsWriter {
 
     /// Set the fieldnorm byte for the given document for the given field.
     ///
-    /// Will internally convert the u32 `fieldnorm` value to the appropriate byte
-    /// to approximate the field norm in less space.
+    /// Will internally convert the u32 `fieldnorm` value to the appropriate byte, given the
+    /// [`FieldNormPrecision`] of the field, to approximate the field norm in less space.
     ///
     /// * doc       - the document id
     /// * field     - the field being set
@@ -86,7 +94,8 @@ impl FieldNormsWriter {
                     panic!("Cannot register a given fieldnorm twice")
                 }
             }
-            fieldnorm_buffer.push(fieldnorm_to_id(fieldnorm));
+            let precision = self.precisions[field.field_id() as usize];
+            fieldnorm_buffer.push(precision.fieldnorm_to_id(fieldnorm));
         }
     }
 
@@ -1,14 +1,15 @@
 use std::collections::HashSet;
 use std::fmt;
-#[cfg(feature = "mmap")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread::available_parallelism;
 
+use columnar::{BlockCache, BlockCacheStats};
+
 use super::segment::Segment;
 use super::segment_reader::merge_field_meta_data;
 use super::{FieldMetadata, IndexSettings};
-use crate::core::{Executor, META_FILEPATH};
+use crate::core::{Executor, META_FILEPATH, PREPARED_META_FILEPATH};
 use crate::directory::error::OpenReadError;
 #[cfg(feature = "mmap")]
 use crate::directory::MmapDirectory;
@@ -19,10 +20,10 @@ use crate::indexer::index_writer::{
     IndexWriterOptions, MAX_NUM_THREAD, MEMORY_BUDGET_NUM_BYTES_MIN,
 };
 use crate::indexer::segment_updater::save_metas;
-use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
+use crate::indexer::{BulkIndexBuilder, IndexWriter, SingleSegmentIndexWriter};
 use crate::reader::{IndexReader, IndexReaderBuilder};
 use crate::schema::document::Document;
-use crate::schema::{Field, FieldType, Schema};
+use crate::schema::{Field, FieldType, Schema, Type};
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
 use crate::SegmentReader;
 
@@ -30,18 +31,34 @@ fn load_metas(
     directory: &dyn Directory,
     inventory: &SegmentMetaInventory,
 ) -> crate::Result<IndexMeta> {
-    let meta_data = directory.atomic_read(&META_FILEPATH)?;
+    load_metas_from_path(&META_FILEPATH, directory, inventory)
+}
+
+fn load_metas_from_path(
+    path: &Path,
+    directory: &dyn Directory,
+    inventory: &SegmentMetaInventory,
+) -> crate::Result<IndexMeta> {
+    let meta_data = directory.atomic_read(path)?;
+    parse_metas(path, meta_data, inventory)
+}
+
+fn parse_metas(
+    path: &Path,
+    meta_data: Vec<u8>,
+    inventory: &SegmentMetaInventory,
+) -> crate::Result<IndexMeta> {
     let meta_string = String::from_utf8(meta_data).map_err(|_utf8_err| {
         error!("Meta data is not valid utf8.");
         DataCorruption::new(
-            META_FILEPATH.to_path_buf(),
+            path.to_path_buf(),
             "Meta file does not contain valid utf8 file.".to_string(),
         )
     })?;
     IndexMeta::deserialize(&meta_string, inventory)
         .map_err(|e| {
             DataCorruption::new(
-                META_FILEPATH.to_path_buf(),
+                path.to_path_buf(),
                 format!("Meta file cannot be deserialized. {e:?}. Content: {meta_string:?}"),
             )
         })
@@ -232,7 +249,68 @@ impl IndexBuilder {
     }
 
     fn validate(&self) -> crate::Result<()> {
-        if let Some(_schema) = self.schema.as_ref() {
+        if let Some(schema) = self.schema.as_ref() {
+            if !self.index_settings.then_sort_by_fields.is_empty()
+                && self.index_settings.sort_by_field.is_none()
+            {
+                return Err(TantivyError::InvalidArgument(
+                    "Additional fields to sort the index by require a sort field".to_string(),
+                ));
+            }
+            for sort_by_field in self.index_settings.sort_by_fields() {
+                let schema_field = schema.get_field(&sort_by_field.field).map_err(|_| {
+                    TantivyError::InvalidArgument(format!(
+                        "Field to sort index {} not found in schema",
+                        sort_by_field.field
+                    ))
+                })?;
+                let entry = schema.get_field_entry(schema_field);
+                if !entry.is_fast() {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Field {} is not a fast field. The field needs to be a fast field to be \
+                         used to sort an index",
+                        sort_by_field.field
+                    )));
+                }
+                if !matches!(
+                    entry.field_type().value_type(),
+                    Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date
+                ) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Field {} of type {:?} cannot be used to sort an index",
+                        sort_by_field.field,
+                        entry.field_type().value_type()
+                    )));
+                }
+            }
+            if let Some(expiration_field) = self.index_settings.expiration_field.as_ref() {
+                let schema_field = schema.get_field(expiration_field).map_err(|_| {
+                    TantivyError::InvalidArgument(format!(
+                        "Expiration field {expiration_field} not found in schema"
+                    ))
+                })?;
+                let entry = schema.get_field_entry(schema_field);
+                if !entry.is_fast() || entry.field_type().value_type() != Type::Date {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Field {expiration_field} is not a date fast field. The field needs to be \
+                         a date fast field to be used as expiration field"
+                    )));
+                }
+            }
+            if let Some(doc_boost_field) = self.index_settings.doc_boost_field.as_ref() {
+                let schema_field = schema.get_field(doc_boost_field).map_err(|_| {
+                    TantivyError::InvalidArgument(format!(
+                        "Doc boost field {doc_boost_field} not found in schema"
+                    ))
+                })?;
+                let entry = schema.get_field_entry(schema_field);
+                if !entry.is_fast() || entry.field_type().value_type() != Type::F64 {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Field {doc_boost_field} is not a f64 fast field. The field needs to be a \
+                         f64 fast field to be used as doc boost field"
+                    )));
+                }
+            }
             Ok(())
         } else {
             Err(TantivyError::InvalidArgument(
@@ -272,6 +350,7 @@ pub struct Index {
     tokenizers: TokenizerManager,
     fast_field_tokenizers: TokenizerManager,
     inventory: SegmentMetaInventory,
+    fast_field_block_cache: Option<Arc<BlockCache>>,
 }
 
 impl Index {
@@ -315,6 +394,32 @@ impl Index {
         self.set_multithread_executor(default_num_threads)
     }
 
+    /// Sets up an LRU cache of `num_bytes` bytes for the reads of the fast fields.
+    ///
+    /// Fast field columns and their dictionaries are then read by blocks, and the blocks kept in
+    /// the cache. This is useful when the directory is not backed by `mmap` and its reads are
+    /// expensive. The cache is shared by all the segments of the index.
+    ///
+    /// Passing `0` disables the cache. The cache is only used by the segment readers opened
+    /// after the call.
+    pub fn set_fast_field_block_cache_num_bytes(&mut self, num_bytes: usize) {
+        self.fast_field_block_cache =
+            (num_bytes > 0).then(|| Arc::new(BlockCache::with_capacity(num_bytes)));
+    }
+
+    /// Returns the hit and miss statistics of the fast field block cache, if it is enabled.
+    ///
+    /// See [`Index::set_fast_field_block_cache_num_bytes`].
+    pub fn fast_field_block_cache_stats(&self) -> Option<BlockCacheStats> {
+        self.fast_field_block_cache
+            .as_ref()
+            .map(|block_cache| block_cache.stats())
+    }
+
+    pub(crate) fn fast_field_block_cache(&self) -> Option<&Arc<BlockCache>> {
+        self.fast_field_block_cache.as_ref()
+    }
+
     /// Creates a new index using the [`RamDirectory`].
     ///
     /// The index will be allocated in anonymous memory.
@@ -383,19 +488,25 @@ impl Index {
         inventory: SegmentMetaInventory,
     ) -> Index {
         let schema = metas.schema.clone();
+        let tokenizers = TokenizerManager::default();
+        tokenizers.register_schema_definitions(&schema);
         Index {
             settings: metas.index_settings.clone(),
             directory,
             schema,
-            tokenizers: TokenizerManager::default(),
+            tokenizers,
             fast_field_tokenizers: TokenizerManager::default(),
             executor: Executor::single_thread(),
             inventory,
+            fast_field_block_cache: None,
         }
     }
 
     /// Setter for the tokenizer manager.
+    ///
+    /// The analyzer definitions of the schema get registered in the new tokenizer manager.
     pub fn set_tokenizers(&mut self, tokenizers: TokenizerManager) {
+        tokenizers.register_schema_definitions(&self.schema);
         self.tokenizers = tokenizers;
     }
 
@@ -521,6 +632,26 @@ impl Index {
         load_metas(self.directory(), &self.inventory)
     }
 
+    /// Reads the index meta file from the directory, along with the checksum of its content.
+    pub(crate) fn load_metas_with_checksum(&self) -> crate::Result<(IndexMeta, u32)> {
+        let meta_data = self.directory.atomic_read(&META_FILEPATH)?;
+        let meta_checksum = crc32fast::hash(&meta_data);
+        let metas = parse_metas(&META_FILEPATH, meta_data, &self.inventory)?;
+        Ok((metas, meta_checksum))
+    }
+
+    /// Reads the meta file of the pending prepared commit from the directory, if any.
+    ///
+    /// A prepared commit is durable but not visible, until it is published with
+    /// [`IndexWriter::commit_prepared`] or discarded with [`IndexWriter::rollback_prepared`].
+    /// See [`PreparedCommit::persist`](crate::indexer::PreparedCommit::persist).
+    pub fn load_prepared_metas(&self) -> crate::Result<Option<IndexMeta>> {
+        if !self.directory().exists(&PREPARED_META_FILEPATH)? {
+            return Ok(None);
+        }
+        load_metas_from_path(&PREPARED_META_FILEPATH, self.directory(), &self.inventory).map(Some)
+    }
+
     /// Open a new index writer with the given options. Attempts to acquire a lockfile.
     ///
     /// The lockfile should be deleted on drop, but it is possible
@@ -622,6 +753,24 @@ impl Index {
         self.writer_with_num_threads(num_threads, memory_budget_in_bytes)
     }
 
+    /// Creates a [`BulkIndexBuilder`], writing a stream of documents straight into the final
+    /// segments of the index, with `num_threads` indexing threads.
+    ///
+    /// The index needs to be empty. Like an `IndexWriter`, the bulk index builder holds the
+    /// index lock until it is finalized.
+    ///
+    /// # Errors
+    /// If the lockfile already exists, returns `TantivyError::LockFailure`.
+    /// If the index is not empty, or if the memory budget per thread is too small or too big,
+    /// returns `TantivyError::InvalidArgument`.
+    pub fn bulk_index_builder<D: Document>(
+        &self,
+        num_threads: usize,
+        memory_budget_per_thread: usize,
+    ) -> crate::Result<BulkIndexBuilder<D>> {
+        BulkIndexBuilder::new(self, num_threads, memory_budget_per_thread)
+    }
+
     /// Accessor to the index settings
     pub fn settings(&self) -> &IndexSettings {
         &self.settings
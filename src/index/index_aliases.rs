@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use once_cell::sync::Lazy;
+
+use crate::directory::error::OpenReadError;
+use crate::directory::{Directory, DirectoryLock, Lock, MmapDirectory};
+use crate::error::{DataCorruption, TantivyError};
+use crate::Index;
+
+/// The aliases file maps each alias to the name of the index it points to.
+static ALIASES_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("aliases.json"));
+
+/// The aliases lock prevents concurrent updates of the aliases file from
+/// overwriting each other.
+static ALIASES_LOCK: Lazy<Lock> = Lazy::new(|| Lock {
+    filepath: PathBuf::from(".tantivy-aliases.lock"),
+    is_blocking: true,
+});
+
+/// Aliases of the indexes stored in the subdirectories of a root directory.
+///
+/// An alias gives a stable name to an index, so that readers can open
+/// `products_current` while a new version of the index is built in `products_v2`.
+/// Once the new version is ready, the alias is switched with [`IndexAliases::set_alias`].
+///
+/// The aliases are kept in an `aliases.json` file in the root directory, which is
+/// replaced atomically on every change: an alias always points either to its former
+/// index or to its new one.
+///
+/// Switching an alias does not affect the indexes that were already opened.
+pub struct IndexAliases {
+    root_path: PathBuf,
+    directory: MmapDirectory,
+}
+
+impl IndexAliases {
+    /// Opens the aliases of the indexes stored in the subdirectories of `root_path`.
+    pub fn open_in_dir<P: AsRef<Path>>(root_path: P) -> crate::Result<IndexAliases> {
+        let root_path = root_path.as_ref().to_path_buf();
+        let directory = MmapDirectory::open(&root_path)?;
+        Ok(IndexAliases {
+            root_path,
+            directory,
+        })
+    }
+
+    /// Returns all of the aliases, with the name of the index they point to.
+    pub fn aliases(&self) -> crate::Result<BTreeMap<String, String>> {
+        let aliases_data = match self.directory.atomic_read(&ALIASES_FILEPATH) {
+            Ok(aliases_data) => aliases_data,
+            Err(OpenReadError::FileDoesNotExist(_)) => return Ok(BTreeMap::new()),
+            Err(err) => return Err(err.into()),
+        };
+        serde_json::from_slice(&aliases_data).map_err(|err| {
+            DataCorruption::new(
+                ALIASES_FILEPATH.to_path_buf(),
+                format!("Aliases file cannot be deserialized. {err:?}"),
+            )
+            .into()
+        })
+    }
+
+    /// Returns the name of the index an alias points to.
+    pub fn resolve(&self, alias: &str) -> crate::Result<String> {
+        self.aliases()?
+            .remove(alias)
+            .ok_or_else(|| TantivyError::InvalidArgument(format!("Unknown index alias {alias:?}")))
+    }
+
+    /// Returns the path of the directory of the index an alias points to.
+    pub fn index_path(&self, alias: &str) -> crate::Result<PathBuf> {
+        Ok(self.root_path.join(self.resolve(alias)?))
+    }
+
+    /// Opens the index an alias points to.
+    pub fn open_index(&self, alias: &str) -> crate::Result<Index> {
+        Index::open_in_dir(self.index_path(alias)?)
+    }
+
+    /// Points an alias to the index stored in the `index_name` subdirectory of the root
+    /// directory, creating the alias if needed.
+    ///
+    /// The switch is atomic: the indexes opened through the alias from then on are
+    /// the new one.
+    ///
+    /// Returns the name of the index the alias pointed to before, if any.
+    pub fn set_alias(&self, alias: &str, index_name: &str) -> crate::Result<Option<String>> {
+        if alias.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "An index alias cannot be empty".to_string(),
+            ));
+        }
+        validate_index_name(index_name)?;
+        let _aliases_lock = self.lock()?;
+        let mut aliases = self.aliases()?;
+        let previous_index_name = aliases.insert(alias.to_string(), index_name.to_string());
+        self.save_aliases(&aliases)?;
+        Ok(previous_index_name)
+    }
+
+    /// Removes an alias.
+    ///
+    /// Returns the name of the index the alias pointed to, if any.
+    pub fn remove_alias(&self, alias: &str) -> crate::Result<Option<String>> {
+        let _aliases_lock = self.lock()?;
+        let mut aliases = self.aliases()?;
+        let previous_index_name = aliases.remove(alias);
+        if previous_index_name.is_some() {
+            self.save_aliases(&aliases)?;
+        }
+        Ok(previous_index_name)
+    }
+
+    fn lock(&self) -> crate::Result<DirectoryLock> {
+        self.directory.acquire_lock(&ALIASES_LOCK).map_err(|err| {
+            TantivyError::LockFailure(
+                err,
+                Some("Failed to acquire the lock of the index aliases".to_string()),
+            )
+        })
+    }
+
+    fn save_aliases(&self, aliases: &BTreeMap<String, String>) -> crate::Result<()> {
+        let aliases_data = serde_json::to_vec_pretty(aliases)?;
+        self.directory
+            .atomic_write(&ALIASES_FILEPATH, &aliases_data[..])?;
+        Ok(())
+    }
+}
+
+/// Index names are plain directory names, so that aliases cannot point outside
+/// of the root directory.
+fn validate_index_name(index_name: &str) -> crate::Result<()> {
+    let mut components = Path::new(index_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(()),
+        _ => Err(TantivyError::InvalidArgument(format!(
+            "Invalid index name {index_name:?}, expected the name of a subdirectory"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexAliases;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter};
+
+    fn create_index(root_path: &std::path::Path, index_name: &str, num_docs: usize) {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index_path = root_path.join(index_name);
+        std::fs::create_dir(&index_path).unwrap();
+        let index = Index::create_in_dir(&index_path, schema_builder.build()).unwrap();
+        let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        for i in 0..num_docs {
+            index_writer
+                .add_document(doc!(id_field => format!("doc{i}")))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+    }
+
+    #[test]
+    fn test_index_aliases_swap() -> crate::Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        create_index(root_dir.path(), "products_v1", 1);
+        create_index(root_dir.path(), "products_v2", 2);
+        let aliases = IndexAliases::open_in_dir(root_dir.path())?;
+        assert!(aliases.aliases()?.is_empty());
+        assert!(aliases.open_index("products_current").is_err());
+
+        assert_eq!(aliases.set_alias("products_current", "products_v1")?, None);
+        let index_v1 = aliases.open_index("products_current")?;
+        assert_eq!(index_v1.reader()?.searcher().num_docs(), 1);
+
+        assert_eq!(
+            aliases.set_alias("products_current", "products_v2")?,
+            Some("products_v1".to_string())
+        );
+        // The aliases are persisted.
+        let aliases = IndexAliases::open_in_dir(root_dir.path())?;
+        assert_eq!(aliases.resolve("products_current")?, "products_v2");
+        let index_v2 = aliases.open_index("products_current")?;
+        assert_eq!(index_v2.reader()?.searcher().num_docs(), 2);
+        // The index opened before the switch is unaffected.
+        assert_eq!(index_v1.reader()?.searcher().num_docs(), 1);
+
+        assert_eq!(
+            aliases.remove_alias("products_current")?,
+            Some("products_v2".to_string())
+        );
+        assert_eq!(aliases.remove_alias("products_current")?, None);
+        assert!(aliases.aliases()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_aliases_invalid_index_name() -> crate::Result<()> {
+        let root_dir = tempfile::tempdir()?;
+        let aliases = IndexAliases::open_in_dir(root_dir.path())?;
+        for index_name in ["", "..", "products/v1", "/products_v1"] {
+            assert!(aliases.set_alias("products_current", index_name).is_err());
+        }
+        assert!(aliases.set_alias("", "products_v1").is_err());
+        assert!(aliases.aliases()?.is_empty());
+        Ok(())
+    }
+}
@@ -18,6 +18,11 @@ struct DeleteMeta {
     opstamp: Opstamp,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct UpdateMeta {
+    opstamp: Opstamp,
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct SegmentMetaInventory {
     inventory: Inventory<InnerSegmentMeta>,
@@ -39,6 +44,7 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            updates: None,
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -139,9 +145,14 @@ impl SegmentMeta {
             SegmentComponent::Terms => ".term".to_string(),
             SegmentComponent::Store => ".store".to_string(),
             SegmentComponent::TempStore => ".store.temp".to_string(),
+            SegmentComponent::Blobs => ".blobs".to_string(),
+            SegmentComponent::Suggest => ".suggest".to_string(),
             SegmentComponent::FastFields => ".fast".to_string(),
             SegmentComponent::FieldNorms => ".fieldnorm".to_string(),
             SegmentComponent::Delete => format!(".{}.del", self.delete_opstamp().unwrap_or(0)),
+            SegmentComponent::FastFieldUpdates => {
+                format!(".{}.upd", self.update_opstamp().unwrap_or(0))
+            }
         });
         PathBuf::from(path)
     }
@@ -175,6 +186,15 @@ impl SegmentMeta {
         self.num_deleted_docs() > 0
     }
 
+    /// Returns the `Opstamp` of the last fast field update operation
+    /// taken in account in this segment, if any.
+    pub fn update_opstamp(&self) -> Option<Opstamp> {
+        self.tracked
+            .updates
+            .as_ref()
+            .map(|update_meta| update_meta.opstamp)
+    }
+
     /// Updates the max_doc value from the `SegmentMeta`.
     pub fn with_max_doc(self, max_doc: u32) -> SegmentMeta {
         assert_eq!(self.tracked.max_doc, 0);
@@ -183,6 +203,7 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc,
             deletes: None,
+            updates: None,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
         });
         SegmentMeta { tracked }
@@ -204,6 +225,21 @@ impl SegmentMeta {
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            updates: inner_meta.updates.clone(),
+        });
+        SegmentMeta { tracked }
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_update_meta(self, opstamp: Opstamp) -> SegmentMeta {
+        let update_meta = UpdateMeta { opstamp };
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            deletes: inner_meta.deletes.clone(),
+            updates: Some(update_meta),
         });
         SegmentMeta { tracked }
     }
@@ -214,6 +250,8 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updates: Option<UpdateMeta>,
     /// If you want to avoid the SegmentComponent::TempStore file to be covered by
     /// garbage collection and deleted, set this to true. This is used during merge.
     #[serde(skip)]
@@ -240,12 +278,26 @@ fn is_true(val: &bool) -> bool {
     *val
 }
 
+fn is_multiply(mode: &DocBoostMode) -> bool {
+    *mode == DocBoostMode::Multiply
+}
+
 /// Search Index Settings.
 ///
 /// Contains settings which are applied on the whole
 /// index, like presort documents.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct IndexSettings {
+    /// Sorts the documents by information
+    /// provided in `IndexSortByField`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort_by_field: Option<IndexSortByField>,
+    /// Additional fields to sort the documents by, in order, when they have the same value
+    /// for the previous sort fields.
+    ///
+    /// It requires `sort_by_field` to be set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub then_sort_by_fields: Vec<IndexSortByField>,
     /// The `Compressor` used to compress the doc store.
     #[serde(default)]
     pub docstore_compression: Compressor,
@@ -258,6 +310,42 @@ pub struct IndexSettings {
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
     pub docstore_blocksize: usize,
+    /// The `Compressor` used to compress the doc store of the segments produced by merges.
+    /// Defaults to `docstore_compression`.
+    ///
+    /// Write-heavy indexes can use a fast compressor for the freshly indexed segments, and a
+    /// stronger one for the merged segments, which are read more often.
+    /// When it differs from `docstore_compression`, merges recompress the doc store blocks
+    /// instead of stacking them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docstore_merge_compression: Option<Compressor>,
+    /// The size of the doc store blocks of the segments produced by merges.
+    /// Defaults to `docstore_blocksize`.
+    ///
+    /// When it differs from `docstore_blocksize`, merges recompress the doc store blocks
+    /// instead of stacking them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docstore_merge_blocksize: Option<usize>,
+    /// Name of a date fast field holding the expiration date of the documents.
+    ///
+    /// Documents whose expiration date is in the past are excluded from search results,
+    /// and deleted on the next commit or merge. Documents without any value for the field
+    /// never expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_field: Option<String>,
+    /// Name of a `f64` fast field holding a static boost of the documents, like their
+    /// popularity.
+    ///
+    /// The boost of the document is combined with the scores as configured by
+    /// `doc_boost_mode`. The boost is applied within the scorers, so that the top-k searches
+    /// skipping blocks of documents take it into account. Negative values are considered to
+    /// be 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_boost_field: Option<String>,
+    /// How the boost of the `doc_boost_field` is combined with the scores.
+    /// (defaults: [`DocBoostMode::Multiply`])
+    #[serde(default, skip_serializing_if = "is_multiply")]
+    pub doc_boost_mode: DocBoostMode,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -268,13 +356,84 @@ fn default_docstore_blocksize() -> usize {
 impl Default for IndexSettings {
     fn default() -> Self {
         Self {
+            sort_by_field: None,
+            then_sort_by_fields: Vec::new(),
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            docstore_merge_compression: None,
+            docstore_merge_blocksize: None,
+            expiration_field: None,
+            doc_boost_field: None,
+            doc_boost_mode: DocBoostMode::Multiply,
         }
     }
 }
 
+impl IndexSettings {
+    /// Returns the compressor of the doc store of the segments produced by merges.
+    pub(crate) fn merge_docstore_compression(&self) -> Compressor {
+        self.docstore_merge_compression
+            .unwrap_or(self.docstore_compression)
+    }
+
+    /// Returns the block size of the doc store of the segments produced by merges.
+    pub(crate) fn merge_docstore_blocksize(&self) -> usize {
+        self.docstore_merge_blocksize
+            .unwrap_or(self.docstore_blocksize)
+    }
+
+    /// Returns the fields the documents are sorted by, starting with `sort_by_field`.
+    ///
+    /// Returns an empty iterator if the index is not sorted.
+    pub fn sort_by_fields(&self) -> impl Iterator<Item = &IndexSortByField> {
+        let then_sort_by_fields = if self.sort_by_field.is_some() {
+            &self.then_sort_by_fields[..]
+        } else {
+            &[]
+        };
+        self.sort_by_field.iter().chain(then_sort_by_fields)
+    }
+}
+
+/// Settings to presort the documents in an index
+///
+/// Presorting documents can greatly improve performance
+/// in some scenarios, by applying top n
+/// optimizations.
+///
+/// The documents of every segment are sorted by the first value of the fast field. The
+/// documents without a value are placed after all of the other documents, regardless of the
+/// order.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct IndexSortByField {
+    /// The field to sort the documents by.
+    ///
+    /// It must be a u64, i64, f64, bool or date fast field.
+    pub field: String,
+    /// The order to sort the documents by
+    pub order: Order,
+}
+
+/// How the static boost of the documents is combined with their scores.
+///
+/// See [`IndexSettings::doc_boost_field`].
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocBoostMode {
+    /// The scores of the term and phrase queries are multiplied by the boost, and so is the
+    /// score of a boolean query summing them.
+    ///
+    /// Documents without any value for the boost field have a boost of 1.
+    #[default]
+    Multiply,
+    /// The boost is added once to the score of the query, whatever the number of its clauses
+    /// matching the document.
+    ///
+    /// Documents without any value for the boost field have a boost of 0.
+    Add,
+}
+
 /// The order to sort by
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Order {
@@ -387,9 +546,9 @@ impl fmt::Debug for IndexMeta {
 #[cfg(test)]
 mod tests {
 
-    use super::IndexMeta;
+    use super::{DocBoostMode, IndexMeta, IndexSortByField, Order};
     use crate::index::index_meta::UntrackedIndexMeta;
-    use crate::schema::{Schema, TEXT};
+    use crate::schema::{Schema, FAST, TEXT};
     use crate::store::Compressor;
     #[cfg(feature = "zstd-compression")]
     use crate::store::ZstdCompressor;
@@ -421,6 +580,94 @@ mod tests {
         assert_eq!(index_metas.opstamp, deser_meta.opstamp);
     }
 
+    #[test]
+    fn test_serialize_doc_boost_mode() {
+        let index_settings = IndexSettings {
+            doc_boost_field: Some("popularity".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&index_settings).unwrap();
+        assert!(json.contains(r#""doc_boost_field":"popularity"}"#));
+
+        let index_settings = IndexSettings {
+            doc_boost_mode: DocBoostMode::Add,
+            ..index_settings
+        };
+        let json = serde_json::to_string(&index_settings).unwrap();
+        assert!(json.contains(r#""doc_boost_field":"popularity","doc_boost_mode":"add"}"#));
+        let deser_settings: IndexSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_settings, index_settings);
+    }
+
+    #[test]
+    fn test_serialize_metas_sort_by_field() {
+        let schema = {
+            let mut schema_builder = Schema::builder();
+            schema_builder.add_u64_field("timestamp", FAST);
+            schema_builder.build()
+        };
+        let index_metas = IndexMeta {
+            index_settings: IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "timestamp".to_string(),
+                    order: Order::Desc,
+                }),
+                ..Default::default()
+            },
+            segments: Vec::new(),
+            schema,
+            opstamp: 0u64,
+            payload: None,
+        };
+        let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
+        assert!(json.starts_with(
+            r#"{"index_settings":{"sort_by_field":{"field":"timestamp","order":"Desc"},"#
+        ));
+        let deser_meta: UntrackedIndexMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(index_metas.index_settings, deser_meta.index_settings);
+    }
+
+    #[test]
+    fn test_serialize_metas_then_sort_by_fields() {
+        let schema = {
+            let mut schema_builder = Schema::builder();
+            schema_builder.add_u64_field("timestamp", FAST);
+            schema_builder.add_u64_field("popularity", FAST);
+            schema_builder.build()
+        };
+        let index_metas = IndexMeta {
+            index_settings: IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "timestamp".to_string(),
+                    order: Order::Desc,
+                }),
+                then_sort_by_fields: vec![IndexSortByField {
+                    field: "popularity".to_string(),
+                    order: Order::Asc,
+                }],
+                ..Default::default()
+            },
+            segments: Vec::new(),
+            schema,
+            opstamp: 0u64,
+            payload: None,
+        };
+        let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
+        assert!(json.starts_with(
+            r#"{"index_settings":{"sort_by_field":{"field":"timestamp","order":"Desc"},"then_sort_by_fields":[{"field":"popularity","order":"Asc"}],"#
+        ));
+        let deser_meta: UntrackedIndexMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(index_metas.index_settings, deser_meta.index_settings);
+        assert_eq!(
+            deser_meta
+                .index_settings
+                .sort_by_fields()
+                .map(|sort_by_field| sort_by_field.field.as_str())
+                .collect::<Vec<_>>(),
+            ["timestamp", "popularity"]
+        );
+    }
+
     #[test]
     #[cfg(feature = "zstd-compression")]
     fn test_serialize_metas_zstd_compressor() {
@@ -431,11 +678,13 @@ mod tests {
         };
         let index_metas = IndexMeta {
             index_settings: IndexSettings {
+                sort_by_field: None,
                 docstore_compression: crate::store::Compressor::Zstd(ZstdCompressor {
                     compression_level: Some(4),
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                ..Default::default()
             },
             segments: Vec::new(),
             schema,
@@ -497,9 +746,16 @@ mod tests {
         assert_eq!(
             index_settings,
             IndexSettings {
+                sort_by_field: None,
+                then_sort_by_fields: Vec::new(),
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                docstore_merge_compression: None,
+                docstore_merge_blocksize: None,
+                expiration_field: None,
+                doc_boost_field: None,
+                doc_boost_mode: DocBoostMode::Multiply,
             }
         );
         {
@@ -531,4 +787,35 @@ mod tests {
             assert_eq!(index_settings_deser, index_settings);
         }
     }
+
+    #[test]
+    #[cfg(feature = "lz4-compression")]
+    fn test_index_settings_merge_docstore() {
+        let mut index_settings = IndexSettings {
+            docstore_compression: Compressor::None,
+            ..Default::default()
+        };
+        assert_eq!(
+            index_settings.merge_docstore_compression(),
+            Compressor::None
+        );
+        assert_eq!(index_settings.merge_docstore_blocksize(), 16_384);
+        index_settings.docstore_merge_compression = Some(Compressor::Lz4);
+        index_settings.docstore_merge_blocksize = Some(65_536);
+        assert_eq!(index_settings.merge_docstore_compression(), Compressor::Lz4);
+        assert_eq!(index_settings.merge_docstore_blocksize(), 65_536);
+        let index_settings_json = serde_json::to_value(&index_settings).unwrap();
+        assert_eq!(
+            index_settings_json,
+            serde_json::json!({
+                "docstore_compression": "none",
+                "docstore_blocksize": 16384,
+                "docstore_merge_compression": "lz4",
+                "docstore_merge_blocksize": 65536,
+            })
+        );
+        let index_settings_deser: IndexSettings =
+            serde_json::from_value(index_settings_json).unwrap();
+        assert_eq!(index_settings_deser, index_settings);
+    }
 }
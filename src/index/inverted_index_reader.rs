@@ -1,7 +1,7 @@
 use std::io;
 
 use common::json_path_writer::JSON_END_OF_PATH;
-use common::BinarySerializable;
+use common::{BinarySerializable, VInt};
 use fnv::FnvHashSet;
 #[cfg(feature = "quickwit")]
 use futures_util::{FutureExt, StreamExt, TryStreamExt};
@@ -15,6 +15,7 @@ use crate::positions::PositionReader;
 use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
 use crate::schema::{IndexRecordOption, Term, Type};
 use crate::termdict::TermDictionary;
+use crate::TERMINATED;
 
 /// The inverted index reader is in charge of accessing
 /// the inverted index associated with a specific field.
@@ -167,21 +168,32 @@ impl InvertedIndexReader {
         let option = option.downgrade(self.record_option);
 
         let block_postings = self.read_block_postings_from_terminfo(term_info, option)?;
-        let position_reader = {
+        let (position_reader, offsets_reader) = {
             if option.has_positions() {
-                let positions_data = self
+                let mut positions_data = self
                     .positions_file_slice
                     .read_bytes_slice(term_info.positions_range.clone())?;
+                let mut offsets_reader = None;
+                if self.record_option.has_offsets() {
+                    // The offsets of the term are stored before its positions.
+                    let offsets_num_bytes = VInt::deserialize(&mut positions_data)?.0 as usize;
+                    let (offsets_data, remaining_positions_data) =
+                        positions_data.split(offsets_num_bytes);
+                    if option.has_offsets() {
+                        offsets_reader = Some(PositionReader::open(offsets_data)?);
+                    }
+                    positions_data = remaining_positions_data;
+                }
                 let position_reader = PositionReader::open(positions_data)?;
-                Some(position_reader)
+                (Some(position_reader), offsets_reader)
             } else {
-                None
+                (None, None)
             }
         };
-        Ok(SegmentPostings::from_block_postings(
-            block_postings,
-            position_reader,
-        ))
+        Ok(
+            SegmentPostings::from_block_postings(block_postings, position_reader)
+                .with_offsets_reader(offsets_reader),
+        )
     }
 
     /// Returns the total number of tokens recorded for all documents
@@ -217,6 +229,47 @@ impl InvertedIndexReader {
             .map(|term_info| term_info.doc_freq)
             .unwrap_or(0u32))
     }
+
+    /// Returns the total number of occurrences of the term, in all documents
+    /// (including deleted documents).
+    ///
+    /// If the positions are recorded, the skip list holds the sum of the term frequencies of
+    /// each full block, and only the last block is decoded. Otherwise, all of the term
+    /// frequencies of the posting list are decoded.
+    ///
+    /// Terms indexed without their term frequencies count as occurring once per document.
+    pub fn total_term_freq(&self, term: &Term) -> io::Result<u64> {
+        let Some(term_info) = self.get_term_info(term)? else {
+            return Ok(0u64);
+        };
+        if !self.record_option.has_freq() {
+            return Ok(u64::from(term_info.doc_freq));
+        }
+        let mut block_postings =
+            self.read_block_postings_from_terminfo(&term_info, IndexRecordOption::WithFreqs)?;
+        if block_postings.freqs().is_empty() {
+            // In JSON fields, numerical terms are indexed without their term frequencies.
+            return Ok(u64::from(term_info.doc_freq));
+        }
+        let sum_block_freqs = |block_postings: &BlockSegmentPostings| {
+            block_postings
+                .freqs()
+                .iter()
+                .map(|&term_freq| u64::from(term_freq))
+                .sum::<u64>()
+        };
+        if self.record_option.has_positions() {
+            // Jumps to the last block, accumulating the term frequencies of the skipped blocks.
+            block_postings.seek(TERMINATED);
+            return Ok(block_postings.position_offset() + sum_block_freqs(&block_postings));
+        }
+        let mut total_term_freq = 0u64;
+        while !block_postings.docs().is_empty() {
+            total_term_freq += sum_block_freqs(&block_postings);
+            block_postings.advance();
+        }
+        Ok(total_term_freq)
+    }
 }
 
 #[cfg(feature = "quickwit")]
@@ -3,18 +3,28 @@
 //! It contains `Index` and `Segment`, where a `Index` consists of one or more `Segment`s.
 
 mod index;
+#[cfg(feature = "mmap")]
+mod index_aliases;
 mod index_meta;
 mod inverted_index_reader;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+#[cfg(feature = "mmap")]
+mod time_partitioned_index;
 
 pub use self::index::{Index, IndexBuilder};
+#[cfg(feature = "mmap")]
+pub use self::index_aliases::IndexAliases;
 pub(crate) use self::index_meta::SegmentMetaInventory;
-pub use self::index_meta::{IndexMeta, IndexSettings, Order, SegmentMeta};
+pub use self::index_meta::{
+    DocBoostMode, IndexMeta, IndexSettings, IndexSortByField, Order, SegmentMeta,
+};
 pub use self::inverted_index_reader::InvertedIndexReader;
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::{FieldMetadata, SegmentReader};
+#[cfg(feature = "mmap")]
+pub use self::time_partitioned_index::{PartitionInterval, TimePartitionedIndex};
@@ -1,10 +1,12 @@
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use super::SegmentComponent;
 use crate::directory::error::{OpenReadError, OpenWriteError};
 use crate::directory::{Directory, FileSlice, WritePtr};
 use crate::index::{Index, SegmentId, SegmentMeta};
+use crate::indexer::merge_scheduler::WriteRateLimiter;
 use crate::schema::Schema;
 use crate::Opstamp;
 
@@ -13,6 +15,7 @@ use crate::Opstamp;
 pub struct Segment {
     index: Index,
     meta: SegmentMeta,
+    write_rate_limiter: Option<Arc<WriteRateLimiter>>,
 }
 
 impl fmt::Debug for Segment {
@@ -24,7 +27,11 @@ impl fmt::Debug for Segment {
 impl Segment {
     /// Creates a new segment given an `Index` and a `SegmentId`
     pub(crate) fn for_index(index: Index, meta: SegmentMeta) -> Segment {
-        Segment { index, meta }
+        Segment {
+            index,
+            meta,
+            write_rate_limiter: None,
+        }
     }
 
     /// Returns the index the segment belongs to.
@@ -48,8 +55,8 @@ impl Segment {
     /// as we finalize a fresh new segment.
     pub(crate) fn with_max_doc(self, max_doc: u32) -> Segment {
         Segment {
-            index: self.index,
             meta: self.meta.with_max_doc(max_doc),
+            ..self
         }
     }
 
@@ -57,8 +64,28 @@ impl Segment {
     #[must_use]
     pub fn with_delete_meta(self, num_deleted_docs: u32, opstamp: Opstamp) -> Segment {
         Segment {
-            index: self.index,
             meta: self.meta.with_delete_meta(num_deleted_docs, opstamp),
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_update_meta(self, opstamp: Opstamp) -> Segment {
+        Segment {
+            meta: self.meta.with_update_meta(opstamp),
+            ..self
+        }
+    }
+
+    /// Limits the throughput of the files written for this segment.
+    pub(crate) fn with_write_rate_limiter(
+        self,
+        write_rate_limiter: Arc<WriteRateLimiter>,
+    ) -> Segment {
+        Segment {
+            write_rate_limiter: Some(write_rate_limiter),
+            ..self
         }
     }
 
@@ -85,6 +112,9 @@ impl Segment {
     pub fn open_write(&mut self, component: SegmentComponent) -> Result<WritePtr, OpenWriteError> {
         let path = self.relative_path(component);
         let write = self.index.directory_mut().open_write(&path)?;
+        if let Some(write_rate_limiter) = &self.write_rate_limiter {
+            return Ok(write_rate_limiter.wrap(write));
+        }
         Ok(write)
     }
 }
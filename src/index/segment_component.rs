@@ -4,7 +4,8 @@ use std::slice;
 ///
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
-/// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
+/// except the delete and fast field updates components that take an
+/// `segment_uuid`.`opstamp`.`component_extension`
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated with terms
@@ -25,15 +26,22 @@ pub enum SegmentComponent {
     Store,
     /// Temporary storage of the documents, before streamed to `Store`.
     TempStore,
+    /// Stored values that are too large to be kept in the blocks of the `Store`.
+    /// The `Store` references them by their offset in this file.
+    Blobs,
     /// Bitset describing which document of the segment is alive.
     /// (It was representing deleted docs but changed to represent alive docs from v0.17)
     Delete,
+    /// Values of fast fields that were updated after the segment was written.
+    FastFieldUpdates,
+    /// Weighted FSTs of the inputs of the suggest fields, used to complete prefixes.
+    Suggest,
 }
 
 impl SegmentComponent {
     /// Iterates through the components.
     pub fn iterator() -> slice::Iter<'static, SegmentComponent> {
-        static SEGMENT_COMPONENTS: [SegmentComponent; 8] = [
+        static SEGMENT_COMPONENTS: [SegmentComponent; 11] = [
             SegmentComponent::Postings,
             SegmentComponent::Positions,
             SegmentComponent::FastFields,
@@ -41,7 +49,10 @@ impl SegmentComponent {
             SegmentComponent::Terms,
             SegmentComponent::Store,
             SegmentComponent::TempStore,
+            SegmentComponent::Blobs,
             SegmentComponent::Delete,
+            SegmentComponent::FastFieldUpdates,
+            SegmentComponent::Suggest,
         ];
         SEGMENT_COMPONENTS.iter()
     }
@@ -68,6 +68,11 @@ impl SegmentId {
         self.0.as_simple().to_string()
     }
 
+    /// Returns the uuid of the segment as a `u128`.
+    pub(crate) fn as_u128(&self) -> u128 {
+        self.0.as_u128()
+    }
+
     /// Build a `SegmentId` string from the full uuid string.
     ///
     /// E.g. "a5c4dfcbdfe645089129e308e26d5523"
@@ -3,18 +3,25 @@ use std::ops::BitOrAssign;
 use std::sync::{Arc, RwLock};
 use std::{fmt, io};
 
+use columnar::ColumnValueUpdates;
+use common::{BinarySerializable, ByteCount};
 use fnv::FnvHashMap;
 use itertools::Itertools;
 
 use crate::directory::{CompositeFile, FileSlice};
 use crate::error::DataCorruption;
-use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
+use crate::fastfield::{
+    intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders, RuntimeField,
+};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
-use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
+use crate::index::{
+    DocBoostMode, IndexSortByField, InvertedIndexReader, Segment, SegmentComponent, SegmentId,
+};
 use crate::json_utils::json_path_sep_to_dot;
-use crate::schema::{Field, IndexRecordOption, Schema, Type};
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, Type};
 use crate::space_usage::SegmentSpaceUsage;
 use crate::store::StoreReader;
+use crate::suggest::SuggestReader;
 use crate::termdict::TermDictionary;
 use crate::{DocId, Opstamp};
 
@@ -41,12 +48,18 @@ pub struct SegmentReader {
     termdict_composite: CompositeFile,
     postings_composite: CompositeFile,
     positions_composite: CompositeFile,
+    suggest_composite: CompositeFile,
     fast_fields_readers: FastFieldReaders,
+    fast_field_updates_num_bytes: ByteCount,
     fieldnorm_readers: FieldNormReaders,
 
     store_file: FileSlice,
+    blobs_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
     schema: Schema,
+    sort_by_field: Option<IndexSortByField>,
+    doc_boost_field: Option<String>,
+    doc_boost_mode: DocBoostMode,
 }
 
 impl SegmentReader {
@@ -67,6 +80,27 @@ impl SegmentReader {
         &self.schema
     }
 
+    /// Returns the field the documents of this segment are sorted by, if the index is sorted.
+    ///
+    /// See [`IndexSettings::sort_by_field`](crate::IndexSettings::sort_by_field).
+    pub fn sort_by_field(&self) -> Option<&IndexSortByField> {
+        self.sort_by_field.as_ref()
+    }
+
+    /// Returns the name of the field holding the static boost of the documents, if any.
+    ///
+    /// See [`IndexSettings::doc_boost_field`](crate::IndexSettings::doc_boost_field).
+    pub fn doc_boost_field(&self) -> Option<&str> {
+        self.doc_boost_field.as_deref()
+    }
+
+    /// Returns how the static boost of the documents is combined with their scores.
+    ///
+    /// See [`IndexSettings::doc_boost_mode`](crate::IndexSettings::doc_boost_mode).
+    pub fn doc_boost_mode(&self) -> DocBoostMode {
+        self.doc_boost_mode
+    }
+
     /// Return the number of documents that have been
     /// deleted in the segment.
     pub fn num_deleted_docs(&self) -> DocId {
@@ -92,6 +126,14 @@ impl SegmentReader {
         &self.fast_fields_readers
     }
 
+    /// Returns a reader of the same segment whose fast fields include `runtime_fields`.
+    pub(crate) fn with_runtime_fields(&self, runtime_fields: &[RuntimeField]) -> SegmentReader {
+        SegmentReader {
+            fast_fields_readers: self.fast_fields_readers.with_runtime_fields(runtime_fields),
+            ..self.clone()
+        }
+    }
+
     /// Accessor to the `FacetReader` associated with a given `Field`.
     pub fn facet_reader(&self, field_name: &str) -> crate::Result<FacetReader> {
         let schema = self.schema();
@@ -136,7 +178,27 @@ impl SegmentReader {
     /// `cache_num_blocks` sets the number of decompressed blocks to be cached in an LRU.
     /// The size of blocks is configurable, this should be reflexted in the
     pub fn get_store_reader(&self, cache_num_blocks: usize) -> io::Result<StoreReader> {
-        StoreReader::open(self.store_file.clone(), cache_num_blocks)
+        Ok(
+            StoreReader::open(self.store_file.clone(), cache_num_blocks)?
+                .with_blobs(self.blobs_file.clone()),
+        )
+    }
+
+    /// Accessor to the suggestions of the suggest field `field`.
+    ///
+    /// Returns an error if the field is not a suggest field.
+    pub fn suggest_reader(&self, field: Field) -> crate::Result<SuggestReader> {
+        let field_entry = self.schema.get_field_entry(field);
+        if !matches!(field_entry.field_type(), FieldType::Suggest(_)) {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "The field {:?} is not a suggest field.",
+                field_entry.name()
+            )));
+        }
+        match self.suggest_composite.open_read(field) {
+            Some(suggest_file) => Ok(SuggestReader::open(suggest_file)?),
+            None => Ok(SuggestReader::empty()),
+        }
     }
 
     /// Open a new segment for reading.
@@ -153,6 +215,9 @@ impl SegmentReader {
         let termdict_composite = CompositeFile::open(&termdict_file)?;
 
         let store_file = segment.open_read(SegmentComponent::Store)?;
+        let blobs_file = segment
+            .open_read(SegmentComponent::Blobs)
+            .unwrap_or_else(|_| FileSlice::empty());
 
         crate::fail_point!("SegmentReader::open#middle");
 
@@ -167,12 +232,37 @@ impl SegmentReader {
             }
         };
 
+        let suggest_composite = {
+            if let Ok(suggest_file) = segment.open_read(SegmentComponent::Suggest) {
+                CompositeFile::open(&suggest_file)?
+            } else {
+                CompositeFile::empty()
+            }
+        };
+
         let schema = segment.schema();
 
-        let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let mut fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
+        if let Some(block_cache) = segment.index().fast_field_block_cache() {
+            fast_fields_data = block_cache.wrap(segment.id().as_u128(), fast_fields_data);
+        }
+        let mut fast_field_updates_num_bytes = ByteCount::default();
+        let fast_field_updates = if segment.meta().update_opstamp().is_some() {
+            let updates_data = segment
+                .open_read(SegmentComponent::FastFieldUpdates)?
+                .read_bytes()?;
+            fast_field_updates_num_bytes = ByteCount::from(updates_data.len() as u64);
+            ColumnValueUpdates::deserialize(&mut updates_data.as_slice())?
+        } else {
+            ColumnValueUpdates::default()
+        };
+        let fast_fields_readers = FastFieldReaders::open_with_updates(
+            fast_fields_data,
+            schema.clone(),
+            fast_field_updates,
+        )?;
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
-        let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        let fieldnorm_readers = FieldNormReaders::open_with_schema(fieldnorm_data, schema.clone())?;
 
         let original_bitset = if segment.meta().has_deletes() {
             let alive_doc_file_slice = segment.open_read(SegmentComponent::Delete)?;
@@ -197,13 +287,19 @@ impl SegmentReader {
             termdict_composite,
             postings_composite,
             fast_fields_readers,
+            fast_field_updates_num_bytes,
             fieldnorm_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
             store_file,
+            blobs_file,
             alive_bitset_opt,
             positions_composite,
+            suggest_composite,
             schema,
+            sort_by_field: segment.index().settings().sort_by_field.clone(),
+            doc_boost_field: segment.index().settings().doc_boost_field.clone(),
+            doc_boost_mode: segment.index().settings().doc_boost_mode,
         })
     }
 
@@ -428,7 +524,9 @@ impl SegmentReader {
                 .as_ref()
                 .map(AliveBitSet::space_usage)
                 .unwrap_or_default(),
-        ))
+        )
+        .with_suggest(self.suggest_composite.space_usage())
+        .with_fast_field_updates(self.fast_field_updates_num_bytes))
     }
 }
 
@@ -0,0 +1,428 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use time::{Date, Month, OffsetDateTime};
+
+use crate::schema::document::Document;
+use crate::schema::{Field, FieldType, Schema, Value};
+use crate::{
+    DateTime, Index, IndexReader, IndexWriter, MultiSearcher, Opstamp, ReloadPolicy,
+    TantivyDocument, TantivyError,
+};
+
+/// Default memory budget of the writer of each partition.
+const DEFAULT_WRITER_MEMORY_BUDGET: usize = 50_000_000;
+
+/// Time span covered by each partition of a [`TimePartitionedIndex`].
+///
+/// Partitions are aligned on UTC hours or days.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PartitionInterval {
+    /// One partition per hour, stored in a `YYYY-MM-DDTHH` directory.
+    Hourly,
+    /// One partition per day, stored in a `YYYY-MM-DD` directory.
+    Daily,
+}
+
+impl PartitionInterval {
+    fn num_secs(self) -> i64 {
+        match self {
+            PartitionInterval::Hourly => 3_600,
+            PartitionInterval::Daily => 86_400,
+        }
+    }
+
+    /// Returns the start of the partition containing `timestamp`.
+    fn partition_start(self, timestamp: DateTime) -> DateTime {
+        let num_secs = self.num_secs();
+        let timestamp_secs = timestamp.into_timestamp_secs();
+        DateTime::from_timestamp_secs(timestamp_secs.div_euclid(num_secs) * num_secs)
+    }
+
+    fn partition_end(self, partition_start: DateTime) -> DateTime {
+        DateTime::from_timestamp_secs(partition_start.into_timestamp_secs() + self.num_secs())
+    }
+
+    fn partition_name(self, partition_start: DateTime) -> String {
+        let start = partition_start.into_utc();
+        let day = format!(
+            "{:04}-{:02}-{:02}",
+            start.year(),
+            u8::from(start.month()),
+            start.day()
+        );
+        match self {
+            PartitionInterval::Hourly => format!("{day}T{:02}", start.hour()),
+            PartitionInterval::Daily => day,
+        }
+    }
+
+    /// Parses the name of a partition directory back into the start of the partition.
+    fn parse_partition_name(self, name: &str) -> Option<DateTime> {
+        let (day, hour) = match self {
+            PartitionInterval::Hourly => {
+                let (day, hour) = name.split_once('T')?;
+                if hour.len() != 2 {
+                    return None;
+                }
+                (day, hour.parse::<u8>().ok()?)
+            }
+            PartitionInterval::Daily => (name, 0),
+        };
+        let mut day_parts = day.split('-');
+        let (year, month, day_of_month) = (day_parts.next()?, day_parts.next()?, day_parts.next()?);
+        if day_parts.next().is_some()
+            || year.len() != 4
+            || month.len() != 2
+            || day_of_month.len() != 2
+        {
+            return None;
+        }
+        let month = Month::try_from(month.parse::<u8>().ok()?).ok()?;
+        let date =
+            Date::from_calendar_date(year.parse().ok()?, month, day_of_month.parse().ok()?).ok()?;
+        let partition_start: OffsetDateTime = date.with_hms(hour, 0, 0).ok()?.assume_utc();
+        Some(DateTime::from_utc(partition_start))
+    }
+}
+
+struct Partition<D: Document> {
+    index: Index,
+    reader: IndexReader,
+    // The writer is only created once documents are added to the partition.
+    writer: Option<IndexWriter<D>>,
+}
+
+/// A family of indexes partitioned by time, typically used for logs.
+///
+/// Each partition is a regular index, stored in a subdirectory of the root directory named
+/// after the hour or the day it covers. Documents are routed to their partition according
+/// to the value of a date field, and the partitions are searched together through a
+/// [`MultiSearcher`].
+///
+/// Expired data is removed by dropping whole partitions with
+/// [`TimePartitionedIndex::drop_partitions_before`], which is much cheaper than deleting
+/// documents.
+pub struct TimePartitionedIndex<D: Document = TantivyDocument> {
+    root_path: PathBuf,
+    schema: Schema,
+    timestamp_field: Field,
+    interval: PartitionInterval,
+    writer_memory_budget: usize,
+    partitions: Mutex<BTreeMap<DateTime, Partition<D>>>,
+}
+
+impl<D: Document> TimePartitionedIndex<D> {
+    /// Opens the partitions stored in the subdirectories of `root_path`.
+    ///
+    /// Documents are routed according to the first value of the date field
+    /// `timestamp_field_name`. The existing partitions must have been created with the
+    /// same schema and the same interval.
+    pub fn open_or_create_in_dir<P: AsRef<Path>>(
+        root_path: P,
+        schema: Schema,
+        timestamp_field_name: &str,
+        interval: PartitionInterval,
+    ) -> crate::Result<TimePartitionedIndex<D>> {
+        let timestamp_field = schema.get_field(timestamp_field_name)?;
+        if !matches!(
+            schema.get_field_entry(timestamp_field).field_type(),
+            FieldType::Date(_)
+        ) {
+            return Err(TantivyError::SchemaError(format!(
+                "The timestamp field {timestamp_field_name:?} must be a date field"
+            )));
+        }
+        let root_path = root_path.as_ref().to_path_buf();
+        let mut partitions = BTreeMap::new();
+        for entry in std::fs::read_dir(&root_path)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let Some(partition_start) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| interval.parse_partition_name(name))
+            else {
+                continue;
+            };
+            let index = Index::open_in_dir(entry.path())?;
+            if index.schema() != schema {
+                return Err(TantivyError::SchemaError(format!(
+                    "The schema of the partition {:?} does not match.",
+                    entry.path()
+                )));
+            }
+            partitions.insert(partition_start, Partition::open(index)?);
+        }
+        Ok(TimePartitionedIndex {
+            root_path,
+            schema,
+            timestamp_field,
+            interval,
+            writer_memory_budget: DEFAULT_WRITER_MEMORY_BUDGET,
+            partitions: Mutex::new(partitions),
+        })
+    }
+
+    /// Sets the memory budget of the writer of each partition.
+    ///
+    /// It only applies to the writers created after the call.
+    pub fn set_writer_memory_budget(&mut self, writer_memory_budget: usize) {
+        self.writer_memory_budget = writer_memory_budget;
+    }
+
+    /// Returns the schema shared by the partitions.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the start of each partition, in chronological order.
+    pub fn partitions(&self) -> Vec<DateTime> {
+        self.partitions.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Returns the index of the partition starting at `partition_start`, if it exists.
+    pub fn partition_index(&self, partition_start: DateTime) -> Option<Index> {
+        self.partitions
+            .lock()
+            .unwrap()
+            .get(&partition_start)
+            .map(|partition| partition.index.clone())
+    }
+
+    /// Adds a document to the partition matching its timestamp, creating the partition
+    /// if needed.
+    ///
+    /// Returns the opstamp of the operation within the partition. As with
+    /// [`IndexWriter::add_document`], the document only becomes searchable after a
+    /// [`TimePartitionedIndex::commit`].
+    pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        let timestamp = document
+            .iter_fields_and_values()
+            .find(|(field, _)| *field == self.timestamp_field)
+            .and_then(|(_, value)| value.as_datetime())
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The document has no {:?} timestamp",
+                    self.schema.get_field_name(self.timestamp_field)
+                ))
+            })?;
+        let partition_start = self.interval.partition_start(timestamp);
+        let mut partitions = self.partitions.lock().unwrap();
+        let partition = match partitions.entry(partition_start) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let partition_path = self
+                    .root_path
+                    .join(self.interval.partition_name(partition_start));
+                std::fs::create_dir_all(&partition_path)?;
+                let index = Index::create_in_dir(&partition_path, self.schema.clone())?;
+                entry.insert(Partition::open(index)?)
+            }
+        };
+        if partition.writer.is_none() {
+            partition.writer = Some(
+                partition
+                    .index
+                    .writer_with_num_threads(1, self.writer_memory_budget)?,
+            );
+        }
+        partition.writer.as_ref().unwrap().add_document(document)
+    }
+
+    /// Commits the documents added to every partition, and makes them searchable.
+    pub fn commit(&self) -> crate::Result<()> {
+        let mut partitions = self.partitions.lock().unwrap();
+        for partition in partitions.values_mut() {
+            if let Some(writer) = partition.writer.as_mut() {
+                writer.commit()?;
+                partition.reader.reload()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a searcher over all of the partitions.
+    pub fn searcher(&self) -> crate::Result<MultiSearcher> {
+        self.searcher_in_range(DateTime::MIN..DateTime::MAX)
+    }
+
+    /// Returns a searcher over the partitions overlapping with `time_range`.
+    ///
+    /// The partitions are selected as a whole: the documents of a selected partition
+    /// that fall outside of `time_range` still need to be filtered by the query.
+    pub fn searcher_in_range(&self, time_range: Range<DateTime>) -> crate::Result<MultiSearcher> {
+        let partitions = self.partitions.lock().unwrap();
+        let searchers = partitions
+            .iter()
+            .filter(|(partition_start, _)| {
+                **partition_start < time_range.end
+                    && self.interval.partition_end(**partition_start) > time_range.start
+            })
+            .map(|(_, partition)| partition.reader.searcher())
+            .collect();
+        MultiSearcher::new(searchers)
+    }
+
+    /// Drops the partitions that only hold documents older than `cutoff`, and deletes
+    /// their directory.
+    ///
+    /// The documents of these partitions that were not committed are lost.
+    ///
+    /// Returns the start of the dropped partitions.
+    pub fn drop_partitions_before(&self, cutoff: DateTime) -> crate::Result<Vec<DateTime>> {
+        let mut partitions = self.partitions.lock().unwrap();
+        let expired_partition_starts: Vec<DateTime> = partitions
+            .keys()
+            .copied()
+            .filter(|partition_start| self.interval.partition_end(*partition_start) <= cutoff)
+            .collect();
+        for partition_start in &expired_partition_starts {
+            // Dropping the partition waits for its indexing threads to stop.
+            drop(partitions.remove(partition_start));
+            let partition_path = self
+                .root_path
+                .join(self.interval.partition_name(*partition_start));
+            std::fs::remove_dir_all(partition_path)?;
+        }
+        Ok(expired_partition_starts)
+    }
+}
+
+impl<D: Document> Partition<D> {
+    fn open(index: Index) -> crate::Result<Partition<D>> {
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        Ok(Partition {
+            index,
+            reader,
+            writer: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PartitionInterval, TimePartitionedIndex};
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, INDEXED, STORED, TEXT};
+    use crate::DateTime;
+
+    const HOUR: i64 = 3_600;
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_partition_names() {
+        // 2024-03-05T07:30:00Z
+        let timestamp = DateTime::from_timestamp_secs(1_709_623_800);
+        for (interval, name, start_secs) in [
+            (PartitionInterval::Hourly, "2024-03-05T07", 1_709_622_000),
+            (PartitionInterval::Daily, "2024-03-05", 1_709_596_800),
+        ] {
+            let partition_start = interval.partition_start(timestamp);
+            assert_eq!(partition_start.into_timestamp_secs(), start_secs);
+            assert_eq!(interval.partition_name(partition_start), name);
+            assert_eq!(interval.parse_partition_name(name), Some(partition_start));
+        }
+        assert_eq!(
+            PartitionInterval::Daily.parse_partition_name("2024-03-05T07"),
+            None
+        );
+        assert_eq!(
+            PartitionInterval::Hourly.parse_partition_name("2024-03-05"),
+            None
+        );
+        assert_eq!(
+            PartitionInterval::Daily.parse_partition_name("2024-13-05"),
+            None
+        );
+        assert_eq!(PartitionInterval::Daily.parse_partition_name("logs"), None);
+    }
+
+    #[test]
+    fn test_time_partitioned_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let timestamp_field = schema_builder.add_date_field("timestamp", INDEXED | STORED);
+        let message_field = schema_builder.add_text_field("message", TEXT);
+        let schema = schema_builder.build();
+        let root_dir = tempfile::tempdir()?;
+        let partitioned_index: TimePartitionedIndex = TimePartitionedIndex::open_or_create_in_dir(
+            root_dir.path(),
+            schema.clone(),
+            "timestamp",
+            PartitionInterval::Daily,
+        )?;
+        let day0 = 19_000 * DAY;
+        for timestamp_secs in [day0, day0 + HOUR, day0 + DAY + HOUR, day0 + 2 * DAY] {
+            partitioned_index.add_document(doc!(
+                timestamp_field => DateTime::from_timestamp_secs(timestamp_secs),
+                message_field => "hello",
+            ))?;
+        }
+        assert!(partitioned_index
+            .add_document(doc!(message_field => "no timestamp"))
+            .is_err());
+        // Nothing is searchable before the commit.
+        assert_eq!(partitioned_index.searcher()?.search(&AllQuery, &Count)?, 0);
+        partitioned_index.commit()?;
+        assert_eq!(partitioned_index.partitions().len(), 3);
+        assert_eq!(partitioned_index.searcher()?.search(&AllQuery, &Count)?, 4);
+        let searcher = partitioned_index.searcher_in_range(
+            DateTime::from_timestamp_secs(day0 + DAY)
+                ..DateTime::from_timestamp_secs(day0 + 2 * DAY),
+        )?;
+        assert_eq!(searcher.searchers().len(), 1);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 1);
+
+        let dropped = partitioned_index
+            .drop_partitions_before(DateTime::from_timestamp_secs(day0 + DAY + HOUR))?;
+        assert_eq!(dropped, vec![DateTime::from_timestamp_secs(day0)]);
+        assert!(!root_dir
+            .path()
+            .join(PartitionInterval::Daily.partition_name(dropped[0]))
+            .exists());
+        assert_eq!(partitioned_index.searcher()?.search(&AllQuery, &Count)?, 2);
+        drop(partitioned_index);
+
+        // The remaining partitions are reopened.
+        let partitioned_index: TimePartitionedIndex = TimePartitionedIndex::open_or_create_in_dir(
+            root_dir.path(),
+            schema,
+            "timestamp",
+            PartitionInterval::Daily,
+        )?;
+        assert_eq!(
+            partitioned_index.partitions(),
+            vec![
+                DateTime::from_timestamp_secs(day0 + DAY),
+                DateTime::from_timestamp_secs(day0 + 2 * DAY)
+            ]
+        );
+        assert_eq!(partitioned_index.searcher()?.search(&AllQuery, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_partitioned_index_requires_date_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("timestamp", TEXT);
+        let root_dir = tempfile::tempdir()?;
+        let partitioned_index: crate::Result<TimePartitionedIndex> =
+            TimePartitionedIndex::open_or_create_in_dir(
+                root_dir.path(),
+                schema_builder.build(),
+                "timestamp",
+                PartitionInterval::Hourly,
+            );
+        assert!(partitioned_index.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::RwLock;
+
+use crate::directory::GarbageCollectionResult;
+use crate::schema::document::Document;
+use crate::{FutureResult, Index, IndexWriter, Opstamp, TantivyDocument, TantivyError, Term};
+
+/// Async front-end to an [`IndexWriter`].
+///
+/// The operations that may block, like adding a document when the indexing queue is full,
+/// or committing, run on a dedicated pool of threads, so that they never block the
+/// async runtime.
+///
+/// Adding and deleting documents can happen concurrently, while committing and rolling
+/// back wait for the operations in progress to complete, and prevent new operations
+/// from starting until they are done.
+///
+/// Dropping one of the returned futures does not cancel the operation once it has
+/// started.
+pub struct AsyncIndexWriter<D: Document = TantivyDocument> {
+    index: Index,
+    index_writer: Arc<RwLock<IndexWriter<D>>>,
+    thread_pool: ThreadPool,
+}
+
+impl<D: Document> AsyncIndexWriter<D> {
+    /// Creates an async front-end to `index_writer`, running its blocking operations on
+    /// a pool of `num_threads` threads.
+    pub fn new(index_writer: IndexWriter<D>, num_threads: usize) -> crate::Result<Self> {
+        if num_threads == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The async index writer requires at least one thread".to_string(),
+            ));
+        }
+        let thread_pool = ThreadPoolBuilder::new()
+            .thread_name(|i| format!("async_index_writer_{i}"))
+            .num_threads(num_threads)
+            .build()?;
+        Ok(AsyncIndexWriter {
+            index: index_writer.index().clone(),
+            index_writer: Arc::new(RwLock::new(index_writer)),
+            thread_pool,
+        })
+    }
+
+    /// Runs a blocking task on the thread pool.
+    fn spawn_blocking<T, F>(&self, task: F) -> FutureResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> crate::Result<T> + Send + 'static,
+    {
+        let (scheduled_result, sender) =
+            FutureResult::create("An async index writer task did not succeed.");
+        self.thread_pool.spawn(move || {
+            let _ = sender.send(task());
+        });
+        scheduled_result
+    }
+
+    /// Returns the index this writer is writing to.
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Adds a document.
+    ///
+    /// See [`IndexWriter::add_document`].
+    pub async fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        let index_writer = self.index_writer.clone().read_owned().await;
+        self.spawn_blocking(move || index_writer.add_document(document))
+            .await
+    }
+
+    /// Deletes all of the documents containing a given term.
+    ///
+    /// See [`IndexWriter::delete_term`].
+    pub async fn delete_term(&self, term: Term) -> Opstamp {
+        self.index_writer.read().await.delete_term(term)
+    }
+
+    /// Commits all of the pending changes.
+    ///
+    /// See [`IndexWriter::commit`].
+    pub async fn commit(&self) -> crate::Result<Opstamp> {
+        let mut index_writer = self.index_writer.clone().write_owned().await;
+        self.spawn_blocking(move || index_writer.commit()).await
+    }
+
+    /// Rolls back to the last commit.
+    ///
+    /// See [`IndexWriter::rollback`].
+    pub async fn rollback(&self) -> crate::Result<Opstamp> {
+        let mut index_writer = self.index_writer.clone().write_owned().await;
+        self.spawn_blocking(move || index_writer.rollback()).await
+    }
+
+    /// Detects and removes the files that are not used by the index anymore.
+    ///
+    /// See [`IndexWriter::garbage_collect_files`].
+    pub async fn garbage_collect_files(&self) -> crate::Result<GarbageCollectionResult> {
+        let garbage_collection_result = self.index_writer.read().await.garbage_collect_files();
+        garbage_collection_result.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::future::join_all;
+
+    use super::AsyncIndexWriter;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_async_index_writer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let async_index_writer = AsyncIndexWriter::new(index_writer, 2)?;
+        block_on(async {
+            let add_futures = (0..10)
+                .map(|i| async_index_writer.add_document(doc!(id_field => format!("doc{i}"))));
+            for opstamp in join_all(add_futures).await {
+                opstamp?;
+            }
+            async_index_writer
+                .delete_term(Term::from_field_text(id_field, "doc3"))
+                .await;
+            async_index_writer.commit().await?;
+            async_index_writer
+                .add_document(doc!(id_field => "doc10"))
+                .await?;
+            async_index_writer.rollback().await?;
+            crate::Result::Ok(())
+        })?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_async_index_writer_requires_threads() {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        assert!(AsyncIndexWriter::new(index_writer, 0).is_err());
+    }
+}
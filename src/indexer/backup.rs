@@ -0,0 +1,414 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use common::HasLen;
+use serde::{Deserialize, Serialize};
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, OpenReadError};
+use crate::directory::{
+    Directory, FileSlice, ManagedDirectory, TerminatingWrite, WritePtr, META_LOCK,
+};
+use crate::error::DataCorruption;
+use crate::index::{IndexMeta, SegmentMetaInventory};
+use crate::{Index, Opstamp, TantivyError};
+
+/// Name of the file listing the generations of the backups stored in a backup directory.
+const BACKUP_CATALOG_FILEPATH: &str = "backup_catalog.json";
+
+/// Files are copied by chunks of 1MB.
+const COPY_CHUNK_NUM_BYTES: usize = 1 << 20;
+
+fn manifest_path(generation: u64) -> PathBuf {
+    PathBuf::from(format!("backup_manifest.{generation}.json"))
+}
+
+#[derive(Clone, Debug, Default, bon::Builder)]
+/// A builder for the options of [`backup_index`].
+pub struct BackupOptions {
+    #[builder(default)]
+    /// Only copies the files that were not part of the latest backup.
+    ///
+    /// The new backup is then chained to the latest one, and restoring it requires the
+    /// files of its ancestors. Without this option, all of the files are copied, and the
+    /// backup starts a new chain.
+    incremental: bool,
+}
+
+/// A file of a backup.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// The path of the file in the index directory.
+    pub path: PathBuf,
+    /// The number of bytes of the file, footer excluded.
+    pub num_bytes: u64,
+    /// The crc32 of the content of the file, footer excluded.
+    pub checksum: u32,
+}
+
+/// Describes a backup generation, as the changes to the files of its parent generation.
+///
+/// Restoring a generation replays the manifests of the chain, from its root to the
+/// generation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The generation of the backup.
+    pub generation: u64,
+    /// The generation this backup is based on, if it is incremental.
+    pub parent_generation: Option<u64>,
+    /// The opstamp of the commit that was backed up.
+    pub opstamp: Opstamp,
+    /// The files copied by this backup.
+    pub added_files: Vec<BackupFile>,
+    /// The files of the parent generation that are not part of this backup.
+    pub removed_files: Vec<PathBuf>,
+    /// The content of the `meta.json` file that was backed up.
+    meta: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BackupCatalog {
+    generations: Vec<u64>,
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(
+    directory: &dyn Directory,
+    path: &Path,
+) -> crate::Result<T> {
+    let data = directory.atomic_read(path)?;
+    serde_json::from_slice(&data).map_err(|err| {
+        DataCorruption::new(path.to_path_buf(), format!("Cannot deserialize: {err:?}")).into()
+    })
+}
+
+fn write_json<T: Serialize>(
+    directory: &dyn Directory,
+    path: &Path,
+    value: &T,
+) -> crate::Result<()> {
+    let data = serde_json::to_vec_pretty(value)?;
+    directory.atomic_write(path, &data)?;
+    Ok(())
+}
+
+fn read_catalog(backup_directory: &dyn Directory) -> crate::Result<BackupCatalog> {
+    match read_json(backup_directory, Path::new(BACKUP_CATALOG_FILEPATH)) {
+        Err(TantivyError::OpenReadError(OpenReadError::FileDoesNotExist(_))) => {
+            Ok(BackupCatalog::default())
+        }
+        catalog_res => catalog_res,
+    }
+}
+
+/// Returns the manifests of the backups stored in the backup directory, oldest first.
+pub fn list_backups(backup_directory: &dyn Directory) -> crate::Result<Vec<BackupManifest>> {
+    read_catalog(backup_directory)?
+        .generations
+        .into_iter()
+        .map(|generation| read_json(backup_directory, &manifest_path(generation)))
+        .collect()
+}
+
+/// Returns the files of a generation, by replaying the manifests of its chain.
+fn files_of_generation(
+    backup_directory: &dyn Directory,
+    generation: u64,
+) -> crate::Result<(BackupManifest, BTreeMap<PathBuf, BackupFile>)> {
+    let manifest: BackupManifest = read_json(backup_directory, &manifest_path(generation))?;
+    let mut chain = vec![manifest.clone()];
+    while let Some(parent_generation) = chain.last().unwrap().parent_generation {
+        chain.push(read_json(
+            backup_directory,
+            &manifest_path(parent_generation),
+        )?);
+    }
+    let mut files = BTreeMap::new();
+    for manifest in chain.into_iter().rev() {
+        for removed_file in &manifest.removed_files {
+            files.remove(removed_file);
+        }
+        for added_file in manifest.added_files {
+            files.insert(added_file.path.clone(), added_file);
+        }
+    }
+    Ok((manifest, files))
+}
+
+/// Copies a file by chunks, returning its checksum.
+fn copy_file(file_slice: &FileSlice, mut write: WritePtr) -> crate::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    let num_bytes = file_slice.len();
+    let mut offset = 0;
+    while offset < num_bytes {
+        let chunk_end = num_bytes.min(offset + COPY_CHUNK_NUM_BYTES);
+        let chunk = file_slice.read_bytes_slice(offset..chunk_end)?;
+        hasher.update(chunk.as_slice());
+        write.write_all(chunk.as_slice())?;
+        offset = chunk_end;
+    }
+    write.terminate()?;
+    Ok(hasher.finalize())
+}
+
+/// Opens the files of the last commit of an index, returning its meta, along with the
+/// content of the `meta.json` file.
+///
+/// The files are opened while holding a shared [`META_LOCK`], which prevents the garbage
+/// collection of the index from removing them in the meantime. Once opened, the files can be
+/// read while the index keeps being written to, as long as the underlying directory keeps
+/// deleted files readable while they are open (e.g. the
+/// [`MmapDirectory`](crate::directory::MmapDirectory) on Unix).
+pub(crate) fn snapshot_last_commit(
+    index: &Index,
+) -> crate::Result<(IndexMeta, String, BTreeMap<PathBuf, FileSlice>)> {
+    let directory = index.directory();
+    let _meta_lock = directory.acquire_shared_lock(&META_LOCK)?;
+    let meta_data = directory.atomic_read(&META_FILEPATH)?;
+    let meta = String::from_utf8(meta_data).map_err(|_| {
+        DataCorruption::new(
+            META_FILEPATH.to_path_buf(),
+            "Meta file does not contain valid utf8 file.".to_string(),
+        )
+    })?;
+    let index_meta =
+        IndexMeta::deserialize(&meta, &SegmentMetaInventory::default()).map_err(|err| {
+            DataCorruption::new(
+                META_FILEPATH.to_path_buf(),
+                format!("Meta file cannot be deserialized. {err:?}."),
+            )
+        })?;
+    let mut file_slices = BTreeMap::new();
+    for segment_meta in &index_meta.segments {
+        for path in segment_meta.list_files() {
+            if directory.exists(&path)? {
+                let file_slice = directory.open_read(&path)?;
+                file_slices.insert(path, file_slice);
+            }
+        }
+    }
+    Ok((index_meta, meta, file_slices))
+}
+
+/// Backs up the last commit of an index in the backup directory.
+///
+/// The files of the commit are opened while holding a shared [`META_LOCK`], which prevents
+/// the garbage collection of the index from removing them in the meantime. Once opened,
+/// the files can be copied while the index keeps being written to, as long as the
+/// underlying directory keeps deleted files readable while they are open (e.g. the
+/// [`MmapDirectory`](crate::directory::MmapDirectory) on Unix).
+///
+/// Segment files are immutable, so an [incremental](BackupOptionsBuilder::incremental)
+/// backup only copies the files that are not part of the latest backup. The manifest of the
+/// backup is written last: a backup that failed midway is simply not listed.
+pub fn backup_index(
+    index: &Index,
+    backup_directory: &dyn Directory,
+    options: &BackupOptions,
+) -> crate::Result<BackupManifest> {
+    let (index_meta, meta, file_slices) = snapshot_last_commit(index)?;
+
+    let mut catalog = read_catalog(backup_directory)?;
+    let parent_generation = if options.incremental {
+        catalog.generations.last().copied()
+    } else {
+        None
+    };
+    let parent_files = match parent_generation {
+        Some(parent_generation) => files_of_generation(backup_directory, parent_generation)?.1,
+        None => BTreeMap::new(),
+    };
+
+    let mut added_files = Vec::new();
+    for (path, file_slice) in &file_slices {
+        if parent_files.contains_key(path) {
+            continue;
+        }
+        // The file may be left over by a backup that failed midway.
+        match backup_directory.delete(path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+            Err(DeleteError::IoError { io_error, .. }) => {
+                return Err(TantivyError::IoError(io_error))
+            }
+        }
+        let checksum = copy_file(file_slice, backup_directory.open_write(path)?)?;
+        added_files.push(BackupFile {
+            path: path.clone(),
+            num_bytes: file_slice.len() as u64,
+            checksum,
+        });
+    }
+    let living_files: HashSet<&PathBuf> = file_slices.keys().collect();
+    let removed_files: Vec<PathBuf> = parent_files
+        .into_keys()
+        .filter(|path| !living_files.contains(path))
+        .collect();
+    backup_directory.sync_directory()?;
+
+    let generation = catalog
+        .generations
+        .last()
+        .map(|generation| generation + 1)
+        .unwrap_or(0);
+    let manifest = BackupManifest {
+        generation,
+        parent_generation,
+        opstamp: index_meta.opstamp,
+        added_files,
+        removed_files,
+        meta,
+    };
+    write_json(backup_directory, &manifest_path(generation), &manifest)?;
+    catalog.generations.push(generation);
+    write_json(
+        backup_directory,
+        Path::new(BACKUP_CATALOG_FILEPATH),
+        &catalog,
+    )?;
+    info!(
+        "Backed up opstamp {} as generation {generation}, copying {} files",
+        manifest.opstamp,
+        manifest.added_files.len()
+    );
+    Ok(manifest)
+}
+
+/// Restores a backup generation in an empty directory, and opens the restored index.
+///
+/// If `generation` is `None`, the latest backup is restored. The checksum of every file is
+/// verified while copying it.
+pub fn restore_index<T: Into<Box<dyn Directory>>>(
+    backup_directory: &dyn Directory,
+    generation: Option<u64>,
+    target_directory: T,
+) -> crate::Result<Index> {
+    let target_directory: Box<dyn Directory> = target_directory.into();
+    if Index::exists(&*target_directory)? {
+        return Err(TantivyError::IndexAlreadyExists);
+    }
+    let generation = match generation {
+        Some(generation) => generation,
+        None => *read_catalog(backup_directory)?
+            .generations
+            .last()
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument("The backup directory has no backup".to_string())
+            })?,
+    };
+    let (manifest, files) = files_of_generation(backup_directory, generation)?;
+    let managed_directory = ManagedDirectory::wrap(target_directory.box_clone())?;
+    for (path, backup_file) in &files {
+        let file_slice = backup_directory.open_read(path)?;
+        let checksum = copy_file(&file_slice, managed_directory.open_write(path)?)?;
+        if checksum != backup_file.checksum || file_slice.len() as u64 != backup_file.num_bytes {
+            return Err(DataCorruption::new(
+                path.clone(),
+                format!("The backup of the file does not match generation {generation}."),
+            )
+            .into());
+        }
+    }
+    managed_directory.sync_directory()?;
+    managed_directory.atomic_write(&META_FILEPATH, manifest.meta.as_bytes())?;
+    info!(
+        "Restored generation {generation} with {} files",
+        files.len()
+    );
+    Index::open(target_directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_index, list_backups, restore_index, BackupOptions};
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, TantivyError, Term};
+
+    fn num_docs(index: &Index) -> crate::Result<usize> {
+        index.reader()?.searcher().search(&AllQuery, &Count)
+    }
+
+    #[test]
+    fn test_incremental_backup() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let backup_directory = RamDirectory::create();
+        let incremental = BackupOptions::builder().incremental(true).build();
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        let first_manifest = backup_index(&index, &backup_directory, &incremental)?;
+        assert_eq!(first_manifest.generation, 0);
+        assert_eq!(first_manifest.parent_generation, None);
+        assert!(!first_manifest.added_files.is_empty());
+
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        let second_manifest = backup_index(&index, &backup_directory, &incremental)?;
+        assert_eq!(second_manifest.generation, 1);
+        assert_eq!(second_manifest.parent_generation, Some(0));
+        // Only the new segment and the delete file of the first segment are copied.
+        let first_paths: Vec<_> = first_manifest
+            .added_files
+            .iter()
+            .map(|file| &file.path)
+            .collect();
+        assert!(second_manifest
+            .added_files
+            .iter()
+            .all(|file| !first_paths.contains(&&file.path)));
+        assert!(second_manifest
+            .added_files
+            .iter()
+            .any(|file| file.path.extension().unwrap() == "del"));
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.commit()?;
+        let third_manifest = backup_index(&index, &backup_directory, &incremental)?;
+        assert!(!third_manifest.removed_files.is_empty());
+        assert_eq!(list_backups(&backup_directory)?.len(), 3);
+
+        let restored_first = restore_index(&backup_directory, Some(0), RamDirectory::create())?;
+        assert_eq!(num_docs(&restored_first)?, 2);
+        let restored_second = restore_index(&backup_directory, Some(1), RamDirectory::create())?;
+        assert_eq!(num_docs(&restored_second)?, 2);
+        let restored_last = restore_index(&backup_directory, None, RamDirectory::create())?;
+        assert_eq!(num_docs(&restored_last)?, 2);
+        assert_eq!(restored_last.searchable_segment_ids()?.len(), 1);
+
+        // The restored index can be written to.
+        let mut restored_writer: IndexWriter = restored_first.writer_for_tests()?;
+        restored_writer.add_document(doc!(id => "d"))?;
+        restored_writer.commit()?;
+        assert_eq!(num_docs(&restored_first)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_full_backup_starts_new_chain() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let backup_directory = RamDirectory::create();
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        let first_manifest = backup_index(&index, &backup_directory, &BackupOptions::default())?;
+        let second_manifest = backup_index(&index, &backup_directory, &BackupOptions::default())?;
+        assert_eq!(second_manifest.parent_generation, None);
+        assert_eq!(second_manifest.added_files, first_manifest.added_files);
+        let restored = restore_index(&backup_directory, None, RamDirectory::create())?;
+        assert_eq!(num_docs(&restored)?, 1);
+        assert!(matches!(
+            restore_index(&backup_directory, None, index.directory().clone()),
+            Err(TantivyError::IndexAlreadyExists)
+        ));
+        Ok(())
+    }
+}
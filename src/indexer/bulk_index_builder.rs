@@ -0,0 +1,224 @@
+use std::thread::{self, JoinHandle};
+
+use super::index_writer::{
+    MARGIN_IN_BYTES, MEMORY_BUDGET_NUM_BYTES_MAX, MEMORY_BUDGET_NUM_BYTES_MIN,
+};
+use super::operation::AddOperation;
+use super::segment_updater::save_metas;
+use super::segment_writer::sort_segment;
+use super::SegmentWriter;
+use crate::directory::{Directory, DirectoryLock, INDEX_WRITER_LOCK};
+use crate::index::SegmentMeta;
+use crate::schema::document::Document;
+use crate::{Index, IndexMeta, TantivyDocument, TantivyError};
+
+// Bounds the number of documents waiting for an indexing thread.
+const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
+
+/// Builds an index from a stream of documents, in a single pass.
+///
+/// Contrary to the [`IndexWriter`](crate::IndexWriter), the documents are written straight
+/// into the final segments of the index: each indexing thread fills a segment up to its memory
+/// budget, and the segments are never merged. Large memory budgets therefore result in a few
+/// large segments. There is no support for deletes, and nothing is searchable until
+/// [`BulkIndexBuilder::finalize`] is called.
+///
+/// If the index is sorted, feeding the documents in the sort order avoids sorting the
+/// segments when they are flushed.
+///
+/// This is designed for full rebuilds of an index, from a batch export for instance.
+pub struct BulkIndexBuilder<D: Document = TantivyDocument> {
+    index: Index,
+    document_sender: crossbeam_channel::Sender<D>,
+    workers: Vec<JoinHandle<crate::Result<Vec<SegmentMeta>>>>,
+    _directory_lock: DirectoryLock,
+}
+
+impl<D: Document> BulkIndexBuilder<D> {
+    pub(crate) fn new(
+        index: &Index,
+        num_threads: usize,
+        memory_budget_per_thread: usize,
+    ) -> crate::Result<BulkIndexBuilder<D>> {
+        if num_threads == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "At least one indexing thread is required, got 0".to_string(),
+            ));
+        }
+        if !(MEMORY_BUDGET_NUM_BYTES_MIN..MEMORY_BUDGET_NUM_BYTES_MAX)
+            .contains(&memory_budget_per_thread)
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The memory budget per thread needs to be within [{MEMORY_BUDGET_NUM_BYTES_MIN}, \
+                 {MEMORY_BUDGET_NUM_BYTES_MAX})"
+            )));
+        }
+        let directory_lock = index
+            .directory()
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some("Failed to acquire the index lock for the bulk index builder".to_string()),
+                )
+            })?;
+        if !index.searchable_segment_metas()?.is_empty() {
+            return Err(TantivyError::InvalidArgument(
+                "The bulk index builder requires an empty index".to_string(),
+            ));
+        }
+        let (document_sender, document_receiver) =
+            crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
+        let workers = (0..num_threads)
+            .map(|thread_id| {
+                let index = index.clone();
+                let document_receiver = document_receiver.clone();
+                thread::Builder::new()
+                    .name(format!("thrd-tantivy-bulk{thread_id}"))
+                    .spawn(move || {
+                        build_segments(&index, memory_budget_per_thread, document_receiver)
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BulkIndexBuilder {
+            index: index.clone(),
+            document_sender,
+            workers,
+            _directory_lock: directory_lock,
+        })
+    }
+
+    /// Adds a document.
+    ///
+    /// If the indexing threads are busy, this call blocks.
+    pub fn add_document(&self, document: D) -> crate::Result<()> {
+        self.document_sender.send(document).map_err(|_| {
+            TantivyError::ErrorInThread(
+                "The indexing threads stopped. The error is returned by `finalize`.".to_string(),
+            )
+        })
+    }
+
+    /// Waits for the indexing threads to flush their last segment, and publishes all of the
+    /// segments.
+    pub fn finalize(self) -> crate::Result<Index> {
+        drop(self.document_sender);
+        let mut segment_metas = Vec::new();
+        for worker in self.workers {
+            let worker_segment_metas = worker.join().map_err(|_| {
+                TantivyError::ErrorInThread("A bulk indexing thread panicked".to_string())
+            })??;
+            segment_metas.extend(worker_segment_metas);
+        }
+        let index_meta = IndexMeta {
+            index_settings: self.index.settings().clone(),
+            segments: segment_metas,
+            schema: self.index.schema(),
+            opstamp: 0,
+            payload: None,
+        };
+        save_metas(&index_meta, self.index.directory())?;
+        self.index.directory().sync_directory()?;
+        Ok(self.index)
+    }
+}
+
+/// Indexes the received documents into segments of `memory_budget` bytes at most.
+fn build_segments<D: Document>(
+    index: &Index,
+    memory_budget: usize,
+    document_receiver: crossbeam_channel::Receiver<D>,
+) -> crate::Result<Vec<SegmentMeta>> {
+    let mut segment_metas = Vec::new();
+    let mut documents = document_receiver.into_iter().peekable();
+    while documents.peek().is_some() {
+        let segment = index.new_segment();
+        let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+        for document in documents.by_ref() {
+            // Opstamps only matter for deletes.
+            segment_writer.add_document(AddOperation {
+                opstamp: 0,
+                document,
+            })?;
+            if segment_writer.mem_usage() >= memory_budget - MARGIN_IN_BYTES {
+                info!(
+                    "Buffer limit reached, flushing segment with maxdoc={}.",
+                    segment_writer.max_doc()
+                );
+                break;
+            }
+        }
+        let max_doc = segment_writer.max_doc();
+        let doc_opstamps = segment_writer.finalize()?;
+        let (segment, _) = sort_segment(segment.with_max_doc(max_doc), doc_opstamps)?;
+        let segment_meta = segment.meta().clone();
+        segment_meta.untrack_temp_docstore();
+        segment_metas.push(segment_meta);
+    }
+    Ok(segment_metas)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, TEXT};
+    use crate::{Index, IndexSettings, IndexSortByField, IndexWriter, Order, TantivyDocument};
+
+    #[test]
+    fn test_bulk_index_builder() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST | STORED);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "id".to_string(),
+                    order: Order::Asc,
+                }),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let bulk_index_builder = index.bulk_index_builder(2, MEMORY_BUDGET_NUM_BYTES_MIN)?;
+        // The index lock is held until the bulk build is finalized.
+        assert!(index.writer_for_tests::<TantivyDocument>().is_err());
+        for id in 0..50_000u64 {
+            bulk_index_builder.add_document(doc!(
+                id_field => id,
+                text_field => format!("document number {id} with some text to fill the segment"),
+            ))?;
+        }
+        let index = bulk_index_builder.finalize()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 50_000);
+        for segment_reader in searcher.segment_readers() {
+            let id_column = segment_reader.fast_fields().u64("id")?;
+            let segment_ids: Vec<u64> = (0..segment_reader.max_doc())
+                .flat_map(|doc| id_column.values_for_doc(doc))
+                .collect();
+            assert!(segment_ids.windows(2).all(|ids| ids[0] < ids[1]));
+        }
+        // The index can be written to as usual afterwards.
+        let _index_writer: IndexWriter = index.writer_for_tests()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_index_builder_requires_empty_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(index
+            .bulk_index_builder::<TantivyDocument>(0, MEMORY_BUDGET_NUM_BYTES_MIN)
+            .is_err());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id_field => 1u64))?;
+        index_writer.commit()?;
+        drop(index_writer);
+        assert!(index
+            .bulk_index_builder::<TantivyDocument>(1, MEMORY_BUDGET_NUM_BYTES_MIN)
+            .is_err());
+        Ok(())
+    }
+}
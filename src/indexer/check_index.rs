@@ -0,0 +1,468 @@
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use super::segment_updater::save_metas;
+use crate::directory::{Directory, INDEX_WRITER_LOCK};
+use crate::docset::{DocSet, TERMINATED};
+use crate::index::{Index, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::postings::Postings;
+use crate::{DocId, TantivyError};
+
+/// The components every segment is expected to have a file for.
+const REQUIRED_COMPONENTS: [SegmentComponent; 6] = [
+    SegmentComponent::Postings,
+    SegmentComponent::Positions,
+    SegmentComponent::Terms,
+    SegmentComponent::FastFields,
+    SegmentComponent::FieldNorms,
+    SegmentComponent::Store,
+];
+
+#[derive(Clone, Debug, Default, bon::Builder)]
+/// A builder for the options of [`check_index`].
+pub struct CheckIndexOptions {
+    #[builder(default)]
+    /// Also decodes all the postings, positions, fast field values and stored documents of
+    /// the segments, instead of only checking the checksums and the document counts.
+    deep: bool,
+    #[builder(default)]
+    /// Removes the corrupt segments from the index, keeping the healthy ones.
+    ///
+    /// The documents of the removed segments are lost.
+    repair: bool,
+}
+
+/// A problem found in a segment by [`check_index`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SegmentProblem {
+    /// A file of the segment is missing.
+    MissingFile(PathBuf),
+    /// The checksum of a file of the segment does not match its content.
+    ChecksumMismatch(PathBuf),
+    /// A component of the segment does not have the number of documents recorded in the
+    /// segment meta.
+    DocCountMismatch {
+        /// The name of the component.
+        component: &'static str,
+        /// The number of documents recorded in the segment meta.
+        expected: u32,
+        /// The number of documents of the component.
+        actual: u32,
+    },
+    /// The segment could not be opened or decoded.
+    Corrupted(String),
+}
+
+impl fmt::Display for SegmentProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentProblem::MissingFile(path) => write!(f, "missing file {path:?}"),
+            SegmentProblem::ChecksumMismatch(path) => write!(f, "checksum mismatch in {path:?}"),
+            SegmentProblem::DocCountMismatch {
+                component,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{component} has {actual} documents, {expected} were expected"
+            ),
+            SegmentProblem::Corrupted(msg) => write!(f, "corrupted: {msg}"),
+        }
+    }
+}
+
+/// The result of the check of a segment.
+#[derive(Clone, Debug)]
+pub struct SegmentCheckReport {
+    /// The id of the segment.
+    pub segment_id: SegmentId,
+    /// The number of documents of the segment, including the deleted ones.
+    pub max_doc: u32,
+    /// The problems found in the segment.
+    pub problems: Vec<SegmentProblem>,
+}
+
+impl SegmentCheckReport {
+    /// Returns true if no problem was found in the segment.
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The result of [`check_index`].
+#[derive(Clone, Debug, Default)]
+pub struct CheckIndexReport {
+    /// The reports of the searchable segments of the index.
+    pub segments: Vec<SegmentCheckReport>,
+    /// The segments removed from the index, if the check was run with
+    /// [`CheckIndexOptions::repair`] enabled.
+    pub dropped_segments: Vec<SegmentId>,
+}
+
+impl CheckIndexReport {
+    /// Returns true if no problem was found in any of the segments.
+    pub fn is_healthy(&self) -> bool {
+        self.segments.iter().all(SegmentCheckReport::is_healthy)
+    }
+
+    /// Returns the ids of the segments in which a problem was found.
+    pub fn corrupt_segment_ids(&self) -> Vec<SegmentId> {
+        self.segments
+            .iter()
+            .filter(|segment_report| !segment_report.is_healthy())
+            .map(|segment_report| segment_report.segment_id)
+            .collect()
+    }
+}
+
+/// Checks the integrity of the searchable segments of an index.
+///
+/// For each segment, the files are checked to exist and to match their checksum, and the
+/// number of documents of the different components is compared to the segment meta. With
+/// [`CheckIndexOptions::deep`], all the postings, positions, fast field values and stored
+/// documents are decoded as well.
+///
+/// With [`CheckIndexOptions::repair`], the segments in which a problem was found are removed
+/// from the index meta. Their files are left to the garbage collection of the next
+/// `IndexWriter`. Repairing takes the `IndexWriter` lock, and fails if it is held.
+pub fn check_index(index: &Index, options: &CheckIndexOptions) -> crate::Result<CheckIndexReport> {
+    let _directory_lock = if options.repair {
+        let directory_lock = index
+            .directory()
+            .acquire_lock(&INDEX_WRITER_LOCK)
+            .map_err(|err| {
+                TantivyError::LockFailure(
+                    err,
+                    Some("Failed to acquire the index lock to repair the index".to_string()),
+                )
+            })?;
+        Some(directory_lock)
+    } else {
+        None
+    };
+    let index_meta = index.load_metas()?;
+    let segments: Vec<SegmentCheckReport> = index_meta
+        .segments
+        .iter()
+        .map(|segment_meta| check_segment(index, segment_meta, options.deep))
+        .collect();
+    let mut report = CheckIndexReport {
+        segments,
+        dropped_segments: Vec::new(),
+    };
+    if !options.repair || report.is_healthy() {
+        return Ok(report);
+    }
+    let dropped_segments = report.corrupt_segment_ids();
+    for segment_report in &report.segments {
+        for problem in &segment_report.problems {
+            warn!(
+                "Dropping segment {}: {problem}",
+                segment_report.segment_id.short_uuid_string()
+            );
+        }
+    }
+    let mut repaired_meta = index_meta;
+    repaired_meta
+        .segments
+        .retain(|segment_meta| !dropped_segments.contains(&segment_meta.id()));
+    save_metas(&repaired_meta, index.directory())?;
+    index.directory().sync_directory()?;
+    report.dropped_segments = dropped_segments;
+    Ok(report)
+}
+
+fn check_segment(index: &Index, segment_meta: &SegmentMeta, deep: bool) -> SegmentCheckReport {
+    let mut problems = check_segment_files(index, segment_meta);
+    // The files are only decoded if they are sound, as decoding corrupt files may panic.
+    if problems.is_empty() {
+        let segment = index.segment(segment_meta.clone());
+        match catch_unwind(AssertUnwindSafe(|| {
+            let segment_reader = SegmentReader::open(&segment)?;
+            let mut problems = check_doc_counts(segment_meta, &segment_reader)?;
+            if deep && problems.is_empty() {
+                problems.extend(deep_check(&segment_reader)?);
+            }
+            crate::Result::Ok(problems)
+        })) {
+            Ok(Ok(segment_problems)) => problems.extend(segment_problems),
+            Ok(Err(err)) => problems.push(SegmentProblem::Corrupted(err.to_string())),
+            Err(panic) => problems.push(SegmentProblem::Corrupted(panic_message(&panic))),
+        }
+    }
+    SegmentCheckReport {
+        segment_id: segment_meta.id(),
+        max_doc: segment_meta.max_doc(),
+        problems,
+    }
+}
+
+fn check_segment_files(index: &Index, segment_meta: &SegmentMeta) -> Vec<SegmentProblem> {
+    let mut required_files: Vec<PathBuf> = REQUIRED_COMPONENTS
+        .iter()
+        .map(|component| segment_meta.relative_path(*component))
+        .collect();
+    if segment_meta.has_deletes() {
+        required_files.push(segment_meta.relative_path(SegmentComponent::Delete));
+    }
+    if segment_meta.update_opstamp().is_some() {
+        required_files.push(segment_meta.relative_path(SegmentComponent::FastFieldUpdates));
+    }
+    let mut problems = Vec::new();
+    let directory = index.directory();
+    let mut files: Vec<PathBuf> = segment_meta.list_files().into_iter().collect();
+    files.sort();
+    for path in files {
+        match directory.exists(&path) {
+            Ok(true) => {}
+            Ok(false) => {
+                if required_files.contains(&path) {
+                    problems.push(SegmentProblem::MissingFile(path));
+                }
+                continue;
+            }
+            Err(err) => {
+                problems.push(SegmentProblem::Corrupted(err.to_string()));
+                continue;
+            }
+        }
+        match directory.validate_checksum(&path) {
+            Ok(true) => {}
+            Ok(false) => problems.push(SegmentProblem::ChecksumMismatch(path)),
+            Err(err) => problems.push(SegmentProblem::Corrupted(err.to_string())),
+        }
+    }
+    problems
+}
+
+fn check_doc_count(
+    component: &'static str,
+    expected: u32,
+    actual: u32,
+    problems: &mut Vec<SegmentProblem>,
+) {
+    if expected != actual {
+        problems.push(SegmentProblem::DocCountMismatch {
+            component,
+            expected,
+            actual,
+        });
+    }
+}
+
+fn check_doc_counts(
+    segment_meta: &SegmentMeta,
+    segment_reader: &SegmentReader,
+) -> crate::Result<Vec<SegmentProblem>> {
+    let mut problems = Vec::new();
+    let max_doc = segment_meta.max_doc();
+    check_doc_count(
+        "alive bitset",
+        segment_meta.num_docs(),
+        segment_reader.num_docs(),
+        &mut problems,
+    );
+    check_doc_count(
+        "fast fields",
+        max_doc,
+        segment_reader.fast_fields().columnar().num_docs(),
+        &mut problems,
+    );
+    let schema = segment_reader.schema();
+    for (field, field_entry) in schema.fields() {
+        if !field_entry.is_indexed() || !field_entry.has_fieldnorms() {
+            continue;
+        }
+        if let Some(fieldnorm_reader) = segment_reader.fieldnorms_readers().get_field(field)? {
+            check_doc_count(
+                "fieldnorms",
+                max_doc,
+                fieldnorm_reader.num_docs(),
+                &mut problems,
+            );
+        }
+    }
+    let store_reader = segment_reader.get_store_reader(0)?;
+    let store_num_docs = store_reader
+        .block_checkpoints()
+        .last()
+        .map(|checkpoint| checkpoint.doc_range.end)
+        .unwrap_or(0);
+    check_doc_count("doc store", max_doc, store_num_docs, &mut problems);
+    Ok(problems)
+}
+
+/// Decodes all of the data of the segment, checking its consistency.
+fn deep_check(segment_reader: &SegmentReader) -> crate::Result<Vec<SegmentProblem>> {
+    let mut problems = Vec::new();
+    let max_doc = segment_reader.max_doc();
+    let schema = segment_reader.schema();
+    let mut positions = Vec::new();
+    for (field, field_entry) in schema.fields() {
+        let Some(record_option) = field_entry.field_type().get_index_record_option() else {
+            continue;
+        };
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let mut term_stream = inverted_index.terms().stream()?;
+        while term_stream.advance() {
+            let term_info = term_stream.value();
+            let mut postings =
+                inverted_index.read_postings_from_terminfo(term_info, record_option)?;
+            let mut doc_freq = 0u32;
+            let mut previous_doc: Option<DocId> = None;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                if doc >= max_doc || previous_doc.is_some_and(|previous_doc| doc <= previous_doc) {
+                    problems.push(SegmentProblem::Corrupted(format!(
+                        "invalid doc id {doc} in the postings of field {:?}",
+                        field_entry.name()
+                    )));
+                    return Ok(problems);
+                }
+                if record_option.has_positions() {
+                    postings.positions(&mut positions);
+                    if positions.len() != postings.term_freq() as usize {
+                        problems.push(SegmentProblem::Corrupted(format!(
+                            "positions do not match the term frequency in field {:?}",
+                            field_entry.name()
+                        )));
+                        return Ok(problems);
+                    }
+                }
+                previous_doc = Some(doc);
+                doc_freq += 1;
+                doc = postings.advance();
+            }
+            if doc_freq != term_info.doc_freq {
+                problems.push(SegmentProblem::Corrupted(format!(
+                    "doc frequency mismatch in the postings of field {:?}",
+                    field_entry.name()
+                )));
+                return Ok(problems);
+            }
+        }
+    }
+    let columnar = segment_reader.fast_fields().columnar();
+    for (_, column_handle) in columnar.list_columns()? {
+        let Some(column) = column_handle.open_u64_lenient()? else {
+            continue;
+        };
+        check_doc_count(
+            "fast field column",
+            max_doc,
+            column.num_docs(),
+            &mut problems,
+        );
+        for doc in 0..column.num_docs().min(max_doc) {
+            for _ in column.values_for_doc(doc) {}
+        }
+    }
+    let store_reader = segment_reader.get_store_reader(1)?;
+    let mut num_stored_docs = 0u32;
+    for doc_bytes_res in store_reader.iter_raw(None) {
+        doc_bytes_res?;
+        num_stored_docs += 1;
+    }
+    check_doc_count("doc store", max_doc, num_stored_docs, &mut problems);
+    Ok(problems)
+}
+
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = panic.downcast_ref::<&str>() {
+        format!("panic: {msg}")
+    } else if let Some(msg) = panic.downcast_ref::<String>() {
+        format!("panic: {msg}")
+    } else {
+        "panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_index, CheckIndexOptions, SegmentProblem};
+    use crate::collector::Count;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::index::SegmentComponent;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, STORED, TEXT};
+    use crate::{Index, IndexWriter};
+
+    fn create_index(directory: RamDirectory) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let num = schema_builder.add_u64_field("num", FAST);
+        let index = Index::create(directory, schema_builder.build(), Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment in 0..3u64 {
+            for i in 0..10u64 {
+                index_writer
+                    .add_document(doc!(text => "hello happy tax payer", num => segment * 10 + i))?;
+            }
+            index_writer.commit()?;
+        }
+        Ok(index)
+    }
+
+    #[test]
+    fn test_check_index_healthy() -> crate::Result<()> {
+        let index = create_index(RamDirectory::create())?;
+        let options = CheckIndexOptions::builder().deep(true).build();
+        let report = check_index(&index, &options)?;
+        assert_eq!(report.segments.len(), 3);
+        assert!(report.is_healthy());
+        assert!(report.dropped_segments.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_index_repair_corrupt_segment() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let index = create_index(directory.clone())?;
+        let segment_metas = index.searchable_segment_metas()?;
+        let store_path = segment_metas[1].relative_path(SegmentComponent::Store);
+        let mut store_data = directory.atomic_read(&store_path)?;
+        store_data[0] ^= 0xFF;
+        directory.atomic_write(&store_path, &store_data)?;
+        let missing_path = segment_metas[2].relative_path(SegmentComponent::FieldNorms);
+        directory.delete(&missing_path).unwrap();
+
+        let report = check_index(&index, &CheckIndexOptions::default())?;
+        assert!(!report.is_healthy());
+        assert!(report.segments[0].is_healthy());
+        assert_eq!(
+            report.segments[1].problems,
+            vec![SegmentProblem::ChecksumMismatch(store_path)]
+        );
+        assert_eq!(
+            report.segments[2].problems,
+            vec![SegmentProblem::MissingFile(missing_path)]
+        );
+        assert!(report.dropped_segments.is_empty());
+        assert_eq!(index.searchable_segment_metas()?.len(), 3);
+
+        let options = CheckIndexOptions::builder().repair(true).build();
+        let report = check_index(&index, &options)?;
+        assert_eq!(
+            report.dropped_segments,
+            vec![segment_metas[1].id(), segment_metas[2].id()]
+        );
+        assert_eq!(index.searchable_segment_ids()?, vec![segment_metas[0].id()]);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 10);
+        assert!(check_index(&index, &CheckIndexOptions::default())?.is_healthy());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_index_repair_requires_lock() -> crate::Result<()> {
+        let index = create_index(RamDirectory::create())?;
+        let _index_writer: IndexWriter = index.writer_for_tests()?;
+        let options = CheckIndexOptions::builder().repair(true).build();
+        assert!(check_index(&index, &options).is_err());
+        assert!(check_index(&index, &CheckIndexOptions::default())?.is_healthy());
+        Ok(())
+    }
+}
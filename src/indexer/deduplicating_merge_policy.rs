@@ -0,0 +1,147 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use columnar::Column;
+
+use super::merge_policy::{MergeCandidate, MergePolicy};
+use super::unique_key::{KeyColumn, UniqueKey};
+use crate::index::{SegmentMeta, SegmentReader};
+use crate::DocId;
+
+/// Merge policy wrapper dropping the older duplicates of a document when merging segments.
+///
+/// Documents are identified by the value of a unique key fast field, which can be a str,
+/// bytes, or numerical field. When several of the segments being merged contain documents
+/// with the same key, only the document with the highest value of the version fast field is
+/// kept. Documents without a version are considered older than the ones with a version, and
+/// documents without a key are always kept.
+///
+/// Opstamps are not stored in the index, so that the version has to be part of the documents.
+/// A timestamp or a sequence number attributed by the ingestion pipeline typically works.
+///
+/// This is useful for pipelines ingesting documents out of order, which cannot deduplicate them
+/// upfront with [`IndexWriter::upsert_document`](crate::IndexWriter::upsert_document). Note
+/// that duplicates remain visible until the segments containing them get merged together.
+///
+/// The choice of the segments to merge is delegated to the wrapped merge policy.
+#[derive(Debug)]
+pub struct DeduplicatingMergePolicy {
+    merge_policy: Box<dyn MergePolicy>,
+    unique_key_field: String,
+    version_field: String,
+}
+
+impl DeduplicatingMergePolicy {
+    /// Wraps `merge_policy`, deduplicating documents by the value of `unique_key_field`, and
+    /// keeping the one with the highest value of `version_field`.
+    pub fn new(
+        merge_policy: Box<dyn MergePolicy>,
+        unique_key_field: impl ToString,
+        version_field: impl ToString,
+    ) -> DeduplicatingMergePolicy {
+        DeduplicatingMergePolicy {
+            merge_policy,
+            unique_key_field: unique_key_field.to_string(),
+            version_field: version_field.to_string(),
+        }
+    }
+}
+
+/// Version of a document, along with its location, to break ties deterministically.
+type DocVersion = (Option<u64>, usize, DocId);
+
+impl MergePolicy for DeduplicatingMergePolicy {
+    fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate> {
+        self.merge_policy.compute_merge_candidates(segments)
+    }
+
+    fn docs_to_drop_on_merge(
+        &self,
+        segment_readers: &[SegmentReader],
+    ) -> crate::Result<Vec<Vec<DocId>>> {
+        let mut docs_to_drop: Vec<Vec<DocId>> = vec![Vec::new(); segment_readers.len()];
+        let mut latest_docs: HashMap<UniqueKey, DocVersion> = HashMap::new();
+        for (segment_ord, segment_reader) in segment_readers.iter().enumerate() {
+            let key_column = KeyColumn::open(segment_reader, &self.unique_key_field)?;
+            let version_column: Option<Column<u64>> = segment_reader
+                .fast_fields()
+                .u64_lenient(&self.version_field)?
+                .map(|(column, _)| column);
+            for doc in segment_reader.doc_ids_alive() {
+                let Some(key) = key_column.key(doc)? else {
+                    continue;
+                };
+                let version = version_column.as_ref().and_then(|column| column.first(doc));
+                let doc_version = (version, segment_ord, doc);
+                match latest_docs.entry(key) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(doc_version);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let older_doc_version = if *entry.get() < doc_version {
+                            entry.insert(doc_version)
+                        } else {
+                            doc_version
+                        };
+                        let (_, older_segment_ord, older_doc) = older_doc_version;
+                        docs_to_drop[older_segment_ord].push(older_doc);
+                    }
+                }
+            }
+        }
+        Ok(docs_to_drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeduplicatingMergePolicy;
+    use crate::collector::Count;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_deduplicating_merge_policy() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING | FAST);
+        let version_field = schema_builder.add_u64_field("version", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(DeduplicatingMergePolicy::new(
+            Box::new(NoMergePolicy),
+            "id",
+            "version",
+        )));
+        // The versions of "a" arrive out of order.
+        for (id, version) in [
+            ("a", 2u64),
+            ("b", 1),
+            ("a", 1),
+            ("c", 1),
+            ("a", 3),
+            ("b", 2),
+        ] {
+            index_writer.add_document(doc!(id_field => id, version_field => version))?;
+            index_writer.commit()?;
+        }
+        index_writer.add_document(doc!(version_field => 1u64))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        // One document per key, plus the document without a key.
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 4);
+        for (version, expected_count) in [(1u64, 2), (2, 1), (3, 1)] {
+            let query = TermQuery::new(
+                Term::from_field_u64(version_field, version),
+                IndexRecordOption::Basic,
+            );
+            assert_eq!(searcher.search(&query, &Count)?, expected_count);
+        }
+        Ok(())
+    }
+}
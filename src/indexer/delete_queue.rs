@@ -266,6 +266,7 @@ mod tests {
         let make_op = |i: usize| DeleteOperation {
             opstamp: i as u64,
             target: Box::new(DummyWeight),
+            fast_field_update: None,
         };
 
         delete_queue.push(make_op(1));
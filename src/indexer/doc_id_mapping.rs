@@ -9,6 +9,8 @@ use crate::DocAddress;
 pub enum MappingType {
     Stacked,
     StackedWithDeletes,
+    /// The documents are reordered, e.g. to sort the index.
+    Shuffled,
 }
 
 /// Struct to provide mapping from new doc_id to old doc_id and segment.
@@ -0,0 +1,156 @@
+use std::ops::Bound;
+
+use common::{BitSet, ReadOnlyBitSet};
+use time::OffsetDateTime;
+
+use crate::fastfield::AliveBitSet;
+use crate::query::{EnableScoring, Query, RangeQuery};
+use crate::{DateTime, DocSet, Index, Segment, SegmentReader, Term, TERMINATED};
+
+/// Returns a query matching the documents that are expired at the current time, if the
+/// index has an expiration field.
+///
+/// See [`IndexSettings::expiration_field`](crate::IndexSettings::expiration_field).
+pub(crate) fn expired_docs_query(index: &Index) -> Option<RangeQuery> {
+    let expiration_field_name = index.settings().expiration_field.as_ref()?;
+    // The expiration field is validated when the index is created.
+    let expiration_field = index.schema().get_field(expiration_field_name).ok()?;
+    let now = DateTime::from_utc(OffsetDateTime::now_utc());
+    Some(RangeQuery::new(
+        Bound::Unbounded,
+        Bound::Excluded(Term::from_field_date(expiration_field, now)),
+    ))
+}
+
+/// Returns the alive bitset of a segment, from which the expired documents have been removed.
+///
+/// Returns `None` if the segment does not contain any expired document.
+pub(crate) fn alive_bitset_without_expired_docs(
+    index: &Index,
+    segment: &Segment,
+) -> crate::Result<Option<AliveBitSet>> {
+    let Some(expired_docs_query) = expired_docs_query(index) else {
+        return Ok(None);
+    };
+    let segment_reader = SegmentReader::open(segment)?;
+    let schema = index.schema();
+    let weight = expired_docs_query.weight(EnableScoring::disabled_from_schema(&schema))?;
+    let mut alive_bitset = BitSet::with_max_value_and_full(segment_reader.max_doc());
+    let mut has_expired_docs = false;
+    let mut scorer = weight.scorer(&segment_reader, 1.0)?;
+    let mut doc = scorer.doc();
+    while doc != TERMINATED {
+        alive_bitset.remove(doc);
+        has_expired_docs = true;
+        doc = scorer.advance();
+    }
+    if !has_expired_docs {
+        return Ok(None);
+    }
+    Ok(Some(AliveBitSet::from(ReadOnlyBitSet::from(&alive_bitset))))
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, OffsetDateTime};
+
+    use crate::collector::Count;
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{DateTime, DocAddress, Index, IndexSettings, IndexWriter, Term};
+
+    fn date_from_now(duration: Duration) -> DateTime {
+        DateTime::from_utc(OffsetDateTime::now_utc() + duration)
+    }
+
+    fn create_index(expiration_field: Option<&str>) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let expires_at_field = schema_builder.add_date_field("expires_at", FAST);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                expiration_field: expiration_field.map(ToString::to_string),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id_field => "expired",
+            expires_at_field => date_from_now(-Duration::hours(1)),
+        ))?;
+        index_writer.add_document(doc!(
+            id_field => "live",
+            expires_at_field => date_from_now(Duration::hours(1)),
+        ))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id_field => "no_expiration"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_expired_docs_deleted_on_commit() -> crate::Result<()> {
+        let index = create_index(Some("expires_at"))?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_docs_excluded_from_search() -> crate::Result<()> {
+        let mut index = create_index(None)?;
+        index.settings_mut().expiration_field = Some("expires_at".to_string());
+        let searcher = index.reader()?.searcher();
+        // The expired document has not been deleted yet...
+        assert_eq!(searcher.num_docs(), 3);
+        // ... but it does not show up in search results anymore.
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+        assert_eq!(AllQuery.count(&searcher)?, 2);
+        let id_field = index.schema().get_field("id").unwrap();
+        let expired_query = TermQuery::new(
+            Term::from_field_text(id_field, "expired"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(expired_query.count(&searcher)?, 0);
+        assert!(expired_query
+            .explain(&searcher, DocAddress::new(0, 0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_docs_dropped_on_merge() -> crate::Result<()> {
+        let mut index = create_index(None)?;
+        index.settings_mut().expiration_field = Some("expires_at".to_string());
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiration_field_validation() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_date_field("expires_at", INDEXED);
+        let err = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                expiration_field: Some("expires_at".to_string()),
+                ..Default::default()
+            })
+            .create_in_ram()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Field expires_at is not a date fast field. The \
+             field needs to be a date fast field to be used as expiration field'"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use super::merge_policy::MergeCandidate;
+use crate::index::SegmentMeta;
+
+/// Groups `segments` into at most `max_num_segments` merges, without producing segments
+/// with more than `max_segment_num_docs` documents.
+///
+/// The segments are assigned from the largest to the smallest to the group with the fewest
+/// documents they fit in. Segments that do not fit in any group start a new one, so that the
+/// size limit may result in more than `max_num_segments` segments.
+///
+/// Groups of a single segment are left as is.
+pub(crate) fn force_merge_candidates(
+    segments: &[SegmentMeta],
+    max_num_segments: usize,
+    max_segment_num_docs: Option<u32>,
+) -> Vec<MergeCandidate> {
+    if segments.len() <= max_num_segments {
+        return Vec::new();
+    }
+    let max_segment_num_docs = max_segment_num_docs.map_or(u64::MAX, u64::from);
+    let mut size_sorted_segments: Vec<&SegmentMeta> = segments.iter().collect();
+    size_sorted_segments.sort_by_key(|segment| std::cmp::Reverse(segment.num_docs()));
+    let mut groups: Vec<(u64, Vec<&SegmentMeta>)> = Vec::new();
+    for segment in size_sorted_segments {
+        let segment_num_docs = u64::from(segment.num_docs());
+        if groups.len() < max_num_segments {
+            groups.push((segment_num_docs, vec![segment]));
+            continue;
+        }
+        let smallest_fitting_group = groups
+            .iter_mut()
+            .filter(|(group_num_docs, _)| group_num_docs + segment_num_docs <= max_segment_num_docs)
+            .min_by_key(|(group_num_docs, _)| *group_num_docs);
+        if let Some((group_num_docs, group)) = smallest_fitting_group {
+            *group_num_docs += segment_num_docs;
+            group.push(segment);
+        } else {
+            groups.push((segment_num_docs, vec![segment]));
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|(_, group)| MergeCandidate(group.iter().map(|segment| segment.id()).collect()))
+        .collect()
+}
+
+/// Returns a merge for each of the segments whose ratio of deleted documents exceeds
+/// `del_docs_ratio_threshold`, in order to rewrite them without their deleted documents.
+pub(crate) fn expunge_deletes_candidates(
+    segments: &[SegmentMeta],
+    del_docs_ratio_threshold: f32,
+) -> Vec<MergeCandidate> {
+    segments
+        .iter()
+        .filter(|segment| {
+            segment.has_deletes()
+                && segment.num_deleted_docs() as f32 / segment.max_doc() as f32
+                    > del_docs_ratio_threshold
+        })
+        .map(|segment| MergeCandidate(vec![segment.id()]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::index::{SegmentId, SegmentMetaInventory};
+
+    static INVENTORY: Lazy<SegmentMetaInventory> = Lazy::new(SegmentMetaInventory::default);
+
+    fn create_random_segment_meta(num_docs: u32) -> SegmentMeta {
+        INVENTORY.new_segment_meta(SegmentId::generate_random(), num_docs)
+    }
+
+    fn candidate_sizes(candidates: &[MergeCandidate], segments: &[SegmentMeta]) -> Vec<u32> {
+        let mut sizes: Vec<u32> = candidates
+            .iter()
+            .map(|candidate| {
+                candidate
+                    .0
+                    .iter()
+                    .map(|segment_id| {
+                        segments
+                            .iter()
+                            .find(|segment| segment.id() == *segment_id)
+                            .unwrap()
+                            .num_docs()
+                    })
+                    .sum()
+            })
+            .collect();
+        sizes.sort();
+        sizes
+    }
+
+    #[test]
+    fn test_force_merge_candidates() {
+        let segments: Vec<SegmentMeta> = [100, 80, 30, 20, 10, 5]
+            .into_iter()
+            .map(create_random_segment_meta)
+            .collect();
+        assert!(force_merge_candidates(&segments, 6, None).is_empty());
+        let candidates = force_merge_candidates(&segments, 1, None);
+        assert_eq!(candidate_sizes(&candidates, &segments), vec![245]);
+        let candidates = force_merge_candidates(&segments, 2, None);
+        assert_eq!(candidate_sizes(&candidates, &segments), vec![120, 125]);
+    }
+
+    #[test]
+    fn test_force_merge_candidates_max_segment_num_docs() {
+        let segments: Vec<SegmentMeta> = [100, 80, 30, 20, 10, 5]
+            .into_iter()
+            .map(create_random_segment_meta)
+            .collect();
+        // The segments with 100 and 80 docs cannot be merged with anything else.
+        let candidates = force_merge_candidates(&segments, 1, Some(100));
+        assert_eq!(candidate_sizes(&candidates, &segments), vec![65]);
+        // The segment with 5 docs fits in neither of the two merged segments.
+        let candidates = force_merge_candidates(&segments, 2, Some(120));
+        assert_eq!(candidate_sizes(&candidates, &segments), vec![120, 120]);
+    }
+
+    #[test]
+    fn test_expunge_deletes_candidates() {
+        let segments = vec![
+            create_random_segment_meta(100),
+            create_random_segment_meta(100).with_delete_meta(10, 1),
+            create_random_segment_meta(100).with_delete_meta(50, 1),
+        ];
+        let candidates = expunge_deletes_candidates(&segments, 0.2);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0, vec![segments[2].id()]);
+        assert_eq!(expunge_deletes_candidates(&segments, 0.0).len(), 2);
+    }
+}
@@ -3,26 +3,37 @@ use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
 
-use common::BitSet;
+use columnar::{ColumnValueUpdates, MonotonicallyMappableToU64};
+use common::{BinarySerializable, BitSet};
 use smallvec::smallvec;
 
 use super::operation::{AddOperation, UserOperation};
-use super::segment_updater::SegmentUpdater;
+use super::segment_updater::{delete_prepared_metas, SegmentUpdater};
 use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
-use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
+use crate::core::PREPARED_META_FILEPATH;
+use crate::directory::error::QuotaExceededError;
+use crate::directory::{Directory, DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
-use crate::fastfield::write_alive_bitset;
+use crate::fastfield::{write_alive_bitset, FastValue};
 use crate::index::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::expiration::expired_docs_query;
+use crate::indexer::force_merge::{expunge_deletes_candidates, force_merge_candidates};
 use crate::indexer::index_writer_status::IndexWriterStatus;
-use crate::indexer::operation::DeleteOperation;
+use crate::indexer::memory_accounting::MemoryAccounting;
+use crate::indexer::operation::{DeleteOperation, FastFieldUpdate};
+use crate::indexer::segment_writer::sort_segment;
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::{
+    MergeCandidate, MergePolicy, MergeSchedulerSettings, SegmentEntry, SegmentWriter,
+};
 use crate::query::{EnableScoring, Query, TermQuery};
 use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
-use crate::{FutureResult, Opstamp};
+use crate::schema::{
+    value_type_to_column_type, Field, FieldType, IndexRecordOption, TantivyDocument, Term,
+};
+use crate::{telemetry, DateTime, FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
 // in the `memory_arena` goes below MARGIN_IN_BYTES.
@@ -54,6 +65,16 @@ pub struct IndexWriterOptions {
     /// When an indexer thread has buffered this much data in memory
     /// it will flush the segment to disk (although this is not searchable until commit is called.)
     memory_budget_per_thread: usize,
+    /// The memory budget shared by all of the indexer threads, if any.
+    ///
+    /// When the indexer threads have buffered this much data in memory altogether, they
+    /// flush their segments to disk, and adding documents blocks until the memory used
+    /// by the segments being flushed is released.
+    /// See [`IndexWriter::try_add_document`] for a non-blocking alternative.
+    ///
+    /// It cannot be lower than `memory_budget_per_thread`. Note that each indexer thread
+    /// preallocates up to a third of its own budget.
+    memory_budget: Option<usize>,
     #[builder(default = 1)]
     /// The number of indexer worker threads to use.
     num_worker_threads: usize,
@@ -62,6 +83,16 @@ pub struct IndexWriterOptions {
     num_merge_threads: usize,
 }
 
+/// Result of [`IndexWriter::try_add_document`].
+#[derive(Debug)]
+pub enum TryAddDocument<D> {
+    /// The document was added, with the given opstamp.
+    Added(Opstamp),
+    /// The document was not added, because the memory budget of the `IndexWriter` is
+    /// exceeded. It should be added again later.
+    Pending(D),
+}
+
 /// `IndexWriter` is the user entry-point to add document to an index.
 ///
 /// It manages a small number of indexing thread, as well as a shared
@@ -79,6 +110,8 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     workers_join_handle: Vec<JoinHandle<crate::Result<()>>>,
 
+    memory_accounting: Arc<MemoryAccounting>,
+
     index_writer_status: IndexWriterStatus<D>,
     operation_sender: AddBatchSender<D>,
 
@@ -98,6 +131,7 @@ fn compute_deleted_bitset(
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &DocToOpstampMapping,
     target_opstamp: Opstamp,
+    fast_field_updates: &mut ColumnValueUpdates,
 ) -> crate::Result<bool> {
     let mut might_have_changed = false;
     while let Some(delete_op) = delete_cursor.get() {
@@ -112,8 +146,17 @@ fn compute_deleted_bitset(
             .for_each_no_score(segment_reader, &mut |docs_matching_delete_query| {
                 for doc_matching_delete_query in docs_matching_delete_query.iter().cloned() {
                     if doc_opstamps.is_deleted(doc_matching_delete_query, delete_op.opstamp) {
-                        alive_bitset.remove(doc_matching_delete_query);
-                        might_have_changed = true;
+                        if let Some(update) = &delete_op.fast_field_update {
+                            fast_field_updates.insert(
+                                &update.column_name,
+                                update.column_type,
+                                doc_matching_delete_query,
+                                update.value,
+                            );
+                        } else {
+                            alive_bitset.remove(doc_matching_delete_query);
+                            might_have_changed = true;
+                        }
                     }
                 }
             })?;
@@ -122,6 +165,20 @@ fn compute_deleted_bitset(
     Ok(might_have_changed)
 }
 
+/// Writes a new fast field updates file for the segment, containing all of the
+/// `fast_field_updates`, and returns the segment with its updated meta.
+fn write_fast_field_updates(
+    segment: Segment,
+    fast_field_updates: &ColumnValueUpdates,
+    opstamp: Opstamp,
+) -> crate::Result<Segment> {
+    let mut segment = segment.with_update_meta(opstamp);
+    let mut updates_file = segment.open_write(SegmentComponent::FastFieldUpdates)?;
+    fast_field_updates.serialize(&mut updates_file)?;
+    updates_file.terminate()?;
+    Ok(segment)
+}
+
 /// Advance delete for the given segment up to the target opstamp.
 ///
 /// Note that there are no guarantee that the resulting `segment_entry` delete_opstamp
@@ -153,12 +210,14 @@ pub(crate) fn advance_deletes(
 
     let num_deleted_docs_before = segment.meta().num_deleted_docs();
 
+    let mut new_fast_field_updates = ColumnValueUpdates::default();
     compute_deleted_bitset(
         &mut alive_bitset,
         &segment_reader,
         segment_entry.delete_cursor(),
         &DocToOpstampMapping::None,
         target_opstamp,
+        &mut new_fast_field_updates,
     )?;
 
     if let Some(seg_alive_bitset) = segment_reader.alive_bitset() {
@@ -175,6 +234,14 @@ pub(crate) fn advance_deletes(
         alive_doc_file.terminate()?;
     }
 
+    if !new_fast_field_updates.is_empty() {
+        // There are new fast field updates. The new updates file also needs to contain the
+        // updates of the previous one.
+        let mut fast_field_updates = segment_reader.fast_fields().columnar().updates().clone();
+        fast_field_updates.merge(&new_fast_field_updates);
+        segment = write_fast_field_updates(segment, &fast_field_updates, target_opstamp)?;
+    }
+
     segment_entry.set_meta(segment.meta().clone());
     Ok(())
 }
@@ -184,14 +251,18 @@ fn index_documents<D: Document>(
     segment: Segment,
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
+    memory_accounting: &Arc<MemoryAccounting>,
     mut delete_cursor: DeleteCursor,
 ) -> crate::Result<()> {
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
+    // The memory of the segment is accounted for until it is flushed.
+    let mut memory_tracker = memory_accounting.track_segment();
     for document_group in grouped_document_iterator {
         for doc in document_group {
             segment_writer.add_document(doc)?;
         }
         let mem_usage = segment_writer.mem_usage();
+        memory_tracker.set_num_bytes(mem_usage);
         if mem_usage >= memory_budget - MARGIN_IN_BYTES {
             info!(
                 "Buffer limit reached, flushing segment with maxdoc={}.",
@@ -199,7 +270,15 @@ fn index_documents<D: Document>(
             );
             break;
         }
+        if memory_accounting.is_over_budget() {
+            info!(
+                "Global memory budget exceeded, flushing segment with maxdoc={}.",
+                segment_writer.max_doc()
+            );
+            break;
+        }
     }
+    memory_tracker.start_flush();
 
     if !segment_updater.is_alive() {
         return Ok(());
@@ -213,9 +292,11 @@ fn index_documents<D: Document>(
 
     let doc_opstamps: Vec<Opstamp> = segment_writer.finalize()?;
 
-    let segment_with_max_doc = segment.with_max_doc(max_doc);
+    let (mut segment_with_max_doc, doc_opstamps) =
+        sort_segment(segment.with_max_doc(max_doc), doc_opstamps)?;
 
-    let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
+    let alive_bitset_opt =
+        apply_deletes(&mut segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
 
     let meta = segment_with_max_doc.meta().clone();
     meta.untrack_temp_docstore();
@@ -226,8 +307,11 @@ fn index_documents<D: Document>(
 }
 
 /// `doc_opstamps` is required to be non-empty.
+///
+/// Fast field updates are written right away, in which case `segment` is
+/// replaced by the segment with its updated meta.
 fn apply_deletes(
-    segment: &Segment,
+    segment: &mut Segment,
     delete_cursor: &mut DeleteCursor,
     doc_opstamps: &[Opstamp],
 ) -> crate::Result<Option<BitSet>> {
@@ -248,13 +332,18 @@ fn apply_deletes(
 
     let max_doc = segment.meta().max_doc();
     let mut deleted_bitset = BitSet::with_max_value_and_full(max_doc);
+    let mut fast_field_updates = ColumnValueUpdates::default();
     let may_have_deletes = compute_deleted_bitset(
         &mut deleted_bitset,
         &segment_reader,
         delete_cursor,
         &doc_to_opstamps,
         max_doc_opstamp,
+        &mut fast_field_updates,
     )?;
+    if !fast_field_updates.is_empty() {
+        *segment = write_fast_field_updates(segment.clone(), &fast_field_updates, max_doc_opstamp)?;
+    }
     Ok(if may_have_deletes {
         Some(deleted_bitset)
     } else {
@@ -295,6 +384,15 @@ impl<D: Document> IndexWriter<D> {
             );
             return Err(TantivyError::InvalidArgument(err_msg));
         }
+        if options
+            .memory_budget
+            .is_some_and(|memory_budget| memory_budget < options.memory_budget_per_thread)
+        {
+            let err_msg = "The memory budget of the index writer cannot be lower than the memory \
+                           budget per thread"
+                .to_string();
+            return Err(TantivyError::InvalidArgument(err_msg));
+        }
         if options.num_worker_threads == 0 {
             let err_msg = "At least one worker thread is required, got 0".to_string();
             return Err(TantivyError::InvalidArgument(err_msg));
@@ -326,6 +424,8 @@ impl<D: Document> IndexWriter<D> {
 
             segment_updater,
 
+            memory_accounting: Arc::new(MemoryAccounting::new(options.memory_budget)),
+
             workers_join_handle: vec![],
 
             delete_queue,
@@ -420,6 +520,7 @@ impl<D: Document> IndexWriter<D> {
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.options.memory_budget_per_thread;
+        let memory_accounting = self.memory_accounting.clone();
         let index = self.index.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
@@ -452,6 +553,7 @@ impl<D: Document> IndexWriter<D> {
                         index.new_segment(),
                         &mut document_iterator,
                         &segment_updater,
+                        &memory_accounting,
                         delete_cursor.clone(),
                     )?;
                 }
@@ -471,6 +573,25 @@ impl<D: Document> IndexWriter<D> {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Accessor to the settings of the scheduling of the merges.
+    pub fn merge_scheduler_settings(&self) -> MergeSchedulerSettings {
+        self.segment_updater.get_merge_scheduler_settings()
+    }
+
+    /// Sets the settings of the scheduling of the merges.
+    ///
+    /// The settings apply right away, including to the running merges for the
+    /// write throughput.
+    ///
+    /// Returns an error if the settings are invalid.
+    pub fn set_merge_scheduler_settings(
+        &self,
+        merge_scheduler_settings: MergeSchedulerSettings,
+    ) -> crate::Result<()> {
+        self.segment_updater
+            .set_merge_scheduler_settings(merge_scheduler_settings)
+    }
+
     fn start_workers(&mut self) -> crate::Result<()> {
         for _ in 0..self.options.num_worker_threads {
             self.add_indexing_worker()?;
@@ -538,6 +659,84 @@ impl<D: Document> IndexWriter<D> {
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Merges the committed segments down to at most `max_num_segments` segments.
+    ///
+    /// The segments are grouped so that the resulting segments have similar sizes.
+    /// Segments that are already being merged are left out.
+    ///
+    /// Blocks until the merges are done, and returns the metas of the new segments.
+    pub fn force_merge(&mut self, max_num_segments: usize) -> crate::Result<Vec<SegmentMeta>> {
+        self.force_merge_with_options(max_num_segments, None)
+    }
+
+    /// Same as [`IndexWriter::force_merge`], but never produces segments with more than
+    /// `max_segment_num_docs` documents.
+    ///
+    /// The index may therefore end up with more than `max_num_segments` segments.
+    /// Segments that already exceed `max_segment_num_docs` are left as is.
+    pub fn force_merge_with_max_segment_num_docs(
+        &mut self,
+        max_num_segments: usize,
+        max_segment_num_docs: u32,
+    ) -> crate::Result<Vec<SegmentMeta>> {
+        self.force_merge_with_options(max_num_segments, Some(max_segment_num_docs))
+    }
+
+    fn force_merge_with_options(
+        &mut self,
+        max_num_segments: usize,
+        max_segment_num_docs: Option<u32>,
+    ) -> crate::Result<Vec<SegmentMeta>> {
+        if max_num_segments == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "A force merge requires at least one target segment".to_string(),
+            ));
+        }
+        let (committed_segments, _) = self.segment_updater.get_mergeable_segments();
+        let merge_candidates =
+            force_merge_candidates(&committed_segments, max_num_segments, max_segment_num_docs);
+        self.run_merges(merge_candidates)
+    }
+
+    /// Rewrites the committed segments whose ratio of deleted documents exceeds
+    /// `del_docs_ratio_threshold`, in order to reclaim the space used by their deleted
+    /// documents.
+    ///
+    /// The other segments are left untouched.
+    ///
+    /// Blocks until the merges are done, and returns the metas of the new segments.
+    pub fn expunge_deletes(
+        &mut self,
+        del_docs_ratio_threshold: f32,
+    ) -> crate::Result<Vec<SegmentMeta>> {
+        if !(0.0..1.0).contains(&del_docs_ratio_threshold) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The ratio of deleted documents should be within [0, 1), got \
+                 {del_docs_ratio_threshold}"
+            )));
+        }
+        let (committed_segments, _) = self.segment_updater.get_mergeable_segments();
+        let merge_candidates =
+            expunge_deletes_candidates(&committed_segments, del_docs_ratio_threshold);
+        self.run_merges(merge_candidates)
+    }
+
+    /// Starts all of the merges, and waits for them to complete.
+    fn run_merges(
+        &mut self,
+        merge_candidates: Vec<MergeCandidate>,
+    ) -> crate::Result<Vec<SegmentMeta>> {
+        let merge_results: Vec<FutureResult<Option<SegmentMeta>>> = merge_candidates
+            .into_iter()
+            .map(|merge_candidate| self.merge(&merge_candidate.0))
+            .collect();
+        let mut merged_segment_metas = Vec::new();
+        for merge_result in merge_results {
+            merged_segment_metas.extend(merge_result.wait()?);
+        }
+        Ok(merged_segment_metas)
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -606,6 +805,8 @@ impl<D: Document> IndexWriter<D> {
     /// call
     /// * `.commit()`: to accept this commit
     /// * `.abort()`: to cancel this commit.
+    /// * `.persist()`: to make this commit durable without making it visible, as the first phase of
+    ///   a two-phase commit.
     ///
     /// In the current implementation, [`PreparedCommit`] borrows
     /// the [`IndexWriter`] mutably so we are guaranteed that no new
@@ -628,6 +829,25 @@ impl<D: Document> IndexWriter<D> {
         // committed segments.
         info!("Preparing commit");
 
+        if self.index.directory().exists(&PREPARED_META_FILEPATH)? {
+            return Err(TantivyError::InvalidArgument(
+                "A prepared commit is pending, it needs to be committed or rolled back first"
+                    .to_string(),
+            ));
+        }
+
+        // The segments being built are flushed as part of the commit. Their size in memory
+        // is used as an estimate of their size once serialized.
+        if let Some(num_bytes_available) = self.index.directory().available_bytes() {
+            let num_bytes_requested = self.memory_accounting.num_bytes() as u64;
+            if num_bytes_requested > num_bytes_available {
+                return Err(TantivyError::QuotaExceeded(QuotaExceededError {
+                    num_bytes_available,
+                    num_bytes_requested,
+                }));
+            }
+        }
+
         // this will drop the current document channel
         // and recreate a new one.
         self.recreate_document_channel();
@@ -642,6 +862,11 @@ impl<D: Document> IndexWriter<D> {
             self.add_indexing_worker()?;
         }
 
+        // The documents that expired are deleted as part of the commit.
+        if let Some(expired_docs_query) = expired_docs_query(&self.index) {
+            self.delete_query(Box::new(expired_docs_query))?;
+        }
+
         let commit_opstamp = self.stamper.stamp();
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
         info!("Prepared commit {commit_opstamp}");
@@ -665,6 +890,44 @@ impl<D: Document> IndexWriter<D> {
         self.prepare_commit()?.commit()
     }
 
+    /// Publishes the pending prepared commit with the given opstamp.
+    ///
+    /// This is the second phase of a two-phase commit started with
+    /// [`PreparedCommit::persist`]. The prepared commit may have been persisted
+    /// by a previous `IndexWriter`, before a crash or a restart. In that case,
+    /// the documents added to this `IndexWriter` since its creation are discarded, as
+    /// with [`IndexWriter::rollback`].
+    pub fn commit_prepared(&mut self, opstamp: Opstamp) -> crate::Result<Opstamp> {
+        info!("Committing prepared commit {opstamp}");
+        let is_persisted_by_self = self
+            .segment_updater
+            .schedule_commit_prepared(opstamp)
+            .wait()?;
+        if !is_persisted_by_self {
+            // The segments of this index writer do not include the ones of the commit.
+            self.rollback()?;
+        }
+        Ok(opstamp)
+    }
+
+    /// Discards the pending prepared commit with the given opstamp.
+    ///
+    /// Like [`IndexWriter::rollback`], this also discards the documents added since the
+    /// prepared commit.
+    pub fn rollback_prepared(&mut self, opstamp: Opstamp) -> crate::Result<Opstamp> {
+        info!("Rolling back prepared commit {opstamp}");
+        if self.index.load_prepared_metas()?.map(|metas| metas.opstamp) != Some(opstamp) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "There is no pending prepared commit with opstamp {opstamp}"
+            )));
+        }
+        // The segment updater is killed first, so that it does not write the prepared meta
+        // file anymore.
+        let committed_opstamp = self.rollback()?;
+        delete_prepared_metas(self.index.directory())?;
+        Ok(committed_opstamp)
+    }
+
     pub(crate) fn segment_updater(&self) -> &SegmentUpdater {
         &self.segment_updater
     }
@@ -701,6 +964,78 @@ impl<D: Document> IndexWriter<D> {
         let delete_operation = DeleteOperation {
             opstamp,
             target: weight,
+            fast_field_update: None,
+        };
+        self.delete_queue.push(delete_operation);
+        Ok(opstamp)
+    }
+
+    /// Updates the value of a fast field, for all documents containing a given term,
+    /// without reindexing them.
+    ///
+    /// This is typically useful to maintain a counter, like the popularity of a document.
+    ///
+    /// Only the fast field (the column) is updated: the stored value and the
+    /// terms of the inverted index of the documents are left unchanged.
+    /// Only documents that have exactly one value for the field are affected.
+    ///
+    /// Like deletes, the update only affects documents that were added in previous commits,
+    /// and documents that were added previously in the same commit, and will be visible
+    /// only after calling `commit()`.
+    ///
+    /// Returns an `Err` if the field is not a fast field of type `T`, or if the index is
+    /// sorted by this field.
+    pub fn update_fast_field<T: FastValue>(
+        &self,
+        term: Term,
+        field: Field,
+        value: T,
+    ) -> crate::Result<Opstamp> {
+        let schema = self.index.schema();
+        let field_entry = schema.get_field_entry(field);
+        let field_name = field_entry.name();
+        if !field_entry.is_fast() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field {field_name:?} is not a fast field."
+            )));
+        }
+        let value_type = field_entry.field_type().value_type();
+        if value_type != T::to_type() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field {field_name:?} is of type {value_type:?}, cannot update it with a value of \
+                 type {:?}.",
+                T::to_type()
+            )));
+        }
+        if self
+            .index
+            .settings()
+            .sort_by_fields()
+            .any(|sort_by_field| sort_by_field.field == field_name)
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Field {field_name:?} is used to sort the index and cannot be updated."
+            )));
+        }
+        let value = match field_entry.field_type() {
+            FieldType::Date(date_options) => DateTime::from_u64(value.to_u64())
+                .truncate(date_options.get_precision())
+                .to_u64(),
+            _ => value.to_u64(),
+        };
+        let column_type = value_type_to_column_type(value_type)
+            .expect("fast value types are always associated to a column type");
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let weight = query.weight(EnableScoring::disabled_from_schema(&schema))?;
+        let opstamp = self.stamper.stamp();
+        let delete_operation = DeleteOperation {
+            opstamp,
+            target: weight,
+            fast_field_update: Some(FastFieldUpdate {
+                column_name: field_name.to_string(),
+                column_type,
+                value,
+            }),
         };
         self.delete_queue.push(delete_operation);
         Ok(opstamp)
@@ -725,11 +1060,61 @@ impl<D: Document> IndexWriter<D> {
     /// be used by the client to align commits with its own
     /// document queue.
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
-        let opstamp = self.stamper.stamp();
-        self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
+        let opstamp = self.stamp();
+        self.add_document_with_opstamp(opstamp, document)?;
         Ok(opstamp)
     }
 
+    /// Reserves the opstamp of the next operation.
+    pub(crate) fn stamp(&self) -> Opstamp {
+        self.stamper.stamp()
+    }
+
+    /// Adds a document with an opstamp reserved by [`IndexWriter::stamp`].
+    pub(crate) fn add_document_with_opstamp(
+        &self,
+        opstamp: Opstamp,
+        document: D,
+    ) -> crate::Result<()> {
+        self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])
+    }
+
+    /// Adds a document, unless the memory budget shared by the indexing threads is exceeded.
+    ///
+    /// Contrary to [`IndexWriter::add_document`], this call does not wait for
+    /// memory to be released. If the document cannot be added right away, it is returned
+    /// as [`TryAddDocument::Pending`].
+    ///
+    /// See [`IndexWriterOptions::builder`] to define the memory budget.
+    pub fn try_add_document(&self, document: D) -> crate::Result<TryAddDocument<D>> {
+        if self.memory_accounting.is_pending() {
+            return Ok(TryAddDocument::Pending(document));
+        }
+        self.add_document(document).map(TryAddDocument::Added)
+    }
+
+    /// Returns the number of bytes of memory currently used by the indexing threads.
+    pub fn memory_usage(&self) -> usize {
+        self.memory_accounting.num_bytes()
+    }
+
+    /// Replaces all documents containing a given term by a new document.
+    ///
+    /// This is typically used with a term of a field holding a unique key: any prior document
+    /// with the same key is deleted, and the new document is added. If no document contains
+    /// the term, the document is simply added.
+    ///
+    /// The delete and the add are executed as a single group of operations (see
+    /// [`IndexWriter::run`]): the delete affects all of the documents added before,
+    /// including the ones that have not been committed yet, but not the new document.
+    ///
+    /// If the indexing pipeline is full, this call may block.
+    ///
+    /// Like adds and deletes, the upsert will be visible only after calling `commit()`.
+    pub fn upsert_document(&self, term: Term, document: D) -> crate::Result<Opstamp> {
+        self.run([UserOperation::Delete(term), UserOperation::Add(document)])
+    }
+
     /// Gets a range of stamps from the stamper and "pops" the last stamp
     /// from the range returning a tuple of the last optstamp and the popped
     /// range.
@@ -782,6 +1167,7 @@ impl<D: Document> IndexWriter<D> {
                     let delete_operation = DeleteOperation {
                         opstamp,
                         target: weight,
+                        fast_field_update: None,
                     };
                     self.delete_queue.push(delete_operation);
                 }
@@ -796,7 +1182,10 @@ impl<D: Document> IndexWriter<D> {
     }
 
     fn send_add_documents_batch(&self, add_ops: AddBatch<D>) -> crate::Result<()> {
+        self.memory_accounting.wait_for_memory();
+        let num_docs = add_ops.len();
         if self.index_writer_status.is_alive() && self.operation_sender.send(add_ops).is_ok() {
+            telemetry::record_docs_added(num_docs);
             Ok(())
         } else {
             Err(error_in_index_worker_thread("An index writer was killed."))
@@ -818,6 +1207,7 @@ impl<D: Document> Drop for IndexWriter<D> {
 mod tests {
     use std::collections::{HashMap, HashSet};
     use std::net::Ipv6Addr;
+    use std::ops::Bound;
 
     use columnar::{Column, MonotonicallyMappableToU128};
     use itertools::Itertools;
@@ -826,10 +1216,13 @@ mod tests {
     use super::super::operation::UserOperation;
     use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
+    use crate::directory::RamDirectory;
     use crate::error::*;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
-    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
-    use crate::query::{QueryParser, TermQuery};
+    use crate::indexer::{
+        IndexWriterOptions, MergeSchedulerSettings, NoMergePolicy, TryAddDocument,
+    };
+    use crate::query::{QueryParser, RangeQuery, TermQuery};
     use crate::schema::{
         self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, JsonObjectOptions,
         NumericOptions, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED, STORED,
@@ -837,8 +1230,8 @@ mod tests {
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
     use crate::{
-        DateTime, DocAddress, Index, IndexSettings, IndexWriter, ReloadPolicy, TantivyDocument,
-        Term,
+        DateTime, DocAddress, Index, IndexSettings, IndexSortByField, IndexWriter, Order,
+        ReloadPolicy, TantivyDocument, Term,
     };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
@@ -1013,6 +1406,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_merge_scheduler_settings() {
+        let schema_builder = schema::Schema::builder();
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        let merge_scheduler_settings = MergeSchedulerSettings {
+            max_concurrent_merges: 1,
+            max_merge_write_bytes_per_sec: Some(10_000_000),
+        };
+        index_writer
+            .set_merge_scheduler_settings(merge_scheduler_settings)
+            .unwrap();
+        assert_eq!(
+            index_writer.merge_scheduler_settings(),
+            merge_scheduler_settings
+        );
+    }
+
+    #[test]
+    fn test_merge_with_throttled_writes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_merge_scheduler_settings(MergeSchedulerSettings {
+            max_concurrent_merges: 1,
+            max_merge_write_bytes_per_sec: Some(1_000_000),
+        })?;
+        for i in 0..3 {
+            index_writer.add_document(doc!(text_field => format!("doc {i}")))?;
+            index_writer.commit()?;
+        }
+        let segment_ids = index.searchable_segment_ids()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        assert_eq!(merged_segment_meta.num_docs(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_force_merge() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for num_docs in [1, 2, 3, 4, 10] {
+            for _ in 0..num_docs {
+                index_writer.add_document(doc!(id_field => 1u64))?;
+            }
+            index_writer.commit()?;
+        }
+        assert!(index_writer.force_merge(0).is_err());
+        assert!(index_writer.force_merge(5)?.is_empty());
+        let merged_segment_metas = index_writer.force_merge_with_max_segment_num_docs(1, 9)?;
+        assert_eq!(merged_segment_metas.len(), 1);
+        assert_eq!(merged_segment_metas[0].num_docs(), 9);
+        let mut num_docs_per_segment: Vec<u32> = index
+            .searchable_segment_metas()?
+            .iter()
+            .map(|segment_meta| segment_meta.num_docs())
+            .collect();
+        num_docs_per_segment.sort();
+        assert_eq!(num_docs_per_segment, vec![1, 9, 10]);
+        let merged_segment_metas = index_writer.force_merge(1)?;
+        assert_eq!(merged_segment_metas.len(), 1);
+        assert_eq!(index.searchable_segment_metas()?.len(), 1);
+        assert_eq!(index.reader()?.searcher().num_docs(), 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expunge_deletes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for segment_ord in 0..3u64 {
+            for id in 0..10u64 {
+                index_writer.add_document(doc!(id_field => segment_ord * 10 + id))?;
+            }
+            index_writer.commit()?;
+        }
+        // Deletes 1 document from the first segment, and 5 from the second one.
+        for id in [0u64, 10, 11, 12, 13, 14] {
+            index_writer.delete_term(Term::from_field_u64(id_field, id));
+        }
+        index_writer.commit()?;
+        assert!(index_writer.expunge_deletes(1.0).is_err());
+        let merged_segment_metas = index_writer.expunge_deletes(0.2)?;
+        assert_eq!(merged_segment_metas.len(), 1);
+        assert_eq!(merged_segment_metas[0].num_docs(), 5);
+        assert!(!merged_segment_metas[0].has_deletes());
+        let num_deleted_docs: u32 = index
+            .searchable_segment_metas()?
+            .iter()
+            .map(|segment_meta| segment_meta.num_deleted_docs())
+            .sum();
+        assert_eq!(num_deleted_docs, 1);
+        assert_eq!(index.reader()?.searcher().num_docs(), 24);
+        Ok(())
+    }
+
     #[test]
     fn test_lockfile_released_on_drop() {
         let schema_builder = schema::Schema::builder();
@@ -1208,6 +1705,129 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_persist_prepared_commit() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        let mut prepared_commit = index_writer.prepare_commit()?;
+        prepared_commit.set_payload("transaction 1");
+        let opstamp = prepared_commit.persist()?;
+        let prepared_metas = index.load_prepared_metas()?.unwrap();
+        assert_eq!(prepared_metas.opstamp, opstamp);
+        assert_eq!(prepared_metas.payload.as_deref(), Some("transaction 1"));
+        // The prepared commit is not visible.
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 0);
+        // No other commit can be prepared in the meantime.
+        index_writer.add_document(doc!(text_field => "b"))?;
+        assert!(index_writer.commit().is_err());
+        assert!(index_writer.commit_prepared(opstamp + 1).is_err());
+        assert_eq!(index_writer.commit_prepared(opstamp)?, opstamp);
+        assert!(index.load_prepared_metas()?.is_none());
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(
+            index.load_metas()?.payload.as_deref(),
+            Some("transaction 1")
+        );
+        // The documents added after the prepared commit are part of the next commit.
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_prepared_commit() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text_field => "b"))?;
+        let opstamp = index_writer.prepare_commit()?.persist()?;
+        index_writer.rollback_prepared(opstamp)?;
+        assert!(index.load_prepared_metas()?.is_none());
+        assert!(index_writer.commit_prepared(opstamp).is_err());
+        index_writer.add_document(doc!(text_field => "c"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        let term_b = Term::from_field_text(text_field, "b");
+        assert_eq!(reader.searcher().doc_freq(&term_b)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_while_commit_is_prepared() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let directory = RamDirectory::create();
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            IndexSettings::default(),
+        )?;
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.add_document(doc!(text_field => "a"))?;
+            index_writer.commit()?;
+            index_writer.add_document(doc!(text_field => "b"))?;
+            index_writer.commit()?;
+            let committed_segment_ids = index.searchable_segment_ids()?;
+            index_writer.add_document(doc!(text_field => "c"))?;
+            index_writer.prepare_commit()?.persist()?;
+            // The merge only updates the prepared meta file. The segments of `meta.json`
+            // must not be garbage collected.
+            index_writer.merge(&committed_segment_ids).wait()?;
+            index_writer.garbage_collect_files().wait()?;
+        }
+        // Reopening the index from `meta.json`, as after a crash.
+        let index = Index::open(directory)?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+        let term_b = Term::from_field_text(text_field, "b");
+        assert_eq!(
+            searcher.search(&TermQuery::new(term_b, IndexRecordOption::Basic), &Count)?,
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_commit_prepared_after_restart() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let opstamp = {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text_field => "a"))?;
+            index_writer.add_document(doc!(text_field => "b"))?;
+            index_writer.prepare_commit()?.persist()?
+        };
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        // The segments of the prepared commit survive the garbage collection.
+        index_writer.garbage_collect_files().wait()?;
+        assert_eq!(index.load_prepared_metas()?.unwrap().opstamp, opstamp);
+        index_writer.commit_prepared(opstamp)?;
+        let reader = index.reader()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        index_writer.add_document(doc!(text_field => "c"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_prepare_but_rollback() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -2550,6 +3170,271 @@ mod tests {
         Ok(())
     }
 
+    fn fast_field_values_by_id(index: &Index, field_name: &str) -> Vec<(u64, Vec<u64>)> {
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let mut values_by_id = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let id_column = segment_reader.fast_fields().u64("id").unwrap();
+            let column = segment_reader.fast_fields().u64(field_name).unwrap();
+            for doc in segment_reader.doc_ids_alive() {
+                let id = id_column.first(doc).unwrap();
+                values_by_id.push((id, column.values_for_doc(doc).collect()));
+            }
+        }
+        values_by_id.sort();
+        values_by_id
+    }
+
+    #[test]
+    fn test_update_fast_field() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let popularity_field = schema_builder.add_u64_field("popularity", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id_field=>1u64, popularity_field=>10u64))?;
+        index_writer.add_document(doc!(id_field=>2u64, popularity_field=>20u64))?;
+        index_writer.add_document(doc!(id_field=>3u64))?;
+        index_writer
+            .add_document(doc!(id_field=>4u64, popularity_field=>40u64, popularity_field=>41u64))?;
+        index_writer.commit()?;
+
+        // Committed segment
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 1),
+            popularity_field,
+            11u64,
+        )?;
+        // Documents with no value or several values are left unchanged.
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 3),
+            popularity_field,
+            31u64,
+        )?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 4),
+            popularity_field,
+            42u64,
+        )?;
+        // Segment being indexed. The update does not affect the document added after it.
+        index_writer.add_document(doc!(id_field=>5u64, popularity_field=>50u64))?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 5),
+            popularity_field,
+            51u64,
+        )?;
+        index_writer.add_document(doc!(id_field=>6u64, popularity_field=>60u64))?;
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 6),
+            popularity_field,
+            61u64,
+        )?;
+        index_writer.add_document(doc!(id_field=>6u64, popularity_field=>62u64))?;
+        index_writer.commit()?;
+
+        let expected = vec![
+            (1, vec![11]),
+            (2, vec![20]),
+            (3, vec![]),
+            (4, vec![40, 41]),
+            (5, vec![51]),
+            (6, vec![61]),
+            (6, vec![62]),
+        ];
+        assert_eq!(fast_field_values_by_id(&index, "popularity"), expected);
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let count_in_range = |lower: u64, upper: u64| {
+            let query = RangeQuery::new(
+                Bound::Included(Term::from_field_u64(popularity_field, lower)),
+                Bound::Included(Term::from_field_u64(popularity_field, upper)),
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count_in_range(10, 10), 0);
+        assert_eq!(count_in_range(11, 11), 1);
+        assert_eq!(count_in_range(10, 20), 2);
+        assert_eq!(count_in_range(60, 62), 2);
+
+        // Updates are applied on top of the previous ones, and survive merges.
+        index_writer.update_fast_field(
+            Term::from_field_u64(id_field, 2),
+            popularity_field,
+            21u64,
+        )?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        let expected = vec![
+            (1, vec![11]),
+            (2, vec![21]),
+            (3, vec![]),
+            (4, vec![40, 41]),
+            (5, vec![51]),
+            (6, vec![61]),
+            (6, vec![62]),
+        ];
+        assert_eq!(fast_field_values_by_id(&index, "popularity"), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_fast_field_with_deletes() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let score_field = schema_builder.add_f64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for id in 0u64..4 {
+            index_writer.add_document(doc!(id_field=>id, score_field=>id as f64))?;
+        }
+        index_writer.commit()?;
+        index_writer.update_fast_field(Term::from_field_u64(id_field, 1), score_field, 1.5f64)?;
+        index_writer.delete_term(Term::from_field_u64(id_field, 2));
+        index_writer.commit()?;
+        index_writer.update_fast_field(Term::from_field_u64(id_field, 3), score_field, 3.5f64)?;
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let segment_reader = searcher.segment_reader(0);
+        let id_column = segment_reader.fast_fields().u64("id")?;
+        let score_column = segment_reader.fast_fields().f64("score")?;
+        let scores: Vec<(u64, f64)> = segment_reader
+            .doc_ids_alive()
+            .map(|doc| {
+                (
+                    id_column.first(doc).unwrap(),
+                    score_column.first(doc).unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(scores, [(0, 0.0), (1, 1.5), (3, 3.5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_upsert_document() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let value_field = schema_builder.add_u64_field("value", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let upsert = |index_writer: &IndexWriter, id: &str, value: u64| {
+            index_writer
+                .upsert_document(
+                    Term::from_field_text(id_field, id),
+                    doc!(id_field=>id, value_field=>value),
+                )
+                .unwrap()
+        };
+        upsert(&index_writer, "a", 1);
+        upsert(&index_writer, "b", 2);
+        // Replaces a document of the in-memory segment.
+        upsert(&index_writer, "a", 3);
+        index_writer.commit()?;
+        // Replaces a committed document, twice in the same commit.
+        upsert(&index_writer, "b", 4);
+        upsert(&index_writer, "b", 5);
+        upsert(&index_writer, "c", 6);
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        let mut values = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let value_column = segment_reader.fast_fields().u64("value")?;
+            values.extend(
+                segment_reader
+                    .doc_ids_alive()
+                    .map(|doc| value_column.first(doc).unwrap()),
+            );
+        }
+        values.sort();
+        assert_eq!(values, [3, 5, 6]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_fast_field_invalid() {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let stored_field = schema_builder.add_u64_field("stored", STORED);
+        let score_field = schema_builder.add_f64_field("score", FAST);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "id".to_string(),
+                    order: Order::Asc,
+                }),
+                ..Default::default()
+            })
+            .create_in_ram()
+            .unwrap();
+        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        let term = Term::from_field_u64(id_field, 1);
+        let err = index_writer
+            .update_fast_field(term.clone(), stored_field, 1u64)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Field \"stored\" is not a fast field.'"
+        );
+        let err = index_writer
+            .update_fast_field(term.clone(), score_field, 1u64)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Field \"score\" is of type F64, cannot update it \
+             with a value of type U64.'"
+        );
+        let err = index_writer
+            .update_fast_field(term, id_field, 1u64)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Field \"id\" is used to sort the index and cannot \
+             be updated.'"
+        );
+    }
+
+    #[test]
+    fn test_writer_global_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(2)
+            .memory_budget_per_thread(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .memory_budget(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..100_000 {
+            let doc = doc!(text_field => format!("term{i} common"));
+            if let TryAddDocument::Pending(doc) = index_writer.try_add_document(doc)? {
+                // Adding the document blocks until some memory is released.
+                index_writer.add_document(doc)?;
+            }
+        }
+        index_writer.commit()?;
+        assert_eq!(index_writer.memory_usage(), 0);
+        // The segments were flushed long before reaching the memory budget per thread.
+        assert!(index.searchable_segment_ids()?.len() > 2);
+        assert_eq!(index.reader()?.searcher().num_docs(), 100_000);
+        Ok(())
+    }
+
     #[test]
     fn test_writer_options_validation() {
         let mut schema_builder = Schema::builder();
@@ -2580,5 +3465,11 @@ mod tests {
             "Writer should reject options with too high memory size"
         );
         assert!(matches!(result, Err(TantivyError::InvalidArgument(_))));
+
+        let opt_with_low_global_memory = IndexWriterOptions::builder()
+            .memory_budget(MEMORY_BUDGET_NUM_BYTES_MIN - 1)
+            .build();
+        let result = index.writer_with_options::<TantivyDocument>(opt_with_low_global_memory);
+        assert!(matches!(result, Err(TantivyError::InvalidArgument(_))));
     }
 }
@@ -0,0 +1,164 @@
+use std::sync::{Arc, Condvar, Mutex};
+
+struct MemoryAccountingState {
+    num_bytes: usize,
+    num_flushing_segments: usize,
+}
+
+/// Accounts for the memory used by the segment writers of all of the indexing threads
+/// of an [`IndexWriter`](crate::IndexWriter), against an optional global memory budget.
+///
+/// When the budget is exceeded, the indexing threads flush their segment, and adding
+/// documents blocks until the memory used by the segments being flushed is released.
+pub(crate) struct MemoryAccounting {
+    memory_budget: Option<usize>,
+    state: Mutex<MemoryAccountingState>,
+    memory_released: Condvar,
+}
+
+impl MemoryAccounting {
+    pub fn new(memory_budget: Option<usize>) -> MemoryAccounting {
+        MemoryAccounting {
+            memory_budget,
+            state: Mutex::new(MemoryAccountingState {
+                num_bytes: 0,
+                num_flushing_segments: 0,
+            }),
+            memory_released: Condvar::new(),
+        }
+    }
+
+    /// Returns the number of bytes used by the segment writers.
+    pub fn num_bytes(&self) -> usize {
+        self.state.lock().unwrap().num_bytes
+    }
+
+    /// Returns true if the segment writers use more memory than the memory budget.
+    pub fn is_over_budget(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        self.is_state_over_budget(&state)
+    }
+
+    fn is_state_over_budget(&self, state: &MemoryAccountingState) -> bool {
+        self.memory_budget
+            .is_some_and(|memory_budget| state.num_bytes > memory_budget)
+    }
+
+    // New documents are only held back while some memory is about to be released.
+    // Otherwise, the indexing threads need new documents to notice that the budget
+    // is exceeded, and flush their segment.
+    fn should_wait(&self, state: &MemoryAccountingState) -> bool {
+        state.num_flushing_segments > 0 && self.is_state_over_budget(state)
+    }
+
+    /// Returns true if adding documents would block.
+    pub fn is_pending(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        self.should_wait(&state)
+    }
+
+    /// Blocks as long as the memory budget is exceeded and segments are being flushed.
+    pub fn wait_for_memory(&self) {
+        let mut state = self.state.lock().unwrap();
+        while self.should_wait(&state) {
+            state = self.memory_released.wait(state).unwrap();
+        }
+    }
+
+    /// Starts tracking the memory usage of a new segment writer.
+    pub fn track_segment(self: &Arc<Self>) -> SegmentMemoryTracker {
+        SegmentMemoryTracker {
+            memory_accounting: self.clone(),
+            num_bytes: 0,
+            is_flushing: false,
+        }
+    }
+}
+
+/// Tracks the memory usage of a segment writer.
+///
+/// The memory is released when the tracker is dropped, once the segment was flushed.
+pub(crate) struct SegmentMemoryTracker {
+    memory_accounting: Arc<MemoryAccounting>,
+    num_bytes: usize,
+    is_flushing: bool,
+}
+
+impl SegmentMemoryTracker {
+    /// Updates the memory usage of the segment writer.
+    pub fn set_num_bytes(&mut self, num_bytes: usize) {
+        let mut state = self.memory_accounting.state.lock().unwrap();
+        state.num_bytes = state.num_bytes - self.num_bytes + num_bytes;
+        self.num_bytes = num_bytes;
+    }
+
+    /// Marks the segment as being flushed.
+    pub fn start_flush(&mut self) {
+        if !self.is_flushing {
+            self.memory_accounting
+                .state
+                .lock()
+                .unwrap()
+                .num_flushing_segments += 1;
+            self.is_flushing = true;
+        }
+    }
+}
+
+impl Drop for SegmentMemoryTracker {
+    fn drop(&mut self) {
+        let mut state = self.memory_accounting.state.lock().unwrap();
+        state.num_bytes -= self.num_bytes;
+        if self.is_flushing {
+            state.num_flushing_segments -= 1;
+        }
+        self.memory_accounting.memory_released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::MemoryAccounting;
+
+    #[test]
+    fn test_memory_accounting() {
+        let memory_accounting = Arc::new(MemoryAccounting::new(Some(1_000)));
+        let mut tracker1 = memory_accounting.track_segment();
+        let mut tracker2 = memory_accounting.track_segment();
+        tracker1.set_num_bytes(600);
+        tracker2.set_num_bytes(300);
+        assert_eq!(memory_accounting.num_bytes(), 900);
+        assert!(!memory_accounting.is_over_budget());
+        tracker2.set_num_bytes(500);
+        assert_eq!(memory_accounting.num_bytes(), 1_100);
+        assert!(memory_accounting.is_over_budget());
+        // Nothing is being flushed: documents should not be held back.
+        assert!(!memory_accounting.is_pending());
+        tracker1.start_flush();
+        assert!(memory_accounting.is_pending());
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(tracker1);
+        });
+        memory_accounting.wait_for_memory();
+        assert_eq!(memory_accounting.num_bytes(), 500);
+        assert!(!memory_accounting.is_pending());
+        handle.join().unwrap();
+        drop(tracker2);
+        assert_eq!(memory_accounting.num_bytes(), 0);
+    }
+
+    #[test]
+    fn test_memory_accounting_without_budget() {
+        let memory_accounting = Arc::new(MemoryAccounting::new(None));
+        let mut tracker = memory_accounting.track_segment();
+        tracker.set_num_bytes(usize::MAX / 2);
+        tracker.start_flush();
+        assert!(!memory_accounting.is_over_budget());
+        assert!(!memory_accounting.is_pending());
+    }
+}
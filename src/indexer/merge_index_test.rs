@@ -7,9 +7,12 @@ mod tests {
     use crate::query::QueryParser;
     use crate::schema::{
         self, BytesOptions, Facet, FacetOptions, IndexRecordOption, NumericOptions,
-        TextFieldIndexing, TextOptions,
+        TextFieldIndexing, TextOptions, Value,
+    };
+    use crate::{
+        DocAddress, DocSet, IndexSettings, IndexSortByField, IndexWriter, Order, TantivyDocument,
+        Term,
     };
-    use crate::{DocAddress, DocSet, IndexSettings, IndexWriter, Term};
 
     fn create_test_index(index_settings: Option<IndexSettings>) -> crate::Result<Index> {
         let mut schema_builder = schema::Schema::builder();
@@ -144,4 +147,215 @@ mod tests {
             assert_eq!(output, vec![1, 3]);
         }
     }
+
+    fn sorted_index_settings(order: Order) -> IndexSettings {
+        IndexSettings {
+            sort_by_field: Some(IndexSortByField {
+                field: "intval".to_string(),
+                order,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn stored_int_vals(index: &Index) -> Vec<u64> {
+        let int_field = index.schema().get_field("intval").unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let segment_reader = searcher.segment_reader(0);
+        (0..segment_reader.max_doc())
+            .map(|doc_id| {
+                let doc: TantivyDocument = searcher.doc(DocAddress::new(0, doc_id)).unwrap();
+                doc.get_first(int_field).unwrap().as_u64().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_sorted_index_desc() {
+        let index = create_test_index(Some(sorted_index_settings(Order::Desc))).unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs(), 6);
+        assert_eq!(segment_reader.max_doc(), 6);
+
+        // fast fields
+        let int_vals = segment_reader.fast_fields().u64("intval").unwrap();
+        let values: Vec<u64> = (0..6).map(|doc| int_vals.first(doc).unwrap()).collect();
+        assert_eq!(values, vec![1_000, 20, 10, 3, 2, 1]);
+        let multi_numbers = segment_reader.fast_fields().u64("multi_numbers").unwrap();
+        let values: Vec<Vec<u64>> = (0..6)
+            .map(|doc| multi_numbers.values_for_doc(doc).collect())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                vec![1001, 1002],
+                vec![20],
+                vec![10, 11],
+                vec![3, 4],
+                vec![2, 3],
+                vec![],
+            ]
+        );
+
+        // doc store
+        assert_eq!(stored_int_vals(&index), vec![1_000, 20, 10, 3, 2, 1]);
+
+        // postings
+        let my_text_field = index.schema().get_field("text_field").unwrap();
+        let do_search = |term: &str| {
+            let query = QueryParser::for_index(&index, vec![my_text_field])
+                .parse_query(term)
+                .unwrap();
+            let top_docs: Vec<(f32, DocAddress)> =
+                searcher.search(&query, &TopDocs::with_limit(3)).unwrap();
+            top_docs.iter().map(|el| el.1.doc_id).collect::<Vec<_>>()
+        };
+        assert_eq!(do_search("some"), vec![3]);
+        assert_eq!(do_search("blubber"), vec![2]);
+        assert_eq!(do_search("biggest"), vec![0]);
+        assert!(do_search("deleteme").is_empty());
+
+        let term_a = Term::from_field_text(my_text_field, "text");
+        let inverted_index = segment_reader.inverted_index(my_text_field).unwrap();
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap()
+            .unwrap();
+        assert_eq!(postings.doc_freq(), 2);
+        assert_eq!(postings.doc(), 3);
+        assert_eq!(postings.term_freq(), 1);
+        let mut output = vec![];
+        postings.positions(&mut output);
+        assert_eq!(output, vec![1]);
+        postings.advance();
+        assert_eq!(postings.doc(), 4);
+        assert_eq!(postings.term_freq(), 2);
+        postings.positions(&mut output);
+        assert_eq!(output, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_merge_sorted_index_asc() {
+        let index = create_test_index(Some(sorted_index_settings(Order::Asc))).unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.num_docs(), 6);
+
+        let int_vals = segment_reader.fast_fields().u64("intval").unwrap();
+        let values: Vec<u64> = (0..6).map(|doc| int_vals.first(doc).unwrap()).collect();
+        assert_eq!(values, vec![1, 2, 3, 10, 20, 1_000]);
+        assert_eq!(stored_int_vals(&index), vec![1, 2, 3, 10, 20, 1_000]);
+
+        let my_text_field = index.schema().get_field("text_field").unwrap();
+        let term_a = Term::from_field_text(my_text_field, "text");
+        let inverted_index = segment_reader.inverted_index(my_text_field).unwrap();
+        let mut postings = inverted_index
+            .read_postings(&term_a, IndexRecordOption::WithFreqsAndPositions)
+            .unwrap()
+            .unwrap();
+        assert_eq!(postings.doc(), 1);
+        assert_eq!(postings.term_freq(), 2);
+        let mut output = vec![];
+        postings.positions(&mut output);
+        assert_eq!(output, vec![1, 3]);
+        postings.advance();
+        assert_eq!(postings.doc(), 2);
+        assert_eq!(postings.term_freq(), 1);
+        postings.positions(&mut output);
+        assert_eq!(output, vec![1]);
+    }
+
+    #[test]
+    fn test_sorted_segments_before_merge() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let int_field = schema_builder
+            .add_u64_field("intval", NumericOptions::default().set_fast().set_indexed());
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(sorted_index_settings(Order::Desc))
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(int_field => 2u64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(int_field => 7u64))?;
+        index_writer.add_document(doc!(int_field => 2u64))?;
+        index_writer.delete_term(Term::from_field_u64(int_field, 7u64));
+        index_writer.add_document(doc!(int_field => 5u64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let int_vals = segment_reader.fast_fields().u64("intval")?;
+        let values: Vec<Option<u64>> = (0..segment_reader.max_doc())
+            .map(|doc| int_vals.first(doc))
+            .collect();
+        // Documents without a value are placed last.
+        assert_eq!(values, vec![Some(7), Some(5), Some(2), Some(2), None]);
+        // The delete applies to the document added before it, wherever it has been moved to.
+        let alive_bitset = segment_reader.alive_bitset().unwrap();
+        assert!(!alive_bitset.is_alive(0));
+        assert_eq!(segment_reader.num_docs(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_index_sorted_by_several_fields() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let category_field =
+            schema_builder.add_u64_field("category", NumericOptions::default().set_fast());
+        let price_field =
+            schema_builder.add_i64_field("price", NumericOptions::default().set_fast());
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                sort_by_field: Some(IndexSortByField {
+                    field: "category".to_string(),
+                    order: Order::Asc,
+                }),
+                then_sort_by_fields: vec![IndexSortByField {
+                    field: "price".to_string(),
+                    order: Order::Desc,
+                }],
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category_field => 2u64, price_field => 10i64))?;
+        index_writer.add_document(doc!(category_field => 1u64, price_field => -5i64))?;
+        index_writer.add_document(doc!(category_field => 2u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category_field => 1u64, price_field => 20i64))?;
+        index_writer.add_document(doc!(category_field => 2u64, price_field => 30i64))?;
+        index_writer.add_document(doc!(price_field => 100i64))?;
+        index_writer.commit()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let categories = segment_reader.fast_fields().u64("category")?;
+        let prices = segment_reader.fast_fields().i64("price")?;
+        let values: Vec<(Option<u64>, Option<i64>)> = (0..segment_reader.max_doc())
+            .map(|doc| (categories.first(doc), prices.first(doc)))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (Some(1), Some(20)),
+                (Some(1), Some(-5)),
+                (Some(2), Some(30)),
+                (Some(2), Some(10)),
+                (Some(2), None),
+                (None, Some(100)),
+            ]
+        );
+        Ok(())
+    }
 }
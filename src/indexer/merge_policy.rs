@@ -1,7 +1,8 @@
 use std::fmt::Debug;
 use std::marker;
 
-use crate::index::{SegmentId, SegmentMeta};
+use crate::index::{SegmentId, SegmentMeta, SegmentReader};
+use crate::DocId;
 
 /// Set of segment suggested for a merge.
 #[derive(Debug, Clone)]
@@ -17,6 +18,20 @@ pub trait MergePolicy: marker::Send + marker::Sync + Debug {
     /// This call happens on the segment updater thread, and will block
     /// other segment updates, so all implementations should happen rapidly.
     fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate>;
+
+    /// Given the readers of the segments about to be merged, returns for each of them
+    /// the documents to leave out of the merged segment, on top of the deleted ones.
+    ///
+    /// The segment readers reflect the deletes up to the merge. Returning an empty `Vec`
+    /// keeps all of the documents, which is the default.
+    ///
+    /// This call happens on the merging thread.
+    fn docs_to_drop_on_merge(
+        &self,
+        _segment_readers: &[SegmentReader],
+    ) -> crate::Result<Vec<Vec<DocId>>> {
+        Ok(Vec::new())
+    }
 }
 
 /// Never merge segments.
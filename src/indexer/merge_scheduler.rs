@@ -0,0 +1,342 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::{AntiCallToken, TerminatingWrite};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::directory::WritePtr;
+use crate::TantivyError;
+
+/// Message of the panics caught in the merge threads.
+pub(crate) const PANIC_CAUGHT: &str = "Panic caught in merge thread";
+
+/// Settings of the scheduling of the merges of an [`IndexWriter`](crate::IndexWriter).
+///
+/// They can be changed at any time with
+/// [`IndexWriter::set_merge_scheduler_settings`](crate::IndexWriter::set_merge_scheduler_settings).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MergeSchedulerSettings {
+    /// The maximum number of merges running at the same time.
+    ///
+    /// The merges that cannot start right away are queued, and started by increasing
+    /// number of documents, so that small merges are not stuck behind large ones.
+    ///
+    /// The number of merge threads of the `IndexWriter` is an upper bound of the number of
+    /// concurrent merges.
+    pub max_concurrent_merges: usize,
+    /// The maximum number of bytes per second written by all of the running merges,
+    /// or `None` if the merges should not be throttled.
+    pub max_merge_write_bytes_per_sec: Option<u64>,
+}
+
+/// Limits the throughput of writers, shared across several writers.
+pub(crate) struct WriteRateLimiter {
+    // 0 means that the throughput is not limited.
+    max_bytes_per_sec: AtomicU64,
+    // Instant at which the next write is allowed to happen.
+    next_write: Mutex<Instant>,
+}
+
+impl WriteRateLimiter {
+    fn new(max_bytes_per_sec: Option<u64>) -> WriteRateLimiter {
+        WriteRateLimiter {
+            max_bytes_per_sec: AtomicU64::new(max_bytes_per_sec.unwrap_or(0)),
+            next_write: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn set_max_bytes_per_sec(&self, max_bytes_per_sec: Option<u64>) {
+        self.max_bytes_per_sec
+            .store(max_bytes_per_sec.unwrap_or(0), AtomicOrdering::Relaxed);
+    }
+
+    /// Blocks until `num_bytes` can be written.
+    fn acquire(&self, num_bytes: usize) {
+        let max_bytes_per_sec = self.max_bytes_per_sec.load(AtomicOrdering::Relaxed);
+        if max_bytes_per_sec == 0 {
+            return;
+        }
+        let write_duration = Duration::from_secs_f64(num_bytes as f64 / max_bytes_per_sec as f64);
+        let wait_duration = {
+            let mut next_write = self.next_write.lock().unwrap();
+            let now = Instant::now();
+            // Time during which nothing was written does not give any credit.
+            let write_start = (*next_write).max(now);
+            *next_write = write_start + write_duration;
+            write_start - now
+        };
+        if !wait_duration.is_zero() {
+            std::thread::sleep(wait_duration);
+        }
+    }
+
+    /// Wraps a writer so that its throughput is limited by this rate limiter.
+    pub(crate) fn wrap(self: &Arc<Self>, write: WritePtr) -> WritePtr {
+        BufWriter::new(Box::new(RateLimitedWrite {
+            underlying: write
+                .into_inner()
+                .map_err(|_| ())
+                .expect("buffer should be empty"),
+            rate_limiter: self.clone(),
+        }))
+    }
+}
+
+struct RateLimitedWrite {
+    underlying: Box<dyn TerminatingWrite>,
+    rate_limiter: Arc<WriteRateLimiter>,
+}
+
+impl Write for RateLimitedWrite {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.rate_limiter.acquire(buf.len());
+        self.underlying.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()
+    }
+}
+
+impl TerminatingWrite for RateLimitedWrite {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        self.underlying.terminate_ref(token)
+    }
+}
+
+type MergeJob = Box<dyn FnOnce() + Send + 'static>;
+
+struct PendingMerge {
+    // Small merges first, and then merges in the order they were scheduled.
+    priority: Reverse<(u64, u64)>,
+    job: MergeJob,
+}
+
+impl PartialEq for PendingMerge {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for PendingMerge {}
+
+impl PartialOrd for PendingMerge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingMerge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+struct SchedulerState {
+    settings: MergeSchedulerSettings,
+    pending_merges: BinaryHeap<PendingMerge>,
+    num_running_merges: usize,
+    num_scheduled_merges: u64,
+}
+
+struct InnerMergeScheduler {
+    thread_pool: ThreadPool,
+    num_threads: usize,
+    state: Mutex<SchedulerState>,
+    write_rate_limiter: Arc<WriteRateLimiter>,
+}
+
+/// Runs the merges on a pool of threads, limiting the number of concurrent merges and the
+/// throughput of their writes.
+#[derive(Clone)]
+pub(crate) struct MergeScheduler(Arc<InnerMergeScheduler>);
+
+impl MergeScheduler {
+    pub fn create(num_threads: usize) -> crate::Result<MergeScheduler> {
+        let thread_pool = ThreadPoolBuilder::new()
+            .thread_name(|i| format!("merge_thread_{i}"))
+            .num_threads(num_threads)
+            .panic_handler(move |panic| {
+                // We don't print the panic content itself,
+                // it is already printed during the unwinding
+                if let Some(message) = panic.downcast_ref::<&str>() {
+                    if *message != PANIC_CAUGHT {
+                        error!("uncaught merge panic")
+                    }
+                }
+            })
+            .build()
+            .map_err(|_| {
+                crate::TantivyError::SystemError(
+                    "Failed to spawn segment merging thread".to_string(),
+                )
+            })?;
+        let num_threads = thread_pool.current_num_threads();
+        let settings = MergeSchedulerSettings {
+            max_concurrent_merges: num_threads,
+            max_merge_write_bytes_per_sec: None,
+        };
+        Ok(MergeScheduler(Arc::new(InnerMergeScheduler {
+            thread_pool,
+            num_threads,
+            state: Mutex::new(SchedulerState {
+                settings,
+                pending_merges: BinaryHeap::new(),
+                num_running_merges: 0,
+                num_scheduled_merges: 0,
+            }),
+            write_rate_limiter: Arc::new(WriteRateLimiter::new(None)),
+        })))
+    }
+
+    pub fn settings(&self) -> MergeSchedulerSettings {
+        self.0.state.lock().unwrap().settings
+    }
+
+    pub fn set_settings(&self, settings: MergeSchedulerSettings) -> crate::Result<()> {
+        if settings.max_concurrent_merges == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "At least one concurrent merge is required, got 0".to_string(),
+            ));
+        }
+        if settings.max_merge_write_bytes_per_sec == Some(0) {
+            return Err(TantivyError::InvalidArgument(
+                "The merge write throughput cannot be 0 bytes per second".to_string(),
+            ));
+        }
+        self.0
+            .write_rate_limiter
+            .set_max_bytes_per_sec(settings.max_merge_write_bytes_per_sec);
+        self.0.state.lock().unwrap().settings = settings;
+        // More merges may be allowed to run.
+        self.start_pending_merges();
+        Ok(())
+    }
+
+    /// Returns the rate limiter the merges should write through.
+    pub fn write_rate_limiter(&self) -> &Arc<WriteRateLimiter> {
+        &self.0.write_rate_limiter
+    }
+
+    /// Schedules a merge of `num_docs` documents.
+    ///
+    /// The merge starts as soon as the number of running merges allows it, after the smaller
+    /// pending merges.
+    pub fn schedule<F>(&self, num_docs: u64, job: F)
+    where F: FnOnce() + Send + 'static {
+        {
+            let mut state = self.0.state.lock().unwrap();
+            let merge_ord = state.num_scheduled_merges;
+            state.num_scheduled_merges += 1;
+            state.pending_merges.push(PendingMerge {
+                priority: Reverse((num_docs, merge_ord)),
+                job: Box::new(job),
+            });
+        }
+        self.start_pending_merges();
+    }
+
+    fn start_pending_merges(&self) {
+        let mut state = self.0.state.lock().unwrap();
+        let max_concurrent_merges = state.settings.max_concurrent_merges.min(self.0.num_threads);
+        while state.num_running_merges < max_concurrent_merges {
+            let Some(pending_merge) = state.pending_merges.pop() else {
+                break;
+            };
+            state.num_running_merges += 1;
+            let running_merge_guard = RunningMergeGuard(self.clone());
+            self.0.thread_pool.spawn(move || {
+                // The guard also releases the slot of the merge if it panics.
+                let _running_merge_guard = running_merge_guard;
+                (pending_merge.job)();
+            });
+        }
+    }
+}
+
+/// Releases the slot of a running merge when dropped, and starts the next pending merges.
+struct RunningMergeGuard(MergeScheduler);
+
+impl Drop for RunningMergeGuard {
+    fn drop(&mut self) {
+        self.0 .0.state.lock().unwrap().num_running_merges -= 1;
+        self.0.start_pending_merges();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier, Mutex};
+    use std::time::{Duration, Instant};
+
+    use super::{MergeScheduler, MergeSchedulerSettings, WriteRateLimiter};
+
+    #[test]
+    fn test_merge_scheduler_runs_small_merges_first() {
+        let merge_scheduler = MergeScheduler::create(2).unwrap();
+        merge_scheduler
+            .set_settings(MergeSchedulerSettings {
+                max_concurrent_merges: 1,
+                max_merge_write_bytes_per_sec: None,
+            })
+            .unwrap();
+        let barrier = Arc::new(Barrier::new(2));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let (done_sender, done_receiver) = crossbeam_channel::unbounded();
+        // The first merge blocks the only slot until the other merges are scheduled.
+        let barrier_clone = barrier.clone();
+        merge_scheduler.schedule(1_000, move || {
+            barrier_clone.wait();
+        });
+        for num_docs in [300, 100, 200] {
+            let order = order.clone();
+            let done_sender = done_sender.clone();
+            merge_scheduler.schedule(num_docs, move || {
+                order.lock().unwrap().push(num_docs);
+                done_sender.send(()).unwrap();
+            });
+        }
+        barrier.wait();
+        for _ in 0..3 {
+            done_receiver.recv().unwrap();
+        }
+        assert_eq!(*order.lock().unwrap(), [100, 200, 300]);
+    }
+
+    #[test]
+    fn test_merge_scheduler_invalid_settings() {
+        let merge_scheduler = MergeScheduler::create(2).unwrap();
+        assert_eq!(merge_scheduler.settings().max_concurrent_merges, 2);
+        assert!(merge_scheduler
+            .set_settings(MergeSchedulerSettings {
+                max_concurrent_merges: 0,
+                max_merge_write_bytes_per_sec: None,
+            })
+            .is_err());
+        assert!(merge_scheduler
+            .set_settings(MergeSchedulerSettings {
+                max_concurrent_merges: 1,
+                max_merge_write_bytes_per_sec: Some(0),
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_write_rate_limiter() {
+        let rate_limiter = WriteRateLimiter::new(Some(10_000));
+        let start = Instant::now();
+        for _ in 0..4 {
+            rate_limiter.acquire(1_000);
+        }
+        // The first write goes through right away, the 3 next ones wait for 100ms each.
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        rate_limiter.set_max_bytes_per_sec(None);
+        let start = Instant::now();
+        rate_limiter.acquire(1_000_000);
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+}
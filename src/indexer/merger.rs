@@ -1,7 +1,11 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
 
+use columnar::column_values::CodecType;
 use columnar::{
-    ColumnType, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder, StackMergeOrder,
+    Column, ColumnType, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder, StackMergeOrder,
 };
 use common::ReadOnlyBitSet;
 use itertools::Itertools;
@@ -12,12 +16,15 @@ use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
-use crate::index::{Segment, SegmentComponent, SegmentReader};
+use crate::index::{IndexSettings, IndexSortByField, Segment, SegmentComponent, SegmentReader};
 use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
 use crate::indexer::SegmentSerializer;
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
-use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
-use crate::store::StoreWriter;
+use crate::schema::{
+    field_type_fast_codec, field_type_to_column_type, Field, FieldType, Schema, TantivyDocument,
+};
+use crate::store::{StoreReader, StoreWriter};
+use crate::suggest::SuggestSerializer;
 use crate::termdict::{TermMerger, TermOrdinal};
 use crate::{DocAddress, DocId, InvertedIndexReader};
 
@@ -26,6 +33,14 @@ use crate::{DocAddress, DocId, InvertedIndexReader};
 /// We do not allow segments with more than
 pub const MAX_DOC_LIMIT: u32 = 1 << 31;
 
+/// Number of doc store blocks cached when the documents are not written in their original order.
+const SHUFFLED_STORE_CACHE_NUM_BLOCKS: usize = 100;
+
+/// A document of a posting list written out of order: its new doc id, its term frequency, and
+/// the ranges of its positions and of its offsets in the buffers shared by the documents of the
+/// term.
+type ShuffledDoc = (DocId, u32, Range<usize>, Range<usize>);
+
 fn estimate_total_num_tokens_in_single_segment(
     reader: &SegmentReader,
     field: Field,
@@ -45,12 +60,13 @@ fn estimate_total_num_tokens_in_single_segment(
             let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
             count[fieldnorm_id as usize] += 1;
         }
+        let precision = fieldnorm_reader.precision();
         let total_num_tokens = count
             .iter()
             .cloned()
             .enumerate()
             .map(|(fieldnorm_ord, count)| {
-                count as u64 * u64::from(FieldNormReader::id_to_fieldnorm(fieldnorm_ord as u8))
+                count as u64 * u64::from(precision.id_to_fieldnorm(fieldnorm_ord as u8))
             })
             .sum::<u64>();
         return Ok(total_num_tokens);
@@ -77,6 +93,7 @@ fn estimate_total_num_tokens(readers: &[SegmentReader], field: Field) -> crate::
 }
 
 pub struct IndexMerger {
+    index_settings: IndexSettings,
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
@@ -112,7 +129,7 @@ fn convert_to_merge_order(
 ) -> MergeRowOrder {
     match doc_id_mapping.mapping_type() {
         MappingType::Stacked => MergeRowOrder::Stack(StackMergeOrder::stack(columnars)),
-        MappingType::StackedWithDeletes => {
+        MappingType::StackedWithDeletes | MappingType::Shuffled => {
             // RUST/LLVM is amazing. The following conversion is actually a no-op:
             // no allocation, no copy.
             let new_row_id_to_old_row_id: Vec<RowAddr> = doc_id_mapping
@@ -131,6 +148,16 @@ fn convert_to_merge_order(
     }
 }
 
+/// Compares the sort keys of two documents, the documents without a value coming last.
+fn cmp_sort_keys(left: Option<u64>, right: Option<u64>) -> Ordering {
+    match (left, right) {
+        (Some(left), Some(right)) => left.cmp(&right),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
 fn extract_fast_field_required_columns(schema: &Schema) -> Vec<(String, ColumnType)> {
     schema
         .fields()
@@ -138,16 +165,35 @@ fn extract_fast_field_required_columns(schema: &Schema) -> Vec<(String, ColumnTy
         .filter(|field_entry| field_entry.is_fast())
         .filter_map(|field_entry| {
             let column_name = field_entry.name().to_string();
-            let column_type = value_type_to_column_type(field_entry.field_type().value_type())?;
+            let column_type = field_type_to_column_type(field_entry.field_type())?;
             Some((column_name, column_type))
         })
         .collect()
 }
 
+fn extract_fast_field_codec_types(schema: &Schema) -> HashMap<String, Vec<CodecType>> {
+    schema
+        .fields()
+        .map(|(_, field_entry)| field_entry)
+        .filter(|field_entry| field_entry.is_fast())
+        .filter_map(|field_entry| {
+            let fast_codec = field_type_fast_codec(field_entry.field_type())?;
+            Some((
+                field_entry.name().to_string(),
+                vec![fast_codec.codec_type()],
+            ))
+        })
+        .collect()
+}
+
 impl IndexMerger {
-    pub fn open(schema: Schema, segments: &[Segment]) -> crate::Result<IndexMerger> {
+    pub fn open(
+        schema: Schema,
+        index_settings: IndexSettings,
+        segments: &[Segment],
+    ) -> crate::Result<IndexMerger> {
         let alive_bitset = segments.iter().map(|_| None).collect_vec();
-        Self::open_with_custom_alive_set(schema, segments, alive_bitset)
+        Self::open_with_custom_alive_set(schema, index_settings, segments, alive_bitset)
     }
 
     // Create merge with a custom delete set.
@@ -164,6 +210,7 @@ impl IndexMerger {
     // segments and partitions them e.g. by a value in a field.
     pub fn open_with_custom_alive_set(
         schema: Schema,
+        index_settings: IndexSettings,
         segments: &[Segment],
         alive_bitset_opt: Vec<Option<AliveBitSet>>,
     ) -> crate::Result<IndexMerger> {
@@ -186,6 +233,7 @@ impl IndexMerger {
             return Err(crate::TantivyError::InvalidArgument(err_msg));
         }
         Ok(IndexMerger {
+            index_settings,
             schema,
             readers,
             max_doc,
@@ -230,15 +278,86 @@ impl IndexMerger {
             .map(|reader| reader.fast_fields().columnar())
             .collect();
         let merge_row_order = convert_to_merge_order(&columnars[..], doc_id_mapping);
-        columnar::merge_columnar(
+        let column_codec_types = extract_fast_field_codec_types(&self.schema);
+        columnar::merge_columnar_with_codec_types(
             &columnars[..],
             &required_columns,
+            &column_codec_types,
             merge_row_order,
             fast_field_wrt,
         )?;
         Ok(())
     }
 
+    /// Returns the order of the documents in the merged segment: sorted by the index sort fields
+    /// if the index is sorted, stacked otherwise.
+    pub(crate) fn get_doc_id_mapping(&self) -> crate::Result<SegmentDocIdMapping> {
+        let sort_by_fields: Vec<&IndexSortByField> = self.index_settings.sort_by_fields().collect();
+        if sort_by_fields.is_empty() {
+            self.get_doc_id_from_concatenated_data()
+        } else {
+            self.get_doc_id_mapping_from_fields(&sort_by_fields)
+        }
+    }
+
+    /// Creates a mapping sorting the alive documents of all of the segments by the first value of
+    /// the sort fields, the following fields breaking the ties of the previous ones.
+    ///
+    /// The sort is stable, so that documents with the same values keep their stacked order, and
+    /// the documents without a value for a field come last for this field.
+    pub(crate) fn get_doc_id_mapping_from_fields(
+        &self,
+        sort_by_fields: &[&IndexSortByField],
+    ) -> crate::Result<SegmentDocIdMapping> {
+        let mut stacked_mapping = self.get_doc_id_from_concatenated_data()?;
+        // For every sort field, the sort column of every segment.
+        let sort_columns: Vec<Vec<Option<Column<u64>>>> = sort_by_fields
+            .iter()
+            .map(|sort_by_field| {
+                self.readers
+                    .iter()
+                    .map(|reader| {
+                        let sort_column_opt =
+                            reader.fast_fields().u64_lenient(&sort_by_field.field)?;
+                        Ok(sort_column_opt.map(|(column, _)| column))
+                    })
+                    .collect::<crate::Result<_>>()
+            })
+            .collect::<crate::Result<_>>()?;
+        let sort_key = |field_ord: usize, doc_addr: &DocAddress| -> Option<u64> {
+            let value = sort_columns[field_ord][doc_addr.segment_ord as usize]
+                .as_ref()?
+                .first(doc_addr.doc_id)?;
+            Some(if sort_by_fields[field_ord].order.is_desc() {
+                u64::MAX - value
+            } else {
+                value
+            })
+        };
+        let cmp_docs = |left: &DocAddress, right: &DocAddress| -> Ordering {
+            (0..sort_by_fields.len())
+                .map(|field_ord| {
+                    cmp_sort_keys(sort_key(field_ord, left), sort_key(field_ord, right))
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
+        let new_doc_id_to_old_doc_addr = &mut stacked_mapping.new_doc_id_to_old_doc_addr;
+        let is_sorted = new_doc_id_to_old_doc_addr
+            .iter()
+            .tuple_windows()
+            .all(|(left, right)| cmp_docs(left, right).is_le());
+        if is_sorted {
+            return Ok(stacked_mapping);
+        }
+        new_doc_id_to_old_doc_addr.sort_by(cmp_docs);
+        Ok(SegmentDocIdMapping::new(
+            std::mem::take(new_doc_id_to_old_doc_addr),
+            MappingType::Shuffled,
+            stacked_mapping.alive_bitsets,
+        ))
+    }
+
     /// Creates a mapping if the segments are stacked. this is helpful to merge codelines between
     /// index sorting and the others
     pub(crate) fn get_doc_id_from_concatenated_data(&self) -> crate::Result<SegmentDocIdMapping> {
@@ -293,6 +412,7 @@ impl IndexMerger {
     ) -> crate::Result<()> {
         debug_time!("write-postings-for-field");
         let mut positions_buffer: Vec<u32> = Vec::with_capacity(1_000);
+        let mut offsets_buffer: Vec<(u32, u32)> = Vec::new();
         let mut delta_computer = DeltaComputer::new();
 
         let mut max_term_ords: Vec<TermOrdinal> = Vec::new();
@@ -357,6 +477,12 @@ impl IndexMerger {
 
         let mut segment_postings_containing_the_term: Vec<(usize, SegmentPostings)> = vec![];
 
+        let is_shuffled = doc_id_mapping.mapping_type() == MappingType::Shuffled;
+        let mut shuffled_docs: Vec<ShuffledDoc> = Vec::new();
+        let mut shuffled_positions: Vec<u32> = Vec::new();
+        let mut shuffled_offsets: Vec<(u32, u32)> = Vec::new();
+        let has_offsets = segment_postings_option.has_offsets();
+
         while merged_terms.advance() {
             segment_postings_containing_the_term.clear();
             let term_bytes: &[u8] = merged_terms.key();
@@ -442,6 +568,9 @@ impl IndexMerger {
                         // there is at least one document.
                         let term_freq = if has_term_freq {
                             segment_postings.positions(&mut positions_buffer);
+                            if has_offsets {
+                                segment_postings.offsets(&mut offsets_buffer);
+                            }
                             segment_postings.term_freq()
                         } else {
                             // The positions_buffer may contain positions from the previous term
@@ -451,13 +580,40 @@ impl IndexMerger {
                             0u32
                         };
 
-                        let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                        field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                        if is_shuffled {
+                            // The documents have to be written in increasing doc id order, so
+                            // they are buffered until all of the segments have been visited.
+                            let positions_start = shuffled_positions.len();
+                            shuffled_positions.extend_from_slice(&positions_buffer);
+                            let offsets_start = shuffled_offsets.len();
+                            shuffled_offsets.extend_from_slice(&offsets_buffer);
+                            shuffled_docs.push((
+                                remapped_doc_id,
+                                term_freq,
+                                positions_start..shuffled_positions.len(),
+                                offsets_start..shuffled_offsets.len(),
+                            ));
+                        } else {
+                            let delta_positions = delta_computer.compute_delta(&positions_buffer);
+                            field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                            field_serializer.write_offsets(&offsets_buffer);
+                        }
                     }
 
                     doc = segment_postings.advance();
                 }
             }
+            if is_shuffled {
+                shuffled_docs.sort_unstable_by_key(|(remapped_doc_id, _, _, _)| *remapped_doc_id);
+                for (remapped_doc_id, term_freq, positions, offsets) in shuffled_docs.drain(..) {
+                    let delta_positions =
+                        delta_computer.compute_delta(&shuffled_positions[positions]);
+                    field_serializer.write_doc(remapped_doc_id, term_freq, delta_positions);
+                    field_serializer.write_offsets(&shuffled_offsets[offsets]);
+                }
+                shuffled_positions.clear();
+                shuffled_offsets.clear();
+            }
             // closing the term.
             field_serializer.close_term()?;
         }
@@ -486,13 +642,48 @@ impl IndexMerger {
         Ok(())
     }
 
-    fn write_storable_fields(&self, store_writer: &mut StoreWriter) -> crate::Result<()> {
+    fn write_storable_fields(
+        &self,
+        store_writer: &mut StoreWriter,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
         debug_time!("write-storable-fields");
         debug!("write-storable-field");
 
+        if doc_id_mapping.mapping_type() == MappingType::Shuffled {
+            let store_readers: Vec<StoreReader> = self
+                .readers
+                .iter()
+                .map(|reader| reader.get_store_reader(SHUFFLED_STORE_CACHE_NUM_BLOCKS))
+                .collect::<Result<_, _>>()?;
+            for old_doc_addr in doc_id_mapping.iter_old_doc_addrs() {
+                let store_reader = &store_readers[old_doc_addr.segment_ord as usize];
+                if store_reader.has_blobs() {
+                    let doc: TantivyDocument = store_reader.get(old_doc_addr.doc_id)?;
+                    store_writer.store(&doc, &self.schema)?;
+                    continue;
+                }
+                let doc_bytes = store_reader.get_document_bytes(old_doc_addr.doc_id)?;
+                store_writer.store_bytes(&doc_bytes)?;
+            }
+            return Ok(());
+        }
+
+        // The blocks of the segments are assumed to have been written with the doc store
+        // settings used for indexing. If the new segment uses different ones, as merged segments
+        // can, they are recompressed instead of stacked.
+        let recompress_blocks = store_writer.compressor()
+            != self.index_settings.docstore_compression
+            || store_writer.block_size() != self.index_settings.docstore_blocksize;
         for reader in &self.readers {
             let store_reader = reader.get_store_reader(1)?;
-            if reader.has_deletes()
+            if store_reader.has_blobs() {
+                // The serialized documents reference the blob file of their segment, so their
+                // values are read back and spilled again into the blob file of the new segment.
+                for doc_res in store_reader.iter::<TantivyDocument>(reader.alive_bitset()) {
+                    store_writer.store(&doc_res?, &self.schema)?;
+                }
+            } else if reader.has_deletes()
                     // If there is not enough data in the store, we avoid stacking in order to
                     // avoid creating many small blocks in the doc store. Once we have 5 full blocks,
                     // we start stacking. In the worst case 2/7 of the blocks would be very small.
@@ -508,6 +699,7 @@ impl IndexMerger {
                     // take 7 in order to not walk over all checkpoints.
                     || store_reader.block_checkpoints().take(7).count() < 6
                     || store_reader.decompressor() != store_writer.compressor().into()
+                    || recompress_blocks
             {
                 for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
                     let doc_bytes = doc_bytes_res?;
@@ -520,13 +712,58 @@ impl IndexMerger {
         Ok(())
     }
 
+    /// Writes the suggestions of the alive documents, with their new doc ids.
+    fn write_suggest(
+        &self,
+        suggest_serializer: &mut SuggestSerializer,
+        doc_id_mapping: &SegmentDocIdMapping,
+    ) -> crate::Result<()> {
+        debug_time!("write-suggest");
+        let mut merged_doc_id_map: Vec<Vec<Option<DocId>>> = self
+            .readers
+            .iter()
+            .map(|reader| vec![None; reader.max_doc() as usize])
+            .collect();
+        for (new_doc_id, old_doc_addr) in doc_id_mapping.iter_old_doc_addrs().enumerate() {
+            let segment_map = &mut merged_doc_id_map[old_doc_addr.segment_ord as usize];
+            segment_map[old_doc_addr.doc_id as usize] = Some(new_doc_id as DocId);
+        }
+        for (field, field_entry) in self.schema.fields() {
+            if !matches!(field_entry.field_type(), FieldType::Suggest(_)) {
+                continue;
+            }
+            let mut entries = Vec::new();
+            for (reader, segment_map) in self.readers.iter().zip(&merged_doc_id_map) {
+                for mut entry in reader.suggest_reader(field)?.entries()? {
+                    // The suggestions of the deleted documents are dropped.
+                    if let Some(new_doc_id) = segment_map[entry.doc as usize] {
+                        entry.doc = new_doc_id;
+                        entries.push(entry);
+                    }
+                }
+            }
+            suggest_serializer.serialize_field(field, entries)?;
+        }
+        Ok(())
+    }
+
     /// Writes the merged segment by pushing information
     /// to the `SegmentSerializer`.
     ///
     /// # Returns
     /// The number of documents in the resulting segment.
-    pub fn write(&self, mut serializer: SegmentSerializer) -> crate::Result<u32> {
-        let doc_id_mapping = self.get_doc_id_from_concatenated_data()?;
+    pub fn write(&self, serializer: SegmentSerializer) -> crate::Result<u32> {
+        let doc_id_mapping = self.get_doc_id_mapping()?;
+        self.write_with_doc_id_mapping(serializer, doc_id_mapping)
+    }
+
+    /// Same as [`IndexMerger::write`], with the order of the documents of the merged segment
+    /// given by `doc_id_mapping`.
+    pub(crate) fn write_with_doc_id_mapping(
+        &self,
+        mut serializer: SegmentSerializer,
+        doc_id_mapping: SegmentDocIdMapping,
+    ) -> crate::Result<u32> {
         debug!("write-fieldnorms");
         if let Some(fieldnorms_serializer) = serializer.extract_fieldnorms_serializer() {
             self.write_fieldnorms(fieldnorms_serializer, &doc_id_mapping)?;
@@ -535,7 +772,8 @@ impl IndexMerger {
         let fieldnorm_data = serializer
             .segment()
             .open_read(SegmentComponent::FieldNorms)?;
-        let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+        let fieldnorm_readers =
+            FieldNormReaders::open_with_schema(fieldnorm_data, self.schema.clone())?;
         self.write_postings(
             serializer.get_postings_serializer(),
             fieldnorm_readers,
@@ -543,7 +781,11 @@ impl IndexMerger {
         )?;
 
         debug!("write-storagefields");
-        self.write_storable_fields(serializer.get_store_writer())?;
+        self.write_storable_fields(serializer.get_store_writer(), &doc_id_mapping)?;
+        if let Some(suggest_serializer) = serializer.get_suggest_serializer() {
+            debug!("write-suggest");
+            self.write_suggest(suggest_serializer, &doc_id_mapping)?;
+        }
         debug!("write-fastfields");
         self.write_fast_fields(serializer.get_fast_field_write(), doc_id_mapping)?;
 
@@ -4,21 +4,32 @@
 //! `IndexWriter` is the main entry point for that, which created from
 //! [`Index::writer`](crate::Index::writer).
 
+#[cfg(feature = "async-writer")]
+mod async_index_writer;
+pub(crate) mod backup;
+mod bulk_index_builder;
+mod check_index;
+mod deduplicating_merge_policy;
 pub(crate) mod delete_queue;
 pub(crate) mod path_to_unordered_id;
 
 pub(crate) mod doc_id_mapping;
 mod doc_opstamp_mapping;
+pub(crate) mod expiration;
 mod flat_map_with_buffer;
+mod force_merge;
 pub(crate) mod index_writer;
 pub(crate) mod index_writer_status;
 mod log_merge_policy;
+mod memory_accounting;
 mod merge_index_test;
 mod merge_operation;
 pub(crate) mod merge_policy;
+pub(crate) mod merge_scheduler;
 pub(crate) mod merger;
 pub(crate) mod operation;
 pub(crate) mod prepared_commit;
+mod resharding;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -27,22 +38,43 @@ pub(crate) mod segment_updater;
 pub(crate) mod segment_writer;
 pub(crate) mod single_segment_index_writer;
 mod stamper;
+mod tiered_merge_policy;
+mod unique_key;
+mod update_by_query;
+mod wal_index_writer;
 
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
-pub use self::index_writer::{IndexWriter, IndexWriterOptions};
+#[cfg(feature = "async-writer")]
+pub use self::async_index_writer::AsyncIndexWriter;
+pub use self::backup::{
+    backup_index, list_backups, restore_index, BackupFile, BackupManifest, BackupOptions,
+};
+pub use self::bulk_index_builder::BulkIndexBuilder;
+pub use self::check_index::{
+    check_index, CheckIndexOptions, CheckIndexReport, SegmentCheckReport, SegmentProblem,
+};
+pub use self::deduplicating_merge_policy::DeduplicatingMergePolicy;
+pub use self::index_writer::{IndexWriter, IndexWriterOptions, TryAddDocument};
 pub use self::log_merge_policy::LogMergePolicy;
 pub use self::merge_operation::MergeOperation;
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::merge_scheduler::MergeSchedulerSettings;
 use self::operation::AddOperation;
 pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
+pub use self::resharding::{shrink_indices, split_index};
 pub use self::segment_entry::SegmentEntry;
 pub(crate) use self::segment_serializer::SegmentSerializer;
 pub use self::segment_updater::{merge_filtered_segments, merge_indices};
 pub use self::segment_writer::SegmentWriter;
 pub use self::single_segment_index_writer::SingleSegmentIndexWriter;
+pub use self::tiered_merge_policy::TieredMergePolicy;
+pub use self::update_by_query::{
+    UpdateByQueryOptions, UpdateByQueryProgress, UpdateByQueryProgressCallback,
+};
+pub use self::wal_index_writer::WalIndexWriter;
 
 /// Alias for the default merge policy, which is the `LogMergePolicy`.
 pub type DefaultMergePolicy = LogMergePolicy;
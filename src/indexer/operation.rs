@@ -1,12 +1,25 @@
+use columnar::ColumnType;
+
 use crate::query::Weight;
 use crate::schema::document::Document;
 use crate::schema::{TantivyDocument, Term};
 use crate::Opstamp;
 
 /// Timestamped Delete operation.
+///
+/// If `fast_field_update` is set, the documents matching `target` are not
+/// deleted but get their fast field value updated instead.
 pub struct DeleteOperation {
     pub opstamp: Opstamp,
     pub target: Box<dyn Weight>,
+    pub fast_field_update: Option<FastFieldUpdate>,
+}
+
+/// New value of a fast field, expressed in its `u64` internal representation.
+pub struct FastFieldUpdate {
+    pub column_name: String,
+    pub column_type: ColumnType,
+    pub value: u64,
 }
 
 /// Timestamped Add operation.
@@ -39,6 +39,26 @@ impl<'a, D: Document> PreparedCommit<'a, D> {
         self.commit_future().wait()
     }
 
+    /// Makes the commit durable, without making it visible.
+    ///
+    /// This is the first phase of a two-phase commit, making it possible for tantivy to
+    /// take part in a transaction alongside another system, a database for instance.
+    ///
+    /// The commit is written in a prepared meta file, which survives a crash or a restart,
+    /// but is ignored by readers. It then has to be either published with
+    /// [`IndexWriter::commit_prepared`] or discarded with [`IndexWriter::rollback_prepared`],
+    /// using the returned opstamp. No other commit can be prepared in the meantime.
+    ///
+    /// After a restart, the pending prepared commit can be found with
+    /// [`Index::load_prepared_metas`](crate::Index::load_prepared_metas).
+    pub fn persist(self) -> crate::Result<Opstamp> {
+        info!("persisting prepared commit {}", self.opstamp);
+        self.index_writer
+            .segment_updater()
+            .schedule_persist_commit(self.opstamp, self.payload)
+            .wait()
+    }
+
     /// Proceeds to commit.
     ///
     /// Unfortunately, contrary to what `PrepareCommit` may suggests,
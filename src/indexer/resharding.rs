@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::hash::Hasher;
+use std::io::Write;
+
+use common::{BitSet, ReadOnlyBitSet, TerminatingWrite};
+use fnv::FnvHasher;
+
+use super::merger::IndexMerger;
+use super::segment_updater::save_metas;
+use super::unique_key::{KeyColumn, UniqueKey};
+use super::SegmentSerializer;
+use crate::directory::Directory;
+use crate::fastfield::AliveBitSet;
+use crate::index::{Index, IndexMeta, Segment, SegmentMeta, SegmentReader};
+use crate::{Opstamp, TantivyError};
+
+/// Splits an index into `output_directories.len()` indexes, routing each document according
+/// to the hash of the value of its `key_field` fast field.
+///
+/// The key field can be a str, bytes or numerical fast field. The documents without a key are
+/// routed to the first index. The hash function is stable, so that documents are routed the
+/// same way from one split to the other.
+///
+/// The segments whose documents are all routed to the same index are copied as is. The other
+/// ones are rewritten into a single new segment per output index.
+///
+/// `output_directories` are assumed to be empty.
+///
+/// # Warning
+/// This function does NOT check or take the `IndexWriter` lock. It is not meant to work if an
+/// `IndexWriter` is running on the source index, or on the output indexes.
+pub fn split_index<T: Into<Box<dyn Directory>>>(
+    index: &Index,
+    key_field: &str,
+    output_directories: Vec<T>,
+) -> crate::Result<Vec<Index>> {
+    let num_shards = output_directories.len();
+    if num_shards == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "An index cannot be split into 0 indexes".to_string(),
+        ));
+    }
+    let schema = index.schema();
+    let key_field_entry = schema.get_field_entry(schema.get_field(key_field)?);
+    if !key_field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {key_field:?} is not a fast field"
+        )));
+    }
+    let index_meta = index.load_metas()?;
+    let output_indexes = output_directories
+        .into_iter()
+        .map(|output_directory| {
+            Index::create(output_directory, index.schema(), index.settings().clone())
+        })
+        .collect::<crate::Result<Vec<Index>>>()?;
+    let mut shard_segment_metas: Vec<Vec<SegmentMeta>> = vec![Vec::new(); num_shards];
+    // Segments to rewrite for each of the shards, along with the documents to keep.
+    let mut shard_segments_to_rewrite: Vec<Vec<(Segment, AliveBitSet)>> =
+        vec![Vec::new(); num_shards];
+    for segment_meta in index_meta.segments {
+        let segment = index.segment(segment_meta);
+        let segment_reader = SegmentReader::open(&segment)?;
+        let key_column = KeyColumn::open(&segment_reader, key_field)?;
+        let mut shard_docs: Vec<BitSet> = (0..num_shards)
+            .map(|_| BitSet::with_max_value(segment_reader.max_doc()))
+            .collect();
+        for doc in segment_reader.doc_ids_alive() {
+            let shard = shard_for_key(key_column.key(doc)?, num_shards);
+            shard_docs[shard].insert(doc);
+        }
+        let non_empty_shards: Vec<usize> = (0..num_shards)
+            .filter(|&shard| shard_docs[shard].len() > 0)
+            .collect();
+        if let [shard] = non_empty_shards[..] {
+            let segment_meta = copy_segment(&segment, &output_indexes[shard])?;
+            shard_segment_metas[shard].push(segment_meta);
+            continue;
+        }
+        for shard in non_empty_shards {
+            let alive_bitset = AliveBitSet::from(ReadOnlyBitSet::from(&shard_docs[shard]));
+            shard_segments_to_rewrite[shard].push((segment.clone(), alive_bitset));
+        }
+    }
+    for (shard, segments_to_rewrite) in shard_segments_to_rewrite.into_iter().enumerate() {
+        if segments_to_rewrite.is_empty() {
+            continue;
+        }
+        let (segments, alive_bitsets): (Vec<Segment>, Vec<AliveBitSet>) =
+            segments_to_rewrite.into_iter().unzip();
+        let segment_meta = rewrite_segments(
+            &output_indexes[shard],
+            &segments,
+            alive_bitsets.into_iter().map(Some).collect(),
+        )?;
+        shard_segment_metas[shard].push(segment_meta);
+    }
+    for (output_index, segment_metas) in output_indexes.iter().zip(shard_segment_metas) {
+        publish_segments(output_index, segment_metas, index_meta.opstamp)?;
+    }
+    Ok(output_indexes)
+}
+
+/// Combines several indexes sharing the same schema and settings into a single index, by
+/// copying their segments.
+///
+/// The segments are not merged: the resulting index has as many segments as the source
+/// indexes altogether. See [`merge_indices`](crate::indexer::merge_indices) to merge them
+/// into a single segment.
+///
+/// `output_directory` is assumed to be empty.
+///
+/// # Warning
+/// This function does NOT check or take the `IndexWriter` lock. It is not meant to work if an
+/// `IndexWriter` is running on the source indexes, or on the output index.
+pub fn shrink_indices<T: Into<Box<dyn Directory>>>(
+    indices: &[Index],
+    output_directory: T,
+) -> crate::Result<Index> {
+    let Some(first_index) = indices.first() else {
+        return Err(TantivyError::InvalidArgument(
+            "No indices given to shrink".to_string(),
+        ));
+    };
+    if indices.iter().skip(1).any(|index| {
+        index.schema() != first_index.schema() || index.settings() != first_index.settings()
+    }) {
+        return Err(TantivyError::InvalidArgument(
+            "Attempt to shrink indices with different schemas or index settings".to_string(),
+        ));
+    }
+    let output_index = Index::create(
+        output_directory,
+        first_index.schema(),
+        first_index.settings().clone(),
+    )?;
+    let mut segment_ids = HashSet::new();
+    let mut segment_metas = Vec::new();
+    let mut opstamp = 0;
+    for index in indices {
+        let index_meta = index.load_metas()?;
+        opstamp = opstamp.max(index_meta.opstamp);
+        for segment_meta in index_meta.segments {
+            if !segment_ids.insert(segment_meta.id()) {
+                return Err(TantivyError::InvalidArgument(format!(
+                    "The segment {} is part of several of the indices",
+                    segment_meta.id().uuid_string()
+                )));
+            }
+            let segment = index.segment(segment_meta);
+            segment_metas.push(copy_segment(&segment, &output_index)?);
+        }
+    }
+    publish_segments(&output_index, segment_metas, opstamp)?;
+    Ok(output_index)
+}
+
+/// Routes a key to a shard, with the FNV-1a hash of its bytes.
+fn shard_for_key(key: Option<UniqueKey>, num_shards: usize) -> usize {
+    let Some(key) = key else {
+        return 0;
+    };
+    let mut hasher = FnvHasher::default();
+    match key {
+        UniqueKey::Term(term_bytes) => hasher.write(&term_bytes),
+        UniqueKey::Value(value) => hasher.write(&value.to_le_bytes()),
+    }
+    (hasher.finish() % num_shards as u64) as usize
+}
+
+/// Copies the files of a segment to the directory of `output_index`, and returns the meta of
+/// the copied segment.
+fn copy_segment(segment: &Segment, output_index: &Index) -> crate::Result<SegmentMeta> {
+    let segment_meta = segment.meta();
+    for path in segment_meta.list_files() {
+        if !segment.index().directory().exists(&path)? {
+            continue;
+        }
+        let file_bytes = segment.index().directory().open_read(&path)?.read_bytes()?;
+        let mut writer = output_index.directory().open_write(&path)?;
+        writer.write_all(file_bytes.as_slice())?;
+        writer.terminate()?;
+    }
+    let mut copied_segment_meta =
+        output_index.new_segment_meta(segment_meta.id(), segment_meta.max_doc());
+    if let Some(delete_opstamp) = segment_meta.delete_opstamp() {
+        copied_segment_meta =
+            copied_segment_meta.with_delete_meta(segment_meta.num_deleted_docs(), delete_opstamp);
+    }
+    if let Some(update_opstamp) = segment_meta.update_opstamp() {
+        copied_segment_meta = copied_segment_meta.with_update_meta(update_opstamp);
+    }
+    copied_segment_meta.untrack_temp_docstore();
+    Ok(copied_segment_meta)
+}
+
+/// Writes the documents of `alive_bitsets` from `segments` into a new segment of
+/// `output_index`.
+fn rewrite_segments(
+    output_index: &Index,
+    segments: &[Segment],
+    alive_bitsets: Vec<Option<AliveBitSet>>,
+) -> crate::Result<SegmentMeta> {
+    let new_segment = output_index.new_segment();
+    let merger = IndexMerger::open_with_custom_alive_set(
+        output_index.schema(),
+        output_index.settings().clone(),
+        segments,
+        alive_bitsets,
+    )?;
+    let segment_serializer = SegmentSerializer::for_merged_segment(new_segment.clone())?;
+    let num_docs = merger.write(segment_serializer)?;
+    Ok(output_index.new_segment_meta(new_segment.id(), num_docs))
+}
+
+fn publish_segments(
+    output_index: &Index,
+    segment_metas: Vec<SegmentMeta>,
+    opstamp: Opstamp,
+) -> crate::Result<()> {
+    let index_meta = IndexMeta {
+        index_settings: output_index.settings().clone(),
+        segments: segment_metas,
+        schema: output_index.schema(),
+        opstamp,
+        payload: None,
+    };
+    save_metas(&index_meta, output_index.directory())?;
+    output_index.directory().sync_directory()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shrink_indices, split_index};
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_split_and_shrink_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING | FAST);
+        let num_field = schema_builder.add_u64_field("num", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..100u64 {
+            index_writer.add_document(doc!(id_field => format!("doc{i}"), num_field => i))?;
+        }
+        index_writer.commit()?;
+        // A segment with a single document is copied as is.
+        index_writer.add_document(doc!(id_field => "doc100", num_field => 100u64))?;
+        index_writer.delete_term(Term::from_field_u64(num_field, 0));
+        index_writer.commit()?;
+
+        let shards = split_index(
+            &index,
+            "id",
+            vec![RamDirectory::default(), RamDirectory::default()],
+        )?;
+        let shard_num_docs: Vec<u64> = shards
+            .iter()
+            .map(|shard| Ok(shard.reader()?.searcher().num_docs()))
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(shard_num_docs.iter().sum::<u64>(), 100);
+        assert!(shard_num_docs.iter().all(|&num_docs| num_docs > 0));
+        // Splitting again routes the documents the same way.
+        let shards_again = split_index(
+            &index,
+            "id",
+            vec![RamDirectory::default(), RamDirectory::default()],
+        )?;
+        for (shard, shard_again) in shards.iter().zip(&shards_again) {
+            assert_eq!(
+                shard.reader()?.searcher().num_docs(),
+                shard_again.reader()?.searcher().num_docs()
+            );
+        }
+
+        let shrunk_index = shrink_indices(&shards, RamDirectory::default())?;
+        let num_segments: usize = shards
+            .iter()
+            .map(|shard| Ok(shard.searchable_segment_metas()?.len()))
+            .collect::<crate::Result<Vec<usize>>>()?
+            .into_iter()
+            .sum();
+        assert_eq!(shrunk_index.searchable_segment_metas()?.len(), num_segments);
+        let searcher = shrunk_index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 100);
+        for (num, expected_count) in [(0u64, 0), (1, 1), (100, 1)] {
+            let query = TermQuery::new(
+                Term::from_field_u64(num_field, num),
+                IndexRecordOption::Basic,
+            );
+            assert_eq!(searcher.search(&query, &Count)?, expected_count);
+        }
+        // The shrunk index can be written to.
+        let mut index_writer: IndexWriter = shrunk_index.writer_for_tests()?;
+        index_writer.delete_term(Term::from_field_u64(num_field, 1));
+        index_writer.commit()?;
+        assert_eq!(shrunk_index.reader()?.searcher().num_docs(), 99);
+
+        // The shards share their segments with the shrunk index.
+        assert!(
+            shrink_indices(&[shrunk_index, shards[0].clone()], RamDirectory::default()).is_err()
+        );
+        Ok(())
+    }
+}
@@ -4,7 +4,9 @@ use crate::directory::WritePtr;
 use crate::fieldnorm::FieldNormsSerializer;
 use crate::index::{Segment, SegmentComponent};
 use crate::postings::InvertedIndexSerializer;
-use crate::store::StoreWriter;
+use crate::schema::FieldType;
+use crate::store::{Compressor, StoreWriter};
+use crate::suggest::SuggestSerializer;
 
 /// Segment serializer is in charge of laying out on disk
 /// the data accumulated and sorted by the `SegmentWriter`.
@@ -14,20 +16,54 @@ pub struct SegmentSerializer {
     fast_field_write: WritePtr,
     fieldnorms_serializer: Option<FieldNormsSerializer>,
     postings_serializer: InvertedIndexSerializer,
+    suggest_serializer: Option<SuggestSerializer>,
 }
 
 impl SegmentSerializer {
     /// Creates a new `SegmentSerializer`.
-    pub fn for_segment(mut segment: Segment) -> crate::Result<SegmentSerializer> {
-        let settings = segment.index().settings().clone();
+    pub fn for_segment(segment: Segment) -> crate::Result<SegmentSerializer> {
+        let settings = segment.index().settings();
+        let docstore_compression = settings.docstore_compression;
+        let docstore_blocksize = settings.docstore_blocksize;
+        Self::with_docstore_settings(segment, docstore_compression, docstore_blocksize)
+    }
+
+    /// Creates a new `SegmentSerializer` for a segment produced by a merge.
+    ///
+    /// Its doc store uses the merge compressor and block size of the index settings.
+    pub fn for_merged_segment(segment: Segment) -> crate::Result<SegmentSerializer> {
+        let settings = segment.index().settings();
+        let docstore_compression = settings.merge_docstore_compression();
+        let docstore_blocksize = settings.merge_docstore_blocksize();
+        Self::with_docstore_settings(segment, docstore_compression, docstore_blocksize)
+    }
+
+    fn with_docstore_settings(
+        mut segment: Segment,
+        docstore_compression: Compressor,
+        docstore_blocksize: usize,
+    ) -> crate::Result<SegmentSerializer> {
+        let docstore_compress_dedicated_thread = segment
+            .index()
+            .settings()
+            .docstore_compress_dedicated_thread;
         let store_writer = {
             let store_write = segment.open_write(SegmentComponent::Store)?;
-            StoreWriter::new(
+            let store_writer = StoreWriter::new(
                 store_write,
-                settings.docstore_compression,
-                settings.docstore_blocksize,
-                settings.docstore_compress_dedicated_thread,
-            )?
+                docstore_compression,
+                docstore_blocksize,
+                docstore_compress_dedicated_thread,
+            )?;
+            let has_blob_fields = segment
+                .schema()
+                .fields()
+                .any(|(_, field_entry)| field_entry.blob_threshold().is_some());
+            if has_blob_fields {
+                store_writer.with_blobs(segment.open_write(SegmentComponent::Blobs)?)
+            } else {
+                store_writer
+            }
         };
 
         let fast_field_write = segment.open_write(SegmentComponent::FastFields)?;
@@ -36,12 +72,24 @@ impl SegmentSerializer {
         let fieldnorms_serializer = FieldNormsSerializer::from_write(fieldnorms_write)?;
 
         let postings_serializer = InvertedIndexSerializer::open(&mut segment)?;
+
+        let has_suggest_fields = segment
+            .schema()
+            .fields()
+            .any(|(_, field_entry)| matches!(field_entry.field_type(), FieldType::Suggest(_)));
+        let suggest_serializer = if has_suggest_fields {
+            let suggest_write = segment.open_write(SegmentComponent::Suggest)?;
+            Some(SuggestSerializer::from_write(suggest_write))
+        } else {
+            None
+        };
         Ok(SegmentSerializer {
             segment,
             store_writer,
             fast_field_write,
             fieldnorms_serializer: Some(fieldnorms_serializer),
             postings_serializer,
+            suggest_serializer,
         })
     }
 
@@ -76,6 +124,13 @@ impl SegmentSerializer {
         &mut self.store_writer
     }
 
+    /// Accessor to the `SuggestSerializer`.
+    ///
+    /// Returns `None` if the schema does not have any suggest field.
+    pub(crate) fn get_suggest_serializer(&mut self) -> Option<&mut SuggestSerializer> {
+        self.suggest_serializer.as_mut()
+    }
+
     /// Finalize the segment serialization.
     pub fn close(mut self) -> crate::Result<()> {
         if let Some(fieldnorms_serializer) = self.extract_fieldnorms_serializer() {
@@ -84,6 +139,9 @@ impl SegmentSerializer {
         self.fast_field_write.terminate()?;
         self.postings_serializer.close()?;
         self.store_writer.close()?;
+        if let Some(suggest_serializer) = self.suggest_serializer {
+            suggest_serializer.close()?;
+        }
         Ok(())
     }
 }
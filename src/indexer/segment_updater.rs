@@ -3,20 +3,29 @@ use std::borrow::BorrowMut;
 use std::collections::HashSet;
 use std::io::Write;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
+use common::{BitSet, ReadOnlyBitSet};
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 use super::segment_manager::SegmentManager;
-use crate::core::META_FILEPATH;
+use crate::core::{META_FILEPATH, PREPARED_META_FILEPATH};
+use crate::directory::error::{DeleteError, QuotaExceededError};
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
-use crate::fastfield::AliveBitSet;
-use crate::index::{Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta};
+use crate::fastfield::{intersect_alive_bitsets, AliveBitSet};
+use crate::index::{
+    Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta, SegmentReader,
+};
 use crate::indexer::delete_queue::DeleteCursor;
+use crate::indexer::expiration::alive_bitset_without_expired_docs;
 use crate::indexer::index_writer::advance_deletes;
 use crate::indexer::merge_operation::MergeOperationInventory;
+use crate::indexer::merge_scheduler::{
+    MergeScheduler, MergeSchedulerSettings, WriteRateLimiter, PANIC_CAUGHT,
+};
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
@@ -24,10 +33,9 @@ use crate::indexer::{
     DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
     SegmentSerializer,
 };
+use crate::telemetry::{self, MergeTracker};
 use crate::{FutureResult, Opstamp, TantivyError};
 
-const PANIC_CAUGHT: &str = "Panic caught in merge thread";
-
 /// Save the index meta file.
 /// This operation is atomic:
 /// Either
@@ -36,7 +44,15 @@ const PANIC_CAUGHT: &str = "Panic caught in merge thread";
 ///
 /// This method is not part of tantivy's public API
 pub(crate) fn save_metas(metas: &IndexMeta, directory: &dyn Directory) -> crate::Result<()> {
-    info!("save metas");
+    save_metas_to_path(metas, &META_FILEPATH, directory)
+}
+
+fn save_metas_to_path(
+    metas: &IndexMeta,
+    path: &Path,
+    directory: &dyn Directory,
+) -> crate::Result<()> {
+    info!("save metas to {path:?}");
     let mut buffer = serde_json::to_vec_pretty(metas)?;
     // Just adding a new line at the end of the buffer.
     writeln!(&mut buffer)?;
@@ -47,11 +63,19 @@ pub(crate) fn save_metas(metas: &IndexMeta, directory: &dyn Directory) -> crate:
         )
     )));
     directory.sync_directory()?;
-    directory.atomic_write(&META_FILEPATH, &buffer[..])?;
+    directory.atomic_write(path, &buffer[..])?;
     debug!("Saved metas {:?}", serde_json::to_string_pretty(&metas));
     Ok(())
 }
 
+/// Deletes the meta file of the pending prepared commit, if any.
+pub(crate) fn delete_prepared_metas(directory: &dyn Directory) -> crate::Result<()> {
+    match directory.delete(&PREPARED_META_FILEPATH) {
+        Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => Ok(()),
+        Err(DeleteError::IoError { io_error, .. }) => Err(TantivyError::IoError(io_error)),
+    }
+}
+
 // The segment update runner is in charge of processing all
 //  of the `SegmentUpdate`s.
 //
@@ -77,9 +101,30 @@ fn garbage_collect_files(
 ) -> crate::Result<GarbageCollectionResult> {
     info!("Running garbage collection");
     let mut index = segment_updater.index.clone();
-    index
-        .directory_mut()
-        .garbage_collect(move || segment_updater.list_files())
+    // After a restart, the segments of a pending prepared commit are not known
+    // by the segment manager.
+    //
+    // As long as the prepared commit is pending, `meta.json` is still the published
+    // commit, and merges only update the prepared meta file. The segments of `meta.json`
+    // must survive until the prepared commit is either published or rolled back.
+    let prepared_files: HashSet<PathBuf> = match index.load_prepared_metas()? {
+        Some(prepared_metas) => {
+            let published_metas = index.load_metas()?;
+            prepared_metas
+                .segments
+                .iter()
+                .chain(published_metas.segments.iter())
+                .flat_map(|segment_meta| segment_meta.list_files())
+                .chain(std::iter::once(PREPARED_META_FILEPATH.to_path_buf()))
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+    index.directory_mut().garbage_collect(move || {
+        let mut files = segment_updater.list_files();
+        files.extend(prepared_files);
+        files
+    })
 }
 
 /// Merges a list of segments the list of segment givens in the `segment_entries`.
@@ -88,6 +133,8 @@ fn merge(
     index: &Index,
     mut segment_entries: Vec<SegmentEntry>,
     target_opstamp: Opstamp,
+    write_rate_limiter: Arc<WriteRateLimiter>,
+    merge_policy: &dyn MergePolicy,
 ) -> crate::Result<Option<SegmentEntry>> {
     let num_docs = segment_entries
         .iter()
@@ -98,7 +145,9 @@ fn merge(
     }
 
     // first we need to apply deletes to our segment.
-    let merged_segment = index.new_segment();
+    let merged_segment = index
+        .new_segment()
+        .with_write_rate_limiter(write_rate_limiter);
 
     // First we apply all of the delete to the merged segment, up to the target opstamp.
     for segment_entry in &mut segment_entries {
@@ -113,11 +162,46 @@ fn merge(
         .map(|segment_entry| index.segment(segment_entry.meta().clone()))
         .collect();
 
+    // The documents that expired since the last commit are dropped by the merge,
+    // as well as the ones filtered out by the merge policy.
+    let segment_readers = segments
+        .iter()
+        .map(SegmentReader::open)
+        .collect::<crate::Result<Vec<_>>>()?;
+    let docs_to_drop = merge_policy.docs_to_drop_on_merge(&segment_readers)?;
+    let mut alive_bitsets = Vec::with_capacity(segments.len());
+    for (segment_ord, segment) in segments.iter().enumerate() {
+        let alive_bitset_opt = alive_bitset_without_expired_docs(index, segment)?;
+        let Some(segment_docs_to_drop) = docs_to_drop
+            .get(segment_ord)
+            .filter(|segment_docs_to_drop| !segment_docs_to_drop.is_empty())
+        else {
+            alive_bitsets.push(alive_bitset_opt);
+            continue;
+        };
+        let mut alive_bitset = BitSet::with_max_value_and_full(segment.meta().max_doc());
+        for &doc in segment_docs_to_drop {
+            alive_bitset.remove(doc);
+        }
+        let alive_bitset = AliveBitSet::from(ReadOnlyBitSet::from(&alive_bitset));
+        alive_bitsets.push(Some(match alive_bitset_opt {
+            Some(alive_bitset_without_expired_docs) => {
+                intersect_alive_bitsets(alive_bitset, alive_bitset_without_expired_docs)
+            }
+            None => alive_bitset,
+        }));
+    }
+
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger = IndexMerger::open(index.schema(), &segments[..])?;
+    let merger: IndexMerger = IndexMerger::open_with_custom_alive_set(
+        index.schema(),
+        index.settings().clone(),
+        &segments[..],
+        alive_bitsets,
+    )?;
 
     // ... we just serialize this index merger in our new segment to merge the segments.
-    let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
+    let segment_serializer = SegmentSerializer::for_merged_segment(merged_segment.clone())?;
 
     let num_docs = merger.write(segment_serializer)?;
 
@@ -218,9 +302,13 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
     )?;
     let merged_segment = merged_index.new_segment();
     let merged_segment_id = merged_segment.id();
-    let merger: IndexMerger =
-        IndexMerger::open_with_custom_alive_set(merged_index.schema(), segments, filter_doc_ids)?;
-    let segment_serializer = SegmentSerializer::for_segment(merged_segment)?;
+    let merger: IndexMerger = IndexMerger::open_with_custom_alive_set(
+        merged_index.schema(),
+        merged_index.settings().clone(),
+        segments,
+        filter_doc_ids,
+    )?;
+    let segment_serializer = SegmentSerializer::for_merged_segment(merged_segment)?;
     let num_docs = merger.write(segment_serializer)?;
 
     let segment_meta = merged_index.new_segment_meta(merged_segment_id, num_docs);
@@ -258,8 +346,11 @@ pub(crate) struct InnerSegmentUpdater {
     // This should be up to date as all update happen through
     // the unique active `SegmentUpdater`.
     active_index_meta: RwLock<Arc<IndexMeta>>,
+    // Opstamp of the commit that was persisted as a prepared commit,
+    // and that has not been published yet.
+    prepared_commit_opstamp: RwLock<Option<Opstamp>>,
     pool: ThreadPool,
-    merge_thread_pool: ThreadPool,
+    merge_scheduler: MergeScheduler,
 
     index: Index,
     segment_manager: SegmentManager,
@@ -287,29 +378,13 @@ impl SegmentUpdater {
                     "Failed to spawn segment updater thread".to_string(),
                 )
             })?;
-        let merge_thread_pool = ThreadPoolBuilder::new()
-            .thread_name(|i| format!("merge_thread_{i}"))
-            .num_threads(num_merge_threads)
-            .panic_handler(move |panic| {
-                // We don't print the panic content itself,
-                // it is already printed during the unwinding
-                if let Some(message) = panic.downcast_ref::<&str>() {
-                    if *message != PANIC_CAUGHT {
-                        error!("uncaught merge panic")
-                    }
-                }
-            })
-            .build()
-            .map_err(|_| {
-                crate::TantivyError::SystemError(
-                    "Failed to spawn segment merging thread".to_string(),
-                )
-            })?;
+        let merge_scheduler = MergeScheduler::create(num_merge_threads)?;
         let index_meta = index.load_metas()?;
         Ok(SegmentUpdater(Arc::new(InnerSegmentUpdater {
             active_index_meta: RwLock::new(Arc::new(index_meta)),
+            prepared_commit_opstamp: RwLock::new(None),
             pool,
-            merge_thread_pool,
+            merge_scheduler,
             index,
             segment_manager,
             merge_policy: RwLock::new(Arc::new(DefaultMergePolicy::default())),
@@ -328,6 +403,17 @@ impl SegmentUpdater {
         *self.merge_policy.write().unwrap() = arc_merge_policy;
     }
 
+    pub fn get_merge_scheduler_settings(&self) -> MergeSchedulerSettings {
+        self.merge_scheduler.settings()
+    }
+
+    pub fn set_merge_scheduler_settings(
+        &self,
+        merge_scheduler_settings: MergeSchedulerSettings,
+    ) -> crate::Result<()> {
+        self.merge_scheduler.set_settings(merge_scheduler_settings)
+    }
+
     fn schedule_task<T: 'static + Send, F: FnOnce() -> crate::Result<T> + 'static + Send>(
         &self,
         task: F,
@@ -411,8 +497,14 @@ impl SegmentUpdater {
                 opstamp,
                 payload: commit_message,
             };
+            // As long as a prepared commit is pending, the new metas are not published.
+            let path: &Path = if self.prepared_commit_opstamp.read().unwrap().is_some() {
+                &PREPARED_META_FILEPATH
+            } else {
+                &META_FILEPATH
+            };
             // TODO add context to the error.
-            save_metas(&index_meta, directory.box_clone().borrow_mut())?;
+            save_metas_to_path(&index_meta, path, directory.box_clone().borrow_mut())?;
             self.store_meta(&index_meta);
         }
         Ok(())
@@ -445,16 +537,72 @@ impl SegmentUpdater {
     ) -> FutureResult<Opstamp> {
         let segment_updater: SegmentUpdater = self.clone();
         self.schedule_task(move || {
+            let start = Instant::now();
             let segment_entries = segment_updater.purge_deletes(opstamp)?;
             segment_updater.segment_manager.commit(segment_entries);
             segment_updater.save_metas(opstamp, payload)?;
+            telemetry::record_commit(start.elapsed());
             let _ = garbage_collect_files(segment_updater.clone());
             segment_updater.consider_merge_options();
             Ok(opstamp)
         })
     }
 
+    /// Persists the commit in the prepared meta file, without publishing it.
+    pub(crate) fn schedule_persist_commit(
+        &self,
+        opstamp: Opstamp,
+        payload: Option<String>,
+    ) -> FutureResult<Opstamp> {
+        let segment_updater: SegmentUpdater = self.clone();
+        self.schedule_task(move || {
+            let segment_entries = segment_updater.purge_deletes(opstamp)?;
+            segment_updater.segment_manager.commit(segment_entries);
+            *segment_updater.prepared_commit_opstamp.write().unwrap() = Some(opstamp);
+            if let Err(err) = segment_updater.save_metas(opstamp, payload) {
+                *segment_updater.prepared_commit_opstamp.write().unwrap() = None;
+                return Err(err);
+            }
+            Ok(opstamp)
+        })
+    }
+
+    /// Publishes the pending prepared commit, by turning the prepared meta file into
+    /// the meta file.
+    ///
+    /// Returns `true` if the prepared commit was persisted by this segment updater, `false`
+    /// if it was persisted before a restart, in which case the segments of this segment updater
+    /// are outdated.
+    pub(crate) fn schedule_commit_prepared(&self, opstamp: Opstamp) -> FutureResult<bool> {
+        let segment_updater: SegmentUpdater = self.clone();
+        self.schedule_task(move || {
+            let index = &segment_updater.index;
+            let prepared_metas = match index.load_prepared_metas()? {
+                Some(prepared_metas) if prepared_metas.opstamp == opstamp => prepared_metas,
+                _ => {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "There is no pending prepared commit with opstamp {opstamp}"
+                    )));
+                }
+            };
+            save_metas(&prepared_metas, index.directory())?;
+            delete_prepared_metas(index.directory())?;
+            let is_persisted_by_self = segment_updater
+                .prepared_commit_opstamp
+                .write()
+                .unwrap()
+                .take()
+                == Some(opstamp);
+            if is_persisted_by_self {
+                let _ = garbage_collect_files(segment_updater.clone());
+                segment_updater.consider_merge_options();
+            }
+            Ok(is_persisted_by_self)
+        })
+    }
+
     fn store_meta(&self, index_meta: &IndexMeta) {
+        telemetry::record_num_segments(index_meta.segments.len());
         *self.active_index_meta.write().unwrap() = Arc::new(index_meta.clone());
     }
 
@@ -507,12 +655,23 @@ impl SegmentUpdater {
             }
         };
 
+        if let Err(err) = self.check_merge_fits(&segment_entries) {
+            warn!("Refusing to start the merge. {err}");
+            return err.into();
+        }
+
         info!("Starting merge  - {:?}", merge_operation.segment_ids());
 
         let (scheduled_result, merging_future_send) =
             FutureResult::create("Merge operation failed.");
 
-        self.merge_thread_pool.spawn(move || {
+        let num_docs: u64 = segment_entries
+            .iter()
+            .map(|segment_entry| segment_entry.meta().num_docs() as u64)
+            .sum();
+        let write_rate_limiter = self.merge_scheduler.write_rate_limiter().clone();
+        let merge_tracker = MergeTracker::schedule();
+        self.merge_scheduler.schedule(num_docs, move || {
             // The fact that `merge_operation` is moved here is important.
             // Its lifetime is used to track how many merging thread are currently running,
             // as well as which segment is currently in merge and therefore should not be
@@ -522,6 +681,8 @@ impl SegmentUpdater {
                     &segment_updater.index,
                     segment_entries,
                     merge_operation.target_opstamp(),
+                    write_rate_limiter,
+                    segment_updater.get_merge_policy().as_ref(),
                 )
             }));
             let merge_res = match merge_panic_res {
@@ -546,7 +707,13 @@ impl SegmentUpdater {
             };
             match merge_res {
                 Ok(after_merge_segment_entry) => {
+                    let num_merged_docs = after_merge_segment_entry
+                        .as_ref()
+                        .map_or(0, |segment_entry| segment_entry.meta().num_docs());
                     let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
+                    if res.is_ok() {
+                        merge_tracker.complete(num_merged_docs);
+                    }
                     let _send_result = merging_future_send.send(res);
                 }
                 Err(merge_error) => {
@@ -566,6 +733,28 @@ impl SegmentUpdater {
         scheduled_result
     }
 
+    // Checks that the directory has room for the result of merging the given segments,
+    // estimated as the total size of their files.
+    fn check_merge_fits(&self, segment_entries: &[SegmentEntry]) -> crate::Result<()> {
+        let directory = self.index.directory();
+        let Some(num_bytes_available) = directory.available_bytes() else {
+            return Ok(());
+        };
+        let num_bytes_requested: u64 = segment_entries
+            .iter()
+            .flat_map(|segment_entry| segment_entry.meta().list_files())
+            .filter_map(|path| directory.get_file_handle(&path).ok())
+            .map(|file_handle| file_handle.len() as u64)
+            .sum();
+        if num_bytes_requested > num_bytes_available {
+            return Err(TantivyError::QuotaExceeded(QuotaExceededError {
+                num_bytes_available,
+                num_bytes_requested,
+            }));
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_mergeable_segments(&self) -> (Vec<SegmentMeta>, Vec<SegmentMeta>) {
         let merge_segment_ids: HashSet<SegmentId> = self.merge_operations.segment_in_merge();
         self.segment_manager
@@ -1099,6 +1288,7 @@ mod tests {
             )?;
             let merger: IndexMerger = IndexMerger::open_with_custom_alive_set(
                 merged_index.schema(),
+                merged_index.settings().clone(),
                 &segments[..],
                 filter_segments,
             )?;
@@ -1114,6 +1304,7 @@ mod tests {
                 Index::create(RamDirectory::default(), target_schema, target_settings)?;
             let merger: IndexMerger = IndexMerger::open_with_custom_alive_set(
                 merged_index.schema(),
+                merged_index.settings().clone(),
                 &segments[..],
                 filter_segments,
             )?;
@@ -4,9 +4,12 @@ use itertools::Itertools;
 use tokenizer_api::BoxTokenStream;
 
 use super::operation::AddOperation;
+use crate::directory::Directory;
 use crate::fastfield::FastFieldsWriter;
 use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent};
+use crate::indexer::doc_id_mapping::MappingType;
+use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_serializer::SegmentSerializer;
 use crate::json_utils::{index_json_value, IndexingPositionsPerPath};
 use crate::postings::{
@@ -14,10 +17,28 @@ use crate::postings::{
     PerFieldPostingsWriter, PostingsWriter,
 };
 use crate::schema::document::{Document, Value};
-use crate::schema::{FieldEntry, FieldType, Schema, Term, DATE_TIME_PRECISION_INDEXED};
-use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
+use crate::schema::{
+    FieldEntry, FieldType, Schema, Term, TextFieldIndexing, DATE_TIME_PRECISION_INDEXED,
+};
+use crate::suggest::{parse_suggest_value, SuggestWriter};
+use crate::tokenizer::{
+    FacetTokenizer, PreTokenizedStream, PreTokenizedString, TextAnalyzer, Tokenizer,
+};
 use crate::{DocId, Opstamp, TantivyError};
 
+/// Returns the token stream of a text value, or of a pre-tokenized text value.
+fn text_token_stream<'a>(
+    text_analyzer: &'a mut TextAnalyzer,
+    text_opt: Option<&'a str>,
+    pre_tokenized_text_opt: Option<&PreTokenizedString>,
+) -> Option<BoxTokenStream<'a>> {
+    if let Some(text) = text_opt {
+        return Some(text_analyzer.token_stream(text));
+    }
+    pre_tokenized_text_opt
+        .map(|tok_str| BoxTokenStream::new(PreTokenizedStream::from(tok_str.clone())))
+}
+
 /// Computes the initial size of the hash table.
 ///
 /// Returns the recommended initial table size as a power of 2.
@@ -51,6 +72,7 @@ pub struct SegmentWriter {
     pub(crate) segment_serializer: SegmentSerializer,
     pub(crate) fast_field_writers: FastFieldsWriter,
     pub(crate) fieldnorms_writer: FieldNormsWriter,
+    pub(crate) suggest_writer: SuggestWriter,
     pub(crate) json_path_writer: JsonPathWriter,
     pub(crate) json_positions_per_path: IndexingPositionsPerPath,
     pub(crate) doc_opstamps: Vec<Opstamp>,
@@ -79,16 +101,17 @@ impl SegmentWriter {
         let per_field_text_analyzers = schema
             .fields()
             .map(|(_, field_entry): (_, &FieldEntry)| {
-                let text_options = match field_entry.field_type() {
-                    FieldType::Str(ref text_options) => text_options.get_indexing_options(),
-                    FieldType::JsonObject(ref json_object_options) => {
-                        json_object_options.get_text_indexing_options()
-                    }
+                let tokenizer_name = match field_entry.field_type() {
+                    FieldType::Str(ref text_options) => text_options
+                        .get_indexing_options()
+                        .map(|text_index_option| text_index_option.tokenizer()),
+                    FieldType::JsonObject(ref json_object_options) => json_object_options
+                        .get_text_indexing_options()
+                        .map(|text_index_option| text_index_option.tokenizer()),
+                    FieldType::Suggest(ref suggest_options) => Some(suggest_options.tokenizer()),
                     _ => None,
-                };
-                let tokenizer_name = text_options
-                    .map(|text_index_option| text_index_option.tokenizer())
-                    .unwrap_or("default");
+                }
+                .unwrap_or("default");
 
                 tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
                     TantivyError::SchemaError(format!(
@@ -103,6 +126,7 @@ impl SegmentWriter {
             ctx: IndexingContext::new(table_size),
             per_field_postings_writers,
             fieldnorms_writer: FieldNormsWriter::for_schema(&schema),
+            suggest_writer: SuggestWriter::for_schema(&schema),
             json_path_writer: JsonPathWriter::default(),
             json_positions_per_path: IndexingPositionsPerPath::default(),
             segment_serializer,
@@ -129,6 +153,7 @@ impl SegmentWriter {
             self.ctx,
             self.fast_field_writers,
             &self.fieldnorms_writer,
+            self.suggest_writer,
             self.segment_serializer,
         )?;
         Ok(self.doc_opstamps)
@@ -140,6 +165,7 @@ impl SegmentWriter {
         self.ctx.mem_usage()
             + self.fieldnorms_writer.mem_usage()
             + self.fast_field_writers.mem_usage()
+            + self.suggest_writer.mem_usage()
             + self.segment_serializer.mem_usage()
     }
 
@@ -163,6 +189,16 @@ impl SegmentWriter {
                     field_entry.name()
                 ))
             };
+            if let FieldType::Suggest(_) = field_entry.field_type() {
+                let text_analyzer = &mut self.per_field_text_analyzers[field.field_id() as usize];
+                for value in values {
+                    let suggest_value =
+                        parse_suggest_value(value.as_value()).ok_or_else(make_schema_error)?;
+                    self.suggest_writer
+                        .add_value(field, doc_id, &suggest_value, text_analyzer);
+                }
+                continue;
+            }
             if !field_entry.is_indexed() {
                 continue;
             }
@@ -190,29 +226,65 @@ impl SegmentWriter {
                         );
                     }
                 }
-                FieldType::Str(_) => {
+                FieldType::Str(ref text_options) => {
+                    let index_reversed = text_options
+                        .get_indexing_options()
+                        .is_some_and(TextFieldIndexing::index_reversed);
                     let mut indexing_position = IndexingPosition::default();
                     for value in values {
                         let value = value.as_value();
 
-                        let mut token_stream = if let Some(text) = value.as_str() {
-                            let text_analyzer =
-                                &mut self.per_field_text_analyzers[field.field_id() as usize];
-                            text_analyzer.token_stream(text)
-                        } else if let Some(tok_str) = value.into_pre_tokenized_text() {
-                            BoxTokenStream::new(PreTokenizedStream::from(*tok_str.clone()))
+                        let text_opt = value.as_str();
+                        let pre_tokenized_text_opt = if text_opt.is_none() {
+                            value.into_pre_tokenized_text()
                         } else {
-                            continue;
+                            None
                         };
-
-                        assert!(term_buffer.is_empty());
-                        postings_writer.index_text(
-                            doc_id,
-                            &mut *token_stream,
-                            term_buffer,
-                            ctx,
-                            &mut indexing_position,
-                        );
+                        let text_analyzer =
+                            &mut self.per_field_text_analyzers[field.field_id() as usize];
+                        let start_position = indexing_position.end_position;
+                        let start_offset = indexing_position.end_offset;
+                        let text_len = text_opt
+                            .or(pre_tokenized_text_opt
+                                .as_ref()
+                                .map(|tok_str| &tok_str.text[..]))
+                            .map_or(0, str::len);
+                        {
+                            let Some(mut token_stream) = text_token_stream(
+                                text_analyzer,
+                                text_opt,
+                                pre_tokenized_text_opt.as_deref(),
+                            ) else {
+                                continue;
+                            };
+
+                            assert!(term_buffer.is_empty());
+                            postings_writer.index_text(
+                                doc_id,
+                                &mut *token_stream,
+                                term_buffer,
+                                ctx,
+                                &mut indexing_position,
+                            );
+                        }
+                        if index_reversed {
+                            if let Some(mut token_stream) = text_token_stream(
+                                text_analyzer,
+                                text_opt,
+                                pre_tokenized_text_opt.as_deref(),
+                            ) {
+                                postings_writer.index_reversed_text(
+                                    doc_id,
+                                    &mut *token_stream,
+                                    term_buffer,
+                                    ctx,
+                                    start_position,
+                                    start_offset,
+                                );
+                            }
+                        }
+                        // The offsets of the next value start after this one and a separator.
+                        indexing_position.end_offset = start_offset + text_len as u32 + 1;
                     }
                     if field_entry.has_fieldnorms() {
                         self.fieldnorms_writer
@@ -337,6 +409,9 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
+                FieldType::Suggest(_) => {
+                    unreachable!("suggest fields are not indexed in the inverted index")
+                }
             }
         }
         Ok(())
@@ -391,6 +466,7 @@ fn remap_and_write(
     ctx: IndexingContext,
     fast_field_writers: FastFieldsWriter,
     fieldnorms_writer: &FieldNormsWriter,
+    suggest_writer: SuggestWriter,
     mut serializer: SegmentSerializer,
 ) -> crate::Result<()> {
     debug!("remap-and-write");
@@ -400,7 +476,7 @@ fn remap_and_write(
     let fieldnorm_data = serializer
         .segment()
         .open_read(SegmentComponent::FieldNorms)?;
-    let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
+    let fieldnorm_readers = FieldNormReaders::open_with_schema(fieldnorm_data, schema.clone())?;
     serialize_postings(
         ctx,
         schema,
@@ -410,6 +486,10 @@ fn remap_and_write(
     )?;
     debug!("fastfield-serialize");
     fast_field_writers.serialize(serializer.get_fast_field_write())?;
+    if let Some(suggest_serializer) = serializer.get_suggest_serializer() {
+        debug!("suggest-serialize");
+        suggest_writer.serialize(suggest_serializer)?;
+    }
 
     debug!("serializer-close");
     serializer.close()?;
@@ -417,6 +497,47 @@ fn remap_and_write(
     Ok(())
 }
 
+/// Rewrites a freshly written segment so that its documents are sorted according to the
+/// [`IndexSortByField`](crate::IndexSortByField) of the index, if any.
+///
+/// The documents are sorted after the fact by merging the segment into a new one, so that the
+/// indexing path does not depend on the sort. `doc_opstamps` are reordered accordingly.
+pub(crate) fn sort_segment(
+    segment: Segment,
+    doc_opstamps: Vec<Opstamp>,
+) -> crate::Result<(Segment, Vec<Opstamp>)> {
+    let index = segment.index().clone();
+    if index.settings().sort_by_field.is_none() {
+        return Ok((segment, doc_opstamps));
+    }
+    let merger = IndexMerger::open(
+        index.schema(),
+        index.settings().clone(),
+        std::slice::from_ref(&segment),
+    )?;
+    let doc_id_mapping = merger.get_doc_id_mapping()?;
+    if doc_id_mapping.mapping_type() != MappingType::Shuffled {
+        // The documents have been added in sorted order.
+        return Ok((segment, doc_opstamps));
+    }
+    let sorted_doc_opstamps: Vec<Opstamp> = doc_id_mapping
+        .iter_old_doc_addrs()
+        .map(|old_doc_addr| doc_opstamps[old_doc_addr.doc_id as usize])
+        .collect();
+    let sorted_segment = index.new_segment();
+    let serializer = SegmentSerializer::for_segment(sorted_segment.clone())?;
+    let max_doc = merger.write_with_doc_id_mapping(serializer, doc_id_mapping)?;
+    drop(merger);
+    // The unsorted segment has never been published, so its files can be removed right away.
+    // Failing to do so is harmless, as the garbage collection will eventually remove them.
+    for file in segment.meta().list_files() {
+        if let Err(err) = index.directory().delete(&file) {
+            debug!("Failed to delete {file:?}: {err:?}");
+        }
+    }
+    Ok((sorted_segment.with_max_doc(max_doc), sorted_doc_opstamps))
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -468,6 +589,7 @@ mod tests {
                 position: 0,
                 text: String::from("A"),
                 position_length: 1,
+                keyword: false,
             }],
         };
 
@@ -984,6 +1106,7 @@ mod tests {
                 position: 0,
                 text: "rollercoaster".to_string(),
                 position_length: 2,
+                keyword: false,
             }],
         };
         doc.add_pre_tokenized_text(text, tokens.clone());
@@ -1024,6 +1147,7 @@ mod tests {
                     position: 0,
                     text: "long_token".to_string(),
                     position_length: 3,
+                    keyword: false,
                 },
                 Token {
                     offset_from: 0,
@@ -1031,6 +1155,7 @@ mod tests {
                     position: 1,
                     text: "short".to_string(),
                     position_length: 1,
+                    keyword: false,
                 },
             ],
         };
@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 
 use crate::indexer::operation::AddOperation;
 use crate::indexer::segment_updater::save_metas;
+use crate::indexer::segment_writer::sort_segment;
 use crate::indexer::SegmentWriter;
 use crate::schema::document::Document;
 use crate::{Directory, Index, IndexMeta, Opstamp, Segment, TantivyDocument};
@@ -39,8 +40,8 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
 
     pub fn finalize(self) -> crate::Result<Index> {
         let max_doc = self.segment_writer.max_doc();
-        self.segment_writer.finalize()?;
-        let segment: Segment = self.segment.with_max_doc(max_doc);
+        let doc_opstamps = self.segment_writer.finalize()?;
+        let (segment, _) = sort_segment(self.segment.with_max_doc(max_doc), doc_opstamps)?;
         let index = segment.index();
         let index_meta = IndexMeta {
             index_settings: index.settings().clone(),
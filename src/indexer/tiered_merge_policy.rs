@@ -0,0 +1,373 @@
+use std::cmp;
+
+use itertools::Itertools;
+
+use super::merge_policy::{MergeCandidate, MergePolicy};
+use crate::index::SegmentMeta;
+
+const DEFAULT_SEGMENTS_PER_TIER: f64 = 10.0;
+const DEFAULT_MAX_MERGE_AT_ONCE: usize = 10;
+const DEFAULT_MAX_MERGED_SEGMENT_NUM_DOCS: u32 = 5_000_000;
+const DEFAULT_FLOOR_SEGMENT_NUM_DOCS: u32 = 10_000;
+const DEFAULT_DEL_DOCS_RATIO_ALLOWED: f32 = 0.33f32;
+
+/// `TieredMergePolicy` merges segments of similar sizes, in tiers of exponentially growing
+/// sizes, while allowing a given number of segments per tier.
+///
+/// Contrary to the [`LogMergePolicy`](super::LogMergePolicy), the merged segments are not
+/// necessarily adjacent in size: among the possible merges, the policy picks the one with the
+/// lowest skew (segments of similar sizes), producing a small segment, and reclaiming the most
+/// deleted documents. It is better suited to indexes receiving a continuous flow of additions
+/// and deletes.
+///
+/// The size of a segment is its number of alive documents.
+#[derive(Debug, Clone)]
+pub struct TieredMergePolicy {
+    segments_per_tier: f64,
+    max_merge_at_once: usize,
+    max_merged_segment_num_docs: u32,
+    floor_segment_num_docs: u32,
+    del_docs_ratio_allowed: f32,
+}
+
+impl TieredMergePolicy {
+    /// Set the number of segments allowed per tier.
+    ///
+    /// A lower value means more merges, and fewer segments.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments_per_tier` is lower than 2.
+    pub fn set_segments_per_tier(&mut self, segments_per_tier: f64) {
+        assert!(segments_per_tier >= 2.0);
+        self.segments_per_tier = segments_per_tier;
+    }
+
+    /// Set the maximum number of segments merged at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_merge_at_once` is lower than 2.
+    pub fn set_max_merge_at_once(&mut self, max_merge_at_once: usize) {
+        assert!(max_merge_at_once >= 2);
+        self.max_merge_at_once = max_merge_at_once;
+    }
+
+    /// Set the maximum number of docs of a segment produced by a merge.
+    ///
+    /// Segments with more than half of this number of documents are not merged
+    /// anymore, unless they have too many deleted documents.
+    pub fn set_max_merged_segment_num_docs(&mut self, max_merged_segment_num_docs: u32) {
+        self.max_merged_segment_num_docs = max_merged_segment_num_docs;
+    }
+
+    /// Set the number of docs under which all segments are considered to have the same size.
+    ///
+    /// This prevents many tiny segments from lingering in the index.
+    pub fn set_floor_segment_num_docs(&mut self, floor_segment_num_docs: u32) {
+        self.floor_segment_num_docs = floor_segment_num_docs;
+    }
+
+    /// Set the ratio of deleted documents in a segment to tolerate.
+    ///
+    /// Segments that are too large to be merged are merged alone, in order to expunge
+    /// their deleted documents, if their ratio of deleted documents exceeds this value.
+    /// The deleted documents also make the merges involving a segment more attractive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if del_docs_ratio_allowed is not within (0..1].
+    pub fn set_del_docs_ratio_allowed(&mut self, del_docs_ratio_allowed: f32) {
+        assert!(del_docs_ratio_allowed <= 1.0f32);
+        assert!(del_docs_ratio_allowed > 0f32);
+        self.del_docs_ratio_allowed = del_docs_ratio_allowed;
+    }
+
+    fn floor_size(&self, segment: &SegmentMeta) -> u32 {
+        cmp::max(self.floor_segment_num_docs, segment.num_docs())
+    }
+
+    /// Returns the number of segments the index is allowed to have, given the tiers
+    /// the documents of `size_sorted_segments` fill.
+    fn num_allowed_segments(&self, size_sorted_segments: &[&SegmentMeta]) -> usize {
+        let Some(smallest_segment) = size_sorted_segments.last() else {
+            return 0;
+        };
+        let max_merged_segment_num_docs = f64::from(self.max_merged_segment_num_docs);
+        let merge_factor = (self.max_merge_at_once as f64).min(self.segments_per_tier);
+        let mut tier_size = f64::from(self.floor_size(smallest_segment)).max(1.0);
+        let mut num_docs_left: f64 = size_sorted_segments
+            .iter()
+            .map(|segment| f64::from(segment.num_docs()))
+            .sum();
+        let mut num_allowed_segments = 0.0;
+        loop {
+            let num_segments_in_tier = num_docs_left / tier_size;
+            if num_segments_in_tier < self.segments_per_tier
+                || tier_size >= max_merged_segment_num_docs
+            {
+                num_allowed_segments += num_segments_in_tier.ceil();
+                break;
+            }
+            num_allowed_segments += self.segments_per_tier;
+            num_docs_left -= self.segments_per_tier * tier_size;
+            tier_size = (tier_size * merge_factor).min(max_merged_segment_num_docs);
+        }
+        num_allowed_segments.max(self.segments_per_tier) as usize
+    }
+
+    /// Scores a merge: the lower the better.
+    ///
+    /// Merges of segments of similar sizes, producing small segments and reclaiming many
+    /// deleted documents are favored.
+    fn merge_score(&self, segments: &[&SegmentMeta], hit_too_large: bool) -> f64 {
+        let num_docs_before_merge: u64 = segments
+            .iter()
+            .map(|segment| u64::from(segment.max_doc()))
+            .sum();
+        let num_docs_after_merge: u64 = segments
+            .iter()
+            .map(|segment| u64::from(segment.num_docs()))
+            .sum();
+        let skew = if hit_too_large {
+            // The merge is as large as it can be: it is considered perfectly balanced.
+            1.0 / self.max_merge_at_once as f64
+        } else {
+            let floored_num_docs: u64 = segments
+                .iter()
+                .map(|segment| u64::from(self.floor_size(segment)))
+                .sum();
+            // The segments are sorted by decreasing size.
+            f64::from(self.floor_size(segments[0])) / floored_num_docs as f64
+        };
+        // Small merges are slightly favored.
+        let mut score = skew * (num_docs_after_merge as f64).powf(0.05);
+        if num_docs_before_merge > 0 {
+            let alive_docs_ratio = num_docs_after_merge as f64 / num_docs_before_merge as f64;
+            score *= alive_docs_ratio * alive_docs_ratio;
+        }
+        score
+    }
+
+    /// Returns the best merge of segments of `size_sorted_segments`, if any.
+    fn find_best_merge<'a>(
+        &self,
+        size_sorted_segments: &[&'a SegmentMeta],
+    ) -> Option<Vec<&'a SegmentMeta>> {
+        let mut best_merge: Option<(f64, Vec<&SegmentMeta>)> = None;
+        for start in 0..size_sorted_segments.len() {
+            let mut merge = Vec::new();
+            let mut merge_num_docs = 0u64;
+            let mut hit_too_large = false;
+            for &segment in &size_sorted_segments[start..] {
+                if merge.len() >= self.max_merge_at_once {
+                    break;
+                }
+                let segment_num_docs = u64::from(segment.num_docs());
+                if merge_num_docs + segment_num_docs > u64::from(self.max_merged_segment_num_docs) {
+                    // Smaller segments may still fit in the merge.
+                    hit_too_large = true;
+                    continue;
+                }
+                merge.push(segment);
+                merge_num_docs += segment_num_docs;
+            }
+            if merge.len() < 2 {
+                continue;
+            }
+            let score = self.merge_score(&merge, hit_too_large);
+            if best_merge
+                .as_ref()
+                .is_none_or(|(best_score, _)| score < *best_score)
+            {
+                best_merge = Some((score, merge));
+            }
+        }
+        best_merge.map(|(_, merge)| merge)
+    }
+}
+
+fn deletes_ratio(segment: &SegmentMeta) -> f32 {
+    if segment.max_doc() == 0 {
+        return 0f32;
+    }
+    segment.num_deleted_docs() as f32 / segment.max_doc() as f32
+}
+
+impl MergePolicy for TieredMergePolicy {
+    fn compute_merge_candidates(&self, segments: &[SegmentMeta]) -> Vec<MergeCandidate> {
+        let mut merge_candidates = Vec::new();
+        let mut size_sorted_segments = Vec::new();
+        for segment in segments
+            .iter()
+            .sorted_by_key(|segment| cmp::Reverse(segment.num_docs()))
+        {
+            if u64::from(segment.num_docs()) * 2 > u64::from(self.max_merged_segment_num_docs) {
+                // The segment is too large to be merged with other segments, but we can still
+                // expunge its deleted documents.
+                if deletes_ratio(segment) > self.del_docs_ratio_allowed {
+                    merge_candidates.push(MergeCandidate(vec![segment.id()]));
+                }
+            } else {
+                size_sorted_segments.push(segment);
+            }
+        }
+
+        let num_allowed_segments = self.num_allowed_segments(&size_sorted_segments);
+        let mut num_segments = size_sorted_segments.len();
+        while num_segments > num_allowed_segments {
+            let Some(merge) = self.find_best_merge(&size_sorted_segments) else {
+                break;
+            };
+            // The merged segments are replaced by a single one.
+            num_segments -= merge.len() - 1;
+            size_sorted_segments.retain(|segment| {
+                !merge
+                    .iter()
+                    .any(|merged_segment| merged_segment.id() == segment.id())
+            });
+            merge_candidates.push(MergeCandidate(
+                merge.iter().map(|segment| segment.id()).collect(),
+            ));
+        }
+        merge_candidates
+    }
+}
+
+impl Default for TieredMergePolicy {
+    fn default() -> TieredMergePolicy {
+        TieredMergePolicy {
+            segments_per_tier: DEFAULT_SEGMENTS_PER_TIER,
+            max_merge_at_once: DEFAULT_MAX_MERGE_AT_ONCE,
+            max_merged_segment_num_docs: DEFAULT_MAX_MERGED_SEGMENT_NUM_DOCS,
+            floor_segment_num_docs: DEFAULT_FLOOR_SEGMENT_NUM_DOCS,
+            del_docs_ratio_allowed: DEFAULT_DEL_DOCS_RATIO_ALLOWED,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::index::{SegmentId, SegmentMetaInventory};
+    use crate::schema::{Schema, INDEXED};
+    use crate::{Index, IndexWriter};
+
+    static INVENTORY: Lazy<SegmentMetaInventory> = Lazy::new(SegmentMetaInventory::default);
+
+    fn create_random_segment_meta(num_docs: u32) -> SegmentMeta {
+        INVENTORY.new_segment_meta(SegmentId::generate_random(), num_docs)
+    }
+
+    fn test_merge_policy() -> TieredMergePolicy {
+        let mut tiered_merge_policy = TieredMergePolicy::default();
+        tiered_merge_policy.set_segments_per_tier(4.0);
+        tiered_merge_policy.set_max_merge_at_once(4);
+        tiered_merge_policy.set_max_merged_segment_num_docs(10_000);
+        tiered_merge_policy.set_floor_segment_num_docs(10);
+        tiered_merge_policy
+    }
+
+    fn num_docs_in_candidate(candidate: &MergeCandidate, segments: &[SegmentMeta]) -> u32 {
+        candidate
+            .0
+            .iter()
+            .map(|segment_id| {
+                segments
+                    .iter()
+                    .find(|segment| segment.id() == *segment_id)
+                    .unwrap()
+                    .num_docs()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_empty() {
+        assert!(test_merge_policy().compute_merge_candidates(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_few_segments() {
+        let test_input: Vec<SegmentMeta> = (0..4).map(|_| create_random_segment_meta(10)).collect();
+        assert!(test_merge_policy()
+            .compute_merge_candidates(&test_input)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_many_small_segments() {
+        let test_input: Vec<SegmentMeta> =
+            (0..10).map(|_| create_random_segment_meta(10)).collect();
+        let merge_candidates = test_merge_policy().compute_merge_candidates(&test_input);
+        assert!(!merge_candidates.is_empty());
+        let mut merged_segment_ids = HashSet::new();
+        for merge_candidate in &merge_candidates {
+            assert!(merge_candidate.0.len() >= 2);
+            assert!(merge_candidate.0.len() <= 4);
+            for segment_id in &merge_candidate.0 {
+                assert!(merged_segment_ids.insert(*segment_id));
+            }
+        }
+        // 10 segments, minus 3 for every merge of 4 segments, are within the 6 allowed segments.
+        assert_eq!(merge_candidates.len(), 2);
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_prefers_similar_sizes() {
+        let mut test_input = vec![create_random_segment_meta(1_000)];
+        test_input.extend((0..8).map(|_| create_random_segment_meta(100)));
+        let merge_candidates = test_merge_policy().compute_merge_candidates(&test_input);
+        assert_eq!(merge_candidates.len(), 1);
+        assert_eq!(merge_candidates[0].0.len(), 4);
+        // The large segment is left alone.
+        assert!(!merge_candidates[0].0.contains(&test_input[0].id()));
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_max_merged_segment_num_docs() {
+        let test_input: Vec<SegmentMeta> =
+            (0..20).map(|_| create_random_segment_meta(3_000)).collect();
+        let merge_candidates = test_merge_policy().compute_merge_candidates(&test_input);
+        assert!(!merge_candidates.is_empty());
+        for merge_candidate in &merge_candidates {
+            assert!(num_docs_in_candidate(merge_candidate, &test_input) <= 10_000);
+        }
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_large_segment_with_deletes() {
+        let test_input = vec![
+            create_random_segment_meta(8_000).with_delete_meta(1_000, 1),
+            create_random_segment_meta(9_000).with_delete_meta(3_500, 1),
+        ];
+        let merge_candidates = test_merge_policy().compute_merge_candidates(&test_input);
+        assert_eq!(merge_candidates.len(), 1);
+        assert_eq!(merge_candidates[0].0, vec![test_input[1].id()]);
+    }
+
+    #[test]
+    fn test_tiered_merge_policy_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let int_field = schema_builder.add_u64_field("intval", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let mut tiered_merge_policy = TieredMergePolicy::default();
+        tiered_merge_policy.set_segments_per_tier(2.0);
+        tiered_merge_policy.set_max_merge_at_once(2);
+        index_writer.set_merge_policy(Box::new(tiered_merge_policy));
+        for i in 0..8u64 {
+            index_writer.add_document(doc!(int_field => i))?;
+            index_writer.commit()?;
+        }
+        index_writer.wait_merging_threads()?;
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() <= 2);
+        assert_eq!(searcher.num_docs(), 8);
+        Ok(())
+    }
+}
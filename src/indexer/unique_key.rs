@@ -0,0 +1,51 @@
+use columnar::{BytesColumn, Column};
+
+use crate::index::SegmentReader;
+use crate::DocId;
+
+/// Value of the unique key of a document, comparable across segments.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) enum UniqueKey {
+    Term(Vec<u8>),
+    Value(u64),
+}
+
+/// Column holding the unique key of the documents of a segment.
+pub(crate) enum KeyColumn {
+    Terms(BytesColumn),
+    Values(Column<u64>),
+    Missing,
+}
+
+impl KeyColumn {
+    /// Opens the column of `key_field`, which can be a str, bytes or numerical fast field.
+    pub fn open(segment_reader: &SegmentReader, key_field: &str) -> crate::Result<KeyColumn> {
+        let fast_fields = segment_reader.fast_fields();
+        if let Some(str_column) = fast_fields.str(key_field)? {
+            return Ok(KeyColumn::Terms(BytesColumn::clone(&str_column)));
+        }
+        if let Some(bytes_column) = fast_fields.bytes(key_field)? {
+            return Ok(KeyColumn::Terms(bytes_column));
+        }
+        if let Some((column, _)) = fast_fields.u64_lenient(key_field)? {
+            return Ok(KeyColumn::Values(column));
+        }
+        Ok(KeyColumn::Missing)
+    }
+
+    /// Returns the key of a document, if it has one.
+    pub fn key(&self, doc: DocId) -> crate::Result<Option<UniqueKey>> {
+        match self {
+            KeyColumn::Terms(bytes_column) => {
+                let Some(term_ord) = bytes_column.term_ords(doc).next() else {
+                    return Ok(None);
+                };
+                let mut term_bytes = Vec::new();
+                bytes_column.ord_to_bytes(term_ord, &mut term_bytes)?;
+                Ok(Some(UniqueKey::Term(term_bytes)))
+            }
+            KeyColumn::Values(column) => Ok(column.first(doc).map(UniqueKey::Value)),
+            KeyColumn::Missing => Ok(None),
+        }
+    }
+}
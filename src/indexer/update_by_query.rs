@@ -0,0 +1,322 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::operation::UserOperation;
+use crate::collector::DocSetCollector;
+use crate::query::Query;
+use crate::{IndexWriter, ReloadPolicy, TantivyDocument, TantivyError};
+
+const DEFAULT_BATCH_SIZE: usize = 1_000;
+
+/// Callback reporting the progress of an [`IndexWriter::update_by_query`] operation.
+pub type UpdateByQueryProgressCallback = Arc<dyn Fn(&UpdateByQueryProgress) + Send + Sync>;
+
+/// Progress of an [`IndexWriter::update_by_query`] operation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct UpdateByQueryProgress {
+    /// The number of documents matching the query.
+    pub num_matching_docs: usize,
+    /// The number of documents that went through the transformation so far.
+    pub num_processed_docs: usize,
+    /// The number of documents that were modified by the transformation so far.
+    pub num_updated_docs: usize,
+}
+
+#[derive(Clone, bon::Builder)]
+/// A builder for the options of an [`IndexWriter::update_by_query`] operation.
+pub struct UpdateByQueryOptions {
+    #[builder(default = DEFAULT_BATCH_SIZE)]
+    /// The number of documents transformed and re-indexed as a single group of operations.
+    batch_size: usize,
+    /// Called after each batch, with the progress of the operation.
+    on_progress: Option<UpdateByQueryProgressCallback>,
+    /// Flag that can be set, from another thread for instance, to cancel the operation.
+    ///
+    /// The documents that were not transformed when the operation is cancelled are left
+    /// unchanged.
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+impl Default for UpdateByQueryOptions {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl UpdateByQueryOptions {
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|cancel_flag| cancel_flag.load(Ordering::Relaxed))
+    }
+}
+
+impl IndexWriter<TantivyDocument> {
+    /// Updates all of the documents matching a given query, by passing them
+    /// through a transformation.
+    ///
+    /// The query is run against the last commit. The matching documents are read from the doc
+    /// store, and passed to `transform`. If it returns a new document, the document gets
+    /// replaced by the new one, otherwise it is left unchanged.
+    ///
+    /// Documents are processed in batches of `batch_size` documents. After each batch,
+    /// the `on_progress` callback is called, and the operation stops if the `cancel_flag` was
+    /// set.
+    ///
+    /// Since documents are rebuilt from the doc store, all of the fields of the schema
+    /// are required to be stored.
+    ///
+    /// Like [`IndexWriter::delete_query`], the operation deletes the documents matching the
+    /// query that were added since the last commit: these should be committed beforehand.
+    ///
+    /// Like adds and deletes, the changes will be visible only after calling `commit()`.
+    pub fn update_by_query<F>(
+        &self,
+        query: &dyn Query,
+        options: &UpdateByQueryOptions,
+        mut transform: F,
+    ) -> crate::Result<UpdateByQueryProgress>
+    where
+        F: FnMut(TantivyDocument) -> Option<TantivyDocument>,
+    {
+        if options.batch_size == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The batch size of an update by query must be at least 1".to_string(),
+            ));
+        }
+        let schema = self.index().schema();
+        if let Some((_, field_entry)) = schema
+            .fields()
+            .find(|(_, field_entry)| !field_entry.is_stored())
+        {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Update by query requires all fields to be stored, but field {:?} is not stored",
+                field_entry.name()
+            )));
+        }
+        let reader = self
+            .index()
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+        let mut doc_addresses: Vec<_> = searcher
+            .search(query, &DocSetCollector)?
+            .into_iter()
+            .collect();
+        doc_addresses.sort();
+
+        let mut progress = UpdateByQueryProgress {
+            num_matching_docs: doc_addresses.len(),
+            ..Default::default()
+        };
+        if doc_addresses.is_empty() {
+            return Ok(progress);
+        }
+
+        // All of the matching documents are deleted at once, and then re-added, transformed
+        // or not, in batches.
+        self.delete_query(query.box_clone())?;
+        let mut cancelled = false;
+        for batch in doc_addresses.chunks(options.batch_size) {
+            cancelled = cancelled || options.is_cancelled();
+            let mut operations = Vec::with_capacity(batch.len());
+            for doc_address in batch {
+                let doc: TantivyDocument = searcher.doc(*doc_address)?;
+                if cancelled {
+                    operations.push(UserOperation::Add(doc));
+                    continue;
+                }
+                let doc = match transform(doc.clone()) {
+                    Some(transformed_doc) => {
+                        progress.num_updated_docs += 1;
+                        transformed_doc
+                    }
+                    None => doc,
+                };
+                operations.push(UserOperation::Add(doc));
+            }
+            self.run(operations)?;
+            if !cancelled {
+                progress.num_processed_docs += batch.len();
+                if let Some(on_progress) = &options.on_progress {
+                    on_progress(&progress);
+                }
+            }
+        }
+        Ok(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::{UpdateByQueryOptions, UpdateByQueryProgress};
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, Value, FAST, STORED, STRING};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
+
+    fn counts_by_id(index: &Index) -> Vec<(String, u64)> {
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        let schema = index.schema();
+        let id_field = schema.get_field("id").unwrap();
+        let count_field = schema.get_field("count").unwrap();
+        let mut counts: Vec<(String, u64)> = searcher
+            .search(&AllQuery, &TopDocs::with_limit(100))
+            .unwrap()
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                let id = doc
+                    .get_first(id_field)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+                let count = doc.get_first(count_field).unwrap().as_u64().unwrap();
+                (id, count)
+            })
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    fn create_index(num_docs: u64) -> crate::Result<(Index, IndexWriter)> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING | STORED);
+        let tag_field = schema_builder.add_text_field("tag", STRING | STORED);
+        let count_field = schema_builder.add_u64_field("count", FAST | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..num_docs {
+            let tag = if i % 2 == 0 { "even" } else { "odd" };
+            index_writer.add_document(doc!(
+                id_field => format!("doc{i}"),
+                tag_field => tag,
+                count_field => i,
+            ))?;
+        }
+        index_writer.commit()?;
+        Ok((index, index_writer))
+    }
+
+    #[test]
+    fn test_update_by_query() -> crate::Result<()> {
+        let (index, mut index_writer) = create_index(5)?;
+        let id_field = index.schema().get_field("id").unwrap();
+        let tag_field = index.schema().get_field("tag").unwrap();
+        let count_field = index.schema().get_field("count").unwrap();
+        let query = TermQuery::new(
+            Term::from_field_text(tag_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let progress_reports = Arc::new(Mutex::new(Vec::new()));
+        let progress_reports_clone = progress_reports.clone();
+        let options = UpdateByQueryOptions::builder()
+            .batch_size(2)
+            .on_progress(Arc::new(move |progress: &UpdateByQueryProgress| {
+                progress_reports_clone.lock().unwrap().push(*progress);
+            }))
+            .build();
+        let progress = index_writer.update_by_query(&query, &options, |doc| {
+            let id = doc
+                .get_first(id_field)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_string();
+            if id == "doc2" {
+                return None;
+            }
+            let count = doc.get_first(count_field).unwrap().as_u64().unwrap();
+            let mut updated_doc = TantivyDocument::default();
+            updated_doc.add_text(id_field, &id);
+            updated_doc.add_text(tag_field, "even");
+            updated_doc.add_u64(count_field, count + 100);
+            Some(updated_doc)
+        })?;
+        assert_eq!(
+            progress,
+            UpdateByQueryProgress {
+                num_matching_docs: 3,
+                num_processed_docs: 3,
+                num_updated_docs: 2,
+            }
+        );
+        assert_eq!(progress_reports.lock().unwrap().len(), 2);
+        index_writer.commit()?;
+        assert_eq!(
+            counts_by_id(&index),
+            [
+                ("doc0".to_string(), 100),
+                ("doc1".to_string(), 1),
+                ("doc2".to_string(), 2),
+                ("doc3".to_string(), 3),
+                ("doc4".to_string(), 104),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_by_query_cancelled() -> crate::Result<()> {
+        let (index, mut index_writer) = create_index(6)?;
+        let count_field = index.schema().get_field("count").unwrap();
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        let cancel_flag_clone = cancel_flag.clone();
+        let options = UpdateByQueryOptions::builder()
+            .batch_size(4)
+            .on_progress(Arc::new(move |_: &UpdateByQueryProgress| {
+                cancel_flag_clone.store(true, Ordering::Relaxed);
+            }))
+            .cancel_flag(cancel_flag)
+            .build();
+        let progress = index_writer.update_by_query(&AllQuery, &options, |mut doc| {
+            doc.add_u64(count_field, 1_000);
+            Some(doc)
+        })?;
+        assert_eq!(progress.num_matching_docs, 6);
+        assert_eq!(progress.num_processed_docs, 4);
+        assert_eq!(progress.num_updated_docs, 4);
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 6);
+        let num_values: Vec<usize> = searcher
+            .search(&AllQuery, &TopDocs::with_limit(10))?
+            .into_iter()
+            .map(|(_, doc_address)| {
+                let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+                doc.get_all(count_field).count()
+            })
+            .collect();
+        assert_eq!(num_values.iter().filter(|&&num| num == 2).count(), 4);
+        assert_eq!(num_values.iter().filter(|&&num| num == 1).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_by_query_requires_stored_fields() {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING | STORED);
+        schema_builder.add_u64_field("count", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        let query = TermQuery::new(
+            Term::from_field_text(id_field, "doc0"),
+            IndexRecordOption::Basic,
+        );
+        let err = index_writer
+            .update_by_query(&query, &UpdateByQueryOptions::default(), Some)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An invalid argument was passed: 'Update by query requires all fields to be stored, \
+             but field \"count\" is not stored'"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use common::BinarySerializable;
+
+use crate::directory::error::DeleteError;
+use crate::directory::{Directory, WritePtr};
+use crate::error::DataCorruption;
+use crate::schema::document::{
+    BinaryDocumentDeserializer, BinaryDocumentSerializer, Document, DocumentDeserialize,
+};
+use crate::store::DOC_STORE_VERSION;
+use crate::{IndexWriter, Opstamp, TantivyDocument, TantivyError, Term};
+
+/// Lists the live write-ahead log files.
+const WAL_FILES_PATH: &str = ".tantivy-wal.json";
+
+const ADD_DOCUMENT_RECORD: u8 = 0;
+const DELETE_TERM_RECORD: u8 = 1;
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct WalFiles {
+    files: Vec<PathBuf>,
+}
+
+struct WalFile {
+    path: PathBuf,
+    writer: WritePtr,
+}
+
+/// An operation recorded in the write-ahead log.
+enum WalRecord<D> {
+    AddDocument(D),
+    DeleteTerm(Term),
+}
+
+/// Front-end to an [`IndexWriter`] recording the added documents and the deleted terms
+/// in a write-ahead log as they are acknowledged.
+///
+/// The operations that were not committed yet are replayed when the writer is opened
+/// again, so that applications can commit rarely without losing the acknowledged
+/// operations when the process crashes.
+///
+/// The log is flushed after every operation, which makes it survive a crash of the
+/// process, but it is not synced to disk: the operations acknowledged right before a
+/// crash of the operating system may be lost.
+///
+/// The log files live next to the index, in files whose name starts with
+/// `.tantivy-wal`, which the garbage collection of the index leaves alone.
+/// The log is truncated after each commit and each rollback.
+pub struct WalIndexWriter<D: Document = TantivyDocument> {
+    index_writer: IndexWriter<D>,
+    wal_file: Mutex<WalFile>,
+}
+
+impl<D: Document + DocumentDeserialize> WalIndexWriter<D> {
+    /// Wraps `index_writer`, replaying the operations logged after its last commit.
+    ///
+    /// If operations are replayed, they are committed before a new log is started.
+    pub fn open(index_writer: IndexWriter<D>) -> crate::Result<Self> {
+        let directory = index_writer.index().directory().inner_directory();
+        let wal_files = read_wal_files(directory)?;
+        let commit_opstamp = index_writer.commit_opstamp();
+        let mut num_replayed_operations = 0;
+        for wal_path in &wal_files.files {
+            let wal_bytes = directory.open_read(wal_path)?.read_bytes()?;
+            for (opstamp, record) in read_records::<D>(wal_bytes.as_slice())? {
+                if opstamp < commit_opstamp {
+                    continue;
+                }
+                match record {
+                    WalRecord::AddDocument(document) => {
+                        index_writer.add_document(document)?;
+                    }
+                    WalRecord::DeleteTerm(term) => {
+                        index_writer.delete_term(term);
+                    }
+                }
+                num_replayed_operations += 1;
+            }
+        }
+        let mut index_writer = index_writer;
+        if num_replayed_operations > 0 {
+            info!("replayed {num_replayed_operations} operations from the write-ahead log");
+            index_writer.commit()?;
+        }
+        let wal_file = rotate_wal(index_writer.index().directory().inner_directory(), None)?;
+        Ok(WalIndexWriter {
+            index_writer,
+            wal_file: Mutex::new(wal_file),
+        })
+    }
+
+    /// Returns the wrapped index writer.
+    ///
+    /// The operations running directly on it, like [`IndexWriter::delete_query`],
+    /// are not logged.
+    pub fn index_writer(&self) -> &IndexWriter<D> {
+        &self.index_writer
+    }
+
+    /// Logs a document, then adds it.
+    ///
+    /// See [`IndexWriter::add_document`].
+    pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        let schema = self.index_writer.index().schema();
+        let mut document_bytes = Vec::new();
+        BinaryDocumentSerializer::new(&mut document_bytes, &schema)
+            .with_unstored_fields()
+            .serialize_doc(&document)?;
+        let mut wal_file = self.wal_file.lock().unwrap();
+        // The document is logged before it is indexed, so that it is replayed if the process
+        // crashes in between.
+        let opstamp = self.index_writer.stamp();
+        append_record(
+            &mut wal_file.writer,
+            opstamp,
+            ADD_DOCUMENT_RECORD,
+            &document_bytes,
+        )?;
+        self.index_writer
+            .add_document_with_opstamp(opstamp, document)?;
+        Ok(opstamp)
+    }
+
+    /// Deletes all of the documents containing a given term, and logs the deletion
+    /// before returning.
+    ///
+    /// See [`IndexWriter::delete_term`].
+    pub fn delete_term(&self, term: Term) -> crate::Result<Opstamp> {
+        let mut wal_file = self.wal_file.lock().unwrap();
+        let term_bytes = term.serialized_term().to_vec();
+        let opstamp = self.index_writer.delete_term(term);
+        append_record(
+            &mut wal_file.writer,
+            opstamp,
+            DELETE_TERM_RECORD,
+            &term_bytes,
+        )?;
+        Ok(opstamp)
+    }
+
+    /// Commits all of the pending changes, then truncates the log.
+    ///
+    /// See [`IndexWriter::commit`].
+    pub fn commit(&mut self) -> crate::Result<Opstamp> {
+        let opstamp = self.index_writer.commit()?;
+        self.rotate()?;
+        Ok(opstamp)
+    }
+
+    /// Truncates the log, then rolls back to the last commit.
+    ///
+    /// See [`IndexWriter::rollback`].
+    pub fn rollback(&mut self) -> crate::Result<Opstamp> {
+        self.rotate()?;
+        self.index_writer.rollback()
+    }
+
+    /// Starts a new log, and deletes the previous one.
+    fn rotate(&mut self) -> crate::Result<()> {
+        let directory = self.index_writer.index().directory().inner_directory();
+        let wal_file = self.wal_file.get_mut().unwrap();
+        *wal_file = rotate_wal(directory, Some(&wal_file.path))?;
+        Ok(())
+    }
+}
+
+fn read_wal_files(directory: &dyn Directory) -> crate::Result<WalFiles> {
+    if !directory.exists(Path::new(WAL_FILES_PATH))? {
+        return Ok(WalFiles::default());
+    }
+    let wal_files_json = directory.atomic_read(Path::new(WAL_FILES_PATH))?;
+    serde_json::from_slice(&wal_files_json).map_err(|err| {
+        DataCorruption::comment_only(format!("Invalid write-ahead log file list: {err}")).into()
+    })
+}
+
+/// Creates a new log file and makes it the only live one, before deleting the
+/// previous log files.
+fn rotate_wal(directory: &dyn Directory, current_path: Option<&Path>) -> crate::Result<WalFile> {
+    let previous_wal_files = read_wal_files(directory)?;
+    let path = PathBuf::from(format!(".tantivy-wal.{}", uuid::Uuid::new_v4().as_simple()));
+    let mut writer = directory.open_write(&path)?;
+    writer.flush()?;
+    let wal_files = WalFiles {
+        files: vec![path.clone()],
+    };
+    directory.atomic_write(Path::new(WAL_FILES_PATH), &serde_json::to_vec(&wal_files)?)?;
+    let stale_paths = previous_wal_files.files.iter().map(PathBuf::as_path);
+    for stale_path in stale_paths.chain(current_path) {
+        match directory.delete(stale_path) {
+            Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => {}
+            Err(DeleteError::IoError { io_error, .. }) => {
+                return Err(TantivyError::IoError(io_error));
+            }
+        }
+    }
+    Ok(WalFile { path, writer })
+}
+
+/// Appends a record, laid out as `[payload length: u32][payload crc32: u32][payload]`,
+/// where the payload is the opstamp, the record type, and the bytes of the operation.
+fn append_record(
+    writer: &mut WritePtr,
+    opstamp: Opstamp,
+    record_type: u8,
+    operation_bytes: &[u8],
+) -> io::Result<()> {
+    let mut payload = Vec::with_capacity(9 + operation_bytes.len());
+    opstamp.serialize(&mut payload)?;
+    record_type.serialize(&mut payload)?;
+    payload.extend_from_slice(operation_bytes);
+    (payload.len() as u32).serialize(writer)?;
+    crc32fast::hash(&payload).serialize(writer)?;
+    writer.write_all(&payload)?;
+    writer.flush()
+}
+
+/// Reads the records of a log file.
+///
+/// Reading stops at the first truncated or corrupted record, which is what a crash
+/// in the middle of an append leaves behind.
+fn read_records<D: Document + DocumentDeserialize>(
+    mut wal_bytes: &[u8],
+) -> crate::Result<Vec<(Opstamp, WalRecord<D>)>> {
+    let mut records = Vec::new();
+    while wal_bytes.len() >= 8 {
+        let payload_len = u32::deserialize(&mut wal_bytes)? as usize;
+        let crc = u32::deserialize(&mut wal_bytes)?;
+        if wal_bytes.len() < payload_len || payload_len < 9 {
+            break;
+        }
+        let (mut payload, remaining_bytes) = wal_bytes.split_at(payload_len);
+        wal_bytes = remaining_bytes;
+        if crc32fast::hash(payload) != crc {
+            break;
+        }
+        let opstamp = Opstamp::deserialize(&mut payload)?;
+        let record = match u8::deserialize(&mut payload)? {
+            ADD_DOCUMENT_RECORD => {
+                let deserializer =
+                    BinaryDocumentDeserializer::from_reader(&mut payload, DOC_STORE_VERSION)?;
+                WalRecord::AddDocument(D::deserialize(deserializer)?)
+            }
+            DELETE_TERM_RECORD => WalRecord::DeleteTerm(Term::wrap(payload.to_vec())),
+            record_type => {
+                warn!("unknown write-ahead log record type {record_type}");
+                break;
+            }
+        };
+        records.push((opstamp, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WalIndexWriter;
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    fn count_docs(index: &Index, term: Term) -> crate::Result<usize> {
+        let searcher = index.reader()?.searcher();
+        searcher.search(&TermQuery::new(term, IndexRecordOption::Basic), &Count)
+    }
+
+    #[test]
+    fn test_wal_replays_uncommitted_operations() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let index_writer: IndexWriter = index.writer_for_tests()?;
+            let mut wal_index_writer = WalIndexWriter::open(index_writer)?;
+            wal_index_writer.add_document(doc!(id_field => "a", body_field => "hello"))?;
+            wal_index_writer.commit()?;
+            wal_index_writer.add_document(doc!(id_field => "b", body_field => "hello"))?;
+            wal_index_writer.add_document(doc!(id_field => "c", body_field => "world"))?;
+            wal_index_writer.delete_term(Term::from_field_text(id_field, "a"))?;
+            // The writer is dropped without committing, as in a crash.
+        }
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let wal_index_writer = WalIndexWriter::open(index_writer)?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        assert_eq!(
+            count_docs(&index, Term::from_field_text(body_field, "hello"))?,
+            1
+        );
+        assert_eq!(count_docs(&index, Term::from_field_text(id_field, "a"))?, 0);
+        drop(wal_index_writer);
+        // The replayed operations were committed, so they are not replayed twice.
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let _wal_index_writer = WalIndexWriter::open(index_writer)?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wal_truncated_on_commit_and_rollback() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let index_writer: IndexWriter = index.writer_for_tests()?;
+            let mut wal_index_writer = WalIndexWriter::open(index_writer)?;
+            wal_index_writer.add_document(doc!(id_field => "a"))?;
+            wal_index_writer.commit()?;
+            wal_index_writer.add_document(doc!(id_field => "b"))?;
+            wal_index_writer.rollback()?;
+        }
+        let index_writer: IndexWriter = index.writer_for_tests()?;
+        let _wal_index_writer = WalIndexWriter::open(index_writer)?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+        assert_eq!(count_docs(&index, Term::from_field_text(id_field, "b"))?, 0);
+        Ok(())
+    }
+}
@@ -194,9 +194,12 @@ pub mod postings;
 
 /// Module containing the different query implementations.
 pub mod query;
+pub mod replication;
 pub mod schema;
 pub mod space_usage;
 pub mod store;
+pub mod suggest;
+pub mod telemetry;
 pub mod termdict;
 
 mod docset;
@@ -205,7 +208,9 @@ mod reader;
 #[cfg(test)]
 mod compat_tests;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
+pub use self::reader::{
+    IndexReader, IndexReaderBuilder, PointInTimeId, ReloadPolicy, Warmer,
+};
 pub mod snippet;
 
 use std::fmt;
@@ -218,13 +223,17 @@ use serde::{Deserialize, Serialize};
 pub use self::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
 #[doc(hidden)]
 pub use crate::core::json_utils;
-pub use crate::core::{Executor, Searcher, SearcherGeneration};
+pub use crate::core::{
+    Executor, MultiSearcher, Scroll, ScrollCursor, Searcher, SearcherGeneration,
+};
 pub use crate::directory::Directory;
 pub use crate::index::{
-    Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order, Segment,
-    SegmentMeta, SegmentReader,
+    DocBoostMode, Index, IndexBuilder, IndexMeta, IndexSettings, IndexSortByField,
+    InvertedIndexReader, Order, Segment, SegmentMeta, SegmentReader,
 };
-pub use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
+#[cfg(feature = "mmap")]
+pub use crate::index::{IndexAliases, PartitionInterval, TimePartitionedIndex};
+pub use crate::indexer::{BulkIndexBuilder, IndexWriter, SingleSegmentIndexWriter};
 pub use crate::schema::{Document, TantivyDocument, Term};
 
 /// Index format version.
@@ -281,7 +290,8 @@ pub fn version_string() -> &'static str {
 /// Defines tantivy's merging strategy
 pub mod merge_policy {
     pub use crate::indexer::{
-        DefaultMergePolicy, LogMergePolicy, MergeCandidate, MergePolicy, NoMergePolicy,
+        DeduplicatingMergePolicy, DefaultMergePolicy, LogMergePolicy, MergeCandidate, MergePolicy,
+        NoMergePolicy, TieredMergePolicy,
     };
 }
 
@@ -979,6 +989,7 @@ pub mod tests {
                 delimiter: crate::query_grammar::Delimiter::None,
                 slop: 0,
                 prefix: false,
+                fuzziness: None,
             };
             assert_eq!(get_doc_ids(user_input_literal), vec![DocAddress::new(0, 0)]);
         }
@@ -989,6 +1000,7 @@ pub mod tests {
                 delimiter: crate::query_grammar::Delimiter::None,
                 slop: 0,
                 prefix: false,
+                fuzziness: None,
             };
             assert_eq!(get_doc_ids(user_input_literal), vec![DocAddress::new(0, 0)]);
         }
@@ -999,6 +1011,7 @@ pub mod tests {
                 delimiter: crate::query_grammar::Delimiter::None,
                 slop: 0,
                 prefix: false,
+                fuzziness: None,
             };
             assert_eq!(get_doc_ids(user_input_literal), vec![DocAddress::new(0, 0)]);
         }
@@ -1009,6 +1022,7 @@ pub mod tests {
                 delimiter: crate::query_grammar::Delimiter::None,
                 slop: 0,
                 prefix: false,
+                fuzziness: None,
             };
             assert_eq!(get_doc_ids(user_input_literal), vec![DocAddress::new(0, 0)]);
         }
@@ -1019,6 +1033,7 @@ pub mod tests {
                 delimiter: crate::query_grammar::Delimiter::None,
                 slop: 0,
                 prefix: false,
+                fuzziness: None,
             };
             assert_eq!(get_doc_ids(user_input_literal), vec![DocAddress::new(0, 0)]);
         }
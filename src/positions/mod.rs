@@ -28,6 +28,14 @@
 //! * *VIntPosDeltas* := *VIntPosDelta*^(*P* % 128).
 //!
 //! The skip widths encoded separately makes it easy and fast to rapidly skip over n positions.
+//!
+//! If the field records offsets (See
+//! [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`](crate::schema::IndexRecordOption)),
+//! the positions of each term are preceded by the byte offsets of its tokens, encoded the same
+//! way, as an offset and a length per position:
+//! * *PositionsWithOffsets* := *OffsetsNumBytes* *Offsets* *Positions*
+//! * *OffsetsNumBytes* := the number of bytes of *Offsets*, encoded as a variable byte integer.
+//! * *Offsets* := the `(offset_from, offset_to - offset_from)` pairs, encoded like *Positions*.
 mod reader;
 mod serializer;
 
@@ -12,79 +12,154 @@ use crate::postings::compression::{BlockEncoder, VIntEncoder};
 pub struct PositionSerializer<W: io::Write> {
     block_encoder: BlockEncoder,
     positions_wrt: CountingWriter<W>,
-    positions_buffer: Vec<u8>,
+    positions: BlockStream,
+    offsets_opt: Option<OffsetsStream>,
+}
+
+/// The offsets of the tokens of a term, encoded like its positions, and serialized
+/// before them.
+struct OffsetsStream {
+    offsets: BlockStream,
+    offsets_buffer: Vec<u8>,
+}
+
+/// Values encoded in bitpacked blocks, and a final variable int encoded block.
+struct BlockStream {
+    buffer: Vec<u8>,
     block: Vec<u32>,
     bit_widths: Vec<u8>,
 }
 
-impl<W: io::Write> PositionSerializer<W> {
-    /// Creates a new PositionSerializer writing into the given positions_wrt.
-    pub fn new(positions_wrt: W) -> PositionSerializer<W> {
-        PositionSerializer {
-            block_encoder: BlockEncoder::new(),
-            positions_wrt: CountingWriter::wrap(positions_wrt),
-            positions_buffer: Vec::with_capacity(128_000),
+impl BlockStream {
+    fn with_capacity(buffer_capacity: usize) -> BlockStream {
+        BlockStream {
+            buffer: Vec::with_capacity(buffer_capacity),
             block: Vec::with_capacity(128),
             bit_widths: Vec::new(),
         }
     }
 
-    /// Returns the number of bytes written in the positions write object
-    /// at this point.
-    /// When called before writing the positions of a term, this value is used as
-    /// start offset.
-    /// When called after writing the positions of a term, this value is used as
-    /// end offset.
-    pub fn written_bytes(&self) -> u64 {
-        self.positions_wrt.written_bytes()
-    }
-
     fn remaining_block_len(&self) -> usize {
         COMPRESSION_BLOCK_SIZE - self.block.len()
     }
 
-    /// Writes all of the given positions delta.
-    pub fn write_positions_delta(&mut self, mut positions_delta: &[u32]) {
-        while !positions_delta.is_empty() {
+    fn write(&mut self, block_encoder: &mut BlockEncoder, mut vals: &[u32]) {
+        while !vals.is_empty() {
             let remaining_block_len = self.remaining_block_len();
-            let num_to_write = remaining_block_len.min(positions_delta.len());
-            self.block.extend(&positions_delta[..num_to_write]);
-            positions_delta = &positions_delta[num_to_write..];
+            let num_to_write = remaining_block_len.min(vals.len());
+            self.block.extend(&vals[..num_to_write]);
+            vals = &vals[num_to_write..];
             if self.remaining_block_len() == 0 {
-                self.flush_block();
+                self.flush_block(block_encoder);
             }
         }
     }
 
-    fn flush_block(&mut self) {
-        // encode the positions in the block
+    fn flush_block(&mut self, block_encoder: &mut BlockEncoder) {
+        // encode the values in the block
         if self.block.is_empty() {
             return;
         }
         if self.block.len() == COMPRESSION_BLOCK_SIZE {
-            let (bit_width, block_encoded): (u8, &[u8]) = self
-                .block_encoder
-                .compress_block_unsorted(&self.block[..], false);
+            let (bit_width, block_encoded): (u8, &[u8]) =
+                block_encoder.compress_block_unsorted(&self.block[..], false);
             self.bit_widths.push(bit_width);
-            self.positions_buffer.extend(block_encoded);
+            self.buffer.extend(block_encoded);
         } else {
             debug_assert!(self.block.len() < COMPRESSION_BLOCK_SIZE);
-            let block_vint_encoded = self.block_encoder.compress_vint_unsorted(&self.block[..]);
-            self.positions_buffer.extend_from_slice(block_vint_encoded);
+            let block_vint_encoded = block_encoder.compress_vint_unsorted(&self.block[..]);
+            self.buffer.extend_from_slice(block_vint_encoded);
         }
         self.block.clear();
     }
 
-    /// Close the positions for the current term.
-    pub fn close_term(&mut self) -> io::Result<()> {
-        self.flush_block();
-        VInt(self.bit_widths.len() as u64).serialize(&mut self.positions_wrt)?;
-        self.positions_wrt.write_all(&self.bit_widths[..])?;
-        self.positions_wrt.write_all(&self.positions_buffer)?;
+    /// Writes the values of the current term, and clears the stream for the next term.
+    fn close_term<W: io::Write>(
+        &mut self,
+        block_encoder: &mut BlockEncoder,
+        wrt: &mut W,
+    ) -> io::Result<()> {
+        self.flush_block(block_encoder);
+        VInt(self.bit_widths.len() as u64).serialize(wrt)?;
+        wrt.write_all(&self.bit_widths[..])?;
+        wrt.write_all(&self.buffer)?;
         self.bit_widths.clear();
-        self.positions_buffer.clear();
+        self.buffer.clear();
         Ok(())
     }
+}
+
+impl<W: io::Write> PositionSerializer<W> {
+    /// Creates a new PositionSerializer writing into the given positions_wrt.
+    pub fn new(positions_wrt: W) -> PositionSerializer<W> {
+        PositionSerializer {
+            block_encoder: BlockEncoder::new(),
+            positions_wrt: CountingWriter::wrap(positions_wrt),
+            positions: BlockStream::with_capacity(128_000),
+            offsets_opt: None,
+        }
+    }
+
+    /// Makes the serializer write the offsets of the tokens of each term, before its positions.
+    ///
+    /// The offsets of all of the positions of the term have to be written with
+    /// [`PositionSerializer::write_offsets`].
+    pub fn with_offsets(mut self) -> PositionSerializer<W> {
+        self.offsets_opt = Some(OffsetsStream {
+            offsets: BlockStream::with_capacity(128_000),
+            offsets_buffer: Vec::new(),
+        });
+        self
+    }
+
+    /// Returns the number of bytes written in the positions write object
+    /// at this point.
+    /// When called before writing the positions of a term, this value is used as
+    /// start offset.
+    /// When called after writing the positions of a term, this value is used as
+    /// end offset.
+    pub fn written_bytes(&self) -> u64 {
+        self.positions_wrt.written_bytes()
+    }
+
+    /// Writes all of the given positions delta.
+    pub fn write_positions_delta(&mut self, positions_delta: &[u32]) {
+        self.positions
+            .write(&mut self.block_encoder, positions_delta);
+    }
+
+    /// Writes the byte offsets of the tokens of the given positions, as `(offset_from,
+    /// offset_to)` pairs.
+    ///
+    /// Does nothing if the serializer was not created [`with_offsets`](Self::with_offsets).
+    pub fn write_offsets(&mut self, offsets: &[(u32, u32)]) {
+        if let Some(offsets_stream) = self.offsets_opt.as_mut() {
+            for &(offset_from, offset_to) in offsets {
+                offsets_stream.offsets.write(
+                    &mut self.block_encoder,
+                    &[offset_from, offset_to - offset_from],
+                );
+            }
+        }
+    }
+
+    /// Close the positions for the current term.
+    ///
+    /// If the serializer was created [`with_offsets`](Self::with_offsets), the positions of the
+    /// term are preceded by the length of its offsets and its offsets.
+    pub fn close_term(&mut self) -> io::Result<()> {
+        if let Some(offsets_stream) = self.offsets_opt.as_mut() {
+            offsets_stream.offsets_buffer.clear();
+            offsets_stream
+                .offsets
+                .close_term(&mut self.block_encoder, &mut offsets_stream.offsets_buffer)?;
+            VInt(offsets_stream.offsets_buffer.len() as u64).serialize(&mut self.positions_wrt)?;
+            self.positions_wrt
+                .write_all(&offsets_stream.offsets_buffer)?;
+        }
+        self.positions
+            .close_term(&mut self.block_encoder, &mut self.positions_wrt)
+    }
 
     /// Close the positions for this term and flushes the data.
     pub fn close(mut self) -> io::Result<()> {
@@ -56,6 +56,25 @@ impl<Rec: Recorder> PostingsWriter for JsonPostingsWriter<Rec> {
         );
     }
 
+    fn index_reversed_text(
+        &mut self,
+        doc_id: DocId,
+        token_stream: &mut dyn TokenStream,
+        term_buffer: &mut Term,
+        ctx: &mut IndexingContext,
+        start_position: u32,
+        start_offset: u32,
+    ) {
+        self.str_posting_writer.index_reversed_text(
+            doc_id,
+            token_stream,
+            term_buffer,
+            ctx,
+            start_position,
+            start_offset,
+        );
+    }
+
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
         &self,
@@ -2,7 +2,7 @@ use std::io;
 use std::marker::PhantomData;
 use std::ops::Range;
 
-use stacker::Addr;
+use stacker::{Addr, MemoryArena};
 
 use crate::fieldnorm::FieldNormReaders;
 use crate::indexer::path_to_unordered_id::OrderedPathId;
@@ -10,7 +10,7 @@ use crate::postings::recorder::{BufferLender, Recorder};
 use crate::postings::{
     FieldSerializer, IndexingContext, InvertedIndexSerializer, PerFieldPostingsWriter,
 };
-use crate::schema::{Field, Schema, Term, Type};
+use crate::schema::{Field, Schema, Term, Type, REVERSED_TOKEN_MARKER};
 use crate::tokenizer::{Token, TokenStream, MAX_TOKEN_LEN};
 use crate::DocId;
 
@@ -97,6 +97,9 @@ pub(crate) fn serialize_postings(
 pub(crate) struct IndexingPosition {
     pub num_tokens: u32,
     pub end_position: u32,
+    /// Byte offset of the start of the next value of the field, in the text made of the
+    /// values joined by a space.
+    pub end_offset: u32,
 }
 
 /// The `PostingsWriter` is in charge of receiving documenting
@@ -113,6 +116,21 @@ pub(crate) trait PostingsWriter: Send + Sync {
     ///   information.
     fn subscribe(&mut self, doc: DocId, pos: u32, term: &Term, ctx: &mut IndexingContext);
 
+    /// Record that a document contains a term at a given position, along with the byte
+    /// offsets of its token.
+    ///
+    /// Postings writers that do not record offsets simply call `subscribe`.
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        pos: u32,
+        _offsets: (u32, u32),
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
+        self.subscribe(doc, pos, term, ctx);
+    }
+
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
@@ -135,6 +153,7 @@ pub(crate) trait PostingsWriter: Send + Sync {
         let end_of_path_idx = term_buffer.len_bytes();
         let mut num_tokens = 0;
         let mut end_position = indexing_position.end_position;
+        let start_offset = indexing_position.end_offset;
         token_stream.process(&mut |token: &Token| {
             // We skip all tokens with a len greater than u16.
             if token.text.len() > MAX_TOKEN_LEN {
@@ -150,7 +169,11 @@ pub(crate) trait PostingsWriter: Send + Sync {
             term_buffer.append_bytes(token.text.as_bytes());
             let start_position = indexing_position.end_position + token.position as u32;
             end_position = end_position.max(start_position + token.position_length as u32);
-            self.subscribe(doc_id, start_position, term_buffer, ctx);
+            let offsets = (
+                start_offset + token.offset_from as u32,
+                start_offset + token.offset_to as u32,
+            );
+            self.subscribe_with_offsets(doc_id, start_position, offsets, term_buffer, ctx);
             num_tokens += 1;
         });
 
@@ -159,6 +182,23 @@ pub(crate) trait PostingsWriter: Send + Sync {
         term_buffer.truncate_value_bytes(end_of_path_idx);
     }
 
+    /// Tokenize a text and subscribe the reversed form of all of its tokens, prefixed by
+    /// [`REVERSED_TOKEN_MARKER`].
+    ///
+    /// `start_position` and `start_offset` are the `end_position` and the `end_offset` of the
+    /// [`IndexingPosition`] before the same text was indexed using `index_text`, so that
+    /// reversed tokens get the position and the offsets of the original ones. Contrary to
+    /// `index_text`, the tokens are not counted in the total number of tokens.
+    fn index_reversed_text(
+        &mut self,
+        doc_id: DocId,
+        token_stream: &mut dyn TokenStream,
+        term_buffer: &mut Term,
+        ctx: &mut IndexingContext,
+        start_position: u32,
+        start_offset: u32,
+    );
+
     fn total_num_tokens(&self) -> u64;
 }
 
@@ -194,14 +234,22 @@ impl<Rec: Recorder> SpecializedPostingsWriter<Rec> {
         serializer.close_term()?;
         Ok(())
     }
-}
 
-impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
     #[inline]
-    fn subscribe(&mut self, doc: DocId, position: u32, term: &Term, ctx: &mut IndexingContext) {
+    fn record_position(
+        &mut self,
+        doc: DocId,
+        position: u32,
+        offsets: Option<(u32, u32)>,
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
         debug_assert!(term.serialized_term().len() >= 4);
-        self.total_num_tokens += 1;
         let (term_index, arena) = (&mut ctx.term_index, &mut ctx.arena);
+        let record = |recorder: &mut Rec, arena: &mut MemoryArena| match offsets {
+            Some(offsets) => recorder.record_position_with_offsets(position, offsets, arena),
+            None => recorder.record_position(position, arena),
+        };
         term_index.mutate_or_create(term.serialized_term(), |opt_recorder: Option<Rec>| {
             if let Some(mut recorder) = opt_recorder {
                 let current_doc = recorder.current_doc();
@@ -209,16 +257,71 @@ impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
                     recorder.close_doc(arena);
                     recorder.new_doc(doc, arena);
                 }
-                recorder.record_position(position, arena);
+                record(&mut recorder, arena);
                 recorder
             } else {
                 let mut recorder = Rec::default();
                 recorder.new_doc(doc, arena);
-                recorder.record_position(position, arena);
+                record(&mut recorder, arena);
                 recorder
             }
         });
     }
+}
+
+impl<Rec: Recorder> PostingsWriter for SpecializedPostingsWriter<Rec> {
+    #[inline]
+    fn subscribe(&mut self, doc: DocId, position: u32, term: &Term, ctx: &mut IndexingContext) {
+        self.total_num_tokens += 1;
+        self.record_position(doc, position, None, term, ctx);
+    }
+
+    #[inline]
+    fn subscribe_with_offsets(
+        &mut self,
+        doc: DocId,
+        position: u32,
+        offsets: (u32, u32),
+        term: &Term,
+        ctx: &mut IndexingContext,
+    ) {
+        self.total_num_tokens += 1;
+        self.record_position(doc, position, Some(offsets), term, ctx);
+    }
+
+    fn index_reversed_text(
+        &mut self,
+        doc_id: DocId,
+        token_stream: &mut dyn TokenStream,
+        term_buffer: &mut Term,
+        ctx: &mut IndexingContext,
+        start_position: u32,
+        start_offset: u32,
+    ) {
+        let end_of_path_idx = term_buffer.len_bytes();
+        let mut char_buffer = [0u8; 4];
+        token_stream.process(&mut |token: &Token| {
+            if token.text.len() + REVERSED_TOKEN_MARKER.len_utf8() > MAX_TOKEN_LEN {
+                return;
+            }
+            term_buffer.truncate_value_bytes(end_of_path_idx);
+            term_buffer.append_bytes(
+                REVERSED_TOKEN_MARKER
+                    .encode_utf8(&mut char_buffer)
+                    .as_bytes(),
+            );
+            for c in token.text.chars().rev() {
+                term_buffer.append_bytes(c.encode_utf8(&mut char_buffer).as_bytes());
+            }
+            let position = start_position + token.position as u32;
+            let offsets = (
+                start_offset + token.offset_from as u32,
+                start_offset + token.offset_to as u32,
+            );
+            self.record_position(doc_id, position, Some(offsets), term_buffer, ctx);
+        });
+        term_buffer.truncate_value_bytes(end_of_path_idx);
+    }
 
     fn serialize(
         &self,
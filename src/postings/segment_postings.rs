@@ -17,6 +17,7 @@ pub struct SegmentPostings {
     pub(crate) block_cursor: BlockSegmentPostings,
     cur: usize,
     position_reader: Option<PositionReader>,
+    offsets_reader: Option<PositionReader>,
 }
 
 impl SegmentPostings {
@@ -26,6 +27,7 @@ impl SegmentPostings {
             block_cursor: BlockSegmentPostings::empty(),
             cur: 0,
             position_reader: None,
+            offsets_reader: None,
         }
     }
 
@@ -150,10 +152,61 @@ impl SegmentPostings {
             block_cursor: segment_block_postings,
             cur: 0, // cursor within the block
             position_reader,
+            offsets_reader: None,
+        }
+    }
+
+    /// Sets the reader of the offsets of the tokens of the term, which are encoded like
+    /// positions, with two values per position.
+    pub(crate) fn with_offsets_reader(
+        mut self,
+        offsets_reader: Option<PositionReader>,
+    ) -> SegmentPostings {
+        self.offsets_reader = offsets_reader;
+        self
+    }
+
+    /// Returns the byte offsets of the tokens of the term in the current document, as
+    /// `(offset_from, offset_to)` pairs, in the order of the positions.
+    ///
+    /// The offsets of the values of a multivalued field are expressed in the text made of its
+    /// values joined by a space.
+    ///
+    /// The output is left empty if the offsets were not requested, or are not recorded for the
+    /// field (See [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`]).
+    ///
+    /// [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`]:
+    /// crate::schema::IndexRecordOption::WithFreqsAndPositionsAndOffsets
+    pub fn offsets(&mut self, output: &mut Vec<(u32, u32)>) {
+        output.clear();
+        let Some(offsets_reader) = self.offsets_reader.as_mut() else {
+            return;
+        };
+        let mut read_offset = 2 * position_read_offset(&self.block_cursor, self.cur);
+        let mut remaining = 2 * self.block_cursor.freq(self.cur) as usize;
+        // The offsets are decoded one block at a time, through a stack buffer.
+        let mut chunk = [0u32; COMPRESSION_BLOCK_SIZE];
+        while remaining > 0 {
+            let chunk_len = remaining.min(COMPRESSION_BLOCK_SIZE);
+            offsets_reader.read(read_offset, &mut chunk[..chunk_len]);
+            output.extend(
+                chunk[..chunk_len].chunks_exact(2).map(|offset_and_len| {
+                    (offset_and_len[0], offset_and_len[0] + offset_and_len[1])
+                }),
+            );
+            read_offset += chunk_len as u64;
+            remaining -= chunk_len;
         }
     }
 }
 
+/// Returns the index of the first position of the document at `cur` in the current block,
+/// among all of the positions of the term.
+fn position_read_offset(block_cursor: &BlockSegmentPostings, cur: usize) -> u64 {
+    block_cursor.position_offset()
+        + (block_cursor.freqs()[..cur].iter().cloned().sum::<u32>() as u64)
+}
+
 impl DocSet for SegmentPostings {
     // goes to the next element.
     // next needs to be called a first time to point to the correct element.
@@ -245,11 +298,7 @@ impl Postings for SegmentPostings {
                 !self.block_cursor.freqs().is_empty(),
                 "No positions available"
             );
-            let read_offset = self.block_cursor.position_offset()
-                + (self.block_cursor.freqs()[..self.cur]
-                    .iter()
-                    .cloned()
-                    .sum::<u32>() as u64);
+            let read_offset = position_read_offset(&self.block_cursor, self.cur);
             // TODO: instead of zeroing the output, we could use MaybeUninit or similar.
             output.resize(prev_len + term_freq as usize, 0u32);
             position_reader.read(read_offset, &mut output[prev_len..]);
@@ -11,7 +11,7 @@ use crate::positions::PositionSerializer;
 use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
 use crate::postings::skip::SkipSerializer;
 use crate::query::Bm25Weight;
-use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
+use crate::schema::{Field, FieldEntry, FieldNormPrecision, FieldType, IndexRecordOption, Schema};
 use crate::termdict::TermDictionaryBuilder;
 use crate::{DocId, Score};
 
@@ -134,7 +134,9 @@ impl<'a> FieldSerializer<'a> {
             index_record_option,
             fieldnorm_reader,
         );
-        let positions_serializer_opt = if index_record_option.has_positions() {
+        let positions_serializer_opt = if index_record_option.has_offsets() {
+            Some(PositionSerializer::new(positions_write).with_offsets())
+        } else if index_record_option.has_positions() {
             Some(PositionSerializer::new(positions_write))
         } else {
             None
@@ -205,6 +207,16 @@ impl<'a> FieldSerializer<'a> {
         }
     }
 
+    /// Serialize the byte offsets of the tokens of the positions written by the last call to
+    /// `write_doc`, as `(offset_from, offset_to)` pairs.
+    ///
+    /// Offsets are ignored by the serializer if the field does not record them.
+    pub fn write_offsets(&mut self, offsets: &[(u32, u32)]) {
+        if let Some(positions_serializer) = self.positions_serializer_opt.as_mut() {
+            positions_serializer.write_offsets(offsets);
+        }
+    }
+
     /// Finish the serialization for this term postings.
     ///
     /// If the current block is incomplete, it needs to be encoded
@@ -344,9 +356,12 @@ impl<W: Write> PostingsSerializer<W> {
             return;
         }
 
-        let num_docs_in_segment: u64 =
+        let (num_docs_in_segment, fieldnorm_precision): (u64, FieldNormPrecision) =
             if let Some(fieldnorm_reader) = self.fieldnorm_reader.as_ref() {
-                fieldnorm_reader.num_docs() as u64
+                (
+                    fieldnorm_reader.num_docs() as u64,
+                    fieldnorm_reader.precision(),
+                )
             } else {
                 return;
             };
@@ -355,11 +370,14 @@ impl<W: Write> PostingsSerializer<W> {
             return;
         }
 
-        self.bm25_weight = Some(Bm25Weight::for_one_term_without_explain(
-            term_doc_freq as u64,
-            num_docs_in_segment,
-            self.avg_fieldnorm,
-        ));
+        self.bm25_weight = Some(
+            Bm25Weight::for_one_term_without_explain(
+                term_doc_freq as u64,
+                num_docs_in_segment,
+                self.avg_fieldnorm,
+            )
+            .with_fieldnorm_precision(fieldnorm_precision),
+        );
     }
 
     fn write_block(&mut self) {
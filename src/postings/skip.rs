@@ -165,7 +165,7 @@ impl SkipReader {
                 block_wand_fieldnorm_id,
                 block_wand_term_freq,
                 ..
-            } => Some(bm25_weight.score(block_wand_fieldnorm_id, block_wand_term_freq)),
+            } => Some(bm25_weight.block_max_score(block_wand_fieldnorm_id, block_wand_term_freq)),
             BlockInfo::VInt { .. } => None,
         }
     }
@@ -214,7 +214,8 @@ impl SkipReader {
                     block_wand_term_freq,
                 };
             }
-            IndexRecordOption::WithFreqsAndPositions => {
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => {
                 let tf_num_bits = bytes[5];
                 let tf_sum = read_u32(&bytes[6..10]);
                 let block_wand_fieldnorm_id = bytes[10];
@@ -32,6 +32,10 @@ impl Weight for AllWeight {
         }
         Ok(Explanation::new("AllQuery", 1.0))
     }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        Ok(reader.num_docs().min(limit))
+    }
 }
 
 /// Scorer associated with the `AllQuery` query.
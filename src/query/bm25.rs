@@ -1,13 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::fieldnorm::FieldNormReader;
 use crate::query::Explanation;
-use crate::schema::Field;
+use crate::schema::{Field, FieldNormPrecision, Similarity, DEFAULT_B, DEFAULT_K1};
 use crate::{Score, Searcher, Term};
 
-const K1: Score = 1.2;
-const B: Score = 0.75;
-
 /// An interface to compute the statistics needed in BM25 scoring.
 ///
 /// The standard implementation is a [Searcher] but you can also
@@ -22,6 +18,18 @@ pub trait Bm25StatisticsProvider {
 
     /// The number of documents containing the given term.
     fn doc_freq(&self, term: &Term) -> crate::Result<u64>;
+
+    /// The total number of occurrences of the given term across all documents in the index.
+    ///
+    /// It is only used by the language model [similarities](Similarity). The default
+    /// implementation returns an error, so that providers that do not implement it can still
+    /// be used with BM25.
+    fn total_term_freq(&self, term: &Term) -> crate::Result<u64> {
+        Err(crate::TantivyError::InvalidArgument(format!(
+            "The statistics provider does not provide the total term frequency of {term:?}, \
+             which is required by the language model similarities"
+        )))
+    }
 }
 
 impl Bm25StatisticsProvider for Searcher {
@@ -47,6 +55,10 @@ impl Bm25StatisticsProvider for Searcher {
     fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
         self.doc_freq(term)
     }
+
+    fn total_term_freq(&self, term: &Term) -> crate::Result<u64> {
+        self.total_term_freq(term)
+    }
 }
 
 pub(crate) fn idf(doc_freq: u64, doc_count: u64) -> Score {
@@ -55,19 +67,124 @@ pub(crate) fn idf(doc_freq: u64, doc_count: u64) -> Score {
     (1.0 + x).ln()
 }
 
-fn cached_tf_component(fieldnorm: u32, average_fieldnorm: Score) -> Score {
-    K1 * (1.0 - B + B * fieldnorm as Score / average_fieldnorm)
+fn one_term_idf_explain(term_doc_freq: u64, total_num_docs: u64) -> Explanation {
+    let idf = idf(term_doc_freq, total_num_docs);
+    let mut idf_explain =
+        Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
+    idf_explain.add_const(
+        "n, number of docs containing this term",
+        term_doc_freq as Score,
+    );
+    idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+    idf_explain
+}
+
+fn cached_tf_component(fieldnorm: u32, average_fieldnorm: Score, k1: Score, b: Score) -> Score {
+    k1 * (1.0 - b + b * fieldnorm as Score / average_fieldnorm)
+}
+
+fn compute_tf_cache(
+    average_fieldnorm: Score,
+    k1: Score,
+    b: Score,
+    precision: FieldNormPrecision,
+) -> [Score; 256] {
+    let mut cache: [Score; 256] = [0.0; 256];
+    for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
+        let fieldnorm = precision.id_to_fieldnorm(fieldnorm_id as u8);
+        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm, k1, b);
+    }
+    cache
 }
 
-fn compute_tf_cache(average_fieldnorm: Score) -> [Score; 256] {
+/// Computes the component of the language model scores only depending on the fieldnorm.
+fn compute_language_model_cache(
+    scoring_function: &ScoringFunction,
+    precision: FieldNormPrecision,
+) -> [Score; 256] {
     let mut cache: [Score; 256] = [0.0; 256];
     for (fieldnorm_id, cache_mut) in cache.iter_mut().enumerate() {
-        let fieldnorm = FieldNormReader::id_to_fieldnorm(fieldnorm_id as u8);
-        *cache_mut = cached_tf_component(fieldnorm, average_fieldnorm);
+        let fieldnorm = precision.id_to_fieldnorm(fieldnorm_id as u8) as Score;
+        *cache_mut = match *scoring_function {
+            ScoringFunction::Bm25 { .. } => 0.0,
+            ScoringFunction::LmDirichlet { mu, .. } => (mu / (fieldnorm + mu)).ln(),
+            ScoringFunction::LmJelinekMercer { .. } => fieldnorm.max(1.0),
+        };
     }
     cache
 }
 
+/// The probability of each term in the whole collection, smoothed so that it is never 0.
+fn collection_probabilities(
+    statistics: &dyn Bm25StatisticsProvider,
+    terms: &[Term],
+) -> crate::Result<Vec<Score>> {
+    let total_num_tokens = statistics.total_num_tokens(terms[0].field())?;
+    terms
+        .iter()
+        .map(|term| {
+            let total_term_freq = statistics.total_term_freq(term)?;
+            Ok((total_term_freq as Score + 1.0) / (total_num_tokens as Score + 1.0))
+        })
+        .collect()
+}
+
+/// The function turning a fieldnorm and a term frequency into a score.
+///
+/// The language models score phrases as the sum of the scores of their terms, each with the
+/// phrase frequency.
+#[derive(Clone)]
+enum ScoringFunction {
+    Bm25 {
+        k1: Score,
+        b: Score,
+    },
+    LmDirichlet {
+        mu: Score,
+        collection_probabilities: Vec<Score>,
+    },
+    LmJelinekMercer {
+        lambda: Score,
+        collection_probabilities: Vec<Score>,
+    },
+}
+
+impl ScoringFunction {
+    fn collection_probabilities(&self) -> &[Score] {
+        match self {
+            ScoringFunction::Bm25 { .. } => &[],
+            ScoringFunction::LmDirichlet {
+                collection_probabilities,
+                ..
+            }
+            | ScoringFunction::LmJelinekMercer {
+                collection_probabilities,
+                ..
+            } => collection_probabilities,
+        }
+    }
+
+    /// Computes the language model score of a term, given its probability in the collection
+    /// and the cached component of the fieldnorm.
+    #[inline]
+    fn language_model_score(
+        &self,
+        collection_probability: Score,
+        fieldnorm_component: Score,
+        term_freq: Score,
+    ) -> Score {
+        match *self {
+            ScoringFunction::Bm25 { .. } => 0.0,
+            ScoringFunction::LmDirichlet { mu, .. } => {
+                ((term_freq / (mu * collection_probability)).ln_1p() + fieldnorm_component).max(0.0)
+            }
+            ScoringFunction::LmJelinekMercer { lambda, .. } => ((1.0 - lambda) * term_freq
+                / (fieldnorm_component * lambda * collection_probability))
+                .ln_1p(),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Bm25Params {
     pub idf: Score,
@@ -75,12 +192,17 @@ pub struct Bm25Params {
 }
 
 /// A struct used for computing BM25 scores.
+///
+/// Despite its name, it computes the scores of the other [similarities](Similarity) too.
 #[derive(Clone)]
 pub struct Bm25Weight {
     idf_explain: Option<Explanation>,
     weight: Score,
+    boost: Score,
     cache: [Score; 256],
     average_fieldnorm: Score,
+    scoring_function: ScoringFunction,
+    fieldnorm_precision: FieldNormPrecision,
 }
 
 impl Bm25Weight {
@@ -89,16 +211,46 @@ impl Bm25Weight {
         Bm25Weight {
             idf_explain: self.idf_explain.clone(),
             weight: self.weight * boost,
+            boost: self.boost * boost,
             cache: self.cache,
             average_fieldnorm: self.average_fieldnorm,
+            scoring_function: self.scoring_function.clone(),
+            fieldnorm_precision: self.fieldnorm_precision,
         }
     }
 
+    /// Decodes the fieldnorm ids with the given precision, instead of the compact one.
+    ///
+    /// It has to match the [`FieldNormPrecision`] of the field the fieldnorm ids come from.
+    #[must_use]
+    pub fn with_fieldnorm_precision(mut self, precision: FieldNormPrecision) -> Bm25Weight {
+        self.cache = match self.scoring_function {
+            ScoringFunction::Bm25 { k1, b } => {
+                compute_tf_cache(self.average_fieldnorm, k1, b, precision)
+            }
+            _ => compute_language_model_cache(&self.scoring_function, precision),
+        };
+        self.fieldnorm_precision = precision;
+        self
+    }
+
     /// Construct a [Bm25Weight] for a phrase of terms.
     pub fn for_terms(
         statistics: &dyn Bm25StatisticsProvider,
         terms: &[Term],
     ) -> crate::Result<Bm25Weight> {
+        Bm25Weight::for_terms_with_similarity(statistics, terms, Similarity::BM25)
+    }
+
+    /// Construct a weight scoring a phrase of terms with the given similarity.
+    ///
+    /// Returns an error if the parameters of the similarity are invalid.
+    pub fn for_terms_with_similarity(
+        statistics: &dyn Bm25StatisticsProvider,
+        terms: &[Term],
+        similarity: Similarity,
+    ) -> crate::Result<Bm25Weight> {
+        similarity.validate()?;
         assert!(!terms.is_empty(), "Bm25 requires at least one term");
         let field = terms[0].field();
         for term in &terms[1..] {
@@ -109,26 +261,47 @@ impl Bm25Weight {
             );
         }
 
+        let (k1, b) = match similarity {
+            Similarity::Bm25 { k1, b } => (k1, b),
+            Similarity::LmDirichlet { mu } => {
+                return Ok(Bm25Weight::for_language_model(
+                    ScoringFunction::LmDirichlet {
+                        mu,
+                        collection_probabilities: collection_probabilities(statistics, terms)?,
+                    },
+                ));
+            }
+            Similarity::LmJelinekMercer { lambda } => {
+                return Ok(Bm25Weight::for_language_model(
+                    ScoringFunction::LmJelinekMercer {
+                        lambda,
+                        collection_probabilities: collection_probabilities(statistics, terms)?,
+                    },
+                ));
+            }
+        };
+
         let total_num_tokens = statistics.total_num_tokens(field)?;
         let total_num_docs = statistics.total_num_docs()?;
         let average_fieldnorm = total_num_tokens as Score / total_num_docs as Score;
 
-        if terms.len() == 1 {
+        let idf_explain = if terms.len() == 1 {
             let term_doc_freq = statistics.doc_freq(&terms[0])?;
-            Ok(Bm25Weight::for_one_term(
-                term_doc_freq,
-                total_num_docs,
-                average_fieldnorm,
-            ))
+            one_term_idf_explain(term_doc_freq, total_num_docs)
         } else {
             let mut idf_sum: Score = 0.0;
             for term in terms {
                 let term_doc_freq = statistics.doc_freq(term)?;
                 idf_sum += idf(term_doc_freq, total_num_docs);
             }
-            let idf_explain = Explanation::new("idf", idf_sum);
-            Ok(Bm25Weight::new(idf_explain, average_fieldnorm))
-        }
+            Explanation::new("idf", idf_sum)
+        };
+        Ok(Bm25Weight::with_params(
+            idf_explain,
+            average_fieldnorm,
+            k1,
+            b,
+        ))
     }
 
     /// Construct a [Bm25Weight] for a single term.
@@ -137,14 +310,7 @@ impl Bm25Weight {
         total_num_docs: u64,
         avg_fieldnorm: Score,
     ) -> Bm25Weight {
-        let idf = idf(term_doc_freq, total_num_docs);
-        let mut idf_explain =
-            Explanation::new("idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))", idf);
-        idf_explain.add_const(
-            "n, number of docs containing this term",
-            term_doc_freq as Score,
-        );
-        idf_explain.add_const("N, total number of docs", total_num_docs as Score);
+        let idf_explain = one_term_idf_explain(term_doc_freq, total_num_docs);
         Bm25Weight::new(idf_explain, avg_fieldnorm)
     }
     /// Construct a [Bm25Weight] for a single term.
@@ -159,33 +325,105 @@ impl Bm25Weight {
     }
 
     pub(crate) fn new(idf_explain: Explanation, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf_explain.value() * (1.0 + K1);
+        Bm25Weight::with_params(idf_explain, average_fieldnorm, DEFAULT_K1, DEFAULT_B)
+    }
+
+    fn with_params(
+        idf_explain: Explanation,
+        average_fieldnorm: Score,
+        k1: Score,
+        b: Score,
+    ) -> Bm25Weight {
+        let weight = idf_explain.value() * (1.0 + k1);
         Bm25Weight {
             idf_explain: Some(idf_explain),
             weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            boost: 1.0,
+            cache: compute_tf_cache(average_fieldnorm, k1, b, FieldNormPrecision::Compact),
             average_fieldnorm,
+            scoring_function: ScoringFunction::Bm25 { k1, b },
+            fieldnorm_precision: FieldNormPrecision::Compact,
         }
     }
+
     pub(crate) fn new_without_explain(idf: f32, average_fieldnorm: Score) -> Bm25Weight {
-        let weight = idf * (1.0 + K1);
+        let weight = idf * (1.0 + DEFAULT_K1);
         Bm25Weight {
             idf_explain: None,
             weight,
-            cache: compute_tf_cache(average_fieldnorm),
+            boost: 1.0,
+            cache: compute_tf_cache(
+                average_fieldnorm,
+                DEFAULT_K1,
+                DEFAULT_B,
+                FieldNormPrecision::Compact,
+            ),
             average_fieldnorm,
+            scoring_function: ScoringFunction::Bm25 {
+                k1: DEFAULT_K1,
+                b: DEFAULT_B,
+            },
+            fieldnorm_precision: FieldNormPrecision::Compact,
+        }
+    }
+
+    fn for_language_model(scoring_function: ScoringFunction) -> Bm25Weight {
+        Bm25Weight {
+            idf_explain: None,
+            weight: 1.0,
+            boost: 1.0,
+            cache: compute_language_model_cache(&scoring_function, FieldNormPrecision::Compact),
+            average_fieldnorm: 0.0,
+            scoring_function,
+            fieldnorm_precision: FieldNormPrecision::Compact,
         }
     }
 
     /// Compute the BM25 score of a single document.
     #[inline]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
-        self.weight * self.tf_factor(fieldnorm_id, term_freq)
+        if let ScoringFunction::Bm25 { .. } = self.scoring_function {
+            return self.weight * self.tf_factor(fieldnorm_id, term_freq);
+        }
+        let term_freq = term_freq as Score;
+        let fieldnorm_component = self.cache[fieldnorm_id as usize];
+        let score: Score = self
+            .scoring_function
+            .collection_probabilities()
+            .iter()
+            .map(|&collection_probability| {
+                self.scoring_function.language_model_score(
+                    collection_probability,
+                    fieldnorm_component,
+                    term_freq,
+                )
+            })
+            .sum();
+        self.weight * score
     }
 
     /// Compute the maximum possible BM25 score given this weight.
     pub fn max_score(&self) -> Score {
-        self.score(255u8, 2_013_265_944)
+        match self.scoring_function {
+            ScoringFunction::Bm25 { .. } => self.score(255u8, 2_013_265_944),
+            // The language model scores decrease with the length of the field.
+            _ => self.score(0u8, 2_013_265_944),
+        }
+    }
+
+    /// Returns an upper bound of the scores of a block of postings, given the fieldnorm id and
+    /// the term frequency of its document with the highest BM25 score with the default
+    /// parameters, as stored in the skip information.
+    pub(crate) fn block_max_score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
+        match self.scoring_function {
+            // The order of the BM25 scores of the documents does not depend on k1.
+            ScoringFunction::Bm25 { b, .. } if b == DEFAULT_B => {
+                self.score(fieldnorm_id, term_freq)
+            }
+            // This document does not necessarily have the highest score of the block for the
+            // other parameters and similarities.
+            _ => self.max_score(),
+        }
     }
 
     #[inline]
@@ -197,6 +435,9 @@ impl Bm25Weight {
 
     /// Produce an [Explanation] of a BM25 score.
     pub fn explain(&self, fieldnorm_id: u8, term_freq: u32) -> Explanation {
+        let ScoringFunction::Bm25 { k1, b } = self.scoring_function else {
+            return self.explain_language_model(fieldnorm_id, term_freq);
+        };
         // The explain format is directly copied from Lucene's.
         // (So, Kudos to Lucene)
         let score = self.score(fieldnorm_id, term_freq);
@@ -211,33 +452,384 @@ impl Bm25Weight {
         );
 
         tf_explanation.add_const("freq, occurrences of term within document", term_freq);
-        tf_explanation.add_const("k1, term saturation parameter", K1);
-        tf_explanation.add_const("b, length normalization parameter", B);
+        tf_explanation.add_const("k1, term saturation parameter", k1);
+        tf_explanation.add_const("b, length normalization parameter", b);
         tf_explanation.add_const(
             "dl, length of field",
-            FieldNormReader::id_to_fieldnorm(fieldnorm_id) as Score,
+            self.fieldnorm_precision.id_to_fieldnorm(fieldnorm_id) as Score,
         );
         tf_explanation.add_const("avgdl, average length of field", self.average_fieldnorm);
 
         let mut explanation = Explanation::new("TermQuery, product of...", score);
-        explanation.add_detail(Explanation::new("(K1+1)", K1 + 1.0));
+        if self.boost != 1.0 {
+            explanation.add_const("boost", self.boost);
+        }
+        explanation.add_detail(Explanation::new("(K1+1)", k1 + 1.0));
         if let Some(idf_explain) = &self.idf_explain {
             explanation.add_detail(idf_explain.clone());
         }
         explanation.add_detail(tf_explanation);
         explanation
     }
+
+    fn explain_language_model(&self, fieldnorm_id: u8, term_freq: u32) -> Explanation {
+        let mut explanation = Explanation::new(
+            "TermQuery, sum of the language model scores of the terms",
+            self.score(fieldnorm_id, term_freq),
+        );
+        if self.boost != 1.0 {
+            explanation.add_const("boost", self.boost);
+        }
+        let fieldnorm_component = self.cache[fieldnorm_id as usize];
+        for &collection_probability in self.scoring_function.collection_probabilities() {
+            let term_score = self.scoring_function.language_model_score(
+                collection_probability,
+                fieldnorm_component,
+                term_freq as Score,
+            );
+            let mut term_explanation = match self.scoring_function {
+                ScoringFunction::LmDirichlet { mu, .. } => {
+                    let mut term_explanation = Explanation::new(
+                        "LM Dirichlet, max(0, log(1 + freq / (mu * P(t|C))) + log(mu / (dl + mu)))",
+                        term_score,
+                    );
+                    term_explanation.add_const("mu, smoothing parameter", mu);
+                    term_explanation
+                }
+                ScoringFunction::LmJelinekMercer { lambda, .. } => {
+                    let mut term_explanation = Explanation::new(
+                        "LM Jelinek-Mercer, log(1 + ((1 - lambda) * freq / dl) / (lambda * \
+                         P(t|C)))",
+                        term_score,
+                    );
+                    term_explanation.add_const("lambda, weight of the collection model", lambda);
+                    term_explanation
+                }
+                ScoringFunction::Bm25 { .. } => Explanation::new("<no score>", term_score),
+            };
+            term_explanation.add_const(
+                "freq, occurrences of term within document",
+                term_freq as Score,
+            );
+            term_explanation.add_const(
+                "dl, length of field",
+                self.fieldnorm_precision.id_to_fieldnorm(fieldnorm_id) as Score,
+            );
+            term_explanation.add_const(
+                "P(t|C), probability of the term in the collection",
+                collection_probability,
+            );
+            explanation.add_detail(term_explanation);
+        }
+        explanation
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::idf;
-    use crate::{assert_nearly_equals, Score};
+    use super::{idf, Bm25StatisticsProvider};
+    use crate::collector::TopDocs;
+    use crate::query::{
+        EnableScoring, MultiPhraseQuery, PhrasePrefixQuery, PhraseQuery, Query, QueryParser,
+        RegexPhraseQuery, TermQuery,
+    };
+    use crate::schema::{
+        Field, IndexRecordOption, Schema, Similarity, TextFieldIndexing, TextOptions, TEXT,
+    };
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Score, Searcher, Term};
 
     #[test]
     fn test_idf() {
         let score: Score = 2.0;
         assert_nearly_equals!(idf(1, 2), score.ln());
     }
+
+    fn index_with_similarity(similarity: Similarity, texts: &[&str]) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_similarity(similarity),
+        );
+        let text = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+        for &text_value in texts {
+            index_writer.add_document(doc!(text => text_value))?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn scores(index: &Index, query: &str) -> crate::Result<Vec<(Score, DocAddress)>> {
+        let text = index.schema().get_field("text").unwrap();
+        let query = QueryParser::for_index(index, vec![text]).parse_query(query)?;
+        index
+            .reader()?
+            .searcher()
+            .search(&query, &TopDocs::with_limit(10))
+    }
+
+    #[test]
+    fn test_total_term_freq() -> crate::Result<()> {
+        let index = index_with_similarity(Similarity::BM25, &["a b", "a a b c d e", "c"])?;
+        let text = index.schema().get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let term_a = Term::from_field_text(text, "a");
+        assert_eq!(
+            Bm25StatisticsProvider::total_term_freq(&searcher, &term_a)?,
+            3
+        );
+        assert_eq!(searcher.doc_freq(&term_a)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_total_term_freq_multiple_blocks() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let with_positions = schema_builder.add_text_field("with_positions", TEXT);
+        let with_freqs = schema_builder.add_text_field(
+            "with_freqs",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default().set_index_option(IndexRecordOption::WithFreqs),
+            ),
+        );
+        let basic = schema_builder.add_text_field(
+            "basic",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default().set_index_option(IndexRecordOption::Basic),
+            ),
+        );
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..300 {
+            let text = if i % 2 == 0 { "a a" } else { "a" };
+            index_writer.add_document(doc!(
+                with_positions => text,
+                with_freqs => text,
+                basic => text,
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        for (field, expected_total_term_freq) in
+            [(with_positions, 450), (with_freqs, 450), (basic, 300)]
+        {
+            let term = Term::from_field_text(field, "a");
+            assert_eq!(searcher.total_term_freq(&term)?, expected_total_term_freq);
+            // The second call is served by the cache.
+            assert_eq!(searcher.total_term_freq(&term)?, expected_total_term_freq);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lm_dirichlet_similarity() -> crate::Result<()> {
+        let similarity = Similarity::LmDirichlet { mu: 2.0 };
+        let index = index_with_similarity(similarity, &["a b", "a a b c d e", "c"])?;
+        // 9 tokens in total, and 3 occurrences of `a`.
+        let probability: Score = 4.0 / 10.0;
+        let top_docs = scores(&index, "a")?;
+        assert_eq!(top_docs.len(), 2);
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        assert_nearly_equals!(
+            top_docs[0].0,
+            (1.0 + 1.0 / (2.0 * probability)).ln() + (2.0 / 4.0 as Score).ln()
+        );
+        // The score is clamped to 0 for long fields.
+        assert_eq!(top_docs[1], (0.0, DocAddress::new(0, 1)));
+
+        let text = index.schema().get_field("text").unwrap();
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("a")?;
+        let explanation = query.explain(&index.reader()?.searcher(), DocAddress::new(0, 0))?;
+        assert_nearly_equals!(explanation.value(), top_docs[0].0);
+        assert!(explanation.to_pretty_json().contains("LM Dirichlet"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lm_jelinek_mercer_similarity() -> crate::Result<()> {
+        let similarity = Similarity::LmJelinekMercer { lambda: 0.5 };
+        let index = index_with_similarity(similarity, &["a b", "a a b c d e", "c"])?;
+        let probability: Score = 4.0 / 10.0;
+        let top_docs = scores(&index, "a")?;
+        assert_eq!(top_docs.len(), 2);
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        assert_nearly_equals!(top_docs[0].0, (1.0 + 0.5 / (2.0 * 0.5 * probability)).ln());
+        assert_eq!(top_docs[1].1, DocAddress::new(0, 1));
+        assert_nearly_equals!(top_docs[1].0, (1.0 + 1.0 / (6.0 * 0.5 * probability)).ln());
+
+        // A phrase is scored as the sum of the scores of its terms, with the phrase frequency.
+        let probability_b: Score = 3.0 / 10.0;
+        let top_docs = scores(&index, "\"a b\"")?;
+        assert_eq!(top_docs.len(), 2);
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        assert_nearly_equals!(
+            top_docs[0].0,
+            (1.0 + 0.5 / (2.0 * 0.5 * probability)).ln()
+                + (1.0 + 0.5 / (2.0 * 0.5 * probability_b)).ln()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_similarity_block_max() -> crate::Result<()> {
+        // The block max information of the full blocks of postings is the fieldnorm and term
+        // frequency of the document with the highest BM25 score, with the default parameters.
+        // Here, it is `short` for the second block, while `medium` documents have higher scores
+        // with LM Dirichlet, or with BM25 without length normalization.
+        let short = "a";
+        let medium = "a a a b b b b b b b";
+        let padded = "a a b b b";
+        let long = format!("a{}", " b".repeat(30));
+        let mut texts = vec![padded; 64];
+        texts.extend(std::iter::repeat_n(long.as_str(), 64));
+        texts.push(short);
+        texts.extend(std::iter::repeat_n(medium, 5));
+        texts.extend(std::iter::repeat_n(long.as_str(), 132));
+        // Lowers the average fieldnorm, so that `short` gets the highest BM25 score.
+        texts.extend(std::iter::repeat_n("b", 5_000));
+        for similarity in [
+            Similarity::LmDirichlet { mu: 20.0 },
+            Similarity::LmJelinekMercer { lambda: 0.1 },
+            Similarity::Bm25 { k1: 1.2, b: 0.0 },
+            Similarity::Bm25 { k1: 3.0, b: 0.75 },
+        ] {
+            let index = index_with_similarity(similarity, &texts)?;
+            let text = index.schema().get_field("text").unwrap();
+            let query = QueryParser::for_index(&index, vec![text]).parse_query("a")?;
+            let searcher = index.reader()?.searcher();
+            let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+            let all_docs = searcher.search(&query, &TopDocs::with_limit(1_000))?;
+            assert_eq!(top_docs.len(), 10);
+            for (top_doc, doc) in top_docs.iter().zip(&all_docs) {
+                assert_nearly_equals!(top_doc.0, doc.0);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bm25_params() -> crate::Result<()> {
+        let texts = ["a", "a b c d", "b", "c"];
+        // Without length normalization, the length of the field does not matter.
+        let index = index_with_similarity(Similarity::Bm25 { k1: 1.2, b: 0.0 }, &texts)?;
+        let top_docs = scores(&index, "a")?;
+        assert_eq!(top_docs.len(), 2);
+        assert_nearly_equals!(top_docs[0].0, top_docs[1].0);
+
+        let index = index_with_similarity(Similarity::BM25, &texts)?;
+        let top_docs = scores(&index, "a")?;
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+        assert!(top_docs[0].0 > top_docs[1].0 * 1.1);
+
+        // The parameters of the field can be overridden for a single query.
+        let text = index.schema().get_field("text").unwrap();
+        let mut query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        query.set_similarity(Similarity::Bm25 { k1: 2.0, b: 0.0 });
+        let searcher = index.reader()?.searcher();
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 2);
+        assert_nearly_equals!(top_docs[0].0, top_docs[1].0);
+        let idf = idf(2, 4);
+        assert_nearly_equals!(top_docs[0].0, idf * 3.0 / (1.0 + 2.0));
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_nearly_equals!(explanation.value(), top_docs[0].0);
+        assert!(explanation.to_pretty_json().contains("\"value\": 2.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_phrase_queries_similarity_override() -> crate::Result<()> {
+        let index = index_with_similarity(Similarity::BM25, &["a b c", "a b c d e f g h"])?;
+        let text = index.schema().get_field("text").unwrap();
+        let term = |text_value: &str| Term::from_field_text(text, text_value);
+        let searcher = index.reader()?.searcher();
+        let no_length_normalization = Similarity::Bm25 { k1: 1.2, b: 0.0 };
+
+        let mut phrase_query = PhraseQuery::new(vec![term("a"), term("b")]);
+        let mut multi_phrase_query = MultiPhraseQuery::new(vec![vec![term("a")], vec![term("b")]]);
+        let mut phrase_prefix_query = PhrasePrefixQuery::new(vec![term("a"), term("b"), term("c")]);
+        let mut regex_phrase_query = RegexPhraseQuery::new(text, vec!["a".into(), "b".into()]);
+        let queries: [&dyn Query; 4] = [
+            &phrase_query,
+            &multi_phrase_query,
+            &phrase_prefix_query,
+            &regex_phrase_query,
+        ];
+        for query in queries {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            assert_eq!(top_docs.len(), 2);
+            assert!(top_docs[0].0 > top_docs[1].0 * 1.1);
+        }
+
+        phrase_query.set_similarity(no_length_normalization);
+        multi_phrase_query.set_similarity(no_length_normalization);
+        phrase_prefix_query.set_similarity(no_length_normalization);
+        regex_phrase_query.set_similarity(no_length_normalization);
+        let queries: [&dyn Query; 4] = [
+            &phrase_query,
+            &multi_phrase_query,
+            &phrase_prefix_query,
+            &regex_phrase_query,
+        ];
+        for query in queries {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            assert_eq!(top_docs.len(), 2);
+            assert_nearly_equals!(top_docs[0].0, top_docs[1].0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics_provider_without_total_term_freq() -> crate::Result<()> {
+        struct DocFreqOnly<'a>(&'a Searcher);
+        impl Bm25StatisticsProvider for DocFreqOnly<'_> {
+            fn total_num_tokens(&self, field: Field) -> crate::Result<u64> {
+                Bm25StatisticsProvider::total_num_tokens(self.0, field)
+            }
+            fn total_num_docs(&self) -> crate::Result<u64> {
+                Bm25StatisticsProvider::total_num_docs(self.0)
+            }
+            fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
+                self.0.doc_freq(term)
+            }
+        }
+        for (similarity, is_supported) in [
+            (Similarity::BM25, true),
+            (Similarity::LmDirichlet { mu: 20.0 }, false),
+        ] {
+            let index = index_with_similarity(similarity, &["a b", "a"])?;
+            let text = index.schema().get_field("text").unwrap();
+            let searcher = index.reader()?.searcher();
+            let query = TermQuery::new(
+                Term::from_field_text(text, "a"),
+                IndexRecordOption::WithFreqs,
+            );
+            let provider = DocFreqOnly(&searcher);
+            let weight = query.weight(EnableScoring::enabled_from_statistics_provider(
+                &provider, &searcher,
+            ));
+            assert_eq!(weight.is_ok(), is_supported);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_similarity() -> crate::Result<()> {
+        let index = index_with_similarity(Similarity::BM25, &["a"])?;
+        let text = index.schema().get_field("text").unwrap();
+        let mut query = TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        query.set_similarity(Similarity::LmDirichlet { mu: -1.0 });
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.search(&query, &TopDocs::with_limit(10)).is_err());
+        Ok(())
+    }
 }
@@ -1,5 +1,5 @@
 use super::boolean_weight::BooleanWeight;
-use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
+use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermMatcher, TermQuery, Weight};
 use crate::schema::{IndexRecordOption, Term};
 
 /// The boolean query returns a set of documents
@@ -173,6 +173,14 @@ impl Query for BooleanQuery {
             subquery.query_terms(visitor);
         }
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        for (occur, subquery) in &self.subqueries {
+            if *occur != Occur::MustNot {
+                subquery.term_matchers(visitor);
+            }
+        }
+    }
 }
 
 impl BooleanQuery {
@@ -270,17 +270,26 @@ impl<TScoreCombiner: ScoreCombiner + Sync> Weight for BooleanWeight<TScoreCombin
 
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
         let mut scorer = self.scorer(reader, 1.0)?;
-        if scorer.seek(doc) != doc {
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
             return Err(does_not_match(doc));
         }
         if !self.scoring_enabled {
             return Ok(Explanation::new("BooleanQuery with no scoring", 1.0));
         }
 
-        let mut explanation = Explanation::new("BooleanClause. sum of ...", scorer.score());
+        let description = (self.score_combiner_fn)().description();
+        let mut explanation =
+            Explanation::new_with_string(format!("BooleanClause. {description}"), scorer.score());
+        if self.minimum_number_should_match > 1 {
+            explanation.add_context(format!(
+                "minimum_number_should_match={}",
+                self.minimum_number_should_match
+            ));
+        }
         for (occur, subweight) in &self.weights {
             if is_positive_occur(*occur) {
-                if let Ok(child_explanation) = subweight.explain(reader, doc) {
+                if let Ok(mut child_explanation) = subweight.explain(reader, doc) {
+                    child_explanation.add_context(format!("Occur={occur:?}"));
                     explanation.add_detail(child_explanation);
                 }
             }
@@ -14,8 +14,8 @@ mod tests {
     use crate::collector::TopDocs;
     use crate::query::term_query::TermScorer;
     use crate::query::{
-        EnableScoring, Intersection, Occur, Query, QueryParser, RequiredOptionalScorer, Scorer,
-        SumCombiner, TermQuery,
+        BoostQuery, DisjunctionMaxQuery, EnableScoring, Explanation, Intersection, Occur, Query,
+        QueryParser, RequiredOptionalScorer, Scorer, SumCombiner, TermQuery,
     };
     use crate::schema::*;
     use crate::{assert_nearly_equals, DocAddress, DocId, Index, IndexWriter, Score};
@@ -311,4 +311,77 @@ mod tests {
         assert_nearly_equals!(explanation.value(), std::f32::consts::LN_2);
         Ok(())
     }
+
+    #[test]
+    pub fn test_explain_clauses() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text=>"a", text=>"b"))?;
+        index_writer.add_document(doc!(text=>"b"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |text_val: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_val),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let query = BooleanQuery::from(vec![
+            (Occur::Must, term_query("a")),
+            (
+                Occur::Should,
+                Box::new(BoostQuery::new(term_query("b"), 2.0)),
+            ),
+            (Occur::MustNot, term_query("c")),
+        ]);
+        let explanation = query.explain(&searcher, DocAddress::new(0, 0u32))?;
+        assert_eq!(explanation.description(), "BooleanClause. sum of ...");
+        let clause_explanations = explanation.details();
+        assert_eq!(clause_explanations.len(), 2);
+        assert_eq!(
+            clause_explanations[0].context().last().map(String::as_str),
+            Some("Occur=Must")
+        );
+        assert_eq!(clause_explanations[1].context(), ["Occur=Should"]);
+        assert_nearly_equals!(
+            explanation.value(),
+            clause_explanations[0].value() + clause_explanations[1].value()
+        );
+        let boosted_explanation = &clause_explanations[1];
+        assert_nearly_equals!(
+            boosted_explanation.value(),
+            2.0 * boosted_explanation.details()[0].value()
+        );
+        let bm25_details: Vec<&str> = boosted_explanation.details()[0]
+            .details()
+            .iter()
+            .map(|explanation| explanation.description())
+            .collect();
+        assert_eq!(
+            bm25_details,
+            [
+                "(K1+1)",
+                "idf, computed as log(1 + (N - n + 0.5) / (n + 0.5))",
+                "freq / (freq + k1 * (1 - b + b * dl / avgdl))"
+            ]
+        );
+        let deserialized_explanation: Explanation =
+            serde_json::from_str(&explanation.to_pretty_json()).unwrap();
+        assert_eq!(
+            deserialized_explanation.to_pretty_json(),
+            explanation.to_pretty_json()
+        );
+
+        let dismax_query =
+            DisjunctionMaxQuery::with_tie_breaker(vec![term_query("a"), term_query("b")], 0.5);
+        let dismax_explanation = dismax_query.explain(&searcher, DocAddress::new(0, 0u32))?;
+        assert_eq!(
+            dismax_explanation.description(),
+            "BooleanClause. max plus 0.5 times the sum of the others of ..."
+        );
+        Ok(())
+    }
 }
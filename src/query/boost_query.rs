@@ -2,7 +2,7 @@ use std::fmt;
 
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
 use crate::fastfield::AliveBitSet;
-use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::query::{EnableScoring, Explanation, Query, Scorer, TermMatcher, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, Term};
 
 /// `BoostQuery` is a wrapper over a query used to boost its score.
@@ -51,6 +51,10 @@ impl Query for BoostQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor)
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.query.term_matchers(visitor);
+    }
 }
 
 /// Weight associated to the BoostQuery.
@@ -83,6 +87,10 @@ impl Weight for BoostWeight {
     fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
         self.weight.count(reader)
     }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.weight.count_up_to(reader, limit)
+    }
 }
 
 pub(crate) struct BoostScorer<S: Scorer> {
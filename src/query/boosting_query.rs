@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::query::{EnableScoring, Explanation, Query, Scorer, TermMatcher, Weight};
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
+
+/// `BoostingQuery` matches the documents of a `positive` query, but demotes
+/// the ones also matching a `negative` query.
+///
+/// Contrary to a `MustNot` clause in a [`BooleanQuery`](crate::query::BooleanQuery),
+/// the documents matching the negative query are not excluded: their score is simply
+/// multiplied by `negative_boost`, which is typically between 0 and 1.
+/// This is useful to down-rank stale or low-quality content.
+///
+/// The score of the negative query itself is not used.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{BoostingQuery, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, TEXT};
+/// use tantivy::{doc, DocAddress, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "apple pie recipe (outdated)"))?;
+/// index_writer.add_document(doc!(title => "apple pie recipe"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let term_query = |text: &str| {
+///     Box::new(TermQuery::new(
+///         Term::from_field_text(title, text),
+///         IndexRecordOption::Basic,
+///     ))
+/// };
+/// let query = BoostingQuery::new(term_query("apple"), term_query("outdated"), 0.2);
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct BoostingQuery {
+    positive: Box<dyn Query>,
+    negative: Box<dyn Query>,
+    negative_boost: Score,
+}
+
+impl BoostingQuery {
+    /// Builds a boosting query.
+    pub fn new(
+        positive: Box<dyn Query>,
+        negative: Box<dyn Query>,
+        negative_boost: Score,
+    ) -> BoostingQuery {
+        BoostingQuery {
+            positive,
+            negative,
+            negative_boost,
+        }
+    }
+}
+
+impl Clone for BoostingQuery {
+    fn clone(&self) -> Self {
+        BoostingQuery {
+            positive: self.positive.box_clone(),
+            negative: self.negative.box_clone(),
+            negative_boost: self.negative_boost,
+        }
+    }
+}
+
+impl fmt::Debug for BoostingQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Boosting(positive={:?}, negative={:?}, negative_boost={})",
+            self.positive, self.negative, self.negative_boost
+        )
+    }
+}
+
+impl Query for BoostingQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let positive_weight = self.positive.weight(enable_scoring)?;
+        if !enable_scoring.is_scoring_enabled() {
+            // The negative query has no impact on the matching documents.
+            return Ok(positive_weight);
+        }
+        let negative_weight = self.negative.weight(EnableScoring::Disabled {
+            schema: enable_scoring.schema(),
+            searcher_opt: enable_scoring.searcher(),
+        })?;
+        Ok(Box::new(BoostingWeight {
+            positive_weight,
+            negative_weight,
+            negative_boost: self.negative_boost,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.positive.query_terms(visitor);
+        self.negative.query_terms(visitor);
+    }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        // The negative query only demotes documents.
+        self.positive.term_matchers(visitor);
+    }
+}
+
+struct BoostingWeight {
+    positive_weight: Box<dyn Weight>,
+    negative_weight: Box<dyn Weight>,
+    negative_boost: Score,
+}
+
+impl Weight for BoostingWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let positive_scorer = self.positive_weight.scorer(reader, boost)?;
+        let negative_scorer = self.negative_weight.scorer(reader, 1.0)?;
+        Ok(Box::new(BoostingScorer {
+            positive_scorer,
+            negative_scorer,
+            negative_boost: self.negative_boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        let positive_explanation = self.positive_weight.explain(reader, doc)?;
+        let mut negative_scorer = self.negative_weight.scorer(reader, 1.0)?;
+        if negative_scorer.doc() > doc || negative_scorer.seek(doc) != doc {
+            return Ok(positive_explanation);
+        }
+        let mut explanation = Explanation::new(
+            "Boosting, product of positive score and negative boost:",
+            scorer.score(),
+        );
+        explanation.add_detail(positive_explanation);
+        explanation.add_const("negative_boost", self.negative_boost);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.positive_weight.count(reader)
+    }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.positive_weight.count_up_to(reader, limit)
+    }
+}
+
+/// Scorer matching the documents of `positive_scorer`, and multiplying the score
+/// of those also matching `negative_scorer` by `negative_boost`.
+struct BoostingScorer {
+    positive_scorer: Box<dyn Scorer>,
+    negative_scorer: Box<dyn Scorer>,
+    negative_boost: Score,
+}
+
+impl DocSet for BoostingScorer {
+    fn advance(&mut self) -> DocId {
+        self.positive_scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.positive_scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.positive_scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.positive_scorer.size_hint()
+    }
+}
+
+impl Scorer for BoostingScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.doc();
+        let score = self.positive_scorer.score();
+        if self.negative_scorer.doc() <= doc && self.negative_scorer.seek(doc) == doc {
+            score * self.negative_boost
+        } else {
+            score
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoostingQuery;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_boosting_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a b"))?;
+        index_writer.add_document(doc!(text => "a c"))?;
+        index_writer.add_document(doc!(text => "b"))?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |text_val: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_val),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let query = BoostingQuery::new(Box::new(AllQuery), term_query("b"), 0.25);
+        let mut top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        top_docs.sort_by_key(|(_, doc_address)| *doc_address);
+        let scores: Vec<f32> = top_docs.iter().map(|(score, _)| *score).collect();
+        assert_eq!(scores, vec![0.25, 1.0, 0.25, 1.0]);
+
+        let query = BoostingQuery::new(term_query("a"), term_query("c"), 0.5);
+        assert_eq!(searcher.search(&query, &crate::collector::Count)?, 3);
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        let positive_score = term_query("a")
+            .explain(&searcher, DocAddress::new(0, 1))?
+            .value();
+        assert_nearly_equals!(explanation.value(), positive_score * 0.5);
+        assert!(explanation.to_pretty_json().contains("negative_boost"));
+        let explanation = query.explain(&searcher, DocAddress::new(0, 0))?;
+        assert_nearly_equals!(explanation.value(), positive_score);
+        assert!(query.explain(&searcher, DocAddress::new(0, 2)).is_err());
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt;
 
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
-use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::query::{EnableScoring, Explanation, Query, Scorer, TermMatcher, Weight};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 /// `ConstScoreQuery` is a wrapper over a query to provide a constant score.
@@ -49,6 +49,10 @@ impl Query for ConstScoreQuery {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.query.query_terms(visitor);
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.query.term_matchers(visitor);
+    }
 }
 
 struct ConstWeight {
@@ -84,6 +88,10 @@ impl Weight for ConstWeight {
     fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
         self.weight.count(reader)
     }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.weight.count_up_to(reader, limit)
+    }
 }
 
 /// Wraps a `DocSet` and simply returns a constant `Scorer`.
@@ -0,0 +1,276 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::query::explanation::does_not_match;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, TermMatcher, Weight};
+use crate::{DocId, DocSet, Score, SegmentReader, Term};
+
+/// A `CustomSegmentScorer` computes the score of the documents of a specific segment
+/// for a [`CustomScoreQuery`].
+///
+/// It is the segment local version of the [`CustomScorer`].
+pub trait CustomSegmentScorer: Send + 'static {
+    /// Computes the score of the document `doc`, given the `score` of the inner query.
+    fn score(&mut self, doc: DocId, score: Score) -> Score;
+}
+
+/// `CustomScorer` defines the score of the documents matched by a [`CustomScoreQuery`].
+///
+/// The `CustomScorer` itself does not make much of the computation itself.
+/// Instead, it is a per-segment setup hook, typically opening the fast field columns required
+/// by the scoring, that builds `Self::Child` instances computing the score at a segment scale.
+pub trait CustomScorer: Send + Sync + 'static {
+    /// Type of the associated [`CustomSegmentScorer`].
+    type Child: CustomSegmentScorer;
+
+    /// Builds a child scorer for a specific segment.
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child>;
+}
+
+impl<F, TSegmentScorer> CustomScorer for F
+where
+    F: 'static + Send + Sync + Fn(&SegmentReader) -> crate::Result<TSegmentScorer>,
+    TSegmentScorer: CustomSegmentScorer,
+{
+    type Child = TSegmentScorer;
+
+    fn segment_scorer(&self, segment_reader: &SegmentReader) -> crate::Result<Self::Child> {
+        (self)(segment_reader)
+    }
+}
+
+impl<F> CustomSegmentScorer for F
+where F: 'static + Send + FnMut(DocId, Score) -> Score
+{
+    fn score(&mut self, doc: DocId, score: Score) -> Score {
+        (self)(doc, score)
+    }
+}
+
+/// `CustomScoreQuery` matches the same documents as an inner query, but lets the caller
+/// compute their score, typically from fast field values.
+///
+/// This makes it possible to implement a bespoke ranking without writing a `Weight` and a
+/// `Scorer`. Contrary to [`TopDocs::tweak_score`](crate::collector::TopDocs::tweak_score),
+/// the custom score can be combined with other queries, e.g. in a
+/// [`BooleanQuery`](crate::query::BooleanQuery).
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{CustomScoreQuery, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, FAST, TEXT};
+/// use tantivy::{doc, DocAddress, DocId, Index, IndexWriter, Score, SegmentReader, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let likes = schema_builder.add_u64_field("likes", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "rust", likes => 3u64))?;
+/// index_writer.add_document(doc!(title => "rust", likes => 10u64))?;
+/// index_writer.commit()?;
+///
+/// let term_query = TermQuery::new(
+///     Term::from_field_text(title, "rust"),
+///     IndexRecordOption::Basic,
+/// );
+/// let query = CustomScoreQuery::new(
+///     Box::new(term_query),
+///     |segment_reader: &SegmentReader| {
+///         let likes_column = segment_reader.fast_fields().u64("likes")?;
+///         Ok(move |doc: DocId, score: Score| {
+///             let likes = likes_column.first(doc).unwrap_or(0);
+///             score * (1.0 + likes as Score).ln()
+///         })
+///     },
+/// );
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct CustomScoreQuery<TCustomScorer> {
+    query: Box<dyn Query>,
+    custom_scorer: Arc<TCustomScorer>,
+}
+
+impl<TCustomScorer: CustomScorer> CustomScoreQuery<TCustomScorer> {
+    /// Builds a custom score query, scoring the documents matching `query`
+    /// with `custom_scorer`.
+    pub fn new(
+        query: Box<dyn Query>,
+        custom_scorer: TCustomScorer,
+    ) -> CustomScoreQuery<TCustomScorer> {
+        CustomScoreQuery {
+            query,
+            custom_scorer: Arc::new(custom_scorer),
+        }
+    }
+}
+
+impl<TCustomScorer> Clone for CustomScoreQuery<TCustomScorer> {
+    fn clone(&self) -> Self {
+        CustomScoreQuery {
+            query: self.query.box_clone(),
+            custom_scorer: self.custom_scorer.clone(),
+        }
+    }
+}
+
+impl<TCustomScorer> fmt::Debug for CustomScoreQuery<TCustomScorer> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CustomScore(query={:?})", self.query)
+    }
+}
+
+impl<TCustomScorer: CustomScorer> Query for CustomScoreQuery<TCustomScorer> {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        if !enable_scoring.is_scoring_enabled() {
+            return Ok(weight);
+        }
+        Ok(Box::new(CustomScoreWeight {
+            weight,
+            custom_scorer: self.custom_scorer.clone(),
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.query.term_matchers(visitor);
+    }
+}
+
+struct CustomScoreWeight<TCustomScorer> {
+    weight: Box<dyn Weight>,
+    custom_scorer: Arc<TCustomScorer>,
+}
+
+impl<TCustomScorer: CustomScorer> Weight for CustomScoreWeight<TCustomScorer> {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, 1.0)?;
+        let segment_scorer = self.custom_scorer.segment_scorer(reader)?;
+        Ok(Box::new(CustomScoreScorer {
+            scorer,
+            segment_scorer,
+            boost,
+        }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new("CustomScore, computed from:", scorer.score());
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.weight.count_up_to(reader, limit)
+    }
+}
+
+struct CustomScoreScorer<TSegmentScorer> {
+    scorer: Box<dyn Scorer>,
+    segment_scorer: TSegmentScorer,
+    boost: Score,
+}
+
+impl<TSegmentScorer: CustomSegmentScorer> DocSet for CustomScoreScorer<TSegmentScorer> {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl<TSegmentScorer: CustomSegmentScorer> Scorer for CustomScoreScorer<TSegmentScorer> {
+    fn score(&mut self) -> Score {
+        let doc = self.doc();
+        let score = self.scorer.score();
+        self.boost * self.segment_scorer.score(doc, score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomScoreQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{AllQuery, BoostQuery, Query, QueryClone};
+    use crate::schema::{Schema, FAST};
+    use crate::{
+        assert_nearly_equals, DocAddress, DocId, Index, IndexWriter, Score, SegmentReader,
+    };
+
+    #[test]
+    fn test_custom_score_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(price => 10.0))?;
+        index_writer.add_document(doc!(price => 2.0))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(price => 5.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = CustomScoreQuery::new(Box::new(AllQuery), |segment_reader: &SegmentReader| {
+            let price_column = segment_reader.fast_fields().f64("price")?;
+            Ok(move |doc: DocId, score: Score| {
+                let price = price_column.first(doc).unwrap_or(100.0);
+                score / price as Score
+            })
+        });
+        assert_eq!(searcher.search(&query, &Count)?, 4);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(4))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (0.5, DocAddress::new(0, 1)),
+                (0.2, DocAddress::new(0, 3)),
+                (0.1, DocAddress::new(0, 0)),
+                (0.01, DocAddress::new(0, 2)),
+            ]
+        );
+        let explanation = query.explain(&searcher, DocAddress::new(0, 3))?;
+        assert_nearly_equals!(explanation.value(), 0.2);
+
+        let boosted_query = BoostQuery::new(query.box_clone(), 2.0);
+        let top_docs = searcher.search(&boosted_query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs, vec![(1.0, DocAddress::new(0, 1))]);
+
+        let failing_query =
+            CustomScoreQuery::new(Box::new(AllQuery), |segment_reader: &SegmentReader| {
+                segment_reader.fast_fields().f64("missing")?;
+                Ok(|_doc: DocId, score: Score| score)
+            });
+        assert!(searcher
+            .search(&failing_query, &TopDocs::with_limit(1))
+            .is_err());
+        Ok(())
+    }
+}
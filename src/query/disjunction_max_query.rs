@@ -1,4 +1,6 @@
-use crate::query::{BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, Weight};
+use crate::query::{
+    BooleanWeight, DisjunctionMaxCombiner, EnableScoring, Occur, Query, TermMatcher, Weight,
+};
 use crate::{Score, Term};
 
 /// The disjunction max query returns documents matching one or more wrapped queries,
@@ -109,6 +111,12 @@ impl Query for DisjunctionMaxQuery {
             disjunct.query_terms(visitor);
         }
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        for disjunct in &self.disjuncts {
+            disjunct.term_matchers(visitor);
+        }
+    }
 }
 
 impl DisjunctionMaxQuery {
@@ -0,0 +1,521 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use columnar::Column;
+use common::DateTime;
+
+use crate::docset::TERMINATED;
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{Schema, Type};
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError};
+
+/// Mean radius of the earth, in meters.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Number of documents for which the candidates are fetched at once when pruning.
+const PRUNING_BLOCK_LEN: u32 = 4_096;
+
+/// Origin of a [`DistanceFeatureQuery`], along with the fields it is compared to.
+#[derive(Clone, Debug)]
+enum DistanceOrigin {
+    Date {
+        field: String,
+        origin: DateTime,
+    },
+    GeoPoint {
+        lat_field: String,
+        lon_field: String,
+        lat: f64,
+        lon: f64,
+    },
+}
+
+/// `DistanceFeatureQuery` matches the documents having a value in a date field, or in
+/// a pair of latitude / longitude fields, and scores them depending on how close this value
+/// is to an origin: `pivot / (pivot + distance)`.
+///
+/// The score is 1 for documents at the origin, and 0.5 for documents at `pivot` from it.
+/// It is typically used as a `Should` clause of a [`BooleanQuery`](crate::query::BooleanQuery),
+/// to boost fresh or nearby documents.
+///
+/// Since the score decreases with the distance, a top-k collection only needs to look at the
+/// documents whose value is within a shrinking window around the origin: the other documents
+/// are skipped without being scored.
+///
+/// For multivalued fields, the first value is used.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::DistanceFeatureQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, DateTime, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(published => DateTime::from_timestamp_secs(1_000)))?;
+/// index_writer.add_document(doc!(published => DateTime::from_timestamp_secs(9_000)))?;
+/// index_writer.add_document(doc!())?;
+/// index_writer.commit()?;
+///
+/// let query = DistanceFeatureQuery::for_date(
+///     "published",
+///     DateTime::from_timestamp_secs(10_000),
+///     Duration::from_secs(1_000),
+/// );
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0], (0.5, DocAddress::new(0, 1)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DistanceFeatureQuery {
+    origin: DistanceOrigin,
+    pivot: f64,
+}
+
+impl DistanceFeatureQuery {
+    /// Creates a new `DistanceFeatureQuery` over a date fast field.
+    pub fn for_date(field_name: &str, origin: DateTime, pivot: Duration) -> DistanceFeatureQuery {
+        DistanceFeatureQuery {
+            origin: DistanceOrigin::Date {
+                field: field_name.to_string(),
+                origin,
+            },
+            pivot: pivot.as_secs_f64(),
+        }
+    }
+
+    /// Creates a new `DistanceFeatureQuery` over a geo point, stored as a latitude and a
+    /// longitude `f64` fast fields, in degrees.
+    ///
+    /// The distance is the great-circle distance, and `pivot_meters` is expressed in meters.
+    pub fn for_geo_point(
+        lat_field_name: &str,
+        lon_field_name: &str,
+        origin_lat: f64,
+        origin_lon: f64,
+        pivot_meters: f64,
+    ) -> DistanceFeatureQuery {
+        DistanceFeatureQuery {
+            origin: DistanceOrigin::GeoPoint {
+                lat_field: lat_field_name.to_string(),
+                lon_field: lon_field_name.to_string(),
+                lat: origin_lat,
+                lon: origin_lon,
+            },
+            pivot: pivot_meters,
+        }
+    }
+}
+
+fn check_fast_field(schema: &Schema, field_name: &str, expected_type: Type) -> crate::Result<()> {
+    let Some((field, _path)) = schema.find_field(field_name) else {
+        return Err(TantivyError::FieldNotFound(field_name.to_string()));
+    };
+    let field_type = schema.get_field_entry(field).field_type();
+    if !field_type.is_fast() || field_type.value_type() != expected_type {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name} is not a {expected_type:?} fast field."
+        )));
+    }
+    Ok(())
+}
+
+impl Query for DistanceFeatureQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        if self.pivot.is_nan() || self.pivot <= 0.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The pivot of a distance feature query must be strictly positive, got {}",
+                self.pivot
+            )));
+        }
+        let schema = enable_scoring.schema();
+        match &self.origin {
+            DistanceOrigin::Date { field, .. } => check_fast_field(schema, field, Type::Date)?,
+            DistanceOrigin::GeoPoint {
+                lat_field,
+                lon_field,
+                ..
+            } => {
+                check_fast_field(schema, lat_field, Type::F64)?;
+                check_fast_field(schema, lon_field, Type::F64)?;
+            }
+        }
+        Ok(Box::new(DistanceFeatureWeight {
+            origin: self.origin.clone(),
+            pivot: self.pivot,
+        }))
+    }
+}
+
+/// Columns of a segment holding the values compared to the origin.
+enum DistanceColumns {
+    Date {
+        column: Column<DateTime>,
+        origin: DateTime,
+    },
+    GeoPoint {
+        lat_column: Column<f64>,
+        lon_column: Column<f64>,
+        lat: f64,
+        lon: f64,
+    },
+}
+
+impl DistanceColumns {
+    /// Returns the distance between the value of the document and the origin, in seconds
+    /// for dates and in meters for geo points.
+    fn distance(&self, doc: DocId) -> Option<f64> {
+        match self {
+            DistanceColumns::Date { column, origin } => {
+                let val = column.first(doc)?;
+                let distance_nanos = (val.into_timestamp_nanos() as f64
+                    - origin.into_timestamp_nanos() as f64)
+                    .abs();
+                Some(distance_nanos / 1_000_000_000.0)
+            }
+            DistanceColumns::GeoPoint {
+                lat_column,
+                lon_column,
+                lat,
+                lon,
+            } => {
+                let doc_lat = lat_column.first(doc)?;
+                let doc_lon = lon_column.first(doc)?;
+                Some(haversine_distance(*lat, *lon, doc_lat, doc_lon))
+            }
+        }
+    }
+
+    /// Pushes to `docs` the documents of `doc_range` which may be within `max_distance` of the
+    /// origin.
+    ///
+    /// The output may contain false positives, and duplicates for multivalued fields.
+    fn candidates(&self, max_distance: f64, doc_range: Range<DocId>, docs: &mut Vec<DocId>) {
+        match self {
+            DistanceColumns::Date { column, origin } => {
+                let origin_nanos = origin.into_timestamp_nanos();
+                // The float to int conversion saturates on overflow.
+                let max_distance_nanos = (max_distance * 1_000_000_000.0).ceil() as i64;
+                let value_range =
+                    DateTime::from_timestamp_nanos(origin_nanos.saturating_sub(max_distance_nanos))
+                        ..=DateTime::from_timestamp_nanos(
+                            origin_nanos.saturating_add(max_distance_nanos),
+                        );
+                column.get_docids_for_value_range(value_range, doc_range, docs);
+            }
+            DistanceColumns::GeoPoint {
+                lat_column, lat, ..
+            } => {
+                // Two points at a given distance cannot have a difference of latitude greater
+                // than the corresponding angle.
+                let max_lat_delta = (max_distance / EARTH_RADIUS_METERS).to_degrees().min(180.0);
+                let value_range = (lat - max_lat_delta)..=(lat + max_lat_delta);
+                lat_column.get_docids_for_value_range(value_range, doc_range, docs);
+            }
+        }
+    }
+}
+
+/// Great-circle distance between two points, in meters.
+fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let half_delta_lat = (lat2 - lat1) / 2.0;
+    let half_delta_lon = (lon2 - lon1).to_radians() / 2.0;
+    let a = half_delta_lat.sin().powi(2) + lat1.cos() * lat2.cos() * half_delta_lon.sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+}
+
+struct DistanceFeatureWeight {
+    origin: DistanceOrigin,
+    pivot: f64,
+}
+
+impl DistanceFeatureWeight {
+    fn distance_columns(&self, reader: &SegmentReader) -> crate::Result<Option<DistanceColumns>> {
+        let fast_fields = reader.fast_fields();
+        let distance_columns = match &self.origin {
+            DistanceOrigin::Date { field, origin } => fast_fields
+                .column_opt::<DateTime>(field)?
+                .map(|column| DistanceColumns::Date {
+                    column,
+                    origin: *origin,
+                }),
+            DistanceOrigin::GeoPoint {
+                lat_field,
+                lon_field,
+                lat,
+                lon,
+            } => fast_fields
+                .column_opt::<f64>(lat_field)?
+                .zip(fast_fields.column_opt::<f64>(lon_field)?)
+                .map(|(lat_column, lon_column)| DistanceColumns::GeoPoint {
+                    lat_column,
+                    lon_column,
+                    lat: *lat,
+                    lon: *lon,
+                }),
+        };
+        Ok(distance_columns)
+    }
+
+    fn compute(&self, distance: f64) -> Score {
+        (self.pivot / (self.pivot + distance)) as Score
+    }
+}
+
+impl Weight for DistanceFeatureWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(columns) = self.distance_columns(reader)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let mut scorer = DistanceFeatureScorer {
+            columns,
+            pivot: self.pivot,
+            boost,
+            doc: 0,
+            distance: 0.0,
+            max_doc: reader.max_doc(),
+        };
+        scorer.seek_to_value(0);
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let distance = self
+            .distance_columns(reader)?
+            .and_then(|columns| columns.distance(doc))
+            .ok_or_else(|| does_not_match(doc))?;
+        let mut explanation = Explanation::new(
+            "DistanceFeature, computed as pivot / (pivot + distance) from:",
+            self.compute(distance),
+        );
+        explanation.add_const("pivot", self.pivot as Score);
+        explanation.add_const("distance", distance as Score);
+        Ok(explanation)
+    }
+
+    fn for_each_pruning(
+        &self,
+        mut threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        let Some(columns) = self.distance_columns(reader)? else {
+            return Ok(());
+        };
+        let max_doc = reader.max_doc();
+        let mut candidates = Vec::new();
+        let mut block_start = 0;
+        while block_start < max_doc {
+            if threshold >= 1.0 {
+                // No document can score more than 1.
+                return Ok(());
+            }
+            let block_end = block_start.saturating_add(PRUNING_BLOCK_LEN).min(max_doc);
+            // A document scores more than the threshold iff its distance is lower than:
+            let max_distance = if threshold > 0.0 {
+                self.pivot * (1.0 / threshold as f64 - 1.0)
+            } else {
+                f64::INFINITY
+            };
+            candidates.clear();
+            columns.candidates(max_distance, block_start..block_end, &mut candidates);
+            let mut last_doc = TERMINATED;
+            for &doc in &candidates {
+                if doc == last_doc {
+                    continue;
+                }
+                last_doc = doc;
+                let Some(distance) = columns.distance(doc) else {
+                    continue;
+                };
+                let score = self.compute(distance);
+                if score > threshold {
+                    threshold = callback(doc, score);
+                }
+            }
+            block_start = block_end;
+        }
+        Ok(())
+    }
+}
+
+struct DistanceFeatureScorer {
+    columns: DistanceColumns,
+    pivot: f64,
+    boost: Score,
+    doc: DocId,
+    distance: f64,
+    max_doc: DocId,
+}
+
+impl DistanceFeatureScorer {
+    /// Positions the scorer on the first document `>= target` having a value.
+    fn seek_to_value(&mut self, target: DocId) -> DocId {
+        let mut doc = target;
+        while doc < self.max_doc {
+            if let Some(distance) = self.columns.distance(doc) {
+                self.doc = doc;
+                self.distance = distance;
+                return doc;
+            }
+            doc += 1;
+        }
+        self.doc = TERMINATED;
+        TERMINATED
+    }
+}
+
+impl DocSet for DistanceFeatureScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.seek_to_value(self.doc + 1)
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc == TERMINATED || target <= self.doc {
+            return self.doc;
+        }
+        self.seek_to_value(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc
+    }
+}
+
+impl Scorer for DistanceFeatureScorer {
+    fn score(&mut self) -> Score {
+        self.boost * (self.pivot / (self.pivot + self.distance)) as Score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{haversine_distance, DistanceFeatureQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, TEXT};
+    use crate::{
+        assert_nearly_equals, DateTime, DocAddress, Index, IndexWriter, TantivyError, Term,
+    };
+
+    #[test]
+    fn test_haversine_distance() {
+        // Paris - London
+        let distance = haversine_distance(48.8566, 2.3522, 51.5074, -0.1278);
+        assert!((distance - 343_500.0).abs() < 1_000.0);
+        assert_nearly_equals!(haversine_distance(10.0, 20.0, 10.0, 20.0) as f32, 0.0);
+    }
+
+    #[test]
+    fn test_distance_feature_query_date() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let published = schema_builder.add_date_field("published", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let secs = DateTime::from_timestamp_secs;
+        index_writer.add_document(doc!(text => "a", published => secs(100)))?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.add_document(doc!(text => "a", published => secs(130)))?;
+        index_writer.add_document(doc!(text => "b", published => secs(90)))?;
+        index_writer.add_document(doc!(text => "a", published => secs(40)))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let distance_query =
+            DistanceFeatureQuery::for_date("published", secs(100), Duration::from_secs(10));
+        assert_eq!(searcher.search(&distance_query, &Count)?, 4);
+        let top_docs = searcher.search(&distance_query, &TopDocs::with_limit(3))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (1.0, DocAddress::new(0, 0)),
+                (0.5, DocAddress::new(0, 3)),
+                (0.25, DocAddress::new(0, 2)),
+            ]
+        );
+        let explanation = distance_query.explain(&searcher, DocAddress::new(0, 4))?;
+        assert_nearly_equals!(explanation.value(), 1.0 / 7.0);
+        assert!(distance_query
+            .explain(&searcher, DocAddress::new(0, 1))
+            .is_err());
+
+        let term_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::Basic,
+        ));
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, term_query),
+            (Occur::Should, Box::new(distance_query)),
+        ]);
+        assert_eq!(searcher.search(&query, &Count)?, 4);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+
+        let text_query = DistanceFeatureQuery::for_date("text", secs(0), Duration::from_secs(1));
+        assert!(matches!(
+            searcher.search(&text_query, &Count),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let invalid_query =
+            DistanceFeatureQuery::for_date("published", secs(0), Duration::from_secs(0));
+        assert!(matches!(
+            searcher.search(&invalid_query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_distance_feature_query_geo_point_pruning() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let lat = schema_builder.add_f64_field("lat", FAST);
+        let lon = schema_builder.add_f64_field("lon", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10_000u32 {
+            let offset = (i % 1_000) as f64 / 100.0;
+            index_writer.add_document(doc!(lat => 45.0 + offset, lon => 5.0 - offset))?;
+        }
+        index_writer.add_document(doc!(lat => 45.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let query = DistanceFeatureQuery::for_geo_point("lat", "lon", 45.0, 5.0, 1_000.0);
+        assert_eq!(searcher.search(&query, &Count)?, 10_000);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(12))?;
+        assert_eq!(top_docs.len(), 12);
+        for (score, doc_address) in &top_docs[..10] {
+            assert_nearly_equals!(*score, 1.0);
+            assert_eq!(doc_address.doc_id % 1_000, 0);
+        }
+        let expected_score = query.explain(&searcher, DocAddress::new(0, 1))?.value();
+        for (score, doc_address) in &top_docs[10..] {
+            assert_nearly_equals!(*score, expected_score);
+            assert_eq!(doc_address.doc_id % 1_000, 1);
+        }
+        Ok(())
+    }
+}
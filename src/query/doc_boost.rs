@@ -0,0 +1,398 @@
+use std::sync::Arc;
+
+use columnar::{Cardinality, ColumnValues};
+
+use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
+use crate::query::{Explanation, Scorer, Weight};
+use crate::{DocBoostMode, DocId, DocSet, Score, SegmentReader};
+
+/// Static boost of the documents of a segment, read from the
+/// [doc boost field](crate::IndexSettings::doc_boost_field).
+///
+/// With [`DocBoostMode::Multiply`], the term and phrase scorers multiply their scores by the
+/// boost of the document. Since the scores of a boolean query are sums, the score of the whole
+/// query is multiplied by it too.
+///
+/// With [`DocBoostMode::Add`], the boost is added once to the score of the whole query, by the
+/// [`AdditiveDocBoostWeight`].
+#[derive(Clone)]
+pub(crate) struct DocBoost {
+    boosts: Arc<dyn ColumnValues<f64>>,
+    max_boost: Score,
+}
+
+impl DocBoost {
+    /// Opens the doc boosts of the segment.
+    ///
+    /// Returns `None` if the index has no doc boost field, if its boost is not applied with the
+    /// given `mode`, or if the segment has no column for it.
+    pub(crate) fn open(
+        reader: &SegmentReader,
+        mode: DocBoostMode,
+    ) -> crate::Result<Option<DocBoost>> {
+        let Some(doc_boost_field) = reader.doc_boost_field() else {
+            return Ok(None);
+        };
+        if reader.doc_boost_mode() != mode {
+            return Ok(None);
+        }
+        let Some(column) = reader.fast_fields().column_opt::<f64>(doc_boost_field)? else {
+            return Ok(None);
+        };
+        // The boost of the documents without a value leaves their score unchanged.
+        let missing_boost = match mode {
+            DocBoostMode::Multiply => 1.0,
+            DocBoostMode::Add => 0.0,
+        };
+        let mut max_boost = column.max_value().max(0.0) as Score;
+        if column.get_cardinality() != Cardinality::Full {
+            max_boost = max_boost.max(missing_boost as Score);
+        }
+        Ok(Some(DocBoost {
+            boosts: column.first_or_default_col(missing_boost),
+            max_boost,
+        }))
+    }
+
+    /// Returns the boost of the document.
+    #[inline]
+    pub(crate) fn boost(&self, doc: DocId) -> Score {
+        (self.boosts.get_val(doc) as Score).max(0.0)
+    }
+
+    /// Returns an upper bound of the boosts of the documents of the segment.
+    ///
+    /// It is applied to the maximum scores used to skip the blocks of documents.
+    pub(crate) fn max_boost(&self) -> Score {
+        self.max_boost
+    }
+
+    /// Adds the boost of the document to the explanation of its score.
+    pub(crate) fn explain(&self, doc: DocId, explanation: &mut Explanation) {
+        explanation.add_const("doc boost, value of the doc boost field", self.boost(doc));
+    }
+}
+
+/// Weight adding the static boost of the documents to the scores of a query, for the indexes
+/// using [`DocBoostMode::Add`].
+///
+/// It wraps the weight of the whole query, so that the boost is added once per document,
+/// whatever the number of clauses matching it.
+pub(crate) struct AdditiveDocBoostWeight {
+    weight: Box<dyn Weight>,
+}
+
+impl AdditiveDocBoostWeight {
+    pub(crate) fn new(weight: Box<dyn Weight>) -> AdditiveDocBoostWeight {
+        AdditiveDocBoostWeight { weight }
+    }
+}
+
+impl Weight for AdditiveDocBoostWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        let Some(doc_boost) = DocBoost::open(reader, DocBoostMode::Add)? else {
+            return Ok(scorer);
+        };
+        Ok(Box::new(AdditiveDocBoostScorer { scorer, doc_boost }))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let underlying_explanation = self.weight.explain(reader, doc)?;
+        let Some(doc_boost) = DocBoost::open(reader, DocBoostMode::Add)? else {
+            return Ok(underlying_explanation);
+        };
+        let mut explanation = Explanation::new(
+            "sum of the score and the doc boost",
+            underlying_explanation.value() + doc_boost.boost(doc),
+        );
+        explanation.add_detail(underlying_explanation);
+        doc_boost.explain(doc, &mut explanation);
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.weight.count_up_to(reader, limit)
+    }
+
+    fn for_each_no_score(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(&[DocId]),
+    ) -> crate::Result<()> {
+        self.weight.for_each_no_score(reader, callback)
+    }
+
+    fn for_each_no_score_until(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId) -> bool,
+    ) -> crate::Result<()> {
+        self.weight.for_each_no_score_until(reader, callback)
+    }
+
+    fn for_each_pruning(
+        &self,
+        mut threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        let Some(doc_boost) = DocBoost::open(reader, DocBoostMode::Add)? else {
+            return self.weight.for_each_pruning(threshold, reader, callback);
+        };
+        // A document can only make it past the threshold if its unboosted score exceeds the
+        // threshold minus the highest boost, which lets the underlying weight keep pruning.
+        let max_boost = doc_boost.max_boost();
+        self.weight
+            .for_each_pruning(threshold - max_boost, reader, &mut |doc, score| {
+                let boosted_score = score + doc_boost.boost(doc);
+                if boosted_score > threshold {
+                    threshold = callback(doc, boosted_score);
+                }
+                threshold - max_boost
+            })
+    }
+}
+
+struct AdditiveDocBoostScorer {
+    scorer: Box<dyn Scorer>,
+    doc_boost: DocBoost,
+}
+
+impl DocSet for AdditiveDocBoostScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId; COLLECT_BLOCK_BUFFER_LEN]) -> usize {
+        self.scorer.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn count(&mut self, alive_bitset: &AliveBitSet) -> u32 {
+        self.scorer.count(alive_bitset)
+    }
+
+    fn count_including_deleted(&mut self) -> u32 {
+        self.scorer.count_including_deleted()
+    }
+}
+
+impl Scorer for AdditiveDocBoostScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score() + self.doc_boost.boost(self.doc())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::{BooleanQuery, Occur, PhraseQuery, Query, TermQuery};
+    use crate::schema::{Field, IndexRecordOption, Schema, FAST, INDEXED, TEXT};
+    use crate::{
+        assert_nearly_equals, DocAddress, DocBoostMode, Index, IndexSettings, IndexWriter,
+        TantivyError, Term,
+    };
+
+    fn create_index(doc_boost_field: Option<&str>) -> crate::Result<(Index, Field, Field)> {
+        create_index_with_mode(doc_boost_field, DocBoostMode::Multiply)
+    }
+
+    fn create_index_with_mode(
+        doc_boost_field: Option<&str>,
+        doc_boost_mode: DocBoostMode,
+    ) -> crate::Result<(Index, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                doc_boost_field: doc_boost_field.map(ToString::to_string),
+                doc_boost_mode,
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        Ok((index, text, popularity))
+    }
+
+    #[test]
+    fn test_doc_boost() -> crate::Result<()> {
+        let (index, text, popularity) = create_index(Some("popularity"))?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello world", popularity => 2.0))?;
+        index_writer.add_document(doc!(text => "hello world"))?;
+        index_writer.add_document(doc!(text => "hello world", popularity => -1.0))?;
+        index_writer.add_document(doc!(text => "hello world", popularity => 0.5))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "hello"),
+            Term::from_field_text(text, "world"),
+        ]);
+        for query in [&term_query as &dyn Query, &phrase_query] {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(4))?;
+            let docs: Vec<DocAddress> = top_docs.iter().map(|(_, doc)| *doc).collect();
+            assert_eq!(
+                docs,
+                vec![
+                    DocAddress::new(0, 0),
+                    DocAddress::new(0, 1),
+                    DocAddress::new(0, 3),
+                    DocAddress::new(0, 2)
+                ]
+            );
+            let unboosted_score = top_docs[1].0;
+            assert_nearly_equals!(top_docs[0].0, 2.0 * unboosted_score);
+            assert_nearly_equals!(top_docs[2].0, 0.5 * unboosted_score);
+            assert_eq!(top_docs[3].0, 0.0);
+            let explanation = query.explain(&searcher, DocAddress::new(0, 0))?;
+            assert_nearly_equals!(explanation.value(), top_docs[0].0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_boost_add_mode() -> crate::Result<()> {
+        let (index, text, popularity) =
+            create_index_with_mode(Some("popularity"), DocBoostMode::Add)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello world", popularity => 2.0))?;
+        index_writer.add_document(doc!(text => "hello world"))?;
+        index_writer.add_document(doc!(text => "hello world", popularity => -1.0))?;
+        index_writer.add_document(doc!(text => "hello world", popularity => 0.5))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let term_query = TermQuery::new(
+            Term::from_field_text(text, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "hello"),
+            Term::from_field_text(text, "world"),
+        ]);
+        // The boost is added once, even if several clauses match the document.
+        let boolean_query = BooleanQuery::new_multiterms_query(vec![
+            Term::from_field_text(text, "hello"),
+            Term::from_field_text(text, "world"),
+        ]);
+        for query in [&term_query as &dyn Query, &phrase_query, &boolean_query] {
+            let top_docs = searcher.search(query, &TopDocs::with_limit(4))?;
+            assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+            assert_eq!(top_docs[1].1, DocAddress::new(0, 3));
+            let unboosted_score = top_docs[2].0;
+            assert_nearly_equals!(top_docs[3].0, unboosted_score);
+            assert_nearly_equals!(top_docs[0].0, unboosted_score + 2.0);
+            assert_nearly_equals!(top_docs[1].0, unboosted_score + 0.5);
+            let explanation = query.explain(&searcher, DocAddress::new(0, 0))?;
+            assert_nearly_equals!(explanation.value(), top_docs[0].0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_boost_without_values() -> crate::Result<()> {
+        let mut scores = Vec::new();
+        for (doc_boost_field, doc_boost_mode) in [
+            (Some("popularity"), DocBoostMode::Multiply),
+            (Some("popularity"), DocBoostMode::Add),
+            (None, DocBoostMode::Multiply),
+        ] {
+            let (index, text, _popularity) =
+                create_index_with_mode(doc_boost_field, doc_boost_mode)?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(text => "hello"))?;
+            index_writer.add_document(doc!(text => "hello hello world"))?;
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            let query = TermQuery::new(
+                Term::from_field_text(text, "hello"),
+                IndexRecordOption::WithFreqs,
+            );
+            scores.push(searcher.search(&query, &TopDocs::with_limit(2))?);
+        }
+        assert_eq!(scores[0], scores[2]);
+        assert_eq!(scores[1], scores[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_boost_block_max_pruning() -> crate::Result<()> {
+        for (doc_boost_mode, neutral_boost) in
+            [(DocBoostMode::Multiply, 1.0), (DocBoostMode::Add, 0.0)]
+        {
+            let (index, text, popularity) =
+                create_index_with_mode(Some("popularity"), doc_boost_mode)?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            // The first blocks of postings hold the documents with the best BM25 score.
+            for _ in 0..256 {
+                index_writer.add_document(doc!(text => "a"))?;
+            }
+            for doc in 256..384 {
+                let popularity_val = if doc == 300 { 10.0 } else { neutral_boost };
+                index_writer.add_document(doc!(
+                    text => "a b b b b b b b b b",
+                    popularity => popularity_val
+                ))?;
+            }
+            index_writer.add_document(doc!(text => "c b b b b b b b b b", popularity => 0.0))?;
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+
+            let term_query = |word: &str| -> Box<dyn Query> {
+                Box::new(TermQuery::new(
+                    Term::from_field_text(text, word),
+                    IndexRecordOption::WithFreqs,
+                ))
+            };
+            let boolean_query = BooleanQuery::new(vec![
+                (Occur::Should, term_query("a")),
+                (Occur::Should, term_query("c")),
+            ]);
+            for query in [term_query("a"), Box::new(boolean_query)] {
+                let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+                assert_eq!(top_docs[0].1, DocAddress::new(0, 300));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_doc_boost_field_validation() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_f64_field("popularity", INDEXED);
+        let err = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                doc_boost_field: Some("popularity".to_string()),
+                ..Default::default()
+            })
+            .create_in_ram()
+            .unwrap_err();
+        assert!(matches!(err, TantivyError::InvalidArgument(_)));
+        assert!(create_index(Some("missing")).is_err());
+        assert!(create_index(None).is_ok());
+    }
+}
@@ -1,13 +1,14 @@
 use core::fmt::Debug;
 
 use columnar::{ColumnIndex, DynamicColumn};
+use common::BitSet;
 
-use super::{ConstScorer, EmptyScorer};
+use super::{BitSetDocSet, ConstScorer, EmptyScorer};
 use crate::docset::{DocSet, TERMINATED};
 use crate::index::SegmentReader;
 use crate::query::explanation::does_not_match;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::schema::Type;
+use crate::schema::{Field, Type};
 use crate::{DocId, Score, TantivyError};
 
 /// Query that matches all documents with a non-null value in the specified
@@ -184,6 +185,64 @@ impl DocSet for ExistsDocSet {
     }
 }
 
+/// Query that matches all documents with at least one token indexed in the specified
+/// field, according to its fieldnorms.
+///
+/// Contrary to [`ExistsQuery`], the field does not need to be a fast field. Note that a
+/// document whose value does not produce any token, e.g. an empty string, is not matched.
+#[derive(Clone, Debug)]
+pub(crate) struct FieldNormExistsQuery {
+    field: Field,
+}
+
+impl FieldNormExistsQuery {
+    pub(crate) fn new(field: Field) -> Self {
+        FieldNormExistsQuery { field }
+    }
+}
+
+impl Query for FieldNormExistsQuery {
+    fn weight(&self, enable_scoring: EnableScoring) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        if !field_entry.has_fieldnorms() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {} does not have fieldnorms.",
+                field_entry.name()
+            )));
+        }
+        Ok(Box::new(FieldNormExistsWeight { field: self.field }))
+    }
+}
+
+struct FieldNormExistsWeight {
+    field: Field,
+}
+
+impl Weight for FieldNormExistsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(fieldnorm_reader) = reader.fieldnorms_readers().get_field(self.field)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        for doc in 0..max_doc {
+            if fieldnorm_reader.fieldnorm_id(doc) != 0 {
+                doc_bitset.insert(doc);
+            }
+        }
+        let docset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FieldNormExistsQuery", 1.0))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::Ipv6Addr;
@@ -192,11 +251,11 @@ mod tests {
     use common::DateTime;
     use time::OffsetDateTime;
 
-    use crate::collector::Count;
-    use crate::query::exist_query::ExistsQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::exist_query::{ExistsQuery, FieldNormExistsQuery};
     use crate::query::{BooleanQuery, RangeQuery};
     use crate::schema::{Facet, FacetOptions, Schema, FAST, INDEXED, STRING, TEXT};
-    use crate::{Index, Searcher, Term};
+    use crate::{DocAddress, Index, Searcher, Term};
 
     #[test]
     fn test_exists_query_simple() -> crate::Result<()> {
@@ -369,6 +428,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_fieldnorm_exists_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let num = schema_builder.add_u64_field("num", INDEXED);
+        let schema = schema_builder.build();
+
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            index_writer.add_document(doc!(title => "hello", num => 1u64))?;
+            index_writer.add_document(doc!(num => 2u64))?;
+            index_writer.add_document(doc!(title => ""))?;
+            index_writer.add_document(doc!(title => "happy", title => "tax payer"))?;
+            index_writer.commit()?;
+        }
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+
+        let count = |field| searcher.search(&FieldNormExistsQuery::new(field), &Count);
+        assert_eq!(count(title)?, 2);
+        assert_eq!(count(num)?, 2);
+        let title_docs = searcher.search(&FieldNormExistsQuery::new(title), &DocSetCollector)?;
+        assert_eq!(
+            title_docs,
+            [DocAddress::new(0, 0), DocAddress::new(0, 3)]
+                .into_iter()
+                .collect()
+        );
+        Ok(())
+    }
+
     fn count_existing_fields(
         searcher: &Searcher,
         field: &str,
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::fmt;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{DocId, Score, TantivyError};
 
@@ -14,13 +14,17 @@ pub(crate) fn does_not_match(doc: DocId) -> TantivyError {
 ///
 /// `.to_pretty_json()` can be useful to print out a human readable
 /// representation of this tree when debugging a given score.
-#[derive(Clone, Serialize)]
+///
+/// Each node holds a value, a description of how the value is computed, and the
+/// explanations of the values it is computed from, so that relevance can be tuned by
+/// walking the tree with [`Explanation::details`], or by reading its JSON representation.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Explanation {
     value: Score,
     description: Cow<'static, str>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     details: Option<Vec<Explanation>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     context: Option<Vec<String>>,
 }
 impl fmt::Debug for Explanation {
@@ -54,6 +58,22 @@ impl Explanation {
         self.value
     }
 
+    /// Returns the description of how the value of the current node is computed.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns the explanations of the values the current node is computed from.
+    pub fn details(&self) -> &[Explanation] {
+        self.details.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the extra context of the current node, like the term or the clause it
+    /// explains.
+    pub fn context(&self) -> &[String] {
+        self.context.as_deref().unwrap_or_default()
+    }
+
     /// Add some detail, explaining some part of the current node formula.
     ///
     /// Details are treated as child of the current node.
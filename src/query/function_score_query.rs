@@ -0,0 +1,687 @@
+use std::fmt;
+use std::time::Duration;
+
+use columnar::{Column, ColumnType};
+use common::DateTime;
+
+use crate::query::explanation::does_not_match;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, TermMatcher, Weight};
+use crate::schema::Type;
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
+
+/// Fast field column types whose values can be used by a [`ScoreFunction`].
+const NUMERICAL_COLUMN_TYPES: [ColumnType; 5] = [
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::Bool,
+    ColumnType::DateTime,
+];
+
+/// Defines how several scores are combined into one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CombineMode {
+    /// The scores are multiplied.
+    #[default]
+    Multiply,
+    /// The scores are summed.
+    Sum,
+    /// The highest score is used.
+    Max,
+}
+
+impl CombineMode {
+    fn combine(self, scores: impl Iterator<Item = f64>) -> Option<f64> {
+        match self {
+            CombineMode::Multiply => scores.reduce(|left, right| left * right),
+            CombineMode::Sum => scores.reduce(|left, right| left + right),
+            CombineMode::Max => scores.reduce(f64::max),
+        }
+    }
+}
+
+/// Shape of a [`DecayFunction`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecayType {
+    /// Normal decay, slow around the origin and in the tail.
+    Gauss,
+    /// Linear decay, reaching 0 at `offset + scale / (1 - decay)` from the origin.
+    Linear,
+    /// Exponential decay, fast around the origin.
+    Exp,
+}
+
+/// Scores documents depending on the distance between the value of a fast field
+/// and an origin.
+///
+/// The function returns 1 for values within `offset` of the origin, and `decay` for values
+/// at `offset + scale` from it.
+///
+/// Date fields are handled in seconds: the origin, the scale and the offset of a date decay
+/// function are expressed in seconds (see [`DecayFunction::for_date`]).
+#[derive(Clone, Debug)]
+pub struct DecayFunction {
+    decay_type: DecayType,
+    field: String,
+    origin: f64,
+    scale: f64,
+    offset: f64,
+    decay: f64,
+}
+
+impl DecayFunction {
+    /// Creates a new decay function over a numerical fast field.
+    ///
+    /// `scale` must be strictly positive.
+    pub fn new(decay_type: DecayType, field: &str, origin: f64, scale: f64) -> DecayFunction {
+        DecayFunction {
+            decay_type,
+            field: field.to_string(),
+            origin,
+            scale,
+            offset: 0.0,
+            decay: 0.5,
+        }
+    }
+
+    /// Creates a new decay function over a date fast field.
+    pub fn for_date(
+        decay_type: DecayType,
+        field: &str,
+        origin: DateTime,
+        scale: Duration,
+    ) -> DecayFunction {
+        let origin_secs = origin.into_timestamp_nanos() as f64 / 1_000_000_000.0;
+        DecayFunction::new(decay_type, field, origin_secs, scale.as_secs_f64())
+    }
+
+    /// Sets the distance to the origin under which the function returns 1.
+    ///
+    /// Defaults to 0.
+    #[must_use]
+    pub fn offset(mut self, offset: f64) -> DecayFunction {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets the value returned for values at `offset + scale` from the origin.
+    ///
+    /// Must be strictly between 0 and 1. Defaults to 0.5.
+    #[must_use]
+    pub fn decay(mut self, decay: f64) -> DecayFunction {
+        self.decay = decay;
+        self
+    }
+
+    fn validate(&self) -> crate::Result<()> {
+        if self.scale.is_nan() || self.scale <= 0.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The scale of a decay function must be strictly positive, got {}",
+                self.scale
+            )));
+        }
+        if self.decay.is_nan() || self.decay <= 0.0 || self.decay >= 1.0 {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The decay of a decay function must be strictly between 0 and 1, got {}",
+                self.decay
+            )));
+        }
+        Ok(())
+    }
+
+    fn compute(&self, val: f64) -> f64 {
+        let distance = ((val - self.origin).abs() - self.offset).max(0.0);
+        match self.decay_type {
+            DecayType::Gauss => {
+                let sigma_squared = -self.scale * self.scale / (2.0 * self.decay.ln());
+                (-distance * distance / (2.0 * sigma_squared)).exp()
+            }
+            DecayType::Linear => {
+                let zero_distance = self.scale / (1.0 - self.decay);
+                ((zero_distance - distance) / zero_distance).max(0.0)
+            }
+            DecayType::Exp => (self.decay.ln() / self.scale * distance).exp(),
+        }
+    }
+}
+
+/// Modifier applied to the value of a fast field by a [`FieldValueFactor`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FieldValueModifier {
+    /// The value is used as is.
+    #[default]
+    None,
+    /// `log10(1 + value)`
+    Log1p,
+    /// `ln(1 + value)`
+    Ln1p,
+    /// `sqrt(value)`
+    Sqrt,
+    /// `value * value`
+    Square,
+    /// `1 / value`
+    Reciprocal,
+}
+
+impl FieldValueModifier {
+    fn apply(self, val: f64) -> f64 {
+        match self {
+            FieldValueModifier::None => val,
+            FieldValueModifier::Log1p => val.ln_1p() / std::f64::consts::LN_10,
+            FieldValueModifier::Ln1p => val.ln_1p(),
+            FieldValueModifier::Sqrt => val.sqrt(),
+            FieldValueModifier::Square => val * val,
+            FieldValueModifier::Reciprocal => 1.0 / val,
+        }
+    }
+}
+
+/// Scores documents using the value of a fast field, e.g. a popularity signal:
+/// `modifier(factor * value)`.
+#[derive(Clone, Debug)]
+pub struct FieldValueFactor {
+    field: String,
+    factor: f64,
+    modifier: FieldValueModifier,
+}
+
+impl FieldValueFactor {
+    /// Creates a new `FieldValueFactor` over a numerical fast field.
+    pub fn new(field: &str, factor: f64, modifier: FieldValueModifier) -> FieldValueFactor {
+        FieldValueFactor {
+            field: field.to_string(),
+            factor,
+            modifier,
+        }
+    }
+
+    fn compute(&self, val: f64) -> f64 {
+        let score = self.modifier.apply(self.factor * val);
+        if score.is_finite() {
+            score
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Function of the value of a fast field, used by a [`FunctionScoreQuery`].
+///
+/// Documents without any value for the field get a neutral score of 1.
+/// For multivalued fields, the first value is used.
+#[derive(Clone, Debug)]
+pub enum ScoreFunction {
+    /// See [`DecayFunction`].
+    Decay(DecayFunction),
+    /// See [`FieldValueFactor`].
+    FieldValueFactor(FieldValueFactor),
+}
+
+impl From<DecayFunction> for ScoreFunction {
+    fn from(decay_function: DecayFunction) -> ScoreFunction {
+        ScoreFunction::Decay(decay_function)
+    }
+}
+
+impl From<FieldValueFactor> for ScoreFunction {
+    fn from(field_value_factor: FieldValueFactor) -> ScoreFunction {
+        ScoreFunction::FieldValueFactor(field_value_factor)
+    }
+}
+
+impl ScoreFunction {
+    fn field(&self) -> &str {
+        match self {
+            ScoreFunction::Decay(decay_function) => &decay_function.field,
+            ScoreFunction::FieldValueFactor(field_value_factor) => &field_value_factor.field,
+        }
+    }
+
+    fn compute(&self, val: f64) -> f64 {
+        match self {
+            ScoreFunction::Decay(decay_function) => decay_function.compute(val),
+            ScoreFunction::FieldValueFactor(field_value_factor) => field_value_factor.compute(val),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            ScoreFunction::Decay(decay_function) => format!(
+                "{:?} decay of `{}` (origin={}, scale={}, offset={}, decay={})",
+                decay_function.decay_type,
+                decay_function.field,
+                decay_function.origin,
+                decay_function.scale,
+                decay_function.offset,
+                decay_function.decay
+            ),
+            ScoreFunction::FieldValueFactor(field_value_factor) => format!(
+                "{:?}({} * `{}`)",
+                field_value_factor.modifier, field_value_factor.factor, field_value_factor.field
+            ),
+        }
+    }
+}
+
+/// `FunctionScoreQuery` combines the score of a query with functions of the fast field
+/// values of the matching documents.
+///
+/// The documents matched are strictly the same as those of the underlying query.
+/// The values of the functions are first combined together according to the
+/// [score mode](FunctionScoreQuery::set_score_mode), and the result is then combined with the
+/// score of the query according to the [boost mode](FunctionScoreQuery::set_boost_mode).
+/// Both default to [`CombineMode::Multiply`].
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{
+///     AllQuery, DecayFunction, DecayType, FieldValueFactor, FieldValueModifier,
+///     FunctionScoreQuery,
+/// };
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, DateTime, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let published = schema_builder.add_date_field("published", FAST);
+/// let likes = schema_builder.add_u64_field("likes", FAST);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// let day = 24 * 3600;
+/// index_writer.add_document(doc!(
+///     published => DateTime::from_timestamp_secs(100 * day),
+///     likes => 100u64,
+/// ))?;
+/// index_writer.add_document(doc!(
+///     published => DateTime::from_timestamp_secs(99 * day),
+///     likes => 10u64,
+/// ))?;
+/// index_writer.add_document(doc!(
+///     published => DateTime::from_timestamp_secs(10 * day),
+///     likes => 1000u64,
+/// ))?;
+/// index_writer.commit()?;
+///
+/// let now = DateTime::from_timestamp_secs(100 * day);
+/// let query = FunctionScoreQuery::new(
+///     Box::new(AllQuery),
+///     vec![
+///         DecayFunction::for_date(
+///             DecayType::Gauss,
+///             "published",
+///             now,
+///             Duration::from_secs(7 * day as u64),
+///         )
+///         .into(),
+///         FieldValueFactor::new("likes", 1.0, FieldValueModifier::Log1p).into(),
+///     ],
+/// );
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+/// assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+/// assert_eq!(top_docs[1].1, DocAddress::new(0, 1));
+/// # Ok(())
+/// # }
+/// ```
+pub struct FunctionScoreQuery {
+    query: Box<dyn Query>,
+    functions: Vec<ScoreFunction>,
+    score_mode: CombineMode,
+    boost_mode: CombineMode,
+}
+
+impl FunctionScoreQuery {
+    /// Creates a new `FunctionScoreQuery`.
+    pub fn new(query: Box<dyn Query>, functions: Vec<ScoreFunction>) -> FunctionScoreQuery {
+        FunctionScoreQuery {
+            query,
+            functions,
+            score_mode: CombineMode::default(),
+            boost_mode: CombineMode::default(),
+        }
+    }
+
+    /// Sets how the values of the functions are combined together.
+    pub fn set_score_mode(&mut self, score_mode: CombineMode) {
+        self.score_mode = score_mode;
+    }
+
+    /// Sets how the combined value of the functions is combined with the score of the query.
+    pub fn set_boost_mode(&mut self, boost_mode: CombineMode) {
+        self.boost_mode = boost_mode;
+    }
+}
+
+impl Clone for FunctionScoreQuery {
+    fn clone(&self) -> Self {
+        FunctionScoreQuery {
+            query: self.query.box_clone(),
+            functions: self.functions.clone(),
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        }
+    }
+}
+
+impl fmt::Debug for FunctionScoreQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FunctionScoreQuery")
+            .field("query", &self.query)
+            .field("functions", &self.functions)
+            .field("score_mode", &self.score_mode)
+            .field("boost_mode", &self.boost_mode)
+            .finish()
+    }
+}
+
+impl Query for FunctionScoreQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        if !enable_scoring.is_scoring_enabled() {
+            return Ok(weight);
+        }
+        let schema = enable_scoring.schema();
+        for function in &self.functions {
+            if let ScoreFunction::Decay(decay_function) = function {
+                decay_function.validate()?;
+            }
+            let field_name = function.field();
+            let Some((field, _path)) = schema.find_field(field_name) else {
+                return Err(TantivyError::FieldNotFound(field_name.to_string()));
+            };
+            let field_type = schema.get_field_entry(field).field_type();
+            let is_numerical = matches!(
+                field_type.value_type(),
+                Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date | Type::Json
+            );
+            if !field_type.is_fast() || !is_numerical {
+                return Err(TantivyError::SchemaError(format!(
+                    "Field {field_name} is not a numerical fast field."
+                )));
+            }
+        }
+        Ok(Box::new(FunctionScoreWeight {
+            weight,
+            functions: self.functions.clone(),
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor);
+    }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.query.term_matchers(visitor);
+    }
+}
+
+struct FunctionScoreWeight {
+    weight: Box<dyn Weight>,
+    functions: Vec<ScoreFunction>,
+    score_mode: CombineMode,
+    boost_mode: CombineMode,
+}
+
+impl FunctionScoreWeight {
+    fn function_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<FunctionScorer> {
+        let scorer = self.weight.scorer(reader, boost)?;
+        let mut columns = Vec::with_capacity(self.functions.len());
+        for function in &self.functions {
+            let column_opt = reader
+                .fast_fields()
+                .u64_lenient_for_type(Some(&NUMERICAL_COLUMN_TYPES), function.field())?;
+            columns.push(column_opt);
+        }
+        Ok(FunctionScorer {
+            scorer,
+            functions: self.functions.clone(),
+            columns,
+            score_mode: self.score_mode,
+            boost_mode: self.boost_mode,
+        })
+    }
+}
+
+impl Weight for FunctionScoreWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        Ok(Box::new(self.function_scorer(reader, boost)?))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.function_scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new_with_string(
+            format!(
+                "FunctionScore, score mode {:?}, boost mode {:?}",
+                self.score_mode, self.boost_mode
+            ),
+            scorer.score(),
+        );
+        explanation.add_detail(self.weight.explain(reader, doc)?);
+        for (function, column_opt) in self.functions.iter().zip(&scorer.columns) {
+            let function_score = compute_function(function, column_opt.as_ref(), doc);
+            explanation.add_detail(Explanation::new_with_string(
+                function.description(),
+                function_score as Score,
+            ));
+        }
+        Ok(explanation)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        self.weight.count(reader)
+    }
+
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        self.weight.count_up_to(reader, limit)
+    }
+}
+
+fn compute_function(
+    function: &ScoreFunction,
+    column_opt: Option<&(Column<u64>, ColumnType)>,
+    doc: DocId,
+) -> f64 {
+    let Some((column, column_type)) = column_opt else {
+        return 1.0;
+    };
+    let Some(val) = column.first(doc) else {
+        return 1.0;
+    };
+    let val = match column_type {
+        ColumnType::DateTime => common::u64_to_i64(val) as f64 / 1_000_000_000.0,
+        _ => crate::aggregation::f64_from_fastfield_u64(val, column_type),
+    };
+    function.compute(val)
+}
+
+struct FunctionScorer {
+    scorer: Box<dyn Scorer>,
+    functions: Vec<ScoreFunction>,
+    columns: Vec<Option<(Column<u64>, ColumnType)>>,
+    score_mode: CombineMode,
+    boost_mode: CombineMode,
+}
+
+impl DocSet for FunctionScorer {
+    fn advance(&mut self) -> DocId {
+        self.scorer.advance()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        self.scorer.seek(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for FunctionScorer {
+    fn score(&mut self) -> Score {
+        let doc = self.doc();
+        let query_score = self.scorer.score() as f64;
+        let function_scores = self
+            .functions
+            .iter()
+            .zip(&self.columns)
+            .map(|(function, column_opt)| compute_function(function, column_opt.as_ref(), doc));
+        let Some(function_score) = self.score_mode.combine(function_scores) else {
+            return query_score as Score;
+        };
+        self.boost_mode
+            .combine([query_score, function_score].into_iter())
+            .unwrap_or(query_score) as Score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::collector::TopDocs;
+    use crate::query::{AllQuery, ConstScoreQuery};
+    use crate::schema::{Schema, FAST, INDEXED};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter};
+
+    #[test]
+    fn test_decay_functions() {
+        let gauss = DecayFunction::new(DecayType::Gauss, "f", 10.0, 5.0);
+        assert_nearly_equals!(gauss.compute(10.0), 1.0);
+        assert_nearly_equals!(gauss.compute(15.0), 0.5);
+        assert_nearly_equals!(gauss.compute(5.0), 0.5);
+        assert!(gauss.compute(20.0) < 0.1);
+        let linear = DecayFunction::new(DecayType::Linear, "f", 0.0, 10.0)
+            .offset(2.0)
+            .decay(0.5);
+        assert_nearly_equals!(linear.compute(2.0), 1.0);
+        assert_nearly_equals!(linear.compute(12.0), 0.5);
+        assert_nearly_equals!(linear.compute(22.0), 0.0);
+        assert_nearly_equals!(linear.compute(100.0), 0.0);
+        let exp = DecayFunction::new(DecayType::Exp, "f", 0.0, 10.0).decay(0.25);
+        assert_nearly_equals!(exp.compute(10.0), 0.25);
+        assert_nearly_equals!(exp.compute(20.0), 0.0625);
+    }
+
+    #[test]
+    fn test_field_value_factor() {
+        let log = FieldValueFactor::new("f", 1.0, FieldValueModifier::Log1p);
+        assert_nearly_equals!(log.compute(99.0), 2.0);
+        let square = FieldValueFactor::new("f", 2.0, FieldValueModifier::Square);
+        assert_nearly_equals!(square.compute(3.0), 36.0);
+        let reciprocal = FieldValueFactor::new("f", 1.0, FieldValueModifier::Reciprocal);
+        assert_nearly_equals!(reciprocal.compute(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_function_score_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let popularity = schema_builder.add_i64_field("popularity", FAST);
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(price => 10.0, popularity => 9i64, not_fast => 1u64))?;
+        index_writer.add_document(doc!(price => 20.0, popularity => 99i64))?;
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let scores = |query: &FunctionScoreQuery| -> crate::Result<Vec<Score>> {
+            let mut top_docs = searcher.search(query, &TopDocs::with_limit(10))?;
+            top_docs.sort_by_key(|(_, doc_address)| *doc_address);
+            Ok(top_docs.into_iter().map(|(score, _)| score).collect())
+        };
+        let functions: Vec<ScoreFunction> = vec![
+            DecayFunction::new(DecayType::Linear, "price", 10.0, 10.0).into(),
+            FieldValueFactor::new("popularity", 1.0, FieldValueModifier::Log1p).into(),
+        ];
+        let const_query = Box::new(ConstScoreQuery::new(Box::new(AllQuery), 2.0));
+        let mut query = FunctionScoreQuery::new(const_query, functions);
+        let expected = [2.0, 2.0 * 0.5 * 2.0, 2.0];
+        for (score, expected) in scores(&query)?.into_iter().zip(expected) {
+            assert_nearly_equals!(score, expected);
+        }
+        query.set_score_mode(CombineMode::Sum);
+        query.set_boost_mode(CombineMode::Sum);
+        let expected = [2.0 + 1.0 + 1.0, 2.0 + 0.5 + 2.0, 2.0 + 1.0 + 1.0];
+        for (score, expected) in scores(&query)?.into_iter().zip(expected) {
+            assert_nearly_equals!(score, expected);
+        }
+        query.set_score_mode(CombineMode::Max);
+        query.set_boost_mode(CombineMode::Max);
+        let expected = [2.0, 2.0, 2.0];
+        for (score, expected) in scores(&query)?.into_iter().zip(expected) {
+            assert_nearly_equals!(score, expected);
+        }
+
+        let explanation = query.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_nearly_equals!(explanation.value(), 2.0);
+        assert!(explanation
+            .to_pretty_json()
+            .contains("Log1p(1 * `popularity`)"));
+
+        let invalid_query = FunctionScoreQuery::new(
+            Box::new(AllQuery),
+            vec![DecayFunction::new(DecayType::Gauss, "price", 0.0, 0.0).into()],
+        );
+        assert!(matches!(
+            searcher.search(&invalid_query, &TopDocs::with_limit(1)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let not_fast_query = FunctionScoreQuery::new(
+            Box::new(AllQuery),
+            vec![FieldValueFactor::new("not_fast", 1.0, FieldValueModifier::None).into()],
+        );
+        assert!(matches!(
+            searcher.search(&not_fast_query, &TopDocs::with_limit(1)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_score_query_date_decay() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let date = schema_builder.add_date_field("date", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(date => DateTime::from_timestamp_secs(3_600)))?;
+        index_writer.add_document(doc!(date => DateTime::from_timestamp_secs(10_800)))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = FunctionScoreQuery::new(
+            Box::new(AllQuery),
+            vec![DecayFunction::for_date(
+                DecayType::Exp,
+                "date",
+                DateTime::from_timestamp_secs(0),
+                Duration::from_secs(3_600),
+            )
+            .offset(3_600.0)
+            .into()],
+        );
+        let mut top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        top_docs.sort_by_key(|(_, doc_address)| *doc_address);
+        assert_nearly_equals!(top_docs[0].0, 1.0);
+        assert_nearly_equals!(top_docs[1].0, 0.25);
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
+use std::sync::Arc;
+
 use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
 use once_cell::sync::OnceCell;
 use tantivy_fst::Automaton;
 
-use crate::query::{AutomatonWeight, EnableScoring, Query, Weight};
+use crate::query::term_matcher::automaton_term_matcher;
+use crate::query::{AutomatonWeight, EnableScoring, Query, TermMatcher, Weight};
 use crate::schema::{Term, Type};
 use crate::TantivyError::InvalidArgument;
 
@@ -31,6 +34,130 @@ impl Automaton for DfaWrapper {
     }
 }
 
+/// Automaton requiring the input to start with `prefix`, the rest of it being matched
+/// by the Levenshtein automaton.
+pub(crate) struct ExactPrefixDfaWrapper {
+    prefix: Vec<u8>,
+    dfa: DfaWrapper,
+}
+
+#[derive(Clone)]
+pub(crate) enum ExactPrefixState {
+    /// Number of bytes of the prefix matched so far.
+    Prefix(usize),
+    Dfa(u32),
+    Sink,
+}
+
+impl ExactPrefixDfaWrapper {
+    fn dfa_start(&self) -> ExactPrefixState {
+        ExactPrefixState::Dfa(self.dfa.start())
+    }
+
+    /// Returns the number of edits between `text` and the text of the automaton, or `None` if
+    /// `text` is not matched by the automaton.
+    pub(crate) fn distance(&self, text: &[u8]) -> Option<u8> {
+        let state = text
+            .iter()
+            .fold(self.start(), |state, &byte| self.accept(&state, byte));
+        match state {
+            ExactPrefixState::Dfa(dfa_state) => match self.dfa.0.distance(dfa_state) {
+                Distance::Exact(distance) => Some(distance),
+                Distance::AtLeast(_) => None,
+            },
+            ExactPrefixState::Prefix(_) | ExactPrefixState::Sink => None,
+        }
+    }
+}
+
+impl Automaton for ExactPrefixDfaWrapper {
+    type State = ExactPrefixState;
+
+    fn start(&self) -> Self::State {
+        if self.prefix.is_empty() {
+            self.dfa_start()
+        } else {
+            ExactPrefixState::Prefix(0)
+        }
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        match state {
+            ExactPrefixState::Dfa(dfa_state) => self.dfa.is_match(dfa_state),
+            ExactPrefixState::Prefix(_) | ExactPrefixState::Sink => false,
+        }
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match state {
+            ExactPrefixState::Dfa(dfa_state) => self.dfa.can_match(dfa_state),
+            ExactPrefixState::Prefix(_) => true,
+            ExactPrefixState::Sink => false,
+        }
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        match *state {
+            ExactPrefixState::Prefix(matched) if self.prefix[matched] == byte => {
+                if matched + 1 == self.prefix.len() {
+                    self.dfa_start()
+                } else {
+                    ExactPrefixState::Prefix(matched + 1)
+                }
+            }
+            ExactPrefixState::Prefix(_) | ExactPrefixState::Sink => ExactPrefixState::Sink,
+            ExactPrefixState::Dfa(dfa_state) => {
+                ExactPrefixState::Dfa(self.dfa.accept(&dfa_state, byte))
+            }
+        }
+    }
+}
+
+/// Builds the automaton matching the strings within `distance` edits of `text`, or of one of its
+/// prefixes if `prefix` is true, the first `prefix_length` chars of `text` being matched exactly.
+pub(crate) fn fuzzy_automaton(
+    text: &str,
+    distance: u8,
+    transposition_cost_one: bool,
+    prefix: bool,
+    prefix_length: usize,
+) -> crate::Result<ExactPrefixDfaWrapper> {
+    static AUTOMATON_BUILDER: [[OnceCell<LevenshteinAutomatonBuilder>; 2]; 3] = [
+        [OnceCell::new(), OnceCell::new()],
+        [OnceCell::new(), OnceCell::new()],
+        [OnceCell::new(), OnceCell::new()],
+    ];
+
+    let automaton_builder = AUTOMATON_BUILDER
+        .get(distance as usize)
+        .ok_or_else(|| {
+            InvalidArgument(format!(
+                "Levenshtein distance of {} is not allowed. Choose a value less than {}",
+                distance,
+                AUTOMATON_BUILDER.len()
+            ))
+        })?
+        .get(transposition_cost_one as usize)
+        .unwrap()
+        .get_or_init(|| LevenshteinAutomatonBuilder::new(distance, transposition_cost_one));
+
+    let prefix_end = text
+        .char_indices()
+        .nth(prefix_length)
+        .map(|(offset, _)| offset)
+        .unwrap_or(text.len());
+    let (exact_prefix, fuzzy_suffix) = text.split_at(prefix_end);
+    let dfa = if prefix {
+        automaton_builder.build_prefix_dfa(fuzzy_suffix)
+    } else {
+        automaton_builder.build_dfa(fuzzy_suffix)
+    };
+    Ok(ExactPrefixDfaWrapper {
+        prefix: exact_prefix.as_bytes().to_vec(),
+        dfa: DfaWrapper(dfa),
+    })
+}
+
 /// A Fuzzy Query matches all of the documents
 /// containing a specific term that is within
 /// Levenshtein distance
@@ -86,6 +213,8 @@ pub struct FuzzyTermQuery {
     transposition_cost_one: bool,
     /// is a starts with query
     prefix: bool,
+    /// Number of leading chars that must match exactly
+    prefix_length: usize,
 }
 
 impl FuzzyTermQuery {
@@ -96,6 +225,7 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: false,
+            prefix_length: 0,
         }
     }
 
@@ -106,31 +236,20 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: true,
+            prefix_length: 0,
         }
     }
 
-    fn specialized_weight(&self) -> crate::Result<AutomatonWeight<DfaWrapper>> {
-        static AUTOMATON_BUILDER: [[OnceCell<LevenshteinAutomatonBuilder>; 2]; 3] = [
-            [OnceCell::new(), OnceCell::new()],
-            [OnceCell::new(), OnceCell::new()],
-            [OnceCell::new(), OnceCell::new()],
-        ];
-
-        let automaton_builder = AUTOMATON_BUILDER
-            .get(self.distance as usize)
-            .ok_or_else(|| {
-                InvalidArgument(format!(
-                    "Levenshtein distance of {} is not allowed. Choose a value less than {}",
-                    self.distance,
-                    AUTOMATON_BUILDER.len()
-                ))
-            })?
-            .get(self.transposition_cost_one as usize)
-            .unwrap()
-            .get_or_init(|| {
-                LevenshteinAutomatonBuilder::new(self.distance, self.transposition_cost_one)
-            });
+    /// Requires the first `prefix_length` chars of the matched terms to be
+    /// identical to those of the query term.
+    ///
+    /// Edits are only allowed after this prefix. Beyond making the query stricter,
+    /// a non-zero prefix length greatly reduces the number of terms to visit.
+    pub fn set_prefix_length(&mut self, prefix_length: usize) {
+        self.prefix_length = prefix_length;
+    }
 
+    fn automaton(&self) -> crate::Result<ExactPrefixDfaWrapper> {
         let term_value = self.term.value();
 
         let term_text = if term_value.typ() == Type::Json {
@@ -153,23 +272,25 @@ impl FuzzyTermQuery {
                 InvalidArgument("The fuzzy term query requires a string term.".to_string())
             })?
         };
-        let automaton = if self.prefix {
-            automaton_builder.build_prefix_dfa(term_text)
-        } else {
-            automaton_builder.build_dfa(term_text)
-        };
+        fuzzy_automaton(
+            term_text,
+            self.distance,
+            self.transposition_cost_one,
+            self.prefix,
+            self.prefix_length,
+        )
+    }
 
-        if let Some((json_path_bytes, _)) = term_value.as_json() {
+    fn specialized_weight(&self) -> crate::Result<AutomatonWeight<ExactPrefixDfaWrapper>> {
+        let automaton = self.automaton()?;
+        if let Some((json_path_bytes, _)) = self.term.value().as_json() {
             Ok(AutomatonWeight::new_for_json_path(
                 self.term.field(),
-                DfaWrapper(automaton),
+                automaton,
                 json_path_bytes,
             ))
         } else {
-            Ok(AutomatonWeight::new(
-                self.term.field(),
-                DfaWrapper(automaton),
-            ))
+            Ok(AutomatonWeight::new(self.term.field(), automaton))
         }
     }
 }
@@ -178,6 +299,19 @@ impl Query for FuzzyTermQuery {
     fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
         Ok(Box::new(self.specialized_weight()?))
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        // The terms of JSON fields are not highlighted.
+        if self.term.value().as_json().is_some() {
+            return;
+        }
+        if let Ok(automaton) = self.automaton() {
+            visitor(automaton_term_matcher(
+                self.term.field(),
+                Arc::new(automaton),
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -343,4 +477,35 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    pub fn test_fuzzy_term_prefix_length() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let country_field = schema_builder.add_text_field("country", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(country_field => "japan"))?;
+        index_writer.add_document(doc!(country_field => "canada"))?;
+        index_writer.add_document(doc!(country_field => "pérou"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let count = |text: &str, distance: u8, prefix_length: usize| {
+            let term = Term::from_field_text(country_field, text);
+            let mut fuzzy_query = FuzzyTermQuery::new(term, distance, true);
+            fuzzy_query.set_prefix_length(prefix_length);
+            searcher.search(&fuzzy_query, &Count).unwrap()
+        };
+        assert_eq!(count("kapan", 1, 0), 1);
+        assert_eq!(count("kapan", 1, 1), 0);
+        assert_eq!(count("jaban", 1, 2), 1);
+        assert_eq!(count("jaban", 1, 3), 0);
+        assert_eq!(count("pérau", 1, 2), 1);
+        assert_eq!(count("pérau", 1, 4), 0);
+        // the prefix may be longer than the term itself.
+        assert_eq!(count("japa", 1, 10), 1);
+        assert_eq!(count("japan", 0, 10), 1);
+        Ok(())
+    }
 }
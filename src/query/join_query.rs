@@ -0,0 +1,530 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use columnar::{BytesColumn, Column, ColumnType};
+use common::BitSet;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::query::explanation::does_not_match;
+use crate::query::{
+    BitSetDocSet, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight,
+};
+use crate::schema::{field_type_to_column_type, Schema};
+use crate::{DocId, Score, Searcher, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// `JoinQuery` matches the documents of an index whose key matches the key of at least one
+/// document of another index matching a query.
+///
+/// The keys are read from a fast field in both indexes. Typically, this makes it possible to
+/// search the orders placed by the customers matching a query in a single search call: the
+/// query is run on the customers index, the identifiers of the matching customers are
+/// collected, and the orders whose customer identifier is one of them are matched.
+///
+/// The key fields must be fast fields of the same type in both schemas. Numerical, date,
+/// boolean, text and bytes fast fields are supported. For multivalued fields, a document
+/// matches if any of its values matches any of the collected keys.
+///
+/// The query on the other index is run when the [`Weight`] of the `JoinQuery` is built, with
+/// scoring disabled. All of the matched documents get the score 1.0.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::{JoinQuery, TermQuery};
+/// use tantivy::schema::{IndexRecordOption, Schema, FAST, STRING};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut customer_schema_builder = Schema::builder();
+/// let customer_id = customer_schema_builder.add_u64_field("id", FAST);
+/// let country = customer_schema_builder.add_text_field("country", STRING);
+/// let customers = Index::create_in_ram(customer_schema_builder.build());
+/// let mut customer_writer: IndexWriter = customers.writer_with_num_threads(1, 20_000_000)?;
+/// customer_writer.add_document(doc!(customer_id => 1u64, country => "fr"))?;
+/// customer_writer.add_document(doc!(customer_id => 2u64, country => "de"))?;
+/// customer_writer.commit()?;
+///
+/// let mut order_schema_builder = Schema::builder();
+/// let order_customer_id = order_schema_builder.add_u64_field("customer_id", FAST);
+/// let orders = Index::create_in_ram(order_schema_builder.build());
+/// let mut order_writer: IndexWriter = orders.writer_with_num_threads(1, 20_000_000)?;
+/// order_writer.add_document(doc!(order_customer_id => 1u64))?;
+/// order_writer.add_document(doc!(order_customer_id => 2u64))?;
+/// order_writer.add_document(doc!(order_customer_id => 1u64))?;
+/// order_writer.commit()?;
+///
+/// let french_customers = TermQuery::new(
+///     Term::from_field_text(country, "fr"),
+///     IndexRecordOption::Basic,
+/// );
+/// let query = JoinQuery::new(
+///     customers.reader()?.searcher(),
+///     Box::new(french_customers),
+///     "id",
+///     "customer_id",
+/// );
+/// let order_searcher = orders.reader()?.searcher();
+/// assert_eq!(order_searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct JoinQuery {
+    from_searcher: Searcher,
+    from_query: Box<dyn Query>,
+    from_field: String,
+    to_field: String,
+}
+
+impl JoinQuery {
+    /// Creates a new `JoinQuery`, matching the documents whose `to_field` value is the
+    /// `from_field` value of one of the documents of `from_searcher` matching `from_query`.
+    ///
+    /// This constructor never fails, but executing the search with this query will return an
+    /// error if either field does not exist, is not a fast field, or if their types differ.
+    pub fn new(
+        from_searcher: Searcher,
+        from_query: Box<dyn Query>,
+        from_field: &str,
+        to_field: &str,
+    ) -> JoinQuery {
+        JoinQuery {
+            from_searcher,
+            from_query,
+            from_field: from_field.to_string(),
+            to_field: to_field.to_string(),
+        }
+    }
+}
+
+impl Clone for JoinQuery {
+    fn clone(&self) -> Self {
+        JoinQuery {
+            from_searcher: self.from_searcher.clone(),
+            from_query: self.from_query.box_clone(),
+            from_field: self.from_field.clone(),
+            to_field: self.to_field.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for JoinQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Join(from_query={:?}, from_field={}, to_field={})",
+            self.from_query, self.from_field, self.to_field
+        )
+    }
+}
+
+/// Returns the type of the column storing the keys of a field, checking that it can be used
+/// as a join key.
+fn key_column_type(schema: &Schema, field_name: &str) -> crate::Result<ColumnType> {
+    let Some((field, _path)) = schema.find_field(field_name) else {
+        return Err(TantivyError::FieldNotFound(field_name.to_string()));
+    };
+    let field_type = schema.get_field_entry(field).field_type();
+    if !field_type.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {field_name} is not a fast field."
+        )));
+    }
+    match field_type_to_column_type(field_type) {
+        Some(
+            column_type @ (ColumnType::U64
+            | ColumnType::I64
+            | ColumnType::F64
+            | ColumnType::Bool
+            | ColumnType::DateTime
+            | ColumnType::Str
+            | ColumnType::Bytes),
+        ) => Ok(column_type),
+        _ => Err(TantivyError::SchemaError(format!(
+            "Field {field_name} of type {:?} cannot be used as a join key.",
+            field_type.value_type()
+        ))),
+    }
+}
+
+impl Query for JoinQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let from_column_type = key_column_type(self.from_searcher.schema(), &self.from_field)?;
+        let to_column_type = key_column_type(enable_scoring.schema(), &self.to_field)?;
+        if from_column_type != to_column_type {
+            return Err(TantivyError::SchemaError(format!(
+                "Cannot join field {} of type {from_column_type:?} with field {} of type \
+                 {to_column_type:?}.",
+                self.from_field, self.to_field
+            )));
+        }
+        let join_keys_collector = JoinKeysCollector {
+            field_name: self.from_field.clone(),
+            column_type: from_column_type,
+        };
+        let keys = self
+            .from_searcher
+            .search(self.from_query.as_ref(), &join_keys_collector)?;
+        Ok(Box::new(JoinWeight {
+            field_name: self.to_field.clone(),
+            column_type: to_column_type,
+            keys: Arc::new(keys),
+        }))
+    }
+}
+
+/// The keys collected on the index the join starts from.
+enum JoinKeys {
+    /// `u64`-mapped values of a numerical, date or boolean column.
+    Numerical(HashSet<u64>),
+    /// Terms of a text or bytes column.
+    Terms(BTreeSet<Vec<u8>>),
+}
+
+/// Opens the column of term ordinals of a text or bytes fast field, along with its dictionary.
+fn open_bytes_column(
+    reader: &SegmentReader,
+    field_name: &str,
+    column_type: ColumnType,
+) -> crate::Result<Option<BytesColumn>> {
+    let fast_fields = reader.fast_fields();
+    if column_type == ColumnType::Str {
+        Ok(fast_fields.str(field_name)?.map(BytesColumn::from))
+    } else {
+        fast_fields.bytes(field_name)
+    }
+}
+
+fn is_term_column_type(column_type: ColumnType) -> bool {
+    matches!(column_type, ColumnType::Str | ColumnType::Bytes)
+}
+
+/// Collects the keys of the documents matching the query the join starts from.
+struct JoinKeysCollector {
+    field_name: String,
+    column_type: ColumnType,
+}
+
+/// The keys collected on a segment: either the values, or the term ordinals along with the
+/// dictionary mapping them back to their terms.
+struct SegmentJoinKeys {
+    values: HashSet<u64>,
+    dictionary: Option<BytesColumn>,
+}
+
+impl Collector for JoinKeysCollector {
+    type Fruit = JoinKeys;
+
+    type Child = JoinKeysSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_ord: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<JoinKeysSegmentCollector> {
+        let (column, dictionary) = if is_term_column_type(self.column_type) {
+            match open_bytes_column(reader, &self.field_name, self.column_type)? {
+                Some(bytes_column) => (Some(bytes_column.ords().clone()), Some(bytes_column)),
+                None => (None, None),
+            }
+        } else {
+            let column = reader
+                .fast_fields()
+                .u64_lenient_for_type(Some(&[self.column_type]), &self.field_name)?
+                .map(|(column, _column_type)| column);
+            (column, None)
+        };
+        Ok(JoinKeysSegmentCollector {
+            column,
+            keys: SegmentJoinKeys {
+                values: HashSet::new(),
+                dictionary,
+            },
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, segment_keys: Vec<SegmentJoinKeys>) -> crate::Result<JoinKeys> {
+        if !is_term_column_type(self.column_type) {
+            let values = segment_keys
+                .into_iter()
+                .flat_map(|segment_keys| segment_keys.values)
+                .collect();
+            return Ok(JoinKeys::Numerical(values));
+        }
+        let mut terms = BTreeSet::new();
+        for segment_keys in segment_keys {
+            let Some(dictionary) = segment_keys.dictionary else {
+                continue;
+            };
+            let mut term_ords: Vec<u64> = segment_keys.values.into_iter().collect();
+            term_ords.sort_unstable();
+            dictionary.sorted_ords_to_bytes_cb(term_ords.into_iter(), |term| {
+                terms.insert(term.to_vec());
+                Ok(())
+            })?;
+        }
+        Ok(JoinKeys::Terms(terms))
+    }
+}
+
+struct JoinKeysSegmentCollector {
+    column: Option<Column<u64>>,
+    keys: SegmentJoinKeys,
+}
+
+impl SegmentCollector for JoinKeysSegmentCollector {
+    type Fruit = SegmentJoinKeys;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        if let Some(column) = &self.column {
+            self.keys.values.extend(column.values_for_doc(doc));
+        }
+    }
+
+    fn harvest(self) -> SegmentJoinKeys {
+        self.keys
+    }
+}
+
+/// Weight associated with the `JoinQuery` query.
+struct JoinWeight {
+    field_name: String,
+    column_type: ColumnType,
+    keys: Arc<JoinKeys>,
+}
+
+impl JoinWeight {
+    /// Returns the column of the segment the keys are looked up in, along with the collected
+    /// keys, expressed as values of this column.
+    fn column_and_values(
+        &self,
+        reader: &SegmentReader,
+    ) -> crate::Result<Option<(Column<u64>, HashSet<u64>)>> {
+        match self.keys.as_ref() {
+            JoinKeys::Numerical(values) => Ok(reader
+                .fast_fields()
+                .u64_lenient_for_type(Some(&[self.column_type]), &self.field_name)?
+                .map(|(column, _column_type)| (column, values.clone()))),
+            JoinKeys::Terms(terms) => {
+                let Some(bytes_column) =
+                    open_bytes_column(reader, &self.field_name, self.column_type)?
+                else {
+                    return Ok(None);
+                };
+                let mut term_ords = HashSet::new();
+                for term in terms {
+                    if let Some(term_ord) = bytes_column.term_ord(term)? {
+                        term_ords.insert(term_ord);
+                    }
+                }
+                Ok(Some((bytes_column.ords().clone(), term_ords)))
+            }
+        }
+    }
+}
+
+impl Weight for JoinWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some((column, values)) = self.column_and_values(reader)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        if values.is_empty() {
+            return Ok(Box::new(EmptyScorer));
+        }
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        for doc in 0..max_doc {
+            if column
+                .values_for_doc(doc)
+                .any(|value| values.contains(&value))
+            {
+                doc_bitset.insert(doc);
+            }
+        }
+        let docset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("JoinQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::{AllQuery, JoinQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    fn customers_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let name = schema_builder.add_text_field("name", STRING | FAST);
+        let country = schema_builder.add_text_field("country", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 1u64, name => "alice", country => "fr"))?;
+        index_writer.add_document(doc!(id => 2u64, name => "bob", country => "de"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 3u64, name => "carol", country => "fr"))?;
+        index_writer.add_document(doc!(id => 4u64, name => "dave", country => "it"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn orders_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let customer_id = schema_builder.add_u64_field("customer_id", FAST);
+        let customer_names = schema_builder.add_text_field("customer_names", STRING | FAST);
+        let amount = schema_builder.add_i64_field("amount", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            customer_id => 1u64,
+            customer_names => "alice",
+            amount => 10i64,
+        ))?;
+        index_writer.add_document(doc!(
+            customer_id => 2u64,
+            customer_names => "bob",
+            customer_names => "dave",
+            amount => 20i64,
+        ))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(
+            customer_id => 3u64,
+            customer_names => "carol",
+            amount => 30i64,
+        ))?;
+        index_writer.add_document(doc!(customer_id => 5u64, amount => 40i64))?;
+        index_writer.add_document(doc!(amount => 50i64))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn country_query(customers: &Index, country: &str) -> Box<TermQuery> {
+        let country_field = customers.schema().get_field("country").unwrap();
+        Box::new(TermQuery::new(
+            Term::from_field_text(country_field, country),
+            IndexRecordOption::Basic,
+        ))
+    }
+
+    fn matching_amounts(orders: &Index, query: &JoinQuery) -> crate::Result<Vec<i64>> {
+        let searcher = orders.reader()?.searcher();
+        let doc_addresses = searcher.search(query, &DocSetCollector)?;
+        let mut amounts = Vec::new();
+        for DocAddress {
+            segment_ord,
+            doc_id,
+        } in doc_addresses
+        {
+            let amount_column = searcher
+                .segment_reader(segment_ord)
+                .fast_fields()
+                .i64("amount")?;
+            amounts.push(amount_column.first(doc_id).unwrap());
+        }
+        amounts.sort_unstable();
+        Ok(amounts)
+    }
+
+    #[test]
+    fn test_join_query_numerical_keys() -> crate::Result<()> {
+        let customers = customers_index()?;
+        let orders = orders_index()?;
+        let customer_searcher = customers.reader()?.searcher();
+
+        let query = JoinQuery::new(
+            customer_searcher.clone(),
+            country_query(&customers, "fr"),
+            "id",
+            "customer_id",
+        );
+        assert_eq!(matching_amounts(&orders, &query)?, vec![10, 30]);
+
+        let query = JoinQuery::new(
+            customer_searcher.clone(),
+            country_query(&customers, "es"),
+            "id",
+            "customer_id",
+        );
+        assert!(matching_amounts(&orders, &query)?.is_empty());
+
+        let query = JoinQuery::new(customer_searcher, Box::new(AllQuery), "id", "customer_id");
+        assert_eq!(matching_amounts(&orders, &query)?, vec![10, 20, 30]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_query_text_keys() -> crate::Result<()> {
+        let customers = customers_index()?;
+        let orders = orders_index()?;
+        let customer_searcher = customers.reader()?.searcher();
+
+        let query = JoinQuery::new(
+            customer_searcher.clone(),
+            country_query(&customers, "fr"),
+            "name",
+            "customer_names",
+        );
+        assert_eq!(matching_amounts(&orders, &query)?, vec![10, 30]);
+
+        // Any of the values of a multivalued field can match.
+        let query = JoinQuery::new(
+            customer_searcher,
+            country_query(&customers, "it"),
+            "name",
+            "customer_names",
+        );
+        assert_eq!(matching_amounts(&orders, &query)?, vec![20]);
+
+        let order_searcher = orders.reader()?.searcher();
+        let doc_address = *order_searcher
+            .search(&query, &DocSetCollector)?
+            .iter()
+            .next()
+            .unwrap();
+        assert_eq!(doc_address.doc_id, 1);
+        let explanation = query.explain(&order_searcher, doc_address)?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(query
+            .explain(&order_searcher, DocAddress::new(doc_address.segment_ord, 0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_query_invalid_fields() -> crate::Result<()> {
+        let customers = customers_index()?;
+        let orders = orders_index()?;
+        let customer_searcher = customers.reader()?.searcher();
+        let order_searcher = orders.reader()?.searcher();
+        let search = |from_field: &str, to_field: &str| {
+            let query = JoinQuery::new(
+                customer_searcher.clone(),
+                Box::new(AllQuery),
+                from_field,
+                to_field,
+            );
+            order_searcher.search(&query, &Count)
+        };
+        assert!(search("id", "customer_id").is_ok());
+        assert!(search("missing", "customer_id").is_err());
+        assert!(search("id", "missing").is_err());
+        // Not a fast field.
+        assert!(search("country", "customer_names").is_err());
+        // Different types.
+        assert!(search("id", "amount").is_err());
+        assert!(search("name", "customer_id").is_err());
+        Ok(())
+    }
+}
@@ -4,25 +4,33 @@ mod bitset;
 mod bm25;
 mod boolean_query;
 mod boost_query;
+mod boosting_query;
 mod const_score_query;
+mod custom_score_query;
 mod disjunction;
 mod disjunction_max_query;
+mod distance_feature_query;
+mod doc_boost;
 mod empty_query;
 mod exclude;
 mod exist_query;
 mod explanation;
+mod function_score_query;
 mod fuzzy_query;
 mod intersection;
+mod join_query;
 mod more_like_this;
 mod phrase_prefix_query;
 mod phrase_query;
 mod query;
 mod query_parser;
 mod range_query;
+mod rank_feature_query;
 mod regex_query;
 mod reqopt_scorer;
 mod scorer;
 mod set_query;
+mod term_matcher;
 mod term_query;
 mod union;
 mod weight;
@@ -39,28 +47,40 @@ pub use self::bitset::BitSetDocSet;
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
+pub use self::boosting_query::BoostingQuery;
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
+pub use self::custom_score_query::{CustomScoreQuery, CustomScorer, CustomSegmentScorer};
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
+pub use self::distance_feature_query::DistanceFeatureQuery;
+pub(crate) use self::doc_boost::AdditiveDocBoostWeight;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
 pub use self::exist_query::ExistsQuery;
 pub use self::explanation::Explanation;
+pub use self::function_score_query::{
+    CombineMode, DecayFunction, DecayType, FieldValueFactor, FieldValueModifier,
+    FunctionScoreQuery, ScoreFunction,
+};
+pub(crate) use self::fuzzy_query::fuzzy_automaton;
 #[cfg(test)]
 pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::{intersect_scorers, Intersection};
+pub use self::join_query::JoinQuery;
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
 pub use self::phrase_query::regex_phrase_query::{wildcard_query_to_regex_str, RegexPhraseQuery};
-pub use self::phrase_query::PhraseQuery;
+pub use self::phrase_query::{MultiPhraseQuery, PhraseQuery};
 pub use self::query::{EnableScoring, Query, QueryClone};
 pub use self::query_parser::{QueryParser, QueryParserError};
 pub use self::range_query::*;
+pub use self::rank_feature_query::{RankFeatureFunction, RankFeatureQuery};
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
 pub use self::score_combiner::{DisjunctionMaxCombiner, ScoreCombiner, SumCombiner};
 pub use self::scorer::Scorer;
 pub use self::set_query::TermSetQuery;
+pub use self::term_matcher::TermMatcher;
 pub use self::term_query::TermQuery;
 pub use self::union::BufferedUnionScorer;
 #[cfg(test)]
@@ -62,6 +62,8 @@ pub struct MoreLikeThis {
     pub boost_factor: Option<f32>,
     /// Current set of stop words.
     pub stop_words: Vec<String>,
+    /// Only extract terms from these fields. All of the fields are used if empty.
+    pub fields: Vec<Field>,
 }
 
 impl Default for MoreLikeThis {
@@ -75,6 +77,7 @@ impl Default for MoreLikeThis {
             max_word_length: None,
             boost_factor: Some(1.0),
             stop_words: vec![],
+            fields: vec![],
         }
     }
 }
@@ -103,6 +106,24 @@ impl MoreLikeThis {
         Ok(query)
     }
 
+    /// Creates a [`BooleanQuery`] using a raw text.
+    ///
+    /// The text is analyzed with the tokenizer of each of the selected fields, or of
+    /// all of the indexed text fields if no field was selected.
+    pub fn query_with_text(&self, searcher: &Searcher, text: &str) -> Result<BooleanQuery> {
+        let schema = searcher.schema();
+        let doc_fields: Vec<(Field, Vec<&str>)> = schema
+            .fields()
+            .filter(|(field, field_entry)| {
+                matches!(field_entry.field_type(), FieldType::Str(_))
+                    && field_entry.is_indexed()
+                    && self.is_selected_field(*field)
+            })
+            .map(|(field, _)| (field, vec![text]))
+            .collect();
+        self.query_with_document_fields(searcher, &doc_fields)
+    }
+
     /// Creates a [`BooleanQuery`] from an ascendingly sorted list of ScoreTerm
     /// This will map the list of ScoreTerm to a list of [`TermQuery`]  and compose a
     /// BooleanQuery using that list as sub queries.
@@ -151,6 +172,9 @@ impl MoreLikeThis {
         }
         let mut field_to_term_freq_map = HashMap::new();
         for (field, values) in field_to_values {
+            if !self.is_selected_field(*field) {
+                continue;
+            }
             self.add_term_frequencies(searcher, *field, values, &mut field_to_term_freq_map)?;
         }
         self.create_score_term(searcher, field_to_term_freq_map)
@@ -272,6 +296,10 @@ impl MoreLikeThis {
         Ok(())
     }
 
+    fn is_selected_field(&self, field: Field) -> bool {
+        self.fields.is_empty() || self.fields.contains(&field)
+    }
+
     /// Determines if the term is likely to be of interest based on "more-like-this" settings
     fn is_noise_word(&self, word: String) -> bool {
         let word_length = word.len();
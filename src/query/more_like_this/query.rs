@@ -34,6 +34,7 @@ pub struct MoreLikeThisQuery {
 enum TargetDocument {
     DocumentAddress(DocAddress),
     DocumentFields(Vec<(Field, Vec<OwnedValue>)>),
+    Text(String),
 }
 
 impl MoreLikeThisQuery {
@@ -67,6 +68,10 @@ impl Query for MoreLikeThisQuery {
                     .query_with_document_fields(searcher, &values)?
                     .weight(enable_scoring)
             }
+            TargetDocument::Text(text) => self
+                .mlt
+                .query_with_text(searcher, text)?
+                .weight(enable_scoring),
         }
     }
 }
@@ -153,6 +158,16 @@ impl MoreLikeThisQueryBuilder {
         self
     }
 
+    /// Sets the fields to extract terms from.
+    ///
+    /// The resulting query will ignore the values of the other fields.
+    /// By default, all of the fields are used.
+    #[must_use]
+    pub fn with_fields(mut self, value: Vec<Field>) -> Self {
+        self.mlt.fields = value;
+        self
+    }
+
     /// Sets the document address
     /// Returns the constructed [`MoreLikeThisQuery`]
     ///
@@ -184,6 +199,20 @@ impl MoreLikeThisQueryBuilder {
             target: TargetDocument::DocumentFields(doc_fields),
         }
     }
+
+    /// Sets a raw text
+    /// Returns the constructed [`MoreLikeThisQuery`]
+    ///
+    /// The text is analyzed as a value of each of the fields set with
+    /// [`with_fields`](Self::with_fields), or of all of the indexed text fields
+    /// if none was set. This is useful to find the documents similar to a text
+    /// that is not in the index.
+    pub fn with_text(self, text: impl Into<String>) -> MoreLikeThisQuery {
+        MoreLikeThisQuery {
+            mlt: self.mlt,
+            target: TargetDocument::Text(text.into()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -222,6 +251,7 @@ mod tests {
         assert_eq!(query.mlt.max_word_length, None);
         assert_eq!(query.mlt.boost_factor, Some(1.0));
         assert_eq!(query.mlt.stop_words, Vec::<String>::new());
+        assert!(query.mlt.fields.is_empty());
         assert_eq!(query.target, TargetDocument::DocumentFields(vec![]));
 
         // custom settings
@@ -291,4 +321,44 @@ mod tests {
         assert_eq!(doc_ids, vec![3, 4]);
         Ok(())
     }
+
+    #[test]
+    fn test_more_like_this_query_with_text() -> crate::Result<()> {
+        let index = create_test_index()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let schema = index.schema();
+        let title = schema.get_field("title")?;
+        let body = schema.get_field("body")?;
+
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_text("sea bbb");
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        let mut doc_ids: Vec<_> = top_docs.iter().map(|item| item.1.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![0, 1]);
+
+        // only the body field is used, so `bbb` is ignored
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_fields(vec![body])
+            .with_text("lady bbb");
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        let mut doc_ids: Vec<_> = top_docs.iter().map(|item| item.1.doc_id).collect();
+        doc_ids.sort_unstable();
+        assert_eq!(doc_ids, vec![3, 4]);
+
+        // the stored body of the 2nd document is ignored
+        let query = MoreLikeThisQuery::builder()
+            .with_min_doc_frequency(1)
+            .with_min_term_frequency(1)
+            .with_fields(vec![title])
+            .with_document(DocAddress::new(0, 1));
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(5))?;
+        assert!(top_docs.is_empty());
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use std::ops::Bound;
 use super::{prefix_end, PhrasePrefixWeight};
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, InvertedIndexRangeWeight, Query, Weight};
-use crate::schema::{Field, IndexRecordOption, Term};
+use crate::schema::{Field, IndexRecordOption, Similarity, Term};
 
 const DEFAULT_MAX_EXPANSIONS: u32 = 50;
 
@@ -27,6 +27,7 @@ pub struct PhrasePrefixQuery {
     phrase_terms: Vec<(usize, Term)>,
     prefix: (usize, Term),
     max_expansions: u32,
+    similarity: Option<Similarity>,
 }
 
 impl PhrasePrefixQuery {
@@ -60,6 +61,7 @@ impl PhrasePrefixQuery {
             prefix: terms.pop().unwrap(),
             phrase_terms: terms,
             max_expansions: DEFAULT_MAX_EXPANSIONS,
+            similarity: None,
         }
     }
 
@@ -68,6 +70,11 @@ impl PhrasePrefixQuery {
         self.max_expansions = value;
     }
 
+    /// Overrides the [`Similarity`] of the field for this query.
+    pub fn set_similarity(&mut self, similarity: Similarity) {
+        self.similarity = Some(similarity);
+    }
+
     /// The [`Field`] this `PhrasePrefixQuery` is targeting.
     pub fn field(&self) -> Field {
         self.field
@@ -111,9 +118,15 @@ impl PhrasePrefixQuery {
         }
         let terms = self.phrase_terms();
         let bm25_weight_opt = match enable_scoring {
-            EnableScoring::Enabled { searcher, .. } => {
-                Some(Bm25Weight::for_terms(searcher, &terms)?)
-            }
+            EnableScoring::Enabled { searcher, .. } => Some(
+                Bm25Weight::for_terms_with_similarity(
+                    searcher,
+                    &terms,
+                    self.similarity
+                        .unwrap_or_else(|| field_entry.field_type().similarity()),
+                )?
+                .with_fieldnorm_precision(field_entry.field_type().fieldnorm_precision()),
+            ),
             EnableScoring::Disabled { .. } => None,
         };
         let weight = PhrasePrefixWeight::new(
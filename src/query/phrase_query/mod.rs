@@ -1,9 +1,12 @@
+mod multi_phrase_query;
+mod multi_phrase_weight;
 mod phrase_query;
 mod phrase_scorer;
 mod phrase_weight;
 pub mod regex_phrase_query;
 mod regex_phrase_weight;
 
+pub use self::multi_phrase_query::MultiPhraseQuery;
 pub use self::phrase_query::PhraseQuery;
 pub(crate) use self::phrase_scorer::intersection_count;
 pub use self::phrase_scorer::PhraseScorer;
@@ -0,0 +1,266 @@
+use super::multi_phrase_weight::MultiPhraseWeight;
+use crate::query::bm25::Bm25Weight;
+use crate::query::{EnableScoring, Query, Weight};
+use crate::schema::{Field, IndexRecordOption, Similarity, Term};
+
+/// `MultiPhraseQuery` matches a sequence of words, accepting several alternative
+/// terms at each position of the phrase.
+///
+/// For instance, the multi phrase query for `[["quick", "fast"], ["fox"]]` will match
+/// both **the quick fox** and **the fast fox**.
+///
+/// This makes it possible to run phrase queries over the output of analyzers emitting
+/// stacked tokens, e.g. synonym or stemming expansions.
+///
+/// [Slop](MultiPhraseQuery::set_slop) allows leniency in term proximity
+/// for some performance trade-off.
+///
+/// Using a `MultiPhraseQuery` on a field requires positions
+/// to be indexed for this field.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::MultiPhraseQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The quick fox"))?;
+/// index_writer.add_document(doc!(title => "The fast fox"))?;
+/// index_writer.add_document(doc!(title => "The fast brown fox"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = MultiPhraseQuery::new(vec![
+///     vec![
+///         Term::from_field_text(title, "quick"),
+///         Term::from_field_text(title, "fast"),
+///     ],
+///     vec![Term::from_field_text(title, "fox")],
+/// ]);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiPhraseQuery {
+    field: Field,
+    phrase_terms: Vec<(usize, Vec<Term>)>,
+    slop: u32,
+    similarity: Option<Similarity>,
+}
+
+impl MultiPhraseQuery {
+    /// Creates a new `MultiPhraseQuery` given a list of term alternatives.
+    ///
+    /// There must be at least two positions, each of them with at least one term,
+    /// and all terms must belong to the same field.
+    /// Offset for each position will be same as index in the Vector
+    pub fn new(terms: Vec<Vec<Term>>) -> MultiPhraseQuery {
+        let terms_with_offset = terms.into_iter().enumerate().collect();
+        MultiPhraseQuery::new_with_offset(terms_with_offset)
+    }
+
+    /// Creates a new `MultiPhraseQuery` given a list of term alternatives and their offsets.
+    ///
+    /// Can be used to provide custom offset for each position.
+    pub fn new_with_offset(terms: Vec<(usize, Vec<Term>)>) -> MultiPhraseQuery {
+        MultiPhraseQuery::new_with_offset_and_slop(terms, 0)
+    }
+
+    /// Creates a new `MultiPhraseQuery` given a list of term alternatives, their offsets and a
+    /// slop
+    pub fn new_with_offset_and_slop(
+        mut terms: Vec<(usize, Vec<Term>)>,
+        slop: u32,
+    ) -> MultiPhraseQuery {
+        assert!(
+            terms.len() > 1,
+            "A multi phrase query is required to have strictly more than one position."
+        );
+        assert!(
+            terms
+                .iter()
+                .all(|(_, alternatives)| !alternatives.is_empty()),
+            "Each position of a multi phrase query is required to have at least one term."
+        );
+        terms.sort_by_key(|&(offset, _)| offset);
+        let field = terms[0].1[0].field();
+        assert!(
+            terms
+                .iter()
+                .flat_map(|(_, alternatives)| alternatives)
+                .all(|term| term.field() == field),
+            "All terms from a multi phrase query must belong to the same field"
+        );
+        MultiPhraseQuery {
+            field,
+            phrase_terms: terms,
+            slop,
+            similarity: None,
+        }
+    }
+
+    /// Slop allowed for the phrase.
+    ///
+    /// See [`PhraseQuery::set_slop`](crate::query::PhraseQuery::set_slop).
+    /// By default the slop is 0 meaning query terms need to be adjacent.
+    pub fn set_slop(&mut self, value: u32) {
+        self.slop = value;
+    }
+
+    /// Overrides the [`Similarity`] of the field for this query.
+    pub fn set_similarity(&mut self, similarity: Similarity) {
+        self.similarity = Some(similarity);
+    }
+
+    /// The [`Field`] this `MultiPhraseQuery` is targeting.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// All of the `Term`s of the phrase, without the associated offsets.
+    pub fn phrase_terms(&self) -> Vec<Term> {
+        self.phrase_terms
+            .iter()
+            .flat_map(|(_, alternatives)| alternatives.iter().cloned())
+            .collect::<Vec<Term>>()
+    }
+
+    /// Returns the [`MultiPhraseWeight`] for the given query given a specific `searcher`.
+    ///
+    /// This function is the same as [`Query::weight()`] except it returns
+    /// a specialized type [`MultiPhraseWeight`] instead of a Boxed trait.
+    pub(crate) fn multi_phrase_weight(
+        &self,
+        enable_scoring: EnableScoring<'_>,
+    ) -> crate::Result<MultiPhraseWeight> {
+        let schema = enable_scoring.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(IndexRecordOption::has_positions)
+            .unwrap_or(false);
+        if !has_positions {
+            let field_name = field_entry.name();
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Applied phrase query on field {field_name:?}, which does not have positions \
+                 indexed"
+            )));
+        }
+        let terms = self.phrase_terms();
+        let bm25_weight_opt = match enable_scoring {
+            EnableScoring::Enabled {
+                statistics_provider,
+                ..
+            } => Some(
+                Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    &terms,
+                    self.similarity
+                        .unwrap_or_else(|| field_entry.field_type().similarity()),
+                )?
+                .with_fieldnorm_precision(field_entry.field_type().fieldnorm_precision()),
+            ),
+            EnableScoring::Disabled { .. } => None,
+        };
+        Ok(MultiPhraseWeight::new(
+            self.field,
+            self.phrase_terms.clone(),
+            bm25_weight_opt,
+            self.slop,
+        ))
+    }
+}
+
+impl Query for MultiPhraseQuery {
+    /// Create the weight associated with a query.
+    ///
+    /// See [`Weight`].
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let multi_phrase_weight = self.multi_phrase_weight(enable_scoring)?;
+        Ok(Box::new(multi_phrase_weight))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        for (_, alternatives) in &self.phrase_terms {
+            for term in alternatives {
+                visitor(term, true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiPhraseQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::phrase_query::tests::create_index;
+    use crate::query::{PhraseQuery, Query};
+    use crate::schema::{Field, Term};
+    use crate::{DocAddress, TantivyError};
+
+    #[test]
+    fn test_multi_phrase_query() -> crate::Result<()> {
+        let index = create_index(&["a b c", "a d c", "a e c", "b d c", "a b x c", "a d"])?;
+        let searcher = index.reader()?.searcher();
+        let text_field = Field::from_field_id(0);
+        let term = |text: &str| Term::from_field_text(text_field, text);
+        let matching_docs = |query: &MultiPhraseQuery| -> crate::Result<Vec<u32>> {
+            let mut doc_ids: Vec<u32> = searcher
+                .search(query, &TopDocs::with_limit(10))?
+                .into_iter()
+                .map(|(_, doc_address)| doc_address.doc_id)
+                .collect();
+            doc_ids.sort_unstable();
+            Ok(doc_ids)
+        };
+
+        let query = MultiPhraseQuery::new(vec![
+            vec![term("a")],
+            vec![term("b"), term("d"), term("missing")],
+            vec![term("c")],
+        ]);
+        assert_eq!(matching_docs(&query)?, vec![0, 1]);
+
+        let mut query = query;
+        query.set_slop(1);
+        assert_eq!(matching_docs(&query)?, vec![0, 1, 4]);
+
+        let query =
+            MultiPhraseQuery::new_with_offset(vec![(0, vec![term("a")]), (2, vec![term("c")])]);
+        assert_eq!(matching_docs(&query)?, vec![0, 1, 2]);
+
+        let query = MultiPhraseQuery::new(vec![vec![term("a")], vec![term("missing")]]);
+        assert_eq!(searcher.search(&query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_phrase_query_single_alternatives_scores_like_phrase() -> crate::Result<()> {
+        let index = create_index(&["a b c", "a b", "b c"])?;
+        let searcher = index.reader()?.searcher();
+        let text_field = Field::from_field_id(0);
+        let term = |text: &str| Term::from_field_text(text_field, text);
+        let multi_phrase_query = MultiPhraseQuery::new(vec![vec![term("a")], vec![term("b")]]);
+        let phrase_query = PhraseQuery::new(vec![term("a"), term("b")]);
+        assert_eq!(
+            searcher.search(&multi_phrase_query, &TopDocs::with_limit(10))?,
+            searcher.search(&phrase_query, &TopDocs::with_limit(10))?
+        );
+        let explanation = multi_phrase_query.explain(&searcher, DocAddress::new(0, 1))?;
+        let expected = phrase_query.explain(&searcher, DocAddress::new(0, 1))?;
+        assert_eq!(explanation.value(), expected.value());
+        assert!(matches!(
+            multi_phrase_query.explain(&searcher, DocAddress::new(0, 2)),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
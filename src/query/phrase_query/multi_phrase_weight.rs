@@ -0,0 +1,121 @@
+use super::PhraseScorer;
+use crate::fieldnorm::FieldNormReader;
+use crate::index::SegmentReader;
+use crate::postings::SegmentPostings;
+use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
+use crate::query::explanation::does_not_match;
+use crate::query::union::SimpleUnion;
+use crate::query::{EmptyScorer, Explanation, Scorer, Weight};
+use crate::schema::{Field, IndexRecordOption, Term};
+use crate::{DocBoostMode, DocId, DocSet, Score};
+
+/// The `MultiPhraseWeight` is the weight associated to a [`MultiPhraseQuery`].
+///
+/// [`MultiPhraseQuery`]: crate::query::MultiPhraseQuery
+pub struct MultiPhraseWeight {
+    field: Field,
+    phrase_terms: Vec<(usize, Vec<Term>)>,
+    similarity_weight_opt: Option<Bm25Weight>,
+    slop: u32,
+}
+
+impl MultiPhraseWeight {
+    /// Creates a new multi phrase weight.
+    /// If `similarity_weight_opt` is None, then scoring is disabled
+    pub fn new(
+        field: Field,
+        phrase_terms: Vec<(usize, Vec<Term>)>,
+        similarity_weight_opt: Option<Bm25Weight>,
+        slop: u32,
+    ) -> MultiPhraseWeight {
+        MultiPhraseWeight {
+            field,
+            phrase_terms,
+            similarity_weight_opt,
+            slop,
+        }
+    }
+
+    fn fieldnorm_reader(&self, reader: &SegmentReader) -> crate::Result<FieldNormReader> {
+        if self.similarity_weight_opt.is_some() {
+            if let Some(fieldnorm_reader) = reader.fieldnorms_readers().get_field(self.field)? {
+                return Ok(fieldnorm_reader);
+            }
+        }
+        Ok(FieldNormReader::constant(reader.max_doc(), 1))
+    }
+
+    pub(crate) fn phrase_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<Option<PhraseScorer<SimpleUnion<SegmentPostings>>>> {
+        let similarity_weight_opt = self
+            .similarity_weight_opt
+            .as_ref()
+            .map(|similarity_weight| similarity_weight.boost_by(boost));
+        let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let doc_boost_opt = if self.similarity_weight_opt.is_some() {
+            DocBoost::open(reader, DocBoostMode::Multiply)?
+        } else {
+            None
+        };
+        let inverted_index = reader.inverted_index(self.field)?;
+        let mut posting_lists = Vec::with_capacity(self.phrase_terms.len());
+        for (offset, terms) in &self.phrase_terms {
+            let mut term_postings_list = Vec::with_capacity(terms.len());
+            for term in terms {
+                if let Some(postings) =
+                    inverted_index.read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+                {
+                    term_postings_list.push(postings);
+                }
+            }
+            // None of the alternatives exist, the phrase can not match any documents.
+            if term_postings_list.is_empty() {
+                return Ok(None);
+            }
+            posting_lists.push((*offset, SimpleUnion::build(term_postings_list)));
+        }
+        Ok(Some(
+            PhraseScorer::new(
+                posting_lists,
+                similarity_weight_opt,
+                fieldnorm_reader,
+                self.slop,
+            )
+            .with_doc_boost(doc_boost_opt),
+        ))
+    }
+}
+
+impl Weight for MultiPhraseWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        if let Some(scorer) = self.phrase_scorer(reader, boost)? {
+            Ok(Box::new(scorer))
+        } else {
+            Ok(Box::new(EmptyScorer))
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let Some(mut scorer) = self.phrase_scorer(reader, 1.0)? else {
+            return Err(does_not_match(doc));
+        };
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
+        let phrase_count = scorer.phrase_count();
+        let mut explanation = Explanation::new("Multi Phrase Scorer", scorer.score());
+        if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
+            explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
+        }
+        if let Some(doc_boost) = scorer.doc_boost() {
+            doc_boost.explain(doc, &mut explanation);
+        }
+        Ok(explanation)
+    }
+}
@@ -1,7 +1,7 @@
 use super::PhraseWeight;
 use crate::query::bm25::Bm25Weight;
-use crate::query::{EnableScoring, Query, Weight};
-use crate::schema::{Field, IndexRecordOption, Term};
+use crate::query::{EnableScoring, Query, TermMatcher, Weight};
+use crate::schema::{Field, IndexRecordOption, Similarity, Term};
 
 /// `PhraseQuery` matches a specific sequence of words.
 ///
@@ -24,6 +24,7 @@ pub struct PhraseQuery {
     field: Field,
     phrase_terms: Vec<(usize, Term)>,
     slop: u32,
+    similarity: Option<Similarity>,
 }
 
 impl PhraseQuery {
@@ -60,6 +61,7 @@ impl PhraseQuery {
             field,
             phrase_terms: terms,
             slop,
+            similarity: None,
         }
     }
 
@@ -81,6 +83,11 @@ impl PhraseQuery {
         self.slop = value;
     }
 
+    /// Overrides the [`Similarity`] of the field for this query.
+    pub fn set_similarity(&mut self, similarity: Similarity) {
+        self.similarity = Some(similarity);
+    }
+
     /// The [`Field`] this `PhraseQuery` is targeting.
     pub fn field(&self) -> Field {
         self.field
@@ -121,7 +128,15 @@ impl PhraseQuery {
             EnableScoring::Enabled {
                 statistics_provider,
                 ..
-            } => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            } => Some(
+                Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    &terms,
+                    self.similarity
+                        .unwrap_or_else(|| field_entry.field_type().similarity()),
+                )?
+                .with_fieldnorm_precision(field_entry.field_type().fieldnorm_precision()),
+            ),
             EnableScoring::Disabled { .. } => None,
         };
         let mut weight = PhraseWeight::new(self.phrase_terms.clone(), bm25_weight_opt);
@@ -146,4 +161,11 @@ impl Query for PhraseQuery {
             visitor(term, true);
         }
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        visitor(TermMatcher::Phrase {
+            terms: &self.phrase_terms,
+            slop: self.slop,
+        });
+    }
 }
@@ -4,6 +4,7 @@ use crate::docset::{DocSet, TERMINATED};
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::Postings;
 use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
 use crate::query::{Intersection, Scorer};
 use crate::{DocId, Score};
 
@@ -51,6 +52,7 @@ pub struct PhraseScorer<TPostings: Postings> {
     phrase_count: u32,
     fieldnorm_reader: FieldNormReader,
     similarity_weight_opt: Option<Bm25Weight>,
+    doc_boost_opt: Option<DocBoost>,
     slop: u32,
     left_slops: Vec<u8>,
     positions_buffer: Vec<u32>,
@@ -388,6 +390,7 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
             right_positions: Vec::with_capacity(100),
             phrase_count: 0u32,
             similarity_weight_opt,
+            doc_boost_opt: None,
             fieldnorm_reader,
             slop,
             left_slops: Vec::with_capacity(100),
@@ -400,6 +403,19 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         scorer
     }
 
+    /// Multiplies the scores by the static boost of the documents.
+    ///
+    /// It has no effect if scoring is disabled.
+    #[must_use]
+    pub(crate) fn with_doc_boost(mut self, doc_boost_opt: Option<DocBoost>) -> Self {
+        self.doc_boost_opt = doc_boost_opt;
+        self
+    }
+
+    pub(crate) fn doc_boost(&self) -> Option<&DocBoost> {
+        self.doc_boost_opt.as_ref()
+    }
+
     pub fn phrase_count(&self) -> u32 {
         self.phrase_count
     }
@@ -542,7 +558,12 @@ impl<TPostings: Postings> Scorer for PhraseScorer<TPostings> {
         let doc = self.doc();
         let fieldnorm_id = self.fieldnorm_reader.fieldnorm_id(doc);
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
-            similarity_weight.score(fieldnorm_id, self.phrase_count)
+            let score = similarity_weight.score(fieldnorm_id, self.phrase_count);
+            if let Some(doc_boost) = self.doc_boost_opt.as_ref() {
+                score * doc_boost.boost(doc)
+            } else {
+                score
+            }
         } else {
             1.0f32
         }
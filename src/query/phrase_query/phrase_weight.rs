@@ -3,10 +3,11 @@ use crate::fieldnorm::FieldNormReader;
 use crate::index::SegmentReader;
 use crate::postings::SegmentPostings;
 use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
 use crate::query::explanation::does_not_match;
 use crate::query::{EmptyScorer, Explanation, Scorer, Weight};
 use crate::schema::{IndexRecordOption, Term};
-use crate::{DocId, DocSet, Score};
+use crate::{DocBoostMode, DocId, DocSet, Score};
 
 pub struct PhraseWeight {
     phrase_terms: Vec<(usize, Term)>,
@@ -49,6 +50,11 @@ impl PhraseWeight {
             .as_ref()
             .map(|similarity_weight| similarity_weight.boost_by(boost));
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let doc_boost_opt = if self.similarity_weight_opt.is_some() {
+            DocBoost::open(reader, DocBoostMode::Multiply)?
+        } else {
+            None
+        };
         let mut term_postings_list = Vec::new();
         for &(offset, ref term) in &self.phrase_terms {
             if let Some(postings) = reader
@@ -60,12 +66,15 @@ impl PhraseWeight {
                 return Ok(None);
             }
         }
-        Ok(Some(PhraseScorer::new(
-            term_postings_list,
-            similarity_weight_opt,
-            fieldnorm_reader,
-            self.slop,
-        )))
+        Ok(Some(
+            PhraseScorer::new(
+                term_postings_list,
+                similarity_weight_opt,
+                fieldnorm_reader,
+                self.slop,
+            )
+            .with_doc_boost(doc_boost_opt),
+        ))
     }
 
     pub fn slop(&mut self, slop: u32) {
@@ -98,6 +107,9 @@ impl Weight for PhraseWeight {
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
             explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
         }
+        if let Some(doc_boost) = scorer.doc_boost() {
+            doc_boost.explain(doc, &mut explanation);
+        }
         Ok(explanation)
     }
 }
@@ -1,7 +1,7 @@
 use super::regex_phrase_weight::RegexPhraseWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Query, Weight};
-use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::schema::{Field, IndexRecordOption, Similarity, Term, Type};
 
 /// `RegexPhraseQuery` matches a specific sequence of regex queries.
 ///
@@ -25,6 +25,7 @@ pub struct RegexPhraseQuery {
     phrase_terms: Vec<(usize, String)>,
     slop: u32,
     max_expansions: u32,
+    similarity: Option<Similarity>,
 }
 
 /// Transform a wildcard query to a regex string.
@@ -71,6 +72,7 @@ impl RegexPhraseQuery {
             phrase_terms: terms,
             slop,
             max_expansions: 1 << 14,
+            similarity: None,
         }
     }
 
@@ -98,6 +100,11 @@ impl RegexPhraseQuery {
         self.max_expansions = value;
     }
 
+    /// Overrides the [`Similarity`] of the field for this query.
+    pub fn set_similarity(&mut self, similarity: Similarity) {
+        self.similarity = Some(similarity);
+    }
+
     /// The [`Field`] this `RegexPhraseQuery` is targeting.
     pub fn field(&self) -> Field {
         self.field
@@ -146,7 +153,15 @@ impl RegexPhraseQuery {
             EnableScoring::Enabled {
                 statistics_provider,
                 ..
-            } => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            } => Some(
+                Bm25Weight::for_terms_with_similarity(
+                    statistics_provider,
+                    &terms,
+                    self.similarity
+                        .unwrap_or_else(|| field_entry.field_type().similarity()),
+                )?
+                .with_fieldnorm_precision(field_entry.field_type().fieldnorm_precision()),
+            ),
             EnableScoring::Disabled { .. } => None,
         };
         let weight = RegexPhraseWeight::new(
@@ -8,11 +8,12 @@ use crate::fieldnorm::FieldNormReader;
 use crate::index::SegmentReader;
 use crate::postings::{LoadedPostings, Postings, SegmentPostings, TermInfo};
 use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
 use crate::query::explanation::does_not_match;
 use crate::query::union::{BitSetPostingUnion, SimpleUnion};
 use crate::query::{AutomatonWeight, BitSetDocSet, EmptyScorer, Explanation, Scorer, Weight};
 use crate::schema::{Field, IndexRecordOption};
-use crate::{DocId, DocSet, InvertedIndexReader, Score};
+use crate::{DocBoostMode, DocId, DocSet, InvertedIndexReader, Score};
 
 type UnionType = SimpleUnion<Box<dyn Postings + 'static>>;
 
@@ -64,6 +65,11 @@ impl RegexPhraseWeight {
             .as_ref()
             .map(|similarity_weight| similarity_weight.boost_by(boost));
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let doc_boost_opt = if self.similarity_weight_opt.is_some() {
+            DocBoost::open(reader, DocBoostMode::Multiply)?
+        } else {
+            None
+        };
         let mut posting_lists = Vec::new();
         let inverted_index = reader.inverted_index(self.field)?;
         let mut num_terms = 0;
@@ -89,12 +95,15 @@ impl RegexPhraseWeight {
             posting_lists.push((offset, union));
         }
 
-        Ok(Some(PhraseScorer::new(
-            posting_lists,
-            similarity_weight_opt,
-            fieldnorm_reader,
-            self.slop,
-        )))
+        Ok(Some(
+            PhraseScorer::new(
+                posting_lists,
+                similarity_weight_opt,
+                fieldnorm_reader,
+                self.slop,
+            )
+            .with_doc_boost(doc_boost_opt),
+        ))
     }
 
     /// Add all docs of the term to the docset
@@ -293,6 +302,9 @@ impl Weight for RegexPhraseWeight {
         if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
             explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
         }
+        if let Some(doc_boost) = scorer.doc_boost() {
+            doc_boost.explain(doc, &mut explanation);
+        }
         Ok(explanation)
     }
 }
@@ -5,7 +5,7 @@ use downcast_rs::impl_downcast;
 use super::bm25::Bm25StatisticsProvider;
 use super::Weight;
 use crate::core::searcher::Searcher;
-use crate::query::Explanation;
+use crate::query::{Explanation, TermMatcher};
 use crate::schema::Schema;
 use crate::{DocAddress, Term};
 
@@ -136,14 +136,14 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
 
     /// Returns an `Explanation` for the score of the document.
     fn explain(&self, searcher: &Searcher, doc_address: DocAddress) -> crate::Result<Explanation> {
-        let weight = self.weight(EnableScoring::enabled_from_searcher(searcher))?;
+        let weight = searcher.weight(self, EnableScoring::enabled_from_searcher(searcher))?;
         let reader = searcher.segment_reader(doc_address.segment_ord);
         weight.explain(reader, doc_address.doc_id)
     }
 
     /// Returns the number of documents matching the query.
     fn count(&self, searcher: &Searcher) -> crate::Result<usize> {
-        let weight = self.weight(EnableScoring::disabled_from_searcher(searcher))?;
+        let weight = searcher.weight(self, EnableScoring::disabled_from_searcher(searcher))?;
         let mut result = 0;
         for reader in searcher.segment_readers() {
             result += weight.count(reader)? as usize;
@@ -160,6 +160,18 @@ pub trait Query: QueryClone + Send + Sync + downcast_rs::Downcast + fmt::Debug {
     /// Note that there can be multiple instances of any given term
     /// in a query and deduplication must be handled by the visitor.
     fn query_terms<'a>(&'a self, _visitor: &mut dyn FnMut(&'a Term, bool)) {}
+
+    /// Describes the terms matched by the query and passes them to the given closure,
+    /// in order to highlight the parts of a text the query matches.
+    ///
+    /// Unlike [`Query::query_terms`], this also describes the phrases, and the terms matched
+    /// by automatons, like the ones of fuzzy and regex queries. The clauses that exclude
+    /// documents are not described.
+    ///
+    /// The default implementation describes the terms of [`Query::query_terms`].
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.query_terms(&mut |term, _| visitor(TermMatcher::Term(term)));
+    }
 }
 
 /// Implements `box_clone`.
@@ -188,6 +200,10 @@ impl Query for Box<dyn Query> {
     fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
         self.as_ref().query_terms(visitor);
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        self.as_ref().term_matchers(visitor);
+    }
 }
 
 impl QueryClone for Box<dyn Query> {
@@ -0,0 +1,232 @@
+//! Date math expressions, usable as the bounds of a range query on a date field,
+//! e.g. `timestamp:[now-7d/d TO now]`.
+//!
+//! An expression starts with an anchor, `now` or a RFC 3339 date followed by `||`, followed by
+//! any number of operations:
+//! - `+<n><unit>` and `-<n><unit>` add or subtract `n` units (1 if omitted).
+//! - `/<unit>` rounds the date to the unit.
+//!
+//! The units are `y` (years), `M` (months), `w` (weeks), `d` (days), `h` or `H` (hours), `m`
+//! (minutes) and `s` (seconds). All of the computations are done in UTC.
+
+use crate::query::QueryParserError;
+use crate::time::format_description::well_known::Rfc3339;
+use crate::time::{Date, Duration, Month, OffsetDateTime, Time};
+
+#[derive(Clone, Copy)]
+enum DateMathUnit {
+    Year,
+    Month,
+    Week,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl DateMathUnit {
+    fn from_char(unit: char) -> Option<DateMathUnit> {
+        match unit {
+            'y' => Some(DateMathUnit::Year),
+            'M' => Some(DateMathUnit::Month),
+            'w' => Some(DateMathUnit::Week),
+            'd' => Some(DateMathUnit::Day),
+            'h' | 'H' => Some(DateMathUnit::Hour),
+            'm' => Some(DateMathUnit::Minute),
+            's' => Some(DateMathUnit::Second),
+            _ => None,
+        }
+    }
+
+    fn add(self, date_time: OffsetDateTime, num: i64) -> Option<OffsetDateTime> {
+        match self {
+            DateMathUnit::Year => add_months(date_time, num.checked_mul(12)?),
+            DateMathUnit::Month => add_months(date_time, num),
+            DateMathUnit::Week => add_seconds(date_time, num, 7 * 24 * 3_600),
+            DateMathUnit::Day => add_seconds(date_time, num, 24 * 3_600),
+            DateMathUnit::Hour => add_seconds(date_time, num, 3_600),
+            DateMathUnit::Minute => add_seconds(date_time, num, 60),
+            DateMathUnit::Second => add_seconds(date_time, num, 1),
+        }
+    }
+
+    fn round_down(self, date_time: OffsetDateTime) -> Option<OffsetDateTime> {
+        let date = date_time.date();
+        let (hour, minute, second) = date_time.to_hms();
+        let (date, time) = match self {
+            DateMathUnit::Year => (
+                Date::from_calendar_date(date.year(), Month::January, 1).ok()?,
+                Time::MIDNIGHT,
+            ),
+            DateMathUnit::Month => (date.replace_day(1).ok()?, Time::MIDNIGHT),
+            DateMathUnit::Week => {
+                let days_from_monday = date.weekday().number_days_from_monday();
+                (
+                    date.checked_sub(Duration::days(days_from_monday as i64))?,
+                    Time::MIDNIGHT,
+                )
+            }
+            DateMathUnit::Day => (date, Time::MIDNIGHT),
+            DateMathUnit::Hour => (date, Time::from_hms(hour, 0, 0).ok()?),
+            DateMathUnit::Minute => (date, Time::from_hms(hour, minute, 0).ok()?),
+            DateMathUnit::Second => (date, Time::from_hms(hour, minute, second).ok()?),
+        };
+        Some(date_time.replace_date(date).replace_time(time))
+    }
+
+    /// Returns the last instant of the unit containing `date_time`.
+    fn round_up(self, date_time: OffsetDateTime) -> Option<OffsetDateTime> {
+        let next_start = self.round_down(self.add(date_time, 1)?)?;
+        next_start.checked_sub(Duration::NANOSECOND)
+    }
+}
+
+fn add_seconds(date_time: OffsetDateTime, num: i64, unit_secs: i64) -> Option<OffsetDateTime> {
+    let secs = num.checked_mul(unit_secs)?;
+    date_time.checked_add(Duration::seconds(secs))
+}
+
+fn add_months(date_time: OffsetDateTime, num_months: i64) -> Option<OffsetDateTime> {
+    let month_ordinal = date_time.year() as i64 * 12 + date_time.month() as i64 - 1;
+    let month_ordinal = month_ordinal.checked_add(num_months)?;
+    let year = i32::try_from(month_ordinal.div_euclid(12)).ok()?;
+    let month = Month::try_from(month_ordinal.rem_euclid(12) as u8 + 1).ok()?;
+    // e.g. one month after January 31th is the last day of February.
+    let num_days_in_month = (28..=31)
+        .rev()
+        .find(|&day| Date::from_calendar_date(year, month, day).is_ok())?;
+    let day = date_time.day().min(num_days_in_month);
+    let date = Date::from_calendar_date(year, month, day).ok()?;
+    Some(date_time.replace_date(date))
+}
+
+/// Returns true if `expr` is a date math expression, as opposed to a plain date.
+pub(crate) fn is_date_math(expr: &str) -> bool {
+    expr.starts_with("now") || expr.contains("||")
+}
+
+/// Evaluates the date math expression `expr`.
+///
+/// If `round_up` is true, the roundings return the last instant of the unit rather than the
+/// first one. This is what an inclusive upper bound or an exclusive lower bound expect:
+/// `[* TO now/d]` includes the whole current day.
+pub(crate) fn parse_date_math(
+    expr: &str,
+    now: OffsetDateTime,
+    round_up: bool,
+) -> Result<OffsetDateTime, QueryParserError> {
+    let invalid_expr = || QueryParserError::InvalidDateMath(expr.to_string());
+    let (mut date_time, operations) = if let Some(operations) = expr.strip_prefix("now") {
+        (now, operations)
+    } else {
+        let (anchor, operations) = expr.split_once("||").ok_or_else(invalid_expr)?;
+        (OffsetDateTime::parse(anchor, &Rfc3339)?, operations)
+    };
+    let mut chars = operations.chars().peekable();
+    while let Some(operator) = chars.next() {
+        match operator {
+            '+' | '-' => {
+                let mut num_str = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    num_str.push(digit);
+                }
+                let mut num: i64 = if num_str.is_empty() {
+                    1
+                } else {
+                    num_str.parse().map_err(|_| invalid_expr())?
+                };
+                if operator == '-' {
+                    num = -num;
+                }
+                let unit = chars
+                    .next()
+                    .and_then(DateMathUnit::from_char)
+                    .ok_or_else(invalid_expr)?;
+                date_time = unit.add(date_time, num).ok_or_else(invalid_expr)?;
+            }
+            '/' => {
+                let unit = chars
+                    .next()
+                    .and_then(DateMathUnit::from_char)
+                    .ok_or_else(invalid_expr)?;
+                let rounded_date_time = if round_up {
+                    unit.round_up(date_time)
+                } else {
+                    unit.round_down(date_time)
+                };
+                date_time = rounded_date_time.ok_or_else(invalid_expr)?;
+            }
+            _ => return Err(invalid_expr()),
+        }
+    }
+    Ok(date_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_date_math, parse_date_math};
+    use crate::query::QueryParserError;
+    use crate::time::format_description::well_known::Rfc3339;
+    use crate::time::OffsetDateTime;
+
+    fn eval(expr: &str, round_up: bool) -> Result<String, QueryParserError> {
+        // A wednesday.
+        let now = OffsetDateTime::parse("2024-01-31T15:42:17.5Z", &Rfc3339).unwrap();
+        let date_time = parse_date_math(expr, now, round_up)?;
+        Ok(date_time.format(&Rfc3339).unwrap())
+    }
+
+    #[test]
+    fn test_date_math() {
+        assert_eq!(eval("now", false).unwrap(), "2024-01-31T15:42:17.5Z");
+        assert_eq!(eval("now-7d", false).unwrap(), "2024-01-24T15:42:17.5Z");
+        assert_eq!(eval("now+1M", false).unwrap(), "2024-02-29T15:42:17.5Z");
+        assert_eq!(eval("now-1y+2h", false).unwrap(), "2023-01-31T17:42:17.5Z");
+        assert_eq!(eval("now-m", false).unwrap(), "2024-01-31T15:41:17.5Z");
+        assert_eq!(eval("now/d", false).unwrap(), "2024-01-31T00:00:00Z");
+        assert_eq!(
+            eval("now/d", true).unwrap(),
+            "2024-01-31T23:59:59.999999999Z"
+        );
+        assert_eq!(eval("now/w", false).unwrap(), "2024-01-29T00:00:00Z");
+        assert_eq!(
+            eval("now/M", true).unwrap(),
+            "2024-01-31T23:59:59.999999999Z"
+        );
+        assert_eq!(eval("now/y", false).unwrap(), "2024-01-01T00:00:00Z");
+        assert_eq!(eval("now/H", false).unwrap(), "2024-01-31T15:00:00Z");
+        assert_eq!(eval("now/s", false).unwrap(), "2024-01-31T15:42:17Z");
+        assert_eq!(eval("now-1d/d", false).unwrap(), "2024-01-30T00:00:00Z");
+        assert_eq!(
+            eval("2020-02-29T10:00:00Z||+1y/M", false).unwrap(),
+            "2021-02-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_date_math_errors() {
+        assert!(matches!(
+            eval("now-7x", false),
+            Err(QueryParserError::InvalidDateMath(_))
+        ));
+        assert!(matches!(
+            eval("now*2d", false),
+            Err(QueryParserError::InvalidDateMath(_))
+        ));
+        assert!(matches!(
+            eval("now+999999999999999999w", false),
+            Err(QueryParserError::InvalidDateMath(_))
+        ));
+        assert!(matches!(
+            eval("now/", false),
+            Err(QueryParserError::InvalidDateMath(_))
+        ));
+        assert!(matches!(
+            eval("2020-02-29||+1d", false),
+            Err(QueryParserError::DateFormatError(_))
+        ));
+        assert!(is_date_math("now-1d"));
+        assert!(is_date_math("2020-02-29T10:00:00Z||/d"));
+        assert!(!is_date_math("2020-02-29T10:00:00Z"));
+    }
+}
@@ -2,12 +2,18 @@ use std::fmt;
 use std::ops::Bound;
 
 use crate::query::Occur;
-use crate::schema::Term;
+use crate::schema::{Field, Term};
 use crate::Score;
 
 #[derive(Clone)]
 pub enum LogicalLiteral {
     Term(Term),
+    FuzzyTerm {
+        term: Term,
+        distance: u8,
+        transposition_cost_one: bool,
+        prefix_length: usize,
+    },
     Phrase {
         terms: Vec<(usize, Term)>,
         slop: u32,
@@ -20,6 +26,16 @@ pub enum LogicalLiteral {
     Set {
         elements: Vec<Term>,
     },
+    /// Matches all of the terms starting with the given term.
+    TermPrefix(Term),
+    /// Matches the documents with a value in the given fast field, or json path.
+    Exists {
+        full_path: String,
+        json_subpaths: bool,
+    },
+    /// Matches the documents with at least one token in the given field, according to its
+    /// fieldnorms.
+    FieldNormExists(Field),
     All,
 }
 
@@ -107,6 +123,9 @@ impl fmt::Debug for LogicalLiteral {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match *self {
             LogicalLiteral::Term(ref term) => write!(formatter, "{term:?}"),
+            LogicalLiteral::FuzzyTerm {
+                ref term, distance, ..
+            } => write!(formatter, "{term:?}~{distance}"),
             LogicalLiteral::Phrase {
                 ref terms,
                 slop,
@@ -146,6 +165,11 @@ impl fmt::Debug for LogicalLiteral {
                 }
                 write!(formatter, "]")
             }
+            LogicalLiteral::TermPrefix(ref term) => write!(formatter, "{term:?}*"),
+            LogicalLiteral::Exists { ref full_path, .. } => {
+                write!(formatter, "$exists({full_path:?})")
+            }
+            LogicalLiteral::FieldNormExists(field) => write!(formatter, "$exists({field:?})"),
             LogicalLiteral::All => write!(formatter, "*"),
         }
     }
@@ -1,3 +1,5 @@
+mod date_math;
+mod query_dsl;
 mod query_parser;
 
 pub mod logical_ast;
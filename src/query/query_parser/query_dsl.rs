@@ -0,0 +1,688 @@
+//! Conversion of a JSON query DSL, similar to the one of Elasticsearch, into queries.
+//!
+//! See [`QueryParser::parse_json_query`].
+
+use query_grammar::{
+    Delimiter, Fuzziness, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral,
+};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value as JsonValue;
+
+use super::{QueryParser, QueryParserError};
+use crate::query::{
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, DisjunctionMaxQuery, EmptyQuery, Occur,
+    Query, RegexQuery,
+};
+use crate::Score;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum QueryDsl {
+    MatchAll(BoostParams),
+    MatchNone(BoostParams),
+    Term(FieldParams<TermParams>),
+    Terms(FieldParams<Vec<JsonValue>>),
+    Match(FieldParams<MatchParams>),
+    MatchPhrase(FieldParams<MatchPhraseParams>),
+    MatchPhrasePrefix(FieldParams<MatchPhraseParams>),
+    Range(FieldParams<RangeParams>),
+    Regexp(FieldParams<RegexpParams>),
+    Exists(ExistsParams),
+    QueryString(QueryStringParams),
+    Bool(BoolParams),
+    DisMax(DisMaxParams),
+    ConstantScore(ConstantScoreParams),
+}
+
+/// Parameters of a query targeting a single field: `{"<field>": <params>}`.
+type FieldParams<P> = std::collections::BTreeMap<String, P>;
+
+/// Parameters that can be written in a short form, with their main value only,
+/// e.g. `{"title": "hello"}` for `{"title": {"query": "hello"}}`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ShortForm<P> {
+    Params(P),
+    Value(JsonValue),
+}
+
+impl<P: From<JsonValue>> ShortForm<P> {
+    fn into_params(self) -> P {
+        match self {
+            ShortForm::Params(params) => params,
+            ShortForm::Value(value) => P::from(value),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoostParams {
+    boost: Option<Score>,
+}
+
+type TermParams = ShortForm<FullTermParams>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FullTermParams {
+    value: JsonValue,
+    boost: Option<Score>,
+}
+
+impl From<JsonValue> for FullTermParams {
+    fn from(value: JsonValue) -> Self {
+        FullTermParams { value, boost: None }
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Operator {
+    #[default]
+    Or,
+    And,
+}
+
+type MatchParams = ShortForm<FullMatchParams>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FullMatchParams {
+    query: JsonValue,
+    #[serde(default)]
+    operator: Operator,
+    fuzziness: Option<JsonValue>,
+    boost: Option<Score>,
+}
+
+impl From<JsonValue> for FullMatchParams {
+    fn from(query: JsonValue) -> Self {
+        FullMatchParams {
+            query,
+            operator: Operator::default(),
+            fuzziness: None,
+            boost: None,
+        }
+    }
+}
+
+type MatchPhraseParams = ShortForm<FullMatchPhraseParams>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FullMatchPhraseParams {
+    query: JsonValue,
+    #[serde(default)]
+    slop: u32,
+    boost: Option<Score>,
+}
+
+impl From<JsonValue> for FullMatchPhraseParams {
+    fn from(query: JsonValue) -> Self {
+        FullMatchPhraseParams {
+            query,
+            slop: 0,
+            boost: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RangeParams {
+    gt: Option<JsonValue>,
+    gte: Option<JsonValue>,
+    lt: Option<JsonValue>,
+    lte: Option<JsonValue>,
+    boost: Option<Score>,
+}
+
+type RegexpParams = ShortForm<FullRegexpParams>;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FullRegexpParams {
+    value: JsonValue,
+    boost: Option<Score>,
+}
+
+impl From<JsonValue> for FullRegexpParams {
+    fn from(value: JsonValue) -> Self {
+        FullRegexpParams { value, boost: None }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExistsParams {
+    field: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryStringParams {
+    query: String,
+    boost: Option<Score>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoolParams {
+    #[serde(default, deserialize_with = "one_or_many")]
+    must: Vec<QueryDsl>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    should: Vec<QueryDsl>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    must_not: Vec<QueryDsl>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    filter: Vec<QueryDsl>,
+    minimum_should_match: Option<usize>,
+    boost: Option<Score>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DisMaxParams {
+    queries: Vec<QueryDsl>,
+    #[serde(default)]
+    tie_breaker: Score,
+    boost: Option<Score>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstantScoreParams {
+    filter: Box<QueryDsl>,
+    boost: Option<Score>,
+}
+
+/// Deserializes either a single query or an array of queries.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<QueryDsl>, D::Error> {
+    let json_value = JsonValue::deserialize(deserializer)?;
+    let json_values = match json_value {
+        JsonValue::Array(json_values) => json_values,
+        json_value => vec![json_value],
+    };
+    json_values
+        .into_iter()
+        .map(|json_value| serde_json::from_value(json_value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn into_single_field<P>(
+    query_type: &str,
+    field_params: FieldParams<P>,
+) -> Result<(String, P), QueryParserError> {
+    if field_params.len() != 1 {
+        return Err(QueryParserError::SyntaxError(format!(
+            "A `{query_type}` query must target exactly one field, got {}.",
+            field_params.len()
+        )));
+    }
+    Ok(field_params.into_iter().next().unwrap())
+}
+
+/// Returns the text representation of a scalar JSON value, as expected by the query parser.
+fn scalar_to_string(json_value: JsonValue) -> Result<String, QueryParserError> {
+    match json_value {
+        JsonValue::String(text) => Ok(text),
+        JsonValue::Number(number) => Ok(number.to_string()),
+        JsonValue::Bool(val) => Ok(val.to_string()),
+        json_value => Err(QueryParserError::SyntaxError(format!(
+            "Expected a string, a number or a boolean, got {json_value}."
+        ))),
+    }
+}
+
+fn parse_fuzziness(json_value: JsonValue) -> Result<Fuzziness, QueryParserError> {
+    match &json_value {
+        JsonValue::String(text) if text.eq_ignore_ascii_case("auto") => {
+            return Ok(Fuzziness::Auto);
+        }
+        JsonValue::Number(number) => {
+            if let Some(distance) = number
+                .as_u64()
+                .and_then(|distance| u8::try_from(distance).ok())
+            {
+                return Ok(Fuzziness::Distance(distance));
+            }
+        }
+        _ => {}
+    }
+    Err(QueryParserError::SyntaxError(format!(
+        "Expected `AUTO` or an edit distance as fuzziness, got {json_value}."
+    )))
+}
+
+fn literal(field: String, phrase: String, delimiter: Delimiter) -> UserInputLiteral {
+    UserInputLiteral {
+        field_name: Some(field),
+        phrase,
+        delimiter,
+        slop: 0,
+        prefix: false,
+        fuzziness: None,
+    }
+}
+
+fn leaf_ast(leaf: UserInputLeaf) -> UserInputAst {
+    UserInputAst::Leaf(Box::new(leaf))
+}
+
+fn to_bound(
+    inclusive: Option<JsonValue>,
+    exclusive: Option<JsonValue>,
+) -> Result<UserInputBound, QueryParserError> {
+    match (inclusive, exclusive) {
+        (Some(_), Some(_)) => Err(QueryParserError::SyntaxError(
+            "A range bound cannot be both inclusive and exclusive.".to_string(),
+        )),
+        (Some(value), None) => Ok(UserInputBound::Inclusive(scalar_to_string(value)?)),
+        (None, Some(value)) => Ok(UserInputBound::Exclusive(scalar_to_string(value)?)),
+        (None, None) => Ok(UserInputBound::Unbounded),
+    }
+}
+
+fn with_boost(query: Box<dyn Query>, boost: Option<Score>) -> Box<dyn Query> {
+    match boost {
+        Some(boost) => Box::new(BoostQuery::new(query, boost)),
+        None => query,
+    }
+}
+
+impl QueryParser {
+    /// Parses a query expressed in a JSON DSL similar to the one of Elasticsearch.
+    ///
+    /// The following queries are supported:
+    /// - `{"match_all": {}}` and `{"match_none": {}}`
+    /// - `{"term": {"<field>": <value>}}`, the value is processed by the tokenizer of the field,
+    ///   like in [`QueryParser::parse_query`].
+    /// - `{"terms": {"<field>": [<value>, ...]}}`
+    /// - `{"match": {"<field>": {"query": <text>, "operator": "or", "fuzziness": "AUTO"}}}`
+    /// - `{"match_phrase": {"<field>": {"query": <text>, "slop": 0}}}` and `match_phrase_prefix`
+    /// - `{"range": {"<field>": {"gte": <value>, "lt": <value>}}}`
+    /// - `{"regexp": {"<field>": <pattern>}}`
+    /// - `{"exists": {"field": "<field>"}}`
+    /// - `{"query_string": {"query": <query>}}`, parsed with [`QueryParser::parse_query`].
+    /// - `{"bool": {"must": [...], "should": [...], "must_not": [...], "filter": [...],
+    ///   "minimum_should_match": 1}}`
+    /// - `{"dis_max": {"queries": [...], "tie_breaker": 0.0}}`
+    /// - `{"constant_score": {"filter": <query>}}`
+    ///
+    /// Most of them accept a `boost` parameter, and the field level parameters can be replaced
+    /// by their main value, e.g. `{"match": {"title": "hello"}}`.
+    ///
+    /// ```rust
+    /// use tantivy::collector::Count;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, FAST, INDEXED, TEXT};
+    /// use tantivy::{doc, Index, IndexWriter};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let year = schema_builder.add_u64_field("year", INDEXED | FAST);
+    /// let schema = schema_builder.build();
+    /// let index = Index::create_in_ram(schema);
+    /// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Old Man and the Sea", year => 1952u64))?;
+    /// index_writer.add_document(doc!(title => "The Sun Also Rises", year => 1926u64))?;
+    /// index_writer.commit()?;
+    ///
+    /// let query_parser = QueryParser::for_index(&index, vec![title]);
+    /// let query = query_parser
+    ///     .parse_json_query(
+    ///         r#"{
+    ///             "bool": {
+    ///                 "must": {"match": {"title": "the sea"}},
+    ///                 "filter": {"range": {"year": {"gte": 1950}}}
+    ///             }
+    ///         }"#,
+    ///     )
+    ///     .unwrap();
+    /// let searcher = index.reader()?.searcher();
+    /// assert_eq!(searcher.search(&query, &Count)?, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_json_query(&self, json_query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+        let query_dsl: QueryDsl = serde_json::from_str(json_query)
+            .map_err(|err| QueryParserError::SyntaxError(err.to_string()))?;
+        self.convert_query_dsl(query_dsl)
+    }
+
+    fn convert_query_dsl(&self, query_dsl: QueryDsl) -> Result<Box<dyn Query>, QueryParserError> {
+        let (query, boost): (Box<dyn Query>, Option<Score>) = match query_dsl {
+            QueryDsl::MatchAll(params) => (Box::new(AllQuery), params.boost),
+            QueryDsl::MatchNone(params) => (Box::new(EmptyQuery), params.boost),
+            QueryDsl::Term(field_params) => {
+                let (field, params) = into_single_field("term", field_params)?;
+                let params = params.into_params();
+                let phrase = scalar_to_string(params.value)?;
+                let ast = leaf_ast(UserInputLeaf::Literal(literal(
+                    field,
+                    phrase,
+                    Delimiter::DoubleQuotes,
+                )));
+                (self.build_query_from_user_input_ast(ast)?, params.boost)
+            }
+            QueryDsl::Terms(field_params) => {
+                let (field, values) = into_single_field("terms", field_params)?;
+                let elements = values
+                    .into_iter()
+                    .map(scalar_to_string)
+                    .collect::<Result<Vec<String>, _>>()?;
+                let ast = leaf_ast(UserInputLeaf::Set {
+                    field: Some(field),
+                    elements,
+                });
+                (self.build_query_from_user_input_ast(ast)?, None)
+            }
+            QueryDsl::Match(field_params) => {
+                let (field, params) = into_single_field("match", field_params)?;
+                let params = params.into_params();
+                let fuzziness = params.fuzziness.map(parse_fuzziness).transpose()?;
+                let occur = match params.operator {
+                    Operator::Or => Occur::Should,
+                    Operator::And => Occur::Must,
+                };
+                let text = scalar_to_string(params.query)?;
+                let clauses: Vec<(Option<Occur>, UserInputAst)> = text
+                    .split_whitespace()
+                    .map(|word| {
+                        let mut literal = literal(field.clone(), word.to_string(), Delimiter::None);
+                        literal.fuzziness = fuzziness;
+                        (Some(occur), leaf_ast(UserInputLeaf::Literal(literal)))
+                    })
+                    .collect();
+                if clauses.is_empty() {
+                    (Box::new(EmptyQuery), None)
+                } else {
+                    let ast = UserInputAst::Clause(clauses);
+                    (self.build_query_from_user_input_ast(ast)?, params.boost)
+                }
+            }
+            QueryDsl::MatchPhrase(field_params) => {
+                self.convert_match_phrase("match_phrase", field_params, false)?
+            }
+            QueryDsl::MatchPhrasePrefix(field_params) => {
+                self.convert_match_phrase("match_phrase_prefix", field_params, true)?
+            }
+            QueryDsl::Range(field_params) => {
+                let (field, params) = into_single_field("range", field_params)?;
+                let lower = to_bound(params.gte, params.gt)?;
+                let upper = to_bound(params.lte, params.lt)?;
+                if lower == UserInputBound::Unbounded && upper == UserInputBound::Unbounded {
+                    return Err(QueryParserError::SyntaxError(format!(
+                        "The `range` query on `{field}` has no bound."
+                    )));
+                }
+                let ast = leaf_ast(UserInputLeaf::Range {
+                    field: Some(field),
+                    lower,
+                    upper,
+                });
+                (self.build_query_from_user_input_ast(ast)?, params.boost)
+            }
+            QueryDsl::Regexp(field_params) => {
+                let (full_path, params) = into_single_field("regexp", field_params)?;
+                let params = params.into_params();
+                let full_path = self.resolve_field_alias(&full_path);
+                let (field, json_path) = self
+                    .split_full_path(&full_path)
+                    .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.to_string()))?;
+                if !json_path.is_empty() {
+                    return Err(QueryParserError::UnsupportedQuery(format!(
+                        "Regexp queries are not supported on json paths: {full_path:?}"
+                    )));
+                }
+                let pattern = scalar_to_string(params.value)?;
+                let regex_query = RegexQuery::from_pattern(&pattern, field)
+                    .map_err(|err| QueryParserError::SyntaxError(err.to_string()))?;
+                (Box::new(regex_query), params.boost)
+            }
+            QueryDsl::Exists(params) => {
+                let ast = UserInputAst::from(UserInputLeaf::Exists {
+                    field: params.field,
+                });
+                (self.build_query_from_user_input_ast(ast)?, None)
+            }
+            QueryDsl::QueryString(params) => (self.parse_query(&params.query)?, params.boost),
+            QueryDsl::Bool(params) => {
+                let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+                for (occur, queries) in [
+                    (Occur::Must, params.must),
+                    (Occur::Should, params.should),
+                    (Occur::MustNot, params.must_not),
+                ] {
+                    for query_dsl in queries {
+                        clauses.push((occur, self.convert_query_dsl(query_dsl)?));
+                    }
+                }
+                for query_dsl in params.filter {
+                    // Filters do not contribute to the score.
+                    let filter = self.convert_query_dsl(query_dsl)?;
+                    clauses.push((Occur::Must, Box::new(ConstScoreQuery::new(filter, 0.0))));
+                }
+                if clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+                    clauses.push((Occur::Must, Box::new(AllQuery)));
+                }
+                let mut bool_query = BooleanQuery::new(clauses);
+                if let Some(minimum_should_match) = params.minimum_should_match {
+                    bool_query.set_minimum_number_should_match(minimum_should_match);
+                }
+                (Box::new(bool_query), params.boost)
+            }
+            QueryDsl::DisMax(params) => {
+                let disjuncts = params
+                    .queries
+                    .into_iter()
+                    .map(|query_dsl| self.convert_query_dsl(query_dsl))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let dis_max_query =
+                    DisjunctionMaxQuery::with_tie_breaker(disjuncts, params.tie_breaker);
+                (Box::new(dis_max_query), params.boost)
+            }
+            QueryDsl::ConstantScore(params) => {
+                let filter = self.convert_query_dsl(*params.filter)?;
+                let score = params.boost.unwrap_or(1.0);
+                (Box::new(ConstScoreQuery::new(filter, score)), None)
+            }
+        };
+        Ok(with_boost(query, boost))
+    }
+
+    fn convert_match_phrase(
+        &self,
+        query_type: &str,
+        field_params: FieldParams<MatchPhraseParams>,
+        prefix: bool,
+    ) -> Result<(Box<dyn Query>, Option<Score>), QueryParserError> {
+        let (field, params) = into_single_field(query_type, field_params)?;
+        let params = params.into_params();
+        let mut literal = literal(
+            field,
+            scalar_to_string(params.query)?,
+            Delimiter::DoubleQuotes,
+        );
+        literal.slop = params.slop;
+        literal.prefix = prefix;
+        let ast = leaf_ast(UserInputLeaf::Literal(literal));
+        Ok((self.build_query_from_user_input_ast(ast)?, params.boost))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{QueryParser, QueryParserError};
+    use crate::schema::{Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Searcher};
+
+    fn make_index() -> crate::Result<(Index, QueryParser)> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let genre = schema_builder.add_text_field("genre", STRING | FAST);
+        let year = schema_builder.add_u64_field("year", INDEXED | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "The Old Man and the Sea",
+            genre => "novel",
+            year => 1952u64
+        ))?;
+        index_writer.add_document(doc!(
+            title => "The Sun Also Rises",
+            genre => "novel",
+            year => 1926u64
+        ))?;
+        index_writer.add_document(doc!(title => "A Moveable Feast", genre => "memoir"))?;
+        index_writer.add_document(doc!(title => "The Sea Around Us", year => 1951u64))?;
+        index_writer.commit()?;
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        Ok((index, query_parser))
+    }
+
+    fn count(searcher: &Searcher, query_parser: &QueryParser, json_query: &str) -> usize {
+        let query = query_parser.parse_json_query(json_query).unwrap();
+        searcher.search(&query, &Count).unwrap()
+    }
+
+    #[test]
+    fn test_json_query_leaves() -> crate::Result<()> {
+        let (index, mut query_parser) = make_index()?;
+        query_parser.set_field_alias("published", "year");
+        let searcher = index.reader()?.searcher();
+        let count = |json_query: &str| count(&searcher, &query_parser, json_query);
+        assert_eq!(count(r#"{"match_all": {}}"#), 4);
+        assert_eq!(count(r#"{"match_none": {}}"#), 0);
+        assert_eq!(count(r#"{"term": {"genre": "novel"}}"#), 2);
+        assert_eq!(
+            count(r#"{"term": {"year": {"value": 1926, "boost": 2.0}}}"#),
+            1
+        );
+        assert_eq!(count(r#"{"terms": {"genre": ["memoir", "essay"]}}"#), 1);
+        assert_eq!(count(r#"{"match": {"title": "old sea"}}"#), 2);
+        assert_eq!(
+            count(r#"{"match": {"title": {"query": "old sea", "operator": "and"}}}"#),
+            1
+        );
+        assert_eq!(
+            count(r#"{"match": {"title": {"query": "feest", "fuzziness": 1}}}"#),
+            1
+        );
+        assert_eq!(count(r#"{"match": {"title": ""}}"#), 0);
+        assert_eq!(count(r#"{"match_phrase": {"title": "the sea"}}"#), 2);
+        assert_eq!(
+            count(r#"{"match_phrase": {"title": {"query": "old the", "slop": 2}}}"#),
+            1
+        );
+        assert_eq!(count(r#"{"match_phrase_prefix": {"title": "the su"}}"#), 1);
+        assert_eq!(
+            count(r#"{"range": {"year": {"gte": 1951, "lt": 1952}}}"#),
+            1
+        );
+        assert_eq!(count(r#"{"range": {"year": {"gt": 1926}}}"#), 2);
+        assert_eq!(count(r#"{"regexp": {"genre": "mem.*"}}"#), 1);
+        assert_eq!(count(r#"{"exists": {"field": "year"}}"#), 3);
+        assert_eq!(count(r#"{"exists": {"field": "published"}}"#), 3);
+        assert_eq!(count(r#"{"exists": {"field": "title"}}"#), 4);
+        assert_eq!(count(r#"{"range": {"published": {"gt": 1926}}}"#), 2);
+        assert_eq!(count(r#"{"query_string": {"query": "sea -old"}}"#), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_query_compound() -> crate::Result<()> {
+        let (index, query_parser) = make_index()?;
+        let searcher = index.reader()?.searcher();
+        let count = |json_query: &str| count(&searcher, &query_parser, json_query);
+        assert_eq!(
+            count(
+                r#"{"bool": {
+                    "must": {"match": {"title": "sea"}},
+                    "filter": [{"range": {"year": {"gte": 1952}}}]
+                }}"#
+            ),
+            1
+        );
+        assert_eq!(
+            count(r#"{"bool": {"must_not": {"term": {"genre": "novel"}}}}"#),
+            2
+        );
+        assert_eq!(count(r#"{"bool": {}}"#), 4);
+        assert_eq!(
+            count(
+                r#"{"bool": {
+                    "should": [
+                        {"match": {"title": "sea"}},
+                        {"term": {"genre": "novel"}},
+                        {"match": {"title": "sun"}}
+                    ],
+                    "minimum_should_match": 2
+                }}"#
+            ),
+            2
+        );
+        assert_eq!(
+            count(
+                r#"{"dis_max": {"queries": [
+                    {"match": {"title": "sun"}}, {"match": {"title": "feast"}}
+                ]}}"#
+            ),
+            2
+        );
+
+        // Filters do not contribute to the score.
+        let query = query_parser.parse_json_query(
+            r#"{"bool": {
+                "must": {"constant_score": {"filter": {"match": {"title": "sea"}}, "boost": 2.0}},
+                "filter": {"term": {"genre": "novel"}}
+            }}"#,
+        )?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(2))?;
+        assert_eq!(top_docs.len(), 1);
+        assert_nearly_equals!(top_docs[0].0, 2.0);
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 0));
+
+        let query = query_parser.parse_json_query(r#"{"match_all": {"boost": 3.0}}"#)?;
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_nearly_equals!(top_docs[0].0, 3.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_query_errors() -> crate::Result<()> {
+        let (_index, query_parser) = make_index()?;
+        let is_syntax_error = |json_query: &str| {
+            matches!(
+                query_parser.parse_json_query(json_query),
+                Err(QueryParserError::SyntaxError(_))
+            )
+        };
+        assert!(is_syntax_error(r#"{"match_all": {}"#));
+        assert!(is_syntax_error(r#"{"unknown": {}}"#));
+        assert!(is_syntax_error(
+            r#"{"term": {"genre": "novel", "title": "sea"}}"#
+        ));
+        assert!(is_syntax_error(r#"{"term": {"genre": ["novel"]}}"#));
+        assert!(is_syntax_error(
+            r#"{"range": {"year": {"gt": 1, "gte": 2}}}"#
+        ));
+        assert!(is_syntax_error(r#"{"range": {"year": {}}}"#));
+        assert!(is_syntax_error(
+            r#"{"bool": {"must": [{"match": {"title": "sea"}}, {"unknown": {}}]}}"#
+        ));
+        assert!(matches!(
+            query_parser.parse_json_query(r#"{"term": {"author": "hemingway"}}"#),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        ));
+        assert!(matches!(
+            query_parser.parse_json_query(r#"{"exists": {"field": "author"}}"#),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,30 +1,38 @@
-use std::net::{AddrParseError, IpAddr};
+use std::borrow::Cow;
+use std::net::{AddrParseError, IpAddr, Ipv6Addr};
 use std::num::{ParseFloatError, ParseIntError};
-use std::ops::Bound;
+use std::ops::{Bound, RangeInclusive};
 use std::str::{FromStr, ParseBoolError};
+use std::sync::Arc;
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use itertools::Itertools;
-use query_grammar::{UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral};
+use query_grammar::{
+    Delimiter, Fuzziness, SimpleQueryFlags, UserInputAst, UserInputBound, UserInputLeaf,
+    UserInputLiteral,
+};
 use rustc_hash::FxHashMap;
 
+use super::date_math::{is_date_math, parse_date_math};
 use super::logical_ast::*;
 use crate::index::Index;
 use crate::json_utils::convert_to_fast_value_and_append_to_json_term;
-use crate::query::range_query::{is_type_valid_for_fastfield_range_query, RangeQuery};
+use crate::query::exist_query::FieldNormExistsQuery;
+use crate::query::phrase_prefix_query::prefix_end;
+use crate::query::range_query::{field_supports_fastfield_range_query, RangeQuery};
 use crate::query::{
-    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
-    PhraseQuery, Query, TermQuery, TermSetQuery,
+    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, ExistsQuery, FuzzyTermQuery,
+    InvertedIndexRangeQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query, TermQuery, TermSetQuery,
 };
 use crate::schema::{
     Facet, FacetParseError, Field, FieldType, IndexRecordOption, IntoIpv6Addr, JsonObjectOptions,
-    Schema, Term, TextFieldIndexing, Type,
+    Schema, Term, TextFieldIndexing, Type, REVERSED_TOKEN_MARKER,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
 use crate::tokenizer::{TextAnalyzer, TokenizerManager};
-use crate::{DateTime, Score};
+use crate::{telemetry, DateTime, Score};
 
 /// Possible error that may happen when parsing a query.
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -96,12 +104,18 @@ pub enum QueryParserError {
     /// The format for the date field is not RFC 3339 compliant.
     #[error("The date field has an invalid format")]
     DateFormatError(#[from] time::error::Parse),
+    /// The date math expression used as a range bound is invalid, e.g. `now-1x`.
+    #[error("Invalid date math expression: {0:?}")]
+    InvalidDateMath(String),
     /// The format for the facet field is invalid.
     #[error("The facet field is malformed: {0}")]
     FacetFormatError(#[from] FacetParseError),
     /// The format for the ip field is invalid.
     #[error("The ip field is malformed: {0}")]
     IpFormatError(#[from] AddrParseError),
+    /// The CIDR block used on an ip field is invalid, e.g. `10.0.0.0/33`.
+    #[error("The CIDR block is malformed: {0:?}")]
+    CidrFormatError(String),
 }
 
 /// Recursively remove empty clause from the AST
@@ -179,8 +193,16 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///   `"2002-10-02T15:00:00.05Z"` or `some_date_field:[2002-10-02T15:00:00Z TO
 ///   2002-10-02T18:00:00Z}`
 ///
+/// * ip values: Ip fields accept IPv4 and IPv6 addresses, ranges of addresses, e.g. `ip:[10.0.0.1
+///   TO 10.0.0.255]`, and CIDR blocks, e.g. `ip:10.0.0.0/8` or `ip:"2001:db8::/32"`. A CIDR block
+///   is executed as a range query.
+///
 /// * all docs query: A plain `*` will match all documents in the index.
 ///
+/// * exists queries: `_exists_:title`, or equivalently `title:*`, matches the documents with a
+///   value in `title`, e.g. `NOT _exists_:title` matches the documents without a title. Fast fields
+///   are checked using their columns, the other indexed fields using their fieldnorms.
+///
 /// Parts of the queries can be boosted by appending `^boostfactor`.
 /// For instance, `"SRE"^2.0 OR devops^0.4` will boost documents containing `SRE` instead of
 /// devops. Negative boosts are not allowed.
@@ -189,6 +211,14 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 /// (See [`set_field_boost(...)`](QueryParser::set_field_boost)). Typically you may want to boost a
 /// title field.
 ///
+/// * fuzzy terms: `title:diary~1` matches the terms within a Levenshtein distance of 1 of `diary`,
+///   e.g. `dairy`. Distances up to 2 are supported. Without a distance (`diary~`), it is chosen
+///   depending on the length of the term: terms of 1-2 chars must match exactly, terms of 3-5 chars
+///   allow 1 edit, longer terms allow 2 edits. (See
+///   [`set_fuzzy_prefix_length(...)`](QueryParser::set_fuzzy_prefix_length) and
+///   [`set_fuzzy_transposition_cost_one(...)`](QueryParser::set_fuzzy_transposition_cost_one) to
+///   tune the resulting [`FuzzyTermQuery`].)
+///
 /// Additionally, specific fields can be marked to use fuzzy term queries for each literal
 /// via the [`QueryParser::set_field_fuzzy`] method.
 ///
@@ -197,7 +227,13 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///
 /// Phrase terms also support the `*` prefix operator which switches the phrase's matching
 /// to consider all documents which contain the last term as a prefix, e.g. `"big bad wo"*` will
-/// match `"big bad wolf"`.
+/// match `"big bad wolf"`. The number of terms the prefix expands to can be bounded with
+/// [`QueryParser::set_phrase_prefix_max_expansions`].
+///
+/// The bounds of range queries on date fields can be date math expressions, relative to the
+/// current time, e.g. `timestamp:[now-7d/d TO now]` (see [`QueryParser::set_clock`]).
+///
+/// Field names used in queries can be aliases, registered with [`QueryParser::set_field_alias`].
 #[derive(Clone)]
 pub struct QueryParser {
     schema: Schema,
@@ -206,8 +242,18 @@ pub struct QueryParser {
     tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    fuzzy_prefix_length: usize,
+    fuzzy_transposition_cost_one: bool,
+    phrase_prefix_max_expansions: Option<u32>,
+    field_aliases: FxHashMap<String, String>,
+    query_tokenizers: FxHashMap<Field, String>,
+    conjunction_by_field: FxHashMap<Field, bool>,
+    clock: Option<Arc<dyn Fn() -> DateTime + Send + Sync>>,
 }
 
+/// Maximum distance supported by [`FuzzyTermQuery`].
+const MAX_FUZZY_DISTANCE: u8 = 2;
+
 #[derive(Clone)]
 struct Fuzzy {
     prefix: bool,
@@ -260,6 +306,13 @@ impl QueryParser {
             conjunction_by_default: false,
             boost: Default::default(),
             fuzzy: Default::default(),
+            fuzzy_prefix_length: 0,
+            fuzzy_transposition_cost_one: true,
+            phrase_prefix_max_expansions: None,
+            field_aliases: Default::default(),
+            query_tokenizers: Default::default(),
+            conjunction_by_field: Default::default(),
+            clock: None,
         }
     }
 
@@ -269,6 +322,20 @@ impl QueryParser {
         self.schema.find_field(full_path)
     }
 
+    // Replaces the alias the full_path starts with, if any, by the path it stands for.
+    pub(crate) fn resolve_field_alias<'a>(&self, full_path: &'a str) -> Cow<'a, str> {
+        if let Some(target) = self.field_aliases.get(full_path) {
+            return Cow::Owned(target.clone());
+        }
+        // The alias may also be followed by a json path, e.g. `alias.subpath`.
+        for (dot_pos, _) in full_path.rmatch_indices('.') {
+            if let Some(target) = self.field_aliases.get(&full_path[..dot_pos]) {
+                return Cow::Owned(format!("{target}{}", &full_path[dot_pos..]));
+            }
+        }
+        Cow::Borrowed(full_path)
+    }
+
     /// Creates a `QueryParser`, given
     ///  * an index
     ///  * a set of default fields used to search if no field is specifically defined in the query.
@@ -285,6 +352,28 @@ impl QueryParser {
         self.conjunction_by_default = true;
     }
 
+    /// Sets the default way to compose the terms targeting a specific field.
+    ///
+    /// This overrides [`QueryParser::set_conjunction_by_default`] for the clauses without an
+    /// explicit operator targeting `field`: after
+    /// `set_field_conjunction_by_default(sku, true)`, the query `sku:(a b) body:(c d)` is
+    /// interpreted as `(+sku:a +sku:b) (body:c body:d)`.
+    pub fn set_field_conjunction_by_default(&mut self, field: Field, conjunction_by_default: bool) {
+        self.conjunction_by_field
+            .insert(field, conjunction_by_default);
+    }
+
+    /// Sets the tokenizer used to process the query text targeting a specific field, instead of
+    /// the tokenizer used at indexing time.
+    ///
+    /// The tokenizer must be registered in the tokenizer manager of the query parser, and should
+    /// produce tokens compatible with the indexed ones, e.g. the query tokenizer of a field
+    /// indexed with a stemmer could expand synonyms before stemming them.
+    pub fn set_field_query_tokenizer(&mut self, field: Field, tokenizer_name: &str) {
+        self.query_tokenizers
+            .insert(field, tokenizer_name.to_string());
+    }
+
     /// Sets a boost for a specific field.
     ///
     /// The parse query will automatically boost this field.
@@ -320,13 +409,71 @@ impl QueryParser {
         );
     }
 
+    /// Sets the number of leading chars that must match exactly for the fuzzy terms of the
+    /// query (e.g. `diary~1`).
+    ///
+    /// Defaults to 0. See [`FuzzyTermQuery::set_prefix_length`].
+    pub fn set_fuzzy_prefix_length(&mut self, prefix_length: usize) {
+        self.fuzzy_prefix_length = prefix_length;
+    }
+
+    /// Sets whether a transposition costs 1 or 2 edits for the fuzzy terms of the query
+    /// (e.g. `diary~1`).
+    ///
+    /// Defaults to `true`, which makes `dairy~1` match `diary`.
+    pub fn set_fuzzy_transposition_cost_one(&mut self, transposition_cost_one: bool) {
+        self.fuzzy_transposition_cost_one = transposition_cost_one;
+    }
+
+    /// Sets the maximum number of terms to which the last term of the phrase prefix queries
+    /// (e.g. `"big bad wo"*`) expands.
+    ///
+    /// Defaults to the default of [`PhrasePrefixQuery`].
+    /// See [`PhrasePrefixQuery::set_max_expansions`].
+    pub fn set_phrase_prefix_max_expansions(&mut self, max_expansions: u32) {
+        self.phrase_prefix_max_expansions = Some(max_expansions);
+    }
+
+    /// Sets the clock used to resolve `now` in the date math expressions of range queries
+    /// (e.g. `timestamp:[now-7d TO now]`).
+    ///
+    /// Defaults to the system clock.
+    pub fn set_clock<F>(&mut self, clock: F)
+    where F: Fn() -> DateTime + Send + Sync + 'static {
+        self.clock = Some(Arc::new(clock));
+    }
+
+    fn now(&self) -> OffsetDateTime {
+        match &self.clock {
+            Some(clock) => clock().into_utc(),
+            None => OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Registers `alias` as another name for the field or json path `full_path`.
+    ///
+    /// After `set_field_alias("title", "data.meta.title")`, the query `title:hello` is
+    /// equivalent to `data.meta.title:hello`. An alias can also be followed by a json path:
+    /// after `set_field_alias("meta", "data.meta")`, `meta.title` targets `data.meta.title`.
+    ///
+    /// Aliases take precedence over the fields of the schema, and are not resolved recursively.
+    pub fn set_field_alias(&mut self, alias: &str, full_path: &str) {
+        self.field_aliases
+            .insert(alias.to_string(), full_path.to_string());
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
     /// is not a valid query.
     pub fn parse_query(&self, query: &str) -> Result<Box<dyn Query>, QueryParserError> {
+        telemetry::enter_span!("parse_query", query);
         let logical_ast = self.parse_query_to_logical_ast(query)?;
-        Ok(convert_to_query(&self.fuzzy, logical_ast))
+        Ok(convert_to_query(
+            &self.fuzzy,
+            self.phrase_prefix_max_expansions,
+            logical_ast,
+        ))
     }
 
     /// Parse a query leniently
@@ -338,8 +485,32 @@ impl QueryParser {
     ///
     /// In case it encountered such issues, they are reported as a Vec of errors.
     pub fn parse_query_lenient(&self, query: &str) -> (Box<dyn Query>, Vec<QueryParserError>) {
+        telemetry::enter_span!("parse_query", query);
         let (logical_ast, errors) = self.parse_query_to_logical_ast_lenient(query);
-        (convert_to_query(&self.fuzzy, logical_ast), errors)
+        (
+            convert_to_query(&self.fuzzy, self.phrase_prefix_max_expansions, logical_ast),
+            errors,
+        )
+    }
+
+    /// Parse a "simple query string", typically typed by an end user in a search box.
+    ///
+    /// Contrary to [`QueryParser::parse_query`], the syntax of a simple query string is never
+    /// invalid: unbalanced quotes and parentheses are ignored, and the reserved characters of
+    /// the operators disabled in `flags` are handled as a part of the terms. The field
+    /// specific syntaxes (`field:term`, ranges, sets...) are not supported: the terms always
+    /// target the default fields. See [`query_grammar::parse_simple_query`] for the supported
+    /// operators.
+    ///
+    /// Like [`QueryParser::parse_query_lenient`], the parts of the query which can't be
+    /// executed are reported as a Vec of errors.
+    pub fn parse_simple_query(
+        &self,
+        query: &str,
+        flags: SimpleQueryFlags,
+    ) -> (Box<dyn Query>, Vec<QueryParserError>) {
+        let user_input_ast = query_grammar::parse_simple_query(query, flags);
+        self.build_query_from_user_input_ast_lenient(user_input_ast)
     }
 
     /// Build a query from an already parsed user input AST
@@ -355,7 +526,11 @@ impl QueryParser {
         if !err.is_empty() {
             return Err(err.swap_remove(0));
         }
-        Ok(convert_to_query(&self.fuzzy, logical_ast))
+        Ok(convert_to_query(
+            &self.fuzzy,
+            self.phrase_prefix_max_expansions,
+            logical_ast,
+        ))
     }
 
     /// Build leniently a query from an already parsed user input AST.
@@ -366,7 +541,10 @@ impl QueryParser {
         user_input_ast: UserInputAst,
     ) -> (Box<dyn Query>, Vec<QueryParserError>) {
         let (logical_ast, errors) = self.compute_logical_ast_lenient(user_input_ast);
-        (convert_to_query(&self.fuzzy, logical_ast), errors)
+        (
+            convert_to_query(&self.fuzzy, self.phrase_prefix_max_expansions, logical_ast),
+            errors,
+        )
     }
 
     /// Parse the user query into an AST.
@@ -425,8 +603,7 @@ impl QueryParser {
     ) -> Result<Term, QueryParserError> {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
-        let field_supports_ff_range_queries = field_type.is_fast()
-            && is_type_valid_for_fastfield_range_query(field_type.value_type());
+        let field_supports_ff_range_queries = field_supports_fastfield_range_query(field_type);
 
         if !field_type.is_indexed() && !field_supports_ff_range_queries {
             return Err(QueryParserError::FieldNotIndexed(
@@ -465,13 +642,7 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_entry.name().to_string())
                 })?;
-                let mut text_analyzer =
-                    self.tokenizer_manager
-                        .get(option.tokenizer())
-                        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                            field: field_entry.name().to_string(),
-                            tokenizer: option.tokenizer().to_string(),
-                        })?;
+                let mut text_analyzer = self.text_analyzer(field, option.tokenizer())?;
                 let mut terms: Vec<Term> = Vec::new();
                 let mut token_stream = text_analyzer.token_stream(phrase);
                 token_stream.process(&mut |token| {
@@ -522,6 +693,10 @@ impl QueryParser {
                 let ip_v6 = IpAddr::from_str(phrase)?.into_ipv6_addr();
                 Ok(Term::from_field_ip_addr(field, ip_v6))
             }
+            // Suggest fields are not indexed.
+            FieldType::Suggest(_) => Err(QueryParserError::FieldNotIndexed(
+                field_entry.name().to_string(),
+            )),
         }
     }
 
@@ -532,11 +707,15 @@ impl QueryParser {
         phrase: &str,
         slop: u32,
         prefix: bool,
+        delimiter: Delimiter,
     ) -> Result<Vec<LogicalLiteral>, QueryParserError> {
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
         let field_name = field_entry.name();
-        if !field_type.is_indexed() {
+        // CIDR blocks are range queries, which can also be executed on fast fields.
+        let is_cidr_block = field_type.value_type() == Type::IpAddr && phrase.contains('/');
+        let is_executable = field_type.is_indexed() || (is_cidr_block && field_type.is_fast());
+        if !is_executable {
             return Err(QueryParserError::FieldNotIndexed(field_name.to_string()));
         }
         if field_type.value_type() != Type::Json && !json_path.is_empty() {
@@ -576,13 +755,23 @@ impl QueryParser {
                     // This should have been seen earlier really.
                     QueryParserError::FieldNotIndexed(field_name.to_string())
                 })?;
-                let mut text_analyzer = self
-                    .tokenizer_manager
-                    .get(indexing_options.tokenizer())
-                    .ok_or_else(|| QueryParserError::UnknownTokenizer {
-                        field: field_name.to_string(),
-                        tokenizer: indexing_options.tokenizer().to_string(),
-                    })?;
+                let mut text_analyzer = self.text_analyzer(field, indexing_options.tokenizer())?;
+                if indexing_options.index_reversed() && !prefix {
+                    if let Some(suffix) = phrase.strip_prefix('*') {
+                        if let Some(literal) =
+                            generate_reversed_literal_for_suffix(field, suffix, &mut text_analyzer)
+                        {
+                            return Ok(vec![literal]);
+                        }
+                    }
+                }
+                if prefix && delimiter == Delimiter::None {
+                    if let Some(literal) =
+                        generate_prefix_literal_for_term(field, phrase, &mut text_analyzer)
+                    {
+                        return Ok(vec![literal]);
+                    }
+                }
                 Ok(generate_literals_for_str(
                     field_name,
                     field,
@@ -595,14 +784,22 @@ impl QueryParser {
                 .into_iter()
                 .collect())
             }
-            FieldType::JsonObject(ref json_options) => generate_literals_for_json_object(
-                field_name,
-                field,
-                json_path,
-                phrase,
-                &self.tokenizer_manager,
-                json_options,
-            ),
+            FieldType::JsonObject(ref json_options) => {
+                let text_options = json_options.get_text_indexing_options().ok_or_else(|| {
+                    // This should have been seen earlier really.
+                    QueryParserError::FieldNotIndexed(field_name.to_string())
+                })?;
+                let mut text_analyzer = self.text_analyzer(field, text_options.tokenizer())?;
+                generate_literals_for_json_object(
+                    field_name,
+                    field,
+                    json_path,
+                    phrase,
+                    json_options,
+                    text_options,
+                    &mut text_analyzer,
+                )
+            }
             FieldType::Facet(_) => match Facet::from_text(phrase) {
                 Ok(facet) => {
                     let facet_term = Term::from_facet(field, &facet);
@@ -617,32 +814,92 @@ impl QueryParser {
                 let bytes_term = Term::from_field_bytes(field, &bytes);
                 Ok(vec![LogicalLiteral::Term(bytes_term)])
             }
+            FieldType::IpAddr(_) if is_cidr_block => {
+                let ip_range = parse_cidr_block(phrase)?;
+                Ok(vec![LogicalLiteral::Range {
+                    lower: Bound::Included(Term::from_field_ip_addr(field, *ip_range.start())),
+                    upper: Bound::Included(Term::from_field_ip_addr(field, *ip_range.end())),
+                }])
+            }
             FieldType::IpAddr(_) => {
                 let ip_v6 = IpAddr::from_str(phrase)?.into_ipv6_addr();
                 let term = Term::from_field_ip_addr(field, ip_v6);
                 Ok(vec![LogicalLiteral::Term(term)])
             }
+            // Suggest fields are not indexed.
+            FieldType::Suggest(_) => Err(QueryParserError::FieldNotIndexed(field_name.to_string())),
         }
     }
 
-    fn default_occur(&self) -> Occur {
-        if self.conjunction_by_default {
+    /// Returns the occur of a clause without an explicit operator.
+    fn default_occur(&self, user_input_ast: &UserInputAst) -> Occur {
+        let conjunction_by_default = self
+            .targeted_field(user_input_ast)
+            .and_then(|field| self.conjunction_by_field.get(&field).copied())
+            .unwrap_or(self.conjunction_by_default);
+        if conjunction_by_default {
             Occur::Must
         } else {
             Occur::Should
         }
     }
 
+    /// Returns the field targeted by a leaf, if it explicitly targets one.
+    fn targeted_field(&self, user_input_ast: &UserInputAst) -> Option<Field> {
+        let full_path = match user_input_ast {
+            UserInputAst::Boost(ast, _) => return self.targeted_field(ast),
+            UserInputAst::Clause(_) => return None,
+            UserInputAst::Leaf(leaf) => match leaf.as_ref() {
+                UserInputLeaf::Literal(literal) => literal.field_name.as_ref()?,
+                UserInputLeaf::Range { field, .. } | UserInputLeaf::Set { field, .. } => {
+                    field.as_ref()?
+                }
+                UserInputLeaf::Exists { field } => field,
+                UserInputLeaf::All => return None,
+            },
+        };
+        let (field, _json_path) = self.split_full_path(&self.resolve_field_alias(full_path))?;
+        Some(field)
+    }
+
+    /// Returns the text analyzer processing the query text targeting `field`.
+    fn text_analyzer(
+        &self,
+        field: Field,
+        indexing_tokenizer_name: &str,
+    ) -> Result<TextAnalyzer, QueryParserError> {
+        let tokenizer_name = self
+            .query_tokenizers
+            .get(&field)
+            .map(String::as_str)
+            .unwrap_or(indexing_tokenizer_name);
+        self.tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+            QueryParserError::UnknownTokenizer {
+                field: self.schema.get_field_name(field).to_string(),
+                tokenizer: tokenizer_name.to_string(),
+            }
+        })
+    }
+
     fn resolve_bound(
         &self,
         field: Field,
         json_path: &str,
         bound: &UserInputBound,
+        is_lower_bound: bool,
     ) -> Result<Bound<Term>, QueryParserError> {
         if bound.term_str() == "*" {
             return Ok(Bound::Unbounded);
         }
-        let term = self.compute_boundary_term(field, json_path, bound.term_str())?;
+        let field_type = self.schema.get_field_entry(field).field_type();
+        let term = if field_type.value_type() == Type::Date && is_date_math(bound.term_str()) {
+            // Inclusive upper bounds and exclusive lower bounds include the whole rounding unit.
+            let round_up = is_lower_bound == matches!(bound, UserInputBound::Exclusive(_));
+            let date_time = parse_date_math(bound.term_str(), self.now(), round_up)?;
+            Term::from_field_date(field, DateTime::from_utc(date_time))
+        } else {
+            self.compute_boundary_term(field, json_path, bound.term_str())?
+        };
         match *bound {
             UserInputBound::Inclusive(_) => Ok(Bound::Included(term)),
             UserInputBound::Exclusive(_) => Ok(Bound::Excluded(term)),
@@ -656,13 +913,12 @@ impl QueryParser {
     ) -> (LogicalAst, Vec<QueryParserError>) {
         match user_input_ast {
             UserInputAst::Clause(sub_queries) => {
-                let default_occur = self.default_occur();
                 let mut logical_sub_queries: Vec<(Occur, LogicalAst)> = Vec::new();
                 let mut errors = Vec::new();
                 for (occur_opt, sub_ast) in sub_queries {
+                    let occur = occur_opt.unwrap_or_else(|| self.default_occur(&sub_ast));
                     let (sub_ast, mut sub_errors) =
                         self.compute_logical_ast_with_occur_lenient(sub_ast);
-                    let occur = occur_opt.unwrap_or(default_occur);
                     logical_sub_queries.push((occur, sub_ast));
                     errors.append(&mut sub_errors);
                 }
@@ -672,7 +928,8 @@ impl QueryParser {
                 let (ast, errors) = self.compute_logical_ast_with_occur_lenient(*ast);
                 (ast.boost(boost as Score), errors)
             }
-            UserInputAst::Leaf(leaf) => {
+            UserInputAst::Leaf(mut leaf) => {
+                self.resolve_field_aliases_in_leaf(&mut leaf);
                 let (ast, errors) = self.compute_logical_ast_from_leaf_lenient(*leaf);
                 // if the error is not recoverable, replace it with an empty clause. We will end up
                 // trimming those later
@@ -684,10 +941,56 @@ impl QueryParser {
         }
     }
 
+    fn resolve_field_aliases_in_leaf(&self, leaf: &mut UserInputLeaf) {
+        if self.field_aliases.is_empty() {
+            return;
+        }
+        let full_path_opt = match leaf {
+            UserInputLeaf::Literal(literal) => literal.field_name.as_mut(),
+            UserInputLeaf::Range { field, .. } | UserInputLeaf::Set { field, .. } => field.as_mut(),
+            UserInputLeaf::Exists { field } => Some(field),
+            UserInputLeaf::All => None,
+        };
+        if let Some(full_path) = full_path_opt {
+            if let Cow::Owned(resolved_path) = self.resolve_field_alias(full_path) {
+                *full_path = resolved_path;
+            }
+        }
+    }
+
     fn field_boost(&self, field: Field) -> Score {
         self.boost.get(&field).cloned().unwrap_or(1.0)
     }
 
+    /// Turns a term literal into a fuzzy term literal.
+    ///
+    /// Phrases and non-text terms are returned as is.
+    fn make_fuzzy(&self, literal: LogicalLiteral, fuzziness: Fuzziness) -> LogicalLiteral {
+        let LogicalLiteral::Term(term) = literal else {
+            return literal;
+        };
+        let Some(num_chars) = num_chars_in_text_term(&term) else {
+            return LogicalLiteral::Term(term);
+        };
+        let distance = match fuzziness {
+            Fuzziness::Auto => match num_chars {
+                0..=2 => 0,
+                3..=5 => 1,
+                _ => 2,
+            },
+            Fuzziness::Distance(distance) => distance,
+        };
+        if distance == 0 {
+            return LogicalLiteral::Term(term);
+        }
+        LogicalLiteral::FuzzyTerm {
+            term,
+            distance,
+            transposition_cost_one: self.fuzzy_transposition_cost_one,
+            prefix_length: self.fuzzy_prefix_length,
+        }
+    }
+
     fn default_indexed_json_fields(&self) -> impl Iterator<Item = Field> + '_ {
         let schema = self.schema.clone();
         self.default_fields.iter().cloned().filter(move |field| {
@@ -748,12 +1051,48 @@ impl QueryParser {
         Ok(triplets)
     }
 
+    /// Builds the literal matching the documents with a value in `full_path`.
+    ///
+    /// Fast fields are checked using their columns, the other fields using their fieldnorms.
+    pub(crate) fn compute_exists_literal(
+        &self,
+        full_path: &str,
+    ) -> Result<LogicalLiteral, QueryParserError> {
+        let (field, json_path) = self
+            .split_full_path(full_path)
+            .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.to_string()))?;
+        let field_entry = self.schema.get_field_entry(field);
+        if field_entry.is_fast() {
+            return Ok(LogicalLiteral::Exists {
+                full_path: full_path.to_string(),
+                json_subpaths: field_entry.field_type().value_type() == Type::Json,
+            });
+        }
+        if json_path.is_empty() && field_entry.has_fieldnorms() {
+            return Ok(LogicalLiteral::FieldNormExists(field));
+        }
+        Err(QueryParserError::UnsupportedQuery(format!(
+            "Exists query on '{full_path}' requires a fast field or a field with fieldnorms."
+        )))
+    }
+
     fn compute_logical_ast_from_leaf_lenient(
         &self,
         leaf: UserInputLeaf,
     ) -> (Option<LogicalAst>, Vec<QueryParserError>) {
         match leaf {
             UserInputLeaf::Literal(literal) => {
+                if let Some(Fuzziness::Distance(distance)) = literal.fuzziness {
+                    if distance > MAX_FUZZY_DISTANCE {
+                        return (
+                            None,
+                            vec![QueryParserError::UnsupportedQuery(format!(
+                                "Fuzzy distance of {distance} is not supported, the maximum is \
+                                 {MAX_FUZZY_DISTANCE}."
+                            ))],
+                        );
+                    }
+                }
                 let term_phrases: Vec<(Field, &str, &str)> =
                     try_tuple!(self.compute_path_triplets_for_literal(&literal));
                 let mut asts: Vec<LogicalAst> = Vec::new();
@@ -765,6 +1104,7 @@ impl QueryParser {
                         phrase,
                         literal.slop,
                         literal.prefix,
+                        literal.delimiter,
                     ) {
                         Ok(asts) => asts,
                         Err(e) => {
@@ -772,7 +1112,10 @@ impl QueryParser {
                             continue;
                         }
                     };
-                    for ast in unboosted_asts {
+                    for mut ast in unboosted_asts {
+                        if let Some(fuzziness) = literal.fuzziness {
+                            ast = self.make_fuzzy(ast, fuzziness);
+                        }
                         // Apply some field specific boost defined at the query parser level.
                         let boost = self.field_boost(field);
                         asts.push(LogicalAst::Leaf(Box::new(ast)).boost(boost));
@@ -812,14 +1155,14 @@ impl QueryParser {
                     .split_full_path(&full_path)
                     .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
                 let mut errors = Vec::new();
-                let lower = match self.resolve_bound(field, json_path, &lower) {
+                let lower = match self.resolve_bound(field, json_path, &lower, true) {
                     Ok(bound) => bound,
                     Err(error) => {
                         errors.push(error);
                         Bound::Unbounded
                     }
                 };
-                let upper = match self.resolve_bound(field, json_path, &upper) {
+                let upper = match self.resolve_bound(field, json_path, &upper, false) {
                     Ok(bound) => bound,
                     Err(error) => {
                         errors.push(error);
@@ -854,18 +1197,17 @@ impl QueryParser {
                 let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Set { elements }));
                 (Some(logical_ast), errors)
             }
-            UserInputLeaf::Exists { .. } => (
-                None,
-                vec![QueryParserError::UnsupportedQuery(
-                    "Range query need to target a specific field.".to_string(),
-                )],
-            ),
+            UserInputLeaf::Exists { field: full_path } => {
+                let exists_literal = try_tuple!(self.compute_exists_literal(&full_path));
+                (Some(LogicalAst::Leaf(Box::new(exists_literal))), Vec::new())
+            }
         }
     }
 }
 
 fn convert_literal_to_query(
     fuzzy: &FxHashMap<Field, Fuzzy>,
+    phrase_prefix_max_expansions: Option<u32>,
     logical_literal: LogicalLiteral,
 ) -> Box<dyn Query> {
     match logical_literal {
@@ -888,23 +1230,135 @@ fn convert_literal_to_query(
                 Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
             }
         }
+        LogicalLiteral::FuzzyTerm {
+            term,
+            distance,
+            transposition_cost_one,
+            prefix_length,
+        } => {
+            let mut fuzzy_query = FuzzyTermQuery::new(term, distance, transposition_cost_one);
+            fuzzy_query.set_prefix_length(prefix_length);
+            Box::new(fuzzy_query)
+        }
         LogicalLiteral::Phrase {
             terms,
             slop,
             prefix,
         } => {
             if prefix {
-                Box::new(PhrasePrefixQuery::new_with_offset(terms))
+                let mut phrase_prefix_query = PhrasePrefixQuery::new_with_offset(terms);
+                if let Some(max_expansions) = phrase_prefix_max_expansions {
+                    phrase_prefix_query.set_max_expansions(max_expansions);
+                }
+                Box::new(phrase_prefix_query)
             } else {
                 Box::new(PhraseQuery::new_with_offset_and_slop(terms, slop))
             }
         }
         LogicalLiteral::Range { lower, upper } => Box::new(RangeQuery::new(lower, upper)),
         LogicalLiteral::Set { elements, .. } => Box::new(TermSetQuery::new(elements)),
+        LogicalLiteral::TermPrefix(term) => {
+            let upper_bound = match prefix_end(term.serialized_value_bytes()) {
+                Some(end) => {
+                    let mut end_term = term.clone();
+                    end_term.truncate_value_bytes(0);
+                    end_term.append_bytes(&end);
+                    Bound::Excluded(end_term)
+                }
+                None => Bound::Unbounded,
+            };
+            Box::new(InvertedIndexRangeQuery::new(
+                Bound::Included(term),
+                upper_bound,
+            ))
+        }
+        LogicalLiteral::Exists {
+            full_path,
+            json_subpaths,
+        } => Box::new(ExistsQuery::new(full_path, json_subpaths)),
+        LogicalLiteral::FieldNormExists(field) => Box::new(FieldNormExistsQuery::new(field)),
         LogicalLiteral::All => Box::new(AllQuery),
     }
 }
 
+/// Parses a CIDR block, e.g. `10.0.0.0/8` or `2001:db8::/32`, into the range of addresses it
+/// contains. IPv4 addresses are mapped to IPv6, like in the ip fields.
+fn parse_cidr_block(cidr_block: &str) -> Result<RangeInclusive<Ipv6Addr>, QueryParserError> {
+    let invalid_cidr_block = || QueryParserError::CidrFormatError(cidr_block.to_string());
+    let (ip_str, prefix_len_str) = cidr_block.split_once('/').ok_or_else(invalid_cidr_block)?;
+    let ip_addr = IpAddr::from_str(ip_str)?;
+    let prefix_len: u32 = prefix_len_str.parse().map_err(|_| invalid_cidr_block())?;
+    let ipv6_prefix_len = match ip_addr {
+        IpAddr::V4(_) if prefix_len <= 32 => prefix_len + 96,
+        IpAddr::V6(_) if prefix_len <= 128 => prefix_len,
+        _ => return Err(invalid_cidr_block()),
+    };
+    let ip_u128 = u128::from(ip_addr.into_ipv6_addr());
+    let host_mask = u128::MAX.checked_shr(ipv6_prefix_len).unwrap_or(0);
+    Ok(Ipv6Addr::from(ip_u128 & !host_mask)..=Ipv6Addr::from(ip_u128 | host_mask))
+}
+
+/// Returns the number of chars of a text term, or `None` if the term is not a text term.
+fn num_chars_in_text_term(term: &Term) -> Option<usize> {
+    let value = term.value();
+    let num_chars = if let Some((_, json_value)) = value.as_json() {
+        json_value.as_str()?.chars().count()
+    } else {
+        value.as_str()?.chars().count()
+    };
+    Some(num_chars)
+}
+
+/// Rewrites a leading wildcard query (e.g. `*ing`) into a prefix query on the reversed
+/// tokens of the field. See [`TextFieldIndexing::set_index_reversed`].
+///
+/// Returns `None` if the suffix contains other wildcards or is not made of exactly one token.
+fn generate_reversed_literal_for_suffix(
+    field: Field,
+    suffix: &str,
+    text_analyzer: &mut TextAnalyzer,
+) -> Option<LogicalLiteral> {
+    if suffix.contains('*') {
+        return None;
+    }
+    let mut tokens: Vec<String> = Vec::new();
+    text_analyzer.token_stream(suffix).process(&mut |token| {
+        tokens.push(token.text.clone());
+    });
+    let [token] = &tokens[..] else {
+        return None;
+    };
+    let reversed_token: String = std::iter::once(REVERSED_TOKEN_MARKER)
+        .chain(token.chars().rev())
+        .collect();
+    Some(LogicalLiteral::TermPrefix(Term::from_field_text(
+        field,
+        &reversed_token,
+    )))
+}
+
+/// Rewrites an unquoted prefix term (e.g. `wol*` in a simple query string) into a prefix
+/// query.
+///
+/// Returns `None` if the term is not made of exactly one token, in which case it is handled
+/// as a phrase prefix.
+fn generate_prefix_literal_for_term(
+    field: Field,
+    phrase: &str,
+    text_analyzer: &mut TextAnalyzer,
+) -> Option<LogicalLiteral> {
+    let mut tokens: Vec<String> = Vec::new();
+    text_analyzer.token_stream(phrase).process(&mut |token| {
+        tokens.push(token.text.clone());
+    });
+    let [token] = &tokens[..] else {
+        return None;
+    };
+    Some(LogicalLiteral::TermPrefix(Term::from_field_text(
+        field, token,
+    )))
+}
+
 fn generate_literals_for_str(
     field_name: &str,
     field: Field,
@@ -950,19 +1404,10 @@ fn generate_literals_for_json_object(
     field: Field,
     json_path: &str,
     phrase: &str,
-    tokenizer_manager: &TokenizerManager,
     json_options: &JsonObjectOptions,
+    text_options: &TextFieldIndexing,
+    text_analyzer: &mut TextAnalyzer,
 ) -> Result<Vec<LogicalLiteral>, QueryParserError> {
-    let text_options = json_options.get_text_indexing_options().ok_or_else(|| {
-        // This should have been seen earlier really.
-        QueryParserError::FieldNotIndexed(field_name.to_string())
-    })?;
-    let mut text_analyzer = tokenizer_manager
-        .get(text_options.tokenizer())
-        .ok_or_else(|| QueryParserError::UnknownTokenizer {
-            field: field_name.to_string(),
-            tokenizer: text_options.tokenizer().to_string(),
-        })?;
     let index_record_option = text_options.index_option();
     let mut logical_literals = Vec::new();
 
@@ -1004,12 +1449,21 @@ fn generate_literals_for_json_object(
     Ok(logical_literals)
 }
 
-fn convert_to_query(fuzzy: &FxHashMap<Field, Fuzzy>, logical_ast: LogicalAst) -> Box<dyn Query> {
+fn convert_to_query(
+    fuzzy: &FxHashMap<Field, Fuzzy>,
+    phrase_prefix_max_expansions: Option<u32>,
+    logical_ast: LogicalAst,
+) -> Box<dyn Query> {
     match trim_ast(logical_ast) {
         Some(LogicalAst::Clause(trimmed_clause)) => {
             let occur_subqueries = trimmed_clause
                 .into_iter()
-                .map(|(occur, subquery)| (occur, convert_to_query(fuzzy, subquery)))
+                .map(|(occur, subquery)| {
+                    (
+                        occur,
+                        convert_to_query(fuzzy, phrase_prefix_max_expansions, subquery),
+                    )
+                })
                 .collect::<Vec<_>>();
             assert!(
                 !occur_subqueries.is_empty(),
@@ -1017,11 +1471,13 @@ fn convert_to_query(fuzzy: &FxHashMap<Field, Fuzzy>, logical_ast: LogicalAst) ->
             );
             Box::new(BooleanQuery::new(occur_subqueries))
         }
-        Some(LogicalAst::Leaf(trimmed_logical_literal)) => {
-            convert_literal_to_query(fuzzy, *trimmed_logical_literal)
-        }
+        Some(LogicalAst::Leaf(trimmed_logical_literal)) => convert_literal_to_query(
+            fuzzy,
+            phrase_prefix_max_expansions,
+            *trimmed_logical_literal,
+        ),
         Some(LogicalAst::Boost(ast, boost)) => {
-            let query = convert_to_query(fuzzy, *ast);
+            let query = convert_to_query(fuzzy, phrase_prefix_max_expansions, *ast);
             let boosted_query = BoostQuery::new(query, boost);
             Box::new(boosted_query)
         }
@@ -1032,6 +1488,7 @@ fn convert_to_query(fuzzy: &FxHashMap<Field, Fuzzy>, logical_ast: LogicalAst) ->
 #[cfg(test)]
 mod test {
     use matches::assert_matches;
+    use query_grammar::SimpleQueryFlags;
 
     use super::super::logical_ast::*;
     use super::{QueryParser, QueryParserError};
@@ -1043,7 +1500,7 @@ mod test {
     use crate::tokenizer::{
         LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
     };
-    use crate::Index;
+    use crate::{DateTime, Index};
 
     fn make_schema() -> Schema {
         let mut schema_builder = Schema::builder();
@@ -1634,6 +2091,126 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_query_parser_ip_cidr_block() -> crate::Result<()> {
+        use std::net::IpAddr;
+        use std::str::FromStr;
+
+        use crate::collector::Count;
+        use crate::schema::IntoIpv6Addr;
+        use crate::IndexWriter;
+
+        let mut schema_builder = Schema::builder();
+        let ip = schema_builder.add_ip_addr_field("ip", INDEXED);
+        let ip_ff = schema_builder.add_ip_addr_field("ip_ff", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for ip_str in [
+            "10.0.0.1",
+            "10.255.0.3",
+            "11.0.0.1",
+            "192.168.1.12",
+            "2001:db8::1",
+        ] {
+            let ip_addr = IpAddr::from_str(ip_str).unwrap().into_ipv6_addr();
+            index_writer.add_document(doc!(ip => ip_addr, ip_ff => ip_addr))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, Vec::new());
+        let count = |query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        for field_name in ["ip", "ip_ff"] {
+            assert_eq!(count(&format!("{field_name}:10.0.0.0/8")), 2);
+            assert_eq!(count(&format!("{field_name}:10.0.0.0/16")), 1);
+            assert_eq!(count(&format!("{field_name}:10.0.0.1/32")), 1);
+            assert_eq!(count(&format!("{field_name}:0.0.0.0/0")), 4);
+            assert_eq!(count(&format!("{field_name}:\"2001:db8::/32\"")), 1);
+            assert_eq!(count(&format!("{field_name}:\"::/0\"")), 5);
+            assert_eq!(count(&format!("{field_name}:[10.0.0.1 TO 11.0.0.1}}")), 2);
+            assert_eq!(count(&format!("{field_name}:>=192.168.1.12")), 2);
+        }
+        assert_eq!(count("ip:10.0.0.1"), 1);
+        assert_matches!(
+            query_parser.parse_query("ip_ff:10.0.0.1"),
+            Err(QueryParserError::FieldNotIndexed(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("ip:10.0.0.0/33"),
+            Err(QueryParserError::CidrFormatError(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("ip:10.0.0.0/a"),
+            Err(QueryParserError::CidrFormatError(_))
+        );
+        assert_matches!(
+            query_parser.parse_query("ip:10.0.0/8"),
+            Err(QueryParserError::IpFormatError(_))
+        );
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_query_parser_leading_wildcard_on_reversed_field() -> crate::Result<()> {
+        use crate::collector::{Count, TopDocs};
+        use crate::IndexWriter;
+
+        let mut schema_builder = Schema::builder();
+        let text_field_indexing = TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_index_reversed(true);
+        let reversed_options = TextOptions::default().set_indexing_options(text_field_indexing);
+        let title = schema_builder.add_text_field("title", reversed_options);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "Running fast", body => "Running fast"))?;
+        index_writer.add_document(doc!(title => "walking", body => "walking"))?;
+        index_writer.add_document(doc!(title => "sing a song", body => "sing a song"))?;
+        index_writer.add_document(doc!(title => "ingot", body => "ingot"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+
+        let query = query_parser.parse_query("title:*ING")?;
+        assert_eq!(
+            format!("{query:?}"),
+            "InvertedIndexRangeQuery { bounds: BoundsRange { lower_bound: Included(Term(field=0, \
+             type=Str, \"\\u{1}gni\")), upper_bound: Excluded(Term(field=0, type=Str, \
+             \"\\u{1}gnj\")) }, limit: None }"
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("title:*alking")?, &Count)?,
+            1
+        );
+        // Reversed tokens do not break phrase queries.
+        assert_eq!(
+            searcher.search(&query_parser.parse_query("title:\"sing a song\"")?, &Count)?,
+            1
+        );
+        // Reversed tokens are not taken in account for scoring.
+        let title_top_docs = searcher.search(
+            &query_parser.parse_query("title:fast")?,
+            &TopDocs::with_limit(1),
+        )?;
+        let body_top_docs = searcher.search(
+            &query_parser.parse_query("body:fast")?,
+            &TopDocs::with_limit(1),
+        )?;
+        assert_eq!(title_top_docs, body_top_docs);
+        // Fields without reversed tokens are not rewritten.
+        assert_eq!(
+            format!("{:?}", query_parser.parse_query("body:*ing")?),
+            "TermQuery(Term(field=1, type=Str, \"ing\"))"
+        );
+        Ok(())
+    }
+
     #[test]
     pub fn test_query_parser_expected_int() {
         let query_parser = make_query_parser();
@@ -1836,6 +2413,172 @@ mod test {
         assert_eq!(query_parser.split_full_path("firsty"), None);
     }
 
+    #[test]
+    fn test_parse_query_date_math() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_clock(|| DateTime::from_timestamp_secs(1_706_715_737));
+        let query = query_parser
+            .parse_query("date:[now-1d/d TO now/d]")
+            .unwrap();
+        let expected_query = query_parser
+            .parse_query("date:[2024-01-30T00:00:00Z TO 2024-01-31T23:59:59.999999999Z]")
+            .unwrap();
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        let query = query_parser.parse_query("date:{now/d TO now/d}").unwrap();
+        let expected_query = query_parser
+            .parse_query("date:{2024-01-31T23:59:59.999999999Z TO 2024-01-31T00:00:00Z}")
+            .unwrap();
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        let query = query_parser
+            .parse_query("date:>=2024-01-01T00:00:00Z||+1M")
+            .unwrap();
+        let expected_query = query_parser
+            .parse_query("date:>=2024-02-01T00:00:00Z")
+            .unwrap();
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        assert!(matches!(
+            query_parser.parse_query("date:[now-1x TO now]"),
+            Err(QueryParserError::InvalidDateMath(_))
+        ));
+        // Date math only applies to date fields.
+        assert!(matches!(
+            query_parser.parse_query("signed:[now-1d TO now]"),
+            Err(QueryParserError::ExpectedInt(_))
+        ));
+    }
+
+    #[test]
+    fn test_field_query_tokenizer() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_query_tokenizer(title, "raw");
+        let query = query_parser.parse_query("title:Hello text:Hello").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \"Hello\"))), \
+             (Should, TermQuery(Term(field=1, type=Str, \"hello\")))], \
+             minimum_number_should_match: 1 }"
+        );
+        query_parser.set_field_query_tokenizer(title, "unknown");
+        assert!(matches!(
+            query_parser.parse_query("title:hello"),
+            Err(QueryParserError::UnknownTokenizer { .. })
+        ));
+    }
+
+    #[test]
+    fn test_field_conjunction_by_default() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        let text = query_parser.schema.get_field("text").unwrap();
+        query_parser.set_field_conjunction_by_default(title, true);
+        let assert_same_query = |query_parser: &QueryParser, query: &str, expected_query: &str| {
+            let query = query_parser.parse_query(query).unwrap();
+            let expected_query = make_query_parser().parse_query(expected_query).unwrap();
+            assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        };
+        assert_same_query(
+            &query_parser,
+            "title:a title:b^2 text:c",
+            "+title:a +title:b^2 text:c",
+        );
+        assert_same_query(
+            &query_parser,
+            "title:(a b) text:(c d)",
+            "(+title:a +title:b) (text:c text:d)",
+        );
+        query_parser.set_conjunction_by_default();
+        query_parser.set_field_conjunction_by_default(text, false);
+        assert_same_query(
+            &query_parser,
+            "title:(a b) text:(c d) c",
+            "+(+title:a +title:b) +(text:c text:d) +c",
+        );
+    }
+
+    #[test]
+    pub fn test_parse_exists_query() {
+        test_parse_query_to_logical_ast_helper("_exists_:u64_ff", "$exists(\"u64_ff\")", false);
+        test_parse_query_to_logical_ast_helper("u64_ff:*", "$exists(\"u64_ff\")", false);
+        test_parse_query_to_logical_ast_helper("_exists_:title", "$exists(Field(0))", false);
+        test_parse_query_to_logical_ast_helper(
+            "a NOT _exists_:text",
+            "(Term(field=0, type=Str, \"a\") Term(field=1, type=Str, \"a\") -$exists(Field(1)))",
+            false,
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("_exists_:notindexed_text", false),
+            Err(QueryParserError::UnsupportedQuery(_))
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("_exists_:json.a", false),
+            Err(QueryParserError::UnsupportedQuery(_))
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("_exists_:boujou", false),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        );
+    }
+
+    #[test]
+    fn test_parse_simple_query() {
+        let query_parser = make_query_parser_with_default_fields(&["title"]);
+        let assert_same_query = |query: &str, flags: SimpleQueryFlags, expected_query: &str| {
+            let (query, errors) = query_parser.parse_simple_query(query, flags);
+            assert!(errors.is_empty());
+            let expected_query = query_parser.parse_query(expected_query).unwrap();
+            assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        };
+        let flags = SimpleQueryFlags::ALL;
+        assert_same_query(
+            "+big -bad | \"big wolf\"~1",
+            flags,
+            "(+big -bad) OR \"big wolf\"~1",
+        );
+        assert_same_query("+(big | bad) wolf~1", flags, "+(big OR bad) wolf~1");
+        assert_same_query("\"big bad wolf", flags, "big bad wolf");
+        assert_same_query("(big wolf", flags, "big wolf");
+        assert_same_query("title:big) wolf", flags, "\"title big\" wolf");
+        assert_same_query(
+            "-big \"bad wolf\"",
+            SimpleQueryFlags::PHRASE,
+            "big \"bad wolf\"",
+        );
+        let (query, errors) = query_parser.parse_simple_query("wol*", flags);
+        assert!(errors.is_empty());
+        assert_eq!(
+            format!("{query:?}"),
+            "InvertedIndexRangeQuery { bounds: BoundsRange { lower_bound: Included(Term(field=0, \
+             type=Str, \"wol\")), upper_bound: Excluded(Term(field=0, type=Str, \"wom\")) }, \
+             limit: None }"
+        );
+    }
+
+    #[test]
+    fn test_field_alias() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_field_alias("headline", "title");
+        query_parser.set_field_alias("meta", "json");
+        query_parser.set_field_alias("author", "json.author.name");
+        let query = query_parser.parse_query("headline:hello").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "TermQuery(Term(field=0, type=Str, \"hello\"))"
+        );
+        let query = query_parser.parse_query("headline:[a TO b]").unwrap();
+        assert!(format!("{query:?}").contains("field=0"));
+        let query = query_parser.parse_query("headline: IN [a b]").unwrap();
+        assert!(format!("{query:?}").contains("field=0"));
+        let query = query_parser.parse_query("meta.title:hello").unwrap();
+        let expected_query = query_parser.parse_query("json.title:hello").unwrap();
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        let query = query_parser.parse_query("author:hello").unwrap();
+        let expected_query = query_parser.parse_query("json.author.name:hello").unwrap();
+        assert_eq!(format!("{query:?}"), format!("{expected_query:?}"));
+        assert_eq!(query_parser.resolve_field_alias("metadata"), "metadata");
+        assert_eq!(query_parser.resolve_field_alias("meta.a.b"), "json.a.b");
+    }
+
     #[test]
     pub fn test_phrase_slop() {
         test_parse_query_to_logical_ast_helper(
@@ -1869,11 +2612,24 @@ mod test {
             format!("{query:?}"),
             "BooleanQuery { subqueries: [(Should, PhrasePrefixQuery { field: Field(0), \
              phrase_terms: [(0, Term(field=0, type=Str, \"big\")), (1, Term(field=0, type=Str, \
-             \"bad\"))], prefix: (2, Term(field=0, type=Str, \"wo\")), max_expansions: 50 }), \
-             (Should, PhrasePrefixQuery { field: Field(1), phrase_terms: [(0, Term(field=1, \
-             type=Str, \"big\")), (1, Term(field=1, type=Str, \"bad\"))], prefix: (2, \
-             Term(field=1, type=Str, \"wo\")), max_expansions: 50 })], \
-             minimum_number_should_match: 1 }"
+             \"bad\"))], prefix: (2, Term(field=0, type=Str, \"wo\")), max_expansions: 50, \
+             similarity: None }), (Should, PhrasePrefixQuery { field: Field(1), phrase_terms: \
+             [(0, Term(field=1, type=Str, \"big\")), (1, Term(field=1, type=Str, \"bad\"))], \
+             prefix: (2, Term(field=1, type=Str, \"wo\")), max_expansions: 50, similarity: None \
+             })], minimum_number_should_match: 1 }"
+        );
+    }
+
+    #[test]
+    pub fn test_phrase_prefix_max_expansions() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_phrase_prefix_max_expansions(10);
+        let query = query_parser.parse_query("title:\"big bad wo\"*").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "PhrasePrefixQuery { field: Field(0), phrase_terms: [(0, Term(field=0, type=Str, \
+             \"big\")), (1, Term(field=0, type=Str, \"bad\"))], prefix: (2, Term(field=0, \
+             type=Str, \"wo\")), max_expansions: 10, similarity: None }"
         );
     }
 
@@ -1937,8 +2693,8 @@ mod test {
             assert_eq!(
                 format!("{query:?}"),
                 "BooleanQuery { subqueries: [(Should, FuzzyTermQuery { term: Term(field=0, \
-                 type=Str, \"abc\"), distance: 1, transposition_cost_one: true, prefix: false }), \
-                 (Should, TermQuery(Term(field=1, type=Str, \"abc\")))], \
+                 type=Str, \"abc\"), distance: 1, transposition_cost_one: true, prefix: false, \
+                 prefix_length: 0 }), (Should, TermQuery(Term(field=1, type=Str, \"abc\")))], \
                  minimum_number_should_match: 1 }"
             );
         }
@@ -1956,12 +2712,68 @@ mod test {
                 format!("{query:?}"),
                 "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \
                  \"abc\"))), (Should, FuzzyTermQuery { term: Term(field=1, type=Str, \"abc\"), \
-                 distance: 2, transposition_cost_one: false, prefix: true })], \
+                 distance: 2, transposition_cost_one: false, prefix: true, prefix_length: 0 })], \
                  minimum_number_should_match: 1 }"
             );
         }
     }
 
+    #[test]
+    pub fn test_parse_fuzzy_term() {
+        test_parse_query_to_logical_ast_helper(
+            "title:diary~1",
+            r#"Term(field=0, type=Str, "diary")~1"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:diary~",
+            r#"Term(field=0, type=Str, "diary")~1"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:diaries~",
+            r#"Term(field=0, type=Str, "diaries")~2"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:ab~",
+            r#"Term(field=0, type=Str, "ab")"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:Diary~0",
+            r#"Term(field=0, type=Str, "diary")"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "signed:2~1",
+            r#"Term(field=2, type=I64, 2)"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:\"a b\"~1",
+            r#""[(0, Term(field=0, type=Str, "a")), (1, Term(field=0, type=Str, "b"))]"~1"#,
+            false,
+        );
+        assert_matches!(
+            parse_query_to_logical_ast("title:diary~3", false),
+            Err(QueryParserError::UnsupportedQuery(_))
+        );
+    }
+
+    #[test]
+    pub fn test_fuzzy_term_options() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_fuzzy_prefix_length(2);
+        query_parser.set_fuzzy_transposition_cost_one(false);
+        let query = query_parser.parse_query("title:diary~2").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "FuzzyTermQuery { term: Term(field=0, type=Str, \"diary\"), distance: 2, \
+             transposition_cost_one: false, prefix: false, prefix_length: 2 }"
+        );
+    }
+
     #[test]
     pub fn test_set_default_field_integer() {
         test_parse_query_to_logical_ast_helper_with_default_fields(
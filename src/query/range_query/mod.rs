@@ -1,4 +1,4 @@
-use crate::schema::Type;
+use crate::schema::{FieldType, Type};
 
 mod fast_field_range_doc_set;
 mod range_query;
@@ -19,3 +19,14 @@ pub(crate) fn is_type_valid_for_fastfield_range_query(typ: Type) -> bool {
         Type::Facet | Type::Bytes => false,
     }
 }
+
+/// Returns true if range queries on the field can be executed on its fast field.
+pub(crate) fn field_supports_fastfield_range_query(field_type: &FieldType) -> bool {
+    if !field_type.is_fast() {
+        return false;
+    }
+    if let FieldType::Bytes(bytes_options) = field_type {
+        return bytes_options.fixed_width().is_some();
+    }
+    is_type_valid_for_fastfield_range_query(field_type.value_type())
+}
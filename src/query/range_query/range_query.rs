@@ -7,7 +7,7 @@ use common::BitSet;
 use super::range_query_fastfield::FastFieldRangeWeight;
 use crate::index::SegmentReader;
 use crate::query::explanation::does_not_match;
-use crate::query::range_query::is_type_valid_for_fastfield_range_query;
+use crate::query::range_query::field_supports_fastfield_range_query;
 use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
 use crate::schema::{Field, IndexRecordOption, Term, Type};
 use crate::termdict::{TermDictionary, TermStreamer};
@@ -105,7 +105,7 @@ impl Query for RangeQuery {
         let schema = enable_scoring.schema();
         let field_type = schema.get_field_entry(self.field()).field_type();
 
-        if field_type.is_fast() && is_type_valid_for_fastfield_range_query(self.value_type()) {
+        if field_supports_fastfield_range_query(field_type) {
             Ok(Box::new(FastFieldRangeWeight::new(self.bounds.clone())))
         } else {
             if field_type.is_json() {
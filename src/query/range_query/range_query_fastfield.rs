@@ -6,16 +6,17 @@ use std::net::Ipv6Addr;
 use std::ops::{Bound, RangeInclusive};
 
 use columnar::{
-    Column, ColumnType, MonotonicallyMappableToU128, MonotonicallyMappableToU64, NumericalType,
-    StrColumn,
+    Column, ColumnType, HasAssociatedColumnType, MonotonicallyMappableToU128,
+    MonotonicallyMappableToU64, NumericalType, StrColumn,
 };
 use common::bounds::{BoundsRange, TransformBound};
 
 use super::fast_field_range_doc_set::RangeDocSet;
+use crate::fastfield::fixed_width_bytes_to_u128;
 use crate::query::{
     AllScorer, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight,
 };
-use crate::schema::{Type, ValueBytes};
+use crate::schema::{FieldType, Type, ValueBytes};
 use crate::{DocId, DocSet, Score, SegmentReader, TantivyError, Term};
 
 #[derive(Clone, Debug)]
@@ -156,6 +157,39 @@ impl Weight for FastFieldRangeWeight {
             );
             let docset = RangeDocSet::new(value_range, ip_addr_column);
             Ok(Box::new(ConstScorer::new(docset, boost)))
+        } else if let FieldType::Bytes(bytes_options) = field_type {
+            let width = bytes_options.fixed_width().ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Range queries on the fast field of {field_name:?} require a fixed width"
+                ))
+            })?;
+            let parse_fixed_width_bytes = |term: &Term| {
+                let value = term.value();
+                let bytes = value.as_bytes().unwrap_or_default();
+                if bytes.len() != width {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "Expected a bound of {width} bytes, got {}",
+                        bytes.len()
+                    )));
+                }
+                Ok(fixed_width_bytes_to_u128(bytes))
+            };
+            let bounds: BoundsRange<u128> = self.bounds.map_bound_res(parse_fixed_width_bytes)?;
+            let Some(column): Option<Column<u128>> =
+                reader.fast_fields().column_opt(&field_name)?
+            else {
+                return Ok(Box::new(EmptyScorer));
+            };
+            let Some(value_range) = bound_range_inclusive_u128(
+                &bounds.lower_bound,
+                &bounds.upper_bound,
+                column.min_value(),
+                column.max_value(),
+            ) else {
+                return Ok(Box::new(EmptyScorer));
+            };
+            let docset = RangeDocSet::new(value_range, column);
+            Ok(Box::new(ConstScorer::new(docset, boost)))
         } else if field_type.is_str() {
             let Some(str_dict_column): Option<StrColumn> = reader.fast_fields().str(&field_name)?
             else {
@@ -232,6 +266,79 @@ impl Weight for FastFieldRangeWeight {
     }
 }
 
+/// `RuntimeFieldRangeQuery` matches the documents whose value of a runtime field is within a
+/// range, see [`RuntimeField`](crate::fastfield::RuntimeField).
+///
+/// The runtime field is identified by its name, and the bounds have the type of its values.
+/// Since the runtime fields are not part of the schema, they cannot be searched with a
+/// [`RangeQuery`](crate::query::RangeQuery), whose bounds are terms of a field of the schema.
+///
+/// ```
+/// use std::ops::Bound;
+///
+/// use tantivy::query::RuntimeFieldRangeQuery;
+///
+/// let cheap =
+///     RuntimeFieldRangeQuery::new("price_with_tax", Bound::Unbounded, Bound::Excluded(10.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RuntimeFieldRangeQuery {
+    field_name: String,
+    column_type: ColumnType,
+    bounds: BoundsRange<u64>,
+}
+
+impl RuntimeFieldRangeQuery {
+    /// Creates a query matching the documents whose value of the runtime field `field_name` is
+    /// within the bounds.
+    pub fn new<T: MonotonicallyMappableToU64 + HasAssociatedColumnType>(
+        field_name: &str,
+        lower_bound: Bound<T>,
+        upper_bound: Bound<T>,
+    ) -> RuntimeFieldRangeQuery {
+        RuntimeFieldRangeQuery {
+            field_name: field_name.to_string(),
+            column_type: T::column_type(),
+            bounds: BoundsRange::new(lower_bound, upper_bound).map_bound(|&value| value.to_u64()),
+        }
+    }
+}
+
+impl Query for RuntimeFieldRangeQuery {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+impl Weight for RuntimeFieldRangeQuery {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(runtime_field_type) = reader.fast_fields().runtime_field_type(&self.field_name)
+        else {
+            return Err(TantivyError::FieldNotFound(self.field_name.clone()));
+        };
+        let Some((column, _)) = reader
+            .fast_fields()
+            .u64_lenient_for_type(Some(&[self.column_type]), &self.field_name)?
+        else {
+            return Err(TantivyError::SchemaError(format!(
+                "The runtime field {:?} is of type {:?}, but the bounds are of type {:?}",
+                self.field_name, runtime_field_type, self.column_type
+            )));
+        };
+        search_on_u64_ff(column, boost, self.bounds.clone())
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("Const", scorer.score()))
+    }
+}
+
 /// On numerical fields the column type may not match the user provided one.
 ///
 /// Convert into fast field value space and search.
@@ -441,6 +548,27 @@ fn bound_range_inclusive_ip(
     start_value..=end_value
 }
 
+// Returns None, if the range cannot be converted to a inclusive range (which equals to a empty
+// range).
+fn bound_range_inclusive_u128(
+    lower_bound: &Bound<u128>,
+    upper_bound: &Bound<u128>,
+    min_value: u128,
+    max_value: u128,
+) -> Option<RangeInclusive<u128>> {
+    let start_value = match lower_bound {
+        Bound::Included(val) => *val,
+        Bound::Excluded(val) => val.checked_add(1)?,
+        Bound::Unbounded => min_value,
+    };
+    let end_value = match upper_bound {
+        Bound::Included(val) => *val,
+        Bound::Excluded(val) => val.checked_sub(1)?,
+        Bound::Unbounded => max_value,
+    };
+    Some(start_value..=end_value)
+}
+
 // Returns None, if the range cannot be converted to a inclusive range (which equals to a empty
 // range).
 fn bound_to_value_range<T: MonotonicallyMappableToU64>(
@@ -483,11 +611,52 @@ mod tests {
     use crate::query::range_query::range_query_fastfield::FastFieldRangeWeight;
     use crate::query::{QueryParser, RangeQuery, Weight};
     use crate::schema::{
-        DateOptions, Field, NumericOptions, Schema, SchemaBuilder, FAST, INDEXED, STORED, STRING,
-        TEXT,
+        BytesOptions, DateOptions, Field, NumericOptions, Schema, SchemaBuilder, FAST, INDEXED,
+        STORED, STRING, TEXT,
     };
     use crate::{Index, IndexWriter, TantivyDocument, Term, TERMINATED};
 
+    #[test]
+    fn test_fixed_width_bytes_ff_range_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let key_field =
+            schema_builder.add_bytes_field("key", BytesOptions::from(FAST).set_fixed_width(4));
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for key in [1u32, 5, 256, 1 << 24, u32::MAX] {
+            index_writer.add_document(doc!(key_field => key.to_be_bytes().to_vec()))?;
+        }
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let bound = |key: u32| Term::from_field_bytes(key_field, &key.to_be_bytes());
+        let count = |lower_bound, upper_bound| {
+            let query = RangeQuery::new(lower_bound, upper_bound);
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(
+            count(Bound::Included(bound(1)), Bound::Included(bound(5))),
+            2
+        );
+        assert_eq!(
+            count(Bound::Excluded(bound(1)), Bound::Excluded(bound(256))),
+            1
+        );
+        assert_eq!(count(Bound::Included(bound(6)), Bound::Unbounded), 3);
+        assert_eq!(count(Bound::Excluded(bound(u32::MAX)), Bound::Unbounded), 0);
+        assert_eq!(count(Bound::Unbounded, Bound::Excluded(bound(0))), 0);
+        assert_eq!(count(Bound::Unbounded, Bound::Included(bound(1 << 24))), 4);
+
+        // The bounds need to have the width of the field.
+        let query = RangeQuery::new(
+            Bound::Included(Term::from_field_bytes(key_field, &[0u8; 3])),
+            Bound::Unbounded,
+        );
+        assert!(searcher.search(&query, &Count).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_text_field_ff_range_query() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
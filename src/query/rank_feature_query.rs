@@ -0,0 +1,329 @@
+use columnar::Column;
+
+use crate::docset::TERMINATED;
+use crate::query::explanation::does_not_match;
+use crate::query::{EmptyScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Type;
+use crate::{DocId, DocSet, Score, SegmentReader, TantivyError};
+
+/// Function turning the value of a rank feature into a score.
+///
+/// All of the functions are increasing. Use them with care on features where lower values
+/// are better (e.g. the length of an url): [`RankFeatureFunction::Saturation`] with a
+/// negative `pivot` is not supported, invert the value at indexing time instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RankFeatureFunction {
+    /// `value / (value + pivot)`
+    ///
+    /// The score is between 0 and 1, and equals 0.5 when the value equals `pivot`.
+    Saturation {
+        /// Value for which the score is 0.5.
+        pivot: f32,
+    },
+    /// `ln(scaling_factor + value)`
+    ///
+    /// `scaling_factor` must be at least 1 for the score to be positive.
+    Log {
+        /// Added to the value before taking its logarithm.
+        scaling_factor: f32,
+    },
+    /// `value^exponent / (value^exponent + pivot^exponent)`
+    ///
+    /// Generalization of the saturation function, with a configurable steepness.
+    Sigmoid {
+        /// Value for which the score is 0.5.
+        pivot: f32,
+        /// Steepness of the curve, typically between 0.5 and 1.
+        exponent: f32,
+    },
+    /// The value itself.
+    Linear,
+}
+
+impl RankFeatureFunction {
+    fn validate(&self) -> crate::Result<()> {
+        let is_valid = match *self {
+            RankFeatureFunction::Saturation { pivot } => pivot > 0.0,
+            RankFeatureFunction::Log { scaling_factor } => scaling_factor >= 1.0,
+            RankFeatureFunction::Sigmoid { pivot, exponent } => pivot > 0.0 && exponent > 0.0,
+            RankFeatureFunction::Linear => true,
+        };
+        if !is_valid {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Invalid rank feature function {self:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    fn compute(&self, val: f32) -> Score {
+        match *self {
+            RankFeatureFunction::Saturation { pivot } => val / (val + pivot),
+            RankFeatureFunction::Log { scaling_factor } => (scaling_factor + val).ln(),
+            RankFeatureFunction::Sigmoid { pivot, exponent } => {
+                let val_pow = val.powf(exponent);
+                val_pow / (val_pow + pivot.powf(exponent))
+            }
+            RankFeatureFunction::Linear => val,
+        }
+    }
+}
+
+/// `RankFeatureQuery` matches the documents having a strictly positive value in a
+/// rank feature field, and scores them using a function of this value.
+///
+/// It is typically used as a `Should` clause of a [`BooleanQuery`](crate::query::BooleanQuery),
+/// so that a static signal like the pagerank of a document contributes to its score.
+///
+/// The field must be a `f64` fast field, as created by
+/// [`SchemaBuilder::add_rank_feature_field`](crate::schema::SchemaBuilder::add_rank_feature_field).
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{RankFeatureFunction, RankFeatureQuery};
+/// use tantivy::schema::Schema;
+/// use tantivy::{doc, DocAddress, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let pagerank = schema_builder.add_rank_feature_field("pagerank");
+/// let schema = schema_builder.build();
+/// let index = Index::create_in_ram(schema);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(pagerank => 2.0))?;
+/// index_writer.add_document(doc!(pagerank => 8.0))?;
+/// index_writer.add_document(doc!())?;
+/// index_writer.commit()?;
+///
+/// let query = RankFeatureQuery::new(
+///     "pagerank",
+///     RankFeatureFunction::Saturation { pivot: 8.0 },
+/// );
+/// let searcher = index.reader()?.searcher();
+/// let top_docs = searcher.search(&query, &TopDocs::with_limit(3))?;
+/// assert_eq!(top_docs.len(), 2);
+/// assert_eq!(top_docs[0], (0.5, DocAddress::new(0, 1)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct RankFeatureQuery {
+    field_name: String,
+    function: RankFeatureFunction,
+}
+
+impl RankFeatureQuery {
+    /// Creates a new `RankFeatureQuery` over the given rank feature field.
+    pub fn new(field_name: &str, function: RankFeatureFunction) -> RankFeatureQuery {
+        RankFeatureQuery {
+            field_name: field_name.to_string(),
+            function,
+        }
+    }
+}
+
+impl Query for RankFeatureQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        self.function.validate()?;
+        let schema = enable_scoring.schema();
+        let Some((field, _path)) = schema.find_field(&self.field_name) else {
+            return Err(TantivyError::FieldNotFound(self.field_name.clone()));
+        };
+        let field_type = schema.get_field_entry(field).field_type();
+        if !field_type.is_fast() || field_type.value_type() != Type::F64 {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {} is not a rank feature field (f64 fast field).",
+                self.field_name
+            )));
+        }
+        Ok(Box::new(RankFeatureWeight {
+            field_name: self.field_name.clone(),
+            function: self.function,
+        }))
+    }
+}
+
+struct RankFeatureWeight {
+    field_name: String,
+    function: RankFeatureFunction,
+}
+
+impl Weight for RankFeatureWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(column) = reader.fast_fields().column_opt::<f64>(&self.field_name)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let mut scorer = RankFeatureScorer {
+            column,
+            function: self.function,
+            boost,
+            doc: 0,
+            val: 0.0,
+            max_doc: reader.max_doc(),
+        };
+        scorer.seek_to_feature(0);
+        Ok(Box::new(scorer))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let mut explanation = Explanation::new_with_string(
+            format!("RankFeature {:?}", self.function),
+            scorer.score(),
+        );
+        let val = reader
+            .fast_fields()
+            .column_opt::<f64>(&self.field_name)?
+            .and_then(|column| column.first(doc))
+            .unwrap_or_default();
+        explanation.add_const("feature value", val as Score);
+        Ok(explanation)
+    }
+}
+
+struct RankFeatureScorer {
+    column: Column<f64>,
+    function: RankFeatureFunction,
+    boost: Score,
+    doc: DocId,
+    val: f32,
+    max_doc: DocId,
+}
+
+impl RankFeatureScorer {
+    /// Positions the scorer on the first document `>= target` with a positive feature value.
+    fn seek_to_feature(&mut self, target: DocId) -> DocId {
+        let mut doc = target;
+        while doc < self.max_doc {
+            if let Some(val) = self.column.first(doc) {
+                if val > 0.0 {
+                    self.doc = doc;
+                    self.val = val as f32;
+                    return doc;
+                }
+            }
+            doc += 1;
+        }
+        self.doc = TERMINATED;
+        TERMINATED
+    }
+}
+
+impl DocSet for RankFeatureScorer {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.seek_to_feature(self.doc + 1)
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc == TERMINATED || target <= self.doc {
+            return self.doc;
+        }
+        self.seek_to_feature(target)
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.column.num_docs()
+    }
+}
+
+impl Scorer for RankFeatureScorer {
+    fn score(&mut self) -> Score {
+        self.boost * self.function.compute(self.val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RankFeatureFunction, RankFeatureQuery};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, TantivyError, Term};
+
+    #[test]
+    fn test_rank_feature_functions() {
+        let saturation = RankFeatureFunction::Saturation { pivot: 2.0 };
+        assert_nearly_equals!(saturation.compute(2.0), 0.5);
+        assert_nearly_equals!(saturation.compute(6.0), 0.75);
+        let log = RankFeatureFunction::Log {
+            scaling_factor: 1.0,
+        };
+        assert_nearly_equals!(log.compute(std::f32::consts::E - 1.0), 1.0);
+        let sigmoid = RankFeatureFunction::Sigmoid {
+            pivot: 2.0,
+            exponent: 2.0,
+        };
+        assert_nearly_equals!(sigmoid.compute(2.0), 0.5);
+        assert_nearly_equals!(sigmoid.compute(6.0), 0.9);
+        assert_nearly_equals!(RankFeatureFunction::Linear.compute(3.0), 3.0);
+    }
+
+    #[test]
+    fn test_rank_feature_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let pagerank = schema_builder.add_rank_feature_field("pagerank");
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a", pagerank => 1.0))?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.add_document(doc!(text => "a", pagerank => 0.0))?;
+        index_writer.add_document(doc!(text => "b", pagerank => 3.0))?;
+        index_writer.add_document(doc!(text => "a", pagerank => 9.0))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let rank_feature_query =
+            RankFeatureQuery::new("pagerank", RankFeatureFunction::Saturation { pivot: 1.0 });
+        let top_docs = searcher.search(&rank_feature_query, &TopDocs::with_limit(10))?;
+        assert_eq!(
+            top_docs,
+            vec![
+                (0.9, DocAddress::new(0, 4)),
+                (0.75, DocAddress::new(0, 3)),
+                (0.5, DocAddress::new(0, 0)),
+            ]
+        );
+        let explanation = rank_feature_query.explain(&searcher, DocAddress::new(0, 3))?;
+        assert_nearly_equals!(explanation.value(), 0.75);
+        assert!(rank_feature_query
+            .explain(&searcher, DocAddress::new(0, 2))
+            .is_err());
+
+        let term_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(text, "a"),
+            IndexRecordOption::Basic,
+        ));
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, term_query),
+            (Occur::Should, Box::new(rank_feature_query)),
+        ]);
+        assert_eq!(searcher.search(&query, &Count)?, 4);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(1))?;
+        assert_eq!(top_docs[0].1, DocAddress::new(0, 4));
+
+        let text_query = RankFeatureQuery::new("text", RankFeatureFunction::Linear);
+        assert!(matches!(
+            searcher.search(&text_query, &Count),
+            Err(TantivyError::SchemaError(_))
+        ));
+        let invalid_query =
+            RankFeatureQuery::new("pagerank", RankFeatureFunction::Saturation { pivot: 0.0 });
+        assert!(matches!(
+            searcher.search(&invalid_query, &Count),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+}
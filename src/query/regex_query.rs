@@ -1,11 +1,18 @@
 use std::clone::Clone;
 use std::sync::Arc;
 
+use common::BitSet;
 use tantivy_fst::Regex;
 
 use crate::error::TantivyError;
-use crate::query::{AutomatonWeight, EnableScoring, Query, Weight};
-use crate::schema::Field;
+use crate::query::explanation::does_not_match;
+use crate::query::term_matcher::automaton_term_matcher;
+use crate::query::{
+    AutomatonWeight, BitSetDocSet, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query,
+    Scorer, TermMatcher, Weight,
+};
+use crate::schema::{Field, FieldType};
+use crate::{DocId, DocSet, Score, SegmentReader};
 
 /// A Regex Query matches all of the documents
 /// containing a specific term that matches
@@ -14,6 +21,11 @@ use crate::schema::Field;
 /// Wildcard queries (e.g. ho*se) can be achieved
 /// by converting them to their regex counterparts.
 ///
+/// On fast text fields whose fast field holds the same terms as the inverted index
+/// (e.g. `STRING | FAST`), or which are not indexed, the regex is evaluated against the
+/// dictionary of the fast field, and the matching documents are found by scanning the term
+/// ordinals of the column. This is much faster for low-cardinality, enum-like fields.
+///
 /// ```rust
 /// use tantivy::collector::Count;
 /// use tantivy::query::RegexQuery;
@@ -81,9 +93,68 @@ impl RegexQuery {
 }
 
 impl Query for RegexQuery {
-    fn weight(&self, _enabled_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let field_entry = enable_scoring.schema().get_field_entry(self.field);
+        if let FieldType::Str(text_options) = field_entry.field_type() {
+            if text_options.fast_field_has_indexed_terms() {
+                return Ok(Box::new(FastFieldRegexWeight {
+                    field_name: field_entry.name().to_string(),
+                    regex: self.regex.clone(),
+                }));
+            }
+        }
         Ok(Box::new(self.specialized_weight()))
     }
+
+    fn term_matchers<'a>(&'a self, visitor: &mut dyn FnMut(TermMatcher<'a>)) {
+        visitor(automaton_term_matcher(self.field, self.regex.clone()));
+    }
+}
+
+/// Weight evaluating a regex against the dictionary of a text fast field.
+struct FastFieldRegexWeight {
+    field_name: String,
+    regex: Arc<Regex>,
+}
+
+impl Weight for FastFieldRegexWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let Some(str_column) = reader.fast_fields().str(&self.field_name)? else {
+            return Ok(Box::new(EmptyScorer));
+        };
+        let mut term_ords = BitSet::with_max_value(str_column.num_terms() as u32);
+        let mut term_stream = str_column
+            .dictionary()
+            .search(self.regex.as_ref())
+            .into_stream()?;
+        while term_stream.advance() {
+            term_ords.insert(term_stream.term_ord() as u32);
+        }
+        if term_ords.len() == 0 {
+            return Ok(Box::new(EmptyScorer));
+        }
+        let max_doc = reader.max_doc();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let ords_column = str_column.ords();
+        for doc in 0..max_doc {
+            if ords_column
+                .values_for_doc(doc)
+                .any(|term_ord| term_ords.contains(term_ord as u32))
+            {
+                doc_bitset.insert(doc);
+            }
+        }
+        let docset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("FastFieldRegexWeight", 1.0))
+    }
 }
 
 #[cfg(test)]
@@ -93,9 +164,10 @@ mod test {
     use tantivy_fst::Regex;
 
     use super::RegexQuery;
-    use crate::collector::TopDocs;
-    use crate::schema::{Field, Schema, TEXT};
-    use crate::{assert_nearly_equals, Index, IndexReader, IndexWriter};
+    use crate::collector::{Count, TopDocs};
+    use crate::query::Query;
+    use crate::schema::{Field, Schema, FAST, STRING, TEXT};
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexReader, IndexWriter};
 
     fn build_test_index() -> crate::Result<(IndexReader, Field)> {
         let mut schema_builder = Schema::builder();
@@ -177,6 +249,40 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    pub fn test_regex_query_on_fast_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let status = schema_builder.add_text_field("status", STRING | FAST);
+        let tag = schema_builder.add_text_field("tag", FAST);
+        let title = schema_builder.add_text_field("title", TEXT | FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(status => "open", tag => "red", title => "Big Bug"))?;
+            index_writer.add_document(doc!(status => "closed", tag => "blue", tag => "green"))?;
+            index_writer.add_document(doc!(status => "reopened"))?;
+            index_writer.add_document(doc!(tag => "grey", title => "bug"))?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let count = |query: RegexQuery| searcher.search(&query, &Count).unwrap();
+
+        assert_eq!(count(RegexQuery::from_pattern(".*open.*", status)?), 2);
+        assert_eq!(count(RegexQuery::from_pattern("open|closed", status)?), 2);
+        assert_eq!(count(RegexQuery::from_pattern("pending", status)?), 0);
+        // Multivalued and not indexed field.
+        assert_eq!(count(RegexQuery::from_pattern("gre.*", tag)?), 2);
+        assert_eq!(count(RegexQuery::from_pattern("(red|blue)", tag)?), 2);
+        // The fast field of `title` is not tokenized, the inverted index is used instead.
+        assert_eq!(count(RegexQuery::from_pattern("bug", title)?), 2);
+
+        let explanation = RegexQuery::from_pattern("reopened", status)?
+            .explain(&searcher, DocAddress::new(0, 2))?;
+        assert_nearly_equals!(explanation.value(), 1.0);
+        Ok(())
+    }
+
     #[test]
     pub fn test_pattern_error() {
         let (_reader, field) = build_test_index().unwrap();
@@ -15,6 +15,11 @@ pub trait ScoreCombiner: Default + Clone + Send + Copy + 'static {
 
     /// Returns the aggregate score.
     fn score(&self) -> Score;
+
+    /// Describes how the scores are combined, in an [`Explanation`](crate::query::Explanation).
+    fn description(&self) -> String {
+        "combination of ...".to_string()
+    }
 }
 
 /// Just ignores scores. The `DoNothingCombiner` does not
@@ -32,6 +37,10 @@ impl ScoreCombiner for DoNothingCombiner {
     fn score(&self) -> Score {
         1.0
     }
+
+    fn description(&self) -> String {
+        "constant score of ...".to_string()
+    }
 }
 
 /// Sums the score of different scorers.
@@ -52,6 +61,10 @@ impl ScoreCombiner for SumCombiner {
     fn score(&self) -> Score {
         self.score
     }
+
+    fn description(&self) -> String {
+        "sum of ...".to_string()
+    }
 }
 
 /// Take max score of different scorers
@@ -89,4 +102,11 @@ impl ScoreCombiner for DisjunctionMaxCombiner {
     fn score(&self) -> Score {
         self.max + (self.sum - self.max) * self.tie_breaker
     }
+
+    fn description(&self) -> String {
+        format!(
+            "max plus {} times the sum of the others of ...",
+            self.tie_breaker
+        )
+    }
 }
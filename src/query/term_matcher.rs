@@ -0,0 +1,67 @@
+use std::fmt;
+use std::sync::Arc;
+
+use tantivy_fst::Automaton;
+
+use crate::schema::Field;
+use crate::Term;
+
+/// Describes the terms matched by a query, so that the parts of a text the query matches
+/// can be highlighted.
+///
+/// See [`Query::term_matchers`](crate::query::Query::term_matchers).
+#[derive(Clone)]
+pub enum TermMatcher<'a> {
+    /// Matches a term.
+    Term(&'a Term),
+    /// Matches a sequence of terms of the same field, where each term is given with its
+    /// position in the sequence.
+    Phrase {
+        /// The terms of the phrase, with their positions.
+        terms: &'a [(usize, Term)],
+        /// The number of positions a term may be away from its position in the phrase.
+        slop: u32,
+    },
+    /// Matches the texts of a field accepted by a predicate, like the ones of a fuzzy
+    /// or a regex query.
+    Text {
+        /// The field of the texts.
+        field: Field,
+        /// Returns `true` if the query matches the text of a term.
+        matches: Arc<dyn Fn(&str) -> bool + Send + Sync>,
+    },
+}
+
+impl fmt::Debug for TermMatcher<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TermMatcher::Term(term) => f.debug_tuple("Term").field(term).finish(),
+            TermMatcher::Phrase { terms, slop } => f
+                .debug_struct("Phrase")
+                .field("terms", terms)
+                .field("slop", slop)
+                .finish(),
+            TermMatcher::Text { field, .. } => {
+                f.debug_struct("Text").field("field", field).finish()
+            }
+        }
+    }
+}
+
+/// Returns a [`TermMatcher::Text`] matching the texts accepted by `automaton`.
+pub(crate) fn automaton_term_matcher<A>(field: Field, automaton: Arc<A>) -> TermMatcher<'static>
+where A: Automaton + Send + Sync + 'static {
+    TermMatcher::Text {
+        field,
+        matches: Arc::new(move |text: &str| {
+            let mut state = automaton.start();
+            for &byte in text.as_bytes() {
+                state = automaton.accept(&state, byte);
+                if !automaton.can_match(&state) {
+                    return false;
+                }
+            }
+            automaton.is_match(&state)
+        }),
+    }
+}
@@ -143,6 +143,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_term_weight_count_up_to() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10 {
+            let text = if i % 2 == 0 { "a b" } else { "a c" };
+            index_writer.add_document(doc!(text_field=>text))?;
+        }
+        index_writer.commit()?;
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let searcher = index.reader()?.searcher();
+        let weight = term_query.weight(EnableScoring::disabled_from_searcher(&searcher))?;
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(weight.count_up_to(segment_reader, 0)?, 0);
+        assert_eq!(weight.count_up_to(segment_reader, 4)?, 4);
+        assert_eq!(weight.count_up_to(segment_reader, 20)?, 10);
+
+        index_writer.delete_term(Term::from_field_text(text_field, "b"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(weight.count_up_to(segment_reader, 4)?, 4);
+        assert_eq!(weight.count_up_to(segment_reader, 20)?, 5);
+        Ok(())
+    }
+
     #[test]
     fn test_term_query_simple_seek() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -3,7 +3,7 @@ use std::fmt;
 use super::term_weight::TermWeight;
 use crate::query::bm25::Bm25Weight;
 use crate::query::{EnableScoring, Explanation, Query, Weight};
-use crate::schema::IndexRecordOption;
+use crate::schema::{IndexRecordOption, Similarity};
 use crate::Term;
 
 /// A Term query matches all of the documents
@@ -58,6 +58,7 @@ use crate::Term;
 pub struct TermQuery {
     term: Term,
     index_record_option: IndexRecordOption,
+    similarity: Option<Similarity>,
 }
 
 impl fmt::Debug for TermQuery {
@@ -72,9 +73,15 @@ impl TermQuery {
         TermQuery {
             term,
             index_record_option: segment_postings_options,
+            similarity: None,
         }
     }
 
+    /// Overrides the [`Similarity`] of the field of the term for this query.
+    pub fn set_similarity(&mut self, similarity: Similarity) {
+        self.similarity = Some(similarity);
+    }
+
     /// The `Term` this query is built out of.
     pub fn term(&self) -> &Term {
         &self.term
@@ -99,7 +106,13 @@ impl TermQuery {
             EnableScoring::Enabled {
                 statistics_provider,
                 ..
-            } => Bm25Weight::for_terms(statistics_provider, &[self.term.clone()])?,
+            } => Bm25Weight::for_terms_with_similarity(
+                statistics_provider,
+                &[self.term.clone()],
+                self.similarity
+                    .unwrap_or_else(|| field_entry.field_type().similarity()),
+            )?
+            .with_fieldnorm_precision(field_entry.field_type().fieldnorm_precision()),
             EnableScoring::Disabled { .. } => {
                 Bm25Weight::new(Explanation::new("<no score>", 1.0f32), 1.0f32)
             }
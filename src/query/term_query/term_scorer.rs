@@ -2,6 +2,7 @@ use crate::docset::DocSet;
 use crate::fieldnorm::FieldNormReader;
 use crate::postings::{FreqReadingOption, Postings, SegmentPostings};
 use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
 use crate::query::{Explanation, Scorer};
 use crate::{DocId, Score};
 
@@ -10,6 +11,7 @@ pub struct TermScorer {
     postings: SegmentPostings,
     fieldnorm_reader: FieldNormReader,
     similarity_weight: Bm25Weight,
+    doc_boost: Option<DocBoost>,
 }
 
 impl TermScorer {
@@ -22,9 +24,17 @@ impl TermScorer {
             postings,
             fieldnorm_reader,
             similarity_weight,
+            doc_boost: None,
         }
     }
 
+    /// Multiplies the scores by the static boost of the documents.
+    #[must_use]
+    pub(crate) fn with_doc_boost(mut self, doc_boost: Option<DocBoost>) -> TermScorer {
+        self.doc_boost = doc_boost;
+        self
+    }
+
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
         self.postings.block_cursor.shallow_seek(target_doc);
     }
@@ -68,10 +78,21 @@ impl TermScorer {
     /// specific is achieved on a different document.
     ///
     /// (The result is on the other hand guaranteed to be correct if there is only one segment).
+    ///
+    /// With a doc boost, the maximum score is multiplied by the highest boost of the segment.
     pub fn block_max_score(&mut self) -> Score {
-        self.postings
+        let block_max_score = self
+            .postings
             .block_cursor
-            .block_max_score(&self.fieldnorm_reader, &self.similarity_weight)
+            .block_max_score(&self.fieldnorm_reader, &self.similarity_weight);
+        block_max_score * self.max_boost()
+    }
+
+    fn max_boost(&self) -> Score {
+        self.doc_boost
+            .as_ref()
+            .map(DocBoost::max_boost)
+            .unwrap_or(1.0)
     }
 
     pub fn term_freq(&self) -> u32 {
@@ -85,11 +106,22 @@ impl TermScorer {
     pub fn explain(&self) -> Explanation {
         let fieldnorm_id = self.fieldnorm_id();
         let term_freq = self.term_freq();
-        self.similarity_weight.explain(fieldnorm_id, term_freq)
+        let explanation = self.similarity_weight.explain(fieldnorm_id, term_freq);
+        let Some(doc_boost) = self.doc_boost.as_ref() else {
+            return explanation;
+        };
+        let doc = self.doc();
+        let mut boosted_explanation = Explanation::new(
+            "TermQuery, product of the score and the doc boost",
+            explanation.value() * doc_boost.boost(doc),
+        );
+        boosted_explanation.add_detail(explanation);
+        doc_boost.explain(doc, &mut boosted_explanation);
+        boosted_explanation
     }
 
     pub fn max_score(&self) -> Score {
-        self.similarity_weight.max_score()
+        self.similarity_weight.max_score() * self.max_boost()
     }
 
     pub fn last_doc_in_block(&self) -> DocId {
@@ -119,7 +151,12 @@ impl Scorer for TermScorer {
     fn score(&mut self) -> Score {
         let fieldnorm_id = self.fieldnorm_id();
         let term_freq = self.term_freq();
-        self.similarity_weight.score(fieldnorm_id, term_freq)
+        let score = self.similarity_weight.score(fieldnorm_id, term_freq);
+        if let Some(doc_boost) = self.doc_boost.as_ref() {
+            score * doc_boost.boost(self.doc())
+        } else {
+            score
+        }
     }
 }
 
@@ -4,11 +4,12 @@ use crate::fieldnorm::FieldNormReader;
 use crate::index::SegmentReader;
 use crate::postings::SegmentPostings;
 use crate::query::bm25::Bm25Weight;
+use crate::query::doc_boost::DocBoost;
 use crate::query::explanation::does_not_match;
-use crate::query::weight::{for_each_docset_buffered, for_each_scorer};
+use crate::query::weight::{count_docset_up_to, for_each_docset_buffered, for_each_scorer};
 use crate::query::{Explanation, Scorer, Weight};
 use crate::schema::IndexRecordOption;
-use crate::{DocId, Score, Term};
+use crate::{DocBoostMode, DocId, Score, Term};
 
 pub struct TermWeight {
     term: Term,
@@ -44,6 +45,16 @@ impl Weight for TermWeight {
         }
     }
 
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            let mut scorer = self.specialized_scorer(reader, 1.0)?;
+            Ok(count_docset_up_to(&mut scorer, Some(alive_bitset), limit))
+        } else {
+            // The document frequency is cheaper than visiting the first `limit` documents.
+            Ok(self.count(reader)?.min(limit))
+        }
+    }
+
     /// Iterates through all of the document matched by the DocSet
     /// `DocSet` and push the scored documents to the collector.
     fn for_each(
@@ -125,20 +136,17 @@ impl TermWeight {
         let fieldnorm_reader =
             fieldnorm_reader_opt.unwrap_or_else(|| FieldNormReader::constant(reader.max_doc(), 1));
         let similarity_weight = self.similarity_weight.boost_by(boost);
+        let doc_boost_opt = if self.scoring_enabled {
+            DocBoost::open(reader, DocBoostMode::Multiply)?
+        } else {
+            None
+        };
         let postings_opt: Option<SegmentPostings> =
             inverted_index.read_postings(&self.term, self.index_record_option)?;
-        if let Some(segment_postings) = postings_opt {
-            Ok(TermScorer::new(
-                segment_postings,
-                fieldnorm_reader,
-                similarity_weight,
-            ))
-        } else {
-            Ok(TermScorer::new(
-                SegmentPostings::empty(),
-                fieldnorm_reader,
-                similarity_weight,
-            ))
-        }
+        let segment_postings = postings_opt.unwrap_or_else(SegmentPostings::empty);
+        Ok(
+            TermScorer::new(segment_postings, fieldnorm_reader, similarity_weight)
+                .with_doc_boost(doc_boost_opt),
+        )
     }
 }
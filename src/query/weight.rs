@@ -1,5 +1,6 @@
 use super::Scorer;
 use crate::docset::COLLECT_BLOCK_BUFFER_LEN;
+use crate::fastfield::AliveBitSet;
 use crate::index::SegmentReader;
 use crate::query::Explanation;
 use crate::{DocId, DocSet, Score, TERMINATED};
@@ -34,6 +35,40 @@ pub(crate) fn for_each_docset_buffered<T: DocSet + ?Sized>(
     }
 }
 
+/// Iterates through the documents matched by the `DocSet`, until `callback` returns `false`.
+pub(crate) fn for_each_docset_until<T: DocSet + ?Sized>(
+    docset: &mut T,
+    callback: &mut dyn FnMut(DocId) -> bool,
+) {
+    let mut doc = docset.doc();
+    while doc != TERMINATED {
+        if !callback(doc) {
+            break;
+        }
+        doc = docset.advance();
+    }
+}
+
+/// Counts the alive documents of the `DocSet`, stopping as soon as `limit` documents have been
+/// counted.
+pub(crate) fn count_docset_up_to<T: DocSet + ?Sized>(
+    docset: &mut T,
+    alive_bitset: Option<&AliveBitSet>,
+    limit: u32,
+) -> u32 {
+    let mut count = 0u32;
+    if limit == 0 {
+        return count;
+    }
+    for_each_docset_until(docset, &mut |doc| {
+        if alive_bitset.is_none_or(|alive_bitset| alive_bitset.is_alive(doc)) {
+            count += 1;
+        }
+        count < limit
+    });
+    count
+}
+
 /// Calls `callback` with all of the `(doc, score)` for which score
 /// is exceeding a given threshold.
 ///
@@ -84,6 +119,20 @@ pub trait Weight: Send + Sync + 'static {
         }
     }
 
+    /// Returns the number of documents within the given [`SegmentReader`], up to `limit`.
+    ///
+    /// This is a hint that the count is not needed beyond `limit`: the documents matching past
+    /// the first `limit` ones do not have to be visited. The returned count is exact if it is
+    /// lower than `limit`, and `limit` otherwise.
+    fn count_up_to(&self, reader: &SegmentReader, limit: u32) -> crate::Result<u32> {
+        let mut docset = self.scorer(reader, 1.0)?;
+        Ok(count_docset_up_to(
+            docset.as_mut(),
+            reader.alive_bitset(),
+            limit,
+        ))
+    }
+
     /// Iterates through all of the document matched by the DocSet
     /// `DocSet` and push the scored documents to the collector.
     fn for_each(
@@ -110,6 +159,21 @@ pub trait Weight: Send + Sync + 'static {
         Ok(())
     }
 
+    /// Iterates through the documents matched by the DocSet `DocSet`, in doc id order, and
+    /// pushes them to `callback` until it returns `false`.
+    ///
+    /// This lets a collector stop the scan of a segment as soon as it has collected enough
+    /// documents, e.g. when the segment is sorted by the field the collector sorts by.
+    fn for_each_no_score_until(
+        &self,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId) -> bool,
+    ) -> crate::Result<()> {
+        let mut docset = self.scorer(reader, 1.0)?;
+        for_each_docset_until(docset.as_mut(), callback);
+        Ok(())
+    }
+
     /// Calls `callback` with all of the `(doc, score)` for which score
     /// is exceeding a given threshold.
     ///
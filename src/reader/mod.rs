@@ -1,16 +1,22 @@
+mod point_in_time;
 mod warming;
 
 use std::sync::atomic::AtomicU64;
-use std::sync::{atomic, Arc, Weak};
+use std::sync::{atomic, Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
 use arc_swap::ArcSwap;
+pub use point_in_time::PointInTimeId;
 pub use warming::Warmer;
 
+use self::point_in_time::PointInTimeRegistry;
 use self::warming::WarmingState;
 use crate::core::searcher::{SearcherGeneration, SearcherInner};
+use crate::core::META_FILEPATH;
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
+use crate::index::{SegmentId, SegmentMeta};
 use crate::store::DOCSTORE_CACHE_CAPACITY;
-use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
+use crate::{telemetry, Index, Inventory, Opstamp, Searcher, SegmentReader, TrackedObject};
 
 /// Defines when a new version of the index should be reloaded.
 ///
@@ -23,7 +29,10 @@ pub enum ReloadPolicy {
     /// All updates of the index should be manual.
     ///
     /// No change is reflected automatically. You are required to call [`IndexReader::reload()`]
-    /// manually.
+    /// or [`IndexReader::reload_if_changed()`] manually.
+    ///
+    /// No thread is started to watch the index, which makes this policy well suited to
+    /// processes sharing an index they only read.
     Manual,
     /// The index is reloaded within milliseconds after a new commit is available.
     /// This is made possible by watching changes in the `meta.json` file.
@@ -149,6 +158,32 @@ impl TryInto<IndexReader> for IndexReaderBuilder {
     }
 }
 
+/// Identifies the state of a segment a [`SegmentReader`] was opened for.
+#[derive(Clone, PartialEq, Eq)]
+struct SegmentVersion {
+    segment_id: SegmentId,
+    delete_opstamp: Option<Opstamp>,
+    update_opstamp: Option<Opstamp>,
+}
+
+impl From<&SegmentMeta> for SegmentVersion {
+    fn from(segment_meta: &SegmentMeta) -> Self {
+        SegmentVersion {
+            segment_id: segment_meta.id(),
+            delete_opstamp: segment_meta.delete_opstamp(),
+            update_opstamp: segment_meta.update_opstamp(),
+        }
+    }
+}
+
+/// The version of the `meta.json` file and the segment readers the current searcher was
+/// created from.
+#[derive(Default)]
+struct LoadedGeneration {
+    meta_checksum: Option<u32>,
+    segment_readers: Vec<(SegmentVersion, SegmentReader)>,
+}
+
 struct InnerIndexReader {
     doc_store_cache_num_blocks: usize,
     index: Index,
@@ -156,6 +191,8 @@ struct InnerIndexReader {
     searcher: arc_swap::ArcSwap<SearcherInner>,
     searcher_generation_counter: Arc<AtomicU64>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
+    points_in_time: PointInTimeRegistry,
+    loaded_generation: Mutex<LoadedGeneration>,
 }
 
 impl InnerIndexReader {
@@ -169,12 +206,13 @@ impl InnerIndexReader {
     ) -> crate::Result<Self> {
         let searcher_generation_counter: Arc<AtomicU64> = Default::default();
 
-        let searcher = Self::create_searcher(
+        let (searcher, loaded_generation) = Self::create_searcher(
             &index,
             doc_store_cache_num_blocks,
             &warming_state,
             &searcher_generation_counter,
             &searcher_generation_inventory,
+            &LoadedGeneration::default(),
         )?;
         Ok(InnerIndexReader {
             doc_store_cache_num_blocks,
@@ -183,21 +221,44 @@ impl InnerIndexReader {
             searcher: ArcSwap::from(searcher),
             searcher_generation_counter,
             searcher_generation_inventory,
+            points_in_time: PointInTimeRegistry::default(),
+            loaded_generation: Mutex::new(loaded_generation),
         })
     }
     /// Opens the freshest segments [`SegmentReader`].
     ///
-    /// This function acquires a lock to prevent GC from removing files
+    /// The segment readers of the previous generation are reused for the segments that did not
+    /// change.
+    ///
+    /// This function acquires a shared lock to prevent GC from removing files
     /// as we are opening our index.
-    fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
+    fn open_segment_readers(
+        index: &Index,
+        previous_generation: &LoadedGeneration,
+    ) -> crate::Result<LoadedGeneration> {
         // Prevents segment files from getting deleted while we are in the process of opening them
-        let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
-        let searchable_segments = index.searchable_segments()?;
-        let segment_readers = searchable_segments
-            .iter()
-            .map(SegmentReader::open)
+        let _meta_lock = index.directory().acquire_shared_lock(&META_LOCK)?;
+        let (index_meta, meta_checksum) = index.load_metas_with_checksum()?;
+        let segment_readers = index_meta
+            .segments
+            .into_iter()
+            .map(|segment_meta| {
+                let segment_version = SegmentVersion::from(&segment_meta);
+                let previous_segment_reader = previous_generation
+                    .segment_readers
+                    .iter()
+                    .find(|(previous_version, _)| *previous_version == segment_version);
+                let segment_reader = match previous_segment_reader {
+                    Some((_, segment_reader)) => segment_reader.clone(),
+                    None => SegmentReader::open(&index.segment(segment_meta))?,
+                };
+                Ok((segment_version, segment_reader))
+            })
             .collect::<crate::Result<_>>()?;
-        Ok(segment_readers)
+        Ok(LoadedGeneration {
+            meta_checksum: Some(meta_checksum),
+            segment_readers,
+        })
     }
 
     fn track_segment_readers_in_inventory(
@@ -217,8 +278,14 @@ impl InnerIndexReader {
         warming_state: &WarmingState,
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
-    ) -> crate::Result<Arc<SearcherInner>> {
-        let segment_readers = Self::open_segment_readers(index)?;
+        previous_generation: &LoadedGeneration,
+    ) -> crate::Result<(Arc<SearcherInner>, LoadedGeneration)> {
+        let loaded_generation = Self::open_segment_readers(index, previous_generation)?;
+        let segment_readers: Vec<SegmentReader> = loaded_generation
+            .segment_readers
+            .iter()
+            .map(|(_, segment_reader)| segment_reader.clone())
+            .collect();
         let searcher_generation = Self::track_segment_readers_in_inventory(
             &segment_readers,
             searcher_generation_counter,
@@ -235,23 +302,39 @@ impl InnerIndexReader {
         )?);
 
         warming_state.warm_new_searcher_generation(&searcher.clone().into())?;
-        Ok(searcher)
+        Ok((searcher, loaded_generation))
     }
 
     fn reload(&self) -> crate::Result<()> {
-        let searcher = Self::create_searcher(
+        let start = Instant::now();
+        let mut loaded_generation = self.loaded_generation.lock().unwrap();
+        let (searcher, new_loaded_generation) = Self::create_searcher(
             &self.index,
             self.doc_store_cache_num_blocks,
             &self.warming_state,
             &self.searcher_generation_counter,
             &self.searcher_generation_inventory,
+            &loaded_generation,
         )?;
 
         self.searcher.store(searcher);
+        *loaded_generation = new_loaded_generation;
+        self.points_in_time.remove_expired();
+        telemetry::record_reader_reload(start.elapsed());
 
         Ok(())
     }
 
+    fn reload_if_changed(&self) -> crate::Result<bool> {
+        let meta_data = self.index.directory().atomic_read(&META_FILEPATH)?;
+        let meta_checksum = crc32fast::hash(&meta_data);
+        if self.loaded_generation.lock().unwrap().meta_checksum == Some(meta_checksum) {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
     fn searcher(&self) -> Searcher {
         self.searcher.load().clone().into()
     }
@@ -263,6 +346,29 @@ impl InnerIndexReader {
 /// you instances of `Searcher` for the last loaded version.
 ///
 /// `IndexReader` just wraps an `Arc`.
+///
+/// # Sharing an index between processes
+///
+/// Any number of processes can open and search an index stored in a
+/// [`MmapDirectory`](crate::directory::MmapDirectory), while at most one process writes it.
+/// Reader processes do not need write access to the index directory, and should use
+/// [`ReloadPolicy::Manual`] along with [`IndexReader::reload_if_changed()`] to pick up the
+/// new commits.
+///
+/// This relies on the following guarantees on the way the writer modifies the index files:
+/// - Segment files are never modified once written.
+/// - The files of a commit are written and synced before the `meta.json` file referencing them is
+///   replaced.
+/// - `meta.json` is replaced atomically, by renaming a new file over it, so that readers see either
+///   the previous or the new version, never a partially written one.
+/// - Files are only deleted by the garbage collection, which holds an exclusive [`META_LOCK`] while
+///   listing the files no longer referenced by `meta.json`. Readers hold a shared [`META_LOCK`]
+///   while reading `meta.json` and opening its segments, which guarantees they never observe a
+///   `meta.json` referencing deleted files. The files of the segments a reader opened can be
+///   deleted afterwards: on Unix, memory mapped files remain readable after being deleted, while on
+///   Windows, their deletion fails and is retried by a later garbage collection.
+///
+/// On reload, the [`SegmentReader`]s of the segments that did not change are reused.
 #[derive(Clone)]
 pub struct IndexReader {
     inner: Arc<InnerIndexReader>,
@@ -288,6 +394,16 @@ impl IndexReader {
         self.inner.reload()
     }
 
+    /// Reloads the searchers if a new version of `meta.json` was committed since they were
+    /// loaded, and returns true if it was.
+    ///
+    /// Checking for a new version only reads `meta.json`, which makes it cheap enough to be
+    /// called before every search, for instance by processes that only read an index, with the
+    /// [`ReloadPolicy::Manual`] policy.
+    pub fn reload_if_changed(&self) -> crate::Result<bool> {
+        self.inner.reload_if_changed()
+    }
+
     /// Returns a searcher
     ///
     /// This method should be called every single time a search
@@ -298,4 +414,86 @@ impl IndexReader {
     pub fn searcher(&self) -> Searcher {
         self.inner.searcher()
     }
+
+    /// Pins the current searcher, so that it can be used by several successive searches, e.g.
+    /// the pages of a paginated export, regardless of the commits and merges happening in the
+    /// meantime.
+    ///
+    /// The returned point in time stays open until it is released with
+    /// [`IndexReader::release_point_in_time`], or until it has not been used for `keep_alive`.
+    /// Expired points in time are removed lazily, when the points in time of the reader are
+    /// accessed or when the reader is reloaded.
+    ///
+    /// An open point in time keeps its segments alive, which can be costly. Points in time
+    /// should be released as soon as they are not needed anymore.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use tantivy::collector::Count;
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, TEXT};
+    /// use tantivy::{doc, Index, IndexWriter, ReloadPolicy};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+    /// index_writer.commit()?;
+    ///
+    /// let reader = index
+    ///     .reader_builder()
+    ///     .reload_policy(ReloadPolicy::Manual)
+    ///     .try_into()?;
+    /// let pit_id = reader.open_point_in_time(Duration::from_secs(60));
+    ///
+    /// index_writer.add_document(doc!(title => "Of Mice and Men"))?;
+    /// index_writer.commit()?;
+    /// reader.reload()?;
+    ///
+    /// assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 2);
+    /// let pit_searcher = reader.point_in_time_searcher(pit_id).unwrap();
+    /// assert_eq!(pit_searcher.search(&AllQuery, &Count)?, 1);
+    ///
+    /// assert!(reader.release_point_in_time(pit_id));
+    /// assert!(reader.point_in_time_searcher(pit_id).is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open_point_in_time(&self, keep_alive: Duration) -> PointInTimeId {
+        self.inner
+            .points_in_time
+            .open(self.inner.searcher(), keep_alive)
+    }
+
+    /// Returns the searcher pinned by the given point in time, or `None` if the point in time
+    /// has been released or has expired.
+    ///
+    /// Fetching the searcher does not extend the lifetime of the point in time. See
+    /// [`IndexReader::keep_point_in_time_alive`].
+    pub fn point_in_time_searcher(&self, pit_id: PointInTimeId) -> Option<Searcher> {
+        self.inner.points_in_time.searcher(pit_id)
+    }
+
+    /// Extends the lifetime of a point in time, so that it expires after `keep_alive`.
+    ///
+    /// Returns false if the point in time has been released or has already expired.
+    pub fn keep_point_in_time_alive(&self, pit_id: PointInTimeId, keep_alive: Duration) -> bool {
+        self.inner.points_in_time.keep_alive(pit_id, keep_alive)
+    }
+
+    /// Releases a point in time, and the segments it keeps alive.
+    ///
+    /// Returns false if the point in time has already been released or has expired.
+    pub fn release_point_in_time(&self, pit_id: PointInTimeId) -> bool {
+        self.inner.points_in_time.release(pit_id)
+    }
+
+    /// Returns the identifiers of the points in time that are still open, sorted by opening
+    /// order.
+    pub fn point_in_time_ids(&self) -> Vec<PointInTimeId> {
+        self.inner.points_in_time.ids()
+    }
 }
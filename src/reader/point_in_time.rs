@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Searcher;
+
+/// Identifies a point in time opened with
+/// [`IndexReader::open_point_in_time`](crate::IndexReader::open_point_in_time).
+///
+/// Identifiers are unique for a given [`IndexReader`](crate::IndexReader), and are never reused.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PointInTimeId(u64);
+
+impl PointInTimeId {
+    /// Returns the identifier as a `u64`, e.g. to send it to a client.
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Builds back an identifier from its `u64` representation.
+    pub fn from_u64(id: u64) -> PointInTimeId {
+        PointInTimeId(id)
+    }
+}
+
+impl fmt::Display for PointInTimeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pit-{}", self.0)
+    }
+}
+
+struct PointInTime {
+    searcher: Searcher,
+    expires_at: Instant,
+}
+
+impl PointInTime {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Keeps the searchers pinned by the points in time of an `IndexReader` alive, until they are
+/// released or they expire.
+///
+/// Expired points in time are removed lazily, every time the registry is accessed.
+#[derive(Default)]
+pub(crate) struct PointInTimeRegistry {
+    id_counter: AtomicU64,
+    points_in_time: Mutex<HashMap<PointInTimeId, PointInTime>>,
+}
+
+impl PointInTimeRegistry {
+    fn lock_and_remove_expired(
+        &self,
+        now: Instant,
+    ) -> std::sync::MutexGuard<'_, HashMap<PointInTimeId, PointInTime>> {
+        let mut points_in_time = self
+            .points_in_time
+            .lock()
+            .expect("point in time registry lock poisoned");
+        points_in_time.retain(|_, point_in_time| !point_in_time.is_expired(now));
+        points_in_time
+    }
+
+    pub fn open(&self, searcher: Searcher, keep_alive: Duration) -> PointInTimeId {
+        let now = Instant::now();
+        let id = PointInTimeId(self.id_counter.fetch_add(1, Ordering::Relaxed));
+        let point_in_time = PointInTime {
+            searcher,
+            expires_at: now + keep_alive,
+        };
+        self.lock_and_remove_expired(now).insert(id, point_in_time);
+        id
+    }
+
+    pub fn searcher(&self, id: PointInTimeId) -> Option<Searcher> {
+        let points_in_time = self.lock_and_remove_expired(Instant::now());
+        let point_in_time = points_in_time.get(&id)?;
+        Some(point_in_time.searcher.clone())
+    }
+
+    pub fn keep_alive(&self, id: PointInTimeId, keep_alive: Duration) -> bool {
+        let now = Instant::now();
+        let mut points_in_time = self.lock_and_remove_expired(now);
+        let Some(point_in_time) = points_in_time.get_mut(&id) else {
+            return false;
+        };
+        point_in_time.expires_at = now + keep_alive;
+        true
+    }
+
+    pub fn release(&self, id: PointInTimeId) -> bool {
+        self.lock_and_remove_expired(Instant::now())
+            .remove(&id)
+            .is_some()
+    }
+
+    pub fn remove_expired(&self) {
+        drop(self.lock_and_remove_expired(Instant::now()));
+    }
+
+    pub fn ids(&self) -> Vec<PointInTimeId> {
+        let mut ids: Vec<PointInTimeId> = self
+            .lock_and_remove_expired(Instant::now())
+            .keys()
+            .copied()
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Document, Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, ReloadPolicy, TantivyDocument};
+
+    #[test]
+    fn test_point_in_time_survives_commits_and_merges() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "first"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "second"))?;
+        index_writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let pit_id = reader.open_point_in_time(Duration::from_secs(3_600));
+
+        index_writer.delete_all_documents()?;
+        index_writer.add_document(doc!(title => "third"))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.garbage_collect_files().wait()?;
+        reader.reload()?;
+
+        assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 1);
+        let pit_searcher = reader.point_in_time_searcher(pit_id).unwrap();
+        assert_eq!(pit_searcher.search(&AllQuery, &Count)?, 2);
+        let mut titles = Vec::new();
+        for segment_ord in 0..2 {
+            let doc: TantivyDocument = pit_searcher.doc(DocAddress::new(segment_ord, 0))?;
+            titles.push(doc.to_json(&index.schema()));
+        }
+        titles.sort();
+        assert_eq!(
+            titles,
+            vec![r#"{"title":["first"]}"#, r#"{"title":["second"]}"#]
+        );
+        assert_eq!(reader.point_in_time_ids(), vec![pit_id]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_point_in_time_release_and_expiry() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let reader = index.reader()?;
+        let pit_id = reader.open_point_in_time(Duration::from_secs(3_600));
+        let expired_pit_id = reader.open_point_in_time(Duration::ZERO);
+        assert_ne!(pit_id, expired_pit_id);
+        assert!(reader.point_in_time_searcher(expired_pit_id).is_none());
+        assert!(!reader.keep_point_in_time_alive(expired_pit_id, Duration::from_secs(1)));
+        assert_eq!(reader.point_in_time_ids(), vec![pit_id]);
+
+        assert!(reader.keep_point_in_time_alive(pit_id, Duration::from_secs(3_600)));
+        assert!(reader.point_in_time_searcher(pit_id).is_some());
+        assert!(reader.keep_point_in_time_alive(pit_id, Duration::ZERO));
+        assert!(reader.point_in_time_searcher(pit_id).is_none());
+        assert!(!reader.release_point_in_time(pit_id));
+
+        let pit_id = reader.open_point_in_time(Duration::from_secs(3_600));
+        assert!(reader.release_point_in_time(pit_id));
+        assert!(!reader.release_point_in_time(pit_id));
+        assert!(reader.point_in_time_ids().is_empty());
+        Ok(())
+    }
+}
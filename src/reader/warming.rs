@@ -1,19 +1,41 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ops::Deref;
 use std::sync::{Arc, Mutex, Weak};
 use std::thread::JoinHandle;
 use std::time::Duration;
 
-use crate::{Executor, Inventory, Searcher, SearcherGeneration, TantivyError};
+use crate::index::SegmentId;
+use crate::{
+    Executor, Inventory, Opstamp, Searcher, SearcherGeneration, SegmentReader, TantivyError,
+};
 
 pub const GC_INTERVAL: Duration = Duration::from_secs(1);
 
 /// `Warmer` can be used to maintain segment-level state e.g. caches.
 ///
 /// They must be registered with the [`IndexReaderBuilder`](super::IndexReaderBuilder).
+///
+/// Warming happens before the new [`Searcher`] is published: searches only start
+/// running on it once all of the warmers are done.
 pub trait Warmer: Sync + Send {
     /// Perform any warming work using the provided [`Searcher`].
-    fn warm(&self, searcher: &Searcher) -> crate::Result<()>;
+    fn warm(&self, _searcher: &Searcher) -> crate::Result<()> {
+        Ok(())
+    }
+
+    /// Perform warming work on the segment readers of the provided [`Searcher`] that were
+    /// not part of the previously warmed searcher, e.g. to pre-build bitsets or pre-load
+    /// fast field blocks.
+    ///
+    /// A segment whose deletes changed since the previous searcher is considered new.
+    /// This is called after [`Warmer::warm`].
+    fn warm_new_segments(
+        &self,
+        _searcher: &Searcher,
+        _new_segment_readers: &[&SegmentReader],
+    ) -> crate::Result<()> {
+        Ok(())
+    }
 
     /// Discards internal state for any [`SearcherGeneration`] not provided.
     fn garbage_collect(&self, live_generations: &[&SearcherGeneration]);
@@ -34,6 +56,7 @@ impl WarmingState {
             warmers,
             gc_thread: None,
             warmed_generation_ids: Default::default(),
+            last_warmed_segments: Default::default(),
             searcher_generation_inventory,
         }))))
     }
@@ -64,6 +87,8 @@ struct WarmingStateInner {
     // This list is used to avoid triggers the individual Warmer GCs
     // if no warmed generation needs to be collected.
     warmed_generation_ids: HashSet<u64>,
+    // Segments of the last warmed generation, with their delete opstamp.
+    last_warmed_segments: BTreeMap<SegmentId, Option<Opstamp>>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
 }
 
@@ -85,8 +110,22 @@ impl WarmingStateInner {
         self.start_gc_thread_maybe(this)?;
         self.warmed_generation_ids
             .insert(searcher.generation().generation_id());
-        warming_executor(self.num_warming_threads.min(warmers.len()))?
-            .map(|warmer| warmer.warm(searcher), warmers.into_iter())?;
+        let new_segment_readers: Vec<&SegmentReader> = searcher
+            .segment_readers()
+            .iter()
+            .filter(|segment_reader| {
+                self.last_warmed_segments.get(&segment_reader.segment_id())
+                    != Some(&segment_reader.delete_opstamp())
+            })
+            .collect();
+        warming_executor(self.num_warming_threads.min(warmers.len()))?.map(
+            |warmer| {
+                warmer.warm(searcher)?;
+                warmer.warm_new_segments(searcher, &new_segment_readers)
+            },
+            warmers.into_iter(),
+        )?;
+        self.last_warmed_segments = searcher.generation().segments().clone();
         Ok(())
     }
 
@@ -182,7 +221,7 @@ mod tests {
     use crate::index::SegmentId;
     use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
     use crate::schema::{Schema, INDEXED};
-    use crate::{Index, IndexSettings, ReloadPolicy, Searcher};
+    use crate::{Index, IndexSettings, IndexWriter, ReloadPolicy, Searcher, SegmentReader, Term};
 
     #[derive(Default)]
     struct TestWarmer {
@@ -247,6 +286,71 @@ mod tests {
             .collect()
     }
 
+    #[derive(Default)]
+    struct NewSegmentsWarmer {
+        new_segment_ids: RwLock<Vec<HashSet<SegmentId>>>,
+    }
+
+    impl Warmer for NewSegmentsWarmer {
+        fn warm_new_segments(
+            &self,
+            _searcher: &Searcher,
+            new_segment_readers: &[&SegmentReader],
+        ) -> crate::Result<()> {
+            let new_segment_ids = new_segment_readers
+                .iter()
+                .map(|segment_reader| segment_reader.segment_id())
+                .collect();
+            self.new_segment_ids.write().unwrap().push(new_segment_ids);
+            Ok(())
+        }
+
+        fn garbage_collect(&self, _live_generations: &[&SearcherGeneration]) {}
+    }
+
+    #[test]
+    fn test_warm_new_segments() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("pk", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        writer.add_document(doc!(field => 1u64))?;
+        writer.add_document(doc!(field => 2u64))?;
+        writer.commit()?;
+
+        let warmer = Arc::new(NewSegmentsWarmer::default());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmers(vec![Arc::downgrade(&warmer) as Weak<dyn Warmer>])
+            .try_into()?;
+        let first_segment_ids = segment_ids(&reader.searcher());
+
+        writer.add_document(doc!(field => 3u64))?;
+        writer.commit()?;
+        reader.reload()?;
+        let second_segment_ids: HashSet<SegmentId> = segment_ids(&reader.searcher())
+            .difference(&first_segment_ids)
+            .copied()
+            .collect();
+        assert_eq!(second_segment_ids.len(), 1);
+
+        // Deleting a document changes the segment readers of the first segment.
+        writer.delete_term(Term::from_field_u64(field, 1u64));
+        writer.commit()?;
+        reader.reload()?;
+
+        assert_eq!(
+            *warmer.new_segment_ids.read().unwrap(),
+            [
+                first_segment_ids.clone(),
+                second_segment_ids,
+                first_segment_ids
+            ]
+        );
+        Ok(())
+    }
+
     fn test_warming(num_warming_threads: usize) -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
         let field = schema_builder.add_u64_field("pk", INDEXED);
@@ -0,0 +1,444 @@
+//! Replication of an index from a primary to read replicas.
+//!
+//! The [`Primary`] publishes the commits of an index as [`CommitManifest`]s, listing the files
+//! of the commit, and serves the content of these files. A [`Replica`] pulls the files it does
+//! not have yet, and then atomically advances its `meta.json` to the published commit.
+//! Segment files are immutable, so only the new segments and the new delete files go over the
+//! wire, and the replicas never need to reindex the documents.
+//!
+//! The transport is left to the application: a replica pulls from any [`ReplicationSource`],
+//! which the [`Primary`] implements in-process. Over the network, the manifest can be
+//! serialized with serde, and the file ranges served as raw bytes.
+//!
+//! ```rust
+//! use tantivy::directory::RamDirectory;
+//! use tantivy::replication::{Primary, Replica};
+//! use tantivy::schema::{Schema, TEXT};
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let text = schema_builder.add_text_field("text", TEXT);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(text => "hello"))?;
+//! index_writer.commit()?;
+//!
+//! let primary = Primary::new(index);
+//! primary.publish()?;
+//!
+//! let replica_directory = RamDirectory::create();
+//! let mut replica = Replica::open(replica_directory.clone())?;
+//! replica.sync(&primary)?;
+//! let replica_index = Index::open(replica_directory)?;
+//! assert_eq!(replica_index.reader()?.searcher().num_docs(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use common::{HasLen, OwnedBytes};
+use serde::{Deserialize, Serialize};
+
+use crate::core::META_FILEPATH;
+use crate::directory::{
+    Directory, DirectoryLock, FileSlice, ManagedDirectory, TerminatingWrite, INDEX_WRITER_LOCK,
+};
+use crate::error::DataCorruption;
+use crate::indexer::backup::snapshot_last_commit;
+use crate::{Index, Opstamp, TantivyError};
+
+/// Number of commits a [`Primary`] keeps serving by default.
+const DEFAULT_NUM_RETAINED_COMMITS: usize = 2;
+
+/// Files are fetched by chunks of 1MB.
+const FETCH_CHUNK_NUM_BYTES: u64 = 1 << 20;
+
+/// A file of a published commit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicatedFile {
+    /// The path of the file in the index directory.
+    pub path: PathBuf,
+    /// The number of bytes of the file, footer excluded.
+    pub num_bytes: u64,
+    /// The crc32 of the content of the file, footer excluded.
+    pub checksum: u32,
+}
+
+/// Describes a commit published by a [`Primary`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitManifest {
+    /// The opstamp of the commit.
+    pub opstamp: Opstamp,
+    /// The files of the commit.
+    pub files: Vec<ReplicatedFile>,
+    /// The content of the `meta.json` file of the commit.
+    meta: String,
+}
+
+/// Source a [`Replica`] pulls the commits from.
+pub trait ReplicationSource: Send + Sync {
+    /// Returns the manifest of the latest published commit.
+    fn latest_manifest(&self) -> crate::Result<CommitManifest>;
+
+    /// Reads a range of bytes of a file of a published commit, footer excluded.
+    fn read_file_range(&self, path: &Path, byte_range: Range<u64>) -> crate::Result<OwnedBytes>;
+}
+
+struct PublishedCommit {
+    manifest: CommitManifest,
+    file_slices: BTreeMap<PathBuf, FileSlice>,
+}
+
+#[derive(Default)]
+struct PrimaryState {
+    /// The published commits, oldest first.
+    published_commits: VecDeque<PublishedCommit>,
+    /// The checksums of the files published so far. Files are immutable, so they only need
+    /// to be computed once.
+    checksums: HashMap<PathBuf, u32>,
+}
+
+/// Publishes the commits of an index to its replicas.
+///
+/// The files of the last published commits are kept open, so that replicas can finish
+/// pulling a commit even after the index moved on and garbage collected them.
+pub struct Primary {
+    index: Index,
+    num_retained_commits: usize,
+    state: Mutex<PrimaryState>,
+}
+
+impl Primary {
+    /// Creates a primary for the given index, serving the last 2 published commits.
+    pub fn new(index: Index) -> Primary {
+        Primary::with_num_retained_commits(index, DEFAULT_NUM_RETAINED_COMMITS)
+    }
+
+    /// Creates a primary for the given index, serving the last `num_retained_commits`
+    /// published commits.
+    ///
+    /// Retaining more commits lets slow replicas finish their pull, at the expense of the
+    /// disk space of the files that would otherwise be garbage collected.
+    pub fn with_num_retained_commits(index: Index, num_retained_commits: usize) -> Primary {
+        Primary {
+            index,
+            num_retained_commits: num_retained_commits.max(1),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Publishes the last commit of the index, and returns its manifest.
+    ///
+    /// Publishing the commit that was already published last is a no-op.
+    pub fn publish(&self) -> crate::Result<CommitManifest> {
+        let (index_meta, meta, file_slices) = snapshot_last_commit(&self.index)?;
+        let mut state = self.state.lock().unwrap();
+        if let Some(published_commit) = state.published_commits.back() {
+            if published_commit.manifest.meta == meta {
+                return Ok(published_commit.manifest.clone());
+            }
+        }
+        let mut files = Vec::with_capacity(file_slices.len());
+        for (path, file_slice) in &file_slices {
+            let checksum = match state.checksums.get(path) {
+                Some(checksum) => *checksum,
+                None => crc32fast::hash(file_slice.read_bytes()?.as_slice()),
+            };
+            files.push(ReplicatedFile {
+                path: path.clone(),
+                num_bytes: file_slice.len() as u64,
+                checksum,
+            });
+        }
+        let manifest = CommitManifest {
+            opstamp: index_meta.opstamp,
+            files,
+            meta,
+        };
+        state.published_commits.push_back(PublishedCommit {
+            manifest: manifest.clone(),
+            file_slices,
+        });
+        while state.published_commits.len() > self.num_retained_commits {
+            state.published_commits.pop_front();
+        }
+        // Only the checksums of the retained files are kept.
+        let retained_files: HashMap<PathBuf, u32> = state
+            .published_commits
+            .iter()
+            .flat_map(|published_commit| &published_commit.manifest.files)
+            .map(|file| (file.path.clone(), file.checksum))
+            .collect();
+        state.checksums = retained_files;
+        info!("Published opstamp {}", manifest.opstamp);
+        Ok(manifest)
+    }
+}
+
+impl ReplicationSource for Primary {
+    fn latest_manifest(&self) -> crate::Result<CommitManifest> {
+        let state = self.state.lock().unwrap();
+        match state.published_commits.back() {
+            Some(published_commit) => Ok(published_commit.manifest.clone()),
+            None => {
+                drop(state);
+                self.publish()
+            }
+        }
+    }
+
+    fn read_file_range(&self, path: &Path, byte_range: Range<u64>) -> crate::Result<OwnedBytes> {
+        let state = self.state.lock().unwrap();
+        let file_slice = state
+            .published_commits
+            .iter()
+            .rev()
+            .find_map(|published_commit| published_commit.file_slices.get(path))
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "The file {path:?} is not part of a published commit"
+                ))
+            })?;
+        let bytes =
+            file_slice.read_bytes_slice(byte_range.start as usize..byte_range.end as usize)?;
+        Ok(bytes)
+    }
+}
+
+/// Outcome of [`Replica::sync()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplicaSyncReport {
+    /// The opstamp of the commit the replica is at.
+    pub opstamp: Opstamp,
+    /// True if the replica advanced to a new commit.
+    pub is_updated: bool,
+    /// The number of files fetched from the source.
+    pub num_files_fetched: usize,
+    /// The number of bytes fetched from the source.
+    pub num_bytes_fetched: u64,
+}
+
+/// Keeps a copy of an index up to date with a [`ReplicationSource`].
+///
+/// The replica holds the [`INDEX_WRITER_LOCK`] of its directory, so that no
+/// [`IndexWriter`](crate::IndexWriter) can modify it. The replicated index is read by
+/// opening an [`Index`] on the same directory, possibly from other processes; its readers
+/// pick up the new commits like they would pick up the commits of a writer.
+pub struct Replica {
+    directory: ManagedDirectory,
+    _directory_lock: DirectoryLock,
+}
+
+impl Replica {
+    /// Opens a replica in the given directory, which is either empty or holds a replica
+    /// of the index.
+    pub fn open<T: Into<Box<dyn Directory>>>(directory: T) -> crate::Result<Replica> {
+        let directory: Box<dyn Directory> = directory.into();
+        let directory_lock = directory.acquire_lock(&INDEX_WRITER_LOCK).map_err(|err| {
+            TantivyError::LockFailure(
+                err,
+                Some(
+                    "Failed to acquire index lock. If you are using a regular directory, this \
+                     means there is already an `IndexWriter` or a `Replica` working on this \
+                     `Directory`, in this process or in a different process."
+                        .to_string(),
+                ),
+            )
+        })?;
+        Ok(Replica {
+            directory: ManagedDirectory::wrap(directory)?,
+            _directory_lock: directory_lock,
+        })
+    }
+
+    /// Returns true if the replica already has the file, fully written.
+    fn has_file(&self, file: &ReplicatedFile) -> crate::Result<bool> {
+        if !self.directory.exists(&file.path)? {
+            return Ok(false);
+        }
+        // A file left over by a sync that failed midway is truncated.
+        match self.directory.open_read(&file.path) {
+            Ok(file_slice) if file_slice.len() as u64 == file.num_bytes => Ok(true),
+            _ => {
+                warn!("Discarding the incomplete file {:?}", file.path);
+                self.directory.delete(&file.path).map_err(|err| {
+                    TantivyError::InternalError(format!("Failed to delete {:?}: {err}", file.path))
+                })?;
+                Ok(false)
+            }
+        }
+    }
+
+    fn fetch_file(
+        &self,
+        source: &dyn ReplicationSource,
+        file: &ReplicatedFile,
+    ) -> crate::Result<()> {
+        let mut write = self.directory.open_write(&file.path)?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut offset = 0;
+        while offset < file.num_bytes {
+            let chunk_end = file.num_bytes.min(offset + FETCH_CHUNK_NUM_BYTES);
+            let chunk = source.read_file_range(&file.path, offset..chunk_end)?;
+            hasher.update(chunk.as_slice());
+            write.write_all(chunk.as_slice())?;
+            offset = chunk_end;
+        }
+        if hasher.finalize() != file.checksum {
+            drop(write);
+            let _ = self.directory.delete(&file.path);
+            return Err(DataCorruption::new(
+                file.path.clone(),
+                "The fetched file does not match the checksum of the manifest.".to_string(),
+            )
+            .into());
+        }
+        write.terminate()?;
+        Ok(())
+    }
+
+    /// Pulls the latest commit of the source.
+    ///
+    /// The missing files are fetched and written first, and `meta.json` is then replaced
+    /// atomically: readers either see the previous commit or the new one. The files that are
+    /// no longer used are garbage collected.
+    pub fn sync(&mut self, source: &dyn ReplicationSource) -> crate::Result<ReplicaSyncReport> {
+        let manifest = source.latest_manifest()?;
+        let mut report = ReplicaSyncReport {
+            opstamp: manifest.opstamp,
+            is_updated: false,
+            num_files_fetched: 0,
+            num_bytes_fetched: 0,
+        };
+        if let Ok(current_meta) = self.directory.atomic_read(&META_FILEPATH) {
+            if current_meta == manifest.meta.as_bytes() {
+                return Ok(report);
+            }
+        }
+        for file in &manifest.files {
+            if self.has_file(file)? {
+                continue;
+            }
+            self.fetch_file(source, file)?;
+            report.num_files_fetched += 1;
+            report.num_bytes_fetched += file.num_bytes;
+        }
+        self.directory.sync_directory()?;
+        self.directory
+            .atomic_write(&META_FILEPATH, manifest.meta.as_bytes())?;
+        report.is_updated = true;
+        info!(
+            "Replica advanced to opstamp {}, fetching {} files",
+            manifest.opstamp, report.num_files_fetched
+        );
+
+        let living_files: HashSet<PathBuf> = manifest
+            .files
+            .iter()
+            .map(|file| file.path.clone())
+            .chain(std::iter::once(META_FILEPATH.to_path_buf()))
+            .collect();
+        if let Err(err) = self.directory.garbage_collect(|| living_files) {
+            warn!("Failed to garbage collect the replica: {err:?}");
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Primary, Replica, ReplicationSource};
+    use crate::directory::{Directory, RamDirectory};
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, Term};
+
+    #[test]
+    fn test_replication() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        let primary = Primary::new(index.clone());
+
+        let replica_directory = RamDirectory::create();
+        let mut replica = Replica::open(replica_directory.clone())?;
+        let report = replica.sync(&primary)?;
+        assert!(report.is_updated);
+        assert!(report.num_files_fetched > 0);
+        let replica_index = Index::open(replica_directory.clone())?;
+        let reader = replica_index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+
+        // Nothing to do until a new commit is published.
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        assert!(!replica.sync(&primary)?.is_updated);
+
+        primary.publish()?;
+        let report = replica.sync(&primary)?;
+        assert!(report.is_updated);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(reader.searcher().segment_readers().len(), 2);
+
+        // After a merge, the files of the former segments are garbage collected.
+        let former_files = primary.latest_manifest()?.files;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        primary.publish()?;
+        replica.sync(&primary)?;
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        assert_eq!(reader.searcher().segment_readers().len(), 1);
+        for file in former_files {
+            assert!(!replica_directory.exists(&file.path)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_replica_lock() -> crate::Result<()> {
+        let replica_directory = RamDirectory::create();
+        let _replica = Replica::open(replica_directory.clone())?;
+        assert!(Replica::open(replica_directory).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_primary_retains_files() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        let primary = Primary::with_num_retained_commits(index.clone(), 1);
+        let manifest = primary.publish()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        // The files of the published commit were garbage collected by the index, but are
+        // still served by the primary.
+        let file = &manifest.files[0];
+        assert!(!index.directory().exists(&file.path)?);
+        let bytes = primary.read_file_range(&file.path, 0..file.num_bytes)?;
+        assert_eq!(bytes.len() as u64, file.num_bytes);
+        primary.publish()?;
+        assert!(primary.read_file_range(&file.path, 0..1).is_err());
+        Ok(())
+    }
+}
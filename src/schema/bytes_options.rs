@@ -11,6 +11,10 @@ pub struct BytesOptions {
     fieldnorms: bool,
     fast: bool,
     stored: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fixed_width: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blob_threshold: Option<usize>,
 }
 
 /// For backward compatibility we add an intermediary to interpret the
@@ -25,6 +29,10 @@ struct BytesOptionsDeser {
     fieldnorms: Option<bool>,
     fast: bool,
     stored: bool,
+    #[serde(default)]
+    fixed_width: Option<usize>,
+    #[serde(default)]
+    blob_threshold: Option<usize>,
 }
 
 impl From<BytesOptionsDeser> for BytesOptions {
@@ -34,6 +42,8 @@ impl From<BytesOptionsDeser> for BytesOptions {
             fieldnorms: deser.fieldnorms.unwrap_or(deser.indexed),
             fast: deser.fast,
             stored: deser.stored,
+            fixed_width: deser.fixed_width,
+            blob_threshold: deser.blob_threshold,
         }
     }
 }
@@ -63,6 +73,19 @@ impl BytesOptions {
         self.stored
     }
 
+    /// Returns the width of the values, if they are declared as fixed-width.
+    #[inline]
+    pub fn fixed_width(&self) -> Option<usize> {
+        self.fixed_width
+    }
+
+    /// Returns the size above which the stored values are spilled into the blob file of the
+    /// segment. See [`BytesOptions::set_blob_threshold`].
+    #[inline]
+    pub fn blob_threshold(&self) -> Option<usize> {
+        self.blob_threshold
+    }
+
     /// Set the field as indexed.
     ///
     /// Setting an integer as indexed will generate
@@ -101,6 +124,35 @@ impl BytesOptions {
         self.stored = true;
         self
     }
+
+    /// Set the field as stored, and spills the values longer than `num_bytes` bytes into a
+    /// separate blob file instead of the doc store blocks.
+    #[must_use]
+    pub fn set_blob_threshold(mut self, num_bytes: usize) -> BytesOptions {
+        self.stored = true;
+        self.blob_threshold = Some(num_bytes);
+        self
+    }
+
+    /// Declares that all of the values of the field are exactly `width` bytes long.
+    ///
+    /// The fast field then stores the values in a 128-bit column instead of a
+    /// dictionary encoded one, which is much more compact for high cardinality
+    /// values like UUIDs or hashes, and supports range queries.
+    /// Adding a value of a different width fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is not within `[1, 16]`.
+    #[must_use]
+    pub fn set_fixed_width(mut self, width: usize) -> BytesOptions {
+        assert!(
+            (1..=16).contains(&width),
+            "The width of fixed-width bytes needs to be within [1, 16], got {width}"
+        );
+        self.fixed_width = Some(width);
+        self
+    }
 }
 
 impl<T: Into<BytesOptions>> BitOr<T> for BytesOptions {
@@ -113,6 +165,8 @@ impl<T: Into<BytesOptions>> BitOr<T> for BytesOptions {
             fieldnorms: self.fieldnorms | other.fieldnorms,
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
+            fixed_width: other.fixed_width.or(self.fixed_width),
+            blob_threshold: other.blob_threshold.or(self.blob_threshold),
         }
     }
 }
@@ -130,6 +184,8 @@ impl From<FastFlag> for BytesOptions {
             fieldnorms: false,
             stored: false,
             fast: true,
+            fixed_width: None,
+            blob_threshold: None,
         }
     }
 }
@@ -141,6 +197,8 @@ impl From<StoredFlag> for BytesOptions {
             fieldnorms: false,
             stored: true,
             fast: false,
+            fixed_width: None,
+            blob_threshold: None,
         }
     }
 }
@@ -152,6 +210,8 @@ impl From<IndexedFlag> for BytesOptions {
             fieldnorms: true,
             stored: false,
             fast: false,
+            fixed_width: None,
+            blob_threshold: None,
         }
     }
 }
@@ -228,7 +288,9 @@ mod tests {
                 indexed: true,
                 fieldnorms: true,
                 fast: false,
-                stored: false
+                stored: false,
+                fixed_width: None,
+                blob_threshold: None,
             }
         );
     }
@@ -247,7 +309,9 @@ mod tests {
                 indexed: false,
                 fieldnorms: false,
                 fast: false,
-                stored: false
+                stored: false,
+                fixed_width: None,
+                blob_threshold: None,
             }
         );
     }
@@ -267,7 +331,9 @@ mod tests {
                 indexed: true,
                 fieldnorms: false,
                 fast: false,
-                stored: false
+                stored: false,
+                fixed_width: None,
+                blob_threshold: None,
             }
         );
     }
@@ -288,8 +354,33 @@ mod tests {
                 indexed: false,
                 fieldnorms: true,
                 fast: false,
-                stored: false
+                stored: false,
+                fixed_width: None,
+                blob_threshold: None,
             }
         );
     }
+
+    #[test]
+    fn test_bytes_options_fixed_width() {
+        let bytes_options = BytesOptions::from(FAST).set_fixed_width(16);
+        assert_eq!(bytes_options.fixed_width(), Some(16));
+        assert_eq!((bytes_options.clone() | STORED).fixed_width(), Some(16));
+        let json = serde_json::to_string(&bytes_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"indexed":false,"fieldnorms":false,"fast":true,"stored":false,"fixed_width":16}"#
+        );
+        let deser_bytes_options: BytesOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_bytes_options, bytes_options);
+        // The width is omitted for regular bytes fields.
+        let json = serde_json::to_string(&BytesOptions::from(FAST)).unwrap();
+        assert!(!json.contains("fixed_width"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bytes_options_fixed_width_too_large() {
+        let _ = BytesOptions::default().set_fixed_width(17);
+    }
 }
@@ -4,6 +4,7 @@ pub use common::DateTimePrecision;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+use crate::schema::FastFieldCodec;
 
 /// The precision of the indexed date/time values in the inverted index.
 pub const DATE_TIME_PRECISION_INDEXED: DateTimePrecision = DateTimePrecision::Seconds;
@@ -21,6 +22,8 @@ pub struct DateOptions {
     // compression on fast fields.
     #[serde(default)]
     precision: DateTimePrecision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fast_codec: Option<FastFieldCodec>,
 }
 
 impl DateOptions {
@@ -106,6 +109,20 @@ impl DateOptions {
     pub fn get_precision(&self) -> DateTimePrecision {
         self.precision
     }
+
+    /// Sets the codec encoding the fast field values, instead of picking the most compact
+    /// codec automatically.
+    #[must_use]
+    pub fn set_fast_codec(mut self, codec: FastFieldCodec) -> DateOptions {
+        self.fast_codec = Some(codec);
+        self
+    }
+
+    /// Returns the codec encoding the fast field values, if it is not picked automatically.
+    #[inline]
+    pub fn get_fast_codec(&self) -> Option<FastFieldCodec> {
+        self.fast_codec
+    }
 }
 
 impl From<()> for DateOptions {
@@ -153,6 +170,7 @@ impl<T: Into<DateOptions>> BitOr<T> for DateOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             precision: self.precision,
+            fast_codec: other.fast_codec.or(self.fast_codec),
         }
     }
 }
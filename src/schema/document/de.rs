@@ -13,6 +13,7 @@ use std::io;
 use std::io::Read;
 use std::marker::PhantomData;
 use std::net::Ipv6Addr;
+use std::ops::Range;
 use std::sync::Arc;
 
 use columnar::MonotonicallyMappableToU128;
@@ -20,9 +21,10 @@ use common::{u64_to_f64, BinarySerializable, DateTime, VInt};
 
 use super::se::BinaryObjectSerializer;
 use super::{OwnedValue, Value};
+use crate::directory::FileSlice;
 use crate::schema::document::type_codes;
 use crate::schema::{Facet, Field};
-use crate::store::DocStoreVersion;
+use crate::store::{DocStoreVersion, JsonPathFilter};
 use crate::tokenizer::PreTokenizedString;
 
 #[derive(Debug, thiserror::Error, Clone)]
@@ -297,6 +299,12 @@ pub struct BinaryDocumentDeserializer<'de, R> {
     position: usize,
     doc_store_version: DocStoreVersion,
     reader: &'de mut R,
+    /// If set, the values of the other fields are skipped.
+    fields_to_load: Option<&'de [Field]>,
+    /// If set, the object values of the JSON field are filtered.
+    json_filter: Option<(Field, &'de JsonPathFilter)>,
+    /// The blob file holding the values spilled out of the document.
+    blobs: Option<&'de FileSlice>,
 }
 
 impl<'de, R> BinaryDocumentDeserializer<'de, R>
@@ -314,9 +322,36 @@ where R: Read
             position: 0,
             doc_store_version,
             reader,
+            fields_to_load: None,
+            json_filter: None,
+            blobs: None,
         })
     }
 
+    /// Reads the values spilled out of the document from `blobs`.
+    pub(crate) fn with_blobs(mut self, blobs: &'de FileSlice) -> Self {
+        self.blobs = Some(blobs);
+        self
+    }
+
+    /// Only deserializes the values of `fields`. The values of the other fields are skipped
+    /// without being decoded.
+    pub(crate) fn with_fields_to_load(mut self, fields: &'de [Field]) -> Self {
+        self.fields_to_load = Some(fields);
+        self
+    }
+
+    /// Only deserializes the paths of the object values of `json_field` selected by `filter`.
+    /// The values of the other paths are skipped without being decoded.
+    pub(crate) fn with_json_filter(
+        mut self,
+        json_field: Field,
+        filter: &'de JsonPathFilter,
+    ) -> Self {
+        self.json_filter = Some((json_field, filter));
+        self
+    }
+
     /// Returns true if the deserializer has deserialized all the entries
     /// within the document.
     fn is_complete(&self) -> bool {
@@ -333,18 +368,203 @@ where R: Read
     }
 
     fn next_field<V: ValueDeserialize>(&mut self) -> Result<Option<(Field, V)>, DeserializeError> {
-        if self.is_complete() {
-            return Ok(None);
+        loop {
+            if self.is_complete() {
+                return Ok(None);
+            }
+
+            let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
+            let mut deserializer =
+                BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+            deserializer.blobs = self.blobs;
+
+            self.position += 1;
+
+            if let Some(fields_to_load) = self.fields_to_load {
+                if !fields_to_load.contains(&field) {
+                    deserializer.skip()?;
+                    continue;
+                }
+            }
+            if let Some((json_field, json_filter)) = self.json_filter {
+                if field == json_field
+                    && deserializer.value_type == ValueType::Object
+                    && deserializer.blob_range.is_none()
+                {
+                    let mut filtered_object = Vec::new();
+                    let is_kept = filter_json_object(
+                        deserializer.reader,
+                        self.doc_store_version,
+                        "",
+                        json_filter,
+                        &mut filtered_object,
+                    )?;
+                    if !is_kept {
+                        continue;
+                    }
+                    let mut filtered_object_bytes: &[u8] = &filtered_object;
+                    let deserializer = BinaryValueDeserializer::from_reader(
+                        &mut filtered_object_bytes,
+                        self.doc_store_version,
+                    )?;
+                    let value = V::deserialize(deserializer)?;
+                    return Ok(Some((field, value)));
+                }
+            }
+            let value = V::deserialize(deserializer)?;
+            return Ok(Some((field, value)));
         }
+    }
+}
 
-        let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
-        let deserializer =
-            BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
-        let value = V::deserialize(deserializer)?;
+/// Copies the bytes read from `reader` to `output`.
+struct TeeReader<'a, R> {
+    reader: &'a mut R,
+    output: &'a mut Vec<u8>,
+}
 
-        self.position += 1;
+impl<R: Read> Read for TeeReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let num_bytes = self.reader.read(buf)?;
+        self.output.extend_from_slice(&buf[..num_bytes]);
+        Ok(num_bytes)
+    }
+}
+
+/// Reads an object, whose type code has already been read, and writes the entries kept by
+/// `filter` to `output`, as a serialized object.
+///
+/// Returns false, and writes nothing, if the whole object is filtered out.
+fn filter_json_object<R: Read>(
+    reader: &mut R,
+    doc_store_version: DocStoreVersion,
+    path: &str,
+    filter: &JsonPathFilter,
+    output: &mut Vec<u8>,
+) -> Result<bool, DeserializeError> {
+    // Objects are stored as an array of keys and values.
+    let num_elements = VInt::deserialize(reader)?.val();
+    let mut entries = Vec::new();
+    let mut num_kept_entries = 0u64;
+    for _ in 0..num_elements / 2 {
+        let mut entry = Vec::new();
+        let key = BinaryValueDeserializer::from_reader(
+            &mut TeeReader {
+                reader: &mut *reader,
+                output: &mut entry,
+            },
+            doc_store_version,
+        )?
+        .deserialize_string()?;
+        let child_path = if path.is_empty() {
+            key
+        } else {
+            format!("{path}.{key}")
+        };
+        if filter_json_value(reader, doc_store_version, &child_path, filter, &mut entry)? {
+            entries.extend_from_slice(&entry);
+            num_kept_entries += 1;
+        }
+    }
+    if num_kept_entries == 0 && (num_elements > 0 || !filter.is_included(path)) {
+        return Ok(false);
+    }
+    output.push(type_codes::OBJECT_CODE);
+    VInt(num_kept_entries * 2).serialize(output)?;
+    output.extend_from_slice(&entries);
+    Ok(true)
+}
+
+/// Reads an array, whose type code has already been read, and writes the elements kept by
+/// `filter` to `output`, as a serialized array.
+///
+/// Returns false, and writes nothing, if the whole array is filtered out.
+fn filter_json_array<R: Read>(
+    reader: &mut R,
+    doc_store_version: DocStoreVersion,
+    path: &str,
+    filter: &JsonPathFilter,
+    output: &mut Vec<u8>,
+) -> Result<bool, DeserializeError> {
+    let num_elements = VInt::deserialize(reader)?.val();
+    let mut elements = Vec::new();
+    let mut num_kept_elements = 0u64;
+    for _ in 0..num_elements {
+        if filter_json_value(reader, doc_store_version, path, filter, &mut elements)? {
+            num_kept_elements += 1;
+        }
+    }
+    if num_kept_elements == 0 && (num_elements > 0 || !filter.is_included(path)) {
+        return Ok(false);
+    }
+    output.push(type_codes::ARRAY_CODE);
+    VInt(num_kept_elements).serialize(output)?;
+    output.extend_from_slice(&elements);
+    Ok(true)
+}
 
-        Ok(Some((field, value)))
+/// Reads the value at `path` and writes it to `output` if it is kept by `filter`.
+///
+/// Values are only decoded as far as needed to apply the filter: excluded values are skipped
+/// and included values are copied.
+fn filter_json_value<R: Read>(
+    reader: &mut R,
+    doc_store_version: DocStoreVersion,
+    path: &str,
+    filter: &JsonPathFilter,
+    output: &mut Vec<u8>,
+) -> Result<bool, DeserializeError> {
+    let is_included = filter.is_included(path);
+    if filter.is_excluded(path) || (!is_included && !filter.may_include_children(path)) {
+        BinaryValueDeserializer::from_reader(reader, doc_store_version)?.skip()?;
+        return Ok(false);
+    }
+    if is_included && !filter.may_exclude_children(path) {
+        let mut tee_reader = TeeReader { reader, output };
+        BinaryValueDeserializer::from_reader(&mut tee_reader, doc_store_version)?.skip()?;
+        return Ok(true);
+    }
+    let mut header = Vec::new();
+    let mut header_reader = TeeReader {
+        reader: &mut *reader,
+        output: &mut header,
+    };
+    let header_deserializer =
+        BinaryValueDeserializer::from_reader(&mut header_reader, doc_store_version)?;
+    let (value_type, blob_range) = (
+        header_deserializer.value_type,
+        header_deserializer.blob_range,
+    );
+    match value_type {
+        ValueType::Object if blob_range.is_none() => {
+            filter_json_object(reader, doc_store_version, path, filter, output)
+        }
+        ValueType::Array if blob_range.is_none() => {
+            filter_json_array(reader, doc_store_version, path, filter, output)
+        }
+        _ if is_included => {
+            output.extend_from_slice(&header);
+            BinaryValueDeserializer {
+                value_type,
+                reader: &mut TeeReader { reader, output },
+                doc_store_version,
+                blob_range,
+                blobs: None,
+            }
+            .skip()?;
+            Ok(true)
+        }
+        _ => {
+            BinaryValueDeserializer {
+                value_type,
+                reader,
+                doc_store_version,
+                blob_range,
+                blobs: None,
+            }
+            .skip()?;
+            Ok(false)
+        }
     }
 }
 
@@ -354,6 +574,9 @@ pub struct BinaryValueDeserializer<'de, R> {
     value_type: ValueType,
     reader: &'de mut R,
     doc_store_version: DocStoreVersion,
+    /// Set if the value is stored in the blob file, at the given range.
+    blob_range: Option<Range<u64>>,
+    blobs: Option<&'de FileSlice>,
 }
 
 impl<'de, R> BinaryValueDeserializer<'de, R>
@@ -366,6 +589,7 @@ where R: Read
     ) -> Result<Self, DeserializeError> {
         let type_code = <u8 as BinarySerializable>::deserialize(reader)?;
 
+        let mut blob_range = None;
         let value_type = match type_code {
             type_codes::TEXT_CODE => ValueType::String,
             type_codes::U64_CODE => ValueType::U64,
@@ -380,6 +604,25 @@ where R: Read
 
                 match ext_type_code {
                     type_codes::TOK_STR_EXT_CODE => ValueType::PreTokStr,
+                    type_codes::BLOB_EXT_CODE => {
+                        let value_type = match <u8 as BinarySerializable>::deserialize(reader)? {
+                            type_codes::TEXT_CODE => ValueType::String,
+                            type_codes::BYTES_CODE => ValueType::Bytes,
+                            blob_type_code => {
+                                return Err(DeserializeError::from(io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    format!(
+                                        "No blob value type is associated with code \
+                                         {blob_type_code:?}"
+                                    ),
+                                )))
+                            }
+                        };
+                        let blob_start = VInt::deserialize(reader)?.val();
+                        let blob_len = VInt::deserialize(reader)?.val();
+                        blob_range = Some(blob_start..blob_start + blob_len);
+                        value_type
+                    }
                     _ => {
                         return Err(DeserializeError::from(io::Error::new(
                             io::ErrorKind::InvalidData,
@@ -408,9 +651,63 @@ where R: Read
             value_type,
             reader,
             doc_store_version,
+            blob_range,
+            blobs: None,
         })
     }
 
+    /// Reads the value from the blob file, if it was spilled out of the document.
+    fn read_blob(&self) -> Result<Option<Vec<u8>>, DeserializeError> {
+        let Some(blob_range) = self.blob_range.clone() else {
+            return Ok(None);
+        };
+        let blobs = self.blobs.ok_or_else(|| {
+            DeserializeError::custom("The value is stored in a blob file, which is unavailable")
+        })?;
+        let blob = blobs.read_bytes_slice(blob_range.start as usize..blob_range.end as usize)?;
+        Ok(Some(blob.as_slice().to_vec()))
+    }
+
+    /// Advances the reader past the value, without decoding it.
+    fn skip(self) -> Result<(), DeserializeError> {
+        if self.blob_range.is_some() {
+            // The reference to the blob has already been read.
+            return Ok(());
+        }
+        let num_bytes: u64 = match self.value_type {
+            ValueType::Null => 0,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 | ValueType::DateTime => 8,
+            ValueType::Bool => 1,
+            ValueType::IpAddr => 16,
+            ValueType::String | ValueType::Facet | ValueType::Bytes | ValueType::PreTokStr => {
+                VInt::deserialize(self.reader)?.val()
+            }
+            ValueType::Array | ValueType::Object => {
+                // Objects are stored as an array of keys and values.
+                let num_elements = VInt::deserialize(self.reader)?.val();
+                for _ in 0..num_elements {
+                    BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?
+                        .skip()?;
+                }
+                0
+            }
+            #[allow(deprecated)]
+            ValueType::JSONObject => {
+                let mut de = serde_json::Deserializer::from_reader(&mut *self.reader);
+                <serde::de::IgnoredAny as serde::Deserialize>::deserialize(&mut de)
+                    .map_err(|err| DeserializeError::Custom(err.to_string()))?;
+                0
+            }
+        };
+        let num_skipped_bytes = io::copy(&mut self.reader.take(num_bytes), &mut io::sink())?;
+        if num_skipped_bytes != num_bytes {
+            return Err(DeserializeError::from(io::Error::from(
+                io::ErrorKind::UnexpectedEof,
+            )));
+        }
+        Ok(())
+    }
+
     fn validate_type(&self, expected_type: ValueType) -> Result<(), DeserializeError> {
         if self.value_type == expected_type {
             Ok(())
@@ -433,6 +730,11 @@ where R: Read
 
     fn deserialize_string(self) -> Result<String, DeserializeError> {
         self.validate_type(ValueType::String)?;
+        if let Some(blob) = self.read_blob()? {
+            return String::from_utf8(blob).map_err(|err| {
+                DeserializeError::from(io::Error::new(io::ErrorKind::InvalidData, err))
+            });
+        }
         <String as BinarySerializable>::deserialize(self.reader).map_err(DeserializeError::from)
     }
 
@@ -474,6 +776,9 @@ where R: Read
 
     fn deserialize_bytes(self) -> Result<Vec<u8>, DeserializeError> {
         self.validate_type(ValueType::Bytes)?;
+        if let Some(blob) = self.read_blob()? {
+            return Ok(blob);
+        }
         <Vec<u8> as BinarySerializable>::deserialize(self.reader).map_err(DeserializeError::from)
     }
 
@@ -271,4 +271,5 @@ pub(crate) mod type_codes {
 
     // Extended type codes
     pub const TOK_STR_EXT_CODE: u8 = 0;
+    pub const BLOB_EXT_CODE: u8 = 1;
 }
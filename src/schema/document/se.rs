@@ -1,11 +1,13 @@
 use std::borrow::Cow;
 use std::io;
 use std::io::Write;
+use std::ops::Range;
 
 use columnar::MonotonicallyMappableToU128;
-use common::{f64_to_u64, BinarySerializable, VInt};
+use common::{f64_to_u64, BinarySerializable, CountingWriter, VInt};
 
 use super::{OwnedValue, ReferenceValueLeaf};
+use crate::directory::WritePtr;
 use crate::schema::document::{type_codes, Document, ReferenceValue, Value};
 use crate::schema::Schema;
 
@@ -13,6 +15,10 @@ use crate::schema::Schema;
 pub struct BinaryDocumentSerializer<'se, W> {
     writer: &'se mut W,
     schema: &'se Schema,
+    /// If set, the values above the blob threshold of their field are written there.
+    blob_writer: Option<&'se mut CountingWriter<WritePtr>>,
+    /// If set, the values of the fields that are not stored are serialized too.
+    include_unstored_fields: bool,
 }
 
 impl<'se, W> BinaryDocumentSerializer<'se, W>
@@ -20,7 +26,28 @@ where W: Write
 {
     /// Creates a new serializer with a provided writer.
     pub(crate) fn new(writer: &'se mut W, schema: &'se Schema) -> Self {
-        Self { writer, schema }
+        Self {
+            writer,
+            schema,
+            blob_writer: None,
+            include_unstored_fields: false,
+        }
+    }
+
+    /// Serializes the values of all of the fields, including the fields that are not stored.
+    pub(crate) fn with_unstored_fields(mut self) -> Self {
+        self.include_unstored_fields = true;
+        self
+    }
+
+    /// Spills the values larger than the blob threshold of their field into `blob_writer`.
+    /// Only a reference to the blob is then written to the document.
+    pub(crate) fn with_blob_writer(
+        mut self,
+        blob_writer: &'se mut CountingWriter<WritePtr>,
+    ) -> Self {
+        self.blob_writer = Some(blob_writer);
+        self
     }
 
     /// Attempts to serialize a given document and write the output
@@ -29,8 +56,9 @@ where W: Write
     pub(crate) fn serialize_doc<D>(&mut self, doc: &D) -> io::Result<()>
     where D: Document {
         let stored_field_values = || {
-            doc.iter_fields_and_values()
-                .filter(|(field, _)| self.schema.get_field_entry(*field).is_stored())
+            doc.iter_fields_and_values().filter(|(field, _)| {
+                self.include_unstored_fields || self.schema.get_field_entry(*field).is_stored()
+            })
         };
         let num_field_values = stored_field_values().count();
         let mut actual_length = 0;
@@ -39,8 +67,23 @@ where W: Write
         for (field, value_access) in stored_field_values() {
             field.serialize(self.writer)?;
 
+            let value = value_access.as_value();
+            let blob_threshold = self.schema.get_field_entry(field).blob_threshold();
+            if let (Some(blob_writer), Some((type_code, bytes))) = (
+                self.blob_writer.as_deref_mut(),
+                oversized_leaf(&value, blob_threshold),
+            ) {
+                let blob_start = blob_writer.written_bytes();
+                blob_writer.write_all(bytes)?;
+                let blob_range = blob_start..blob_writer.written_bytes();
+                BinaryValueSerializer::new(self.writer)
+                    .serialize_blob_ref(type_code, blob_range)?;
+                actual_length += 1;
+                continue;
+            }
+
             let mut serializer = BinaryValueSerializer::new(self.writer);
-            match value_access.as_value() {
+            match value {
                 ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)) => {
                     serializer.serialize_value(ReferenceValue::Leaf::<&'_ OwnedValue>(
                         ReferenceValueLeaf::Str(&pre_tokenized_text.text),
@@ -65,6 +108,30 @@ where W: Write
     }
 }
 
+/// Returns the type code and the bytes of `value` if it is a string or a bytes value larger than
+/// `blob_threshold`.
+fn oversized_leaf<'b, 'a: 'b, V: Value<'a>>(
+    value: &'b ReferenceValue<'a, V>,
+    blob_threshold: Option<usize>,
+) -> Option<(u8, &'b [u8])> {
+    let blob_threshold = blob_threshold?;
+    let (type_code, bytes) = match value {
+        ReferenceValue::Leaf(ReferenceValueLeaf::Str(text)) => {
+            (type_codes::TEXT_CODE, str::as_bytes(text))
+        }
+        ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)) => {
+            (type_codes::TEXT_CODE, pre_tokenized_text.text.as_bytes())
+        }
+        ReferenceValue::Leaf(ReferenceValueLeaf::Bytes(bytes)) => (type_codes::BYTES_CODE, *bytes),
+        _ => return None,
+    };
+    if bytes.len() > blob_threshold {
+        Some((type_code, bytes))
+    } else {
+        None
+    }
+}
+
 /// A serializer for a single value.
 pub struct BinaryValueSerializer<'se, W> {
     writer: &'se mut W,
@@ -167,6 +234,19 @@ where W: Write
         }
     }
 
+    /// Writes a reference to a string or bytes value, stored in the blob file at `blob_range`.
+    pub(crate) fn serialize_blob_ref(
+        &mut self,
+        type_code: u8,
+        blob_range: Range<u64>,
+    ) -> io::Result<()> {
+        self.write_type_code(type_codes::EXT_CODE)?;
+        self.write_type_code(type_codes::BLOB_EXT_CODE)?;
+        self.write_type_code(type_code)?;
+        VInt(blob_range.start).serialize(self.writer)?;
+        VInt(blob_range.end - blob_range.start).serialize(self.writer)
+    }
+
     fn write_type_code(&mut self, code: u8) -> io::Result<()> {
         code.serialize(self.writer)
     }
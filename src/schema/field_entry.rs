@@ -4,7 +4,7 @@ use super::ip_options::IpAddrOptions;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::{
     is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
-    TextOptions,
+    SuggestOptions, TextOptions,
 };
 
 /// A `FieldEntry` represents a field and its configuration.
@@ -80,6 +80,11 @@ impl FieldEntry {
         Self::new(field_name, FieldType::JsonObject(json_object_options))
     }
 
+    /// Creates a field entry for a suggest field
+    pub fn new_suggest(field_name: String, suggest_options: SuggestOptions) -> FieldEntry {
+        Self::new(field_name, FieldType::Suggest(suggest_options))
+    }
+
     /// Returns the name of the field
     pub fn name(&self) -> &str {
         &self.name
@@ -129,6 +134,18 @@ impl FieldEntry {
             FieldType::Bytes(ref options) => options.is_stored(),
             FieldType::JsonObject(ref options) => options.is_stored(),
             FieldType::IpAddr(ref options) => options.is_stored(),
+            FieldType::Suggest(ref options) => options.is_stored(),
+        }
+    }
+
+    /// Returns the size above which the stored values of the field are spilled into the blob
+    /// file of the segment, if any.
+    #[inline]
+    pub fn blob_threshold(&self) -> Option<usize> {
+        match self.field_type {
+            FieldType::Str(ref options) => options.blob_threshold(),
+            FieldType::Bytes(ref options) => options.blob_threshold(),
+            _ => None,
         }
     }
 }
@@ -13,8 +13,8 @@ use super::IntoIpv6Addr;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
-    DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, OwnedValue,
-    TextFieldIndexing, TextOptions,
+    DateOptions, Facet, FieldNormPrecision, IndexRecordOption, JsonObjectOptions, NumericOptions,
+    OwnedValue, Similarity, SuggestOptions, TextFieldIndexing, TextOptions,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -84,6 +84,9 @@ impl From<ColumnType> for Type {
             ColumnType::DateTime => Type::Date,
             ColumnType::Bytes => Type::Bytes,
             ColumnType::IpAddr => Type::IpAddr,
+            ColumnType::U128 => Type::Bytes,
+            // Geo points are exposed through their `u64` morton code.
+            ColumnType::GeoPoint => Type::U64,
         }
     }
 }
@@ -189,6 +192,14 @@ pub enum FieldType {
     JsonObject(JsonObjectOptions),
     /// IpAddr field
     IpAddr(IpAddrOptions),
+    /// Inputs of completion suggestions
+    Suggest(SuggestOptions),
+}
+
+/// Offsets are only recorded for text fields: the record option of the text of a JSON field
+/// is at most [`IndexRecordOption::WithFreqsAndPositions`].
+fn json_index_record_option(index_record_option: IndexRecordOption) -> IndexRecordOption {
+    index_record_option.downgrade(IndexRecordOption::WithFreqsAndPositions)
 }
 
 impl FieldType {
@@ -205,6 +216,8 @@ impl FieldType {
             FieldType::Bytes(_) => Type::Bytes,
             FieldType::JsonObject(_) => Type::Json,
             FieldType::IpAddr(_) => Type::IpAddr,
+            // The inputs of the suggestions are strings.
+            FieldType::Suggest(_) => Type::Str,
         }
     }
 
@@ -241,6 +254,7 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_indexed(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_indexed(),
+            FieldType::Suggest(_) => false,
         }
     }
 
@@ -254,7 +268,8 @@ impl FieldType {
                 .map(|text_indexing| text_indexing.index_option()),
             FieldType::JsonObject(json_object_options) => json_object_options
                 .get_text_indexing_options()
-                .map(|text_indexing| text_indexing.index_option()),
+                .map(|text_indexing| text_indexing.index_option())
+                .map(json_index_record_option),
             field_type => {
                 if field_type.is_indexed() {
                     Some(IndexRecordOption::Basic)
@@ -278,6 +293,7 @@ impl FieldType {
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_fast(),
             FieldType::Facet(_) => true,
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_fast(),
+            FieldType::Suggest(_) => false,
         }
     }
 
@@ -297,6 +313,7 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
             FieldType::JsonObject(ref _json_object_options) => false,
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.fieldnorms(),
+            FieldType::Suggest(_) => false,
         }
     }
 
@@ -340,7 +357,8 @@ impl FieldType {
             }
             FieldType::JsonObject(ref json_obj_options) => json_obj_options
                 .get_text_indexing_options()
-                .map(TextFieldIndexing::index_option),
+                .map(TextFieldIndexing::index_option)
+                .map(json_index_record_option),
             FieldType::IpAddr(ref ip_addr_options) => {
                 if ip_addr_options.is_indexed() {
                     Some(IndexRecordOption::Basic)
@@ -348,6 +366,33 @@ impl FieldType {
                     None
                 }
             }
+            FieldType::Suggest(_) => None,
+        }
+    }
+
+    /// Returns the similarity scoring the documents matching the terms of the field.
+    ///
+    /// Only text and JSON fields can be configured with another similarity than BM25.
+    pub fn similarity(&self) -> Similarity {
+        self.text_indexing_options()
+            .map(TextFieldIndexing::similarity)
+            .unwrap_or_default()
+    }
+
+    /// Returns how the fieldnorms of the field are encoded.
+    ///
+    /// Only text and JSON fields can be configured with another precision than the compact one.
+    pub fn fieldnorm_precision(&self) -> FieldNormPrecision {
+        self.text_indexing_options()
+            .map(TextFieldIndexing::fieldnorm_precision)
+            .unwrap_or_default()
+    }
+
+    fn text_indexing_options(&self) -> Option<&TextFieldIndexing> {
+        match self {
+            FieldType::Str(text_options) => text_options.get_indexing_options(),
+            FieldType::JsonObject(json_obj_options) => json_obj_options.get_text_indexing_options(),
+            _ => None,
         }
     }
 
@@ -369,7 +414,7 @@ impl FieldType {
                             })?;
                         Ok(DateTime::from_utc(dt_with_fixed_tz).into())
                     }
-                    FieldType::Str(_) => Ok(OwnedValue::Str(field_text)),
+                    FieldType::Str(_) | FieldType::Suggest(_) => Ok(OwnedValue::Str(field_text)),
                     FieldType::U64(opt) => {
                         if opt.should_coerce() {
                             Ok(OwnedValue::U64(field_text.parse().map_err(|_| {
@@ -508,6 +553,10 @@ impl FieldType {
                     expected: "a string with an ip addr",
                     json: JsonValue::Number(field_val_num),
                 }),
+                FieldType::Suggest(_) => Err(ValueParsingError::TypeError {
+                    expected: "a string or an object with an input",
+                    json: JsonValue::Number(field_val_num),
+                }),
             },
             JsonValue::Object(json_map) => match self {
                 FieldType::Str(_) => {
@@ -522,7 +571,7 @@ impl FieldType {
                         })
                     }
                 }
-                FieldType::JsonObject(_) => Ok(OwnedValue::from(json_map)),
+                FieldType::JsonObject(_) | FieldType::Suggest(_) => Ok(OwnedValue::from(json_map)),
                 _ => Err(ValueParsingError::TypeError {
                     expected: self.value_type().name(),
                     json: JsonValue::Object(json_map),
@@ -761,6 +810,7 @@ mod tests {
                     position: 0,
                     text: String::from("The"),
                     position_length: 1,
+                    keyword: false,
                 },
                 Token {
                     offset_from: 4,
@@ -768,6 +818,7 @@ mod tests {
                     position: 1,
                     text: String::from("Old"),
                     position_length: 1,
+                    keyword: false,
                 },
                 Token {
                     offset_from: 8,
@@ -775,6 +826,7 @@ mod tests {
                     position: 2,
                     text: String::from("Man"),
                     position_length: 1,
+                    keyword: false,
                 },
             ],
         });
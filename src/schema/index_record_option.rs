@@ -26,6 +26,15 @@ pub enum IndexRecordOption {
     /// Positions are required to run a [`PhraseQuery`](crate::query::PhraseQuery).
     #[serde(rename = "position")]
     WithFreqsAndPositions,
+    /// records the document id, the term frequency, the positions of
+    /// the occurrences in the document, and the byte offsets of their tokens in the text.
+    /// Offsets make it possible to highlight a document without tokenizing its text again
+    /// (See [`OffsetsHighlighter`](crate::snippet::OffsetsHighlighter)).
+    ///
+    /// Offsets are only recorded for text fields. In JSON fields, this option behaves like
+    /// [`IndexRecordOption::WithFreqsAndPositions`].
+    #[serde(rename = "offsets")]
+    WithFreqsAndPositionsAndOffsets,
 }
 
 impl IndexRecordOption {
@@ -34,7 +43,9 @@ impl IndexRecordOption {
     pub fn has_freq(self) -> bool {
         match self {
             IndexRecordOption::Basic => false,
-            IndexRecordOption::WithFreqs | IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqs
+            | IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
@@ -43,20 +54,20 @@ impl IndexRecordOption {
     pub fn has_positions(self) -> bool {
         match self {
             IndexRecordOption::Basic | IndexRecordOption::WithFreqs => false,
-            IndexRecordOption::WithFreqsAndPositions => true,
+            IndexRecordOption::WithFreqsAndPositions
+            | IndexRecordOption::WithFreqsAndPositionsAndOffsets => true,
         }
     }
 
+    /// Returns true if this option include encoding
+    /// the offsets of the tokens of the terms.
+    pub fn has_offsets(self) -> bool {
+        self == IndexRecordOption::WithFreqsAndPositionsAndOffsets
+    }
+
     /// Downgrades to the next level if provided `IndexRecordOption` is unavailable.
     pub fn downgrade(&self, other: IndexRecordOption) -> IndexRecordOption {
-        use IndexRecordOption::*;
-
-        match (other, self) {
-            (WithFreqsAndPositions, WithFreqsAndPositions) => WithFreqsAndPositions,
-            (WithFreqs, WithFreqs) => WithFreqs,
-            (WithFreqsAndPositions, WithFreqs) => WithFreqs,
-            (WithFreqs, WithFreqsAndPositions) => WithFreqs,
-            _ => Basic,
-        }
+        // Each option includes the information recorded by the options before it.
+        (*self).min(other)
     }
 }
@@ -110,6 +110,7 @@ pub mod document;
 mod facet;
 mod facet_options;
 mod schema;
+mod similarity;
 pub(crate) mod term;
 
 mod field_entry;
@@ -124,6 +125,7 @@ mod ip_options;
 mod json_object_options;
 mod named_field_document;
 mod numeric_options;
+mod suggest_options;
 mod text_options;
 
 use columnar::ColumnType;
@@ -142,10 +144,15 @@ pub use self::index_record_option::IndexRecordOption;
 pub use self::ip_options::{IntoIpv6Addr, IpAddrOptions};
 pub use self::json_object_options::JsonObjectOptions;
 pub use self::named_field_document::NamedFieldDocument;
-pub use self::numeric_options::NumericOptions;
+pub use self::numeric_options::{FastFieldCodec, FloatPrecision, NumericOptions};
 pub use self::schema::{Schema, SchemaBuilder};
+pub use self::similarity::Similarity;
+pub(crate) use self::similarity::{DEFAULT_B, DEFAULT_K1};
+pub use self::suggest_options::SuggestOptions;
 pub use self::term::{Term, ValueBytes};
-pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
+pub use self::text_options::{
+    FieldNormPrecision, TextFieldIndexing, TextOptions, REVERSED_TOKEN_MARKER, STRING, TEXT,
+};
 
 /// Validator for a potential `field_name`.
 /// Returns true if the name can be use for a field name.
@@ -171,6 +178,31 @@ pub(crate) fn value_type_to_column_type(typ: Type) -> Option<ColumnType> {
     }
 }
 
+/// Returns the type of the column storing the fast field values of a field.
+///
+/// This only differs from [`value_type_to_column_type`] for fixed-width bytes, which are
+/// stored in a u128 column.
+pub(crate) fn field_type_to_column_type(field_type: &FieldType) -> Option<ColumnType> {
+    if let FieldType::Bytes(bytes_options) = field_type {
+        if bytes_options.fixed_width().is_some() {
+            return Some(ColumnType::U128);
+        }
+    }
+    value_type_to_column_type(field_type.value_type())
+}
+
+/// Returns the codec set to encode the fast field values of a field, if any.
+pub(crate) fn field_type_fast_codec(field_type: &FieldType) -> Option<FastFieldCodec> {
+    match field_type {
+        FieldType::U64(numeric_options)
+        | FieldType::I64(numeric_options)
+        | FieldType::F64(numeric_options)
+        | FieldType::Bool(numeric_options) => numeric_options.get_fast_codec(),
+        FieldType::Date(date_options) => date_options.get_fast_codec(),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
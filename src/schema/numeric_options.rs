@@ -1,5 +1,6 @@
 use std::ops::BitOr;
 
+use columnar::column_values::CodecType;
 use serde::{Deserialize, Serialize};
 
 use super::flags::CoerceFlag;
@@ -16,12 +17,80 @@ pub struct NumericOptions {
     stored: bool,
     #[serde(skip_serializing_if = "is_false")]
     coerce: bool,
+    #[serde(skip_serializing_if = "is_full_precision")]
+    fast_precision: FloatPrecision,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fast_codec: Option<FastFieldCodec>,
 }
 
 fn is_false(val: &bool) -> bool {
     !val
 }
 
+fn is_full_precision(precision: &FloatPrecision) -> bool {
+    *precision == FloatPrecision::F64
+}
+
+/// Precision of the values of a `f64` fast field.
+///
+/// Reduced precisions round the values when they are indexed, so that they compress to 16
+/// bits. They are still read as `f64` values.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FloatPrecision {
+    /// Full precision.
+    #[default]
+    F64,
+    /// IEEE 754 half precision: 11 significant bits, and a maximum magnitude of 65504.
+    ///
+    /// Larger values are rounded to infinity, and tiny values to zero.
+    F16,
+    /// Brain floating point: 8 significant bits, with the range of a `f32`.
+    BF16,
+}
+
+impl FloatPrecision {
+    /// Rounds `val` to the precision.
+    pub fn round(self, val: f64) -> f64 {
+        match self {
+            FloatPrecision::F64 => val,
+            FloatPrecision::F16 => half::f16::from_f64(val).to_f64(),
+            FloatPrecision::BF16 => half::bf16::from_f64(val).to_f64(),
+        }
+    }
+}
+
+/// Codec encoding the values of a numerical or date fast field.
+///
+/// By default, the most compact of [`FastFieldCodec::Bitpacked`] and
+/// [`FastFieldCodec::BlockwiseLinear`] is picked for each segment. The codec used by a segment,
+/// and the number of bits per value it achieved, are returned by
+/// [`FastFieldReaders::codec_stats`](crate::fastfield::FastFieldReaders::codec_stats).
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FastFieldCodec {
+    /// Frame of reference: bitpacks the offset of the values to the minimum value, divided by
+    /// their greatest common divisor.
+    Bitpacked,
+    /// Bitpacks the offset of the values to a line going through the first and last values.
+    ///
+    /// This is efficient for values increasing steadily with the doc ids, like timestamps in an
+    /// index sorted by time.
+    Linear,
+    /// Same as [`FastFieldCodec::Linear`], with one line per block of 512 values.
+    BlockwiseLinear,
+}
+
+impl FastFieldCodec {
+    pub(crate) fn codec_type(self) -> CodecType {
+        match self {
+            FastFieldCodec::Bitpacked => CodecType::Bitpacked,
+            FastFieldCodec::Linear => CodecType::Linear,
+            FastFieldCodec::BlockwiseLinear => CodecType::BlockwiseLinear,
+        }
+    }
+}
+
 /// For backward compatibility we add an intermediary to interpret the
 /// lack of fieldnorms attribute as "true" if and only if indexed.
 ///
@@ -37,6 +106,10 @@ struct NumericOptionsDeser {
     stored: bool,
     #[serde(default)]
     coerce: bool,
+    #[serde(default)]
+    fast_precision: FloatPrecision,
+    #[serde(default)]
+    fast_codec: Option<FastFieldCodec>,
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -47,6 +120,8 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fast: deser.fast,
             stored: deser.stored,
             coerce: deser.coerce,
+            fast_precision: deser.fast_precision,
+            fast_codec: deser.fast_codec,
         }
     }
 }
@@ -129,6 +204,41 @@ impl NumericOptions {
         self.fast = true;
         self
     }
+
+    /// Sets the precision of the fast field values of a `f64` field.
+    ///
+    /// Reduced precisions make the fast field about four times smaller, at the cost of
+    /// rounding the values, which is fine for features used in scoring for instance. The
+    /// values of the inverted index and of the doc store are not affected.
+    ///
+    /// The compression relies on all of the values of a segment having the same sign.
+    ///
+    /// This has no effect on `u64` and `i64` fields.
+    #[must_use]
+    pub fn set_fast_precision(mut self, precision: FloatPrecision) -> NumericOptions {
+        self.fast_precision = precision;
+        self
+    }
+
+    /// Returns the precision of the fast field values of a `f64` field.
+    #[inline]
+    pub fn get_fast_precision(&self) -> FloatPrecision {
+        self.fast_precision
+    }
+
+    /// Sets the codec encoding the fast field values, instead of picking the most compact
+    /// codec automatically.
+    #[must_use]
+    pub fn set_fast_codec(mut self, codec: FastFieldCodec) -> NumericOptions {
+        self.fast_codec = Some(codec);
+        self
+    }
+
+    /// Returns the codec encoding the fast field values, if it is not picked automatically.
+    #[inline]
+    pub fn get_fast_codec(&self) -> Option<FastFieldCodec> {
+        self.fast_codec
+    }
 }
 
 impl From<()> for NumericOptions {
@@ -145,6 +255,8 @@ impl From<CoerceFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: true,
+            fast_precision: FloatPrecision::F64,
+            fast_codec: None,
         }
     }
 }
@@ -157,6 +269,8 @@ impl From<FastFlag> for NumericOptions {
             stored: false,
             fast: true,
             coerce: false,
+            fast_precision: FloatPrecision::F64,
+            fast_codec: None,
         }
     }
 }
@@ -169,6 +283,8 @@ impl From<StoredFlag> for NumericOptions {
             stored: true,
             fast: false,
             coerce: false,
+            fast_precision: FloatPrecision::F64,
+            fast_codec: None,
         }
     }
 }
@@ -181,6 +297,8 @@ impl From<IndexedFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: false,
+            fast_precision: FloatPrecision::F64,
+            fast_codec: None,
         }
     }
 }
@@ -196,6 +314,12 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            fast_precision: if is_full_precision(&other.fast_precision) {
+                self.fast_precision
+            } else {
+                other.fast_precision
+            },
+            fast_codec: other.fast_codec.or(self.fast_codec),
         }
     }
 }
@@ -214,6 +338,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::STORED;
 
     #[test]
     fn test_int_options_deser_if_fieldnorm_missing_indexed_true() {
@@ -230,6 +355,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_precision: FloatPrecision::F64,
+                fast_codec: None,
             }
         );
     }
@@ -249,6 +376,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_precision: FloatPrecision::F64,
+                fast_codec: None,
             }
         );
     }
@@ -269,6 +398,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_precision: FloatPrecision::F64,
+                fast_codec: None,
             }
         );
     }
@@ -290,6 +421,8 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                fast_precision: FloatPrecision::F64,
+                fast_codec: None,
             }
         );
     }
@@ -312,7 +445,61 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: true,
+                fast_precision: FloatPrecision::F64,
+                fast_codec: None,
             }
         );
     }
+
+    #[test]
+    fn test_numeric_options_fast_precision() {
+        let numeric_options = NumericOptions::default()
+            .set_fast()
+            .set_fast_precision(FloatPrecision::BF16);
+        let json = serde_json::to_string(&numeric_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"indexed":false,"fieldnorms":false,"fast":true,"stored":false,"fast_precision":"bf16"}"#
+        );
+        let deser_numeric_options: NumericOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_numeric_options, numeric_options);
+        assert_eq!(
+            (numeric_options | STORED).get_fast_precision(),
+            FloatPrecision::BF16
+        );
+        assert!(!serde_json::to_string(&NumericOptions::default())
+            .unwrap()
+            .contains("fast_precision"));
+    }
+
+    #[test]
+    fn test_numeric_options_fast_codec() {
+        let numeric_options = NumericOptions::default()
+            .set_fast()
+            .set_fast_codec(FastFieldCodec::BlockwiseLinear);
+        let json = serde_json::to_string(&numeric_options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"indexed":false,"fieldnorms":false,"fast":true,"stored":false,"fast_codec":"blockwise_linear"}"#
+        );
+        let deser_numeric_options: NumericOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deser_numeric_options, numeric_options);
+        assert_eq!(
+            (numeric_options | STORED).get_fast_codec(),
+            Some(FastFieldCodec::BlockwiseLinear)
+        );
+        assert_eq!(NumericOptions::default().get_fast_codec(), None);
+    }
+
+    #[test]
+    fn test_float_precision_round() {
+        assert_eq!(FloatPrecision::F64.round(0.1), 0.1);
+        assert_eq!(FloatPrecision::BF16.round(1.0 + 1.0 / 512.0), 1.0);
+        assert_eq!(
+            FloatPrecision::F16.round(1.0 + 1.0 / 512.0),
+            1.0 + 1.0 / 512.0
+        );
+        assert_eq!(FloatPrecision::F16.round(1.0e6), f64::INFINITY);
+        assert_eq!(FloatPrecision::BF16.round(1.0e6), 999_424.0);
+    }
 }
@@ -89,6 +89,20 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a new rank feature field.
+    /// Returns the associated field handle
+    ///
+    /// A rank feature field is a `f64` fast field holding a strictly positive static signal
+    /// about the document, e.g. its pagerank or popularity, meant to be used with a
+    /// [`RankFeatureQuery`](crate::query::RankFeatureQuery).
+    ///
+    /// # Panics
+    ///
+    /// Panics when field already exists.
+    pub fn add_rank_feature_field(&mut self, field_name_str: &str) -> Field {
+        self.add_f64_field(field_name_str, NumericOptions::default().set_fast())
+    }
+
     /// Adds a new bool field.
     /// Returns the associated field handle
     ///
@@ -165,6 +179,19 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a suggest field to the schema.
+    ///
+    /// The inputs of a suggest field are completed by the
+    /// [`Suggester`](crate::suggest::Suggester).
+    pub fn add_suggest_field(
+        &mut self,
+        field_name: &str,
+        suggest_options: impl Into<SuggestOptions>,
+    ) -> Field {
+        let field_entry = FieldEntry::new_suggest(field_name.to_string(), suggest_options.into());
+        self.add_field(field_entry)
+    }
+
     /// Adds a fast bytes field to the schema.
     ///
     /// Bytes field are not searchable and are only used
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Score, TantivyError};
+
+/// Default term frequency saturation parameter of BM25.
+pub(crate) const DEFAULT_K1: Score = 1.2;
+/// Default length normalization parameter of BM25.
+pub(crate) const DEFAULT_B: Score = 0.75;
+
+fn default_k1() -> Score {
+    DEFAULT_K1
+}
+
+fn default_b() -> Score {
+    DEFAULT_B
+}
+
+/// Scoring function ranking the documents matching the terms of a text field.
+///
+/// The similarity of a field is set in its [`TextFieldIndexing`](crate::schema::TextFieldIndexing)
+/// options, and is used by the term and phrase queries on this field. Changing it does not
+/// require reindexing. [`TermQuery`](crate::query::TermQuery) and
+/// [`PhraseQuery`](crate::query::PhraseQuery) can also override it for a single query.
+///
+/// The language model similarities score a document by the likelihood that its language
+/// model generates the term, smoothed with the probability of the term in the whole
+/// collection. They tend to outperform BM25 on short queries over short fields.
+///
+/// The parameters of a similarity are validated when it is set on a field, when it is
+/// deserialized, and when it overrides the similarity of a query.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    try_from = "UncheckedSimilarity"
+)]
+pub enum Similarity {
+    /// Okapi BM25: `idf * (k1 + 1) * freq / (freq + k1 * (1 - b + b * dl / avgdl))`.
+    ///
+    /// `k1` controls how fast the score saturates with the term frequency, and must be
+    /// positive. `b` controls how much the score is normalized by the length of the field, from
+    /// 0 (not at all) to 1 (fully). Short fields like titles usually call for a lower `b` than
+    /// long bodies.
+    ///
+    /// Top-k searches skip blocks of documents less efficiently when `b` is not the default
+    /// 0.75.
+    Bm25 {
+        /// Term frequency saturation parameter, 1.2 by default.
+        k1: Score,
+        /// Length normalization parameter, 0.75 by default.
+        b: Score,
+    },
+    /// Language model with Dirichlet smoothing:
+    /// `log(1 + freq / (mu * P(t|C))) + log(mu / (dl + mu))`, or 0 if negative.
+    ///
+    /// `mu` is the smoothing parameter, and must be strictly positive. Larger values give more
+    /// weight to the collection model, which helps on long documents: a value around 2000 is
+    /// common.
+    LmDirichlet {
+        /// Smoothing parameter.
+        mu: Score,
+    },
+    /// Language model with Jelinek-Mercer smoothing:
+    /// `log(1 + ((1 - lambda) * freq / dl) / (lambda * P(t|C)))`.
+    ///
+    /// `lambda` is the weight of the collection model, in `]0, 1]`. A value around 0.1 suits
+    /// short queries matching all of their terms, and around 0.7 long queries.
+    LmJelinekMercer {
+        /// Weight of the collection model.
+        lambda: Score,
+    },
+}
+
+/// Mirror of [`Similarity`], deserialized before its parameters are validated.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum UncheckedSimilarity {
+    Bm25 {
+        #[serde(default = "default_k1")]
+        k1: Score,
+        #[serde(default = "default_b")]
+        b: Score,
+    },
+    LmDirichlet {
+        mu: Score,
+    },
+    LmJelinekMercer {
+        lambda: Score,
+    },
+}
+
+impl TryFrom<UncheckedSimilarity> for Similarity {
+    type Error = TantivyError;
+
+    fn try_from(unchecked: UncheckedSimilarity) -> crate::Result<Similarity> {
+        let similarity = match unchecked {
+            UncheckedSimilarity::Bm25 { k1, b } => Similarity::Bm25 { k1, b },
+            UncheckedSimilarity::LmDirichlet { mu } => Similarity::LmDirichlet { mu },
+            UncheckedSimilarity::LmJelinekMercer { lambda } => {
+                Similarity::LmJelinekMercer { lambda }
+            }
+        };
+        similarity.validate()?;
+        Ok(similarity)
+    }
+}
+
+// The parameters are compared bitwise, so that the equality is reflexive even for NaN.
+impl PartialEq for Similarity {
+    fn eq(&self, other: &Similarity) -> bool {
+        match (*self, *other) {
+            (
+                Similarity::Bm25 { k1, b },
+                Similarity::Bm25 {
+                    k1: other_k1,
+                    b: other_b,
+                },
+            ) => k1.to_bits() == other_k1.to_bits() && b.to_bits() == other_b.to_bits(),
+            (Similarity::LmDirichlet { mu }, Similarity::LmDirichlet { mu: other_mu }) => {
+                mu.to_bits() == other_mu.to_bits()
+            }
+            (
+                Similarity::LmJelinekMercer { lambda },
+                Similarity::LmJelinekMercer {
+                    lambda: other_lambda,
+                },
+            ) => lambda.to_bits() == other_lambda.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Similarity {}
+
+impl Default for Similarity {
+    fn default() -> Similarity {
+        Similarity::BM25
+    }
+}
+
+impl Similarity {
+    /// BM25 with its default parameters, `k1 = 1.2` and `b = 0.75`.
+    pub const BM25: Similarity = Similarity::Bm25 {
+        k1: DEFAULT_K1,
+        b: DEFAULT_B,
+    };
+
+    /// Returns an error if the parameters of the similarity are out of their range.
+    pub(crate) fn validate(&self) -> crate::Result<()> {
+        match *self {
+            Similarity::Bm25 { k1, b } => {
+                if !(k1.is_finite() && k1 >= 0.0) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The k1 parameter of the BM25 similarity must be positive, got {k1}"
+                    )));
+                }
+                if !(0.0..=1.0).contains(&b) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The b parameter of the BM25 similarity must be in [0, 1], got {b}"
+                    )));
+                }
+                Ok(())
+            }
+            Similarity::LmDirichlet { mu } => {
+                if !(mu.is_finite() && mu > 0.0) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The mu parameter of the LM Dirichlet similarity must be strictly \
+                         positive, got {mu}"
+                    )));
+                }
+                Ok(())
+            }
+            Similarity::LmJelinekMercer { lambda } => {
+                if !(lambda > 0.0 && lambda <= 1.0) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The lambda parameter of the LM Jelinek-Mercer similarity must be in ]0, \
+                         1], got {lambda}"
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Similarity;
+
+    #[test]
+    fn test_similarity_serialization() {
+        let similarity = Similarity::LmDirichlet { mu: 2000.0 };
+        let json = serde_json::to_string(&similarity).unwrap();
+        assert_eq!(json, r#"{"type":"lm_dirichlet","mu":2000.0}"#);
+        assert_eq!(
+            serde_json::from_str::<Similarity>(&json).unwrap(),
+            similarity
+        );
+        assert_eq!(
+            serde_json::to_string(&Similarity::BM25).unwrap(),
+            r#"{"type":"bm25","k1":1.2,"b":0.75}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Similarity>(r#"{"type":"bm25"}"#).unwrap(),
+            Similarity::BM25
+        );
+        assert_eq!(
+            serde_json::from_str::<Similarity>(r#"{"type":"bm25","b":0.3}"#).unwrap(),
+            Similarity::Bm25 { k1: 1.2, b: 0.3 }
+        );
+    }
+
+    #[test]
+    fn test_similarity_validate() {
+        assert!(Similarity::BM25.validate().is_ok());
+        assert!(Similarity::Bm25 { k1: 0.0, b: 1.0 }.validate().is_ok());
+        assert!(Similarity::Bm25 { k1: -1.0, b: 0.5 }.validate().is_err());
+        assert!(Similarity::Bm25 { k1: 1.2, b: 1.1 }.validate().is_err());
+        assert!(Similarity::LmDirichlet { mu: 100.0 }.validate().is_ok());
+        assert!(Similarity::LmDirichlet { mu: 0.0 }.validate().is_err());
+        assert!(Similarity::LmDirichlet { mu: f32::NAN }.validate().is_err());
+        assert!(Similarity::LmJelinekMercer { lambda: 1.0 }
+            .validate()
+            .is_ok());
+        assert!(Similarity::LmJelinekMercer { lambda: 0.0 }
+            .validate()
+            .is_err());
+        assert!(Similarity::LmJelinekMercer { lambda: 1.5 }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_similarity_deserialization_validates() {
+        let err =
+            serde_json::from_str::<Similarity>(r#"{"type":"lm_dirichlet","mu":-1.0}"#).unwrap_err();
+        assert!(err.to_string().contains("must be strictly positive"));
+        assert!(serde_json::from_str::<Similarity>(r#"{"type":"bm25","b":2.0}"#).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::ops::BitOr;
+
+use serde::{Deserialize, Serialize};
+
+use super::text_options::TokenizerName;
+use crate::schema::flags::{SchemaFlagList, StoredFlag};
+
+/// Define how a suggest field should be handled by tantivy.
+///
+/// A suggest field is not searchable: its inputs are added to a weighted FST of each segment,
+/// used to complete prefixes with the [`Suggester`](crate::suggest::Suggester).
+///
+/// The values of a suggest field are either a string, the input of a suggestion, or an object
+/// with an `input` string or array of strings, and an optional `weight`, `payload` and
+/// `contexts`, associating context names to a string or an array of strings:
+///
+/// ```json
+/// {"input": ["Nevermind", "Nirvana"], "weight": 34, "payload": "album:42",
+///  "contexts": {"genre": ["grunge", "rock"], "tenant": "acme"}}
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct SuggestOptions {
+    stored: bool,
+    #[serde(default)]
+    tokenizer: TokenizerName,
+}
+
+impl SuggestOptions {
+    /// Returns true if the value is stored.
+    #[inline]
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns the name of the tokenizer used to normalize the inputs and the prefixes.
+    pub fn tokenizer(&self) -> &str {
+        self.tokenizer.name()
+    }
+
+    /// Set the field as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    #[must_use]
+    pub fn set_stored(mut self) -> SuggestOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Sets the tokenizer normalizing the inputs and the prefixes.
+    ///
+    /// The tokens of an input, joined by a space, are the key of the input in the FST.
+    /// Defaults to the `default` tokenizer, which splits on punctuation and lowercases.
+    #[must_use]
+    pub fn set_tokenizer(mut self, tokenizer_name: &str) -> SuggestOptions {
+        self.tokenizer = TokenizerName::from_name(tokenizer_name);
+        self
+    }
+}
+
+impl From<()> for SuggestOptions {
+    fn from(_: ()) -> SuggestOptions {
+        SuggestOptions::default()
+    }
+}
+
+impl From<StoredFlag> for SuggestOptions {
+    fn from(_: StoredFlag) -> Self {
+        SuggestOptions::default().set_stored()
+    }
+}
+
+impl<T: Into<SuggestOptions>> BitOr<T> for SuggestOptions {
+    type Output = SuggestOptions;
+
+    fn bitor(self, other: T) -> SuggestOptions {
+        let other = other.into();
+        let tokenizer = if self.tokenizer == TokenizerName::default() {
+            other.tokenizer
+        } else {
+            self.tokenizer
+        };
+        SuggestOptions {
+            stored: self.stored | other.stored,
+            tokenizer,
+        }
+    }
+}
+
+impl<Head, Tail> From<SchemaFlagList<Head, Tail>> for SuggestOptions
+where
+    Head: Clone,
+    Tail: Clone,
+    Self: BitOr<Output = Self> + From<Head> + From<Tail>,
+{
+    fn from(head_tail: SchemaFlagList<Head, Tail>) -> Self {
+        Self::from(head_tail.head) | Self::from(head_tail.tail)
+    }
+}
@@ -4,8 +4,10 @@ use std::ops::BitOr;
 use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
+use crate::fieldnorm::FieldNormReader;
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
-use crate::schema::IndexRecordOption;
+use crate::schema::{IndexRecordOption, Similarity};
+use crate::tokenizer::AnalyzerDefinition;
 
 /// Define how a text field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -21,6 +23,9 @@ pub struct TextOptions {
     #[serde(skip_serializing_if = "is_false")]
     /// coerce values into string if they are not of type string
     coerce: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blob_threshold: Option<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -104,6 +109,29 @@ impl TextOptions {
         }
     }
 
+    /// Returns true if the field is fast, and its fast field holds the same terms as its
+    /// inverted index, i.e. both use the same tokenizer, or the field is not indexed.
+    ///
+    /// Term level queries can then be evaluated against the dictionary of the fast field.
+    pub(crate) fn fast_field_has_indexed_terms(&self) -> bool {
+        if !self.is_fast() {
+            return false;
+        }
+        let fast_field_tokenizer = self
+            .get_fast_field_tokenizer_name()
+            .unwrap_or(NO_TOKENIZER_NAME);
+        self.indexing
+            .as_ref()
+            .is_none_or(|indexing| indexing.tokenizer() == fast_field_tokenizer)
+    }
+
+    /// Returns the size above which the stored values are spilled into the blob file of the
+    /// segment. See [`TextOptions::set_blob_threshold`].
+    #[inline]
+    pub fn blob_threshold(&self) -> Option<usize> {
+        self.blob_threshold
+    }
+
     /// Returns true if values should be coerced to strings (numbers, null).
     #[inline]
     pub fn should_coerce(&self) -> bool {
@@ -152,6 +180,18 @@ impl TextOptions {
         self
     }
 
+    /// Sets the field as stored, and spills the values longer than `num_bytes` bytes into a
+    /// separate blob file instead of the doc store blocks.
+    ///
+    /// The store only keeps a reference to the blob, so that huge values do not bloat the
+    /// blocks, and do not slow down fetching the documents stored next to them.
+    #[must_use]
+    pub fn set_blob_threshold(mut self, num_bytes: usize) -> TextOptions {
+        self.stored = true;
+        self.blob_threshold = Some(num_bytes);
+        self
+    }
+
     /// Sets the field as indexed, with the specific indexing options.
     #[must_use]
     pub fn set_indexing_options(mut self, indexing: TextFieldIndexing) -> TextOptions {
@@ -194,6 +234,10 @@ impl TokenizerName {
 /// - The name of the `Tokenizer` that should be used to process the field.
 /// - Flag indicating, if fieldnorms should be stored (See [fieldnorm](crate::fieldnorm)). Defaults
 ///   to `true`.
+/// - Optionally, the definition of the analyzer registered under the tokenizer name (See
+///   [`AnalyzerDefinition`]).
+/// - The [`Similarity`] scoring the documents matching its terms. Defaults to BM25.
+/// - The [`FieldNormPrecision`] of its fieldnorms. Defaults to the compact encoding.
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     #[serde(default)]
@@ -202,6 +246,26 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analyzer: Option<AnalyzerDefinition>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    index_reversed: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_default_similarity")]
+    similarity: Similarity,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_compact_fieldnorm")]
+    fieldnorm_precision: FieldNormPrecision,
+}
+
+fn is_default_similarity(similarity: &Similarity) -> bool {
+    *similarity == Similarity::BM25
+}
+
+fn is_compact_fieldnorm(precision: &FieldNormPrecision) -> bool {
+    *precision == FieldNormPrecision::Compact
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
@@ -214,6 +278,10 @@ impl Default for TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            analyzer: None,
+            index_reversed: false,
+            similarity: Similarity::BM25,
+            fieldnorm_precision: FieldNormPrecision::Compact,
         }
     }
 }
@@ -231,7 +299,26 @@ impl TextFieldIndexing {
         self.tokenizer.name()
     }
 
+    /// Attaches an analyzer definition to the field.
+    ///
+    /// The definition is saved with the schema. When the index is created or opened, it
+    /// gets registered in the index's [`TokenizerManager`](crate::tokenizer::TokenizerManager)
+    /// under the name of the field's tokenizer, replacing any tokenizer with the same name.
+    #[must_use]
+    pub fn set_analyzer_definition(mut self, definition: AnalyzerDefinition) -> TextFieldIndexing {
+        self.analyzer = Some(definition);
+        self
+    }
+
+    /// Returns the analyzer definition attached to the field, if any.
+    pub fn analyzer_definition(&self) -> Option<&AnalyzerDefinition> {
+        self.analyzer.as_ref()
+    }
+
     /// Sets fieldnorms
+    ///
+    /// Without fieldnorms, all of the documents are scored as if their field had the same
+    /// length: the score does not depend on the length of the field anymore.
     #[must_use]
     pub fn set_fieldnorms(mut self, fieldnorms: bool) -> TextFieldIndexing {
         self.fieldnorms = fieldnorms;
@@ -243,6 +330,20 @@ impl TextFieldIndexing {
         self.fieldnorms
     }
 
+    /// Sets how the fieldnorms of the field are encoded.
+    ///
+    /// See [`FieldNormPrecision`] for more detail.
+    #[must_use]
+    pub fn set_fieldnorm_precision(mut self, precision: FieldNormPrecision) -> TextFieldIndexing {
+        self.fieldnorm_precision = precision;
+        self
+    }
+
+    /// Returns how the fieldnorms of the field are encoded.
+    pub fn fieldnorm_precision(&self) -> FieldNormPrecision {
+        self.fieldnorm_precision
+    }
+
     /// Sets which information should be indexed with the tokens.
     ///
     /// See [`IndexRecordOption`] for more detail.
@@ -258,18 +359,104 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Sets whether the reversed tokens should be indexed too.
+    ///
+    /// Each token is then also indexed reversed, prefixed by [`REVERSED_TOKEN_MARKER`],
+    /// at the same position. This allows the [`QueryParser`](crate::query::QueryParser)
+    /// to run leading wildcard queries like `*suffix` as a scan of the terms starting with
+    /// the reversed suffix, instead of a scan of the whole term dictionary.
+    ///
+    /// Reversed tokens are not counted in the fieldnorms.
+    #[must_use]
+    pub fn set_index_reversed(mut self, index_reversed: bool) -> TextFieldIndexing {
+        self.index_reversed = index_reversed;
+        self
+    }
+
+    /// Returns true if and only if the reversed tokens are indexed too.
+    pub fn index_reversed(&self) -> bool {
+        self.index_reversed
+    }
+
+    /// Sets the similarity scoring the documents matching the terms of the field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters of the similarity are out of their range.
+    #[must_use]
+    pub fn set_similarity(mut self, similarity: Similarity) -> TextFieldIndexing {
+        if let Err(err) = similarity.validate() {
+            panic!("Invalid similarity: {err}");
+        }
+        self.similarity = similarity;
+        self
+    }
+
+    /// Returns the similarity scoring the documents matching the terms of the field.
+    pub fn similarity(&self) -> Similarity {
+        self.similarity
+    }
 }
 
+/// Encoding of the [fieldnorms](crate::fieldnorm) of a text field, on one byte per document.
+///
+/// The compact encoding distinguishes lengths up to 2 billion tokens, but is only exact up to
+/// 40 tokens: longer fields are rounded down to a log scale. On short structured fields, like
+/// titles or product names, documents of different lengths then get the same score. The
+/// linear encoding is exact up to 255 tokens instead, and considers longer fields to be 255
+/// tokens long.
+///
+/// Changing the precision of a field requires reindexing it.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldNormPrecision {
+    /// Exact up to 40 tokens, on a log scale beyond.
+    #[default]
+    Compact,
+    /// Exact up to 255 tokens, capped beyond.
+    Linear,
+}
+
+impl FieldNormPrecision {
+    /// Converts a `fieldnorm_id` into a fieldnorm.
+    #[inline]
+    pub fn id_to_fieldnorm(self, fieldnorm_id: u8) -> u32 {
+        match self {
+            FieldNormPrecision::Compact => FieldNormReader::id_to_fieldnorm(fieldnorm_id),
+            FieldNormPrecision::Linear => u32::from(fieldnorm_id),
+        }
+    }
+
+    /// Converts a fieldnorm into a `fieldnorm_id`.
+    #[inline]
+    pub fn fieldnorm_to_id(self, fieldnorm: u32) -> u8 {
+        match self {
+            FieldNormPrecision::Compact => FieldNormReader::fieldnorm_to_id(fieldnorm),
+            FieldNormPrecision::Linear => fieldnorm.min(u32::from(u8::MAX)) as u8,
+        }
+    }
+}
+
+/// Char prefixing the reversed tokens of the fields indexed with
+/// [`TextFieldIndexing::set_index_reversed`].
+pub const REVERSED_TOKEN_MARKER: char = '\u{1}';
+
 /// The field will be untokenized and indexed.
 pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        analyzer: None,
+        index_reversed: false,
+        similarity: Similarity::BM25,
+        fieldnorm_precision: FieldNormPrecision::Compact,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
     coerce: false,
+    blob_threshold: None,
 };
 
 /// The field will be tokenized and indexed.
@@ -278,10 +465,15 @@ pub const TEXT: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        analyzer: None,
+        index_reversed: false,
+        similarity: Similarity::BM25,
+        fieldnorm_precision: FieldNormPrecision::Compact,
     }),
     stored: false,
     coerce: false,
     fast: FastFieldTextOptions::IsEnabled(false),
+    blob_threshold: None,
 };
 
 impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
@@ -294,6 +486,7 @@ impl<T: Into<TextOptions>> BitOr<T> for TextOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            blob_threshold: other.blob_threshold.or(self.blob_threshold),
         }
     }
 }
@@ -311,6 +504,7 @@ impl From<StoredFlag> for TextOptions {
             stored: true,
             fast: FastFieldTextOptions::default(),
             coerce: false,
+            blob_threshold: None,
         }
     }
 }
@@ -322,6 +516,7 @@ impl From<CoerceFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::default(),
             coerce: true,
+            blob_threshold: None,
         }
     }
 }
@@ -333,6 +528,7 @@ impl From<FastFlag> for TextOptions {
             stored: false,
             fast: FastFieldTextOptions::IsEnabled(true),
             coerce: false,
+            blob_threshold: None,
         }
     }
 }
@@ -394,6 +590,53 @@ mod tests {
         assert_eq!(options3.indexing, None);
     }
 
+    #[test]
+    fn serde_index_reversed() {
+        let options =
+            TEXT.set_indexing_options(TextFieldIndexing::default().set_index_reversed(true));
+        let json = serde_json::to_string(&options).unwrap();
+        assert!(json.contains(r#""index_reversed":true"#));
+        let options2: TextOptions = serde_json::from_str(&json).unwrap();
+        assert!(options2.get_indexing_options().unwrap().index_reversed());
+        let json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!json.contains("index_reversed"));
+    }
+
+    #[test]
+    fn serde_fieldnorm_precision() {
+        let options = TEXT.set_indexing_options(
+            TextFieldIndexing::default().set_fieldnorm_precision(FieldNormPrecision::Linear),
+        );
+        let json = serde_json::to_string(&options).unwrap();
+        assert!(json.contains(r#""fieldnorm_precision":"linear""#));
+        let options2: TextOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            options2
+                .get_indexing_options()
+                .unwrap()
+                .fieldnorm_precision(),
+            FieldNormPrecision::Linear
+        );
+        let json = serde_json::to_string(&TEXT).unwrap();
+        assert!(!json.contains("fieldnorm_precision"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid similarity")]
+    fn test_set_invalid_similarity_should_panic() {
+        let _ =
+            TextFieldIndexing::default().set_similarity(Similarity::LmDirichlet { mu: f32::NAN });
+    }
+
+    #[test]
+    fn test_fieldnorm_precision_ids() {
+        assert_eq!(FieldNormPrecision::Linear.fieldnorm_to_id(50), 50);
+        assert_eq!(FieldNormPrecision::Linear.id_to_fieldnorm(50), 50);
+        assert_eq!(FieldNormPrecision::Linear.fieldnorm_to_id(1_000), 255);
+        assert_eq!(FieldNormPrecision::Compact.fieldnorm_to_id(49), 44);
+        assert_eq!(FieldNormPrecision::Compact.id_to_fieldnorm(44), 48);
+    }
+
     #[test]
     fn serde_fast_field_tokenizer() {
         let json = r#" {
@@ -0,0 +1,721 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::{Snippet, DEFAULT_MAX_NUM_CHARS, DEFAULT_SNIPPET_POSTFIX, DEFAULT_SNIPPET_PREFIX};
+use crate::query::{Query, TermMatcher};
+use crate::schema::document::{Document, Value};
+use crate::schema::Field;
+use crate::tokenizer::{TextAnalyzer, Token};
+use crate::{Score, Searcher, Term};
+
+/// Score of a term matched by a predicate, like the ones of fuzzy queries.
+///
+/// It is the score of a term appearing in a single document.
+const TEXT_MATCHER_SCORE: Score = 0.5;
+
+/// Splits a text into the fragments a [`Highlighter`] selects its snippets from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fragmenter {
+    /// Fragments of at most `max_num_chars`, cut at token boundaries, like the ones of the
+    /// [`SnippetGenerator`](super::SnippetGenerator).
+    Span {
+        /// The maximum length of a fragment.
+        max_num_chars: usize,
+    },
+    /// Fragments made of a sentence. The sentences longer than `max_num_chars` are cut at
+    /// whitespaces.
+    Sentence {
+        /// The maximum length of a fragment.
+        max_num_chars: usize,
+    },
+    /// A single fragment, holding the whole text.
+    WholeField,
+}
+
+impl Default for Fragmenter {
+    fn default() -> Self {
+        Fragmenter::Span {
+            max_num_chars: DEFAULT_MAX_NUM_CHARS,
+        }
+    }
+}
+
+impl Fragmenter {
+    /// Returns the byte ranges of the fragments of `text`, given the byte ranges of its tokens.
+    pub(super) fn fragments(&self, text: &str, tokens: &[Range<usize>]) -> Vec<Range<usize>> {
+        match *self {
+            Fragmenter::Span { max_num_chars } => span_fragments(tokens, max_num_chars),
+            Fragmenter::Sentence { max_num_chars } => sentences(text)
+                .into_iter()
+                .flat_map(|sentence| split_at_whitespaces(text, sentence, max_num_chars))
+                .collect(),
+            Fragmenter::WholeField => std::iter::once(0..text.len()).collect(),
+        }
+    }
+}
+
+fn span_fragments(tokens: &[Range<usize>], max_num_chars: usize) -> Vec<Range<usize>> {
+    let mut fragments = Vec::new();
+    let mut fragment: Option<Range<usize>> = None;
+    for token in tokens {
+        fragment = match fragment {
+            Some(fragment) if token.end - fragment.start <= max_num_chars => {
+                Some(fragment.start..token.end)
+            }
+            fragment_opt => {
+                fragments.extend(fragment_opt);
+                Some(token.clone())
+            }
+        };
+    }
+    fragments.extend(fragment);
+    fragments
+}
+
+/// Returns the byte ranges of the sentences of `text`, without their surrounding whitespaces.
+///
+/// A sentence ends with a line break, or with a `.`, `!` or `?` followed by a whitespace.
+fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut sentence_start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let is_sentence_end = match c {
+            '\n' => true,
+            '.' | '!' | '?' => chars
+                .peek()
+                .is_none_or(|(_, next_c)| next_c.is_whitespace()),
+            _ => false,
+        };
+        if is_sentence_end {
+            let sentence_end = offset + c.len_utf8();
+            sentences.extend(trim_range(text, sentence_start..sentence_end));
+            sentence_start = sentence_end;
+        }
+    }
+    sentences.extend(trim_range(text, sentence_start..text.len()));
+    sentences
+}
+
+/// Removes the whitespaces around a range of `text`, returning `None` if nothing is left.
+fn trim_range(text: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let slice = &text[range.clone()];
+    let trimmed_start = slice.trim_start();
+    let start = range.start + slice.len() - trimmed_start.len();
+    let end = start + trimmed_start.trim_end().len();
+    (start < end).then_some(start..end)
+}
+
+/// Splits a range of `text` into ranges of at most `max_num_chars`, cut at whitespaces when
+/// possible.
+fn split_at_whitespaces(
+    text: &str,
+    range: Range<usize>,
+    max_num_chars: usize,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = range.start;
+    while range.end - start > max_num_chars {
+        let mut limit = start + max_num_chars;
+        while !text.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        let mut cut = text[start..limit]
+            .rfind(char::is_whitespace)
+            .map(|offset| start + offset)
+            .filter(|&cut| cut > start)
+            .unwrap_or(limit);
+        if cut == start {
+            // A single char is longer than `max_num_chars`.
+            cut = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+        ranges.extend(trim_range(text, start..cut));
+        start = cut;
+    }
+    ranges.extend(trim_range(text, start..range.end));
+    ranges
+}
+
+#[derive(Clone, Debug, bon::Builder)]
+/// Options of the highlighting of a field, see [`Highlighter::set_field_options`].
+pub struct HighlightOptions {
+    #[builder(default)]
+    /// How the text is split into fragments.
+    ///
+    /// Defaults to fragments of 150 chars, cut at token boundaries.
+    fragmenter: Fragmenter,
+    #[builder(default = 1)]
+    /// The maximum number of fragments highlighted, the ones with the best matches.
+    max_num_fragments: usize,
+    #[builder(default = true)]
+    /// Whether the selected fragments that are next to each other in the text are merged
+    /// into a single snippet.
+    merge_adjacent_fragments: bool,
+    #[builder(default = DEFAULT_SNIPPET_PREFIX.to_string(), into)]
+    /// The tag inserted before the highlighted parts by [`Snippet::to_html`].
+    pre_tag: String,
+    #[builder(default = DEFAULT_SNIPPET_POSTFIX.to_string(), into)]
+    /// The tag inserted after the highlighted parts by [`Snippet::to_html`].
+    post_tag: String,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions::builder().build()
+    }
+}
+
+/// Returns `true` if the query matches the text of a term, see [`TermMatcher::Text`].
+pub(super) type TextMatcher = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// The terms of a phrase with their positions, and its slop.
+pub(super) type PhraseTerms<'a> = (&'a [(usize, Term)], u32);
+
+/// The terms matched by a query, grouped by field.
+#[derive(Default)]
+pub(super) struct QueryTerms<'a> {
+    terms: HashMap<Field, Vec<&'a Term>>,
+    phrases: HashMap<Field, Vec<PhraseTerms<'a>>>,
+    text_matchers: HashMap<Field, Vec<TextMatcher>>,
+}
+
+impl<'a> QueryTerms<'a> {
+    pub(super) fn collect(query: &'a dyn Query) -> QueryTerms<'a> {
+        let mut query_terms = QueryTerms::default();
+        query.term_matchers(&mut |term_matcher| match term_matcher {
+            TermMatcher::Term(term) => {
+                query_terms
+                    .terms
+                    .entry(term.field())
+                    .or_default()
+                    .push(term);
+            }
+            TermMatcher::Phrase { terms, slop } => {
+                if let Some((_, first_term)) = terms.first() {
+                    query_terms
+                        .phrases
+                        .entry(first_term.field())
+                        .or_default()
+                        .push((terms, slop));
+                }
+            }
+            TermMatcher::Text { field, matches } => {
+                query_terms
+                    .text_matchers
+                    .entry(field)
+                    .or_default()
+                    .push(matches);
+            }
+        });
+        query_terms
+    }
+
+    /// Removes and returns the terms, the phrases and the text matchers of `field`.
+    pub(super) fn take_field(
+        &mut self,
+        field: Field,
+    ) -> (Vec<&'a Term>, Vec<PhraseTerms<'a>>, Vec<TextMatcher>) {
+        (
+            self.terms.remove(&field).unwrap_or_default(),
+            self.phrases.remove(&field).unwrap_or_default(),
+            self.text_matchers.remove(&field).unwrap_or_default(),
+        )
+    }
+}
+
+/// Returns the score of the matches of a term, the rarer the term the higher the score, or `None`
+/// if the term is not in the index.
+pub(super) fn term_score(searcher: &Searcher, term: &Term) -> crate::Result<Option<Score>> {
+    let doc_freq = searcher.doc_freq(term)?;
+    if doc_freq == 0 {
+        return Ok(None);
+    }
+    Ok(Some(1.0 / (1.0 + doc_freq as Score)))
+}
+
+/// Returns the text of the values of `field` in the document, joined by a space.
+///
+/// The text is built the way the indexer computes the offsets of the tokens: every value,
+/// including empty strings and pre-tokenized strings, is followed by a separator.
+pub(super) fn field_text<D: Document>(doc: &D, field: Field) -> String {
+    let mut text = String::new();
+    let mut is_first_value = true;
+    for (value_field, value) in doc.iter_fields_and_values() {
+        let value = value as D::Value<'_>;
+        if value_field != field {
+            continue;
+        }
+        let pre_tokenized_text;
+        let val = if let Some(val) = value.as_str() {
+            val
+        } else if let Some(pre_tokenized) = value.as_pre_tokenized_text() {
+            pre_tokenized_text = pre_tokenized.text;
+            &pre_tokenized_text[..]
+        } else {
+            continue;
+        };
+        if !is_first_value {
+            text.push(' ');
+        }
+        is_first_value = false;
+        text.push_str(val);
+    }
+    text
+}
+
+struct PhraseMatcher {
+    /// The text of the terms, with their position in the phrase and their score.
+    terms: Vec<(usize, String, Score)>,
+    slop: u32,
+}
+
+struct FieldHighlighter {
+    tokenizer: TextAnalyzer,
+    terms: BTreeMap<String, Score>,
+    phrases: Vec<PhraseMatcher>,
+    text_matchers: Vec<TextMatcher>,
+    options: HighlightOptions,
+}
+
+impl FieldHighlighter {
+    /// Returns the byte ranges of the parts of the text matched by the query, with their score.
+    fn find_matches(&self, tokens: &[Token]) -> Vec<(Range<usize>, Score)> {
+        let mut matches = Vec::new();
+        for token in tokens {
+            if let Some(&score) = self.terms.get(&token.text) {
+                matches.push((token.offset_from..token.offset_to, score));
+            } else if self
+                .text_matchers
+                .iter()
+                .any(|text_matcher| text_matcher(&token.text))
+            {
+                matches.push((token.offset_from..token.offset_to, TEXT_MATCHER_SCORE));
+            }
+        }
+        if self.phrases.is_empty() {
+            return matches;
+        }
+        let mut tokens_by_position: HashMap<usize, Vec<&Token>> = HashMap::new();
+        for token in tokens {
+            tokens_by_position
+                .entry(token.position)
+                .or_default()
+                .push(token);
+        }
+        for phrase in &self.phrases {
+            let (first_offset, first_text, _) = &phrase.terms[0];
+            for first_token in tokens.iter().filter(|token| &token.text == first_text) {
+                let Some(phrase_position) = first_token.position.checked_sub(*first_offset) else {
+                    continue;
+                };
+                let phrase_tokens: Option<Vec<&Token>> = phrase
+                    .terms
+                    .iter()
+                    .map(|(offset, text, _)| {
+                        let position = phrase_position + offset;
+                        let slop = phrase.slop as usize;
+                        (position.saturating_sub(slop)..=position + slop)
+                            .flat_map(|position| tokens_by_position.get(&position))
+                            .flatten()
+                            .find(|token| &token.text == text)
+                            .copied()
+                    })
+                    .collect();
+                if let Some(phrase_tokens) = phrase_tokens {
+                    for (token, (_, _, score)) in phrase_tokens.iter().zip(&phrase.terms) {
+                        matches.push((token.offset_from..token.offset_to, *score));
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    fn highlight(&self, text: &str) -> Vec<Snippet> {
+        let mut tokens = Vec::new();
+        self.tokenizer
+            .clone()
+            .token_stream(text)
+            .process(&mut |token| tokens.push(token.clone()));
+        let matches = self.find_matches(&tokens);
+        let token_ranges: Vec<Range<usize>> = tokens
+            .iter()
+            .map(|token| token.offset_from..token.offset_to)
+            .collect();
+        select_snippets(text, &token_ranges, &matches, &self.options)
+    }
+}
+
+/// Returns the snippets of the best fragments of `text`, given the byte ranges of its tokens and
+/// the parts of the text matched by the query, with their score.
+pub(super) fn select_snippets(
+    text: &str,
+    tokens: &[Range<usize>],
+    matches: &[(Range<usize>, Score)],
+    options: &HighlightOptions,
+) -> Vec<Snippet> {
+    if matches.is_empty() {
+        return Vec::new();
+    }
+    let mut scored_fragments: Vec<(Score, Range<usize>)> = options
+        .fragmenter
+        .fragments(text, tokens)
+        .into_iter()
+        .map(|fragment| {
+            let score = matches
+                .iter()
+                .filter(|(range, _)| contains(&fragment, range))
+                .map(|(_, score)| score)
+                .sum();
+            (score, fragment)
+        })
+        .filter(|(score, _)| *score > 0.0)
+        .collect();
+    scored_fragments.sort_by(|(left_score, left), (right_score, right)| {
+        right_score
+            .total_cmp(left_score)
+            .then(left.start.cmp(&right.start))
+    });
+    let mut fragments: Vec<Range<usize>> = scored_fragments
+        .into_iter()
+        .take(options.max_num_fragments)
+        .map(|(_, fragment)| fragment)
+        .collect();
+    fragments.sort_by_key(|fragment| fragment.start);
+    if options.merge_adjacent_fragments {
+        fragments = merge_adjacent_fragments(text, fragments);
+    }
+    fragments
+        .into_iter()
+        .map(|fragment| {
+            let highlighted = matches
+                .iter()
+                .filter(|(range, _)| contains(&fragment, range))
+                .map(|(range, _)| range.start - fragment.start..range.end - fragment.start)
+                .collect();
+            let mut snippet = Snippet::new(&text[fragment], highlighted);
+            snippet.set_snippet_prefix_postfix(&options.pre_tag, &options.post_tag);
+            snippet
+        })
+        .collect()
+}
+
+fn contains(fragment: &Range<usize>, range: &Range<usize>) -> bool {
+    fragment.start <= range.start && range.end <= fragment.end
+}
+
+/// Merges the sorted fragments that overlap, or that are only separated by whitespaces.
+fn merge_adjacent_fragments(text: &str, fragments: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut merged_fragments: Vec<Range<usize>> = Vec::with_capacity(fragments.len());
+    for fragment in fragments {
+        if let Some(last_fragment) = merged_fragments.last_mut() {
+            if fragment.start <= last_fragment.end
+                || text[last_fragment.end..fragment.start].trim().is_empty()
+            {
+                last_fragment.end = last_fragment.end.max(fragment.end);
+                continue;
+            }
+        }
+        merged_fragments.push(fragment);
+    }
+    merged_fragments
+}
+
+/// `Highlighter` highlights the parts of the fields of a document that are matched by a query.
+///
+/// Contrary to the [`SnippetGenerator`](super::SnippetGenerator), it works on several fields,
+/// each with its own [`HighlightOptions`], and relies on [`Query::term_matchers`] to highlight
+/// what the query actually matches: the phrases of phrase queries are only highlighted where
+/// their terms are next to each other, and the terms matched by fuzzy or regex queries are
+/// highlighted too.
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::query::QueryParser;
+/// # use tantivy::schema::{Schema, TEXT};
+/// # use tantivy::{doc, Index, IndexWriter};
+/// use tantivy::snippet::{Fragmenter, HighlightOptions, Highlighter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// let doc = doc!(body => "The sea is calm. A boat sails on the sea. Birds fly.");
+/// index_writer.add_document(doc.clone())?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![body]).parse_query("boat")?;
+/// let mut highlighter = Highlighter::create(&searcher, &*query, &[body])?;
+/// highlighter.set_field_options(
+///     body,
+///     HighlightOptions::builder()
+///         .fragmenter(Fragmenter::Sentence { max_num_chars: 100 })
+///         .pre_tag("<em>")
+///         .post_tag("</em>")
+///         .build(),
+/// );
+/// let snippets = highlighter.highlight_doc(&doc, body);
+/// assert_eq!(snippets[0].to_html(), "A <em>boat</em> sails on the sea.");
+/// #    Ok(())
+/// # }
+/// ```
+pub struct Highlighter {
+    fields: HashMap<Field, FieldHighlighter>,
+}
+
+impl Highlighter {
+    /// Creates a highlighter for the matches of `query` in the given fields, with the
+    /// default [`HighlightOptions`].
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        fields: &[Field],
+    ) -> crate::Result<Highlighter> {
+        let mut query_terms = QueryTerms::collect(query);
+        let term_text_and_score = |term: &Term| -> crate::Result<Option<(String, Score)>> {
+            let term_value = term.value();
+            let Some(term_str) = term_value.as_str() else {
+                return Ok(None);
+            };
+            Ok(term_score(searcher, term)?.map(|score| (term_str.to_string(), score)))
+        };
+        let mut field_highlighters = HashMap::new();
+        for &field in fields {
+            let (field_terms, field_phrases, text_matchers) = query_terms.take_field(field);
+            let mut terms = BTreeMap::new();
+            for term in field_terms {
+                terms.extend(term_text_and_score(term)?);
+            }
+            let mut phrases = Vec::new();
+            for (phrase_terms, slop) in field_phrases {
+                let mut terms = Vec::with_capacity(phrase_terms.len());
+                for (offset, term) in phrase_terms {
+                    if let Some((text, score)) = term_text_and_score(term)? {
+                        terms.push((*offset, text, score));
+                    }
+                }
+                // A phrase with a term that is not in the index matches nothing.
+                if terms.len() == phrase_terms.len() {
+                    terms.sort_by_key(|(offset, _, _)| *offset);
+                    phrases.push(PhraseMatcher { terms, slop });
+                }
+            }
+            let field_highlighter = FieldHighlighter {
+                tokenizer: searcher.index().tokenizer_for_field(field)?,
+                terms,
+                phrases,
+                text_matchers,
+                options: HighlightOptions::default(),
+            };
+            field_highlighters.insert(field, field_highlighter);
+        }
+        Ok(Highlighter {
+            fields: field_highlighters,
+        })
+    }
+
+    /// Sets the options of the highlighting of `field`.
+    ///
+    /// This has no effect if the highlighter was not created for this field.
+    pub fn set_field_options(&mut self, field: Field, options: HighlightOptions) {
+        if let Some(field_highlighter) = self.fields.get_mut(&field) {
+            field_highlighter.options = options;
+        }
+    }
+
+    /// Highlights the values of `field` in the given document.
+    ///
+    /// The values of the field are joined with a whitespace. Returns the snippets of the
+    /// selected fragments, in the order of the text, or no snippets if nothing is matched.
+    pub fn highlight_doc<D: Document>(&self, doc: &D, field: Field) -> Vec<Snippet> {
+        self.highlight_text(field, &field_text(doc, field))
+    }
+
+    /// Highlights a text of `field`.
+    ///
+    /// Returns the snippets of the selected fragments, in the order of the text, or no
+    /// snippets if nothing is matched.
+    pub fn highlight_text(&self, field: Field, text: &str) -> Vec<Snippet> {
+        self.fields
+            .get(&field)
+            .map(|field_highlighter| field_highlighter.highlight(text))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sentences, Fragmenter, HighlightOptions, Highlighter};
+    use crate::query::{FuzzyTermQuery, PhraseQuery, Query, QueryParser};
+    use crate::schema::{Field, Schema, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    const BODY: &str =
+        "The quick brown fox jumps. The dog is quick!\nA fox, brown and quick, naps.";
+
+    fn create_index() -> crate::Result<(Index, Field, Field)> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "A quick fox", body => BODY))?;
+        index_writer.commit()?;
+        Ok((index, title, body))
+    }
+
+    fn highlight(
+        index: &Index,
+        query: &dyn Query,
+        field: Field,
+        options: HighlightOptions,
+    ) -> crate::Result<Vec<String>> {
+        let searcher = index.reader()?.searcher();
+        let mut highlighter = Highlighter::create(&searcher, query, &[field])?;
+        highlighter.set_field_options(field, options);
+        Ok(highlighter
+            .highlight_text(field, BODY)
+            .iter()
+            .map(|snippet| snippet.to_html())
+            .collect())
+    }
+
+    #[test]
+    fn test_sentences() {
+        let sentences: Vec<&str> = sentences(BODY)
+            .into_iter()
+            .map(|sentence| &BODY[sentence])
+            .collect();
+        assert_eq!(
+            sentences,
+            [
+                "The quick brown fox jumps.",
+                "The dog is quick!",
+                "A fox, brown and quick, naps."
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlighter_fragmenters() -> crate::Result<()> {
+        let (index, _title, body) = create_index()?;
+        let query = QueryParser::for_index(&index, vec![body]).parse_query("dog")?;
+        let sentence_options =
+            || HighlightOptions::builder().fragmenter(Fragmenter::Sentence { max_num_chars: 100 });
+        assert_eq!(
+            highlight(&index, &*query, body, sentence_options().build())?,
+            ["The <b>dog</b> is quick!"]
+        );
+        assert_eq!(
+            highlight(
+                &index,
+                &*query,
+                body,
+                HighlightOptions::builder()
+                    .fragmenter(Fragmenter::WholeField)
+                    .pre_tag("[")
+                    .post_tag("]")
+                    .build()
+            )?,
+            ["The quick brown fox jumps. The [dog] is quick!\nA fox, brown and quick, naps."]
+        );
+        let query = QueryParser::for_index(&index, vec![body]).parse_query("jumps naps")?;
+        assert_eq!(
+            highlight(
+                &index,
+                &*query,
+                body,
+                sentence_options().max_num_fragments(2).build()
+            )?,
+            [
+                "The quick brown fox <b>jumps</b>.",
+                "A fox, brown and quick, <b>naps</b>."
+            ]
+        );
+        let query = QueryParser::for_index(&index, vec![body]).parse_query("jumps dog")?;
+        assert_eq!(
+            highlight(
+                &index,
+                &*query,
+                body,
+                sentence_options().max_num_fragments(2).build()
+            )?,
+            ["The quick brown fox <b>jumps</b>. The <b>dog</b> is quick!"]
+        );
+        assert_eq!(
+            highlight(
+                &index,
+                &*query,
+                body,
+                sentence_options()
+                    .max_num_fragments(2)
+                    .merge_adjacent_fragments(false)
+                    .build()
+            )?,
+            [
+                "The quick brown fox <b>jumps</b>.",
+                "The <b>dog</b> is quick!"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_highlighter_phrase_and_fuzzy_queries() -> crate::Result<()> {
+        let (index, _title, body) = create_index()?;
+        let whole_field = || {
+            HighlightOptions::builder()
+                .fragmenter(Fragmenter::WholeField)
+                .build()
+        };
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(body, "quick"),
+            Term::from_field_text(body, "brown"),
+        ]);
+        assert_eq!(
+            highlight(&index, &phrase_query, body, whole_field())?,
+            [
+                "The <b>quick</b> <b>brown</b> fox jumps. The dog is quick!\nA fox, brown and \
+                 quick, naps."
+            ]
+        );
+        let fuzzy_query = FuzzyTermQuery::new(Term::from_field_text(body, "dgo"), 1, true);
+        assert_eq!(
+            highlight(&index, &fuzzy_query, body, whole_field())?,
+            [
+                "The quick brown fox jumps. The <b>dog</b> is quick!\nA fox, brown and quick, \
+                 naps."
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_highlighter_per_field_options() -> crate::Result<()> {
+        let (index, title, body) = create_index()?;
+        let searcher = index.reader()?.searcher();
+        let query = QueryParser::for_index(&index, vec![title, body]).parse_query("fox")?;
+        let mut highlighter = Highlighter::create(&searcher, &*query, &[title, body])?;
+        highlighter.set_field_options(
+            title,
+            HighlightOptions::builder()
+                .pre_tag("<em>")
+                .post_tag("</em>")
+                .build(),
+        );
+        highlighter.set_field_options(
+            body,
+            HighlightOptions::builder()
+                .fragmenter(Fragmenter::Sentence { max_num_chars: 20 })
+                .build(),
+        );
+        let title_snippets = highlighter.highlight_text(title, "A quick fox");
+        assert_eq!(title_snippets[0].to_html(), "A quick <em>fox</em>");
+        let body_snippets = highlighter.highlight_text(body, BODY);
+        assert_eq!(body_snippets[0].to_html(), "The quick brown <b>fox</b>");
+        Ok(())
+    }
+}
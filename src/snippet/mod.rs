@@ -54,6 +54,14 @@
 //!
 //! SnippetGenerator needs to be created from the `Searcher` and the query, and the field on which
 //! the `SnippetGenerator` should generate the snippets.
+//!
+//! [`Highlighter`] highlights several fields at once, with a choice of [`Fragmenter`] and of
+//! tags per field, and highlights the terms actually matched by phrase, fuzzy or regex queries.
+//! [`OffsetsHighlighter`] does the same without tokenizing the text again, from the offsets
+//! recorded in the postings of the fields.
+
+mod highlighter;
+mod offsets_highlighter;
 
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
@@ -61,6 +69,8 @@ use std::ops::Range;
 
 use htmlescape::encode_minimal;
 
+pub use self::highlighter::{Fragmenter, HighlightOptions, Highlighter};
+pub use self::offsets_highlighter::OffsetsHighlighter;
 use crate::query::Query;
 use crate::schema::document::{Document, Value};
 use crate::schema::Field;
@@ -0,0 +1,460 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use super::highlighter::{field_text, select_snippets, term_score, QueryTerms};
+use super::{HighlightOptions, Snippet};
+use crate::docset::{DocSet, TERMINATED};
+use crate::postings::{Postings, SegmentPostings};
+use crate::query::Query;
+use crate::schema::document::Document;
+use crate::schema::{Field, IndexRecordOption};
+use crate::{DocAddress, Score, Searcher, TantivyError, Term};
+
+struct OffsetsPhraseMatcher {
+    /// The terms, with their position in the phrase and their score.
+    terms: Vec<(usize, Term, Score)>,
+    slop: u32,
+}
+
+struct OffsetsFieldHighlighter {
+    terms: BTreeMap<Term, Score>,
+    phrases: Vec<OffsetsPhraseMatcher>,
+    options: HighlightOptions,
+}
+
+/// The positions and the offsets of a term in a document.
+struct TermOccurrences {
+    positions: Vec<u32>,
+    offsets: Vec<(u32, u32)>,
+}
+
+/// Returns the positions and the offsets of the occurrences of a term in the document,
+/// or `None` if the document does not contain the term.
+fn term_occurrences(postings_opt: Option<SegmentPostings>, doc_id: u32) -> Option<TermOccurrences> {
+    let mut postings = postings_opt?;
+    if postings.seek(doc_id) != doc_id || doc_id == TERMINATED {
+        return None;
+    }
+    let mut occurrences = TermOccurrences {
+        positions: Vec::new(),
+        offsets: Vec::new(),
+    };
+    postings.positions(&mut occurrences.positions);
+    postings.offsets(&mut occurrences.offsets);
+    Some(occurrences)
+}
+
+fn offsets_range((offset_from, offset_to): (u32, u32)) -> Range<usize> {
+    offset_from as usize..offset_to as usize
+}
+
+/// `OffsetsHighlighter` highlights the parts of the fields of a document that are matched by a
+/// query, using the offsets recorded in the postings.
+///
+/// Contrary to the [`Highlighter`](super::Highlighter), it does not tokenize the text of the
+/// fields again, which dominates the cost of highlighting large documents. The fields have to be
+/// indexed with [`IndexRecordOption::WithFreqsAndPositionsAndOffsets`], and the text given to
+/// the highlighter has to be the text that was indexed: the values of a multivalued field are
+/// joined by a space.
+///
+/// The terms matched by fuzzy or regex queries are found by going through the term dictionaries
+/// of the fields when the highlighter is created.
+///
+/// # Example
+///
+/// ```rust
+/// # use tantivy::query::QueryParser;
+/// # use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, STORED};
+/// # use tantivy::{doc, DocAddress, Index, IndexWriter, TantivyDocument};
+/// use tantivy::snippet::OffsetsHighlighter;
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let text_options = TextOptions::default()
+///     .set_indexing_options(
+///         TextFieldIndexing::default()
+///             .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+///     )
+///     | STORED;
+/// let body = schema_builder.add_text_field("body", text_options);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(body => "The sea is calm. A boat sails on the sea."))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+/// let query = QueryParser::for_index(&index, vec![body]).parse_query("boat")?;
+/// let highlighter = OffsetsHighlighter::create(&searcher, &*query, &[body])?;
+/// let doc_address = DocAddress::new(0, 0);
+/// let doc: TantivyDocument = searcher.doc(doc_address)?;
+/// let snippets = highlighter.highlight_doc(&searcher, doc_address, &doc, body)?;
+/// assert_eq!(snippets[0].to_html(), "The sea is calm. A <b>boat</b> sails on the sea.");
+/// #    Ok(())
+/// # }
+/// ```
+pub struct OffsetsHighlighter {
+    fields: HashMap<Field, OffsetsFieldHighlighter>,
+}
+
+impl OffsetsHighlighter {
+    /// Creates a highlighter for the matches of `query` in the given fields, with the
+    /// default [`HighlightOptions`].
+    ///
+    /// Returns an error if one of the fields does not record offsets.
+    pub fn create(
+        searcher: &Searcher,
+        query: &dyn Query,
+        fields: &[Field],
+    ) -> crate::Result<OffsetsHighlighter> {
+        let schema = searcher.schema();
+        let mut query_terms = QueryTerms::collect(query);
+        let mut field_highlighters = HashMap::new();
+        for &field in fields {
+            let field_entry = schema.get_field_entry(field);
+            let has_offsets = field_entry
+                .field_type()
+                .index_record_option()
+                .is_some_and(IndexRecordOption::has_offsets);
+            if !has_offsets {
+                return Err(TantivyError::SchemaError(format!(
+                    "The field {:?} does not record offsets",
+                    field_entry.name()
+                )));
+            }
+            let (field_terms, field_phrases, text_matchers) = query_terms.take_field(field);
+            let mut terms = BTreeMap::new();
+            for term in field_terms {
+                if let Some(score) = term_score(searcher, term)? {
+                    terms.insert(term.clone(), score);
+                }
+            }
+            if !text_matchers.is_empty() {
+                for segment_reader in searcher.segment_readers() {
+                    let inverted_index = segment_reader.inverted_index(field)?;
+                    let mut term_stream = inverted_index.terms().stream()?;
+                    while term_stream.advance() {
+                        let Ok(text) = std::str::from_utf8(term_stream.key()) else {
+                            continue;
+                        };
+                        if text_matchers.iter().any(|text_matcher| text_matcher(text)) {
+                            let term = Term::from_field_text(field, text);
+                            if let Entry::Vacant(entry) = terms.entry(term) {
+                                if let Some(score) = term_score(searcher, entry.key())? {
+                                    entry.insert(score);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            let mut phrases = Vec::new();
+            for (phrase_terms, slop) in field_phrases {
+                let mut terms = Vec::with_capacity(phrase_terms.len());
+                for (offset, term) in phrase_terms {
+                    if let Some(score) = term_score(searcher, term)? {
+                        terms.push((*offset, term.clone(), score));
+                    }
+                }
+                // A phrase with a term that is not in the index matches nothing.
+                if terms.len() == phrase_terms.len() {
+                    terms.sort_by_key(|(offset, _, _)| *offset);
+                    phrases.push(OffsetsPhraseMatcher { terms, slop });
+                }
+            }
+            let field_highlighter = OffsetsFieldHighlighter {
+                terms,
+                phrases,
+                options: HighlightOptions::default(),
+            };
+            field_highlighters.insert(field, field_highlighter);
+        }
+        Ok(OffsetsHighlighter {
+            fields: field_highlighters,
+        })
+    }
+
+    /// Sets the options of the highlighting of `field`.
+    ///
+    /// This has no effect if the highlighter was not created for this field.
+    pub fn set_field_options(&mut self, field: Field, options: HighlightOptions) {
+        if let Some(field_highlighter) = self.fields.get_mut(&field) {
+            field_highlighter.options = options;
+        }
+    }
+
+    /// Highlights the values of `field` in the document at `doc_address`, whose stored version
+    /// is `doc`.
+    ///
+    /// Returns the snippets of the selected fragments, in the order of the text, or no
+    /// snippets if nothing is matched.
+    pub fn highlight_doc<D: Document>(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+        doc: &D,
+        field: Field,
+    ) -> crate::Result<Vec<Snippet>> {
+        self.highlight_text(searcher, doc_address, field, &field_text(doc, field))
+    }
+
+    /// Highlights `text`, the text of `field` in the document at `doc_address`.
+    ///
+    /// Returns the snippets of the selected fragments, in the order of the text, or no
+    /// snippets if nothing is matched.
+    pub fn highlight_text(
+        &self,
+        searcher: &Searcher,
+        doc_address: DocAddress,
+        field: Field,
+        text: &str,
+    ) -> crate::Result<Vec<Snippet>> {
+        let Some(field_highlighter) = self.fields.get(&field) else {
+            return Ok(Vec::new());
+        };
+        let inverted_index = searcher
+            .segment_reader(doc_address.segment_ord)
+            .inverted_index(field)?;
+        let read_occurrences = |term: &Term| -> crate::Result<Option<TermOccurrences>> {
+            let postings_opt = inverted_index
+                .read_postings(term, IndexRecordOption::WithFreqsAndPositionsAndOffsets)?;
+            Ok(term_occurrences(postings_opt, doc_address.doc_id))
+        };
+        let mut matches: Vec<(Range<usize>, Score)> = Vec::new();
+        for (term, &score) in &field_highlighter.terms {
+            if let Some(occurrences) = read_occurrences(term)? {
+                matches.extend(
+                    occurrences
+                        .offsets
+                        .into_iter()
+                        .map(|offsets| (offsets_range(offsets), score)),
+                );
+            }
+        }
+        'phrases: for phrase in &field_highlighter.phrases {
+            let mut phrase_occurrences = Vec::with_capacity(phrase.terms.len());
+            for (_, term, _) in &phrase.terms {
+                let Some(occurrences) = read_occurrences(term)? else {
+                    continue 'phrases;
+                };
+                phrase_occurrences.push(occurrences);
+            }
+            let (first_offset, _, _) = phrase.terms[0];
+            for &first_position in &phrase_occurrences[0].positions {
+                let Some(phrase_position) = (first_position as usize).checked_sub(first_offset)
+                else {
+                    continue;
+                };
+                let slop = phrase.slop as usize;
+                let phrase_offsets: Option<Vec<(u32, u32)>> = phrase
+                    .terms
+                    .iter()
+                    .zip(&phrase_occurrences)
+                    .map(|((offset, _, _), occurrences)| {
+                        let position = phrase_position + offset;
+                        let position_range = position.saturating_sub(slop)..=position + slop;
+                        occurrences
+                            .positions
+                            .iter()
+                            .position(|&position| position_range.contains(&(position as usize)))
+                            .map(|idx| occurrences.offsets[idx])
+                    })
+                    .collect();
+                if let Some(phrase_offsets) = phrase_offsets {
+                    for (offsets, (_, _, score)) in phrase_offsets.into_iter().zip(&phrase.terms) {
+                        matches.push((offsets_range(offsets), *score));
+                    }
+                }
+            }
+        }
+        // Offsets that do not fit in the text mean that it is not the indexed text.
+        matches.retain(|(range, _)| {
+            range.end <= text.len()
+                && text.is_char_boundary(range.start)
+                && text.is_char_boundary(range.end)
+        });
+        let words: Vec<Range<usize>> = words(text);
+        Ok(select_snippets(
+            text,
+            &words,
+            &matches,
+            &field_highlighter.options,
+        ))
+    }
+}
+
+/// Returns the byte ranges of the words of `text`, the parts separated by whitespaces, which are
+/// used instead of tokens to build fragments.
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut word_start = None;
+    for (offset, c) in text.char_indices() {
+        match (c.is_whitespace(), word_start) {
+            (true, Some(start)) => {
+                words.push(start..offset);
+                word_start = None;
+            }
+            (false, None) => word_start = Some(offset),
+            _ => {}
+        }
+    }
+    if let Some(start) = word_start {
+        words.push(start..text.len());
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OffsetsHighlighter;
+    use crate::query::{FuzzyTermQuery, PhraseQuery, QueryParser};
+    use crate::schema::{
+        IndexRecordOption, OwnedValue, Schema, TextFieldIndexing, TextOptions, STORED, TEXT,
+    };
+    use crate::snippet::{Fragmenter, HighlightOptions};
+    use crate::tokenizer::{PreTokenizedString, Token};
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_offsets_highlighter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+        ) | STORED;
+        let body = schema_builder.add_text_field("body", text_options);
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "A dog barks."))?;
+        index_writer.add_document(doc!(
+            body => "The quick brown fox jumps. The dog is quick!",
+            body => "A fox, brown and quick, naps.",
+        ))?;
+        index_writer.commit()?;
+        // Merging the segments keeps the offsets.
+        index_writer.add_document(doc!(body => "Another dog."))?;
+        index_writer.commit()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let doc_address = DocAddress::new(0, 1);
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let highlight = |query: &dyn crate::query::Query| -> crate::Result<Vec<String>> {
+            let mut highlighter = OffsetsHighlighter::create(&searcher, query, &[body])?;
+            highlighter.set_field_options(
+                body,
+                HighlightOptions::builder()
+                    .fragmenter(Fragmenter::Sentence { max_num_chars: 100 })
+                    .max_num_fragments(3)
+                    .merge_adjacent_fragments(false)
+                    .build(),
+            );
+            Ok(highlighter
+                .highlight_doc(&searcher, doc_address, &doc, body)?
+                .iter()
+                .map(|snippet| snippet.to_html())
+                .collect())
+        };
+        let query = QueryParser::for_index(&index, vec![body]).parse_query("dog naps")?;
+        assert_eq!(
+            highlight(&*query)?,
+            [
+                "The <b>dog</b> is quick!",
+                "A fox, brown and quick, <b>naps</b>."
+            ]
+        );
+        let phrase_query = PhraseQuery::new(vec![
+            Term::from_field_text(body, "quick"),
+            Term::from_field_text(body, "brown"),
+        ]);
+        assert_eq!(
+            highlight(&phrase_query)?,
+            ["The <b>quick</b> <b>brown</b> fox jumps."]
+        );
+        let fuzzy_query = FuzzyTermQuery::new(Term::from_field_text(body, "nap"), 1, true);
+        assert_eq!(
+            highlight(&fuzzy_query)?,
+            ["A fox, brown and quick, <b>naps</b>."]
+        );
+
+        let query = QueryParser::for_index(&index, vec![title]).parse_query("dog")?;
+        assert!(OffsetsHighlighter::create(&searcher, &*query, &[title]).is_err());
+        Ok(())
+    }
+
+    fn highlight_values(values: Vec<OwnedValue>, query_str: &str) -> crate::Result<Vec<String>> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositionsAndOffsets),
+        ) | STORED;
+        let body = schema_builder.add_text_field("body", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let mut doc = TantivyDocument::default();
+        for value in values {
+            doc.add_field_value(body, &value);
+        }
+        index_writer.add_document(doc)?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+        let doc: TantivyDocument = searcher.doc(doc_address)?;
+        let query = QueryParser::for_index(&index, vec![body]).parse_query(query_str)?;
+        let highlighter = OffsetsHighlighter::create(&searcher, &*query, &[body])?;
+        Ok(highlighter
+            .highlight_doc(&searcher, doc_address, &doc, body)?
+            .iter()
+            .map(|snippet| snippet.to_html())
+            .collect())
+    }
+
+    #[test]
+    fn test_offsets_highlighter_empty_value() -> crate::Result<()> {
+        let values = vec![OwnedValue::from(""), OwnedValue::from("foo bar")];
+        assert_eq!(highlight_values(values, "bar")?, ["foo <b>bar</b>"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_offsets_highlighter_pre_tokenized_value() -> crate::Result<()> {
+        let pre_tokenized = PreTokenizedString {
+            text: "Fox jumps".to_string(),
+            tokens: vec![
+                Token {
+                    offset_from: 0,
+                    offset_to: 3,
+                    position: 0,
+                    text: "fox".to_string(),
+                    position_length: 1,
+                    ..Token::default()
+                },
+                Token {
+                    offset_from: 4,
+                    offset_to: 9,
+                    position: 1,
+                    text: "jumps".to_string(),
+                    position_length: 1,
+                    ..Token::default()
+                },
+            ],
+        };
+        let values = vec![
+            OwnedValue::PreTokStr(pre_tokenized),
+            OwnedValue::from("foo bar"),
+        ];
+        assert_eq!(
+            highlight_values(values.clone(), "bar")?,
+            ["Fox jumps foo <b>bar</b>"]
+        );
+        assert_eq!(
+            highlight_values(values, "jumps")?,
+            ["Fox <b>jumps</b> foo bar"]
+        );
+        Ok(())
+    }
+}
@@ -68,7 +68,11 @@ pub struct SegmentSpaceUsage {
     postings: PerFieldSpaceUsage,
     positions: PerFieldSpaceUsage,
     fast_fields: PerFieldSpaceUsage,
+    #[serde(default)]
+    fast_field_updates: ByteCount,
     fieldnorms: PerFieldSpaceUsage,
+    #[serde(default)]
+    suggest: PerFieldSpaceUsage,
 
     store: StoreSpaceUsage,
 
@@ -102,13 +106,34 @@ impl SegmentSpaceUsage {
             postings,
             positions,
             fast_fields,
+            fast_field_updates: ByteCount::default(),
             fieldnorms,
+            suggest: PerFieldSpaceUsage::default(),
             store,
             deletes,
             total,
         }
     }
 
+    pub(crate) fn with_suggest(self, suggest: PerFieldSpaceUsage) -> SegmentSpaceUsage {
+        SegmentSpaceUsage {
+            total: self.total + suggest.total(),
+            suggest,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_fast_field_updates(
+        self,
+        fast_field_updates: ByteCount,
+    ) -> SegmentSpaceUsage {
+        SegmentSpaceUsage {
+            total: self.total + fast_field_updates,
+            fast_field_updates,
+            ..self
+        }
+    }
+
     /// Space usage for the given component
     ///
     /// Clones the underlying data.
@@ -120,10 +145,13 @@ impl SegmentSpaceUsage {
             Postings => PerField(self.postings().clone()),
             Positions => PerField(self.positions().clone()),
             FastFields => PerField(self.fast_fields().clone()),
+            FastFieldUpdates => Basic(self.fast_field_updates()),
             FieldNorms => PerField(self.fieldnorms().clone()),
             Terms => PerField(self.termdict().clone()),
             SegmentComponent::Store => ComponentSpaceUsage::Store(self.store().clone()),
             SegmentComponent::TempStore => ComponentSpaceUsage::Store(self.store().clone()),
+            SegmentComponent::Blobs => ComponentSpaceUsage::Store(self.store().clone()),
+            Suggest => PerField(self.suggest().clone()),
             Delete => Basic(self.deletes()),
         }
     }
@@ -153,11 +181,21 @@ impl SegmentSpaceUsage {
         &self.fast_fields
     }
 
+    /// Space usage for the in-place updates of the fast fields
+    pub fn fast_field_updates(&self) -> ByteCount {
+        self.fast_field_updates
+    }
+
     /// Space usage for field norms
     pub fn fieldnorms(&self) -> &PerFieldSpaceUsage {
         &self.fieldnorms
     }
 
+    /// Space usage for the suggest FSTs
+    pub fn suggest(&self) -> &PerFieldSpaceUsage {
+        &self.suggest
+    }
+
     /// Space usage for stored documents
     pub fn store(&self) -> &StoreSpaceUsage {
         &self.store
@@ -176,18 +214,29 @@ impl SegmentSpaceUsage {
 
 /// Represents space usage for the Store for this segment.
 ///
-/// This is composed of two parts.
+/// This is composed of three parts.
 /// `data` represents the compressed data itself.
 /// `offsets` represents a lookup to find the start of a block
+/// `blobs` represents the values spilled out of the blocks into the blob file
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StoreSpaceUsage {
     data: ByteCount,
     offsets: ByteCount,
+    #[serde(default)]
+    blobs: ByteCount,
 }
 
 impl StoreSpaceUsage {
     pub(crate) fn new(data: ByteCount, offsets: ByteCount) -> StoreSpaceUsage {
-        StoreSpaceUsage { data, offsets }
+        StoreSpaceUsage {
+            data,
+            offsets,
+            blobs: ByteCount::default(),
+        }
+    }
+
+    pub(crate) fn with_blobs(self, blobs: ByteCount) -> StoreSpaceUsage {
+        StoreSpaceUsage { blobs, ..self }
     }
 
     /// Space usage for the data part of the store
@@ -200,9 +249,14 @@ impl StoreSpaceUsage {
         self.offsets
     }
 
+    /// Space usage for the blob file of the store
+    pub fn blobs_usage(&self) -> ByteCount {
+        self.blobs
+    }
+
     /// Total space usage in bytes for this Store
     pub fn total(&self) -> ByteCount {
-        self.data + self.offsets
+        self.data + self.offsets + self.blobs
     }
 }
 
@@ -210,7 +264,7 @@ impl StoreSpaceUsage {
 ///
 /// A field can appear with a single index (typically 0) or with multiple indexes.
 /// Multiple indexes are used to handle variable length things, where
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PerFieldSpaceUsage {
     fields: HashMap<Field, FieldUsage>,
     total: ByteCount,
@@ -290,9 +344,9 @@ impl FieldUsage {
 
 #[cfg(test)]
 mod test {
-    use crate::index::Index;
+    use crate::index::{Index, SegmentComponent};
     use crate::schema::{Field, Schema, FAST, INDEXED, STORED, TEXT};
-    use crate::space_usage::PerFieldSpaceUsage;
+    use crate::space_usage::{ComponentSpaceUsage, PerFieldSpaceUsage};
     use crate::{IndexWriter, Term};
 
     #[test]
@@ -482,4 +536,39 @@ mod test {
         assert!(segment_space_usage.deletes() > 0);
         Ok(())
     }
+
+    #[test]
+    fn test_fast_field_updates() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => 1u64, popularity => 1u64))?;
+        index_writer.add_document(doc!(id => 2u64, popularity => 2u64))?;
+        index_writer.commit()?;
+
+        let fast_fields_total = {
+            let searcher_space_usage = index.reader()?.searcher().space_usage()?;
+            let segment_space_usage = &searcher_space_usage.segments()[0];
+            assert_eq!(segment_space_usage.fast_field_updates(), 0);
+            segment_space_usage.fast_fields().total()
+        };
+
+        index_writer.update_fast_field(Term::from_field_u64(id, 1u64), popularity, 10u64)?;
+        index_writer.commit()?;
+
+        let searcher_space_usage = index.reader()?.searcher().space_usage()?;
+        let segment_space_usage = &searcher_space_usage.segments()[0];
+        let fast_field_updates = segment_space_usage.fast_field_updates();
+        assert!(fast_field_updates > 0);
+        assert_eq!(segment_space_usage.fast_fields().total(), fast_fields_total);
+        match segment_space_usage.component(SegmentComponent::FastFieldUpdates) {
+            ComponentSpaceUsage::Basic(num_bytes) => assert_eq!(num_bytes, fast_field_updates),
+            _ => panic!("expected a basic space usage for the fast field updates"),
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,157 @@
+/// Selects the paths of a stored JSON value returned by
+/// [`Searcher::doc_with_json_filter`](crate::Searcher::doc_with_json_filter).
+///
+/// Paths are relative to the JSON field, and their segments are separated by dots, e.g.
+/// `user.address.city`. Patterns are globs, in which `*` matches any sequence of characters,
+/// dots included.
+///
+/// A path is kept if it, or one of its parents, matches one of the include patterns, and
+/// neither it nor one of its parents matches one of the exclude patterns. If there are no
+/// include patterns, all the paths are included. Arrays do not add a segment to the path.
+///
+/// Objects left empty by the filtering are removed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonPathFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl JsonPathFilter {
+    /// Creates a new `JsonPathFilter` from include and exclude glob patterns.
+    pub fn new<S: ToString>(include: &[S], exclude: &[S]) -> JsonPathFilter {
+        JsonPathFilter {
+            include: include.iter().map(ToString::to_string).collect(),
+            exclude: exclude.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    /// Returns true if the path is excluded, assuming none of its parents is.
+    pub(crate) fn is_excluded(&self, path: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| glob_matches(pattern, path))
+    }
+
+    /// Returns true if the path, or one of its parents, matches an include pattern.
+    pub(crate) fn is_included(&self, path: &str) -> bool {
+        if self.include.is_empty() {
+            return true;
+        }
+        self.include.iter().any(|pattern| {
+            glob_matches(pattern, path)
+                || path
+                    .match_indices('.')
+                    .any(|(pos, _)| glob_matches(pattern, &path[..pos]))
+        })
+    }
+
+    /// Returns true if an include pattern may match one of the children of `path`.
+    pub(crate) fn may_include_children(&self, path: &str) -> bool {
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| glob_may_match_children(pattern, path))
+    }
+
+    /// Returns true if an exclude pattern may match one of the children of `path`.
+    pub(crate) fn may_exclude_children(&self, path: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| glob_may_match_children(pattern, path))
+    }
+}
+
+/// Returns the positions of the pattern reachable after matching `text`.
+///
+/// Position `pattern.len()` being reachable means that the pattern matches the whole text.
+fn glob_states(pattern: &[u8], text: &[u8]) -> Vec<bool> {
+    let mut states = vec![false; pattern.len() + 1];
+    states[0] = true;
+    close_over_stars(pattern, &mut states);
+    for &c in text {
+        let mut next_states = vec![false; pattern.len() + 1];
+        for (pos, _) in states.iter().enumerate().filter(|(_, &state)| state) {
+            match pattern.get(pos) {
+                Some(b'*') => next_states[pos] = true,
+                Some(&pattern_c) if pattern_c == c => next_states[pos + 1] = true,
+                _ => {}
+            }
+        }
+        close_over_stars(pattern, &mut next_states);
+        states = next_states;
+    }
+    states
+}
+
+/// A `*` may match the empty string.
+fn close_over_stars(pattern: &[u8], states: &mut [bool]) {
+    for pos in 0..pattern.len() {
+        if states[pos] && pattern[pos] == b'*' {
+            states[pos + 1] = true;
+        }
+    }
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    glob_states(pattern.as_bytes(), text.as_bytes())[pattern.len()]
+}
+
+/// Returns true if the pattern matches a path of the form `{path}.{suffix}`.
+///
+/// Whatever remains of a glob pattern can always be matched by some suffix.
+fn glob_may_match_children(pattern: &str, path: &str) -> bool {
+    let prefix = if path.is_empty() {
+        String::new()
+    } else {
+        format!("{path}.")
+    };
+    glob_states(pattern.as_bytes(), prefix.as_bytes())
+        .into_iter()
+        .any(|state| state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("a.b", "a.b"));
+        assert!(!glob_matches("a.b", "a.bc"));
+        assert!(glob_matches("a.*", "a.bc"));
+        assert!(glob_matches("a.*", "a.b.c"));
+        assert!(glob_matches("*.id", "a.b.id"));
+        assert!(!glob_matches("*.id", "id"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("a*c*e", "abcde"));
+        assert!(!glob_matches("a*c*e", "abcd"));
+    }
+
+    #[test]
+    fn test_json_path_filter_include() {
+        let filter = JsonPathFilter::new(&["user.name", "*.id"], &[]);
+        assert!(filter.is_included("user.name"));
+        assert!(filter.is_included("user.name.first"));
+        assert!(!filter.is_included("user"));
+        assert!(!filter.is_included("user.names"));
+        assert!(filter.is_included("group.id"));
+        assert!(filter.may_include_children(""));
+        assert!(filter.may_include_children("user"));
+        assert!(filter.may_include_children("group"));
+        let filter = JsonPathFilter::new(&["user.name"], &[]);
+        assert!(filter.may_include_children("user"));
+        assert!(!filter.may_include_children("group"));
+        assert!(!filter.may_exclude_children("user"));
+    }
+
+    #[test]
+    fn test_json_path_filter_exclude() {
+        let filter = JsonPathFilter::new(&[], &["user.password"]);
+        assert!(filter.is_included("user"));
+        assert!(!filter.is_excluded("user"));
+        assert!(filter.is_excluded("user.password"));
+        assert!(filter.may_exclude_children("user"));
+        assert!(!filter.may_exclude_children("group"));
+    }
+}
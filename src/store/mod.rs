@@ -7,6 +7,10 @@
 //! When the buffer exceeds `block_size` (defaults to 16K), the buffer is compressed
 //! using LZ4 or Zstd and the resulting block is written to disk.
 //!
+//! Values larger than the blob threshold of their field (see
+//! [`TextOptions::set_blob_threshold`](crate::schema::TextOptions::set_blob_threshold)) are
+//! written to a separate blob file instead, and the block only keeps their offset.
+//!
 //! One can then request for a specific `DocId`.
 //! A skip list helps navigating to the right block,
 //! decompresses it entirely and returns the document within it.
@@ -33,11 +37,13 @@ mod compressors;
 mod decompressors;
 mod footer;
 mod index;
+mod json_path_filter;
 mod reader;
 mod writer;
 
 pub use self::compressors::{Compressor, ZstdCompressor};
 pub use self::decompressors::Decompressor;
+pub use self::json_path_filter::JsonPathFilter;
 pub use self::reader::{CacheStats, StoreReader};
 pub(crate) use self::reader::{DocStoreVersion, DOCSTORE_CACHE_CAPACITY};
 pub use self::writer::StoreWriter;
@@ -318,6 +324,60 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "lz4-compression")]
+    #[test]
+    fn test_merge_with_merge_docstore_settings() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let text_field = schema_builder.add_text_field("text_field", TEXT | STORED);
+        let schema = schema_builder.build();
+        let index = Index::builder()
+            .schema(schema)
+            .settings(crate::IndexSettings {
+                docstore_compression: Compressor::None,
+                docstore_blocksize: 1_000,
+                docstore_merge_compression: Some(Compressor::Lz4),
+                docstore_merge_blocksize: Some(BLOCK_SIZE),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            // put enough data create enough blocks in the doc store to be considered for stacking
+            for _ in 0..2 {
+                for _ in 0..200 {
+                    index_writer.add_document(doc!(text_field=> LOREM))?;
+                }
+                index_writer.commit()?;
+            }
+        }
+        let searcher = index.reader()?.searcher();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(0)?;
+            assert_eq!(store.decompressor(), Decompressor::None);
+            assert!(store.block_checkpoints().count() > 50);
+        }
+        {
+            let segment_ids = index.searchable_segment_ids()?;
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let reader = searcher.segment_reader(0);
+        let store = reader.get_store_reader(10)?;
+        assert_eq!(store.decompressor(), Decompressor::Lz4);
+        assert!(store.block_checkpoints().count() < 20);
+        for doc in store.iter::<TantivyDocument>(reader.alive_bitset()) {
+            assert_eq!(
+                doc?.get_first(text_field).and_then(|v| v.as_str()),
+                Some(LOREM)
+            );
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_merge_of_small_segments() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -356,6 +416,87 @@ pub(crate) mod tests {
         assert_eq!(store.block_checkpoints().count(), 1);
         Ok(())
     }
+
+    #[test]
+    fn test_store_blobs() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let id_field = schema_builder.add_u64_field("id", schema::INDEXED | STORED);
+        let body_field =
+            schema_builder.add_text_field("body", TEXT.set_blob_threshold(LOREM.len()));
+        let bytes_field = schema_builder.add_bytes_field(
+            "bytes",
+            schema::BytesOptions::default().set_blob_threshold(8),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let large_body = LOREM.repeat(10);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            for id in 0..10u64 {
+                let body: &str = if id % 2 == 0 { &large_body } else { LOREM };
+                index_writer.add_document(doc!(
+                    id_field => id,
+                    body_field => body,
+                    bytes_field => vec![id as u8; id as usize],
+                ))?;
+                if id == 4 {
+                    index_writer.commit()?;
+                }
+            }
+            index_writer.commit()?;
+        }
+
+        let check_docs = |searcher: &crate::Searcher, ids: &[u64]| -> crate::Result<()> {
+            let mut num_docs = 0;
+            for segment_reader in searcher.segment_readers() {
+                let store = segment_reader.get_store_reader(0)?;
+                assert!(store.has_blobs());
+                let space_usage = store.space_usage();
+                assert!(space_usage.blobs_usage().get_bytes() > large_body.len() as u64);
+                assert!(space_usage.data_usage().get_bytes() < large_body.len() as u64);
+                for doc in store.iter::<TantivyDocument>(segment_reader.alive_bitset()) {
+                    let doc = doc?;
+                    let id = doc.get_first(id_field).and_then(|v| v.as_u64()).unwrap();
+                    assert!(ids.contains(&id));
+                    let expected_body: &str = if id % 2 == 0 { &large_body } else { LOREM };
+                    assert_eq!(
+                        doc.get_first(body_field).and_then(|v| v.as_str()),
+                        Some(expected_body)
+                    );
+                    assert_eq!(
+                        doc.get_first(bytes_field).and_then(|v| v.as_bytes()),
+                        Some(&vec![id as u8; id as usize][..])
+                    );
+                    num_docs += 1;
+                }
+            }
+            assert_eq!(num_docs, ids.len());
+            Ok(())
+        };
+
+        let reader = index.reader()?;
+        check_docs(&reader.searcher(), &(0..10).collect::<Vec<u64>>())?;
+
+        // Blobs are not read when their field is not loaded.
+        let store = reader.searcher().segment_reader(0).get_store_reader(0)?;
+        let doc: TantivyDocument = store.get_projected(0, &[id_field])?;
+        assert_eq!(doc.len(), 1);
+
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.delete_term(Term::from_field_u64(id_field, 3));
+            index_writer.delete_term(Term::from_field_u64(id_field, 4));
+            index_writer.commit()?;
+            let segment_ids = index.searchable_segment_ids()?;
+            index_writer.merge(&segment_ids).wait()?;
+            index_writer.wait_merging_threads()?;
+        }
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        check_docs(&searcher, &[0, 1, 2, 5, 6, 7, 8, 9])?;
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
@@ -6,7 +6,7 @@ use std::ops::{AddAssign, Range};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-use common::{BinarySerializable, OwnedBytes};
+use common::{BinarySerializable, HasLen, OwnedBytes};
 use lru::LruCache;
 
 use super::footer::DocStoreFooter;
@@ -16,11 +16,13 @@ use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
 use crate::schema::document::{BinaryDocumentDeserializer, DocumentDeserialize};
+use crate::schema::Field;
 use crate::space_usage::StoreSpaceUsage;
 use crate::store::index::Checkpoint;
-use crate::DocId;
+use crate::store::JsonPathFilter;
 #[cfg(feature = "quickwit")]
 use crate::Executor;
+use crate::{telemetry, DocId};
 
 pub(crate) const DOCSTORE_CACHE_CAPACITY: usize = 100;
 
@@ -64,6 +66,7 @@ pub struct StoreReader {
     decompressor: Decompressor,
     doc_store_version: DocStoreVersion,
     data: FileSlice,
+    blobs: FileSlice,
     skip_index: Arc<SkipIndex>,
     space_usage: StoreSpaceUsage,
     cache: BlockCache,
@@ -84,9 +87,11 @@ impl BlockCache {
             .and_then(|cache| cache.lock().unwrap().get(&pos).cloned())
         {
             self.cache_hits.fetch_add(1, Ordering::SeqCst);
+            telemetry::record_doc_store_cache_lookup(true);
             return Some(block);
         }
         self.cache_misses.fetch_add(1, Ordering::SeqCst);
+        telemetry::record_doc_store_cache_lookup(false);
         None
     }
 
@@ -166,6 +171,7 @@ impl StoreReader {
             decompressor: footer.decompressor,
             doc_store_version: footer.doc_store_version,
             data: data_file,
+            blobs: FileSlice::empty(),
             cache: BlockCache {
                 cache: NonZeroUsize::new(cache_num_blocks)
                     .map(|cache_num_blocks| Mutex::new(LruCache::new(cache_num_blocks))),
@@ -177,6 +183,20 @@ impl StoreReader {
         })
     }
 
+    /// Reads the values spilled out of the store blocks from `blobs_file`.
+    ///
+    /// See [`StoreWriter::with_blobs`](crate::store::StoreWriter::with_blobs).
+    pub fn with_blobs(mut self, blobs_file: FileSlice) -> StoreReader {
+        self.space_usage = self.space_usage.with_blobs(blobs_file.num_bytes());
+        self.blobs = blobs_file;
+        self
+    }
+
+    /// Returns true if some values were spilled out of the store blocks into the blob file.
+    pub(crate) fn has_blobs(&self) -> bool {
+        !self.blobs.is_empty()
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -241,7 +261,47 @@ impl StoreReader {
 
         let deserializer =
             BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                .map_err(crate::TantivyError::from)?;
+                .map_err(crate::TantivyError::from)?
+                .with_blobs(&self.blobs);
+        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    }
+
+    /// Reads the document `doc_id`, only deserializing the values of the given `fields`.
+    ///
+    /// The values of the other fields are skipped without being decoded, which is cheaper than
+    /// [`get`](Self::get) for documents with many stored fields.
+    pub fn get_projected<D: DocumentDeserialize>(
+        &self,
+        doc_id: DocId,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        let mut doc_bytes = self.get_document_bytes(doc_id)?;
+
+        let deserializer =
+            BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
+                .map_err(crate::TantivyError::from)?
+                .with_blobs(&self.blobs)
+                .with_fields_to_load(fields);
+        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    }
+
+    /// Reads the document `doc_id`, only deserializing the paths of the values of `json_field`
+    /// selected by `filter`.
+    ///
+    /// The values of the other paths are skipped without being decoded.
+    pub fn get_with_json_filter<D: DocumentDeserialize>(
+        &self,
+        doc_id: DocId,
+        json_field: Field,
+        filter: &JsonPathFilter,
+    ) -> crate::Result<D> {
+        let mut doc_bytes = self.get_document_bytes(doc_id)?;
+
+        let deserializer =
+            BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
+                .map_err(crate::TantivyError::from)?
+                .with_blobs(&self.blobs)
+                .with_json_filter(json_field, filter);
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 
@@ -283,7 +343,8 @@ impl StoreReader {
 
             let deserializer =
                 BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                    .map_err(crate::TantivyError::from)?;
+                    .map_err(crate::TantivyError::from)?
+                    .with_blobs(&self.blobs);
             D::deserialize(deserializer).map_err(crate::TantivyError::from)
         })
     }
@@ -433,21 +494,26 @@ impl StoreReader {
 
         let deserializer =
             BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
-                .map_err(crate::TantivyError::from)?;
+                .map_err(crate::TantivyError::from)?
+                .with_blobs(&self.blobs);
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::net::Ipv6Addr;
     use std::path::Path;
 
+    use serde_json::json;
+
     use super::*;
     use crate::directory::RamDirectory;
-    use crate::schema::{Field, TantivyDocument, Value};
+    use crate::schema::{Facet, Field, OwnedValue, Schema, TantivyDocument, Value, STORED, TEXT};
     use crate::store::tests::write_lorem_ipsum_store;
     use crate::store::Compressor;
-    use crate::Directory;
+    use crate::tokenizer::PreTokenizedString;
+    use crate::{DateTime, Directory, DocAddress, Index, IndexWriter};
 
     const BLOCK_SIZE: usize = 16_384;
 
@@ -460,6 +526,122 @@ mod tests {
         assert!(DocStoreVersion::V1 < DocStoreVersion::V2);
     }
 
+    #[test]
+    fn test_store_get_projected() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let score = schema_builder.add_f64_field("score", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let payload = schema_builder.add_bytes_field("payload", STORED);
+        let ip = schema_builder.add_ip_addr_field("ip", STORED);
+        let date = schema_builder.add_date_field("date", STORED);
+        let category = schema_builder.add_facet_field("category", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let pre_tokenized_body = PreTokenizedString {
+            text: "pre tokenized".to_string(),
+            tokens: Vec::new(),
+        };
+        index_writer.add_document(doc!(
+            title => "title",
+            body => "first body",
+            count => 3u64,
+            score => 1.5f64,
+            attributes => json!({"color": "red", "sizes": [1, 2, {"eu": 40}]}),
+            payload => vec![1u8, 2, 3],
+            ip => Ipv6Addr::LOCALHOST,
+            date => DateTime::from_timestamp_secs(1_000),
+            category => Facet::from("/a/b"),
+            body => pre_tokenized_body,
+            count => 4u64,
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+        let full_doc: TantivyDocument = searcher.doc(doc_address)?;
+
+        let projected_doc: TantivyDocument = searcher.doc_projected(doc_address, &[count])?;
+        let counts: Vec<u64> = projected_doc
+            .get_all(count)
+            .flat_map(|value| value.as_u64())
+            .collect();
+        assert_eq!(counts, vec![3, 4]);
+        assert_eq!(projected_doc.len(), 2);
+
+        let projected_doc: TantivyDocument =
+            searcher.doc_projected(doc_address, &[body, category])?;
+        let expected_values: Vec<(Field, OwnedValue)> = full_doc
+            .field_values()
+            .filter(|(field, _)| [body, category].contains(field))
+            .map(|(field, value)| (field, OwnedValue::from(value.as_value())))
+            .collect();
+        let values: Vec<(Field, OwnedValue)> = projected_doc
+            .field_values()
+            .map(|(field, value)| (field, OwnedValue::from(value.as_value())))
+            .collect();
+        assert_eq!(values.len(), 3);
+        assert_eq!(values, expected_values);
+
+        let projected_doc: TantivyDocument = searcher.doc_projected(doc_address, &[])?;
+        assert_eq!(projected_doc.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_get_with_json_filter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let attributes = schema_builder.add_json_field("attributes", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "title",
+            attributes => json!({
+                "user": {"name": "paul", "password": "secret", "tags": []},
+                "items": [{"id": 1, "price": 3}, {"price": 4}, 5],
+                "color": "red",
+            }),
+        ))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let doc_address = DocAddress::new(0, 0);
+        let filtered_attributes = |include: &[&str], exclude: &[&str]| -> crate::Result<_> {
+            let filter = JsonPathFilter::new(include, exclude);
+            let doc: TantivyDocument =
+                searcher.doc_with_json_filter(doc_address, attributes, &filter)?;
+            assert_eq!(doc.get_first(title).and_then(|v| v.as_str()), Some("title"));
+            let attributes_values: Vec<serde_json::Value> = doc
+                .get_all(attributes)
+                .map(|value| serde_json::to_value(OwnedValue::from(value.as_value())).unwrap())
+                .collect();
+            Ok(attributes_values)
+        };
+
+        assert_eq!(
+            filtered_attributes(&[], &["user.password", "items"])?,
+            vec![json!({"user": {"name": "paul", "tags": []}, "color": "red"})]
+        );
+        assert_eq!(
+            filtered_attributes(&["user.name", "*.id"], &[])?,
+            vec![json!({"user": {"name": "paul"}, "items": [{"id": 1}]})]
+        );
+        assert_eq!(
+            filtered_attributes(&["items"], &["items.price"])?,
+            vec![json!({"items": [{"id": 1}, 5]})]
+        );
+        assert_eq!(
+            filtered_attributes(&["user"], &["user.*"])?,
+            Vec::<serde_json::Value>::new()
+        );
+        assert_eq!(
+            filtered_attributes(&["missing"], &[])?,
+            Vec::<serde_json::Value>::new()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_store_lru_cache() -> crate::Result<()> {
         let directory = RamDirectory::create();
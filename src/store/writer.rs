@@ -1,6 +1,6 @@
 use std::io;
 
-use common::BinarySerializable;
+use common::{BinarySerializable, CountingWriter, TerminatingWrite};
 
 use super::compressors::Compressor;
 use super::StoreReader;
@@ -24,6 +24,7 @@ pub struct StoreWriter {
     current_block: Vec<u8>,
     doc_pos: Vec<u32>,
     block_compressor: BlockCompressor,
+    blob_writer: Option<CountingWriter<WritePtr>>,
 }
 
 impl StoreWriter {
@@ -45,13 +46,28 @@ impl StoreWriter {
             doc_pos: Vec::new(),
             current_block: Vec::new(),
             block_compressor,
+            blob_writer: None,
         })
     }
 
+    /// Spills the values larger than the blob threshold of their field into `blob_writer`
+    /// instead of the store blocks.
+    ///
+    /// The blob file then needs to be given to the [`StoreReader`] with
+    /// [`StoreReader::with_blobs`].
+    pub fn with_blobs(mut self, blob_writer: WritePtr) -> StoreWriter {
+        self.blob_writer = Some(CountingWriter::wrap(blob_writer));
+        self
+    }
+
     pub(crate) fn compressor(&self) -> Compressor {
         self.compressor
     }
 
+    pub(crate) fn block_size(&self) -> usize {
+        self.block_size
+    }
+
     /// The memory used (inclusive childs)
     pub fn mem_usage(&self) -> usize {
         self.current_block.capacity() + self.doc_pos.capacity() * std::mem::size_of::<u32>()
@@ -100,6 +116,9 @@ impl StoreWriter {
         self.doc_pos.push(self.current_block.len() as u32);
 
         let mut serializer = BinaryDocumentSerializer::new(&mut self.current_block, schema);
+        if let Some(blob_writer) = self.blob_writer.as_mut() {
+            serializer = serializer.with_blob_writer(blob_writer);
+        }
         serializer.serialize_doc(document)?;
 
         self.num_docs_in_current_block += 1;
@@ -111,6 +130,8 @@ impl StoreWriter {
     ///
     /// The document id is implicitly the current number
     /// of documents.
+    ///
+    /// The document must not reference any blob, as its blob file is not copied.
     pub fn store_bytes(&mut self, serialized_document: &[u8]) -> io::Result<()> {
         self.doc_pos.push(self.current_block.len() as u32);
         self.current_block.extend_from_slice(serialized_document);
@@ -123,7 +144,12 @@ impl StoreWriter {
     /// This method is an optimization compared to iterating over the documents
     /// in the store and adding them one by one, as the store's data will
     /// not be decompressed and then recompressed.
+    ///
+    /// The store reader must not have any blob, as its blob file is not copied.
     pub fn stack(&mut self, store_reader: StoreReader) -> io::Result<()> {
+        if store_reader.has_blobs() {
+            return Err(io::Error::other("Cannot stack a store reader with blobs"));
+        }
         // We flush the current block first before stacking
         self.send_current_block_to_compressor()?;
         self.block_compressor.stack_reader(store_reader)?;
@@ -137,6 +163,9 @@ impl StoreWriter {
     pub fn close(mut self) -> io::Result<()> {
         self.send_current_block_to_compressor()?;
         self.block_compressor.close()?;
+        if let Some(blob_writer) = self.blob_writer {
+            blob_writer.terminate()?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,495 @@
+//! Completion suggestions backed by a weighted FST.
+//!
+//! The inputs of a suggest field (see
+//! [`SchemaBuilder::add_suggest_field`](crate::schema::SchemaBuilder::add_suggest_field)) are not
+//! indexed in the inverted index. Each segment instead stores, for each suggest field, an FST
+//! associating the normalized inputs to their suggestions: the original input, a weight, a
+//! payload and the document it comes from. The FST is rebuilt when segments are merged, leaving
+//! out the suggestions of the deleted documents.
+//!
+//! The [`Suggester`] returns the suggestions with the highest weights among the ones whose input
+//! starts with a prefix, or, optionally, with a prefix within a Levenshtein distance of it.
+//! The outputs of the FST hold the highest weight of the inputs they lead to, so that the
+//! search visits the most promising inputs first and stops as soon as it has found enough
+//! suggestions, whatever the number of inputs matching the prefix.
+//!
+//! The [`TermSuggester`] corrects the terms of a query ("did you mean") with the terms of the
+//! term dictionary of a text field, and the [`PhraseSuggester`] corrects whole texts, ranking
+//! the combinations of corrections with the bigram statistics of a shingle field.
+//!
+//! ```rust
+//! use tantivy::schema::{Schema, STORED};
+//! use tantivy::suggest::Suggester;
+//! use tantivy::{doc, Index, IndexWriter, TantivyDocument};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let song = schema_builder.add_suggest_field("song", STORED);
+//! let schema = schema_builder.build();
+//! let index = Index::create_in_ram(schema.clone());
+//! let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+//! index_writer.add_document(TantivyDocument::parse_json(
+//!     &schema,
+//!     r#"{"song": {"input": ["Nevermind", "Nirvana"], "weight": 34, "payload": "album"}}"#,
+//! )?)?;
+//! index_writer.add_document(doc!(song => "Never Gonna Give You Up"))?;
+//! index_writer.commit()?;
+//!
+//! let searcher = index.reader()?.searcher();
+//! let suggester = Suggester::new(song, 10);
+//! let suggestions = suggester.suggest(&searcher, "neve")?;
+//! let texts: Vec<&str> = suggestions.iter().map(|suggestion| suggestion.text()).collect();
+//! assert_eq!(texts, ["Nevermind", "Never Gonna Give You Up"]);
+//! assert_eq!(suggestions[0].payload(), "album");
+//! # Ok(())
+//! # }
+//! ```
+mod phrase_suggester;
+mod reader;
+mod serializer;
+mod suggester;
+mod term_suggester;
+mod writer;
+
+pub use self::phrase_suggester::{PhraseSuggester, PhraseSuggestion};
+pub use self::reader::SuggestReader;
+pub(crate) use self::serializer::SuggestSerializer;
+pub use self::suggester::{Suggester, Suggestion};
+pub use self::term_suggester::{
+    CorrectedSearch, SuggestMode, TermCorrection, TermSuggester, TermSuggestions,
+};
+pub(crate) use self::writer::SuggestWriter;
+use crate::schema::document::{ReferenceValue, ReferenceValueLeaf, Value};
+use crate::tokenizer::TextAnalyzer;
+use crate::DocId;
+
+/// A suggestion of a suggest field, as stored in a segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SuggestEntry {
+    /// The normalized input, key of the suggestion in the FST.
+    pub key: String,
+    /// The input, as given in the document.
+    pub input: String,
+    pub doc: DocId,
+    pub weight: u32,
+    pub payload: String,
+    /// The `(name, value)` pairs of the contexts of the suggestion.
+    pub contexts: Vec<(String, String)>,
+}
+
+/// The inputs, weight, payload and contexts of a value of a suggest field.
+pub(crate) struct SuggestValue<'a> {
+    pub inputs: Vec<&'a str>,
+    pub weight: u32,
+    pub payload: &'a str,
+    pub contexts: Vec<(&'a str, &'a str)>,
+}
+
+fn as_weight<'a, V: Value<'a>>(value: &V) -> Option<u32> {
+    match value.as_leaf()? {
+        ReferenceValueLeaf::U64(weight) => u32::try_from(weight).ok(),
+        ReferenceValueLeaf::I64(weight) => u32::try_from(weight).ok(),
+        _ => None,
+    }
+}
+
+/// Reads the value of a suggest field: either a string, or an object with an `input` string or
+/// array of strings, an optional `weight` fitting in a `u32`, an optional `payload` string and
+/// optional `contexts`, an object associating context names to a string or an array of strings.
+///
+/// Returns `None` if the value does not have this shape.
+pub(crate) fn parse_suggest_value<'a, V: Value<'a>>(
+    value: ReferenceValue<'a, V>,
+) -> Option<SuggestValue<'a>> {
+    let mut suggest_value = SuggestValue {
+        inputs: Vec::new(),
+        weight: 0,
+        payload: "",
+        contexts: Vec::new(),
+    };
+    match value {
+        ReferenceValue::Leaf(ReferenceValueLeaf::Str(input)) => {
+            suggest_value.inputs.push(input);
+        }
+        ReferenceValue::Object(entries) => {
+            for (key, value) in entries {
+                match key {
+                    "input" => match value.as_value() {
+                        ReferenceValue::Leaf(ReferenceValueLeaf::Str(input)) => {
+                            suggest_value.inputs.push(input);
+                        }
+                        ReferenceValue::Array(inputs) => {
+                            for input in inputs {
+                                suggest_value.inputs.push(input.as_str()?);
+                            }
+                        }
+                        _ => return None,
+                    },
+                    "weight" => suggest_value.weight = as_weight(&value)?,
+                    "payload" => suggest_value.payload = value.as_str()?,
+                    "contexts" => {
+                        let ReferenceValue::Object(contexts) = value.as_value() else {
+                            return None;
+                        };
+                        for (name, context_values) in contexts {
+                            match context_values.as_value() {
+                                ReferenceValue::Leaf(ReferenceValueLeaf::Str(context_value)) => {
+                                    suggest_value.contexts.push((name, context_value));
+                                }
+                                ReferenceValue::Array(context_values) => {
+                                    for context_value in context_values {
+                                        suggest_value
+                                            .contexts
+                                            .push((name, context_value.as_str()?));
+                                    }
+                                }
+                                _ => return None,
+                            }
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(suggest_value)
+}
+
+/// Normalizes `text` into the tokens of `text_analyzer` joined by a space.
+///
+/// If `keep_trailing_separator` is true and `text` ends with characters that are not part of a
+/// token, a space is appended: a prefix ending with a separator only completes to the inputs
+/// having a token after the last token of the prefix.
+pub(crate) fn normalize(
+    text_analyzer: &mut TextAnalyzer,
+    text: &str,
+    keep_trailing_separator: bool,
+) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut last_offset_to = 0;
+    let mut token_stream = text_analyzer.token_stream(text);
+    token_stream.process(&mut |token| {
+        if !normalized.is_empty() {
+            normalized.push(' ');
+        }
+        normalized.push_str(&token.text);
+        last_offset_to = token.offset_to;
+    });
+    if keep_trailing_separator && !normalized.is_empty() && last_offset_to < text.len() {
+        normalized.push(' ');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize, parse_suggest_value};
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::document::Value;
+    use crate::schema::{OwnedValue, Schema, SuggestOptions, STORED, TEXT};
+    use crate::suggest::Suggester;
+    use crate::tokenizer::TokenizerManager;
+    use crate::{DocAddress, Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_normalize() {
+        let mut text_analyzer = TokenizerManager::default().get("default").unwrap();
+        assert_eq!(
+            normalize(&mut text_analyzer, "Hello,  World!", false),
+            "hello world"
+        );
+        assert_eq!(
+            normalize(&mut text_analyzer, "Hello,  World!", true),
+            "hello world "
+        );
+        assert_eq!(normalize(&mut text_analyzer, "Hello Wo", true), "hello wo");
+        assert_eq!(normalize(&mut text_analyzer, " ", true), "");
+    }
+
+    fn suggestion_texts(
+        suggester: &Suggester,
+        searcher: &crate::Searcher,
+        prefix: &str,
+    ) -> crate::Result<Vec<String>> {
+        Ok(suggester
+            .suggest(searcher, prefix)?
+            .iter()
+            .map(|suggestion| suggestion.text().to_string())
+            .collect())
+    }
+
+    #[test]
+    fn test_suggest() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let city = schema_builder.add_suggest_field("city", STORED);
+        let raw_city = schema_builder
+            .add_suggest_field("raw_city", SuggestOptions::default().set_tokenizer("raw"));
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let cities = [
+            ("New York", 8_000_000),
+            ("New Orleans", 400_000),
+            ("Newark", 300_000),
+            ("Newcastle", 170_000),
+            ("Nice", 340_000),
+            ("Paris", 2_100_000),
+        ];
+        for (name, population) in cities {
+            let doc_json = format!(
+                r#"{{"title": "{name}", "city": {{"input": "{name}", "weight": {population}, "payload": "{name}"}}, "raw_city": "{name}"}}"#
+            );
+            index_writer.add_document(TantivyDocument::parse_json(&schema, &doc_json)?)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let suggester = Suggester::new(city, 3);
+        assert_eq!(
+            suggestion_texts(&suggester, &searcher, "ne")?,
+            ["New York", "New Orleans", "Newark"]
+        );
+        assert_eq!(
+            suggestion_texts(&suggester, &searcher, "NEW ")?,
+            ["New York", "New Orleans"]
+        );
+        assert_eq!(
+            suggestion_texts(&suggester, &searcher, "n")?,
+            ["New York", "New Orleans", "Nice"]
+        );
+        assert!(suggestion_texts(&suggester, &searcher, "x")?.is_empty());
+        let suggestions = suggester.suggest(&searcher, "pa")?;
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].weight(), 2_100_000);
+        assert_eq!(suggestions[0].payload(), "Paris");
+        assert_eq!(suggestions[0].doc_address(), DocAddress::new(0, 5));
+
+        // The inputs of the raw suggest field are not lowercased.
+        let raw_suggester = Suggester::new(raw_city, 10);
+        assert!(suggestion_texts(&raw_suggester, &searcher, "ne")?.is_empty());
+        assert_eq!(
+            suggestion_texts(&raw_suggester, &searcher, "New")?,
+            ["New Orleans", "New York", "Newark", "Newcastle"]
+        );
+
+        let mut fuzzy_suggester = Suggester::new(city, 3);
+        fuzzy_suggester.set_fuzzy(1, true);
+        assert_eq!(
+            suggestion_texts(&fuzzy_suggester, &searcher, "nwe")?,
+            ["New York", "New Orleans", "Newark"]
+        );
+        assert_eq!(
+            suggestion_texts(&fuzzy_suggester, &searcher, "parsi")?,
+            ["Paris"]
+        );
+        // The first char has to match exactly by default.
+        assert!(suggestion_texts(&fuzzy_suggester, &searcher, "baris")?.is_empty());
+        fuzzy_suggester.set_prefix_length(0);
+        assert_eq!(
+            suggestion_texts(&fuzzy_suggester, &searcher, "baris")?,
+            ["Paris"]
+        );
+
+        assert!(Suggester::new(title, 3).suggest(&searcher, "ne").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_deletes_and_merges() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", TEXT);
+        let name = schema_builder.add_suggest_field("name", ());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let add_doc = |index_writer: &mut IndexWriter, doc_id: &str, doc_json: &str| {
+            let mut doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+            doc.add_text(id, doc_id);
+            index_writer.add_document(doc).unwrap();
+        };
+        add_doc(
+            &mut index_writer,
+            "a",
+            r#"{"name": {"input": ["Tantivy", "Search engine"], "weight": 10}}"#,
+        );
+        add_doc(&mut index_writer, "b", r#"{"name": "Tanzania"}"#);
+        index_writer.commit()?;
+        add_doc(
+            &mut index_writer,
+            "c",
+            r#"{"name": {"input": "Tango", "weight": 5}}"#,
+        );
+        add_doc(
+            &mut index_writer,
+            "d",
+            r#"{"name": {"input": "Tantivy", "weight": 3}}"#,
+        );
+        index_writer.commit()?;
+
+        let reader = index.reader()?;
+        let suggester = Suggester::new(name, 10);
+        assert_eq!(
+            suggestion_texts(&suggester, &reader.searcher(), "tan")?,
+            ["Tantivy", "Tango", "Tantivy", "Tanzania"]
+        );
+
+        // The suggestions of the deleted documents are not returned, and are dropped by merges.
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(
+            suggestion_texts(&suggester, &reader.searcher(), "tan")?,
+            ["Tango", "Tantivy", "Tanzania"]
+        );
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let suggest_reader = searcher.segment_reader(0).suggest_reader(name)?;
+        assert_eq!(suggest_reader.num_suggestions(), 3);
+        let suggestions = suggester.suggest(&searcher, "tan")?;
+        let suggestions: Vec<(&str, u32)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.text(), suggestion.weight()))
+            .collect();
+        assert_eq!(suggestions, [("Tango", 5), ("Tantivy", 3), ("Tanzania", 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_suggest_contexts() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let product = schema_builder.add_suggest_field("product", ());
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let products = [
+            (
+                "Tablet",
+                50,
+                r#"{"category": "electronics", "tenant": ["acme", "globex"]}"#,
+            ),
+            (
+                "Table",
+                40,
+                r#"{"category": "furniture", "tenant": "acme"}"#,
+            ),
+            ("Tabasco", 30, r#"{"category": "food", "tenant": "globex"}"#),
+            ("Tableware", 20, r#"{"category": ["furniture", "kitchen"]}"#),
+            ("Tabbouleh", 10, r#"{}"#),
+        ];
+        for (input, weight, contexts) in products {
+            let doc_json = format!(
+                r#"{{"product": {{"input": "{input}", "weight": {weight}, "contexts": {contexts}}}}}"#
+            );
+            index_writer.add_document(TantivyDocument::parse_json(&schema, &doc_json)?)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let suggester = Suggester::new(product, 3);
+        assert_eq!(
+            suggestion_texts(&suggester, &searcher, "tab")?,
+            ["Tablet", "Table", "Tabasco"]
+        );
+        let suggestions = suggester.suggest(&searcher, "tab")?;
+        assert_eq!(
+            suggestions[1].contexts(),
+            [
+                ("category".to_string(), "furniture".to_string()),
+                ("tenant".to_string(), "acme".to_string())
+            ]
+        );
+
+        let mut filtered_suggester = Suggester::new(product, 3);
+        filtered_suggester.add_context_filter("category", &["furniture", "food"]);
+        assert_eq!(
+            suggestion_texts(&filtered_suggester, &searcher, "tab")?,
+            ["Table", "Tabasco", "Tableware"]
+        );
+        // The suggestions have to match the filters of all of the contexts.
+        filtered_suggester.add_context_filter("tenant", &["acme"]);
+        assert_eq!(
+            suggestion_texts(&filtered_suggester, &searcher, "tab")?,
+            ["Table"]
+        );
+
+        let mut boosted_suggester = Suggester::new(product, 3);
+        boosted_suggester.add_context_boost("category", "kitchen", 3.0);
+        boosted_suggester.add_context_boost("category", "food", 1.5);
+        let suggestions = boosted_suggester.suggest(&searcher, "tab")?;
+        let suggestions: Vec<(&str, f64)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.text(), suggestion.score()))
+            .collect();
+        assert_eq!(
+            suggestions,
+            [("Tableware", 60.0), ("Tablet", 50.0), ("Tabasco", 45.0)]
+        );
+
+        // Boosts below one demote the suggestions.
+        let mut demoting_suggester = Suggester::new(product, 2);
+        demoting_suggester.add_context_boost("tenant", "acme", 0.1);
+        assert_eq!(
+            suggestion_texts(&demoting_suggester, &searcher, "tab")?,
+            ["Tabasco", "Tableware"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_suggest_value() {
+        let parse = |json: &str| -> Option<(Vec<String>, u32, String)> {
+            let value = OwnedValue::from(serde_json::from_str::<serde_json::Value>(json).unwrap());
+            let suggest_value = parse_suggest_value((&value).as_value())?;
+            Some((
+                suggest_value
+                    .inputs
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect(),
+                suggest_value.weight,
+                suggest_value.payload.to_string(),
+            ))
+        };
+        assert_eq!(
+            parse(r#""Tantivy""#),
+            Some((vec!["Tantivy".to_string()], 0, String::new()))
+        );
+        assert_eq!(
+            parse(r#"{"input": ["Tantivy", "Tango"], "weight": 3, "payload": "p"}"#),
+            Some((
+                vec!["Tantivy".to_string(), "Tango".to_string()],
+                3,
+                "p".to_string()
+            ))
+        );
+        assert_eq!(parse(r#"3"#), None);
+        assert_eq!(parse(r#"{"inputs": "Tantivy"}"#), None);
+        assert_eq!(parse(r#"{"input": "Tantivy", "weight": -1}"#), None);
+        assert_eq!(parse(r#"{"input": "Tantivy", "weight": 5000000000}"#), None);
+        assert_eq!(parse(r#"{"input": [3]}"#), None);
+
+        let value = OwnedValue::from(
+            serde_json::from_str::<serde_json::Value>(
+                r#"{"input": "Tantivy", "contexts": {"lang": ["rust", "c"], "license": "mit"}}"#,
+            )
+            .unwrap(),
+        );
+        let suggest_value = parse_suggest_value((&value).as_value()).unwrap();
+        assert_eq!(
+            suggest_value.contexts,
+            [("lang", "rust"), ("lang", "c"), ("license", "mit")]
+        );
+        assert_eq!(parse(r#"{"input": "Tantivy", "contexts": ["rust"]}"#), None);
+        assert_eq!(
+            parse(r#"{"input": "Tantivy", "contexts": {"lang": 3}}"#),
+            None
+        );
+    }
+}
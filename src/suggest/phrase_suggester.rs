@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use super::{SuggestMode, TermSuggester};
+use crate::schema::{Field, Term};
+use crate::{Score, Searcher};
+
+/// Factor applied to the probability of a word when its bigram does not appear in the index.
+const BACKOFF_DISCOUNT: f64 = 0.4;
+/// Minimum number of candidate phrases kept at each step of the search.
+const MIN_BEAM_WIDTH: usize = 10;
+
+/// A correction of a whole text, proposed by the [`PhraseSuggester`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhraseSuggestion {
+    text: String,
+    highlighted: String,
+    score: Score,
+}
+
+impl PhraseSuggestion {
+    /// The corrected text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The corrected text, with the corrected tokens surrounded by the highlight tags.
+    pub fn highlighted(&self) -> &str {
+        &self.highlighted
+    }
+
+    /// The log-probability of the corrected text.
+    pub fn score(&self) -> Score {
+        self.score
+    }
+}
+
+/// A candidate for a token of the text.
+struct Candidate {
+    text: String,
+    /// Log-probability of the token being typed when the candidate was meant.
+    error_score: f64,
+}
+
+/// A sequence of candidates for the first tokens of the text.
+#[derive(Clone)]
+struct Path {
+    candidate_ords: Vec<usize>,
+    score: f64,
+}
+
+/// Suggests corrections of whole texts, scoring the combinations of the corrections of their
+/// tokens with the bigram statistics of a shingle field.
+///
+/// The field has to be indexed with the [`ShingleFilter`](crate::tokenizer::ShingleFilter)
+/// emitting both the tokens and the bigrams, so that its term dictionary holds the document
+/// frequency of each word and of each pair of consecutive words.
+///
+/// The candidates for each token are the token itself and its corrections, as found by a
+/// [`TermSuggester`]. A combination of candidates is scored by:
+/// - the likelihood of each token being a typo of its candidate: the [real word error
+///   likelihood](PhraseSuggester::set_real_word_error_likelihood) if the candidate is the token
+///   itself, and one minus it per edit otherwise,
+/// - a bigram language model with stupid backoff, estimated from the document frequencies.
+///
+/// Unlike a [`TermSuggester`], the `PhraseSuggester` can correct a word appearing in the index,
+/// when it does not fit with its neighbours.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TextFieldIndexing, TextOptions};
+/// use tantivy::suggest::PhraseSuggester;
+/// use tantivy::tokenizer::{LowerCaser, ShingleFilter, SimpleTokenizer, TextAnalyzer};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let text_options = TextOptions::default()
+///     .set_indexing_options(TextFieldIndexing::default().set_tokenizer("shingles"));
+/// let title = schema_builder.add_text_field("title", text_options);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let shingles = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LowerCaser)
+///     .filter(ShingleFilter::new(2, 2)?)
+///     .build();
+/// index.tokenizers().register("shingles", shingles);
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Wise Man's Fear"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let phrase_suggester = PhraseSuggester::new(title);
+/// let suggestions = phrase_suggester.suggest(&searcher, "The wize man")?;
+/// assert_eq!(suggestions[0].text(), "The wise man");
+/// assert_eq!(suggestions[0].highlighted(), "The <em>wise</em> man");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PhraseSuggester {
+    field: Field,
+    term_suggester: TermSuggester,
+    limit: usize,
+    real_word_error_likelihood: f64,
+    confidence: f64,
+    highlight_tags: (String, String),
+}
+
+impl PhraseSuggester {
+    /// Creates a `PhraseSuggester` using the terms of the shingle field `field`.
+    pub fn new(field: Field) -> PhraseSuggester {
+        let mut term_suggester = TermSuggester::new(field);
+        term_suggester.set_mode(SuggestMode::Always);
+        PhraseSuggester {
+            field,
+            term_suggester,
+            limit: 5,
+            real_word_error_likelihood: 0.95,
+            confidence: 1.0,
+            highlight_tags: ("<em>".to_string(), "</em>".to_string()),
+        }
+    }
+
+    /// Sets the maximum number of suggestions. Defaults to 5.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Sets the maximum Levenshtein distance between a token and its corrections.
+    ///
+    /// See [`TermSuggester::set_max_distance`].
+    pub fn set_max_distance(&mut self, max_distance: u8) {
+        self.term_suggester.set_max_distance(max_distance);
+    }
+
+    /// Sets the number of chars at the start of a token that its corrections share.
+    ///
+    /// See [`TermSuggester::set_prefix_length`].
+    pub fn set_prefix_length(&mut self, prefix_length: usize) {
+        self.term_suggester.set_prefix_length(prefix_length);
+    }
+
+    /// Sets the maximum number of corrections considered for each token. Defaults to 5.
+    pub fn set_candidates_per_token(&mut self, candidates_per_token: usize) {
+        self.term_suggester.set_limit(candidates_per_token);
+    }
+
+    /// Sets the likelihood of a token appearing in the index being the word that was meant.
+    ///
+    /// Defaults to 0.95. Lower values make the corrections of the words appearing in the index
+    /// more likely.
+    pub fn set_real_word_error_likelihood(&mut self, real_word_error_likelihood: f64) {
+        self.real_word_error_likelihood = real_word_error_likelihood;
+    }
+
+    /// Sets the factor applied to the probability of the text to get the minimum probability
+    /// of a suggestion.
+    ///
+    /// Defaults to 1, only returning the suggestions more likely than the text. Values lower
+    /// than 1 also return less likely suggestions, 0 returning all of them.
+    pub fn set_confidence(&mut self, confidence: f64) {
+        self.confidence = confidence;
+    }
+
+    /// Sets the tags surrounding the corrected tokens in
+    /// [`PhraseSuggestion::highlighted`]. Defaults to `<em>` and `</em>`.
+    pub fn set_highlight_tags(&mut self, pre_tag: &str, post_tag: &str) {
+        self.highlight_tags = (pre_tag.to_string(), post_tag.to_string());
+    }
+
+    /// Returns the corrections of `text`, most likely first.
+    ///
+    /// `text` is tokenized with the tokenizer of the field, the shingles being left out.
+    pub fn suggest(&self, searcher: &Searcher, text: &str) -> crate::Result<Vec<PhraseSuggestion>> {
+        let mut text_analyzer = searcher.index().tokenizer_for_field(self.field)?;
+        let mut tokens = Vec::new();
+        text_analyzer.token_stream(text).process(&mut |token| {
+            if token.position_length == 1 {
+                tokens.push((token.text.clone(), token.offset_from, token.offset_to));
+            }
+        });
+        let num_docs: u64 = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| u64::from(segment_reader.max_doc()))
+            .sum();
+        if tokens.is_empty() || num_docs == 0 {
+            return Ok(Vec::new());
+        }
+        let mut language_model = LanguageModel {
+            searcher,
+            field: self.field,
+            num_docs: num_docs as f64,
+            doc_freqs: HashMap::new(),
+        };
+
+        let correct_word_score = self.real_word_error_likelihood.ln();
+        let error_score = (1.0 - self.real_word_error_likelihood).ln();
+        let mut candidates_per_token: Vec<Vec<Candidate>> = Vec::with_capacity(tokens.len());
+        for (token, _, _) in &tokens {
+            let doc_freq = language_model.doc_freq(token)?;
+            // A token missing from the index is unlikely to be the word that was meant.
+            let mut candidates = vec![Candidate {
+                text: token.clone(),
+                error_score: if doc_freq > 0 {
+                    correct_word_score
+                } else {
+                    error_score
+                },
+            }];
+            for correction in self.term_suggester.corrections(searcher, token, doc_freq)? {
+                // The bigrams of the shingle field are not words.
+                if correction.text().contains(' ') {
+                    continue;
+                }
+                candidates.push(Candidate {
+                    text: correction.text().to_string(),
+                    error_score: error_score * f64::from(correction.distance()),
+                });
+            }
+            candidates_per_token.push(candidates);
+        }
+
+        // Beam search of the most likely combinations of candidates. The combination of the
+        // tokens themselves, always the first candidates, is scored as well.
+        let beam_width = MIN_BEAM_WIDTH.max(self.limit + 1);
+        let mut paths = vec![Path {
+            candidate_ords: Vec::new(),
+            score: 0.0,
+        }];
+        let mut original_score = 0.0;
+        for (token_ord, candidates) in candidates_per_token.iter().enumerate() {
+            let previous_word = |path: &Path| {
+                token_ord.checked_sub(1).map(|previous_ord| {
+                    candidates_per_token[previous_ord][path.candidate_ords[previous_ord]]
+                        .text
+                        .as_str()
+                })
+            };
+            let original_previous_word = token_ord
+                .checked_sub(1)
+                .map(|previous_ord| candidates_per_token[previous_ord][0].text.as_str());
+            original_score += candidates[0].error_score
+                + language_model.score(original_previous_word, &candidates[0].text)?;
+            let mut next_paths = Vec::with_capacity(paths.len() * candidates.len());
+            for path in &paths {
+                for (candidate_ord, candidate) in candidates.iter().enumerate() {
+                    let score = path.score
+                        + candidate.error_score
+                        + language_model.score(previous_word(path), &candidate.text)?;
+                    let mut candidate_ords = path.candidate_ords.clone();
+                    candidate_ords.push(candidate_ord);
+                    next_paths.push(Path {
+                        candidate_ords,
+                        score,
+                    });
+                }
+            }
+            next_paths.sort_by(|left, right| right.score.total_cmp(&left.score));
+            next_paths.truncate(beam_width);
+            paths = next_paths;
+        }
+
+        let min_score = original_score + self.confidence.ln();
+        let (pre_tag, post_tag) = &self.highlight_tags;
+        let suggestions = paths
+            .into_iter()
+            .filter(|path| {
+                path.score.is_finite()
+                    && path.score > min_score
+                    && path
+                        .candidate_ords
+                        .iter()
+                        .any(|&candidate_ord| candidate_ord > 0)
+            })
+            .take(self.limit)
+            .map(|path| {
+                let mut corrected = String::with_capacity(text.len());
+                let mut highlighted = String::with_capacity(text.len());
+                let mut copied_up_to = 0;
+                for (token_ord, &candidate_ord) in path.candidate_ords.iter().enumerate() {
+                    if candidate_ord == 0 {
+                        continue;
+                    }
+                    let (_, offset_from, offset_to) = tokens[token_ord];
+                    let candidate = &candidates_per_token[token_ord][candidate_ord];
+                    corrected.push_str(&text[copied_up_to..offset_from]);
+                    corrected.push_str(&candidate.text);
+                    highlighted.push_str(&text[copied_up_to..offset_from]);
+                    highlighted.push_str(pre_tag);
+                    highlighted.push_str(&candidate.text);
+                    highlighted.push_str(post_tag);
+                    copied_up_to = offset_to;
+                }
+                corrected.push_str(&text[copied_up_to..]);
+                highlighted.push_str(&text[copied_up_to..]);
+                PhraseSuggestion {
+                    text: corrected,
+                    highlighted,
+                    score: path.score as Score,
+                }
+            })
+            .collect();
+        Ok(suggestions)
+    }
+}
+
+/// Bigram language model with stupid backoff, estimated from the document frequencies of the
+/// terms of a shingle field.
+struct LanguageModel<'a> {
+    searcher: &'a Searcher,
+    field: Field,
+    num_docs: f64,
+    doc_freqs: HashMap<String, u64>,
+}
+
+impl LanguageModel<'_> {
+    fn doc_freq(&mut self, text: &str) -> crate::Result<u64> {
+        if let Some(&doc_freq) = self.doc_freqs.get(text) {
+            return Ok(doc_freq);
+        }
+        let doc_freq = self
+            .searcher
+            .doc_freq(&Term::from_field_text(self.field, text))?;
+        self.doc_freqs.insert(text.to_string(), doc_freq);
+        Ok(doc_freq)
+    }
+
+    /// Returns the log-probability of `word` following `previous_word`.
+    fn score(&mut self, previous_word: Option<&str>, word: &str) -> crate::Result<f64> {
+        if let Some(previous_word) = previous_word {
+            let bigram_doc_freq = self.doc_freq(&format!("{previous_word} {word}"))?;
+            let previous_doc_freq = self.doc_freq(previous_word)?;
+            if bigram_doc_freq > 0 && previous_doc_freq > 0 {
+                return Ok((bigram_doc_freq as f64 / previous_doc_freq as f64).ln());
+            }
+            return Ok(BACKOFF_DISCOUNT.ln() + self.unigram_score(word)?);
+        }
+        self.unigram_score(word)
+    }
+
+    /// Returns the log-probability of `word`, with add-one smoothing.
+    fn unigram_score(&mut self, word: &str) -> crate::Result<f64> {
+        let doc_freq = self.doc_freq(word)?;
+        Ok(((doc_freq + 1) as f64 / (self.num_docs + 1.0)).ln())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PhraseSuggester;
+    use crate::schema::{Schema, TextFieldIndexing, TextOptions};
+    use crate::tokenizer::{LowerCaser, ShingleFilter, SimpleTokenizer, TextAnalyzer};
+    use crate::{Index, IndexWriter, Searcher};
+
+    fn suggestion_texts(
+        phrase_suggester: &PhraseSuggester,
+        searcher: &Searcher,
+        text: &str,
+    ) -> crate::Result<Vec<String>> {
+        Ok(phrase_suggester
+            .suggest(searcher, text)?
+            .iter()
+            .map(|suggestion| suggestion.text().to_string())
+            .collect())
+    }
+
+    #[test]
+    fn test_phrase_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_tokenizer("shingles"));
+        let body = schema_builder.add_text_field("body", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let shingles = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(ShingleFilter::new(2, 2)?)
+            .build();
+        index.tokenizers().register("shingles", shingles);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "The sea shore"))?;
+        index_writer.add_document(doc!(body => "Walking on the sea shore"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(body => "I see you"))?;
+        index_writer.add_document(doc!(body => "I see you again"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut phrase_suggester = PhraseSuggester::new(body);
+        let suggestions = phrase_suggester.suggest(&searcher, "The sea shroe")?;
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].text(), "The sea shore");
+        assert_eq!(suggestions[0].highlighted(), "The sea <em>shore</em>");
+        assert!(suggestions[0].score() < 0.0);
+        assert!(suggestion_texts(&phrase_suggester, &searcher, "the sea shore")?.is_empty());
+
+        // The words appearing in the index are corrected when they do not fit in their context.
+        assert!(suggestion_texts(&phrase_suggester, &searcher, "the see shore")?.is_empty());
+        phrase_suggester.set_real_word_error_likelihood(0.8);
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "the see shore")?,
+            ["the sea shore"]
+        );
+        assert_eq!(
+            suggestion_texts(&phrase_suggester, &searcher, "I sea you")?,
+            ["I see you"]
+        );
+
+        phrase_suggester.set_highlight_tags("[", "]");
+        let suggestions = phrase_suggester.suggest(&searcher, "I sea yuo!")?;
+        assert_eq!(suggestions[0].highlighted(), "I [see] [you]!");
+
+        // Less likely suggestions are returned with a lower confidence.
+        phrase_suggester.set_confidence(0.0);
+        let suggestions = phrase_suggester.suggest(&searcher, "the sea shore")?;
+        assert!(!suggestions.is_empty());
+        assert!(suggestions
+            .windows(2)
+            .all(|window| window[0].score() >= window[1].score()));
+        phrase_suggester.set_limit(1);
+        assert_eq!(
+            phrase_suggester.suggest(&searcher, "the sea shore")?.len(),
+            1
+        );
+        Ok(())
+    }
+}
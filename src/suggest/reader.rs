@@ -0,0 +1,288 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::io;
+use std::sync::Arc;
+
+use common::{BinarySerializable, OwnedBytes};
+use tantivy_fst::raw::{CompiledAddr, Fst};
+use tantivy_fst::{Automaton, Streamer};
+
+use super::serializer::write_entries;
+use super::suggester::ContextQuery;
+use super::SuggestEntry;
+use crate::directory::FileSlice;
+use crate::fastfield::AliveBitSet;
+
+const FOOTER_LEN: usize = 16;
+
+/// A node of the FST reached by the search: its address, the key leading to it and the state of
+/// the automaton.
+type PendingNode<S> = (CompiledAddr, Vec<u8>, S);
+
+/// A candidate of the best-first search of the suggestions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Candidate {
+    /// An input matching the automaton, identified by the ordinal of its key in the search.
+    Input { key_ord: usize },
+    /// A node of the FST that may lead to inputs matching the automaton.
+    Node(usize),
+}
+
+/// A suggestion, with its weight multiplied by the boost of its contexts.
+#[derive(Clone, Debug)]
+pub(crate) struct ScoredEntry {
+    pub score: f64,
+    pub entry: SuggestEntry,
+}
+
+impl Ord for ScoredEntry {
+    /// The best suggestion is the greatest: the one with the highest score, then the smallest
+    /// normalized input and doc id.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.entry.key.cmp(&self.entry.key))
+            .then(other.entry.doc.cmp(&self.entry.doc))
+    }
+}
+
+impl PartialOrd for ScoredEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ScoredEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScoredEntry {}
+
+/// The `SuggestReader` gives access to the suggestions of a suggest field of a segment.
+#[derive(Clone)]
+pub struct SuggestReader {
+    fst: Arc<Fst<OwnedBytes>>,
+    entries: OwnedBytes,
+    entry_offsets: OwnedBytes,
+    input_starts: OwnedBytes,
+    num_entries: u32,
+}
+
+impl SuggestReader {
+    /// Opens the suggestions of a suggest field.
+    pub fn open(file: FileSlice) -> io::Result<SuggestReader> {
+        let bytes = file.read_bytes()?;
+        if bytes.len() < FOOTER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Suggest data is corrupted: missing footer",
+            ));
+        }
+        let (body, mut footer) = bytes.rsplit(FOOTER_LEN);
+        let fst_len = u64::deserialize(&mut footer)? as usize;
+        let num_entries = u32::deserialize(&mut footer)?;
+        let num_inputs = u32::deserialize(&mut footer)?;
+        let input_starts_len = (num_inputs as usize + 1) * 4;
+        let entry_offsets_len = (num_entries as usize + 1) * 8;
+        if fst_len + entry_offsets_len + input_starts_len > body.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Suggest data is corrupted: truncated file",
+            ));
+        }
+        let (fst_bytes, body) = body.split(fst_len);
+        let (body, input_starts) = body.rsplit(input_starts_len);
+        let (entries, entry_offsets) = body.rsplit(entry_offsets_len);
+        let fst = Fst::new(fst_bytes).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Fst data is corrupted: {err:?}"),
+            )
+        })?;
+        Ok(SuggestReader {
+            fst: Arc::new(fst),
+            entries,
+            entry_offsets,
+            input_starts,
+            num_entries,
+        })
+    }
+
+    /// Creates a reader without any suggestion.
+    pub fn empty() -> SuggestReader {
+        let mut bytes = Vec::new();
+        write_entries(&[], &mut bytes).expect("Writing suggestions in a Vec<u8> should never fail");
+        SuggestReader::open(FileSlice::from(bytes))
+            .expect("Opening an empty suggest file should never fail")
+    }
+
+    /// Returns the number of suggestions, including the ones of deleted documents.
+    pub fn num_suggestions(&self) -> u32 {
+        self.num_entries
+    }
+
+    fn entry_offset(&self, entry_ord: u32) -> usize {
+        let start = entry_ord as usize * 8;
+        let mut bytes = &self.entry_offsets.as_slice()[start..start + 8];
+        u64::deserialize(&mut bytes).unwrap() as usize
+    }
+
+    fn input_start(&self, input_ord: u32) -> u32 {
+        let start = input_ord as usize * 4;
+        let mut bytes = &self.input_starts.as_slice()[start..start + 4];
+        u32::deserialize(&mut bytes).unwrap()
+    }
+
+    fn entry_ords(&self, input_ord: u32) -> std::ops::Range<u32> {
+        self.input_start(input_ord)..self.input_start(input_ord + 1)
+    }
+
+    /// Reads the doc id and the weight of a suggestion.
+    fn doc_and_weight(&self, entry_ord: u32) -> io::Result<(u32, u32)> {
+        let offset = self.entry_offset(entry_ord);
+        let mut bytes = &self.entries.as_slice()[offset..];
+        let doc = u32::deserialize(&mut bytes)?;
+        let weight = u32::deserialize(&mut bytes)?;
+        Ok((doc, weight))
+    }
+
+    fn entry(&self, entry_ord: u32, key: &str) -> io::Result<SuggestEntry> {
+        let start = self.entry_offset(entry_ord);
+        let end = self.entry_offset(entry_ord + 1);
+        let mut bytes = &self.entries.as_slice()[start..end];
+        let doc = u32::deserialize(&mut bytes)?;
+        let weight = u32::deserialize(&mut bytes)?;
+        let input = String::deserialize(&mut bytes)?;
+        let payload = String::deserialize(&mut bytes)?;
+        let contexts = Vec::<(String, String)>::deserialize(&mut bytes)?;
+        Ok(SuggestEntry {
+            key: key.to_string(),
+            input,
+            doc,
+            weight,
+            payload,
+            contexts,
+        })
+    }
+
+    /// Returns all of the suggestions, including the ones of deleted documents.
+    pub(crate) fn entries(&self) -> io::Result<Vec<SuggestEntry>> {
+        let mut entries = Vec::with_capacity(self.num_entries as usize);
+        let mut stream = self.fst.stream();
+        while let Some((key_bytes, output)) = stream.next() {
+            let key = String::from_utf8_lossy(key_bytes);
+            for entry_ord in self.entry_ords(output.value() as u32) {
+                entries.push(self.entry(entry_ord, &key)?);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Returns the `limit` suggestions with the highest scores among the ones whose normalized
+    /// input matches `automaton`, leaving out the ones of the documents not in `alive_bitset` and
+    /// the ones filtered out by `context_query`.
+    ///
+    /// The score of a suggestion is its weight multiplied by the boost of its contexts. The
+    /// suggestions are sorted by decreasing score, then by normalized input and doc id.
+    pub(crate) fn top_k<A: Automaton>(
+        &self,
+        automaton: &A,
+        limit: usize,
+        alive_bitset: Option<&AliveBitSet>,
+        context_query: &ContextQuery,
+    ) -> io::Result<Vec<ScoredEntry>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+        // The output accumulated on the path to a node is a lower bound of the outputs of the
+        // inputs below it, and its high bits hold an upper bound of their weights: popping the
+        // candidates by increasing priority visits the inputs by decreasing weight, and the
+        // search stops once no remaining suggestion can score higher than the hits.
+        let max_boost = context_query.max_boost();
+        let mut hits: BinaryHeap<Reverse<ScoredEntry>> = BinaryHeap::new();
+        let min_hit_score = |hits: &BinaryHeap<Reverse<ScoredEntry>>| {
+            if hits.len() < limit {
+                return None;
+            }
+            hits.peek().map(|Reverse(hit)| hit.score)
+        };
+        let mut nodes: Vec<Option<PendingNode<A::State>>> = Vec::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut heap: BinaryHeap<Reverse<(u64, Candidate)>> = BinaryHeap::new();
+        let start_state = automaton.start();
+        if automaton.can_match(&start_state) {
+            nodes.push(Some((self.fst.root().addr(), Vec::new(), start_state)));
+            heap.push(Reverse((0, Candidate::Node(0))));
+        }
+        while let Some(Reverse((priority, candidate))) = heap.pop() {
+            let max_weight = u32::MAX - (priority >> 32) as u32;
+            if min_hit_score(&hits)
+                .is_some_and(|min_score| min_score >= f64::from(max_weight) * max_boost)
+            {
+                break;
+            }
+            match candidate {
+                Candidate::Input { key_ord } => {
+                    let input_ord = priority as u32;
+                    for entry_ord in self.entry_ords(input_ord) {
+                        let (doc, weight) = self.doc_and_weight(entry_ord)?;
+                        // The suggestions of an input are sorted by decreasing weight.
+                        if min_hit_score(&hits)
+                            .is_some_and(|min_score| min_score >= f64::from(weight) * max_boost)
+                        {
+                            break;
+                        }
+                        if alive_bitset.is_some_and(|alive_bitset| alive_bitset.is_deleted(doc)) {
+                            continue;
+                        }
+                        let entry = self.entry(entry_ord, &keys[key_ord])?;
+                        let Some(boost) = context_query.boost(&entry.contexts) else {
+                            continue;
+                        };
+                        hits.push(Reverse(ScoredEntry {
+                            score: f64::from(weight) * boost,
+                            entry,
+                        }));
+                        if hits.len() > limit {
+                            hits.pop();
+                        }
+                    }
+                }
+                Candidate::Node(node_ord) => {
+                    let (addr, key, state) =
+                        nodes[node_ord].take().expect("A node is only popped once");
+                    let node = self.fst.node(addr);
+                    if node.is_final() && automaton.is_match(&state) {
+                        keys.push(String::from_utf8_lossy(&key).into_owned());
+                        heap.push(Reverse((
+                            priority + node.final_output().value(),
+                            Candidate::Input {
+                                key_ord: keys.len() - 1,
+                            },
+                        )));
+                    }
+                    for transition in node.transitions() {
+                        let next_state = automaton.accept(&state, transition.inp);
+                        if !automaton.can_match(&next_state) {
+                            continue;
+                        }
+                        let mut next_key = key.clone();
+                        next_key.push(transition.inp);
+                        nodes.push(Some((transition.addr, next_key, next_state)));
+                        heap.push(Reverse((
+                            priority + transition.out.value(),
+                            Candidate::Node(nodes.len() - 1),
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(hits
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(hit)| hit)
+            .collect())
+    }
+}
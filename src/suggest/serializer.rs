@@ -0,0 +1,106 @@
+use std::io::{self, Write};
+
+use common::{BinarySerializable, CountingWriter};
+
+use super::SuggestEntry;
+use crate::directory::{CompositeWrite, WritePtr};
+use crate::schema::Field;
+
+/// Returns the FST output of an input whose suggestions have at most a weight of `max_weight`.
+///
+/// Outputs are summed along the paths of the FST, and the FST builder pushes the smallest output
+/// of the keys sharing a prefix towards the root. Encoding the highest weight as the high bits of
+/// a smaller-is-better output therefore makes the output accumulated on the path to a node a
+/// bound of the weight of all of the inputs below it.
+pub(crate) fn input_output(max_weight: u32, input_ord: u32) -> u64 {
+    (u64::from(u32::MAX - max_weight) << 32) | u64::from(input_ord)
+}
+
+/// The `SuggestSerializer` is in charge of the serialization of the suggestions of all of the
+/// suggest fields of a segment.
+///
+/// The suggestions of a field are laid out as follows:
+/// - the FST associating each normalized input to its [`input_output`],
+/// - the suggestions, sorted by input and by decreasing weight: the doc id and the weight as `u32`,
+///   followed by the input, the payload and the `(name, value)` pairs of the contexts,
+/// - the offsets of the suggestions, as `u64`, with an extra offset for the end of the last one,
+/// - the ordinal of the first suggestion of each input, as `u32`, with an extra one for the end,
+/// - a footer with the length of the FST as `u64`, and the number of suggestions and of inputs as
+///   `u32`.
+pub(crate) struct SuggestSerializer {
+    composite_write: CompositeWrite,
+}
+
+impl SuggestSerializer {
+    pub fn from_write(write: WritePtr) -> SuggestSerializer {
+        SuggestSerializer {
+            composite_write: CompositeWrite::wrap(write),
+        }
+    }
+
+    /// Serializes the suggestions of `field`, given in any order.
+    pub fn serialize_field(
+        &mut self,
+        field: Field,
+        mut entries: Vec<SuggestEntry>,
+    ) -> io::Result<()> {
+        entries.sort_by(|left, right| {
+            left.key
+                .cmp(&right.key)
+                .then(right.weight.cmp(&left.weight))
+                .then(left.doc.cmp(&right.doc))
+        });
+        let write = self.composite_write.for_field(field);
+        write_entries(&entries, &mut *write)?;
+        write.flush()
+    }
+
+    /// Clean up / flush / close
+    pub fn close(self) -> io::Result<()> {
+        self.composite_write.close()
+    }
+}
+
+pub(super) fn write_entries<W: Write>(entries: &[SuggestEntry], write: W) -> io::Result<()> {
+    let mut write = CountingWriter::wrap(write);
+    let mut fst_builder = tantivy_fst::MapBuilder::memory();
+    let mut input_starts: Vec<u32> = Vec::new();
+    for (entry_ord, entry) in entries.iter().enumerate() {
+        if entry_ord > 0 && entries[entry_ord - 1].key == entry.key {
+            continue;
+        }
+        let input_ord = input_starts.len() as u32;
+        input_starts.push(entry_ord as u32);
+        // The suggestions of an input are sorted by decreasing weight, the first one has the
+        // highest weight.
+        fst_builder
+            .insert(&entry.key, input_output(entry.weight, input_ord))
+            .map_err(io::Error::other)?;
+    }
+    let num_inputs = input_starts.len() as u32;
+    input_starts.push(entries.len() as u32);
+    let fst_bytes = fst_builder.into_inner().map_err(io::Error::other)?;
+    write.write_all(&fst_bytes)?;
+
+    let entries_start = write.written_bytes();
+    let mut entry_offsets: Vec<u64> = Vec::with_capacity(entries.len() + 1);
+    for entry in entries {
+        entry_offsets.push(write.written_bytes() - entries_start);
+        entry.doc.serialize(&mut write)?;
+        entry.weight.serialize(&mut write)?;
+        entry.input.serialize(&mut write)?;
+        entry.payload.serialize(&mut write)?;
+        entry.contexts.serialize(&mut write)?;
+    }
+    entry_offsets.push(write.written_bytes() - entries_start);
+    for entry_offset in entry_offsets {
+        entry_offset.serialize(&mut write)?;
+    }
+    for input_start in input_starts {
+        input_start.serialize(&mut write)?;
+    }
+    (fst_bytes.len() as u64).serialize(&mut write)?;
+    (entries.len() as u32).serialize(&mut write)?;
+    num_inputs.serialize(&mut write)?;
+    Ok(())
+}
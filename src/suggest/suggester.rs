@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+
+use super::normalize;
+use crate::query::fuzzy_automaton;
+use crate::schema::{Field, FieldType};
+use crate::{DocAddress, Score, Searcher, TantivyError};
+
+/// A completion returned by the [`Suggester`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    key: String,
+    text: String,
+    weight: u32,
+    score: f64,
+    payload: String,
+    contexts: Vec<(String, String)>,
+    doc_address: DocAddress,
+}
+
+impl Suggestion {
+    /// The input of the suggestion, as given in the document.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The weight of the suggestion.
+    pub fn weight(&self) -> u32 {
+        self.weight
+    }
+
+    /// The score of the suggestion: its weight multiplied by the boosts of its contexts.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// The payload of the suggestion, empty if none was given.
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+
+    /// The `(name, value)` pairs of the contexts of the suggestion.
+    pub fn contexts(&self) -> &[(String, String)] {
+        &self.contexts
+    }
+
+    /// The address of the document the suggestion comes from.
+    pub fn doc_address(&self) -> DocAddress {
+        self.doc_address
+    }
+}
+
+/// The contexts restricting and boosting the suggestions.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ContextQuery {
+    filters: HashMap<String, HashSet<String>>,
+    boosts: Vec<(String, String, Score)>,
+}
+
+impl ContextQuery {
+    /// Returns the boost of a suggestion with the given contexts, or `None` if the suggestion is
+    /// filtered out.
+    pub fn boost(&self, contexts: &[(String, String)]) -> Option<f64> {
+        for (name, values) in &self.filters {
+            let is_match = contexts.iter().any(|(context_name, context_value)| {
+                context_name == name && values.contains(context_value)
+            });
+            if !is_match {
+                return None;
+            }
+        }
+        let mut boost = 1.0;
+        for (name, value, context_boost) in &self.boosts {
+            if contexts
+                .iter()
+                .any(|(context_name, context_value)| context_name == name && context_value == value)
+            {
+                boost *= f64::from(*context_boost);
+            }
+        }
+        Some(boost)
+    }
+
+    /// Returns an upper bound of the boosts returned by [`ContextQuery::boost`], in absolute
+    /// value.
+    pub fn max_boost(&self) -> f64 {
+        self.boosts
+            .iter()
+            .map(|(_, _, boost)| f64::from(boost.abs()).max(1.0))
+            .product()
+    }
+}
+
+/// Completes prefixes with the suggestions of a suggest field.
+///
+/// The suggestions can be restricted to, or boosted by, the values of their contexts. The
+/// contexts are free-form `(name, value)` pairs given in the documents, such as a category, a
+/// tenant, or the geohash cells of a location at several precisions, which a query restricts to
+/// the cell of the chosen precision.
+///
+/// The suggestions are sorted by decreasing score, their weight multiplied by the boosts of their
+/// contexts, then by normalized input.
+#[derive(Clone, Debug)]
+pub struct Suggester {
+    field: Field,
+    limit: usize,
+    fuzziness: Option<(u8, bool)>,
+    prefix_length: usize,
+    context_query: ContextQuery,
+}
+
+impl Suggester {
+    /// Creates a `Suggester` returning at most `limit` suggestions of the suggest field `field`.
+    pub fn new(field: Field, limit: usize) -> Suggester {
+        Suggester {
+            field,
+            limit,
+            fuzziness: None,
+            prefix_length: 1,
+            context_query: ContextQuery::default(),
+        }
+    }
+
+    /// Also returns the suggestions whose normalized input starts with a text within a
+    /// Levenshtein distance of `distance` of the normalized prefix.
+    ///
+    /// If `transposition_cost_one` is true, a transposition counts as a single edit.
+    pub fn set_fuzzy(&mut self, distance: u8, transposition_cost_one: bool) {
+        self.fuzziness = Some((distance, transposition_cost_one));
+    }
+
+    /// Sets the number of chars at the start of the prefix that have to match exactly when the
+    /// suggester is fuzzy.
+    ///
+    /// Defaults to 1, limiting the number of inputs visited by a fuzzy search.
+    pub fn set_prefix_length(&mut self, prefix_length: usize) {
+        self.prefix_length = prefix_length;
+    }
+
+    /// Only returns the suggestions having one of `values` for the context `name`.
+    ///
+    /// When several contexts are filtered, the suggestions have to match all of them.
+    pub fn add_context_filter(&mut self, name: &str, values: &[&str]) {
+        self.context_query
+            .filters
+            .entry(name.to_string())
+            .or_default()
+            .extend(values.iter().map(ToString::to_string));
+    }
+
+    /// Multiplies the score of the suggestions having the value `value` for the context `name` by
+    /// `boost`, without filtering out the other suggestions.
+    ///
+    /// The boosts of all of the contexts of a suggestion are multiplied. Boosting the suggestions
+    /// makes the search visit the inputs whose weights, multiplied by the highest possible
+    /// boost, are greater than the scores of the hits, so the boosts should stay small.
+    pub fn add_context_boost(&mut self, name: &str, value: &str, boost: Score) {
+        self.context_query
+            .boosts
+            .push((name.to_string(), value.to_string(), boost));
+    }
+
+    /// Returns the suggestions completing `prefix`.
+    ///
+    /// The prefix is normalized with the tokenizer of the suggest field.
+    pub fn suggest(&self, searcher: &Searcher, prefix: &str) -> crate::Result<Vec<Suggestion>> {
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        let FieldType::Suggest(suggest_options) = field_entry.field_type() else {
+            return Err(TantivyError::SchemaError(format!(
+                "The field {:?} is not a suggest field.",
+                field_entry.name()
+            )));
+        };
+        let tokenizer_name = suggest_options.tokenizer();
+        let mut text_analyzer = searcher
+            .index()
+            .tokenizers()
+            .get(tokenizer_name)
+            .ok_or_else(|| {
+                TantivyError::SchemaError(format!(
+                    "Error getting tokenizer for field: {}",
+                    field_entry.name()
+                ))
+            })?;
+        let normalized = normalize(&mut text_analyzer, prefix, true);
+        let automaton = match self.fuzziness {
+            Some((distance, transposition_cost_one)) => fuzzy_automaton(
+                &normalized,
+                distance,
+                transposition_cost_one,
+                true,
+                self.prefix_length,
+            )?,
+            None => fuzzy_automaton(&normalized, 0, true, true, usize::MAX)?,
+        };
+        let mut suggestions = Vec::new();
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            let suggest_reader = segment_reader.suggest_reader(self.field)?;
+            let hits = suggest_reader.top_k(
+                &automaton,
+                self.limit,
+                segment_reader.alive_bitset(),
+                &self.context_query,
+            )?;
+            suggestions.extend(hits.into_iter().map(|hit| Suggestion {
+                key: hit.entry.key,
+                text: hit.entry.input,
+                weight: hit.entry.weight,
+                score: hit.score,
+                payload: hit.entry.payload,
+                contexts: hit.entry.contexts,
+                doc_address: DocAddress::new(segment_ord as u32, hit.entry.doc),
+            }));
+        }
+        suggestions.sort_by(|left, right| {
+            right
+                .score
+                .total_cmp(&left.score)
+                .then_with(|| left.key.cmp(&right.key))
+                .then(left.doc_address.cmp(&right.doc_address))
+        });
+        suggestions.truncate(self.limit);
+        Ok(suggestions)
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+
+use crate::collector::Collector;
+use crate::query::{fuzzy_automaton, QueryParser};
+use crate::schema::{Field, Term};
+use crate::Searcher;
+
+/// Defines for which terms the [`TermSuggester`] suggests corrections.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SuggestMode {
+    /// Only suggests corrections for the terms that do not appear in the index.
+    #[default]
+    Missing,
+    /// Only suggests corrections appearing in more documents than the term.
+    Popular,
+    /// Suggests corrections for all of the terms.
+    Always,
+}
+
+/// A correction of a term, proposed by the [`TermSuggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermCorrection {
+    text: String,
+    distance: u8,
+    doc_freq: u64,
+}
+
+impl TermCorrection {
+    /// The corrected term.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The Levenshtein distance between the term and the correction.
+    pub fn distance(&self) -> u8 {
+        self.distance
+    }
+
+    /// The number of documents containing the correction, deleted documents included.
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+}
+
+/// The corrections of a token of the text given to the [`TermSuggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermSuggestions {
+    token: String,
+    offset_from: usize,
+    offset_to: usize,
+    doc_freq: u64,
+    corrections: Vec<TermCorrection>,
+}
+
+impl TermSuggestions {
+    /// The token, as produced by the tokenizer of the field.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// The byte range of the token in the text.
+    pub fn offsets(&self) -> std::ops::Range<usize> {
+        self.offset_from..self.offset_to
+    }
+
+    /// The number of documents containing the token, deleted documents included.
+    pub fn doc_freq(&self) -> u64 {
+        self.doc_freq
+    }
+
+    /// The corrections of the token, best first.
+    pub fn corrections(&self) -> &[TermCorrection] {
+        &self.corrections
+    }
+}
+
+/// The result of [`TermSuggester::search`].
+#[derive(Debug)]
+pub struct CorrectedSearch<F> {
+    /// The fruit of the collector, for the corrected query if it was run.
+    pub fruit: F,
+    /// The number of documents matching the query that was run.
+    pub num_hits: usize,
+    /// The corrected query, if the corrected query was run.
+    pub corrected_query: Option<String>,
+}
+
+/// Suggests corrections of the terms of a text ("did you mean") among the terms of the term
+/// dictionary of a text field.
+///
+/// The candidates are the terms within a Levenshtein distance of the token. They are ranked by
+/// distance, then by decreasing document frequency.
+///
+/// ```rust
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::suggest::TermSuggester;
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Wise Man's Fear"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let term_suggester = TermSuggester::new(title);
+/// let corrected = term_suggester.correct(&searcher, "the wize man")?;
+/// assert_eq!(corrected.as_deref(), Some("the wise man"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TermSuggester {
+    field: Field,
+    max_distance: u8,
+    transposition_cost_one: bool,
+    prefix_length: usize,
+    limit: usize,
+    mode: SuggestMode,
+}
+
+impl TermSuggester {
+    /// Creates a `TermSuggester` suggesting the terms of the text field `field`.
+    pub fn new(field: Field) -> TermSuggester {
+        TermSuggester {
+            field,
+            max_distance: 2,
+            transposition_cost_one: true,
+            prefix_length: 1,
+            limit: 5,
+            mode: SuggestMode::default(),
+        }
+    }
+
+    /// Sets the maximum Levenshtein distance between a term and its corrections.
+    ///
+    /// Defaults to 2, the highest supported distance.
+    pub fn set_max_distance(&mut self, max_distance: u8) {
+        self.max_distance = max_distance;
+    }
+
+    /// If true, a transposition counts as a single edit. Defaults to true.
+    pub fn set_transposition_cost_one(&mut self, transposition_cost_one: bool) {
+        self.transposition_cost_one = transposition_cost_one;
+    }
+
+    /// Sets the number of chars at the start of a term that its corrections share.
+    ///
+    /// Defaults to 1, limiting the number of terms visited.
+    pub fn set_prefix_length(&mut self, prefix_length: usize) {
+        self.prefix_length = prefix_length;
+    }
+
+    /// Sets the maximum number of corrections per term. Defaults to 5.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Sets the terms for which corrections are suggested. Defaults to [`SuggestMode::Missing`].
+    pub fn set_mode(&mut self, mode: SuggestMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the corrections of the tokens of `text`, tokenized with the tokenizer of the
+    /// field.
+    pub fn suggest(&self, searcher: &Searcher, text: &str) -> crate::Result<Vec<TermSuggestions>> {
+        let mut text_analyzer = searcher.index().tokenizer_for_field(self.field)?;
+        let mut tokens = Vec::new();
+        text_analyzer.token_stream(text).process(&mut |token| {
+            tokens.push((token.text.clone(), token.offset_from, token.offset_to));
+        });
+        let mut term_suggestions = Vec::with_capacity(tokens.len());
+        for (token, offset_from, offset_to) in tokens {
+            let doc_freq = searcher.doc_freq(&Term::from_field_text(self.field, &token))?;
+            let corrections = self.corrections(searcher, &token, doc_freq)?;
+            term_suggestions.push(TermSuggestions {
+                token,
+                offset_from,
+                offset_to,
+                doc_freq,
+                corrections,
+            });
+        }
+        Ok(term_suggestions)
+    }
+
+    /// Returns the corrections of `token`, which appears in `doc_freq` documents.
+    pub(super) fn corrections(
+        &self,
+        searcher: &Searcher,
+        token: &str,
+        doc_freq: u64,
+    ) -> crate::Result<Vec<TermCorrection>> {
+        if self.mode == SuggestMode::Missing && doc_freq > 0 {
+            return Ok(Vec::new());
+        }
+        let automaton = fuzzy_automaton(
+            token,
+            self.max_distance,
+            self.transposition_cost_one,
+            false,
+            self.prefix_length,
+        )?;
+        let mut candidate_doc_freqs: HashMap<Vec<u8>, u64> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(self.field)?;
+            let mut term_stream = inverted_index.terms().search(&automaton).into_stream()?;
+            while term_stream.advance() {
+                *candidate_doc_freqs
+                    .entry(term_stream.key().to_vec())
+                    .or_default() += u64::from(term_stream.value().doc_freq);
+            }
+        }
+        let mut corrections: Vec<TermCorrection> = candidate_doc_freqs
+            .into_iter()
+            .filter(|(candidate, candidate_doc_freq)| {
+                candidate.as_slice() != token.as_bytes()
+                    && (self.mode != SuggestMode::Popular || *candidate_doc_freq > doc_freq)
+            })
+            .filter_map(|(candidate, candidate_doc_freq)| {
+                let distance = automaton.distance(&candidate)?;
+                Some(TermCorrection {
+                    text: String::from_utf8(candidate).ok()?,
+                    distance,
+                    doc_freq: candidate_doc_freq,
+                })
+            })
+            .collect();
+        corrections.sort_by(|left, right| {
+            left.distance
+                .cmp(&right.distance)
+                .then(right.doc_freq.cmp(&left.doc_freq))
+                .then_with(|| left.text.cmp(&right.text))
+        });
+        corrections.truncate(self.limit);
+        Ok(corrections)
+    }
+
+    /// Returns `text` with each token having a correction replaced by its best correction, or
+    /// `None` if no token has a correction.
+    pub fn correct(&self, searcher: &Searcher, text: &str) -> crate::Result<Option<String>> {
+        let mut corrected = String::with_capacity(text.len());
+        let mut copied_up_to = 0;
+        for term_suggestions in self.suggest(searcher, text)? {
+            let Some(correction) = term_suggestions.corrections.first() else {
+                continue;
+            };
+            corrected.push_str(&text[copied_up_to..term_suggestions.offset_from]);
+            corrected.push_str(&correction.text);
+            copied_up_to = term_suggestions.offset_to;
+        }
+        if corrected.is_empty() {
+            return Ok(None);
+        }
+        corrected.push_str(&text[copied_up_to..]);
+        Ok(Some(corrected))
+    }
+
+    /// Runs the query parsed from `query_text` and, if it matches less than `min_hits`
+    /// documents, runs it again with its terms corrected.
+    ///
+    /// The whole query text goes through [`TermSuggester::correct`]: the query syntax is not
+    /// interpreted. The results of the corrected query are returned if it matches more
+    /// documents than the original one.
+    pub fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        query_parser: &QueryParser,
+        query_text: &str,
+        collector: &C,
+        min_hits: usize,
+    ) -> crate::Result<CorrectedSearch<C::Fruit>> {
+        let query = query_parser.parse_query(query_text)?;
+        let num_hits = query.count(searcher)?;
+        if num_hits < min_hits {
+            if let Some(corrected_query_text) = self.correct(searcher, query_text)? {
+                let corrected_query = query_parser.parse_query(&corrected_query_text)?;
+                let corrected_num_hits = corrected_query.count(searcher)?;
+                if corrected_num_hits > num_hits {
+                    return Ok(CorrectedSearch {
+                        fruit: searcher.search(&corrected_query, collector)?,
+                        num_hits: corrected_num_hits,
+                        corrected_query: Some(corrected_query_text),
+                    });
+                }
+            }
+        }
+        Ok(CorrectedSearch {
+            fruit: searcher.search(&query, collector)?,
+            num_hits,
+            corrected_query: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SuggestMode, TermSuggester};
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, Searcher};
+
+    fn corrections(
+        term_suggester: &TermSuggester,
+        searcher: &Searcher,
+        text: &str,
+    ) -> crate::Result<Vec<Vec<(String, u8, u64)>>> {
+        Ok(term_suggester
+            .suggest(searcher, text)?
+            .iter()
+            .map(|term_suggestions| {
+                term_suggestions
+                    .corrections()
+                    .iter()
+                    .map(|correction| {
+                        (
+                            correction.text().to_string(),
+                            correction.distance(),
+                            correction.doc_freq(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_term_suggester() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the quick brown fox"))?;
+        index_writer.add_document(doc!(title => "quick brown foxes"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "a brown bear"))?;
+        index_writer.add_document(doc!(title => "quack", id => "Quick"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let mut term_suggester = TermSuggester::new(title);
+        let term_suggestions = term_suggester.suggest(&searcher, "Quikc, brwn!")?;
+        assert_eq!(term_suggestions.len(), 2);
+        assert_eq!(term_suggestions[0].token(), "quikc");
+        assert_eq!(term_suggestions[0].offsets(), 0..5);
+        assert_eq!(term_suggestions[0].doc_freq(), 0);
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "Quikc, brwn!")?,
+            [
+                vec![("quick".to_string(), 1, 2), ("quack".to_string(), 2, 1)],
+                vec![("brown".to_string(), 1, 3)],
+            ]
+        );
+
+        // Only the missing terms are corrected by default.
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "fox")?,
+            [Vec::<(String, u8, u64)>::new()]
+        );
+        term_suggester.set_mode(SuggestMode::Always);
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "fox")?,
+            [vec![("foxes".to_string(), 2, 1)]]
+        );
+        term_suggester.set_mode(SuggestMode::Popular);
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "quack")?,
+            [vec![("quick".to_string(), 1, 2)]]
+        );
+        term_suggester.set_mode(SuggestMode::Missing);
+
+        term_suggester.set_max_distance(1);
+        term_suggester.set_limit(1);
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "quikc")?,
+            [vec![("quick".to_string(), 1, 2)]]
+        );
+        term_suggester.set_transposition_cost_one(false);
+        assert_eq!(
+            corrections(&term_suggester, &searcher, "quikc")?,
+            [Vec::<(String, u8, u64)>::new()]
+        );
+
+        assert_eq!(
+            TermSuggester::new(title).correct(&searcher, "The quikc brwn bear")?,
+            Some("The quick brown bear".to_string())
+        );
+        assert_eq!(
+            TermSuggester::new(title).correct(&searcher, "the quick bear")?,
+            None
+        );
+        assert!(TermSuggester::new(id).suggest(&searcher, "quick").is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_term_suggester_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the quick brown fox"))?;
+        index_writer.add_document(doc!(title => "quick brown foxes"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let term_suggester = TermSuggester::new(title);
+
+        let corrected_search = term_suggester.search(
+            &searcher,
+            &query_parser,
+            "quikc",
+            &TopDocs::with_limit(10),
+            1,
+        )?;
+        assert_eq!(corrected_search.corrected_query.as_deref(), Some("quick"));
+        assert_eq!(corrected_search.num_hits, 2);
+        assert_eq!(corrected_search.fruit.len(), 2);
+
+        // The original query matches enough documents.
+        let corrected_search =
+            term_suggester.search(&searcher, &query_parser, "fox", &TopDocs::with_limit(10), 1)?;
+        assert_eq!(corrected_search.corrected_query, None);
+        assert_eq!(corrected_search.num_hits, 1);
+
+        // Nothing to correct.
+        let corrected_search = term_suggester.search(
+            &searcher,
+            &query_parser,
+            "zebra",
+            &TopDocs::with_limit(10),
+            1,
+        )?;
+        assert_eq!(corrected_search.corrected_query, None);
+        assert_eq!(corrected_search.num_hits, 0);
+        Ok(())
+    }
+}
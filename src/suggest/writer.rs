@@ -0,0 +1,90 @@
+use std::io;
+
+use super::{normalize, SuggestEntry, SuggestSerializer, SuggestValue};
+use crate::schema::{Field, FieldType, Schema};
+use crate::tokenizer::TextAnalyzer;
+use crate::DocId;
+
+/// The `SuggestWriter` is in charge of collecting the suggestions of the suggest fields of the
+/// documents of a segment, until they are serialized.
+pub(crate) struct SuggestWriter {
+    per_field_entries: Vec<Option<Vec<SuggestEntry>>>,
+    num_bytes: usize,
+}
+
+impl SuggestWriter {
+    /// Initializes the writer for the suggest fields of the schema.
+    pub fn for_schema(schema: &Schema) -> SuggestWriter {
+        let per_field_entries = schema
+            .fields()
+            .map(|(_, field_entry)| match field_entry.field_type() {
+                FieldType::Suggest(_) => Some(Vec::new()),
+                _ => None,
+            })
+            .collect();
+        SuggestWriter {
+            per_field_entries,
+            num_bytes: 0,
+        }
+    }
+
+    /// The memory used by the suggestions collected so far.
+    pub fn mem_usage(&self) -> usize {
+        self.num_bytes
+    }
+
+    /// Records the suggestions of a value of the suggest field `field` of the document `doc`.
+    ///
+    /// The inputs are normalized with `text_analyzer`. Inputs without any token are ignored.
+    pub fn add_value(
+        &mut self,
+        field: Field,
+        doc: DocId,
+        suggest_value: &SuggestValue,
+        text_analyzer: &mut TextAnalyzer,
+    ) {
+        let Some(entries) = self.per_field_entries[field.field_id() as usize].as_mut() else {
+            return;
+        };
+        for input in &suggest_value.inputs {
+            let key = normalize(text_analyzer, input, false);
+            if key.is_empty() {
+                continue;
+            }
+            let entry = SuggestEntry {
+                key,
+                input: input.to_string(),
+                doc,
+                weight: suggest_value.weight,
+                payload: suggest_value.payload.to_string(),
+                contexts: suggest_value
+                    .contexts
+                    .iter()
+                    .map(|&(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            };
+            self.num_bytes += std::mem::size_of::<SuggestEntry>()
+                + entry.key.capacity()
+                + entry.input.capacity()
+                + entry.payload.capacity()
+                + entry
+                    .contexts
+                    .iter()
+                    .map(|(name, value)| {
+                        std::mem::size_of::<(String, String)>() + name.capacity() + value.capacity()
+                    })
+                    .sum::<usize>();
+            entries.push(entry);
+        }
+    }
+
+    /// Serializes the suggestions of all of the suggest fields.
+    pub fn serialize(self, serializer: &mut SuggestSerializer) -> io::Result<()> {
+        for (field_id, entries_opt) in self.per_field_entries.into_iter().enumerate() {
+            if let Some(entries) = entries_opt {
+                serializer.serialize_field(Field::from_field_id(field_id as u32), entries)?;
+            }
+        }
+        Ok(())
+    }
+}
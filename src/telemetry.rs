@@ -0,0 +1,360 @@
+//! Metrics describing the activity of tantivy.
+//!
+//! With the `metrics` feature, tantivy reports its indexing, merging, reloading and
+//! search activity through the [`metrics`](https://docs.rs/metrics) facade. The metrics are
+//! sent to the recorder installed by the application, for instance the exporter of the
+//! `metrics-exporter-prometheus` crate, and [`describe_metrics`] registers their
+//! descriptions and units.
+//!
+//! Without the `metrics` feature, nothing is recorded.
+//!
+//! The metrics are global to the process: the activity of all of the indexes is summed up.
+//!
+//! With the `tracing` feature, tantivy also emits [`tracing`](https://docs.rs/tracing) spans
+//! for the phases of a search, so that slow searches can be diagnosed in distributed traces:
+//! - `parse_query`, carrying the `query` string, when a [`QueryParser`](crate::query::QueryParser)
+//!   parses a query,
+//! - `search`, carrying the `query`, around the whole search,
+//! - `weight`, around the creation of the weight of the query,
+//! - `collect_segment`, carrying the `segment_id` and the `segment_ord`, around the scoring and the
+//!   collection of the documents of each segment,
+//! - `merge_fruits`, around the merge of the results of the segments.
+#![cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+
+use std::time::{Duration, Instant};
+
+/// Counter of the documents added to an [`IndexWriter`](crate::IndexWriter).
+pub const DOCS_ADDED_TOTAL: &str = "tantivy_indexer_docs_added_total";
+/// Counter of the commits.
+pub const COMMITS_TOTAL: &str = "tantivy_indexer_commits_total";
+/// Histogram of the time spent publishing a commit, in seconds.
+pub const COMMIT_DURATION_SECONDS: &str = "tantivy_indexer_commit_duration_seconds";
+/// Gauge of the number of segments of the last commit of an index.
+pub const SEGMENTS: &str = "tantivy_indexer_segments";
+/// Gauge of the merges that are scheduled or running.
+pub const MERGES_PENDING: &str = "tantivy_merges_pending";
+/// Counter of the merges that completed.
+pub const MERGES_TOTAL: &str = "tantivy_merges_total";
+/// Counter of the documents written by the merges that completed.
+pub const MERGED_DOCS_TOTAL: &str = "tantivy_merged_docs_total";
+/// Histogram of the time spent in a merge, from the moment it is scheduled, in seconds.
+pub const MERGE_DURATION_SECONDS: &str = "tantivy_merge_duration_seconds";
+/// Counter of the reloads of an [`IndexReader`](crate::IndexReader).
+pub const READER_RELOADS_TOTAL: &str = "tantivy_reader_reloads_total";
+/// Histogram of the time spent reloading an [`IndexReader`](crate::IndexReader), in seconds.
+pub const READER_RELOAD_DURATION_SECONDS: &str = "tantivy_reader_reload_duration_seconds";
+/// Counter of the searches.
+pub const SEARCHES_TOTAL: &str = "tantivy_searches_total";
+/// Histogram of the time spent in each phase of a search, in seconds.
+///
+/// The `phase` label is one of `weight`, for the creation of the weight of the query,
+/// `collect`, for the scoring and the collection of the documents of the segments,
+/// and `merge`, for the merge of the results of the segments.
+pub const SEARCH_PHASE_DURATION_SECONDS: &str = "tantivy_search_phase_duration_seconds";
+/// Counter of the lookups in the cache of decompressed doc store blocks that hit the cache.
+pub const DOC_STORE_CACHE_HITS_TOTAL: &str = "tantivy_doc_store_cache_hits_total";
+/// Counter of the lookups in the cache of decompressed doc store blocks that missed the cache.
+pub const DOC_STORE_CACHE_MISSES_TOTAL: &str = "tantivy_doc_store_cache_misses_total";
+
+/// Registers the description and the unit of the metrics of tantivy in the installed
+/// recorder.
+#[cfg(feature = "metrics")]
+pub fn describe_metrics() {
+    use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+
+    describe_counter!(
+        DOCS_ADDED_TOTAL,
+        Unit::Count,
+        "Documents added to an index writer."
+    );
+    describe_counter!(COMMITS_TOTAL, Unit::Count, "Commits.");
+    describe_histogram!(
+        COMMIT_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time spent publishing a commit."
+    );
+    describe_gauge!(
+        SEGMENTS,
+        Unit::Count,
+        "Segments of the last commit of an index."
+    );
+    describe_gauge!(
+        MERGES_PENDING,
+        Unit::Count,
+        "Merges that are scheduled or running."
+    );
+    describe_counter!(MERGES_TOTAL, Unit::Count, "Merges that completed.");
+    describe_counter!(
+        MERGED_DOCS_TOTAL,
+        Unit::Count,
+        "Documents written by the merges that completed."
+    );
+    describe_histogram!(
+        MERGE_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time spent in a merge, from the moment it is scheduled."
+    );
+    describe_counter!(READER_RELOADS_TOTAL, Unit::Count, "Index reader reloads.");
+    describe_histogram!(
+        READER_RELOAD_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time spent reloading an index reader."
+    );
+    describe_counter!(SEARCHES_TOTAL, Unit::Count, "Searches.");
+    describe_histogram!(
+        SEARCH_PHASE_DURATION_SECONDS,
+        Unit::Seconds,
+        "Time spent in each phase of a search."
+    );
+    describe_counter!(
+        DOC_STORE_CACHE_HITS_TOTAL,
+        Unit::Count,
+        "Doc store block cache hits."
+    );
+    describe_counter!(
+        DOC_STORE_CACHE_MISSES_TOTAL,
+        Unit::Count,
+        "Doc store block cache misses."
+    );
+}
+
+/// Enters an info level `tracing` span until the end of the current scope, if the `tracing`
+/// feature is enabled.
+///
+/// The arguments are the ones of `tracing::info_span!`.
+macro_rules! enter_span {
+    ($($span_args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span_guard = ::tracing::info_span!($($span_args)*).entered();
+    };
+}
+
+pub(crate) use enter_span;
+
+pub(crate) fn record_docs_added(num_docs: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(DOCS_ADDED_TOTAL).increment(num_docs as u64);
+}
+
+pub(crate) fn record_commit(duration: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(COMMITS_TOTAL).increment(1);
+        metrics::histogram!(COMMIT_DURATION_SECONDS).record(duration);
+    }
+}
+
+pub(crate) fn record_num_segments(num_segments: usize) {
+    #[cfg(feature = "metrics")]
+    metrics::gauge!(SEGMENTS).set(num_segments as f64);
+}
+
+pub(crate) fn record_reader_reload(duration: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(READER_RELOADS_TOTAL).increment(1);
+        metrics::histogram!(READER_RELOAD_DURATION_SECONDS).record(duration);
+    }
+}
+
+pub(crate) fn record_search() {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(SEARCHES_TOTAL).increment(1);
+}
+
+pub(crate) fn record_search_phase(phase: &'static str, duration: Duration) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!(SEARCH_PHASE_DURATION_SECONDS, "phase" => phase).record(duration);
+}
+
+pub(crate) fn record_doc_store_cache_lookup(is_hit: bool) {
+    #[cfg(feature = "metrics")]
+    if is_hit {
+        metrics::counter!(DOC_STORE_CACHE_HITS_TOTAL).increment(1);
+    } else {
+        metrics::counter!(DOC_STORE_CACHE_MISSES_TOTAL).increment(1);
+    }
+}
+
+/// Tracks a merge, from the moment it is scheduled until it is dropped.
+pub(crate) struct MergeTracker {
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    scheduled_at: Instant,
+}
+
+impl MergeTracker {
+    pub(crate) fn schedule() -> MergeTracker {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(MERGES_PENDING).increment(1.0);
+        MergeTracker {
+            scheduled_at: Instant::now(),
+        }
+    }
+
+    /// Records that the merge completed, writing `num_docs` documents.
+    pub(crate) fn complete(self, num_docs: u32) {
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!(MERGES_TOTAL).increment(1);
+            metrics::counter!(MERGED_DOCS_TOTAL).increment(num_docs as u64);
+            metrics::histogram!(MERGE_DURATION_SECONDS).record(self.scheduled_at.elapsed());
+        }
+    }
+}
+
+impl Drop for MergeTracker {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        metrics::gauge!(MERGES_PENDING).decrement(1.0);
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter};
+
+    /// Records the value of the counters, and ignores the other metrics.
+    #[derive(Default)]
+    struct CounterRecorder {
+        counters: Mutex<HashMap<String, Arc<AtomicU64>>>,
+    }
+
+    impl CounterRecorder {
+        fn counter_value(&self, name: &str) -> u64 {
+            self.counters
+                .lock()
+                .unwrap()
+                .get(name)
+                .map_or(0, |counter| counter.load(Ordering::Relaxed))
+        }
+    }
+
+    impl Recorder for CounterRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            let counter = self
+                .counters
+                .lock()
+                .unwrap()
+                .entry(key.name().to_string())
+                .or_default()
+                .clone();
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn test_telemetry_records_docs_added_and_searches() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let recorder = CounterRecorder::default();
+        metrics::with_local_recorder(&recorder, || {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(id_field => "a"))?;
+            index_writer.add_document(doc!(id_field => "b"))?;
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.search(&AllQuery, &Count)?, 2);
+            crate::Result::Ok(())
+        })?;
+        assert_eq!(recorder.counter_value(super::DOCS_ADDED_TOTAL), 2);
+        assert_eq!(recorder.counter_value(super::SEARCHES_TOTAL), 1);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tracing_tests {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::collector::Count;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexWriter};
+
+    /// Records the name of the spans.
+    #[derive(Default)]
+    struct SpanNameRecorder {
+        span_names: Mutex<Vec<&'static str>>,
+        num_spans: AtomicU64,
+    }
+
+    impl Subscriber for SpanNameRecorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.span_names.lock().unwrap().push(span.metadata().name());
+            Id::from_u64(self.num_spans.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_telemetry_search_spans() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text_field => "hello world"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let recorder = Arc::new(SpanNameRecorder::default());
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let query_parser = QueryParser::for_index(&index, vec![text_field]);
+            let query = query_parser.parse_query("hello")?;
+            assert_eq!(searcher.search(&query, &Count)?, 1);
+            crate::Result::Ok(())
+        })?;
+        assert_eq!(
+            *recorder.span_names.lock().unwrap(),
+            [
+                "parse_query",
+                "search",
+                "weight",
+                "collect_segment",
+                "merge_fruits"
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+use serde::{Deserialize, Serialize};
+use tokenizer_api::{BoxTokenStream, Tokenizer};
+
+use super::decompounder::{DEFAULT_MAX_SUBWORD_SIZE, DEFAULT_MIN_SUBWORD_SIZE};
+use super::{
+    AlphaNumOnlyFilter, AsciiFoldingFilter, Decompounder, HtmlStripCharFilter, KeywordRepeatFilter,
+    Language, LowerCaser, NgramTokenizer, PatternReplaceCharFilter, RawTokenizer, RegexTokenizer,
+    RemoveDuplicatesFilter, RemoveLongFilter, ShingleFilter, SimpleTokenizer, SplitCompoundWords,
+    Stemmer, StopWordFilter, TextAnalyzer, TextAnalyzerBuilder, TokenizerManager,
+    WhitespaceTokenizer,
+};
+use crate::TantivyError;
+
+/// Declarative definition of a [`TextAnalyzer`].
+///
+/// Contrary to a `TextAnalyzer`, an `AnalyzerDefinition` can be serialized. Attached to a
+/// text field (see
+/// [`TextFieldIndexing::set_analyzer_definition`](crate::schema::TextFieldIndexing::set_analyzer_definition)),
+/// it is persisted with the schema, so that the index can rebuild the analyzer when it is
+/// opened, without requiring the analyzer to be registered again.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let definition = AnalyzerDefinition::new(TokenizerDefinition::Simple)
+///     .char_filter(CharFilterDefinition::HtmlStrip)
+///     .filter(TokenFilterDefinition::LowerCaser)
+///     .filter(TokenFilterDefinition::Stemmer {
+///         language: Language::English,
+///         preserve_original: false,
+///     });
+/// let mut analyzer = definition.build(&TokenizerManager::default()).unwrap();
+/// let mut stream = analyzer.token_stream("<b>Running</b>");
+/// assert_eq!(stream.next().unwrap().text, "run");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyzerDefinition {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    char_filters: Vec<CharFilterDefinition>,
+    tokenizer: TokenizerDefinition,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    filters: Vec<TokenFilterDefinition>,
+}
+
+/// Definition of a [`CharFilter`](super::CharFilter), as part of an [`AnalyzerDefinition`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CharFilterDefinition {
+    /// See [`HtmlStripCharFilter`].
+    HtmlStrip,
+    /// See [`PatternReplaceCharFilter`].
+    PatternReplace {
+        /// Regular expression to look for.
+        pattern: String,
+        /// Replacement string, possibly referring to capture groups.
+        replacement: String,
+    },
+}
+
+/// Definition of a [`Tokenizer`], as part of an [`AnalyzerDefinition`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenizerDefinition {
+    /// See [`SimpleTokenizer`].
+    Simple,
+    /// See [`WhitespaceTokenizer`].
+    Whitespace,
+    /// See [`RawTokenizer`].
+    Raw,
+    /// See [`NgramTokenizer`].
+    Ngram {
+        /// Minimum size of the ngrams.
+        min_gram: usize,
+        /// Maximum size of the ngrams.
+        max_gram: usize,
+        /// Only emit ngrams starting at the beginning of the text.
+        #[serde(default)]
+        prefix_only: bool,
+    },
+    /// See [`RegexTokenizer`].
+    Regex {
+        /// Pattern whose matches are emitted as tokens.
+        pattern: String,
+    },
+    /// Analyzer registered by name in the [`TokenizerManager`].
+    ///
+    /// This makes it possible to use a custom tokenizer implementation in a definition.
+    /// The analyzer has to be registered before the definition gets built.
+    Custom {
+        /// Name of the registered analyzer.
+        name: String,
+    },
+}
+
+/// Definition of a [`TokenFilter`](super::TokenFilter), as part of an [`AnalyzerDefinition`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenFilterDefinition {
+    /// See [`LowerCaser`].
+    LowerCaser,
+    /// See [`AsciiFoldingFilter`].
+    AsciiFolding {
+        /// See [`AsciiFoldingFilter::preserve_original`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "is_false")]
+        preserve_original: bool,
+    },
+    /// See [`AlphaNumOnlyFilter`].
+    AlphaNumOnly,
+    /// See [`RemoveLongFilter`].
+    RemoveLong {
+        /// Tokens with a length (in bytes) greater or equal to this limit are removed.
+        limit: usize,
+    },
+    /// See [`Stemmer`].
+    Stemmer {
+        /// Language of the stemmer.
+        language: Language,
+        /// See [`Stemmer::preserve_original`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "is_false")]
+        preserve_original: bool,
+    },
+    /// See [`KeywordRepeatFilter`].
+    KeywordRepeat,
+    /// See [`RemoveDuplicatesFilter`].
+    RemoveDuplicates,
+    /// See [`StopWordFilter`].
+    StopWords {
+        /// Language whose built-in list of stop words should be removed.
+        ///
+        /// Requires the `stopwords` feature.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<Language>,
+        /// Additional words to remove.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        words: Vec<String>,
+    },
+    /// See [`SplitCompoundWords`].
+    SplitCompoundWords {
+        /// Dictionary of the words compounds are made of.
+        dictionary: Vec<String>,
+    },
+    /// See [`Decompounder`].
+    Decompounder {
+        /// Dictionary of the words compounds are made of.
+        dictionary: Vec<String>,
+        /// See [`Decompounder::min_word_size`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min_word_size: Option<usize>,
+        /// See [`Decompounder::subword_size`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min_subword_size: Option<usize>,
+        /// See [`Decompounder::subword_size`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_subword_size: Option<usize>,
+        /// See [`Decompounder::only_longest_match`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "is_false")]
+        only_longest_match: bool,
+    },
+    /// See [`ShingleFilter`].
+    Shingle {
+        /// Minimum number of tokens of the shingles.
+        min_shingle_size: usize,
+        /// Maximum number of tokens of the shingles.
+        max_shingle_size: usize,
+        /// See [`ShingleFilter::without_unigrams`].
+        #[serde(default)]
+        #[serde(skip_serializing_if = "is_false")]
+        without_unigrams: bool,
+    },
+}
+
+impl AnalyzerDefinition {
+    /// Creates a new definition for an analyzer with the given tokenizer.
+    pub fn new(tokenizer: TokenizerDefinition) -> AnalyzerDefinition {
+        AnalyzerDefinition {
+            char_filters: Vec::new(),
+            tokenizer,
+            filters: Vec::new(),
+        }
+    }
+
+    /// Appends a char filter to the definition.
+    #[must_use]
+    pub fn char_filter(mut self, char_filter: CharFilterDefinition) -> AnalyzerDefinition {
+        self.char_filters.push(char_filter);
+        self
+    }
+
+    /// Appends a token filter to the definition.
+    #[must_use]
+    pub fn filter(mut self, filter: TokenFilterDefinition) -> AnalyzerDefinition {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Builds the [`TextAnalyzer`] described by this definition.
+    ///
+    /// `tokenizer_manager` is used to resolve [`TokenizerDefinition::Custom`] tokenizers.
+    pub fn build(&self, tokenizer_manager: &TokenizerManager) -> crate::Result<TextAnalyzer> {
+        let mut builder = self.tokenizer.builder(tokenizer_manager)?;
+        for char_filter in &self.char_filters {
+            builder = match char_filter {
+                CharFilterDefinition::HtmlStrip => builder.char_filter(HtmlStripCharFilter),
+                CharFilterDefinition::PatternReplace {
+                    pattern,
+                    replacement,
+                } => builder.char_filter(PatternReplaceCharFilter::new(pattern, replacement)?),
+            };
+        }
+        for filter in &self.filters {
+            builder = filter.append_to(builder)?;
+        }
+        Ok(builder.build())
+    }
+}
+
+impl TokenizerDefinition {
+    fn builder(&self, tokenizer_manager: &TokenizerManager) -> crate::Result<TextAnalyzerBuilder> {
+        let builder = match self {
+            TokenizerDefinition::Simple => {
+                TextAnalyzer::builder(SimpleTokenizer::default()).dynamic()
+            }
+            TokenizerDefinition::Whitespace => {
+                TextAnalyzer::builder(WhitespaceTokenizer::default()).dynamic()
+            }
+            TokenizerDefinition::Raw => TextAnalyzer::builder(RawTokenizer::default()).dynamic(),
+            TokenizerDefinition::Ngram {
+                min_gram,
+                max_gram,
+                prefix_only,
+            } => TextAnalyzer::builder(NgramTokenizer::new(*min_gram, *max_gram, *prefix_only)?)
+                .dynamic(),
+            TokenizerDefinition::Regex { pattern } => {
+                TextAnalyzer::builder(RegexTokenizer::new(pattern)?).dynamic()
+            }
+            TokenizerDefinition::Custom { name } => {
+                let analyzer = tokenizer_manager.get_registered(name).ok_or_else(|| {
+                    TantivyError::InvalidArgument(format!(
+                        "No tokenizer registered under the name {name:?}"
+                    ))
+                })?;
+                TextAnalyzer::builder(RegisteredAnalyzer(analyzer)).dynamic()
+            }
+        };
+        Ok(builder)
+    }
+}
+
+impl TokenFilterDefinition {
+    fn append_to(&self, builder: TextAnalyzerBuilder) -> crate::Result<TextAnalyzerBuilder> {
+        let builder = match self {
+            TokenFilterDefinition::LowerCaser => builder.filter_dynamic(LowerCaser),
+            TokenFilterDefinition::AsciiFolding { preserve_original } => {
+                if *preserve_original {
+                    builder.filter_dynamic(AsciiFoldingFilter.preserve_original())
+                } else {
+                    builder.filter_dynamic(AsciiFoldingFilter)
+                }
+            }
+            TokenFilterDefinition::AlphaNumOnly => builder.filter_dynamic(AlphaNumOnlyFilter),
+            TokenFilterDefinition::RemoveLong { limit } => {
+                builder.filter_dynamic(RemoveLongFilter::limit(*limit))
+            }
+            TokenFilterDefinition::Stemmer {
+                language,
+                preserve_original,
+            } => {
+                let stemmer = Stemmer::new(*language);
+                if *preserve_original {
+                    builder.filter_dynamic(stemmer.preserve_original())
+                } else {
+                    builder.filter_dynamic(stemmer)
+                }
+            }
+            TokenFilterDefinition::KeywordRepeat => builder.filter_dynamic(KeywordRepeatFilter),
+            TokenFilterDefinition::RemoveDuplicates => {
+                builder.filter_dynamic(RemoveDuplicatesFilter)
+            }
+            TokenFilterDefinition::StopWords { language, words } => {
+                let mut stop_words = words.clone();
+                if let Some(language) = language {
+                    stop_words.extend(language_stop_words(*language)?);
+                }
+                builder.filter_dynamic(StopWordFilter::remove(stop_words))
+            }
+            TokenFilterDefinition::SplitCompoundWords { dictionary } => {
+                builder.filter_dynamic(SplitCompoundWords::from_dictionary(dictionary)?)
+            }
+            TokenFilterDefinition::Decompounder {
+                dictionary,
+                min_word_size,
+                min_subword_size,
+                max_subword_size,
+                only_longest_match,
+            } => {
+                let mut decompounder = Decompounder::from_dictionary(dictionary.iter().cloned());
+                if let Some(min_word_size) = min_word_size {
+                    decompounder = decompounder.min_word_size(*min_word_size);
+                }
+                if min_subword_size.is_some() || max_subword_size.is_some() {
+                    decompounder = decompounder.subword_size(
+                        min_subword_size.unwrap_or(DEFAULT_MIN_SUBWORD_SIZE),
+                        max_subword_size.unwrap_or(DEFAULT_MAX_SUBWORD_SIZE),
+                    );
+                }
+                if *only_longest_match {
+                    decompounder = decompounder.only_longest_match();
+                }
+                builder.filter_dynamic(decompounder)
+            }
+            TokenFilterDefinition::Shingle {
+                min_shingle_size,
+                max_shingle_size,
+                without_unigrams,
+            } => {
+                let shingle_filter = ShingleFilter::new(*min_shingle_size, *max_shingle_size)?;
+                if *without_unigrams {
+                    builder.filter_dynamic(shingle_filter.without_unigrams())
+                } else {
+                    builder.filter_dynamic(shingle_filter)
+                }
+            }
+        };
+        Ok(builder)
+    }
+}
+
+fn is_false(val: &bool) -> bool {
+    !val
+}
+
+#[cfg(feature = "stopwords")]
+fn language_stop_words(language: Language) -> crate::Result<Vec<String>> {
+    StopWordFilter::new(language)
+        .map(|stop_word_filter| stop_word_filter.words().map(str::to_string).collect())
+        .ok_or_else(|| {
+            TantivyError::InvalidArgument(format!("No stop words available for {language:?}"))
+        })
+}
+
+#[cfg(not(feature = "stopwords"))]
+fn language_stop_words(language: Language) -> crate::Result<Vec<String>> {
+    Err(TantivyError::InvalidArgument(format!(
+        "Stop words for {language:?} require the `stopwords` feature"
+    )))
+}
+
+/// Wraps a registered analyzer so that it can be used as the tokenizer of another one.
+#[derive(Clone)]
+struct RegisteredAnalyzer(TextAnalyzer);
+
+impl Tokenizer for RegisteredAnalyzer {
+    type TokenStream<'a> = BoxTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.0.token_stream(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::{Token, TokenStream};
+
+    fn tokens(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        analyzer.token_stream(text).process(&mut |token: &Token| {
+            tokens.push(token.text.clone());
+        });
+        tokens
+    }
+
+    #[test]
+    fn test_analyzer_definition_serialization() {
+        let definition = AnalyzerDefinition::new(TokenizerDefinition::Simple)
+            .char_filter(CharFilterDefinition::HtmlStrip)
+            .filter(TokenFilterDefinition::RemoveLong { limit: 40 })
+            .filter(TokenFilterDefinition::LowerCaser)
+            .filter(TokenFilterDefinition::Stemmer {
+                language: Language::English,
+                preserve_original: false,
+            });
+        let json = serde_json::to_string(&definition).unwrap();
+        assert_eq!(
+            json,
+            r#"{"char_filters":[{"type":"html_strip"}],"tokenizer":{"type":"simple"},"filters":[{"type":"remove_long","limit":40},{"type":"lower_caser"},{"type":"stemmer","language":"English"}]}"#
+        );
+        let deserialized: AnalyzerDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, definition);
+    }
+
+    #[test]
+    fn test_analyzer_definition_build() {
+        let definition = AnalyzerDefinition::new(TokenizerDefinition::Whitespace)
+            .filter(TokenFilterDefinition::LowerCaser)
+            .filter(TokenFilterDefinition::StopWords {
+                language: None,
+                words: vec!["the".to_string()],
+            })
+            .filter(TokenFilterDefinition::AsciiFolding {
+                preserve_original: false,
+            });
+        let mut analyzer = definition.build(&TokenizerManager::new()).unwrap();
+        assert_eq!(tokens(&mut analyzer, "The Café"), vec!["cafe".to_string()]);
+    }
+
+    #[test]
+    fn test_analyzer_definition_custom_tokenizer() {
+        let tokenizer_manager = TokenizerManager::new();
+        let definition = AnalyzerDefinition::new(TokenizerDefinition::Custom {
+            name: "my_tokenizer".to_string(),
+        })
+        .filter(TokenFilterDefinition::LowerCaser);
+        assert!(definition.build(&tokenizer_manager).is_err());
+        tokenizer_manager.register("my_tokenizer", WhitespaceTokenizer::default());
+        let mut analyzer = definition.build(&tokenizer_manager).unwrap();
+        assert_eq!(
+            tokens(&mut analyzer, "Hello, World"),
+            vec!["hello,".to_string(), "world".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_analyzer_definition_invalid_parameters() {
+        let definition = AnalyzerDefinition::new(TokenizerDefinition::Ngram {
+            min_gram: 3,
+            max_gram: 2,
+            prefix_only: false,
+        });
+        assert!(definition.build(&TokenizerManager::new()).is_err());
+        let definition = AnalyzerDefinition::new(TokenizerDefinition::Simple).filter(
+            TokenFilterDefinition::Shingle {
+                min_shingle_size: 1,
+                max_shingle_size: 2,
+                without_unigrams: false,
+            },
+        );
+        assert!(definition.build(&TokenizerManager::new()).is_err());
+    }
+}
@@ -5,9 +5,32 @@ use super::{Token, TokenFilter, TokenStream, Tokenizer};
 /// This class converts alphabetic, numeric, and symbolic Unicode characters
 /// which are not in the first 127 ASCII characters (the "Basic Latin" Unicode
 /// block) into their ASCII equivalents, if one exists.
+///
+/// Tokens marked as [keywords](Token::keyword) are left untouched.
 #[derive(Clone)]
 pub struct AsciiFoldingFilter;
 
+impl AsciiFoldingFilter {
+    /// Returns a filter that also emits the original token, at the same position,
+    /// whenever folding modified it. The original token is emitted first.
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::*;
+    ///
+    /// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+    ///     .filter(AsciiFoldingFilter.preserve_original())
+    ///     .build();
+    /// let mut stream = tokenizer.token_stream("straße");
+    /// assert_eq!(stream.next().unwrap().text, "straße");
+    /// assert_eq!(stream.next().unwrap().text, "strasse");
+    /// assert!(stream.next().is_none());
+    /// ```
+    #[must_use]
+    pub fn preserve_original(self) -> PreserveOriginalAsciiFoldingFilter {
+        PreserveOriginalAsciiFoldingFilter
+    }
+}
+
 impl TokenFilter for AsciiFoldingFilter {
     type Tokenizer<T: Tokenizer> = AsciiFoldingFilterWrapper<T>;
 
@@ -15,6 +38,27 @@ impl TokenFilter for AsciiFoldingFilter {
         AsciiFoldingFilterWrapper {
             tokenizer,
             buffer: String::new(),
+            preserve_original: false,
+        }
+    }
+}
+
+/// Same as [`AsciiFoldingFilter`], except that the original token is emitted
+/// right before its folded version if they differ.
+///
+/// This makes it possible to rank exact matches higher, as both the folded and
+/// the original forms are indexed.
+#[derive(Clone)]
+pub struct PreserveOriginalAsciiFoldingFilter;
+
+impl TokenFilter for PreserveOriginalAsciiFoldingFilter {
+    type Tokenizer<T: Tokenizer> = AsciiFoldingFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> AsciiFoldingFilterWrapper<T> {
+        AsciiFoldingFilterWrapper {
+            tokenizer,
+            buffer: String::new(),
+            preserve_original: true,
         }
     }
 }
@@ -23,6 +67,7 @@ impl TokenFilter for AsciiFoldingFilter {
 pub struct AsciiFoldingFilterWrapper<T> {
     tokenizer: T,
     buffer: String,
+    preserve_original: bool,
 }
 
 impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
@@ -33,6 +78,8 @@ impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
         AsciiFoldingFilterTokenStream {
             buffer: &mut self.buffer,
             tail: self.tokenizer.token_stream(text),
+            preserve_original: self.preserve_original,
+            folded_pending: false,
         }
     }
 }
@@ -40,16 +87,30 @@ impl<T: Tokenizer> Tokenizer for AsciiFoldingFilterWrapper<T> {
 pub struct AsciiFoldingFilterTokenStream<'a, T> {
     buffer: &'a mut String,
     tail: T,
+    preserve_original: bool,
+    // `true` if `buffer` holds the folded text of the current (original) token,
+    // which has yet to be emitted.
+    folded_pending: bool,
 }
 
 impl<T: TokenStream> TokenStream for AsciiFoldingFilterTokenStream<'_, T> {
     fn advance(&mut self) -> bool {
+        if self.folded_pending {
+            self.folded_pending = false;
+            mem::swap(&mut self.tail.token_mut().text, self.buffer);
+            return true;
+        }
         if !self.tail.advance() {
             return false;
         }
-        if !self.token_mut().text.is_ascii() {
-            // ignore its already ascii
-            to_ascii(&self.tail.token().text, self.buffer);
+        let token = self.tail.token();
+        if token.keyword || token.text.is_ascii() {
+            return true;
+        }
+        to_ascii(&token.text, self.buffer);
+        if self.preserve_original {
+            self.folded_pending = *self.buffer != token.text;
+        } else {
             mem::swap(&mut self.tail.token_mut().text, self.buffer);
         }
         true
@@ -1564,7 +1625,10 @@ mod tests {
     use std::iter;
 
     use super::to_ascii;
-    use crate::tokenizer::{AsciiFoldingFilter, RawTokenizer, SimpleTokenizer, TextAnalyzer};
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        AsciiFoldingFilter, KeywordRepeatFilter, RawTokenizer, SimpleTokenizer, TextAnalyzer, Token,
+    };
 
     #[test]
     fn test_ascii_folding() {
@@ -1578,6 +1642,41 @@ mod tests {
         assert_eq!(&folding_helper("Usagi"), &["Usagi"]);
     }
 
+    #[test]
+    fn test_ascii_folding_preserve_original() {
+        let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(AsciiFoldingFilter.preserve_original())
+            .build();
+        let mut tokens = Vec::new();
+        tokenizer
+            .token_stream("Crème brûlée 馬 done")
+            .process(&mut |token: &Token| {
+                tokens.push(token.clone());
+            });
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "Crème", 0, 6);
+        assert_token(&tokens[1], 0, "Creme", 0, 6);
+        assert_token(&tokens[2], 1, "brûlée", 7, 15);
+        assert_token(&tokens[3], 1, "brulee", 7, 15);
+        assert_token(&tokens[4], 2, "馬", 16, 19);
+        assert_token(&tokens[5], 3, "done", 20, 24);
+    }
+
+    #[test]
+    fn test_ascii_folding_skips_keywords() {
+        let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(KeywordRepeatFilter)
+            .filter(AsciiFoldingFilter)
+            .build();
+        let mut tokens = Vec::new();
+        tokenizer
+            .token_stream("crème")
+            .process(&mut |token: &Token| {
+                tokens.push(token.text.clone());
+            });
+        assert_eq!(tokens, ["crème", "creme"]);
+    }
+
     fn folding_helper(text: &str) -> Vec<String> {
         let mut tokens = Vec::new();
         TextAnalyzer::builder(SimpleTokenizer::default())
@@ -0,0 +1,173 @@
+use super::{Token, TokenStream};
+
+/// A `CharFilter` preprocesses the text before it is handed over to the tokenizer.
+///
+/// Character filters can add, remove or replace characters (e.g. strip HTML markup, or
+/// normalize some punctuation). Because the tokenizer then runs over the filtered text,
+/// each filter also records how offsets of its output map back to offsets of its input,
+/// so that the tokens emitted by a [`TextAnalyzer`](super::TextAnalyzer) keep pointing into
+/// the original text (this is for instance required for snippets).
+pub trait CharFilter: 'static + Send + Sync {
+    /// Filters `text` and appends the result to `output`.
+    ///
+    /// Every time a span of the input is rewritten, the filter must register it in
+    /// `offset_mapping` via [`OffsetMapping::add_replacement`].
+    fn filter(&self, text: &str, output: &mut String, offset_mapping: &mut OffsetMapping);
+}
+
+/// Maps byte offsets of a filtered text back to byte offsets of the text it was
+/// produced from.
+///
+/// The mapping is expressed as a sorted list of anchors `(filtered_offset, original_offset)`.
+/// Offsets located between two anchors are mapped linearly from the previous anchor.
+#[derive(Clone, Debug, Default)]
+pub struct OffsetMapping {
+    anchors: Vec<(usize, usize)>,
+}
+
+impl OffsetMapping {
+    /// Resets the mapping to the identity.
+    pub fn clear(&mut self) {
+        self.anchors.clear();
+    }
+
+    /// Records that the original span `original_from..original_to` was rewritten into
+    /// `filtered_from..filtered_to` in the filtered text.
+    ///
+    /// Replacements must be registered in increasing order.
+    pub fn add_replacement(
+        &mut self,
+        filtered_from: usize,
+        filtered_to: usize,
+        original_from: usize,
+        original_to: usize,
+    ) {
+        debug_assert!(filtered_from <= filtered_to);
+        debug_assert!(original_from <= original_to);
+        debug_assert!(self
+            .anchors
+            .last()
+            .map(|&(filtered, original)| filtered <= filtered_from && original <= original_from)
+            .unwrap_or(true));
+        self.anchors.push((filtered_from, original_from));
+        self.anchors.push((filtered_to, original_to));
+    }
+
+    /// Returns the original offset of a token starting at `filtered_offset`.
+    ///
+    /// If a span was removed at this position, the token is considered to start after it.
+    pub fn original_start(&self, filtered_offset: usize) -> usize {
+        let end = self
+            .anchors
+            .partition_point(|&(filtered, _)| filtered <= filtered_offset);
+        if end > 0 && self.anchors[end - 1].0 == filtered_offset {
+            return self.anchors[end - 1].1;
+        }
+        self.interpolate(filtered_offset, end)
+    }
+
+    /// Returns the original offset of a token ending at `filtered_offset`.
+    ///
+    /// If a span was removed at this position, the token is considered to end before it.
+    pub fn original_end(&self, filtered_offset: usize) -> usize {
+        let start = self
+            .anchors
+            .partition_point(|&(filtered, _)| filtered < filtered_offset);
+        if start < self.anchors.len() && self.anchors[start].0 == filtered_offset {
+            return self.anchors[start].1;
+        }
+        self.interpolate(filtered_offset, start)
+    }
+
+    // `next` is the index of the first anchor located strictly after `filtered_offset`.
+    fn interpolate(&self, filtered_offset: usize, next: usize) -> usize {
+        let (prev_filtered, prev_original) = if next == 0 {
+            (0, 0)
+        } else {
+            self.anchors[next - 1]
+        };
+        let original = prev_original + (filtered_offset - prev_filtered);
+        if let Some(&(_, next_original)) = self.anchors.get(next) {
+            original.min(next_original)
+        } else {
+            original
+        }
+    }
+}
+
+/// Token stream correcting the offsets of the underlying stream, so that they point
+/// into the text as it was before character filtering.
+pub(crate) struct OffsetCorrectingTokenStream<'a, T> {
+    pub(crate) tail: T,
+    // One mapping per char filter, in the order the filters were applied.
+    pub(crate) offset_mappings: &'a [OffsetMapping],
+}
+
+impl<T: TokenStream> TokenStream for OffsetCorrectingTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        for offset_mapping in self.offset_mappings.iter().rev() {
+            token.offset_from = offset_mapping.original_start(token.offset_from);
+            token.offset_to = offset_mapping.original_end(token.offset_to);
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OffsetMapping;
+
+    #[test]
+    fn test_offset_mapping_identity() {
+        let offset_mapping = OffsetMapping::default();
+        assert_eq!(offset_mapping.original_start(3), 3);
+        assert_eq!(offset_mapping.original_end(3), 3);
+    }
+
+    #[test]
+    fn test_offset_mapping_removal() {
+        // "<b>foo</b> bar" -> "foo bar"
+        let mut offset_mapping = OffsetMapping::default();
+        offset_mapping.add_replacement(0, 0, 0, 3);
+        offset_mapping.add_replacement(3, 3, 6, 10);
+        assert_eq!(offset_mapping.original_start(0), 3);
+        assert_eq!(offset_mapping.original_end(3), 6);
+        assert_eq!(offset_mapping.original_start(4), 11);
+        assert_eq!(offset_mapping.original_end(7), 14);
+    }
+
+    #[test]
+    fn test_offset_mapping_replacement() {
+        // "a &amp; b" -> "a & b"
+        let mut offset_mapping = OffsetMapping::default();
+        offset_mapping.add_replacement(2, 3, 2, 7);
+        assert_eq!(offset_mapping.original_start(2), 2);
+        assert_eq!(offset_mapping.original_end(3), 7);
+        assert_eq!(offset_mapping.original_start(4), 8);
+        assert_eq!(offset_mapping.original_end(5), 9);
+    }
+
+    #[test]
+    fn test_offset_mapping_expansion() {
+        // "½ cup" -> "1/2 cup", offsets within the replacement are clamped to its original span.
+        let mut offset_mapping = OffsetMapping::default();
+        offset_mapping.add_replacement(0, 3, 0, 2);
+        assert_eq!(offset_mapping.original_start(0), 0);
+        assert_eq!(offset_mapping.original_end(1), 1);
+        assert_eq!(offset_mapping.original_start(2), 2);
+        assert_eq!(offset_mapping.original_end(3), 2);
+        assert_eq!(offset_mapping.original_start(4), 3);
+    }
+}
@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use rustc_hash::FxHashSet;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// A [`TokenFilter`] which indexes the dictionary words found inside of compound words,
+/// in addition to the compound words themselves.
+///
+/// Contrary to [`SplitCompoundWords`](super::SplitCompoundWords), words do not need to be
+/// fully decomposable into dictionary words: every dictionary word found inside of a token
+/// is emitted, at the same position as the token. This makes the filter robust to linking
+/// morphemes (e.g. the `s` of the German "Arbeitsplatz") and to incomplete dictionaries,
+/// which matters for the [compound nouns][compound] common in German or Dutch.
+///
+/// Tokens are expected to be lowercased beforehand, and the dictionary to be lowercase.
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LowerCaser)
+///     .filter(Decompounder::from_dictionary(["fuß", "boden", "schleif", "maschine"]))
+///     .build();
+/// let mut stream = tokenizer.token_stream("Fußbodenschleifmaschine");
+/// assert_eq!(stream.next().unwrap().text, "fußbodenschleifmaschine");
+/// assert_eq!(stream.next().unwrap().text, "fuß");
+/// assert_eq!(stream.next().unwrap().text, "boden");
+/// assert_eq!(stream.next().unwrap().text, "schleif");
+/// assert_eq!(stream.next().unwrap().text, "maschine");
+/// assert!(stream.next().is_none());
+/// ```
+///
+/// [compound]: https://en.wikipedia.org/wiki/Compound_(linguistics)
+#[derive(Clone)]
+pub struct Decompounder {
+    dictionary: Arc<FxHashSet<String>>,
+    min_word_size: usize,
+    min_subword_size: usize,
+    max_subword_size: usize,
+    only_longest_match: bool,
+}
+
+/// Default minimum length, in chars, of the words to decompound.
+const DEFAULT_MIN_WORD_SIZE: usize = 5;
+/// Default minimum length, in chars, of the emitted subwords.
+pub(crate) const DEFAULT_MIN_SUBWORD_SIZE: usize = 2;
+/// Default maximum length, in chars, of the emitted subwords.
+pub(crate) const DEFAULT_MAX_SUBWORD_SIZE: usize = 15;
+
+impl Decompounder {
+    /// Creates a `Decompounder` looking for the words of the given dictionary.
+    pub fn from_dictionary<I, S>(dictionary: I) -> Decompounder
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Decompounder {
+            dictionary: Arc::new(dictionary.into_iter().map(Into::into).collect()),
+            min_word_size: DEFAULT_MIN_WORD_SIZE,
+            min_subword_size: DEFAULT_MIN_SUBWORD_SIZE,
+            max_subword_size: DEFAULT_MAX_SUBWORD_SIZE,
+            only_longest_match: false,
+        }
+    }
+
+    /// Tokens shorter than `min_word_size` chars are not decompounded.
+    #[must_use]
+    pub fn min_word_size(mut self, min_word_size: usize) -> Decompounder {
+        self.min_word_size = min_word_size;
+        self
+    }
+
+    /// Only emits subwords whose length, in chars, lies within the given bounds.
+    #[must_use]
+    pub fn subword_size(
+        mut self,
+        min_subword_size: usize,
+        max_subword_size: usize,
+    ) -> Decompounder {
+        self.min_subword_size = min_subword_size.max(1);
+        self.max_subword_size = max_subword_size;
+        self
+    }
+
+    /// Only emits the longest dictionary word starting at each position of the token.
+    ///
+    /// For instance, with both "schleif" and "schleifmaschine" in the dictionary, only
+    /// "schleifmaschine" would be emitted for "fußbodenschleifmaschine".
+    #[must_use]
+    pub fn only_longest_match(mut self) -> Decompounder {
+        self.only_longest_match = true;
+        self
+    }
+}
+
+impl TokenFilter for Decompounder {
+    type Tokenizer<T: Tokenizer> = DecompounderFilter<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> DecompounderFilter<T> {
+        DecompounderFilter {
+            decompounder: self,
+            inner: tokenizer,
+            char_offsets: Vec::new(),
+            parts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DecompounderFilter<T> {
+    decompounder: Decompounder,
+    inner: T,
+    char_offsets: Vec<usize>,
+    parts: Vec<Token>,
+}
+
+impl<T: Tokenizer> Tokenizer for DecompounderFilter<T> {
+    type TokenStream<'a> = DecompounderTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.char_offsets.clear();
+        self.parts.clear();
+        DecompounderTokenStream {
+            decompounder: &self.decompounder,
+            tail: self.inner.token_stream(text),
+            char_offsets: &mut self.char_offsets,
+            parts: &mut self.parts,
+            emitting_parts: false,
+        }
+    }
+}
+
+pub struct DecompounderTokenStream<'a, T> {
+    decompounder: &'a Decompounder,
+    tail: T,
+    char_offsets: &'a mut Vec<usize>,
+    // Subwords of the current token, in reverse order so that
+    // `parts.pop()` yields them in their original order.
+    parts: &'a mut Vec<Token>,
+    // `false` while the current token of `tail` is emitted, `true` while its subwords are.
+    emitting_parts: bool,
+}
+
+impl<T: TokenStream> DecompounderTokenStream<'_, T> {
+    // Fills `self.parts` with the dictionary words found in `self.tail.token()`.
+    fn decompound(&mut self) {
+        let token = self.tail.token();
+        let decompounder = self.decompounder;
+        self.char_offsets.clear();
+        self.char_offsets
+            .extend(token.text.char_indices().map(|(offset, _)| offset));
+        let num_chars = self.char_offsets.len();
+        if num_chars < decompounder.min_word_size {
+            return;
+        }
+        self.char_offsets.push(token.text.len());
+        for start in 0..num_chars {
+            let min_end = start + decompounder.min_subword_size;
+            let max_end = (start + decompounder.max_subword_size).min(num_chars);
+            let mut longest_match: Option<usize> = None;
+            for end in min_end..=max_end {
+                let subword = &token.text[self.char_offsets[start]..self.char_offsets[end]];
+                // The token itself is already emitted.
+                if subword.len() == token.text.len() || !decompounder.dictionary.contains(subword) {
+                    continue;
+                }
+                if decompounder.only_longest_match {
+                    longest_match = Some(end);
+                } else {
+                    self.parts.push(Token {
+                        text: subword.to_string(),
+                        ..*token
+                    });
+                }
+            }
+            if let Some(end) = longest_match {
+                self.parts.push(Token {
+                    text: token.text[self.char_offsets[start]..self.char_offsets[end]].to_string(),
+                    ..*token
+                });
+            }
+        }
+        self.parts.reverse();
+    }
+}
+
+impl<T: TokenStream> TokenStream for DecompounderTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if self.emitting_parts {
+            self.parts.pop();
+        }
+        if !self.parts.is_empty() {
+            self.emitting_parts = true;
+            return true;
+        }
+        self.emitting_parts = false;
+        if !self.tail.advance() {
+            return false;
+        }
+        self.decompound();
+        true
+    }
+
+    fn token(&self) -> &Token {
+        if self.emitting_parts {
+            self.parts.last().unwrap_or_else(|| self.tail.token())
+        } else {
+            self.tail.token()
+        }
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        if self.emitting_parts {
+            self.parts
+                .last_mut()
+                .unwrap_or_else(|| self.tail.token_mut())
+        } else {
+            self.tail.token_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+
+    fn token_stream_helper(decompounder: Decompounder, text: &str) -> Vec<Token> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(decompounder)
+            .build();
+        let mut tokens = vec![];
+        analyzer.token_stream(text).process(&mut |token: &Token| {
+            tokens.push(token.clone());
+        });
+        tokens
+    }
+
+    fn texts(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|token| token.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_decompounder_linking_morpheme() {
+        let decompounder = Decompounder::from_dictionary(["arbeit", "platz"]);
+        let tokens = token_stream_helper(decompounder, "Arbeitsplatz sicher");
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "arbeitsplatz", 0, 12);
+        assert_token(&tokens[1], 0, "arbeit", 0, 12);
+        assert_token(&tokens[2], 0, "platz", 0, 12);
+        assert_token(&tokens[3], 1, "sicher", 13, 19);
+    }
+
+    #[test]
+    fn test_decompounder_longest_match() {
+        let dictionary = ["schleif", "schleifmaschine", "maschine"];
+        let tokens = token_stream_helper(
+            Decompounder::from_dictionary(dictionary),
+            "schleifmaschinenbau",
+        );
+        assert_eq!(
+            texts(&tokens),
+            vec![
+                "schleifmaschinenbau",
+                "schleif",
+                "schleifmaschine",
+                "maschine"
+            ]
+        );
+        let tokens = token_stream_helper(
+            Decompounder::from_dictionary(dictionary).only_longest_match(),
+            "schleifmaschinenbau",
+        );
+        assert_eq!(
+            texts(&tokens),
+            vec!["schleifmaschinenbau", "schleifmaschine", "maschine"]
+        );
+    }
+
+    #[test]
+    fn test_decompounder_sizes() {
+        let dictionary = ["ab", "abc", "cd"];
+        let tokens = token_stream_helper(Decompounder::from_dictionary(dictionary), "abcd");
+        assert_eq!(texts(&tokens), vec!["abcd"]);
+        let decompounder = Decompounder::from_dictionary(dictionary).min_word_size(4);
+        let tokens = token_stream_helper(decompounder, "abcd");
+        assert_eq!(texts(&tokens), vec!["abcd", "ab", "abc", "cd"]);
+        let decompounder = Decompounder::from_dictionary(dictionary)
+            .min_word_size(4)
+            .subword_size(3, 3);
+        let tokens = token_stream_helper(decompounder, "abcd");
+        assert_eq!(texts(&tokens), vec!["abcd", "abc"]);
+    }
+
+    #[test]
+    fn test_decompounder_no_match() {
+        let decompounder = Decompounder::from_dictionary(["haus"]);
+        let tokens = token_stream_helper(decompounder, "haus baum");
+        assert_eq!(texts(&tokens), vec!["haus", "baum"]);
+    }
+}
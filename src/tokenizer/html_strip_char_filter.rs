@@ -0,0 +1,289 @@
+use super::{CharFilter, OffsetMapping};
+
+/// Elements that introduce a break in the text flow.
+/// Tags of these elements are replaced by a line break rather than just removed,
+/// so that `foo<br>bar` is not indexed as `foobar`.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "option",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "title",
+    "tr",
+    "ul",
+];
+
+/// Elements whose content is not text and is removed altogether.
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style"];
+
+/// `CharFilter` removing HTML markup from the text.
+///
+/// - tags are removed, tags of block elements are replaced by a line break,
+/// - comments, as well as `<script>` and `<style>` elements, are removed entirely,
+/// - character references (`&amp;`, `&#233;`, `&#xE9;`, ...) are decoded.
+///
+/// Offsets of the resulting tokens point into the original HTML.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .char_filter(HtmlStripCharFilter)
+///     .build();
+/// let mut stream = analyzer.token_stream("<p>Caf&eacute;</p><p>au <b>lait</b></p>");
+/// let token = stream.next().unwrap();
+/// assert_eq!(token.text, "Café");
+/// assert_eq!((token.offset_from, token.offset_to), (3, 14));
+/// assert_eq!(stream.next().unwrap().text, "au");
+/// let token = stream.next().unwrap();
+/// assert_eq!(token.text, "lait");
+/// assert_eq!((token.offset_from, token.offset_to), (27, 31));
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HtmlStripCharFilter;
+
+impl CharFilter for HtmlStripCharFilter {
+    fn filter(&self, text: &str, output: &mut String, offset_mapping: &mut OffsetMapping) {
+        let bytes = text.as_bytes();
+        let mut pos = 0;
+        let mut copied_up_to = 0;
+        while let Some(delta) = bytes[pos..].iter().position(|&b| b == b'<' || b == b'&') {
+            let start = pos + delta;
+            let parsed = if bytes[start] == b'<' {
+                parse_markup(text, start)
+            } else {
+                parse_character_reference(text, start)
+            };
+            let Some((end, replacement)) = parsed else {
+                pos = start + 1;
+                continue;
+            };
+            output.push_str(&text[copied_up_to..start]);
+            let filtered_from = output.len();
+            if let Some(replacement) = replacement {
+                output.push(replacement);
+            }
+            offset_mapping.add_replacement(filtered_from, output.len(), start, end);
+            pos = end;
+            copied_up_to = end;
+        }
+        output.push_str(&text[copied_up_to..]);
+    }
+}
+
+/// Parses the markup starting at `start` (which points to a `<`).
+///
+/// Returns the end of the markup and its replacement, or `None` if `<` does not
+/// open any markup and should be kept as is.
+fn parse_markup(text: &str, start: usize) -> Option<(usize, Option<char>)> {
+    let bytes = text.as_bytes();
+    let rest = &text[start..];
+    if let Some(comment) = rest.strip_prefix("<!--") {
+        let end = comment
+            .find("-->")
+            .map(|comment_len| start + 4 + comment_len + 3)
+            .unwrap_or(text.len());
+        return Some((end, None));
+    }
+    let name_start = start + 1 + usize::from(bytes.get(start + 1) == Some(&b'/'));
+    let first_char = *bytes.get(name_start)?;
+    if !(first_char.is_ascii_alphabetic() || first_char == b'!' || first_char == b'?') {
+        return None;
+    }
+    let tag_end = start + rest.find('>')? + 1;
+    let name_end = bytes[name_start..tag_end]
+        .iter()
+        .position(|b| !b.is_ascii_alphanumeric())
+        .map(|name_len| name_start + name_len)
+        .unwrap_or(tag_end);
+    let name = text[name_start..name_end].to_ascii_lowercase();
+    let is_closing = name_start != start + 1;
+    if !is_closing && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+        let closing_tag = format!("</{name}");
+        let end = find_ascii_case_insensitive(&text[tag_end..], &closing_tag)
+            .and_then(|closing_start| {
+                let closing_start = tag_end + closing_start;
+                text[closing_start..]
+                    .find('>')
+                    .map(|closing_len| closing_start + closing_len + 1)
+            })
+            .unwrap_or(text.len());
+        return Some((end, None));
+    }
+    if BLOCK_ELEMENTS.contains(&name.as_str()) {
+        Some((tag_end, Some('\n')))
+    } else {
+        Some((tag_end, None))
+    }
+}
+
+fn find_ascii_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Longest character reference we attempt to decode, e.g. `&#x10FFFF;`.
+const MAX_CHARACTER_REFERENCE_LEN: usize = 10;
+
+/// Parses the character reference starting at `start` (which points to a `&`).
+///
+/// Returns the end of the reference and the decoded character, or `None` if the
+/// reference is unknown or malformed and should be kept as is.
+fn parse_character_reference(text: &str, start: usize) -> Option<(usize, Option<char>)> {
+    let rest = &text.as_bytes()[start + 1..];
+    let len = rest
+        .iter()
+        .take(MAX_CHARACTER_REFERENCE_LEN)
+        .position(|&b| b == b';')?;
+    let reference = std::str::from_utf8(&rest[..len]).ok()?;
+    let decoded = if let Some(numeric) = reference.strip_prefix('#') {
+        let code_point = if let Some(hex) = numeric
+            .strip_prefix('x')
+            .or_else(|| numeric.strip_prefix('X'))
+        {
+            u32::from_str_radix(hex, 16).ok()?
+        } else {
+            numeric.parse::<u32>().ok()?
+        };
+        char::from_u32(code_point)?
+    } else {
+        named_character_reference(reference)?
+    };
+    Some((start + len + 2, Some(decoded)))
+}
+
+fn named_character_reference(name: &str) -> Option<char> {
+    let decoded = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "euro" => '€',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "laquo" => '«',
+        "raquo" => '»',
+        "szlig" => 'ß',
+        "aacute" => 'á',
+        "agrave" => 'à',
+        "acirc" => 'â',
+        "auml" => 'ä',
+        "eacute" => 'é',
+        "egrave" => 'è',
+        "ecirc" => 'ê',
+        "euml" => 'ë',
+        "iacute" => 'í',
+        "icirc" => 'î',
+        "iuml" => 'ï',
+        "oacute" => 'ó',
+        "ocirc" => 'ô',
+        "ouml" => 'ö',
+        "uacute" => 'ú',
+        "ugrave" => 'ù',
+        "ucirc" => 'û',
+        "uuml" => 'ü',
+        "ccedil" => 'ç',
+        "ntilde" => 'ñ',
+        "Auml" => 'Ä',
+        "Eacute" => 'É',
+        "Ouml" => 'Ö',
+        "Uuml" => 'Ü',
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlStripCharFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{CharFilter, OffsetMapping, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn strip(text: &str) -> String {
+        let mut output = String::new();
+        HtmlStripCharFilter.filter(text, &mut output, &mut OffsetMapping::default());
+        output
+    }
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .char_filter(HtmlStripCharFilter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_html_strip_tags() {
+        assert_eq!(strip("<b>bold</b> text"), "bold text");
+        assert_eq!(strip("line<br/>break"), "line\nbreak");
+        assert_eq!(strip("<DIV class=\"a\">x</DIV>"), "\nx\n");
+        assert_eq!(strip("a <!-- comment --> b"), "a  b");
+        assert_eq!(strip("a<script>var x = '<b>';</script>b"), "ab");
+        assert_eq!(strip("a<STYLE>p { }</style >b"), "ab");
+    }
+
+    #[test]
+    fn test_html_strip_keeps_non_markup() {
+        assert_eq!(strip("1 < 2 > 0"), "1 < 2 > 0");
+        assert_eq!(strip("AT&T; a & b"), "AT&T; a & b");
+        assert_eq!(strip("unclosed <b"), "unclosed <b");
+    }
+
+    #[test]
+    fn test_html_strip_character_references() {
+        assert_eq!(strip("a &amp; b"), "a & b");
+        assert_eq!(strip("&#233;t&#xE9;"), "été");
+        assert_eq!(strip("&lt;b&gt;"), "<b>");
+    }
+
+    #[test]
+    fn test_html_strip_offsets() {
+        let text = "<p>Hello <i>wor</i>ld</p>&eacute;t&eacute;";
+        let tokens = token_stream_helper(text);
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "Hello", 3, 8);
+        assert_token(&tokens[1], 1, "world", 12, 21);
+        assert_token(&tokens[2], 2, "été", 25, 42);
+        assert_eq!(&text[25..42], "&eacute;t&eacute;");
+    }
+}
@@ -0,0 +1,151 @@
+//! # Example
+//! ```rust
+//! use tantivy::tokenizer::*;
+//!
+//! let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+//!   .filter(LowerCaser)
+//!   .filter(KeywordRepeatFilter)
+//!   .filter(Stemmer::new(Language::English))
+//!   .filter(RemoveDuplicatesFilter)
+//!   .build();
+//!
+//! let mut stream = tokenizer.token_stream("Cats fish");
+//! // `cats` is indexed both as is and stemmed.
+//! assert_eq!(stream.next().unwrap().text, "cats");
+//! assert_eq!(stream.next().unwrap().text, "cat");
+//! // `fish` is its own stem, the duplicate is removed.
+//! assert_eq!(stream.next().unwrap().text, "fish");
+//! assert!(stream.next().is_none());
+//! ```
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `KeywordRepeatFilter` emits every token twice, at the same position.
+///
+/// The first copy is marked as a [keyword](Token::keyword), so that the following
+/// stemmers leave it untouched, while the second one is processed as usual. Both the exact
+/// and the stemmed form of each word are therefore indexed.
+///
+/// When the stem of a word is the word itself, the two copies are identical:
+/// [`RemoveDuplicatesFilter`](super::RemoveDuplicatesFilter) should be added after the
+/// stemmer to avoid inflating term frequencies.
+#[derive(Clone)]
+pub struct KeywordRepeatFilter;
+
+impl TokenFilter for KeywordRepeatFilter {
+    type Tokenizer<T: Tokenizer> = KeywordRepeatFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> KeywordRepeatFilterWrapper<T> {
+        KeywordRepeatFilterWrapper {
+            inner: tokenizer,
+            buffer: String::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct KeywordRepeatFilterWrapper<T> {
+    inner: T,
+    buffer: String,
+}
+
+impl<T: Tokenizer> Tokenizer for KeywordRepeatFilterWrapper<T> {
+    type TokenStream<'a> = KeywordRepeatTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.buffer.clear();
+        KeywordRepeatTokenStream {
+            tail: self.inner.token_stream(text),
+            original_text: &mut self.buffer,
+            repeat: false,
+        }
+    }
+}
+
+pub struct KeywordRepeatTokenStream<'a, T> {
+    tail: T,
+    // Text of the current token, to restore it in case the keyword copy was altered.
+    original_text: &'a mut String,
+    repeat: bool,
+}
+
+impl<T: TokenStream> TokenStream for KeywordRepeatTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        if self.repeat {
+            self.repeat = false;
+            let token = self.tail.token_mut();
+            token.text.clear();
+            token.text.push_str(self.original_text);
+            token.keyword = false;
+            return true;
+        }
+        if !self.tail.advance() {
+            return false;
+        }
+        let token = self.tail.token_mut();
+        if !token.keyword {
+            self.original_text.clear();
+            self.original_text.push_str(&token.text);
+            token.keyword = true;
+            self.repeat = true;
+        }
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        KeywordRepeatFilter, Language, LowerCaser, RemoveDuplicatesFilter, SimpleTokenizer,
+        Stemmer, TextAnalyzer, Token,
+    };
+
+    fn token_stream_helper(text: &str, remove_duplicates: bool) -> Vec<Token> {
+        let builder = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(KeywordRepeatFilter)
+            .filter(Stemmer::new(Language::English));
+        let mut analyzer = if remove_duplicates {
+            builder.filter(RemoveDuplicatesFilter).build()
+        } else {
+            builder.build()
+        };
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_keyword_repeat() {
+        let tokens = token_stream_helper("Running dogs", false);
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "running", 0, 7);
+        assert!(tokens[0].keyword);
+        assert_token(&tokens[1], 0, "run", 0, 7);
+        assert!(!tokens[1].keyword);
+        assert_token(&tokens[2], 1, "dogs", 8, 12);
+        assert_token(&tokens[3], 1, "dog", 8, 12);
+    }
+
+    #[test]
+    fn test_keyword_repeat_remove_duplicates() {
+        let tokens = token_stream_helper("fish running fish", true);
+        assert_eq!(tokens.len(), 4);
+        assert_token(&tokens[0], 0, "fish", 0, 4);
+        assert_token(&tokens[1], 1, "running", 5, 12);
+        assert_token(&tokens[2], 1, "run", 5, 12);
+        assert_token(&tokens[3], 2, "fish", 13, 17);
+    }
+}
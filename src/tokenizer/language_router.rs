@@ -0,0 +1,338 @@
+use std::sync::Arc;
+
+use tokenizer_api::{BoxTokenStream, Tokenizer};
+
+use super::{Language, TextAnalyzer};
+use crate::schema::{Field, Value};
+use crate::TantivyDocument;
+
+/// Detects the language of a text.
+///
+/// With the `language-detection` feature, tantivy provides an implementation based on the
+/// `whatlang` crate: [`WhatlangDetector`]. Closures can also be used as detectors.
+pub trait LanguageDetector: 'static + Send + Sync {
+    /// Returns the language of `text`, or `None` if it could not be detected.
+    fn detect(&self, text: &str) -> Option<Language>;
+}
+
+impl<F> LanguageDetector for F
+where F: Fn(&str) -> Option<Language> + 'static + Send + Sync
+{
+    fn detect(&self, text: &str) -> Option<Language> {
+        self(text)
+    }
+}
+
+/// `Tokenizer` detecting the language of each text value, and routing it to the
+/// analyzer configured for this language.
+///
+/// Texts whose language is not detected, or has no dedicated analyzer, are handled by
+/// the fallback analyzer.
+///
+/// Since tokenizers only see one value at a time, the detected language cannot be indexed
+/// by the router itself. [`LanguageRouter::tag_document`] can be called before adding a
+/// document to record it in a keyword field, so that searches can be filtered by language.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// // Any `LanguageDetector` works, e.g. `WhatlangDetector` with the `language-detection`
+/// // feature. This toy detector only recognizes French.
+/// let detector = |text: &str| text.contains(" le ").then_some(Language::French);
+/// let mut router = LanguageRouter::new(detector, SimpleTokenizer::default())
+///     .route(
+///         Language::French,
+///         TextAnalyzer::builder(SimpleTokenizer::default())
+///             .filter(LowerCaser)
+///             .filter(Stemmer::new(Language::French))
+///             .build(),
+///     );
+/// {
+///     let mut stream = router.token_stream("Tous les chemins mènent à Rome");
+///     assert_eq!(stream.next().unwrap().text, "Tous");
+/// }
+/// let mut stream = router.token_stream("Prends le chemin");
+/// assert_eq!(stream.next().unwrap().text, "prend");
+/// ```
+#[derive(Clone)]
+pub struct LanguageRouter {
+    detector: Arc<dyn LanguageDetector>,
+    routes: Vec<(Language, TextAnalyzer)>,
+    fallback: TextAnalyzer,
+}
+
+impl LanguageRouter {
+    /// Creates a new `LanguageRouter`, sending all texts to `fallback` until routes are added.
+    pub fn new<D, A>(detector: D, fallback: A) -> LanguageRouter
+    where
+        D: LanguageDetector,
+        TextAnalyzer: From<A>,
+    {
+        LanguageRouter {
+            detector: Arc::new(detector),
+            routes: Vec::new(),
+            fallback: TextAnalyzer::from(fallback),
+        }
+    }
+
+    /// Routes the texts written in `language` to `analyzer`.
+    ///
+    /// Replaces the analyzer previously configured for this language, if any.
+    #[must_use]
+    pub fn route<A>(mut self, language: Language, analyzer: A) -> LanguageRouter
+    where TextAnalyzer: From<A> {
+        let analyzer = TextAnalyzer::from(analyzer);
+        if let Some(route) = self
+            .routes
+            .iter_mut()
+            .find(|(route_language, _)| *route_language == language)
+        {
+            route.1 = analyzer;
+        } else {
+            self.routes.push((language, analyzer));
+        }
+        self
+    }
+
+    /// Detects the language of `text`.
+    pub fn detect(&self, text: &str) -> Option<Language> {
+        self.detector.detect(text)
+    }
+
+    /// Detects the language of the text values of `source_field`, and records its
+    /// [code](Language::code) in `language_field`.
+    ///
+    /// Returns the detected language. If it could not be detected, the document is left
+    /// untouched.
+    pub fn tag_document(
+        &self,
+        doc: &mut TantivyDocument,
+        source_field: Field,
+        language_field: Field,
+    ) -> Option<Language> {
+        let texts: Vec<&str> = doc
+            .get_all(source_field)
+            .flat_map(|value| value.as_str())
+            .collect();
+        let language = self.detect(&texts.join("\n"))?;
+        doc.add_text(language_field, language.code());
+        Some(language)
+    }
+}
+
+impl Tokenizer for LanguageRouter {
+    type TokenStream<'a> = BoxTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        let language_opt = self.detector.detect(text);
+        let analyzer = language_opt
+            .and_then(|language| {
+                self.routes
+                    .iter_mut()
+                    .find(|(route_language, _)| *route_language == language)
+            })
+            .map(|(_, analyzer)| analyzer)
+            .unwrap_or(&mut self.fallback);
+        analyzer.token_stream(text)
+    }
+}
+
+/// [`LanguageDetector`] based on the [whatlang](https://docs.rs/whatlang) crate.
+///
+/// Only the languages listed in [`Language`] are considered.
+#[cfg(feature = "language-detection")]
+#[derive(Clone)]
+pub struct WhatlangDetector {
+    detector: whatlang::Detector,
+    min_confidence: f64,
+}
+
+#[cfg(feature = "language-detection")]
+impl Default for WhatlangDetector {
+    fn default() -> Self {
+        WhatlangDetector::new(&[])
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl WhatlangDetector {
+    /// Creates a new detector, restricted to the given languages.
+    ///
+    /// An empty list means all of the languages listed in [`Language`].
+    pub fn new(languages: &[Language]) -> WhatlangDetector {
+        let allowlist: Vec<whatlang::Lang> = if languages.is_empty() {
+            ALL_LANGUAGES.iter().copied().map(whatlang_lang).collect()
+        } else {
+            languages.iter().copied().map(whatlang_lang).collect()
+        };
+        WhatlangDetector {
+            detector: whatlang::Detector::with_allowlist(allowlist),
+            min_confidence: 0.0,
+        }
+    }
+
+    /// Only reports a language if the confidence of the detection, between `0.0` and `1.0`,
+    /// is at least `min_confidence`.
+    #[must_use]
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> WhatlangDetector {
+        self.min_confidence = min_confidence;
+        self
+    }
+}
+
+#[cfg(feature = "language-detection")]
+impl LanguageDetector for WhatlangDetector {
+    fn detect(&self, text: &str) -> Option<Language> {
+        let info = self.detector.detect(text)?;
+        if info.confidence() < self.min_confidence {
+            return None;
+        }
+        ALL_LANGUAGES
+            .iter()
+            .copied()
+            .find(|&language| whatlang_lang(language) == info.lang())
+    }
+}
+
+#[cfg(feature = "language-detection")]
+const ALL_LANGUAGES: [Language; 18] = [
+    Language::Arabic,
+    Language::Danish,
+    Language::Dutch,
+    Language::English,
+    Language::Finnish,
+    Language::French,
+    Language::German,
+    Language::Greek,
+    Language::Hungarian,
+    Language::Italian,
+    Language::Norwegian,
+    Language::Portuguese,
+    Language::Romanian,
+    Language::Russian,
+    Language::Spanish,
+    Language::Swedish,
+    Language::Tamil,
+    Language::Turkish,
+];
+
+#[cfg(feature = "language-detection")]
+fn whatlang_lang(language: Language) -> whatlang::Lang {
+    use whatlang::Lang;
+    match language {
+        Language::Arabic => Lang::Ara,
+        Language::Danish => Lang::Dan,
+        Language::Dutch => Lang::Nld,
+        Language::English => Lang::Eng,
+        Language::Finnish => Lang::Fin,
+        Language::French => Lang::Fra,
+        Language::German => Lang::Deu,
+        Language::Greek => Lang::Ell,
+        Language::Hungarian => Lang::Hun,
+        Language::Italian => Lang::Ita,
+        Language::Norwegian => Lang::Nob,
+        Language::Portuguese => Lang::Por,
+        Language::Romanian => Lang::Ron,
+        Language::Russian => Lang::Rus,
+        Language::Spanish => Lang::Spa,
+        Language::Swedish => Lang::Swe,
+        Language::Tamil => Lang::Tam,
+        Language::Turkish => Lang::Tur,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, Stemmer, Token, TokenStream};
+
+    fn toy_detector(text: &str) -> Option<Language> {
+        if text.contains(" der ") {
+            Some(Language::German)
+        } else if text.contains(" the ") {
+            Some(Language::English)
+        } else {
+            None
+        }
+    }
+
+    fn stemming_analyzer(language: Language) -> TextAnalyzer {
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LowerCaser)
+            .filter(Stemmer::new(language))
+            .build()
+    }
+
+    fn tokens(router: &mut LanguageRouter, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        router.token_stream(text).process(&mut |token: &Token| {
+            tokens.push(token.text.clone());
+        });
+        tokens
+    }
+
+    #[test]
+    fn test_language_router() {
+        let mut router = LanguageRouter::new(toy_detector, SimpleTokenizer::default())
+            .route(Language::English, stemming_analyzer(Language::English))
+            .route(Language::German, stemming_analyzer(Language::German));
+        assert_eq!(
+            tokens(&mut router, "Walking the dogs"),
+            vec!["walk", "the", "dog"]
+        );
+        assert_eq!(
+            tokens(&mut router, "Häuser der Stadt"),
+            vec!["haus", "der", "stadt"]
+        );
+        assert_eq!(
+            tokens(&mut router, "Unknown Words"),
+            vec!["Unknown", "Words"]
+        );
+    }
+
+    #[test]
+    fn test_language_router_tag_document() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let language_field = schema_builder.add_text_field("language", STRING);
+        let _schema = schema_builder.build();
+        let router = LanguageRouter::new(toy_detector, SimpleTokenizer::default());
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(text_field, "Walking the dogs");
+        assert_eq!(
+            router.tag_document(&mut doc, text_field, language_field),
+            Some(Language::English)
+        );
+        assert_eq!(
+            doc.get_first(language_field)
+                .and_then(|value| value.as_str()),
+            Some("en")
+        );
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(text_field, "Unknown");
+        assert_eq!(
+            router.tag_document(&mut doc, text_field, language_field),
+            None
+        );
+        assert!(doc.get_first(language_field).is_none());
+    }
+
+    #[cfg(feature = "language-detection")]
+    #[test]
+    fn test_whatlang_detector() {
+        let detector = WhatlangDetector::default();
+        assert_eq!(
+            detector.detect("The quick brown fox jumps over the lazy dog and runs away."),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detector.detect("Der schnelle braune Fuchs springt über den faulen Hund."),
+            Some(Language::German)
+        );
+        let detector = WhatlangDetector::new(&[Language::English]).with_min_confidence(1.1);
+        assert_eq!(detector.detect("The quick brown fox"), None);
+    }
+}
@@ -52,6 +52,20 @@
 //! remove their inflection. This tokenizer is slower than the default one,
 //! but is recommended to improve recall.
 //!
+//! # Char filters
+//!
+//! A [`TextAnalyzer`] can also preprocess the text before it gets tokenized, using
+//! [`CharFilter`]s. `tantivy` ships with the [`HtmlStripCharFilter`] and the
+//! [`PatternReplaceCharFilter`]. The offsets of the resulting tokens still point into
+//! the original text.
+//!
+//! # Analyzer definitions
+//!
+//! Registering a tokenizer by name is not persisted in the index: the same tokenizers
+//! need to be registered every time the index is opened. Alternatively, an
+//! [`AnalyzerDefinition`] can be attached to a text field. It is then saved with the schema
+//! and the index registers it automatically when it is opened.
+//!
 //! # Custom tokenizer Library
 //! Avoid using tantivy as dependency and prefer `tantivy-tokenizer-api` instead.
 //!
@@ -122,14 +136,23 @@
 //!     .register("custom_en", custom_en_tokenizer);
 //! ```
 mod alphanum_only;
+mod analyzer_definition;
 mod ascii_folding_filter;
+mod char_filter;
+mod decompounder;
 mod empty_tokenizer;
 mod facet_tokenizer;
+mod html_strip_char_filter;
+mod keyword_repeat;
+mod language_router;
 mod lower_caser;
 mod ngram_tokenizer;
+mod pattern_replace_char_filter;
 mod raw_tokenizer;
 mod regex_tokenizer;
+mod remove_duplicates;
 mod remove_long;
+mod shingle_filter;
 mod simple_tokenizer;
 mod split_compound_words;
 mod stemmer;
@@ -142,13 +165,26 @@ mod whitespace_tokenizer;
 pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokenizer};
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
-pub use self::ascii_folding_filter::AsciiFoldingFilter;
+pub use self::analyzer_definition::{
+    AnalyzerDefinition, CharFilterDefinition, TokenFilterDefinition, TokenizerDefinition,
+};
+pub use self::ascii_folding_filter::{AsciiFoldingFilter, PreserveOriginalAsciiFoldingFilter};
+pub use self::char_filter::{CharFilter, OffsetMapping};
+pub use self::decompounder::Decompounder;
 pub use self::facet_tokenizer::FacetTokenizer;
+pub use self::html_strip_char_filter::HtmlStripCharFilter;
+pub use self::keyword_repeat::KeywordRepeatFilter;
+#[cfg(feature = "language-detection")]
+pub use self::language_router::WhatlangDetector;
+pub use self::language_router::{LanguageDetector, LanguageRouter};
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
+pub use self::pattern_replace_char_filter::PatternReplaceCharFilter;
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::regex_tokenizer::RegexTokenizer;
+pub use self::remove_duplicates::RemoveDuplicatesFilter;
 pub use self::remove_long::RemoveLongFilter;
+pub use self::shingle_filter::ShingleFilter;
 pub use self::simple_tokenizer::{SimpleTokenStream, SimpleTokenizer};
 pub use self::split_compound_words::SplitCompoundWords;
 pub use self::stemmer::{Language, Stemmer};
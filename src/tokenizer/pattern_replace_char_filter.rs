@@ -0,0 +1,112 @@
+use regex::Regex;
+
+use super::{CharFilter, OffsetMapping};
+use crate::TantivyError;
+
+/// `CharFilter` replacing all of the matches of a regular expression.
+///
+/// The replacement string can refer to capture groups using the syntax
+/// supported by [`regex::Captures::expand`] (`$1`, `${name}`...).
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// // Index "123-456-789" as a single token.
+/// let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .char_filter(PatternReplaceCharFilter::new(r"(\d)-(\d)", "$1$2").unwrap())
+///     .build();
+/// let mut stream = analyzer.token_stream("call 123-456-789");
+/// assert_eq!(stream.next().unwrap().text, "call");
+/// let token = stream.next().unwrap();
+/// assert_eq!(token.text, "123456789");
+/// assert_eq!((token.offset_from, token.offset_to), (5, 16));
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PatternReplaceCharFilter {
+    regex: Regex,
+    replacement: String,
+}
+
+impl PatternReplaceCharFilter {
+    /// Creates a new `PatternReplaceCharFilter`.
+    ///
+    /// Returns an error if the pattern is not a valid regular expression.
+    pub fn new(pattern: &str, replacement: &str) -> crate::Result<PatternReplaceCharFilter> {
+        let regex =
+            Regex::new(pattern).map_err(|_| TantivyError::InvalidArgument(pattern.to_owned()))?;
+        Ok(PatternReplaceCharFilter {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+}
+
+impl CharFilter for PatternReplaceCharFilter {
+    fn filter(&self, text: &str, output: &mut String, offset_mapping: &mut OffsetMapping) {
+        let mut copied_up_to = 0;
+        for captures in self.regex.captures_iter(text) {
+            let matched = captures.get(0).expect("group 0 is always present");
+            if matched.is_empty() {
+                continue;
+            }
+            output.push_str(&text[copied_up_to..matched.start()]);
+            let filtered_from = output.len();
+            captures.expand(&self.replacement, output);
+            offset_mapping.add_replacement(
+                filtered_from,
+                output.len(),
+                matched.start(),
+                matched.end(),
+            );
+            copied_up_to = matched.end();
+        }
+        output.push_str(&text[copied_up_to..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PatternReplaceCharFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        CharFilter, HtmlStripCharFilter, OffsetMapping, SimpleTokenizer, TextAnalyzer, Token,
+    };
+
+    fn replace(pattern: &str, replacement: &str, text: &str) -> String {
+        let mut output = String::new();
+        PatternReplaceCharFilter::new(pattern, replacement)
+            .unwrap()
+            .filter(text, &mut output, &mut OffsetMapping::default());
+        output
+    }
+
+    #[test]
+    fn test_pattern_replace() {
+        assert_eq!(replace("a+", "b", "caaat aa"), "cbt b");
+        assert_eq!(replace(r"(\w+)@(\w+)", "$2 $1", "john@doe"), "doe john");
+        assert_eq!(replace("x*", "-", "abc"), "abc");
+    }
+
+    #[test]
+    fn test_pattern_replace_invalid_regex() {
+        assert!(PatternReplaceCharFilter::new("(", "").is_err());
+    }
+
+    #[test]
+    fn test_chained_char_filters_offsets() {
+        let text = "<b>C++</b> &amp; C#";
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .char_filter(HtmlStripCharFilter)
+            .char_filter(PatternReplaceCharFilter::new(r"C\+\+", "cplusplus").unwrap())
+            .char_filter(PatternReplaceCharFilter::new("C#", "csharp").unwrap())
+            .build();
+        let mut tokens = vec![];
+        analyzer.token_stream(text).process(&mut |token: &Token| {
+            tokens.push(token.clone());
+        });
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "cplusplus", 3, 6);
+        assert_token(&tokens[1], 1, "csharp", 17, 19);
+    }
+}
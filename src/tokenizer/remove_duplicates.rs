@@ -0,0 +1,105 @@
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+
+/// `RemoveDuplicatesFilter` removes tokens having the same text as a previous token
+/// emitted at the same position.
+///
+/// This is typically used after a [`KeywordRepeatFilter`](super::KeywordRepeatFilter)
+/// and a stemmer, to drop stems identical to the original word.
+#[derive(Clone)]
+pub struct RemoveDuplicatesFilter;
+
+impl TokenFilter for RemoveDuplicatesFilter {
+    type Tokenizer<T: Tokenizer> = RemoveDuplicatesFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> RemoveDuplicatesFilterWrapper<T> {
+        RemoveDuplicatesFilterWrapper {
+            inner: tokenizer,
+            seen: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RemoveDuplicatesFilterWrapper<T> {
+    inner: T,
+    seen: Vec<String>,
+}
+
+impl<T: Tokenizer> Tokenizer for RemoveDuplicatesFilterWrapper<T> {
+    type TokenStream<'a> = RemoveDuplicatesTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        RemoveDuplicatesTokenStream {
+            tail: self.inner.token_stream(text),
+            position: usize::MAX,
+            seen: &mut self.seen,
+            num_seen: 0,
+        }
+    }
+}
+
+pub struct RemoveDuplicatesTokenStream<'a, T> {
+    tail: T,
+    position: usize,
+    // Texts of the tokens emitted at `position` are stored in `seen[..num_seen]`.
+    // The remaining strings are only kept around to reuse their allocation.
+    seen: &'a mut Vec<String>,
+    num_seen: usize,
+}
+
+impl<T: TokenStream> TokenStream for RemoveDuplicatesTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        while self.tail.advance() {
+            let token = self.tail.token();
+            if token.position != self.position {
+                self.position = token.position;
+                self.num_seen = 0;
+            } else if self.seen[..self.num_seen].contains(&token.text) {
+                continue;
+            }
+            if self.num_seen == self.seen.len() {
+                self.seen.push(String::new());
+            }
+            let seen_text = &mut self.seen[self.num_seen];
+            seen_text.clear();
+            seen_text.push_str(&token.text);
+            self.num_seen += 1;
+            return true;
+        }
+        false
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{
+        KeywordRepeatFilter, RemoveDuplicatesFilter, TextAnalyzer, Token, WhitespaceTokenizer,
+    };
+
+    #[test]
+    fn test_remove_duplicates() {
+        let mut analyzer = TextAnalyzer::builder(WhitespaceTokenizer::default())
+            .filter(KeywordRepeatFilter)
+            .filter(RemoveDuplicatesFilter)
+            .build();
+        let mut tokens = vec![];
+        analyzer
+            .token_stream("a b a")
+            .process(&mut |token: &Token| {
+                tokens.push(token.clone());
+            });
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_token(&tokens[1], 1, "b", 2, 3);
+        assert_token(&tokens[2], 2, "a", 4, 5);
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// A [`TokenFilter`] emitting shingles, i.e. the sequences of consecutive tokens, joined by a
+/// space, in addition to the tokens themselves.
+///
+/// A shingle is emitted at the position of its first token, and its
+/// [`position_length`](Token::position_length) is its number of tokens. Indexing the shingles
+/// of a field gives access to the co-occurrence statistics of its words, used for instance by
+/// the [`PhraseSuggester`](crate::suggest::PhraseSuggester).
+///
+/// # Example
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LowerCaser)
+///     .filter(ShingleFilter::new(2, 2).unwrap())
+///     .build();
+/// let mut stream = tokenizer.token_stream("Ice cream sundae");
+/// assert_eq!(stream.next().unwrap().text, "ice");
+/// assert_eq!(stream.next().unwrap().text, "ice cream");
+/// assert_eq!(stream.next().unwrap().text, "cream");
+/// assert_eq!(stream.next().unwrap().text, "cream sundae");
+/// assert_eq!(stream.next().unwrap().text, "sundae");
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone)]
+pub struct ShingleFilter {
+    min_shingle_size: usize,
+    max_shingle_size: usize,
+    output_unigrams: bool,
+}
+
+impl ShingleFilter {
+    /// Creates a `ShingleFilter` emitting the shingles of `min_shingle_size` to
+    /// `max_shingle_size` tokens.
+    ///
+    /// Returns an error if `min_shingle_size` is lower than 2 or greater than
+    /// `max_shingle_size`.
+    pub fn new(min_shingle_size: usize, max_shingle_size: usize) -> crate::Result<ShingleFilter> {
+        if min_shingle_size < 2 {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must be at least 2".to_string(),
+            ));
+        }
+        if min_shingle_size > max_shingle_size {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must not be greater than max_shingle_size".to_string(),
+            ));
+        }
+        Ok(ShingleFilter {
+            min_shingle_size,
+            max_shingle_size,
+            output_unigrams: true,
+        })
+    }
+
+    /// Only emits the shingles, leaving out the tokens themselves.
+    #[must_use]
+    pub fn without_unigrams(mut self) -> ShingleFilter {
+        self.output_unigrams = false;
+        self
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            inner: tokenizer,
+            shingle_filter: self,
+            window: VecDeque::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T> {
+    inner: T,
+    shingle_filter: ShingleFilter,
+    window: VecDeque<Token>,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleTokenStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.window.clear();
+        ShingleTokenStream {
+            tail: self.inner.token_stream(text),
+            shingle_filter: &self.shingle_filter,
+            window: &mut self.window,
+            shingle_size: 0,
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct ShingleTokenStream<'a, T> {
+    tail: T,
+    shingle_filter: &'a ShingleFilter,
+    // The tokens of the shingles starting at the first one.
+    window: &'a mut VecDeque<Token>,
+    // Number of tokens of the next shingle starting at the first token of the window, 0 if the
+    // window has to be moved to the next token.
+    shingle_size: usize,
+    token: Token,
+}
+
+impl<T: TokenStream> ShingleTokenStream<'_, T> {
+    fn fill_window(&mut self) {
+        while self.window.len() < self.shingle_filter.max_shingle_size && self.tail.advance() {
+            self.window.push_back(self.tail.token().clone());
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleTokenStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        loop {
+            if self.shingle_size == 0 {
+                self.fill_window();
+                if self.window.is_empty() {
+                    return false;
+                }
+                self.shingle_size = if self.shingle_filter.output_unigrams {
+                    1
+                } else {
+                    self.shingle_filter.min_shingle_size
+                };
+            }
+            if self.shingle_size > self.window.len() {
+                // The window only lacks tokens at the end of the stream: no more shingle starts
+                // at the first token.
+                self.window.pop_front();
+                self.shingle_size = 0;
+                continue;
+            }
+            let first_token = &self.window[0];
+            let last_token = &self.window[self.shingle_size - 1];
+            if self.shingle_size == 1 {
+                self.token.clone_from(first_token);
+            } else {
+                self.token.text.clear();
+                for (token_ord, token) in self.window.iter().take(self.shingle_size).enumerate() {
+                    if token_ord > 0 {
+                        self.token.text.push(' ');
+                    }
+                    self.token.text.push_str(&token.text);
+                }
+                self.token.offset_from = first_token.offset_from;
+                self.token.offset_to = last_token.offset_to;
+                self.token.position = first_token.position;
+                self.token.position_length = self.shingle_size;
+                self.token.keyword = false;
+            }
+            self.shingle_size = if self.shingle_size == 1 {
+                self.shingle_filter.min_shingle_size
+            } else {
+                self.shingle_size + 1
+            };
+            if self.shingle_size > self.shingle_filter.max_shingle_size {
+                self.window.pop_front();
+                self.shingle_size = 0;
+            }
+            return true;
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{ShingleFilter, SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(text: &str, shingle_filter: ShingleFilter) -> Vec<Token> {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(shingle_filter)
+            .build();
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_shingle_filter() {
+        let tokens = token_stream_helper("a bb ccc", ShingleFilter::new(2, 3).unwrap());
+        assert_eq!(tokens.len(), 6);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+        assert_token(&tokens[1], 0, "a bb", 0, 4);
+        assert_token(&tokens[2], 0, "a bb ccc", 0, 8);
+        assert_token(&tokens[3], 1, "bb", 2, 4);
+        assert_token(&tokens[4], 1, "bb ccc", 2, 8);
+        assert_token(&tokens[5], 2, "ccc", 5, 8);
+        assert_eq!(tokens[2].position_length, 3);
+        assert_eq!(tokens[3].position_length, 1);
+    }
+
+    #[test]
+    fn test_shingle_filter_without_unigrams() {
+        let tokens = token_stream_helper(
+            "a bb ccc dddd",
+            ShingleFilter::new(3, 3).unwrap().without_unigrams(),
+        );
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, "a bb ccc", 0, 8);
+        assert_token(&tokens[1], 1, "bb ccc dddd", 2, 13);
+
+        assert!(
+            token_stream_helper("a bb", ShingleFilter::new(3, 3).unwrap().without_unigrams())
+                .is_empty()
+        );
+        assert!(token_stream_helper("", ShingleFilter::new(2, 2).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_shingle_filter_invalid_sizes() {
+        assert!(ShingleFilter::new(1, 2).is_err());
+        assert!(ShingleFilter::new(3, 2).is_err());
+    }
+}
@@ -31,6 +31,31 @@ pub enum Language {
 }
 
 impl Language {
+    /// Returns the ISO 639-1 code of the language, e.g. `en` for English.
+    pub fn code(self) -> &'static str {
+        use self::Language::*;
+        match self {
+            Arabic => "ar",
+            Danish => "da",
+            Dutch => "nl",
+            English => "en",
+            Finnish => "fi",
+            French => "fr",
+            German => "de",
+            Greek => "el",
+            Hungarian => "hu",
+            Italian => "it",
+            Norwegian => "no",
+            Portuguese => "pt",
+            Romanian => "ro",
+            Russian => "ru",
+            Spanish => "es",
+            Swedish => "sv",
+            Tamil => "ta",
+            Turkish => "tr",
+        }
+    }
+
     fn algorithm(self) -> Algorithm {
         use self::Language::*;
         match self {
@@ -59,9 +84,12 @@ impl Language {
 /// `Stemmer` token filter. Several languages are supported, see [`Language`] for the available
 /// languages.
 /// Tokens are expected to be lowercased beforehand.
+///
+/// Tokens marked as [keywords](Token::keyword) are left untouched.
 #[derive(Clone)]
 pub struct Stemmer {
     stemmer_algorithm: Algorithm,
+    preserve_original: bool,
 }
 
 impl Stemmer {
@@ -69,8 +97,32 @@ impl Stemmer {
     pub fn new(language: Language) -> Stemmer {
         Stemmer {
             stemmer_algorithm: language.algorithm(),
+            preserve_original: false,
         }
     }
+
+    /// Also emits the original token, at the same position, when it differs from its stem.
+    ///
+    /// The original token is emitted first, marked as a keyword.
+    /// This makes it possible to match the exact form of a word, in addition to its stem.
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::*;
+    ///
+    /// let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+    ///     .filter(Stemmer::new(Language::English).preserve_original())
+    ///     .build();
+    /// let mut stream = analyzer.token_stream("running fast");
+    /// assert_eq!(stream.next().unwrap().text, "running");
+    /// assert_eq!(stream.next().unwrap().text, "run");
+    /// assert_eq!(stream.next().unwrap().text, "fast");
+    /// assert!(stream.next().is_none());
+    /// ```
+    #[must_use]
+    pub fn preserve_original(mut self) -> Stemmer {
+        self.preserve_original = true;
+        self
+    }
 }
 
 impl Default for Stemmer {
@@ -86,6 +138,7 @@ impl TokenFilter for Stemmer {
     fn transform<T: Tokenizer>(self, tokenizer: T) -> StemmerFilter<T> {
         StemmerFilter {
             stemmer_algorithm: self.stemmer_algorithm,
+            preserve_original: self.preserve_original,
             inner: tokenizer,
         }
     }
@@ -94,6 +147,7 @@ impl TokenFilter for Stemmer {
 #[derive(Clone)]
 pub struct StemmerFilter<T> {
     stemmer_algorithm: Algorithm,
+    preserve_original: bool,
     inner: T,
 }
 
@@ -105,6 +159,8 @@ impl<T: Tokenizer> Tokenizer for StemmerFilter<T> {
         StemmerTokenStream {
             tail: self.inner.token_stream(text),
             stemmer,
+            preserve_original: self.preserve_original,
+            stem_pending: false,
             buffer: String::new(),
         }
     }
@@ -113,16 +169,39 @@ impl<T: Tokenizer> Tokenizer for StemmerFilter<T> {
 pub struct StemmerTokenStream<T> {
     tail: T,
     stemmer: rust_stemmers::Stemmer,
+    preserve_original: bool,
+    // Set when the original token was just emitted, and its stem,
+    // stored in `buffer`, should be emitted next.
+    stem_pending: bool,
     buffer: String,
 }
 
 impl<T: TokenStream> TokenStream for StemmerTokenStream<T> {
     fn advance(&mut self) -> bool {
+        if self.stem_pending {
+            self.stem_pending = false;
+            let token = self.tail.token_mut();
+            mem::swap(&mut token.text, &mut self.buffer);
+            token.keyword = false;
+            return true;
+        }
         if !self.tail.advance() {
             return false;
         }
         let token = self.tail.token_mut();
+        if token.keyword {
+            return true;
+        }
         let stemmed_str = self.stemmer.stem(&token.text);
+        if self.preserve_original {
+            if stemmed_str != token.text {
+                self.buffer.clear();
+                self.buffer.push_str(&stemmed_str);
+                token.keyword = true;
+                self.stem_pending = true;
+            }
+            return true;
+        }
         match stemmed_str {
             Cow::Owned(stemmed_str) => token.text = stemmed_str,
             Cow::Borrowed(stemmed_str) => {
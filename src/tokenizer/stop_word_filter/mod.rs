@@ -69,6 +69,11 @@ impl StopWordFilter {
             words: Arc::new(words.into_iter().collect()),
         }
     }
+
+    #[cfg(feature = "stopwords")]
+    pub(crate) fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(String::as_str)
+    }
 }
 
 impl TokenFilter for StopWordFilter {
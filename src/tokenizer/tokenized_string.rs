@@ -105,6 +105,7 @@ mod tests {
                     position: 0,
                     text: String::from("A"),
                     position_length: 1,
+                    keyword: false,
                 },
                 Token {
                     offset_from: 2,
@@ -112,6 +113,7 @@ mod tests {
                     position: 1,
                     text: String::from("a"),
                     position_length: 1,
+                    keyword: false,
                 },
             ],
         };
@@ -1,13 +1,24 @@
 /// The tokenizer module contains all of the tools used to process
 /// text in `tantivy`.
+use std::mem;
+use std::sync::Arc;
+
 use tokenizer_api::{BoxTokenStream, TokenFilter, Tokenizer};
 
+use crate::tokenizer::char_filter::{CharFilter, OffsetCorrectingTokenStream, OffsetMapping};
 use crate::tokenizer::empty_tokenizer::EmptyTokenizer;
 
 /// `TextAnalyzer` tokenizes an input text into tokens and modifies the resulting `TokenStream`.
+///
+/// The input text is first processed by the analyzer's [`CharFilter`]s, if any.
 #[derive(Clone)]
 pub struct TextAnalyzer {
+    char_filters: Vec<Arc<dyn CharFilter>>,
     tokenizer: Box<dyn BoxableTokenizer>,
+    // Buffers reused across calls to `token_stream` when char filters are configured.
+    filtered_text: String,
+    char_filter_buffer: String,
+    offset_mappings: Vec<OffsetMapping>,
 }
 
 impl Tokenizer for Box<dyn BoxableTokenizer> {
@@ -61,21 +72,72 @@ impl<T: Tokenizer + Clone> From<T> for TextAnalyzer {
 impl TextAnalyzer {
     /// Create a new TextAnalyzerBuilder
     pub fn builder<T: Tokenizer>(tokenizer: T) -> TextAnalyzerBuilder<T> {
-        TextAnalyzerBuilder { tokenizer }
+        TextAnalyzerBuilder {
+            char_filters: Vec::new(),
+            tokenizer,
+        }
     }
 
     /// Creates a token stream for a given `str`.
+    ///
+    /// The offsets of the emitted tokens always refer to `text`, even if
+    /// char filters modified it before tokenization.
     pub fn token_stream<'a>(&'a mut self, text: &'a str) -> BoxTokenStream<'a> {
-        self.tokenizer.token_stream(text)
+        if self.char_filters.is_empty() {
+            return self.tokenizer.token_stream(text);
+        }
+        self.offset_mappings
+            .resize_with(self.char_filters.len(), OffsetMapping::default);
+        let mut filtered_text = mem::take(&mut self.filtered_text);
+        let mut buffer = mem::take(&mut self.char_filter_buffer);
+        for (ord, (char_filter, offset_mapping)) in self
+            .char_filters
+            .iter()
+            .zip(self.offset_mappings.iter_mut())
+            .enumerate()
+        {
+            let input: &str = if ord == 0 { text } else { &filtered_text };
+            buffer.clear();
+            offset_mapping.clear();
+            char_filter.filter(input, &mut buffer, offset_mapping);
+            mem::swap(&mut filtered_text, &mut buffer);
+        }
+        self.filtered_text = filtered_text;
+        self.char_filter_buffer = buffer;
+        BoxTokenStream::new(OffsetCorrectingTokenStream {
+            tail: self.tokenizer.token_stream(&self.filtered_text),
+            offset_mappings: &self.offset_mappings,
+        })
     }
 }
 
 /// Builder helper for [`TextAnalyzer`]
 pub struct TextAnalyzerBuilder<T = Box<dyn BoxableTokenizer>> {
+    char_filters: Vec<Arc<dyn CharFilter>>,
     tokenizer: T,
 }
 
 impl<T: Tokenizer> TextAnalyzerBuilder<T> {
+    /// Appends a char filter to the current builder.
+    ///
+    /// Char filters are applied on the text, in the order they were added,
+    /// before it gets tokenized.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::tokenizer::*;
+    ///
+    /// let html_analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+    ///     .char_filter(HtmlStripCharFilter)
+    ///     .filter(LowerCaser)
+    ///     .build();
+    /// ```
+    pub fn char_filter<F: CharFilter>(mut self, char_filter: F) -> TextAnalyzerBuilder<T> {
+        self.char_filters.push(Arc::new(char_filter));
+        self
+    }
+
     /// Appends a token filter to the current builder.
     ///
     /// # Example
@@ -91,6 +153,7 @@ impl<T: Tokenizer> TextAnalyzerBuilder<T> {
     /// ```
     pub fn filter<F: TokenFilter>(self, token_filter: F) -> TextAnalyzerBuilder<F::Tokenizer<T>> {
         TextAnalyzerBuilder {
+            char_filters: self.char_filters,
             tokenizer: token_filter.transform(self.tokenizer),
         }
     }
@@ -100,6 +163,7 @@ impl<T: Tokenizer> TextAnalyzerBuilder<T> {
     pub fn dynamic(self) -> TextAnalyzerBuilder {
         let boxed_tokenizer = Box::new(self.tokenizer);
         TextAnalyzerBuilder {
+            char_filters: self.char_filters,
             tokenizer: boxed_tokenizer,
         }
     }
@@ -115,7 +179,11 @@ impl<T: Tokenizer> TextAnalyzerBuilder<T> {
     /// Finalize building the TextAnalyzer
     pub fn build(self) -> TextAnalyzer {
         TextAnalyzer {
+            char_filters: self.char_filters,
             tokenizer: Box::new(self.tokenizer),
+            filtered_text: String::new(),
+            char_filter_buffer: String::new(),
+            offset_mappings: Vec::new(),
         }
     }
 }
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+use crate::schema::{FieldType, Schema};
 use crate::tokenizer::stemmer::Language;
 use crate::tokenizer::tokenizer::TextAnalyzer;
 use crate::tokenizer::{
-    LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, WhitespaceTokenizer,
+    AnalyzerDefinition, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    WhitespaceTokenizer,
 };
 
 /// The tokenizer manager serves as a store for
@@ -18,9 +20,18 @@ use crate::tokenizer::{
 /// - `en_stem` : Like `default`, but also applies stemming on the resulting tokens. Stemming can
 ///   improve the recall of your search engine.
 /// - `whitespace` : Splits the text on whitespaces.
+///
+/// Tokenizers can also be registered as an [`AnalyzerDefinition`]. The definition is then
+/// only built the first time the tokenizer is requested.
 #[derive(Clone)]
 pub struct TokenizerManager {
-    tokenizers: Arc<RwLock<HashMap<String, TextAnalyzer>>>,
+    tokenizers: Arc<RwLock<HashMap<String, RegisteredTokenizer>>>,
+}
+
+#[derive(Clone)]
+enum RegisteredTokenizer {
+    Analyzer(TextAnalyzer),
+    Definition(AnalyzerDefinition),
 }
 
 impl TokenizerManager {
@@ -38,16 +49,96 @@ impl TokenizerManager {
         self.tokenizers
             .write()
             .expect("Acquiring the lock should never fail")
-            .insert(tokenizer_name.to_string(), boxed_tokenizer);
+            .insert(
+                tokenizer_name.to_string(),
+                RegisteredTokenizer::Analyzer(boxed_tokenizer),
+            );
+    }
+
+    /// Registers an analyzer definition associated with a given name.
+    ///
+    /// The definition replaces any tokenizer previously registered under the same name.
+    /// It is built lazily, the first time the tokenizer is accessed, so that the custom
+    /// tokenizers it refers to can be registered afterwards.
+    pub fn register_definition(&self, tokenizer_name: &str, definition: AnalyzerDefinition) {
+        self.tokenizers
+            .write()
+            .expect("Acquiring the lock should never fail")
+            .insert(
+                tokenizer_name.to_string(),
+                RegisteredTokenizer::Definition(definition),
+            );
+    }
+
+    /// Registers the analyzer definitions attached to the text fields of a schema.
+    ///
+    /// See [`TextFieldIndexing::set_analyzer_definition`](crate::schema::TextFieldIndexing::set_analyzer_definition).
+    pub fn register_schema_definitions(&self, schema: &Schema) {
+        for (_field, field_entry) in schema.fields() {
+            let indexing_options_opt = match field_entry.field_type() {
+                FieldType::Str(options) => options.get_indexing_options(),
+                FieldType::JsonObject(options) => options.get_text_indexing_options(),
+                _ => None,
+            };
+            let Some(indexing_options) = indexing_options_opt else {
+                continue;
+            };
+            if let Some(definition) = indexing_options.analyzer_definition() {
+                self.register_definition(indexing_options.tokenizer(), definition.clone());
+            }
+        }
     }
 
     /// Accessing a tokenizer given its name.
+    ///
+    /// Returns `None` if no tokenizer is registered under this name, or if it is registered
+    /// as a definition that cannot be built.
     pub fn get(&self, tokenizer_name: &str) -> Option<TextAnalyzer> {
-        self.tokenizers
+        let definition = match self
+            .tokenizers
+            .read()
+            .expect("Acquiring the lock should never fail")
+            .get(tokenizer_name)?
+        {
+            RegisteredTokenizer::Analyzer(text_analyzer) => return Some(text_analyzer.clone()),
+            RegisteredTokenizer::Definition(definition) => definition.clone(),
+        };
+        let text_analyzer = match definition.build(self) {
+            Ok(text_analyzer) => text_analyzer,
+            Err(err) => {
+                warn!("Failed to build tokenizer {tokenizer_name:?}: {err}");
+                return None;
+            }
+        };
+        let mut tokenizers = self
+            .tokenizers
+            .write()
+            .expect("Acquiring the lock should never fail");
+        // The definition may have been replaced while we were building it.
+        let is_unchanged = matches!(
+            tokenizers.get(tokenizer_name),
+            Some(RegisteredTokenizer::Definition(current_definition)) if *current_definition == definition
+        );
+        if is_unchanged {
+            tokenizers.insert(
+                tokenizer_name.to_string(),
+                RegisteredTokenizer::Analyzer(text_analyzer.clone()),
+            );
+        }
+        Some(text_analyzer)
+    }
+
+    /// Accessing a tokenizer given its name, ignoring definitions that have not been built yet.
+    pub(crate) fn get_registered(&self, tokenizer_name: &str) -> Option<TextAnalyzer> {
+        match self
+            .tokenizers
             .read()
             .expect("Acquiring the lock should never fail")
-            .get(tokenizer_name)
-            .cloned()
+            .get(tokenizer_name)?
+        {
+            RegisteredTokenizer::Analyzer(text_analyzer) => Some(text_analyzer.clone()),
+            RegisteredTokenizer::Definition(_) => None,
+        }
     }
 }
 
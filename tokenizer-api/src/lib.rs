@@ -26,6 +26,14 @@ pub struct Token {
     pub text: String,
     /// Is the length expressed in term of number of original tokens.
     pub position_length: usize,
+    /// Marks the token as a keyword. Token filters altering the text of a token
+    /// (e.g. stemmers) are expected to leave keywords untouched.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub keyword: bool,
+}
+
+fn is_false(val: &bool) -> bool {
+    !*val
 }
 
 impl Default for Token {
@@ -36,6 +44,7 @@ impl Default for Token {
             position: usize::MAX,
             text: String::new(),
             position_length: 1,
+            keyword: false,
         }
     }
 }
@@ -48,6 +57,7 @@ impl Token {
         self.position = usize::MAX;
         self.text.clear();
         self.position_length = 1;
+        self.keyword = false;
     }
 }
 
@@ -169,6 +179,7 @@ mod test {
             offset_to: 3,
             text: "abc".to_string(),
             position_length: 1,
+            keyword: false,
         };
         let t2 = t1.clone();
 